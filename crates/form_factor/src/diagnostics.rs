@@ -0,0 +1,688 @@
+//! Crash reporting and diagnostic bundles
+//!
+//! - [`LogBuffer`] keeps the most recent tracing events in memory, fed by a
+//!   [`LogBufferLayer`] added to the subscriber. Events are tagged with the
+//!   [`TraceId`](crate::TraceId) of the request whose span they are in.
+//! - [`CrashReporter`] installs a panic hook that writes a human-readable
+//!   [`CrashReport`] (panic message, backtrace, recent events, app state).
+//! - [`DiagnosticBundle`] zips logs, runtime configuration, crash reports
+//!   and an anonymized [`ProjectSummary`] for attaching to bug reports.
+//!
+//! Nothing collected here contains shape names, OCR text, comments or file
+//! paths from the project.
+
+use crate::{DrawingCanvas, IoError, IoOperation, Shape, TRACE_ID_FIELD};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{self, Write as _};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Default number of events kept by a [`LogBuffer`]
+pub const DEFAULT_LOG_CAPACITY: usize = 2000;
+
+/// Number of recent events included in a crash report
+const CRASH_REPORT_EVENTS: usize = 200;
+
+/// File name prefix of crash reports
+const CRASH_REPORT_PREFIX: &str = "crash-";
+
+// ============================================================================
+// Log buffer
+// ============================================================================
+
+/// One captured tracing event
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogRecord {
+    /// Seconds since the Unix epoch
+    pub timestamp: f64,
+    /// Event level
+    pub level: Level,
+    /// Module path or explicit target of the event
+    pub target: String,
+    /// Event message followed by any structured fields
+    pub message: String,
+    /// Trace ID of the request the event was logged in, if any
+    pub trace_id: Option<String>,
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.3} {:>5} {}: ", self.timestamp, self.level, self.target)?;
+        if let Some(trace_id) = &self.trace_id {
+            write!(f, "[{}] ", trace_id)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Shared ring buffer of recent tracing events
+///
+/// Cloning the buffer yields another handle to the same events.
+#[derive(Debug, Clone)]
+pub struct LogBuffer {
+    records: Arc<Mutex<VecDeque<LogRecord>>>,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    /// Create a buffer holding at most `capacity` events
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Layer that feeds this buffer; add it to the tracing subscriber
+    pub fn layer(&self) -> LogBufferLayer {
+        LogBufferLayer { buffer: self.clone() }
+    }
+
+    /// Append an event, evicting the oldest when full
+    pub fn push(&self, record: LogRecord) {
+        let mut records = self.lock();
+        if records.len() >= self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Copy of all buffered events, oldest first
+    pub fn records(&self) -> Vec<LogRecord> {
+        self.lock().iter().cloned().collect()
+    }
+
+    /// Copy of the newest `count` events, oldest first
+    pub fn recent(&self, count: usize) -> Vec<LogRecord> {
+        let records = self.lock();
+        records.iter().skip(records.len().saturating_sub(count)).cloned().collect()
+    }
+
+    /// Number of buffered events
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether no events have been buffered
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Remove all buffered events
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// All buffered events as text, one per line
+    pub fn to_text(&self) -> String {
+        self.lock().iter().fold(String::new(), |mut out, record| {
+            let _ = writeln!(out, "{}", record);
+            out
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<LogRecord>> {
+        // A panic while logging must not disable logging for the crash report
+        self.records.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::new(DEFAULT_LOG_CAPACITY)
+    }
+}
+
+/// Tracing layer that records events into a [`LogBuffer`]
+///
+/// A span's trace ID is its own [`TRACE_ID_FIELD`], or else the one of its
+/// parent or of the first span it follows from.
+#[derive(Debug, Clone)]
+pub struct LogBufferLayer {
+    buffer: LogBuffer,
+}
+
+/// Trace ID of a span, kept in the span's extensions
+struct SpanTraceId(String);
+
+impl<S> Layer<S> for LogBufferLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut visitor = TraceIdVisitor::default();
+        attrs.record(&mut visitor);
+        let trace_id = visitor.trace_id.or_else(|| {
+            let parent = span.parent()?;
+            let extensions = parent.extensions();
+            extensions.get::<SpanTraceId>().map(|SpanTraceId(id)| id.clone())
+        });
+        if let Some(trace_id) = trace_id {
+            span.extensions_mut().insert(SpanTraceId(trace_id));
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let mut visitor = TraceIdVisitor::default();
+        values.record(&mut visitor);
+        if let Some(trace_id) = visitor.trace_id
+            && let Some(span) = ctx.span(id)
+        {
+            span.extensions_mut().replace(SpanTraceId(trace_id));
+        }
+    }
+
+    fn on_follows_from(&self, id: &Id, follows: &Id, ctx: Context<'_, S>) {
+        let (Some(span), Some(follows)) = (ctx.span(id), ctx.span(follows)) else {
+            return;
+        };
+        if span.extensions().get::<SpanTraceId>().is_some() {
+            return;
+        }
+        let trace_id = follows.extensions().get::<SpanTraceId>().map(|SpanTraceId(id)| id.clone());
+        if let Some(trace_id) = trace_id {
+            span.extensions_mut().insert(SpanTraceId(trace_id));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let trace_id = ctx.event_span(event).and_then(|span| {
+            let extensions = span.extensions();
+            extensions.get::<SpanTraceId>().map(|SpanTraceId(id)| id.clone())
+        });
+        let metadata = event.metadata();
+        self.buffer.push(LogRecord {
+            timestamp: unix_time(),
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: visitor.finish(),
+            trace_id,
+        });
+    }
+}
+
+/// Finds the [`TRACE_ID_FIELD`] among a span's fields
+#[derive(Default)]
+struct TraceIdVisitor {
+    trace_id: Option<String>,
+}
+
+impl Visit for TraceIdVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == TRACE_ID_FIELD {
+            self.trace_id = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == TRACE_ID_FIELD {
+            self.trace_id = Some(format!("{:?}", value));
+        }
+    }
+}
+
+/// Collects an event's message and fields into one line
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl MessageVisitor {
+    fn finish(self) -> String {
+        if self.fields.is_empty() {
+            self.message
+        } else if self.message.is_empty() {
+            self.fields
+        } else {
+            format!("{} {}", self.message, self.fields)
+        }
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            if !self.fields.is_empty() {
+                self.fields.push(' ');
+            }
+            let _ = write!(self.fields, "{}={:?}", field.name(), value);
+        }
+    }
+}
+
+fn unix_time() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// Project summary
+// ============================================================================
+
+/// Anonymized description of a project
+///
+/// Counts and sizes only: no names, text, comments or paths.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ProjectSummary {
+    /// Project file format version
+    pub format_version: u32,
+    /// Number of shapes per kind (e.g. "Rectangle")
+    pub shapes_by_kind: BTreeMap<String, usize>,
+    /// Number of shapes marked sensitive
+    pub sensitive_shapes: usize,
+    /// Number of detections
+    pub detections: usize,
+    /// Number of detections flagged as PII
+    pub pii_flags: usize,
+    /// Number of comment threads
+    pub comment_threads: usize,
+    /// Whether a form image is loaded
+    pub has_form_image: bool,
+    /// Form image size in pixels, if loaded
+    pub form_image_size: Option<(f32, f32)>,
+    /// Current zoom level
+    pub zoom: f32,
+}
+
+impl ProjectSummary {
+    /// Summarize a canvas
+    pub fn from_canvas(canvas: &DrawingCanvas) -> Self {
+        let mut shapes_by_kind = BTreeMap::new();
+        for shape in canvas.shapes() {
+            let kind = match shape {
+                Shape::Rectangle(_) => "Rectangle",
+                Shape::Circle(_) => "Circle",
+                Shape::Ellipse(_) => "Ellipse",
+                Shape::Polygon(_) => "Polygon",
+                Shape::Line(_) => "Line",
+                Shape::Polyline(_) => "Polyline",
+                Shape::Arrow(_) => "Arrow",
+                Shape::Callout(_) => "Callout",
+            };
+            *shapes_by_kind.entry(kind.to_string()).or_insert(0) += 1;
+        }
+
+        Self {
+            format_version: *canvas.format_version(),
+            shapes_by_kind,
+            sensitive_shapes: canvas.shapes().iter().filter(|s| s.is_sensitive()).count(),
+            detections: canvas.detections().len(),
+            pii_flags: canvas.pii_flags().len(),
+            comment_threads: canvas.comment_threads().len(),
+            has_form_image: canvas.form_image_path().is_some(),
+            form_image_size: canvas.form_image_size().map(|size| (size.x, size.y)),
+            zoom: *canvas.zoom_level(),
+        }
+    }
+}
+
+// ============================================================================
+// Runtime configuration
+// ============================================================================
+
+/// Build and platform information for bug reports
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SystemInfo {
+    /// Crate version
+    pub version: String,
+    /// Operating system
+    pub os: String,
+    /// CPU architecture
+    pub arch: String,
+    /// Cargo features compiled in
+    pub features: Vec<String>,
+    /// Value of `RUST_LOG`, if set
+    pub rust_log: Option<String>,
+}
+
+impl SystemInfo {
+    /// Information about the running build
+    pub fn current() -> Self {
+        let features = [
+            ("backend-eframe", cfg!(feature = "backend-eframe")),
+            ("text-detection", cfg!(feature = "text-detection")),
+            ("logo-detection", cfg!(feature = "logo-detection")),
+            ("ocr", cfg!(feature = "ocr")),
+            ("plugins", cfg!(feature = "plugins")),
+            ("metrics", cfg!(feature = "metrics")),
+        ]
+        .into_iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| name.to_string())
+        .collect();
+
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            features,
+            rust_log: std::env::var("RUST_LOG").ok(),
+        }
+    }
+}
+
+// ============================================================================
+// Crash reports
+// ============================================================================
+
+/// Everything known about a panic
+#[derive(Debug, Clone)]
+pub struct CrashReport {
+    /// Seconds since the Unix epoch
+    pub timestamp: f64,
+    /// Panic payload, if it was a string
+    pub message: String,
+    /// Source location of the panic
+    pub location: Option<String>,
+    /// Name of the panicking thread
+    pub thread: Option<String>,
+    /// Captured backtrace
+    pub backtrace: String,
+    /// Events leading up to the panic
+    pub recent_events: Vec<LogRecord>,
+    /// Application state at the time of the panic
+    pub state: Option<ProjectSummary>,
+    /// Build and platform information
+    pub system: SystemInfo,
+}
+
+impl CrashReport {
+    /// Human-readable report text
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Form Factor crash report");
+        let _ = writeln!(out, "========================");
+        let _ = writeln!(out, "Time: {:.0}", self.timestamp);
+        let _ = writeln!(
+            out,
+            "Version: {} ({}/{})",
+            self.system.version, self.system.os, self.system.arch
+        );
+        let _ = writeln!(out, "Features: {}", self.system.features.join(", "));
+        let _ = writeln!(out, "Thread: {}", self.thread.as_deref().unwrap_or("<unnamed>"));
+        let _ = writeln!(out, "Location: {}", self.location.as_deref().unwrap_or("<unknown>"));
+        let _ = writeln!(out, "\nPanic: {}", self.message);
+
+        let _ = writeln!(out, "\nApplication state");
+        let _ = writeln!(out, "-----------------");
+        match &self.state {
+            Some(state) => {
+                let _ = writeln!(out, "{}", serde_json::to_string_pretty(state).unwrap_or_default());
+            }
+            None => {
+                let _ = writeln!(out, "<not recorded>");
+            }
+        }
+
+        let _ = writeln!(out, "\nRecent events");
+        let _ = writeln!(out, "-------------");
+        for record in &self.recent_events {
+            let _ = writeln!(out, "{}", record);
+        }
+
+        let _ = writeln!(out, "\nBacktrace");
+        let _ = writeln!(out, "---------");
+        let _ = writeln!(out, "{}", self.backtrace);
+        out
+    }
+}
+
+/// Captures panics as crash reports on disk
+///
+/// Clones share the same recorded state.
+#[derive(Debug, Clone)]
+pub struct CrashReporter {
+    report_dir: PathBuf,
+    logs: Option<LogBuffer>,
+    state: Arc<Mutex<Option<ProjectSummary>>>,
+}
+
+impl CrashReporter {
+    /// Create a reporter that writes into `report_dir`
+    pub fn new(report_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            report_dir: report_dir.into(),
+            logs: None,
+            state: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Default report directory: `crashes` inside the config directory
+    pub fn default_dir() -> PathBuf {
+        crate::config_dir().join("crashes")
+    }
+
+    /// Include recent events from a log buffer in reports
+    pub fn with_logs(mut self, logs: LogBuffer) -> Self {
+        self.logs = Some(logs);
+        self
+    }
+
+    /// Log buffer included in reports, if any
+    pub fn logs(&self) -> Option<&LogBuffer> {
+        self.logs.as_ref()
+    }
+
+    /// Directory reports are written to
+    pub fn report_dir(&self) -> &Path {
+        &self.report_dir
+    }
+
+    /// Record the application state to include if a panic occurs
+    pub fn update_state(&self, summary: ProjectSummary) {
+        *self.state.lock().unwrap_or_else(|e| e.into_inner()) = Some(summary);
+    }
+
+    /// Install the panic hook
+    ///
+    /// The previous hook still runs afterwards, so panics keep printing to
+    /// stderr as usual. Where the report went is logged through `tracing`.
+    pub fn install(&self) {
+        let reporter = self.clone();
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let report = reporter.capture(
+                panic_message(info.payload()),
+                info.location().map(|l| l.to_string()),
+            );
+            match reporter.write_report(&report) {
+                Ok(path) => tracing::info!(path = %path.display(), "Crash report written"),
+                Err(e) => tracing::error!("Failed to write crash report: {}", e),
+            }
+            previous(info);
+        }));
+    }
+
+    /// Build a report for a panic with the given message and location
+    pub fn capture(&self, message: String, location: Option<String>) -> CrashReport {
+        CrashReport {
+            timestamp: unix_time(),
+            message,
+            location,
+            thread: std::thread::current().name().map(str::to_string),
+            backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+            recent_events: self
+                .logs
+                .as_ref()
+                .map(|logs| logs.recent(CRASH_REPORT_EVENTS))
+                .unwrap_or_default(),
+            // try_lock: the panic may have happened while the state was locked
+            state: self.state.try_lock().ok().and_then(|s| s.clone()),
+            system: SystemInfo::current(),
+        }
+    }
+
+    /// Write a report into the report directory
+    pub fn write_report(&self, report: &CrashReport) -> Result<PathBuf, IoError> {
+        let dir = self.report_dir.display().to_string();
+        std::fs::create_dir_all(&self.report_dir)
+            .map_err(|e| IoError::new(e.to_string(), &dir, IoOperation::Create, line!(), file!()))?;
+
+        let path = self
+            .report_dir
+            .join(format!("{}{}.txt", CRASH_REPORT_PREFIX, (report.timestamp * 1000.0) as u64));
+        std::fs::write(&path, report.render()).map_err(|e| {
+            IoError::new(e.to_string(), path.display().to_string(), IoOperation::Write, line!(), file!())
+        })?;
+        Ok(path)
+    }
+
+    /// Crash reports found in the report directory, oldest first
+    pub fn reports(&self) -> Vec<PathBuf> {
+        let Ok(entries) = std::fs::read_dir(&self.report_dir) else {
+            return Vec::new();
+        };
+        let mut reports: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(CRASH_REPORT_PREFIX) && n.ends_with(".txt"))
+            })
+            .collect();
+        reports.sort();
+        reports
+    }
+}
+
+/// Extract the message from a panic payload
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "<non-string panic payload>".to_string()
+    }
+}
+
+// ============================================================================
+// Diagnostic bundle
+// ============================================================================
+
+/// Zip archive of everything useful for a bug report
+///
+/// Contains `system.json`, `logs.txt`, `project_summary.json` (when a
+/// project is given) and `crashes/*.txt`.
+#[derive(Debug, Clone)]
+pub struct DiagnosticBundle {
+    system: SystemInfo,
+    logs: String,
+    project: Option<ProjectSummary>,
+    crash_reports: Vec<PathBuf>,
+}
+
+impl DiagnosticBundle {
+    /// Start a bundle for the running build
+    pub fn new() -> Self {
+        Self {
+            system: SystemInfo::current(),
+            logs: String::new(),
+            project: None,
+            crash_reports: Vec::new(),
+        }
+    }
+
+    /// Include the contents of a log buffer
+    pub fn with_logs(mut self, logs: &LogBuffer) -> Self {
+        self.logs = logs.to_text();
+        self
+    }
+
+    /// Include an anonymized summary of the open project
+    pub fn with_project(mut self, summary: ProjectSummary) -> Self {
+        self.project = Some(summary);
+        self
+    }
+
+    /// Include the crash reports a reporter has written
+    pub fn with_crash_reports(mut self, reporter: &CrashReporter) -> Self {
+        self.crash_reports = reporter.reports();
+        self
+    }
+
+    /// Names of the files the bundle will contain
+    pub fn entries(&self) -> Vec<String> {
+        let mut entries = vec!["system.json".to_string(), "logs.txt".to_string()];
+        if self.project.is_some() {
+            entries.push("project_summary.json".to_string());
+        }
+        entries.extend(self.crash_reports.iter().filter_map(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| format!("crashes/{}", n))
+        }));
+        entries
+    }
+
+    /// Write the bundle as a zip archive
+    #[tracing::instrument(skip(self), fields(crash_reports = self.crash_reports.len()))]
+    pub fn export(&self, path: &str) -> Result<(), IoError> {
+        let io_err = |e: &dyn fmt::Display| IoError::new(e.to_string(), path, IoOperation::Write, line!(), file!());
+
+        let file = std::fs::File::create(path)
+            .map_err(|e| IoError::new(e.to_string(), path, IoOperation::Create, line!(), file!()))?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default();
+
+        let mut add = |name: &str, contents: &[u8]| -> Result<(), IoError> {
+            zip.start_file(name, options).map_err(|e| io_err(&e))?;
+            zip.write_all(contents).map_err(|e| io_err(&e))
+        };
+
+        let system = serde_json::to_string_pretty(&self.system).map_err(|e| io_err(&e))?;
+        add("system.json", system.as_bytes())?;
+        add("logs.txt", self.logs.as_bytes())?;
+        if let Some(project) = &self.project {
+            let summary = serde_json::to_string_pretty(project).map_err(|e| io_err(&e))?;
+            add("project_summary.json", summary.as_bytes())?;
+        }
+        for report in &self.crash_reports {
+            let Some(name) = report.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            match std::fs::read(report) {
+                Ok(contents) => add(&format!("crashes/{}", name), &contents)?,
+                Err(e) => tracing::warn!("Skipping unreadable crash report {}: {}", report.display(), e),
+            }
+        }
+
+        zip.finish().map_err(|e| io_err(&e))?;
+        tracing::info!("Exported diagnostic bundle to {}", path);
+        Ok(())
+    }
+}
+
+impl Default for DiagnosticBundle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
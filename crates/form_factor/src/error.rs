@@ -83,16 +83,19 @@ pub enum FormErrorKind {
     /// Shape creation and manipulation errors
     Shape(crate::ShapeError),
 
-    /// Text detection errors
+    /// Collaboration sync errors
+    Collab(crate::CollabError),
+
+    /// Computer vision errors (text and logo detection, bubble-sheet reading)
     ///
-    /// Available with the `text-detection` feature.
-    #[cfg(feature = "text-detection")]
-    TextDetection(crate::TextDetectionError),
+    /// Available with the `text-detection`, `logo-detection` or `omr` feature.
+    #[cfg(all(any(feature = "text-detection", feature = "logo-detection", feature = "omr"), not(target_arch = "wasm32")))]
+    Cv(crate::CvError),
 
     /// OCR errors
     ///
     /// Available with the `ocr` feature.
-    #[cfg(feature = "ocr")]
+    #[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
     Ocr(crate::OCRError),
 }
 
@@ -321,9 +324,10 @@ impl fmt::Display for FormErrorKind {
             FormErrorKind::Canvas(e) => write!(f, "{}", e),
             FormErrorKind::Layer(e) => write!(f, "{}", e),
             FormErrorKind::Shape(e) => write!(f, "{}", e),
-            #[cfg(feature = "text-detection")]
-            FormErrorKind::TextDetection(e) => write!(f, "{}", e),
-            #[cfg(feature = "ocr")]
+            FormErrorKind::Collab(e) => write!(f, "{}", e),
+            #[cfg(all(any(feature = "text-detection", feature = "logo-detection", feature = "omr"), not(target_arch = "wasm32")))]
+            FormErrorKind::Cv(e) => write!(f, "{}", e),
+            #[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
             FormErrorKind::Ocr(e) => write!(f, "{}", e),
         }
     }
@@ -406,9 +410,10 @@ impl std::error::Error for FormError {
             FormErrorKind::Canvas(e) => Some(e),
             FormErrorKind::Layer(e) => Some(e),
             FormErrorKind::Shape(e) => Some(e),
-            #[cfg(feature = "text-detection")]
-            FormErrorKind::TextDetection(e) => Some(e),
-            #[cfg(feature = "ocr")]
+            FormErrorKind::Collab(e) => Some(e),
+            #[cfg(all(any(feature = "text-detection", feature = "logo-detection", feature = "omr"), not(target_arch = "wasm32")))]
+            FormErrorKind::Cv(e) => Some(e),
+            #[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
             FormErrorKind::Ocr(e) => Some(e),
         }
     }
@@ -486,14 +491,41 @@ impl From<crate::ShapeError> for FormError {
     }
 }
 
-#[cfg(feature = "text-detection")]
+impl From<crate::CollabError> for FormError {
+    fn from(err: crate::CollabError) -> Self {
+        FormError::new(FormErrorKind::from(err))
+    }
+}
+
+#[cfg(all(any(feature = "text-detection", feature = "logo-detection", feature = "omr"), not(target_arch = "wasm32")))]
+impl From<crate::CvError> for FormError {
+    fn from(err: crate::CvError) -> Self {
+        FormError::new(FormErrorKind::from(err))
+    }
+}
+
+#[cfg(all(feature = "text-detection", not(target_arch = "wasm32")))]
 impl From<crate::TextDetectionError> for FormError {
     fn from(err: crate::TextDetectionError) -> Self {
-        FormError::new(FormErrorKind::from(err))
+        crate::CvError::from(err).into()
+    }
+}
+
+#[cfg(all(feature = "logo-detection", not(target_arch = "wasm32")))]
+impl From<crate::LogoDetectionError> for FormError {
+    fn from(err: crate::LogoDetectionError) -> Self {
+        crate::CvError::from(err).into()
+    }
+}
+
+#[cfg(all(feature = "omr", not(target_arch = "wasm32")))]
+impl From<crate::OmrError> for FormError {
+    fn from(err: crate::OmrError) -> Self {
+        crate::CvError::from(err).into()
     }
 }
 
-#[cfg(feature = "ocr")]
+#[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
 impl From<crate::OCRError> for FormError {
     fn from(err: crate::OCRError) -> Self {
         FormError::new(FormErrorKind::from(err))
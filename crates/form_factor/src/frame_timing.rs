@@ -0,0 +1,373 @@
+//! Per-frame timing overlay for finding performance regressions
+//!
+//! The app wraps each part of its frame in the span of a [`FrameSection`].
+//! A [`FrameTimingLayer`] added to the tracing subscriber adds up the time
+//! spent inside those spans, and [`FrameTimings::end_frame`] files the totals
+//! as one [`FrameSample`]. The [`FrameTimingOverlay`] window shows the latest,
+//! average and slowest recent frames per section, with a bar per frame.
+//!
+//! The spans are at `INFO` level with target `form_factor::frame_timing`, so
+//! a `RUST_LOG` filter that hides them also leaves the overlay empty.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use strum::IntoEnumIterator;
+use tracing::span::Id;
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Default number of frames kept by [`FrameTimings`]
+pub const DEFAULT_FRAME_HISTORY: usize = 240;
+
+/// Frame time of a 60 Hz display, drawn as a line over the bars
+const FRAME_BUDGET: Duration = Duration::from_micros(16_667);
+
+/// Number of [`FrameSection`] variants
+const SECTIONS: usize = 4;
+
+/// Part of a frame timed separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::EnumIter, strum::Display)]
+pub enum FrameSection {
+    /// Canvas, status bar and floating windows
+    #[strum(to_string = "Canvas rendering")]
+    CanvasRendering,
+    /// Plugin sidebar
+    #[strum(to_string = "Plugin rendering")]
+    PluginRendering,
+    /// Application and plugin events
+    #[strum(to_string = "Event processing")]
+    EventProcessing,
+    /// Dropped files, pasted images and autosave
+    #[strum(to_string = "Async results")]
+    AsyncResults,
+}
+
+impl FrameSection {
+    /// Span timing this section; enter it around the section's work
+    pub fn span(self) -> tracing::Span {
+        match self {
+            Self::CanvasRendering => tracing::info_span!("canvas_rendering"),
+            Self::PluginRendering => tracing::info_span!("plugin_rendering"),
+            Self::EventProcessing => tracing::info_span!("event_processing"),
+            Self::AsyncResults => tracing::info_span!("async_results"),
+        }
+    }
+
+    /// Section timed by the span named `name`
+    pub fn from_span_name(name: &str) -> Option<Self> {
+        match name {
+            "canvas_rendering" => Some(Self::CanvasRendering),
+            "plugin_rendering" => Some(Self::PluginRendering),
+            "event_processing" => Some(Self::EventProcessing),
+            "async_results" => Some(Self::AsyncResults),
+            _ => None,
+        }
+    }
+
+    /// Bar color in the overlay
+    fn color(self) -> egui::Color32 {
+        match self {
+            Self::CanvasRendering => egui::Color32::from_rgb(90, 150, 230),
+            Self::PluginRendering => egui::Color32::from_rgb(170, 110, 220),
+            Self::EventProcessing => egui::Color32::from_rgb(230, 160, 60),
+            Self::AsyncResults => egui::Color32::from_rgb(90, 190, 120),
+        }
+    }
+}
+
+/// Time spent in one frame, in total and per section
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameSample {
+    /// Time from the start to the end of the frame
+    pub total: Duration,
+    /// Time inside each section's span
+    sections: [Duration; SECTIONS],
+}
+
+impl FrameSample {
+    /// Time spent in a section
+    pub fn section(&self, section: FrameSection) -> Duration {
+        self.sections[section as usize]
+    }
+
+    /// Time not spent in any section
+    pub fn other(&self) -> Duration {
+        self.total.saturating_sub(self.sections.iter().sum())
+    }
+}
+
+/// Timing of the frame in progress and the frames before it
+#[derive(Debug)]
+struct TimingState {
+    /// When the frame in progress started
+    started: Option<Instant>,
+    /// Section times of the frame in progress
+    current: [Duration; SECTIONS],
+    /// Finished frames, oldest first
+    history: VecDeque<FrameSample>,
+}
+
+/// Shared history of frame timings
+///
+/// Cloning yields another handle to the same history.
+#[derive(Debug, Clone)]
+pub struct FrameTimings {
+    state: Arc<Mutex<TimingState>>,
+    capacity: usize,
+}
+
+impl FrameTimings {
+    /// Create a history holding at most `capacity` frames
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(TimingState {
+                started: None,
+                current: [Duration::ZERO; SECTIONS],
+                history: VecDeque::with_capacity(capacity),
+            })),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Layer that times section spans; add it to the tracing subscriber
+    pub fn layer(&self) -> FrameTimingLayer {
+        FrameTimingLayer { timings: self.clone() }
+    }
+
+    /// Start timing a frame
+    pub fn begin_frame(&self) {
+        let mut state = self.lock();
+        state.started = Some(Instant::now());
+        state.current = [Duration::ZERO; SECTIONS];
+    }
+
+    /// Finish the frame started by [`Self::begin_frame`] and keep its sample
+    pub fn end_frame(&self) {
+        let mut state = self.lock();
+        let Some(started) = state.started.take() else {
+            return;
+        };
+        let sample = FrameSample {
+            total: started.elapsed(),
+            sections: std::mem::take(&mut state.current),
+        };
+        if state.history.len() >= self.capacity {
+            state.history.pop_front();
+        }
+        state.history.push_back(sample);
+    }
+
+    /// Add time spent in a section to the frame in progress
+    pub fn record(&self, section: FrameSection, elapsed: Duration) {
+        self.lock().current[section as usize] += elapsed;
+    }
+
+    /// Copy of the finished frames, oldest first
+    pub fn samples(&self) -> Vec<FrameSample> {
+        self.lock().history.iter().copied().collect()
+    }
+
+    /// Most recently finished frame
+    pub fn latest(&self) -> Option<FrameSample> {
+        self.lock().history.back().copied()
+    }
+
+    /// Mean of the finished frames
+    pub fn average(&self) -> Option<FrameSample> {
+        let state = self.lock();
+        let count = u32::try_from(state.history.len()).ok().filter(|&n| n > 0)?;
+        let mut sum = state.history.iter().fold(FrameSample::default(), |mut sum, sample| {
+            sum.total += sample.total;
+            for (total, section) in sum.sections.iter_mut().zip(sample.sections) {
+                *total += section;
+            }
+            sum
+        });
+        sum.total /= count;
+        for section in &mut sum.sections {
+            *section /= count;
+        }
+        Some(sum)
+    }
+
+    /// Slowest of the finished frames
+    pub fn slowest(&self) -> Option<FrameSample> {
+        self.lock().history.iter().copied().max_by_key(|sample| sample.total)
+    }
+
+    /// Number of finished frames kept
+    pub fn len(&self) -> usize {
+        self.lock().history.len()
+    }
+
+    /// Whether no frame has finished yet
+    pub fn is_empty(&self) -> bool {
+        self.lock().history.is_empty()
+    }
+
+    /// Forget the finished frames
+    pub fn clear(&self) {
+        self.lock().history.clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TimingState> {
+        // Timing must keep working after a panic on another thread
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for FrameTimings {
+    fn default() -> Self {
+        Self::new(DEFAULT_FRAME_HISTORY)
+    }
+}
+
+/// When a section span was entered, kept in the span's extensions
+struct Entered(Instant);
+
+/// Tracing layer that adds the time spent in section spans to [`FrameTimings`]
+#[derive(Debug, Clone)]
+pub struct FrameTimingLayer {
+    timings: FrameTimings,
+}
+
+impl<S> Layer<S> for FrameTimingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id)
+            && FrameSection::from_span_name(span.name()).is_some()
+        {
+            span.extensions_mut().replace(Entered(Instant::now()));
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id)
+            && let Some(section) = FrameSection::from_span_name(span.name())
+            && let Some(Entered(entered)) = span.extensions_mut().remove::<Entered>()
+        {
+            self.timings.record(section, entered.elapsed());
+        }
+    }
+}
+
+/// Developer window showing where frame time goes
+#[derive(Debug, Clone)]
+pub struct FrameTimingOverlay {
+    /// Source of timings
+    timings: FrameTimings,
+    /// Whether the window is visible
+    open: bool,
+}
+
+impl FrameTimingOverlay {
+    /// Create a hidden overlay over a timing history
+    pub fn new(timings: FrameTimings) -> Self {
+        Self { timings, open: false }
+    }
+
+    /// Timing history shown by the overlay
+    pub fn timings(&self) -> &FrameTimings {
+        &self.timings
+    }
+
+    /// Whether the window is visible
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Show or hide the window
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    /// Show the frame timing window
+    ///
+    /// Returns true if the window was shown.
+    pub fn show(&mut self, ctx: &egui::Context) -> bool {
+        if !self.open {
+            return false;
+        }
+
+        let mut open = true;
+        egui::Window::new("Frame Timing")
+            .open(&mut open)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                self.render_table(ui);
+                ui.separator();
+                self.render_bars(ui);
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} frames", self.timings.len()));
+                    if ui.button("Clear").clicked() {
+                        self.timings.clear();
+                    }
+                });
+            });
+
+        if !open {
+            self.open = false;
+        }
+        // Keep timing frames while the window is open, even when idle
+        ctx.request_repaint();
+        true
+    }
+
+    fn render_table(&self, ui: &mut egui::Ui) {
+        let columns = [self.timings.latest(), self.timings.average(), self.timings.slowest()];
+        let milliseconds = |duration: Duration| format!("{:.2} ms", duration.as_secs_f64() * 1000.0);
+
+        egui::Grid::new("frame_timing_grid").num_columns(4).striped(true).show(ui, |ui| {
+            ui.label("");
+            for heading in ["Last", "Average", "Slowest"] {
+                ui.strong(heading);
+            }
+            ui.end_row();
+
+            let row = |ui: &mut egui::Ui, label: egui::RichText, value: &dyn Fn(&FrameSample) -> Duration| {
+                ui.label(label);
+                for sample in &columns {
+                    ui.label(sample.as_ref().map_or_else(|| "-".to_string(), |s| milliseconds(value(s))));
+                }
+                ui.end_row();
+            };
+            for section in FrameSection::iter() {
+                let label = egui::RichText::new(section.to_string()).color(section.color());
+                row(ui, label, &|sample| sample.section(section));
+            }
+            row(ui, egui::RichText::new("Other"), &FrameSample::other);
+            row(ui, egui::RichText::new("Total").strong(), &|sample| sample.total);
+        });
+    }
+
+    /// One stacked bar per recent frame, scaled to twice the 60 Hz budget
+    fn render_bars(&self, ui: &mut egui::Ui) {
+        let samples = self.timings.samples();
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), 80.0), egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+        let scale = rect.height() / (2.0 * FRAME_BUDGET.as_secs_f32());
+        let bar_width = rect.width() / self.timings.capacity as f32;
+        for (idx, sample) in samples.iter().rev().enumerate() {
+            let right = rect.right() - idx as f32 * bar_width;
+            let mut bottom = rect.bottom();
+            let segments = FrameSection::iter()
+                .map(|section| (sample.section(section), section.color()))
+                .chain(std::iter::once((sample.other(), ui.visuals().weak_text_color())));
+            for (duration, color) in segments {
+                let top = (bottom - duration.as_secs_f32() * scale).max(rect.top());
+                let bar = egui::Rect::from_min_max(egui::pos2(right - bar_width, top), egui::pos2(right, bottom));
+                painter.rect_filled(bar, 0.0, color);
+                bottom = top;
+            }
+        }
+
+        let budget = rect.bottom() - FRAME_BUDGET.as_secs_f32() * scale;
+        painter.hline(rect.x_range(), budget, egui::Stroke::new(1.0, egui::Color32::RED));
+    }
+}
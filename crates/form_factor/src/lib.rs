@@ -33,6 +33,39 @@
 // Top-level error module stays here (aggregates errors from all crates)
 mod error;
 
+// Crash reporting and diagnostic bundles
+mod diagnostics;
+
+// In-app log viewer
+mod log_viewer;
+
+// Developer overlay of per-frame timing
+mod frame_timing;
+
+// Overlay windows
+mod overlay;
+mod settings_overlay;
+
+// Prompts before unsaved work is lost
+mod unsaved;
+
+// Workspace arrangement remembered per mode
+mod workspace;
+
+// First-run guided tours
+mod onboarding;
+
+// Platform-neutral review app, the browser entry point
+mod review;
+
+// Desktop application shell, the native entry point
+#[cfg(not(target_arch = "wasm32"))]
+mod shell;
+
+// Canvas and plugin sidebar for embedding in other egui apps
+#[cfg(feature = "plugins")]
+mod widget;
+
 // ============================================================================
 // Core Application Types
 // ============================================================================
@@ -43,6 +76,19 @@ pub use form_factor_core::App;
 /// Context provided to your app each frame (egui context, timing, etc.)
 pub use form_factor_core::AppContext;
 
+/// Bottom status bar showing cursor, zoom, tool, layer and task count
+pub use form_factor_core::StatusBar;
+
+/// Frame-rate independent tweens for overlays and visual feedback
+pub use form_factor_core::{Animations, Easing, Lerp, Tween, approach};
+
+/// Canvas review app that also runs in the browser
+pub use review::ReviewApp;
+
+/// Full desktop app, extensible for building custom apps
+#[cfg(not(target_arch = "wasm32"))]
+pub use shell::{AppShell, ShellExtension};
+
 // ============================================================================
 // Backend System
 // ============================================================================
@@ -53,10 +99,23 @@ pub use form_factor_core::Backend;
 /// Configuration for backend initialization (window size, vsync, etc.)
 pub use form_factor_core::BackendConfig;
 
+/// Hosting an app on an event loop owned by the embedding application
+pub use form_factor_core::EmbeddedBackend;
+
+/// Callback run every frame after the app has updated
+pub use form_factor_core::FrameCallback;
+
+/// Extra native window, e.g. a detached panel on a second monitor
+pub use form_factor_core::SecondaryWindow;
+
 // Backend implementations (conditional compilation)
 #[cfg(feature = "backend-eframe")]
 pub use form_factor_backends::{EframeBackend, EframeError};
 
+/// Off-screen backend for tests and batch jobs without a display
+#[cfg(feature = "backend-eframe")]
+pub use form_factor_backends::{HEADLESS_MAX_FRAMES, HeadlessBackend, HeadlessError, HeadlessFrame, HeadlessRunner};
+
 // ============================================================================
 // Error Types
 // ============================================================================
@@ -70,6 +129,9 @@ pub use error::FormErrorKind;
 /// I/O error types (re-exported from core)
 pub use form_factor_core::{IoError, IoOperation};
 
+/// Identifiers linking the log events of one request
+pub use form_factor_core::{TRACE_ID_FIELD, TraceId};
+
 /// Specific error types for each category
 pub use error::{AccessKitError, AppError, BackendError, ConfigError, EguiError};
 
@@ -78,39 +140,234 @@ pub use error::{AccessKitError, AppError, BackendError, ConfigError, EguiError};
 // ============================================================================
 
 /// Drawing canvas for form annotations
-pub use form_factor_drawing::{CanvasError, CanvasErrorKind, DetectionSubtype, DrawingCanvas};
+pub use form_factor_drawing::{CanvasAction, CanvasError, CanvasErrorKind, DrawingCanvas};
+
+/// Read-only view of the canvas handed to plugins each frame
+pub use form_factor_drawing::CanvasSnapshot;
+
+/// Commands that change the canvas view state, applied by a single reducer
+pub use form_factor_drawing::{CanvasCommand, CanvasPanel, MAX_PENDING_CHANGES};
+
+/// Back and forward through the shapes and detections inspected
+pub use form_factor_drawing::{MAX_SELECTION_HISTORY, SelectionHistory};
+
+/// Current project file format version
+pub use form_factor_drawing::PROJECT_FORMAT_VERSION;
+
+/// Named viewport bookmarks
+pub use form_factor_drawing::{MAX_BOOKMARK_SHORTCUTS, ViewBookmark};
+
+/// Canvas guide lines
+pub use form_factor_drawing::{Guide, GuideOrientation};
+
+/// Highlighter strokes over the form image
+pub use form_factor_drawing::{HIGHLIGHT_COLOR, HIGHLIGHT_WIDTH, Highlight};
+
+/// Tooltips on the field or detection under the pointer
+pub use form_factor_drawing::HoverTooltip;
+
+/// Color legend rows
+pub use form_factor_drawing::LegendEntry;
+
+/// Grid repeat layout for stamping copies of a shape
+pub use form_factor_drawing::{MAX_REPEAT_COPIES, RepeatGrid};
+
+/// Image, canvas and screen coordinate spaces and the mapping between them
+pub use form_factor_drawing::{CanvasPos, CoordinateMapper, ImageFit, ImagePos, ScreenPos};
+
+/// Automatic field naming patterns
+pub use form_factor_drawing::{NameParts, NamingScheme};
+
+/// Stamp symbols, the stamp library and placed stamps
+pub use form_factor_drawing::{STAMP_HEIGHT, Stamp, StampLibrary, StampSource, StampSymbol};
+
+/// Named annotation styles for new shapes
+pub use form_factor_drawing::{StylePreset, StylePresets};
+
+/// User-defined subtypes detections can be tagged with
+pub use form_factor_drawing::{DetectionSubtype, DetectionTaxonomy};
+
+/// Find and rename over shape and detection names
+pub use form_factor_drawing::{RenameMatch, RenameQuery};
+
+/// PNG and SVG export of the canvas
+pub use form_factor_drawing::{ExportFormat, MAX_EXPORT_DIMENSION};
+
+/// Plain text and Markdown export of the text read from the form
+pub use form_factor_drawing::{TextExportOptions, TextFormat};
+
+/// How long newly added detections flash
+pub use form_factor_drawing::DETECTION_FLASH_SECONDS;
+
+/// Splitting and merging detection regions
+pub use form_factor_drawing::{MIN_SPLIT_SIZE, SplitDirection};
+
+/// PDF printing with selected layers and a field appendix
+pub use form_factor_drawing::{PageSize, PrintOptions};
+
+/// Field values typeset onto a clean form image
+pub use form_factor_drawing::{REFILL_INK, RefillValue};
+
+/// Direction fields are ordered in for data entry
+pub use form_factor_drawing::ReadingDirection;
+
+/// Project templates for the new-project window
+pub use form_factor_drawing::{ProjectTemplate, project_templates_dir};
+
+/// Where a project's form image, detections and text came from
+pub use form_factor_drawing::{Provenance, SourceChannel};
+
+/// Searching, filtering and sorting project templates
+pub use form_factor_drawing::{
+    TemplateFilter, TemplateLoadProgress, TemplateSortOrder, template_departments, template_languages, template_tags,
+};
+
+/// Page layouts of multi-page templates
+pub use form_factor_drawing::{PageMatch, PageOrientation, TemplateAnchor, TemplatePage};
+
+/// Previewing field layouts with sample data
+pub use form_factor_drawing::{MIN_PREVIEW_TEXT_SCALE, PreviewValue, sample_value};
+
+/// Review of low-confidence field values across many filled forms
+pub use form_factor_drawing::{
+    BatchReview, DEFAULT_REVIEW_THRESHOLD, ReviewAction, ReviewInstance, ReviewItem, ReviewReason, ReviewStats,
+};
+
+/// Differences between two versions of a project
+pub use form_factor_drawing::{FieldChange, ImageChange, ProjectDiff, ShapeChange};
+
+/// Save conflicts on project files shared between operators
+pub use form_factor_drawing::SaveConflict;
+
+/// CSV and HTML validation reports across many filled forms
+pub use form_factor_drawing::{InstanceSummary, ReportFormat, Severity, ValidationFinding, ValidationReport};
+
+/// OCR text recorded per detection, for re-reading only changed regions, its word boxes, and corrections made to it
+pub use form_factor_drawing::{OcrRecord, OcrWord};
+
+/// OCR readings paired with the values operators kept, for tracking OCR accuracy
+pub use form_factor_drawing::OcrSample;
+
+/// On-disk cache of detection results per image and detector configuration
+pub use form_factor_drawing::{clear_detection_cache, detection_cache_dir};
+
+/// Incremental project saves and autosave
+pub use form_factor_drawing::{AUTOSAVE_INTERVAL_SECS, JournalSave, MAX_JOURNAL_RECORDS, journal_path};
+
+/// JSON and binary project file encodings
+pub use form_factor_drawing::{BINARY_PROJECT_MAGIC, ProjectEncoding};
+
+/// zstd compression of project files and detection caches
+pub use form_factor_drawing::{ZSTD_MAGIC, compress, is_compressed};
+
+/// Password-protected project files
+pub use form_factor_drawing::{ENCRYPTED_PROJECT_MAGIC, decrypt, encrypt, is_encrypted};
+
+/// Texture memory budget with least-recently-used eviction
+pub use form_factor_drawing::{DEFAULT_TEXTURE_BUDGET_MB, TextureCache};
+
+/// Drag-and-drop of form images and project files
+pub use form_factor_drawing::{DROP_IMAGE_EXTENSIONS, DROP_PROJECT_EXTENSIONS, DropKind, DroppedFile};
+
+/// Pasting clipboard images onto the canvas
+pub use form_factor_drawing::{PasteTarget, clipboard_image, pasted_images_dir};
+
+/// Screenshots of canvas regions for feedback
+pub use form_factor_drawing::{ScreenshotCapture, ScreenshotStage, copy_image_to_clipboard, screenshots_dir};
+
+/// Field values and validation rules
+pub use form_factor_drawing::{FieldCondition, FieldFormat, FieldIssue, FieldRule, field_applies, is_checked};
+
+/// Field groups and repeating sections of templates
+pub use form_factor_drawing::{FieldGroup, RepeatingSection, row_field_name};
 
-/// Shape types (rectangles, circles, polygons)
+/// Shape types (rectangles, circles, ellipses, polygons, lines, polylines, arrow and callout markups) and outline styles
 pub use form_factor_drawing::{
-    Circle, CircleBuilder, PolygonShape, Rectangle, Shape, ShapeError, ShapeErrorKind,
+    Arrow, Callout, Circle, CircleBuilder, Ellipse, Line, PolygonShape, Polyline, Rectangle, Shape, ShapeError,
+    ShapeErrorKind, StrokeStyle, length_label,
 };
 
-/// Drawing tool mode (rectangle, circle, freehand, select)
-pub use form_factor_drawing::ToolMode;
+/// Drawing tool mode (rectangle, circle, freehand, select) and custom tools registered next to it
+pub use form_factor_drawing::{CustomTool, SharedTool, ToolInfo, ToolInput, ToolMode, ToolRegistry};
+
+/// Cursor shown where a drag would rotate
+pub use form_factor_drawing::ROTATE_CURSOR;
+
+/// Annotation effort statistics
+pub use form_factor_drawing::{IDLE_TIMEOUT_SECS, SessionStats};
+
+/// OCR confidence calibration learned from review decisions
+pub use form_factor_drawing::{
+    CALIBRATION_BAND_WIDTH, CalibrationBand, CalibrationCurve, ConfidenceCalibration, MIN_CALIBRATION_SAMPLES,
+    TemplateCalibration,
+};
+
+/// Headless input simulator for scripted canvas tests
+pub use form_factor_drawing::CanvasSimulator;
+
+/// Page assignment and status tracking
+pub use form_factor_drawing::{PageTask, TaskStatus, Workflow};
 
 /// Layer management types
 pub use form_factor_drawing::{Layer, LayerError, LayerManager, LayerType};
 
 /// Recent projects tracking
-pub use form_factor_drawing::RecentProjects;
+pub use form_factor_drawing::{MAX_RECENT_PROJECTS, RecentProjects};
+
+/// Platform-specific application configuration directory
+pub use form_factor_drawing::config_dir;
+
+/// Persistent user settings
+pub use form_factor_drawing::{
+    AppearanceSettings, CanvasSettings, CompressionSettings, DetectionColors, DetectionKind, DetectionSettings,
+    OcrSettings, Settings, ShortcutSettings, Theme,
+};
+
+/// Shortcut action names
+pub use form_factor_drawing::{ACTION_OPEN_SETTINGS, ACTION_TOGGLE_FRAME_TIMING, ACTION_TOGGLE_LOG};
+
+/// Review comment threads
+pub use form_factor_drawing::{Comment, CommentTarget, CommentThread};
+
+/// Collaborative editing over a shared operation log
+pub use form_factor_drawing::{
+    COLLAB_SYNC_INTERVAL_SECS, CollabError, CollabErrorKind, CollabSession, InMemorySyncClient, InMemorySyncServer, OpId,
+    Operation, OperationLog, PRESENCE_TIMEOUT_SECS, Presence, ShapeEdit, StoreSyncTransport, SyncTransport,
+};
+
+/// Storage for projects and instances on disk or in S3-compatible buckets
+pub use form_factor_drawing::{
+    FileStore, HttpClient, HttpRequest, HttpResponse, ProjectStore, S3Credentials, S3Settings, S3Store, StorageSettings,
+    StoreError, StoreErrorKind, default_store_dir,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use form_factor_drawing::UreqClient;
+
+/// PII detection and redaction
+pub use form_factor_drawing::{PiiKind, RedactionReason, RedactionRegion, detect_pii, redact_image};
 
 // ============================================================================
 // Text Detection
 // ============================================================================
 
-#[cfg(feature = "text-detection")]
+// Detection and OCR wrap native OpenCV and Tesseract, which aren't built for
+// the browser. With the features on in a wasm32 build these types are left
+// out and the canvas's detection methods return
+// `CanvasErrorKind::NotInBrowser`.
+
+#[cfg(all(feature = "text-detection", not(target_arch = "wasm32")))]
 /// Text detector using OpenCV DB model
 pub use form_factor_cv::TextDetector;
 
-#[cfg(feature = "text-detection")]
+#[cfg(all(feature = "text-detection", not(target_arch = "wasm32")))]
 /// Detected text region
 pub use form_factor_cv::TextRegion;
 
-#[cfg(feature = "text-detection")]
+#[cfg(all(feature = "text-detection", not(target_arch = "wasm32")))]
 /// Text detection error
 pub use form_factor_cv::TextDetectionError;
 
-#[cfg(feature = "text-detection")]
+#[cfg(all(feature = "text-detection", not(target_arch = "wasm32")))]
 /// Text detection error kind
 pub use form_factor_cv::TextDetectionErrorKind;
 
@@ -118,70 +375,172 @@ pub use form_factor_cv::TextDetectionErrorKind;
 // Logo Detection
 // ============================================================================
 
-#[cfg(feature = "logo-detection")]
+#[cfg(all(feature = "logo-detection", not(target_arch = "wasm32")))]
 /// Logo detector using OpenCV template and feature matching
 pub use form_factor_cv::LogoDetector;
 
-#[cfg(feature = "logo-detection")]
+#[cfg(all(feature = "logo-detection", not(target_arch = "wasm32")))]
 /// Logo detection method (template matching or feature matching)
 pub use form_factor_cv::LogoDetectionMethod;
 
-#[cfg(feature = "logo-detection")]
+#[cfg(all(feature = "logo-detection", not(target_arch = "wasm32")))]
 /// Logo template for detection
 pub use form_factor_cv::Logo;
 
-#[cfg(feature = "logo-detection")]
+#[cfg(all(feature = "logo-detection", not(target_arch = "wasm32")))]
 /// Logo detection result
 pub use form_factor_cv::LogoDetectionResult;
 
-#[cfg(feature = "logo-detection")]
+#[cfg(all(feature = "logo-detection", not(target_arch = "wasm32")))]
 /// Logo location in image
 pub use form_factor_cv::LogoLocation;
 
-#[cfg(feature = "logo-detection")]
+#[cfg(all(feature = "logo-detection", not(target_arch = "wasm32")))]
 /// Logo size
 pub use form_factor_cv::LogoSize;
 
+#[cfg(all(feature = "logo-detection", not(target_arch = "wasm32")))]
+/// Logo detection error
+pub use form_factor_cv::LogoDetectionError;
+
+#[cfg(all(feature = "logo-detection", not(target_arch = "wasm32")))]
+/// Logo detection error kind
+pub use form_factor_cv::LogoDetectionErrorKind;
+
+// ============================================================================
+// Bubble-Sheet (OMR) Reading
+// ============================================================================
+
+#[cfg(all(feature = "omr", not(target_arch = "wasm32")))]
+/// Bubble-sheet answer detection
+pub use form_factor_cv::{AnswerGrid, CHOICE_SEPARATOR, OmrAnswer, OmrDetector, OmrError, OmrErrorKind};
+
+#[cfg(all(any(feature = "text-detection", feature = "logo-detection", feature = "omr"), not(target_arch = "wasm32")))]
+/// Computer vision error wrapping text detection, logo detection and bubble-sheet errors
+pub use form_factor_cv::{CvError, CvErrorKind};
+
 // ============================================================================
 // OCR (Optical Character Recognition)
 // ============================================================================
 
-#[cfg(feature = "ocr")]
+#[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
 /// OCR engine for text extraction using Tesseract
 pub use form_factor_ocr::OCREngine;
 
-#[cfg(feature = "ocr")]
+#[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
 /// OCR configuration options
 pub use form_factor_ocr::OCRConfig;
 
-#[cfg(feature = "ocr")]
+#[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
 /// Page segmentation mode for OCR
 pub use form_factor_ocr::PageSegmentationMode;
 
-#[cfg(feature = "ocr")]
+#[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
 /// OCR engine mode (LSTM, Legacy, or both)
 pub use form_factor_ocr::EngineMode;
 
-#[cfg(feature = "ocr")]
+#[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
 /// Result of OCR text extraction
 pub use form_factor_ocr::OCRResult;
 
-#[cfg(feature = "ocr")]
+#[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
 /// Word-level OCR result with bounding box
 pub use form_factor_ocr::WordResult;
 
-#[cfg(feature = "ocr")]
+#[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
 /// Bounding box for text regions
 pub use form_factor_ocr::BoundingBox;
 
-#[cfg(feature = "ocr")]
+#[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
 /// OCR error
 pub use form_factor_ocr::OCRError;
 
-#[cfg(feature = "ocr")]
+#[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
 /// OCR error kind
 pub use form_factor_ocr::OCRErrorKind;
 
+#[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
+/// Normalize separators in a number read by OCR
+pub use form_factor_ocr::normalize_number;
+
+// ============================================================================
+// Diagnostics
+// ============================================================================
+
+/// In-memory log capture for crash reports and bug reports
+pub use diagnostics::{DEFAULT_LOG_CAPACITY, LogBuffer, LogBufferLayer, LogRecord};
+
+/// Panic capture and crash reports
+pub use diagnostics::{CrashReport, CrashReporter};
+
+/// Diagnostic bundle export
+pub use diagnostics::{DiagnosticBundle, ProjectSummary, SystemInfo};
+
+/// Filterable log viewer window
+pub use log_viewer::{LogFilter, LogViewer};
+
+/// Per-frame timing by section, fed by tracing spans
+pub use frame_timing::{DEFAULT_FRAME_HISTORY, FrameSample, FrameSection, FrameTimingLayer, FrameTimingOverlay, FrameTimings};
+
+// ============================================================================
+// Overlays
+// ============================================================================
+
+/// Stack of overlay windows, and modal confirmation dialogs
+pub use overlay::{DialogOutcome, ModalDialog, OverlayManager};
+
+/// Unsaved work to ask about before it is lost
+pub use unsaved::UnsavedWork;
+
+/// Workspace arrangement remembered per mode
+pub use workspace::{WorkspaceLayout, WorkspaceMemory, WorkspaceMode};
+
+/// Callbacks subsystems run on entering and leaving a workspace mode
+pub use workspace::{ModeHook, ModeHooks};
+
+/// How overlays are presented, stacked and dismissed
+pub use form_factor_core::{DismissPolicy, OverlayKind, OverlaySpec};
+
+/// Tabbed settings editor
+pub use settings_overlay::{SETTINGS_OVERLAY, SettingsOverlay, SettingsTab};
+
+// ============================================================================
+// Onboarding
+// ============================================================================
+
+/// Screen areas of named UI elements, used to point at them
+pub use form_factor_core::{
+    ANCHOR_CANVAS, ANCHOR_DETECTION, ANCHOR_FILE, ANCHOR_LAYERS, ANCHOR_TOOLBAR, anchor_rect, register_anchor,
+};
+
+/// Declarative guided tour definitions
+pub use onboarding::{Tour, TourStep, WELCOME_TOUR};
+
+/// Guided tour playback and first-run state
+pub use onboarding::{GuidedTour, OnboardingState, TourCompletion, TourOutcome};
+
+// ============================================================================
+// Metrics
+// ============================================================================
+
+#[cfg(feature = "metrics")]
+/// Thread-safe registry of counters and histograms
+pub use form_factor_core::MetricsRegistry;
+
+#[cfg(feature = "metrics")]
+/// Metric data types
+pub use form_factor_core::{Histogram, MetricsSnapshot};
+
+#[cfg(feature = "metrics")]
+/// Pluggable output formats and the HTTP scrape endpoint
+pub use form_factor_core::{JsonExporter, MetricsEndpoint, MetricsExporter, PrometheusExporter};
+
+#[cfg(feature = "metrics")]
+/// Names of the standard metrics
+pub use form_factor_core::{
+    DEFAULT_BUCKETS, DETECTION_LATENCY_SECONDS, DETECTIONS_TOTAL, FRAME_TIME_SECONDS, OCR_CONFIDENCE,
+};
+
 // ============================================================================
 // Plugin System
 // ============================================================================
@@ -206,14 +565,26 @@ pub use form_factor_plugins::EventBus;
 /// Event sender for publishing events
 pub use form_factor_plugins::EventSender;
 
+#[cfg(feature = "plugins")]
+/// Event drained from the bus with its hop span
+pub use form_factor_plugins::TracedEvent;
+
 #[cfg(feature = "plugins")]
 /// Application event types for inter-plugin communication
-pub use form_factor_plugins::AppEvent;
+pub use form_factor_plugins::{AppEvent, DecodeError, EVENT_SCHEMA_VERSION};
 
 #[cfg(feature = "plugins")]
 /// Plugin builder trait
 pub use form_factor_plugins::PluginBuilder;
 
+#[cfg(feature = "plugins")]
+/// Canvas and plugin sidebar embeddable in other egui applications
+pub use widget::{FormFactorWidget, apply_canvas_event, canvas_command, take_canvas_changed, take_ocr_sample_events};
+
+#[cfg(feature = "plugins")]
+/// Searchable command palette overlay
+pub use form_factor_plugins::{Command, CommandPalette, CommandRegistry, PALETTE_SHORTCUT, fuzzy_score};
+
 // Plugin implementations (feature-gated)
 #[cfg(feature = "plugin-canvas")]
 pub use form_factor_plugins::canvas;
@@ -230,6 +601,9 @@ pub use form_factor_plugins::detection;
 #[cfg(feature = "plugin-ocr")]
 pub use form_factor_plugins::ocr;
 
+#[cfg(feature = "plugin-quality")]
+pub use form_factor_plugins::quality;
+
 // ============================================================================
 // Advanced: Direct module access for backend implementations
 // ============================================================================
@@ -241,4 +615,6 @@ pub use form_factor_plugins::ocr;
 pub mod backends {
     #[cfg(feature = "backend-eframe")]
     pub use form_factor_backends::eframe_backend;
+    #[cfg(feature = "backend-eframe")]
+    pub use form_factor_backends::headless_backend;
 }
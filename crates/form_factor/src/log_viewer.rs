@@ -0,0 +1,217 @@
+//! In-app log viewer window
+//!
+//! Shows the events captured by a [`LogBuffer`] with level, target and text
+//! filtering, so failures can be diagnosed without restarting from a
+//! terminal with `RUST_LOG` set. Filtering by trace ID shows every event
+//! of one request, such as a detection run.
+
+use crate::{LogBuffer, LogRecord};
+use tracing::Level;
+
+/// Levels offered in the viewer, most to least severe
+const LEVELS: [Level; 5] = [Level::ERROR, Level::WARN, Level::INFO, Level::DEBUG, Level::TRACE];
+
+/// Criteria for selecting log records
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFilter {
+    /// Least severe level to show
+    pub min_level: Level,
+    /// Only show targets starting with this prefix (empty shows all)
+    pub target: String,
+    /// Case-insensitive text that must appear in the target or message
+    pub search: String,
+    /// Only show records logged for this trace ID (empty shows all)
+    pub trace_id: String,
+}
+
+impl LogFilter {
+    /// Filter that shows every record
+    pub fn new() -> Self {
+        Self {
+            min_level: Level::TRACE,
+            target: String::new(),
+            search: String::new(),
+            trace_id: String::new(),
+        }
+    }
+
+    /// Show only records at least as severe as `level`
+    pub fn with_min_level(mut self, level: Level) -> Self {
+        self.min_level = level;
+        self
+    }
+
+    /// Show only records whose target starts with `prefix`
+    pub fn with_target(mut self, prefix: impl Into<String>) -> Self {
+        self.target = prefix.into();
+        self
+    }
+
+    /// Show only records containing `text`
+    pub fn with_search(mut self, text: impl Into<String>) -> Self {
+        self.search = text.into();
+        self
+    }
+
+    /// Show only records logged for the request with trace ID `trace_id`
+    pub fn with_trace_id(mut self, trace_id: impl Into<String>) -> Self {
+        self.trace_id = trace_id.into();
+        self
+    }
+
+    /// Whether a record passes the filter
+    pub fn matches(&self, record: &LogRecord) -> bool {
+        // tracing orders levels by verbosity: TRACE > DEBUG > ... > ERROR
+        if record.level > self.min_level {
+            return false;
+        }
+        if !record.target.starts_with(self.target.trim()) {
+            return false;
+        }
+        let trace_id = self.trace_id.trim();
+        if !trace_id.is_empty() && record.trace_id.as_deref() != Some(trace_id) {
+            return false;
+        }
+        let search = self.search.trim().to_lowercase();
+        search.is_empty()
+            || record.message.to_lowercase().contains(&search)
+            || record.target.to_lowercase().contains(&search)
+    }
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogBuffer {
+    /// Buffered records that pass a filter, oldest first
+    pub fn filtered(&self, filter: &LogFilter) -> Vec<LogRecord> {
+        self.records().into_iter().filter(|r| filter.matches(r)).collect()
+    }
+}
+
+/// Floating window listing captured log events
+#[derive(Debug, Clone)]
+pub struct LogViewer {
+    /// Source of events
+    logs: LogBuffer,
+    /// Current filter settings
+    filter: LogFilter,
+    /// Whether the window is visible
+    open: bool,
+    /// Keep the newest event in view
+    follow: bool,
+}
+
+impl LogViewer {
+    /// Create a hidden viewer over a log buffer
+    pub fn new(logs: LogBuffer) -> Self {
+        Self {
+            logs,
+            filter: LogFilter::new().with_min_level(Level::INFO),
+            open: false,
+            follow: true,
+        }
+    }
+
+    /// Current filter settings
+    pub fn filter(&self) -> &LogFilter {
+        &self.filter
+    }
+
+    /// Mutable access to the filter settings
+    pub fn filter_mut(&mut self) -> &mut LogFilter {
+        &mut self.filter
+    }
+
+    /// Whether the window is visible
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Show or hide the window
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    /// Show the log viewer window
+    ///
+    /// Returns true if the window was shown.
+    pub fn show(&mut self, ctx: &egui::Context) -> bool {
+        if !self.open {
+            return false;
+        }
+
+        let mut open = true;
+        egui::Window::new("Log")
+            .open(&mut open)
+            .default_size([640.0, 360.0])
+            .show(ctx, |ui| {
+                self.render_controls(ui);
+                ui.separator();
+                self.render_records(ui);
+            });
+
+        if !open {
+            self.open = false;
+        }
+        true
+    }
+
+    fn render_controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("log_viewer_level")
+                .selected_text(self.filter.min_level.as_str())
+                .show_ui(ui, |ui| {
+                    for level in LEVELS {
+                        ui.selectable_value(&mut self.filter.min_level, level, level.as_str());
+                    }
+                });
+            ui.label("Target:");
+            ui.add(egui::TextEdit::singleline(&mut self.filter.target).desired_width(140.0));
+            ui.label("Search:");
+            ui.add(egui::TextEdit::singleline(&mut self.filter.search).desired_width(160.0));
+            ui.label("Trace:");
+            ui.add(egui::TextEdit::singleline(&mut self.filter.trace_id).desired_width(80.0));
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.follow, "Follow");
+            if ui.button("Copy").clicked() {
+                let text = self
+                    .logs
+                    .filtered(&self.filter)
+                    .iter()
+                    .map(|r| r.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                ui.ctx().copy_text(text);
+            }
+            if ui.button("Clear").clicked() {
+                self.logs.clear();
+            }
+        });
+    }
+
+    fn render_records(&self, ui: &mut egui::Ui) {
+        let records = self.logs.filtered(&self.filter);
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+
+        egui::ScrollArea::both()
+            .auto_shrink([false, false])
+            .stick_to_bottom(self.follow)
+            .show_rows(ui, row_height, records.len(), |ui, range| {
+                for record in &records[range] {
+                    let color = match record.level {
+                        Level::ERROR => egui::Color32::from_rgb(230, 80, 80),
+                        Level::WARN => egui::Color32::from_rgb(230, 180, 60),
+                        Level::INFO => ui.visuals().text_color(),
+                        _ => ui.visuals().weak_text_color(),
+                    };
+                    ui.label(egui::RichText::new(record.to_string()).monospace().color(color));
+                }
+            });
+        ui.label(format!("{} of {} events", records.len(), self.logs.len()));
+    }
+}
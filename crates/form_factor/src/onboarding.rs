@@ -0,0 +1,427 @@
+//! First-run onboarding and guided tours
+//!
+//! A [`Tour`] is a declarative list of steps, each with a title, body text
+//! and an optional UI anchor to highlight (see [`register_anchor`]). Tours
+//! can be built in code or loaded from JSON, so applications embedding the
+//! framework can describe their own. [`GuidedTour`] plays a tour back as a
+//! popup next to the highlighted element, and [`OnboardingState`] remembers
+//! which tours the user has already finished or skipped.
+//!
+//! [`register_anchor`]: crate::register_anchor
+
+use crate::{
+    ANCHOR_CANVAS, ANCHOR_DETECTION, ANCHOR_FILE, ANCHOR_LAYERS, ANCHOR_TOOLBAR, ConfigError, IoError,
+    IoOperation, anchor_rect, config_dir,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use tracing::{debug, instrument, warn};
+
+/// Id of the built-in first-run tour
+pub const WELCOME_TOUR: &str = "welcome";
+
+/// Width of the tour popup
+const POPUP_WIDTH: f32 = 320.0;
+
+/// Gap between the highlighted element and the popup
+const POPUP_GAP: f32 = 12.0;
+
+/// One step of a guided tour
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TourStep {
+    /// Heading of the step
+    pub title: String,
+    /// Explanation shown below the heading
+    pub body: String,
+    /// Anchor name of the UI element to highlight
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+}
+
+impl TourStep {
+    /// Create a step that is shown centered on screen
+    pub fn new(title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            body: body.into(),
+            target: None,
+        }
+    }
+
+    /// Highlight the UI element registered under an anchor name
+    pub fn highlight(mut self, anchor: impl Into<String>) -> Self {
+        self.target = Some(anchor.into());
+        self
+    }
+}
+
+/// Declarative guided tour definition
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tour {
+    /// Identifier used to remember whether the tour was completed
+    pub id: String,
+    /// Steps in display order
+    pub steps: Vec<TourStep>,
+}
+
+impl Tour {
+    /// Create a tour with no steps
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            steps: Vec::new(),
+        }
+    }
+
+    /// Append a step
+    pub fn step(mut self, step: TourStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Parse and validate a tour definition from JSON
+    pub fn from_json(json: &str) -> Result<Self, ConfigError> {
+        let tour: Self = serde_json::from_str(json).map_err(|e| {
+            ConfigError::new(format!("Invalid tour definition: {}", e), line!(), file!())
+                .with_expected("JSON object with \"id\" and \"steps\"")
+        })?;
+        tour.validate()?;
+        Ok(tour)
+    }
+
+    /// Check that the tour has an id and at least one step
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.id.trim().is_empty() {
+            return Err(ConfigError::new("Tour id is empty", line!(), file!()).with_key("id"));
+        }
+        if self.steps.is_empty() {
+            return Err(ConfigError::new(format!("Tour '{}' has no steps", self.id), line!(), file!())
+                .with_key("steps"));
+        }
+        Ok(())
+    }
+
+    /// Built-in tour of the main window shown on first launch
+    pub fn welcome() -> Self {
+        Self::new(WELCOME_TOUR)
+            .step(TourStep::new(
+                "Welcome to Form Factor",
+                "This short tour shows where everything is. Use Next and Back to move \
+                 between steps, or skip the tour at any time.",
+            ))
+            .step(
+                TourStep::new(
+                    "Drawing tools",
+                    "Pick a tool to draw rectangles, circles and freehand regions, or to \
+                     select, edit and rotate existing shapes.",
+                )
+                .highlight(ANCHOR_TOOLBAR),
+            )
+            .step(
+                TourStep::new(
+                    "Canvas",
+                    "Load a scanned form here and draw over it. Scroll to zoom and drag \
+                     with the middle mouse button to pan.",
+                )
+                .highlight(ANCHOR_CANVAS),
+            )
+            .step(
+                TourStep::new(
+                    "Layers",
+                    "Show, hide, select and clear the image, shapes, detections and grid.",
+                )
+                .highlight(ANCHOR_LAYERS),
+            )
+            .step(
+                TourStep::new(
+                    "Detection",
+                    "Find text regions and logos automatically; they appear on the \
+                     detections layer.",
+                )
+                .highlight(ANCHOR_DETECTION),
+            )
+            .step(
+                TourStep::new("Projects", "Open and save projects and export your work.").highlight(ANCHOR_FILE),
+            )
+            .step(TourStep::new(
+                "You're all set",
+                "You can replay this tour at any time with the Tour button.",
+            ))
+    }
+}
+
+/// How a tour ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+pub enum TourOutcome {
+    /// The user went through every step
+    Finished,
+    /// The user dismissed the tour early
+    Skipped,
+}
+
+/// Tour that just ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TourCompletion {
+    /// Id of the tour
+    pub tour_id: String,
+    /// How it ended
+    pub outcome: TourOutcome,
+}
+
+/// Playback state of a guided tour
+#[derive(Debug, Clone, Default)]
+pub struct GuidedTour {
+    /// Tour being shown
+    tour: Option<Tour>,
+    /// Index of the current step
+    step: usize,
+}
+
+impl GuidedTour {
+    /// Create a player with no active tour
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a tour from its first step, replacing any active tour
+    ///
+    /// Tours without steps are ignored.
+    pub fn start(&mut self, tour: Tour) {
+        if tour.steps.is_empty() {
+            warn!(tour = %tour.id, "Ignoring tour without steps");
+            return;
+        }
+        debug!(tour = %tour.id, steps = tour.steps.len(), "Starting tour");
+        self.tour = Some(tour);
+        self.step = 0;
+    }
+
+    /// Whether a tour is being shown
+    pub fn is_active(&self) -> bool {
+        self.tour.is_some()
+    }
+
+    /// Tour being shown
+    pub fn tour(&self) -> Option<&Tour> {
+        self.tour.as_ref()
+    }
+
+    /// Index of the current step
+    pub fn step_index(&self) -> usize {
+        self.step
+    }
+
+    /// Step being shown
+    pub fn current_step(&self) -> Option<&TourStep> {
+        self.tour.as_ref().and_then(|tour| tour.steps.get(self.step))
+    }
+
+    /// Advance to the next step
+    ///
+    /// Returns the completion when advancing past the last step.
+    pub fn advance(&mut self) -> Option<TourCompletion> {
+        let len = self.tour.as_ref()?.steps.len();
+        if self.step + 1 < len {
+            self.step += 1;
+            None
+        } else {
+            self.end(TourOutcome::Finished)
+        }
+    }
+
+    /// Go back to the previous step
+    pub fn back(&mut self) {
+        self.step = self.step.saturating_sub(1);
+    }
+
+    /// Dismiss the active tour
+    pub fn skip(&mut self) -> Option<TourCompletion> {
+        self.end(TourOutcome::Skipped)
+    }
+
+    fn end(&mut self, outcome: TourOutcome) -> Option<TourCompletion> {
+        let tour = self.tour.take()?;
+        self.step = 0;
+        debug!(tour = %tour.id, %outcome, "Tour ended");
+        Some(TourCompletion {
+            tour_id: tour.id,
+            outcome,
+        })
+    }
+
+    /// Draw the current step and handle its buttons
+    ///
+    /// The target element, if registered this frame, is outlined and the
+    /// popup placed beside it; otherwise the popup is centered. Returns the
+    /// completion when the tour ends this frame.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<TourCompletion> {
+        let tour = self.tour.as_ref()?;
+        let step = tour.steps.get(self.step)?.clone();
+        let count = tour.steps.len();
+        let target = step.target.as_deref().and_then(|name| anchor_rect(ctx, name));
+
+        let mut area = egui::Area::new(egui::Id::new("guided_tour")).order(egui::Order::Foreground);
+        if let Some(rect) = target {
+            let highlight = egui::LayerId::new(egui::Order::Foreground, egui::Id::new("guided_tour_highlight"));
+            let stroke = egui::Stroke::new(3.0, ctx.style().visuals.selection.stroke.color);
+            ctx.layer_painter(highlight)
+                .rect_stroke(rect.expand(4.0), 6.0, stroke, egui::StrokeKind::Outside);
+
+            // Prefer below the target, but go above it near the bottom of the screen
+            area = if rect.center().y < ctx.content_rect().center().y {
+                area.pivot(egui::Align2::LEFT_TOP)
+                    .fixed_pos(rect.left_bottom() + egui::vec2(0.0, POPUP_GAP))
+            } else {
+                area.pivot(egui::Align2::LEFT_BOTTOM)
+                    .fixed_pos(rect.left_top() - egui::vec2(0.0, POPUP_GAP))
+            };
+        } else {
+            area = area.anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO);
+        }
+
+        let mut action = None;
+        area.show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_width(POPUP_WIDTH);
+                ui.strong(&step.title);
+                ui.label(&step.body);
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.weak(format!("{} of {}", self.step + 1, count));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        let next = if self.step + 1 < count { "Next" } else { "Finish" };
+                        if ui.button(next).clicked() {
+                            action = Some(TourAction::Next);
+                        }
+                        if ui.add_enabled(self.step > 0, egui::Button::new("Back")).clicked() {
+                            action = Some(TourAction::Back);
+                        }
+                        if ui.button("Skip tour").clicked() {
+                            action = Some(TourAction::Skip);
+                        }
+                    });
+                });
+            });
+        });
+
+        match action? {
+            TourAction::Next => self.advance(),
+            TourAction::Back => {
+                self.back();
+                None
+            }
+            TourAction::Skip => self.skip(),
+        }
+    }
+}
+
+/// Button pressed in the tour popup
+#[derive(Debug, Clone, Copy)]
+enum TourAction {
+    Next,
+    Back,
+    Skip,
+}
+
+/// Tours the user has already seen
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnboardingState {
+    /// Ids of tours that were finished or skipped
+    #[serde(default)]
+    completed_tours: BTreeSet<String>,
+}
+
+impl OnboardingState {
+    /// Default state file path: `onboarding.json` in the config directory
+    pub fn default_path() -> PathBuf {
+        config_dir().join("onboarding.json")
+    }
+
+    /// Load state from the default path
+    ///
+    /// Returns an empty state if the file doesn't exist or cannot be read.
+    pub fn load() -> Self {
+        Self::load_from(&Self::default_path())
+    }
+
+    /// Load state from a file
+    ///
+    /// Returns an empty state if the file doesn't exist or cannot be read.
+    /// Errors are logged but not propagated.
+    #[instrument]
+    pub fn load_from(path: &Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                warn!(?path, error = %e, "Failed to parse onboarding state");
+                Self::default()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                debug!("No onboarding state found, treating as first run");
+                Self::default()
+            }
+            Err(e) => {
+                warn!(?path, error = %e, "Failed to read onboarding state");
+                Self::default()
+            }
+        }
+    }
+
+    /// Save state to the default path
+    pub fn save(&self) -> Result<(), IoError> {
+        self.save_to(&Self::default_path())
+    }
+
+    /// Save state to a file, creating its directory if needed
+    #[instrument(skip(self))]
+    pub fn save_to(&self, path: &Path) -> Result<(), IoError> {
+        let path_str = path.to_string_lossy().to_string();
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                IoError::new(
+                    format!("Failed to create config directory: {}", e),
+                    parent.to_string_lossy().to_string(),
+                    IoOperation::Create,
+                    line!(),
+                    file!(),
+                )
+            })?;
+        }
+
+        let json = serde_json::to_string_pretty(self).map_err(|e| {
+            IoError::new(
+                format!("Failed to serialize onboarding state: {}", e),
+                &path_str,
+                IoOperation::Write,
+                line!(),
+                file!(),
+            )
+        })?;
+
+        std::fs::write(path, json).map_err(|e| {
+            IoError::new(
+                format!("Failed to write onboarding state: {}", e),
+                &path_str,
+                IoOperation::Write,
+                line!(),
+                file!(),
+            )
+        })
+    }
+
+    /// Whether a tour was finished or skipped before
+    pub fn is_completed(&self, tour_id: &str) -> bool {
+        self.completed_tours.contains(tour_id)
+    }
+
+    /// Remember that a tour was finished or skipped
+    pub fn mark_completed(&mut self, tour_id: impl Into<String>) {
+        self.completed_tours.insert(tour_id.into());
+    }
+
+    /// Forget a tour so it is offered again
+    pub fn reset(&mut self, tour_id: &str) {
+        self.completed_tours.remove(tour_id);
+    }
+}
@@ -0,0 +1,386 @@
+//! Stack of overlay windows, and modal dialogs
+//!
+//! [`OverlayManager`] tracks which overlays are open and in what order, and
+//! draws each one with consistent chrome. Overlays own their own state; the
+//! manager only decides whether and where they appear.
+//!
+//! Overlays registered with an [`OverlaySpec`], such as those contributed by
+//! plugins, choose how they are presented, their z-order and how they may be
+//! dismissed. Unregistered overlays are windows at z-order 0 that Escape
+//! closes. Overlays stack by z-order, then in the order they were opened or
+//! clicked; modal dialogs stack above every other kind. Escape closes the
+//! topmost overlay unless its dismissal policy says otherwise.
+//!
+//! [`ModalDialog`] is a short prompt with a row of buttons, such as a
+//! confirmation before a destructive action. It keeps keyboard focus on its
+//! buttons while open, starting on the default button so Enter chooses it,
+//! and Escape cancels. Show dialogs before
+//! [`OverlayManager::handle_escape`] so Escape cancels the dialog rather than
+//! closing an overlay beneath it.
+
+use crate::{DismissPolicy, OverlayKind, OverlaySpec};
+use std::collections::BTreeMap;
+
+/// Default size of an overlay window
+const DEFAULT_OVERLAY_SIZE: [f32; 2] = [520.0, 380.0];
+
+/// Default width of a side sheet
+const SIDE_SHEET_WIDTH: f32 = 360.0;
+
+/// Width of a modal dialog's message
+const DIALOG_WIDTH: f32 = 360.0;
+
+/// Open/closed state and stacking order of overlay windows
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OverlayManager {
+    /// Open overlay ids, topmost last
+    stack: Vec<String>,
+    /// Presentation of registered overlays, by id
+    specs: BTreeMap<String, OverlaySpec>,
+}
+
+impl OverlayManager {
+    /// Create a manager with no open overlays
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register how an overlay is presented, replacing any earlier registration with the same id
+    pub fn register(&mut self, spec: OverlaySpec) {
+        tracing::debug!(overlay = %spec.id, kind = ?spec.kind, z_order = spec.z_order, "Overlay registered");
+        let id = spec.id.clone();
+        self.specs.insert(id.clone(), spec);
+        if self.is_open(&id) {
+            self.open(&id);
+        }
+    }
+
+    /// Registration of an overlay, `None` for unregistered overlays
+    pub fn spec(&self, id: &str) -> Option<&OverlaySpec> {
+        self.specs.get(id)
+    }
+
+    /// Presentation and dismissal policy of an overlay
+    fn style(&self, id: &str) -> (OverlayKind, DismissPolicy) {
+        self.specs.get(id).map_or_else(Default::default, |spec| (spec.kind, spec.dismiss))
+    }
+
+    /// Key the stack is ordered by: modals above everything else, then z-order
+    fn stack_key(&self, id: &str) -> (bool, i32) {
+        self.specs
+            .get(id)
+            .map_or((false, 0), |spec| (spec.kind == OverlayKind::Modal, spec.z_order))
+    }
+
+    /// Open an overlay, or bring it to the top of its z-order if already open
+    pub fn open(&mut self, id: &str) {
+        self.stack.retain(|open| open != id);
+        let key = self.stack_key(id);
+        let at = self
+            .stack
+            .iter()
+            .rposition(|open| self.stack_key(open) <= key)
+            .map_or(0, |i| i + 1);
+        self.stack.insert(at, id.to_string());
+    }
+
+    /// Close an overlay
+    pub fn close(&mut self, id: &str) {
+        self.stack.retain(|open| open != id);
+    }
+
+    /// Open a closed overlay or close an open one
+    pub fn toggle(&mut self, id: &str) {
+        if self.is_open(id) {
+            self.close(id);
+        } else {
+            self.open(id);
+        }
+    }
+
+    /// Whether an overlay is open
+    pub fn is_open(&self, id: &str) -> bool {
+        self.stack.iter().any(|open| open == id)
+    }
+
+    /// Whether any overlay is open
+    pub fn is_any_open(&self) -> bool {
+        !self.stack.is_empty()
+    }
+
+    /// Ids of the open overlays, bottom first
+    pub fn open_overlays(&self) -> &[String] {
+        &self.stack
+    }
+
+    /// Id of the topmost overlay
+    pub fn top(&self) -> Option<&str> {
+        self.stack.last().map(String::as_str)
+    }
+
+    /// Close the topmost overlay regardless of its dismissal policy, returning its id
+    pub fn close_top(&mut self) -> Option<String> {
+        self.stack.pop()
+    }
+
+    /// Close the topmost overlay if Escape was pressed this frame and its dismissal policy allows it
+    ///
+    /// Call once per frame before showing overlays. Escape is consumed
+    /// while any overlay is open, so it never reaches an overlay further
+    /// down or the canvas.
+    pub fn handle_escape(&mut self, ctx: &egui::Context) {
+        if !self.is_any_open() || !ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape)) {
+            return;
+        }
+        let Some(id) = self.top().map(str::to_string) else {
+            return;
+        };
+        if self.style(&id).1.allows_escape() {
+            self.close(&id);
+            tracing::debug!(overlay = %id, "Overlay closed with Escape");
+        } else {
+            tracing::debug!(overlay = %id, "Overlay ignores Escape");
+        }
+    }
+
+    /// Draw an overlay if it is open
+    ///
+    /// Returns the value produced by `add_contents`, or `None` when the
+    /// overlay is closed. Registered overlays are drawn as their spec says;
+    /// others are windows. Dismissing the overlay in a way its policy
+    /// allows closes it, and clicking a window brings it to the top of its
+    /// z-order.
+    pub fn show<R>(
+        &mut self,
+        ctx: &egui::Context,
+        id: &str,
+        title: &str,
+        add_contents: impl FnOnce(&mut egui::Ui) -> R,
+    ) -> Option<R> {
+        if !self.is_open(id) {
+            return None;
+        }
+
+        let (kind, dismiss) = self.style(id);
+        let mut open = true;
+        let inner = match kind {
+            OverlayKind::Modal => {
+                let response = egui::Modal::new(overlay_id(id)).show(ctx, |ui| {
+                    ui.heading(title);
+                    ui.separator();
+                    add_contents(ui)
+                });
+                if dismiss.allows_click_outside() && response.backdrop_response.clicked() {
+                    open = false;
+                }
+                Some(response.inner)
+            }
+            OverlayKind::Window | OverlayKind::SideSheet => {
+                let window = egui::Window::new(title).id(overlay_id(id)).collapsible(false);
+                let window = match kind {
+                    OverlayKind::SideSheet => window
+                        .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::ZERO)
+                        .default_size([SIDE_SHEET_WIDTH, ctx.content_rect().height()]),
+                    _ => window.default_size(DEFAULT_OVERLAY_SIZE),
+                };
+                // Without `open`, the window has no close button
+                let window = if dismiss.allows_escape() { window.open(&mut open) } else { window };
+                let response = window.show(ctx, add_contents)?;
+                if let Some(pos) = pressed_at(ctx) {
+                    if response.response.rect.contains(pos) {
+                        self.open(id);
+                    } else if dismiss.allows_click_outside() {
+                        open = false;
+                    }
+                }
+                response.inner
+            }
+        };
+
+        if !open {
+            tracing::debug!(overlay = %id, "Overlay dismissed");
+            self.close(id);
+        }
+        inner
+    }
+
+    /// Draw a registered overlay if it is open, titled from its spec
+    ///
+    /// See [`show`](Self::show).
+    pub fn show_registered<R>(
+        &mut self,
+        ctx: &egui::Context,
+        id: &str,
+        add_contents: impl FnOnce(&mut egui::Ui) -> R,
+    ) -> Option<R> {
+        let title = self.specs.get(id).map_or_else(|| id.to_string(), |spec| spec.title.clone());
+        self.show(ctx, id, &title, add_contents)
+    }
+
+    /// Stack the open overlays' layers in z-order
+    ///
+    /// egui raises a window when it is clicked, which can lift it above
+    /// overlays with a higher z-order. Call once per frame after showing
+    /// overlays.
+    pub fn arrange(&self, ctx: &egui::Context) {
+        for id in &self.stack {
+            let order = match self.style(id).0 {
+                OverlayKind::Modal => egui::Order::Foreground,
+                OverlayKind::Window | OverlayKind::SideSheet => egui::Order::Middle,
+            };
+            ctx.move_to_top(egui::LayerId::new(order, overlay_id(id)));
+        }
+    }
+}
+
+/// egui id of an overlay's window or modal area
+fn overlay_id(id: &str) -> egui::Id {
+    egui::Id::new(("overlay", id))
+}
+
+/// Where the pointer was pressed this frame, if it was
+fn pressed_at(ctx: &egui::Context) -> Option<egui::Pos2> {
+    ctx.input(|i| if i.pointer.any_pressed() { i.pointer.interact_pos() } else { None })
+}
+
+/// Role of a modal dialog button
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ButtonRole {
+    /// Ordinary choice
+    Normal,
+    /// Choice Enter picks when the dialog opens
+    Default,
+    /// Choice that destroys work, drawn in the error color
+    Destructive,
+}
+
+/// How the user answered a modal dialog
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DialogOutcome<T> {
+    /// A button other than Cancel was chosen
+    Chosen(T),
+    /// Cancel or Escape
+    Cancelled,
+}
+
+/// Short modal prompt with a row of buttons and Cancel
+///
+/// Show it every frame until [`show`](Self::show) returns an outcome. The
+/// rest of the application is dimmed and blocked meanwhile, and assistive
+/// technology announces it as an alert dialog.
+#[derive(Debug, Clone)]
+pub struct ModalDialog<T> {
+    /// Id unique among dialogs open at the same time
+    id: String,
+    /// Heading and accessible name
+    title: String,
+    /// Question or explanation
+    message: String,
+    /// Buttons before Cancel, in order
+    buttons: Vec<(String, T, ButtonRole)>,
+    /// Label of the Cancel button
+    cancel_label: String,
+}
+
+impl<T: Clone> ModalDialog<T> {
+    /// Dialog with only a Cancel button, which is the default
+    pub fn new(id: impl Into<String>, title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            message: message.into(),
+            buttons: Vec::new(),
+            cancel_label: "Cancel".to_string(),
+        }
+    }
+
+    /// Adds a button that answers `value`
+    pub fn with_button(mut self, label: impl Into<String>, value: T) -> Self {
+        self.buttons.push((label.into(), value, ButtonRole::Normal));
+        self
+    }
+
+    /// Adds the button Enter chooses, instead of Cancel
+    pub fn with_default_button(mut self, label: impl Into<String>, value: T) -> Self {
+        self.buttons.push((label.into(), value, ButtonRole::Default));
+        self
+    }
+
+    /// Adds a button for a choice that destroys work
+    pub fn with_destructive_button(mut self, label: impl Into<String>, value: T) -> Self {
+        self.buttons.push((label.into(), value, ButtonRole::Destructive));
+        self
+    }
+
+    /// Sets the label of the Cancel button
+    pub fn with_cancel_label(mut self, label: impl Into<String>) -> Self {
+        self.cancel_label = label.into();
+        self
+    }
+
+    /// Heading of the dialog
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Draw the dialog
+    ///
+    /// Returns the user's answer in the frame they give it, and `None`
+    /// while the dialog stays open.
+    pub fn show(&self, ctx: &egui::Context) -> Option<DialogOutcome<T>> {
+        let escape = ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape));
+        let mut outcome = None;
+        egui::Modal::new(egui::Id::new(("dialog", &self.id))).show(ctx, |ui| {
+            ui.ctx().accesskit_node_builder(ui.unique_id(), |node| {
+                node.set_role(egui::accesskit::Role::AlertDialog);
+                node.set_label(self.title.as_str());
+                node.set_description(self.message.as_str());
+                node.set_modal();
+            });
+            ui.set_max_width(DIALOG_WIDTH);
+            ui.heading(&self.title);
+            ui.add_space(4.0);
+            ui.label(&self.message);
+            ui.add_space(8.0);
+
+            ui.horizontal(|ui| {
+                let mut buttons = Vec::with_capacity(self.buttons.len() + 1);
+                let mut default = None;
+                for (label, value, role) in &self.buttons {
+                    let text = match role {
+                        ButtonRole::Destructive => egui::RichText::new(label).color(ui.visuals().error_fg_color),
+                        ButtonRole::Normal | ButtonRole::Default => egui::RichText::new(label),
+                    };
+                    let response = ui.button(text);
+                    if *role == ButtonRole::Default && default.is_none() {
+                        default = Some(response.id);
+                    }
+                    if response.clicked() {
+                        outcome = Some(DialogOutcome::Chosen(value.clone()));
+                    }
+                    buttons.push(response.id);
+                }
+                let cancel = ui.button(&self.cancel_label);
+                if cancel.clicked() {
+                    outcome = Some(DialogOutcome::Cancelled);
+                }
+                buttons.push(cancel.id);
+
+                // Keep focus on the dialog's buttons, starting on the default
+                let focused = ui.ctx().memory(|m| m.focused());
+                if !focused.is_some_and(|id| buttons.contains(&id)) {
+                    ui.ctx().memory_mut(|m| m.request_focus(default.unwrap_or(cancel.id)));
+                }
+            });
+        });
+
+        if outcome.is_none() && escape {
+            outcome = Some(DialogOutcome::Cancelled);
+        }
+        if let Some(outcome) = &outcome {
+            let cancelled = matches!(outcome, DialogOutcome::Cancelled);
+            tracing::debug!(dialog = %self.id, cancelled, "Dialog answered");
+        }
+        outcome
+    }
+}
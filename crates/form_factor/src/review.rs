@@ -0,0 +1,115 @@
+//! Lightweight review app for the browser
+//!
+//! The desktop demo app relies on native file dialogs, OpenCV and Tesseract,
+//! none of which exist on `wasm32-unknown-unknown`. [`ReviewApp`] is the
+//! subset that runs everywhere: open a project or form image by dropping it
+//! on the window, then review, annotate and comment on the canvas.
+
+use form_factor_core::{App, AppContext};
+use form_factor_drawing::{
+    CanvasCommand, CanvasError, CanvasErrorKind, CanvasPanel, DrawingCanvas, DroppedFile, LayerType,
+};
+use strum::IntoEnumIterator;
+use tracing::{error, info};
+
+/// Canvas review app without native-only features
+#[derive(Default)]
+pub struct ReviewApp {
+    canvas: DrawingCanvas,
+    /// Problem opening the last dropped file, shown in the top bar
+    last_error: Option<String>,
+}
+
+impl ReviewApp {
+    /// Create an app with an empty canvas
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an app reviewing an existing canvas
+    pub fn with_canvas(canvas: DrawingCanvas) -> Self {
+        Self {
+            canvas,
+            last_error: None,
+        }
+    }
+
+    /// Canvas under review
+    pub fn canvas(&self) -> &DrawingCanvas {
+        &self.canvas
+    }
+
+    /// Mutable access to the canvas under review
+    pub fn canvas_mut(&mut self) -> &mut DrawingCanvas {
+        &mut self.canvas
+    }
+
+    /// Open a file from its name and contents
+    ///
+    /// Projects (`.ffp`, `.json`) replace the canvas; images become the form
+    /// image. Projects reference their image by path, which the browser
+    /// can't read, so drop the image after the project.
+    pub fn open_file(&mut self, name: &str, bytes: &[u8], ctx: &egui::Context) -> Result<(), CanvasError> {
+        let file = DroppedFile::from_bytes(name, bytes).ok_or_else(|| {
+            CanvasError::new(CanvasErrorKind::UnsupportedFile(name.to_string()), line!(), file!())
+        })?;
+        self.canvas.open_dropped_file(&file, ctx)?;
+        info!("Opened {}", name);
+        Ok(())
+    }
+}
+
+impl App for ReviewApp {
+    fn update(&mut self, ctx: &AppContext) {
+        match self.canvas.handle_file_drop(ctx.egui_ctx) {
+            Some(Ok(())) => self.last_error = None,
+            Some(Err(e)) => {
+                error!("Failed to open dropped file: {}", e);
+                self.last_error = Some(e.to_string());
+            }
+            None => {}
+        }
+
+        egui::TopBottomPanel::top("review_top_panel").show(ctx.egui_ctx, |ui| {
+            ui.horizontal(|ui| {
+                for layer_type in LayerType::iter() {
+                    let mut visible = self.canvas.layer_manager().is_visible(layer_type);
+                    if ui.checkbox(&mut visible, layer_type.to_string()).changed() {
+                        self.canvas.apply_command(CanvasCommand::SetLayerVisible {
+                            layer: layer_type,
+                            visible,
+                        });
+                    }
+                }
+                ui.separator();
+                if ui.button("Comments").clicked() {
+                    self.canvas.apply_command(CanvasCommand::TogglePanel(CanvasPanel::Comments));
+                }
+                if ui.button("Workflow").clicked() {
+                    self.canvas.apply_command(CanvasCommand::TogglePanel(CanvasPanel::Workflow));
+                }
+                ui.separator();
+                match &self.last_error {
+                    Some(message) => ui.colored_label(ui.visuals().error_fg_color, message),
+                    None => ui.weak("Drop a project (.ffp) or form image to open it"),
+                };
+            });
+        });
+
+        // Status bar (must be added before the central panel)
+        self.canvas.status_bar().show(ctx.egui_ctx);
+
+        self.canvas.advance_animations(ctx);
+        egui::CentralPanel::default().show(ctx.egui_ctx, |ui| {
+            self.canvas.ui(ui);
+        });
+
+        self.canvas.show_comments_panel(ctx.egui_ctx);
+        self.canvas.show_workflow_panel(ctx.egui_ctx);
+        self.canvas.show_drop_queue_panel(ctx.egui_ctx);
+    }
+
+    fn name(&self) -> &str {
+        "Form Factor Review"
+    }
+}
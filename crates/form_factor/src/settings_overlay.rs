@@ -0,0 +1,519 @@
+//! Settings overlay
+//!
+//! One window with tabs for every user setting. Edits go to a draft copy;
+//! saving writes the draft to the settings store and hands the new settings
+//! back to the application to apply.
+
+use crate::{
+    DetectionKind, DetectionSubtype, IoError, OverlayManager, ProjectEncoding, Settings, StampSource, StampSymbol, StrokeStyle,
+    StylePreset, Theme, clear_detection_cache, detection_cache_dir,
+};
+use std::path::PathBuf;
+use strum::IntoEnumIterator;
+use tracing::instrument;
+
+/// Overlay id of the settings window
+pub const SETTINGS_OVERLAY: &str = "settings";
+
+/// Sections of the settings overlay
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, strum::EnumIter, strum::Display)]
+pub enum SettingsTab {
+    /// Zoom, grid, field names, stamps and style presets
+    #[default]
+    Canvas,
+    /// Text and logo detection, detection colors and subtypes
+    Detection,
+    /// Text recognition
+    #[strum(to_string = "OCR")]
+    Ocr,
+    /// Keyboard shortcuts
+    Shortcuts,
+    /// Theme and scaling
+    Appearance,
+}
+
+/// Tabbed editor for the persistent settings store
+#[derive(Debug, Clone)]
+pub struct SettingsOverlay {
+    /// File the settings are stored in
+    path: PathBuf,
+    /// Settings as last saved
+    saved: Settings,
+    /// Settings being edited
+    draft: Settings,
+    /// Selected tab
+    tab: SettingsTab,
+    /// Text of the logo scales field
+    scales_text: String,
+    /// Text stamp being added to the stamp library
+    new_stamp: StampSymbol,
+    /// Path of the image to import as a stamp
+    stamp_image_path: String,
+    /// Error from the last stamp image import
+    stamp_error: Option<String>,
+    /// Style preset being added to the preset list
+    new_preset: StylePreset,
+    /// Subtype being added to the detection taxonomy
+    new_subtype: DetectionSubtype,
+    /// Error from the last save attempt
+    error: Option<String>,
+}
+
+impl SettingsOverlay {
+    /// Load settings from the default store
+    pub fn load() -> Self {
+        Self::with_path(Settings::default_path())
+    }
+
+    /// Load settings from a specific file
+    pub fn with_path(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let saved = Settings::load_from(&path);
+        let scales_text = format_scales(&saved.detection.logo_scales);
+        Self {
+            path,
+            draft: saved.clone(),
+            saved,
+            tab: SettingsTab::default(),
+            scales_text,
+            new_stamp: StampSymbol::text("", "", egui::Color32::from_rgb(200, 30, 30)),
+            stamp_image_path: String::new(),
+            stamp_error: None,
+            new_preset: StylePreset::new(
+                "",
+                egui::Stroke::new(2.0, egui::Color32::from_rgb(0, 120, 215)),
+                egui::Color32::TRANSPARENT,
+                StrokeStyle::Solid,
+            ),
+            new_subtype: DetectionSubtype::new("", egui::Color32::from_rgb(150, 60, 200), ""),
+            error: None,
+        }
+    }
+
+    /// Settings as last saved
+    pub fn settings(&self) -> &Settings {
+        &self.saved
+    }
+
+    /// Settings being edited
+    pub fn draft(&self) -> &Settings {
+        &self.draft
+    }
+
+    /// Mutable access to the settings being edited
+    pub fn draft_mut(&mut self) -> &mut Settings {
+        &mut self.draft
+    }
+
+    /// Selected tab
+    pub fn tab(&self) -> SettingsTab {
+        self.tab
+    }
+
+    /// Select a tab
+    pub fn set_tab(&mut self, tab: SettingsTab) {
+        self.tab = tab;
+    }
+
+    /// Whether the draft differs from the saved settings
+    pub fn is_dirty(&self) -> bool {
+        self.draft != self.saved
+    }
+
+    /// Write the draft to the store and make it the current settings
+    #[instrument(skip(self), fields(path = %self.path.display()))]
+    pub fn save(&mut self) -> Result<&Settings, IoError> {
+        self.draft.save_to(&self.path).inspect_err(|e| self.error = Some(e.to_string()))?;
+        self.saved = self.draft.clone();
+        self.error = None;
+        tracing::info!("Settings saved");
+        Ok(&self.saved)
+    }
+
+    /// Discard unsaved edits
+    pub fn revert(&mut self) {
+        self.draft = self.saved.clone();
+        self.scales_text = format_scales(&self.draft.detection.logo_scales);
+        self.error = None;
+    }
+
+    /// Replace the draft with default settings
+    pub fn reset_to_defaults(&mut self) {
+        self.draft = Settings::default();
+        self.scales_text = format_scales(&self.draft.detection.logo_scales);
+    }
+
+    /// Import the image at the stamp image path into the draft stamp library
+    pub fn import_stamp_image(&mut self) -> Result<StampSymbol, IoError> {
+        let path = PathBuf::from(self.stamp_image_path.trim());
+        let result = self.draft.canvas.stamps.import_image(&path);
+        self.stamp_error = result.as_ref().err().map(ToString::to_string);
+        if result.is_ok() {
+            self.stamp_image_path.clear();
+        }
+        result
+    }
+
+    /// Set the path of the image to import as a stamp
+    pub fn set_stamp_image_path(&mut self, path: impl Into<String>) {
+        self.stamp_image_path = path.into();
+    }
+
+    /// Show the overlay if it is open
+    ///
+    /// Returns the new settings when the user saves them.
+    pub fn show(&mut self, overlays: &mut OverlayManager, ctx: &egui::Context) -> Option<Settings> {
+        overlays.show(ctx, SETTINGS_OVERLAY, "Settings", |ui| self.ui(ui)).flatten()
+    }
+
+    /// Render the tabs and buttons
+    ///
+    /// Returns the new settings when the user saves them.
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<Settings> {
+        ui.horizontal(|ui| {
+            for tab in SettingsTab::iter() {
+                ui.selectable_value(&mut self.tab, tab, tab.to_string());
+            }
+        });
+        ui.separator();
+
+        egui::ScrollArea::vertical()
+            .max_height(ui.available_height() - 40.0)
+            .show(ui, |ui| match self.tab {
+                SettingsTab::Canvas => self.canvas_tab(ui),
+                SettingsTab::Detection => self.detection_tab(ui),
+                SettingsTab::Ocr => self.ocr_tab(ui),
+                SettingsTab::Shortcuts => self.shortcuts_tab(ui),
+                SettingsTab::Appearance => self.appearance_tab(ui),
+            });
+
+        ui.separator();
+        let mut saved = None;
+        ui.horizontal(|ui| {
+            if ui.add_enabled(self.is_dirty(), egui::Button::new("Save")).clicked()
+                && let Ok(settings) = self.save()
+            {
+                saved = Some(settings.clone());
+            }
+            if ui.add_enabled(self.is_dirty(), egui::Button::new("Revert")).clicked() {
+                self.revert();
+            }
+            if ui.button("Reset to Defaults").clicked() {
+                self.reset_to_defaults();
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        saved
+    }
+
+    fn canvas_tab(&mut self, ui: &mut egui::Ui) {
+        let canvas = &mut self.draft.canvas;
+        ui.label("Zoom Sensitivity:");
+        ui.add(egui::Slider::new(&mut canvas.zoom_sensitivity, 0.1..=10.0).logarithmic(true));
+        ui.label("Higher values make zoom more responsive");
+        ui.separator();
+
+        ui.label("Grid Spacing:");
+        ui.add(
+            egui::Slider::new(&mut canvas.grid_spacing_horizontal, 0.1..=100.0)
+                .text("Horizontal")
+                .logarithmic(true),
+        );
+        ui.add(
+            egui::Slider::new(&mut canvas.grid_spacing_vertical, 0.1..=100.0)
+                .text("Vertical")
+                .logarithmic(true),
+        );
+        ui.label("Distance between grid lines");
+        ui.separator();
+
+        ui.checkbox(&mut canvas.show_rulers, "Show rulers");
+        ui.checkbox(&mut canvas.snap_to_guides, "Snap to guides");
+        ui.label("Drag from a ruler to add a guide; drag it back to remove it");
+        ui.separator();
+
+        let naming = &mut canvas.field_naming;
+        ui.label("Field Names:");
+        ui.add(egui::TextEdit::singleline(&mut naming.pattern).hint_text("field_{n}"));
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut naming.start).prefix("Start: "));
+            ui.add(egui::DragValue::new(&mut naming.width).range(1..=6).prefix("Digits: "));
+        });
+        if naming.is_enabled() {
+            ui.label(format!("Example: {}", naming.next_name(&[])));
+        }
+        ui.label("Names new shapes; use {n} for a number, {row} and {col} for grid repeats");
+        ui.separator();
+
+        self.stamps_section(ui);
+        ui.separator();
+
+        self.style_presets_section(ui);
+        ui.separator();
+
+        let canvas = &mut self.draft.canvas;
+        ui.horizontal(|ui| {
+            ui.label("Save projects as:");
+            for encoding in ProjectEncoding::iter() {
+                ui.selectable_value(&mut canvas.project_encoding, encoding, encoding.to_string());
+            }
+        });
+        ui.label("Binary projects are smaller and faster for large forms; either kind opens");
+
+        let compression = &mut canvas.compression;
+        ui.checkbox(&mut compression.enabled, "Compress projects and caches (zstd)");
+        ui.add_enabled(
+            compression.enabled,
+            egui::Slider::new(&mut compression.level, 1..=19).text("Level"),
+        );
+        ui.label("Higher levels are smaller but slower to save; compressed files open either way");
+        ui.separator();
+
+        ui.label("Texture Memory:");
+        ui.add(
+            egui::Slider::new(&mut canvas.texture_budget_mb, 64..=4096)
+                .suffix(" MB")
+                .logarithmic(true),
+        );
+        ui.label("Images not drawn recently are unloaded past this budget and reloaded when needed");
+    }
+
+    fn style_presets_section(&mut self, ui: &mut egui::Ui) {
+        let presets = &mut self.draft.canvas.style_presets;
+        ui.label("Style presets:");
+        let mut removed = None;
+        for preset in &mut presets.presets {
+            ui.horizontal(|ui| {
+                if ui.small_button("✖").on_hover_text("Remove the preset").clicked() {
+                    removed = Some(preset.name.clone());
+                }
+                let name = preset.name.clone();
+                ui.label(&name);
+                style_editor(ui, &name, preset);
+            });
+        }
+        if let Some(name) = removed {
+            presets.remove(&name);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.new_preset.name).hint_text("Name").desired_width(80.0));
+            style_editor(ui, "new_style_preset", &mut self.new_preset);
+            if ui.add_enabled(!self.new_preset.name.trim().is_empty(), egui::Button::new("Add")).clicked() {
+                presets.add(self.new_preset.clone());
+                self.new_preset.name.clear();
+            }
+        });
+        ui.label("Styles new shapes can be drawn with; adding a name that exists replaces it");
+    }
+
+    fn stamps_section(&mut self, ui: &mut egui::Ui) {
+        let library = &mut self.draft.canvas.stamps;
+        ui.label("Stamps:");
+        let mut removed = None;
+        for symbol in &library.symbols {
+            ui.horizontal(|ui| {
+                if ui.small_button("✖").on_hover_text("Remove from the library").clicked() {
+                    removed = Some(symbol.name.clone());
+                }
+                match &symbol.source {
+                    StampSource::Text { text, color } => {
+                        ui.colored_label(*color, text);
+                    }
+                    StampSource::Image { path } => {
+                        ui.label(path);
+                    }
+                }
+                ui.weak(&symbol.name);
+            });
+        }
+        if let Some(name) = removed {
+            library.remove(&name);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.new_stamp.name).hint_text("Name").desired_width(80.0));
+            if let StampSource::Text { text, color } = &mut self.new_stamp.source {
+                ui.add(egui::TextEdit::singleline(text).hint_text("Text").desired_width(100.0));
+                ui.color_edit_button_srgba(color);
+            }
+            let ready = !self.new_stamp.name.trim().is_empty()
+                && matches!(&self.new_stamp.source, StampSource::Text { text, .. } if !text.trim().is_empty());
+            if ui.add_enabled(ready, egui::Button::new("Add")).clicked() {
+                library.add(self.new_stamp.clone());
+                self.new_stamp.name.clear();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.stamp_image_path).hint_text("Image path"));
+            if ui.add_enabled(!self.stamp_image_path.trim().is_empty(), egui::Button::new("Import")).clicked() {
+                let _ = self.import_stamp_image();
+            }
+        });
+        if let Some(error) = &self.stamp_error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        ui.label("Symbols the Stamp tool places; adding a name that exists replaces it");
+    }
+
+    fn detection_tab(&mut self, ui: &mut egui::Ui) {
+        let detection = &mut self.draft.detection;
+        ui.strong("Text detection");
+        ui.add(egui::Slider::new(&mut detection.text_confidence, 0.0..=1.0).text("Min confidence"));
+        ui.horizontal(|ui| {
+            ui.label("Model:");
+            ui.text_edit_singleline(&mut detection.text_model_path);
+        });
+        ui.separator();
+
+        ui.strong("Logo detection");
+        ui.add(egui::Slider::new(&mut detection.logo_confidence, 0.0..=1.0).text("Min confidence"));
+        ui.horizontal(|ui| {
+            ui.label("Templates:");
+            ui.text_edit_singleline(&mut detection.logos_dir);
+        });
+        ui.horizontal(|ui| {
+            ui.label("Scales:");
+            if ui.text_edit_singleline(&mut self.scales_text).changed()
+                && let Some(scales) = parse_scales(&self.scales_text)
+            {
+                detection.logo_scales = scales;
+            }
+        });
+        if parse_scales(&self.scales_text).is_none() {
+            ui.colored_label(ui.visuals().error_fg_color, "Scales must be positive numbers separated by commas");
+        }
+        ui.separator();
+
+        ui.strong("New Results");
+        ui.checkbox(&mut detection.flash_new, "Flash new detections")
+            .on_hover_text("Briefly highlight the detections a run just added");
+        ui.checkbox(&mut detection.zoom_to_new, "Zoom to new detections")
+            .on_hover_text("Fit the view to the detections a run just added");
+        ui.separator();
+
+        ui.strong("Cache");
+        ui.checkbox(&mut detection.cache_results, "Reuse results for unchanged images")
+            .on_hover_text("Skip detection when the same image was already run with the same settings");
+        if ui.button("Clear Cache").clicked() {
+            match clear_detection_cache(&detection_cache_dir()) {
+                Ok(count) => tracing::info!("Removed {} cached detection results", count),
+                Err(e) => tracing::error!("Failed to clear detection cache: {}", e),
+            }
+        }
+        ui.separator();
+
+        ui.strong("Colors");
+        for kind in DetectionKind::iter() {
+            ui.horizontal(|ui| {
+                ui.color_edit_button_srgba(detection.colors.color_mut(kind));
+                ui.label(kind.to_string());
+            });
+        }
+        ui.separator();
+
+        self.subtypes_section(ui);
+    }
+
+    fn subtypes_section(&mut self, ui: &mut egui::Ui) {
+        let taxonomy = &mut self.draft.detection.taxonomy;
+        ui.strong("Subtypes");
+        let mut removed = None;
+        for subtype in &mut taxonomy.subtypes {
+            ui.horizontal(|ui| {
+                if ui.small_button("✖").on_hover_text("Remove the subtype").clicked() {
+                    removed = Some(subtype.name.clone());
+                }
+                ui.color_edit_button_srgba(&mut subtype.color);
+                ui.label(&subtype.name);
+                ui.add(egui::TextEdit::singleline(&mut subtype.description).hint_text("Description"));
+            });
+        }
+        if let Some(name) = removed {
+            taxonomy.remove(&name);
+        }
+
+        ui.horizontal(|ui| {
+            ui.color_edit_button_srgba(&mut self.new_subtype.color);
+            ui.add(egui::TextEdit::singleline(&mut self.new_subtype.name).hint_text("Name").desired_width(80.0));
+            ui.add(egui::TextEdit::singleline(&mut self.new_subtype.description).hint_text("Description"));
+            if ui.add_enabled(!self.new_subtype.name.trim().is_empty(), egui::Button::new("Add")).clicked() {
+                taxonomy.add(self.new_subtype.clone());
+                self.new_subtype.name.clear();
+                self.new_subtype.description.clear();
+            }
+        });
+        ui.label("Detections are tagged from their context menu and drawn in their subtype's color");
+    }
+
+    fn ocr_tab(&mut self, ui: &mut egui::Ui) {
+        let ocr = &mut self.draft.ocr;
+        ui.horizontal(|ui| {
+            ui.label("Language:");
+            ui.text_edit_singleline(&mut ocr.language);
+        });
+        ui.label("Tesseract language codes, e.g. eng or eng+fra");
+        ui.add(egui::Slider::new(&mut ocr.min_confidence, 0..=100).text("Min confidence"));
+        ui.checkbox(&mut ocr.preprocessing, "Preprocess images before recognition");
+    }
+
+    fn shortcuts_tab(&mut self, ui: &mut egui::Ui) {
+        let error_color = ui.visuals().error_fg_color;
+        egui::Grid::new("settings_shortcuts").num_columns(2).striped(true).show(ui, |ui| {
+            for (action, key) in self.draft.shortcuts.bindings.iter_mut() {
+                ui.label(action.replace('_', " "));
+                let valid = egui::Key::from_name(key).is_some();
+                let edit = egui::TextEdit::singleline(key).desired_width(80.0);
+                if valid {
+                    ui.add(edit);
+                } else {
+                    ui.add(edit.text_color(error_color))
+                        .on_hover_text("Not a recognized key name");
+                }
+                ui.end_row();
+            }
+        });
+        ui.label("Key names such as F12, Escape, A or Space");
+    }
+
+    fn appearance_tab(&mut self, ui: &mut egui::Ui) {
+        let appearance = &mut self.draft.appearance;
+        ui.horizontal(|ui| {
+            ui.label("Theme:");
+            for theme in Theme::iter() {
+                ui.selectable_value(&mut appearance.theme, theme, theme.to_string());
+            }
+        });
+        ui.add(egui::Slider::new(&mut appearance.ui_scale, 0.5..=3.0).text("Interface scale"));
+    }
+}
+
+/// Stroke width, stroke color, fill color and outline pattern of a preset
+fn style_editor(ui: &mut egui::Ui, id: &str, preset: &mut StylePreset) {
+    ui.add(egui::DragValue::new(&mut preset.stroke.width).range(0.5..=20.0).speed(0.1).suffix(" px"));
+    ui.color_edit_button_srgba(&mut preset.stroke.color);
+    ui.color_edit_button_srgba(&mut preset.fill);
+    egui::ComboBox::from_id_salt(("style_preset_pattern", id))
+        .selected_text(preset.stroke_style.to_string())
+        .show_ui(ui, |ui| {
+            for option in StrokeStyle::iter() {
+                ui.selectable_value(&mut preset.stroke_style, option, option.to_string());
+            }
+        });
+}
+
+/// Format scales as comma-separated text
+fn format_scales(scales: &[f64]) -> String {
+    scales.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// Parse comma-separated positive scales
+fn parse_scales(text: &str) -> Option<Vec<f64>> {
+    let scales: Option<Vec<f64>> = text
+        .split(',')
+        .map(|part| part.trim().parse::<f64>().ok().filter(|s| s.is_finite() && *s > 0.0))
+        .collect();
+    scales.filter(|s| !s.is_empty())
+}
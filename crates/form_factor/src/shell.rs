@@ -0,0 +1,1241 @@
+//! Desktop application shell
+//!
+//! [`AppShell`] is the full Form Factor desktop app as a library type: it
+//! routes events between the plugins, the canvas and native file dialogs,
+//! switches workspace modes, tracks the selection, and draws the sidebar,
+//! canvas windows, overlays and prompts each frame. Applications built on it
+//! add their own behavior through plugins, [`ModeHooks`] and
+//! [`ShellExtension`]s instead of copying the frame loop:
+//!
+//! ```no_run
+//! use form_factor::{AppShell, Backend, BackendConfig, CrashReporter, EframeBackend, FrameTimings, LogBuffer};
+//!
+//! let crash_reporter = CrashReporter::new(CrashReporter::default_dir());
+//! let shell = AppShell::new(LogBuffer::default(), FrameTimings::default(), crash_reporter).with_name("My Forms");
+//! EframeBackend::run(Box::new(shell), BackendConfig::default()).unwrap();
+//! ```
+
+use crate::{
+    ACTION_OPEN_SETTINGS, ACTION_TOGGLE_FRAME_TIMING, ACTION_TOGGLE_LOG, ANCHOR_CANVAS, App, AppContext, BackendConfig,
+    CanvasAction, CrashReporter, DialogOutcome, DrawingCanvas, DropKind, FrameSection, FrameTimingOverlay, FrameTimings,
+    GuidedTour, LogBuffer, LogViewer, ModalDialog, ModeHooks, OnboardingState, OverlayManager, ProjectSummary,
+    SETTINGS_OVERLAY, SecondaryWindow, SettingsOverlay, Tour, UnsavedWork, WELCOME_TOUR, WorkspaceLayout,
+    WorkspaceMemory, WorkspaceMode, register_anchor,
+};
+#[cfg(feature = "plugins")]
+use crate::{AppEvent, CommandPalette, Plugin, PluginManager, TracedEvent};
+
+/// Extension point for applications built on [`AppShell`]
+///
+/// Every method has a default that does nothing, so an extension only
+/// implements what it needs.
+pub trait ShellExtension {
+    /// Handles an application event before the shell does
+    ///
+    /// Returns true if the event was handled, so the shell skips its own
+    /// handling. Plugins still receive the event either way.
+    #[cfg(feature = "plugins")]
+    fn on_event(&mut self, _canvas: &mut DrawingCanvas, _event: &AppEvent, _ctx: &egui::Context) -> bool {
+        false
+    }
+
+    /// Draws the extension's own UI, after the canvas and its windows
+    fn ui(&mut self, _canvas: &mut DrawingCanvas, _ctx: &egui::Context) {}
+
+    /// Reacts to another shape being selected, or the selection being cleared
+    fn on_selection_changed(&mut self, _canvas: &DrawingCanvas, _selected: Option<usize>) {}
+}
+
+/// Subsystems set up and torn down as the workspace changes mode
+fn mode_hooks() -> ModeHooks {
+    let mut hooks = ModeHooks::new();
+    // Picking a template belongs to laying out; filling in or reviewing closes the browser
+    hooks.on_exit(WorkspaceMode::Layout, "template browser", DrawingCanvas::close_new_project_panel);
+    // A batch review edits its own instances, not the project in the data entry window
+    hooks.on_enter(WorkspaceMode::BatchReview, "data entry", DrawingCanvas::close_data_entry_panel);
+    hooks
+}
+
+/// Answer to a confirmation prompt
+#[derive(Debug, Clone, Copy)]
+enum Confirm {
+    /// Save the unsaved work, then go ahead
+    SaveFirst(UnsavedWork),
+    /// Go ahead
+    Proceed,
+}
+
+/// Action held back until the user confirms it
+#[derive(Debug, Clone)]
+enum Guarded {
+    /// Event from the event bus, emitted again once confirmed
+    #[cfg(feature = "plugins")]
+    Event(AppEvent),
+    /// Opening the dropped project at the front of the drop queue
+    OpenDropped,
+    /// Opening a project chosen in the project store window
+    OpenStored(String),
+    /// Closing the window
+    Quit,
+}
+
+/// Prompt to save, discard or cancel before `action` loses `unsaved` work, or `None` if nothing would be lost
+fn unsaved_work_dialog(canvas: &DrawingCanvas, unsaved: UnsavedWork, action: &str) -> Option<ModalDialog<Confirm>> {
+    if unsaved.is_empty() {
+        return None;
+    }
+    Some(
+        ModalDialog::new("unsaved_changes", "Unsaved Changes", unsaved.question(canvas.project_name(), action))
+            .with_default_button("Save", Confirm::SaveFirst(unsaved))
+            .with_destructive_button("Don't Save", Confirm::Proceed),
+    )
+}
+
+/// Prompt to confirm `event` first, if it would destroy work
+#[cfg(feature = "plugins")]
+fn confirmation_prompt(canvas: &DrawingCanvas, event: &AppEvent) -> Option<ModalDialog<Confirm>> {
+    match event {
+        AppEvent::LayerClearRequested { layer_name } => Some(
+            ModalDialog::new(
+                "clear_layer",
+                "Clear Layer",
+                format!("Remove everything on the {} layer?", layer_name),
+            )
+            .with_destructive_button("Clear", Confirm::Proceed),
+        ),
+        AppEvent::NewProjectRequested => {
+            unsaved_work_dialog(canvas, UnsavedWork::of_project(canvas), "starting a new project")
+        }
+        AppEvent::OpenFileRequested => {
+            unsaved_work_dialog(canvas, UnsavedWork::of_project(canvas), "opening another project")
+        }
+        AppEvent::BatchReviewRequested => {
+            unsaved_work_dialog(canvas, UnsavedWork::of_review(canvas), "starting another batch review")
+        }
+        _ => None,
+    }
+}
+
+/// The Form Factor desktop application, ready to run on a backend
+pub struct AppShell {
+    name: String,
+    canvas: DrawingCanvas,
+    /// Panic hook state and recent log events, kept current each frame
+    crash_reporter: CrashReporter,
+    /// Log viewer window
+    log_viewer: LogViewer,
+    /// Developer window of per-frame timing by section
+    frame_timing: FrameTimingOverlay,
+    /// Open overlay windows
+    overlays: OverlayManager,
+    /// Confirmation prompt waiting for an answer, and the action it holds back
+    prompt: Option<(ModalDialog<Confirm>, Guarded)>,
+    /// Confirmed event, let through once without asking again
+    #[cfg(feature = "plugins")]
+    confirmed: Option<AppEvent>,
+    /// Set once the user confirmed closing the window
+    closing: bool,
+    /// Workspace arrangement last used in each mode
+    workspace: WorkspaceMemory,
+    /// Set-up and tear-down of subsystems on entering and leaving modes
+    mode_hooks: ModeHooks,
+    /// Shape selected as of last frame
+    selected_shape: Option<usize>,
+    /// Behavior added by the application built on the shell
+    extensions: Vec<Box<dyn ShellExtension>>,
+    /// Width of the plugin sidebar last frame
+    #[cfg(feature = "plugins")]
+    sidebar_width: f32,
+    /// Sidebar width to restore next frame after a mode switch
+    #[cfg(feature = "plugins")]
+    sidebar_restore: Option<f32>,
+    /// Persistent settings and their editor
+    settings: SettingsOverlay,
+    /// Guided tour shown on first launch or on request
+    tour: GuidedTour,
+    /// Tours the user has already seen
+    onboarding: OnboardingState,
+    /// Selected shape properties, in a window that can go on another monitor
+    properties_window: SecondaryWindow,
+    #[cfg(feature = "plugins")]
+    plugin_manager: PluginManager,
+    /// Ctrl+Shift+P command palette, runs commands through the event bus
+    #[cfg(feature = "plugins")]
+    palette: CommandPalette,
+}
+
+impl AppShell {
+    /// Shell with an empty canvas and no plugins
+    ///
+    /// `logs` and `timings` should be the buffers whose tracing layers are
+    /// installed, so the log viewer and frame timing window show them.
+    pub fn new(logs: LogBuffer, timings: FrameTimings, crash_reporter: CrashReporter) -> Self {
+        Self {
+            name: String::from("Form Factor"),
+            canvas: DrawingCanvas::new(),
+            crash_reporter,
+            log_viewer: LogViewer::new(logs),
+            frame_timing: FrameTimingOverlay::new(timings),
+            overlays: OverlayManager::new(),
+            prompt: None,
+            #[cfg(feature = "plugins")]
+            confirmed: None,
+            closing: false,
+            workspace: WorkspaceMemory::new(),
+            mode_hooks: mode_hooks(),
+            selected_shape: None,
+            extensions: Vec::new(),
+            #[cfg(feature = "plugins")]
+            sidebar_width: 280.0,
+            #[cfg(feature = "plugins")]
+            sidebar_restore: None,
+            settings: SettingsOverlay::load(),
+            tour: GuidedTour::new(),
+            onboarding: OnboardingState::load(),
+            properties_window: SecondaryWindow::new("properties", "Properties").with_config(BackendConfig {
+                window_width: 320,
+                window_height: 480,
+                ..Default::default()
+            }),
+            #[cfg(feature = "plugins")]
+            plugin_manager: PluginManager::new(),
+            #[cfg(feature = "plugins")]
+            palette: CommandPalette::default(),
+        }
+    }
+
+    /// Sets the application name
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Starts on `canvas` instead of an empty one, keeping the custom tools registered so far
+    pub fn with_canvas(mut self, mut canvas: DrawingCanvas) -> Self {
+        self.canvas.transfer_custom_tools(&mut canvas);
+        self.canvas = canvas;
+        self
+    }
+
+    /// Registers a plugin in the sidebar, along with the overlays and tools it contributes
+    #[cfg(feature = "plugins")]
+    pub fn with_plugin(mut self, plugin: Box<dyn Plugin>) -> Self {
+        self.plugin_manager.register(plugin);
+        for tool in self.plugin_manager.take_tools() {
+            self.canvas.register_tool(tool);
+        }
+        // Plugins contribute overlays; the overlay manager draws their chrome
+        for spec in self.plugin_manager.overlays() {
+            self.overlays.register(spec.clone());
+        }
+        self
+    }
+
+    /// Registers the plugins bundled with Form Factor whose features are enabled
+    #[cfg(feature = "plugins")]
+    pub fn with_bundled_plugins(self) -> Self {
+        #[allow(unused_mut)]
+        let mut shell = self;
+
+        #[cfg(feature = "plugin-canvas")]
+        {
+            shell = shell.with_plugin(Box::new(crate::canvas::CanvasPlugin::new()));
+            tracing::info!("Registered canvas plugin");
+        }
+
+        #[cfg(feature = "plugin-layers")]
+        {
+            shell = shell.with_plugin(Box::new(crate::layers::LayersPlugin::new()));
+            tracing::info!("Registered layers plugin");
+        }
+
+        #[cfg(feature = "plugin-file")]
+        {
+            shell = shell.with_plugin(Box::new(crate::file::FilePlugin::with_recent_projects(
+                crate::RecentProjects::load(),
+            )));
+            tracing::info!("Registered file plugin");
+        }
+
+        #[cfg(feature = "plugin-detection")]
+        {
+            shell = shell.with_plugin(Box::new(crate::detection::DetectionPlugin::new()));
+            tracing::info!("Registered detection plugin");
+        }
+
+        #[cfg(feature = "plugin-ocr")]
+        {
+            shell = shell.with_plugin(Box::new(crate::ocr::OcrPlugin::new()));
+            tracing::info!("Registered OCR plugin");
+        }
+
+        #[cfg(feature = "plugin-quality")]
+        {
+            shell = shell.with_plugin(Box::new(crate::quality::QualityPlugin::new()));
+            tracing::info!("Registered quality plugin");
+        }
+
+        shell
+    }
+
+    /// Adds an extension, called after those added before it
+    pub fn with_extension(mut self, extension: Box<dyn ShellExtension>) -> Self {
+        self.extensions.push(extension);
+        self
+    }
+
+    /// Canvas being annotated
+    pub fn canvas(&self) -> &DrawingCanvas {
+        &self.canvas
+    }
+
+    /// Mutable access to the canvas being annotated
+    pub fn canvas_mut(&mut self) -> &mut DrawingCanvas {
+        &mut self.canvas
+    }
+
+    /// Plugins shown in the sidebar
+    #[cfg(feature = "plugins")]
+    pub fn plugins(&self) -> &PluginManager {
+        &self.plugin_manager
+    }
+
+    /// Mutable access to the plugins, e.g. to send them events
+    #[cfg(feature = "plugins")]
+    pub fn plugins_mut(&mut self) -> &mut PluginManager {
+        &mut self.plugin_manager
+    }
+
+    /// Overlay windows, e.g. to register more
+    pub fn overlays_mut(&mut self) -> &mut OverlayManager {
+        &mut self.overlays
+    }
+
+    /// Hooks run on entering and leaving workspace modes, e.g. to register more
+    pub fn mode_hooks_mut(&mut self) -> &mut ModeHooks {
+        &mut self.mode_hooks
+    }
+
+    /// Mode the workspace was in as of the last frame
+    pub fn mode(&self) -> WorkspaceMode {
+        self.workspace.mode()
+    }
+
+    /// Carry out, or drop, the action a confirmation prompt held back
+    fn resolve_prompt(&mut self, outcome: DialogOutcome<Confirm>, guarded: Guarded, ctx: &egui::Context) {
+        let DialogOutcome::Chosen(choice) = outcome else {
+            tracing::debug!(?guarded, "Confirmation cancelled");
+            return;
+        };
+        if let Confirm::SaveFirst(unsaved) = choice
+            && !self.save_unsaved(unsaved)
+        {
+            return;
+        }
+        match guarded {
+            #[cfg(feature = "plugins")]
+            Guarded::Event(event) => {
+                self.confirmed = Some(event.clone());
+                self.plugin_manager.event_bus().sender().emit(event);
+            }
+            Guarded::OpenDropped => {
+                if let Some(Err(e)) = self.canvas.open_next_dropped(ctx) {
+                    tracing::error!("Failed to open dropped file: {}", e);
+                }
+            }
+            Guarded::OpenStored(name) => {
+                if let Err(e) = self.canvas.open_store_request(&name, ctx) {
+                    tracing::error!("Failed to open stored project: {}", e);
+                }
+            }
+            Guarded::Quit => {
+                self.closing = true;
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+        }
+    }
+
+    /// Save work before an action replaces it
+    ///
+    /// A project from the project store is saved back there, and one
+    /// without a file is saved to one the user picks. Returns false if any
+    /// of the work was not saved, so the action should not go ahead.
+    fn save_unsaved(&mut self, unsaved: UnsavedWork) -> bool {
+        if unsaved.reviewed_instances > 0
+            && let Some(review) = self.canvas.batch_review_mut()
+            && let Err(e) = review.save()
+        {
+            tracing::error!("Failed to save reviewed instances: {}", e);
+            return false;
+        }
+        if !unsaved.project {
+            return true;
+        }
+
+        let result = if self.canvas.project_file().is_some() {
+            self.canvas.compact_journal().map(|_| ())
+        } else if let Some(name) = self.canvas.stored_project().map(str::to_string) {
+            if let Err(e) = self.canvas.save_to_store(&name) {
+                tracing::error!("Failed to save project to the store: {}", e);
+                return false;
+            }
+            return true;
+        } else {
+            let Some(path) = rfd::FileDialog::new()
+                .add_filter("Form Factor Project", &["ffp"])
+                .set_file_name(format!("{}.ffp", self.canvas.project_name()))
+                .save_file()
+            else {
+                return false;
+            };
+            self.canvas.save_to_file(&path.to_string_lossy())
+        };
+        if let Err(e) = result {
+            tracing::error!("Failed to save project: {}", e);
+            return false;
+        }
+        #[cfg(feature = "plugins")]
+        if let Some(path) = self.canvas.project_file().map(std::path::PathBuf::from) {
+            self.plugin_manager.event_bus().sender().emit(AppEvent::FileSaved { path });
+        }
+        true
+    }
+
+    /// Ask for a file and export the canvas in the format of the action
+    fn export_canvas(&self, action: CanvasAction) {
+        let (label, extension) = match action {
+            CanvasAction::ExportSvg => ("SVG Image", "svg"),
+            CanvasAction::ExportGeoJson => ("GeoJSON", "geojson"),
+            CanvasAction::ExportText => match self.canvas.text_export_options().format {
+                crate::TextFormat::PlainText => ("Text", "txt"),
+                crate::TextFormat::Markdown => ("Markdown", "md"),
+            },
+            CanvasAction::ExportPdf => ("PDF Document", "pdf"),
+            _ => ("PNG Image", "png"),
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(label, &[extension])
+            .set_file_name(format!("{}.{}", self.canvas.project_name(), extension))
+            .save_file()
+        else {
+            return;
+        };
+        let Some(path_str) = path.to_str() else {
+            return;
+        };
+        let result = match action {
+            CanvasAction::ExportSvg => self.canvas.export_svg(path_str),
+            CanvasAction::ExportGeoJson => self.canvas.export_geojson(path_str).map(|_| ()),
+            CanvasAction::ExportText => self
+                .canvas
+                .export_extracted_text(path_str, self.canvas.text_export_options())
+                .map(|_| ()),
+            CanvasAction::ExportPdf => self.canvas.export_pdf(path_str, self.canvas.print_options()),
+            _ => self.canvas.export_png(path_str, *self.canvas.export_scale()).map(|_| ()),
+        };
+        if let Err(e) = result {
+            tracing::error!("Failed to export canvas: {}", e);
+        }
+    }
+
+    /// Ask for a file and save the captured screenshot region to it
+    fn save_screenshot(&self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("PNG Image", &["png"])
+            .set_file_name(format!("{}_screenshot.png", self.canvas.project_name()))
+            .save_file()
+        else {
+            return;
+        };
+        let Some(path_str) = path.to_str() else {
+            return;
+        };
+        if let Err(e) = self.canvas.save_screenshot(path_str) {
+            tracing::error!("Failed to save screenshot: {}", e);
+        }
+    }
+
+    /// Export the instances checked in the batch review to a chosen folder
+    fn export_instances(&mut self) {
+        let Some(dir) = rfd::FileDialog::new().pick_folder() else {
+            return;
+        };
+        let Some(review) = self.canvas.batch_review_mut() else {
+            return;
+        };
+        if let Err(e) = review.export_selected(&dir) {
+            tracing::error!("Failed to export instances: {}", e);
+        }
+    }
+
+    /// Run OCR over the detections, or only those changed since the last pass
+    #[cfg(all(feature = "ocr", feature = "plugins"))]
+    fn extract_text(&mut self, changed_only: bool) {
+        use crate::{OCRConfig, OCREngine, PageSegmentationMode};
+
+        // Projects made from a template can carry their own OCR settings
+        let ocr_settings = self
+            .canvas
+            .ocr_settings()
+            .as_ref()
+            .unwrap_or(&self.settings.settings().ocr);
+        let engine = format!(
+            "tesseract;language={};min_confidence={};preprocessing={}",
+            ocr_settings.language, ocr_settings.min_confidence, ocr_settings.preprocessing
+        );
+        let ocr = match OCREngine::new(
+            OCRConfig::new()
+                .with_language(ocr_settings.language.clone())
+                .with_psm(PageSegmentationMode::Auto)
+                .with_min_confidence(ocr_settings.min_confidence)
+                .with_preprocessing(ocr_settings.preprocessing),
+        ) {
+            Ok(ocr) => ocr,
+            Err(e) => {
+                tracing::error!("Failed to initialize OCR engine: {}", e);
+                return;
+            }
+        };
+
+        let results = if changed_only {
+            self.canvas.extract_text_from_changed_detections(&ocr)
+        } else {
+            self.canvas.extract_text_from_detections(&ocr)
+        };
+        let results = match results {
+            Ok(results) => results,
+            Err(e) => {
+                tracing::error!("Failed to extract text: {}", e);
+                return;
+            }
+        };
+        tracing::info!("Extracted text from {} detections", results.len());
+        self.canvas.record_ocr_engine(engine);
+
+        // Flag detections whose text contains PII for redaction; a partial pass keeps the other flags
+        if !changed_only {
+            self.canvas.clear_pii_flags();
+        }
+        for (idx, result) in &results {
+            self.canvas.record_ocr_result(*idx, result.text(), *result.confidence());
+            let words = result.words().iter().flatten().map(crate::OcrWord::from).collect();
+            self.canvas.record_ocr_words(*idx, words);
+            self.canvas.flag_pii(*idx, result.text());
+        }
+
+        // In reading order, so the text follows the document rather than the detector
+        let texts: Vec<String> = self
+            .canvas
+            .ocr_reading_order()
+            .into_iter()
+            .filter_map(|idx| self.canvas.ocr_text(idx))
+            .map(|text| text.trim().to_string())
+            .collect();
+
+        // Emit custom event with extracted text
+        if let Ok(event) = AppEvent::custom("ocr", "text_extracted", &texts) {
+            self.plugin_manager.event_bus().sender().emit(event);
+        }
+    }
+
+    /// Hand this frame's events to the prompts, extensions, canvas and shell, then to the plugins
+    #[cfg(feature = "plugins")]
+    fn route_events(&mut self, ctx: &egui::Context) {
+        let _section = FrameSection::EventProcessing.span().entered();
+
+        // First, drain events for the application to handle
+        // This must happen BEFORE process_events() which also drains
+        let events = self.plugin_manager.event_bus_mut().drain_traced();
+
+        // Handle application events; canvas state changes are shared with the embeddable widget
+        for TracedEvent { event, span } in &events {
+            let _hop = span.enter();
+            if self.confirmed.as_ref() == Some(event) {
+                self.confirmed = None;
+            } else if let Some(dialog) = confirmation_prompt(&self.canvas, event) {
+                self.prompt = Some((dialog, Guarded::Event(event.clone())));
+                continue;
+            }
+            if self.extensions.iter_mut().any(|extension| extension.on_event(&mut self.canvas, event, ctx)) {
+                continue;
+            }
+            if crate::apply_canvas_event(&mut self.canvas, event) {
+                continue;
+            }
+            self.handle_event(event, ctx);
+        }
+
+        // Now distribute those same events to plugins for their reaction
+        // Re-emit them so plugins can process them
+        for TracedEvent { event, span } in events {
+            let _hop = span.enter();
+            self.plugin_manager.event_bus().sender().emit(event);
+        }
+
+        // Plugins read this frame's canvas state from one shared snapshot
+        self.plugin_manager.set_canvas_snapshot(self.canvas.snapshot());
+
+        // Process plugin events (which now includes the re-emitted events)
+        self.plugin_manager.process_events();
+    }
+
+    /// Carry out an event that needs the shell: file dialogs, detection, OCR and the shell's windows
+    #[cfg(feature = "plugins")]
+    fn handle_event(&mut self, event: &AppEvent, ctx: &egui::Context) {
+        match event {
+            AppEvent::OpenFileRequested => {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Form Factor Project", &["ffp"])
+                    .pick_file()
+                    && let Some(path_str) = path.to_str()
+                {
+                    match self.canvas.load_from_file(path_str, ctx) {
+                        Ok(()) => {
+                            tracing::info!("Loaded project from {}", path_str);
+                            // Emit FileOpened event
+                            self.plugin_manager.event_bus().sender().emit(AppEvent::FileOpened { path });
+                        }
+                        Err(e) => {
+                            tracing::error!("Failed to load project: {}", e);
+                        }
+                    }
+                }
+            }
+            AppEvent::BatchReviewRequested => {
+                if let Some(paths) = rfd::FileDialog::new()
+                    .add_filter("Form Factor Project", &["ffp", "json"])
+                    .pick_files()
+                {
+                    let mut review =
+                        crate::BatchReview::default().with_calibration(crate::ConfidenceCalibration::load());
+                    for path in &paths {
+                        if let Err(e) = review.add_project_file(path) {
+                            tracing::error!("Failed to load project for review: {}", e);
+                        }
+                    }
+                    self.canvas.start_batch_review(review);
+                }
+            }
+            AppEvent::ProjectDiffRequested => {
+                if let Some(before) = rfd::FileDialog::new()
+                    .set_title("Earlier version")
+                    .add_filter("Form Factor Project", &["ffp", "json"])
+                    .pick_file()
+                    && let Some(after) = rfd::FileDialog::new()
+                        .set_title("Later version")
+                        .add_filter("Form Factor Project", &["ffp", "json"])
+                        .pick_file()
+                {
+                    match crate::ProjectDiff::from_files(&before, &after) {
+                        Ok(diff) => self.canvas.start_project_diff(diff),
+                        Err(e) => tracing::error!("Failed to compare projects: {}", e),
+                    }
+                }
+            }
+            AppEvent::RefillExportRequested => {
+                if let Some(template) = rfd::FileDialog::new()
+                    .set_title("Clean form image")
+                    .add_filter("Images", &["png", "jpg", "jpeg", "webp"])
+                    .pick_file()
+                    && let Some(path) = rfd::FileDialog::new()
+                        .add_filter("PNG Image", &["png"])
+                        .add_filter("PDF Document", &["pdf"])
+                        .set_file_name(format!("{}_refilled.png", self.canvas.project_name()))
+                        .save_file()
+                    && let Err(e) = self.canvas.export_refill(
+                        &template.to_string_lossy(),
+                        &path.to_string_lossy(),
+                        self.canvas.print_options().page_size,
+                    )
+                {
+                    tracing::error!("Failed to export refilled form: {}", e);
+                }
+            }
+            AppEvent::ValidationReportRequested => {
+                // Report on the batch under review, or on project files picked now
+                let report = match self.canvas.batch_review() {
+                    Some(review) => Some(crate::ValidationReport::from_review(review)),
+                    None => rfd::FileDialog::new()
+                        .add_filter("Form Factor Project", &["ffp", "json"])
+                        .pick_files()
+                        .map(|paths| {
+                            let mut review =
+                                crate::BatchReview::default().with_calibration(crate::ConfidenceCalibration::load());
+                            for path in &paths {
+                                if let Err(e) = review.add_project_file(path) {
+                                    tracing::error!("Failed to load project for report: {}", e);
+                                }
+                            }
+                            crate::ValidationReport::from_review(&review)
+                        }),
+                };
+                if let Some(report) = report
+                    && let Some(path) = rfd::FileDialog::new()
+                        .add_filter("CSV", &["csv"])
+                        .add_filter("HTML", &["html"])
+                        .set_file_name("validation_report.csv")
+                        .save_file()
+                    && let Err(e) = report.save(&path)
+                {
+                    tracing::error!("Failed to save validation report: {}", e);
+                }
+            }
+            AppEvent::SaveFileRequested => {
+                // Save to current file or show save dialog
+                if let Some(path) = self.canvas.project_file().map(std::path::PathBuf::from) {
+                    match self.canvas.compact_journal() {
+                        Ok(_) => {
+                            tracing::info!("Saved project to {}", path.display());
+                            self.plugin_manager.event_bus().sender().emit(AppEvent::FileSaved { path });
+                        }
+                        Err(e) => {
+                            tracing::error!("Failed to save project: {}", e);
+                        }
+                    }
+                } else if let Some(name) = self.canvas.stored_project().map(str::to_string) {
+                    // Projects opened from the store are saved back there; a conflict opens the conflict window
+                    match self.canvas.save_to_store(&name) {
+                        Ok(()) => tracing::info!("Saved project to the store as {}", name),
+                        Err(e) => tracing::error!("Failed to save project to the store: {}", e),
+                    }
+                } else {
+                    self.save_as();
+                }
+            }
+            AppEvent::SaveAsRequested => self.save_as(),
+            AppEvent::LoadImageRequested => {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Image", &["png", "jpg", "jpeg", "tif", "tiff", "bmp"])
+                    .pick_file()
+                    && let Some(path_str) = path.to_str()
+                    && let Err(e) = self.canvas.load_form_image(path_str, ctx)
+                {
+                    tracing::error!("Failed to load form image: {}", e);
+                }
+            }
+            AppEvent::LogViewerToggled => {
+                self.log_viewer.toggle();
+            }
+            AppEvent::FrameTimingToggled => {
+                self.frame_timing.toggle();
+            }
+            AppEvent::SettingsRequested => {
+                self.overlays.open(SETTINGS_OVERLAY);
+            }
+            AppEvent::TourRequested => {
+                self.tour.start(Tour::welcome());
+            }
+            AppEvent::OverlayOpenRequested { id } => {
+                self.overlays.open(id);
+            }
+            AppEvent::TextExportRequested => {
+                let options = *self.canvas.text_export_options();
+                let extension = options.format.extension();
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter(options.format.to_string(), &[extension])
+                    .set_file_name(format!("{}.{}", self.canvas.project_name(), extension))
+                    .save_file()
+                    && let Some(path_str) = path.to_str()
+                    && let Err(e) = self.canvas.export_extracted_text(path_str, &options)
+                {
+                    tracing::error!("Failed to export extracted text: {}", e);
+                }
+            }
+            AppEvent::RedactedExportRequested => {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("PNG Image", &["png"])
+                    .set_file_name(format!("{}_redacted.png", self.canvas.project_name()))
+                    .save_file()
+                    && let Some(path_str) = path.to_str()
+                {
+                    match self.canvas.export_redacted_image(path_str) {
+                        Ok(count) => {
+                            tracing::info!("Exported redacted image with {} regions to {}", count, path_str);
+                        }
+                        Err(e) => {
+                            tracing::error!("Failed to export redacted image: {}", e);
+                        }
+                    }
+                }
+            }
+            AppEvent::DiagnosticBundleRequested => {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Zip Archive", &["zip"])
+                    .set_file_name("form_factor_diagnostics.zip")
+                    .save_file()
+                    && let Some(path_str) = path.to_str()
+                {
+                    let mut bundle = crate::DiagnosticBundle::new()
+                        .with_project(ProjectSummary::from_canvas(&self.canvas))
+                        .with_crash_reports(&self.crash_reporter);
+                    if let Some(logs) = self.crash_reporter.logs() {
+                        bundle = bundle.with_logs(logs);
+                    }
+                    if let Err(e) = bundle.export(path_str) {
+                        tracing::error!("Failed to export diagnostic bundle: {}", e);
+                    }
+                }
+            }
+            #[cfg(feature = "text-detection")]
+            AppEvent::TextDetectionRequested => {
+                let _detection = detection_span("text").entered();
+                let threshold = self.settings.settings().detection.text_confidence;
+                match self.canvas.detect_text_regions(threshold) {
+                    Ok(count) => {
+                        tracing::info!("Detected {} text regions", count);
+                        self.plugin_manager.event_bus().sender().emit(AppEvent::DetectionComplete {
+                            count,
+                            detection_type: "text".to_string(),
+                        });
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to detect text: {}", e);
+                    }
+                }
+            }
+            #[cfg(feature = "logo-detection")]
+            AppEvent::LogoDetectionRequested => {
+                let _detection = detection_span("logo").entered();
+                match self.canvas.detect_logos() {
+                    Ok(count) => {
+                        tracing::info!("Detected {} logos", count);
+                        self.plugin_manager.event_bus().sender().emit(AppEvent::DetectionComplete {
+                            count,
+                            detection_type: "logo".to_string(),
+                        });
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to detect logos: {}", e);
+                    }
+                }
+            }
+            #[cfg(feature = "ocr")]
+            AppEvent::OcrExtractionRequested => self.extract_text(false),
+            #[cfg(feature = "ocr")]
+            AppEvent::OcrRefreshRequested => self.extract_text(true),
+            _ => {
+                // Ignore other events
+            }
+        }
+    }
+
+    /// Ask for a file and save the project to it
+    #[cfg(feature = "plugins")]
+    fn save_as(&mut self) {
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("Form Factor Project", &["ffp"])
+            .set_file_name(format!("{}.ffp", self.canvas.project_name()))
+            .save_file()
+            && let Some(path_str) = path.to_str()
+        {
+            match self.canvas.save_to_file(path_str) {
+                Ok(()) => {
+                    tracing::info!("Saved project to {}", path_str);
+                    self.plugin_manager.event_bus().sender().emit(AppEvent::FileSaved { path });
+                }
+                Err(e) => {
+                    tracing::error!("Failed to save project: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Run the subsystems' exit and enter hooks on a mode switch, then bring
+    /// back the workspace as last arranged in the new mode
+    fn change_mode(&mut self) {
+        let (from, to) = (self.workspace.mode(), WorkspaceMode::of(&self.canvas));
+        self.mode_hooks.transition(from, to, &mut self.canvas);
+        if let Some(layout) = self.workspace.switch(to).cloned() {
+            layout.apply_to_canvas(&mut self.canvas);
+            #[cfg(feature = "plugins")]
+            {
+                self.plugin_manager.set_hidden_plugins(layout.hidden_plugins);
+                self.sidebar_restore = layout.sidebar_width;
+            }
+        }
+    }
+
+    /// Plugin sidebar, with a menu to show and hide plugins
+    #[cfg(feature = "plugins")]
+    fn show_sidebar(&mut self, ctx: &egui::Context) {
+        let _section = FrameSection::PluginRendering.span().entered();
+        let mut sidebar = egui::SidePanel::right("plugin_panel").default_width(280.0);
+        if let Some(width) = self.sidebar_restore.take() {
+            sidebar = sidebar.exact_width(width);
+        }
+        let sidebar = sidebar.show(ctx, |ui| {
+            ui.menu_button("Plugins", |ui| {
+                let names: Vec<String> = self.plugin_manager.plugin_names().into_iter().map(String::from).collect();
+                for name in names {
+                    let mut visible = self.plugin_manager.is_plugin_visible(&name);
+                    if ui.checkbox(&mut visible, &name).changed() {
+                        self.plugin_manager.set_plugin_visible(&name, visible);
+                    }
+                }
+            });
+            ui.separator();
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                self.plugin_manager.render_plugins(ui);
+            });
+        });
+        self.sidebar_width = sidebar.response.rect.width();
+    }
+
+    /// Route context menu actions to the matching event handlers
+    fn route_actions(&mut self) {
+        for action in self.canvas.take_actions() {
+            if matches!(
+                action,
+                CanvasAction::ExportPng
+                    | CanvasAction::ExportSvg
+                    | CanvasAction::ExportGeoJson
+                    | CanvasAction::ExportText
+                    | CanvasAction::ExportPdf
+            ) {
+                self.export_canvas(action);
+                continue;
+            }
+            if action == CanvasAction::SaveScreenshot {
+                self.save_screenshot();
+                continue;
+            }
+            if action == CanvasAction::ExportInstances {
+                self.export_instances();
+                continue;
+            }
+            #[cfg(feature = "plugins")]
+            {
+                let event = match action {
+                    CanvasAction::LoadImage => AppEvent::LoadImageRequested,
+                    CanvasAction::DetectText => AppEvent::TextDetectionRequested,
+                    CanvasAction::DetectLogos => AppEvent::LogoDetectionRequested,
+                    CanvasAction::RunOcr => AppEvent::OcrExtractionRequested,
+                    CanvasAction::ExportPng
+                    | CanvasAction::ExportSvg
+                    | CanvasAction::ExportGeoJson
+                    | CanvasAction::ExportText
+                    | CanvasAction::ExportPdf
+                    | CanvasAction::SaveScreenshot
+                    | CanvasAction::ExportInstances => continue,
+                };
+                self.plugin_manager.event_bus().sender().emit(event);
+            }
+            #[cfg(not(feature = "plugins"))]
+            tracing::debug!(?action, "Canvas action ignored without plugins");
+        }
+    }
+
+    /// Tell plugins and extensions when another shape is selected or the selection is cleared
+    fn track_selection(&mut self) {
+        let selected = *self.canvas.selected_shape();
+        if selected == self.selected_shape {
+            return;
+        }
+        self.selected_shape = selected;
+        tracing::debug!(?selected, "Selection changed");
+        #[cfg(feature = "plugins")]
+        self.plugin_manager.event_bus().sender().emit(match selected {
+            Some(index) => AppEvent::ShapeSelected { index },
+            None => AppEvent::SelectionCleared,
+        });
+        for extension in &mut self.extensions {
+            extension.on_selection_changed(&self.canvas, selected);
+        }
+    }
+}
+
+impl App for AppShell {
+    fn setup(&mut self, ctx: &egui::Context) {
+        let settings = self.settings.settings();
+        settings.appearance.apply(ctx);
+        self.canvas.apply_settings(settings);
+
+        // Try to load the most recent project (defers image loading)
+        match self.canvas.load_recent_on_startup(ctx) {
+            Ok(()) => {
+                tracing::info!("Auto-loaded most recent project");
+            }
+            Err(e) => {
+                tracing::debug!("No recent project to load: {}", e);
+                tracing::info!("Starting with default workspace");
+            }
+        }
+
+        // Show the guided tour on first launch
+        if !self.onboarding.is_completed(WELCOME_TOUR) {
+            self.tour.start(Tour::welcome());
+        }
+
+        tracing::info!("Application setup complete");
+    }
+
+    fn update(&mut self, ctx: &AppContext) {
+        self.frame_timing.timings().begin_frame();
+
+        #[cfg(feature = "metrics")]
+        crate::MetricsRegistry::global().observe(crate::FRAME_TIME_SECONDS, ctx.delta_time as f64);
+
+        // Process plugin events and wire them to canvas operations
+        #[cfg(feature = "plugins")]
+        self.route_events(ctx.egui_ctx);
+
+        self.change_mode();
+
+        // Plugin sidebar (if plugins feature is enabled)
+        #[cfg(feature = "plugins")]
+        self.show_sidebar(ctx.egui_ctx);
+
+        let async_results = FrameSection::AsyncResults.span().entered();
+
+        // Open files dropped on the window; extra files wait in a queue. A
+        // dropped project asks first if it would replace unsaved changes.
+        if self.canvas.queue_file_drop(ctx.egui_ctx) {
+            let dialog = match self.canvas.next_dropped() {
+                Some(file) if file.kind == DropKind::Project => unsaved_work_dialog(
+                    &self.canvas,
+                    UnsavedWork::of_project(&self.canvas),
+                    "opening the dropped project",
+                ),
+                _ => None,
+            };
+            match dialog {
+                Some(dialog) if self.prompt.is_none() => self.prompt = Some((dialog, Guarded::OpenDropped)),
+                // Waits in the queue while another prompt is open
+                Some(_) => {}
+                None => {
+                    if let Some(Err(e)) = self.canvas.open_next_dropped(ctx.egui_ctx) {
+                        tracing::error!("Failed to open dropped file: {}", e);
+                    }
+                }
+            }
+        }
+
+        // Paste clipboard images, e.g. screenshots of forms, with Ctrl+V
+        if let Some(Err(e)) = self.canvas.handle_paste_shortcut(ctx.egui_ctx) {
+            tracing::error!("Failed to paste image: {}", e);
+        }
+
+        // Append changes to the project's journal every AUTOSAVE_INTERVAL_SECS
+        match self.canvas.autosave(ctx.egui_ctx.input(|i| i.time)) {
+            Some(Ok(saved)) => tracing::debug!(?saved, "Autosaved project"),
+            Some(Err(e)) => tracing::error!("Failed to autosave project: {}", e),
+            None => {}
+        }
+
+        // Exchange shape edits and cursors with collaborators every COLLAB_SYNC_INTERVAL_SECS
+        match self.canvas.sync_collaboration(ctx.egui_ctx.input(|i| i.time)) {
+            Some(Ok(received)) if received > 0 => tracing::debug!(received, "Applied collaborators' edits"),
+            Some(Err(e)) => tracing::error!("Failed to sync collaboration session: {}", e),
+            _ => {}
+        }
+        drop(async_results);
+
+        // Keep the crash report's view of the project current
+        self.crash_reporter.update_state(ProjectSummary::from_canvas(&self.canvas));
+
+        // Status bar (must be added before the central panel)
+        let canvas_rendering = FrameSection::CanvasRendering.span().entered();
+        self.canvas.status_bar().show(ctx.egui_ctx);
+
+        // Main canvas area
+        self.canvas.advance_animations(ctx);
+        let canvas_panel = egui::CentralPanel::default().show(ctx.egui_ctx, |ui| {
+            self.canvas.ui(ui);
+        });
+        drop(canvas_rendering);
+        register_anchor(ctx.egui_ctx, ANCHOR_CANVAS, canvas_panel.response.rect);
+
+        self.route_actions();
+
+        // Floating windows
+        let canvas_rendering = FrameSection::CanvasRendering.span().entered();
+        self.canvas.show_comments_panel(ctx.egui_ctx);
+        self.canvas.show_workflow_panel(ctx.egui_ctx);
+        self.canvas.show_stats_panel(ctx.egui_ctx);
+        self.canvas.show_bookmarks_panel(ctx.egui_ctx);
+        self.canvas.show_repeat_panel(ctx.egui_ctx);
+        self.canvas.show_rename_panel(ctx.egui_ctx);
+        self.canvas.show_data_entry_panel(ctx.egui_ctx);
+        self.canvas.show_tab_order_panel(ctx.egui_ctx);
+        self.canvas.show_ocr_order_panel(ctx.egui_ctx);
+        self.canvas.show_field_groups_panel(ctx.egui_ctx);
+        self.canvas.show_batch_review_panel(ctx.egui_ctx);
+        self.canvas.show_project_diff_panel(ctx.egui_ctx);
+        self.canvas.show_save_conflict_panel(ctx.egui_ctx);
+        self.canvas.show_password_panel(ctx.egui_ctx);
+        self.canvas.show_store_panel(ctx.egui_ctx);
+        // A project chosen in the store window asks first if it would replace unsaved changes
+        if let Some(name) = self.canvas.take_store_open_request() {
+            match unsaved_work_dialog(&self.canvas, UnsavedWork::of_project(&self.canvas), "opening another project") {
+                Some(dialog) if self.prompt.is_none() => self.prompt = Some((dialog, Guarded::OpenStored(name))),
+                Some(_) => tracing::debug!(name, "Stored project not opened while another prompt is open"),
+                None => {
+                    if let Err(e) = self.canvas.open_store_request(&name, ctx.egui_ctx) {
+                        tracing::error!("Failed to open stored project: {}", e);
+                    }
+                }
+            }
+        }
+        self.canvas.show_collab_panel(ctx.egui_ctx);
+        self.canvas.show_unlock_panel(ctx.egui_ctx);
+        self.canvas.show_export_panel(ctx.egui_ctx);
+        self.canvas.show_print_panel(ctx.egui_ctx);
+        self.canvas.show_drop_queue_panel(ctx.egui_ctx);
+        self.canvas.show_paste_panel(ctx.egui_ctx);
+        self.canvas.show_new_project_panel(ctx.egui_ctx);
+        self.canvas.show_screenshot_panel(ctx.egui_ctx);
+        self.canvas.show_stamp_panel(ctx.egui_ctx);
+        for extension in &mut self.extensions {
+            extension.ui(&mut self.canvas, ctx.egui_ctx);
+        }
+
+        // Plugins follow the canvas through one notification per frame
+        #[cfg(feature = "plugins")]
+        if let Some(event) = crate::take_canvas_changed(&mut self.canvas) {
+            self.plugin_manager.event_bus().sender().emit(event);
+        }
+        // OCR readings people settled on feed the quality dashboard
+        #[cfg(feature = "plugins")]
+        for event in crate::take_ocr_sample_events(&mut self.canvas) {
+            self.plugin_manager.event_bus().sender().emit(event);
+        }
+        #[cfg(not(feature = "plugins"))]
+        self.canvas.take_ocr_samples();
+        self.track_selection();
+
+        // Properties follow the canvas, and closing the window hides them
+        if *self.canvas.show_properties() {
+            self.properties_window.open();
+        } else {
+            self.properties_window.close();
+        }
+        self.properties_window
+            .show(ctx.egui_ctx, |ui| self.canvas.show_inline_properties(ui));
+        if !self.properties_window.is_open() {
+            self.canvas.set_show_properties(false);
+        }
+        drop(canvas_rendering);
+
+        // Configurable shortcuts
+        let shortcuts = &self.settings.settings().shortcuts;
+        let pressed = |action| {
+            shortcuts
+                .key(action)
+                .is_some_and(|key| ctx.egui_ctx.input(|i| i.key_pressed(key)))
+        };
+        let toggle_log = pressed(ACTION_TOGGLE_LOG);
+        let open_settings = pressed(ACTION_OPEN_SETTINGS);
+        let toggle_frame_timing = pressed(ACTION_TOGGLE_FRAME_TIMING);
+        if toggle_log {
+            self.log_viewer.toggle();
+        }
+        if toggle_frame_timing {
+            self.frame_timing.toggle();
+        }
+        if open_settings {
+            self.overlays.toggle(SETTINGS_OVERLAY);
+        }
+        self.log_viewer.show(ctx.egui_ctx);
+        self.frame_timing.show(ctx.egui_ctx);
+
+        // Ask before closing the window would lose unsaved work
+        if ctx.egui_ctx.input(|i| i.viewport().close_requested()) && !self.closing {
+            let dialog = if self.prompt.is_some() {
+                None
+            } else {
+                unsaved_work_dialog(&self.canvas, UnsavedWork::of_canvas(&self.canvas), "quitting")
+            };
+            if self.prompt.is_some() || dialog.is_some() {
+                ctx.egui_ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            }
+            if let Some(dialog) = dialog {
+                self.prompt = Some((dialog, Guarded::Quit));
+            }
+        }
+
+        // Confirmation prompt, answered before Escape can reach the overlays beneath it
+        if let Some((dialog, _)) = &self.prompt
+            && let Some(outcome) = dialog.show(ctx.egui_ctx)
+            && let Some((_, guarded)) = self.prompt.take()
+        {
+            self.resolve_prompt(outcome, guarded, ctx.egui_ctx);
+        }
+
+        // Overlays
+        self.overlays.handle_escape(ctx.egui_ctx);
+        if let Some(settings) = self.settings.show(&mut self.overlays, ctx.egui_ctx) {
+            settings.appearance.apply(ctx.egui_ctx);
+            self.canvas.apply_settings(&settings);
+        }
+        #[cfg(feature = "plugins")]
+        {
+            let ids: Vec<String> = self.plugin_manager.overlays().map(|spec| spec.id.clone()).collect();
+            for id in ids {
+                let plugins = &mut self.plugin_manager;
+                let open = self.overlays.show_registered(ctx.egui_ctx, &id, |ui| plugins.render_overlay(&id, ui));
+                if open == Some(Some(false)) {
+                    self.overlays.close(&id);
+                }
+            }
+        }
+        self.overlays.arrange(ctx.egui_ctx);
+
+        // Command palette
+        #[cfg(feature = "plugins")]
+        {
+            self.palette.handle_shortcut(ctx.egui_ctx);
+            let events = self.plugin_manager.event_bus().sender();
+            self.palette.show(ctx.egui_ctx, &events);
+        }
+
+        // Guided tour, drawn above everything else
+        if let Some(done) = self.tour.show(ctx.egui_ctx) {
+            tracing::info!(tour = %done.tour_id, outcome = %done.outcome, "Guided tour ended");
+            self.onboarding.mark_completed(done.tour_id);
+            if let Err(e) = self.onboarding.save() {
+                tracing::warn!("Failed to save onboarding state: {}", e);
+            }
+        }
+
+        // Remember this frame's arrangement for the current mode
+        let layout = WorkspaceLayout::of_canvas(&self.canvas);
+        #[cfg(feature = "plugins")]
+        let layout = layout.with_sidebar(self.sidebar_width, self.plugin_manager.hidden_plugins().clone());
+        self.workspace.remember(WorkspaceMode::of(&self.canvas), layout);
+
+        self.frame_timing.timings().end_frame();
+    }
+
+    fn on_exit(&mut self) {
+        tracing::info!("Application exiting");
+
+        #[cfg(feature = "metrics")]
+        if let Ok(path) = std::env::var("FORM_FACTOR_METRICS_DUMP") {
+            match crate::MetricsRegistry::global().dump(&crate::JsonExporter, &path) {
+                Ok(()) => tracing::info!("Wrote metrics to {}", path),
+                Err(e) => tracing::error!("Failed to write metrics: {}", e),
+            }
+        }
+
+        #[cfg(feature = "plugins")]
+        {
+            tracing::info!("Shutting down plugins");
+            self.plugin_manager.shutdown();
+        }
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Span of one detection request, under a new trace ID
+///
+/// Cache lookups, the detector, adding results to the canvas and the
+/// `DetectionComplete` notification all log inside it.
+#[cfg(all(feature = "plugins", any(feature = "text-detection", feature = "logo-detection")))]
+fn detection_span(kind: &'static str) -> tracing::Span {
+    let span = tracing::info_span!("detection", trace_id = %crate::TraceId::next(), kind);
+    span.in_scope(|| tracing::info!("Detection requested"));
+    span
+}
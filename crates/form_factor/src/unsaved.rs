@@ -0,0 +1,60 @@
+//! Unsaved work an action would lose
+//!
+//! Quitting, opening or starting another project and starting another batch
+//! review replace work that may not be saved yet. [`UnsavedWork`] collects
+//! what would be lost so the application can ask to save, discard or cancel
+//! first.
+
+use crate::DrawingCanvas;
+
+/// Work on the canvas not yet saved to disk
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UnsavedWork {
+    /// Whether the project changed since it was last saved or opened
+    pub project: bool,
+    /// Batch review instances with corrections not yet saved back to their files
+    pub reviewed_instances: usize,
+}
+
+impl UnsavedWork {
+    /// Unsaved changes to the project on the canvas
+    pub fn of_project(canvas: &DrawingCanvas) -> Self {
+        Self {
+            project: canvas.has_unsaved_changes(),
+            reviewed_instances: 0,
+        }
+    }
+
+    /// Unsaved corrections in the canvas's batch review
+    pub fn of_review(canvas: &DrawingCanvas) -> Self {
+        Self {
+            project: false,
+            reviewed_instances: canvas.batch_review().as_ref().map_or(0, |review| review.unsaved_instances()),
+        }
+    }
+
+    /// All unsaved work on the canvas
+    pub fn of_canvas(canvas: &DrawingCanvas) -> Self {
+        Self {
+            project: canvas.has_unsaved_changes(),
+            ..Self::of_review(canvas)
+        }
+    }
+
+    /// Whether nothing would be lost
+    pub fn is_empty(&self) -> bool {
+        !self.project && self.reviewed_instances == 0
+    }
+
+    /// Question asking to save the work before `action`, e.g. "quitting"
+    pub fn question(&self, project_name: &str, action: &str) -> String {
+        let mut work = Vec::new();
+        if self.project {
+            work.push(format!("changes to {}", project_name));
+        }
+        if self.reviewed_instances > 0 {
+            work.push(format!("{} reviewed instance(s)", self.reviewed_instances));
+        }
+        format!("Save {} before {}?", work.join(" and "), action)
+    }
+}
@@ -0,0 +1,367 @@
+//! Embeddable annotation widget for third-party egui applications
+//!
+//! [`FormFactorWidget`] bundles the drawing canvas, its floating windows and
+//! the plugin sidebar behind a narrow API, so a host application can embed
+//! the annotation experience without adopting the [`App`](crate::App) and
+//! [`Backend`](crate::Backend) traits:
+//!
+//! - [`FormFactorWidget::show`] lays the widget out inside any `Ui`
+//! - [`FormFactorWidget::send`] feeds events in
+//! - [`FormFactorWidget::take_events`] and [`FormFactorWidget::take_actions`]
+//!   hand back the requests only the host can carry out, such as file
+//!   dialogs, detection and export destinations
+//! - [`DrawingCanvas::take_store_open_request`] on
+//!   [`FormFactorWidget::canvas_mut`] hands back a project chosen in the
+//!   project store window, for the host to open once it has dealt with
+//!   unsaved work
+//!
+//! ```no_run
+//! use form_factor::{AppEvent, DrawingCanvas, FormFactorWidget};
+//!
+//! let mut widget = FormFactorWidget::new(DrawingCanvas::new());
+//!
+//! // Each frame, inside the host's own UI
+//! # let ctx = egui::Context::default();
+//! egui::CentralPanel::default().show(&ctx, |ui| {
+//!     widget.show(ui);
+//! });
+//! for event in widget.take_events() {
+//!     if event == AppEvent::LoadImageRequested {
+//!         // Pick a file with the host's dialog and call widget.canvas_mut().load_form_image(..)
+//!     }
+//! }
+//! ```
+
+use form_factor_core::AppContext;
+use form_factor_drawing::{CanvasAction, CanvasCommand, CanvasPanel, DrawingCanvas, LayerType, ToolMode};
+use form_factor_plugins::{AppEvent, Plugin, PluginManager};
+use strum::IntoEnumIterator;
+use tracing::{debug, error, instrument};
+
+/// Drawing canvas and plugin sidebar as a single embeddable widget
+pub struct FormFactorWidget {
+    canvas: DrawingCanvas,
+    plugins: PluginManager,
+    /// Whether the plugin sidebar is shown
+    sidebar: bool,
+    /// Events the widget could not handle itself, oldest first
+    outbox: Vec<AppEvent>,
+    /// Export actions from the canvas windows and context menu
+    actions: Vec<CanvasAction>,
+}
+
+impl FormFactorWidget {
+    /// Wrap a canvas, with no plugins registered
+    pub fn new(canvas: DrawingCanvas) -> Self {
+        Self {
+            canvas,
+            plugins: PluginManager::new(),
+            sidebar: true,
+            outbox: Vec::new(),
+            actions: Vec::new(),
+        }
+    }
+
+    /// Register a plugin in the sidebar, along with the tools it contributes
+    pub fn with_plugin(mut self, plugin: Box<dyn Plugin>) -> Self {
+        self.plugins.register(plugin);
+        for tool in self.plugins.take_tools() {
+            self.canvas.register_tool(tool);
+        }
+        self
+    }
+
+    /// Show or hide the plugin sidebar
+    pub fn with_sidebar(mut self, sidebar: bool) -> Self {
+        self.sidebar = sidebar;
+        self
+    }
+
+    /// Canvas being annotated
+    pub fn canvas(&self) -> &DrawingCanvas {
+        &self.canvas
+    }
+
+    /// Mutable access to the canvas being annotated
+    pub fn canvas_mut(&mut self) -> &mut DrawingCanvas {
+        &mut self.canvas
+    }
+
+    /// Plugins shown in the sidebar
+    pub fn plugins(&self) -> &PluginManager {
+        &self.plugins
+    }
+
+    /// Mutable access to the plugins, e.g. to register more
+    pub fn plugins_mut(&mut self) -> &mut PluginManager {
+        &mut self.plugins
+    }
+
+    /// Queue an event for the widget and its plugins, handled on the next [`show`](Self::show)
+    pub fn send(&self, event: AppEvent) {
+        self.plugins.event_bus().sender().emit(event);
+    }
+
+    /// Take the events the widget left for the host, oldest first
+    ///
+    /// These are requests that need the host (opening and saving files,
+    /// running detection or OCR, settings) and notifications such as
+    /// [`AppEvent::ShapeSelected`].
+    pub fn take_events(&mut self) -> Vec<AppEvent> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    /// Take the export actions the user confirmed, oldest first
+    ///
+    /// The host picks a destination and calls the matching export method,
+    /// e.g. [`DrawingCanvas::export_png`].
+    pub fn take_actions(&mut self) -> Vec<CanvasAction> {
+        std::mem::take(&mut self.actions)
+    }
+
+    /// Lay out the sidebar and canvas in the available space
+    ///
+    /// Returns the response of the canvas area.
+    #[instrument(skip_all)]
+    pub fn show(&mut self, ui: &mut egui::Ui) -> egui::Response {
+        self.process_events();
+
+        if self.sidebar {
+            egui::SidePanel::right(ui.id().with("form_factor_plugins"))
+                .default_width(280.0)
+                .show_inside(ui, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        self.plugins.render_plugins(ui);
+                    });
+                });
+        }
+
+        // Hosts don't run the App trait, so frame timing comes from egui
+        let app_ctx = AppContext {
+            egui_ctx: ui.ctx(),
+            delta_time: ui.input(|i| i.stable_dt),
+            frame_count: ui.ctx().cumulative_frame_nr(),
+        };
+        self.canvas.advance_animations(&app_ctx);
+
+        let response = egui::CentralPanel::default()
+            .show_inside(ui, |ui| {
+                self.canvas.ui(ui);
+            })
+            .response;
+
+        // Exports and screenshots need a destination from the host; everything else goes through the bus
+        for action in self.canvas.take_actions() {
+            let event = match action {
+                CanvasAction::ExportPng
+                | CanvasAction::ExportSvg
+                | CanvasAction::ExportGeoJson
+                | CanvasAction::ExportText
+                | CanvasAction::ExportPdf
+                | CanvasAction::SaveScreenshot
+                | CanvasAction::ExportInstances => {
+                    self.actions.push(action);
+                    continue;
+                }
+                CanvasAction::LoadImage => AppEvent::LoadImageRequested,
+                CanvasAction::DetectText => AppEvent::TextDetectionRequested,
+                CanvasAction::DetectLogos => AppEvent::LogoDetectionRequested,
+                CanvasAction::RunOcr => AppEvent::OcrExtractionRequested,
+            };
+            self.send(event);
+        }
+
+        let ctx = ui.ctx().clone();
+        self.canvas.show_comments_panel(&ctx);
+        self.canvas.show_workflow_panel(&ctx);
+        self.canvas.show_stats_panel(&ctx);
+        self.canvas.show_bookmarks_panel(&ctx);
+        self.canvas.show_repeat_panel(&ctx);
+        self.canvas.show_rename_panel(&ctx);
+        self.canvas.show_data_entry_panel(&ctx);
+        self.canvas.show_tab_order_panel(&ctx);
+        self.canvas.show_ocr_order_panel(&ctx);
+        self.canvas.show_field_groups_panel(&ctx);
+        self.canvas.show_batch_review_panel(&ctx);
+        self.canvas.show_project_diff_panel(&ctx);
+        self.canvas.show_store_panel(&ctx);
+        self.canvas.show_collab_panel(&ctx);
+        if let Some(Err(e)) = self.canvas.sync_collaboration(ctx.input(|i| i.time)) {
+            error!("Failed to sync collaboration session: {}", e);
+        }
+        self.canvas.show_export_panel(&ctx);
+        self.canvas.show_print_panel(&ctx);
+        self.canvas.show_new_project_panel(&ctx);
+        self.canvas.show_screenshot_panel(&ctx);
+        self.canvas.show_stamp_panel(&ctx);
+
+        // Plugins follow the canvas through one notification per frame
+        if let Some(event) = take_canvas_changed(&mut self.canvas) {
+            self.send(event);
+        }
+        for event in take_ocr_sample_events(&mut self.canvas) {
+            self.send(event);
+        }
+
+        response
+    }
+
+    /// Apply queued events to the canvas, pass the rest to the host, then let plugins react
+    fn process_events(&mut self) {
+        let events = self.plugins.event_bus_mut().drain_events();
+        for event in &events {
+            if !apply_canvas_event(&mut self.canvas, event) {
+                self.outbox.push(event.clone());
+            }
+        }
+
+        for event in events {
+            self.send(event);
+        }
+        self.plugins.set_canvas_snapshot(self.canvas.snapshot());
+        self.plugins.process_events();
+    }
+}
+
+/// Apply an event that only concerns canvas state
+///
+/// Covers zoom and pan, tool, style preset and layer changes, and toggling
+/// the canvas windows. A selected tool name is looked up among the canvas's
+/// custom tools before the built-in ones. Returns `false` for events that need the host, such
+/// as file dialogs or detection, and for notifications.
+#[instrument(skip(canvas))]
+pub fn apply_canvas_event(canvas: &mut DrawingCanvas, event: &AppEvent) -> bool {
+    if let AppEvent::StylePresetSelected { name } = event {
+        canvas.choose_style_preset(name.clone());
+        return true;
+    }
+    if let AppEvent::ToolSelected { tool_name } = event
+        && canvas.select_custom_tool(tool_name)
+    {
+        debug!("Selected custom tool");
+        return true;
+    }
+    let Some(command) = canvas_command(event) else {
+        return false;
+    };
+    canvas.apply_command(command);
+    debug!("Applied canvas event");
+    true
+}
+
+/// Canvas command requested by an event, if the event concerns only canvas state
+pub fn canvas_command(event: &AppEvent) -> Option<CanvasCommand> {
+    let command = match event {
+        AppEvent::CanvasZoomChanged { zoom } => CanvasCommand::SetZoom(*zoom),
+        AppEvent::CanvasPanChanged { x, y } => CanvasCommand::SetPan { x: *x, y: *y },
+        AppEvent::ToolSelected { tool_name } => {
+            CanvasCommand::SetTool(ToolMode::iter().find(|tool| tool.to_string() == *tool_name)?)
+        }
+        AppEvent::LayerVisibilityChanged { layer_name, visible } => CanvasCommand::SetLayerVisible {
+            layer: layer_named(layer_name)?,
+            visible: *visible,
+        },
+        AppEvent::LayerSelected { layer_name } => CanvasCommand::SelectLayer(layer_named(layer_name)),
+        AppEvent::LayerClearRequested { layer_name } => CanvasCommand::ClearLayer(layer_named(layer_name)?),
+        AppEvent::ObjectVisibilityChanged {
+            layer_name,
+            index,
+            visible,
+        } => CanvasCommand::SetObjectHidden {
+            layer: layer_named(layer_name)?,
+            index: *index,
+            hidden: !visible,
+        },
+        AppEvent::ObjectLockChanged {
+            layer_name,
+            index,
+            locked,
+        } => CanvasCommand::SetObjectLocked {
+            layer: layer_named(layer_name)?,
+            index: *index,
+            locked: *locked,
+        },
+        AppEvent::ObjectMoveRequested {
+            layer_name,
+            index,
+            dx,
+            dy,
+        } => CanvasCommand::MoveObject {
+            layer: layer_named(layer_name)?,
+            index: *index,
+            dx: *dx,
+            dy: *dy,
+        },
+        AppEvent::ObjectDeleteRequested { layer_name, index } => CanvasCommand::DeleteObject {
+            layer: layer_named(layer_name)?,
+            index: *index,
+        },
+        AppEvent::CommentsPanelToggled => CanvasCommand::TogglePanel(CanvasPanel::Comments),
+        AppEvent::WorkflowPanelToggled => CanvasCommand::TogglePanel(CanvasPanel::Workflow),
+        AppEvent::StatsPanelToggled => CanvasCommand::TogglePanel(CanvasPanel::Stats),
+        AppEvent::BookmarksPanelToggled => CanvasCommand::TogglePanel(CanvasPanel::Bookmarks),
+        AppEvent::LegendToggled => CanvasCommand::TogglePanel(CanvasPanel::Legend),
+        AppEvent::RenamePanelToggled => CanvasCommand::TogglePanel(CanvasPanel::Rename),
+        AppEvent::DataEntryPanelToggled => CanvasCommand::TogglePanel(CanvasPanel::DataEntry),
+        AppEvent::TabOrderPanelToggled => CanvasCommand::TogglePanel(CanvasPanel::TabOrder),
+        AppEvent::OcrOrderPanelToggled => CanvasCommand::TogglePanel(CanvasPanel::OcrOrder),
+        AppEvent::OcrWordsToggled => CanvasCommand::TogglePanel(CanvasPanel::OcrWords),
+        AppEvent::FieldGroupsPanelToggled => CanvasCommand::TogglePanel(CanvasPanel::FieldGroups),
+        AppEvent::ProjectPasswordRequested => CanvasCommand::TogglePanel(CanvasPanel::Password),
+        AppEvent::ProjectStoreRequested => CanvasCommand::TogglePanel(CanvasPanel::Store),
+        AppEvent::CollaborationRequested => CanvasCommand::TogglePanel(CanvasPanel::Collaborate),
+        AppEvent::CanvasExportRequested => CanvasCommand::TogglePanel(CanvasPanel::Export),
+        AppEvent::PrintRequested => CanvasCommand::TogglePanel(CanvasPanel::Print),
+        AppEvent::RedactionModeChanged { enabled } => CanvasCommand::SetRedactionMode(*enabled),
+        AppEvent::TemplatePreviewChanged { enabled } => CanvasCommand::SetTemplatePreview(*enabled),
+        AppEvent::NewProjectRequested => CanvasCommand::OpenNewProject,
+        AppEvent::ScreenshotRequested => CanvasCommand::BeginScreenshot,
+        AppEvent::SelectionBackRequested => CanvasCommand::SelectionBack,
+        AppEvent::SelectionForwardRequested => CanvasCommand::SelectionForward,
+        _ => return None,
+    };
+    Some(command)
+}
+
+/// Canvas state to broadcast, if commands changed the canvas since the last call
+///
+/// Drains [`DrawingCanvas::take_changes`] and describes the resulting view
+/// state in an [`AppEvent::CanvasChanged`].
+pub fn take_canvas_changed(canvas: &mut DrawingCanvas) -> Option<AppEvent> {
+    if canvas.take_changes().is_empty() {
+        return None;
+    }
+    Some(AppEvent::CanvasChanged {
+        zoom: *canvas.zoom_level(),
+        pan_x: canvas.pan_offset().x,
+        pan_y: canvas.pan_offset().y,
+        tool: canvas.active_tool_name(),
+        visible_layers: LayerType::iter()
+            .filter(|layer_type| canvas.layer_manager().is_visible(*layer_type))
+            .map(|layer_type| layer_type.to_string())
+            .collect(),
+        selected_layer: canvas.selected_layer().map(|layer_type| layer_type.to_string()),
+    })
+}
+
+/// `ocr`/`correction` events for the OCR readings people settled on since the last call
+///
+/// Drains [`DrawingCanvas::take_ocr_samples`]; each event carries an
+/// [`OcrSample`](crate::OcrSample), the payload the quality dashboard reads.
+pub fn take_ocr_sample_events(canvas: &mut DrawingCanvas) -> Vec<AppEvent> {
+    canvas
+        .take_ocr_samples()
+        .into_iter()
+        .filter_map(|sample| {
+            debug!(field = %sample.field, corrected = sample.was_corrected(), "Reporting OCR sample");
+            AppEvent::custom("ocr", "correction", &sample)
+                .map_err(|e| error!("Failed to encode OCR sample: {}", e))
+                .ok()
+        })
+        .collect()
+}
+
+/// Layer with the given display name
+fn layer_named(name: &str) -> Option<LayerType> {
+    LayerType::iter().find(|layer_type| layer_type.to_string() == name)
+}
@@ -0,0 +1,203 @@
+//! Workspace arrangement remembered per mode
+//!
+//! Laying out a template, entering field values and reviewing a batch each
+//! call for a different workspace: another sidebar width, other plugins, other
+//! layers shown. [`WorkspaceMemory`] keeps the arrangement last used in each
+//! [`WorkspaceMode`] and hands it back when that mode is entered again, so
+//! opening data entry and closing it returns to the workspace as it was.
+//!
+//! Subsystems that belong to a mode register [`ModeHooks`] to set themselves
+//! up when the mode is entered and tear themselves down when it is left.
+
+use crate::{CanvasCommand, DrawingCanvas, LayerType};
+use std::collections::{BTreeMap, BTreeSet};
+use strum::IntoEnumIterator;
+
+/// What the user is doing on the canvas
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, strum::Display)]
+pub enum WorkspaceMode {
+    /// Drawing and arranging the template's fields
+    #[default]
+    Layout,
+    /// Filling in field values in the data entry window
+    #[strum(to_string = "Data entry")]
+    DataEntry,
+    /// Stepping through a batch of filled instances
+    #[strum(to_string = "Batch review")]
+    BatchReview,
+}
+
+impl WorkspaceMode {
+    /// Mode the canvas is in; a batch review wins over the data entry window
+    pub fn of(canvas: &DrawingCanvas) -> Self {
+        if canvas.batch_review().is_some() {
+            Self::BatchReview
+        } else if *canvas.show_data_entry() {
+            Self::DataEntry
+        } else {
+            Self::Layout
+        }
+    }
+}
+
+/// Arrangement of the workspace in one mode
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkspaceLayout {
+    /// Width of the plugin sidebar in points, if there is one
+    pub sidebar_width: Option<f32>,
+    /// Names of the plugins hidden from the sidebar
+    pub hidden_plugins: BTreeSet<String>,
+    /// Selected layer
+    pub selected_layer: Option<LayerType>,
+    /// Layers hidden on the canvas
+    pub hidden_layers: BTreeSet<LayerType>,
+}
+
+impl WorkspaceLayout {
+    /// Selected and hidden layers of the canvas, with no sidebar
+    pub fn of_canvas(canvas: &DrawingCanvas) -> Self {
+        Self {
+            sidebar_width: None,
+            hidden_plugins: BTreeSet::new(),
+            selected_layer: *canvas.selected_layer(),
+            hidden_layers: LayerType::iter()
+                .filter(|layer| !canvas.layer_manager().is_visible(*layer))
+                .collect(),
+        }
+    }
+
+    /// Adds the plugin sidebar's width and hidden plugins
+    pub fn with_sidebar(mut self, width: f32, hidden_plugins: BTreeSet<String>) -> Self {
+        self.sidebar_width = Some(width);
+        self.hidden_plugins = hidden_plugins;
+        self
+    }
+
+    /// Selects and shows or hides the canvas's layers as remembered
+    pub fn apply_to_canvas(&self, canvas: &mut DrawingCanvas) {
+        for layer in LayerType::iter() {
+            let visible = !self.hidden_layers.contains(&layer);
+            canvas.apply_command(CanvasCommand::SetLayerVisible { layer, visible });
+        }
+        canvas.apply_command(CanvasCommand::SelectLayer(self.selected_layer));
+    }
+}
+
+/// Workspace arrangement last used in each mode
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceMemory {
+    /// Mode the remembered arrangement is currently kept for
+    mode: WorkspaceMode,
+    /// Last arrangement seen in each mode
+    layouts: BTreeMap<WorkspaceMode, WorkspaceLayout>,
+}
+
+impl WorkspaceMemory {
+    /// Memory in layout mode with nothing remembered yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mode arrangements are currently remembered for
+    pub fn mode(&self) -> WorkspaceMode {
+        self.mode
+    }
+
+    /// Arrangement last seen in `mode`
+    pub fn layout(&self, mode: WorkspaceMode) -> Option<&WorkspaceLayout> {
+        self.layouts.get(&mode)
+    }
+
+    /// Remembers the arrangement seen while in `mode`
+    ///
+    /// Call once per frame. An arrangement seen after the mode changed but
+    /// before [`switch`](Self::switch) noticed is ignored, so whatever the new
+    /// mode did on entering it is not saved for the mode being left.
+    pub fn remember(&mut self, mode: WorkspaceMode, layout: WorkspaceLayout) {
+        if mode == self.mode {
+            self.layouts.insert(mode, layout);
+        }
+    }
+
+    /// Notices a switch to `mode`
+    ///
+    /// Returns the arrangement to restore when the mode changed and was used
+    /// before, and `None` otherwise.
+    pub fn switch(&mut self, mode: WorkspaceMode) -> Option<&WorkspaceLayout> {
+        if mode == self.mode {
+            return None;
+        }
+        tracing::debug!(from = %self.mode, to = %mode, "Workspace mode changed");
+        self.mode = mode;
+        self.layouts.get(&mode)
+    }
+}
+
+/// Callback run on a mode transition
+pub type ModeHook = Box<dyn FnMut(&mut DrawingCanvas)>;
+
+/// Enter and exit callbacks subsystems register for each mode
+#[derive(Default)]
+pub struct ModeHooks {
+    /// Callbacks run on entering a mode, with the subsystem that registered them
+    enter: Vec<(WorkspaceMode, &'static str, ModeHook)>,
+    /// Callbacks run on leaving a mode, with the subsystem that registered them
+    exit: Vec<(WorkspaceMode, &'static str, ModeHook)>,
+}
+
+impl std::fmt::Debug for ModeHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = |hooks: &[(WorkspaceMode, &'static str, ModeHook)]| {
+            hooks.iter().map(|(mode, subsystem, _)| (*mode, *subsystem)).collect::<Vec<_>>()
+        };
+        f.debug_struct("ModeHooks")
+            .field("enter", &names(&self.enter))
+            .field("exit", &names(&self.exit))
+            .finish()
+    }
+}
+
+impl ModeHooks {
+    /// No hooks registered
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs `hook` whenever `mode` is entered
+    pub fn on_enter(
+        &mut self,
+        mode: WorkspaceMode,
+        subsystem: &'static str,
+        hook: impl FnMut(&mut DrawingCanvas) + 'static,
+    ) {
+        self.enter.push((mode, subsystem, Box::new(hook)));
+    }
+
+    /// Runs `hook` whenever `mode` is left
+    pub fn on_exit(
+        &mut self,
+        mode: WorkspaceMode,
+        subsystem: &'static str,
+        hook: impl FnMut(&mut DrawingCanvas) + 'static,
+    ) {
+        self.exit.push((mode, subsystem, Box::new(hook)));
+    }
+
+    /// Runs the exit hooks of `from`, then the enter hooks of `to`
+    ///
+    /// Hooks run in the order they were registered. Nothing runs when the
+    /// mode did not change.
+    pub fn transition(&mut self, from: WorkspaceMode, to: WorkspaceMode, canvas: &mut DrawingCanvas) {
+        if from == to {
+            return;
+        }
+        for (_, subsystem, hook) in self.exit.iter_mut().filter(|(mode, _, _)| *mode == from) {
+            tracing::debug!(mode = %from, subsystem, "Leaving mode");
+            hook(canvas);
+        }
+        for (_, subsystem, hook) in self.enter.iter_mut().filter(|(mode, _, _)| *mode == to) {
+            tracing::debug!(mode = %to, subsystem, "Entering mode");
+            hook(canvas);
+        }
+    }
+}
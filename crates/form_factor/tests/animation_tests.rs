@@ -0,0 +1,223 @@
+//! Integration tests for frame-rate independent animations
+
+use egui::{Color32, Pos2, Stroke};
+use form_factor::{
+    AppContext, Animations, DETECTION_FLASH_SECONDS, DrawingCanvas, Easing, Rectangle, Settings, Shape, Tween,
+    approach,
+};
+
+fn app_ctx(ctx: &egui::Context, delta_time: f32) -> AppContext<'_> {
+    AppContext {
+        egui_ctx: ctx,
+        delta_time,
+        frame_count: 0,
+    }
+}
+
+/// Advance `tween` through `seconds` in frames of `frame_time`
+fn run(mut tween: Tween<f32>, seconds: f32, frame_time: f32) -> f32 {
+    let frames = (seconds / frame_time).round() as usize;
+    for _ in 0..frames {
+        tween.advance(frame_time);
+    }
+    tween.value()
+}
+
+// ============================================================================
+// Tweens
+// ============================================================================
+
+#[test]
+fn tweens_reach_the_same_value_at_any_frame_rate() {
+    for easing in [Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut, Easing::Pulse] {
+        let tween = Tween::new(0.0, 10.0, 1.0).with_easing(easing);
+        let at_30 = run(tween.clone(), 0.5, 1.0 / 30.0);
+        let at_144 = run(tween, 0.5, 1.0 / 144.0);
+
+        assert!((at_30 - at_144).abs() < 1e-3, "{:?}: {} at 30 Hz, {} at 144 Hz", easing, at_30, at_144);
+    }
+}
+
+#[test]
+fn tweens_stop_at_their_end() {
+    let mut tween = Tween::new(Pos2::ZERO, Pos2::new(100.0, 50.0), 0.5).with_easing(Easing::EaseOut);
+    assert_eq!(tween.value(), Pos2::ZERO);
+
+    tween.advance(2.0);
+    assert!(tween.is_finished());
+    assert_eq!(tween.progress(), 1.0);
+    assert_eq!(tween.value(), Pos2::new(100.0, 50.0));
+
+    // Time running backwards is ignored
+    let mut tween = Tween::new(0.0, 1.0, 1.0);
+    tween.advance(-1.0);
+    assert_eq!(tween.value(), 0.0);
+}
+
+#[test]
+fn pulses_return_to_their_start() {
+    let mut pulse = Tween::new(0.0, 1.0, 1.0).with_easing(Easing::Pulse);
+
+    assert!((pulse.advance(0.5) - 1.0).abs() < 1e-6, "Peak halfway through");
+    assert!(pulse.advance(0.5).abs() < 1e-6);
+}
+
+#[test]
+fn retargeting_continues_from_the_current_value() {
+    let mut tween = Tween::new(Color32::BLACK, Color32::WHITE, 1.0);
+    tween.advance(0.5);
+    let midway = tween.value();
+
+    tween.retarget(Color32::RED, 1.0);
+
+    assert_eq!(tween.value(), midway);
+    assert_eq!(tween.target(), Color32::RED);
+    assert!(!tween.is_finished());
+}
+
+#[test]
+fn approach_is_independent_of_frame_rate() {
+    let settle = |frame_time: f32| {
+        let mut value = 0.0;
+        for _ in 0..(1.0 / frame_time).round() as usize {
+            value = approach(value, 100.0, 0.25, frame_time);
+        }
+        value
+    };
+
+    // Four half-lives close all but a sixteenth of the gap
+    assert!((settle(1.0 / 30.0) - 93.75).abs() < 0.01);
+    assert!((settle(1.0 / 144.0) - 93.75).abs() < 0.01);
+    assert_eq!(approach(0.0, 100.0, 0.0, 0.01), 100.0);
+}
+
+// ============================================================================
+// Keyed animations
+// ============================================================================
+
+#[test]
+fn finished_animations_are_dropped() {
+    let mut animations = Animations::new();
+    animations.start("short", Tween::new(0.0, 1.0, 0.1));
+    animations.start("long", Tween::new(0.0, 1.0, 1.0));
+
+    animations.advance(0.5);
+
+    assert_eq!(animations.get(&"short"), None);
+    assert_eq!(animations.get(&"long"), Some(0.5));
+    assert_eq!(animations.len(), 1);
+}
+
+#[test]
+fn ticking_requests_frames_while_animating() {
+    let ctx = egui::Context::default();
+    let mut animations = Animations::new();
+    animations.start(0, Tween::new(0.0, 1.0, 0.1));
+
+    let _ = ctx.run(egui::RawInput::default(), |ctx| animations.tick(&app_ctx(ctx, 0.05)));
+    assert!(animations.is_animating());
+    assert!(ctx.has_requested_repaint());
+
+    let _ = ctx.run(egui::RawInput::default(), |ctx| animations.tick(&app_ctx(ctx, 0.05)));
+    assert!(animations.is_empty());
+}
+
+// ============================================================================
+// Detection flashes
+// ============================================================================
+
+/// Canvas with one detection added from a detection cache
+fn canvas_with_cached_detection(name: &str) -> DrawingCanvas {
+    add_cached_detection(DrawingCanvas::new(), name)
+}
+
+/// Add one detection to `canvas` from a detection cache
+fn add_cached_detection(mut canvas: DrawingCanvas, name: &str) -> DrawingCanvas {
+    let dir = std::env::temp_dir().join(format!("form_factor_{}_{}", std::process::id(), name));
+    let rect = Rectangle::from_corners(
+        Pos2::new(10.0, 10.0),
+        Pos2::new(60.0, 25.0),
+        Stroke::new(2.0, Color32::BLACK),
+        Color32::TRANSPARENT,
+    )
+    .expect("Valid rectangle");
+    let mut project = serde_json::to_value(DrawingCanvas::new()).expect("Serializable");
+    project["detections"] = serde_json::json!([Shape::Rectangle(rect)]);
+    let detected = DrawingCanvas::from_json(&project.to_string()).expect("Valid project");
+    detected.cache_detections(&dir, "key", "text", 0).expect("Cached");
+
+    assert_eq!(canvas.load_cached_detections(&dir, "key"), Some(1));
+    let _ = std::fs::remove_dir_all(&dir);
+    canvas
+}
+
+#[test]
+fn detections_loaded_from_the_cache_flash() {
+    let mut canvas = canvas_with_cached_detection("flash_cache");
+    assert!(canvas.is_animating());
+
+    let ctx = egui::Context::default();
+    canvas.advance_animations(&app_ctx(&ctx, DETECTION_FLASH_SECONDS / 2.0));
+    assert!(canvas.detection_flash(0).expect("Flashing") > 0.99, "Flash peaks halfway through");
+
+    canvas.advance_animations(&app_ctx(&ctx, DETECTION_FLASH_SECONDS));
+    assert_eq!(canvas.detection_flash(0), None);
+    assert!(!canvas.is_animating());
+}
+
+#[test]
+fn clearing_detections_stops_their_flashes() {
+    let mut canvas = canvas_with_cached_detection("flash_clear");
+
+    canvas.clear_detections();
+
+    assert!(!canvas.is_animating());
+}
+
+#[test]
+fn flashing_new_detections_can_be_turned_off() {
+    let mut settings = Settings::default();
+    assert!(settings.detection.flash_new);
+    settings.detection.flash_new = false;
+    let mut canvas = DrawingCanvas::new();
+    canvas.apply_settings(&settings);
+
+    let canvas = add_cached_detection(canvas, "flash_off");
+
+    assert!(!canvas.is_animating());
+    assert_eq!(canvas.detection_flash(0), None);
+}
+
+#[test]
+fn view_zooms_to_new_detections_when_enabled() {
+    let ctx = egui::Context::default();
+    let mut png = Vec::new();
+    image::RgbaImage::from_pixel(400, 300, image::Rgba([255, 255, 255, 255]))
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .expect("Encodable");
+    let mut settings = Settings::default();
+    assert!(!settings.detection.zoom_to_new);
+    settings.detection.zoom_to_new = true;
+    let mut canvas = DrawingCanvas::new();
+    canvas.apply_settings(&settings);
+    canvas.load_form_image_bytes("form.png", &png, &ctx).expect("Valid image");
+    let frame = |canvas: &mut DrawingCanvas| {
+        let input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(Pos2::ZERO, egui::vec2(800.0, 600.0))),
+            ..Default::default()
+        };
+        let _ = ctx.run(input, |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| canvas.ui(ui));
+        });
+    };
+    frame(&mut canvas);
+    let zoom = *canvas.zoom_level();
+
+    let mut canvas = add_cached_detection(canvas, "zoom_to_new");
+    frame(&mut canvas);
+
+    assert!(*canvas.zoom_level() > zoom, "Small detection on a large view should zoom in");
+    let mapper = canvas.coordinate_mapper();
+    let center = mapper.image_to_screen(form_factor::ImagePos(Pos2::new(35.0, 17.5))).expect("Image loaded");
+    assert!((center.0 - mapper.viewport().center()).length() < 1.0);
+}
@@ -0,0 +1,186 @@
+//! Integration tests for backend configuration
+
+use form_factor::{
+    App, AppContext, Backend, BackendConfig, FrameCallback, HeadlessBackend, HeadlessError, HeadlessRunner,
+};
+use std::sync::{Arc, Mutex};
+
+fn app_ctx(ctx: &egui::Context, frame_count: u64) -> AppContext<'_> {
+    AppContext {
+        egui_ctx: ctx,
+        delta_time: 1.0 / 60.0,
+        frame_count,
+    }
+}
+
+// ============================================================================
+// Frame callbacks
+// ============================================================================
+
+#[test]
+fn frame_callbacks_run_in_order_every_frame() {
+    let ctx = egui::Context::default();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (first, second) = (log.clone(), log.clone());
+    let config = BackendConfig::default()
+        .with_frame_callback(move |ctx| first.lock().expect("Unpoisoned").push(("first", ctx.frame_count)))
+        .with_frame_callback(move |ctx| second.lock().expect("Unpoisoned").push(("second", ctx.frame_count)));
+
+    config.run_frame_callbacks(&app_ctx(&ctx, 0));
+    config.run_frame_callbacks(&app_ctx(&ctx, 1));
+
+    assert_eq!(
+        *log.lock().expect("Unpoisoned"),
+        vec![("first", 0), ("second", 0), ("first", 1), ("second", 1)]
+    );
+}
+
+#[test]
+fn cloned_configs_share_callback_state() {
+    let ctx = egui::Context::default();
+    let mut frames = 0;
+    let count = Arc::new(Mutex::new(0));
+    let seen = count.clone();
+    let callback = FrameCallback::new(move |_| {
+        frames += 1;
+        *seen.lock().expect("Unpoisoned") = frames;
+    });
+    let config = BackendConfig {
+        frame_callbacks: vec![callback],
+        ..Default::default()
+    };
+
+    config.clone().run_frame_callbacks(&app_ctx(&ctx, 0));
+    config.run_frame_callbacks(&app_ctx(&ctx, 1));
+
+    assert_eq!(*count.lock().expect("Unpoisoned"), 2);
+}
+
+#[test]
+fn default_config_has_no_frame_callbacks() {
+    assert!(BackendConfig::default().frame_callbacks.is_empty());
+}
+
+// ============================================================================
+// Headless backend
+// ============================================================================
+
+/// App recording what it sees each frame
+#[derive(Default)]
+struct Recorder {
+    /// Screen rect and frame count of each update
+    frames: Arc<Mutex<Vec<(egui::Rect, u64)>>>,
+    /// Whether on_exit ran
+    exited: Arc<Mutex<bool>>,
+    /// Frames to keep requesting repaints for
+    animate_frames: u64,
+}
+
+impl App for Recorder {
+    fn update(&mut self, ctx: &AppContext) {
+        egui::CentralPanel::default().show(ctx.egui_ctx, |ui| ui.label("Headless"));
+        let rect = ctx.egui_ctx.input(|i| i.content_rect());
+        self.frames.lock().expect("Unpoisoned").push((rect, ctx.frame_count));
+        if ctx.frame_count < self.animate_frames {
+            ctx.egui_ctx.request_repaint();
+        }
+    }
+
+    fn on_exit(&mut self) {
+        *self.exited.lock().expect("Unpoisoned") = true;
+    }
+}
+
+fn headless_config(width: u32, height: u32) -> BackendConfig {
+    BackendConfig {
+        window_width: width,
+        window_height: height,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn headless_frames_lay_out_at_the_configured_resolution() {
+    let app = Recorder::default();
+    let frames = app.frames.clone();
+    let mut runner = HeadlessRunner::new(Box::new(app), headless_config(800, 600)).expect("Non-empty viewport");
+
+    let frame = runner.frame();
+    runner.frame();
+
+    assert!(!frame.primitives.is_empty(), "Panel should tessellate into meshes");
+    let expected = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(800.0, 600.0));
+    assert_eq!(*frames.lock().expect("Unpoisoned"), vec![(expected, 0), (expected, 1)]);
+    assert_eq!(runner.frame_count(), 2);
+}
+
+#[test]
+fn headless_run_stops_when_the_app_settles() {
+    let app = Recorder {
+        animate_frames: 3,
+        ..Default::default()
+    };
+    let (frames, exited) = (app.frames.clone(), app.exited.clone());
+    let callback_frames = Arc::new(Mutex::new(0));
+    let seen = callback_frames.clone();
+    let config = headless_config(320, 240).with_frame_callback(move |_| *seen.lock().expect("Unpoisoned") += 1);
+
+    HeadlessBackend::run(Box::new(app), config).expect("Headless run");
+
+    // Frames 0 to 2 ask for another frame; egui may add a frame of its own
+    // after the last request before settling
+    let ran = frames.lock().expect("Unpoisoned").len();
+    assert!((4..=6).contains(&ran), "Ran {} frames", ran);
+    assert_eq!(*callback_frames.lock().expect("Unpoisoned"), ran);
+    assert!(*exited.lock().expect("Unpoisoned"));
+}
+
+#[test]
+fn headless_run_stops_when_the_app_closes() {
+    struct Closer;
+    impl App for Closer {
+        fn update(&mut self, ctx: &AppContext) {
+            ctx.egui_ctx.request_repaint();
+            ctx.egui_ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+    }
+
+    let mut runner = HeadlessRunner::new(Box::new(Closer), headless_config(100, 100)).expect("Non-empty viewport");
+
+    assert_eq!(runner.run_until_idle(10), 1);
+}
+
+#[test]
+fn headless_input_events_reach_the_app() {
+    struct KeyWatcher(Arc<Mutex<bool>>);
+    impl App for KeyWatcher {
+        fn update(&mut self, ctx: &AppContext) {
+            if ctx.egui_ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
+                *self.0.lock().expect("Unpoisoned") = true;
+            }
+        }
+    }
+
+    let pressed = Arc::new(Mutex::new(false));
+    let app = KeyWatcher(pressed.clone());
+    let mut runner = HeadlessRunner::new(Box::new(app), headless_config(100, 100)).expect("Non-empty viewport");
+    runner.frame();
+    assert!(!*pressed.lock().expect("Unpoisoned"));
+
+    runner.push_event(egui::Event::Key {
+        key: egui::Key::Enter,
+        physical_key: None,
+        pressed: true,
+        repeat: false,
+        modifiers: egui::Modifiers::NONE,
+    });
+    runner.frame();
+    assert!(*pressed.lock().expect("Unpoisoned"));
+}
+
+#[test]
+fn headless_backend_rejects_an_empty_viewport() {
+    let result = HeadlessBackend::run(Box::new(Recorder::default()), headless_config(0, 600));
+
+    assert!(matches!(result, Err(HeadlessError::EmptyViewport { width: 0, height: 600 })));
+}
@@ -0,0 +1,314 @@
+//! Integration tests for batch review of flagged field values across instances
+
+mod common;
+
+use common::extracted_field;
+use form_factor::{
+    BatchReview, CanvasErrorKind, DrawingCanvas, FieldFormat, FieldIssue, FieldRule, JournalSave, OcrSample,
+    ProjectEncoding, ReviewAction, ReviewReason, compress, is_compressed,
+};
+use std::path::PathBuf;
+
+/// A filled W-2 with one field per confidence band
+fn instance(wages: &str, wages_confidence: f32) -> DrawingCanvas {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![
+        extracted_field("name", FieldRule::new(FieldFormat::Text), "Jane Doe", Some(97.0)),
+        extracted_field(
+            "wages",
+            FieldRule::new(FieldFormat::Number),
+            wages,
+            Some(wages_confidence),
+        ),
+        extracted_field("state", FieldRule::new(FieldFormat::Text), "OR", None),
+    ]);
+    canvas
+}
+
+fn batch() -> BatchReview {
+    let mut review = BatchReview::new(80.0);
+    review.add_instance("jane", None, instance("52,000", 41.0));
+    review.add_instance("john", None, instance("5z,000", 93.0));
+    review.add_instance("ann", None, instance("48,250", 88.0));
+    review
+}
+
+/// Fresh directory for instance project files
+fn review_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("form_factor_review_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Creatable");
+    dir
+}
+
+// ============================================================================
+// Building the queue
+// ============================================================================
+
+#[test]
+fn queue_holds_low_confidence_and_invalid_fields_in_instance_order() {
+    let review = batch();
+
+    let queued: Vec<(usize, usize, ReviewReason)> = review
+        .queue()
+        .iter()
+        .map(|item| (item.instance, item.field, item.reason))
+        .collect();
+    assert_eq!(
+        queued,
+        [
+            (0, 1, ReviewReason::LowConfidence(41.0)),
+            (1, 1, ReviewReason::Invalid(FieldIssue::NotANumber)),
+        ]
+    );
+    assert_eq!(review.remaining(), 2);
+}
+
+#[test]
+fn entered_values_have_no_confidence() {
+    let mut shape = extracted_field("wages", FieldRule::default(), "52,000", Some(41.0));
+    assert_eq!(shape.confidence(), Some(41.0));
+
+    shape.set_value("52,500");
+
+    assert_eq!(shape.confidence(), None);
+}
+
+// ============================================================================
+// Decisions
+// ============================================================================
+
+#[test]
+fn accept_and_correct_update_the_instance_and_move_on() {
+    let mut review = batch();
+
+    assert!(review.apply(ReviewAction::Accept, 0.0));
+    assert!(review.apply(ReviewAction::Correct("52,000".to_string()), 2.0));
+
+    let jane = &review.instances()[0];
+    assert!(jane.modified);
+    assert_eq!(jane.canvas.shapes()[1].confidence(), None);
+    let john = &review.instances()[1];
+    assert_eq!(john.canvas.shapes()[1].value(), "52,000");
+    assert!(review.current().is_none());
+    assert!(!review.apply(ReviewAction::Skip, 3.0));
+}
+
+#[test]
+fn decisions_on_extracted_values_are_reported() {
+    let mut review = batch();
+
+    review.apply(ReviewAction::Accept, 0.0);
+    review.apply(ReviewAction::Correct("52,000".to_string()), 1.0);
+
+    let samples = review.take_ocr_samples();
+    assert_eq!(
+        samples,
+        [
+            OcrSample {
+                field: "wages".to_string(),
+                proposed: "52,000".to_string(),
+                corrected: "52,000".to_string(),
+                confidence: 41.0,
+            },
+            OcrSample {
+                field: "wages".to_string(),
+                proposed: "5z,000".to_string(),
+                corrected: "52,000".to_string(),
+                confidence: 93.0,
+            },
+        ]
+    );
+    assert!(review.take_ocr_samples().is_empty());
+
+    // The canvas hosting the review passes them on with its own
+    let mut canvas = DrawingCanvas::new();
+    let mut review = batch();
+    review.apply(ReviewAction::Accept, 0.0);
+    canvas.start_batch_review(review);
+    assert_eq!(canvas.take_ocr_samples().len(), 1);
+}
+
+#[test]
+fn invalid_values_cannot_be_accepted_or_saved_as_corrections() {
+    let mut review = batch();
+    review.apply(ReviewAction::Skip, 0.0);
+
+    assert!(!review.apply(ReviewAction::Accept, 1.0));
+    assert!(!review.apply(ReviewAction::Correct("fifty".to_string()), 2.0));
+
+    assert_eq!(review.current().map(|item| item.instance), Some(1));
+    assert!(!review.instances()[1].modified);
+    assert_eq!(review.stats().reviewed(), 1);
+}
+
+#[test]
+fn stats_count_decisions_and_pace() {
+    let mut review = BatchReview::new(100.0);
+    for name in ["a", "b", "c"] {
+        review.add_instance(name, None, instance("1", 50.0));
+    }
+    // Name and wages of every instance fall under a threshold of 100
+    assert_eq!(review.remaining(), 6);
+
+    review.apply(ReviewAction::Accept, 10.0);
+    review.apply(ReviewAction::Correct("2".to_string()), 12.0);
+    review.apply(ReviewAction::Skip, 14.0);
+    review.apply(ReviewAction::Accept, 16.0);
+
+    let stats = review.stats();
+    assert_eq!((stats.accepted, stats.corrected, stats.skipped), (2, 1, 1));
+    assert_eq!(stats.elapsed_seconds(), 6.0);
+    assert_eq!(stats.fields_per_minute(), Some(30.0));
+    assert_eq!(review.remaining(), 2);
+}
+
+#[test]
+fn changing_the_threshold_brings_back_only_skipped_fields() {
+    let mut review = batch();
+    review.apply(ReviewAction::Accept, 0.0);
+    review.apply(ReviewAction::Skip, 1.0);
+
+    review.set_threshold(90.0);
+
+    let queued: Vec<(usize, usize)> = review.queue().iter().map(|item| (item.instance, item.field)).collect();
+    assert_eq!(queued, [(1, 1), (2, 1)]);
+}
+
+// ============================================================================
+// Project files
+// ============================================================================
+
+#[test]
+fn reviewed_instances_are_saved_back_to_their_files() {
+    let dir = review_dir("save");
+    let paths: Vec<PathBuf> = [("jane", 41.0), ("john", 99.0)]
+        .iter()
+        .map(|(name, confidence)| {
+            let path = dir.join(format!("{}.ffp", name));
+            std::fs::write(&path, instance("52,000", *confidence).to_json().expect("Serializable")).expect("Writable");
+            path
+        })
+        .collect();
+
+    let mut review = BatchReview::new(80.0);
+    for path in &paths {
+        review.add_project_file(path).expect("Loadable");
+    }
+    assert_eq!(review.instances()[0].name, "jane");
+    assert_eq!(review.remaining(), 1);
+
+    review.apply(ReviewAction::Correct("52,100".to_string()), 0.0);
+    assert_eq!(review.save().expect("Saved"), 1);
+
+    let saved = DrawingCanvas::from_json(&std::fs::read_to_string(&paths[0]).expect("Readable")).expect("Loadable");
+    assert_eq!(saved.shapes()[1].value(), "52,100");
+    assert_eq!(saved.shapes()[1].confidence(), None);
+    assert_eq!(saved.shapes()[0].confidence(), Some(97.0));
+    assert!(!review.instances()[0].modified);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn autosaved_changes_are_reviewed_and_saved_into_the_snapshot() {
+    let dir = review_dir("journal");
+    let path = dir.join("jane.ffp");
+    let mut canvas = instance("52,000", 95.0);
+    canvas.save_to_file(path.to_str().expect("UTF-8 path")).expect("Saved");
+    // An autosave records the extractor's second reading in the journal only
+    let mut shapes = canvas.shapes().clone();
+    shapes[1].set_extracted_value("52,O00", Some(35.0));
+    canvas.set_shapes(shapes);
+    assert!(matches!(canvas.save_incremental(), Ok(JournalSave::Delta { .. })));
+
+    let mut review = BatchReview::new(80.0);
+    review.add_project_file(&path).expect("Loadable");
+    assert_eq!(review.remaining(), 1);
+    assert_eq!(review.instances()[0].canvas.shapes()[1].value(), "52,O00");
+
+    review.apply(ReviewAction::Correct("52,000".to_string()), 0.0);
+    assert_eq!(review.save().expect("Saved"), 1);
+
+    let saved = DrawingCanvas::from_bytes(&std::fs::read(&path).expect("Readable")).expect("Loadable");
+    assert_eq!(saved.shapes()[1].value(), "52,000");
+    assert_eq!(saved.shapes()[1].confidence(), None);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn binary_instances_are_loaded_and_saved_back_as_binary() {
+    let dir = review_dir("binary");
+    let path = dir.join("jane.ffp");
+    let bytes = instance("52,00O", 41.0)
+        .to_bytes(ProjectEncoding::MessagePack)
+        .expect("Serializable");
+    std::fs::write(&path, bytes).expect("Writable");
+
+    let mut review = BatchReview::new(80.0);
+    review.add_project_file(&path).expect("Loadable");
+    assert_eq!(review.remaining(), 1);
+    review.apply(ReviewAction::Correct("52,000".to_string()), 0.0);
+    assert_eq!(review.save().expect("Saved"), 1);
+
+    let bytes = std::fs::read(&path).expect("Readable");
+    assert_eq!(ProjectEncoding::detect(&bytes), ProjectEncoding::MessagePack);
+    let saved = DrawingCanvas::from_bytes(&bytes).expect("Loadable");
+    assert_eq!(saved.shapes()[1].value(), "52,000");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn compressed_instances_are_loaded_and_saved_back_compressed() {
+    let dir = review_dir("compressed");
+    let path = dir.join("jane.ffp");
+    let bytes = instance("52,00O", 41.0)
+        .to_bytes(ProjectEncoding::MessagePack)
+        .expect("Serializable");
+    std::fs::write(&path, compress(&bytes, 3).expect("Compressible")).expect("Writable");
+
+    let mut review = BatchReview::new(80.0);
+    review.add_project_file(&path).expect("Loadable");
+    assert_eq!(review.remaining(), 1);
+    review.apply(ReviewAction::Correct("52,000".to_string()), 0.0);
+    assert_eq!(review.save().expect("Saved"), 1);
+
+    let bytes = std::fs::read(&path).expect("Readable");
+    assert!(is_compressed(&bytes));
+    let saved = DrawingCanvas::from_bytes(&bytes).expect("Loadable");
+    assert_eq!(saved.shapes()[1].value(), "52,000");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn instances_saved_by_someone_else_meanwhile_are_not_overwritten() {
+    let dir = review_dir("conflict");
+    let path = dir.join("jane.ffp");
+    std::fs::write(&path, instance("52,00O", 41.0).to_json().expect("Serializable")).expect("Writable");
+
+    let mut review = BatchReview::new(80.0);
+    review.add_project_file(&path).expect("Loadable");
+    review.apply(ReviewAction::Correct("52,000".to_string()), 0.0);
+    // Another operator saves the same instance before the review is saved
+    std::fs::write(&path, instance("52,010", 99.0).to_json().expect("Serializable")).expect("Writable");
+
+    let err = review.save().expect_err("Save conflict");
+    assert!(matches!(err.kind, CanvasErrorKind::SaveConflict(_)));
+    assert!(review.instances()[0].modified);
+    let on_disk = DrawingCanvas::from_bytes(&std::fs::read(&path).expect("Readable")).expect("Loadable");
+    assert_eq!(on_disk.shapes()[1].value(), "52,010");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn unreadable_project_files_are_reported() {
+    let dir = review_dir("broken");
+    let path = dir.join("broken.ffp");
+    std::fs::write(&path, "not json").expect("Writable");
+
+    let mut review = BatchReview::default();
+
+    assert!(review.add_project_file(&path).is_err());
+    assert!(review.add_project_file(&dir.join("missing.ffp")).is_err());
+    assert!(review.instances().is_empty());
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,152 @@
+//! Integration tests for named viewport bookmarks
+
+mod common;
+
+use common::{remove, reopen, temp_path};
+use egui::{Key, Modifiers, vec2};
+use form_factor::{CanvasCommand, CanvasPanel, CanvasSimulator, DrawingCanvas, ViewBookmark};
+
+/// Canvas with a "Header" bookmark at 2x and a "Totals" bookmark at 4x further down
+fn header_and_totals() -> DrawingCanvas {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_zoom(2.0);
+    canvas.set_pan_offset(0.0, 300.0);
+    canvas.add_bookmark("Header");
+    canvas.set_zoom(4.0);
+    canvas.set_pan_offset(-50.0, -1200.0);
+    canvas.add_bookmark("Totals");
+    canvas
+}
+
+// ============================================================================
+// Bookmarks
+// ============================================================================
+
+#[test]
+fn bookmarks_capture_the_current_view() {
+    let canvas = header_and_totals();
+
+    assert_eq!(
+        canvas.bookmarks(),
+        &vec![
+            ViewBookmark {
+                name: "Header".into(),
+                zoom: 2.0,
+                pan: vec2(0.0, 300.0),
+                page: 0,
+            },
+            ViewBookmark {
+                name: "Totals".into(),
+                zoom: 4.0,
+                pan: vec2(-50.0, -1200.0),
+                page: 0,
+            },
+        ]
+    );
+}
+
+#[test]
+fn going_to_a_bookmark_restores_and_broadcasts_its_view() {
+    let mut canvas = header_and_totals();
+    canvas.take_changes();
+
+    assert!(canvas.go_to_bookmark(0));
+
+    assert_eq!(*canvas.zoom_level(), 2.0);
+    assert_eq!(*canvas.pan_offset(), vec2(0.0, 300.0));
+    assert_eq!(
+        canvas.take_changes(),
+        vec![CanvasCommand::SetZoom(2.0), CanvasCommand::SetPan { x: 0.0, y: 300.0 }]
+    );
+    assert!(!canvas.go_to_bookmark(2), "No third bookmark");
+}
+
+#[test]
+fn blank_names_are_numbered() {
+    let mut canvas = header_and_totals();
+
+    let idx = canvas.add_bookmark("  ");
+    assert_eq!(canvas.bookmarks()[idx].name, "View 3");
+
+    // Renaming to a blank name keeps the old one
+    assert!(canvas.rename_bookmark(idx, ""));
+    assert_eq!(canvas.bookmarks()[idx].name, "View 3");
+    assert!(canvas.rename_bookmark(idx, "Signature"));
+    assert_eq!(canvas.bookmarks()[idx].name, "Signature");
+}
+
+#[test]
+fn updating_a_bookmark_keeps_its_name() {
+    let mut canvas = header_and_totals();
+    canvas.set_zoom(3.0);
+
+    assert!(canvas.update_bookmark(0));
+    assert_eq!(canvas.bookmarks()[0].name, "Header");
+    assert_eq!(canvas.bookmarks()[0].zoom, 3.0);
+
+    assert_eq!(canvas.remove_bookmark(1).map(|b| b.name), Some("Totals".to_string()));
+    assert!(canvas.remove_bookmark(1).is_none());
+}
+
+#[test]
+fn bookmarks_are_saved_with_the_project() {
+    let path = temp_path("bookmarks.ffp");
+    let mut canvas = header_and_totals();
+    canvas.save_to_file(path.to_str().expect("UTF-8 path")).expect("Saved");
+
+    let reopened = reopen(&path);
+    remove(&path);
+
+    assert_eq!(reopened.bookmarks(), canvas.bookmarks());
+}
+
+#[test]
+fn projects_without_bookmarks_still_open() {
+    let mut project = serde_json::to_value(DrawingCanvas::new()).expect("Serialized");
+    project.as_object_mut().expect("Object").remove("bookmarks");
+
+    let canvas = DrawingCanvas::from_json(&project.to_string()).expect("Valid project");
+
+    assert!(canvas.bookmarks().is_empty());
+}
+
+// ============================================================================
+// Shortcuts and window
+// ============================================================================
+
+#[test]
+fn ctrl_digits_jump_between_bookmarks() {
+    let mut sim = CanvasSimulator::new(header_and_totals());
+
+    sim.key_press(Key::Num1, Modifiers::COMMAND);
+    assert_eq!(*sim.canvas().zoom_level(), 2.0);
+
+    sim.key_press(Key::Num2, Modifiers::COMMAND);
+    assert_eq!(*sim.canvas().zoom_level(), 4.0);
+    assert_eq!(*sim.canvas().pan_offset(), vec2(-50.0, -1200.0));
+
+    // Nothing bookmarked on 3
+    sim.key_press(Key::Num3, Modifiers::COMMAND);
+    assert_eq!(*sim.canvas().zoom_level(), 4.0);
+}
+
+#[test]
+fn ctrl_b_bookmarks_the_view() {
+    let mut sim = CanvasSimulator::new(header_and_totals());
+
+    sim.key_press(Key::B, Modifiers::COMMAND);
+
+    assert_eq!(sim.canvas().bookmarks().len(), 3);
+    assert_eq!(sim.canvas().bookmarks()[2].name, "View 3");
+}
+
+#[test]
+fn bookmarks_window_toggles() {
+    let mut canvas = DrawingCanvas::new();
+
+    assert!(canvas.apply_command(CanvasCommand::TogglePanel(CanvasPanel::Bookmarks)));
+    assert!(*canvas.show_bookmarks());
+
+    canvas.apply_command(CanvasCommand::TogglePanel(CanvasPanel::Bookmarks));
+    assert!(!*canvas.show_bookmarks());
+}
@@ -0,0 +1,173 @@
+//! Integration tests for calibrating OCR confidence from review decisions
+
+mod common;
+
+use common::field_at;
+use form_factor::{
+    BatchReview, CalibrationCurve, ConfidenceCalibration, DrawingCanvas, MIN_CALIBRATION_SAMPLES,
+    ReviewAction, ReviewReason, Shape,
+};
+
+fn field(name: &str, value: &str, confidence: f32) -> Shape {
+    let mut shape = field_at(name, 10.0, 10.0);
+    shape.set_extracted_value(value, Some(confidence));
+    shape
+}
+
+fn instance(template: &str, fields: Vec<Shape>) -> DrawingCanvas {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_template_name(Some(template.to_string()));
+    canvas.set_shapes(fields);
+    canvas
+}
+
+/// Record `correct` right and `wrong` wrong values at one confidence
+fn record(
+    calibration: &mut ConfidenceCalibration,
+    template: &str,
+    field: &str,
+    confidence: f32,
+    correct: u32,
+    wrong: u32,
+) {
+    for _ in 0..correct {
+        calibration.record(template, field, confidence, true);
+    }
+    for _ in 0..wrong {
+        calibration.record(template, field, confidence, false);
+    }
+}
+
+// ============================================================================
+// Curves
+// ============================================================================
+
+#[test]
+fn curves_report_accuracy_once_a_band_has_enough_samples() {
+    let mut curve = CalibrationCurve::default();
+    for _ in 0..MIN_CALIBRATION_SAMPLES - 1 {
+        curve.record(72.0, true);
+    }
+    assert_eq!(curve.accuracy(75.0), None);
+
+    curve.record(79.9, false);
+
+    let expected = (MIN_CALIBRATION_SAMPLES - 1) as f32 * 100.0 / MIN_CALIBRATION_SAMPLES as f32;
+    assert_eq!(curve.accuracy(70.0), Some(expected));
+    assert_eq!(curve.accuracy(80.0), None);
+    assert_eq!(curve.samples(), MIN_CALIBRATION_SAMPLES);
+}
+
+#[test]
+fn out_of_range_confidences_fall_in_the_end_bands() {
+    let mut curve = CalibrationCurve::default();
+    curve.record(100.0, true);
+    curve.record(140.0, true);
+    curve.record(-3.0, false);
+
+    assert_eq!(curve.band(95.0).total, 2);
+    assert_eq!(curve.band(0.0).total, 1);
+}
+
+// ============================================================================
+// Calibrating
+// ============================================================================
+
+#[test]
+fn field_curves_are_preferred_over_the_template_curve() {
+    let mut calibration = ConfidenceCalibration::new();
+    record(&mut calibration, "w2", "wages", 85.0, 1, 4);
+    record(&mut calibration, "w2", "name", 85.0, 10, 0);
+
+    // Wages is right one time in five, while the template as a whole is right 11 times in 15
+    assert_eq!(calibration.calibrate("w2", "wages", 88.0), 20.0);
+    assert_eq!(calibration.calibrate("w2", "employer", 88.0), 11.0 * 100.0 / 15.0);
+}
+
+#[test]
+fn confidences_without_enough_samples_are_left_alone() {
+    let mut calibration = ConfidenceCalibration::new();
+    record(&mut calibration, "w2", "wages", 85.0, 1, 4);
+
+    assert_eq!(calibration.calibrate("w2", "wages", 45.0), 45.0);
+    assert_eq!(calibration.calibrate("invoice", "wages", 88.0), 88.0);
+}
+
+#[test]
+fn calibration_is_saved_and_loaded() {
+    let mut calibration = ConfidenceCalibration::new();
+    record(&mut calibration, "w2", "wages", 62.0, 3, 2);
+    let path = std::env::temp_dir()
+        .join(format!("form_factor_calibration_{}", std::process::id()))
+        .join("calibration.json");
+
+    calibration.save_to(&path).expect("Saved");
+    let loaded = ConfidenceCalibration::load_from(&path);
+    let _ = std::fs::remove_dir_all(path.parent().expect("Has a directory"));
+
+    assert_eq!(loaded, calibration);
+    assert!(ConfidenceCalibration::load_from(&path).is_empty());
+}
+
+// ============================================================================
+// Batch review
+// ============================================================================
+
+#[test]
+fn review_decisions_teach_the_calibration() {
+    let mut review = BatchReview::new(80.0);
+    review.add_instance("a", None, instance("w2", vec![field("wages", "52,000", 60.0)]));
+    review.add_instance("b", None, instance("w2", vec![field("wages", "5z,000", 65.0)]));
+    review.add_instance("c", None, instance("w2", vec![field("wages", "48,250", 70.0)]));
+
+    assert!(review.apply(ReviewAction::Accept, 0.0));
+    assert!(review.apply(ReviewAction::Correct("52,000".to_string()), 1.0));
+    assert!(review.apply(ReviewAction::Skip, 2.0));
+
+    let curve = &review.calibration().template("w2").expect("Learned").fields["wages"];
+    assert_eq!(curve.samples(), 2);
+    assert_eq!(curve.band(60.0).correct, 1);
+}
+
+#[test]
+fn queue_uses_calibrated_confidence() {
+    // Values around 70% have always been right on this template, but rarely around 90%
+    let mut calibration = ConfidenceCalibration::new();
+    record(&mut calibration, "invoice", "total", 72.0, 10, 0);
+    record(&mut calibration, "invoice", "total", 91.0, 2, 8);
+
+    let mut review = BatchReview::new(80.0).with_calibration(calibration);
+    review.add_instance(
+        "inv-1",
+        None,
+        instance(
+            "invoice",
+            vec![field("total", "12.50", 74.0), field("total", "19.99", 93.0)],
+        ),
+    );
+    // Another template keeps raw confidences
+    review.add_instance("w2", None, instance("w2", vec![field("total", "1.00", 74.0)]));
+
+    let queued: Vec<(usize, usize, ReviewReason)> = review
+        .queue()
+        .iter()
+        .map(|item| (item.instance, item.field, item.reason))
+        .collect();
+    assert_eq!(
+        queued,
+        [
+            (0, 1, ReviewReason::LowConfidence(20.0)),
+            (1, 0, ReviewReason::LowConfidence(74.0)),
+        ]
+    );
+}
+
+#[test]
+fn template_name_is_kept_in_projects() {
+    let canvas = instance("w2", Vec::new());
+
+    let loaded = DrawingCanvas::from_json(&canvas.to_json().expect("Serializable")).expect("Loadable");
+
+    assert_eq!(loaded.template_name().as_deref(), Some("w2"));
+    assert_eq!(*DrawingCanvas::new().template_name(), None);
+}
@@ -0,0 +1,149 @@
+//! Integration tests for canvas commands and the reducer that applies them
+
+mod common;
+
+use common::{names, region};
+use form_factor::{CanvasCommand, CanvasPanel, DrawingCanvas, LayerType, MAX_PENDING_CHANGES, ToolMode};
+
+// ============================================================================
+// Reducer
+// ============================================================================
+
+#[test]
+fn commands_change_the_canvas_and_are_queued() {
+    let mut canvas = DrawingCanvas::new();
+
+    assert!(canvas.apply_command(CanvasCommand::SetTool(ToolMode::Rectangle)));
+    assert!(canvas.apply_command(CanvasCommand::SetLayerVisible {
+        layer: LayerType::Detections,
+        visible: false,
+    }));
+    assert!(canvas.apply_command(CanvasCommand::SelectLayer(Some(LayerType::Shapes))));
+
+    assert_eq!(*canvas.current_tool(), ToolMode::Rectangle);
+    assert!(!canvas.layer_manager().is_visible(LayerType::Detections));
+    assert_eq!(*canvas.selected_layer(), Some(LayerType::Shapes));
+    assert_eq!(canvas.take_changes().len(), 3);
+    assert!(canvas.take_changes().is_empty());
+}
+
+#[test]
+fn commands_that_change_nothing_are_not_queued() {
+    let mut canvas = DrawingCanvas::new();
+
+    assert!(!canvas.apply_command(CanvasCommand::SetTool(ToolMode::Select)));
+    assert!(!canvas.apply_command(CanvasCommand::SetLayerVisible {
+        layer: LayerType::Canvas,
+        visible: true,
+    }));
+    assert!(!canvas.apply_command(CanvasCommand::ClearLayer(LayerType::Grid)));
+
+    assert!(canvas.take_changes().is_empty());
+}
+
+#[test]
+fn zoom_is_queued_as_applied() {
+    let mut canvas = DrawingCanvas::new();
+
+    canvas.apply_command(CanvasCommand::SetZoom(500.0));
+
+    assert_eq!(*canvas.zoom_level(), 100.0);
+    assert_eq!(canvas.take_changes(), [CanvasCommand::SetZoom(100.0)]);
+}
+
+#[test]
+fn setters_go_through_the_reducer() {
+    let mut canvas = DrawingCanvas::new();
+
+    canvas.set_zoom(2.0);
+    canvas.set_pan_offset(10.0, -5.0);
+    canvas.set_redaction_mode(true);
+    canvas.apply_command(CanvasCommand::TogglePanel(CanvasPanel::Stats));
+
+    assert_eq!(
+        canvas.take_changes(),
+        [
+            CanvasCommand::SetZoom(2.0),
+            CanvasCommand::SetPan { x: 10.0, y: -5.0 },
+            CanvasCommand::SetRedactionMode(true),
+            CanvasCommand::TogglePanel(CanvasPanel::Stats),
+        ]
+    );
+    assert!(*canvas.show_stats());
+}
+
+#[test]
+fn shape_edits_go_through_the_reducer() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![
+        region("a", 0.0, 0.0, 50.0, 20.0),
+        region("b", 100.0, 0.0, 50.0, 20.0),
+        region("c", 200.0, 0.0, 50.0, 20.0),
+    ]);
+
+    assert!(canvas.apply_command(CanvasCommand::MoveObject {
+        layer: LayerType::Shapes,
+        index: 0,
+        dx: 5.0,
+        dy: 10.0,
+    }));
+    assert!(canvas.apply_command(CanvasCommand::BringToFront(0)));
+    assert!(canvas.apply_command(CanvasCommand::DuplicateShape(0)));
+    assert!(canvas.apply_command(CanvasCommand::DeleteObject {
+        layer: LayerType::Shapes,
+        index: 1,
+    }));
+
+    assert_eq!(names(&canvas), ["b", "a", "b copy"]);
+    assert_eq!(canvas.shapes()[1].bounding_rect().min, egui::Pos2::new(5.0, 10.0));
+    assert_eq!(canvas.take_changes().len(), 4);
+}
+
+#[test]
+fn shape_edits_that_change_nothing_are_not_queued() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![region("a", 0.0, 0.0, 50.0, 20.0), region("b", 100.0, 0.0, 50.0, 20.0)]);
+    canvas.apply_command(CanvasCommand::SetObjectLocked {
+        layer: LayerType::Shapes,
+        index: 0,
+        locked: true,
+    });
+    canvas.take_changes();
+
+    assert!(!canvas.apply_command(CanvasCommand::MoveObject {
+        layer: LayerType::Shapes,
+        index: 0,
+        dx: 5.0,
+        dy: 0.0,
+    }));
+    assert!(!canvas.apply_command(CanvasCommand::BringToFront(1)));
+    assert!(!canvas.apply_command(CanvasCommand::SendToBack(0)));
+    assert!(!canvas.apply_command(CanvasCommand::DeleteObject {
+        layer: LayerType::Detections,
+        index: 0,
+    }));
+    assert!(!canvas.apply_command(CanvasCommand::DuplicateShape(7)));
+
+    assert_eq!(names(&canvas), ["a", "b"]);
+    assert_eq!(canvas.shapes()[0].bounding_rect().min, egui::Pos2::ZERO);
+    assert!(canvas.take_changes().is_empty());
+}
+
+#[test]
+fn undrained_changes_keep_only_the_latest() {
+    let mut canvas = DrawingCanvas::new();
+
+    for step in 0..MAX_PENDING_CHANGES + 10 {
+        canvas.set_pan_offset(step as f32, 0.0);
+    }
+
+    let changes = canvas.take_changes();
+    assert_eq!(changes.len(), MAX_PENDING_CHANGES);
+    assert_eq!(
+        changes.last(),
+        Some(&CanvasCommand::SetPan {
+            x: (MAX_PENDING_CHANGES + 9) as f32,
+            y: 0.0
+        })
+    );
+}
@@ -0,0 +1,75 @@
+//! Integration tests for typed canvas I/O errors
+//!
+//! File failures must report what went wrong, which file was involved, and
+//! which operation failed, so callers can offer recovery options.
+
+mod common;
+
+use common::temp_path;
+use form_factor::{CanvasErrorKind, DrawingCanvas, IoOperation};
+use std::error::Error;
+use std::io::ErrorKind;
+
+// ============================================================================
+// Project files
+// ============================================================================
+
+#[test]
+fn missing_project_reports_not_found() {
+    let path = temp_path("missing.ffp").display().to_string();
+    let mut canvas = DrawingCanvas::new();
+    let ctx = egui::Context::default();
+
+    let err = canvas.load_from_file(&path, &ctx).expect_err("Missing file should not load");
+
+    assert_eq!(err.kind, CanvasErrorKind::Io(ErrorKind::NotFound));
+    assert_eq!(err.operation, Some(IoOperation::Read));
+    assert_eq!(err.path.as_deref(), Some(path.as_str()));
+    assert!(err.source().is_some());
+}
+
+#[test]
+fn saving_into_missing_directory_reports_write() {
+    let path = temp_path("no_such_dir").join("project.ffp").display().to_string();
+    let mut canvas = DrawingCanvas::new();
+
+    let err = canvas.save_to_file(&path).expect_err("Save into missing directory should fail");
+
+    assert_eq!(err.kind, CanvasErrorKind::Io(ErrorKind::NotFound));
+    assert_eq!(err.operation, Some(IoOperation::Write));
+    assert!(err.to_string().contains("project.ffp"));
+}
+
+#[test]
+fn corrupt_project_reports_path() {
+    let path = temp_path("corrupt.ffp").display().to_string();
+    std::fs::write(&path, "{ not json").expect("Temp file should be writable");
+    let mut canvas = DrawingCanvas::new();
+    let ctx = egui::Context::default();
+
+    let err = canvas.load_from_file(&path, &ctx).expect_err("Corrupt file should not load");
+    let _ = std::fs::remove_file(&path);
+
+    assert!(matches!(err.kind, CanvasErrorKind::Deserialization(_)));
+    assert_eq!(err.operation, Some(IoOperation::Read));
+    assert!(err.path.is_some());
+}
+
+// ============================================================================
+// Form images
+// ============================================================================
+
+#[test]
+fn missing_form_image_reports_not_found() {
+    let path = temp_path("missing.png").display().to_string();
+    let mut canvas = DrawingCanvas::new();
+    let ctx = egui::Context::default();
+
+    let err = canvas
+        .load_form_image(&path, &ctx)
+        .expect_err("Missing image should not load");
+
+    assert_eq!(err.kind, CanvasErrorKind::Io(ErrorKind::NotFound));
+    assert_eq!(err.operation, Some(IoOperation::Read));
+    assert!(err.source().is_some());
+}
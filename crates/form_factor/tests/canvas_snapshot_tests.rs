@@ -0,0 +1,55 @@
+//! Integration tests for read-only canvas snapshots handed to plugins
+
+mod common;
+
+use common::region;
+use form_factor::{CanvasSnapshot, DrawingCanvas, LayerType, Shape, ToolMode};
+
+// ============================================================================
+// Contents
+// ============================================================================
+
+#[test]
+fn snapshot_copies_shapes_layers_and_view() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![region("name", 0.0, 0.0, 10.0, 10.0), region("date", 0.0, 0.0, 10.0, 10.0)]);
+    canvas.set_tool(ToolMode::Circle);
+    canvas.set_zoom(2.0);
+    canvas.layer_manager_mut().set_visible(LayerType::Detections, false);
+    canvas.set_selected_layer(Some(LayerType::Shapes));
+
+    let snapshot = canvas.snapshot();
+
+    let names: Vec<&str> = snapshot.shapes().iter().map(Shape::name).collect();
+    assert_eq!(names, ["name", "date"]);
+    assert!(snapshot.detections().is_empty());
+    assert!(!snapshot.layers().is_visible(LayerType::Detections));
+    assert_eq!(snapshot.selected_layer(), Some(LayerType::Shapes));
+    assert_eq!(snapshot.tool(), ToolMode::Circle);
+    assert_eq!(snapshot.zoom(), 2.0);
+    assert_eq!(snapshot.selected(), None);
+}
+
+#[test]
+fn snapshot_does_not_follow_later_edits() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![region("name", 0.0, 0.0, 10.0, 10.0)]);
+    canvas.set_zoom(1.0);
+    let snapshot = canvas.snapshot();
+
+    canvas.set_shapes(Vec::new());
+    canvas.set_zoom(4.0);
+
+    assert_eq!(snapshot.shapes().len(), 1);
+    assert_eq!(snapshot.zoom(), 1.0);
+}
+
+#[test]
+fn clones_share_one_copy() {
+    let canvas = DrawingCanvas::new();
+    let snapshot = canvas.snapshot();
+
+    assert!(snapshot.clone().ptr_eq(&snapshot));
+    assert!(!canvas.snapshot().ptr_eq(&snapshot));
+    assert!(CanvasSnapshot::default().shapes().is_empty());
+}
@@ -0,0 +1,269 @@
+//! Integration tests for collaborative editing
+
+mod common;
+
+use common::{region, shape_names, temp_path};
+use egui::{Color32, Pos2};
+use form_factor::{
+    CollabErrorKind, CollabSession, DrawingCanvas, FileStore, InMemorySyncServer, OperationLog, Presence, ProjectStore,
+    Settings, Shape, ShapeEdit, StorageSettings, StoreSyncTransport, SyncTransport,
+};
+use std::sync::Arc;
+
+// ============================================================================
+// Operation Log Tests
+// ============================================================================
+
+#[test]
+fn merge_is_idempotent() {
+    let mut alice = OperationLog::new("alice");
+    let op = alice.record(ShapeEdit::Insert(region("a", 0.0, 0.0, 10.0, 10.0)));
+
+    let mut bob = OperationLog::new("bob");
+    assert_eq!(bob.merge(vec![op.clone()]), 1);
+    assert_eq!(bob.merge(vec![op]), 0);
+    assert_eq!(bob.materialize().len(), 1);
+}
+
+#[test]
+fn replicas_converge_regardless_of_delivery_order() {
+    let mut alice = OperationLog::new("alice");
+    let mut bob = OperationLog::new("bob");
+
+    let a1 = alice.record(ShapeEdit::Insert(region("alice-1", 0.0, 0.0, 10.0, 10.0)));
+    let b1 = bob.record(ShapeEdit::Insert(region("bob-1", 20.0, 0.0, 10.0, 10.0)));
+    let a2 = alice.record(ShapeEdit::Replace {
+        target: a1.id.clone(),
+        shape: region("alice-1-renamed", 0.0, 0.0, 10.0, 10.0),
+    });
+
+    let mut left = OperationLog::new("left");
+    left.merge(vec![a1.clone(), b1.clone(), a2.clone()]);
+    let mut right = OperationLog::new("right");
+    right.merge(vec![a2, b1, a1]);
+
+    assert_eq!(left.materialize(), right.materialize());
+    let shapes: Vec<Shape> = left.materialize().into_iter().map(|(_, s)| s).collect();
+    assert_eq!(shape_names(&shapes), vec!["alice-1-renamed", "bob-1"]);
+}
+
+#[test]
+fn remove_deletes_shape() {
+    let mut log = OperationLog::new("alice");
+    let insert = log.record(ShapeEdit::Insert(region("a", 0.0, 0.0, 10.0, 10.0)));
+    log.record(ShapeEdit::Remove { target: insert.id });
+
+    assert!(log.materialize().is_empty());
+}
+
+// ============================================================================
+// Session Sync Tests
+// ============================================================================
+
+#[test]
+fn sessions_sync_through_server() {
+    let server = InMemorySyncServer::new();
+    let mut alice = CollabSession::new("alice", server.connect("alice"));
+    let mut bob = CollabSession::new("bob", server.connect("bob"));
+
+    alice.edit(ShapeEdit::Insert(region("from-alice", 0.0, 0.0, 10.0, 10.0)));
+    bob.edit(ShapeEdit::Insert(region("from-bob", 20.0, 0.0, 10.0, 10.0)));
+
+    alice.sync().expect("Sync should succeed");
+    bob.sync().expect("Sync should succeed");
+    alice.sync().expect("Sync should succeed");
+
+    assert_eq!(alice.shapes(), bob.shapes());
+    assert_eq!(alice.shapes().len(), 2);
+}
+
+#[test]
+fn presence_excludes_self() {
+    let server = InMemorySyncServer::new();
+    let mut alice = CollabSession::new("alice", server.connect("alice"));
+    let mut bob = CollabSession::new("bob", server.connect("bob"));
+
+    let presence = |actor: &str| Presence {
+        actor: actor.to_string(),
+        display_name: actor.to_uppercase(),
+        cursor: Some(Pos2::new(1.0, 2.0)),
+        color: Color32::RED,
+    };
+
+    alice.exchange_presence(presence("alice")).expect("Presence should succeed");
+    let peers = bob.exchange_presence(presence("bob")).expect("Presence should succeed");
+
+    assert_eq!(peers.len(), 1);
+    assert_eq!(peers[0].actor, "alice");
+}
+
+// ============================================================================
+// Tracking Tests
+// ============================================================================
+
+#[test]
+fn tracking_turns_shape_changes_into_edits() {
+    let server = InMemorySyncServer::new();
+    let base = vec![region("a", 0.0, 0.0, 10.0, 10.0), region("b", 20.0, 0.0, 10.0, 10.0), region("c", 40.0, 0.0, 10.0, 10.0)];
+    let mut alice = CollabSession::with_shapes("alice", server.connect("alice"), base.clone());
+    let mut bob = CollabSession::with_shapes("bob", server.connect("bob"), base.clone());
+    assert_eq!(alice.track(&base), 0);
+
+    // Rename b, drop c, add d
+    let edited = vec![region("a", 0.0, 0.0, 10.0, 10.0), region("b-renamed", 20.0, 0.0, 10.0, 10.0), region("d", 60.0, 0.0, 10.0, 10.0)];
+    assert_eq!(alice.track(&edited), 2);
+    assert_eq!(alice.shapes(), edited);
+    let shorter = vec![region("a", 0.0, 0.0, 10.0, 10.0), region("d", 60.0, 0.0, 10.0, 10.0)];
+    assert_eq!(alice.track(&shorter), 1);
+    assert_eq!(alice.shapes(), shorter);
+
+    alice.sync().expect("Sync should succeed");
+    bob.sync().expect("Sync should succeed");
+    assert_eq!(bob.shapes(), shorter);
+}
+
+// ============================================================================
+// Project Store Transport Tests
+// ============================================================================
+
+#[test]
+fn sessions_sync_through_the_project_store() {
+    let root = temp_path("collab_store");
+    let _ = std::fs::remove_dir_all(&root);
+    let store: Arc<dyn ProjectStore + Send + Sync> = Arc::new(FileStore::new(&root));
+    let transport = |actor: &str| StoreSyncTransport::new(store.clone(), "w2", actor).expect("Valid names");
+    let base = vec![region("a", 0.0, 0.0, 10.0, 10.0)];
+    let mut alice = CollabSession::with_shapes("alice", transport("alice"), base.clone());
+    let mut bob = CollabSession::with_shapes("bob", transport("bob"), base);
+
+    alice.track(&[region("a", 0.0, 0.0, 10.0, 10.0), region("from-alice", 20.0, 0.0, 10.0, 10.0)]);
+    bob.track(&[region("a-renamed", 0.0, 0.0, 10.0, 10.0)]);
+    alice.sync().expect("Sync should succeed");
+    assert_eq!(bob.sync().expect("Sync should succeed"), 1);
+    assert_eq!(alice.sync().expect("Sync should succeed"), 1);
+    assert_eq!(alice.shapes(), bob.shapes());
+    assert_eq!(shape_names(&alice.shapes()), vec!["a-renamed", "from-alice"]);
+    assert_eq!(bob.sync().expect("Sync should succeed"), 0);
+
+    let presence = |actor: &str| Presence {
+        actor: actor.to_string(),
+        display_name: actor.to_uppercase(),
+        cursor: Some(Pos2::new(1.0, 2.0)),
+        color: Color32::RED,
+    };
+    alice.exchange_presence(presence("alice")).expect("Presence should succeed");
+    let peers = bob.exchange_presence(presence("bob")).expect("Presence should succeed");
+    assert_eq!(peers, vec![presence("alice")]);
+    alice.transport().leave().expect("Left the session");
+    assert!(bob.exchange_presence(presence("bob")).expect("Presence should succeed").is_empty());
+
+    let Err(invalid) = StoreSyncTransport::new(store.clone(), "w2", "a/b") else {
+        panic!("Joined with an actor that is not a single key segment");
+    };
+    assert!(matches!(invalid.kind, CollabErrorKind::InvalidName(_)));
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn each_push_writes_a_new_batch_and_pulls_fetch_only_new_ones() {
+    let root = temp_path("collab_batches");
+    let _ = std::fs::remove_dir_all(&root);
+    let store: Arc<dyn ProjectStore + Send + Sync> = Arc::new(FileStore::new(&root));
+    let transport = |actor: &str| StoreSyncTransport::new(store.clone(), "w2", actor).expect("Valid names");
+    let mut log = OperationLog::new("alice");
+    let mut alice = transport("alice");
+    let mut bob = transport("bob");
+
+    alice.push(&[log.record(ShapeEdit::Insert(region("a", 0.0, 0.0, 10.0, 10.0)))]).expect("Pushed");
+    alice.push(&[]).expect("Pushed");
+    assert_eq!(bob.pull().expect("Pulled").len(), 1);
+    alice.push(&[log.record(ShapeEdit::Insert(region("b", 20.0, 0.0, 10.0, 10.0)))]).expect("Pushed");
+    // Rejoining continues after the batches already pushed
+    let mut rejoined = transport("alice");
+    rejoined.push(&[log.record(ShapeEdit::Insert(region("c", 40.0, 0.0, 10.0, 10.0)))]).expect("Pushed");
+
+    let batches = store.list("collab/w2/operations/alice/").expect("Listed");
+    let pulled = bob.pull().expect("Pulled");
+    let _ = std::fs::remove_dir_all(&root);
+    assert_eq!(batches.len(), 3, "{:?}", batches);
+    assert_eq!(pulled.len(), 2);
+    assert_eq!(pulled.iter().map(|op| op.id.counter).collect::<Vec<_>>(), [2, 3]);
+}
+
+#[test]
+fn canvases_collaborate_through_the_configured_store() {
+    let root = temp_path("collab_canvas");
+    let _ = std::fs::remove_dir_all(&root);
+    let settings = Settings {
+        storage: StorageSettings::Filesystem { root: root.clone() },
+        ..Settings::default()
+    };
+    let canvas = |author: &str| {
+        let mut canvas = DrawingCanvas::new();
+        canvas.apply_settings(&settings);
+        canvas.set_comment_author(author);
+        canvas.set_shapes(vec![region("a", 0.0, 0.0, 10.0, 10.0)]);
+        canvas.start_collaboration("w2").expect("Joined the session");
+        canvas
+    };
+    let mut alice = canvas("Alice");
+    let mut bob = canvas("Bob");
+    assert_eq!(alice.collab_session(), Some("w2"));
+
+    alice.set_shapes(vec![region("a", 0.0, 0.0, 10.0, 10.0), region("from-alice", 20.0, 0.0, 10.0, 10.0)]);
+    assert_eq!(alice.sync_collaboration(10.0).map(|r| r.expect("Synced")), Some(0));
+    assert!(alice.sync_collaboration(10.5).is_none(), "Synced again before the interval");
+    assert_eq!(bob.sync_collaboration(10.0).map(|r| r.expect("Synced")), Some(1));
+    assert_eq!(shape_names(bob.shapes()), vec!["a", "from-alice"]);
+    assert_eq!(bob.peers().len(), 1);
+    assert_eq!(bob.peers()[0].display_name, "Alice");
+
+    let ctx = egui::Context::default();
+    let other = DrawingCanvas::new().to_json().expect("Serializable");
+    bob.load_project_bytes("other.ffp", other.as_bytes(), &ctx)
+        .expect("Opened another project");
+    assert_eq!(bob.collab_session(), None);
+    assert!(bob.peers().is_empty());
+    alice.sync_collaboration(20.0).expect("Sync was due").expect("Synced");
+    assert!(alice.peers().is_empty());
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn protected_projects_are_not_shared_in_plaintext() {
+    let root = temp_path("collab_protected");
+    let _ = std::fs::remove_dir_all(&root);
+    let settings = Settings {
+        storage: StorageSettings::Filesystem { root: root.clone() },
+        ..Settings::default()
+    };
+    let mut canvas = DrawingCanvas::new();
+    canvas.apply_settings(&settings);
+    canvas.set_comment_author("Alice");
+    canvas.set_shapes(vec![region("secret-salary", 0.0, 0.0, 10.0, 10.0)]);
+    canvas.set_project_password(Some("hunter2".to_string()));
+
+    let refused = canvas.start_collaboration("w2").expect_err("Protected projects aren't shared");
+    assert!(matches!(refused.kind, CollabErrorKind::ProtectedProject));
+    assert_eq!(canvas.collab_session(), None);
+
+    fn contents(dir: &std::path::Path, found: &mut String) {
+        for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                contents(&path, found);
+            } else {
+                found.push_str(&String::from_utf8_lossy(&std::fs::read(&path).expect("Readable")));
+            }
+        }
+    }
+    let mut stored = String::new();
+    contents(&root, &mut stored);
+    assert!(!stored.contains("secret-salary"), "Plaintext shapes reached the store");
+
+    canvas.set_project_password(None);
+    canvas.start_collaboration("w2").expect("Joined the session");
+    canvas.set_project_password(Some("hunter2".to_string()));
+    assert_eq!(canvas.collab_session(), None, "Protecting the project left the session");
+    let _ = std::fs::remove_dir_all(&root);
+}
@@ -0,0 +1,116 @@
+//! Integration tests for review comment threads
+
+use egui::{Color32, Pos2, Stroke};
+use form_factor::{CommentTarget, DrawingCanvas, Rectangle, Shape};
+
+fn canvas_with_shapes(count: usize) -> DrawingCanvas {
+    let shapes = (0..count)
+        .map(|i| {
+            let x = i as f32 * 20.0;
+            Shape::Rectangle(
+                Rectangle::from_corners(
+                    Pos2::new(x, 0.0),
+                    Pos2::new(x + 10.0, 10.0),
+                    Stroke::new(1.0, Color32::BLACK),
+                    Color32::TRANSPARENT,
+                )
+                .expect("Valid rectangle"),
+            )
+        })
+        .collect();
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(shapes);
+    canvas
+}
+
+// ============================================================================
+// Thread Lifecycle Tests
+// ============================================================================
+
+#[test]
+fn reply_reopens_resolved_thread() {
+    let mut canvas = canvas_with_shapes(1);
+    let thread = canvas.add_comment_thread(CommentTarget::Shape(0), "Is this the right field?");
+
+    assert!(canvas.set_thread_resolved(thread, true));
+    assert_eq!(canvas.unresolved_thread_count(), 0);
+
+    assert!(canvas.reply_to_thread(thread, "Actually, no"));
+    assert_eq!(canvas.unresolved_thread_count(), 1);
+    assert_eq!(canvas.comment_threads()[thread].comments.len(), 2);
+}
+
+#[test]
+fn missing_thread_operations_report_failure() {
+    let mut canvas = DrawingCanvas::new();
+    assert!(!canvas.reply_to_thread(3, "hello"));
+    assert!(!canvas.set_thread_resolved(3, true));
+}
+
+#[test]
+fn threads_for_filters_by_target() {
+    let mut canvas = canvas_with_shapes(2);
+    canvas.add_comment_thread(CommentTarget::Shape(0), "first");
+    canvas.add_comment_thread(CommentTarget::Shape(1), "second");
+    canvas.add_comment_thread(CommentTarget::Shape(0), "third");
+
+    let indices: Vec<usize> = canvas.threads_for(CommentTarget::Shape(0)).map(|(i, _)| i).collect();
+    assert_eq!(indices, vec![0, 2]);
+}
+
+#[test]
+fn comment_author_is_recorded() {
+    let mut canvas = canvas_with_shapes(1);
+    canvas.set_comment_author("alice");
+    let thread = canvas.add_comment_thread(CommentTarget::Shape(0), "looks good");
+
+    assert_eq!(canvas.comment_threads()[thread].comments[0].author, "alice");
+}
+
+// ============================================================================
+// Persistence and Pruning Tests
+// ============================================================================
+
+#[test]
+fn threads_survive_serialization() {
+    let mut canvas = canvas_with_shapes(1);
+    let thread = canvas.add_comment_thread(CommentTarget::Shape(0), "check spelling");
+    canvas.set_thread_resolved(thread, true);
+
+    let json = serde_json::to_string(&canvas).expect("Serialization should succeed");
+    let restored: DrawingCanvas = serde_json::from_str(&json).expect("Deserialization should succeed");
+
+    assert_eq!(restored.comment_threads(), canvas.comment_threads());
+}
+
+#[test]
+fn undo_drops_threads_on_removed_shape() {
+    let mut canvas = canvas_with_shapes(2);
+    canvas.add_comment_thread(CommentTarget::Shape(0), "keep");
+    canvas.add_comment_thread(CommentTarget::Shape(1), "drop");
+
+    canvas.undo();
+
+    assert_eq!(canvas.comment_threads().len(), 1);
+    assert_eq!(canvas.comment_threads()[0].target, CommentTarget::Shape(0));
+}
+
+#[test]
+fn comments_window_opens_on_the_chosen_object() {
+    let mut canvas = canvas_with_shapes(3);
+
+    canvas.comment_on(CommentTarget::Shape(2));
+    assert!(*canvas.show_comments());
+    assert_eq!(*canvas.comment_target(), Some(CommentTarget::Shape(2)));
+
+    // Deleting a shape below keeps the window on the same field
+    canvas.delete_shape(1);
+    assert_eq!(*canvas.comment_target(), Some(CommentTarget::Shape(1)));
+    canvas.delete_shape(1);
+    assert_eq!(*canvas.comment_target(), None);
+
+    canvas.comment_on(CommentTarget::Detection(0));
+    canvas.toggle_comments_panel();
+    assert!(!*canvas.show_comments());
+    assert_eq!(*canvas.comment_target(), None);
+}
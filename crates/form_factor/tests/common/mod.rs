@@ -0,0 +1,145 @@
+//! Helpers shared by the integration tests
+//!
+//! Each test file is its own crate and uses only some of these.
+
+#![allow(dead_code)]
+
+use egui::{Color32, Pos2, Stroke, vec2};
+use form_factor::{CanvasSimulator, DrawingCanvas, FieldRule, Rectangle, Shape, journal_path};
+use std::path::{Path, PathBuf};
+
+/// Path in the temp directory, unique to this test run
+pub fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("form_factor_{}_{}", std::process::id(), name))
+}
+
+/// Project file at `path`, opened in a new canvas
+pub fn reopen(path: &Path) -> DrawingCanvas {
+    let mut canvas = DrawingCanvas::new();
+    canvas
+        .load_from_file(path.to_str().expect("UTF-8 path"), &egui::Context::default())
+        .expect("Reopened");
+    canvas
+}
+
+/// Delete a project file and its journal
+pub fn remove(path: &Path) {
+    let _ = std::fs::remove_file(path);
+    let _ = std::fs::remove_file(journal_path(path));
+}
+
+/// Small named field with its top left corner at `min`
+pub fn field(name: &str, min: Pos2) -> Shape {
+    let mut rect = Rectangle::from_corners(
+        min,
+        min + vec2(40.0, 10.0),
+        Stroke::new(2.0, Color32::BLACK),
+        Color32::TRANSPARENT,
+    )
+    .expect("Valid rectangle");
+    rect.name = name.to_string();
+    Shape::Rectangle(rect)
+}
+
+/// Named 100 x 20 field with its top left corner at (`x`, `y`)
+pub fn field_at(name: &str, x: f32, y: f32) -> Shape {
+    region(name, x, y, 100.0, 20.0)
+}
+
+/// Named `width` x `height` rectangle with its top left corner at (`x`, `y`), e.g. a detection
+pub fn region(name: &str, x: f32, y: f32, width: f32, height: f32) -> Shape {
+    let rect = Rectangle::from_corners(
+        Pos2::new(x, y),
+        Pos2::new(x + width, y + height),
+        Stroke::new(1.0, Color32::BLACK),
+        Color32::TRANSPARENT,
+    )
+    .expect("Valid rectangle");
+    let mut shape = Shape::Rectangle(rect);
+    shape.set_name(name);
+    shape
+}
+
+/// Field holding a value extracted by OCR
+pub fn extracted_field(name: &str, rule: FieldRule, value: &str, confidence: Option<f32>) -> Shape {
+    let mut shape = field_at(name, 10.0, 10.0);
+    shape.set_rule(rule);
+    shape.set_extracted_value(value, confidence);
+    shape
+}
+
+/// Names of `shapes`, in order
+pub fn shape_names(shapes: &[Shape]) -> Vec<&str> {
+    shapes.iter().map(Shape::name).collect()
+}
+
+/// Names of the canvas's shapes, in order
+pub fn names(canvas: &DrawingCanvas) -> Vec<&str> {
+    shape_names(canvas.shapes())
+}
+
+/// New project holding `detections`
+pub fn with_detections(detections: Vec<Shape>) -> DrawingCanvas {
+    let mut project = serde_json::to_value(DrawingCanvas::new()).expect("Serializable");
+    project["detections"] = serde_json::to_value(detections).expect("Serializable");
+    DrawingCanvas::from_json(&project.to_string()).expect("Valid project")
+}
+
+/// New project holding `detections`, the first of them read by OCR as `texts`
+pub fn read_canvas(detections: Vec<Shape>, texts: &[&str]) -> DrawingCanvas {
+    let mut canvas = with_detections(detections);
+    for (idx, text) in texts.iter().enumerate() {
+        canvas.record_ocr_text(idx, text);
+    }
+    canvas
+}
+
+/// Blank white form image encoded as PNG
+pub fn form_png(width: u32, height: u32) -> Vec<u8> {
+    let image = image::RgbaImage::from_pixel(width, height, image::Rgba([255, 255, 255, 255]));
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut bytes, image::ImageFormat::Png).expect("Encodable");
+    bytes.into_inner()
+}
+
+/// Simulator over an empty canvas at 100% zoom
+pub fn simulator() -> CanvasSimulator {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_zoom(1.0);
+    CanvasSimulator::new(canvas)
+}
+
+/// Simulator over `canvas` with a blank `width` x `height` form image, after one frame
+pub fn form_simulator(canvas: DrawingCanvas, width: u32, height: u32) -> CanvasSimulator {
+    let mut sim = CanvasSimulator::new(canvas);
+    let ctx = sim.ctx().clone();
+    sim.canvas_mut()
+        .load_form_image_bytes("form.png", &form_png(width, height), &ctx)
+        .expect("Loadable");
+    sim.run_frames(1);
+    sim
+}
+
+/// Simulator over an empty canvas with a blank `width` x `height` form image saved at `path`, after one frame
+///
+/// For exports, which read the form image back from its file.
+pub fn saved_form_simulator(path: &Path, width: u32, height: u32) -> CanvasSimulator {
+    std::fs::write(path, form_png(width, height)).expect("Temp image");
+    let mut sim = CanvasSimulator::new(DrawingCanvas::new());
+    let ctx = sim.ctx().clone();
+    sim.canvas_mut()
+        .load_form_image(path.to_str().expect("UTF-8 path"), &ctx)
+        .expect("Loadable");
+    sim.run_frames(1);
+    sim
+}
+
+/// Run one frame of a host app that embeds the widget in its central panel
+#[cfg(feature = "plugins")]
+pub fn widget_frame(ctx: &egui::Context, widget: &mut form_factor::FormFactorWidget) {
+    let _ = ctx.run(egui::RawInput::default(), |ctx| {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            widget.show(ui);
+        });
+    });
+}
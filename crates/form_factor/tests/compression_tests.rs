@@ -0,0 +1,128 @@
+//! Integration tests for compressed project files and caches
+
+mod common;
+
+use common::{remove, reopen, temp_path};
+use egui::{Color32, Pos2, Stroke};
+use form_factor::{
+    DrawingCanvas, JournalSave, PolygonShape, ProjectEncoding, Settings, Shape, ZSTD_MAGIC, compress, is_compressed,
+};
+
+/// Polygon tracing a circle with `points` vertices
+fn outline(points: usize) -> Shape {
+    let outline = (0..points)
+        .map(|idx| {
+            let angle = idx as f32 / points as f32 * std::f32::consts::TAU;
+            Pos2::new(500.0 + 300.0 * angle.cos(), 400.0 + 200.0 * angle.sin())
+        })
+        .collect();
+    let polygon = PolygonShape::from_points(outline, Stroke::new(2.0, Color32::RED), Color32::TRANSPARENT)
+        .expect("Valid polygon");
+    Shape::Polygon(polygon)
+}
+
+fn settings(encoding: ProjectEncoding, compressed: bool) -> Settings {
+    let mut settings = Settings::default();
+    settings.canvas.project_encoding = encoding;
+    settings.canvas.compression.enabled = compressed;
+    settings
+}
+
+fn canvas_with(settings: &Settings, shapes: Vec<Shape>) -> DrawingCanvas {
+    let mut canvas = DrawingCanvas::new();
+    canvas.apply_settings(settings);
+    canvas.set_shapes(shapes);
+    canvas
+}
+
+#[test]
+fn compressed_projects_are_smaller_and_reopen_in_either_encoding() {
+    for encoding in [ProjectEncoding::Json, ProjectEncoding::MessagePack] {
+        let plain_path = temp_path(&format!("compress_plain_{:?}.ffp", encoding));
+        let packed_path = temp_path(&format!("compress_packed_{:?}.ffp", encoding));
+        for (path, compressed) in [(&plain_path, false), (&packed_path, true)] {
+            canvas_with(&settings(encoding, compressed), vec![outline(2_000)])
+                .save_to_file(path.to_str().expect("UTF-8 path"))
+                .expect("Saved");
+        }
+
+        let plain = std::fs::read(&plain_path).expect("Saved file");
+        let packed = std::fs::read(&packed_path).expect("Saved file");
+        let reopened = reopen(&packed_path);
+        remove(&plain_path);
+        remove(&packed_path);
+
+        assert!(packed.starts_with(&ZSTD_MAGIC), "{:?}", encoding);
+        assert!(!is_compressed(&plain));
+        assert!(packed.len() < plain.len(), "{:?}: {} vs {} bytes", encoding, packed.len(), plain.len());
+        assert_eq!(reopened.shapes(), &vec![outline(2_000)]);
+    }
+}
+
+#[test]
+fn compaction_writes_compressed_snapshots_the_journal_replays_over() {
+    let path = temp_path("compress_journal.ffp");
+    let mut canvas = canvas_with(&settings(ProjectEncoding::Json, true), vec![outline(50)]);
+    canvas.save_to_file(path.to_str().expect("UTF-8 path")).expect("Saved");
+
+    canvas.set_shapes(vec![outline(50), outline(60)]);
+    let delta = canvas.save_incremental().expect("Saved");
+    canvas.set_shapes(vec![outline(70)]);
+    let snapshot = canvas.compact_journal().expect("Compacted");
+    canvas.set_shapes(vec![outline(70), outline(80)]);
+    canvas.save_incremental().expect("Saved");
+
+    let written = std::fs::read(&path).expect("Snapshot");
+    let reopened = reopen(&path);
+    remove(&path);
+
+    assert!(matches!(delta, JournalSave::Delta { .. }));
+    assert!(matches!(snapshot, JournalSave::Snapshot { .. }));
+    assert!(is_compressed(&written));
+    assert_eq!(reopened.shapes(), canvas.shapes());
+}
+
+#[test]
+fn cached_detections_are_compressed_when_enabled() {
+    let dir = temp_path("compress_cache_dir");
+    let mut project = serde_json::to_value(DrawingCanvas::new()).expect("Serializable");
+    project["detections"] = serde_json::to_value(vec![outline(500)]).expect("Serializable");
+    let mut detected = DrawingCanvas::from_json(&project.to_string()).expect("Valid project");
+    detected.apply_settings(&settings(ProjectEncoding::Json, true));
+
+    let path = detected.cache_detections(&dir, "key", "text", 0).expect("Cached");
+    let written = std::fs::read(&path).expect("Cache entry");
+    let mut reopened = DrawingCanvas::new();
+    let loaded = reopened.load_cached_detections(&dir, "key");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert!(is_compressed(&written));
+    assert_eq!(loaded, Some(1));
+    assert_eq!(reopened.detections(), &vec![outline(500)]);
+}
+
+#[test]
+fn corrupt_compressed_projects_are_errors() {
+    let json = DrawingCanvas::new().to_json().expect("Serializable");
+    let mut packed = compress(json.as_bytes(), 3).expect("Compressed");
+    let middle = packed.len() / 2;
+    packed[middle] ^= 0xff;
+    let truncated = compress(json.as_bytes(), 3).expect("Compressed");
+
+    assert!(DrawingCanvas::from_bytes(&packed).is_err());
+    assert!(DrawingCanvas::from_bytes(&truncated[..truncated.len() / 2]).is_err());
+    assert!(DrawingCanvas::from_bytes(&ZSTD_MAGIC).is_err());
+}
+
+#[test]
+fn compression_is_off_by_default() {
+    let path = temp_path("compress_settings.json");
+    std::fs::write(&path, r#"{"canvas": {"compression": {"enabled": true}}}"#).expect("Temp settings");
+
+    let settings = Settings::load_from(&path);
+    let _ = std::fs::remove_file(&path);
+
+    assert!(!Settings::default().canvas.compression.enabled);
+    assert!(settings.canvas.compression.enabled);
+    assert_eq!(settings.canvas.compression.level, 3, "missing level takes the default");
+}
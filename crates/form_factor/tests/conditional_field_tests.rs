@@ -0,0 +1,167 @@
+//! Integration tests for fields that apply only when another field has a value
+
+mod common;
+
+use common::field_at;
+use form_factor::{
+    BatchReview, DrawingCanvas, FieldCondition, FieldFormat, FieldIssue, FieldRule, ProjectTemplate, Shape,
+    field_applies, is_checked,
+};
+
+fn field(name: &str, format: FieldFormat, condition: Option<FieldCondition>) -> Shape {
+    let mut shape = field_at(name, 10.0, 10.0);
+    // A blank checkbox is an answer, so only the other fields are required
+    let rule = match format {
+        FieldFormat::Checkbox => FieldRule::new(format),
+        _ => FieldRule::new(format).required(),
+    };
+    shape.set_rule(rule);
+    shape.set_condition(condition);
+    shape
+}
+
+/// Tax form where the spouse fields apply only to married filers
+fn return_fields() -> Vec<Shape> {
+    vec![
+        field("name", FieldFormat::Text, None),
+        field("married", FieldFormat::Checkbox, None),
+        field(
+            "spouse_name",
+            FieldFormat::Text,
+            Some(FieldCondition::Checked("married".to_string())),
+        ),
+        field(
+            "spouse_ssn",
+            FieldFormat::Text,
+            Some(FieldCondition::Filled("spouse_name".to_string())),
+        ),
+        field("wages", FieldFormat::Number, None),
+    ]
+}
+
+// ============================================================================
+// Conditions
+// ============================================================================
+
+#[test]
+fn checkbox_values_read_as_checked_or_unchecked() {
+    let rule = FieldRule::new(FieldFormat::Checkbox);
+    for checked in ["x", "X", " yes ", "✓", "1"] {
+        assert!(is_checked(checked), "{checked}");
+        assert_eq!(rule.check(checked), Ok(()));
+    }
+    for unchecked in ["", "no", "Off", "0"] {
+        assert!(!is_checked(unchecked), "{unchecked}");
+        assert_eq!(rule.check(unchecked), Ok(()));
+    }
+    assert_eq!(rule.check("maybe"), Err(FieldIssue::NotACheckbox));
+}
+
+#[test]
+fn conditional_fields_follow_their_controlling_field() {
+    let mut fields = return_fields();
+    assert!(field_applies(&fields, 0));
+    assert!(!field_applies(&fields, 2));
+    assert!(!field_applies(&fields, 3));
+
+    fields[1].set_value("x");
+    assert!(field_applies(&fields, 2));
+    assert!(!field_applies(&fields, 3));
+
+    fields[2].set_value("Sam Doe");
+    assert!(field_applies(&fields, 3));
+
+    // Unchecking the box drops the whole chain, whatever was entered below it
+    fields[1].set_value("");
+    assert!(!field_applies(&fields, 2));
+    assert!(!field_applies(&fields, 3));
+}
+
+#[test]
+fn equals_conditions_ignore_case_and_whitespace() {
+    let condition = FieldCondition::Equals {
+        field: "status".to_string(),
+        value: "Head of household".to_string(),
+    };
+
+    assert!(condition.is_met_by(" head of HOUSEHOLD "));
+    assert!(!condition.is_met_by("single"));
+    assert_eq!(condition.field(), "status");
+}
+
+#[test]
+fn conditions_on_missing_or_looping_fields_never_apply() {
+    let fields = vec![
+        field("a", FieldFormat::Text, Some(FieldCondition::Filled("b".to_string()))),
+        field("b", FieldFormat::Text, Some(FieldCondition::Filled("a".to_string()))),
+        field("c", FieldFormat::Text, Some(FieldCondition::Filled("gone".to_string()))),
+    ];
+
+    assert!(!field_applies(&fields, 0));
+    assert!(!field_applies(&fields, 1));
+    assert!(!field_applies(&fields, 2));
+    assert!(!field_applies(&fields, 3));
+}
+
+// ============================================================================
+// Data entry and review
+// ============================================================================
+
+#[test]
+fn data_entry_passes_over_fields_that_do_not_apply() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(return_fields());
+    canvas.open_data_entry_panel();
+
+    canvas.set_data_entry_value("Jane Doe");
+    assert!(canvas.advance_field());
+    assert!(canvas.advance_field());
+    assert_eq!(*canvas.data_entry_field(), Some(4));
+    canvas.set_data_entry_value("52,000");
+
+    // Empty spouse fields don't count as problems for a single filer
+    assert!(!canvas.advance_field());
+    assert_eq!(
+        canvas.data_entry_status().as_deref(),
+        Some("Last field reached; all values are valid")
+    );
+
+    assert!(canvas.previous_field());
+    assert_eq!(*canvas.data_entry_field(), Some(1));
+    canvas.set_data_entry_value("x");
+    assert!(canvas.advance_field());
+    assert_eq!(*canvas.data_entry_field(), Some(2));
+}
+
+#[test]
+fn review_does_not_flag_fields_that_do_not_apply() {
+    let mut single = DrawingCanvas::new();
+    let mut fields = return_fields();
+    fields[0].set_value("Jane Doe");
+    fields[4].set_value("52,000");
+    single.set_shapes(fields.clone());
+
+    let mut married = DrawingCanvas::new();
+    fields[1].set_value("x");
+    married.set_shapes(fields);
+
+    let mut review = BatchReview::new(80.0);
+    review.add_instance("single", None, single);
+    review.add_instance("married", None, married);
+
+    let queued: Vec<(usize, usize)> = review.queue().iter().map(|item| (item.instance, item.field)).collect();
+    assert_eq!(queued, [(1, 2)]);
+}
+
+#[test]
+fn conditions_are_kept_in_templates_and_projects() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(return_fields());
+
+    let template = ProjectTemplate::from_canvas("1040", &canvas);
+    let loaded = DrawingCanvas::from_json(&canvas.to_json().expect("Serializable")).expect("Loadable");
+
+    let expected = Some(FieldCondition::Checked("married".to_string()));
+    assert_eq!(template.fields[2].condition(), expected.as_ref());
+    assert_eq!(loaded.shapes()[2].condition(), expected.as_ref());
+}
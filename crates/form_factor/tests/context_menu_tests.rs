@@ -0,0 +1,104 @@
+//! Integration tests for the canvas context menu operations
+
+mod common;
+
+use common::{names, region, with_detections};
+use egui::Pos2;
+use form_factor::{CommentTarget, DrawingCanvas};
+
+fn canvas_with_shapes(names: &[&str]) -> DrawingCanvas {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| region(name, i as f32 * 20.0, 0.0, 10.0, 10.0))
+            .collect(),
+    );
+    canvas
+}
+
+// ============================================================================
+// Shape actions
+// ============================================================================
+
+#[test]
+fn delete_shape_keeps_comments_on_remaining_shapes() {
+    let mut canvas = canvas_with_shapes(&["a", "b", "c"]);
+    canvas.add_comment_thread(CommentTarget::Shape(1), "on b");
+    canvas.add_comment_thread(CommentTarget::Shape(2), "on c");
+
+    let removed = canvas.delete_shape(1).expect("Shape exists");
+
+    assert_eq!(removed.name(), "b");
+    assert_eq!(names(&canvas), vec!["a", "c"]);
+    assert_eq!(canvas.comment_threads().len(), 1);
+    assert_eq!(canvas.comment_threads()[0].target, CommentTarget::Shape(1));
+    assert!(canvas.delete_shape(5).is_none());
+}
+
+#[test]
+fn duplicate_shape_adds_offset_copy_on_top() {
+    let mut canvas = canvas_with_shapes(&["name"]);
+
+    let copy = canvas.duplicate_shape(0).expect("Shape exists");
+
+    assert_eq!(copy, 1);
+    assert_eq!(names(&canvas), vec!["name", "name copy"]);
+    assert_eq!(*canvas.selected_shape(), Some(1));
+    let original = canvas.shapes()[0].bounding_rect();
+    let duplicate = canvas.shapes()[1].bounding_rect();
+    assert_eq!(duplicate.min, original.min + egui::vec2(10.0, 10.0));
+}
+
+#[test]
+fn reordering_moves_comments_with_shapes() {
+    let mut canvas = canvas_with_shapes(&["a", "b", "c"]);
+    canvas.add_comment_thread(CommentTarget::Shape(0), "on a");
+
+    canvas.bring_to_front(0);
+    assert_eq!(names(&canvas), vec!["b", "c", "a"]);
+    assert_eq!(canvas.comment_threads()[0].target, CommentTarget::Shape(2));
+
+    canvas.send_to_back(2);
+    assert_eq!(names(&canvas), vec!["a", "b", "c"]);
+    assert_eq!(canvas.comment_threads()[0].target, CommentTarget::Shape(0));
+}
+
+#[test]
+fn copy_and_paste_centers_shape_at_position() {
+    let mut canvas = canvas_with_shapes(&["a"]);
+    assert!(!canvas.can_paste());
+    assert!(canvas.paste_shape(None).is_none());
+
+    canvas.copy_shape(0);
+    let pasted = canvas.paste_shape(Some(Pos2::new(100.0, 100.0))).expect("Clipboard holds a shape");
+
+    assert_eq!(canvas.shapes()[pasted].bounding_rect().center(), Pos2::new(100.0, 100.0));
+    assert_eq!(canvas.shapes()[pasted].name(), "a copy");
+}
+
+// ============================================================================
+// Detection actions
+// ============================================================================
+
+#[test]
+fn delete_detection_remaps_comment_targets() {
+    let mut canvas = with_detections(vec![
+        region("Text Region 1", 0.0, 0.0, 10.0, 10.0),
+        region("Text Region 2", 20.0, 0.0, 10.0, 10.0),
+    ]);
+    canvas.add_comment_thread(CommentTarget::Detection(1), "second region");
+
+    let removed = canvas.delete_detection(0).expect("Detection exists");
+
+    assert_eq!(removed.name(), "Text Region 1");
+    assert_eq!(canvas.detections().len(), 1);
+    assert_eq!(canvas.comment_threads()[0].target, CommentTarget::Detection(0));
+}
+
+#[test]
+fn canvas_has_no_pending_actions_by_default() {
+    let mut canvas = DrawingCanvas::new();
+    assert!(canvas.take_actions().is_empty());
+}
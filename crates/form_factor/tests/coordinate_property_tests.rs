@@ -0,0 +1,225 @@
+//! Property tests for coordinate space mapping
+//!
+//! Detections live in image pixels, drawn shapes in canvas units and input
+//! in screen points; several past bugs came from one conversion disagreeing
+//! with another. For any viewport, zoom, pan and image size, mapping a
+//! point or shape to another space and back must return it within a
+//! tolerance scaled to the coordinates involved, and mapping a rotated
+//! shape must agree with mapping its points.
+
+use egui::{Color32, Pos2, Rect, Stroke, Vec2, vec2};
+use form_factor::{CanvasPos, CoordinateMapper, Ellipse, ImagePos, Rectangle, ScreenPos, Shape};
+use proptest::prelude::*;
+use std::f32::consts::PI;
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Largest error allowed after mapping coordinates up to `magnitude` to another space and back
+///
+/// f32 rounding grows with the size of the coordinates, including the
+/// viewport's, and with how much zoom and image fit magnify or shrink them.
+fn tolerance(mapper: &CoordinateMapper, magnitude: f32) -> f32 {
+    let scale = mapper.image_fit().map_or(1.0, |fit| fit.scale);
+    let gain = mapper.zoom().max(1.0 / mapper.zoom()) * scale.max(1.0 / scale);
+    let magnitude = magnitude.max(mapper.viewport().max.x).max(mapper.viewport().max.y);
+    1e-6 * (1.0 + magnitude) * gain + 1e-4
+}
+
+/// Fail unless `actual` is within `tolerance` of `expected`
+fn assert_near(actual: Pos2, expected: Pos2, tolerance: f32) -> Result<(), TestCaseError> {
+    prop_assert!(
+        actual.distance(expected) <= tolerance,
+        "{:?} is {} from {:?}, over {}",
+        actual,
+        actual.distance(expected),
+        expected,
+        tolerance
+    );
+    Ok(())
+}
+
+fn magnitude(pos: Pos2) -> f32 {
+    pos.x.abs().max(pos.y.abs())
+}
+
+// ============================================================================
+// Generators
+// ============================================================================
+
+fn arb_viewport() -> impl Strategy<Value = Rect> {
+    (0.0f32..500.0, 0.0f32..500.0, 50.0f32..3000.0, 50.0f32..3000.0)
+        .prop_map(|(x, y, width, height)| Rect::from_min_size(Pos2::new(x, y), vec2(width, height)))
+}
+
+fn arb_image_size() -> impl Strategy<Value = Vec2> {
+    (10.0f32..10000.0, 10.0f32..10000.0).prop_map(|(width, height)| vec2(width, height))
+}
+
+/// Mapper over the canvas's zoom range with a form image loaded
+fn arb_mapper() -> impl Strategy<Value = CoordinateMapper> {
+    (arb_viewport(), 0.1f32..100.0, -2000.0f32..2000.0, -2000.0f32..2000.0, arb_image_size()).prop_map(
+        |(viewport, zoom, pan_x, pan_y, image_size)| {
+            CoordinateMapper::new(viewport, zoom, vec2(pan_x, pan_y)).with_image_size(Some(image_size))
+        },
+    )
+}
+
+fn arb_pos() -> impl Strategy<Value = Pos2> {
+    (-5000.0f32..5000.0, -5000.0f32..5000.0).prop_map(|(x, y)| Pos2::new(x, y))
+}
+
+/// Corners of a `width` × `height` rectangle centered on `center`, rotated by `angle`
+fn rotated_corners(center: Pos2, width: f32, height: f32, angle: f32) -> [Pos2; 4] {
+    let rotation = egui::emath::Rot2::from_angle(angle);
+    [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+        .map(|(sx, sy)| center + rotation * vec2(sx * width / 2.0, sy * height / 2.0))
+}
+
+/// Rotated rectangle in image pixels, like a skewed text detection
+fn arb_rotated_rectangle() -> impl Strategy<Value = Rectangle> {
+    (0.0f32..5000.0, 0.0f32..5000.0, 5.0f32..800.0, 5.0f32..800.0, -PI..PI).prop_filter_map(
+        "invalid rectangle",
+        |(x, y, width, height, angle)| {
+            let corners = rotated_corners(Pos2::new(x, y), width, height, angle);
+            Rectangle::from_four_corners(corners, Stroke::new(2.0, Color32::RED), Color32::TRANSPARENT).ok()
+        },
+    )
+}
+
+fn arb_rotated_ellipse() -> impl Strategy<Value = Ellipse> {
+    (0.0f32..5000.0, 0.0f32..5000.0, 1.0f32..800.0, 1.0f32..800.0, -PI..PI).prop_filter_map(
+        "invalid ellipse",
+        |(x, y, rx, ry, rotation)| {
+            Ellipse::new(Pos2::new(x, y), vec2(rx, ry), rotation, Stroke::new(2.0, Color32::BLUE), Color32::TRANSPARENT)
+                .ok()
+        },
+    )
+}
+
+// ============================================================================
+// Points
+// ============================================================================
+
+proptest! {
+    #[test]
+    fn canvas_to_screen_round_trips(mapper in arb_mapper(), pos in arb_pos()) {
+        let screen = mapper.canvas_to_screen(CanvasPos(pos));
+        let back = mapper.screen_to_canvas(screen);
+
+        assert_near(back.0, pos, tolerance(&mapper, magnitude(screen.0).max(magnitude(pos))))?;
+    }
+
+    #[test]
+    fn image_to_canvas_round_trips(mapper in arb_mapper(), pos in arb_pos()) {
+        let canvas = mapper.image_to_canvas(ImagePos(pos)).expect("Image loaded");
+        let back = mapper.canvas_to_image(canvas).expect("Image loaded");
+
+        assert_near(back.0, pos, tolerance(&mapper, magnitude(canvas.0).max(magnitude(pos))))?;
+    }
+
+    #[test]
+    fn image_to_screen_round_trips(mapper in arb_mapper(), pos in arb_pos()) {
+        let screen = mapper.image_to_screen(ImagePos(pos)).expect("Image loaded");
+        let back = mapper.screen_to_image(screen).expect("Image loaded");
+
+        assert_near(back.0, pos, tolerance(&mapper, magnitude(screen.0).max(magnitude(pos))))?;
+    }
+
+    #[test]
+    fn screen_to_image_round_trips(mapper in arb_mapper(), pos in arb_pos()) {
+        let image = mapper.screen_to_image(ScreenPos(pos)).expect("Image loaded");
+        let back = mapper.image_to_screen(image).expect("Image loaded");
+
+        assert_near(back.0, pos, tolerance(&mapper, magnitude(image.0).max(magnitude(pos))))?;
+    }
+
+    #[test]
+    fn painting_transform_matches_point_mapping(mapper in arb_mapper(), pos in arb_pos()) {
+        let painted = mapper.to_screen().mul_pos(pos);
+        let mapped = mapper.canvas_to_screen(CanvasPos(pos)).0;
+
+        assert_near(painted, mapped, tolerance(&mapper, magnitude(mapped)))?;
+    }
+
+    #[test]
+    fn zoom_moves_the_viewport_center_by_the_pan_alone(
+        viewport in arb_viewport(),
+        zoom in 0.1f32..100.0,
+        pan in (-2000.0f32..2000.0, -2000.0f32..2000.0),
+    ) {
+        let pan = vec2(pan.0, pan.1);
+        let mapper = CoordinateMapper::new(viewport, zoom, pan);
+        let center = viewport.center();
+        let screen = mapper.canvas_to_screen(CanvasPos(center)).0;
+
+        assert_near(screen, center + pan, tolerance(&mapper, magnitude(screen)))?;
+    }
+
+    #[test]
+    fn fitted_image_stays_inside_the_viewport(mapper in arb_mapper()) {
+        let fit = mapper.image_fit().expect("Image loaded");
+        let image = fit.canvas_rect();
+        let viewport = mapper.viewport();
+        let slack = tolerance(&mapper, magnitude(viewport.max));
+
+        prop_assert!(viewport.expand(slack).contains_rect(image), "{:?} outside {:?}", image, viewport);
+        // Centered along both axes
+        assert_near(image.center(), viewport.center(), slack)?;
+    }
+}
+
+// ============================================================================
+// Shapes
+// ============================================================================
+
+proptest! {
+    #[test]
+    fn rotated_rectangle_corners_map_like_points(mapper in arb_mapper(), rect in arb_rotated_rectangle()) {
+        let Some(Shape::Rectangle(mapped)) = mapper.image_shape_to_canvas(&Shape::Rectangle(rect.clone())) else {
+            return Err(TestCaseError::fail("Rectangle should map to a rectangle"));
+        };
+
+        for (corner, mapped) in rect.corners().iter().zip(mapped.corners()) {
+            let expected = mapper.image_to_canvas(ImagePos(*corner)).expect("Image loaded").0;
+            assert_near(*mapped, expected, tolerance(&mapper, magnitude(expected)))?;
+        }
+    }
+
+    #[test]
+    fn rotated_rectangle_round_trips_through_canvas(mapper in arb_mapper(), rect in arb_rotated_rectangle()) {
+        let shape = Shape::Rectangle(rect.clone());
+        let canvas = mapper.image_shape_to_canvas(&shape).expect("Image loaded");
+        let Some(Shape::Rectangle(back)) = mapper.canvas_shape_to_image(&canvas) else {
+            return Err(TestCaseError::fail("Rectangle should map back to a rectangle"));
+        };
+
+        let scale = mapper.image_fit().expect("Image loaded").scale;
+        for (corner, back) in rect.corners().iter().zip(back.corners()) {
+            assert_near(*back, *corner, tolerance(&mapper, magnitude(*corner) * scale.max(1.0)))?;
+        }
+        prop_assert_eq!(&back.name, &rect.name);
+    }
+
+    #[test]
+    fn rotated_ellipse_keeps_its_rotation(mapper in arb_mapper(), ellipse in arb_rotated_ellipse()) {
+        let Some(Shape::Ellipse(mapped)) = mapper.image_shape_to_canvas(&Shape::Ellipse(ellipse.clone())) else {
+            return Err(TestCaseError::fail("Ellipse should map to an ellipse"));
+        };
+
+        let scale = mapper.image_fit().expect("Image loaded").scale;
+        let center = mapper.image_to_canvas(ImagePos(*ellipse.center())).expect("Image loaded").0;
+        assert_near(*mapped.center(), center, tolerance(&mapper, magnitude(center)))?;
+        prop_assert!((*mapped.rotation() - *ellipse.rotation()).abs() <= 1e-6);
+        let radii = *ellipse.radii() * scale;
+        prop_assert!((*mapped.radii() - radii).length() <= tolerance(&mapper, radii.length()));
+
+        let Some(Shape::Ellipse(back)) = mapper.canvas_shape_to_image(&Shape::Ellipse(mapped)) else {
+            return Err(TestCaseError::fail("Ellipse should map back to an ellipse"));
+        };
+        let magnitude = magnitude(center).max(magnitude(*ellipse.center()));
+        assert_near(*back.center(), *ellipse.center(), tolerance(&mapper, magnitude))?;
+        prop_assert!((*back.radii() - *ellipse.radii()).length() <= tolerance(&mapper, ellipse.radii().length()));
+    }
+}
@@ -0,0 +1,126 @@
+//! Integration tests for coordinate space mapping
+
+use egui::{Color32, Pos2, Rect, Stroke, Vec2, vec2};
+use form_factor::{CanvasPos, CanvasSimulator, CoordinateMapper, DrawingCanvas, ImagePos, Rectangle, ScreenPos, Shape};
+
+fn close(a: Pos2, b: Pos2) -> bool {
+    a.distance(b) < 1e-3
+}
+
+/// 200x100 viewport at (10, 20) showing a 100x100 image
+fn mapper() -> CoordinateMapper {
+    CoordinateMapper::new(
+        Rect::from_min_size(Pos2::new(10.0, 20.0), vec2(200.0, 100.0)),
+        2.0,
+        vec2(5.0, -5.0),
+    )
+    .with_image_size(Some(vec2(100.0, 100.0)))
+}
+
+// ============================================================================
+// Point mapping
+// ============================================================================
+
+#[test]
+fn image_is_fitted_and_centered_in_the_viewport() {
+    let fit = mapper().image_fit().expect("Image fitted");
+
+    assert_eq!(fit.scale, 1.0);
+    assert_eq!(fit.offset, Pos2::new(60.0, 20.0));
+    assert_eq!(
+        fit.canvas_rect(),
+        Rect::from_min_size(Pos2::new(60.0, 20.0), vec2(100.0, 100.0))
+    );
+}
+
+#[test]
+fn zoom_scales_about_the_viewport_center_then_pans() {
+    let mapper = mapper();
+    let center = mapper.viewport().center();
+
+    assert!(close(
+        mapper.canvas_to_screen(CanvasPos(center)).0,
+        center + vec2(5.0, -5.0)
+    ));
+    let screen = mapper.canvas_to_screen(CanvasPos(center + vec2(10.0, 0.0))).0;
+    assert!(close(screen, center + vec2(25.0, -5.0)));
+}
+
+#[test]
+fn conversions_round_trip_through_every_space() {
+    let mapper = mapper();
+    let image = ImagePos(Pos2::new(12.5, 80.0));
+
+    let canvas = mapper.image_to_canvas(image).expect("Image loaded");
+    let screen = mapper.canvas_to_screen(canvas);
+
+    assert!(close(mapper.screen_to_canvas(screen).0, canvas.0));
+    assert!(close(mapper.screen_to_image(screen).expect("Image loaded").0, image.0));
+    assert!(close(mapper.image_to_screen(image).expect("Image loaded").0, screen.0));
+}
+
+#[test]
+fn image_space_is_unavailable_without_an_image() {
+    let mapper = CoordinateMapper::new(Rect::from_min_size(Pos2::ZERO, vec2(100.0, 100.0)), 1.0, Vec2::ZERO);
+
+    assert!(mapper.image_fit().is_none());
+    assert!(mapper.canvas_to_image(CanvasPos(Pos2::ZERO)).is_none());
+    assert!(mapper.screen_to_image(ScreenPos(Pos2::ZERO)).is_none());
+}
+
+// ============================================================================
+// Shapes and crops
+// ============================================================================
+
+#[test]
+fn shapes_map_between_image_and_canvas() {
+    let mapper = mapper();
+    let rect = Rectangle::from_corners(
+        Pos2::new(10.0, 10.0),
+        Pos2::new(30.0, 20.0),
+        Stroke::new(1.0, Color32::BLACK),
+        Color32::TRANSPARENT,
+    )
+    .expect("Valid rectangle");
+    let detection = Shape::Rectangle(rect);
+
+    let on_canvas = mapper.image_shape_to_canvas(&detection).expect("Image loaded");
+    assert_eq!(
+        on_canvas.bounding_rect(),
+        Rect::from_min_max(Pos2::new(70.0, 30.0), Pos2::new(90.0, 40.0))
+    );
+    let back = mapper.canvas_shape_to_image(&on_canvas).expect("Image loaded");
+    assert_eq!(back.bounding_rect(), detection.bounding_rect());
+}
+
+#[test]
+fn pixel_crop_rounds_outward_and_clamps_to_the_image() {
+    let mapper = mapper();
+
+    assert_eq!(
+        mapper.pixel_crop(Rect::from_min_max(Pos2::new(10.4, 5.6), Pos2::new(20.2, 9.0))),
+        (10, 5, 11, 4)
+    );
+    assert_eq!(
+        mapper.pixel_crop(Rect::from_min_max(Pos2::new(-5.0, 90.0), Pos2::new(50.0, 130.0))),
+        (0, 90, 50, 10)
+    );
+}
+
+// ============================================================================
+// Canvas integration
+// ============================================================================
+
+#[test]
+fn canvas_records_the_mapper_of_the_last_frame() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_zoom(2.0);
+    let mut sim = CanvasSimulator::new(canvas);
+    sim.step();
+
+    let mapper = *sim.canvas().coordinate_mapper();
+    assert_eq!(mapper.viewport(), sim.canvas_rect());
+    assert_eq!(mapper.zoom(), 2.0);
+    let pos = sim.canvas_rect().center() + vec2(30.0, 12.0);
+    assert!(close(mapper.canvas_to_screen(CanvasPos(pos)).0, sim.to_screen(pos)));
+}
@@ -0,0 +1,65 @@
+//! Integration tests for cursor feedback and panning the canvas
+
+mod common;
+
+use common::simulator;
+use egui::{CursorIcon, PointerButton, pos2};
+use form_factor::{LayerType, ROTATE_CURSOR, ToolMode};
+
+#[test]
+fn drawing_tools_show_a_crosshair() {
+    let mut sim = simulator();
+    sim.canvas_mut().set_tool(ToolMode::Rectangle);
+
+    sim.pointer_move(sim.to_screen(pos2(100.0, 100.0)));
+
+    assert_eq!(sim.cursor(), CursorIcon::Crosshair);
+}
+
+#[test]
+fn select_tool_points_at_shapes_only() {
+    let mut sim = simulator();
+    sim.draw_rectangle(pos2(100.0, 100.0), pos2(300.0, 200.0));
+    sim.canvas_mut().set_tool(ToolMode::Select);
+
+    assert_eq!(sim.canvas().cursor_at(pos2(200.0, 150.0)), CursorIcon::PointingHand);
+    assert_eq!(sim.canvas().cursor_at(pos2(500.0, 500.0)), CursorIcon::Default);
+}
+
+#[test]
+fn edit_tool_shows_resize_arrows_over_corners() {
+    let mut sim = simulator();
+    sim.draw_rectangle(pos2(100.0, 100.0), pos2(300.0, 200.0));
+    sim.canvas_mut().set_tool(ToolMode::Edit);
+    let canvas = sim.canvas();
+
+    assert_eq!(canvas.cursor_at(pos2(100.0, 100.0)), CursorIcon::ResizeNwSe);
+    assert_eq!(canvas.cursor_at(pos2(300.0, 100.0)), CursorIcon::ResizeNeSw);
+    assert_eq!(canvas.cursor_at(pos2(200.0, 150.0)), CursorIcon::PointingHand);
+}
+
+#[test]
+fn rotate_tool_shows_rotate_cursor_only_with_a_target() {
+    let mut sim = simulator();
+    sim.draw_rectangle(pos2(100.0, 100.0), pos2(300.0, 200.0));
+    sim.canvas_mut().set_tool(ToolMode::Rotate);
+    sim.canvas_mut().set_selected_layer(Some(LayerType::Shapes));
+
+    assert_eq!(sim.canvas().cursor_at(pos2(500.0, 500.0)), ROTATE_CURSOR);
+
+    sim.canvas_mut().set_selected_layer(Some(LayerType::Detections));
+
+    assert_eq!(sim.canvas().cursor_at(pos2(500.0, 500.0)), CursorIcon::NotAllowed);
+}
+
+#[test]
+fn middle_drag_pans_without_drawing() {
+    let mut sim = simulator();
+    sim.canvas_mut().set_tool(ToolMode::Rectangle);
+    let pan = *sim.canvas().pan_offset();
+
+    sim.drag_with(PointerButton::Middle, pos2(400.0, 300.0), pos2(450.0, 340.0));
+
+    assert_eq!(*sim.canvas().pan_offset(), pan + egui::vec2(50.0, 40.0));
+    assert_eq!(sim.canvas().shape_count(), 0);
+}
@@ -0,0 +1,293 @@
+//! Integration tests for field validation and keyboard-first data entry
+
+mod common;
+
+use common::{field_at, form_png};
+use egui::{Color32, Pos2, Stroke};
+use form_factor::{CanvasPos, DrawingCanvas, FieldFormat, FieldIssue, FieldRule, OcrSample, Rectangle, Shape};
+
+fn field(name: &str, y: f32, rule: FieldRule) -> Shape {
+    let mut shape = field_at(name, 100.0, y);
+    shape.set_rule(rule);
+    shape
+}
+
+fn form() -> DrawingCanvas {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![
+        field("name", 0.0, FieldRule::new(FieldFormat::Text).required()),
+        field("wages", 40.0, FieldRule::new(FieldFormat::Number)),
+        field("signed", 80.0, FieldRule::new(FieldFormat::Date)),
+    ]);
+    canvas
+}
+
+/// Run one frame showing the canvas and the data entry window
+fn frame(ctx: &egui::Context, canvas: &mut DrawingCanvas, events: Vec<egui::Event>) {
+    // Held modifiers are reported alongside the key events
+    let modifiers = events
+        .iter()
+        .find_map(|event| match event {
+            egui::Event::Key { modifiers, .. } => Some(*modifiers),
+            _ => None,
+        })
+        .unwrap_or_default();
+    let input = egui::RawInput {
+        screen_rect: Some(egui::Rect::from_min_size(Pos2::ZERO, egui::vec2(800.0, 600.0))),
+        modifiers,
+        events,
+        ..Default::default()
+    };
+    let _ = ctx.run(input, |ctx| {
+        egui::CentralPanel::default().show(ctx, |ui| canvas.ui(ui));
+        canvas.show_data_entry_panel(ctx);
+    });
+}
+
+fn key(key: egui::Key, modifiers: egui::Modifiers) -> egui::Event {
+    egui::Event::Key {
+        key,
+        physical_key: None,
+        pressed: true,
+        repeat: false,
+        modifiers,
+    }
+}
+
+// ============================================================================
+// Validation rules
+// ============================================================================
+
+#[test]
+fn rules_check_required_numbers_and_dates() {
+    let required = FieldRule::new(FieldFormat::Text).required();
+    assert_eq!(required.check("  "), Err(FieldIssue::Missing));
+    assert_eq!(required.check("Jane"), Ok(()));
+
+    let number = FieldRule::new(FieldFormat::Number);
+    assert_eq!(number.check(""), Ok(()));
+    assert_eq!(number.check("$1,234.50"), Ok(()));
+    assert_eq!(number.check("-12"), Ok(()));
+    assert_eq!(number.check("12a"), Err(FieldIssue::NotANumber));
+
+    let date = FieldRule::new(FieldFormat::Date);
+    assert_eq!(date.check("2024-02-29"), Ok(()));
+    assert_eq!(date.check("12/31/2023"), Ok(()));
+    assert_eq!(date.check("2023-02-29"), Err(FieldIssue::NotADate));
+    assert_eq!(date.check("31/12/2023"), Err(FieldIssue::NotADate));
+    assert_eq!(date.check("24-01-01"), Err(FieldIssue::NotADate));
+}
+
+#[test]
+fn values_and_rules_are_saved_with_the_project() {
+    let mut canvas = form();
+    let mut shapes = canvas.shapes().clone();
+    shapes[1].set_value("1,000");
+    canvas.set_shapes(shapes);
+
+    let loaded = DrawingCanvas::from_json(&canvas.to_json().expect("Serializable")).expect("Loadable");
+
+    assert_eq!(loaded.shapes()[1].value(), "1,000");
+    assert_eq!(loaded.shapes()[1].rule(), FieldRule::new(FieldFormat::Number));
+}
+
+// ============================================================================
+// Moving between fields
+// ============================================================================
+
+#[test]
+fn entry_starts_at_the_first_field_with_a_problem() {
+    let mut canvas = form();
+    let mut shapes = canvas.shapes().clone();
+    shapes[0].set_value("Jane");
+    shapes[1].set_value("lots");
+    canvas.set_shapes(shapes);
+
+    canvas.open_data_entry_panel();
+
+    assert_eq!(*canvas.data_entry_field(), Some(1));
+    assert_eq!(*canvas.selected_shape(), Some(1));
+}
+
+#[test]
+fn advancing_stops_on_invalid_values_and_at_the_end() {
+    let mut canvas = form();
+    canvas.open_data_entry_panel();
+    assert_eq!(*canvas.data_entry_field(), Some(0));
+
+    // Required field is empty
+    assert!(!canvas.advance_field());
+    assert_eq!(*canvas.data_entry_field(), Some(0));
+
+    canvas.set_data_entry_value("Jane");
+    assert!(canvas.advance_field());
+    canvas.set_data_entry_value("52,000");
+    assert!(canvas.advance_field());
+    canvas.set_data_entry_value("2024-04-15");
+    assert!(!canvas.advance_field());
+    assert_eq!(*canvas.data_entry_field(), Some(2));
+
+    assert!(canvas.previous_field());
+    assert_eq!(*canvas.data_entry_field(), Some(1));
+}
+
+#[test]
+fn changed_values_count_as_corrections_once_per_visit() {
+    let mut canvas = form();
+    let mut shapes = canvas.shapes().clone();
+    shapes[0].set_value("Jane");
+    canvas.set_shapes(shapes);
+    canvas.open_data_entry_panel();
+
+    // Accepted unchanged
+    assert!(canvas.advance_field());
+    // Typed in several keystrokes
+    canvas.set_data_entry_value("5");
+    canvas.set_data_entry_value("52,000");
+    assert!(canvas.advance_field());
+    assert_eq!(canvas.session_stats().fields_corrected(), 1);
+
+    canvas.set_data_entry_value("2024-04-15");
+    canvas.close_data_entry_panel();
+    assert_eq!(canvas.session_stats().fields_corrected(), 2);
+}
+
+#[test]
+fn extracted_values_are_reported_once_settled() {
+    let mut canvas = form();
+    let mut shapes = canvas.shapes().clone();
+    shapes[0].set_extracted_value("Jane", Some(92.0));
+    shapes[1].set_extracted_value("52,00O", Some(41.0));
+    shapes[2].set_extracted_value("2024-04-15", Some(88.0));
+    canvas.set_shapes(shapes);
+    // Data entry starts at the field whose extracted value breaks its rule
+    canvas.open_data_entry_panel();
+    assert_eq!(*canvas.data_entry_field(), Some(1));
+
+    canvas.set_data_entry_value("52,000");
+    // Going back leaves the corrected field without accepting it, and reports the change
+    assert!(canvas.previous_field());
+    assert!(canvas.advance_field());
+    // The last field is never reached, so its value is left unchecked
+    canvas.close_data_entry_panel();
+
+    let sample = |field: &str, proposed: &str, corrected: &str, confidence: f32| OcrSample {
+        field: field.to_string(),
+        proposed: proposed.to_string(),
+        corrected: corrected.to_string(),
+        confidence,
+    };
+    assert_eq!(
+        canvas.take_ocr_samples(),
+        [sample("wages", "52,00O", "52,000", 41.0), sample("name", "Jane", "Jane", 92.0)]
+    );
+    // Accepted values were checked by a person
+    assert_eq!(canvas.shapes()[0].confidence(), None);
+    assert_eq!(canvas.shapes()[2].confidence(), Some(88.0));
+    assert!(canvas.take_ocr_samples().is_empty());
+}
+
+#[test]
+fn enter_in_the_value_box_moves_to_the_next_field() {
+    let ctx = egui::Context::default();
+    let mut canvas = form();
+    canvas.open_data_entry_panel();
+    canvas.set_data_entry_value("Jane");
+
+    // The value box takes the focus, then Enter accepts the value
+    frame(&ctx, &mut canvas, Vec::new());
+    frame(&ctx, &mut canvas, Vec::new());
+    frame(&ctx, &mut canvas, vec![key(egui::Key::Enter, egui::Modifiers::NONE)]);
+    assert_eq!(*canvas.data_entry_field(), Some(1));
+
+    frame(&ctx, &mut canvas, Vec::new());
+    frame(&ctx, &mut canvas, vec![key(egui::Key::Enter, egui::Modifiers::SHIFT)]);
+    assert_eq!(*canvas.data_entry_field(), Some(0));
+}
+
+// ============================================================================
+// Auto-zoom
+// ============================================================================
+
+#[test]
+fn reaching_a_field_zooms_to_it() {
+    let ctx = egui::Context::default();
+    let mut canvas = form();
+    frame(&ctx, &mut canvas, Vec::new());
+
+    canvas.open_data_entry_panel();
+    canvas.set_data_entry_value("Jane");
+    canvas.advance_field();
+    frame(&ctx, &mut canvas, Vec::new());
+
+    assert!(*canvas.zoom_level() > 1.0);
+    let mapper = canvas.coordinate_mapper();
+    let center = canvas.shapes()[1].bounding_rect().center();
+    let on_screen = mapper.to_screen().mul_pos(center);
+    assert!((on_screen - mapper.viewport().center()).length() < 1.0);
+}
+
+#[test]
+fn auto_zoom_can_be_turned_off() {
+    let ctx = egui::Context::default();
+    let mut canvas = form();
+    frame(&ctx, &mut canvas, Vec::new());
+    canvas.set_data_entry_auto_zoom(false);
+    let zoom = *canvas.zoom_level();
+
+    canvas.open_data_entry_panel();
+
+    assert_eq!(*canvas.zoom_level(), zoom);
+    assert_eq!(*canvas.selected_shape(), Some(0));
+}
+
+// ============================================================================
+// Field images
+// ============================================================================
+
+#[test]
+fn field_images_are_cropped_from_the_form_image() {
+    let ctx = egui::Context::default();
+    let mut canvas = form();
+    assert_eq!(canvas.field_image_crop(0), None);
+
+    canvas
+        .load_form_image_bytes("form.png", &form_png(400, 300), &ctx)
+        .expect("Loadable");
+    frame(&ctx, &mut canvas, Vec::new());
+
+    let mapper = canvas.coordinate_mapper();
+    let bounds = canvas.shapes()[1].bounding_rect();
+    let min = mapper.canvas_to_image(CanvasPos(bounds.min)).expect("Image loaded").0;
+    let max = mapper.canvas_to_image(CanvasPos(bounds.max)).expect("Image loaded").0;
+    let (x, y, width, height) = canvas.field_image_crop(1).expect("Field is on the image");
+    assert_eq!((x, y), (min.x.floor() as u32, min.y.floor() as u32));
+    assert_eq!((x + width, y + height), (max.x.ceil() as u32, max.y.ceil() as u32));
+}
+
+#[test]
+fn field_images_stay_within_the_form_image() {
+    let ctx = egui::Context::default();
+    let mut canvas = form();
+    canvas
+        .load_form_image_bytes("form.png", &form_png(400, 300), &ctx)
+        .expect("Loadable");
+    frame(&ctx, &mut canvas, Vec::new());
+
+    let mapper = canvas.coordinate_mapper();
+    let image_rect = mapper.image_fit().expect("Image loaded").canvas_rect();
+    let outline = |min: Pos2, max: Pos2| {
+        Shape::Rectangle(
+            Rectangle::from_corners(min, max, Stroke::new(1.0, Color32::BLACK), Color32::TRANSPARENT)
+                .expect("Valid rectangle"),
+        )
+    };
+    canvas.set_shapes(vec![
+        outline(image_rect.min - egui::vec2(50.0, 50.0), image_rect.max + egui::vec2(50.0, 50.0)),
+        outline(image_rect.max + egui::vec2(10.0, 10.0), image_rect.max + egui::vec2(60.0, 40.0)),
+    ]);
+
+    assert_eq!(canvas.field_image_crop(0), Some((0, 0, 400, 300)));
+    assert_eq!(canvas.field_image_crop(1), None);
+    assert_eq!(canvas.field_image_crop(99), None);
+}
@@ -0,0 +1,135 @@
+//! Integration tests for the on-disk detection result cache
+
+mod common;
+
+use common::{region, temp_path};
+use form_factor::{DrawingCanvas, Settings, Shape, clear_detection_cache};
+use std::path::Path;
+
+/// Canvas named `name` whose form image is `image`, with `detections`
+fn canvas_on(name: &str, image: &Path, detections: Vec<Shape>) -> DrawingCanvas {
+    let mut project = serde_json::to_value(DrawingCanvas::new()).expect("Serializable");
+    project["project_name"] = serde_json::json!(name);
+    project["form_image_path"] = serde_json::json!(image.to_str().expect("UTF-8 path"));
+    project["detections"] = serde_json::to_value(detections).expect("Serializable");
+    DrawingCanvas::from_json(&project.to_string()).expect("Valid project")
+}
+
+fn write_image(path: &Path, shade: u8) {
+    image::RgbaImage::from_pixel(20, 20, image::Rgba([shade, shade, shade, 255]))
+        .save(path)
+        .expect("Temp image");
+}
+
+#[test]
+fn keys_follow_image_contents_and_detector_configuration() {
+    let first = temp_path("cache_key_a.png");
+    let copy = temp_path("cache_key_copy.png");
+    let other = temp_path("cache_key_b.png");
+    write_image(&first, 100);
+    write_image(&copy, 100);
+    write_image(&other, 200);
+
+    let key = |image: &Path, detector: &str| {
+        canvas_on("Form", image, Vec::new())
+            .detection_cache_key(detector)
+            .expect("Image readable")
+    };
+    let (same, moved, edited, reconfigured) = (
+        key(&first, "text;confidence=0.5"),
+        key(&copy, "text;confidence=0.5"),
+        key(&other, "text;confidence=0.5"),
+        key(&first, "text;confidence=0.7"),
+    );
+    for path in [&first, &copy, &other] {
+        let _ = std::fs::remove_file(path);
+    }
+
+    assert_eq!(same, moved, "identical pixels share a key wherever the file is");
+    assert_ne!(same, edited);
+    assert_ne!(same, reconfigured);
+    assert!(DrawingCanvas::new().detection_cache_key("text").is_err(), "no form image");
+}
+
+#[test]
+fn cached_detections_are_added_on_a_hit() {
+    let image = temp_path("cache_hit.png");
+    let dir = temp_path("cache_hit_dir");
+    write_image(&image, 50);
+    let detected = canvas_on("Form", &image, vec![region("Existing", 10.0, 10.0, 50.0, 15.0), region("Text Region 1", 10.0, 10.0, 50.0, 15.0)]);
+    let key = detected.detection_cache_key("text").expect("Image readable");
+
+    let path = detected.cache_detections(&dir, &key, "text", 1).expect("Cached");
+    let mut reopened = canvas_on("Form", &image, vec![region("Existing", 10.0, 10.0, 50.0, 15.0)]);
+    let hit = reopened.load_cached_detections(&dir, &key);
+    let miss = reopened.load_cached_detections(&dir, "0000000000000000-0000000000000000");
+    let _ = std::fs::remove_file(&image);
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert!(path.starts_with(&dir));
+    assert_eq!(hit, Some(1), "only detections from the pass are cached");
+    assert_eq!(miss, None);
+    let names: Vec<&str> = reopened.detections().iter().map(Shape::name).collect();
+    assert_eq!(names, ["Existing", "Text Region 1"]);
+}
+
+#[test]
+fn corrupt_cache_files_are_misses() {
+    let dir = temp_path("cache_corrupt_dir");
+    std::fs::create_dir_all(&dir).expect("Temp dir");
+    std::fs::write(dir.join("bad.json"), "{ not json").expect("Temp file");
+
+    let mut canvas = DrawingCanvas::new();
+    let loaded = canvas.load_cached_detections(&dir, "bad");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(loaded, None);
+    assert!(canvas.detections().is_empty());
+}
+
+#[test]
+fn each_project_has_its_own_directory() {
+    let root = Path::new("cache");
+    let mut project = serde_json::to_value(DrawingCanvas::new()).expect("Serializable");
+    project["project_name"] = serde_json::json!("W-2 / 2024");
+    let canvas = DrawingCanvas::from_json(&project.to_string()).expect("Valid project");
+    project["project_name"] = serde_json::json!("???");
+    let unnamed = DrawingCanvas::from_json(&project.to_string()).expect("Valid project");
+
+    assert_eq!(canvas.project_cache_dir(root), root.join("W-2___2024"));
+    assert_eq!(unnamed.project_cache_dir(root), root.join("Untitled"));
+}
+
+#[test]
+fn clearing_removes_every_cached_result() {
+    let image = temp_path("cache_clear.png");
+    let root = temp_path("cache_clear_dir");
+    write_image(&image, 10);
+    for name in ["First", "Second"] {
+        let canvas = canvas_on(name, &image, vec![region("Logo: acme", 10.0, 10.0, 50.0, 15.0)]);
+        let key = canvas.detection_cache_key("logo").expect("Image readable");
+        canvas
+            .cache_detections(&canvas.project_cache_dir(&root), &key, "logo", 0)
+            .expect("Cached");
+    }
+
+    let removed = clear_detection_cache(&root);
+    let again = clear_detection_cache(&root);
+    let _ = std::fs::remove_file(&image);
+
+    assert_eq!(removed.expect("Cleared"), 2);
+    assert!(!root.exists());
+    assert_eq!(again.expect("Nothing to clear"), 0);
+}
+
+#[test]
+fn caching_is_on_unless_turned_off() {
+    let path = temp_path("cache_settings.json");
+    std::fs::write(&path, r#"{"detection": {"text_confidence": 0.6}}"#).expect("Temp settings");
+
+    let settings = Settings::load_from(&path);
+    let _ = std::fs::remove_file(&path);
+
+    assert!(Settings::default().detection.cache_results);
+    assert!(settings.detection.cache_results, "older settings files cache results");
+}
@@ -0,0 +1,126 @@
+//! Integration tests for splitting and merging detection regions
+
+mod common;
+
+use common::{region, with_detections};
+use egui::{Pos2, Rect};
+use form_factor::{CommentTarget, OcrWord, Shape, SplitDirection};
+
+fn word(text: &str, x: f32, width: f32, confidence: f32) -> OcrWord {
+    OcrWord {
+        text: text.to_string(),
+        confidence,
+        bounds: Rect::from_min_size(Pos2::new(x, 2.0), egui::vec2(width, 16.0)),
+    }
+}
+
+#[test]
+fn split_divides_the_region_and_its_words() {
+    let mut canvas = with_detections(vec![region("", 0.0, 0.0, 200.0, 20.0)]);
+    canvas.record_ocr_result(0, "Total $1,200", 90.0);
+    canvas.record_ocr_words(0, vec![word("Total", 4.0, 50.0, 96.0), word("$1,200", 120.0, 70.0, 84.0)]);
+
+    let new = canvas.split_detection(0, SplitDirection::Vertical, 100.0).expect("Split");
+
+    assert_eq!(new, 1);
+    assert_eq!(canvas.detections()[0].bounding_rect().max.x, 100.0);
+    assert_eq!(canvas.detections()[1].bounding_rect().min.x, 100.0);
+    assert_eq!(canvas.ocr_text(0), Some("Total"));
+    assert_eq!(canvas.ocr_text(1), Some("$1,200"));
+    assert_eq!(canvas.ocr_confidence(1), Some(84.0));
+    // The parts match their records, so no re-read is needed
+    assert!(canvas.changed_ocr_regions().is_empty());
+}
+
+#[test]
+fn split_without_words_leaves_both_parts_to_be_read() {
+    let mut canvas = with_detections(vec![region("", 0.0, 0.0, 200.0, 20.0)]);
+    canvas.record_ocr_text(0, "Total $1,200");
+
+    canvas.split_detection(0, SplitDirection::Horizontal, 10.0).expect("Split");
+
+    assert_eq!(canvas.ocr_text(0), None);
+    assert_eq!(canvas.changed_ocr_regions(), vec![0, 1]);
+}
+
+#[test]
+fn split_line_must_leave_two_parts() {
+    let mut canvas = with_detections(vec![region("", 0.0, 0.0, 200.0, 20.0)]);
+
+    assert_eq!(canvas.split_detection(0, SplitDirection::Vertical, 250.0), None);
+    assert_eq!(canvas.split_detection(0, SplitDirection::Horizontal, 0.5), None);
+    assert_eq!(canvas.detections().len(), 1);
+}
+
+#[test]
+fn rotated_regions_are_not_split() {
+    let Shape::Rectangle(mut rect) = region("", 0.0, 0.0, 200.0, 20.0) else {
+        unreachable!("Regions are rectangles");
+    };
+    rect.rotate(0.3, Pos2::new(100.0, 10.0)).expect("Rotated");
+    let rotated = Shape::Rectangle(rect);
+    let mut canvas = with_detections(vec![rotated.clone()]);
+
+    assert_eq!(canvas.split_detection(0, SplitDirection::Vertical, 100.0), None);
+    assert_eq!(canvas.detections(), &[rotated]);
+}
+
+#[test]
+fn merge_joins_regions_and_text_in_reading_order() {
+    let mut canvas = with_detections(vec![region("", 0.0, 30.0, 100.0, 20.0), region("", 110.0, 0.0, 100.0, 20.0), region("", 0.0, 0.0, 100.0, 20.0)]);
+    canvas.record_ocr_result(0, "Springfield", 80.0);
+    canvas.record_ocr_result(1, "Street", 90.0);
+    canvas.record_ocr_result(2, "742 Evergreen", 70.0);
+
+    let merged = canvas.merge_detections(&[2, 0, 1]).expect("Merged");
+
+    assert_eq!(merged, 0);
+    assert_eq!(canvas.detections().len(), 1);
+    assert_eq!(
+        canvas.detections()[0].bounding_rect(),
+        Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(210.0, 50.0))
+    );
+    assert_eq!(canvas.ocr_text(0), Some("742 Evergreen Street\nSpringfield"));
+    assert_eq!(canvas.ocr_confidence(0), Some(80.0));
+}
+
+#[test]
+fn merging_an_unread_detection_drops_the_text() {
+    let mut canvas = with_detections(vec![region("", 0.0, 0.0, 100.0, 20.0), region("", 100.0, 0.0, 100.0, 20.0)]);
+    canvas.record_ocr_text(0, "Total");
+
+    canvas.merge_detections(&[0, 1]).expect("Merged");
+
+    assert_eq!(canvas.ocr_text(0), None);
+    assert_eq!(canvas.changed_ocr_regions(), vec![0]);
+}
+
+#[test]
+fn merge_selection_toggles_and_merges() {
+    let mut canvas = with_detections(vec![region("", 0.0, 0.0, 100.0, 20.0), region("", 100.0, 0.0, 100.0, 20.0), region("", 0.0, 50.0, 100.0, 20.0)]);
+
+    canvas.toggle_detection_selection(2);
+    canvas.toggle_detection_selection(1);
+    canvas.toggle_detection_selection(2);
+    assert_eq!(canvas.selected_detections(), [1]);
+    assert_eq!(canvas.merge_selected_detections(), None);
+
+    canvas.toggle_detection_selection(0);
+    assert_eq!(canvas.merge_selected_detections(), Some(0));
+    assert_eq!(canvas.detections().len(), 2);
+    assert!(canvas.selected_detections().is_empty());
+}
+
+#[test]
+fn merge_keeps_comments_and_subtypes_of_the_parts() {
+    let mut canvas = with_detections(vec![region("", 0.0, 0.0, 100.0, 20.0), region("", 100.0, 0.0, 100.0, 20.0), region("", 0.0, 50.0, 100.0, 20.0)]);
+    assert!(canvas.tag_detection(1, Some("Signature".to_string())));
+    canvas.add_comment_thread(CommentTarget::Detection(1), "Signed twice?");
+    canvas.add_comment_thread(CommentTarget::Detection(2), "Unrelated");
+
+    canvas.merge_detections(&[0, 1]).expect("Merged");
+
+    assert_eq!(canvas.detection_tag(0), Some("Signature"));
+    assert_eq!(canvas.threads_for(CommentTarget::Detection(0)).count(), 1);
+    assert_eq!(canvas.threads_for(CommentTarget::Detection(1)).count(), 1);
+}
@@ -0,0 +1,151 @@
+//! Integration tests for the detection subtype taxonomy and detection tags
+
+mod common;
+
+use common::{region, temp_path, with_detections};
+use egui::Color32;
+use form_factor::{DetectionSubtype, DetectionTaxonomy, DrawingCanvas, Settings};
+
+/// Canvas with two text regions and one logo
+fn detected_canvas() -> DrawingCanvas {
+    with_detections(vec![
+        region("Text Region 1", 0.0, 0.0, 10.0, 10.0),
+        region("Logo: acme", 0.0, 0.0, 10.0, 10.0),
+        region("Text Region 2", 0.0, 0.0, 10.0, 10.0),
+    ])
+}
+
+// ============================================================================
+// Taxonomy
+// ============================================================================
+
+#[test]
+fn taxonomy_starts_with_common_subtypes() {
+    let taxonomy = DetectionTaxonomy::default();
+
+    let names: Vec<&str> = taxonomy.subtypes.iter().map(|subtype| subtype.name.as_str()).collect();
+    assert_eq!(names, ["Logo", "Text", "Signature"]);
+    assert!(!taxonomy.get("Signature").expect("Default").description.is_empty());
+}
+
+#[test]
+fn adding_a_subtype_replaces_one_with_the_same_name() {
+    let mut taxonomy = DetectionTaxonomy::default();
+    taxonomy.add(DetectionSubtype::new("Text", Color32::RED, "Typed answers"));
+    taxonomy.add(DetectionSubtype::new("Barcode", Color32::BLACK, "Linear and 2D codes"));
+
+    assert_eq!(taxonomy.subtypes.len(), 4);
+    assert_eq!(taxonomy.subtypes[1].color, Color32::RED);
+    assert!(taxonomy.remove("Barcode").is_some());
+    assert!(taxonomy.remove("Barcode").is_none());
+}
+
+#[test]
+fn settings_without_a_taxonomy_load_the_default() {
+    let path = temp_path("old_detection_settings.json");
+    std::fs::write(&path, r#"{"detection": {"text_confidence": 0.7}}"#).expect("Temp settings");
+
+    let settings = Settings::load_from(&path);
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(settings.detection.text_confidence, 0.7);
+    assert_eq!(settings.detection.taxonomy, DetectionTaxonomy::default());
+}
+
+// ============================================================================
+// Tagging
+// ============================================================================
+
+#[test]
+fn tags_must_name_a_subtype_and_a_detection() {
+    let mut canvas = detected_canvas();
+
+    assert!(canvas.tag_detection(0, Some("Signature".to_string())));
+    assert!(!canvas.tag_detection(0, Some("Barcode".to_string())));
+    assert!(!canvas.tag_detection(3, Some("Text".to_string())));
+    assert_eq!(canvas.detection_tag(0), Some("Signature"));
+
+    assert!(canvas.tag_detection(0, None));
+    assert_eq!(canvas.detection_tag(0), None);
+}
+
+#[test]
+fn tagged_detections_take_the_subtype_color() {
+    let mut canvas = detected_canvas();
+    let mut settings = Settings::default();
+    settings
+        .detection
+        .taxonomy
+        .add(DetectionSubtype::new("Barcode", Color32::BLACK, ""));
+    canvas.apply_settings(&settings);
+    canvas.tag_detection(1, Some("Barcode".to_string()));
+
+    let styled = canvas.styled_detection(1).expect("Detection exists");
+    assert_eq!(styled.stroke().color, Color32::BLACK);
+
+    // Removing the subtype keeps the tag but falls back to the kind's color
+    settings.detection.taxonomy.remove("Barcode");
+    canvas.apply_settings(&settings);
+    let styled = canvas.styled_detection(1).expect("Detection exists");
+    assert_eq!(canvas.detection_tag(1), Some("Barcode"));
+    assert_eq!(styled.stroke().color, settings.detection.colors.logo);
+}
+
+#[test]
+fn tags_follow_detections_when_one_is_deleted() {
+    let mut canvas = detected_canvas();
+    canvas.tag_detection(0, Some("Text".to_string()));
+    canvas.tag_detection(2, Some("Signature".to_string()));
+
+    canvas.delete_detection(0);
+
+    assert_eq!(canvas.detection_tag(0), None);
+    assert_eq!(canvas.detection_tag(1), Some("Signature"));
+    assert_eq!(canvas.subtype_count("Text"), 0);
+}
+
+#[test]
+fn filter_shows_only_the_selected_subtype() {
+    let mut canvas = detected_canvas();
+    canvas.tag_detection(2, Some("Signature".to_string()));
+
+    canvas.set_selected_detection_subtype(Some("Signature".to_string()));
+    assert!(!canvas.passes_subtype_filter(0));
+    assert!(canvas.passes_subtype_filter(2));
+
+    canvas.set_selected_detection_subtype(None);
+    assert!(canvas.passes_subtype_filter(0));
+}
+
+#[test]
+fn legend_lists_subtypes_in_use() {
+    let mut canvas = detected_canvas();
+    canvas.tag_detection(2, Some("Signature".to_string()));
+
+    let rows: Vec<(String, usize)> = canvas
+        .legend_entries()
+        .into_iter()
+        .map(|entry| (entry.label, entry.count))
+        .collect();
+
+    let expected = [("Shapes", 0), ("Text", 1), ("Logo", 1), ("OCR", 0), ("Signature", 1)];
+    assert_eq!(rows, expected.map(|(label, count)| (label.to_string(), count)));
+}
+
+// ============================================================================
+// Project files
+// ============================================================================
+
+#[test]
+fn tags_are_saved_with_the_project() {
+    let mut canvas = detected_canvas();
+    canvas.tag_detection(1, Some("Logo".to_string()));
+
+    let json = canvas.to_json().expect("Serializable");
+    let restored = DrawingCanvas::from_json(&json).expect("Loadable");
+    assert_eq!(restored.detection_tag(1), Some("Logo"));
+
+    let mut project: serde_json::Value = serde_json::from_str(&json).expect("JSON");
+    project["detection_tags"] = serde_json::json!([[7, "Logo"]]);
+    assert!(DrawingCanvas::from_json(&project.to_string()).is_err(), "tag on a missing detection");
+}
@@ -0,0 +1,181 @@
+//! Integration tests for crash reports and diagnostic bundles
+
+mod common;
+
+use common::temp_path;
+use egui::{Color32, Pos2, Stroke};
+use form_factor::{
+    Circle, CrashReporter, DiagnosticBundle, DrawingCanvas, LogBuffer, ProjectSummary, Shape, TraceId,
+};
+#[cfg(feature = "plugins")]
+use form_factor::{AppEvent, EventBus, TracedEvent};
+use tracing_subscriber::layer::SubscriberExt;
+
+fn canvas_with_secret() -> DrawingCanvas {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_project_name("Acme Payroll");
+    let mut circle = Circle::new(Pos2::new(10.0, 10.0), 5.0, Stroke::NONE, Color32::RED).expect("Valid circle");
+    circle.name = "employee_ssn".to_string();
+    let mut shape = Shape::Circle(circle);
+    shape.set_sensitive(true);
+    canvas.set_shapes(vec![shape]);
+    canvas
+}
+
+// ============================================================================
+// Log capture
+// ============================================================================
+
+#[test]
+fn layer_captures_events_with_fields() {
+    let logs = LogBuffer::new(10);
+    let subscriber = tracing_subscriber::registry().with(logs.layer());
+
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::warn!(count = 3, "Detection failed");
+    });
+
+    let records = logs.records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].level, tracing::Level::WARN);
+    assert_eq!(records[0].message, "Detection failed count=3");
+}
+
+#[test]
+fn buffer_evicts_oldest_events() {
+    let logs = LogBuffer::new(3);
+    let subscriber = tracing_subscriber::registry().with(logs.layer());
+
+    tracing::subscriber::with_default(subscriber, || {
+        for i in 0..5 {
+            tracing::info!("event {}", i);
+        }
+    });
+
+    let messages: Vec<String> = logs.records().into_iter().map(|r| r.message).collect();
+    assert_eq!(messages, vec!["event 2", "event 3", "event 4"]);
+    assert_eq!(logs.recent(1)[0].message, "event 4");
+}
+
+#[test]
+fn events_in_a_traced_span_carry_its_trace_id() {
+    let logs = LogBuffer::new(10);
+    let subscriber = tracing_subscriber::registry().with(logs.layer());
+    let trace_id = TraceId::next();
+
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::info!("before");
+        let _detection = tracing::info_span!("detection", trace_id = %trace_id).entered();
+        let _insert = tracing::debug_span!("insert_detections").entered();
+        tracing::info!("inside");
+    });
+
+    let records = logs.records();
+    assert_eq!(records[0].trace_id, None);
+    assert_eq!(records[1].trace_id, Some(trace_id.to_string()));
+    assert!(records[1].to_string().contains(&format!("[{}] inside", trace_id)));
+}
+
+#[test]
+#[cfg(feature = "plugins")]
+fn trace_id_follows_events_across_the_event_bus() {
+    let logs = LogBuffer::new(20);
+    let subscriber = tracing_subscriber::registry().with(logs.layer());
+    let trace_id = TraceId::next();
+
+    tracing::subscriber::with_default(subscriber, || {
+        let mut bus = EventBus::new();
+        let sender = bus.sender();
+        tracing::info_span!("detection", trace_id = %trace_id).in_scope(|| {
+            sender.emit(AppEvent::DetectionComplete { count: 2, detection_type: "text".to_string() });
+        });
+        sender.emit(AppEvent::SelectionCleared);
+
+        for TracedEvent { event, span } in bus.drain_traced() {
+            let _hop = span.enter();
+            tracing::info!(?event, "handled");
+        }
+    });
+
+    let handled: Vec<_> = logs.records().into_iter().filter(|r| r.message.starts_with("handled")).collect();
+    assert_eq!(handled.len(), 2);
+    assert_eq!(handled[0].trace_id, Some(trace_id.to_string()));
+    assert_eq!(handled[1].trace_id, None);
+}
+
+#[test]
+fn trace_ids_are_unique() {
+    let first = TraceId::next();
+    let second = TraceId::next();
+    assert_ne!(first, second);
+    assert_ne!(first.to_string(), second.to_string());
+}
+
+// ============================================================================
+// Anonymization
+// ============================================================================
+
+#[test]
+fn project_summary_contains_no_names() {
+    let summary = ProjectSummary::from_canvas(&canvas_with_secret());
+    let json = serde_json::to_string(&summary).expect("Summary should serialize");
+
+    assert_eq!(summary.shapes_by_kind.get("Circle"), Some(&1));
+    assert_eq!(summary.sensitive_shapes, 1);
+    assert!(!json.contains("Acme"));
+    assert!(!json.contains("employee_ssn"));
+}
+
+// ============================================================================
+// Crash reports and bundles
+// ============================================================================
+
+#[test]
+fn crash_report_includes_state_and_events() {
+    let dir = temp_path("crashes");
+    let logs = LogBuffer::new(10);
+    let subscriber = tracing_subscriber::registry().with(logs.layer());
+    tracing::subscriber::with_default(subscriber, || tracing::error!("Last words"));
+
+    let reporter = CrashReporter::new(&dir).with_logs(logs);
+    reporter.update_state(ProjectSummary::from_canvas(&canvas_with_secret()));
+
+    let report = reporter.capture("boom".to_string(), Some("src/main.rs:1:1".to_string()));
+    let path = reporter.write_report(&report).expect("Report should be written");
+    let text = std::fs::read_to_string(&path).expect("Report should be readable");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert!(text.contains("Panic: boom"));
+    assert!(text.contains("Location: src/main.rs:1:1"));
+    assert!(text.contains("Last words"));
+    assert!(text.contains("\"sensitive_shapes\": 1"));
+    assert!(text.contains("Backtrace"));
+}
+
+#[test]
+fn bundle_zips_logs_summary_and_crashes() {
+    let dir = temp_path("bundle");
+    let reporter = CrashReporter::new(dir.join("crashes"));
+    let report = reporter.capture("boom".to_string(), None);
+    reporter.write_report(&report).expect("Report should be written");
+
+    let logs = LogBuffer::new(10);
+    let bundle = DiagnosticBundle::new()
+        .with_logs(&logs)
+        .with_project(ProjectSummary::from_canvas(&canvas_with_secret()))
+        .with_crash_reports(&reporter);
+    let zip_path = dir.join("bundle.zip");
+    bundle
+        .export(zip_path.to_str().expect("UTF-8 temp path"))
+        .expect("Bundle should export");
+
+    let bytes = std::fs::read(&zip_path).expect("Bundle should exist");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(bundle.entries().len(), 4);
+    assert!(bytes.starts_with(b"PK"));
+    for entry in bundle.entries() {
+        let name = entry.as_bytes();
+        assert!(bytes.windows(name.len()).any(|w| w == name), "Missing {}", entry);
+    }
+}
@@ -0,0 +1,113 @@
+//! Integration tests for modal confirmation dialogs
+
+use form_factor::{DialogOutcome, ModalDialog};
+
+fn key(key: egui::Key) -> egui::RawInput {
+    egui::RawInput {
+        events: vec![egui::Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers: egui::Modifiers::NONE,
+        }],
+        ..Default::default()
+    }
+}
+
+/// Show `dialog` for one frame of `input`
+fn frame(
+    ctx: &egui::Context,
+    dialog: &ModalDialog<&'static str>,
+    input: egui::RawInput,
+) -> Option<DialogOutcome<&'static str>> {
+    let mut outcome = None;
+    let _ = ctx.run(input, |ctx| outcome = dialog.show(ctx));
+    outcome
+}
+
+fn clear_dialog() -> ModalDialog<&'static str> {
+    ModalDialog::new("clear", "Clear Layer", "Remove everything on the Shapes layer?")
+        .with_destructive_button("Clear", "clear")
+}
+
+fn save_dialog() -> ModalDialog<&'static str> {
+    ModalDialog::new("unsaved", "Unsaved Changes", "Save changes before quitting?")
+        .with_default_button("Save", "save")
+        .with_destructive_button("Don't Save", "discard")
+}
+
+// ============================================================================
+// Keyboard
+// ============================================================================
+
+#[test]
+fn enter_chooses_the_default_button() {
+    let ctx = egui::Context::default();
+    let dialog = save_dialog();
+
+    let opened = frame(&ctx, &dialog, egui::RawInput::default());
+    let entered = frame(&ctx, &dialog, key(egui::Key::Enter));
+
+    assert_eq!(opened, None);
+    assert_eq!(entered, Some(DialogOutcome::Chosen("save")));
+}
+
+#[test]
+fn enter_cancels_when_no_button_is_the_default() {
+    let ctx = egui::Context::default();
+    let dialog = clear_dialog();
+
+    let _ = frame(&ctx, &dialog, egui::RawInput::default());
+    let entered = frame(&ctx, &dialog, key(egui::Key::Enter));
+
+    assert_eq!(entered, Some(DialogOutcome::Cancelled));
+}
+
+#[test]
+fn escape_cancels() {
+    let ctx = egui::Context::default();
+
+    let escaped = frame(&ctx, &save_dialog(), key(egui::Key::Escape));
+
+    assert_eq!(escaped, Some(DialogOutcome::Cancelled));
+}
+
+#[test]
+fn focus_is_pulled_back_into_the_dialog() {
+    let ctx = egui::Context::default();
+    let dialog = save_dialog();
+    let _ = frame(&ctx, &dialog, egui::RawInput::default());
+
+    ctx.memory_mut(|m| m.request_focus(egui::Id::new("canvas_text_field")));
+    let _ = frame(&ctx, &dialog, egui::RawInput::default());
+    let entered = frame(&ctx, &dialog, key(egui::Key::Enter));
+
+    assert_ne!(ctx.memory(|m| m.focused()), Some(egui::Id::new("canvas_text_field")));
+    assert_eq!(entered, Some(DialogOutcome::Chosen("save")));
+}
+
+// ============================================================================
+// Accessibility
+// ============================================================================
+
+#[test]
+fn dialogs_are_announced_as_modal_alert_dialogs() {
+    let ctx = egui::Context::default();
+    ctx.enable_accesskit();
+    let dialog = clear_dialog();
+
+    let _ = frame(&ctx, &dialog, egui::RawInput::default());
+    let output = ctx.run(egui::RawInput::default(), |ctx| {
+        let _ = dialog.show(ctx);
+    });
+
+    let update = output.platform_output.accesskit_update.expect("AccessKit update");
+    let (_, node) = update
+        .nodes
+        .into_iter()
+        .find(|(_, node)| node.role() == egui::accesskit::Role::AlertDialog)
+        .expect("Dialog node");
+    assert_eq!(node.label(), Some("Clear Layer"));
+    assert!(node.is_modal());
+}
@@ -0,0 +1,184 @@
+//! Integration tests for drag-and-drop of images and projects
+
+mod common;
+
+use common::form_png;
+use egui::{Color32, Pos2, Stroke};
+use form_factor::{DrawingCanvas, DropKind, DroppedFile, Rectangle, Shape};
+use std::sync::Arc;
+
+fn project_bytes(shapes: usize) -> Arc<[u8]> {
+    let mut project = DrawingCanvas::new();
+    project.set_shapes(
+        (0..shapes)
+            .map(|i| {
+                let min = Pos2::new(0.0, i as f32 * 20.0);
+                let rect = Rectangle::from_corners(
+                    min,
+                    min + egui::vec2(10.0, 10.0),
+                    Stroke::new(1.0, Color32::BLACK),
+                    Color32::TRANSPARENT,
+                )
+                .expect("Valid rectangle");
+                Shape::Rectangle(rect)
+            })
+            .collect(),
+    );
+    project.to_json().expect("Serializable").into_bytes().into()
+}
+
+/// Files dropped by a browser, which delivers contents instead of paths
+fn dropped(name: &str, bytes: Arc<[u8]>) -> egui::DroppedFile {
+    egui::DroppedFile {
+        name: name.to_string(),
+        bytes: Some(bytes),
+        ..Default::default()
+    }
+}
+
+/// Run one frame in which `files` are dropped on the window
+fn drop_files(
+    ctx: &egui::Context,
+    canvas: &mut DrawingCanvas,
+    files: Vec<egui::DroppedFile>,
+) -> Option<Result<(), form_factor::CanvasError>> {
+    let mut result = None;
+    let input = egui::RawInput {
+        dropped_files: files,
+        ..Default::default()
+    };
+    let _ = ctx.run(input, |ctx| result = canvas.handle_file_drop(ctx));
+    result
+}
+
+// ============================================================================
+// File kinds
+// ============================================================================
+
+#[test]
+fn file_kind_comes_from_the_extension() {
+    assert_eq!(DropKind::of("scan.PNG"), Some(DropKind::Image));
+    assert_eq!(DropKind::of("/forms/w2.tiff"), Some(DropKind::Image));
+    assert_eq!(DropKind::of("w2.ffp"), Some(DropKind::Project));
+    assert_eq!(DropKind::of("w2.json"), Some(DropKind::Project));
+    assert_eq!(DropKind::of("notes.txt"), None);
+    assert_eq!(DropKind::of("README"), None);
+    assert!(DroppedFile::from_path("notes.txt").is_none());
+}
+
+// ============================================================================
+// Dropping
+// ============================================================================
+
+#[test]
+fn dropping_an_image_makes_it_the_form_image() {
+    let ctx = egui::Context::default();
+    let mut canvas = DrawingCanvas::new();
+
+    let result = drop_files(&ctx, &mut canvas, vec![dropped("scan.png", form_png(40, 30).into())]);
+
+    assert!(matches!(result, Some(Ok(()))));
+    assert_eq!(*canvas.form_image_size(), Some(egui::vec2(40.0, 30.0)));
+    assert!(canvas.drop_queue().is_empty());
+}
+
+#[test]
+fn dropping_a_project_opens_it() {
+    let ctx = egui::Context::default();
+    let mut canvas = DrawingCanvas::new();
+
+    let result = drop_files(&ctx, &mut canvas, vec![dropped("w2.ffp", project_bytes(3))]);
+
+    assert!(matches!(result, Some(Ok(()))));
+    assert_eq!(canvas.shapes().len(), 3);
+}
+
+#[test]
+fn unsupported_files_are_skipped() {
+    let ctx = egui::Context::default();
+    let mut canvas = DrawingCanvas::new();
+
+    let result = drop_files(&ctx, &mut canvas, vec![dropped("notes.txt", Arc::from(&b"hello"[..]))]);
+
+    assert!(result.is_none());
+    assert!(canvas.drop_queue().is_empty());
+}
+
+#[test]
+fn malformed_files_report_errors() {
+    let ctx = egui::Context::default();
+    let mut canvas = DrawingCanvas::new();
+
+    let result = drop_files(&ctx, &mut canvas, vec![dropped("scan.png", Arc::from(&b"not a png"[..]))]);
+
+    assert!(matches!(result, Some(Err(_))));
+}
+
+// ============================================================================
+// Batches
+// ============================================================================
+
+#[test]
+fn dropping_several_files_opens_the_first_and_queues_the_rest() {
+    let ctx = egui::Context::default();
+    let mut canvas = DrawingCanvas::new();
+
+    drop_files(
+        &ctx,
+        &mut canvas,
+        vec![
+            dropped("first.png", form_png(10, 10).into()),
+            dropped("notes.txt", Arc::from(&b"hello"[..])),
+            dropped("second.png", form_png(20, 10).into()),
+            dropped("third.ffp", project_bytes(2)),
+        ],
+    );
+
+    assert_eq!(*canvas.form_image_size(), Some(egui::vec2(10.0, 10.0)));
+    let queued: Vec<&str> = canvas.drop_queue().iter().map(|file| file.name.as_str()).collect();
+    assert_eq!(queued, ["second.png", "third.ffp"]);
+
+    assert!(matches!(canvas.open_next_dropped(&ctx), Some(Ok(()))));
+    assert_eq!(*canvas.form_image_size(), Some(egui::vec2(20.0, 10.0)));
+    assert!(matches!(canvas.open_next_dropped(&ctx), Some(Ok(()))));
+    assert_eq!(canvas.shapes().len(), 2);
+    assert!(canvas.open_next_dropped(&ctx).is_none());
+}
+
+#[test]
+fn new_drops_go_ahead_of_an_earlier_batch() {
+    let ctx = egui::Context::default();
+    let mut canvas = DrawingCanvas::new();
+    canvas.queue_dropped_files(DroppedFile::from_bytes("old.png", form_png(5, 5)));
+
+    drop_files(
+        &ctx,
+        &mut canvas,
+        vec![dropped("new.png", form_png(10, 10).into()), dropped("next.png", form_png(15, 10).into())],
+    );
+
+    let queued: Vec<&str> = canvas.drop_queue().iter().map(|file| file.name.as_str()).collect();
+    assert_eq!(queued, ["next.png", "old.png"]);
+
+    canvas.clear_drop_queue();
+    assert!(canvas.drop_queue().is_empty());
+}
+
+#[test]
+fn queued_drops_wait_to_be_opened() {
+    let ctx = egui::Context::default();
+    let mut canvas = DrawingCanvas::new();
+
+    let mut queued = false;
+    let input = egui::RawInput {
+        dropped_files: vec![dropped("form.ffp", project_bytes(2))],
+        ..Default::default()
+    };
+    let _ = ctx.run(input, |ctx| queued = canvas.queue_file_drop(ctx));
+
+    assert!(queued);
+    assert!(canvas.shapes().is_empty());
+    assert_eq!(canvas.next_dropped().map(|file| file.kind), Some(DropKind::Project));
+    assert!(matches!(canvas.open_next_dropped(&ctx), Some(Ok(()))));
+    assert_eq!(canvas.shapes().len(), 2);
+}
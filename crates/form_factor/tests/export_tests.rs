@@ -0,0 +1,184 @@
+//! Integration tests for PNG and SVG canvas export
+
+mod common;
+
+use common::{region, saved_form_simulator, temp_path};
+use egui::{Color32, Pos2, Stroke, vec2};
+use form_factor::{Arrow, Callout, DrawingCanvas, Ellipse, Line, MAX_EXPORT_DIMENSION, Polyline, Shape, StrokeStyle};
+
+/// `shape` with a blue outline and a red fill, to tell it apart from the background in renders
+fn painted(mut shape: Shape) -> Shape {
+    shape.set_stroke(Stroke::new(2.0, Color32::BLUE));
+    shape.set_fill(Color32::RED);
+    shape
+}
+
+// ============================================================================
+// PNG
+// ============================================================================
+
+#[test]
+fn empty_canvas_has_nothing_to_export() {
+    assert!(DrawingCanvas::new().render_png(1.0).is_err());
+    assert!(DrawingCanvas::new().to_svg().is_err());
+}
+
+#[test]
+fn shapes_without_image_export_in_canvas_units() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![painted(region("box", 100.0, 200.0, 40.0, 20.0))]);
+
+    let image = canvas.render_png(1.0).expect("Exportable");
+
+    // 10 unit margin on every side
+    assert_eq!(image.dimensions(), (60, 40));
+    assert_eq!(image.get_pixel(30, 20).0, [255, 0, 0, 255]);
+    assert_eq!(image.get_pixel(10, 20).0, [0, 0, 255, 255]);
+    assert_eq!(image.get_pixel(2, 2).0, [255, 255, 255, 255]);
+}
+
+#[test]
+fn scale_sets_output_resolution() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![painted(region("box", 0.0, 0.0, 40.0, 20.0))]);
+
+    assert_eq!(canvas.render_png(2.0).expect("Exportable").dimensions(), (120, 80));
+    assert!(canvas.render_png(0.0).is_err());
+    assert!(canvas.render_png(f32::NAN).is_err());
+    assert!(canvas.render_png(MAX_EXPORT_DIMENSION as f32).is_err());
+}
+
+#[test]
+fn export_composes_shapes_over_form_image_in_image_pixels() {
+    let path = temp_path("export_png.png");
+    let mut sim = saved_form_simulator(&path, 100, 50);
+    let center = sim.canvas_rect().center();
+    sim.canvas_mut()
+        .set_shapes(vec![painted(region("center", center.x - 40.0, center.y - 40.0, 80.0, 80.0))]);
+
+    let image = sim.canvas().render_png(1.0).expect("Exportable");
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(image.dimensions(), (100, 50));
+    assert_eq!(image.get_pixel(50, 25).0, [255, 0, 0, 255]);
+    assert_eq!(image.get_pixel(5, 5).0, [255, 255, 255, 255]);
+}
+
+#[test]
+fn export_png_writes_file() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![painted(region("box", 0.0, 0.0, 40.0, 20.0))]);
+    let path = temp_path("canvas_export.png");
+
+    let size = canvas.export_png(path.to_str().expect("UTF-8 path"), 1.0).expect("Exported");
+    let written = image::image_dimensions(&path).expect("Readable PNG");
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(size, (60, 40));
+    assert_eq!(written, size);
+}
+
+// ============================================================================
+// SVG
+// ============================================================================
+
+#[test]
+fn svg_embeds_image_and_keeps_shapes_as_vectors() {
+    let path = temp_path("export_svg.png");
+    let mut sim = saved_form_simulator(&path, 100, 50);
+    let center = sim.canvas_rect().center();
+    sim.canvas_mut()
+        .set_shapes(vec![painted(region("name & <value>", center.x, center.y, 6.0, 6.0))]);
+
+    let svg = sim.canvas().to_svg().expect("Exportable");
+    let _ = std::fs::remove_file(&path);
+
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.contains("viewBox=\"0 0 100 50\""));
+    // Base64 of the PNG signature
+    assert!(svg.contains("href=\"data:image/png;base64,iVBORw0KGgo"));
+    assert!(svg.contains("<g id=\"shapes\">"));
+    assert!(svg.contains("<polygon points=\"50.00,25.00 "));
+    assert!(svg.contains("<title>name &amp; &lt;value&gt;</title>"));
+    assert!(svg.trim_end().ends_with("</svg>"));
+}
+
+#[test]
+fn markups_export_as_lines_and_text() {
+    let stroke = Stroke::new(2.0, Color32::BLUE);
+    let arrow = Arrow::new(Pos2::new(0.0, 0.0), Pos2::new(40.0, 0.0), stroke).expect("Valid arrow");
+    let text_box = egui::Rect::from_min_size(Pos2::new(60.0, -20.0), vec2(40.0, 20.0));
+    let callout = Callout::new(Pos2::new(0.0, -10.0), text_box, "Smudged <date>", stroke, Color32::YELLOW)
+        .expect("Valid callout");
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![Shape::Arrow(arrow), Shape::Callout(callout)]);
+
+    let svg = canvas.to_svg().expect("Exportable");
+    let image = canvas.render_png(1.0).expect("Exportable");
+
+    // Shapes are offset by the 10 unit margin around their bounds, whose top left is (0, -20)
+    assert!(svg.contains("<line x1=\"10.00\" y1=\"30.00\""));
+    assert!(svg.contains("<tspan x=\"74.00\" dy=\"1.2em\">Smudged &lt;date&gt;</tspan>"));
+    assert_eq!(image.get_pixel(30, 30).0, [0, 0, 255, 255], "arrow shaft");
+    assert_eq!(image.get_pixel(40, 20).0, [0, 0, 255, 255], "callout leader");
+    assert_eq!(image.get_pixel(90, 20).0, [255, 255, 0, 255], "callout box");
+}
+
+#[test]
+fn lines_export_open_with_length_labels() {
+    let stroke = Stroke::new(2.0, Color32::BLUE);
+    let mut line = Line::new(Pos2::new(0.0, 0.0), Pos2::new(40.0, 0.0), stroke).expect("Valid line");
+    line.show_length = true;
+    let points = vec![Pos2::new(0.0, 20.0), Pos2::new(20.0, 20.0), Pos2::new(20.0, 40.0)];
+    let polyline = Polyline::from_points(points, stroke).expect("Valid polyline");
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![Shape::Line(line), Shape::Polyline(polyline)]);
+
+    let svg = canvas.to_svg().expect("Exportable");
+    let image = canvas.render_png(1.0).expect("Exportable");
+
+    // Bounds span (0, 0) to (40, 40) plus a 10 unit margin
+    assert!(svg.contains("<line x1=\"10.00\" y1=\"10.00\" x2=\"50.00\" y2=\"10.00\""));
+    assert!(svg.contains("<polyline points=\"10.00,30.00 30.00,30.00 30.00,50.00\""));
+    assert!(svg.contains(">40.0</text>"), "only the line shows its length");
+    assert_eq!(svg.matches("</text>").count(), 1);
+    assert_eq!(image.get_pixel(30, 10).0, [0, 0, 255, 255], "line");
+    assert_eq!(image.get_pixel(30, 40).0, [0, 0, 255, 255], "polyline corner leg");
+    assert_eq!(image.get_pixel(20, 40).0, [255, 255, 255, 255], "polylines stay open");
+}
+
+#[test]
+fn ellipses_export_with_their_rotation() {
+    let upright = std::f32::consts::FRAC_PI_2;
+    let ellipse =
+        Ellipse::new(Pos2::new(30.0, 20.0), vec2(30.0, 10.0), upright, Stroke::NONE, Color32::RED).expect("Valid ellipse");
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![Shape::Ellipse(ellipse)]);
+
+    let svg = canvas.to_svg().expect("Exportable");
+    let image = canvas.render_png(1.0).expect("Exportable");
+
+    // Upright, the ellipse spans (20, -10) to (40, 50), so it is offset by (-10, 20) after the margin
+    assert!(svg.contains("<ellipse cx=\"20.00\" cy=\"40.00\" rx=\"30.00\" ry=\"10.00\""));
+    assert!(svg.contains("transform=\"rotate(90.00 20.00 40.00)\""));
+    assert_eq!(image.dimensions(), (40, 80));
+    assert_eq!(image.get_pixel(20, 10).0, [255, 0, 0, 255], "along the long axis");
+    assert_eq!(image.get_pixel(27, 10).0, [255, 255, 255, 255], "outside the curve");
+}
+
+#[test]
+fn patterned_strokes_export_as_dash_arrays() {
+    let mut dashed = painted(region("dashed", 0.0, 0.0, 20.0, 20.0));
+    dashed.set_stroke_style(StrokeStyle::Dashed);
+    let mut dotted = painted(region("dotted", 30.0, 0.0, 20.0, 20.0));
+    dotted.set_stroke_style(StrokeStyle::Dotted);
+    let solid = painted(region("solid", 60.0, 0.0, 20.0, 20.0));
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![dashed, dotted, solid]);
+
+    let svg = canvas.to_svg().expect("Exportable");
+
+    assert!(svg.contains("stroke-dasharray=\"8.00 5.00\""));
+    assert!(svg.contains("stroke-dasharray=\"0.00 5.00\" stroke-linecap=\"round\""));
+    assert_eq!(svg.matches("stroke-dasharray").count(), 2, "solid outlines have no pattern");
+}
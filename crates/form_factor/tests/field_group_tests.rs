@@ -0,0 +1,157 @@
+//! Integration tests for field groups and repeating sections
+
+mod common;
+
+use common::field_at;
+use egui::{Pos2, vec2};
+use form_factor::{
+    CanvasErrorKind, DrawingCanvas, FieldCondition, FieldGroup, ProjectTemplate, RepeatingSection, Shape,
+    row_field_name,
+};
+
+/// Invoice with a customer block and one declared line item row
+fn invoice() -> DrawingCanvas {
+    let mut amount = field_at("amount", 240.0, 100.0);
+    amount.set_condition(Some(FieldCondition::Filled("quantity".into())));
+
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![
+        field_at("customer", 0.0, 0.0),
+        field_at("address", 0.0, 30.0),
+        field_at("description", 0.0, 100.0),
+        field_at("quantity", 120.0, 100.0),
+        amount,
+    ]);
+    canvas
+        .add_field_group(FieldGroup::new("bill_to", ["customer", "address"]))
+        .expect("Valid group");
+    canvas
+        .add_field_group(
+            FieldGroup::new("line_items", ["description", "quantity", "amount"])
+                .repeating(RepeatingSection::new(vec2(0.0, 24.0)).with_max_rows(4)),
+        )
+        .expect("Valid section");
+    canvas
+}
+
+fn shape<'a>(canvas: &'a DrawingCanvas, name: &str) -> &'a Shape {
+    canvas.shapes().iter().find(|shape| shape.name() == name).expect("Field exists")
+}
+
+// ============================================================================
+// Groups
+// ============================================================================
+
+#[test]
+fn row_names_follow_grid_repeat_naming() {
+    assert_eq!(row_field_name("amount", 1), "amount");
+    assert_eq!(row_field_name("amount", 3), "amount 3");
+
+    let group = FieldGroup::new("items", ["amount"]).repeating(RepeatingSection::new(vec2(0.0, 20.0)));
+    assert_eq!(group.locate("amount 3"), Some((3, "amount")));
+    assert_eq!(group.locate("amount"), Some((1, "amount")));
+    assert_eq!(group.locate("amounts"), None);
+    assert_eq!(FieldGroup::new("once", ["amount"]).locate("amount 3"), None);
+}
+
+#[test]
+fn groups_need_existing_fields_and_a_pitch() {
+    let mut canvas = invoice();
+
+    let missing = canvas.add_field_group(FieldGroup::new("totals", ["total"])).expect_err("No total field");
+    assert!(matches!(missing.kind, CanvasErrorKind::InvalidFieldGroup(_)));
+
+    let flat = FieldGroup::new("items", ["amount"]).repeating(RepeatingSection::new(vec2(0.0, 0.0)));
+    assert!(canvas.add_field_group(flat).is_err());
+
+    // Same name replaces the group
+    canvas.add_field_group(FieldGroup::new("bill_to", ["customer"])).expect("Valid group");
+    assert_eq!(canvas.field_groups().len(), 2);
+    assert_eq!(canvas.field_group("bill_to").map(|group| group.fields.len()), Some(1));
+}
+
+// ============================================================================
+// Rows
+// ============================================================================
+
+#[test]
+fn adding_a_row_copies_the_first_row_below_the_last() {
+    let mut canvas = invoice();
+    let mut shapes = canvas.shapes().clone();
+    shapes[4].set_value("12.50");
+    canvas.set_shapes(shapes);
+
+    let added = canvas.add_section_row("line_items").expect("Row added");
+    canvas.add_section_row("line_items").expect("Row added");
+
+    assert_eq!(added.len(), 3);
+    assert_eq!(canvas.section_rows("line_items"), 3);
+    let third = shape(&canvas, "amount 3");
+    assert_eq!(third.bounding_rect().min, Pos2::new(240.0, 148.0));
+    assert_eq!(third.value(), "", "Values are not copied");
+    assert_eq!(third.condition(), Some(&FieldCondition::Filled("quantity 3".into())));
+    assert_eq!(canvas.group_of_field(added[1]).map(|(group, row)| (group.name.as_str(), row)), Some(("line_items", 2)));
+}
+
+#[test]
+fn rows_stay_within_the_section_limits() {
+    let mut canvas = invoice();
+
+    assert_eq!(canvas.set_section_rows("line_items", 10).expect("Rows set"), 4);
+    assert!(canvas.add_section_row("line_items").is_err(), "At the maximum");
+
+    assert!(canvas.remove_section_row("line_items"));
+    assert_eq!(canvas.section_rows("line_items"), 3);
+    assert!(canvas.shapes().iter().all(|shape| shape.name() != "amount 4"));
+
+    assert_eq!(canvas.set_section_rows("line_items", 0).expect("Rows set"), 1);
+    assert!(!canvas.remove_section_row("line_items"), "The first row stays");
+    assert_eq!(canvas.shapes().len(), 5);
+}
+
+#[test]
+fn groups_that_do_not_repeat_have_no_rows_to_add() {
+    let mut canvas = invoice();
+
+    assert_eq!(canvas.section_rows("bill_to"), 1);
+    assert!(canvas.add_section_row("bill_to").is_err());
+    assert!(!canvas.remove_section_row("bill_to"));
+}
+
+// ============================================================================
+// Templates and instances
+// ============================================================================
+
+#[test]
+fn instances_from_templates_get_the_minimum_rows() {
+    let mut canvas = invoice();
+    canvas
+        .add_field_group(
+            FieldGroup::new("line_items", ["description", "quantity", "amount"])
+                .repeating(RepeatingSection::new(vec2(0.0, 24.0)).with_min_rows(3)),
+        )
+        .expect("Valid section");
+
+    let template = ProjectTemplate::from_canvas("Invoice", &canvas);
+    let mut instance = DrawingCanvas::new();
+    instance.start_project_from_template("INV-1042", &template, &egui::Context::default());
+
+    assert_eq!(instance.field_groups(), canvas.field_groups());
+    assert_eq!(instance.section_rows("line_items"), 3);
+
+    // Each instance grows its own rows
+    instance.add_section_row("line_items").expect("Row added");
+    assert_eq!(instance.section_rows("line_items"), 4);
+    assert_eq!(canvas.section_rows("line_items"), 1);
+}
+
+#[test]
+fn groups_are_saved_with_the_project() {
+    let mut canvas = invoice();
+    canvas.add_section_row("line_items").expect("Row added");
+
+    let loaded = DrawingCanvas::from_json(&canvas.to_json().expect("Serializable")).expect("Loadable");
+
+    assert_eq!(loaded.field_groups(), canvas.field_groups());
+    assert_eq!(loaded.section_rows("line_items"), 2);
+}
@@ -0,0 +1,93 @@
+//! Integration tests for per-frame timing
+
+use form_factor::{ACTION_TOGGLE_FRAME_TIMING, FrameSection, FrameTimingOverlay, FrameTimings, Settings};
+use std::time::Duration;
+use strum::IntoEnumIterator;
+use tracing_subscriber::layer::SubscriberExt;
+
+/// Run `frame` as one timed frame with `timings` fed by tracing
+fn timed_frame(timings: &FrameTimings, frame: impl FnOnce()) {
+    let subscriber = tracing_subscriber::registry().with(timings.layer());
+    tracing::subscriber::with_default(subscriber, || {
+        timings.begin_frame();
+        frame();
+        timings.end_frame();
+    });
+}
+
+#[test]
+fn section_spans_are_timed() {
+    let timings = FrameTimings::default();
+    timed_frame(&timings, || {
+        let _section = FrameSection::CanvasRendering.span().entered();
+        std::thread::sleep(Duration::from_millis(5));
+    });
+
+    let sample = timings.latest().expect("Frame recorded");
+    assert!(sample.section(FrameSection::CanvasRendering) >= Duration::from_millis(5));
+    assert_eq!(sample.section(FrameSection::PluginRendering), Duration::ZERO);
+    assert!(sample.total >= sample.section(FrameSection::CanvasRendering));
+}
+
+#[test]
+fn time_in_a_section_adds_up_across_its_spans() {
+    let timings = FrameTimings::default();
+    timed_frame(&timings, || {
+        for _ in 0..2 {
+            let _section = FrameSection::EventProcessing.span().entered();
+            std::thread::sleep(Duration::from_millis(3));
+        }
+        std::thread::sleep(Duration::from_millis(3));
+    });
+
+    let sample = timings.latest().expect("Frame recorded");
+    assert!(sample.section(FrameSection::EventProcessing) >= Duration::from_millis(6));
+    assert!(sample.other() >= Duration::from_millis(3));
+}
+
+#[test]
+fn other_spans_are_not_timed() {
+    let timings = FrameTimings::default();
+    timed_frame(&timings, || {
+        let _span = tracing::info_span!("unrelated").entered();
+        std::thread::sleep(Duration::from_millis(2));
+    });
+
+    let sample = timings.latest().expect("Frame recorded");
+    for section in FrameSection::iter() {
+        assert_eq!(sample.section(section), Duration::ZERO, "{}", section);
+    }
+    assert_eq!(sample.other(), sample.total);
+}
+
+#[test]
+fn history_keeps_the_newest_frames() {
+    let timings = FrameTimings::new(3);
+    for _ in 0..5 {
+        timings.begin_frame();
+        timings.record(FrameSection::AsyncResults, Duration::from_millis(1));
+        timings.end_frame();
+    }
+    // A frame that never began is not recorded
+    timings.end_frame();
+
+    assert_eq!(timings.len(), 3);
+    let average = timings.average().expect("Frames recorded");
+    assert_eq!(average.section(FrameSection::AsyncResults), Duration::from_millis(1));
+    assert!(timings.slowest().expect("Frames recorded").total >= average.total);
+
+    timings.clear();
+    assert!(timings.is_empty());
+    assert!(timings.average().is_none());
+}
+
+#[test]
+fn overlay_toggles_with_its_shortcut() {
+    let settings = Settings::default();
+    assert_eq!(settings.shortcuts.key(ACTION_TOGGLE_FRAME_TIMING), Some(egui::Key::F9));
+
+    let mut overlay = FrameTimingOverlay::new(FrameTimings::default());
+    assert!(!overlay.is_open());
+    overlay.toggle();
+    assert!(overlay.is_open());
+}
@@ -0,0 +1,118 @@
+//! Integration tests for GeoJSON export of shape and detection geometry
+
+mod common;
+
+use common::{field_at, form_simulator, temp_path};
+use egui::{Color32, Pos2, Rect, Stroke, vec2};
+use form_factor::{Callout, DrawingCanvas, Line, Shape};
+use serde_json::Value;
+
+fn features(canvas: &DrawingCanvas) -> (Value, Vec<Value>) {
+    let collection: Value = serde_json::from_str(&canvas.to_geojson().expect("Exportable")).expect("Valid JSON");
+    let features = collection["features"].as_array().expect("Feature array").clone();
+    (collection, features)
+}
+
+#[test]
+fn shapes_become_features_with_field_properties() {
+    let mut canvas = DrawingCanvas::new();
+    let mut total = field_at("total", 10.0, 20.0);
+    total.set_value("42.00");
+    total.set_hidden(true);
+    canvas.set_shapes(vec![total]);
+
+    let (collection, features) = features(&canvas);
+
+    assert_eq!(collection["type"], "FeatureCollection");
+    assert_eq!(collection["units"], "canvas");
+    assert_eq!(features.len(), 1);
+    let properties = &features[0]["properties"];
+    assert_eq!(properties["layer"], "Shapes");
+    assert_eq!(properties["name"], "total");
+    assert_eq!(properties["shape"], "Rectangle");
+    assert_eq!(properties["value"], "42.00");
+    assert_eq!(properties["hidden"], true, "hidden shapes are included");
+
+    let geometry = &features[0]["geometry"];
+    assert_eq!(geometry["type"], "Polygon");
+    let ring = geometry["coordinates"][0].as_array().expect("Exterior ring");
+    assert_eq!(ring.first(), ring.last(), "rings are closed");
+    assert_eq!(ring[0], serde_json::json!([10.0, 20.0]));
+    assert!(properties["wkt"].as_str().expect("WKT").starts_with("POLYGON ((10 20, "));
+}
+
+#[test]
+fn open_shapes_and_callouts_map_to_matching_geometry() {
+    let line = Line::new(Pos2::new(0.0, 0.0), Pos2::new(30.0, 40.0), Stroke::new(1.0, Color32::BLACK))
+        .expect("Valid line");
+    let callout = Callout::new(
+        Pos2::new(5.0, 5.0),
+        Rect::from_min_size(Pos2::new(20.0, 20.0), vec2(60.0, 20.0)),
+        "Check",
+        Stroke::new(1.0, Color32::BLACK),
+        Color32::WHITE,
+    )
+    .expect("Valid callout");
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![Shape::Line(line), Shape::Callout(callout)]);
+
+    let (_, features) = features(&canvas);
+
+    assert_eq!(features[0]["geometry"]["type"], "LineString");
+    assert_eq!(features[0]["properties"]["wkt"], "LINESTRING (0 0, 30 40)");
+    let parts = features[1]["geometry"]["geometries"].as_array().expect("Collection");
+    assert_eq!(parts[0]["type"], "Polygon");
+    assert_eq!(parts[1], serde_json::json!({"type": "Point", "coordinates": [5.0, 5.0]}));
+}
+
+#[test]
+fn circle_geometry_traces_its_outline() {
+    let shape = Shape::Circle(
+        form_factor::Circle::new(Pos2::new(50.0, 50.0), 10.0, Stroke::new(1.0, Color32::BLACK), Color32::WHITE)
+            .expect("Valid circle"),
+    );
+
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![shape]);
+
+    let (_, features) = features(&canvas);
+
+    assert_eq!(features[0]["geometry"]["type"], "Polygon");
+    let ring = features[0]["geometry"]["coordinates"][0].as_array().expect("Exterior ring");
+    assert!(ring.len() > 16);
+    for point in ring {
+        let (x, y) = (point[0].as_f64().expect("x"), point[1].as_f64().expect("y"));
+        let radius = (x - 50.0).hypot(y - 50.0);
+        assert!((radius - 10.0).abs() < 1e-3, "point at radius {}", radius);
+    }
+}
+
+#[test]
+fn shapes_over_a_form_image_export_in_image_pixels() {
+    let mut sim = form_simulator(DrawingCanvas::new(), 100, 50);
+    let center = sim.canvas_rect().center();
+    sim.canvas_mut()
+        .set_shapes(vec![field_at("center", center.x - 50.0, center.y - 10.0)]);
+
+    let (collection, features) = features(sim.canvas());
+
+    assert_eq!(collection["units"], "image");
+    let ring = features[0]["geometry"]["coordinates"][0].as_array().expect("Exterior ring");
+    let xs: Vec<f64> = ring.iter().map(|p| p[0].as_f64().expect("x")).collect();
+    let mid = (xs.iter().cloned().fold(f64::MAX, f64::min) + xs.iter().cloned().fold(f64::MIN, f64::max)) / 2.0;
+    assert!((mid - 50.0).abs() < 0.5, "box is centered on the image, got {}", mid);
+}
+
+#[test]
+fn export_geojson_writes_file() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![field_at("a", 0.0, 0.0), field_at("b", 0.0, 50.0)]);
+    let path = temp_path("canvas.geojson");
+
+    let count = canvas.export_geojson(path.to_str().expect("UTF-8 path")).expect("Exported");
+    let written = std::fs::read_to_string(&path).expect("Readable");
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(count, 2);
+    assert!(written.contains("\"FeatureCollection\""));
+}
@@ -0,0 +1,132 @@
+//! Integration tests for rulers and guide lines
+
+use egui::{Pos2, vec2};
+use form_factor::{CanvasSimulator, DrawingCanvas, GuideOrientation, Settings};
+
+// ============================================================================
+// Snapping
+// ============================================================================
+
+#[test]
+fn snap_moves_nearby_points_onto_guides() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_zoom(1.0);
+    canvas.add_guide(GuideOrientation::Vertical, 100.0);
+    canvas.add_guide(GuideOrientation::Horizontal, 50.0);
+
+    assert_eq!(canvas.snap_to_guides(Pos2::new(104.0, 47.0)), Pos2::new(100.0, 50.0));
+    assert_eq!(canvas.snap_to_guides(Pos2::new(104.0, 80.0)), Pos2::new(100.0, 80.0));
+    assert_eq!(canvas.snap_to_guides(Pos2::new(130.0, 80.0)), Pos2::new(130.0, 80.0));
+}
+
+#[test]
+fn snap_distance_shrinks_when_zoomed_in() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.add_guide(GuideOrientation::Vertical, 100.0);
+
+    canvas.set_zoom(1.0);
+    assert_eq!(canvas.snap_to_guides(Pos2::new(104.0, 0.0)).x, 100.0);
+    canvas.set_zoom(4.0);
+    assert_eq!(canvas.snap_to_guides(Pos2::new(104.0, 0.0)).x, 104.0);
+}
+
+#[test]
+fn snap_can_be_disabled_in_settings() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_zoom(1.0);
+    canvas.add_guide(GuideOrientation::Vertical, 100.0);
+    let mut settings = Settings::default();
+    settings.canvas.snap_to_guides = false;
+    canvas.apply_settings(&settings);
+
+    assert_eq!(canvas.snap_to_guides(Pos2::new(104.0, 0.0)), Pos2::new(104.0, 0.0));
+}
+
+#[test]
+fn drawn_rectangle_corners_snap_to_guides() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_zoom(1.0);
+    let mut sim = CanvasSimulator::new(canvas);
+    sim.step();
+    let origin = sim.canvas_rect().center();
+    sim.canvas_mut().add_guide(GuideOrientation::Vertical, origin.x);
+    sim.canvas_mut().add_guide(GuideOrientation::Horizontal, origin.y + 60.0);
+
+    sim.draw_rectangle(origin + vec2(3.0, 2.0), origin + vec2(80.0, 57.0));
+
+    let bounds = sim.canvas().shapes()[0].bounding_rect();
+    assert_eq!(bounds.left(), origin.x);
+    assert_eq!(bounds.bottom(), origin.y + 60.0);
+    assert_eq!(bounds.top(), origin.y + 2.0);
+}
+
+// ============================================================================
+// Ruler interaction
+// ============================================================================
+
+#[test]
+fn dragging_from_top_ruler_creates_horizontal_guide() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_zoom(1.0);
+    let mut sim = CanvasSimulator::new(canvas);
+    sim.step();
+    let rect = sim.canvas_rect();
+    let target = rect.center();
+
+    sim.drag(Pos2::new(target.x, rect.top() + 5.0), target);
+
+    let guides = sim.canvas().guides();
+    assert_eq!(guides.len(), 1);
+    assert_eq!(guides[0].orientation, GuideOrientation::Horizontal);
+    assert!((guides[0].position - target.y).abs() < 0.5);
+}
+
+#[test]
+fn dragging_guide_back_onto_ruler_removes_it() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_zoom(1.0);
+    let mut sim = CanvasSimulator::new(canvas);
+    sim.step();
+    let rect = sim.canvas_rect();
+    let x = rect.center().x;
+    sim.canvas_mut().add_guide(GuideOrientation::Vertical, x);
+    sim.step();
+
+    sim.drag(Pos2::new(x, rect.center().y), Pos2::new(rect.left() + 5.0, rect.center().y));
+
+    assert!(sim.canvas().guides().is_empty());
+}
+
+// ============================================================================
+// Persistence
+// ============================================================================
+
+#[test]
+fn guides_round_trip_through_project_json() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.add_guide(GuideOrientation::Vertical, 12.5);
+    canvas.add_guide(GuideOrientation::Horizontal, 40.0);
+
+    let json = canvas.to_json().expect("Serializable");
+    let loaded = DrawingCanvas::from_json(&json).expect("Valid project");
+
+    assert_eq!(loaded.guides(), canvas.guides());
+}
+
+#[test]
+fn projects_without_guides_still_load() {
+    let mut project = serde_json::to_value(DrawingCanvas::new()).expect("Serializable");
+    project.as_object_mut().expect("Object").remove("guides");
+
+    let loaded = DrawingCanvas::from_json(&project.to_string()).expect("Valid project");
+
+    assert!(loaded.guides().is_empty());
+}
+
+#[test]
+fn non_finite_guide_position_is_rejected() {
+    let mut project = serde_json::to_value(DrawingCanvas::new()).expect("Serializable");
+    project["guides"] = serde_json::json!([{ "orientation": "Vertical", "position": 1e40 }]);
+
+    assert!(DrawingCanvas::from_json(&project.to_string()).is_err());
+}
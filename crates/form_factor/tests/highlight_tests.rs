@@ -0,0 +1,110 @@
+//! Integration tests for highlighter strokes
+
+use egui::{Color32, Pos2, Stroke, vec2};
+use form_factor::{
+    CanvasCommand, CanvasSimulator, DrawingCanvas, HIGHLIGHT_COLOR, HIGHLIGHT_WIDTH, Highlight, LayerType, Rectangle,
+    Shape,
+};
+
+fn black_box(min: Pos2, size: egui::Vec2) -> Shape {
+    let rect = Rectangle::from_corners(min, min + size, Stroke::NONE, Color32::BLACK).expect("Valid rectangle");
+    Shape::Rectangle(rect)
+}
+
+fn stroke(from: Pos2, to: Pos2) -> Highlight {
+    Highlight::new(vec![from, to], 10.0, HIGHLIGHT_COLOR)
+}
+
+// ============================================================================
+// Drawing
+// ============================================================================
+
+#[test]
+fn highlighter_draws_on_the_highlights_layer() {
+    let mut sim = CanvasSimulator::new(DrawingCanvas::new());
+    sim.canvas_mut().set_zoom(1.0);
+
+    sim.draw_highlight(Pos2::new(100.0, 100.0), Pos2::new(200.0, 100.0));
+
+    let canvas = sim.canvas();
+    assert!(canvas.shapes().is_empty());
+    assert_eq!(canvas.highlights().len(), 1);
+    let highlight = &canvas.highlights()[0];
+    assert_eq!(highlight.width, HIGHLIGHT_WIDTH);
+    assert_eq!(highlight.color, HIGHLIGHT_COLOR);
+    assert!(highlight.contains_point(Pos2::new(150.0, 100.0)));
+    assert!(!highlight.contains_point(Pos2::new(150.0, 100.0 + HIGHLIGHT_WIDTH)));
+}
+
+#[test]
+fn clearing_the_layer_removes_highlights() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.add_highlight(stroke(Pos2::ZERO, Pos2::new(20.0, 0.0)));
+
+    assert!(canvas.apply_command(CanvasCommand::ClearLayer(LayerType::Highlights)));
+    assert!(canvas.highlights().is_empty());
+    assert!(!canvas.apply_command(CanvasCommand::ClearLayer(LayerType::Highlights)));
+}
+
+#[test]
+fn highlights_are_saved_with_the_project() {
+    let mut canvas = DrawingCanvas::new();
+    let highlight = stroke(Pos2::ZERO, Pos2::new(20.0, 5.0));
+    canvas.add_highlight(highlight.clone());
+
+    let json = canvas.to_json().expect("Serializable");
+    let restored = DrawingCanvas::from_json(&json).expect("Loadable");
+    assert_eq!(restored.highlights(), &vec![highlight]);
+
+    // Too large for an f32, so it loads as infinity
+    let broken = json.replace("\"width\": 10.0", "\"width\": 1e39");
+    assert_ne!(broken, json);
+    assert!(DrawingCanvas::from_json(&broken).is_err());
+}
+
+// ============================================================================
+// Export
+// ============================================================================
+
+#[test]
+fn png_export_multiplies_highlights_onto_the_page() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![black_box(Pos2::ZERO, vec2(20.0, 20.0))]);
+    canvas.add_highlight(stroke(Pos2::new(0.0, 10.0), Pos2::new(40.0, 10.0)));
+
+    let image = canvas.render_png(1.0).expect("Exportable");
+
+    // Bounds span (-5, 0) to (45, 20) plus a 10 unit margin, so canvas (x, y) is pixel (x + 15, y + 10)
+    let [r, g, b, a] = HIGHLIGHT_COLOR.to_srgba_unmultiplied();
+    let alpha = a as f32 / 255.0;
+    let tint = |channel: u8| (255.0 * (1.0 - alpha + alpha * channel as f32 / 255.0)).round() as u8;
+    assert_eq!(image.get_pixel(45, 20).0, [tint(r), tint(g), tint(b), 255], "paper takes the color");
+    assert_eq!(image.get_pixel(20, 20).0, [0, 0, 0, 255], "ink stays dark");
+    assert_eq!(image.get_pixel(45, 5).0, [255, 255, 255, 255], "outside the stroke");
+}
+
+#[test]
+fn hidden_highlights_layer_is_left_out() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![black_box(Pos2::ZERO, vec2(20.0, 20.0))]);
+    canvas.add_highlight(stroke(Pos2::new(0.0, 10.0), Pos2::new(200.0, 10.0)));
+    canvas.apply_command(CanvasCommand::SetLayerVisible {
+        layer: LayerType::Highlights,
+        visible: false,
+    });
+
+    assert_eq!(canvas.render_png(1.0).expect("Exportable").dimensions(), (40, 40));
+    assert!(!canvas.to_svg().expect("Exportable").contains("<g id=\"highlights\">"));
+}
+
+#[test]
+fn svg_blends_highlights_with_multiply() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.add_highlight(stroke(Pos2::ZERO, Pos2::new(40.0, 0.0)));
+
+    let svg = canvas.to_svg().expect("Exportable");
+
+    assert!(svg.contains("<g id=\"highlights\">"));
+    assert!(svg.contains("<polyline points=\"15.00,15.00 55.00,15.00\""));
+    assert!(svg.contains("style=\"mix-blend-mode:multiply\""));
+}
@@ -0,0 +1,149 @@
+//! Integration tests for bulk actions on the instances of a batch review
+
+use form_factor::{BatchReview, DrawingCanvas, TaskStatus};
+use std::path::{Path, PathBuf};
+
+/// Fresh directory for instance project files
+fn instance_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("form_factor_instances_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Creatable");
+    dir
+}
+
+/// Batch of W-2 projects saved in `dir`
+fn saved_batch(dir: &Path, names: &[&str]) -> BatchReview {
+    let mut review = BatchReview::default();
+    for name in names {
+        let mut canvas = DrawingCanvas::new();
+        canvas.set_project_name(*name);
+        let path = dir.join(format!("{}.json", name));
+        std::fs::write(&path, canvas.to_json().expect("Serializable")).expect("Writable");
+        review.add_project_file(&path).expect("Loadable");
+    }
+    review
+}
+
+fn unsaved_batch(names: &[&str]) -> BatchReview {
+    let mut review = BatchReview::default();
+    for name in names {
+        review.add_instance(*name, None, DrawingCanvas::new());
+    }
+    review
+}
+
+// ============================================================================
+// Selection
+// ============================================================================
+
+#[test]
+fn selection_ignores_missing_instances() {
+    let mut review = unsaved_batch(&["jane", "john", "ann"]);
+
+    review.select_instance(2, true);
+    review.select_instance(0, true);
+    review.select_instance(7, true);
+    assert_eq!(review.selected_instances(), [0, 2]);
+
+    review.select_instance(0, false);
+    assert_eq!(review.selected_instances(), [2]);
+    review.select_all_instances();
+    assert_eq!(review.selected_instances(), [0, 1, 2]);
+    review.clear_instance_selection();
+    assert!(review.selected_instances().is_empty());
+}
+
+// ============================================================================
+// Changing instances
+// ============================================================================
+
+#[test]
+fn reassigning_marks_only_changed_instances() {
+    let mut review = BatchReview::default();
+    let mut current = DrawingCanvas::new();
+    current.set_template_name(Some("W-2 (2024)".to_string()));
+    review.add_instance("jane", None, current);
+    review.add_instance("john", None, DrawingCanvas::new());
+    review.select_all_instances();
+
+    assert_eq!(review.reassign_selected(Some("W-2 (2024)")), 1);
+
+    let instances = review.instances();
+    assert!(!instances[0].modified);
+    assert!(instances[1].modified);
+    assert_eq!(instances[1].canvas.template_name().as_deref(), Some("W-2 (2024)"));
+    assert_eq!(review.reassign_selected(None), 2);
+    assert!(review.instances().iter().all(|instance| instance.canvas.template_name().is_none()));
+}
+
+#[test]
+fn status_changes_only_the_checked_instances() {
+    let mut review = unsaved_batch(&["jane", "john"]);
+    review.select_instance(1, true);
+
+    assert_eq!(review.set_selected_status(TaskStatus::Done), 1);
+    assert_eq!(review.set_selected_status(TaskStatus::Done), 0);
+
+    let instances = review.instances();
+    assert_eq!(instances[0].canvas.workflow().status(0), TaskStatus::Unassigned);
+    assert_eq!(instances[1].canvas.workflow().status(0), TaskStatus::Done);
+    assert!(!instances[0].modified);
+    assert!(instances[1].modified);
+}
+
+// ============================================================================
+// Exporting and deleting
+// ============================================================================
+
+#[test]
+fn export_writes_one_project_per_checked_instance() {
+    let dir = instance_dir("export").join("out");
+    let mut review = unsaved_batch(&["jane", "john", "ann"]);
+    review.select_instance(0, true);
+    review.select_instance(2, true);
+
+    let written = review.export_selected(&dir).expect("Exported");
+
+    assert_eq!(written, [dir.join("jane.json"), dir.join("ann.json")]);
+    let json = std::fs::read_to_string(&written[0]).expect("Readable");
+    assert!(DrawingCanvas::from_json(&json).is_ok());
+    let _ = std::fs::remove_dir_all(dir.parent().expect("Has parent"));
+}
+
+#[test]
+fn export_keeps_the_password_of_protected_instances() {
+    let dir = instance_dir("export_protected");
+    let mut review = BatchReview::default();
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_project_name("jane");
+    canvas.set_project_password(Some("secret".to_string()));
+    review.add_instance("jane", None, canvas);
+    review.select_all_instances();
+
+    let written = review.export_selected(&dir).expect("Exported");
+
+    assert_eq!(written, [dir.join("jane.ffp")]);
+    let bytes = std::fs::read(&written[0]).expect("Readable");
+    assert!(std::str::from_utf8(&bytes).map_or(true, |text| !text.contains("jane")));
+    let restored = DrawingCanvas::from_protected_bytes(&bytes, "secret").expect("Decryptable");
+    assert_eq!(restored.project_name(), "jane");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn delete_removes_instances_and_their_files() {
+    let dir = instance_dir("delete");
+    let mut review = saved_batch(&dir, &["jane", "john", "ann"]);
+    std::fs::remove_file(dir.join("ann.json")).expect("Removable");
+    review.select_instance(0, true);
+    review.select_instance(2, true);
+
+    assert_eq!(review.delete_selected().expect("Deleted"), 2);
+
+    let names: Vec<&str> = review.instances().iter().map(|instance| instance.name.as_str()).collect();
+    assert_eq!(names, ["john"]);
+    assert!(review.selected_instances().is_empty());
+    assert!(!dir.join("jane.json").exists());
+    assert!(dir.join("john.json").exists());
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -95,7 +95,28 @@ fn deserialization_handles_extra_fields() {
     }"#;
 
     let manager: LayerManager = serde_json::from_str(json).expect("Should handle extra fields");
-    assert_eq!(manager.len(), 4);
+    assert_eq!(manager.len(), 5);
+}
+
+#[test]
+fn deserialization_fills_layers_missing_from_older_projects() {
+    // Projects saved before the Highlights layer existed
+    let json = r#"{
+        "layers": {
+            "Canvas": {"name": "Canvas", "layer_type": "Canvas", "visible": true, "locked": false},
+            "Detections": {"name": "Detections", "layer_type": "Detections", "visible": false, "locked": false},
+            "Shapes": {"name": "Shapes", "layer_type": "Shapes", "visible": true, "locked": true},
+            "Grid": {"name": "Grid", "layer_type": "Grid", "visible": false, "locked": false}
+        }
+    }"#;
+
+    let manager: LayerManager = serde_json::from_str(json).expect("Deserialization should succeed");
+
+    assert!(manager.validate().is_ok());
+    assert!(manager.is_visible(LayerType::Highlights));
+    assert_eq!(manager.get_layer(LayerType::Highlights).name(), "Highlights");
+    assert!(!manager.is_visible(LayerType::Detections));
+    assert!(manager.is_locked(LayerType::Shapes));
 }
 
 #[test]
@@ -162,13 +183,14 @@ fn get_layer_mut_for_all_layer_types() {
     let mut manager = LayerManager::new();
 
     // Modify each layer type
-    for layer_type in [LayerType::Canvas, LayerType::Detections, LayerType::Shapes, LayerType::Grid] {
+    for layer_type in [LayerType::Canvas, LayerType::Highlights, LayerType::Detections, LayerType::Shapes, LayerType::Grid] {
         let layer = manager.get_layer_mut(layer_type);
         layer.set_name(format!("Modified {}", layer_type));
     }
 
     // Verify all modifications
     assert_eq!(manager.get_layer(LayerType::Canvas).name(), "Modified Canvas");
+    assert_eq!(manager.get_layer(LayerType::Highlights).name(), "Modified Highlights");
     assert_eq!(manager.get_layer(LayerType::Detections).name(), "Modified Detections");
     assert_eq!(manager.get_layer(LayerType::Shapes).name(), "Modified Shapes");
     assert_eq!(manager.get_layer(LayerType::Grid).name(), "Modified Grid");
@@ -213,7 +235,7 @@ fn layer_mutations_through_different_apis_are_consistent() {
 fn get_layer_returns_correct_layer() {
     let manager = LayerManager::new();
 
-    for layer_type in [LayerType::Canvas, LayerType::Detections, LayerType::Shapes, LayerType::Grid] {
+    for layer_type in [LayerType::Canvas, LayerType::Highlights, LayerType::Detections, LayerType::Shapes, LayerType::Grid] {
         let layer = manager.get_layer(layer_type);
         assert_eq!(layer.layer_type(), &layer_type);
     }
@@ -236,7 +258,7 @@ fn default_trait_matches_new() {
     let manager2 = LayerManager::default();
 
     // Compare all layer states
-    for layer_type in [LayerType::Canvas, LayerType::Detections, LayerType::Shapes, LayerType::Grid] {
+    for layer_type in [LayerType::Canvas, LayerType::Highlights, LayerType::Detections, LayerType::Shapes, LayerType::Grid] {
         assert_eq!(
             manager1.is_visible(layer_type),
             manager2.is_visible(layer_type)
@@ -255,7 +277,7 @@ fn default_trait_matches_new() {
 #[test]
 fn len_returns_correct_count() {
     let manager = LayerManager::new();
-    assert_eq!(manager.len(), 4);
+    assert_eq!(manager.len(), 5);
 }
 
 #[test]
@@ -268,7 +290,7 @@ fn is_empty_always_returns_false() {
 fn layers_in_order_iteration_count() {
     let manager = LayerManager::new();
     let count = manager.layers_in_order().count();
-    assert_eq!(count, 4);
+    assert_eq!(count, 5);
 }
 
 #[test]
@@ -280,6 +302,7 @@ fn layers_in_order_preserves_render_sequence() {
 
     assert_eq!(order, vec![
         LayerType::Canvas,
+        LayerType::Highlights,
         LayerType::Detections,
         LayerType::Shapes,
         LayerType::Grid,
@@ -293,6 +316,7 @@ fn layers_in_order_preserves_render_sequence() {
 #[test]
 fn layer_type_display_all_variants() {
     assert_eq!(LayerType::Canvas.to_string(), "Canvas");
+    assert_eq!(LayerType::Highlights.to_string(), "Highlights");
     assert_eq!(LayerType::Detections.to_string(), "Detections");
     assert_eq!(LayerType::Shapes.to_string(), "Shapes");
     assert_eq!(LayerType::Grid.to_string(), "Grid");
@@ -435,15 +459,23 @@ fn layer_type_equality() {
 
 #[test]
 fn layer_type_ordering() {
-    assert!(LayerType::Canvas < LayerType::Detections);
+    assert!(LayerType::Canvas < LayerType::Highlights);
+    assert!(LayerType::Highlights < LayerType::Detections);
     assert!(LayerType::Detections < LayerType::Shapes);
     assert!(LayerType::Shapes < LayerType::Grid);
 
     // Verify render order is ascending
-    let mut types = vec![LayerType::Grid, LayerType::Canvas, LayerType::Shapes, LayerType::Detections];
+    let mut types = vec![
+        LayerType::Grid,
+        LayerType::Canvas,
+        LayerType::Shapes,
+        LayerType::Highlights,
+        LayerType::Detections,
+    ];
     types.sort();
     assert_eq!(types, vec![
         LayerType::Canvas,
+        LayerType::Highlights,
         LayerType::Detections,
         LayerType::Shapes,
         LayerType::Grid,
@@ -505,12 +537,13 @@ fn all_layers_visible_simultaneously() {
     let mut manager = LayerManager::new();
 
     // Make all layers visible
-    for layer_type in [LayerType::Canvas, LayerType::Detections, LayerType::Shapes, LayerType::Grid] {
+    for layer_type in [LayerType::Canvas, LayerType::Highlights, LayerType::Detections, LayerType::Shapes, LayerType::Grid] {
         manager.set_visible(layer_type, true);
     }
 
     // Verify all visible
     assert!(manager.is_visible(LayerType::Canvas));
+    assert!(manager.is_visible(LayerType::Highlights));
     assert!(manager.is_visible(LayerType::Detections));
     assert!(manager.is_visible(LayerType::Shapes));
     assert!(manager.is_visible(LayerType::Grid));
@@ -521,7 +554,7 @@ fn all_layers_hidden_simultaneously() {
     let mut manager = LayerManager::new();
 
     // Hide all layers
-    for layer_type in [LayerType::Canvas, LayerType::Detections, LayerType::Shapes, LayerType::Grid] {
+    for layer_type in [LayerType::Canvas, LayerType::Highlights, LayerType::Detections, LayerType::Shapes, LayerType::Grid] {
         manager.set_visible(layer_type, false);
     }
 
@@ -537,7 +570,7 @@ fn all_layers_locked_simultaneously() {
     let mut manager = LayerManager::new();
 
     // Lock all layers
-    for layer_type in [LayerType::Canvas, LayerType::Detections, LayerType::Shapes, LayerType::Grid] {
+    for layer_type in [LayerType::Canvas, LayerType::Highlights, LayerType::Detections, LayerType::Shapes, LayerType::Grid] {
         manager.set_locked(layer_type, true);
     }
 
@@ -0,0 +1,66 @@
+//! Integration tests for the color legend overlay
+
+mod common;
+
+use common::{region, with_detections};
+use egui::Color32;
+use form_factor::{CanvasCommand, CanvasPanel, CanvasSimulator, DrawingCanvas, LayerType, Settings};
+
+/// Canvas with two text regions, one logo and one shape
+fn annotated_canvas() -> DrawingCanvas {
+    let mut canvas = with_detections(vec![
+        region("Text Region 1", 0.0, 0.0, 10.0, 10.0),
+        region("Logo: acme", 0.0, 0.0, 10.0, 10.0),
+        region("Text Region 2", 0.0, 0.0, 10.0, 10.0),
+    ]);
+    canvas.set_shapes(vec![region("name", 0.0, 0.0, 10.0, 10.0)]);
+    canvas
+}
+
+#[test]
+fn entries_count_objects_of_each_kind() {
+    let mut canvas = annotated_canvas();
+    canvas.record_ocr_text(2, "Total");
+
+    let rows: Vec<(String, usize)> = canvas
+        .legend_entries()
+        .into_iter()
+        .map(|entry| (entry.label, entry.count))
+        .collect();
+
+    let expected = [("Shapes", 1), ("Text", 1), ("Logo", 1), ("OCR", 1)];
+    assert_eq!(rows, expected.map(|(label, count)| (label.to_string(), count)));
+}
+
+#[test]
+fn entries_follow_color_settings_and_layer_visibility() {
+    let mut canvas = annotated_canvas();
+    let mut settings = Settings::default();
+    settings.detection.colors.text = Color32::RED;
+    canvas.apply_settings(&settings);
+    canvas.apply_command(CanvasCommand::SetLayerVisible {
+        layer: LayerType::Detections,
+        visible: false,
+    });
+
+    let entries = canvas.legend_entries();
+
+    assert_eq!(entries[1].color, Color32::RED);
+    assert_eq!(entries[2].color, settings.detection.colors.logo);
+    assert!(entries[0].visible);
+    assert!(entries[1..].iter().all(|entry| !entry.visible));
+}
+
+#[test]
+fn legend_is_toggled_by_command_and_drawn() {
+    let mut canvas = annotated_canvas();
+    assert!(!*canvas.show_legend());
+
+    assert!(canvas.apply_command(CanvasCommand::TogglePanel(CanvasPanel::Legend)));
+    assert!(*canvas.show_legend());
+
+    // Drawing the legend over a canvas must not disturb the frame
+    let mut sim = CanvasSimulator::new(canvas);
+    sim.run_frames(2);
+    assert_eq!(sim.canvas().legend_entries().len(), 4);
+}
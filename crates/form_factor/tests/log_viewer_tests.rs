@@ -0,0 +1,93 @@
+//! Integration tests for log filtering and the log viewer window
+
+use form_factor::{LogBuffer, LogFilter, LogRecord, LogViewer};
+use tracing::Level;
+
+fn record(level: Level, target: &str, message: &str) -> LogRecord {
+    LogRecord {
+        timestamp: 0.0,
+        level,
+        target: target.to_string(),
+        message: message.to_string(),
+        trace_id: None,
+    }
+}
+
+fn sample_logs() -> LogBuffer {
+    let logs = LogBuffer::new(10);
+    logs.push(record(Level::DEBUG, "form_factor_drawing::canvas::io", "Saving project"));
+    logs.push(record(Level::WARN, "form_factor_cv::text_detection", "Model not found"));
+    logs.push(record(Level::ERROR, "form_factor", "Failed to detect text"));
+    logs.push(record(Level::INFO, "form_factor_plugins::manager", "Registered plugin"));
+    logs
+}
+
+fn messages(records: Vec<LogRecord>) -> Vec<String> {
+    records.into_iter().map(|r| r.message).collect()
+}
+
+// ============================================================================
+// Filtering
+// ============================================================================
+
+#[test]
+fn default_filter_shows_everything() {
+    assert_eq!(sample_logs().filtered(&LogFilter::new()).len(), 4);
+}
+
+#[test]
+fn level_filter_hides_verbose_events() {
+    let filter = LogFilter::new().with_min_level(Level::WARN);
+
+    assert_eq!(
+        messages(sample_logs().filtered(&filter)),
+        vec!["Model not found", "Failed to detect text"]
+    );
+}
+
+#[test]
+fn target_filter_matches_prefix() {
+    let filter = LogFilter::new().with_target("form_factor_cv");
+
+    assert_eq!(messages(sample_logs().filtered(&filter)), vec!["Model not found"]);
+}
+
+#[test]
+fn search_is_case_insensitive_over_message_and_target() {
+    let by_message = LogFilter::new().with_search("FAILED");
+    let by_target = LogFilter::new().with_search("plugins::manager");
+
+    assert_eq!(messages(sample_logs().filtered(&by_message)), vec!["Failed to detect text"]);
+    assert_eq!(messages(sample_logs().filtered(&by_target)), vec!["Registered plugin"]);
+}
+
+#[test]
+fn trace_filter_shows_one_request() {
+    let logs = sample_logs();
+    let mut traced = record(Level::INFO, "form_factor", "Detection requested");
+    traced.trace_id = Some("0000002a".to_string());
+    logs.push(traced);
+
+    let filter = LogFilter::new().with_trace_id(" 0000002a ");
+    assert_eq!(messages(logs.filtered(&filter)), vec!["Detection requested"]);
+    assert_eq!(logs.filtered(&LogFilter::new()).len(), 5);
+}
+
+// ============================================================================
+// Window
+// ============================================================================
+
+#[test]
+fn viewer_renders_only_when_open() {
+    let mut viewer = LogViewer::new(sample_logs());
+    let ctx = egui::Context::default();
+
+    let mut shown = false;
+    let _ = ctx.run(egui::RawInput::default(), |ctx| shown = viewer.show(ctx));
+    assert!(!shown);
+
+    viewer.toggle();
+    let _ = ctx.run(egui::RawInput::default(), |ctx| shown = viewer.show(ctx));
+    assert!(shown);
+    assert_eq!(viewer.filter().min_level, Level::INFO);
+}
@@ -0,0 +1,121 @@
+//! Integration tests for the metrics registry and exporters
+//!
+//! Run with `cargo test -p form_factor --features metrics`.
+
+#![cfg(feature = "metrics")]
+
+use form_factor::{
+    FRAME_TIME_SECONDS, Histogram, JsonExporter, MetricsEndpoint, MetricsExporter, MetricsRegistry,
+    MetricsSnapshot, OCR_CONFIDENCE, PrometheusExporter,
+};
+use std::io::{Read, Write};
+
+// ============================================================================
+// Registry
+// ============================================================================
+
+#[test]
+fn histogram_buckets_observations() {
+    let mut histogram = Histogram::new(&[1.0, 0.5, 2.0]);
+    for value in [0.1, 0.5, 0.7, 3.0, f64::NAN] {
+        histogram.observe(value);
+    }
+
+    assert_eq!(histogram.bounds(), &[0.5, 1.0, 2.0]);
+    assert_eq!(histogram.counts(), &[2, 1, 0]);
+    assert_eq!(histogram.cumulative_counts(), vec![2, 3, 3]);
+    assert_eq!(histogram.count(), 4);
+    assert_eq!(histogram.sum(), 4.3);
+}
+
+#[test]
+fn counters_and_histograms_accumulate() {
+    let registry = MetricsRegistry::new();
+    registry.increment("documents_total", 2);
+    registry.increment("documents_total", 3);
+    registry.observe("latency", 0.02);
+
+    assert_eq!(registry.counter("documents_total"), Some(5));
+    assert_eq!(registry.histogram("latency").map(|h| h.count()), Some(1));
+
+    registry.reset();
+    assert_eq!(registry.counter("documents_total"), Some(0));
+    assert_eq!(registry.histogram("latency").map(|h| h.count()), Some(0));
+}
+
+#[test]
+fn global_registry_has_standard_metrics() {
+    let snapshot = MetricsRegistry::global().snapshot();
+
+    assert_eq!(snapshot.histograms[OCR_CONFIDENCE].bounds().last(), Some(&100.0));
+    assert!(snapshot.histograms.contains_key(FRAME_TIME_SECONDS));
+}
+
+// ============================================================================
+// Exporters
+// ============================================================================
+
+fn sample_registry() -> MetricsRegistry {
+    let registry = MetricsRegistry::new();
+    registry.register_histogram("confidence", &[50.0, 100.0]);
+    registry.observe("confidence", 40.0);
+    registry.observe("confidence", 90.0);
+    registry.increment("detections_total", 7);
+    registry
+}
+
+#[test]
+fn prometheus_format() {
+    let text = sample_registry().export(&PrometheusExporter);
+
+    assert!(text.contains("# TYPE detections_total counter\ndetections_total 7\n"));
+    assert!(text.contains("confidence_bucket{le=\"50\"} 1\n"));
+    assert!(text.contains("confidence_bucket{le=\"100\"} 2\n"));
+    assert!(text.contains("confidence_bucket{le=\"+Inf\"} 2\n"));
+    assert!(text.contains("confidence_sum 130\n"));
+    assert!(text.contains("confidence_count 2\n"));
+}
+
+#[test]
+fn json_dump_round_trips() {
+    let registry = sample_registry();
+    let json = registry.export(&JsonExporter);
+    let restored: MetricsSnapshot = serde_json::from_str(&json).expect("Dump should be valid JSON");
+
+    assert_eq!(restored, registry.snapshot());
+}
+
+#[test]
+fn endpoint_serves_current_metrics() {
+    let registry: &'static MetricsRegistry = Box::leak(Box::new(sample_registry()));
+    let endpoint = MetricsEndpoint::serve("127.0.0.1:0", registry, Box::new(PrometheusExporter))
+        .expect("Endpoint should bind");
+
+    let mut stream = std::net::TcpStream::connect(endpoint.local_addr()).expect("Endpoint should accept");
+    stream
+        .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .expect("Request should send");
+    let mut response = String::new();
+    stream.read_to_string(&mut response).expect("Response should arrive");
+
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.contains(PrometheusExporter.content_type()));
+    assert!(response.ends_with(&registry.export(&PrometheusExporter)));
+}
+
+#[test]
+fn a_stalled_client_does_not_hold_up_other_scrapes() {
+    let registry: &'static MetricsRegistry = Box::leak(Box::new(sample_registry()));
+    let endpoint = MetricsEndpoint::serve("127.0.0.1:0", registry, Box::new(JsonExporter))
+        .expect("Endpoint should bind");
+    let _stalled = std::net::TcpStream::connect(endpoint.local_addr()).expect("Endpoint should accept");
+
+    let started = std::time::Instant::now();
+    let mut stream = std::net::TcpStream::connect(endpoint.local_addr()).expect("Endpoint should accept");
+    stream.write_all(b"GET / HTTP/1.1\r\n\r\n").expect("Request should send");
+    let mut response = String::new();
+    stream.read_to_string(&mut response).expect("Response should arrive");
+
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(started.elapsed() < std::time::Duration::from_secs(2));
+}
@@ -0,0 +1,87 @@
+//! Integration tests for automatic field naming
+
+mod common;
+
+use common::region;
+use egui::vec2;
+use form_factor::{CanvasSimulator, DrawingCanvas, NameParts, NamingScheme, RepeatGrid, Settings, Shape};
+
+// ============================================================================
+// Pattern expansion
+// ============================================================================
+
+#[test]
+fn format_substitutes_padded_placeholders() {
+    let scheme = NamingScheme::new("line_{row}_{name}_c{col}_#{n}").with_width(2);
+    let parts = NameParts {
+        index: 4,
+        row: 1,
+        column: 2,
+        name: "amount",
+    };
+
+    assert_eq!(scheme.format(&parts), "line_02_amount_c03_#05");
+}
+
+#[test]
+fn format_keeps_unknown_braces_literally() {
+    let scheme = NamingScheme::new("{x}_{n}_{").with_start(0);
+
+    assert_eq!(scheme.format(&NameParts::default()), "{x}_0_{");
+}
+
+#[test]
+fn next_name_skips_taken_numbers() {
+    let scheme = NamingScheme::new("field_{n}");
+
+    assert_eq!(scheme.next_name(&[]), "field_1");
+    assert_eq!(scheme.next_name(&["field_1", "field_3"]), "field_2");
+    assert_eq!(scheme.next_name(&["field_1", "field_2"]), "field_3");
+    assert_eq!(NamingScheme::new("total").next_name(&["total"]), "total");
+}
+
+#[test]
+fn empty_pattern_is_disabled() {
+    assert!(!NamingScheme::default().is_enabled());
+    assert!(!NamingScheme::new("  ").is_enabled());
+    assert!(NamingScheme::new("line_{row}").uses_grid());
+    assert!(!NamingScheme::new("field_{n}").uses_grid());
+}
+
+// ============================================================================
+// Canvas integration
+// ============================================================================
+
+#[test]
+fn grid_repeat_names_every_row() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![region("amount", 0.0, 0.0, 30.0, 10.0)]);
+    let grid = RepeatGrid::adjacent(30, 1, vec2(30.0, 10.0));
+    let scheme = NamingScheme::new("line_{row}_{name}").with_width(2);
+
+    let added = canvas.repeat_shape_named(0, &grid, &scheme);
+
+    assert_eq!(added.len(), 29);
+    let names: Vec<&str> = canvas.shapes().iter().map(Shape::name).collect();
+    assert_eq!(names[0], "line_01_amount");
+    assert_eq!(names[1], "line_02_amount");
+    assert_eq!(names[29], "line_30_amount");
+}
+
+#[test]
+fn drawn_shapes_are_numbered_from_settings() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_zoom(1.0);
+    let mut settings = Settings::default();
+    settings.canvas.field_naming = NamingScheme::new("field_{n}").with_width(2);
+    canvas.apply_settings(&settings);
+    let mut sim = CanvasSimulator::new(canvas);
+    sim.step();
+    let center = sim.canvas_rect().center();
+
+    sim.draw_rectangle(center, center + vec2(40.0, 20.0));
+    sim.draw_rectangle(center + vec2(0.0, 40.0), center + vec2(40.0, 60.0));
+
+    let names: Vec<&str> = sim.canvas().shapes().iter().map(Shape::name).collect();
+    assert_eq!(names, vec!["field_01", "field_02"]);
+}
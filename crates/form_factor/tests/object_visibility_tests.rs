@@ -0,0 +1,163 @@
+//! Integration tests for hiding and locking individual shapes and detections
+
+mod common;
+
+use common::{region, with_detections};
+use egui::pos2;
+use form_factor::{CanvasCommand, CanvasSimulator, DrawingCanvas, LayerType};
+
+fn hide(layer: LayerType, index: usize) -> CanvasCommand {
+    CanvasCommand::SetObjectHidden {
+        layer,
+        index,
+        hidden: true,
+    }
+}
+
+fn lock(layer: LayerType, index: usize) -> CanvasCommand {
+    CanvasCommand::SetObjectLocked {
+        layer,
+        index,
+        locked: true,
+    }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+#[test]
+fn objects_are_visible_and_unlocked_by_default() {
+    let shape = region("name", 0.0, 0.0, 40.0, 20.0);
+
+    assert!(!shape.is_hidden());
+    assert!(!shape.is_locked());
+}
+
+#[test]
+fn commands_change_only_the_addressed_object() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![region("a", 0.0, 0.0, 40.0, 20.0), region("b", 50.0, 0.0, 40.0, 20.0)]);
+
+    assert!(canvas.apply_command(hide(LayerType::Shapes, 1)));
+    assert!(canvas.apply_command(lock(LayerType::Shapes, 0)));
+
+    assert!(!canvas.shapes()[0].is_hidden());
+    assert!(canvas.shapes()[0].is_locked());
+    assert!(canvas.shapes()[1].is_hidden());
+    assert!(!canvas.shapes()[1].is_locked());
+    assert_eq!(canvas.take_changes().len(), 2);
+}
+
+#[test]
+fn commands_without_effect_are_not_queued() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![region("a", 0.0, 0.0, 40.0, 20.0)]);
+    canvas.apply_command(hide(LayerType::Shapes, 0));
+    canvas.take_changes();
+
+    assert!(!canvas.apply_command(hide(LayerType::Shapes, 0)));
+    assert!(!canvas.apply_command(hide(LayerType::Shapes, 3)));
+    assert!(!canvas.apply_command(hide(LayerType::Grid, 0)));
+    assert!(canvas.take_changes().is_empty());
+}
+
+#[test]
+fn hiding_or_locking_the_selected_shape_clears_the_selection() {
+    let mut sim = CanvasSimulator::new(DrawingCanvas::new());
+    sim.canvas_mut().set_zoom(1.0);
+    sim.draw_rectangle(pos2(100.0, 100.0), pos2(200.0, 150.0));
+    assert_eq!(*sim.canvas().selected_shape(), Some(0));
+
+    sim.canvas_mut().apply_command(lock(LayerType::Shapes, 0));
+
+    assert_eq!(*sim.canvas().selected_shape(), None);
+}
+
+// ============================================================================
+// Hit-testing and export
+// ============================================================================
+
+#[test]
+fn hidden_and_locked_shapes_cannot_be_picked() {
+    let mut sim = CanvasSimulator::new(DrawingCanvas::new());
+    sim.canvas_mut().set_zoom(1.0);
+    sim.draw_rectangle(pos2(100.0, 100.0), pos2(200.0, 150.0));
+    sim.draw_rectangle(pos2(300.0, 100.0), pos2(400.0, 150.0));
+    sim.canvas_mut().apply_command(hide(LayerType::Shapes, 0));
+    sim.canvas_mut().apply_command(lock(LayerType::Shapes, 1));
+
+    sim.select_at(pos2(150.0, 120.0));
+    assert_eq!(*sim.canvas().selected_shape(), None);
+    sim.select_at(pos2(350.0, 120.0));
+    assert_eq!(*sim.canvas().selected_shape(), None);
+
+    sim.canvas_mut().apply_command(CanvasCommand::SetObjectLocked {
+        layer: LayerType::Shapes,
+        index: 1,
+        locked: false,
+    });
+    sim.select_at(pos2(350.0, 120.0));
+    assert_eq!(*sim.canvas().selected_shape(), Some(1));
+}
+
+#[test]
+fn hidden_shapes_are_left_out_of_exports() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![region("kept", 0.0, 0.0, 40.0, 20.0), region("hidden", 100.0, 0.0, 40.0, 20.0)]);
+    canvas.apply_command(hide(LayerType::Shapes, 1));
+
+    let image = canvas.render_png(1.0).expect("Exportable");
+    let svg = canvas.to_svg().expect("Exportable");
+
+    // Only the first box and its 10 unit margin remain
+    assert_eq!(image.dimensions(), (60, 40));
+    assert!(svg.contains("kept"));
+    assert!(!svg.contains("hidden"));
+}
+
+// ============================================================================
+// Project files
+// ============================================================================
+
+#[test]
+fn flags_are_kept_in_projects() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![region("a", 0.0, 0.0, 40.0, 20.0), region("b", 50.0, 0.0, 40.0, 20.0)]);
+    canvas.apply_command(hide(LayerType::Shapes, 0));
+    canvas.apply_command(lock(LayerType::Shapes, 1));
+
+    let loaded = DrawingCanvas::from_json(&canvas.to_json().expect("Serializable")).expect("Loadable");
+
+    assert!(loaded.shapes()[0].is_hidden());
+    assert!(!loaded.shapes()[0].is_locked());
+    assert!(!loaded.shapes()[1].is_hidden());
+    assert!(loaded.shapes()[1].is_locked());
+}
+
+#[test]
+fn detections_carry_the_same_flags() {
+    let mut canvas = with_detections(vec![region("", 0.0, 0.0, 40.0, 20.0)]);
+
+    assert!(canvas.apply_command(hide(LayerType::Detections, 0)));
+    assert!(canvas.apply_command(lock(LayerType::Detections, 0)));
+
+    let loaded = DrawingCanvas::from_json(&canvas.to_json().expect("Serializable")).expect("Loadable");
+    assert!(loaded.detections()[0].is_hidden());
+    assert!(loaded.detections()[0].is_locked());
+}
+
+#[test]
+fn projects_without_flags_load_visible_and_unlocked() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![region("a", 0.0, 0.0, 40.0, 20.0)]);
+    let mut project = serde_json::to_value(&canvas).expect("Serializable");
+    let shape = project["shapes"][0]["Rectangle"].as_object_mut().expect("Rectangle");
+    shape.remove("hidden");
+    shape.remove("locked");
+
+    let loaded = DrawingCanvas::from_json(&project.to_string()).expect("Loadable");
+
+    assert!(!loaded.shapes()[0].is_hidden());
+    assert!(!loaded.shapes()[0].is_locked());
+}
@@ -0,0 +1,89 @@
+//! Integration tests for correcting OCR text in place on the canvas
+
+mod common;
+
+use common::{form_simulator, region, with_detections};
+use egui::{Key, Modifiers, Pos2};
+use form_factor::{CanvasSimulator, DrawingCanvas, ImagePos, OcrSample};
+
+/// Canvas with two detections, the first read by OCR
+fn misread_canvas() -> DrawingCanvas {
+    let mut canvas = with_detections(vec![region("", 0.0, 0.0, 100.0, 20.0), region("", 200.0, 0.0, 100.0, 20.0)]);
+    canvas.record_ocr_result(0, "Tota1", 62.0);
+    canvas
+}
+
+/// Screen position of an image pixel
+fn screen_pos(sim: &CanvasSimulator, x: f32, y: f32) -> Pos2 {
+    let mapper = sim.canvas().coordinate_mapper();
+    mapper
+        .image_to_screen(ImagePos(Pos2::new(x, y)))
+        .expect("Image loaded")
+        .0
+}
+
+#[test]
+fn correction_replaces_text_and_is_queued() {
+    let mut canvas = misread_canvas();
+
+    assert!(canvas.correct_ocr_text(0, "Total"));
+
+    assert_eq!(canvas.ocr_text(0), Some("Total"));
+    assert_eq!(canvas.ocr_confidence(0), None);
+    assert_eq!(
+        canvas.take_ocr_samples(),
+        [OcrSample {
+            field: "Detection 1".to_string(),
+            proposed: "Tota1".to_string(),
+            corrected: "Total".to_string(),
+            confidence: 62.0,
+        }]
+    );
+    assert!(canvas.take_ocr_samples().is_empty());
+    assert_eq!(canvas.session_stats().fields_corrected(), 1);
+}
+
+#[test]
+fn unchanged_text_is_not_a_correction() {
+    let mut canvas = misread_canvas();
+
+    assert!(canvas.correct_ocr_text(0, "Tota1"));
+
+    assert_eq!(canvas.ocr_confidence(0), Some(62.0));
+    assert!(canvas.take_ocr_samples().is_empty());
+    assert_eq!(canvas.session_stats().fields_corrected(), 0);
+}
+
+#[test]
+fn detections_without_ocr_text_cannot_be_edited() {
+    let mut canvas = misread_canvas();
+
+    assert!(!canvas.begin_ocr_edit(1));
+    assert!(!canvas.correct_ocr_text(1, "Date"));
+    assert_eq!(canvas.ocr_edit_target(), None);
+}
+
+#[test]
+fn double_click_opens_editor_and_enter_saves() {
+    let mut sim = form_simulator(misread_canvas(), 400, 300);
+
+    sim.double_click(screen_pos(&sim, 50.0, 10.0));
+    assert_eq!(sim.canvas().ocr_edit_target(), Some(0));
+
+    sim.key_press(Key::Backspace, Modifiers::NONE);
+    sim.type_text("l");
+    sim.key_press(Key::Enter, Modifiers::NONE);
+
+    assert_eq!(sim.canvas().ocr_edit_target(), None);
+    assert_eq!(sim.canvas().ocr_text(0), Some("Total"));
+}
+
+#[test]
+fn deleting_the_edited_detection_closes_the_editor() {
+    let mut canvas = misread_canvas();
+    canvas.begin_ocr_edit(0);
+
+    canvas.delete_detection(0);
+
+    assert_eq!(canvas.ocr_edit_target(), None);
+}
@@ -0,0 +1,94 @@
+//! Integration tests for the reading order of OCR text
+
+mod common;
+
+use common::{read_canvas, region};
+use form_factor::{CanvasCommand, CanvasPanel, DrawingCanvas, ReadingDirection};
+
+/// Canvas whose detections were found out of reading order
+///
+/// Reads as `Name`, `SSN` on the first row and `Wages`, `Tips` on the
+/// second; the detection at the bottom was never read.
+fn out_of_order_canvas() -> DrawingCanvas {
+    read_canvas(
+        vec![
+            region("", 0.0, 52.0, 100.0, 20.0),
+            region("", 200.0, 0.0, 100.0, 20.0),
+            region("", 200.0, 48.0, 100.0, 20.0),
+            region("", 0.0, 4.0, 100.0, 20.0),
+            region("", 0.0, 200.0, 100.0, 20.0),
+        ],
+        &["Wages", "SSN", "Tips", "Name"],
+    )
+}
+
+#[test]
+fn text_follows_detection_order_by_default() {
+    let canvas = out_of_order_canvas();
+
+    assert_eq!(canvas.ocr_reading_order(), vec![0, 1, 2, 3]);
+    assert_eq!(canvas.ocr_document_text(), "Wages\nSSN\nTips\nName");
+}
+
+#[test]
+fn auto_orders_rows_from_the_top() {
+    let mut canvas = out_of_order_canvas();
+
+    canvas.auto_ocr_reading_order(ReadingDirection::LeftToRight);
+    assert_eq!(canvas.ocr_document_text(), "Name\nSSN\nWages\nTips");
+
+    canvas.auto_ocr_reading_order(ReadingDirection::RightToLeft);
+    assert_eq!(canvas.ocr_document_text(), "SSN\nName\nTips\nWages");
+
+    canvas.reset_ocr_reading_order();
+    assert_eq!(canvas.ocr_reading_order(), vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn dragging_moves_a_detection_in_the_order() {
+    let mut canvas = out_of_order_canvas();
+
+    assert!(canvas.move_in_ocr_reading_order(3, 0));
+    assert_eq!(canvas.ocr_reading_order(), vec![3, 0, 1, 2]);
+    assert!(!canvas.move_in_ocr_reading_order(0, 4));
+}
+
+#[test]
+fn unread_and_unknown_detections_are_left_out() {
+    let mut canvas = out_of_order_canvas();
+
+    canvas.set_ocr_reading_order(vec![4, 2, 9, 2]);
+    assert_eq!(canvas.ocr_reading_order(), vec![2, 0, 1, 3]);
+
+    // Text read later follows the ordered detections
+    canvas.record_ocr_text(4, "Signature");
+    assert_eq!(canvas.ocr_reading_order(), vec![2, 0, 1, 3, 4]);
+}
+
+#[test]
+fn deleting_a_detection_keeps_the_order() {
+    let mut canvas = out_of_order_canvas();
+    canvas.auto_ocr_reading_order(ReadingDirection::LeftToRight);
+
+    canvas.delete_detection(1);
+
+    assert_eq!(canvas.ocr_document_text(), "Name\nWages\nTips");
+}
+
+#[test]
+fn reading_order_is_saved_with_the_project() {
+    let mut canvas = out_of_order_canvas();
+    canvas.auto_ocr_reading_order(ReadingDirection::LeftToRight);
+
+    let loaded = DrawingCanvas::from_json(&canvas.to_json().expect("Serializable")).expect("Loadable");
+
+    assert_eq!(loaded.ocr_reading_order(), canvas.ocr_reading_order());
+}
+
+#[test]
+fn reading_order_window_toggles() {
+    let mut canvas = out_of_order_canvas();
+
+    assert!(canvas.apply_command(CanvasCommand::TogglePanel(CanvasPanel::OcrOrder)));
+    assert!(*canvas.show_ocr_order());
+}
@@ -0,0 +1,146 @@
+//! Integration tests for tracking which detections need OCR again
+
+mod common;
+
+use common::{form_simulator, read_canvas, region, with_detections};
+use egui::{Color32, Pos2, Stroke};
+use form_factor::{DrawingCanvas, FieldFormat, FieldRule, ImagePos, Rectangle, Shape};
+
+/// Canvas with three detections, all read by a first OCR pass
+fn first_pass_canvas() -> DrawingCanvas {
+    read_canvas(
+        vec![region("", 0.0, 0.0, 10.0, 10.0), region("", 20.0, 0.0, 10.0, 10.0), region("", 40.0, 0.0, 10.0, 10.0)],
+        &["Name", "Date", "Total"],
+    )
+}
+
+// ============================================================================
+// Changed regions
+// ============================================================================
+
+#[test]
+fn every_detection_needs_ocr_before_the_first_pass() {
+    let canvas = with_detections(vec![region("", 0.0, 0.0, 10.0, 10.0), region("", 20.0, 0.0, 10.0, 10.0)]);
+
+    assert_eq!(canvas.changed_ocr_regions(), [0, 1]);
+    assert_eq!(canvas.ocr_text(0), None);
+}
+
+#[test]
+fn moved_and_added_detections_need_ocr_again() {
+    let canvas = first_pass_canvas();
+    assert!(canvas.changed_ocr_regions().is_empty());
+
+    let detections = vec![
+        region("", 0.0, 0.0, 10.0, 10.0),
+        region("", 25.0, 0.0, 10.0, 10.0),
+        region("", 40.0, 0.0, 10.0, 10.0),
+        region("", 60.0, 0.0, 10.0, 10.0),
+    ];
+    // Detections are only created by the detectors, so move them through the project file
+    let mut project = serde_json::to_value(&canvas).expect("Serializable");
+    project["detections"] = serde_json::to_value(detections).expect("Serializable");
+    let mut canvas = DrawingCanvas::from_json(&project.to_string()).expect("Valid project");
+
+    assert_eq!(canvas.changed_ocr_regions(), [1, 3]);
+    assert_eq!(canvas.ocr_text(1), Some("Date"));
+
+    canvas.record_ocr_text(1, "Due date");
+    canvas.record_ocr_text(3, "Signature");
+    assert!(canvas.changed_ocr_regions().is_empty());
+    assert_eq!(canvas.ocr_text(1), Some("Due date"));
+    assert_eq!(canvas.ocr_records().len(), 4);
+}
+
+#[test]
+fn clearing_records_brings_back_every_detection() {
+    let mut canvas = first_pass_canvas();
+
+    canvas.clear_ocr_records();
+
+    assert_eq!(canvas.changed_ocr_regions(), [0, 1, 2]);
+}
+
+// ============================================================================
+// Detection edits and project files
+// ============================================================================
+
+#[test]
+fn deleting_a_detection_keeps_text_on_the_remaining_ones() {
+    let mut canvas = first_pass_canvas();
+
+    canvas.delete_detection(1);
+
+    assert_eq!(canvas.ocr_text(0), Some("Name"));
+    assert_eq!(canvas.ocr_text(1), Some("Total"));
+    assert!(canvas.changed_ocr_regions().is_empty());
+
+    canvas.clear_detections();
+    assert!(canvas.ocr_records().is_empty());
+}
+
+#[test]
+fn recorded_text_is_kept_in_projects() {
+    let canvas = first_pass_canvas();
+
+    let loaded = DrawingCanvas::from_json(&canvas.to_json().expect("Serializable")).expect("Loadable");
+
+    assert_eq!(loaded.ocr_records(), canvas.ocr_records());
+    assert!(loaded.changed_ocr_regions().is_empty());
+}
+
+#[test]
+fn text_on_a_missing_detection_is_rejected() {
+    let mut project = serde_json::to_value(first_pass_canvas()).expect("Serializable");
+    project["detections"] = serde_json::to_value(vec![region("", 0.0, 0.0, 10.0, 10.0)]).expect("Serializable");
+
+    assert!(DrawingCanvas::from_json(&project.to_string()).is_err());
+}
+
+// ============================================================================
+// Numeric fields
+// ============================================================================
+
+fn number_rule() -> FieldRule {
+    FieldRule::new(FieldFormat::Number)
+}
+
+#[test]
+fn detections_with_a_number_rule_read_numbers() {
+    let mut amount = region("", 20.0, 0.0, 10.0, 10.0);
+    amount.set_rule(number_rule());
+    let canvas = with_detections(vec![region("", 0.0, 0.0, 10.0, 10.0), amount]);
+
+    assert!(!canvas.reads_numbers(0));
+    assert!(canvas.reads_numbers(1));
+    assert!(!canvas.reads_numbers(2));
+}
+
+#[test]
+fn detections_on_a_number_field_read_numbers() {
+    let canvas = with_detections(vec![region("", 0.0, 0.0, 10.0, 10.0), region("", 100.0, 0.0, 10.0, 10.0)]);
+    let mut sim = form_simulator(canvas, 400, 300);
+
+    // Field drawn over the first detection, in canvas space
+    let mapper = sim.canvas().coordinate_mapper();
+    let min = mapper.image_to_canvas(ImagePos(Pos2::new(-5.0, -5.0))).expect("Image loaded").0;
+    let max = mapper.image_to_canvas(ImagePos(Pos2::new(15.0, 15.0))).expect("Image loaded").0;
+    let mut field = Rectangle::from_corners(min, max, Stroke::new(1.0, Color32::BLACK), Color32::TRANSPARENT)
+        .expect("Valid rectangle");
+    field.rule = number_rule();
+    sim.canvas_mut().set_shapes(vec![Shape::Rectangle(field)]);
+
+    assert!(sim.canvas().reads_numbers(0));
+    assert!(!sim.canvas().reads_numbers(1));
+}
+
+#[test]
+fn ocr_confidence_is_kept_with_the_text() {
+    let mut canvas = first_pass_canvas();
+
+    canvas.record_ocr_result(1, "2024-01-31", 87.5);
+
+    assert_eq!(canvas.ocr_text(1), Some("2024-01-31"));
+    assert_eq!(canvas.ocr_confidence(1), Some(87.5));
+    assert_eq!(canvas.ocr_confidence(0), None);
+}
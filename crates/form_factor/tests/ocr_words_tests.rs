@@ -0,0 +1,106 @@
+//! Integration tests for OCR word boxes and word-level field assignment
+
+mod common;
+
+use common::{form_simulator, region, with_detections};
+use egui::{Pos2, Rect};
+use form_factor::{CanvasCommand, CanvasPanel, CanvasSimulator, DrawingCanvas, ImagePos, OcrWord, ToolMode};
+
+fn word(text: &str, x: f32, width: f32, confidence: f32) -> OcrWord {
+    OcrWord {
+        text: text.to_string(),
+        confidence,
+        bounds: Rect::from_min_size(Pos2::new(x, 2.0), egui::vec2(width, 16.0)),
+    }
+}
+
+/// Canvas with one detection read as a label and an amount
+fn words_canvas() -> DrawingCanvas {
+    let mut canvas = with_detections(vec![region("", 0.0, 0.0, 200.0, 20.0), region("", 250.0, 0.0, 200.0, 20.0)]);
+    canvas.record_ocr_result(0, "Total $1,200", 90.0);
+    canvas.record_ocr_words(0, vec![word("Total", 4.0, 50.0, 96.0), word("$1,200", 120.0, 70.0, 84.0)]);
+    canvas
+}
+
+/// Screen position of an image pixel
+fn screen_pos(sim: &CanvasSimulator, x: f32, y: f32) -> Pos2 {
+    let mapper = sim.canvas().coordinate_mapper();
+    mapper
+        .image_to_screen(ImagePos(Pos2::new(x, y)))
+        .expect("Image loaded")
+        .0
+}
+
+#[test]
+fn words_are_kept_with_the_ocr_record() {
+    let mut canvas = words_canvas();
+
+    assert_eq!(canvas.ocr_words(0).len(), 2);
+    assert!(canvas.ocr_words(1).is_empty());
+
+    let loaded = DrawingCanvas::from_json(&canvas.to_json().expect("Serializable")).expect("Loadable");
+    assert_eq!(loaded.ocr_words(0), canvas.ocr_words(0));
+
+    // Corrected text no longer matches the boxes
+    canvas.correct_ocr_text(0, "Total $1,250");
+    assert!(canvas.ocr_words(0).is_empty());
+}
+
+#[test]
+fn clicking_a_word_selects_it_while_boxes_are_shown() {
+    let mut sim = form_simulator(words_canvas(), 500, 300);
+    sim.canvas_mut().set_tool(ToolMode::Select);
+
+    sim.click(screen_pos(&sim, 150.0, 10.0));
+    assert_eq!(sim.canvas().selected_ocr_word(), None);
+
+    assert!(sim.canvas_mut().apply_command(CanvasCommand::TogglePanel(CanvasPanel::OcrWords)));
+    sim.click(screen_pos(&sim, 150.0, 10.0));
+    assert_eq!(sim.canvas().selected_ocr_word(), Some((0, 1)));
+
+    // Between words nothing is selected
+    sim.click(screen_pos(&sim, 90.0, 10.0));
+    assert_eq!(sim.canvas().selected_ocr_word(), None);
+}
+
+#[test]
+fn field_from_word_covers_the_word_and_holds_its_text() {
+    let mut sim = form_simulator(words_canvas(), 500, 300);
+
+    let idx = sim.canvas_mut().field_from_ocr_word(0, 1).expect("Field created");
+
+    let canvas = sim.canvas();
+    let field = &canvas.shapes()[idx];
+    assert_eq!(field.value(), "$1,200");
+    assert_eq!(field.confidence(), Some(84.0));
+    assert_eq!(*canvas.selected_shape(), Some(idx));
+    let mapper = canvas.coordinate_mapper();
+    let min = mapper.image_to_canvas(ImagePos(Pos2::new(120.0, 2.0))).expect("Image loaded").0;
+    assert!((field.bounding_rect().min - min).length() < 0.01);
+}
+
+#[test]
+fn word_fills_an_existing_field() {
+    let mut canvas = words_canvas();
+    canvas.set_shapes(vec![region("", 0.0, 0.0, 200.0, 20.0)]);
+
+    assert!(canvas.assign_ocr_word(0, 0, 0));
+
+    assert_eq!(canvas.shapes()[0].value(), "Total");
+    assert_eq!(canvas.shapes()[0].confidence(), Some(96.0));
+    assert!(!canvas.assign_ocr_word(0, 5, 0));
+}
+
+#[test]
+fn deleting_a_detection_moves_the_word_selection() {
+    let mut canvas = words_canvas();
+    canvas.record_ocr_text(1, "Date");
+    canvas.record_ocr_words(1, vec![word("Date", 260.0, 40.0, 90.0)]);
+    assert!(canvas.select_ocr_word(Some((1, 0))));
+    assert!(!canvas.select_ocr_word(Some((1, 3))));
+
+    canvas.delete_detection(0);
+
+    assert_eq!(canvas.selected_ocr_word(), Some((0, 0)));
+    assert_eq!(canvas.ocr_words(0)[0].text, "Date");
+}
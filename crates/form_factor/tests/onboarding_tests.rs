@@ -0,0 +1,120 @@
+//! Integration tests for guided tours and onboarding state
+
+mod common;
+
+use common::temp_path;
+use form_factor::{
+    ANCHOR_TOOLBAR, GuidedTour, OnboardingState, Tour, TourOutcome, TourStep, WELCOME_TOUR, anchor_rect,
+    register_anchor,
+};
+
+fn two_step_tour() -> Tour {
+    Tour::new("custom")
+        .step(TourStep::new("First", "Intro"))
+        .step(TourStep::new("Second", "Toolbar").highlight(ANCHOR_TOOLBAR))
+}
+
+// ============================================================================
+// Tour definitions
+// ============================================================================
+
+#[test]
+fn tours_load_from_json() {
+    let json = r#"{
+        "id": "custom",
+        "steps": [
+            { "title": "First", "body": "Intro" },
+            { "title": "Second", "body": "Toolbar", "target": "toolbar" }
+        ]
+    }"#;
+
+    assert_eq!(Tour::from_json(json).expect("Valid tour"), two_step_tour());
+}
+
+#[test]
+fn invalid_tours_are_rejected() {
+    let empty = Tour::from_json(r#"{ "id": "empty", "steps": [] }"#).expect_err("No steps");
+    assert_eq!(empty.key.as_deref(), Some("steps"));
+
+    let malformed = Tour::from_json(r#"{ "steps": 3 }"#).expect_err("Malformed");
+    assert!(malformed.desc.contains("Invalid tour definition"));
+}
+
+#[test]
+fn welcome_tour_is_valid() {
+    let tour = Tour::welcome();
+    assert_eq!(tour.id, WELCOME_TOUR);
+    assert!(tour.validate().is_ok());
+    assert!(tour.steps.iter().any(|step| step.target.as_deref() == Some(ANCHOR_TOOLBAR)));
+}
+
+// ============================================================================
+// Playback
+// ============================================================================
+
+#[test]
+fn tour_steps_forward_and_back() {
+    let mut tour = GuidedTour::new();
+    assert!(tour.advance().is_none());
+
+    tour.start(two_step_tour());
+    assert_eq!(tour.current_step().map(|s| s.title.as_str()), Some("First"));
+
+    tour.back();
+    assert_eq!(tour.step_index(), 0);
+    assert!(tour.advance().is_none());
+    assert_eq!(tour.current_step().map(|s| s.title.as_str()), Some("Second"));
+
+    let done = tour.advance().expect("Last step finishes the tour");
+    assert_eq!(done.tour_id, "custom");
+    assert_eq!(done.outcome, TourOutcome::Finished);
+    assert!(!tour.is_active());
+}
+
+#[test]
+fn skipping_ends_the_tour() {
+    let mut tour = GuidedTour::new();
+    tour.start(two_step_tour());
+
+    let done = tour.skip().expect("Active tour can be skipped");
+    assert_eq!(done.outcome, TourOutcome::Skipped);
+    assert!(tour.skip().is_none());
+}
+
+#[test]
+fn tour_renders_next_to_registered_anchor() {
+    let mut tour = GuidedTour::new();
+    tour.start(two_step_tour());
+    tour.advance();
+
+    let ctx = egui::Context::default();
+    let rect = egui::Rect::from_min_size(egui::pos2(10.0, 10.0), egui::vec2(100.0, 20.0));
+    let mut done = None;
+    let _ = ctx.run(egui::RawInput::default(), |ctx| {
+        register_anchor(ctx, ANCHOR_TOOLBAR, rect);
+        done = tour.show(ctx);
+    });
+
+    assert!(done.is_none());
+    assert_eq!(anchor_rect(&ctx, ANCHOR_TOOLBAR), Some(rect));
+    assert!(anchor_rect(&ctx, "missing").is_none());
+}
+
+// ============================================================================
+// First-run state
+// ============================================================================
+
+#[test]
+fn onboarding_state_round_trips() {
+    let path = temp_path("onboarding").join("onboarding.json");
+    assert!(!OnboardingState::load_from(&path).is_completed(WELCOME_TOUR));
+
+    let mut state = OnboardingState::default();
+    state.mark_completed(WELCOME_TOUR);
+    state.save_to(&path).expect("State should save");
+    let loaded = OnboardingState::load_from(&path);
+    let _ = std::fs::remove_dir_all(path.parent().expect("Has parent"));
+
+    assert!(loaded.is_completed(WELCOME_TOUR));
+    assert_eq!(loaded, state);
+}
@@ -0,0 +1,165 @@
+//! Integration tests for password-protected project files
+
+mod common;
+
+use common::{field, names, remove, temp_path};
+use egui::Pos2;
+use form_factor::{
+    CanvasErrorKind, DrawingCanvas, ENCRYPTED_PROJECT_MAGIC, decrypt, encrypt, is_encrypted,
+    journal_path,
+};
+use std::path::Path;
+
+const PASSWORD: &str = "correct horse battery staple";
+
+/// Project with a field named `ssn_field`, protected with [`PASSWORD`] and saved to `path`
+fn protected_project(path: &Path) -> DrawingCanvas {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![field("ssn_field", Pos2::ZERO)]);
+    canvas.set_project_password(Some(PASSWORD.to_string()));
+    canvas.save_to_file(path.to_str().expect("UTF-8 path")).expect("Saved");
+    canvas
+}
+
+fn contains(haystack: &[u8], needle: &str) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle.as_bytes())
+}
+
+// ============================================================================
+// Encryption
+// ============================================================================
+
+#[test]
+fn encrypted_bytes_decrypt_only_with_the_password() {
+    let encrypted = encrypt(b"{\"project_name\": \"W-2\"}", PASSWORD).expect("Encrypted");
+
+    assert!(is_encrypted(&encrypted));
+    assert!(encrypted.starts_with(ENCRYPTED_PROJECT_MAGIC));
+    assert!(!contains(&encrypted, "W-2"));
+    assert_eq!(decrypt(&encrypted, PASSWORD).expect("Decrypted"), b"{\"project_name\": \"W-2\"}");
+    let wrong = decrypt(&encrypted, "hunter2").expect_err("Wrong password");
+    assert!(matches!(wrong.kind, CanvasErrorKind::WrongPassword));
+}
+
+#[test]
+fn altered_files_are_refused() {
+    let mut encrypted = encrypt(b"{}", PASSWORD).expect("Encrypted");
+    let last = encrypted.len() - 1;
+    encrypted[last] ^= 1;
+
+    let err = decrypt(&encrypted, PASSWORD).expect_err("Altered");
+
+    assert!(matches!(err.kind, CanvasErrorKind::WrongPassword));
+}
+
+#[test]
+fn excessive_key_costs_are_refused_before_deriving_the_key() {
+    let mut encrypted = encrypt(b"{}", PASSWORD).expect("Encrypted");
+    let memory = ENCRYPTED_PROJECT_MAGIC.len() + 1;
+    encrypted[memory..memory + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    let err = decrypt(&encrypted, PASSWORD).expect_err("Excessive costs");
+
+    assert!(matches!(err.kind, CanvasErrorKind::Deserialization(_)));
+}
+
+// ============================================================================
+// Saving and opening
+// ============================================================================
+
+#[test]
+fn protected_projects_are_saved_encrypted() {
+    let path = temp_path("password_saved.ffp");
+    protected_project(&path);
+
+    let bytes = std::fs::read(&path).expect("Saved");
+
+    remove(&path);
+    assert!(is_encrypted(&bytes));
+    assert!(!contains(&bytes, "ssn_field"));
+}
+
+#[test]
+fn opening_a_protected_project_asks_for_the_password() {
+    let path = temp_path("password_prompt.ffp");
+    protected_project(&path);
+    let path_str = path.to_str().expect("UTF-8 path");
+    let ctx = egui::Context::default();
+    let mut canvas = DrawingCanvas::new();
+
+    let err = canvas.load_from_file(path_str, &ctx).expect_err("Locked");
+    let locked = canvas.locked_project();
+    let wrong = canvas.load_protected_file(path_str, "hunter2", &ctx).expect_err("Wrong password");
+    canvas.load_protected_file(path_str, PASSWORD, &ctx).expect("Unlocked");
+
+    remove(&path);
+    assert!(matches!(err.kind, CanvasErrorKind::PasswordRequired));
+    assert_eq!(locked.as_deref(), Some(path_str));
+    assert!(matches!(wrong.kind, CanvasErrorKind::WrongPassword));
+    assert_eq!(names(&canvas), ["ssn_field"]);
+    assert!(canvas.is_password_protected());
+}
+
+#[test]
+fn dropped_protected_bytes_ask_for_the_password_too() {
+    let path = temp_path("password_bytes.ffp");
+    protected_project(&path);
+    let bytes = std::fs::read(&path).expect("Saved");
+    remove(&path);
+    let ctx = egui::Context::default();
+    let mut canvas = DrawingCanvas::new();
+
+    let err = canvas.load_project_bytes("form.ffp", &bytes, &ctx).expect_err("Locked");
+    canvas.load_protected_bytes("form.ffp", &bytes, PASSWORD, &ctx).expect("Unlocked");
+
+    assert!(matches!(err.kind, CanvasErrorKind::PasswordRequired));
+    assert_eq!(names(&canvas), ["ssn_field"]);
+}
+
+#[test]
+fn unlocked_projects_stay_encrypted_when_saved_again() {
+    let path = temp_path("password_resave.ffp");
+    protected_project(&path);
+    let path_str = path.to_str().expect("UTF-8 path");
+    let ctx = egui::Context::default();
+    let mut canvas = DrawingCanvas::new();
+    canvas.load_protected_file(path_str, PASSWORD, &ctx).expect("Unlocked");
+
+    canvas.set_shapes(vec![field("ssn_field", Pos2::ZERO), field("dob_field", Pos2::new(0.0, 20.0))]);
+    canvas.save_incremental().expect("Saved");
+
+    let bytes = std::fs::read(&path).expect("Saved");
+    let journal = std::fs::read(journal_path(&path)).expect("Journal");
+    let mut reopened = DrawingCanvas::new();
+    reopened.load_protected_file(path_str, PASSWORD, &ctx).expect("Unlocked");
+    remove(&path);
+    assert!(is_encrypted(&bytes));
+    assert!(!contains(&journal, "dob_field"), "Journal records hold changes in plain text");
+    assert_eq!(names(&reopened), ["ssn_field", "dob_field"]);
+}
+
+#[test]
+fn removing_the_password_saves_in_plain_text() {
+    let path = temp_path("password_removed.ffp");
+    let mut canvas = protected_project(&path);
+
+    canvas.set_project_password(None);
+    canvas.save_to_file(path.to_str().expect("UTF-8 path")).expect("Saved");
+
+    let bytes = std::fs::read(&path).expect("Saved");
+    remove(&path);
+    assert!(!canvas.is_password_protected());
+    assert!(!is_encrypted(&bytes));
+    assert!(contains(&bytes, "ssn_field"));
+}
+
+#[test]
+fn new_projects_are_not_protected() {
+    let path = temp_path("password_new.ffp");
+    let mut canvas = protected_project(&path);
+    remove(&path);
+
+    canvas.start_blank_project(&egui::Context::default());
+
+    assert!(!canvas.is_password_protected());
+}
@@ -0,0 +1,103 @@
+//! Integration tests for pasting clipboard images onto the canvas
+//!
+//! The system clipboard isn't available headless, so these paste images
+//! directly, the way the Ctrl+V handler does once it has read one.
+
+use form_factor::{DrawingCanvas, DropKind, PasteTarget};
+use std::path::PathBuf;
+
+fn screenshot(width: u32, height: u32) -> image::RgbaImage {
+    image::RgbaImage::from_pixel(width, height, image::Rgba([200, 200, 200, 255]))
+}
+
+/// Fresh directory for pasted images
+fn paste_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("form_factor_paste_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+// ============================================================================
+// Targets
+// ============================================================================
+
+#[test]
+fn pasting_as_form_image_loads_it() {
+    let ctx = egui::Context::default();
+    let mut canvas = DrawingCanvas::new();
+    let dir = paste_dir("form_image");
+
+    let path = canvas
+        .paste_image(&screenshot(40, 30), PasteTarget::FormImage, &dir, &ctx)
+        .expect("Pasted");
+
+    assert!(path.starts_with(&dir));
+    assert_eq!(path.extension().and_then(|e| e.to_str()), Some("png"));
+    assert_eq!(*canvas.form_image_size(), Some(egui::vec2(40.0, 30.0)));
+    assert_eq!(
+        canvas.form_image_path().as_deref(),
+        Some(path.to_string_lossy().as_ref())
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn pasting_as_next_page_queues_it() {
+    let ctx = egui::Context::default();
+    let mut canvas = DrawingCanvas::new();
+    let dir = paste_dir("next_page");
+    canvas
+        .paste_image(&screenshot(40, 30), PasteTarget::FormImage, &dir, &ctx)
+        .expect("Pasted");
+
+    let path = canvas
+        .paste_image(&screenshot(20, 10), PasteTarget::NextPage, &dir, &ctx)
+        .expect("Pasted");
+
+    // The current form image stays until the queued page is opened
+    assert_eq!(*canvas.form_image_size(), Some(egui::vec2(40.0, 30.0)));
+    let queued = canvas.drop_queue().front().expect("Queued page");
+    assert_eq!(queued.kind, DropKind::Image);
+    assert_eq!(queued.path.as_deref(), Some(path.as_path()));
+
+    assert!(matches!(canvas.open_next_dropped(&ctx), Some(Ok(()))));
+    assert_eq!(*canvas.form_image_size(), Some(egui::vec2(20.0, 10.0)));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+// ============================================================================
+// Files
+// ============================================================================
+
+#[test]
+fn repeated_pastes_do_not_overwrite_each_other() {
+    let ctx = egui::Context::default();
+    let mut canvas = DrawingCanvas::new();
+    let dir = paste_dir("unique");
+
+    let paths: Vec<PathBuf> = (0..3)
+        .map(|_| {
+            canvas
+                .paste_image(&screenshot(10, 10), PasteTarget::NextPage, &dir, &ctx)
+                .expect("Pasted")
+        })
+        .collect();
+
+    assert_ne!(paths[0], paths[1]);
+    assert_ne!(paths[1], paths[2]);
+    assert!(paths.iter().all(|path| path.exists()));
+    assert_eq!(canvas.drop_queue().len(), 3);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn nothing_is_pending_without_a_paste() {
+    let ctx = egui::Context::default();
+    let mut canvas = DrawingCanvas::new();
+
+    let mut shown = true;
+    let _ = ctx.run(egui::RawInput::default(), |ctx| shown = canvas.show_paste_panel(ctx));
+
+    assert!(!shown);
+    assert!(canvas.pending_paste().is_none());
+}
@@ -0,0 +1,137 @@
+//! Integration tests for PDF printing
+
+mod common;
+
+use common::{region, saved_form_simulator, temp_path};
+use egui::Color32;
+use form_factor::{DrawingCanvas, LayerType, PageSize, PrintOptions};
+
+fn count(haystack: &[u8], needle: &str) -> usize {
+    haystack.windows(needle.len()).filter(|window| *window == needle.as_bytes()).count()
+}
+
+/// Decode the JPEG embedded in a print
+fn embedded_image(pdf: &[u8]) -> image::RgbImage {
+    let start = pdf
+        .windows(2)
+        .position(|window| window == [0xff, 0xd8])
+        .expect("JPEG start");
+    image::load_from_memory(&pdf[start..]).expect("Decodable JPEG").to_rgb8()
+}
+
+// ============================================================================
+// Document structure
+// ============================================================================
+
+#[test]
+fn print_is_a_pdf_with_a_valid_xref() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![region("box", 0.0, 0.0, 40.0, 20.0)]);
+
+    let pdf = canvas.to_pdf(&PrintOptions::new([LayerType::Shapes])).expect("Printable");
+    let text = String::from_utf8_lossy(&pdf);
+
+    assert!(pdf.starts_with(b"%PDF-1.4"));
+    assert!(text.trim_end().ends_with("%%EOF"));
+    assert_eq!(count(&pdf, "/Type /Page "), 1);
+    let startxref: usize = text
+        .rsplit("startxref\n")
+        .next()
+        .and_then(|tail| tail.lines().next())
+        .and_then(|line| line.parse().ok())
+        .expect("startxref offset");
+    assert!(pdf[startxref..].starts_with(b"xref\n"));
+}
+
+#[test]
+fn page_size_sets_media_box() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![region("box", 0.0, 0.0, 40.0, 20.0)]);
+
+    let letter = canvas.to_pdf(&PrintOptions::new([LayerType::Shapes])).expect("Printable");
+    let a4 = canvas
+        .to_pdf(&PrintOptions::new([LayerType::Shapes]).with_page_size(PageSize::A4))
+        .expect("Printable");
+
+    assert_eq!(count(&letter, "/MediaBox [0 0 612 792]"), 1);
+    assert_eq!(count(&a4, "/MediaBox [0 0 595 842]"), 1);
+}
+
+#[test]
+fn nothing_to_print_is_an_error() {
+    assert!(DrawingCanvas::new().to_pdf(&PrintOptions::default()).is_err());
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![region("box", 0.0, 0.0, 40.0, 20.0)]);
+    assert!(canvas.to_pdf(&PrintOptions::new([LayerType::Shapes]).with_scale(0.0)).is_err());
+}
+
+// ============================================================================
+// Layers
+// ============================================================================
+
+#[test]
+fn selected_layers_are_printed_regardless_of_visibility() {
+    let path = temp_path("print_layers.png");
+    let mut sim = saved_form_simulator(&path, 100, 50);
+    let center = sim.canvas_rect().center();
+    let mut amount = region("amount", center.x - 40.0, center.y - 40.0, 80.0, 80.0);
+    amount.set_fill(Color32::RED);
+    sim.canvas_mut().set_shapes(vec![amount]);
+    sim.canvas_mut().layer_manager_mut().toggle_layer(LayerType::Shapes);
+
+    let with_fields = sim
+        .canvas()
+        .to_pdf(&PrintOptions::new([LayerType::Canvas, LayerType::Shapes]))
+        .expect("Printable");
+    let image_only = sim.canvas().to_pdf(&PrintOptions::new([LayerType::Canvas])).expect("Printable");
+    let _ = std::fs::remove_file(&path);
+
+    let printed = embedded_image(&with_fields);
+    assert_eq!(printed.dimensions(), (100, 50));
+    let [r, g, b] = printed.get_pixel(50, 25).0;
+    assert!(r > 200 && g < 60 && b < 60, "expected red, got {:?}", (r, g, b));
+
+    let [r, g, b] = embedded_image(&image_only).get_pixel(50, 25).0;
+    assert!(r > 245 && g > 245 && b > 245, "expected the white form, got {:?}", (r, g, b));
+}
+
+// ============================================================================
+// Appendix
+// ============================================================================
+
+#[test]
+fn appendix_lists_fields_of_printed_layers() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![
+        region("amount (usd)", 0.0, 0.0, 40.0, 20.0),
+        region("total", 0.0, 30.0, 40.0, 20.0),
+    ]);
+
+    let plain = canvas.to_pdf(&PrintOptions::new([LayerType::Shapes])).expect("Printable");
+    let pdf = canvas
+        .to_pdf(&PrintOptions::new([LayerType::Shapes]).with_appendix(true))
+        .expect("Printable");
+
+    assert_eq!(count(&plain, "Field Values"), 0);
+    assert_eq!(count(&pdf, "/Type /Page "), 2);
+    assert_eq!(count(&pdf, "Field Values"), 1);
+    assert_eq!(count(&pdf, "(amount \\(usd\\)) Tj"), 1);
+    assert_eq!(count(&pdf, "(total) Tj"), 1);
+}
+
+#[test]
+fn long_appendix_continues_on_more_pages() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(
+        (0..100)
+            .map(|i| region(&format!("field_{}", i), 0.0, i as f32 * 30.0, 40.0, 20.0))
+            .collect(),
+    );
+
+    let pdf = canvas
+        .to_pdf(&PrintOptions::new([LayerType::Shapes]).with_appendix(true))
+        .expect("Printable");
+
+    assert_eq!(count(&pdf, "/Type /Page "), 4);
+    assert_eq!(count(&pdf, "(field_99) Tj"), 1);
+}
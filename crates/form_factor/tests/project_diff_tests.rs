@@ -0,0 +1,151 @@
+//! Integration tests for comparing two versions of a project
+
+mod common;
+
+use common::{field, temp_path};
+use egui::Pos2;
+use form_factor::{CanvasSimulator, DrawingCanvas, FieldRule, ImageChange, LayerType, ProjectDiff, Shape, ShapeChange};
+
+fn canvas_with(shapes: Vec<Shape>) -> DrawingCanvas {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(shapes);
+    canvas
+}
+
+#[test]
+fn identical_projects_have_no_changes() {
+    let before = canvas_with(vec![field("name", Pos2::ZERO), field("", Pos2::new(0.0, 20.0))]);
+
+    let diff = ProjectDiff::compare(&before, &before.clone());
+
+    assert!(diff.is_empty());
+    assert_eq!(diff.counts(), (0, 0, 0));
+}
+
+#[test]
+fn shapes_are_paired_by_name_to_find_added_removed_and_moved() {
+    let before = canvas_with(vec![field("name", Pos2::ZERO), field("date", Pos2::new(0.0, 20.0))]);
+    let after = canvas_with(vec![
+        field("total", Pos2::new(0.0, 40.0)),
+        field("name", Pos2::new(5.0, 0.0)),
+    ]);
+
+    let diff = ProjectDiff::compare(&before, &after);
+
+    assert_eq!(diff.counts(), (1, 1, 1));
+    let labels: Vec<(String, String)> = diff
+        .shape_changes()
+        .iter()
+        .map(|change| {
+            let kind = match change {
+                ShapeChange::Added { .. } => "added",
+                ShapeChange::Removed { .. } => "removed",
+                ShapeChange::Moved { .. } => "moved",
+            };
+            (kind.to_string(), change.label())
+        })
+        .collect();
+    assert!(labels.contains(&("removed".to_string(), "date".to_string())));
+    assert!(labels.contains(&("added".to_string(), "total".to_string())));
+    assert!(labels.contains(&("moved".to_string(), "name".to_string())));
+    assert!(diff.field_changes().is_empty(), "moving isn't a field change");
+}
+
+#[test]
+fn renamed_shapes_are_recognized_by_geometry() {
+    let before = canvas_with(vec![field("", Pos2::ZERO)]);
+    let after = canvas_with(vec![field("signature", Pos2::ZERO)]);
+
+    let diff = ProjectDiff::compare(&before, &after);
+
+    assert!(diff.shape_changes().is_empty());
+    let change = &diff.field_changes()[0];
+    assert_eq!(change.field, "signature");
+    assert_eq!(change.property, "name");
+    assert_eq!((change.before.as_str(), change.after.as_str()), ("", "signature"));
+}
+
+#[test]
+fn field_properties_that_changed_are_listed() {
+    let before = canvas_with(vec![field("total", Pos2::ZERO)]);
+    let mut total = field("total", Pos2::ZERO);
+    total.set_value("42.00");
+    total.set_rule(FieldRule {
+        required: true,
+        ..FieldRule::default()
+    });
+    let after = canvas_with(vec![total]);
+
+    let diff = ProjectDiff::compare(&before, &after);
+
+    let properties: Vec<(&str, &str, &str)> = diff
+        .field_changes()
+        .iter()
+        .map(|change| (change.property.as_str(), change.before.as_str(), change.after.as_str()))
+        .collect();
+    assert_eq!(properties, [("value", "", "42.00"), ("rule", "Text", "Text, required")]);
+    assert_eq!(diff.field_changes()[0].layer, LayerType::Shapes);
+}
+
+#[test]
+fn detections_and_image_changes_are_compared() {
+    let mut before = serde_json::to_value(DrawingCanvas::new()).expect("Serializable");
+    before["form_image_path"] = serde_json::json!("scan_v1.png");
+    let mut after = before.clone();
+    after["form_image_path"] = serde_json::json!("scan_v2.png");
+    after["form_image_rotation"] = serde_json::json!(std::f32::consts::FRAC_PI_2);
+    after["detections"] = serde_json::to_value(vec![field("Text Region 1", Pos2::ZERO)]).expect("Serializable");
+    let before = DrawingCanvas::from_json(&before.to_string()).expect("Valid project");
+    let after = DrawingCanvas::from_json(&after.to_string()).expect("Valid project");
+
+    let diff = ProjectDiff::compare(&before, &after);
+
+    assert_eq!(diff.shape_changes()[0].layer(), LayerType::Detections);
+    assert_eq!(
+        diff.image_changes()[0],
+        ImageChange::Replaced {
+            before: Some("scan_v1.png".to_string()),
+            after: Some("scan_v2.png".to_string()),
+        }
+    );
+    assert_eq!(diff.image_changes()[1].to_string(), "Image rotation 0.0° → 90.0°");
+}
+
+#[test]
+fn project_files_are_compared_under_their_file_names() {
+    let before_path = temp_path("diff_pass1.ffp");
+    let after_path = temp_path("diff_pass2.ffp");
+    canvas_with(vec![field("name", Pos2::ZERO)])
+        .save_to_file(before_path.to_str().expect("UTF-8 path"))
+        .expect("Saved");
+    canvas_with(Vec::new())
+        .save_to_file(after_path.to_str().expect("UTF-8 path"))
+        .expect("Saved");
+
+    let diff = ProjectDiff::from_files(&before_path, &after_path);
+    let missing = ProjectDiff::from_files(&before_path, &temp_path("diff_missing.ffp"));
+    let _ = std::fs::remove_file(&before_path);
+    let _ = std::fs::remove_file(&after_path);
+
+    let diff = diff.expect("Both projects load");
+    assert!(diff.before_name().ends_with("diff_pass1"));
+    assert!(diff.after_name().ends_with("diff_pass2"));
+    assert_eq!(diff.counts(), (0, 1, 0));
+    assert!(missing.is_err());
+}
+
+#[test]
+fn diff_window_opens_and_closes_on_the_canvas() {
+    let mut canvas = DrawingCanvas::new();
+    let diff = ProjectDiff::compare(&DrawingCanvas::new(), &canvas_with(vec![field("name", Pos2::ZERO)]));
+
+    canvas.start_project_diff(diff.clone());
+    assert_eq!(canvas.project_diff().as_ref(), Some(&diff));
+
+    // The overlay draws over the canvas while the diff is open
+    let mut sim = CanvasSimulator::new(canvas);
+    sim.step();
+
+    assert_eq!(sim.canvas_mut().close_project_diff(), Some(diff));
+    assert!(sim.canvas().project_diff().is_none());
+}
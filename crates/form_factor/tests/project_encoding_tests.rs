@@ -0,0 +1,107 @@
+//! Integration tests for binary project files
+
+mod common;
+
+use common::{remove, reopen, temp_path};
+use egui::{Color32, Pos2, Stroke};
+use form_factor::{
+    BINARY_PROJECT_MAGIC, CanvasErrorKind, DrawingCanvas, PolygonShape, ProjectEncoding, Settings, Shape,
+};
+
+/// Freehand-style polygon tracing a circle with `points` vertices
+fn freehand(points: usize) -> Shape {
+    let outline = (0..points)
+        .map(|idx| {
+            let angle = idx as f32 / points as f32 * std::f32::consts::TAU;
+            Pos2::new(500.0 + 300.3 * angle.cos(), 400.0 + 200.7 * angle.sin())
+        })
+        .collect();
+    let polygon = PolygonShape::from_points(outline, Stroke::new(2.0, Color32::RED), Color32::TRANSPARENT)
+        .expect("Valid polygon");
+    Shape::Polygon(polygon)
+}
+
+/// Canvas saving projects in `encoding`
+fn canvas_saving_as(encoding: ProjectEncoding, shapes: Vec<Shape>) -> DrawingCanvas {
+    let mut settings = Settings::default();
+    settings.canvas.project_encoding = encoding;
+    let mut canvas = DrawingCanvas::new();
+    canvas.apply_settings(&settings);
+    canvas.set_shapes(shapes);
+    canvas
+}
+
+#[test]
+fn binary_projects_are_smaller_than_json() {
+    let canvas = canvas_saving_as(ProjectEncoding::Json, vec![freehand(5_000), freehand(3_000)]);
+
+    let json = canvas.to_bytes(ProjectEncoding::Json).expect("Serializable");
+    let binary = canvas.to_bytes(ProjectEncoding::MessagePack).expect("Serializable");
+
+    assert!(binary.starts_with(BINARY_PROJECT_MAGIC));
+    assert!(binary.len() * 2 < json.len(), "{} binary bytes vs {} JSON bytes", binary.len(), json.len());
+    assert_eq!(DrawingCanvas::from_bytes(&binary).expect("Loads").shapes(), canvas.shapes());
+}
+
+#[test]
+fn projects_are_saved_in_the_configured_encoding_and_detected_on_open() {
+    let binary_path = temp_path("encoding_binary.ffp");
+    let json_path = temp_path("encoding_json.ffp");
+    for (path, encoding) in [(&binary_path, ProjectEncoding::MessagePack), (&json_path, ProjectEncoding::Json)] {
+        canvas_saving_as(encoding, vec![freehand(100)])
+            .save_to_file(path.to_str().expect("UTF-8 path"))
+            .expect("Saved");
+    }
+
+    let binary = std::fs::read(&binary_path).expect("Saved file");
+    let json = std::fs::read(&json_path).expect("Saved file");
+    let (from_binary, from_json) = (reopen(&binary_path), reopen(&json_path));
+    remove(&binary_path);
+    remove(&json_path);
+
+    assert_eq!(ProjectEncoding::detect(&binary), ProjectEncoding::MessagePack);
+    assert_eq!(ProjectEncoding::detect(&json), ProjectEncoding::Json);
+    assert_eq!(from_binary.shapes(), from_json.shapes());
+    assert_eq!(from_binary.shapes().len(), 1);
+}
+
+#[test]
+fn autosave_journals_binary_projects_too() {
+    let path = temp_path("encoding_journal.ffp");
+    let mut canvas = canvas_saving_as(ProjectEncoding::MessagePack, vec![freehand(2_000)]);
+    canvas.save_to_file(path.to_str().expect("UTF-8 path")).expect("Saved");
+
+    let unchanged = canvas.save_incremental().expect("Saved");
+    canvas.set_shapes(vec![freehand(2_000), freehand(10)]);
+    let saved = canvas.save_incremental().expect("Saved");
+    let reopened = reopen(&path);
+    remove(&path);
+
+    assert_eq!(unchanged, form_factor::JournalSave::Unchanged, "a fresh snapshot has nothing to journal");
+    assert!(matches!(saved, form_factor::JournalSave::Delta { .. }));
+    assert_eq!(reopened.shapes(), canvas.shapes());
+}
+
+#[test]
+fn unknown_binary_formats_are_rejected() {
+    let mut bytes = BINARY_PROJECT_MAGIC.to_vec();
+    bytes.extend([9, 0x80]);
+
+    let err = DrawingCanvas::from_bytes(&bytes).expect_err("Unknown format");
+    let empty = DrawingCanvas::from_bytes(BINARY_PROJECT_MAGIC).expect_err("No payload");
+
+    assert!(matches!(err.kind, CanvasErrorKind::Deserialization(_)));
+    assert!(matches!(empty.kind, CanvasErrorKind::Deserialization(_)));
+}
+
+#[test]
+fn json_stays_the_default_encoding() {
+    let path = temp_path("encoding_settings.json");
+    std::fs::write(&path, r#"{"canvas": {"show_rulers": false}}"#).expect("Temp settings");
+
+    let settings = Settings::load_from(&path);
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(Settings::default().canvas.project_encoding, ProjectEncoding::Json);
+    assert_eq!(settings.canvas.project_encoding, ProjectEncoding::Json, "older settings files save JSON");
+}
@@ -0,0 +1,191 @@
+//! Integration tests for incremental project saves
+
+mod common;
+
+use common::{field, names, remove, reopen, temp_path};
+use egui::Pos2;
+use form_factor::{
+    AUTOSAVE_INTERVAL_SECS, CanvasErrorKind, CanvasSimulator, DrawingCanvas, JournalSave, Shape, journal_path,
+};
+use std::io::Write;
+use std::path::Path;
+
+fn fields(count: usize) -> Vec<Shape> {
+    (0..count)
+        .map(|idx| field(&format!("field_{}", idx), Pos2::new(0.0, idx as f32 * 20.0)))
+        .collect()
+}
+
+/// Canvas with `shapes`, saved to `path`
+fn saved_canvas(path: &Path, shapes: Vec<Shape>) -> DrawingCanvas {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(shapes);
+    canvas.save_to_file(path.to_str().expect("UTF-8 path")).expect("Saved");
+    canvas
+}
+
+#[test]
+fn changes_are_appended_as_small_deltas_and_replayed_on_open() {
+    let path = temp_path("journal_delta.ffp");
+    let mut canvas = saved_canvas(&path, fields(200));
+    let snapshot_bytes = std::fs::metadata(&path).expect("Snapshot").len() as usize;
+
+    let mut shapes = canvas.shapes().clone();
+    shapes[7] = field("renamed", Pos2::new(0.0, 140.0));
+    shapes.truncate(150);
+    canvas.set_shapes(shapes);
+    let saved = canvas.save_incremental().expect("Saved");
+    let unchanged = canvas.save_incremental().expect("Saved");
+
+    let reopened = reopen(&path);
+    remove(&path);
+
+    let JournalSave::Delta { bytes } = saved else {
+        panic!("expected a delta, got {:?}", saved);
+    };
+    assert!(bytes * 20 < snapshot_bytes, "{} byte delta for a {} byte project", bytes, snapshot_bytes);
+    assert_eq!(unchanged, JournalSave::Unchanged);
+    assert_eq!(reopened.shapes().len(), 150);
+    assert_eq!(names(&reopened)[7], "renamed");
+    assert_eq!(reopened.project_file(), Some(path.as_path()));
+}
+
+#[test]
+fn a_growing_journal_is_compacted_into_the_snapshot() {
+    let path = temp_path("journal_compact.ffp");
+    let mut canvas = saved_canvas(&path, fields(2));
+
+    let mut results = Vec::new();
+    for idx in 0..30 {
+        let mut shapes = canvas.shapes().clone();
+        shapes.push(field(&format!("added_{}", idx), Pos2::new(100.0, idx as f32 * 20.0)));
+        canvas.set_shapes(shapes);
+        results.push(canvas.save_incremental().expect("Saved"));
+    }
+    let journal = std::fs::read_to_string(journal_path(&path)).expect("Journal");
+    let reopened = reopen(&path);
+    remove(&path);
+
+    assert!(matches!(results[0], JournalSave::Delta { .. }));
+    assert!(results.iter().any(|saved| matches!(saved, JournalSave::Snapshot { .. })));
+    assert!(journal.lines().count() < 30, "compaction starts the journal over");
+    assert_eq!(names(&reopened), names(&canvas));
+}
+
+#[test]
+fn a_journal_for_another_snapshot_is_ignored() {
+    let path = temp_path("journal_stale.ffp");
+    let mut canvas = saved_canvas(&path, fields(3));
+    canvas.set_shapes(fields(1));
+    canvas.save_incremental().expect("Saved");
+
+    // The snapshot is replaced behind the journal's back
+    let mut other = DrawingCanvas::new();
+    other.set_shapes(fields(5));
+    std::fs::write(&path, other.to_json().expect("Serializable")).expect("Replaced");
+
+    let reopened = reopen(&path);
+    remove(&path);
+
+    assert_eq!(reopened.shapes().len(), 5);
+}
+
+#[test]
+fn a_record_torn_by_a_crash_is_dropped() {
+    let path = temp_path("journal_torn.ffp");
+    let mut canvas = saved_canvas(&path, fields(20));
+    canvas.set_shapes(fields(21));
+    canvas.save_incremental().expect("Saved");
+    let mut journal = std::fs::OpenOptions::new()
+        .append(true)
+        .open(journal_path(&path))
+        .expect("Journal");
+    journal.write_all(br#"{"shapes":{"Elements":{"len":3,"se"#).expect("Appended");
+
+    let reopened = reopen(&path);
+    remove(&path);
+
+    assert_eq!(reopened.shapes().len(), 21);
+}
+
+#[test]
+fn projects_without_a_file_are_not_autosaved() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(fields(1));
+
+    assert!(canvas.autosave(1_000.0).is_none());
+    let err = canvas.save_incremental().expect_err("No project file");
+    assert_eq!(err.kind, CanvasErrorKind::NoProjectFile);
+    assert!(canvas.project_file().is_none());
+}
+
+#[test]
+fn autosave_waits_for_the_interval() {
+    let path = temp_path("journal_interval.ffp");
+    let mut canvas = saved_canvas(&path, fields(2));
+    canvas.set_shapes(fields(3));
+
+    let early = canvas.autosave(AUTOSAVE_INTERVAL_SECS - 1.0);
+    let due = canvas.autosave(AUTOSAVE_INTERVAL_SECS);
+    let again = canvas.autosave(AUTOSAVE_INTERVAL_SECS + 1.0);
+    remove(&path);
+
+    assert!(early.is_none());
+    assert!(matches!(due, Some(Ok(JournalSave::Delta { .. }))));
+    assert!(again.is_none());
+}
+
+#[test]
+fn autosave_skips_a_project_that_was_only_viewed() {
+    let path = temp_path("journal_viewed.ffp");
+    let mut sim = CanvasSimulator::new(saved_canvas(&path, fields(2)));
+    sim.pointer_move(Pos2::new(50.0, 80.0));
+    sim.wait(5.0);
+    sim.canvas_mut().set_zoom(2.0);
+
+    let viewed = sim.canvas_mut().autosave(AUTOSAVE_INTERVAL_SECS);
+    sim.canvas_mut().set_shapes(fields(3));
+    let edited = sim.canvas_mut().save_incremental().expect("Saved");
+    let reopened = reopen(&path);
+    remove(&path);
+
+    assert!(matches!(viewed, Some(Ok(JournalSave::Unchanged))), "{:?}", viewed);
+    assert!(matches!(edited, JournalSave::Delta { .. }));
+    assert_eq!(reopened.zoom_level(), &2.0);
+    assert_eq!(reopened.session_stats().active_seconds(), sim.canvas().session_stats().active_seconds());
+}
+
+#[test]
+fn unsaved_changes_are_tracked_until_the_next_save() {
+    let path = temp_path("journal_unsaved.ffp");
+    let mut canvas = DrawingCanvas::new();
+    let blank = canvas.has_unsaved_changes();
+    canvas.set_shapes(fields(1));
+    let drawn = canvas.has_unsaved_changes();
+    canvas.save_to_file(path.to_str().expect("UTF-8 path")).expect("Saved");
+    let saved = canvas.has_unsaved_changes();
+    canvas.set_shapes(fields(2));
+    let edited = canvas.has_unsaved_changes();
+    canvas.save_incremental().expect("Saved");
+
+    remove(&path);
+    assert!(!blank);
+    assert!(drawn);
+    assert!(!saved);
+    assert!(edited);
+    assert!(!canvas.has_unsaved_changes());
+}
+
+#[test]
+fn moving_the_pointer_over_a_saved_project_leaves_it_unchanged() {
+    let path = temp_path("journal_pointer.ffp");
+    let mut sim = CanvasSimulator::new(saved_canvas(&path, fields(2)));
+    for step in 0..10 {
+        sim.pointer_move(Pos2::new(50.0 + step as f32 * 20.0, 80.0));
+        sim.wait(1.0);
+    }
+
+    remove(&path);
+    assert!(sim.canvas().session_stats().active_seconds() > 0.0);
+    assert!(!sim.canvas().has_unsaved_changes());
+}
@@ -0,0 +1,80 @@
+//! Integration tests for opening projects with the form image decoded in the background
+
+mod common;
+
+use common::{form_png, region, remove, reopen, temp_path};
+use form_factor::{CanvasSimulator, DrawingCanvas};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Save a project with one shape whose form image is `image`
+fn save_project(path: &Path, image: &Path) {
+    let mut project = serde_json::to_value(DrawingCanvas::new()).expect("Serializable");
+    project["form_image_path"] = serde_json::json!(image.to_str().expect("UTF-8 path"));
+    project["shapes"] = serde_json::to_value(vec![region("", 10.0, 10.0, 50.0, 15.0)]).expect("Serializable");
+    let mut canvas = DrawingCanvas::from_json(&project.to_string()).expect("Valid project");
+    canvas.save_to_file(path.to_str().expect("UTF-8 path")).expect("Saved");
+}
+
+/// Draw frames until the form image has finished decoding
+fn finish_loading(sim: &mut CanvasSimulator) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while sim.canvas().form_image_loading() {
+        assert!(Instant::now() < deadline, "form image never finished decoding");
+        std::thread::sleep(Duration::from_millis(5));
+        sim.step();
+    }
+}
+
+#[test]
+fn annotations_are_available_before_the_image_is_decoded() {
+    let image = temp_path("open_lazy.png");
+    let project = temp_path("open_lazy.ffp");
+    std::fs::write(&image, form_png(640, 480)).expect("Temp image");
+    save_project(&project, &image);
+
+    let mut sim = CanvasSimulator::new(reopen(&project));
+    let shapes_on_open = sim.canvas().shapes().len();
+    let path_on_open = sim.canvas().form_image_path().clone();
+    finish_loading(&mut sim);
+    remove(&image);
+    remove(&project);
+
+    assert_eq!(shapes_on_open, 1);
+    assert_eq!(path_on_open.as_deref(), image.to_str());
+    assert!(sim.canvas().form_image().is_some());
+    assert_eq!(*sim.canvas().form_image_size(), Some(egui::vec2(640.0, 480.0)));
+}
+
+#[test]
+fn a_missing_image_leaves_the_project_open_without_one() {
+    let project = temp_path("open_missing_image.ffp");
+    let image = temp_path("open_missing_image.png");
+    save_project(&project, &image);
+
+    let mut sim = CanvasSimulator::new(reopen(&project));
+    finish_loading(&mut sim);
+    remove(&project);
+
+    assert_eq!(sim.canvas().shapes().len(), 1);
+    assert!(sim.canvas().form_image().is_none());
+    assert_eq!(sim.canvas().form_image_path().as_deref(), image.to_str(), "the project keeps its image path");
+}
+
+#[test]
+fn clearing_the_image_cancels_decoding() {
+    let image = temp_path("open_cancel.png");
+    let project = temp_path("open_cancel.ffp");
+    std::fs::write(&image, form_png(32, 32)).expect("Temp image");
+    save_project(&project, &image);
+
+    let mut sim = CanvasSimulator::new(reopen(&project));
+    sim.canvas_mut().clear_canvas_image();
+    std::thread::sleep(Duration::from_millis(50));
+    sim.run_frames(3);
+    remove(&image);
+    remove(&project);
+
+    assert!(!sim.canvas().form_image_loading());
+    assert!(sim.canvas().form_image().is_none());
+}
@@ -0,0 +1,293 @@
+//! Property tests for project file round-tripping
+//!
+//! Any canvas built through the public API must survive save and load
+//! unchanged, and no input — however corrupted — may panic the loader.
+
+use egui::{Color32, Pos2, Stroke};
+use form_factor::{
+    Arrow, Callout, CanvasErrorKind, Circle, DrawingCanvas, Ellipse, Line, PROJECT_FORMAT_VERSION, PolygonShape,
+    Polyline, ProjectEncoding, Rectangle, Shape, StrokeStyle,
+};
+use proptest::prelude::*;
+use std::f32::consts::PI;
+use strum::IntoEnumIterator;
+
+// ============================================================================
+// Generators
+// ============================================================================
+
+fn arb_pos() -> impl Strategy<Value = Pos2> {
+    (-5000.0f32..5000.0, -5000.0f32..5000.0).prop_map(|(x, y)| Pos2::new(x, y))
+}
+
+fn arb_color() -> impl Strategy<Value = Color32> {
+    any::<[u8; 4]>().prop_map(|[r, g, b, a]| Color32::from_rgba_premultiplied(r, g, b, a))
+}
+
+fn arb_stroke() -> impl Strategy<Value = Stroke> {
+    (0.0f32..20.0, arb_color()).prop_map(|(width, color)| Stroke::new(width, color))
+}
+
+fn arb_rectangle() -> impl Strategy<Value = Shape> {
+    prop_oneof![
+        (arb_pos(), arb_pos(), arb_stroke(), arb_color())
+            .prop_filter_map("degenerate rectangle", |(a, b, stroke, fill)| {
+                Rectangle::from_corners(a, b, stroke, fill).ok()
+            }),
+        ([arb_pos(), arb_pos(), arb_pos(), arb_pos()], arb_stroke(), arb_color())
+            .prop_filter_map("invalid quadrilateral", |(corners, stroke, fill)| {
+                Rectangle::from_four_corners(corners, stroke, fill).ok()
+            }),
+    ]
+    .prop_map(Shape::Rectangle)
+}
+
+fn arb_circle() -> impl Strategy<Value = Shape> {
+    (arb_pos(), 0.1f32..1000.0, arb_stroke(), arb_color()).prop_filter_map(
+        "invalid circle",
+        |(center, radius, stroke, fill)| Circle::new(center, radius, stroke, fill).ok().map(Shape::Circle),
+    )
+}
+
+fn arb_ellipse() -> impl Strategy<Value = Shape> {
+    (arb_pos(), 0.1f32..1000.0, 0.1f32..1000.0, -PI..PI, arb_stroke(), arb_color()).prop_filter_map(
+        "invalid ellipse",
+        |(center, rx, ry, rotation, stroke, fill)| {
+            Ellipse::new(center, egui::vec2(rx, ry), rotation, stroke, fill)
+                .ok()
+                .map(Shape::Ellipse)
+        },
+    )
+}
+
+fn arb_polygon() -> impl Strategy<Value = Shape> {
+    (prop::collection::vec(arb_pos(), 3..12), arb_stroke(), arb_color()).prop_filter_map(
+        "invalid polygon",
+        |(points, stroke, fill)| PolygonShape::from_points(points, stroke, fill).ok().map(Shape::Polygon),
+    )
+}
+
+fn arb_line() -> impl Strategy<Value = Shape> {
+    (arb_pos(), arb_pos(), arb_stroke(), any::<bool>()).prop_filter_map(
+        "degenerate line",
+        |(start, end, stroke, show_length)| {
+            Line::new(start, end, stroke).ok().map(|mut line| {
+                line.show_length = show_length;
+                Shape::Line(line)
+            })
+        },
+    )
+}
+
+fn arb_polyline() -> impl Strategy<Value = Shape> {
+    (prop::collection::vec(arb_pos(), 2..12), arb_stroke()).prop_filter_map(
+        "degenerate polyline",
+        |(points, stroke)| Polyline::from_points(points, stroke).ok().map(Shape::Polyline),
+    )
+}
+
+fn arb_arrow() -> impl Strategy<Value = Shape> {
+    (arb_pos(), arb_pos(), arb_stroke()).prop_filter_map("degenerate arrow", |(start, end, stroke)| {
+        Arrow::new(start, end, stroke).ok().map(Shape::Arrow)
+    })
+}
+
+fn arb_callout() -> impl Strategy<Value = Shape> {
+    (arb_pos(), arb_pos(), arb_pos(), "\\PC{0,40}", arb_stroke(), arb_color()).prop_filter_map(
+        "degenerate callout",
+        |(anchor, a, b, text, stroke, fill)| {
+            Callout::new(anchor, egui::Rect::from_two_pos(a, b), text, stroke, fill)
+                .ok()
+                .map(Shape::Callout)
+        },
+    )
+}
+
+/// Any valid shape, with an arbitrary name, sensitivity flag and outline style
+fn arb_shape() -> impl Strategy<Value = Shape> {
+    (
+        prop_oneof![
+            arb_rectangle(),
+            arb_circle(),
+            arb_ellipse(),
+            arb_polygon(),
+            arb_line(),
+            arb_polyline(),
+            arb_arrow(),
+            arb_callout()
+        ],
+        "\\PC{0,24}",
+        any::<bool>(),
+        prop::sample::select(StrokeStyle::iter().collect::<Vec<_>>()),
+    )
+        .prop_map(|(mut shape, name, sensitive, stroke_style)| {
+            shape.set_name(name);
+            shape.set_sensitive(sensitive);
+            shape.set_stroke_style(stroke_style);
+            shape
+        })
+}
+
+fn arb_canvas() -> impl Strategy<Value = DrawingCanvas> {
+    (
+        "\\PC{0,32}",
+        prop::collection::vec(arb_shape(), 0..8),
+        0.1f32..20.0,
+        arb_pos(),
+    )
+        .prop_map(|(name, shapes, zoom, pan)| {
+            let mut canvas = DrawingCanvas::new();
+            canvas.set_project_name(name);
+            canvas.set_shapes(shapes);
+            canvas.set_zoom(zoom);
+            canvas.set_pan_offset(pan.x, pan.y);
+            canvas
+        })
+}
+
+fn to_json(canvas: &DrawingCanvas) -> String {
+    canvas.to_json().expect("Serialization should succeed")
+}
+
+fn to_binary(canvas: &DrawingCanvas) -> Vec<u8> {
+    canvas.to_bytes(ProjectEncoding::MessagePack).expect("Serialization should succeed")
+}
+
+// ============================================================================
+// Round-trip properties
+// ============================================================================
+
+proptest! {
+    #[test]
+    fn canvas_round_trips_losslessly(canvas in arb_canvas()) {
+        let json = to_json(&canvas);
+        let restored = DrawingCanvas::from_json(&json).expect("Valid project should load");
+
+        prop_assert_eq!(restored.shapes(), canvas.shapes());
+        prop_assert_eq!(restored.project_name(), canvas.project_name());
+        prop_assert_eq!(restored.zoom_level(), canvas.zoom_level());
+        prop_assert_eq!(restored.pan_offset(), canvas.pan_offset());
+        prop_assert_eq!(to_json(&restored), json);
+    }
+
+    #[test]
+    fn unversioned_files_load_as_legacy(canvas in arb_canvas()) {
+        let mut value: serde_json::Value = serde_json::from_str(&to_json(&canvas)).expect("Valid JSON");
+        value.as_object_mut().expect("Project is an object").remove("format_version");
+
+        let restored = DrawingCanvas::from_json(&value.to_string()).expect("Legacy project should load");
+
+        prop_assert_eq!(restored.shapes(), canvas.shapes());
+        prop_assert_eq!(*restored.format_version(), PROJECT_FORMAT_VERSION);
+    }
+
+    #[test]
+    fn corrupted_bytes_never_panic(canvas in arb_canvas(), idx in any::<prop::sample::Index>(), byte in any::<u8>()) {
+        let mut bytes = to_json(&canvas).into_bytes();
+        let at = idx.index(bytes.len());
+        bytes[at] = byte;
+
+        if let Ok(loaded) = DrawingCanvas::from_json(&String::from_utf8_lossy(&bytes)) {
+            for shape in loaded.shapes() {
+                prop_assert!(shape.validate().is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn truncated_files_never_panic(canvas in arb_canvas(), idx in any::<prop::sample::Index>()) {
+        let json = to_json(&canvas);
+        let cut = json.floor_char_boundary(idx.index(json.len()));
+
+        prop_assert!(DrawingCanvas::from_json(&json[..cut]).is_err());
+    }
+
+    #[test]
+    fn arbitrary_text_never_panics(text in "\\PC*") {
+        let _ = DrawingCanvas::from_json(&text);
+    }
+
+    #[test]
+    fn binary_projects_round_trip_losslessly(canvas in arb_canvas()) {
+        let bytes = to_binary(&canvas);
+        let restored = DrawingCanvas::from_bytes(&bytes).expect("Valid project should load");
+
+        prop_assert_eq!(ProjectEncoding::detect(&bytes), ProjectEncoding::MessagePack);
+        prop_assert_eq!(restored.shapes(), canvas.shapes());
+        prop_assert_eq!(restored.project_name(), canvas.project_name());
+        prop_assert_eq!(restored.zoom_level(), canvas.zoom_level());
+        prop_assert_eq!(to_binary(&restored), bytes);
+        prop_assert_eq!(to_json(&restored), to_json(&canvas));
+    }
+
+    #[test]
+    fn corrupted_binary_never_panics(canvas in arb_canvas(), idx in any::<prop::sample::Index>(), byte in any::<u8>()) {
+        let mut bytes = to_binary(&canvas);
+        let at = idx.index(bytes.len());
+        bytes[at] = byte;
+
+        if let Ok(loaded) = DrawingCanvas::from_bytes(&bytes) {
+            for shape in loaded.shapes() {
+                prop_assert!(shape.validate().is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn truncated_binary_never_panics(canvas in arb_canvas(), idx in any::<prop::sample::Index>()) {
+        let bytes = to_binary(&canvas);
+
+        prop_assert!(DrawingCanvas::from_bytes(&bytes[..idx.index(bytes.len())]).is_err());
+    }
+}
+
+// ============================================================================
+// Typed errors
+// ============================================================================
+
+/// Serialized canvas holding one circle, as an editable JSON value
+fn circle_project() -> serde_json::Value {
+    let mut canvas = DrawingCanvas::new();
+    let circle = Circle::new(Pos2::new(10.0, 10.0), 5.0, Stroke::NONE, Color32::RED).expect("Valid circle");
+    canvas.set_shapes(vec![Shape::Circle(circle)]);
+    serde_json::from_str(&to_json(&canvas)).expect("Valid JSON")
+}
+
+#[test]
+fn syntax_errors_are_deserialization_errors() {
+    let err = DrawingCanvas::from_json("{ not json").expect_err("Garbage should not load");
+    assert!(matches!(err.kind, CanvasErrorKind::Deserialization(_)));
+}
+
+#[test]
+fn newer_format_versions_are_rejected() {
+    let mut project = circle_project();
+    project["format_version"] = (PROJECT_FORMAT_VERSION + 1).into();
+
+    let err = DrawingCanvas::from_json(&project.to_string()).expect_err("Future format should not load");
+    assert_eq!(
+        err.kind,
+        CanvasErrorKind::UnsupportedFormatVersion {
+            found: PROJECT_FORMAT_VERSION + 1,
+            supported: PROJECT_FORMAT_VERSION,
+        }
+    );
+}
+
+#[test]
+fn invalid_geometry_is_rejected() {
+    let mut project = circle_project();
+    project["shapes"][0]["Circle"]["radius"] = (-1.0).into();
+
+    let err = DrawingCanvas::from_json(&project.to_string()).expect_err("Negative radius should not load");
+    assert!(matches!(err.kind, CanvasErrorKind::InvalidProject(_)));
+}
+
+#[test]
+fn dangling_pii_flags_are_rejected() {
+    let mut project = circle_project();
+    project["pii_flags"] = serde_json::json!([[3, "Email"]]);
+
+    let err = DrawingCanvas::from_json(&project.to_string()).expect_err("Flag on missing detection should not load");
+    assert!(matches!(err.kind, CanvasErrorKind::InvalidProject(_)));
+}
@@ -0,0 +1,227 @@
+//! Integration tests for project templates and starting new projects from them
+
+mod common;
+
+use common::field_at;
+use form_factor::{DrawingCanvas, LayerType, OcrSettings, ProjectTemplate, TemplateLoadProgress};
+use std::path::PathBuf;
+
+fn w2_ocr() -> OcrSettings {
+    OcrSettings {
+        language: "eng+spa".to_string(),
+        min_confidence: 75,
+        preprocessing: false,
+    }
+}
+
+/// Fresh directory for saved templates
+fn template_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("form_factor_templates_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+// ============================================================================
+// Capturing templates
+// ============================================================================
+
+#[test]
+fn template_from_canvas_keeps_setup_but_not_content() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![field_at("employee_name", 10.0, 0.0), field_at("wages", 10.0, 40.0)]);
+    canvas.set_ocr_settings(Some(w2_ocr()));
+    canvas.layer_manager_mut().set_visible(LayerType::Grid, false);
+    canvas.set_project_name("Acme W-2");
+
+    let template = ProjectTemplate::from_canvas("W-2", &canvas);
+
+    assert_eq!(template.name, "W-2");
+    assert_eq!(template.fields.len(), 2);
+    assert_eq!(template.ocr, Some(w2_ocr()));
+    assert!(!template.layers.is_visible(LayerType::Grid));
+}
+
+#[test]
+fn file_names_come_from_template_names() {
+    assert_eq!(ProjectTemplate::new("W-2 (2024)").file_name(), "w_2__2024_.json");
+    assert_eq!(ProjectTemplate::new("  ").file_name(), "template.json");
+}
+
+// ============================================================================
+// Saving and loading
+// ============================================================================
+
+#[test]
+fn saved_templates_load_sorted_by_name() {
+    let dir = template_dir("load_all");
+    ProjectTemplate::new("W-2")
+        .with_ocr(w2_ocr())
+        .save_in(&dir)
+        .expect("Saved");
+    ProjectTemplate::new("1099-NEC")
+        .with_description("Contractor income")
+        .save_in(&dir)
+        .expect("Saved");
+    std::fs::write(dir.join("broken.json"), "not json").expect("Writable");
+    std::fs::write(dir.join("notes.txt"), "ignored").expect("Writable");
+
+    let templates = ProjectTemplate::load_all(&dir);
+
+    let names: Vec<&str> = templates.iter().map(|template| template.name.as_str()).collect();
+    assert_eq!(names, ["1099-NEC", "W-2"]);
+    assert_eq!(templates[0].description, "Contractor income");
+    assert_eq!(templates[1].ocr, Some(w2_ocr()));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn missing_template_directory_has_no_templates() {
+    assert!(ProjectTemplate::load_all(&template_dir("missing")).is_empty());
+}
+
+// ============================================================================
+// New projects
+// ============================================================================
+
+#[test]
+fn new_project_from_template_starts_preconfigured() {
+    let ctx = egui::Context::default();
+    let mut template_source = DrawingCanvas::new();
+    template_source.set_shapes(vec![field_at("employee_name", 10.0, 0.0)]);
+    template_source.set_ocr_settings(Some(w2_ocr()));
+    template_source
+        .layer_manager_mut()
+        .set_visible(LayerType::Detections, false);
+    let template = ProjectTemplate::from_canvas("W-2", &template_source);
+
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![field_at("old", 10.0, 0.0), field_at("older", 10.0, 40.0)]);
+    canvas.start_project_from_template("Jane Doe W-2", &template, &ctx);
+
+    assert_eq!(canvas.project_name(), "Jane Doe W-2");
+    assert_eq!(canvas.shapes().len(), 1);
+    assert_eq!(canvas.shapes()[0].name(), "employee_name");
+    assert_eq!(*canvas.ocr_settings(), Some(w2_ocr()));
+    assert!(!canvas.layer_manager().is_visible(LayerType::Detections));
+    assert!(canvas.form_image_path().is_none());
+}
+
+#[test]
+fn project_ocr_settings_survive_save_and_load() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_ocr_settings(Some(w2_ocr()));
+
+    let loaded = DrawingCanvas::from_json(&canvas.to_json().expect("Serializable")).expect("Loadable");
+
+    assert_eq!(*loaded.ocr_settings(), Some(w2_ocr()));
+}
+
+#[test]
+fn blank_project_resets_the_canvas() {
+    let ctx = egui::Context::default();
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![field_at("old", 10.0, 0.0)]);
+    canvas.set_ocr_settings(Some(w2_ocr()));
+    canvas.set_project_name("Old");
+
+    canvas.start_blank_project(&ctx);
+
+    assert_eq!(canvas.project_name(), "Untitled");
+    assert!(canvas.shapes().is_empty());
+    assert!(canvas.ocr_settings().is_none());
+}
+
+// ============================================================================
+// Usage and deprecation
+// ============================================================================
+
+#[test]
+fn instances_are_counted_in_the_saved_template() {
+    let dir = template_dir("instances");
+    let mut template = ProjectTemplate::new("W-2");
+
+    template.record_instance(&dir).expect("Saved");
+    let path = template.record_instance(&dir).expect("Saved");
+
+    assert_eq!(ProjectTemplate::load_from(&path).expect("Loaded").instances, 2);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn deprecated_templates_keep_their_projects() {
+    let ctx = egui::Context::default();
+    let dir = template_dir("deprecated");
+    let mut source = DrawingCanvas::new();
+    source.set_shapes(vec![field_at("employee_name", 10.0, 0.0)]);
+    let template = ProjectTemplate::from_canvas("W-2 (2023)", &source);
+    let mut project = DrawingCanvas::new();
+    project.start_project_from_template("Jane Doe W-2", &template, &ctx);
+
+    let path = template.with_deprecated(true).save_in(&dir).expect("Saved");
+    let loaded = ProjectTemplate::load_from(&path).expect("Loaded");
+    let reopened = DrawingCanvas::from_json(&project.to_json().expect("Serializable")).expect("Loadable");
+
+    assert!(loaded.deprecated);
+    assert_eq!(reopened.template_name().as_deref(), Some("W-2 (2023)"));
+    assert_eq!(reopened.shapes().len(), 1);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn templates_saved_before_usage_tracking_load_unused() {
+    let template: ProjectTemplate = serde_json::from_str(r#"{"name": "1099-NEC"}"#).expect("Loadable");
+
+    assert_eq!(template.instances, 0);
+    assert!(!template.deprecated);
+}
+
+// ============================================================================
+// Background loading
+// ============================================================================
+
+#[test]
+fn templates_load_in_the_background() {
+    let ctx = egui::Context::default();
+    let dir = template_dir("background");
+    for name in ["W-2", "1099-NEC", "I-9"] {
+        ProjectTemplate::new(name).save_in(&dir).expect("Saved");
+    }
+    std::fs::write(dir.join("broken.json"), "not json").expect("Writable");
+    let mut canvas = DrawingCanvas::new();
+
+    assert!(canvas.project_templates_loading().is_none());
+    canvas.load_project_templates_lazily(&dir);
+    assert!(canvas.project_templates_loading().is_some());
+    let started = std::time::Instant::now();
+    while !canvas.poll_project_templates(&ctx) {
+        assert!(started.elapsed().as_secs() < 10, "Templates never finished loading");
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+
+    let names: Vec<&str> = canvas.project_templates().iter().map(|template| template.name.as_str()).collect();
+    assert_eq!(names, ["1099-NEC", "I-9", "W-2"]);
+    assert!(canvas.project_templates_loading().is_none());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn progress_counts_every_template_file() {
+    let dir = template_dir("progress");
+    ProjectTemplate::new("W-2").save_in(&dir).expect("Saved");
+    std::fs::write(dir.join("broken.json"), "not json").expect("Writable");
+    let mut canvas = DrawingCanvas::new();
+
+    canvas.load_project_templates_lazily(&dir);
+    let started = std::time::Instant::now();
+    let progress = loop {
+        let progress = canvas.project_templates_loading().expect("Loading");
+        if progress.total > 0 && progress.loaded == progress.total {
+            break progress;
+        }
+        assert!(started.elapsed().as_secs() < 10, "Templates never finished loading");
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    };
+
+    assert_eq!(progress, TemplateLoadProgress { loaded: 2, total: 2 });
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,123 @@
+//! Integration tests for recording where a project's form image and data came from
+
+mod common;
+
+use common::{form_png, temp_path};
+use form_factor::{DrawingCanvas, DroppedFile, Provenance, SourceChannel};
+
+// ============================================================================
+// Recording
+// ============================================================================
+
+#[test]
+fn new_projects_record_the_app_version_and_time() {
+    let canvas = DrawingCanvas::new();
+
+    let provenance = canvas.provenance();
+    assert_eq!(provenance.app_version, env!("CARGO_PKG_VERSION"));
+    assert!(provenance.created_at > 0);
+    assert_eq!(provenance.channel, SourceChannel::Unknown);
+    assert!(provenance.source_hash.is_none());
+}
+
+#[test]
+fn form_images_record_their_source_and_hash() {
+    let ctx = egui::Context::default();
+    let dir = temp_path("provenance_source");
+    std::fs::create_dir_all(&dir).expect("Temp dir");
+    let path = dir.join("scan.png");
+    std::fs::write(&path, form_png(40, 30)).expect("Writable");
+    let path_str = path.to_str().expect("UTF-8 path");
+
+    let mut opened = DrawingCanvas::new();
+    opened.load_form_image(path_str, &ctx).expect("Loadable");
+    let mut uploaded = DrawingCanvas::new();
+    uploaded
+        .load_form_image_bytes("scan.png", &form_png(40, 30), &ctx)
+        .expect("Loadable");
+
+    assert_eq!(opened.provenance().source_path.as_deref(), Some(path_str));
+    assert_eq!(opened.provenance().channel, SourceChannel::Opened);
+    assert_eq!(uploaded.provenance().channel, SourceChannel::Uploaded);
+    assert!(opened.provenance().source_hash.is_some());
+    assert_eq!(opened.provenance().source_hash, uploaded.provenance().source_hash);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn dropped_images_record_the_drop() {
+    let ctx = egui::Context::default();
+    let dir = temp_path("provenance_drop");
+    std::fs::create_dir_all(&dir).expect("Temp dir");
+    let path = dir.join("scan.png");
+    std::fs::write(&path, form_png(40, 30)).expect("Writable");
+    let mut canvas = DrawingCanvas::new();
+
+    let file = DroppedFile::from_path(&path).expect("Supported file");
+    canvas.open_dropped_file(&file, &ctx).expect("Opened");
+
+    assert_eq!(canvas.provenance().channel, SourceChannel::Dropped);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn ocr_engine_is_recorded() {
+    let mut canvas = DrawingCanvas::new();
+
+    canvas.record_ocr_engine("tesseract;language=eng");
+
+    assert_eq!(canvas.provenance().ocr_engine.as_deref(), Some("tesseract;language=eng"));
+}
+
+// ============================================================================
+// Saving and loading
+// ============================================================================
+
+#[test]
+fn reopening_a_project_keeps_its_provenance() {
+    let ctx = egui::Context::default();
+    let dir = temp_path("provenance_reopen");
+    std::fs::create_dir_all(&dir).expect("Temp dir");
+    let image = dir.join("scan.png");
+    std::fs::write(&image, form_png(40, 30)).expect("Writable");
+    let mut canvas = DrawingCanvas::new();
+    canvas
+        .open_dropped_file(&DroppedFile::from_path(&image).expect("Supported file"), &ctx)
+        .expect("Opened");
+    canvas.record_ocr_engine("tesseract;language=eng");
+    let project = dir.join("project.json");
+    let project_str = project.to_str().expect("UTF-8 path");
+    canvas.save_to_file(project_str).expect("Saved");
+
+    let mut reopened = DrawingCanvas::new();
+    reopened.load_from_file(project_str, &ctx).expect("Loaded");
+
+    assert_eq!(reopened.provenance(), canvas.provenance());
+    assert_eq!(reopened.provenance().channel, SourceChannel::Dropped);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn projects_saved_without_provenance_show_it_unknown() {
+    let mut json: serde_json::Value =
+        serde_json::from_str(&DrawingCanvas::new().to_json().expect("Serializable")).expect("Valid JSON");
+    json.as_object_mut().expect("Object").remove("provenance");
+
+    let loaded = DrawingCanvas::from_json(&json.to_string()).expect("Loadable");
+
+    assert_eq!(*loaded.provenance(), Provenance::default());
+    assert!(loaded.provenance().entries().iter().all(|(_, value)| value == "unknown" || value == "Unknown"));
+}
+
+#[test]
+fn creation_time_is_shown_in_utc() {
+    let provenance = Provenance {
+        created_at: 1_709_285_400,
+        ..Provenance::default()
+    };
+
+    let entries = provenance.entries();
+
+    let created = entries.iter().find(|(label, _)| *label == "Created").expect("Listed");
+    assert_eq!(created.1, "2024-03-01 09:30 UTC");
+}
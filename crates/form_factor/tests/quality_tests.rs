@@ -0,0 +1,96 @@
+//! Integration tests for OCR samples reaching the quality dashboard
+//!
+//! Run with `cargo test -p form_factor --features plugin-quality`.
+
+#![cfg(feature = "plugin-quality")]
+
+mod common;
+
+use common::{widget_frame, with_detections};
+use form_factor::quality::QualityPlugin;
+use form_factor::{AppEvent, DrawingCanvas, FormFactorWidget, Plugin, PluginContext, Rectangle, Shape};
+use std::sync::{Arc, Mutex};
+
+/// Quality plugin shared with the test, so its report can be read after the widget runs
+struct SharedQuality(Arc<Mutex<QualityPlugin>>);
+
+impl Plugin for SharedQuality {
+    fn name(&self) -> &str {
+        "shared-quality"
+    }
+
+    fn ui(&mut self, _ui: &mut egui::Ui, _ctx: &PluginContext) {}
+
+    fn on_event(&mut self, event: &AppEvent, ctx: &PluginContext) -> Option<AppEvent> {
+        self.0.lock().expect("Unpoisoned").on_event(event, ctx)
+    }
+}
+
+/// Canvas with one detection whose text OCR read
+fn read_canvas(text: &str, confidence: f32) -> DrawingCanvas {
+    let mut canvas = with_detections(vec![extracted("", "", 0.0)]);
+    canvas.record_ocr_result(0, text, confidence);
+    canvas
+}
+
+fn extracted(name: &str, value: &str, confidence: f32) -> Shape {
+    let rect = Rectangle::from_corners(
+        egui::Pos2::new(0.0, 0.0),
+        egui::Pos2::new(100.0, 20.0),
+        egui::Stroke::new(1.0, egui::Color32::BLACK),
+        egui::Color32::TRANSPARENT,
+    )
+    .expect("Valid rectangle");
+    let mut shape = Shape::Rectangle(rect);
+    shape.set_name(name);
+    shape.set_extracted_value(value, Some(confidence));
+    shape
+}
+
+#[test]
+fn settled_values_reach_the_quality_dashboard() {
+    let ctx = egui::Context::default();
+    let quality = Arc::new(Mutex::new(QualityPlugin::new()));
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![extracted("name", "Jane", 95.0), extracted("zip", "9721O", 48.0)]);
+    let mut widget = FormFactorWidget::new(canvas)
+        .with_sidebar(false)
+        .with_plugin(Box::new(SharedQuality(quality.clone())));
+
+    widget.canvas_mut().open_data_entry_panel();
+    assert!(widget.canvas_mut().advance_field());
+    widget.canvas_mut().set_data_entry_value("97210");
+    widget.canvas_mut().close_data_entry_panel();
+    // One frame sends the samples, the next delivers them to plugins
+    widget_frame(&ctx, &mut widget);
+    widget_frame(&ctx, &mut widget);
+
+    let quality = quality.lock().expect("Unpoisoned");
+    let samples = quality.report().samples();
+    assert_eq!(samples.len(), 2);
+    assert!(!samples[0].was_corrected());
+    assert_eq!(samples[1].field, "zip");
+    assert!(samples[1].was_corrected());
+    assert_eq!(quality.report().overall_accuracy(), Some(0.5));
+}
+
+#[test]
+fn corrected_ocr_text_reaches_the_quality_dashboard() {
+    let ctx = egui::Context::default();
+    let quality = Arc::new(Mutex::new(QualityPlugin::new()));
+    let mut widget = FormFactorWidget::new(read_canvas("Tota1", 62.0))
+        .with_sidebar(false)
+        .with_plugin(Box::new(SharedQuality(quality.clone())));
+
+    assert!(widget.canvas_mut().correct_ocr_text(0, "Total"));
+    widget_frame(&ctx, &mut widget);
+    widget_frame(&ctx, &mut widget);
+
+    let quality = quality.lock().expect("Unpoisoned");
+    let samples = quality.report().samples();
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0].field, "Detection 1");
+    assert_eq!(samples[0].proposed, "Tota1");
+    assert_eq!(samples[0].corrected, "Total");
+    assert_eq!(samples[0].confidence, 62.0);
+}
@@ -0,0 +1,131 @@
+//! Integration tests for recent projects pinning, grouping and cleanup
+
+use form_factor::{MAX_RECENT_PROJECTS, RecentProjects};
+use std::path::{Path, PathBuf};
+
+fn recent(paths: &[&str]) -> RecentProjects {
+    let mut recent = RecentProjects::new();
+    for path in paths {
+        recent.add(PathBuf::from(path));
+    }
+    recent
+}
+
+// ============================================================================
+// Max entries
+// ============================================================================
+
+#[test]
+fn oldest_entries_are_evicted_beyond_the_maximum() {
+    let mut recent = RecentProjects::new();
+    for i in 0..MAX_RECENT_PROJECTS + 3 {
+        recent.add(PathBuf::from(format!("/forms/{}.ffp", i)));
+    }
+
+    assert_eq!(recent.len(), MAX_RECENT_PROJECTS);
+    assert_eq!(
+        recent.most_recent(),
+        Some(&PathBuf::from(format!("/forms/{}.ffp", MAX_RECENT_PROJECTS + 2)))
+    );
+
+    recent.set_max_entries(2);
+    let kept: Vec<&PathBuf> = recent.projects().iter().collect();
+    assert_eq!(kept.len(), 2);
+    assert_eq!(recent.max_entries(), 2);
+}
+
+#[test]
+fn pinned_entries_are_never_evicted() {
+    let mut recent = recent(&["/forms/w2.ffp"]);
+    assert!(recent.pin(Path::new("/forms/w2.ffp")));
+    recent.set_max_entries(2);
+
+    for name in ["a", "b", "c"] {
+        recent.add(PathBuf::from(format!("/forms/{}.ffp", name)));
+    }
+
+    assert!(recent.projects().contains(&PathBuf::from("/forms/w2.ffp")));
+    assert_eq!(recent.unpinned().count(), 2);
+    assert_eq!(recent.len(), 3);
+
+    // Unpinning makes it the oldest entry again
+    recent.unpin(Path::new("/forms/w2.ffp"));
+    assert!(!recent.projects().contains(&PathBuf::from("/forms/w2.ffp")));
+}
+
+// ============================================================================
+// Pinning and groups
+// ============================================================================
+
+#[test]
+fn only_listed_projects_can_be_pinned_or_grouped() {
+    let mut recent = recent(&["/forms/w2.ffp"]);
+
+    assert!(!recent.pin(Path::new("/forms/other.ffp")));
+    assert!(!recent.set_group(Path::new("/forms/other.ffp"), Some("W-2".into())));
+    assert!(recent.pinned().is_empty());
+}
+
+#[test]
+fn grouped_lists_groups_by_recency_with_ungrouped_last() {
+    let mut recent = recent(&["/a.ffp", "/b.ffp", "/c.ffp", "/d.ffp", "/e.ffp"]);
+    recent.set_group(Path::new("/a.ffp"), Some("W-2".into()));
+    recent.set_group(Path::new("/c.ffp"), Some("W-2".into()));
+    recent.set_group(Path::new("/d.ffp"), Some(" 1099 ".into()));
+    recent.set_group(Path::new("/b.ffp"), Some("  ".into()));
+    recent.pin(Path::new("/e.ffp"));
+
+    let grouped = recent.grouped();
+    let labels: Vec<Option<&str>> = grouped.iter().map(|(group, _)| *group).collect();
+    assert_eq!(labels, [Some("1099"), Some("W-2"), None]);
+    assert_eq!(grouped[1].1, [&PathBuf::from("/c.ffp"), &PathBuf::from("/a.ffp")]);
+    // Pinned projects are listed separately
+    assert_eq!(grouped[2].1, [&PathBuf::from("/b.ffp")]);
+    assert_eq!(recent.group_names(), ["1099", "W-2"]);
+}
+
+#[test]
+fn removing_a_project_forgets_its_pin_and_group() {
+    let mut recent = recent(&["/forms/w2.ffp"]);
+    recent.pin(Path::new("/forms/w2.ffp"));
+    recent.set_group(Path::new("/forms/w2.ffp"), Some("W-2".into()));
+
+    assert!(recent.remove(Path::new("/forms/w2.ffp")));
+    assert!(!recent.remove(Path::new("/forms/w2.ffp")));
+
+    recent.add(PathBuf::from("/forms/w2.ffp"));
+    assert!(!recent.is_pinned(Path::new("/forms/w2.ffp")));
+    assert_eq!(recent.group(Path::new("/forms/w2.ffp")), None);
+}
+
+// ============================================================================
+// Pruning and persistence
+// ============================================================================
+
+#[test]
+fn prune_missing_removes_deleted_files() {
+    let dir = std::env::temp_dir().join(format!("form_factor_recent_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("Temp dir");
+    let existing = dir.join("existing.ffp");
+    std::fs::write(&existing, "{}").expect("Writable");
+    let missing = dir.join("missing.ffp");
+
+    let mut recent = RecentProjects::new();
+    recent.add(existing.clone());
+    recent.add(missing.clone());
+    recent.pin(&missing);
+
+    assert_eq!(recent.prune_missing(), [missing]);
+    assert_eq!(recent.projects(), [existing]);
+    assert!(recent.pinned().is_empty());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn lists_saved_before_pinning_still_load() {
+    let recent: RecentProjects = serde_json::from_str(r#"{"projects": ["/forms/w2.ffp"]}"#).expect("Old format parses");
+
+    assert_eq!(recent.len(), 1);
+    assert!(recent.pinned().is_empty());
+    assert_eq!(recent.max_entries(), MAX_RECENT_PROJECTS);
+}
@@ -0,0 +1,147 @@
+//! Integration tests for PII detection and redaction
+
+mod common;
+
+use common::temp_path;
+use egui::{Color32, Pos2, Rect, Stroke};
+use form_factor::{CanvasErrorKind, DrawingCanvas, PiiKind, RedactionReason, RedactionRegion, Rectangle, Shape, detect_pii, redact_image};
+
+// ============================================================================
+// PII Detection Tests
+// ============================================================================
+
+#[test]
+fn detects_each_pii_kind() {
+    assert_eq!(detect_pii("SSN: 123-45-6789"), vec![PiiKind::Ssn]);
+    assert_eq!(detect_pii("mail jane.doe@example.org today"), vec![PiiKind::Email]);
+    assert_eq!(detect_pii("Call (555) 867-5309"), vec![PiiKind::Phone]);
+    assert_eq!(detect_pii("Card 4111 1111 1111 1111"), vec![PiiKind::CreditCard]);
+}
+
+#[test]
+fn plain_text_has_no_pii() {
+    assert!(detect_pii("Applicant signature").is_empty());
+    assert!(detect_pii("Total: 1,234.56").is_empty());
+    assert!(detect_pii("").is_empty());
+}
+
+#[test]
+fn detects_multiple_kinds_in_one_string() {
+    let kinds = detect_pii("jane@example.com / 123-45-6789");
+    assert!(kinds.contains(&PiiKind::Email));
+    assert!(kinds.contains(&PiiKind::Ssn));
+}
+
+// ============================================================================
+// Image Redaction Tests
+// ============================================================================
+
+#[test]
+fn redact_image_blacks_out_region() {
+    let mut image = image::RgbaImage::from_pixel(10, 10, image::Rgba([255, 255, 255, 255]));
+    let region = RedactionRegion::new(
+        Rect::from_min_max(Pos2::new(2.0, 2.0), Pos2::new(5.0, 5.0)),
+        RedactionReason::Pii(PiiKind::Ssn),
+    );
+
+    let count = redact_image(&mut image, &[region]);
+
+    assert_eq!(count, 1);
+    assert_eq!(image.get_pixel(3, 3), &image::Rgba([0, 0, 0, 255]));
+    assert_eq!(image.get_pixel(0, 0), &image::Rgba([255, 255, 255, 255]));
+    assert_eq!(image.get_pixel(5, 5), &image::Rgba([255, 255, 255, 255]));
+}
+
+#[test]
+fn redact_image_clips_and_skips_out_of_bounds_regions() {
+    let mut image = image::RgbaImage::from_pixel(4, 4, image::Rgba([255, 255, 255, 255]));
+    let partial = RedactionRegion::new(
+        Rect::from_min_max(Pos2::new(-5.0, -5.0), Pos2::new(1.0, 1.0)),
+        RedactionReason::SensitiveField,
+    );
+    let outside = RedactionRegion::new(
+        Rect::from_min_max(Pos2::new(10.0, 10.0), Pos2::new(20.0, 20.0)),
+        RedactionReason::SensitiveField,
+    );
+
+    let count = redact_image(&mut image, &[partial, outside]);
+
+    assert_eq!(count, 1);
+    assert_eq!(image.get_pixel(0, 0), &image::Rgba([0, 0, 0, 255]));
+    assert_eq!(image.get_pixel(1, 1), &image::Rgba([255, 255, 255, 255]));
+}
+
+// ============================================================================
+// Sensitivity Flag Tests
+// ============================================================================
+
+#[test]
+fn sensitivity_flag_survives_serialization() {
+    let rect = Rectangle::from_corners(
+        Pos2::new(0.0, 0.0),
+        Pos2::new(10.0, 10.0),
+        Stroke::new(1.0, Color32::BLACK),
+        Color32::TRANSPARENT,
+    )
+    .expect("Valid rectangle");
+    let mut shape = Shape::Rectangle(rect);
+    shape.set_sensitive(true);
+
+    let json = serde_json::to_string(&shape).expect("Serialization should succeed");
+    let restored: Shape = serde_json::from_str(&json).expect("Deserialization should succeed");
+
+    assert!(restored.is_sensitive());
+}
+
+#[test]
+fn shapes_without_sensitivity_field_default_to_not_sensitive() {
+    let rect = Rectangle::from_corners(
+        Pos2::new(0.0, 0.0),
+        Pos2::new(10.0, 10.0),
+        Stroke::new(1.0, Color32::BLACK),
+        Color32::TRANSPARENT,
+    )
+    .expect("Valid rectangle");
+    let mut value = serde_json::to_value(Shape::Rectangle(rect)).expect("Serialization should succeed");
+    value["Rectangle"]
+        .as_object_mut()
+        .expect("Rectangle object")
+        .remove("sensitive");
+
+    let restored: Shape = serde_json::from_value(value).expect("Deserialization should succeed");
+
+    assert!(!restored.is_sensitive());
+}
+
+#[test]
+fn redacted_export_refuses_sensitive_shapes_it_cannot_place() {
+    let dir = temp_path("unmapped_redaction");
+    std::fs::create_dir_all(&dir).expect("Temp dir");
+    let form = dir.join("form.png");
+    let output = dir.join("redacted.png");
+    image::RgbaImage::from_pixel(20, 20, image::Rgba([255, 255, 255, 255]))
+        .save(&form)
+        .expect("Form image written");
+    let rect = Rectangle::from_corners(
+        Pos2::new(0.0, 0.0),
+        Pos2::new(10.0, 10.0),
+        Stroke::new(1.0, Color32::BLACK),
+        Color32::TRANSPARENT,
+    )
+    .expect("Valid rectangle");
+    let mut shape = Shape::Rectangle(rect);
+    shape.set_sensitive(true);
+    let mut project = serde_json::to_value(DrawingCanvas::new()).expect("Serializable");
+    project["shapes"] = serde_json::to_value(vec![shape]).expect("Serializable");
+    project["form_image_path"] = serde_json::to_value(form.to_str()).expect("Serializable");
+    let canvas = DrawingCanvas::from_json(&project.to_string()).expect("Valid project");
+
+    assert_eq!(canvas.unmapped_sensitive_shapes(), 1);
+    let err = canvas
+        .export_redacted_image(output.to_str().expect("UTF-8 path"))
+        .expect_err("Unrendered canvas");
+
+    assert!(matches!(err.kind, CanvasErrorKind::InvalidExport(_)));
+    assert!(!output.exists());
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,216 @@
+//! Integration tests for typesetting field values onto a clean form image
+
+mod common;
+
+use common::{form_png, form_simulator};
+use egui::{Color32, Pos2, Rect, Stroke};
+use form_factor::{
+    CanvasSimulator, DrawingCanvas, FieldCondition, FieldFormat, FieldRule, ImagePos, PageSize, Rectangle, Shape,
+};
+use image::{Rgba, RgbaImage};
+
+/// Field covering `bounds` in image pixels, holding `value`
+fn field(sim: &CanvasSimulator, name: &str, bounds: Rect, format: FieldFormat, value: &str) -> Shape {
+    let mapper = sim.canvas().coordinate_mapper();
+    let min = mapper.image_to_canvas(ImagePos(bounds.min)).expect("Image loaded").0;
+    let max = mapper.image_to_canvas(ImagePos(bounds.max)).expect("Image loaded").0;
+    let mut rect = Rectangle::from_corners(min, max, Stroke::new(1.0, Color32::BLACK), Color32::TRANSPARENT)
+        .expect("Valid rectangle");
+    rect.name = name.to_string();
+    rect.rule = FieldRule::new(format);
+    let mut shape = Shape::Rectangle(rect);
+    shape.set_value(value);
+    shape
+}
+
+fn image_rect(x: f32, y: f32, width: f32, height: f32) -> Rect {
+    Rect::from_min_size(Pos2::new(x, y), egui::vec2(width, height))
+}
+
+/// Whether any pixel inside `rect` is darker than the paper
+fn has_ink(image: &RgbaImage, rect: Rect) -> bool {
+    (rect.min.y as u32..rect.max.y as u32)
+        .flat_map(|y| (rect.min.x as u32..rect.max.x as u32).map(move |x| (x, y)))
+        .any(|(x, y)| image.get_pixel(x, y).0[0] < 200)
+}
+
+// ============================================================================
+// Values
+// ============================================================================
+
+#[test]
+fn values_are_placed_in_image_pixels_and_blank_fields_are_skipped() {
+    let mut sim = form_simulator(DrawingCanvas::new(), 400, 300);
+    let fields = vec![
+        field(
+            &sim,
+            "name",
+            image_rect(20.0, 20.0, 200.0, 30.0),
+            FieldFormat::Text,
+            "Jane  Doe\n",
+        ),
+        field(
+            &sim,
+            "phone",
+            image_rect(20.0, 60.0, 200.0, 30.0),
+            FieldFormat::Text,
+            "",
+        ),
+        field(
+            &sim,
+            "married",
+            image_rect(20.0, 100.0, 20.0, 20.0),
+            FieldFormat::Checkbox,
+            "yes",
+        ),
+        field(
+            &sim,
+            "retired",
+            image_rect(60.0, 100.0, 20.0, 20.0),
+            FieldFormat::Checkbox,
+            "no",
+        ),
+    ];
+    sim.canvas_mut().set_shapes(fields);
+
+    let values = sim.canvas().refill_values().expect("Placed");
+
+    let texts: Vec<&str> = values.iter().map(|value| value.text.as_str()).collect();
+    assert_eq!(texts, ["Jane Doe", "X"]);
+    let bounds = values[0].bounds;
+    assert!((bounds.min - Pos2::new(20.0, 20.0)).length() < 0.01);
+    assert!((bounds.max - Pos2::new(220.0, 50.0)).length() < 0.01);
+}
+
+#[test]
+fn fields_whose_condition_is_unmet_are_left_blank() {
+    let mut sim = form_simulator(DrawingCanvas::new(), 400, 300);
+    let mut spouse = field(
+        &sim,
+        "spouse",
+        image_rect(20.0, 60.0, 200.0, 30.0),
+        FieldFormat::Text,
+        "John",
+    );
+    spouse.set_condition(Some(FieldCondition::Checked("married".to_string())));
+    let married = field(
+        &sim,
+        "married",
+        image_rect(20.0, 20.0, 20.0, 20.0),
+        FieldFormat::Checkbox,
+        "",
+    );
+    sim.canvas_mut().set_shapes(vec![married, spouse]);
+
+    assert!(sim.canvas().refill_values().expect("Placed").is_empty());
+}
+
+#[test]
+fn values_need_a_rendered_form_image() {
+    let mut canvas = DrawingCanvas::new();
+    let mut rect = Rectangle::from_corners(
+        Pos2::ZERO,
+        Pos2::new(10.0, 10.0),
+        Stroke::new(1.0, Color32::BLACK),
+        Color32::TRANSPARENT,
+    )
+    .expect("Valid rectangle");
+    rect.name = "name".to_string();
+    let mut shape = Shape::Rectangle(rect);
+    shape.set_value("Jane");
+    canvas.set_shapes(vec![shape]);
+
+    assert!(canvas.refill_values().is_err());
+}
+
+// ============================================================================
+// Output
+// ============================================================================
+
+#[test]
+fn values_are_typeset_inside_their_fields() {
+    let mut sim = form_simulator(DrawingCanvas::new(), 400, 300);
+    let bounds = image_rect(20.0, 20.0, 200.0, 30.0);
+    let shape = field(&sim, "total", bounds, FieldFormat::Number, "1,234.50");
+    sim.canvas_mut().set_shapes(vec![shape]);
+    let clean = RgbaImage::from_pixel(400, 300, Rgba([255, 255, 255, 255]));
+
+    let refilled = sim.canvas().render_refill(&clean).expect("Rendered");
+
+    assert!(has_ink(&refilled, bounds));
+    assert!(!has_ink(&refilled, image_rect(0.0, 60.0, 400.0, 240.0)));
+}
+
+#[test]
+fn clean_images_of_another_resolution_are_scaled() {
+    let mut sim = form_simulator(DrawingCanvas::new(), 400, 300);
+    let shape = field(
+        &sim,
+        "total",
+        image_rect(200.0, 200.0, 150.0, 40.0),
+        FieldFormat::Text,
+        "Paid",
+    );
+    sim.canvas_mut().set_shapes(vec![shape]);
+    let clean = RgbaImage::from_pixel(800, 600, Rgba([255, 255, 255, 255]));
+
+    let refilled = sim.canvas().render_refill(&clean).expect("Rendered");
+
+    assert!(has_ink(&refilled, image_rect(400.0, 400.0, 300.0, 80.0)));
+    assert!(!has_ink(&refilled, image_rect(0.0, 0.0, 800.0, 390.0)));
+}
+
+#[test]
+fn pdf_keeps_values_as_text() {
+    let mut sim = form_simulator(DrawingCanvas::new(), 400, 300);
+    let shape = field(
+        &sim,
+        "name",
+        image_rect(20.0, 20.0, 200.0, 30.0),
+        FieldFormat::Text,
+        "Jane (Doe)",
+    );
+    sim.canvas_mut().set_shapes(vec![shape]);
+    let clean = RgbaImage::from_pixel(400, 300, Rgba([255, 255, 255, 255]));
+
+    let pdf = sim.canvas().refill_pdf(&clean, PageSize::A4).expect("Built");
+    let pdf = String::from_utf8_lossy(&pdf);
+
+    assert!(pdf.starts_with("%PDF-1.4"));
+    assert!(pdf.contains("(Jane \\(Doe\\)) Tj"));
+    assert!(pdf.contains("/MediaBox [0 0 595 842]"));
+}
+
+#[test]
+fn export_picks_the_format_from_the_extension() {
+    let mut sim = form_simulator(DrawingCanvas::new(), 400, 300);
+    let shape = field(
+        &sim,
+        "name",
+        image_rect(20.0, 20.0, 200.0, 30.0),
+        FieldFormat::Text,
+        "Jane",
+    );
+    sim.canvas_mut().set_shapes(vec![shape]);
+    let dir = std::env::temp_dir().join(format!("form_factor_refill_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("Creatable");
+    let clean = dir.join("clean.png");
+    std::fs::write(&clean, form_png(400, 300)).expect("Writable");
+    let (png, pdf) = (dir.join("out.png"), dir.join("out.pdf"));
+
+    for output in [&png, &pdf] {
+        sim.canvas()
+            .export_refill(
+                clean.to_str().expect("UTF-8 path"),
+                output.to_str().expect("UTF-8 path"),
+                PageSize::Letter,
+            )
+            .expect("Exported");
+    }
+    let png_size = image::open(&png).map(|image| (image.width(), image.height()));
+    let pdf_bytes = std::fs::read(&pdf).expect("Readable");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(png_size.expect("Readable PNG"), (400, 300));
+    assert!(pdf_bytes.starts_with(b"%PDF"));
+}
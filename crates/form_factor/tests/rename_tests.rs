@@ -0,0 +1,125 @@
+//! Integration tests for find and rename
+
+mod common;
+
+use common::{field, names, with_detections};
+use egui::Pos2;
+use form_factor::{CommentTarget, DrawingCanvas, FieldCondition, FieldGroup, RenameQuery};
+
+fn canvas_with_names(names: &[&str]) -> DrawingCanvas {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(names.iter().map(|name| field(name, Pos2::ZERO)).collect());
+    canvas
+}
+
+// ============================================================================
+// Preview
+// ============================================================================
+
+#[test]
+fn preview_lists_changes_without_applying_them() {
+    let canvas = canvas_with_names(&["line_1_amount", "line_2_amount", "total"]);
+
+    let preview = canvas.preview_rename(&RenameQuery::new("amount", "amt")).expect("Valid query");
+
+    assert_eq!(preview.len(), 2);
+    assert_eq!(preview[0].target, CommentTarget::Shape(0));
+    assert_eq!(preview[0].old_name, "line_1_amount");
+    assert_eq!(preview[0].new_name, "line_1_amt");
+    assert!(!preview[0].conflict);
+    assert_eq!(names(&canvas), vec!["line_1_amount", "line_2_amount", "total"]);
+}
+
+#[test]
+fn preview_flags_colliding_names() {
+    let canvas = canvas_with_names(&["name_first", "name_last", "other"]);
+
+    let preview = canvas
+        .preview_rename(&RenameQuery::new("_(first|last)", "").with_regex(true))
+        .expect("Valid query");
+
+    assert_eq!(preview.len(), 2);
+    assert!(preview.iter().all(|rename| rename.conflict));
+}
+
+#[test]
+fn empty_search_matches_nothing() {
+    let canvas = canvas_with_names(&["a"]);
+
+    assert!(canvas.preview_rename(&RenameQuery::new("", "x")).expect("Valid query").is_empty());
+}
+
+#[test]
+fn invalid_regex_is_an_error() {
+    let canvas = canvas_with_names(&["a"]);
+
+    assert!(canvas.preview_rename(&RenameQuery::new("(", "x").with_regex(true)).is_err());
+}
+
+// ============================================================================
+// Apply
+// ============================================================================
+
+#[test]
+fn regex_captures_reorder_name_parts() {
+    let mut canvas = canvas_with_names(&["amount_1", "amount_12", "notes"]);
+
+    let query = RenameQuery::new(r"^(\w+)_(\d+)$", "line_${2}_$1").with_regex(true);
+    let renamed = canvas.apply_rename(&query).expect("Valid query");
+
+    assert_eq!(renamed.len(), 2);
+    assert_eq!(names(&canvas), vec!["line_1_amount", "line_12_amount", "notes"]);
+}
+
+#[test]
+fn plain_text_ignores_regex_syntax_and_dollar_signs() {
+    let mut canvas = canvas_with_names(&["cost (usd)", "Cost (USD)"]);
+
+    canvas
+        .apply_rename(&RenameQuery::new("cost (usd)", "price $").with_case_sensitive(false))
+        .expect("Valid query");
+
+    assert_eq!(names(&canvas), vec!["price $", "price $"]);
+}
+
+#[test]
+fn detections_are_renamed_only_when_included() {
+    let mut canvas = with_detections(vec![field("Text Region 1", Pos2::ZERO)]);
+    canvas.set_shapes(vec![field("Region notes", Pos2::ZERO)]);
+
+    canvas.apply_rename(&RenameQuery::new("Region", "Area")).expect("Valid query");
+    assert_eq!(canvas.detections()[0].name(), "Text Region 1");
+
+    canvas
+        .apply_rename(&RenameQuery::new("Region", "Area").with_detections(true))
+        .expect("Valid query");
+    assert_eq!(canvas.detections()[0].name(), "Text Area 1");
+    assert_eq!(names(&canvas), vec!["Area notes"]);
+}
+
+#[test]
+fn renaming_a_field_updates_the_conditions_and_groups_that_name_it() {
+    let mut shapes: Vec<_> = ["has_spouse", "spouse_name", "spouse_ssn"]
+        .iter()
+        .map(|name| field(name, Pos2::ZERO))
+        .collect();
+    shapes[1].set_condition(Some(FieldCondition::Checked("has_spouse".into())));
+    shapes[2].set_condition(Some(FieldCondition::Equals {
+        field: "has_spouse".into(),
+        value: "yes".into(),
+    }));
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(shapes);
+    canvas
+        .add_field_group(FieldGroup::new("spouse", ["has_spouse", "spouse_name"]))
+        .expect("Valid group");
+
+    canvas.apply_rename(&RenameQuery::new("has_spouse", "married")).expect("Valid query");
+
+    assert_eq!(canvas.shapes()[1].condition(), Some(&FieldCondition::Checked("married".into())));
+    assert_eq!(canvas.shapes()[2].condition().map(FieldCondition::field), Some("married"));
+    assert_eq!(
+        canvas.field_group("spouse").map(|group| group.fields.clone()),
+        Some(vec!["married".to_string(), "spouse_name".to_string()])
+    );
+}
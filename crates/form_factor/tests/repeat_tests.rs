@@ -0,0 +1,86 @@
+//! Integration tests for shape duplication and grid repeat
+
+mod common;
+
+use common::region;
+use egui::{Key, Modifiers, Pos2, vec2};
+use form_factor::{CanvasSimulator, DrawingCanvas, MAX_REPEAT_COPIES, RepeatGrid, Shape};
+
+fn canvas_with_box() -> DrawingCanvas {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![region("amount", 0.0, 0.0, 30.0, 10.0)]);
+    canvas
+}
+
+// ============================================================================
+// Grid repeat
+// ============================================================================
+
+#[test]
+fn repeat_fills_grid_in_row_major_order() {
+    let mut canvas = canvas_with_box();
+    let grid = RepeatGrid::new(2, 3, vec2(0.0, 15.0), vec2(40.0, 0.0));
+
+    let added = canvas.repeat_shape(0, &grid);
+
+    assert_eq!(added, vec![1, 2, 3, 4, 5]);
+    let names: Vec<&str> = canvas.shapes().iter().map(Shape::name).collect();
+    assert_eq!(names, vec!["amount", "amount 2", "amount 3", "amount 4", "amount 5", "amount 6"]);
+    assert_eq!(canvas.shapes()[2].bounding_rect().min, Pos2::new(80.0, 0.0));
+    assert_eq!(canvas.shapes()[5].bounding_rect().min, Pos2::new(80.0, 15.0));
+}
+
+#[test]
+fn adjacent_grid_stacks_copies_edge_to_edge() {
+    let mut canvas = canvas_with_box();
+    let size = canvas.shapes()[0].bounding_rect().size();
+
+    canvas.repeat_shape(0, &RepeatGrid::adjacent(3, 1, size));
+
+    let tops: Vec<f32> = canvas.shapes().iter().map(|s| s.bounding_rect().top()).collect();
+    assert_eq!(tops, vec![0.0, size.y, 2.0 * size.y]);
+}
+
+#[test]
+fn repeat_rejects_missing_shape_and_oversized_grid() {
+    let mut canvas = canvas_with_box();
+
+    assert!(canvas.repeat_shape(3, &RepeatGrid::default()).is_empty());
+    let huge = RepeatGrid::new(MAX_REPEAT_COPIES, 2, vec2(0.0, 1.0), vec2(1.0, 0.0));
+    assert!(canvas.repeat_shape(0, &huge).is_empty());
+    assert!(canvas.repeat_shape(0, &RepeatGrid::new(1, 1, vec2(0.0, 1.0), vec2(1.0, 0.0))).is_empty());
+    assert_eq!(canvas.shapes().len(), 1);
+}
+
+// ============================================================================
+// Duplicate shortcut
+// ============================================================================
+
+#[test]
+fn ctrl_d_duplicates_selected_shape() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_zoom(1.0);
+    let mut sim = CanvasSimulator::new(canvas);
+    sim.step();
+    let center = sim.canvas_rect().center();
+    sim.canvas_mut().set_shapes(vec![region("amount", center.x, center.y, 30.0, 10.0)]);
+    sim.select_at(center + vec2(15.0, 5.0));
+    assert_eq!(*sim.canvas().selected_shape(), Some(0));
+
+    sim.key_press(Key::D, Modifiers::COMMAND);
+    sim.key_press(Key::D, Modifiers::COMMAND);
+
+    let names: Vec<&str> = sim.canvas().shapes().iter().map(Shape::name).collect();
+    assert_eq!(names, vec!["amount", "amount copy", "amount copy copy"]);
+    assert_eq!(*sim.canvas().selected_shape(), Some(2));
+}
+
+#[test]
+fn ctrl_d_without_selection_does_nothing() {
+    let mut sim = CanvasSimulator::new(canvas_with_box());
+    sim.step();
+
+    sim.key_press(Key::D, Modifiers::COMMAND);
+
+    assert_eq!(sim.canvas().shapes().len(), 1);
+}
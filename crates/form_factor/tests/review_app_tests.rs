@@ -0,0 +1,53 @@
+//! Integration tests for the browser review app
+
+mod common;
+
+use common::form_png;
+use egui::{Color32, Pos2, Stroke};
+use form_factor::{DrawingCanvas, Rectangle, ReviewApp, Shape};
+
+// ============================================================================
+// Opening files
+// ============================================================================
+
+#[test]
+fn dropped_image_bytes_become_the_form_image() {
+    let ctx = egui::Context::default();
+    let mut app = ReviewApp::new();
+
+    app.open_file("scan.png", &form_png(40, 30), &ctx).expect("Image opens");
+
+    assert_eq!(*app.canvas().form_image_size(), Some(egui::vec2(40.0, 30.0)));
+    assert_eq!(app.canvas().form_image_path().as_deref(), Some("scan.png"));
+}
+
+#[test]
+fn dropped_project_replaces_the_canvas() {
+    let ctx = egui::Context::default();
+    let mut project = DrawingCanvas::new();
+    let rect = Rectangle::from_corners(
+        Pos2::new(0.0, 0.0),
+        Pos2::new(20.0, 10.0),
+        Stroke::new(1.0, Color32::BLACK),
+        Color32::TRANSPARENT,
+    )
+    .expect("Valid rectangle");
+    project.set_shapes(vec![Shape::Rectangle(rect)]);
+    let json = project.to_json().expect("Serializable");
+
+    let mut app = ReviewApp::new();
+    app.open_file("Form.JSON", json.as_bytes(), &ctx).expect("Project opens");
+
+    assert_eq!(app.canvas().shapes().len(), 1);
+}
+
+#[test]
+fn unreadable_files_are_errors_and_keep_the_canvas() {
+    let ctx = egui::Context::default();
+    let mut app = ReviewApp::new();
+    app.open_file("scan.png", &form_png(40, 30), &ctx).expect("Image opens");
+
+    assert!(app.open_file("broken.json", b"{ not json", &ctx).is_err());
+    assert!(app.open_file("broken.png", b"not an image", &ctx).is_err());
+    assert_eq!(*app.canvas().form_image_size(), Some(egui::vec2(40.0, 30.0)));
+}
@@ -0,0 +1,252 @@
+//! Integration tests for save conflicts between operators sharing a project file
+
+mod common;
+
+use common::{field, names, remove, reopen, temp_path};
+use egui::Pos2;
+use form_factor::{
+    AUTOSAVE_INTERVAL_SECS, CanvasErrorKind, CanvasSimulator, CommentTarget, DrawingCanvas, Shape,
+};
+use std::path::Path;
+
+/// Project with `field_0` and `field_1`, saved to `path`
+fn shared_project(path: &Path) -> DrawingCanvas {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![field("field_0", Pos2::ZERO), field("field_1", Pos2::new(0.0, 20.0))]);
+    canvas.save_to_file(path.to_str().expect("UTF-8 path")).expect("Saved");
+    canvas
+}
+
+fn add_field(canvas: &mut DrawingCanvas, name: &str) {
+    let mut shapes = canvas.shapes().clone();
+    shapes.push(field(name, Pos2::new(100.0, 20.0 * shapes.len() as f32)));
+    canvas.set_shapes(shapes);
+}
+
+fn rename_first(canvas: &mut DrawingCanvas, name: &str) {
+    let mut shapes = canvas.shapes().clone();
+    shapes[0] = field(name, Pos2::ZERO);
+    canvas.set_shapes(shapes);
+}
+
+/// Two operators with the project at `path` open, where the second has saved a field named `theirs`
+fn conflicting(path: &Path) -> DrawingCanvas {
+    let mut ours = shared_project(path);
+    let mut theirs = reopen(path);
+    add_field(&mut theirs, "theirs");
+    theirs.save_incremental().expect("Saved");
+    add_field(&mut ours, "ours");
+    ours
+}
+
+// ============================================================================
+// Detecting conflicts
+// ============================================================================
+
+#[test]
+fn saving_a_file_nobody_else_changed_succeeds() {
+    let path = temp_path("conflict_none.ffp");
+    let mut canvas = shared_project(&path);
+
+    add_field(&mut canvas, "field_2");
+    canvas.save_incremental().expect("Saved");
+    add_field(&mut canvas, "field_3");
+    canvas.compact_journal().expect("Compacted");
+    add_field(&mut canvas, "field_4");
+    canvas.save_to_file(path.to_str().expect("UTF-8 path")).expect("Saved");
+
+    let reopened = reopen(&path);
+    remove(&path);
+    assert!(canvas.save_conflict().is_none());
+    assert_eq!(reopened.shapes().len(), 5);
+}
+
+#[test]
+fn saving_over_another_operators_changes_is_a_conflict() {
+    let path = temp_path("conflict_detected.ffp");
+    let mut ours = conflicting(&path);
+
+    let err = ours.save_incremental().expect_err("Conflict");
+
+    let on_disk = reopen(&path);
+    remove(&path);
+    assert!(matches!(err.kind, CanvasErrorKind::SaveConflict(_)), "{}", err);
+    let conflict = ours.save_conflict().as_ref().expect("Conflict window open");
+    assert_eq!(conflict.path(), Some(path.as_path()));
+    assert_eq!(conflict.their_changes().counts(), (1, 0, 0));
+    assert_eq!(names(&on_disk), ["field_0", "field_1", "theirs"]);
+}
+
+#[test]
+fn saving_to_the_shared_file_conflicts_but_saving_elsewhere_does_not() {
+    let path = temp_path("conflict_save_as.ffp");
+    let other = temp_path("conflict_save_as_copy.ffp");
+    let mut ours = conflicting(&path);
+
+    let err = ours.save_to_file(path.to_str().expect("UTF-8 path")).expect_err("Conflict");
+    ours.cancel_save_conflict();
+    ours.save_to_file(other.to_str().expect("UTF-8 path")).expect("Saved as a copy");
+
+    let on_disk = reopen(&path);
+    remove(&path);
+    remove(&other);
+    assert!(matches!(err.kind, CanvasErrorKind::SaveConflict(_)));
+    assert_eq!(names(&on_disk), ["field_0", "field_1", "theirs"]);
+}
+
+#[test]
+fn autosave_waits_while_a_conflict_is_open() {
+    let path = temp_path("conflict_autosave.ffp");
+    let mut ours = conflicting(&path);
+    let _ = ours.compact_journal();
+
+    let autosaved = ours.autosave(AUTOSAVE_INTERVAL_SECS * 10.0);
+
+    remove(&path);
+    assert!(ours.save_conflict().is_some());
+    assert!(autosaved.is_none());
+}
+
+// ============================================================================
+// Resolving conflicts
+// ============================================================================
+
+#[test]
+fn overwriting_saves_your_version() {
+    let path = temp_path("conflict_overwrite.ffp");
+    let mut ours = conflicting(&path);
+    let _ = ours.save_incremental();
+
+    ours.overwrite_save_conflict().expect("Overwritten");
+    add_field(&mut ours, "after");
+    let saved = ours.save_incremental();
+
+    let on_disk = reopen(&path);
+    remove(&path);
+    assert!(ours.save_conflict().is_none());
+    assert!(saved.is_ok(), "{:?}", saved);
+    assert_eq!(names(&on_disk), ["field_0", "field_1", "ours", "after"]);
+}
+
+#[test]
+fn merging_keeps_both_operators_new_fields() {
+    let path = temp_path("conflict_merge.ffp");
+    let mut ours = conflicting(&path);
+    let _ = ours.save_incremental();
+
+    let overwritten = ours.merge_save_conflict(&egui::Context::default()).expect("Merged");
+
+    let on_disk = reopen(&path);
+    remove(&path);
+    assert!(ours.save_conflict().is_none());
+    assert!(!overwritten.contains(&"shapes".to_string()), "{:?}", overwritten);
+    assert_eq!(names(&ours), ["field_0", "field_1", "theirs", "ours"]);
+    assert_eq!(names(&on_disk), ["field_0", "field_1", "theirs", "ours"]);
+}
+
+/// Add a detection named `name` to the canvas, by way of the detection cache
+fn add_detection(canvas: &mut DrawingCanvas, name: &str) {
+    let dir = temp_path(&format!("conflict_cache_{}", name));
+    let mut project = serde_json::to_value(DrawingCanvas::new()).expect("Serializable");
+    project["detections"] = serde_json::to_value(vec![field(name, Pos2::new(0.0, 40.0))]).expect("Serializable");
+    let source = DrawingCanvas::from_json(&project.to_string()).expect("Valid project");
+    source.cache_detections(&dir, name, "test", 0).expect("Cached");
+    canvas.load_cached_detections(&dir, name).expect("Loaded from the cache");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn merging_keeps_your_comments_and_tags_on_your_new_detections() {
+    let path = temp_path("conflict_merge_detections.ffp");
+    let mut ours = shared_project(&path);
+    let mut theirs = reopen(&path);
+    add_detection(&mut theirs, "theirs");
+    theirs.add_comment_thread(CommentTarget::Detection(0), "their note");
+    theirs.save_incremental().expect("Saved");
+    add_detection(&mut ours, "ours");
+    ours.add_comment_thread(CommentTarget::Detection(0), "our note");
+    assert!(ours.tag_detection(0, Some("Signature".to_string())));
+    let _ = ours.save_incremental();
+
+    ours.merge_save_conflict(&egui::Context::default()).expect("Merged");
+
+    let on_disk = reopen(&path);
+    remove(&path);
+    let detections: Vec<&str> = on_disk.detections().iter().map(Shape::name).collect();
+    assert_eq!(detections, ["theirs", "ours"]);
+    let targets: Vec<(&str, CommentTarget)> = on_disk
+        .comment_threads()
+        .iter()
+        .map(|thread| (thread.comments[0].body.as_str(), thread.target))
+        .collect();
+    assert_eq!(
+        targets,
+        [("their note", CommentTarget::Detection(0)), ("our note", CommentTarget::Detection(1))]
+    );
+    assert_eq!(on_disk.detection_tag(0), None);
+    assert_eq!(on_disk.detection_tag(1), Some("Signature"));
+}
+
+#[test]
+fn merging_keeps_your_edit_where_both_changed_the_same_field() {
+    let path = temp_path("conflict_merge_both.ffp");
+    let mut ours = shared_project(&path);
+    let mut theirs = reopen(&path);
+    rename_first(&mut theirs, "their_name");
+    theirs.save_incremental().expect("Saved");
+    rename_first(&mut ours, "our_name");
+    let _ = ours.save_incremental();
+
+    let overwritten = ours.merge_save_conflict(&egui::Context::default()).expect("Merged");
+
+    let on_disk = reopen(&path);
+    remove(&path);
+    assert!(overwritten.contains(&"shapes".to_string()), "{:?}", overwritten);
+    assert_eq!(names(&on_disk), ["our_name", "field_1"]);
+}
+
+/// `canvas` after `seconds` of moving the pointer over it
+fn annotate_for(canvas: DrawingCanvas, seconds: usize) -> DrawingCanvas {
+    let mut sim = CanvasSimulator::new(canvas);
+    for step in 0..=seconds {
+        sim.pointer_move(Pos2::new(50.0 + step as f32, 80.0));
+        sim.wait(1.0);
+    }
+    sim.into_canvas()
+}
+
+#[test]
+fn merging_adds_up_both_operators_effort() {
+    let path = temp_path("conflict_merge_stats.ffp");
+    let ours = shared_project(&path);
+    let mut theirs = annotate_for(reopen(&path), 10);
+    add_field(&mut theirs, "theirs");
+    theirs.save_incremental().expect("Saved");
+    let mut ours = annotate_for(ours, 5);
+    add_field(&mut ours, "ours");
+    let _ = ours.save_incremental();
+    let both = theirs.session_stats().active_seconds() + ours.session_stats().active_seconds();
+
+    let overwritten = ours.merge_save_conflict(&egui::Context::default()).expect("Merged");
+
+    let on_disk = reopen(&path);
+    remove(&path);
+    assert!(!overwritten.contains(&"session_stats".to_string()), "{:?}", overwritten);
+    assert!(both > 0.0);
+    assert_eq!(on_disk.session_stats().active_seconds(), both);
+}
+
+#[test]
+fn cancelling_asks_again_on_the_next_save() {
+    let path = temp_path("conflict_cancel.ffp");
+    let mut ours = conflicting(&path);
+    let _ = ours.save_incremental();
+
+    let cancelled = ours.cancel_save_conflict();
+    let again = ours.save_incremental();
+
+    remove(&path);
+    assert!(cancelled.is_some());
+    assert!(again.is_err());
+    assert!(ours.save_conflict().is_some());
+}
@@ -0,0 +1,122 @@
+//! Integration tests for capturing canvas regions as screenshots
+
+mod common;
+
+use common::{simulator, temp_path};
+use egui::{Color32, ColorImage, pos2};
+use form_factor::{CanvasCommand, CanvasSimulator, CommentTarget, DrawingCanvas, ScreenshotStage};
+
+/// Answer the canvas's screenshot request with a window image of solid `color`
+fn deliver_screenshot(sim: &mut CanvasSimulator, color: Color32) {
+    assert!(sim.screenshot_requested());
+    let size = sim.ctx().content_rect().size();
+    sim.deliver_screenshot(ColorImage::filled([size.x as usize, size.y as usize], color));
+}
+
+// ============================================================================
+// Region selection
+// ============================================================================
+
+#[test]
+fn dragging_while_armed_selects_a_region_instead_of_drawing() {
+    let mut sim = simulator();
+    sim.canvas_mut().set_tool(form_factor::ToolMode::Rectangle);
+    assert!(sim.canvas_mut().apply_command(CanvasCommand::BeginScreenshot));
+
+    sim.drag(pos2(100.0, 100.0), pos2(200.0, 160.0));
+
+    assert_eq!(sim.canvas().shape_count(), 0);
+    let ScreenshotStage::Waiting { region } = sim.canvas().screenshot_stage() else {
+        panic!(
+            "Expected a screenshot request, got {:?}",
+            sim.canvas().screenshot_stage()
+        );
+    };
+    assert_eq!(region.size(), egui::vec2(100.0, 60.0));
+}
+
+#[test]
+fn captured_region_is_cropped_from_the_window() {
+    let mut sim = simulator();
+    sim.canvas_mut().begin_screenshot();
+    sim.drag(pos2(100.0, 100.0), pos2(200.0, 160.0));
+
+    deliver_screenshot(&mut sim, Color32::from_rgb(10, 20, 30));
+
+    let capture = sim.canvas().screenshot().expect("Captured");
+    assert_eq!(capture.image.dimensions(), (100, 60));
+    assert_eq!(capture.image.get_pixel(50, 30).0, [10, 20, 30, 255]);
+}
+
+#[test]
+fn escape_cancels_the_selection() {
+    let mut sim = simulator();
+    sim.canvas_mut().begin_screenshot();
+
+    sim.key_press(egui::Key::Escape, egui::Modifiers::NONE);
+
+    assert_eq!(*sim.canvas().screenshot_stage(), ScreenshotStage::Idle);
+}
+
+// ============================================================================
+// Using the capture
+// ============================================================================
+
+#[test]
+fn capture_is_attached_to_a_comment_on_the_shape_it_shows() {
+    let mut sim = simulator();
+    sim.draw_rectangle(pos2(100.0, 100.0), pos2(200.0, 150.0));
+    sim.select_at(pos2(600.0, 600.0));
+    sim.canvas_mut().begin_screenshot();
+    sim.drag(pos2(90.0, 90.0), pos2(150.0, 130.0));
+    deliver_screenshot(&mut sim, Color32::WHITE);
+
+    assert_eq!(sim.canvas().screenshot_target(), Some(CommentTarget::Shape(0)));
+
+    let dir = temp_path("screenshot_attach");
+    let path = sim
+        .canvas_mut()
+        .attach_screenshot(CommentTarget::Shape(0), "Label is cut off", &dir)
+        .expect("Attached");
+    let attached = image::open(&path).expect("Readable PNG").to_rgba8().dimensions();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(attached, (60, 40));
+    let thread = &sim.canvas().comment_threads()[0];
+    assert_eq!(thread.target, CommentTarget::Shape(0));
+    assert_eq!(thread.comments[0].body, "Label is cut off");
+    assert_eq!(thread.comments[0].attachment.as_deref(), Some(path.as_path()));
+    assert!(sim.canvas().screenshot().is_none());
+}
+
+#[test]
+fn attachments_are_kept_in_projects() {
+    let mut sim = simulator();
+    sim.draw_rectangle(pos2(100.0, 100.0), pos2(200.0, 150.0));
+    sim.canvas_mut().begin_screenshot();
+    sim.drag(pos2(90.0, 90.0), pos2(150.0, 130.0));
+    deliver_screenshot(&mut sim, Color32::WHITE);
+    let dir = temp_path("screenshot_project");
+    let path = sim
+        .canvas_mut()
+        .attach_screenshot(CommentTarget::Shape(0), "", &dir)
+        .expect("Attached");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let json = sim.canvas().to_json().expect("Serializable");
+    let loaded = DrawingCanvas::from_json(&json).expect("Loadable");
+
+    assert_eq!(
+        loaded.comment_threads()[0].comments[0].attachment.as_deref(),
+        Some(path.as_path())
+    );
+}
+
+#[test]
+fn nothing_to_save_without_a_capture() {
+    let canvas = DrawingCanvas::new();
+    let path = temp_path("screenshot_none").with_extension("png");
+
+    assert!(canvas.save_screenshot(path.to_str().expect("UTF-8 path")).is_err());
+    assert!(!path.exists());
+}
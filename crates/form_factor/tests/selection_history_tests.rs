@@ -0,0 +1,141 @@
+//! Integration tests for going back and forward through inspected objects
+
+use egui::{Key, Modifiers, PointerButton, pos2};
+use form_factor::{
+    CanvasCommand, CanvasSimulator, CommentTarget, DrawingCanvas, MAX_SELECTION_HISTORY, SelectionHistory,
+};
+
+/// Simulator with three rectangles side by side, the last one selected
+fn three_shapes() -> CanvasSimulator {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_zoom(1.0);
+    let mut sim = CanvasSimulator::new(canvas);
+    for x in [100.0, 250.0, 400.0] {
+        sim.draw_rectangle(pos2(x, 100.0), pos2(x + 80.0, 160.0));
+    }
+    sim
+}
+
+/// Press and release an extra mouse button over the canvas
+fn mouse_button(sim: &mut CanvasSimulator, button: PointerButton) {
+    let pos = sim.canvas_rect().center();
+    for pressed in [true, false] {
+        sim.push_event(egui::Event::PointerButton {
+            pos,
+            button,
+            pressed,
+            modifiers: Modifiers::NONE,
+        });
+    }
+    sim.step();
+}
+
+// ============================================================================
+// History
+// ============================================================================
+
+#[test]
+fn back_and_forward_step_through_visits() {
+    let mut history = SelectionHistory::default();
+    for idx in 0..3 {
+        history.visit(CommentTarget::Shape(idx));
+    }
+    // Revisiting the current object is not a new step
+    history.visit(CommentTarget::Shape(2));
+
+    assert_eq!(history.back(|_| true), Some(CommentTarget::Shape(1)));
+    assert_eq!(history.back(|_| true), Some(CommentTarget::Shape(0)));
+    assert_eq!(history.back(|_| true), None);
+    assert!(history.can_go_forward());
+    assert_eq!(history.forward(|_| true), Some(CommentTarget::Shape(1)));
+
+    // A new visit forgets the objects ahead
+    history.visit(CommentTarget::Detection(4));
+    assert!(!history.can_go_forward());
+    assert_eq!(history.back(|_| true), Some(CommentTarget::Shape(1)));
+}
+
+#[test]
+fn missing_objects_are_skipped() {
+    let mut history = SelectionHistory::default();
+    for target in [CommentTarget::Shape(0), CommentTarget::Detection(7), CommentTarget::Shape(1)] {
+        history.visit(target);
+    }
+
+    let back = history.back(|target| matches!(target, CommentTarget::Shape(_)));
+
+    assert_eq!(back, Some(CommentTarget::Shape(0)));
+    assert!(!history.can_go_back());
+}
+
+#[test]
+fn history_is_bounded() {
+    let mut history = SelectionHistory::default();
+    for idx in 0..MAX_SELECTION_HISTORY + 10 {
+        history.visit(CommentTarget::Shape(idx));
+    }
+
+    let mut steps = 0;
+    while history.back(|_| true).is_some() {
+        steps += 1;
+    }
+    assert_eq!(steps, MAX_SELECTION_HISTORY);
+    assert_eq!(history.current(), Some(CommentTarget::Shape(9)), "Oldest visits are forgotten");
+}
+
+// ============================================================================
+// Canvas
+// ============================================================================
+
+#[test]
+fn mouse_back_and_forward_buttons_reselect_shapes() {
+    let mut sim = three_shapes();
+    sim.select_at(pos2(140.0, 130.0));
+    assert_eq!(*sim.canvas().selected_shape(), Some(0));
+
+    mouse_button(&mut sim, PointerButton::Extra1);
+    assert_eq!(*sim.canvas().selected_shape(), Some(2));
+
+    mouse_button(&mut sim, PointerButton::Extra2);
+    assert_eq!(*sim.canvas().selected_shape(), Some(0));
+}
+
+#[test]
+fn alt_arrows_and_commands_step_through_history() {
+    let mut sim = three_shapes();
+
+    sim.key_press(Key::ArrowLeft, Modifiers::ALT);
+    assert_eq!(*sim.canvas().selected_shape(), Some(1));
+
+    assert!(sim.canvas_mut().apply_command(CanvasCommand::SelectionBack));
+    assert_eq!(*sim.canvas().selected_shape(), Some(0));
+    assert!(!sim.canvas_mut().apply_command(CanvasCommand::SelectionBack), "Nothing further back");
+
+    sim.key_press(Key::ArrowRight, Modifiers::ALT);
+    assert_eq!(*sim.canvas().selected_shape(), Some(1));
+}
+
+#[test]
+fn going_back_pans_the_shape_into_view() {
+    let mut sim = three_shapes();
+    sim.canvas_mut().set_pan_offset(-2000.0, 0.0);
+    sim.step();
+
+    assert!(sim.canvas_mut().selection_back());
+    sim.step();
+
+    let center = sim.canvas().shapes()[1].bounding_rect().center();
+    let on_screen = sim.to_screen(center);
+    assert!((on_screen - sim.canvas_rect().center()).length() < 1.0, "{:?} off center", on_screen);
+    assert_eq!(*sim.canvas().zoom_level(), 1.0);
+}
+
+#[test]
+fn deleted_shapes_are_skipped() {
+    let mut sim = three_shapes();
+
+    sim.canvas_mut().delete_shape(1);
+
+    assert!(sim.canvas_mut().selection_back());
+    assert_eq!(*sim.canvas().selected_shape(), Some(0));
+}
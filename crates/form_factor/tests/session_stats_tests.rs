@@ -0,0 +1,68 @@
+//! Integration tests for annotation effort tracking
+
+use form_factor::{IDLE_TIMEOUT_SECS, SessionStats};
+
+#[test]
+fn continuous_activity_is_counted() {
+    let mut stats = SessionStats::new();
+    stats.tick(0.0, true);
+    stats.tick(10.0, true);
+    stats.tick(25.0, true);
+
+    assert_eq!(stats.active_seconds(), 25.0);
+}
+
+#[test]
+fn idle_gap_counts_only_up_to_timeout() {
+    let mut stats = SessionStats::new();
+    stats.tick(0.0, true);
+    // No repaint for a long time, then the user comes back
+    stats.tick(1000.0, true);
+    stats.tick(1005.0, false);
+
+    assert_eq!(stats.active_seconds(), IDLE_TIMEOUT_SECS + 5.0);
+}
+
+#[test]
+fn no_time_before_first_input() {
+    let mut stats = SessionStats::new();
+    stats.tick(0.0, false);
+    stats.tick(30.0, false);
+
+    assert_eq!(stats.active_seconds(), 0.0);
+}
+
+#[test]
+fn hourly_rates() {
+    let mut stats = SessionStats::new();
+    assert_eq!(stats.shapes_per_hour(), None);
+
+    stats.tick(0.0, true);
+    stats.tick(30.0, true);
+    stats.tick(60.0, true);
+    for _ in 0..3 {
+        stats.record_shape_created();
+    }
+    stats.record_field_corrected();
+
+    assert_eq!(stats.shapes_per_hour(), Some(180.0));
+    assert_eq!(stats.corrections_per_hour(), Some(60.0));
+}
+
+#[test]
+fn counters_survive_serialization_but_clock_resets() {
+    let mut stats = SessionStats::new();
+    stats.tick(0.0, true);
+    stats.tick(10.0, true);
+    stats.record_shape_created();
+
+    let json = serde_json::to_string(&stats).expect("Serialization should succeed");
+    let mut restored: SessionStats = serde_json::from_str(&json).expect("Deserialization should succeed");
+
+    assert_eq!(restored.active_seconds(), 10.0);
+    assert_eq!(restored.shapes_created(), 1);
+
+    // A fresh session does not count the time since the last save
+    restored.tick(5000.0, true);
+    assert_eq!(restored.active_seconds(), 10.0);
+}
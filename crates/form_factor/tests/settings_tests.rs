@@ -0,0 +1,248 @@
+//! Integration tests for the settings store and settings overlay
+
+mod common;
+
+use common::{region, temp_path, with_detections};
+use egui::{Color32, Pos2, Stroke};
+use form_factor::{
+    ACTION_TOGGLE_LOG, DetectionKind, DismissPolicy, DrawingCanvas, OverlayKind, OverlayManager, OverlaySpec,
+    SETTINGS_OVERLAY, Settings, SettingsOverlay, Theme,
+};
+
+/// Input with Escape pressed
+fn escape() -> egui::RawInput {
+    egui::RawInput {
+        events: vec![egui::Event::Key {
+            key: egui::Key::Escape,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers: egui::Modifiers::NONE,
+        }],
+        ..Default::default()
+    }
+}
+
+// ============================================================================
+// Settings store
+// ============================================================================
+
+#[test]
+fn settings_round_trip_through_file() {
+    let path = temp_path("round_trip").join("settings.json");
+    let mut settings = Settings::default();
+    settings.canvas.zoom_sensitivity = 2.5;
+    settings.ocr.language = "eng+fra".to_string();
+    settings.appearance.theme = Theme::Dark;
+
+    settings.save_to(&path).expect("Settings should save");
+    let loaded = Settings::load_from(&path);
+    let _ = std::fs::remove_dir_all(path.parent().expect("Has parent"));
+
+    assert_eq!(loaded, settings);
+}
+
+#[test]
+fn partial_files_fill_in_defaults() {
+    let path = temp_path("partial").join("settings.json");
+    std::fs::create_dir_all(path.parent().expect("Has parent")).expect("Temp dir");
+    std::fs::write(&path, r#"{ "ocr": { "min_confidence": 80 } }"#).expect("Temp file");
+
+    let loaded = Settings::load_from(&path);
+    let _ = std::fs::remove_dir_all(path.parent().expect("Has parent"));
+
+    assert_eq!(loaded.ocr.min_confidence, 80);
+    assert_eq!(loaded.ocr.language, "eng");
+    assert_eq!(loaded.canvas, Settings::default().canvas);
+    assert_eq!(loaded.detection.colors, Settings::default().detection.colors);
+}
+
+#[test]
+fn missing_or_corrupt_files_use_defaults() {
+    assert_eq!(Settings::load_from(&temp_path("missing").join("settings.json")), Settings::default());
+
+    let path = temp_path("corrupt").join("settings.json");
+    std::fs::create_dir_all(path.parent().expect("Has parent")).expect("Temp dir");
+    std::fs::write(&path, "not json").expect("Temp file");
+    let loaded = Settings::load_from(&path);
+    let _ = std::fs::remove_dir_all(path.parent().expect("Has parent"));
+
+    assert_eq!(loaded, Settings::default());
+}
+
+#[test]
+fn shortcut_bindings_resolve_key_names() {
+    let mut settings = Settings::default();
+    assert_eq!(settings.shortcuts.key(ACTION_TOGGLE_LOG), Some(egui::Key::F12));
+
+    settings.shortcuts.bindings.insert(ACTION_TOGGLE_LOG.to_string(), "NotAKey".to_string());
+    assert_eq!(settings.shortcuts.key(ACTION_TOGGLE_LOG), None);
+    assert_eq!(settings.shortcuts.invalid_bindings(), vec![ACTION_TOGGLE_LOG]);
+}
+
+#[test]
+fn canvas_applies_settings() {
+    let mut settings = Settings::default();
+    settings.canvas.grid_spacing_vertical = 42.0;
+    settings.detection.logos_dir = "custom_logos".to_string();
+
+    let mut canvas = DrawingCanvas::new();
+    canvas.apply_settings(&settings);
+
+    assert_eq!(canvas.canvas_settings().grid_spacing_vertical, 42.0);
+    assert_eq!(canvas.detection_settings().logos_dir, "custom_logos");
+}
+
+#[test]
+fn detections_are_drawn_in_the_configured_colors() {
+    let mut canvas = with_detections(vec![
+        region("Text Region 1", 0.0, 0.0, 10.0, 10.0),
+        region("Logo: acme", 0.0, 0.0, 10.0, 10.0),
+        region("Text Region 2", 0.0, 0.0, 10.0, 10.0),
+    ]);
+    canvas.record_ocr_text(2, "Total");
+
+    let mut settings = Settings::default();
+    settings.detection.colors.logo = Color32::RED;
+    canvas.apply_settings(&settings);
+
+    let kinds: Vec<_> = (0..3).filter_map(|idx| canvas.detection_kind(idx)).collect();
+    assert_eq!(kinds, [DetectionKind::Text, DetectionKind::Logo, DetectionKind::Ocr]);
+    let colors = &settings.detection.colors;
+    for (idx, kind) in kinds.into_iter().enumerate() {
+        let styled = canvas.styled_detection(idx).expect("Detection exists");
+        assert_eq!(styled.stroke(), Stroke::new(1.0, colors.color(kind)));
+    }
+    assert_eq!(canvas.styled_detection(1).expect("Detection exists").stroke().color, Color32::RED);
+    assert!(canvas.detection_kind(3).is_none());
+}
+
+// ============================================================================
+// Overlays
+// ============================================================================
+
+#[test]
+fn overlays_stack_in_open_order() {
+    let mut overlays = OverlayManager::new();
+    overlays.open("a");
+    overlays.open("b");
+    overlays.open("a");
+
+    assert_eq!(overlays.top(), Some("a"));
+    assert_eq!(overlays.close_top().as_deref(), Some("a"));
+    assert_eq!(overlays.top(), Some("b"));
+
+    overlays.toggle("b");
+    assert!(!overlays.is_any_open());
+}
+
+#[test]
+fn escape_closes_topmost_overlay() {
+    let mut overlays = OverlayManager::new();
+    overlays.open("a");
+    overlays.open("b");
+
+    let ctx = egui::Context::default();
+    let _ = ctx.run(escape(), |ctx| overlays.handle_escape(ctx));
+
+    assert!(overlays.is_open("a"));
+    assert!(!overlays.is_open("b"));
+}
+
+#[test]
+fn overlays_stay_below_higher_z_orders() {
+    let mut overlays = OverlayManager::new();
+    overlays.register(OverlaySpec::new("sheet", "Sheet").with_kind(OverlayKind::SideSheet).with_z_order(10));
+    overlays.register(OverlaySpec::new("dialog", "Dialog").with_kind(OverlayKind::Modal));
+    overlays.open("dialog");
+    overlays.open("sheet");
+    overlays.open("a");
+    overlays.open("b");
+
+    assert_eq!(overlays.open_overlays(), ["a", "b", "sheet", "dialog"]);
+
+    overlays.open("a");
+    assert_eq!(overlays.open_overlays(), ["b", "a", "sheet", "dialog"]);
+}
+
+#[test]
+fn escape_leaves_explicitly_dismissed_overlays_open() {
+    let mut overlays = OverlayManager::new();
+    overlays.register(OverlaySpec::new("confirm", "Confirm").with_dismiss(DismissPolicy::Explicit));
+    overlays.open("a");
+    overlays.open("confirm");
+
+    let ctx = egui::Context::default();
+    let _ = ctx.run(escape(), |ctx| overlays.handle_escape(ctx));
+
+    assert!(overlays.is_open("a"), "Escape never reaches overlays further down");
+    assert!(overlays.is_open("confirm"));
+}
+
+#[test]
+fn clicking_outside_closes_overlays_that_allow_it() {
+    let mut overlays = OverlayManager::new();
+    overlays.register(OverlaySpec::new("popover", "Popover").with_dismiss(DismissPolicy::ClickOutside));
+    overlays.register(OverlaySpec::new("sheet", "Sheet").with_kind(OverlayKind::SideSheet));
+    overlays.open("popover");
+    overlays.open("sheet");
+
+    let ctx = egui::Context::default();
+    let show = |overlays: &mut OverlayManager, ctx: &egui::Context| {
+        let _ = overlays.show_registered(ctx, "popover", |ui| ui.label("Popover"));
+        let _ = overlays.show_registered(ctx, "sheet", |ui| ui.label("Sheet"));
+    };
+    let _ = ctx.run(egui::RawInput::default(), |ctx| show(&mut overlays, ctx));
+    let click = Pos2::new(1.0, 1000.0);
+    let input = egui::RawInput {
+        screen_rect: Some(egui::Rect::from_min_size(Pos2::ZERO, egui::vec2(1600.0, 1200.0))),
+        events: vec![
+            egui::Event::PointerMoved(click),
+            egui::Event::PointerButton {
+                pos: click,
+                button: egui::PointerButton::Primary,
+                pressed: true,
+                modifiers: egui::Modifiers::NONE,
+            },
+        ],
+        ..Default::default()
+    };
+    let _ = ctx.run(input, |ctx| show(&mut overlays, ctx));
+
+    assert!(!overlays.is_open("popover"));
+    assert!(overlays.is_open("sheet"));
+}
+
+#[test]
+fn settings_overlay_saves_draft() {
+    let path = temp_path("overlay").join("settings.json");
+    let mut overlay = SettingsOverlay::with_path(&path);
+    assert!(!overlay.is_dirty());
+
+    overlay.draft_mut().appearance.ui_scale = 1.5;
+    assert!(overlay.is_dirty());
+    assert_eq!(overlay.settings().appearance.ui_scale, 1.0);
+
+    overlay.save().expect("Settings should save");
+    let reloaded = Settings::load_from(&path);
+    let _ = std::fs::remove_dir_all(path.parent().expect("Has parent"));
+
+    assert!(!overlay.is_dirty());
+    assert_eq!(reloaded.appearance.ui_scale, 1.5);
+}
+
+#[test]
+fn settings_overlay_revert_discards_edits() {
+    let mut overlay = SettingsOverlay::with_path(temp_path("revert").join("settings.json"));
+    overlay.draft_mut().ocr.min_confidence = 5;
+    overlay.revert();
+
+    assert!(!overlay.is_dirty());
+
+    let mut overlays = OverlayManager::new();
+    overlays.open(SETTINGS_OVERLAY);
+    let ctx = egui::Context::default();
+    let mut saved = None;
+    let _ = ctx.run(egui::RawInput::default(), |ctx| saved = overlay.show(&mut overlays, ctx));
+    assert!(saved.is_none());
+}
@@ -4,7 +4,10 @@
 //! focusing on pure business logic without GUI dependencies.
 
 use egui::{Color32, Pos2, Stroke};
-use form_factor::{Circle, PolygonShape, Rectangle, Shape, ShapeErrorKind};
+use form_factor::{
+    Arrow, Callout, Circle, Ellipse, Line, PolygonShape, Polyline, Rectangle, Shape, ShapeErrorKind, StrokeStyle,
+    length_label,
+};
 use std::f32::consts::PI;
 
 // ============================================================================
@@ -753,3 +756,330 @@ fn rectangle_with_negative_coordinates() {
         assert!(r.contains_point(Pos2::new(-5.0, -5.0)));
     }
 }
+
+// ============================================================================
+// Arrow and Callout Markup Tests
+// ============================================================================
+
+fn callout_box() -> egui::Rect {
+    egui::Rect::from_min_max(Pos2::new(50.0, 0.0), Pos2::new(100.0, 20.0))
+}
+
+#[test]
+fn arrow_rejects_coincident_points() {
+    let stroke = Stroke::new(2.0, Color32::RED);
+
+    let result = Arrow::new(Pos2::new(5.0, 5.0), Pos2::new(5.0, 5.0), stroke);
+    assert!(matches!(result.unwrap_err().kind, ShapeErrorKind::DegenerateShape));
+
+    let result = Arrow::new(Pos2::new(f32::NAN, 0.0), Pos2::new(5.0, 5.0), stroke);
+    assert!(matches!(result.unwrap_err().kind, ShapeErrorKind::InvalidCoordinate));
+}
+
+#[test]
+fn arrow_hits_along_its_line_and_head() {
+    let arrow = Arrow::new(Pos2::new(0.0, 0.0), Pos2::new(100.0, 0.0), Stroke::new(2.0, Color32::RED)).unwrap();
+
+    assert!(arrow.contains_point(Pos2::new(50.0, 2.0)));
+    assert!(arrow.contains_point(Pos2::new(97.0, 0.5)));
+    assert!(!arrow.contains_point(Pos2::new(50.0, 20.0)));
+    assert!(!arrow.contains_point(Pos2::new(120.0, 0.0)));
+}
+
+#[test]
+fn arrow_head_sits_at_the_tip_and_fits_short_arrows() {
+    let arrow = Arrow::new(Pos2::new(0.0, 0.0), Pos2::new(4.0, 0.0), Stroke::new(2.0, Color32::RED)).unwrap();
+    let [tip, left, right] = arrow.head();
+
+    assert_eq!(tip, Pos2::new(4.0, 0.0));
+    assert!(left.x >= 0.0 && right.x >= 0.0);
+    assert!(left.y * right.y < 0.0);
+}
+
+#[test]
+fn arrow_translates_and_rotates() {
+    let mut arrow = Arrow::new(Pos2::new(0.0, 0.0), Pos2::new(10.0, 0.0), Stroke::new(1.0, Color32::RED)).unwrap();
+
+    arrow.translate(egui::vec2(5.0, 5.0)).unwrap();
+    assert_eq!(*arrow.start(), Pos2::new(5.0, 5.0));
+
+    arrow.rotate(PI / 2.0, *arrow.start()).unwrap();
+    assert!((arrow.end().x - 5.0).abs() < 0.001);
+    assert!((arrow.end().y - 15.0).abs() < 0.001);
+}
+
+#[test]
+fn callout_rejects_empty_box() {
+    let empty = egui::Rect::from_min_max(Pos2::new(10.0, 10.0), Pos2::new(10.0, 30.0));
+
+    let result = Callout::new(Pos2::ZERO, empty, "note", Stroke::new(1.0, Color32::RED), Color32::WHITE);
+    assert!(matches!(result.unwrap_err().kind, ShapeErrorKind::DegenerateShape));
+}
+
+#[test]
+fn callout_leader_meets_nearest_point_of_box() {
+    let callout = Callout::new(Pos2::new(0.0, 10.0), callout_box(), "note", Stroke::new(1.0, Color32::RED), Color32::WHITE)
+        .unwrap();
+
+    assert_eq!(callout.leader_end(), Pos2::new(50.0, 10.0));
+}
+
+#[test]
+fn callout_hits_box_and_leader() {
+    let callout = Callout::new(Pos2::new(0.0, 10.0), callout_box(), "note", Stroke::new(1.0, Color32::RED), Color32::WHITE)
+        .unwrap();
+
+    assert!(callout.contains_point(Pos2::new(75.0, 10.0)));
+    assert!(callout.contains_point(Pos2::new(25.0, 11.0)));
+    assert!(!callout.contains_point(Pos2::new(25.0, 30.0)));
+}
+
+#[test]
+fn callout_box_stays_axis_aligned_when_rotated() {
+    let mut callout =
+        Callout::new(Pos2::new(0.0, 10.0), callout_box(), "note", Stroke::new(1.0, Color32::RED), Color32::WHITE)
+            .unwrap();
+
+    callout.rotate(PI, Pos2::new(0.0, 10.0)).unwrap();
+
+    assert_eq!(callout.text_box().size(), callout_box().size());
+    assert!((callout.text_box().center().x + 75.0).abs() < 0.001);
+    assert!((callout.text_box().center().y - 10.0).abs() < 0.001);
+}
+
+#[test]
+fn markups_validate_and_bound_like_other_shapes() {
+    let stroke = Stroke::new(1.0, Color32::RED);
+    let arrow = Shape::Arrow(Arrow::new(Pos2::new(0.0, 0.0), Pos2::new(20.0, 0.0), stroke).unwrap());
+    let callout = Shape::Callout(Callout::new(Pos2::new(0.0, 10.0), callout_box(), "", stroke, Color32::WHITE).unwrap());
+
+    assert!(arrow.validate().is_ok());
+    assert!(callout.validate().is_ok());
+    assert!(arrow.bounding_rect().contains(Pos2::new(20.0, 0.0)));
+    assert_eq!(callout.bounding_rect(), egui::Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(100.0, 20.0)));
+}
+
+// ============================================================================
+// Line and Polyline Tests
+// ============================================================================
+
+#[test]
+fn line_rejects_coincident_ends() {
+    let stroke = Stroke::new(1.0, Color32::BLACK);
+
+    let result = Line::new(Pos2::new(5.0, 5.0), Pos2::new(5.0, 5.0), stroke);
+    assert!(matches!(result.unwrap_err().kind, ShapeErrorKind::DegenerateShape));
+
+    let result = Line::new(Pos2::new(0.0, f32::INFINITY), Pos2::new(5.0, 5.0), stroke);
+    assert!(matches!(result.unwrap_err().kind, ShapeErrorKind::InvalidCoordinate));
+}
+
+#[test]
+fn line_measures_and_hits_along_its_length() {
+    let mut line = Line::new(Pos2::new(0.0, 0.0), Pos2::new(30.0, 40.0), Stroke::new(1.0, Color32::BLACK)).unwrap();
+
+    assert_eq!(line.length(), 50.0);
+    assert_eq!(length_label(line.length()), "50.0");
+    assert!(line.contains_point(Pos2::new(15.0, 21.0)));
+    assert!(!line.contains_point(Pos2::new(30.0, 0.0)));
+    assert!(!line.contains_point(Pos2::new(-10.0, -10.0)));
+
+    line.set_end(Pos2::new(0.0, 10.0)).unwrap();
+    assert_eq!(line.length(), 10.0);
+    assert!(line.set_start(Pos2::new(0.0, 10.0)).is_err());
+}
+
+#[test]
+fn line_translates_and_rotates() {
+    let mut line = Line::new(Pos2::new(0.0, 0.0), Pos2::new(10.0, 0.0), Stroke::new(1.0, Color32::BLACK)).unwrap();
+
+    line.translate(egui::vec2(5.0, 5.0)).unwrap();
+    line.rotate(PI / 2.0, *line.start()).unwrap();
+
+    assert_eq!(*line.start(), Pos2::new(5.0, 5.0));
+    assert!((line.end().x - 5.0).abs() < 0.001);
+    assert!((line.end().y - 15.0).abs() < 0.001);
+}
+
+#[test]
+fn polyline_needs_two_distinct_points() {
+    let stroke = Stroke::new(1.0, Color32::BLACK);
+
+    let result = Polyline::from_points(vec![Pos2::new(1.0, 1.0)], stroke);
+    assert!(matches!(result.unwrap_err().kind, ShapeErrorKind::TooFewPoints(1)));
+
+    let result = Polyline::from_points(vec![Pos2::new(1.0, 1.0); 3], stroke);
+    assert!(matches!(result.unwrap_err().kind, ShapeErrorKind::DegenerateShape));
+
+    assert!(Polyline::from_points(vec![Pos2::new(0.0, 0.0), Pos2::new(1.0, 0.0)], stroke).is_ok());
+}
+
+#[test]
+fn polyline_stays_open() {
+    let points = vec![Pos2::new(0.0, 0.0), Pos2::new(40.0, 0.0), Pos2::new(40.0, 30.0)];
+    let polyline = Polyline::from_points(points, Stroke::new(1.0, Color32::BLACK)).unwrap();
+
+    assert_eq!(polyline.length(), 70.0);
+    assert_eq!(polyline.midpoint(), Pos2::new(35.0, 0.0));
+    assert!(polyline.contains_point(Pos2::new(40.0, 15.0)));
+    // The closing edge of a polygon through the same points is not part of the path
+    assert!(!polyline.contains_point(Pos2::new(20.0, 15.0)));
+    assert!(!polyline.contains_point(Pos2::new(30.0, 10.0)));
+}
+
+#[test]
+fn polyline_simplifies_hand_drawn_paths() {
+    let mut points: Vec<Pos2> = (0..=20).map(|i| Pos2::new(i as f32 * 5.0, (i % 2) as f32 * 0.5)).collect();
+    points.extend((1..=10).map(|i| Pos2::new(100.0, i as f32 * 5.0)));
+
+    let polyline = Polyline::simplified(points, 2.0, Stroke::new(1.0, Color32::BLACK)).unwrap();
+
+    assert_eq!(polyline.points().len(), 3);
+    assert_eq!(polyline.points()[2], Pos2::new(100.0, 50.0));
+}
+
+#[test]
+fn polyline_vertices_edit_and_transform() {
+    let points = vec![Pos2::new(0.0, 0.0), Pos2::new(10.0, 0.0), Pos2::new(10.0, 10.0)];
+    let mut polyline = Polyline::from_points(points, Stroke::new(1.0, Color32::BLACK)).unwrap();
+
+    polyline.set_vertex(2, Pos2::new(20.0, 0.0)).unwrap();
+    polyline.set_vertex(9, Pos2::new(99.0, 99.0)).unwrap();
+    assert_eq!(polyline.points()[2], Pos2::new(20.0, 0.0));
+
+    polyline.translate(egui::vec2(0.0, 5.0)).unwrap();
+    polyline.rotate(PI, Pos2::new(10.0, 5.0)).unwrap();
+    assert!((polyline.points()[0].x - 20.0).abs() < 0.001);
+    assert!((polyline.points()[0].y - 5.0).abs() < 0.001);
+}
+
+#[test]
+fn lines_validate_and_bound_like_other_shapes() {
+    let stroke = Stroke::new(1.0, Color32::BLACK);
+    let line = Shape::Line(Line::new(Pos2::new(0.0, 0.0), Pos2::new(20.0, 5.0), stroke).unwrap());
+    let polyline = Shape::Polyline(
+        Polyline::from_points(vec![Pos2::new(0.0, 0.0), Pos2::new(20.0, 5.0), Pos2::new(-5.0, 10.0)], stroke).unwrap(),
+    );
+
+    assert!(line.validate().is_ok());
+    assert!(polyline.validate().is_ok());
+    assert_eq!(line.bounding_rect(), egui::Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(20.0, 5.0)));
+    assert_eq!(polyline.bounding_rect(), egui::Rect::from_min_max(Pos2::new(-5.0, 0.0), Pos2::new(20.0, 10.0)));
+}
+
+// ============================================================================
+// Ellipse Tests
+// ============================================================================
+
+#[test]
+fn ellipse_rejects_invalid_geometry() {
+    let stroke = Stroke::new(1.0, Color32::BLACK);
+    let center = Pos2::new(10.0, 10.0);
+
+    let result = Ellipse::new(center, egui::vec2(5.0, 0.0), 0.0, stroke, Color32::TRANSPARENT);
+    assert!(matches!(result.unwrap_err().kind, ShapeErrorKind::InvalidRadius(r) if r == 0.0));
+
+    let result = Ellipse::new(center, egui::vec2(5.0, 3.0), f32::NAN, stroke, Color32::TRANSPARENT);
+    assert!(matches!(result.unwrap_err().kind, ShapeErrorKind::InvalidCoordinate));
+
+    // A flat drag has no height to fill
+    let result = Ellipse::from_corners(Pos2::new(0.0, 5.0), Pos2::new(20.0, 5.0), stroke, Color32::TRANSPARENT);
+    assert!(matches!(result.unwrap_err().kind, ShapeErrorKind::InvalidRadius(_)));
+}
+
+#[test]
+fn ellipse_fills_the_dragged_box() {
+    let stroke = Stroke::new(1.0, Color32::BLACK);
+    let ellipse = Ellipse::from_corners(Pos2::new(40.0, 30.0), Pos2::new(0.0, 10.0), stroke, Color32::WHITE).unwrap();
+
+    assert_eq!(*ellipse.center(), Pos2::new(20.0, 20.0));
+    assert_eq!(*ellipse.radii(), egui::vec2(20.0, 10.0));
+    assert_eq!(*ellipse.rotation(), 0.0);
+    assert_eq!(
+        Shape::Ellipse(ellipse).bounding_rect(),
+        egui::Rect::from_min_max(Pos2::new(0.0, 10.0), Pos2::new(40.0, 30.0))
+    );
+}
+
+#[test]
+fn ellipse_hit_testing_follows_both_radii() {
+    let stroke = Stroke::new(1.0, Color32::BLACK);
+    let mut ellipse = Ellipse::new(Pos2::ZERO, egui::vec2(20.0, 10.0), 0.0, stroke, Color32::WHITE).unwrap();
+
+    assert!(ellipse.contains_point(Pos2::new(19.0, 0.0)));
+    assert!(!ellipse.contains_point(Pos2::new(0.0, 11.0)));
+    // Inside the bounding box but outside the curve
+    assert!(!ellipse.contains_point(Pos2::new(15.0, 8.0)));
+
+    ellipse.set_rotation(PI / 2.0).unwrap();
+    assert!(ellipse.contains_point(Pos2::new(0.0, 19.0)));
+    assert!(!ellipse.contains_point(Pos2::new(11.0, 0.0)));
+    assert!(!ellipse.contains_point(Pos2::new(f32::NAN, 0.0)));
+}
+
+#[test]
+fn ellipse_rotates_its_axes_with_the_shape() {
+    let stroke = Stroke::new(1.0, Color32::BLACK);
+    let mut ellipse = Ellipse::new(Pos2::new(10.0, 0.0), egui::vec2(20.0, 10.0), 0.0, stroke, Color32::WHITE).unwrap();
+
+    ellipse.rotate(PI / 2.0, Pos2::ZERO).unwrap();
+
+    assert!(ellipse.center().x.abs() < 0.001);
+    assert!((ellipse.center().y - 10.0).abs() < 0.001);
+    assert!((ellipse.rotation() - PI / 2.0).abs() < 0.001);
+    let bounds = Shape::Ellipse(ellipse).bounding_rect();
+    assert!((bounds.width() - 20.0).abs() < 0.001);
+    assert!((bounds.height() - 40.0).abs() < 0.001);
+}
+
+#[test]
+fn ellipse_handles_change_one_radius() {
+    let stroke = Stroke::new(1.0, Color32::BLACK);
+    let mut ellipse = Ellipse::new(Pos2::ZERO, egui::vec2(20.0, 10.0), PI / 2.0, stroke, Color32::WHITE).unwrap();
+    let [x_end, y_end] = ellipse.handles();
+    assert!(x_end.distance(Pos2::new(0.0, 20.0)) < 0.001);
+    assert!(y_end.distance(Pos2::new(-10.0, 0.0)) < 0.001);
+
+    // Only the distance along the axis counts
+    ellipse.set_handle(0, Pos2::new(5.0, 30.0)).unwrap();
+    ellipse.set_handle(1, Pos2::new(15.0, 100.0)).unwrap();
+    ellipse.set_handle(2, Pos2::new(99.0, 99.0)).unwrap();
+    assert!((*ellipse.radii() - egui::vec2(30.0, 15.0)).length() < 0.001);
+
+    assert!(ellipse.set_handle(0, Pos2::ZERO).is_err());
+    assert!((ellipse.radii().x - 30.0).abs() < 0.001);
+}
+
+// ============================================================================
+// Stroke Style Tests
+// ============================================================================
+
+#[test]
+fn shapes_start_solid_and_keep_their_stroke_style() {
+    let stroke = Stroke::new(2.0, Color32::BLACK);
+    let mut shape = Shape::Line(Line::new(Pos2::ZERO, Pos2::new(10.0, 0.0), stroke).unwrap());
+    assert_eq!(shape.stroke_style(), StrokeStyle::Solid);
+
+    shape.set_stroke_style(StrokeStyle::Dashed);
+    shape.translate(egui::vec2(5.0, 5.0)).unwrap();
+    assert_eq!(shape.stroke_style(), StrokeStyle::Dashed);
+}
+
+#[test]
+fn stroke_patterns_scale_with_width() {
+    assert_eq!(StrokeStyle::Solid.pattern(2.0), None);
+    assert_eq!(StrokeStyle::Dashed.pattern(2.0), Some((8.0, 5.0)));
+    assert_eq!(StrokeStyle::Dotted.pattern(2.0), Some((0.0, 5.0)));
+    // Hairlines still get a visible pattern
+    assert_eq!(StrokeStyle::Dashed.pattern(0.1), StrokeStyle::Dashed.pattern(1.0));
+}
+
+#[test]
+fn shapes_saved_before_stroke_styles_load_solid() {
+    let circle = Circle::new(Pos2::new(5.0, 5.0), 3.0, Stroke::new(1.0, Color32::BLACK), Color32::WHITE).unwrap();
+    let mut json = serde_json::to_value(Shape::Circle(circle)).unwrap();
+    json["Circle"].as_object_mut().unwrap().remove("stroke_style");
+
+    let shape: Shape = serde_json::from_value(json).unwrap();
+    assert_eq!(shape.stroke_style(), StrokeStyle::Solid);
+}
@@ -0,0 +1,115 @@
+//! Integration tests for the desktop application shell and its extension points
+
+use egui::{Color32, Pos2, Stroke};
+use form_factor::{
+    App, AppContext, AppShell, CrashReporter, DrawingCanvas, FrameTimings, LogBuffer, Rectangle, Shape,
+    ShellExtension, WorkspaceMode,
+};
+use std::{cell::RefCell, rc::Rc};
+
+/// Calls an extension received, in order
+#[derive(Default)]
+struct Calls {
+    frames: usize,
+    selections: Vec<Option<usize>>,
+}
+
+struct Recorder(Rc<RefCell<Calls>>);
+
+impl ShellExtension for Recorder {
+    fn ui(&mut self, _canvas: &mut DrawingCanvas, _ctx: &egui::Context) {
+        self.0.borrow_mut().frames += 1;
+    }
+
+    fn on_selection_changed(&mut self, _canvas: &DrawingCanvas, selected: Option<usize>) {
+        self.0.borrow_mut().selections.push(selected);
+    }
+}
+
+/// Canvas with one field, so data entry has something to select
+fn form() -> DrawingCanvas {
+    let mut rect = Rectangle::from_corners(
+        Pos2::new(100.0, 0.0),
+        Pos2::new(200.0, 20.0),
+        Stroke::new(1.0, Color32::BLACK),
+        Color32::TRANSPARENT,
+    )
+    .expect("Valid rectangle");
+    rect.name = "name".to_string();
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![Shape::Rectangle(rect)]);
+    canvas
+}
+
+fn shell() -> AppShell {
+    let crash_reporter = CrashReporter::new(std::env::temp_dir().join("form_factor_shell_tests"));
+    AppShell::new(LogBuffer::default(), FrameTimings::default(), crash_reporter).with_canvas(form())
+}
+
+/// Run one frame of the shell
+fn frame(ctx: &egui::Context, shell: &mut AppShell) {
+    let input = egui::RawInput {
+        screen_rect: Some(egui::Rect::from_min_size(Pos2::ZERO, egui::vec2(1024.0, 768.0))),
+        ..Default::default()
+    };
+    let _ = ctx.run(input, |ctx| {
+        shell.update(&AppContext {
+            egui_ctx: ctx,
+            delta_time: 1.0 / 60.0,
+            frame_count: 0,
+        })
+    });
+}
+
+// ============================================================================
+// Extensions
+// ============================================================================
+
+#[test]
+fn extensions_draw_every_frame() {
+    let calls = Rc::new(RefCell::new(Calls::default()));
+    let mut shell = shell().with_extension(Box::new(Recorder(calls.clone())));
+    let ctx = egui::Context::default();
+
+    frame(&ctx, &mut shell);
+    frame(&ctx, &mut shell);
+
+    assert_eq!(calls.borrow().frames, 2);
+}
+
+#[test]
+fn extensions_hear_of_selection_changes_once() {
+    let calls = Rc::new(RefCell::new(Calls::default()));
+    let mut shell = shell().with_extension(Box::new(Recorder(calls.clone())));
+    let ctx = egui::Context::default();
+    frame(&ctx, &mut shell);
+
+    // Data entry selects the field it starts at
+    shell.canvas_mut().open_data_entry_panel();
+    frame(&ctx, &mut shell);
+    frame(&ctx, &mut shell);
+
+    assert_eq!(calls.borrow().selections, vec![Some(0)]);
+}
+
+// ============================================================================
+// Modes
+// ============================================================================
+
+#[test]
+fn mode_hooks_run_as_the_shell_changes_mode() {
+    let entered = Rc::new(RefCell::new(0));
+    let mut shell = shell().with_name("Intake");
+    let counter = entered.clone();
+    shell
+        .mode_hooks_mut()
+        .on_enter(WorkspaceMode::DataEntry, "test", move |_| *counter.borrow_mut() += 1);
+    let ctx = egui::Context::default();
+
+    shell.canvas_mut().open_data_entry_panel();
+    frame(&ctx, &mut shell);
+
+    assert_eq!(shell.name(), "Intake");
+    assert_eq!(shell.mode(), WorkspaceMode::DataEntry);
+    assert_eq!(*entered.borrow(), 1);
+}
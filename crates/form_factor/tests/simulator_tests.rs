@@ -0,0 +1,124 @@
+//! Integration tests driving the canvas through synthetic input
+
+mod common;
+
+use common::simulator;
+use egui::{Key, Modifiers, pos2};
+use form_factor::{CanvasSimulator, DrawingCanvas, Shape, ToolMode};
+
+#[test]
+fn drag_draws_rectangle_between_corners() {
+    let mut sim = simulator();
+    sim.draw_rectangle(pos2(100.0, 100.0), pos2(300.0, 200.0));
+
+    let canvas = sim.canvas();
+    assert_eq!(canvas.shape_count(), 1);
+    assert!(matches!(canvas.shapes()[0], Shape::Rectangle(_)));
+    let bounds = canvas.shapes()[0].bounding_rect();
+    assert_eq!(bounds.min, pos2(100.0, 100.0));
+    assert_eq!(bounds.max, pos2(300.0, 200.0));
+}
+
+#[test]
+fn new_shape_is_selected_and_can_be_renamed() {
+    let mut sim = simulator();
+    sim.draw_rectangle(pos2(100.0, 100.0), pos2(300.0, 200.0));
+    assert_eq!(*sim.canvas().selected_shape(), Some(0));
+
+    sim.rename_selected("invoice_number");
+
+    assert_eq!(sim.canvas().shapes()[0].name(), "invoice_number");
+}
+
+#[test]
+fn select_rename_flow_across_shapes() {
+    let mut sim = simulator();
+    sim.draw_rectangle(pos2(100.0, 100.0), pos2(200.0, 150.0));
+    sim.draw_circle(pos2(500.0, 400.0), pos2(540.0, 400.0));
+
+    // Clicking empty space clears the selection
+    sim.select_at(pos2(800.0, 700.0));
+    assert_eq!(*sim.canvas().selected_shape(), None);
+
+    sim.select_at(pos2(150.0, 120.0));
+    assert_eq!(*sim.canvas().selected_shape(), Some(0));
+    sim.rename_selected("date");
+
+    sim.select_at(pos2(500.0, 400.0));
+    assert_eq!(*sim.canvas().selected_shape(), Some(1));
+    sim.rename_selected("signature");
+
+    let names: Vec<&str> = sim.canvas().shapes().iter().map(Shape::name).collect();
+    assert_eq!(names, vec!["date", "signature"]);
+    assert_eq!(*sim.canvas().current_tool(), ToolMode::Select);
+}
+
+#[test]
+fn drag_draws_ellipse_in_its_bounding_box() {
+    let mut sim = simulator();
+    sim.draw_ellipse(pos2(100.0, 100.0), pos2(160.0, 130.0));
+
+    let canvas = sim.canvas();
+    let Shape::Ellipse(ellipse) = &canvas.shapes()[0] else {
+        panic!("Expected an ellipse, got {:?}", canvas.shapes()[0]);
+    };
+    assert_eq!(*ellipse.center(), pos2(130.0, 115.0));
+    assert_eq!(*ellipse.radii(), egui::vec2(30.0, 15.0));
+
+    // The corners of the box are outside the oval
+    sim.select_at(pos2(102.0, 102.0));
+    assert_eq!(*sim.canvas().selected_shape(), None);
+    sim.select_at(pos2(155.0, 115.0));
+    assert_eq!(*sim.canvas().selected_shape(), Some(0));
+}
+
+#[test]
+fn line_tools_draw_open_shapes() {
+    let mut sim = simulator();
+    sim.draw_line(pos2(100.0, 300.0), pos2(400.0, 300.0));
+    sim.draw_polyline(&[pos2(100.0, 400.0), pos2(250.0, 400.0), pos2(250.0, 500.0)]);
+
+    let canvas = sim.canvas();
+    assert_eq!(canvas.shape_count(), 2);
+    let Shape::Line(line) = &canvas.shapes()[0] else {
+        panic!("Expected a line, got {:?}", canvas.shapes()[0]);
+    };
+    assert!((line.length() - 300.0).abs() < 0.01);
+    let Shape::Polyline(polyline) = &canvas.shapes()[1] else {
+        panic!("Expected a polyline, got {:?}", canvas.shapes()[1]);
+    };
+    // The drag is simplified back to its corners
+    assert_eq!(polyline.points().len(), 3);
+    assert!((polyline.length() - 250.0).abs() < 0.5);
+}
+
+#[test]
+fn scripts_are_deterministic() {
+    let run = || {
+        let mut sim = simulator();
+        sim.draw_rectangle(pos2(50.0, 60.0), pos2(170.0, 90.0));
+        sim.key_press(Key::Escape, Modifiers::NONE);
+        sim.run_frames(3);
+        (
+            sim.time(),
+            serde_json::to_string(sim.canvas().shapes()).expect("Serialization should succeed"),
+        )
+    };
+
+    assert_eq!(run(), run());
+}
+
+#[test]
+fn canvas_coordinates_follow_zoom_and_pan() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_zoom(2.0);
+    canvas.set_pan_offset(40.0, -20.0);
+    let mut sim = CanvasSimulator::new(canvas);
+
+    let center = sim.canvas_rect().center();
+    sim.draw_rectangle(center, center + egui::vec2(50.0, 30.0));
+
+    let bounds = sim.canvas().shapes()[0].bounding_rect();
+    assert!((bounds.min - center).length() < 0.01);
+    assert!((bounds.size() - egui::vec2(50.0, 30.0)).length() < 0.01);
+}
@@ -0,0 +1,186 @@
+//! Integration tests for the Stamp tool and stamp library
+
+mod common;
+
+use common::temp_path;
+use egui::{Color32, Pos2};
+use form_factor::{
+    CanvasCommand, CanvasSimulator, DrawingCanvas, LayerType, STAMP_HEIGHT, Settings, SettingsOverlay, Stamp,
+    StampLibrary, StampSource, StampSymbol,
+};
+
+fn paid_stamp(center: Pos2) -> Stamp {
+    Stamp::new(StampSymbol::text("Paid", "PAID", Color32::from_rgb(30, 80, 200)), center)
+}
+
+// ============================================================================
+// Library
+// ============================================================================
+
+#[test]
+fn library_starts_with_review_verdicts() {
+    let library = StampLibrary::default();
+
+    let names: Vec<&str> = library.symbols.iter().map(|symbol| symbol.name.as_str()).collect();
+    assert_eq!(names, ["Approved", "Rejected", "Paid"]);
+    assert!(matches!(&library.get("Paid").expect("Default").source, StampSource::Text { text, .. } if text == "PAID"));
+}
+
+#[test]
+fn adding_a_symbol_replaces_one_with_the_same_name() {
+    let mut library = StampLibrary::default();
+    library.add(StampSymbol::text("Paid", "PAID IN FULL", Color32::BLACK));
+    library.add(StampSymbol::text("Void", "VOID", Color32::BLACK));
+
+    assert_eq!(library.symbols.len(), 4);
+    assert_eq!(library.symbols[2].source, StampSource::Text {
+        text: "PAID IN FULL".to_string(),
+        color: Color32::BLACK
+    });
+    assert!(library.remove("Void").is_some());
+    assert!(library.remove("Void").is_none());
+}
+
+#[test]
+fn importing_an_image_adds_it_by_file_name() {
+    let path = temp_path("signature.png");
+    image::RgbaImage::from_pixel(60, 20, image::Rgba([0, 0, 0, 255]))
+        .save(&path)
+        .expect("Temp image");
+    let mut library = StampLibrary::default();
+
+    let symbol = library.import_image(&path);
+    let missing = library.import_image(&temp_path("missing.png"));
+    let _ = std::fs::remove_file(&path);
+
+    let symbol = symbol.expect("Importable");
+    assert_eq!(symbol.name, format!("form_factor_{}_signature", std::process::id()));
+    assert_eq!(library.symbols.last(), Some(&symbol));
+    assert!(missing.is_err());
+    assert_eq!(library.symbols.len(), 4);
+}
+
+#[test]
+fn settings_overlay_reports_failed_imports() {
+    let mut overlay = SettingsOverlay::with_path(temp_path("stamp_settings.json"));
+    overlay.set_stamp_image_path(temp_path("missing.png").to_string_lossy());
+
+    assert!(overlay.import_stamp_image().is_err());
+    assert_eq!(overlay.draft().canvas.stamps, StampLibrary::default());
+}
+
+#[test]
+fn settings_without_stamps_load_the_default_library() {
+    let path = temp_path("old_settings.json");
+    std::fs::write(&path, r#"{"canvas": {"zoom_sensitivity": 2.0}}"#).expect("Temp settings");
+
+    let settings = Settings::load_from(&path);
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(settings.canvas.zoom_sensitivity, 2.0);
+    assert_eq!(settings.canvas.stamps, StampLibrary::default());
+}
+
+// ============================================================================
+// Placing
+// ============================================================================
+
+#[test]
+fn clicking_places_the_chosen_stamp() {
+    let mut sim = CanvasSimulator::new(DrawingCanvas::new());
+    sim.canvas_mut().set_zoom(1.0);
+    sim.canvas_mut().choose_stamp("Rejected");
+
+    sim.stamp_at(Pos2::new(150.0, 120.0));
+
+    let canvas = sim.canvas();
+    assert!(canvas.shapes().is_empty());
+    assert_eq!(canvas.stamps().len(), 1);
+    let stamp = &canvas.stamps()[0];
+    assert_eq!(stamp.symbol.name, "Rejected");
+    assert_eq!(stamp.bounds.height(), STAMP_HEIGHT);
+    assert!(stamp.bounds.contains(Pos2::new(150.0, 120.0)));
+}
+
+#[test]
+fn stamp_tool_falls_back_to_the_first_symbol() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.choose_stamp("Removed from the library");
+
+    let idx = canvas.place_stamp(Pos2::new(50.0, 50.0)).expect("Library has symbols");
+    assert_eq!(canvas.stamps()[idx].symbol.name, "Approved");
+
+    let mut settings = Settings::default();
+    settings.canvas.stamps.symbols.clear();
+    canvas.apply_settings(&settings);
+    assert!(canvas.place_stamp(Pos2::new(50.0, 50.0)).is_none());
+}
+
+#[test]
+fn topmost_stamp_is_found_and_removed() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.add_stamp(paid_stamp(Pos2::new(50.0, 50.0)));
+    let top = canvas.add_stamp(paid_stamp(Pos2::new(60.0, 50.0)));
+
+    assert_eq!(canvas.stamp_at(Pos2::new(55.0, 50.0)), Some(top));
+    assert_eq!(canvas.stamp_at(Pos2::new(500.0, 50.0)), None);
+    assert!(canvas.remove_stamp(top).is_some());
+    assert_eq!(canvas.stamp_at(Pos2::new(55.0, 50.0)), Some(0));
+}
+
+#[test]
+fn clearing_the_shapes_layer_removes_stamps() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.add_stamp(paid_stamp(Pos2::new(50.0, 50.0)));
+
+    canvas.apply_command(CanvasCommand::ClearLayer(LayerType::Shapes));
+    assert!(canvas.stamps().is_empty());
+}
+
+#[test]
+fn stamps_are_saved_with_the_project() {
+    let mut canvas = DrawingCanvas::new();
+    let stamp = paid_stamp(Pos2::new(50.0, 50.0));
+    canvas.add_stamp(stamp.clone());
+
+    let json = canvas.to_json().expect("Serializable");
+    let restored = DrawingCanvas::from_json(&json).expect("Loadable");
+    assert_eq!(restored.stamps(), &vec![stamp]);
+}
+
+// ============================================================================
+// Export
+// ============================================================================
+
+#[test]
+fn png_export_draws_stamps() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.add_stamp(paid_stamp(Pos2::new(50.0, 50.0)));
+
+    let image = canvas.render_png(1.0).expect("Exportable");
+
+    let [r, g, b, _] = Color32::from_rgb(30, 80, 200).to_srgba_unmultiplied();
+    // The border runs along the edge of the stamp, which sits inside the 10 unit margin
+    assert_eq!(image.get_pixel(11, image.height() / 2).0, [r, g, b, 255], "border");
+    assert_eq!(image.get_pixel(2, 2).0, [255, 255, 255, 255], "margin");
+    let inked = image.enumerate_pixels().filter(|(x, _, pixel)| *x > 20 && pixel.0 != [255, 255, 255, 255]);
+    assert!(inked.count() > 50, "text is drawn inside the border");
+}
+
+#[test]
+fn svg_export_keeps_stamp_text() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.add_stamp(paid_stamp(Pos2::new(50.0, 50.0)));
+
+    let svg = canvas.to_svg().expect("Exportable");
+
+    assert!(svg.contains("<g id=\"stamps\">"));
+    assert!(svg.contains("<title>Paid</title>"));
+    assert!(svg.contains(">PAID</text>"));
+
+    canvas.apply_command(CanvasCommand::SetLayerVisible {
+        layer: LayerType::Shapes,
+        visible: false,
+    });
+    assert!(canvas.to_svg().is_err(), "nothing is left to export");
+}
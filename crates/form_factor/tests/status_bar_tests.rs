@@ -0,0 +1,84 @@
+//! Integration tests for the status bar component
+
+use egui::Pos2;
+use form_factor::{CanvasSimulator, DrawingCanvas, StatusBar, ToolMode};
+
+// ============================================================================
+// Formatting
+// ============================================================================
+
+#[test]
+fn segments_show_cursor_zoom_tool_and_layer() {
+    let bar = StatusBar {
+        cursor: Some(Pos2::new(120.4, 45.6)),
+        zoom: 2.5,
+        tool: Some("Rectangle".to_string()),
+        layer: Some("Shapes".to_string()),
+        ..StatusBar::default()
+    }
+    .with_item("Page", "2 of 5");
+
+    assert_eq!(
+        bar.segments(),
+        vec!["120, 46 px", "250%", "Tool: Rectangle", "Layer: Shapes", "Page: 2 of 5"]
+    );
+}
+
+#[test]
+fn empty_bar_shows_placeholders() {
+    assert_eq!(StatusBar::new().segments(), vec!["-, - px", "100%"]);
+}
+
+#[test]
+fn bar_renders_with_background_tasks() {
+    let ctx = egui::Context::default();
+    let bar = StatusBar::new().with_background_tasks(2);
+    let _ = ctx.run(egui::RawInput::default(), |ctx| bar.show(ctx));
+}
+
+// ============================================================================
+// Canvas integration
+// ============================================================================
+
+#[test]
+fn canvas_reports_tool_and_zoom() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_tool(ToolMode::Circle);
+    canvas.set_zoom(2.0);
+
+    let bar = canvas.status_bar();
+
+    assert_eq!(bar.tool.as_deref(), Some("Circle"));
+    assert_eq!(bar.zoom, 2.0);
+    assert!(bar.layer.is_none());
+}
+
+#[test]
+fn cursor_is_unknown_without_an_image() {
+    let mut sim = CanvasSimulator::new(DrawingCanvas::new());
+    sim.step();
+    let center = sim.canvas_rect().center();
+    sim.pointer_move(center);
+
+    assert!(sim.canvas().status_bar().cursor.is_none());
+}
+
+#[test]
+fn cursor_is_reported_in_image_pixels() {
+    let path = std::env::temp_dir().join(format!("form_factor_{}_status_bar.png", std::process::id()));
+    image::RgbaImage::new(100, 50).save(&path).expect("Temp image");
+
+    let mut sim = CanvasSimulator::new(DrawingCanvas::new());
+    let ctx = sim.ctx().clone();
+    sim.canvas_mut()
+        .load_form_image(path.to_str().expect("UTF-8 path"), &ctx)
+        .expect("Image loads");
+    let _ = std::fs::remove_file(&path);
+    sim.step();
+    let center = sim.canvas_rect().center();
+    sim.pointer_move(center);
+
+    let cursor = sim.canvas().status_bar().cursor.expect("Cursor over image");
+    assert!((cursor.x - 50.0).abs() < 0.5, "x = {}", cursor.x);
+    assert!((cursor.y - 25.0).abs() < 0.5, "y = {}", cursor.y);
+}
@@ -0,0 +1,114 @@
+//! Integration tests for named style presets
+
+mod common;
+
+use common::temp_path;
+use egui::{Color32, Stroke, vec2};
+use form_factor::{CanvasSimulator, DrawingCanvas, Settings, Shape, StrokeStyle, StylePreset, StylePresets};
+
+fn review_preset() -> StylePreset {
+    StylePreset::new(
+        "Review",
+        Stroke::new(3.0, Color32::from_rgb(230, 150, 0)),
+        Color32::from_rgba_unmultiplied(230, 150, 0, 40),
+        StrokeStyle::Dotted,
+    )
+}
+
+// ============================================================================
+// Preset list
+// ============================================================================
+
+#[test]
+fn presets_start_with_field_and_issue() {
+    let presets = StylePresets::default();
+
+    assert_eq!(presets.names(), ["Field", "Issue"]);
+    assert_eq!(presets.get("Field").expect("Default").stroke_style, StrokeStyle::Solid);
+    assert_eq!(presets.get("Issue").expect("Default").stroke_style, StrokeStyle::Dashed);
+}
+
+#[test]
+fn adding_a_preset_replaces_one_with_the_same_name() {
+    let mut presets = StylePresets::default();
+    let mut issue = review_preset();
+    issue.name = "Issue".to_string();
+    presets.add(issue.clone());
+    presets.add(review_preset());
+
+    assert_eq!(presets.names(), ["Field", "Issue", "Review"]);
+    assert_eq!(presets.get("Issue"), Some(&issue));
+    assert!(presets.remove("Review").is_some());
+    assert!(presets.remove("Review").is_none());
+}
+
+#[test]
+fn settings_without_presets_load_the_defaults() {
+    let path = temp_path("old_style_settings.json");
+    std::fs::write(&path, r#"{"canvas": {"zoom_sensitivity": 2.0}}"#).expect("Temp settings");
+
+    let settings = Settings::load_from(&path);
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(settings.canvas.zoom_sensitivity, 2.0);
+    assert_eq!(settings.canvas.style_presets, StylePresets::default());
+}
+
+// ============================================================================
+// Drawing
+// ============================================================================
+
+#[test]
+fn new_shapes_take_the_chosen_preset() {
+    let mut settings = Settings::default();
+    settings.canvas.style_presets.add(review_preset());
+    let mut canvas = DrawingCanvas::new();
+    canvas.apply_settings(&settings);
+    canvas.choose_style_preset(Some("Review".to_string()));
+    let mut sim = CanvasSimulator::new(canvas);
+    sim.step();
+    let center = sim.canvas_rect().center();
+
+    sim.draw_rectangle(center, center + vec2(40.0, 20.0));
+    sim.draw_line(center + vec2(0.0, 40.0), center + vec2(40.0, 40.0));
+
+    let shapes = sim.canvas().shapes();
+    assert_eq!(shapes.len(), 2);
+    let preset = review_preset();
+    for shape in shapes {
+        assert_eq!(shape.stroke(), preset.stroke);
+        assert_eq!(shape.stroke_style(), StrokeStyle::Dotted);
+    }
+    assert!(matches!(&shapes[0], Shape::Rectangle(rect) if rect.fill == preset.fill));
+}
+
+#[test]
+fn removed_preset_falls_back_to_the_default_style() {
+    let mut canvas = DrawingCanvas::new();
+    let default_stroke = *canvas.stroke();
+    canvas.choose_style_preset(Some("Issue".to_string()));
+    assert_eq!(canvas.style_preset().map(|preset| preset.name.as_str()), Some("Issue"));
+
+    let mut settings = Settings::default();
+    settings.canvas.style_presets.remove("Issue");
+    canvas.apply_settings(&settings);
+    assert!(canvas.style_preset().is_none());
+
+    let mut sim = CanvasSimulator::new(canvas);
+    sim.step();
+    let center = sim.canvas_rect().center();
+    sim.draw_rectangle(center, center + vec2(40.0, 20.0));
+    let shape = &sim.canvas().shapes()[0];
+    assert_eq!(shape.stroke(), default_stroke);
+    assert_eq!(shape.stroke_style(), StrokeStyle::Solid);
+}
+
+#[test]
+fn snapshot_lists_presets_for_the_toolbar() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.choose_style_preset(Some("Field".to_string()));
+
+    let snapshot = canvas.snapshot();
+    assert_eq!(snapshot.style_presets(), ["Field", "Issue"]);
+    assert_eq!(snapshot.style_preset(), Some("Field"));
+}
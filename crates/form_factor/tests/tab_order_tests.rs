@@ -0,0 +1,132 @@
+//! Integration tests for the field tab order followed by data entry
+
+mod common;
+
+use common::field_at;
+use form_factor::{
+    CanvasCommand, CanvasPanel, DrawingCanvas, ProjectTemplate, ReadingDirection,
+};
+
+/// Form whose fields were drawn out of reading order
+///
+/// Reads as `name`, `ssn` on the first row and `wages`, `tips` on the second.
+fn form() -> DrawingCanvas {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![
+        field_at("wages", 0.0, 52.0),
+        field_at("ssn", 200.0, 0.0),
+        field_at("tips", 200.0, 48.0),
+        field_at("name", 0.0, 4.0),
+    ]);
+    canvas
+}
+
+/// Names of the fields in tab order
+fn in_tab_order(canvas: &DrawingCanvas) -> Vec<&str> {
+    canvas.field_order().into_iter().map(|idx| canvas.shapes()[idx].name()).collect()
+}
+
+/// Names of the fields data entry visits, from the first
+fn entry_sequence(canvas: &mut DrawingCanvas) -> Vec<String> {
+    canvas.open_data_entry_panel();
+    let mut visited = Vec::new();
+    loop {
+        let idx = canvas.data_entry_field().expect("Entering a field");
+        visited.push(canvas.shapes()[idx].name().to_string());
+        if !canvas.advance_field() {
+            return visited;
+        }
+    }
+}
+
+// ============================================================================
+// Ordering
+// ============================================================================
+
+#[test]
+fn fields_follow_drawing_order_by_default() {
+    let canvas = form();
+
+    assert_eq!(canvas.field_order(), vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn auto_order_reads_rows_from_the_top() {
+    let mut canvas = form();
+
+    canvas.auto_tab_order(ReadingDirection::LeftToRight);
+    assert_eq!(in_tab_order(&canvas), ["name", "ssn", "wages", "tips"]);
+
+    canvas.auto_tab_order(ReadingDirection::RightToLeft);
+    assert_eq!(in_tab_order(&canvas), ["ssn", "name", "tips", "wages"]);
+
+    canvas.reset_tab_order();
+    assert_eq!(canvas.field_order(), vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn moving_a_field_shifts_the_others() {
+    let mut canvas = form();
+
+    assert!(canvas.move_in_tab_order(3, 0));
+    assert_eq!(in_tab_order(&canvas), ["name", "wages", "ssn", "tips"]);
+    assert!(canvas.move_in_tab_order(0, 3));
+    assert_eq!(in_tab_order(&canvas), ["wages", "ssn", "tips", "name"]);
+    assert!(!canvas.move_in_tab_order(4, 0), "Out of range");
+}
+
+#[test]
+fn unlisted_fields_follow_in_drawing_order() {
+    let mut canvas = form();
+
+    canvas.set_tab_order(vec![2, 2, 9, 0]);
+
+    assert_eq!(canvas.field_order(), vec![2, 0, 1, 3]);
+}
+
+#[test]
+fn deleting_a_field_keeps_the_order_of_the_rest() {
+    let mut canvas = form();
+    canvas.auto_tab_order(ReadingDirection::LeftToRight);
+
+    canvas.delete_shape(1);
+
+    assert_eq!(in_tab_order(&canvas), ["name", "wages", "tips"]);
+}
+
+// ============================================================================
+// Data entry and persistence
+// ============================================================================
+
+#[test]
+fn data_entry_visits_fields_in_tab_order() {
+    let mut canvas = form();
+    canvas.auto_tab_order(ReadingDirection::LeftToRight);
+
+    assert_eq!(entry_sequence(&mut canvas), ["name", "ssn", "wages", "tips"]);
+
+    assert!(canvas.previous_field());
+    assert_eq!(*canvas.data_entry_field(), Some(0), "Back from tips to wages");
+}
+
+#[test]
+fn tab_order_is_saved_with_projects_and_templates() {
+    let mut canvas = form();
+    canvas.auto_tab_order(ReadingDirection::LeftToRight);
+
+    let loaded = DrawingCanvas::from_json(&canvas.to_json().expect("Serializable")).expect("Loadable");
+    assert_eq!(loaded.field_order(), canvas.field_order());
+
+    let template = ProjectTemplate::from_canvas("W-2", &canvas);
+    let mut project = DrawingCanvas::new();
+    project.start_project_from_template("Next W-2", &template, &egui::Context::default());
+    assert_eq!(in_tab_order(&project), ["name", "ssn", "wages", "tips"]);
+}
+
+#[test]
+fn tab_order_window_toggles() {
+    let mut canvas = form();
+
+    assert!(canvas.apply_command(CanvasCommand::TogglePanel(CanvasPanel::TabOrder)));
+    assert!(*canvas.show_tab_order());
+}
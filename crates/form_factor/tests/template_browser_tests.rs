@@ -0,0 +1,140 @@
+//! Integration tests for searching, filtering and sorting project templates
+
+use form_factor::{
+    OcrSettings, ProjectTemplate, TemplateFilter, TemplateSortOrder, template_departments, template_languages,
+    template_tags,
+};
+
+fn ocr(language: &str) -> OcrSettings {
+    OcrSettings {
+        language: language.to_string(),
+        min_confidence: 60,
+        preprocessing: true,
+    }
+}
+
+fn used(template: ProjectTemplate, instances: u64) -> ProjectTemplate {
+    ProjectTemplate { instances, ..template }
+}
+
+/// Templates of a small payroll and HR office
+fn registry() -> Vec<ProjectTemplate> {
+    vec![
+        used(
+            ProjectTemplate::new("Wage and Tax Statement")
+                .with_form_number("W-2")
+                .with_department("Payroll")
+                .with_tag("tax")
+                .with_tag("annual")
+                .with_ocr(ocr("eng")),
+            40,
+        ),
+        used(
+            ProjectTemplate::new("Employment Eligibility")
+                .with_form_number("I-9")
+                .with_department("HR")
+                .with_tag("onboarding")
+                .with_ocr(ocr("eng+spa")),
+            12,
+        ),
+        used(
+            ProjectTemplate::new("Direct Deposit")
+                .with_department("payroll")
+                .with_tag("Onboarding")
+                .with_description("Bank account for wages"),
+            12,
+        ),
+        ProjectTemplate::new("Old W-4").with_form_number("W-4").with_tag("tax").with_deprecated(true),
+    ]
+}
+
+fn listed_names(templates: &[ProjectTemplate], listed: Vec<usize>) -> Vec<&str> {
+    listed.into_iter().map(|idx| templates[idx].name.as_str()).collect()
+}
+
+// ============================================================================
+// Tags
+// ============================================================================
+
+#[test]
+fn tags_ignore_case_and_blanks() {
+    let mut template = ProjectTemplate::new("W-2").with_tag("Tax");
+
+    assert!(!template.add_tag("tax"));
+    assert!(!template.add_tag("  "));
+    assert!(template.add_tag(" annual "));
+    assert!(template.has_tag("ANNUAL"));
+    assert!(template.remove_tag("TAX"));
+    assert_eq!(template.tags, ["annual"]);
+}
+
+// ============================================================================
+// Filtering
+// ============================================================================
+
+#[test]
+fn search_looks_in_names_numbers_descriptions_and_tags() {
+    let templates = registry();
+
+    let search = |query: &str| {
+        let listed = TemplateFilter::search(query).apply(&templates, TemplateSortOrder::Name);
+        listed_names(&templates, listed)
+    };
+
+    assert_eq!(search("w-2"), ["Wage and Tax Statement"]);
+    assert_eq!(search("onboard"), ["Direct Deposit", "Employment Eligibility"]);
+    assert_eq!(search("bank"), ["Direct Deposit"]);
+    assert_eq!(search("  "), ["Direct Deposit", "Employment Eligibility", "Wage and Tax Statement"]);
+}
+
+#[test]
+fn filters_narrow_by_tag_department_and_language() {
+    let templates = registry();
+    let list = |filter: TemplateFilter| listed_names(&templates, filter.apply(&templates, TemplateSortOrder::Name));
+
+    let onboarding = list(TemplateFilter::default().with_tag("ONBOARDING"));
+    assert_eq!(onboarding, ["Direct Deposit", "Employment Eligibility"]);
+    let payroll = list(TemplateFilter::default().with_department("Payroll"));
+    assert_eq!(payroll, ["Direct Deposit", "Wage and Tax Statement"]);
+    assert_eq!(list(TemplateFilter::default().with_language("spa")), ["Employment Eligibility"]);
+    assert_eq!(list(TemplateFilter::search("statement").with_department("HR")), Vec::<&str>::new());
+}
+
+#[test]
+fn deprecated_templates_are_listed_only_on_request() {
+    let templates = registry();
+    let filter = TemplateFilter {
+        include_deprecated: true,
+        ..TemplateFilter::default().with_tag("tax")
+    };
+
+    let listed = filter.apply(&templates, TemplateSortOrder::Name);
+
+    assert_eq!(listed_names(&templates, listed), ["Old W-4", "Wage and Tax Statement"]);
+}
+
+// ============================================================================
+// Sorting and choices
+// ============================================================================
+
+#[test]
+fn sort_orders_break_ties_by_name() {
+    let templates = registry();
+    let sorted = |order| listed_names(&templates, TemplateFilter::default().apply(&templates, order));
+
+    let by_number = sorted(TemplateSortOrder::FormNumber);
+    assert_eq!(by_number, ["Employment Eligibility", "Wage and Tax Statement", "Direct Deposit"]);
+    let by_department = sorted(TemplateSortOrder::Department);
+    assert_eq!(by_department, ["Employment Eligibility", "Direct Deposit", "Wage and Tax Statement"]);
+    let by_use = sorted(TemplateSortOrder::MostUsed);
+    assert_eq!(by_use, ["Wage and Tax Statement", "Direct Deposit", "Employment Eligibility"]);
+}
+
+#[test]
+fn filter_choices_come_from_the_templates() {
+    let templates = registry();
+
+    assert_eq!(template_tags(&templates), ["annual", "onboarding", "tax"]);
+    assert_eq!(template_departments(&templates), ["HR", "Payroll"]);
+    assert_eq!(template_languages(&templates), ["eng", "spa"]);
+}
@@ -0,0 +1,149 @@
+//! Integration tests for multi-page templates and page matching
+
+mod common;
+
+use common::{field_at, names};
+use egui::{Pos2, Rect, vec2};
+use form_factor::{
+    DrawingCanvas, FieldGroup, PageMatch, PageOrientation, ProjectTemplate, TemplatePage,
+};
+
+fn page(name: &str, size: (f32, f32), fields: &[&str]) -> TemplatePage {
+    let mut page = TemplatePage::new(name).with_reference_size(vec2(size.0, size.1));
+    page.fields = fields.iter().enumerate().map(|(i, name)| field_at(name, 0.0, i as f32 * 30.0)).collect();
+    page
+}
+
+/// Tax packet: portrait cover sheet, landscape schedule, portrait signature page
+fn packet(page_match: PageMatch) -> ProjectTemplate {
+    ProjectTemplate::new("Return")
+        .with_page(
+            page("Cover", (850.0, 1100.0), &["name", "ssn"])
+                .with_anchor("irs_seal", Rect::from_min_size(Pos2::new(20.0, 20.0), vec2(80.0, 80.0))),
+        )
+        .with_page(page("Schedule", (1100.0, 850.0), &["income", "expenses", "profit"]))
+        .with_page(
+            page("Signature", (850.0, 1100.0), &["signature", "date"])
+                .with_anchor("sign_here", Rect::from_min_size(Pos2::new(40.0, 900.0), vec2(120.0, 40.0))),
+        )
+        .with_page_match(page_match)
+}
+
+/// Canvas showing a blank form image of the given size
+fn form_of_size(width: u32, height: u32) -> DrawingCanvas {
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    image::RgbaImage::from_pixel(width, height, image::Rgba([255, 255, 255, 255]))
+        .write_to(&mut bytes, image::ImageFormat::Png)
+        .expect("Encodable");
+    let mut canvas = DrawingCanvas::new();
+    canvas
+        .load_form_image_bytes("page.png", bytes.get_ref(), &egui::Context::default())
+        .expect("Valid image");
+    canvas
+}
+
+// ============================================================================
+// Matching
+// ============================================================================
+
+#[test]
+fn sequence_matching_reuses_the_last_page() {
+    let template = packet(PageMatch::Sequence);
+
+    assert_eq!(template.match_page(1, None, []), Some(1));
+    assert_eq!(template.match_page(7, None, []), Some(2));
+    assert_eq!(ProjectTemplate::new("Single").match_page(0, None, []), None);
+}
+
+#[test]
+fn orientation_matching_prefers_the_page_nearest_in_sequence() {
+    let template = packet(PageMatch::Orientation);
+
+    assert_eq!(template.match_page(0, Some(vec2(1100.0, 850.0)), []), Some(1));
+    assert_eq!(template.match_page(2, Some(vec2(850.0, 1100.0)), []), Some(2));
+    assert_eq!(template.match_page(1, Some(vec2(850.0, 1100.0)), []), Some(0), "Tie goes to the earlier page");
+    assert_eq!(template.match_page(1, None, []), Some(1), "No image falls back to the sequence");
+    assert_eq!(template.pages[1].orientation(), Some(PageOrientation::Landscape));
+}
+
+#[test]
+fn aspect_ratio_matching_picks_the_closest_proportions() {
+    let template = ProjectTemplate::new("Mixed")
+        .with_page(page("Letter", (850.0, 1100.0), &[]))
+        .with_page(page("Legal", (850.0, 1400.0), &[]))
+        .with_page_match(PageMatch::AspectRatio);
+
+    assert_eq!(template.match_page(0, Some(vec2(1700.0, 2790.0)), []), Some(1));
+    assert_eq!(template.match_page(1, Some(vec2(1700.0, 2210.0)), []), Some(0));
+}
+
+#[test]
+fn anchor_matching_counts_detected_logos() {
+    let template = packet(PageMatch::Anchors);
+
+    assert_eq!(template.match_page(0, None, ["sign_here"]), Some(2));
+    assert_eq!(template.match_page(2, None, ["irs_seal", "barcode"]), Some(0));
+    assert_eq!(template.match_page(1, None, ["barcode"]), Some(1), "No anchors found falls back to the sequence");
+}
+
+// ============================================================================
+// Applying pages
+// ============================================================================
+
+#[test]
+fn forms_get_the_fields_of_their_matching_page() {
+    let template = packet(PageMatch::Orientation);
+    let mut canvas = form_of_size(110, 85);
+
+    assert_eq!(canvas.apply_matching_template_page(&template, 0), Some(1));
+
+    assert_eq!(names(&canvas), ["income", "expenses", "profit"]);
+    assert_eq!(canvas.template_name().as_deref(), Some("Return"));
+    assert_eq!(canvas.template_page().as_deref(), Some("Schedule"));
+    assert!(canvas.form_image_size().is_some(), "The form image is kept");
+}
+
+#[test]
+fn new_projects_start_on_the_first_page() {
+    let template = packet(PageMatch::Sequence);
+    let mut canvas = DrawingCanvas::new();
+
+    canvas.start_project_from_template("Smith 2025", &template, &egui::Context::default());
+
+    assert_eq!(names(&canvas), ["name", "ssn"]);
+    assert!(!canvas.apply_template_page(&template, 3), "No fourth page");
+}
+
+#[test]
+fn pages_keep_the_layout_of_the_project_they_came_from() {
+    let mut canvas = form_of_size(110, 85);
+    canvas.set_shapes(vec![field_at("income", 0.0, 0.0), field_at("profit", 0.0, 30.0)]);
+    canvas.set_tab_order(vec![1, 0]);
+    canvas.add_field_group(FieldGroup::new("totals", ["income", "profit"])).expect("Valid group");
+
+    let page = TemplatePage::from_canvas("Schedule", &canvas);
+
+    assert_eq!(page.reference_size, Some(vec2(110.0, 85.0)));
+    assert_eq!(page.reference_image.as_deref(), Some(std::path::Path::new("page.png")));
+    assert_eq!(page.fields.len(), 2);
+    assert_eq!(page.tab_order, [1, 0]);
+    assert_eq!(page.groups, canvas.field_groups().clone());
+}
+
+#[test]
+fn pages_are_saved_with_templates_and_projects() {
+    let dir = std::env::temp_dir().join(format!("form_factor_{}_template_pages", std::process::id()));
+    let template = packet(PageMatch::Anchors);
+
+    let path = template.save_in(&dir).expect("Saved");
+    let loaded = ProjectTemplate::load_from(&path).expect("Loaded");
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(loaded.pages, template.pages);
+    assert_eq!(loaded.page_match, PageMatch::Anchors);
+
+    let mut canvas = DrawingCanvas::new();
+    canvas.apply_template_page(&loaded, 2);
+    let reopened = DrawingCanvas::from_json(&canvas.to_json().expect("Serializable")).expect("Loadable");
+    assert_eq!(reopened.template_page().as_deref(), Some("Signature"));
+}
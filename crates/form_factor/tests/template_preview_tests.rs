@@ -0,0 +1,80 @@
+//! Integration tests for previewing field layouts with sample data
+
+mod common;
+
+use common::{field, region};
+use egui::Pos2;
+use form_factor::{CanvasCommand, DrawingCanvas, FieldFormat, FieldRule, Shape, sample_value};
+
+/// `shape` as an optional field holding values of `format`
+fn formatted(mut shape: Shape, format: FieldFormat) -> Shape {
+    shape.set_rule(FieldRule {
+        required: false,
+        format,
+    });
+    shape
+}
+
+// ============================================================================
+// Sample values
+// ============================================================================
+
+#[test]
+fn samples_follow_the_field_format() {
+    assert_eq!(sample_value("agree", FieldFormat::Checkbox), "X");
+    assert_eq!(sample_value("anything", FieldFormat::Number), "1,234.56");
+    assert_eq!(sample_value("signed_on", FieldFormat::Date), "2024-03-15");
+}
+
+#[test]
+fn text_samples_are_guessed_from_the_field_name() {
+    assert_eq!(sample_value("Employee Name", FieldFormat::Text), "Jane Doe");
+    assert_eq!(sample_value("home_phone", FieldFormat::Text), "(555) 010-0199");
+    assert_eq!(sample_value("ZIP", FieldFormat::Text), "90210");
+    assert_eq!(sample_value("street_address", FieldFormat::Text), "123 Main St");
+    assert_eq!(sample_value("box 14", FieldFormat::Text), "Sample text");
+}
+
+// ============================================================================
+// Preview
+// ============================================================================
+
+#[test]
+fn narrow_fields_are_flagged() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![
+        formatted(region("employee_name", 0.0, 0.0, 200.0, 20.0), FieldFormat::Text),
+        formatted(region("email", 0.0, 0.0, 30.0, 20.0), FieldFormat::Text),
+        formatted(region("agree", 0.0, 0.0, 20.0, 20.0), FieldFormat::Checkbox),
+    ]);
+
+    let preview = canvas.preview_values();
+
+    let fits: Vec<(&str, bool)> = preview.iter().map(|value| (value.text.as_str(), value.fits)).collect();
+    assert_eq!(fits, [("Jane Doe", true), ("jane.doe@example.com", false), ("X", true)]);
+}
+
+#[test]
+fn previewing_leaves_values_alone() {
+    let mut canvas = DrawingCanvas::new();
+    let mut wages = formatted(field("wages", Pos2::ZERO), FieldFormat::Number);
+    wages.set_value("52,000.00");
+    let mut hidden = field("hidden", Pos2::ZERO);
+    hidden.set_hidden(true);
+    canvas.set_shapes(vec![wages, hidden]);
+
+    canvas.toggle_template_preview();
+
+    assert!(*canvas.template_preview());
+    assert_eq!(canvas.preview_values().len(), 1, "Hidden fields are not previewed");
+    assert_eq!(canvas.shapes()[0].value(), "52,000.00");
+}
+
+#[test]
+fn preview_changes_are_commands() {
+    let mut canvas = DrawingCanvas::new();
+
+    assert!(canvas.apply_command(CanvasCommand::SetTemplatePreview(true)));
+    assert!(!canvas.apply_command(CanvasCommand::SetTemplatePreview(true)), "Already on");
+    assert_eq!(canvas.take_changes(), [CanvasCommand::SetTemplatePreview(true)]);
+}
@@ -0,0 +1,73 @@
+//! Integration tests for exporting the text read from the form
+
+mod common;
+
+use common::{field_at, read_canvas};
+use form_factor::{DrawingCanvas, ReadingDirection, TextExportOptions, TextFormat};
+
+/// Canvas whose detections were found bottom to top, the last one blank
+fn w2_canvas() -> DrawingCanvas {
+    let mut canvas = read_canvas(
+        vec![field_at("", 0.0, 100.0), field_at("Employer", 0.0, 0.0), field_at("", 0.0, 200.0)],
+        &["Wages 52,000", " Acme Corp \n", "  "],
+    );
+    canvas.set_project_name("W-2");
+    canvas.auto_ocr_reading_order(ReadingDirection::LeftToRight);
+    canvas
+}
+
+fn options(format: TextFormat, page_headers: bool, region_headers: bool) -> TextExportOptions {
+    TextExportOptions {
+        format,
+        page_headers,
+        region_headers,
+    }
+}
+
+#[test]
+fn plain_text_follows_reading_order() {
+    let canvas = w2_canvas();
+
+    let text = canvas.extracted_text(&options(TextFormat::PlainText, false, false));
+
+    assert_eq!(text, "Acme Corp\nWages 52,000\n");
+}
+
+#[test]
+fn plain_text_headers_name_the_page_and_regions() {
+    let canvas = w2_canvas();
+
+    let text = canvas.extracted_text(&options(TextFormat::PlainText, true, true));
+
+    assert_eq!(text, "=== W-2 ===\n\n[Employer]\nAcme Corp\n\n[Region 1]\nWages 52,000\n");
+}
+
+#[test]
+fn markdown_uses_headings_and_paragraphs() {
+    let canvas = w2_canvas();
+
+    assert_eq!(
+        canvas.extracted_text(&options(TextFormat::Markdown, true, true)),
+        "# W-2\n\n## Employer\n\nAcme Corp\n\n## Region 1\n\nWages 52,000\n"
+    );
+    assert_eq!(
+        canvas.extracted_text(&options(TextFormat::Markdown, false, false)),
+        "Acme Corp\n\nWages 52,000\n"
+    );
+}
+
+#[test]
+fn export_writes_the_file() {
+    let canvas = w2_canvas();
+    let path = std::env::temp_dir().join(format!("form_factor_text_export_{}.md", std::process::id()));
+    let opts = options(TextFormat::Markdown, true, false);
+
+    let count = canvas
+        .export_extracted_text(path.to_str().expect("UTF-8 path"), &opts)
+        .expect("Exported");
+
+    assert_eq!(count, 2);
+    assert_eq!(std::fs::read_to_string(&path).expect("Readable"), canvas.extracted_text(&opts));
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(TextFormat::Markdown.extension(), "md");
+}
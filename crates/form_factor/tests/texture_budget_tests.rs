@@ -0,0 +1,91 @@
+//! Integration tests for the texture memory budget
+
+use egui::{Color32, ColorImage, TextureHandle, TextureOptions};
+use form_factor::{DEFAULT_TEXTURE_BUDGET_MB, DrawingCanvas, Settings, TextureCache};
+
+/// Square texture holding `side` × `side` × 4 bytes
+fn texture(ctx: &egui::Context, name: &str, side: usize) -> TextureHandle {
+    ctx.load_texture(name, ColorImage::filled([side, side], Color32::RED), TextureOptions::default())
+}
+
+const SIDE: usize = 16;
+const BYTES: usize = SIDE * SIDE * 4;
+
+#[test]
+fn least_recently_used_textures_are_evicted_over_budget() {
+    let ctx = egui::Context::default();
+    let mut cache = TextureCache::new(2 * BYTES);
+
+    cache.get_or_load("a", || Some(texture(&ctx, "a", SIDE)));
+    cache.begin_frame(0);
+    cache.get_or_load("b", || Some(texture(&ctx, "b", SIDE)));
+    cache.begin_frame(0);
+    cache.get_or_load("c", || Some(texture(&ctx, "c", SIDE)));
+    cache.begin_frame(0);
+
+    assert!(!cache.contains("a"), "Oldest texture should be evicted");
+    assert!(cache.contains("b"));
+    assert!(cache.contains("c"));
+    assert_eq!(cache.used(), 2 * BYTES);
+    assert_eq!(cache.evictions(), 1);
+}
+
+#[test]
+fn textures_drawn_in_the_last_frame_are_kept_over_budget() {
+    let ctx = egui::Context::default();
+    let mut cache = TextureCache::new(BYTES);
+
+    for key in ["a", "b", "c"] {
+        cache.get_or_load(key, || Some(texture(&ctx, key, SIDE)));
+    }
+    cache.begin_frame(0);
+
+    assert_eq!(cache.len(), 3);
+    assert_eq!(cache.evictions(), 0);
+
+    cache.get_or_load("c", || panic!("Cached texture should not be reloaded"));
+    cache.begin_frame(0);
+
+    assert_eq!(cache.len(), 1);
+    assert!(cache.contains("c"));
+}
+
+#[test]
+fn unreadable_images_are_remembered_and_not_evicted() {
+    let mut cache = TextureCache::new(0);
+
+    assert!(cache.get_or_load("missing", || None).is_none());
+    cache.begin_frame(0);
+    cache.begin_frame(0);
+
+    assert!(cache.contains("missing"));
+    assert!(cache.get_or_load("missing", || panic!("Unreadable image should not be read again")).is_none());
+    assert_eq!(cache.used(), 0);
+}
+
+#[test]
+fn reserved_memory_counts_against_the_budget() {
+    let ctx = egui::Context::default();
+    let mut cache = TextureCache::new(2 * BYTES);
+
+    cache.get_or_load("a", || Some(texture(&ctx, "a", SIDE)));
+    cache.get_or_load("b", || Some(texture(&ctx, "b", SIDE)));
+    cache.begin_frame(0);
+    cache.begin_frame(BYTES);
+
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.used(), BYTES);
+}
+
+#[test]
+fn settings_set_the_canvas_texture_budget() {
+    let mut settings = Settings::default();
+    assert_eq!(settings.canvas.texture_budget_mb, DEFAULT_TEXTURE_BUDGET_MB);
+
+    settings.canvas.texture_budget_mb = 128;
+    let mut canvas = DrawingCanvas::new();
+    canvas.apply_settings(&settings);
+
+    assert_eq!(canvas.textures().budget(), 128 * 1024 * 1024);
+    assert_eq!(canvas.texture_memory(), 0);
+}
@@ -1,8 +1,9 @@
 //! Integration tests for tool module
 //!
-//! Tests validate ToolMode enum behavior, serialization, and trait implementations.
+//! Tests validate ToolMode enum behavior, serialization, and trait implementations,
+//! and the registry of custom tools.
 
-use form_factor::ToolMode;
+use form_factor::{CustomTool, DrawingCanvas, ToolInput, ToolMode, ToolRegistry};
 use strum::IntoEnumIterator;
 
 // ============================================================================
@@ -18,7 +19,7 @@ fn default_tool_is_select() {
 #[test]
 fn all_tool_modes_are_unique() {
     let tools: Vec<_> = ToolMode::iter().collect();
-    assert_eq!(tools.len(), 6);
+    assert_eq!(tools.len(), 13);
 
     // Verify all are unique
     for (i, tool1) in tools.iter().enumerate() {
@@ -39,7 +40,14 @@ fn enum_iteration_has_all_tools() {
             ToolMode::Select,
             ToolMode::Rectangle,
             ToolMode::Circle,
+            ToolMode::Ellipse,
             ToolMode::Freehand,
+            ToolMode::Line,
+            ToolMode::Polyline,
+            ToolMode::Arrow,
+            ToolMode::Callout,
+            ToolMode::Highlighter,
+            ToolMode::Stamp,
             ToolMode::Edit,
             ToolMode::Rotate,
         ]
@@ -48,7 +56,7 @@ fn enum_iteration_has_all_tools() {
 
 #[test]
 fn enum_iteration_count() {
-    assert_eq!(ToolMode::iter().count(), 6);
+    assert_eq!(ToolMode::iter().count(), 13);
 }
 
 // ============================================================================
@@ -60,7 +68,14 @@ fn display_all_tool_modes() {
     assert_eq!(ToolMode::Select.to_string(), "Select");
     assert_eq!(ToolMode::Rectangle.to_string(), "Rectangle");
     assert_eq!(ToolMode::Circle.to_string(), "Circle");
+    assert_eq!(ToolMode::Ellipse.to_string(), "Ellipse");
     assert_eq!(ToolMode::Freehand.to_string(), "Freehand");
+    assert_eq!(ToolMode::Line.to_string(), "Line");
+    assert_eq!(ToolMode::Polyline.to_string(), "Polyline");
+    assert_eq!(ToolMode::Arrow.to_string(), "Arrow");
+    assert_eq!(ToolMode::Callout.to_string(), "Callout");
+    assert_eq!(ToolMode::Highlighter.to_string(), "Highlighter");
+    assert_eq!(ToolMode::Stamp.to_string(), "Stamp");
     assert_eq!(ToolMode::Edit.to_string(), "Edit");
     assert_eq!(ToolMode::Rotate.to_string(), "Rotate");
 }
@@ -89,8 +104,15 @@ fn ordering_matches_declaration_order() {
     // Tools should be ordered as declared in enum
     assert!(ToolMode::Select < ToolMode::Rectangle);
     assert!(ToolMode::Rectangle < ToolMode::Circle);
-    assert!(ToolMode::Circle < ToolMode::Freehand);
-    assert!(ToolMode::Freehand < ToolMode::Edit);
+    assert!(ToolMode::Circle < ToolMode::Ellipse);
+    assert!(ToolMode::Ellipse < ToolMode::Freehand);
+    assert!(ToolMode::Freehand < ToolMode::Line);
+    assert!(ToolMode::Line < ToolMode::Polyline);
+    assert!(ToolMode::Polyline < ToolMode::Arrow);
+    assert!(ToolMode::Arrow < ToolMode::Callout);
+    assert!(ToolMode::Callout < ToolMode::Highlighter);
+    assert!(ToolMode::Highlighter < ToolMode::Stamp);
+    assert!(ToolMode::Stamp < ToolMode::Edit);
     assert!(ToolMode::Edit < ToolMode::Rotate);
 }
 
@@ -100,8 +122,15 @@ fn ordering_is_consistent() {
         ToolMode::Rotate,
         ToolMode::Select,
         ToolMode::Edit,
+        ToolMode::Callout,
+        ToolMode::Highlighter,
         ToolMode::Circle,
+        ToolMode::Stamp,
+        ToolMode::Ellipse,
+        ToolMode::Polyline,
         ToolMode::Freehand,
+        ToolMode::Line,
+        ToolMode::Arrow,
         ToolMode::Rectangle,
     ];
     tools.sort();
@@ -112,7 +141,14 @@ fn ordering_is_consistent() {
             ToolMode::Select,
             ToolMode::Rectangle,
             ToolMode::Circle,
+            ToolMode::Ellipse,
             ToolMode::Freehand,
+            ToolMode::Line,
+            ToolMode::Polyline,
+            ToolMode::Arrow,
+            ToolMode::Callout,
+            ToolMode::Highlighter,
+            ToolMode::Stamp,
             ToolMode::Edit,
             ToolMode::Rotate,
         ]
@@ -233,7 +269,16 @@ fn pattern_matching_works() {
     let tool = ToolMode::Circle;
 
     let is_drawing_tool = match tool {
-        ToolMode::Rectangle | ToolMode::Circle | ToolMode::Freehand => true,
+        ToolMode::Rectangle
+        | ToolMode::Circle
+        | ToolMode::Ellipse
+        | ToolMode::Freehand
+        | ToolMode::Line
+        | ToolMode::Polyline
+        | ToolMode::Arrow
+        | ToolMode::Callout
+        | ToolMode::Highlighter
+        | ToolMode::Stamp => true,
         ToolMode::Select | ToolMode::Edit | ToolMode::Rotate => false,
     };
 
@@ -246,7 +291,16 @@ fn pattern_matching_with_select() {
 
     let is_manipulation_tool = match tool {
         ToolMode::Select | ToolMode::Edit | ToolMode::Rotate => true,
-        ToolMode::Rectangle | ToolMode::Circle | ToolMode::Freehand => false,
+        ToolMode::Rectangle
+        | ToolMode::Circle
+        | ToolMode::Ellipse
+        | ToolMode::Freehand
+        | ToolMode::Line
+        | ToolMode::Polyline
+        | ToolMode::Arrow
+        | ToolMode::Callout
+        | ToolMode::Highlighter
+        | ToolMode::Stamp => false,
     };
 
     assert!(is_manipulation_tool);
@@ -291,7 +345,7 @@ fn default_can_be_compared_with_all_tools() {
 #[test]
 fn all_tools_can_be_stored_in_vec() {
     let tools: Vec<ToolMode> = ToolMode::iter().collect();
-    assert_eq!(tools.len(), 6);
+    assert_eq!(tools.len(), 13);
 
     // Verify no data loss
     for (i, tool) in ToolMode::iter().enumerate() {
@@ -345,3 +399,82 @@ fn tool_can_cycle_through_all_modes() {
         assert!(tools.contains(&current));
     }
 }
+
+// ============================================================================
+// Custom Tool Tests
+// ============================================================================
+
+struct MeasureTool;
+
+impl CustomTool for MeasureTool {
+    fn id(&self) -> &str {
+        "Measure"
+    }
+
+    fn icon(&self) -> &str {
+        "📏"
+    }
+
+    fn handle_input(&mut self, _input: &ToolInput, _canvas: &mut DrawingCanvas) {}
+}
+
+struct ShadowingTool;
+
+impl CustomTool for ShadowingTool {
+    fn id(&self) -> &str {
+        "Rectangle"
+    }
+
+    fn handle_input(&mut self, _input: &ToolInput, _canvas: &mut DrawingCanvas) {}
+}
+
+#[test]
+fn registry_lists_tools_in_registration_order() {
+    let mut registry = ToolRegistry::new();
+
+    assert!(registry.register(Box::new(MeasureTool)));
+
+    assert_eq!(registry.len(), 1);
+    assert_eq!(registry.index_of("Measure"), Some(0));
+    let info = registry.info(0).expect("tool registered");
+    assert_eq!(info.label, "Measure");
+    assert_eq!(info.icon, "📏");
+}
+
+#[test]
+fn registry_rejects_taken_ids() {
+    let mut registry = ToolRegistry::new();
+
+    assert!(registry.register(Box::new(MeasureTool)));
+    assert!(!registry.register(Box::new(MeasureTool)));
+    assert!(!registry.register(Box::new(ShadowingTool)));
+
+    assert_eq!(registry.len(), 1);
+}
+
+#[test]
+fn custom_tool_replaces_built_in_tool_until_one_is_set() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.register_tool(Box::new(MeasureTool));
+
+    assert!(canvas.select_custom_tool("Measure"));
+    assert!(!canvas.select_custom_tool("Unknown"));
+    assert_eq!(canvas.active_tool_name(), "Measure");
+
+    canvas.set_tool(ToolMode::Rectangle);
+
+    assert_eq!(canvas.active_custom_tool(), None);
+    assert_eq!(canvas.active_tool_name(), "Rectangle");
+}
+
+#[test]
+fn custom_tools_move_to_a_new_canvas() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.register_tool(Box::new(MeasureTool));
+    let mut opened = DrawingCanvas::new();
+
+    canvas.transfer_custom_tools(&mut opened);
+
+    assert!(canvas.custom_tool_infos().is_empty());
+    assert_eq!(opened.custom_tool_infos().len(), 1);
+}
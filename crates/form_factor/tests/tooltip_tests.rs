@@ -0,0 +1,71 @@
+//! Integration tests for tooltips on hovered fields and detections
+
+mod common;
+
+use common::{form_simulator, region, with_detections};
+use egui::Pos2;
+use form_factor::{CanvasSimulator, FieldFormat, FieldRule, HoverTooltip, ImagePos};
+
+/// Simulator over a loaded form with one detection at image pixels (0, 0)-(100, 20)
+fn detected_simulator() -> CanvasSimulator {
+    form_simulator(with_detections(vec![region("Text Region 1", 0.0, 0.0, 100.0, 20.0)]), 400, 300)
+}
+
+/// Canvas position of an image pixel
+fn canvas_pos(sim: &CanvasSimulator, x: f32, y: f32) -> Pos2 {
+    sim.canvas()
+        .coordinate_mapper()
+        .image_to_canvas(ImagePos(Pos2::new(x, y)))
+        .expect("Image loaded")
+        .0
+}
+
+#[test]
+fn detection_tooltip_shows_text_and_confidence() {
+    let mut sim = detected_simulator();
+    sim.canvas_mut().record_ocr_result(0, "ACME Corp\n", 91.6);
+    let pos = canvas_pos(&sim, 50.0, 10.0);
+
+    assert_eq!(
+        sim.canvas().hover_tooltip(pos),
+        Some(HoverTooltip {
+            title: "Text Region 1".to_string(),
+            lines: vec!["Text: ACME Corp".to_string(), "Confidence: 92%".to_string()],
+        })
+    );
+}
+
+#[test]
+fn detection_without_ocr_says_so() {
+    let sim = detected_simulator();
+    let pos = canvas_pos(&sim, 50.0, 10.0);
+
+    let tooltip = sim.canvas().hover_tooltip(pos).expect("Detection under pointer");
+
+    assert_eq!(tooltip.lines, ["Not read by OCR yet"]);
+}
+
+#[test]
+fn field_tooltip_shows_label_and_type() {
+    let mut sim = detected_simulator();
+    let min = canvas_pos(&sim, 200.0, 200.0);
+    let max = canvas_pos(&sim, 300.0, 250.0);
+    let mut field = region("total", min.x, min.y, max.x - min.x, max.y - min.y);
+    field.set_rule(FieldRule {
+        required: true,
+        format: FieldFormat::Number,
+    });
+    sim.canvas_mut().set_shapes(vec![field]);
+
+    let tooltip = sim.canvas().hover_tooltip(min.lerp(max, 0.5)).expect("Field under pointer");
+
+    assert_eq!(tooltip.title, "total");
+    assert_eq!(tooltip.lines, ["Type: Number (required)"]);
+}
+
+#[test]
+fn empty_canvas_has_no_tooltip() {
+    let sim = detected_simulator();
+
+    assert_eq!(sim.canvas().hover_tooltip(canvas_pos(&sim, 350.0, 50.0)), None);
+}
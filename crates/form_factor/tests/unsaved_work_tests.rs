@@ -0,0 +1,76 @@
+//! Integration tests for finding unsaved work before it is lost
+
+mod common;
+
+use common::field;
+use egui::Pos2;
+use form_factor::{BatchReview, DrawingCanvas, UnsavedWork};
+
+/// Canvas reviewing two instances, the second of them changed by review
+fn reviewing() -> DrawingCanvas {
+    let mut review = BatchReview::default();
+    review.add_instance("jane", None, DrawingCanvas::new());
+    review.add_instance("john", None, DrawingCanvas::new());
+    review.select_all_instances();
+    let mut current = DrawingCanvas::new();
+    current.set_template_name(Some("W-2 (2024)".to_string()));
+    review.add_instance("june", None, current);
+    review.reassign_selected(Some("W-2 (2024)"));
+
+    let mut canvas = DrawingCanvas::new();
+    canvas.start_batch_review(review);
+    canvas
+}
+
+// ============================================================================
+// Finding unsaved work
+// ============================================================================
+
+#[test]
+fn a_blank_canvas_has_nothing_to_lose() {
+    let canvas = DrawingCanvas::new();
+
+    assert!(UnsavedWork::of_canvas(&canvas).is_empty());
+}
+
+#[test]
+fn project_changes_are_unsaved_work() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![field("name", Pos2::ZERO)]);
+
+    let unsaved = UnsavedWork::of_canvas(&canvas);
+
+    assert!(unsaved.project);
+    assert_eq!(unsaved.reviewed_instances, 0);
+    assert!(UnsavedWork::of_review(&canvas).is_empty());
+}
+
+#[test]
+fn reviewed_instances_are_unsaved_work() {
+    let canvas = reviewing();
+
+    assert_eq!(UnsavedWork::of_review(&canvas).reviewed_instances, 2);
+    assert!(UnsavedWork::of_project(&canvas).is_empty());
+}
+
+// ============================================================================
+// Prompts
+// ============================================================================
+
+#[test]
+fn the_question_names_everything_that_would_be_lost() {
+    let project = UnsavedWork {
+        project: true,
+        reviewed_instances: 0,
+    };
+    let both = UnsavedWork {
+        project: true,
+        reviewed_instances: 3,
+    };
+
+    assert_eq!(project.question("W-2", "quitting"), "Save changes to W-2 before quitting?");
+    assert_eq!(
+        both.question("W-2", "quitting"),
+        "Save changes to W-2 and 3 reviewed instance(s) before quitting?"
+    );
+}
@@ -0,0 +1,133 @@
+//! Integration tests for validation reports across filled forms
+
+mod common;
+
+use common::extracted_field;
+use form_factor::{
+    BatchReview, DrawingCanvas, FieldCondition, FieldFormat, FieldRule, ReportFormat, Severity,
+    ValidationReport,
+};
+use std::path::{Path, PathBuf};
+
+/// A filled form with the given wages and spouse name
+fn instance(wages: &str, confidence: f32, married: &str, spouse: &str) -> DrawingCanvas {
+    let mut spouse_name = extracted_field("spouse_name", FieldRule::default(), spouse, None);
+    spouse_name.set_condition(Some(FieldCondition::Checked("married".to_string())));
+
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![
+        extracted_field(
+            "wages",
+            FieldRule::new(FieldFormat::Number).required(),
+            wages,
+            Some(confidence),
+        ),
+        extracted_field("married", FieldRule::new(FieldFormat::Checkbox), married, None),
+        spouse_name,
+    ]);
+    canvas
+}
+
+fn report() -> ValidationReport {
+    let mut report = ValidationReport::new(80.0);
+    report.add_instance("clean", &instance("52,000", 99.0, "x", "Sam"));
+    report.add_instance("smudged, \"copy\"", &instance("5z,000", 99.0, "", "Sam"));
+    report.add_instance("faint", &instance("48,250", 40.0, "", ""));
+    report
+}
+
+/// Fresh directory for written reports
+fn report_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("form_factor_report_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Creatable");
+    dir
+}
+
+// ============================================================================
+// Findings
+// ============================================================================
+
+#[test]
+fn findings_cover_errors_warnings_and_fields_that_do_not_apply() {
+    let report = report();
+
+    let findings: Vec<(usize, &str, Severity)> = report
+        .findings()
+        .iter()
+        .map(|finding| (finding.instance, finding.field.as_str(), finding.severity))
+        .collect();
+    assert_eq!(
+        findings,
+        [
+            (1, "wages", Severity::Error),
+            (1, "spouse_name", Severity::Warning),
+            (2, "wages", Severity::Warning),
+        ]
+    );
+    assert_eq!((report.error_count(), report.warning_count()), (1, 2));
+
+    let counts: Vec<(usize, usize, usize)> = report
+        .instances()
+        .iter()
+        .map(|instance| (instance.fields, instance.errors, instance.warnings))
+        .collect();
+    assert_eq!(counts, [(3, 0, 0), (2, 1, 1), (2, 0, 1)]);
+}
+
+#[test]
+fn report_from_a_review_uses_its_instances_and_threshold() {
+    let mut review = BatchReview::new(30.0);
+    review.add_instance("faint", None, instance("48,250", 40.0, "", ""));
+
+    let report = ValidationReport::from_review(&review);
+
+    assert_eq!(report.instances()[0].name, "faint");
+    assert!(report.findings().is_empty());
+}
+
+// ============================================================================
+// Output formats
+// ============================================================================
+
+#[test]
+fn csv_has_a_row_per_finding_with_quoting() {
+    let csv = report().to_csv();
+
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "instance,field,severity,message,value");
+    assert_eq!(lines.len(), 4);
+    assert_eq!(
+        lines[1],
+        "\"smudged, \"\"copy\"\"\",wages,Error,Expected a number,\"5z,000\""
+    );
+    assert_eq!(lines[3], "faint,wages,Warning,Low confidence (40%),\"48,250\"");
+}
+
+#[test]
+fn html_summarizes_instances_and_escapes_text() {
+    let html = report().to_html();
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("3 instance(s), 1 error(s), 2 warning(s)"));
+    assert!(html.contains("<h2>smudged, &quot;copy&quot;</h2>"));
+    // Instances without findings are in the summary only
+    assert!(!html.contains("<h2>clean</h2>"));
+}
+
+#[test]
+fn saving_picks_the_format_from_the_extension() {
+    let dir = report_dir("save");
+    let report = report();
+
+    report.save(&dir.join("report.csv")).expect("Saved");
+    report.save(&dir.join("report.HTML")).expect("Saved");
+
+    let csv = std::fs::read_to_string(dir.join("report.csv")).expect("Readable");
+    assert!(csv.starts_with("instance,field"));
+    let html = std::fs::read_to_string(dir.join("report.HTML")).expect("Readable");
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(report.save(&dir.join("report.pdf")).is_err());
+    assert_eq!(ReportFormat::from_path(Path::new("notes")), None);
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,193 @@
+//! Integration tests for the embeddable canvas widget
+//!
+//! Run with `cargo test -p form_factor --features plugins`.
+
+#![cfg(feature = "plugins")]
+
+mod common;
+
+use common::widget_frame;
+use form_factor::{
+    AppEvent, CanvasCommand, CanvasPanel, DrawingCanvas, FormFactorWidget, LayerType, Plugin, PluginContext,
+    apply_canvas_event, canvas_command,
+};
+
+// ============================================================================
+// Events in
+// ============================================================================
+
+#[test]
+fn canvas_events_are_applied_by_the_widget() {
+    let ctx = egui::Context::default();
+    let mut widget = FormFactorWidget::new(DrawingCanvas::new());
+
+    widget.send(AppEvent::CanvasZoomChanged { zoom: 2.5 });
+    widget.send(AppEvent::LayerVisibilityChanged {
+        layer_name: "Detections".to_string(),
+        visible: false,
+    });
+    widget_frame(&ctx, &mut widget);
+
+    assert_eq!(*widget.canvas().zoom_level(), 2.5);
+    assert!(!widget.canvas().layer_manager().is_visible(LayerType::Detections));
+    assert!(widget.take_events().is_empty());
+}
+
+#[test]
+fn apply_canvas_event_rejects_host_events() {
+    let mut canvas = DrawingCanvas::new();
+
+    assert!(apply_canvas_event(&mut canvas, &AppEvent::StatsPanelToggled));
+    assert!(!apply_canvas_event(&mut canvas, &AppEvent::OpenFileRequested));
+    assert!(!apply_canvas_event(&mut canvas, &AppEvent::SelectionCleared));
+}
+
+#[test]
+fn style_preset_events_choose_the_preset_for_new_shapes() {
+    let mut canvas = DrawingCanvas::new();
+
+    let selected = AppEvent::StylePresetSelected {
+        name: Some("Issue".to_string()),
+    };
+    assert!(apply_canvas_event(&mut canvas, &selected));
+    assert_eq!(canvas.style_preset().map(|preset| preset.name.as_str()), Some("Issue"));
+
+    assert!(apply_canvas_event(&mut canvas, &AppEvent::StylePresetSelected { name: None }));
+    assert!(canvas.style_preset().is_none());
+}
+
+// ============================================================================
+// Events out
+// ============================================================================
+
+#[test]
+fn host_events_are_passed_out_in_order() {
+    let ctx = egui::Context::default();
+    let mut widget = FormFactorWidget::new(DrawingCanvas::new()).with_sidebar(false);
+
+    widget.send(AppEvent::OpenFileRequested);
+    widget.send(AppEvent::CommentsPanelToggled);
+    widget.send(AppEvent::LoadImageRequested);
+    widget_frame(&ctx, &mut widget);
+
+    assert_eq!(
+        widget.take_events(),
+        vec![AppEvent::OpenFileRequested, AppEvent::LoadImageRequested]
+    );
+    assert!(widget.take_events().is_empty());
+}
+
+#[test]
+fn canvas_changes_are_broadcast_once_per_frame() {
+    let ctx = egui::Context::default();
+    let mut widget = FormFactorWidget::new(DrawingCanvas::new()).with_sidebar(false);
+
+    widget.send(AppEvent::CanvasZoomChanged { zoom: 2.0 });
+    widget.send(AppEvent::ToolSelected {
+        tool_name: "Circle".to_string(),
+    });
+    widget_frame(&ctx, &mut widget);
+    widget_frame(&ctx, &mut widget);
+
+    assert_eq!(
+        widget.take_events(),
+        vec![AppEvent::CanvasChanged {
+            zoom: 2.0,
+            pan_x: 0.0,
+            pan_y: 0.0,
+            tool: "Circle".to_string(),
+            visible_layers: vec![
+                "Canvas".to_string(),
+                "Highlights".to_string(),
+                "Detections".to_string(),
+                "Shapes".to_string(),
+            ],
+            selected_layer: None,
+        }]
+    );
+
+    // Nothing changed since, so nothing more is sent
+    widget_frame(&ctx, &mut widget);
+    assert!(widget.take_events().is_empty());
+}
+
+#[test]
+fn events_translate_to_canvas_commands() {
+    assert_eq!(
+        canvas_command(&AppEvent::LayerClearRequested {
+            layer_name: "Detections".to_string()
+        }),
+        Some(CanvasCommand::ClearLayer(LayerType::Detections))
+    );
+    assert_eq!(
+        canvas_command(&AppEvent::PrintRequested),
+        Some(CanvasCommand::TogglePanel(CanvasPanel::Print))
+    );
+    assert_eq!(
+        canvas_command(&AppEvent::ObjectVisibilityChanged {
+            layer_name: "Shapes".to_string(),
+            index: 2,
+            visible: false,
+        }),
+        Some(CanvasCommand::SetObjectHidden {
+            layer: LayerType::Shapes,
+            index: 2,
+            hidden: true,
+        })
+    );
+    assert_eq!(
+        canvas_command(&AppEvent::ObjectDeleteRequested {
+            layer_name: "Detections".to_string(),
+            index: 1,
+        }),
+        Some(CanvasCommand::DeleteObject {
+            layer: LayerType::Detections,
+            index: 1,
+        })
+    );
+    assert_eq!(
+        canvas_command(&AppEvent::ToolSelected {
+            tool_name: "Lasso".to_string()
+        }),
+        None
+    );
+}
+
+// ============================================================================
+// Plugins
+// ============================================================================
+
+/// Plugin that records the shape count it sees in its context
+struct ShapeCounter(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+impl Plugin for ShapeCounter {
+    fn name(&self) -> &str {
+        "shape-counter"
+    }
+
+    fn ui(&mut self, _ui: &mut egui::Ui, ctx: &PluginContext) {
+        self.0
+            .store(ctx.canvas.shapes().len(), std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn plugins_render_from_the_canvas_snapshot() {
+    let ctx = egui::Context::default();
+    let seen = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(usize::MAX));
+    let rect = form_factor::Rectangle::from_corners(
+        egui::Pos2::new(0.0, 0.0),
+        egui::Pos2::new(10.0, 10.0),
+        egui::Stroke::new(1.0, egui::Color32::BLACK),
+        egui::Color32::TRANSPARENT,
+    )
+    .expect("Valid rectangle");
+    let mut canvas = DrawingCanvas::new();
+    canvas.set_shapes(vec![form_factor::Shape::Rectangle(rect); 3]);
+    let mut widget = FormFactorWidget::new(canvas).with_plugin(Box::new(ShapeCounter(seen.clone())));
+
+    widget_frame(&ctx, &mut widget);
+
+    assert_eq!(seen.load(std::sync::atomic::Ordering::SeqCst), 3);
+    assert_eq!(widget.plugins().canvas_snapshot().shapes().len(), 3);
+}
@@ -0,0 +1,68 @@
+//! Integration tests for secondary windows
+
+use form_factor::{BackendConfig, SecondaryWindow};
+
+/// Run one headless frame showing the window; returns whether its contents ran
+fn frame(ctx: &egui::Context, window: &mut SecondaryWindow) -> bool {
+    let mut shown = false;
+    let _ = ctx.run(egui::RawInput::default(), |ctx| {
+        window.show(ctx, |ui| {
+            ui.label("Contents");
+            shown = true;
+        });
+    });
+    shown
+}
+
+// ============================================================================
+// Opening and closing
+// ============================================================================
+
+#[test]
+fn windows_start_closed_and_show_only_when_open() {
+    let ctx = egui::Context::default();
+    let mut window = SecondaryWindow::new("properties", "Properties");
+
+    assert!(!window.is_open());
+    assert!(!frame(&ctx, &mut window));
+
+    window.open();
+    assert!(frame(&ctx, &mut window));
+    assert!(window.is_open());
+
+    window.toggle();
+    assert!(!frame(&ctx, &mut window));
+}
+
+#[test]
+fn single_window_backends_fall_back_to_a_floating_window() {
+    let ctx = egui::Context::default();
+    let mut window = SecondaryWindow::new("entry", "Data Entry");
+    window.open();
+
+    assert!(frame(&ctx, &mut window));
+    // Native windows report their monitor's scale factor; the fallback has none
+    assert_eq!(window.native_pixels_per_point(), None);
+    assert!(window.is_open());
+}
+
+// ============================================================================
+// Per-window config
+// ============================================================================
+
+#[test]
+fn each_window_keeps_its_own_config() {
+    let window = SecondaryWindow::new("entry", "Data Entry").with_config(BackendConfig {
+        window_width: 320,
+        window_height: 480,
+        resizable: false,
+        ..Default::default()
+    });
+
+    let builder = window.config().viewport_builder(window.title());
+
+    assert_eq!(builder.inner_size, Some(egui::vec2(320.0, 480.0)));
+    assert_eq!(builder.resizable, Some(false));
+    assert_eq!(builder.title.as_deref(), Some("Data Entry"));
+    assert_eq!(SecondaryWindow::new("other", "Other").config().window_width, 1024);
+}
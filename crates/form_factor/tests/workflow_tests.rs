@@ -0,0 +1,75 @@
+//! Integration tests for page assignment workflow
+
+use form_factor::{DrawingCanvas, ProjectTemplate, TaskStatus, TemplatePage, Workflow};
+
+#[test]
+fn unknown_pages_are_unassigned() {
+    let workflow = Workflow::new();
+    assert_eq!(workflow.status(4), TaskStatus::Unassigned);
+    assert!(workflow.task(4).is_none());
+}
+
+#[test]
+fn assigning_starts_and_unassigning_resets_progress() {
+    let mut workflow = Workflow::new();
+
+    workflow.assign(0, Some("alice".to_string()));
+    assert_eq!(workflow.status(0), TaskStatus::InProgress);
+
+    workflow.assign(0, None);
+    assert_eq!(workflow.status(0), TaskStatus::Unassigned);
+}
+
+#[test]
+fn unassigning_keeps_finished_pages_done() {
+    let mut workflow = Workflow::new();
+    workflow.assign(0, Some("alice".to_string()));
+    workflow.set_status(0, TaskStatus::Done);
+
+    workflow.assign(0, None);
+
+    assert_eq!(workflow.status(0), TaskStatus::Done);
+}
+
+#[test]
+fn tasks_are_ordered_by_page_and_filterable() {
+    let mut workflow = Workflow::new();
+    workflow.assign(2, Some("bob".to_string()));
+    workflow.assign(0, Some("alice".to_string()));
+    workflow.assign(1, Some("bob".to_string()));
+
+    let pages: Vec<usize> = workflow.tasks().iter().map(|t| t.page).collect();
+    assert_eq!(pages, vec![0, 1, 2]);
+
+    let bob: Vec<usize> = workflow.tasks_for("bob").map(|t| t.page).collect();
+    assert_eq!(bob, vec![1, 2]);
+    assert_eq!(workflow.assignees(), vec!["alice", "bob"]);
+}
+
+#[test]
+fn workflow_is_saved_with_project() {
+    let mut canvas = DrawingCanvas::new();
+    canvas.workflow_mut().assign(0, Some("alice".to_string()));
+
+    let json = serde_json::to_string(&canvas).expect("Serialization should succeed");
+    let restored: DrawingCanvas = serde_json::from_str(&json).expect("Deserialization should succeed");
+
+    assert_eq!(restored.workflow(), canvas.workflow());
+}
+
+#[test]
+fn assignments_follow_the_applied_template_page() {
+    let template = ProjectTemplate::new("Packet")
+        .with_page(TemplatePage::new("Cover"))
+        .with_page(TemplatePage::new("Schedule"));
+
+    let mut canvas = DrawingCanvas::new();
+    assert_eq!(canvas.current_page(), 0);
+
+    assert!(canvas.apply_template_page(&template, 1));
+    assert_eq!(canvas.current_page(), 1);
+
+    let json = serde_json::to_string(&canvas).expect("Serialization should succeed");
+    let restored: DrawingCanvas = serde_json::from_str(&json).expect("Deserialization should succeed");
+    assert_eq!(restored.current_page(), 1);
+}
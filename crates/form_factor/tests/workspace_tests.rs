@@ -0,0 +1,131 @@
+//! Integration tests for workspace modes, their remembered arrangement and their hooks
+
+use form_factor::{BatchReview, DrawingCanvas, LayerType, ModeHooks, WorkspaceLayout, WorkspaceMemory, WorkspaceMode};
+use std::{cell::RefCell, rc::Rc};
+
+/// Remember the canvas's arrangement, with a sidebar, for the mode it is in
+fn remember(memory: &mut WorkspaceMemory, canvas: &DrawingCanvas, sidebar_width: f32) {
+    let layout = WorkspaceLayout::of_canvas(canvas).with_sidebar(sidebar_width, Default::default());
+    memory.remember(WorkspaceMode::of(canvas), layout);
+}
+
+// ============================================================================
+// Modes
+// ============================================================================
+
+#[test]
+fn mode_follows_data_entry_and_batch_review() {
+    let mut canvas = DrawingCanvas::new();
+    assert_eq!(WorkspaceMode::of(&canvas), WorkspaceMode::Layout);
+
+    canvas.open_data_entry_panel();
+    assert_eq!(WorkspaceMode::of(&canvas), WorkspaceMode::DataEntry);
+
+    canvas.start_batch_review(BatchReview::default());
+    assert_eq!(WorkspaceMode::of(&canvas), WorkspaceMode::BatchReview);
+}
+
+// ============================================================================
+// Restoring
+// ============================================================================
+
+#[test]
+fn leaving_data_entry_restores_the_layout_workspace() {
+    let mut canvas = DrawingCanvas::new();
+    let mut memory = WorkspaceMemory::new();
+    canvas.layer_manager_mut().set_visible(LayerType::Grid, false);
+    canvas.set_selected_layer(Some(LayerType::Shapes));
+    remember(&mut memory, &canvas, 340.0);
+
+    canvas.open_data_entry_panel();
+    assert_eq!(memory.switch(WorkspaceMode::of(&canvas)), None, "Data entry was not used before");
+    canvas.layer_manager_mut().set_visible(LayerType::Grid, true);
+    canvas.set_selected_layer(Some(LayerType::Detections));
+    remember(&mut memory, &canvas, 200.0);
+
+    canvas.toggle_data_entry_panel();
+    let layout = memory.switch(WorkspaceMode::of(&canvas)).cloned().expect("Layout workspace");
+    layout.apply_to_canvas(&mut canvas);
+
+    assert_eq!(layout.sidebar_width, Some(340.0));
+    assert!(!canvas.layer_manager().is_visible(LayerType::Grid));
+    assert_eq!(*canvas.selected_layer(), Some(LayerType::Shapes));
+    assert_eq!(memory.layout(WorkspaceMode::DataEntry).and_then(|l| l.sidebar_width), Some(200.0));
+}
+
+#[test]
+fn arrangement_seen_before_a_switch_is_noticed_is_not_kept() {
+    let mut canvas = DrawingCanvas::new();
+    let mut memory = WorkspaceMemory::new();
+    remember(&mut memory, &canvas, 280.0);
+
+    // The data entry window opened and changed the layers this frame
+    canvas.open_data_entry_panel();
+    canvas.set_selected_layer(Some(LayerType::Shapes));
+    remember(&mut memory, &canvas, 280.0);
+
+    assert_eq!(memory.mode(), WorkspaceMode::Layout);
+    assert_eq!(memory.layout(WorkspaceMode::Layout).map(|l| l.selected_layer), Some(None));
+}
+
+#[test]
+fn staying_in_a_mode_restores_nothing() {
+    let canvas = DrawingCanvas::new();
+    let mut memory = WorkspaceMemory::new();
+    remember(&mut memory, &canvas, 280.0);
+
+    assert_eq!(memory.switch(WorkspaceMode::Layout), None);
+}
+
+// ============================================================================
+// Hooks
+// ============================================================================
+
+/// Hooks that log which subsystem ran on entering or leaving layout and data entry
+fn logging_hooks(log: &Rc<RefCell<Vec<&'static str>>>) -> ModeHooks {
+    let mut hooks = ModeHooks::new();
+    for (subsystem, entry) in [("browser", "browser exit"), ("palette", "palette exit")] {
+        let log = log.clone();
+        hooks.on_exit(WorkspaceMode::Layout, subsystem, move |_| log.borrow_mut().push(entry));
+    }
+    let log_enter = log.clone();
+    hooks.on_enter(WorkspaceMode::DataEntry, "data entry", move |_| log_enter.borrow_mut().push("data entry enter"));
+    hooks
+}
+
+#[test]
+fn exit_hooks_run_before_enter_hooks_in_registration_order() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut hooks = logging_hooks(&log);
+
+    hooks.transition(WorkspaceMode::Layout, WorkspaceMode::DataEntry, &mut DrawingCanvas::new());
+
+    assert_eq!(*log.borrow(), vec!["browser exit", "palette exit", "data entry enter"]);
+}
+
+#[test]
+fn hooks_only_run_for_their_modes() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut hooks = logging_hooks(&log);
+    let mut canvas = DrawingCanvas::new();
+
+    hooks.transition(WorkspaceMode::Layout, WorkspaceMode::Layout, &mut canvas);
+    hooks.transition(WorkspaceMode::DataEntry, WorkspaceMode::BatchReview, &mut canvas);
+
+    assert!(log.borrow().is_empty());
+}
+
+#[test]
+fn hooks_tear_down_canvas_windows() {
+    let mut hooks = ModeHooks::new();
+    hooks.on_exit(WorkspaceMode::Layout, "template browser", DrawingCanvas::close_new_project_panel);
+    hooks.on_enter(WorkspaceMode::BatchReview, "data entry", DrawingCanvas::close_data_entry_panel);
+    let mut canvas = DrawingCanvas::new();
+    canvas.open_data_entry_panel();
+    canvas.start_batch_review(BatchReview::default());
+
+    hooks.transition(WorkspaceMode::DataEntry, WorkspaceMode::BatchReview, &mut canvas);
+
+    assert!(!*canvas.show_data_entry());
+    assert_eq!(WorkspaceMode::of(&canvas), WorkspaceMode::BatchReview);
+}
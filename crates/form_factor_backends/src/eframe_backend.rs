@@ -3,7 +3,36 @@
 //! This module provides a backend that uses eframe for window management
 //! and rendering. eframe is a high-level framework that handles the event
 //! loop and integrates with multiple rendering backends (glow, wgpu).
-
+//!
+//! On `wasm32` targets the backend runs the app inside the page's canvas
+//! element named by [`BackendConfig::canvas_id`] instead of opening a window.
+//!
+//! Natively, applications that already own a winit event loop can host the
+//! app through [`EmbeddedBackend::create`] and keep driving the loop:
+//!
+//! ```no_run
+//! use form_factor_backends::EframeBackend;
+//! use form_factor_core::{App, AppContext, BackendConfig, EmbeddedBackend};
+//! use winit::event_loop::EventLoop;
+//!
+//! struct MyApp;
+//!
+//! impl App for MyApp {
+//!     fn update(&mut self, _ctx: &AppContext) {}
+//! }
+//!
+//! let event_loop = EventLoop::<eframe::UserEvent>::with_user_event().build()?;
+//! let config = BackendConfig::default().with_frame_callback(|ctx| {
+//!     // Host bookkeeping after every frame
+//!     let _ = ctx.frame_count;
+//! });
+//! let mut handler = EframeBackend::create(Box::new(MyApp), config, &event_loop)?;
+//! event_loop.run_app(&mut handler)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+#[cfg(not(target_arch = "wasm32"))]
+use form_factor_core::EmbeddedBackend;
 use form_factor_core::{App, AppContext, Backend, BackendConfig};
 
 /// eframe-based backend implementation
@@ -12,28 +41,26 @@ pub struct EframeBackend;
 /// Wrapper that adapts our App trait to eframe's epi::App trait
 struct EframeApp {
     app: Box<dyn App>,
+    /// Frame callbacks are taken from here
+    config: BackendConfig,
     frame_count: u64,
-    last_frame_time: Option<std::time::Instant>,
 }
 
 impl EframeApp {
-    fn new(app: Box<dyn App>) -> Self {
+    fn new(app: Box<dyn App>, config: BackendConfig) -> Self {
         Self {
             app,
+            config,
             frame_count: 0,
-            last_frame_time: None,
         }
     }
 }
 
 impl eframe::App for EframeApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        let now = std::time::Instant::now();
-        let delta_time = self.last_frame_time
-            .map(|last| now.duration_since(last).as_secs_f32())
-            .unwrap_or(0.016); // Default to ~60fps for first frame
-
-        self.last_frame_time = Some(now);
+        // egui measures frame time itself, which also works in the browser
+        // where std::time::Instant is unavailable
+        let delta_time = ctx.input(|i| i.unstable_dt);
 
         let app_ctx = AppContext {
             egui_ctx: ctx,
@@ -42,6 +69,7 @@ impl eframe::App for EframeApp {
         };
 
         self.app.update(&app_ctx);
+        self.config.run_frame_callbacks(&app_ctx);
         self.frame_count += 1;
     }
 
@@ -54,33 +82,31 @@ impl eframe::App for EframeApp {
 #[derive(Debug, thiserror::Error)]
 pub enum EframeError {
     /// Failed to initialize the eframe backend
+    #[cfg(not(target_arch = "wasm32"))]
     #[error("Failed to initialize eframe: {0}")]
     InitError(#[from] eframe::Error),
+
+    /// The page has no canvas element with the configured id
+    #[cfg(target_arch = "wasm32")]
+    #[error("No canvas element with id '{0}' in the page")]
+    CanvasNotFound(String),
 }
 
 impl Backend for EframeBackend {
     type Error = EframeError;
 
+    #[cfg(not(target_arch = "wasm32"))]
     fn run(mut app: Box<dyn App>, config: BackendConfig) -> Result<(), Self::Error> {
         // Get the app name before moving it
         let app_name = app.name().to_string();
 
-        let native_options = eframe::NativeOptions {
-            viewport: egui::ViewportBuilder::default()
-                .with_inner_size([config.window_width as f32, config.window_height as f32])
-                .with_resizable(config.resizable)
-                .with_title(&app_name),
-            vsync: config.vsync,
-            multisampling: config.msaa_samples as u16,
-            renderer: eframe::Renderer::Wgpu,
-            ..Default::default()
-        };
+        let native_options = native_options(&app_name, &config);
 
         // Call setup before starting the event loop
         let ctx = egui::Context::default();
         app.setup(&ctx);
 
-        let eframe_app = EframeApp::new(app);
+        let eframe_app = EframeApp::new(app, config);
 
         eframe::run_native(
             &app_name,
@@ -90,4 +116,72 @@ impl Backend for EframeBackend {
 
         Ok(())
     }
+
+    #[cfg(target_arch = "wasm32")]
+    fn run(mut app: Box<dyn App>, config: BackendConfig) -> Result<(), Self::Error> {
+        use wasm_bindgen::JsCast;
+
+        let canvas = web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.get_element_by_id(&config.canvas_id))
+            .and_then(|element| element.dyn_into::<web_sys::HtmlCanvasElement>().ok())
+            .ok_or_else(|| EframeError::CanvasNotFound(config.canvas_id.clone()))?;
+
+        let ctx = egui::Context::default();
+        app.setup(&ctx);
+        let eframe_app = EframeApp::new(app, config);
+
+        // The browser drives the event loop, so start the runner and return
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(e) = eframe::WebRunner::new()
+                .start(
+                    canvas,
+                    eframe::WebOptions::default(),
+                    Box::new(|_cc| Ok(Box::new(eframe_app))),
+                )
+                .await
+            {
+                web_sys::console::error_1(&e);
+            }
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl EmbeddedBackend for EframeBackend {
+    type EventLoop = winit::event_loop::EventLoop<eframe::UserEvent>;
+    type Handler = eframe::EframeWinitApplication<'static>;
+
+    fn create(
+        mut app: Box<dyn App>,
+        config: BackendConfig,
+        event_loop: &Self::EventLoop,
+    ) -> Result<Self::Handler, Self::Error> {
+        let app_name = app.name().to_string();
+        let native_options = native_options(&app_name, &config);
+
+        Ok(eframe::create_native(
+            &app_name,
+            native_options,
+            Box::new(move |cc| {
+                app.setup(&cc.egui_ctx);
+                Ok(Box::new(EframeApp::new(app, config)))
+            }),
+            event_loop,
+        ))
+    }
+}
+
+/// Window options for a native run
+#[cfg(not(target_arch = "wasm32"))]
+fn native_options(app_name: &str, config: &BackendConfig) -> eframe::NativeOptions {
+    eframe::NativeOptions {
+        viewport: config.viewport_builder(app_name),
+        vsync: config.vsync,
+        multisampling: config.msaa_samples as u16,
+        renderer: eframe::Renderer::Wgpu,
+        ..Default::default()
+    }
 }
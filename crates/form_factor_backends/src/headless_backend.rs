@@ -0,0 +1,202 @@
+//! Headless backend for running apps without a display
+//!
+//! This module provides a backend that drives an app's egui context
+//! off-screen at the fixed resolution of [`BackendConfig::window_width`] by
+//! [`BackendConfig::window_height`]. Each frame is laid out and tessellated
+//! in software, so nothing needs a window, GPU or event loop. This suits
+//! integration tests and batch jobs such as exporting canvases on a server.
+//!
+//! [`HeadlessBackend::run`] runs frames until the app settles or asks to
+//! close. Callers that need to inspect the app between frames step a
+//! [`HeadlessRunner`] themselves:
+//!
+//! ```
+//! use form_factor_backends::HeadlessRunner;
+//! use form_factor_core::{App, AppContext, BackendConfig};
+//!
+//! struct MyApp;
+//!
+//! impl App for MyApp {
+//!     fn update(&mut self, ctx: &AppContext) {
+//!         egui::CentralPanel::default().show(ctx.egui_ctx, |ui| ui.label("Hello"));
+//!     }
+//! }
+//!
+//! let mut runner = HeadlessRunner::new(Box::new(MyApp), BackendConfig::default())?;
+//! let frame = runner.frame();
+//! assert!(!frame.primitives.is_empty());
+//! runner.finish();
+//! # Ok::<(), form_factor_backends::HeadlessError>(())
+//! ```
+
+use form_factor_core::{App, AppContext, Backend, BackendConfig};
+use std::time::Duration;
+
+/// Most frames [`HeadlessBackend::run`] runs before giving up on the app settling
+pub const HEADLESS_MAX_FRAMES: u64 = 600;
+
+/// Simulated time between frames, as on a 60 Hz display
+const FRAME_TIME: f32 = 1.0 / 60.0;
+
+/// Backend that runs apps off-screen without a display
+pub struct HeadlessBackend;
+
+/// Errors from the headless backend
+#[derive(Debug, thiserror::Error)]
+pub enum HeadlessError {
+    /// The configured resolution has no area to lay out into
+    #[error("Headless viewport must not be empty, got {width}x{height}")]
+    EmptyViewport {
+        /// Configured width in points
+        width: u32,
+        /// Configured height in points
+        height: u32,
+    },
+}
+
+/// Output of one headless frame
+pub struct HeadlessFrame {
+    /// Everything egui produced for the frame
+    pub output: egui::FullOutput,
+    /// The frame's shapes tessellated into meshes
+    pub primitives: Vec<egui::ClippedPrimitive>,
+}
+
+impl HeadlessFrame {
+    /// Whether the app asked for another frame straight away
+    pub fn repaint_requested(&self) -> bool {
+        self.output
+            .viewport_output
+            .get(&egui::ViewportId::ROOT)
+            .is_some_and(|viewport| viewport.repaint_delay == Duration::ZERO)
+    }
+
+    /// Whether the app asked to close its window
+    pub fn close_requested(&self) -> bool {
+        self.output
+            .viewport_output
+            .get(&egui::ViewportId::ROOT)
+            .is_some_and(|viewport| {
+                viewport
+                    .commands
+                    .iter()
+                    .any(|command| matches!(command, egui::ViewportCommand::Close))
+            })
+    }
+}
+
+/// App hosted off-screen, advanced one frame at a time
+pub struct HeadlessRunner {
+    app: Box<dyn App>,
+    /// Resolution and frame callbacks are taken from here
+    config: BackendConfig,
+    ctx: egui::Context,
+    frame_count: u64,
+    /// Input events delivered with the next frame
+    pending_events: Vec<egui::Event>,
+}
+
+impl HeadlessRunner {
+    /// Set up an app for headless frames at the configured resolution
+    pub fn new(mut app: Box<dyn App>, config: BackendConfig) -> Result<Self, HeadlessError> {
+        if config.window_width == 0 || config.window_height == 0 {
+            return Err(HeadlessError::EmptyViewport {
+                width: config.window_width,
+                height: config.window_height,
+            });
+        }
+
+        let ctx = egui::Context::default();
+        app.setup(&ctx);
+
+        Ok(Self {
+            app,
+            config,
+            ctx,
+            frame_count: 0,
+            pending_events: Vec::new(),
+        })
+    }
+
+    /// The egui context the app draws into
+    pub fn context(&self) -> &egui::Context {
+        &self.ctx
+    }
+
+    /// Number of frames run so far
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Area the app lays out into, in points
+    pub fn screen_rect(&self) -> egui::Rect {
+        egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(self.config.window_width as f32, self.config.window_height as f32),
+        )
+    }
+
+    /// Queue an input event, such as a key press, for the next frame
+    pub fn push_event(&mut self, event: egui::Event) {
+        self.pending_events.push(event);
+    }
+
+    /// Run the app for one frame
+    pub fn frame(&mut self) -> HeadlessFrame {
+        let raw_input = egui::RawInput {
+            screen_rect: Some(self.screen_rect()),
+            // Simulated time keeps animations and timeouts deterministic
+            time: Some(self.frame_count as f64 * f64::from(FRAME_TIME)),
+            predicted_dt: FRAME_TIME,
+            focused: true,
+            events: std::mem::take(&mut self.pending_events),
+            ..Default::default()
+        };
+
+        let (app, config, frame_count) = (&mut self.app, &self.config, self.frame_count);
+        let output = self.ctx.run(raw_input, |ctx| {
+            let app_ctx = AppContext {
+                egui_ctx: ctx,
+                delta_time: FRAME_TIME,
+                frame_count,
+            };
+            app.update(&app_ctx);
+            config.run_frame_callbacks(&app_ctx);
+        });
+        self.frame_count += 1;
+
+        let primitives = self.ctx.tessellate(output.shapes.clone(), output.pixels_per_point);
+        HeadlessFrame { output, primitives }
+    }
+
+    /// Run frames until the app stops asking for repaints or asks to close
+    ///
+    /// Runs at most `max_frames` frames and returns how many ran.
+    pub fn run_until_idle(&mut self, max_frames: u64) -> u64 {
+        let mut frames = 0;
+        while frames < max_frames {
+            let frame = self.frame();
+            frames += 1;
+            if frame.close_requested() || !frame.repaint_requested() {
+                break;
+            }
+        }
+        frames
+    }
+
+    /// Shut the app down
+    pub fn finish(mut self) {
+        self.app.on_exit();
+    }
+}
+
+impl Backend for HeadlessBackend {
+    type Error = HeadlessError;
+
+    fn run(app: Box<dyn App>, config: BackendConfig) -> Result<(), Self::Error> {
+        let mut runner = HeadlessRunner::new(app, config)?;
+        runner.run_until_idle(HEADLESS_MAX_FRAMES);
+        runner.finish();
+        Ok(())
+    }
+}
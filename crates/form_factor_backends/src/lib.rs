@@ -1,13 +1,15 @@
 //! Backend implementations for form_factor
 //!
 //! This crate provides the backend implementations (eframe, etc.)
-//! that implement the Backend trait from form_factor_core.
+//! that implement the Backend trait from form_factor_core. The headless
+//! backend needs no windowing support and is always available.
 
 #![warn(missing_docs)]
 #![forbid(unsafe_code)]
 
 #[cfg(feature = "eframe")]
 pub mod eframe_backend;
+pub mod headless_backend;
 
 // Miniquad backend - reference implementation for future use
 // Uncomment when egui-miniquad supports egui 0.33+
@@ -15,3 +17,4 @@ pub mod eframe_backend;
 
 #[cfg(feature = "eframe")]
 pub use eframe_backend::{EframeBackend, EframeError};
+pub use headless_backend::{HEADLESS_MAX_FRAMES, HeadlessBackend, HeadlessError, HeadlessFrame, HeadlessRunner};
@@ -0,0 +1,53 @@
+//! Named screen areas of UI elements
+//!
+//! Widgets that other parts of the application need to point at (e.g. the
+//! guided tour highlighting the toolbar) register their rectangle under a
+//! well-known name each frame. Consumers look the rectangle up by name
+//! without depending on the crate that drew the widget.
+
+use egui::{Context, Id, Rect};
+
+/// Anchor name of the drawing tool buttons
+pub const ANCHOR_TOOLBAR: &str = "toolbar";
+
+/// Anchor name of the drawing canvas
+pub const ANCHOR_CANVAS: &str = "canvas";
+
+/// Anchor name of the layer list
+pub const ANCHOR_LAYERS: &str = "layers";
+
+/// Anchor name of the detection buttons
+pub const ANCHOR_DETECTION: &str = "detection";
+
+/// Anchor name of the file operations panel
+pub const ANCHOR_FILE: &str = "file";
+
+/// Rectangle and the frame it was recorded in
+#[derive(Debug, Clone, Copy)]
+struct AnchorEntry {
+    rect: Rect,
+    frame: u64,
+}
+
+fn anchor_id(name: &str) -> Id {
+    Id::new(("form_factor_anchor", name))
+}
+
+/// Record the screen area of a named UI element for this frame
+pub fn register_anchor(ctx: &Context, name: &str, rect: Rect) {
+    let entry = AnchorEntry {
+        rect,
+        frame: ctx.cumulative_frame_nr(),
+    };
+    ctx.data_mut(|data| data.insert_temp(anchor_id(name), entry));
+}
+
+/// Screen area of a named UI element
+///
+/// Returns `None` if the element was not drawn in this or the previous
+/// frame, so hidden elements are not pointed at.
+pub fn anchor_rect(ctx: &Context, name: &str) -> Option<Rect> {
+    let entry: AnchorEntry = ctx.data(|data| data.get_temp(anchor_id(name)))?;
+    let frame = ctx.cumulative_frame_nr();
+    (entry.frame + 1 >= frame).then_some(entry.rect)
+}
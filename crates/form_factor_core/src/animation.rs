@@ -0,0 +1,250 @@
+//! Frame-rate independent animations for visual feedback
+//!
+//! Animations advance by the time elapsed between frames, taken from
+//! [`AppContext::delta_time`], rather than by a fixed step per frame. A flash
+//! therefore lasts as long at 30 Hz as at 144 Hz, with vsync on or off.
+//!
+//! - [`Tween`] moves a value from one end to the other over a duration
+//! - [`Animations`] keeps keyed tweens, such as one flash per new detection,
+//!   and keeps frames coming while any of them runs
+//! - [`approach`] eases a value toward a moving target, such as a minimap
+//!   viewport following the canvas
+
+use crate::AppContext;
+use egui::{Color32, Pos2, Rect, Vec2};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Value that can be interpolated between two ends
+pub trait Lerp: Copy {
+    /// Value a fraction `t` of the way from `self` to `to`
+    fn lerp(self, to: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Lerp for Vec2 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Lerp for Pos2 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        Pos2::lerp(&self, to, t)
+    }
+}
+
+impl Lerp for Rect {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        Rect::from_min_max(self.min.lerp(to.min, t), self.max.lerp(to.max, t))
+    }
+}
+
+impl Lerp for Color32 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        self.lerp_to_gamma(to, t)
+    }
+}
+
+/// Shape of an animation's progress over time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Easing {
+    /// Constant speed
+    #[default]
+    Linear,
+    /// Starts slow and speeds up
+    EaseIn,
+    /// Starts fast and slows down
+    EaseOut,
+    /// Slow at both ends
+    EaseInOut,
+    /// Rises to the far end halfway through and falls back, for flashes
+    Pulse,
+}
+
+impl Easing {
+    /// Eased fraction for a fraction `t` of the duration, clamped to 0..=1
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t * t,
+            Self::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Self::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Self::Pulse => (t * std::f32::consts::PI).sin(),
+        }
+    }
+}
+
+/// Value animated from one end to another over a fixed duration
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tween<T: Lerp> {
+    from: T,
+    to: T,
+    /// Length of the animation in seconds
+    duration: f32,
+    /// Seconds advanced so far
+    elapsed: f32,
+    easing: Easing,
+}
+
+impl<T: Lerp> Tween<T> {
+    /// Animate linearly from `from` to `to` over `duration` seconds
+    pub fn new(from: T, to: T, duration: f32) -> Self {
+        Self {
+            from,
+            to,
+            duration: duration.max(0.0),
+            elapsed: 0.0,
+            easing: Easing::Linear,
+        }
+    }
+
+    /// Use a different easing curve
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Move the animation on by `delta_time` seconds and return its value
+    pub fn advance(&mut self, delta_time: f32) -> T {
+        // Negative or NaN frame times would run the animation backwards
+        if delta_time > 0.0 {
+            self.elapsed = (self.elapsed + delta_time).min(self.duration);
+        }
+        self.value()
+    }
+
+    /// Move the animation on by the time since the last frame and return its value
+    pub fn tick(&mut self, ctx: &AppContext) -> T {
+        self.advance(ctx.delta_time)
+    }
+
+    /// Current value
+    pub fn value(&self) -> T {
+        self.from.lerp(self.to, self.easing.apply(self.progress()))
+    }
+
+    /// Fraction of the duration elapsed, from 0 to 1
+    pub fn progress(&self) -> f32 {
+        if self.duration > 0.0 {
+            self.elapsed / self.duration
+        } else {
+            1.0
+        }
+    }
+
+    /// Whether the whole duration has elapsed
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Value the animation ends at
+    pub fn target(&self) -> T {
+        self.to
+    }
+
+    /// Restart toward a new end from the current value, without a jump
+    pub fn retarget(&mut self, to: T, duration: f32) {
+        *self = Self::new(self.value(), to, duration).with_easing(self.easing);
+    }
+}
+
+/// Keyed set of running tweens
+///
+/// Finished tweens are dropped when advancing, after which [`Self::get`]
+/// returns `None` for their keys.
+#[derive(Debug, Clone)]
+pub struct Animations<K, T: Lerp> {
+    tweens: HashMap<K, Tween<T>>,
+}
+
+impl<K: Eq + Hash, T: Lerp> Animations<K, T> {
+    /// Create an empty set
+    pub fn new() -> Self {
+        Self { tweens: HashMap::new() }
+    }
+
+    /// Start a tween for `key`, replacing any running one
+    pub fn start(&mut self, key: K, tween: Tween<T>) {
+        self.tweens.insert(key, tween);
+    }
+
+    /// Current value of the tween for `key`, if one is running
+    pub fn get(&self, key: &K) -> Option<T> {
+        self.tweens.get(key).map(Tween::value)
+    }
+
+    /// Move every tween on by `delta_time` seconds and drop the finished ones
+    pub fn advance(&mut self, delta_time: f32) {
+        self.tweens.retain(|_, tween| {
+            tween.advance(delta_time);
+            !tween.is_finished()
+        });
+    }
+
+    /// Move every tween on by the time since the last frame
+    ///
+    /// Requests another frame while tweens remain, so animations keep
+    /// running when there is no input.
+    pub fn tick(&mut self, ctx: &AppContext) {
+        self.advance(ctx.delta_time);
+        if self.is_animating() {
+            ctx.egui_ctx.request_repaint();
+        }
+    }
+
+    /// Whether any tween is running
+    pub fn is_animating(&self) -> bool {
+        !self.tweens.is_empty()
+    }
+
+    /// Number of running tweens
+    pub fn len(&self) -> usize {
+        self.tweens.len()
+    }
+
+    /// Whether no tween is running
+    pub fn is_empty(&self) -> bool {
+        self.tweens.is_empty()
+    }
+
+    /// Stop the tween for `key`
+    pub fn remove(&mut self, key: &K) {
+        self.tweens.remove(key);
+    }
+
+    /// Stop every tween
+    pub fn clear(&mut self) {
+        self.tweens.clear();
+    }
+}
+
+impl<K: Eq + Hash, T: Lerp> Default for Animations<K, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Move `current` toward `target`, closing half the gap every `half_life` seconds
+///
+/// Unlike moving a fixed fraction per frame, the result after one second is
+/// the same however many frames the second is split into.
+pub fn approach<T: Lerp>(current: T, target: T, half_life: f32, delta_time: f32) -> T {
+    if half_life <= 0.0 {
+        return target;
+    }
+    let t = 1.0 - 0.5f32.powf(delta_time.max(0.0) / half_life);
+    current.lerp(target, t)
+}
@@ -3,15 +3,45 @@
 //! This module defines the trait that rendering backends must implement
 //! to run applications defined with the `App` trait.
 
-use crate::App;
+use crate::{App, AppContext};
+use std::sync::{Arc, Mutex};
+
+/// Callback a backend runs every frame, after the app has updated
+///
+/// Lets an embedding application observe or drive each frame without
+/// wrapping the [`App`]. Clones share the same closure.
+#[derive(Clone)]
+pub struct FrameCallback(Arc<Mutex<FrameFn>>);
+
+type FrameFn = dyn FnMut(&AppContext) + Send;
+
+impl FrameCallback {
+    /// Wrap a closure to run every frame
+    pub fn new(callback: impl FnMut(&AppContext) + Send + 'static) -> Self {
+        Self(Arc::new(Mutex::new(callback)))
+    }
+
+    /// Run the callback for one frame
+    pub fn call(&self, ctx: &AppContext) {
+        // A callback that panicked earlier is still safe to call again
+        let mut callback = self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        callback(ctx);
+    }
+}
+
+impl std::fmt::Debug for FrameCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FrameCallback")
+    }
+}
 
 /// Configuration options for initializing a backend
 #[derive(Debug, Clone)]
 pub struct BackendConfig {
-    /// Initial window width in pixels
+    /// Initial window width in points, which the monitor's scale factor turns into pixels
     pub window_width: u32,
 
-    /// Initial window height in pixels
+    /// Initial window height in points, which the monitor's scale factor turns into pixels
     pub window_height: u32,
 
     /// Whether the window is resizable
@@ -22,6 +52,35 @@ pub struct BackendConfig {
 
     /// MSAA sample count (1 = disabled, 2/4/8 = enabled)
     pub msaa_samples: u32,
+
+    /// Id of the HTML canvas element to draw into when running in a browser
+    pub canvas_id: String,
+
+    /// Callbacks run every frame after the app has updated, in order
+    pub frame_callbacks: Vec<FrameCallback>,
+}
+
+impl BackendConfig {
+    /// Window settings for a native window with the given title
+    pub fn viewport_builder(&self, title: &str) -> egui::ViewportBuilder {
+        egui::ViewportBuilder::default()
+            .with_inner_size([self.window_width as f32, self.window_height as f32])
+            .with_resizable(self.resizable)
+            .with_title(title)
+    }
+
+    /// Add a callback to run every frame after the app has updated
+    pub fn with_frame_callback(mut self, callback: impl FnMut(&AppContext) + Send + 'static) -> Self {
+        self.frame_callbacks.push(FrameCallback::new(callback));
+        self
+    }
+
+    /// Run every frame callback for one frame
+    pub fn run_frame_callbacks(&self, ctx: &AppContext) {
+        for callback in &self.frame_callbacks {
+            callback.call(ctx);
+        }
+    }
 }
 
 impl Default for BackendConfig {
@@ -32,6 +91,8 @@ impl Default for BackendConfig {
             resizable: true,
             vsync: true,
             msaa_samples: 1,
+            canvas_id: String::from("form_factor_canvas"),
+            frame_callbacks: Vec::new(),
         }
     }
 }
@@ -50,6 +111,26 @@ pub trait Backend {
     /// Runs the application with this backend.
     ///
     /// This method takes ownership of the app and starts the event loop.
-    /// It typically blocks until the application exits.
+    /// Native backends typically block until the application exits; in a
+    /// browser the loop is driven by the page, so this returns once the app
+    /// has been started.
     fn run(app: Box<dyn App>, config: BackendConfig) -> Result<(), Self::Error>;
 }
+
+/// Backend that can run inside an event loop owned by the embedding application.
+///
+/// [`Backend::run`] creates and drives its own event loop, which an
+/// application that already has one can't use. Instead, the embedder
+/// creates a handler for its event loop and keeps driving the loop itself,
+/// e.g. `event_loop.run_app(&mut handler)` with winit.
+pub trait EmbeddedBackend: Backend {
+    /// Event loop owned by the embedding application
+    type EventLoop;
+
+    /// Handler the embedding application passes its events to
+    type Handler;
+
+    /// Creates a handler that runs the app on an existing event loop.
+    fn create(app: Box<dyn App>, config: BackendConfig, event_loop: &Self::EventLoop)
+    -> Result<Self::Handler, Self::Error>;
+}
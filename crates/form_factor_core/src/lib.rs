@@ -6,10 +6,32 @@
 #![warn(missing_docs)]
 #![forbid(unsafe_code)]
 
+mod anchor;
+mod animation;
 mod app;
 mod backend;
 mod error;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod overlay;
+mod status_bar;
+mod trace;
+mod window;
 
+pub use anchor::{
+    ANCHOR_CANVAS, ANCHOR_DETECTION, ANCHOR_FILE, ANCHOR_LAYERS, ANCHOR_TOOLBAR, anchor_rect, register_anchor,
+};
+pub use animation::{Animations, Easing, Lerp, Tween, approach};
 pub use app::{App, AppContext};
-pub use backend::{Backend, BackendConfig};
+pub use backend::{Backend, BackendConfig, EmbeddedBackend, FrameCallback};
 pub use error::{IoError, IoOperation};
+#[cfg(feature = "metrics")]
+pub use metrics::{
+    DEFAULT_BUCKETS, DETECTION_LATENCY_SECONDS, DETECTIONS_TOTAL, FRAME_TIME_SECONDS, Histogram,
+    JsonExporter, MetricsEndpoint, MetricsExporter, MetricsRegistry, MetricsSnapshot,
+    OCR_CONFIDENCE, PrometheusExporter,
+};
+pub use overlay::{DismissPolicy, OverlayKind, OverlaySpec};
+pub use status_bar::StatusBar;
+pub use trace::{TRACE_ID_FIELD, TraceId};
+pub use window::SecondaryWindow;
@@ -0,0 +1,356 @@
+//! Counters and histograms for production monitoring
+//!
+//! A [`MetricsRegistry`] collects named counters and bucketed histograms.
+//! The process-wide registry returned by [`MetricsRegistry::global`] is
+//! pre-populated with the standard form_factor metrics (detection latency,
+//! OCR confidence, frame time).
+//!
+//! Exporters turn a [`MetricsSnapshot`] into text. [`PrometheusExporter`]
+//! produces the Prometheus text exposition format and [`JsonExporter`]
+//! produces a JSON dump; custom formats implement [`MetricsExporter`].
+//! Snapshots can be written to disk with [`MetricsRegistry::dump`] or served
+//! over HTTP with [`MetricsEndpoint`].
+
+use crate::{IoError, IoOperation};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Time taken by a text or logo detection pass, in seconds
+pub const DETECTION_LATENCY_SECONDS: &str = "form_factor_detection_latency_seconds";
+
+/// Number of regions found by detection passes
+pub const DETECTIONS_TOTAL: &str = "form_factor_detections_total";
+
+/// Confidence of individual OCR results (0-100)
+pub const OCR_CONFIDENCE: &str = "form_factor_ocr_confidence";
+
+/// Time between rendered frames, in seconds
+pub const FRAME_TIME_SECONDS: &str = "form_factor_frame_time_seconds";
+
+/// Default histogram bucket bounds, suited to latencies in seconds
+pub const DEFAULT_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Bucket bounds for OCR confidence percentages
+const CONFIDENCE_BUCKETS: &[f64] = &[10.0, 20.0, 30.0, 40.0, 50.0, 60.0, 70.0, 80.0, 90.0, 100.0];
+
+/// Bucket bounds for frame times, centered on common refresh rates
+const FRAME_BUCKETS: &[f64] = &[0.004, 0.008, 0.0167, 0.033, 0.05, 0.1, 0.25, 1.0];
+
+/// Longest the metrics endpoint waits on a client to send its request or take the response
+const CLIENT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Distribution of observed values over fixed buckets
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Histogram {
+    /// Inclusive upper bound of each bucket, ascending
+    bounds: Vec<f64>,
+    /// Number of observations in each bucket (not cumulative)
+    counts: Vec<u64>,
+    /// Total number of observations, including those above the last bound
+    count: u64,
+    /// Sum of all observed values
+    sum: f64,
+}
+
+impl Histogram {
+    /// Create an empty histogram with the given bucket bounds
+    ///
+    /// Bounds are sorted and deduplicated; non-finite bounds are dropped.
+    pub fn new(bounds: &[f64]) -> Self {
+        let mut bounds: Vec<f64> = bounds.iter().copied().filter(|b| b.is_finite()).collect();
+        bounds.sort_by(f64::total_cmp);
+        bounds.dedup();
+        let counts = vec![0; bounds.len()];
+        Self {
+            bounds,
+            counts,
+            count: 0,
+            sum: 0.0,
+        }
+    }
+
+    /// Record one observation
+    ///
+    /// Non-finite values are ignored.
+    pub fn observe(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        if let Some(idx) = self.bounds.iter().position(|&bound| value <= bound) {
+            self.counts[idx] += 1;
+        }
+        self.count += 1;
+        self.sum += value;
+    }
+
+    /// Bucket upper bounds
+    pub fn bounds(&self) -> &[f64] {
+        &self.bounds
+    }
+
+    /// Per-bucket observation counts
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// Total number of observations
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Sum of all observations
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// Mean of all observations, or `None` when empty
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+
+    /// Cumulative counts per bucket, as used by Prometheus
+    pub fn cumulative_counts(&self) -> Vec<u64> {
+        self.counts
+            .iter()
+            .scan(0, |total, &c| {
+                *total += c;
+                Some(*total)
+            })
+            .collect()
+    }
+}
+
+/// Point-in-time copy of all metrics in a registry
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    /// Counter values by name
+    pub counters: BTreeMap<String, u64>,
+    /// Histograms by name
+    pub histograms: BTreeMap<String, Histogram>,
+}
+
+/// Thread-safe collection of named counters and histograms
+#[derive(Debug, Default)]
+pub struct MetricsRegistry {
+    metrics: Mutex<MetricsSnapshot>,
+}
+
+impl MetricsRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The process-wide registry with the standard metrics registered
+    pub fn global() -> &'static MetricsRegistry {
+        static GLOBAL: OnceLock<MetricsRegistry> = OnceLock::new();
+        GLOBAL.get_or_init(|| {
+            let registry = MetricsRegistry::new();
+            registry.register_histogram(DETECTION_LATENCY_SECONDS, DEFAULT_BUCKETS);
+            registry.register_histogram(OCR_CONFIDENCE, CONFIDENCE_BUCKETS);
+            registry.register_histogram(FRAME_TIME_SECONDS, FRAME_BUCKETS);
+            registry.increment(DETECTIONS_TOTAL, 0);
+            registry
+        })
+    }
+
+    /// Register a histogram with custom bucket bounds
+    ///
+    /// Replaces any existing histogram of the same name.
+    pub fn register_histogram(&self, name: &str, bounds: &[f64]) {
+        self.with_metrics(|m| {
+            m.histograms.insert(name.to_string(), Histogram::new(bounds));
+        });
+    }
+
+    /// Add to a counter, creating it at zero if needed
+    pub fn increment(&self, name: &str, by: u64) {
+        self.with_metrics(|m| {
+            let counter = m.counters.entry(name.to_string()).or_default();
+            *counter = counter.saturating_add(by);
+        });
+    }
+
+    /// Record a histogram observation
+    ///
+    /// Unregistered histograms are created with [`DEFAULT_BUCKETS`].
+    pub fn observe(&self, name: &str, value: f64) {
+        self.with_metrics(|m| {
+            m.histograms
+                .entry(name.to_string())
+                .or_insert_with(|| Histogram::new(DEFAULT_BUCKETS))
+                .observe(value);
+        });
+    }
+
+    /// Current value of a counter
+    pub fn counter(&self, name: &str) -> Option<u64> {
+        self.with_metrics(|m| m.counters.get(name).copied())
+    }
+
+    /// Copy of a histogram
+    pub fn histogram(&self, name: &str) -> Option<Histogram> {
+        self.with_metrics(|m| m.histograms.get(name).cloned())
+    }
+
+    /// Copy all current metrics
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        self.with_metrics(|m| m.clone())
+    }
+
+    /// Zero every counter and histogram, keeping histogram buckets
+    pub fn reset(&self) {
+        self.with_metrics(|m| {
+            m.counters.values_mut().for_each(|c| *c = 0);
+            m.histograms
+                .values_mut()
+                .for_each(|h| *h = Histogram::new(&h.bounds));
+        });
+    }
+
+    /// Render the current metrics with an exporter
+    pub fn export(&self, exporter: &dyn MetricsExporter) -> String {
+        exporter.render(&self.snapshot())
+    }
+
+    /// Write the current metrics to a file with an exporter
+    pub fn dump(&self, exporter: &dyn MetricsExporter, path: &str) -> Result<(), IoError> {
+        std::fs::write(path, self.export(exporter))
+            .map_err(|e| IoError::new(e.to_string(), path, IoOperation::Write, line!(), file!()))
+    }
+
+    fn with_metrics<T>(&self, f: impl FnOnce(&mut MetricsSnapshot) -> T) -> T {
+        // Metrics must never take the application down, so recover from poisoning
+        let mut metrics = self.metrics.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut metrics)
+    }
+}
+
+/// Output format for a metrics snapshot
+pub trait MetricsExporter: Send + Sync {
+    /// HTTP content type of the rendered output
+    fn content_type(&self) -> &'static str;
+
+    /// Render a snapshot as text
+    fn render(&self, snapshot: &MetricsSnapshot) -> String;
+}
+
+/// Prometheus text exposition format (version 0.0.4)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PrometheusExporter;
+
+impl MetricsExporter for PrometheusExporter {
+    fn content_type(&self) -> &'static str {
+        "text/plain; version=0.0.4"
+    }
+
+    fn render(&self, snapshot: &MetricsSnapshot) -> String {
+        let mut out = String::new();
+        // Writing to a String cannot fail
+        for (name, value) in &snapshot.counters {
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            let _ = writeln!(out, "{} {}", name, value);
+        }
+        for (name, histogram) in &snapshot.histograms {
+            let _ = writeln!(out, "# TYPE {} histogram", name);
+            for (bound, count) in histogram.bounds().iter().zip(histogram.cumulative_counts()) {
+                let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, count);
+            }
+            let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, histogram.count());
+            let _ = writeln!(out, "{}_sum {}", name, histogram.sum());
+            let _ = writeln!(out, "{}_count {}", name, histogram.count());
+        }
+        out
+    }
+}
+
+/// Pretty-printed JSON dump of the snapshot
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonExporter;
+
+impl MetricsExporter for JsonExporter {
+    fn content_type(&self) -> &'static str {
+        "application/json"
+    }
+
+    fn render(&self, snapshot: &MetricsSnapshot) -> String {
+        // Snapshots hold only maps, numbers and strings, which always serialize
+        serde_json::to_string_pretty(snapshot).unwrap_or_default()
+    }
+}
+
+/// Minimal HTTP endpoint that serves a registry for scraping
+///
+/// Every request, regardless of path, receives the current metrics. The
+/// server runs on a background thread for the lifetime of the process.
+/// Each client is answered on a thread of its own, which gives up on a
+/// client that stalls for more than five seconds.
+#[derive(Debug)]
+pub struct MetricsEndpoint {
+    addr: SocketAddr,
+}
+
+impl MetricsEndpoint {
+    /// Bind to an address and start serving
+    ///
+    /// Use port 0 to let the OS choose; [`MetricsEndpoint::local_addr`]
+    /// reports the bound address.
+    pub fn serve(
+        addr: &str,
+        registry: &'static MetricsRegistry,
+        exporter: Box<dyn MetricsExporter>,
+    ) -> Result<Self, IoError> {
+        let exporter: Arc<dyn MetricsExporter> = Arc::from(exporter);
+        let listener = TcpListener::bind(addr)
+            .map_err(|e| IoError::new(e.to_string(), addr, IoOperation::Open, line!(), file!()))?;
+        let local = listener
+            .local_addr()
+            .map_err(|e| IoError::new(e.to_string(), addr, IoOperation::Open, line!(), file!()))?;
+
+        std::thread::Builder::new()
+            .name("metrics-endpoint".to_string())
+            .spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let exporter = Arc::clone(&exporter);
+                    // A misbehaving client only loses its own response
+                    let _ = std::thread::Builder::new()
+                        .name("metrics-client".to_string())
+                        .spawn(move || Self::respond(stream, registry, exporter.as_ref()));
+                }
+            })
+            .map_err(|e| IoError::new(e.to_string(), addr, IoOperation::Create, line!(), file!()))?;
+
+        Ok(Self { addr: local })
+    }
+
+    /// Address the endpoint is listening on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    fn respond(
+        mut stream: TcpStream,
+        registry: &MetricsRegistry,
+        exporter: &dyn MetricsExporter,
+    ) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+        // The request itself is irrelevant; read it so the client sees a clean close
+        let mut request = [0u8; 1024];
+        let _ = stream.read(&mut request)?;
+
+        let body = registry.export(exporter);
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            exporter.content_type(),
+            body.len(),
+            body
+        )?;
+        stream.flush()
+    }
+}
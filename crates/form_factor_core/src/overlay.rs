@@ -0,0 +1,88 @@
+//! Overlay descriptions shared by the application and its plugins
+//!
+//! An [`OverlaySpec`] tells the application's overlay manager how to present
+//! an overlay: as a window, a modal dialog or a side sheet, how high it
+//! stacks against other overlays, and how the user may dismiss it. Plugins
+//! return specs for the overlays they contribute and only draw the contents.
+
+/// How an overlay is presented
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum OverlayKind {
+    /// Movable window with a title bar
+    #[default]
+    Window,
+    /// Centered dialog that dims and blocks the rest of the application
+    Modal,
+    /// Full-height sheet along the right edge of the screen
+    SideSheet,
+}
+
+/// Ways the user can dismiss an overlay
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DismissPolicy {
+    /// Escape or the close button
+    #[default]
+    Escape,
+    /// Escape, the close button, or a click outside the overlay
+    ClickOutside,
+    /// Only the overlay itself, e.g. through its OK and Cancel buttons
+    Explicit,
+}
+
+impl DismissPolicy {
+    /// Whether Escape and the close button dismiss the overlay
+    pub fn allows_escape(self) -> bool {
+        !matches!(self, Self::Explicit)
+    }
+
+    /// Whether a click outside the overlay dismisses it
+    pub fn allows_click_outside(self) -> bool {
+        matches!(self, Self::ClickOutside)
+    }
+}
+
+/// Description of an overlay registered with the overlay manager
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OverlaySpec {
+    /// Unique overlay id, used to open and close it
+    pub id: String,
+    /// Title shown in the overlay's title bar or heading
+    pub title: String,
+    /// How the overlay is presented
+    pub kind: OverlayKind,
+    /// How the user may dismiss the overlay
+    pub dismiss: DismissPolicy,
+    /// Stacking order; overlays with a higher z-order always stay above lower ones
+    pub z_order: i32,
+}
+
+impl OverlaySpec {
+    /// Window overlay dismissed with Escape, at z-order 0
+    pub fn new(id: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            kind: OverlayKind::default(),
+            dismiss: DismissPolicy::default(),
+            z_order: 0,
+        }
+    }
+
+    /// Sets how the overlay is presented
+    pub fn with_kind(mut self, kind: OverlayKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Sets how the user may dismiss the overlay
+    pub fn with_dismiss(mut self, dismiss: DismissPolicy) -> Self {
+        self.dismiss = dismiss;
+        self
+    }
+
+    /// Sets the stacking order
+    pub fn with_z_order(mut self, z_order: i32) -> Self {
+        self.z_order = z_order;
+        self
+    }
+}
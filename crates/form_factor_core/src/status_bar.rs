@@ -0,0 +1,108 @@
+//! Status bar component
+//!
+//! A one-line bar along the bottom of the window summarizing the editor
+//! state: cursor position in image pixels, zoom, active tool and layer, and
+//! how many background tasks are running. Applications fill in whatever
+//! they know and add their own segments with [`StatusBar::with_item`].
+
+use egui::{Context, Pos2, Ui};
+
+/// Contents of the status bar for one frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusBar {
+    /// Cursor position in image pixel coordinates, if over the image
+    pub cursor: Option<Pos2>,
+
+    /// Zoom factor (1.0 = 100%)
+    pub zoom: f32,
+
+    /// Name of the active tool
+    pub tool: Option<String>,
+
+    /// Name of the active layer
+    pub layer: Option<String>,
+
+    /// Number of background tasks in progress
+    pub background_tasks: usize,
+
+    /// Extra application-specific segments, as (label, value) pairs
+    pub items: Vec<(String, String)>,
+}
+
+impl Default for StatusBar {
+    fn default() -> Self {
+        Self {
+            cursor: None,
+            zoom: 1.0,
+            tool: None,
+            layer: None,
+            background_tasks: 0,
+            items: Vec::new(),
+        }
+    }
+}
+
+impl StatusBar {
+    /// Create an empty status bar at 100% zoom
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of background tasks in progress
+    pub fn with_background_tasks(mut self, count: usize) -> Self {
+        self.background_tasks = count;
+        self
+    }
+
+    /// Append an application-specific segment
+    pub fn with_item(mut self, label: impl Into<String>, value: impl Into<String>) -> Self {
+        self.items.push((label.into(), value.into()));
+        self
+    }
+
+    /// Text of each segment, left to right
+    pub fn segments(&self) -> Vec<String> {
+        let mut segments = vec![
+            match self.cursor {
+                Some(pos) => format!("{:.0}, {:.0} px", pos.x, pos.y),
+                None => "-, - px".to_string(),
+            },
+            format!("{:.0}%", self.zoom * 100.0),
+        ];
+        if let Some(tool) = &self.tool {
+            segments.push(format!("Tool: {}", tool));
+        }
+        if let Some(layer) = &self.layer {
+            segments.push(format!("Layer: {}", layer));
+        }
+        segments.extend(self.items.iter().map(|(label, value)| format!("{}: {}", label, value)));
+        segments
+    }
+
+    /// Show the bar as a panel along the bottom of the window
+    pub fn show(&self, ctx: &Context) {
+        egui::TopBottomPanel::bottom("status_bar")
+            .exact_height(22.0)
+            .show(ctx, |ui| self.ui(ui));
+    }
+
+    /// Draw the bar's contents into an existing layout
+    pub fn ui(&self, ui: &mut Ui) {
+        ui.horizontal_centered(|ui| {
+            for (i, segment) in self.segments().into_iter().enumerate() {
+                if i > 0 {
+                    ui.separator();
+                }
+                ui.monospace(segment);
+            }
+
+            if self.background_tasks > 0 {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let noun = if self.background_tasks == 1 { "task" } else { "tasks" };
+                    ui.label(format!("{} {} running", self.background_tasks, noun));
+                    ui.spinner();
+                });
+            }
+        });
+    }
+}
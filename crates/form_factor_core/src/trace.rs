@@ -0,0 +1,39 @@
+//! Identifiers linking the log events of one request
+//!
+//! A request that passes through several components, such as a detection
+//! run that hops across the event bus and ends with shapes on the canvas,
+//! is given a [`TraceId`] recorded in the [`TRACE_ID_FIELD`] field of its
+//! outermost tracing span. Every log event inside that span, or inside a
+//! span linked to it on another thread or after an event bus hop, belongs
+//! to the request, so one ID finds all of them.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Name of the span field holding a [`TraceId`]
+pub const TRACE_ID_FIELD: &str = "trace_id";
+
+/// Next ID handed out by [`TraceId::next`]
+static NEXT_TRACE_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Identifier of one request, unique within the process
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TraceId(u64);
+
+impl TraceId {
+    /// A new ID, different from every earlier one
+    pub fn next() -> Self {
+        Self(NEXT_TRACE_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Numeric value of the ID
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for TraceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:08x}", self.0)
+    }
+}
@@ -0,0 +1,118 @@
+//! Secondary windows
+//!
+//! Apps open extra windows, such as a detached properties or data-entry
+//! window on a second monitor, through [`SecondaryWindow`]. Each window takes
+//! its own [`BackendConfig`] for its size and resizability.
+//!
+//! Backends that support several native windows (eframe on desktop) give it
+//! an OS window of its own, rendered at the scale factor of whichever monitor
+//! it is on. Sizes are in points, so a window keeps its apparent size when
+//! moved between monitors of different DPI. Where only one window is
+//! available, e.g. in the browser, it falls back to a floating window inside
+//! the main one.
+
+use crate::BackendConfig;
+use egui::{ViewportClass, ViewportId};
+
+/// Extra window shown alongside the main one
+#[derive(Debug, Clone)]
+pub struct SecondaryWindow {
+    id: ViewportId,
+    title: String,
+    config: BackendConfig,
+    open: bool,
+    /// Physical pixels per point of the monitor the window was last shown on
+    native_pixels_per_point: Option<f32>,
+}
+
+impl SecondaryWindow {
+    /// Closed window with the default config; `key` must be unique per window
+    pub fn new(key: &str, title: impl Into<String>) -> Self {
+        Self {
+            id: ViewportId::from_hash_of(key),
+            title: title.into(),
+            config: BackendConfig::default(),
+            open: false,
+            native_pixels_per_point: None,
+        }
+    }
+
+    /// Use the size and resizability of `config` for this window
+    pub fn with_config(mut self, config: BackendConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Window title
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Config the window opens with
+    pub fn config(&self) -> &BackendConfig {
+        &self.config
+    }
+
+    /// Whether the window is open
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Open the window on the next [`show`](Self::show)
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    /// Close the window
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    /// Open the window if closed, close it if open
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    /// Physical pixels per point of the monitor the window is on
+    ///
+    /// `None` until the window has been shown as a native window.
+    pub fn native_pixels_per_point(&self) -> Option<f32> {
+        self.native_pixels_per_point
+    }
+
+    /// Show the window with the given contents, if it is open
+    ///
+    /// Call once per frame from the main window's update. Closing the
+    /// window, natively or through the fallback's close button, closes it
+    /// here too.
+    pub fn show(&mut self, ctx: &egui::Context, mut add_contents: impl FnMut(&mut egui::Ui)) {
+        if !self.open {
+            return;
+        }
+
+        let title = self.title.clone();
+        let id = self.id;
+        let (open, pixels_per_point) =
+            ctx.show_viewport_immediate(id, self.config.viewport_builder(&title), |ctx, class| {
+                if class == ViewportClass::Embedded {
+                    let mut open = true;
+                    egui::Window::new(title.as_str())
+                        .id(egui::Id::new(id))
+                        .open(&mut open)
+                        .show(ctx, |ui| add_contents(ui));
+                    return (open, None);
+                }
+
+                egui::CentralPanel::default().show(ctx, |ui| add_contents(ui));
+                ctx.input(|i| {
+                    let viewport = i.viewport();
+                    (!viewport.close_requested(), viewport.native_pixels_per_point)
+                })
+            });
+
+        self.open = open;
+        if pixels_per_point.is_some() {
+            self.native_pixels_per_point = pixels_per_point;
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Crate-level error type
+//!
+//! `CvError` wraps the error of whichever detector failed, so callers that
+//! run several detectors can handle them through one type and still match on
+//! the specific kind.
+
+#[cfg(feature = "logo-detection")]
+use crate::LogoDetectionError;
+#[cfg(feature = "omr")]
+use crate::OmrError;
+#[cfg(feature = "text-detection")]
+use crate::TextDetectionError;
+
+/// Computer vision error
+///
+/// Wraps a boxed `CvErrorKind` to keep the error pointer-sized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CvError(Box<CvErrorKind>);
+
+impl CvError {
+    /// Create a new CvError from a CvErrorKind
+    pub fn new(kind: CvErrorKind) -> Self {
+        Self(Box::new(kind))
+    }
+
+    /// Get a reference to the underlying error kind
+    pub fn kind(&self) -> &CvErrorKind {
+        &self.0
+    }
+
+    /// Consume the error and return the underlying kind
+    pub fn into_kind(self) -> CvErrorKind {
+        *self.0
+    }
+}
+
+/// Categories of computer vision errors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CvErrorKind {
+    /// Text detection errors
+    #[cfg(feature = "text-detection")]
+    TextDetection(TextDetectionError),
+
+    /// Logo detection errors
+    #[cfg(feature = "logo-detection")]
+    LogoDetection(LogoDetectionError),
+
+    /// Bubble-sheet reading errors
+    #[cfg(feature = "omr")]
+    Omr(OmrError),
+}
+
+impl std::fmt::Display for CvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CV Error: {}", self.0)
+    }
+}
+
+impl std::fmt::Display for CvErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            #[cfg(feature = "text-detection")]
+            CvErrorKind::TextDetection(e) => write!(f, "{}", e),
+            #[cfg(feature = "logo-detection")]
+            CvErrorKind::LogoDetection(e) => write!(f, "{}", e),
+            #[cfg(feature = "omr")]
+            CvErrorKind::Omr(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for CvError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self.kind() {
+            #[cfg(feature = "text-detection")]
+            CvErrorKind::TextDetection(e) => Some(e),
+            #[cfg(feature = "logo-detection")]
+            CvErrorKind::LogoDetection(e) => Some(e),
+            #[cfg(feature = "omr")]
+            CvErrorKind::Omr(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(feature = "text-detection")]
+impl From<TextDetectionError> for CvError {
+    fn from(err: TextDetectionError) -> Self {
+        CvError::new(CvErrorKind::TextDetection(err))
+    }
+}
+
+#[cfg(feature = "logo-detection")]
+impl From<LogoDetectionError> for CvError {
+    fn from(err: LogoDetectionError) -> Self {
+        CvError::new(CvErrorKind::LogoDetection(err))
+    }
+}
+
+#[cfg(feature = "omr")]
+impl From<OmrError> for CvError {
+    fn from(err: OmrError) -> Self {
+        CvError::new(CvErrorKind::Omr(err))
+    }
+}
@@ -1,19 +1,35 @@
 //! Computer vision capabilities for form_factor
 //!
-//! This crate provides text detection and logo detection using OpenCV.
+//! This crate provides text detection, logo detection and bubble-sheet (OMR)
+//! reading using OpenCV.
 //! Heavy dependencies (opencv) are isolated here.
 
 #![warn(missing_docs)]
 #![forbid(unsafe_code)]
 
+#[cfg(any(feature = "text-detection", feature = "logo-detection", feature = "omr"))]
+mod error;
+
 #[cfg(feature = "text-detection")]
 mod text_detection;
 
 #[cfg(feature = "logo-detection")]
 mod logo_detection;
 
+#[cfg(feature = "omr")]
+mod omr;
+
+#[cfg(any(feature = "text-detection", feature = "logo-detection", feature = "omr"))]
+pub use error::{CvError, CvErrorKind};
+
 #[cfg(feature = "text-detection")]
 pub use text_detection::{TextDetectionError, TextDetectionErrorKind, TextDetector, TextRegion};
 
 #[cfg(feature = "logo-detection")]
-pub use logo_detection::{Logo, LogoDetectionMethod, LogoDetectionResult, LogoDetector, LogoLocation, LogoSize};
+pub use logo_detection::{
+    Logo, LogoDetectionError, LogoDetectionErrorKind, LogoDetectionMethod, LogoDetectionResult,
+    LogoDetector, LogoLocation, LogoSize,
+};
+
+#[cfg(feature = "omr")]
+pub use omr::{AnswerGrid, CHOICE_SEPARATOR, OmrAnswer, OmrDetector, OmrError, OmrErrorKind};
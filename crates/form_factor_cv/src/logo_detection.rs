@@ -43,6 +43,63 @@ use serde::{Deserialize, Serialize};
 use std::path::Path;
 use tracing::{debug, info, instrument, trace, warn};
 
+// ============================================================================
+// Error Types
+// ============================================================================
+
+/// Kinds of errors that can occur during logo detection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogoDetectionErrorKind {
+    /// Path is not valid UTF-8
+    InvalidPath(String),
+    /// Failed to load image file
+    ImageLoad(String),
+    /// Image is empty or corrupted
+    ImageEmpty(String),
+    /// Image conversion or resizing failed
+    ImageProcessing(String),
+    /// Template matching operation failed
+    Matching(String),
+}
+
+impl std::fmt::Display for LogoDetectionErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogoDetectionErrorKind::InvalidPath(path) => write!(f, "Invalid path encoding: {}", path),
+            LogoDetectionErrorKind::ImageLoad(msg) => write!(f, "Failed to load image: {}", msg),
+            LogoDetectionErrorKind::ImageEmpty(what) => write!(f, "{} is empty or invalid", what),
+            LogoDetectionErrorKind::ImageProcessing(msg) => write!(f, "Image processing failed: {}", msg),
+            LogoDetectionErrorKind::Matching(msg) => write!(f, "Template matching failed: {}", msg),
+        }
+    }
+}
+
+/// Logo detection error with location information
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogoDetectionError {
+    /// Error category
+    pub kind: LogoDetectionErrorKind,
+    /// Line number where error occurred
+    pub line: u32,
+    /// File where error occurred
+    pub file: &'static str,
+}
+
+impl LogoDetectionError {
+    /// Create a new logo detection error
+    pub fn new(kind: LogoDetectionErrorKind, line: u32, file: &'static str) -> Self {
+        Self { kind, line, file }
+    }
+}
+
+impl std::fmt::Display for LogoDetectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Logo Detection Error: {} at line {} in {}", self.kind, self.line, self.file)
+    }
+}
+
+impl std::error::Error for LogoDetectionError {}
+
 /// Method used for logo detection
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LogoDetectionMethod {
@@ -75,7 +132,7 @@ impl Logo {
     ///
     /// Returns an error if the image cannot be read or is invalid
     #[instrument(skip_all, fields(name, path))]
-    pub fn from_file(name: impl Into<String>, path: impl AsRef<Path>) -> Result<Self, String> {
+    pub fn from_file(name: impl Into<String>, path: impl AsRef<Path>) -> Result<Self, LogoDetectionError> {
         let name = name.into();
         let path = path.as_ref();
 
@@ -83,19 +140,35 @@ impl Logo {
 
         // Read the logo image
         let image = imgcodecs::imread(
-            path.to_str().ok_or("Invalid path encoding")?,
+            path_str(path)?,
             IMREAD_COLOR,
         )
-        .map_err(|e| format!("Failed to read logo image: {}", e))?;
+        .map_err(|e| {
+            LogoDetectionError::new(
+                LogoDetectionErrorKind::ImageLoad(format!("logo {:?}: {}", path, e)),
+                line!(),
+                file!(),
+            )
+        })?;
 
         if image.empty() {
-            return Err(format!("Logo image '{}' is empty or invalid", name));
+            return Err(LogoDetectionError::new(
+                LogoDetectionErrorKind::ImageEmpty(format!("Logo image '{}'", name)),
+                line!(),
+                file!(),
+            ));
         }
 
         // Convert to grayscale (cache for performance)
         let mut image_gray = Mat::default();
         imgproc::cvt_color(&image, &mut image_gray, imgproc::COLOR_BGR2GRAY, 0, core::AlgorithmHint::ALGO_HINT_DEFAULT)
-            .map_err(|e| format!("Failed to convert logo to grayscale: {}", e))?;
+            .map_err(|e| {
+                LogoDetectionError::new(
+                    LogoDetectionErrorKind::ImageProcessing(format!("convert logo to grayscale: {}", e)),
+                    line!(),
+                    file!(),
+                )
+            })?;
 
         info!(
             "Loaded logo '{}': {}x{} pixels",
@@ -117,19 +190,29 @@ impl Logo {
     ///
     /// Returns an error if the Mat is empty or grayscale conversion fails
     #[instrument(skip(image), fields(name))]
-    pub fn from_mat(name: impl Into<String>, image: Mat) -> Result<Self, String> {
+    pub fn from_mat(name: impl Into<String>, image: Mat) -> Result<Self, LogoDetectionError> {
         let name = name.into();
         let width = image.cols();
         let height = image.rows();
 
         if image.empty() {
-            return Err(format!("Logo image '{}' is empty", name));
+            return Err(LogoDetectionError::new(
+                LogoDetectionErrorKind::ImageEmpty(format!("Logo image '{}'", name)),
+                line!(),
+                file!(),
+            ));
         }
 
         // Convert to grayscale
         let mut image_gray = Mat::default();
         imgproc::cvt_color(&image, &mut image_gray, imgproc::COLOR_BGR2GRAY, 0, core::AlgorithmHint::ALGO_HINT_DEFAULT)
-            .map_err(|e| format!("Failed to convert logo to grayscale: {}", e))?;
+            .map_err(|e| {
+                LogoDetectionError::new(
+                    LogoDetectionErrorKind::ImageProcessing(format!("convert logo to grayscale: {}", e)),
+                    line!(),
+                    file!(),
+                )
+            })?;
 
         debug!("Created logo '{}' from Mat: {}x{}", name, width, height);
 
@@ -228,7 +311,7 @@ impl LogoDetector {
     ///
     /// Returns an error if the logo image cannot be loaded
     #[instrument(skip(self), fields(name, path))]
-    pub fn add_logo(&mut self, name: impl Into<String>, path: impl AsRef<Path>) -> Result<(), String> {
+    pub fn add_logo(&mut self, name: impl Into<String>, path: impl AsRef<Path>) -> Result<(), LogoDetectionError> {
         let logo = Logo::from_file(name, path)?;
         info!("Added logo '{}' to detector", logo.name);
         self.logos.push(logo);
@@ -240,7 +323,7 @@ impl LogoDetector {
     /// # Errors
     ///
     /// Returns an error if the Mat is invalid
-    pub fn add_logo_from_mat(&mut self, name: impl Into<String>, image: Mat) -> Result<(), String> {
+    pub fn add_logo_from_mat(&mut self, name: impl Into<String>, image: Mat) -> Result<(), LogoDetectionError> {
         let logo = Logo::from_mat(name, image)?;
         info!("Added logo '{}' to detector", logo.name);
         self.logos.push(logo);
@@ -283,18 +366,28 @@ impl LogoDetector {
     ///
     /// Returns an error if the image cannot be read or detection fails
     #[instrument(skip(self), fields(path, logos = self.logos.len()))]
-    pub fn detect_logos_from_path(&self, path: impl AsRef<Path>) -> Result<Vec<LogoDetectionResult>, String> {
+    pub fn detect_logos_from_path(&self, path: impl AsRef<Path>) -> Result<Vec<LogoDetectionResult>, LogoDetectionError> {
         let path = path.as_ref();
         debug!("Loading image from {:?}", path);
 
         let image = imgcodecs::imread(
-            path.to_str().ok_or("Invalid path encoding")?,
+            path_str(path)?,
             IMREAD_COLOR,
         )
-        .map_err(|e| format!("Failed to read input image: {}", e))?;
+        .map_err(|e| {
+            LogoDetectionError::new(
+                LogoDetectionErrorKind::ImageLoad(format!("input {:?}: {}", path, e)),
+                line!(),
+                file!(),
+            )
+        })?;
 
         if image.empty() {
-            return Err("Input image is empty or invalid".to_string());
+            return Err(LogoDetectionError::new(
+                LogoDetectionErrorKind::ImageEmpty("Input image".to_string()),
+                line!(),
+                file!(),
+            ));
         }
 
         self.detect_logos(&image)
@@ -306,9 +399,13 @@ impl LogoDetector {
     ///
     /// Returns an error if the image is invalid or detection fails
     #[instrument(skip(self, image), fields(width = image.cols(), height = image.rows(), logos = self.logos.len()))]
-    pub fn detect_logos(&self, image: &Mat) -> Result<Vec<LogoDetectionResult>, String> {
+    pub fn detect_logos(&self, image: &Mat) -> Result<Vec<LogoDetectionResult>, LogoDetectionError> {
         if image.empty() {
-            return Err("Input image is empty".to_string());
+            return Err(LogoDetectionError::new(
+                LogoDetectionErrorKind::ImageEmpty("Input image".to_string()),
+                line!(),
+                file!(),
+            ));
         }
 
         if self.logos.is_empty() {
@@ -327,7 +424,13 @@ impl LogoDetector {
         // Convert input image to grayscale once (optimization)
         let mut image_gray = Mat::default();
         imgproc::cvt_color(image, &mut image_gray, imgproc::COLOR_BGR2GRAY, 0, core::AlgorithmHint::ALGO_HINT_DEFAULT)
-            .map_err(|e| format!("Failed to convert image to grayscale: {}", e))?;
+            .map_err(|e| {
+                LogoDetectionError::new(
+                    LogoDetectionErrorKind::ImageProcessing(format!("convert image to grayscale: {}", e)),
+                    line!(),
+                    file!(),
+                )
+            })?;
 
         // Detect all logos
         let mut results = Vec::new();
@@ -353,7 +456,7 @@ impl LogoDetector {
 
     /// Detect a single logo in an image (all instances)
     #[instrument(skip(self, image_gray, logo), fields(logo_name = %logo.name))]
-    fn detect_logo(&self, image_gray: &Mat, logo: &Logo) -> Result<Vec<LogoDetectionResult>, String> {
+    fn detect_logo(&self, image_gray: &Mat, logo: &Logo) -> Result<Vec<LogoDetectionResult>, LogoDetectionError> {
         match self.method {
             LogoDetectionMethod::TemplateMatching => {
                 self.detect_logo_template_matching(image_gray, logo)
@@ -370,7 +473,7 @@ impl LogoDetector {
         &self,
         image_gray: &Mat,
         logo: &Logo,
-    ) -> Result<Vec<LogoDetectionResult>, String> {
+    ) -> Result<Vec<LogoDetectionResult>, LogoDetectionError> {
         let mut best_result: Option<LogoDetectionResult> = None;
 
         // Debug output for testing
@@ -404,7 +507,13 @@ impl LogoDetector {
                     scale,
                     imgproc::INTER_LINEAR,
                 )
-                .map_err(|e| format!("Failed to resize logo template: {}", e))?;
+                .map_err(|e| {
+                    LogoDetectionError::new(
+                        LogoDetectionErrorKind::ImageProcessing(format!("resize logo template: {}", e)),
+                        line!(),
+                        file!(),
+                    )
+                })?;
             }
 
             // Perform template matching
@@ -423,7 +532,13 @@ impl LogoDetector {
                 CV_32FC1,
                 core::Scalar::all(0.0),
             )
-            .map_err(|e| format!("Failed to create result matrix: {}", e))?;
+            .map_err(|e| {
+                LogoDetectionError::new(
+                    LogoDetectionErrorKind::Matching(format!("create result matrix: {}", e)),
+                    line!(),
+                    file!(),
+                )
+            })?;
 
             imgproc::match_template(
                 image_gray,
@@ -432,7 +547,13 @@ impl LogoDetector {
                 TM_CCOEFF_NORMED,
                 &core::no_array(),
             )
-            .map_err(|e| format!("Failed to perform template matching: {}", e))?;
+            .map_err(|e| {
+                LogoDetectionError::new(
+                    LogoDetectionErrorKind::Matching(format!("perform template matching: {}", e)),
+                    line!(),
+                    file!(),
+                )
+            })?;
 
             // Find the maximum value
             let mut min_val = 0.0;
@@ -448,7 +569,13 @@ impl LogoDetector {
                 Some(&mut max_loc),
                 &core::no_array(),
             )
-            .map_err(|e| format!("Failed to find maximum value: {}", e))?;
+            .map_err(|e| {
+                LogoDetectionError::new(
+                    LogoDetectionErrorKind::Matching(format!("find maximum value: {}", e)),
+                    line!(),
+                    file!(),
+                )
+            })?;
 
             trace!(
                 "Scale {:.2}: confidence = {:.4} at ({}, {})",
@@ -510,7 +637,7 @@ impl LogoDetector {
         &self,
         _image_gray: &Mat,
         logo: &Logo,
-    ) -> Result<Vec<LogoDetectionResult>, String> {
+    ) -> Result<Vec<LogoDetectionResult>, LogoDetectionError> {
         warn!(
             "Feature matching not yet implemented for logo '{}'",
             logo.name
@@ -519,6 +646,17 @@ impl LogoDetector {
     }
 }
 
+/// Convert a path to the UTF-8 string OpenCV expects
+fn path_str(path: &Path) -> Result<&str, LogoDetectionError> {
+    path.to_str().ok_or_else(|| {
+        LogoDetectionError::new(
+            LogoDetectionErrorKind::InvalidPath(path.to_string_lossy().into_owned()),
+            line!(),
+            file!(),
+        )
+    })
+}
+
 impl Default for LogoDetector {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,518 @@
+//! Bubble-sheet (OMR) answer detection
+//!
+//! This module reads answer sheets: surveys and test sheets where each
+//! question is a row of bubbles and the respondent fills in their choice.
+//! The bubbles are laid out by an [`AnswerGrid`], which a template defines
+//! in image pixels. Each bubble is scored by how much of it is dark, and the
+//! bubbles above the fill threshold become the question's answer.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use form_factor_cv::{AnswerGrid, OmrDetector};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // 20 questions, choices A-D, bubbles 24x18 pixels
+//! let grid = AnswerGrid::new(120, 340, 24, 18, 20, ["A", "B", "C", "D"])?
+//!     .with_spacing(40, 32)?;
+//!
+//! let detector = OmrDetector::new().with_fill_threshold(0.4)?;
+//! for answer in detector.detect_from_file("answers.png", &grid)? {
+//!     println!("Question {}: {}", answer.question() + 1, answer.value());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use derive_getters::Getters;
+use opencv::{
+    core::{self, Mat, Rect},
+    imgcodecs,
+    imgproc,
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tracing::{debug, instrument, trace};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Default share of a bubble that must be dark for it to count as filled
+const DEFAULT_FILL_THRESHOLD: f32 = 0.45;
+
+/// Default share of each bubble side ignored at the edges, so the printed
+/// outline doesn't count as ink
+const DEFAULT_INSET: f32 = 0.2;
+
+/// How much more filled the darkest bubble must be than the runner-up for a
+/// single-choice question to be read unambiguously
+const AMBIGUITY_MARGIN: f32 = 0.15;
+
+/// Separator between the labels of several marked choices in a field value
+pub const CHOICE_SEPARATOR: &str = ",";
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+/// Kinds of errors that can occur during bubble-sheet reading
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OmrErrorKind {
+    /// Failed to load image file
+    ImageLoad(String),
+    /// Image is empty or corrupted
+    ImageEmpty,
+    /// Grayscale conversion, thresholding or cropping failed
+    ImageProcessing(String),
+    /// Invalid grid or detector parameter
+    InvalidParameter(String),
+}
+
+impl std::fmt::Display for OmrErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OmrErrorKind::ImageLoad(msg) => write!(f, "Failed to load image: {}", msg),
+            OmrErrorKind::ImageEmpty => write!(f, "Image is empty"),
+            OmrErrorKind::ImageProcessing(msg) => write!(f, "Image processing failed: {}", msg),
+            OmrErrorKind::InvalidParameter(msg) => write!(f, "Invalid parameter: {}", msg),
+        }
+    }
+}
+
+/// Bubble-sheet reading error with location information
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OmrError {
+    /// Error category
+    pub kind: OmrErrorKind,
+    /// Line number where error occurred
+    pub line: u32,
+    /// File where error occurred
+    pub file: &'static str,
+}
+
+impl OmrError {
+    /// Create a new bubble-sheet reading error
+    pub fn new(kind: OmrErrorKind, line: u32, file: &'static str) -> Self {
+        Self { kind, line, file }
+    }
+}
+
+impl std::fmt::Display for OmrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "OMR Error: {} at line {} in {}", self.kind, self.line, self.file)
+    }
+}
+
+impl std::error::Error for OmrError {}
+
+// ============================================================================
+// Answer Grid
+// ============================================================================
+
+/// Layout of the bubbles on an answer sheet
+///
+/// Questions are rows and choices are columns: the bubble for question `q`
+/// and choice `c` has its top-left corner at
+/// `(x + c * column_spacing, y + q * row_spacing)`. Coordinates are pixels of
+/// the aligned form image.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Getters)]
+pub struct AnswerGrid {
+    /// X coordinate of the first bubble's top-left corner
+    x: i32,
+    /// Y coordinate of the first bubble's top-left corner
+    y: i32,
+    /// Width of one bubble in pixels
+    bubble_width: i32,
+    /// Height of one bubble in pixels
+    bubble_height: i32,
+    /// Distance between the left edges of neighbouring choices
+    column_spacing: i32,
+    /// Distance between the top edges of neighbouring questions
+    row_spacing: i32,
+    /// Number of questions (rows)
+    questions: usize,
+    /// Label of each choice, e.g. "A".."E" or "Agree".."Disagree"
+    choices: Vec<String>,
+    /// Whether a question may have several choices marked
+    #[serde(default)]
+    multiple: bool,
+}
+
+impl AnswerGrid {
+    /// Create a grid of touching bubbles
+    ///
+    /// Spacing defaults to the bubble size; use [`AnswerGrid::with_spacing`]
+    /// for the gaps on a real sheet.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if:
+    /// - Bubble width or height is not positive
+    /// - There are no questions or no choices
+    pub fn new<S: Into<String>>(
+        x: i32,
+        y: i32,
+        bubble_width: i32,
+        bubble_height: i32,
+        questions: usize,
+        choices: impl IntoIterator<Item = S>,
+    ) -> Result<Self, OmrError> {
+        if bubble_width <= 0 || bubble_height <= 0 {
+            return Err(OmrError::new(
+                OmrErrorKind::InvalidParameter(format!(
+                    "Bubble size must be positive, got {}x{}",
+                    bubble_width, bubble_height
+                )),
+                line!(),
+                file!(),
+            ));
+        }
+        let choices: Vec<String> = choices.into_iter().map(Into::into).collect();
+        if questions == 0 || choices.is_empty() {
+            return Err(OmrError::new(
+                OmrErrorKind::InvalidParameter(format!(
+                    "Grid needs at least one question and one choice, got {} and {}",
+                    questions,
+                    choices.len()
+                )),
+                line!(),
+                file!(),
+            ));
+        }
+
+        Ok(Self {
+            x,
+            y,
+            bubble_width,
+            bubble_height,
+            column_spacing: bubble_width,
+            row_spacing: bubble_height,
+            questions,
+            choices,
+            multiple: false,
+        })
+    }
+
+    /// Set the distance between neighbouring choices and questions
+    ///
+    /// # Errors
+    ///
+    /// Returns error if either spacing is smaller than the bubble, which
+    /// would make bubbles overlap.
+    pub fn with_spacing(mut self, column_spacing: i32, row_spacing: i32) -> Result<Self, OmrError> {
+        if column_spacing < self.bubble_width || row_spacing < self.bubble_height {
+            return Err(OmrError::new(
+                OmrErrorKind::InvalidParameter(format!(
+                    "Spacing {}x{} is smaller than the {}x{} bubbles",
+                    column_spacing, row_spacing, self.bubble_width, self.bubble_height
+                )),
+                line!(),
+                file!(),
+            ));
+        }
+        self.column_spacing = column_spacing;
+        self.row_spacing = row_spacing;
+        Ok(self)
+    }
+
+    /// Allow several choices per question, e.g. "select all that apply"
+    pub fn allow_multiple(mut self) -> Self {
+        self.multiple = true;
+        self
+    }
+
+    /// Bounds of the bubble for a question and choice, or `None` outside the grid
+    pub fn bubble(&self, question: usize, choice: usize) -> Option<Rect> {
+        if question >= self.questions || choice >= self.choices.len() {
+            return None;
+        }
+        Some(Rect::new(
+            self.x + choice as i32 * self.column_spacing,
+            self.y + question as i32 * self.row_spacing,
+            self.bubble_width,
+            self.bubble_height,
+        ))
+    }
+
+    /// Bounds of the whole grid
+    pub fn bounds(&self) -> Rect {
+        Rect::new(
+            self.x,
+            self.y,
+            (self.choices.len() as i32 - 1) * self.column_spacing + self.bubble_width,
+            (self.questions as i32 - 1) * self.row_spacing + self.bubble_height,
+        )
+    }
+}
+
+// ============================================================================
+// Answers
+// ============================================================================
+
+/// The answer read for one question
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Getters)]
+pub struct OmrAnswer {
+    /// Question index (row of the grid)
+    question: usize,
+    /// Share of each choice's bubble that is dark, between 0.0 and 1.0
+    fills: Vec<f32>,
+    /// Indices of the choices read as marked
+    selected: Vec<usize>,
+    /// Labels of the marked choices
+    labels: Vec<String>,
+    /// Whether a single-choice question had several bubbles filled about equally
+    ambiguous: bool,
+}
+
+impl OmrAnswer {
+    /// Read an answer from the fill of each choice's bubble
+    ///
+    /// Single-choice questions take the fullest bubble above the threshold.
+    /// If another bubble is nearly as full the answer is ambiguous and
+    /// nothing is selected, so a person reviews it instead of a guess being
+    /// recorded. Multiple-choice questions take every bubble above the
+    /// threshold.
+    pub fn from_fills(question: usize, fills: Vec<f32>, grid: &AnswerGrid, threshold: f32) -> Self {
+        let mut marked: Vec<usize> = (0..fills.len()).filter(|&i| fills[i] >= threshold).collect();
+        let mut ambiguous = false;
+
+        if !grid.multiple && marked.len() > 1 {
+            marked.sort_by(|&a, &b| fills[b].total_cmp(&fills[a]));
+            if fills[marked[0]] - fills[marked[1]] < AMBIGUITY_MARGIN {
+                ambiguous = true;
+                marked.clear();
+            } else {
+                marked.truncate(1);
+            }
+        }
+
+        let labels = marked.iter().filter_map(|&i| grid.choices.get(i).cloned()).collect();
+        Self {
+            question,
+            fills,
+            selected: marked,
+            labels,
+            ambiguous,
+        }
+    }
+
+    /// The answer as a choice field value
+    ///
+    /// The marked choice's label, the labels of several marked choices
+    /// joined by [`CHOICE_SEPARATOR`], or empty if nothing is marked.
+    pub fn value(&self) -> String {
+        self.labels.join(CHOICE_SEPARATOR)
+    }
+
+    /// Whether no choice was read as marked
+    pub fn is_blank(&self) -> bool {
+        self.selected.is_empty()
+    }
+}
+
+// ============================================================================
+// OMR Detector
+// ============================================================================
+
+/// Reads filled bubbles from an answer sheet
+#[derive(Debug, Clone)]
+pub struct OmrDetector {
+    fill_threshold: f32,
+    inset: f32,
+}
+
+impl OmrDetector {
+    /// Create a detector with the default fill threshold and inset
+    pub fn new() -> Self {
+        Self {
+            fill_threshold: DEFAULT_FILL_THRESHOLD,
+            inset: DEFAULT_INSET,
+        }
+    }
+
+    /// Set the share of a bubble (0.0-1.0) that must be dark for it to count as filled
+    ///
+    /// # Errors
+    ///
+    /// Returns error if threshold is not in range [0.0, 1.0]
+    pub fn with_fill_threshold(mut self, threshold: f32) -> Result<Self, OmrError> {
+        if !(0.0..=1.0).contains(&threshold) {
+            return Err(OmrError::new(
+                OmrErrorKind::InvalidParameter(format!(
+                    "Fill threshold must be between 0.0 and 1.0, got: {}",
+                    threshold
+                )),
+                line!(),
+                file!(),
+            ));
+        }
+        self.fill_threshold = threshold;
+        Ok(self)
+    }
+
+    /// Set the share of each bubble side (0.0-0.45) ignored at the edges
+    ///
+    /// # Errors
+    ///
+    /// Returns error if inset is not in range [0.0, 0.45]
+    pub fn with_inset(mut self, inset: f32) -> Result<Self, OmrError> {
+        if !(0.0..=0.45).contains(&inset) {
+            return Err(OmrError::new(
+                OmrErrorKind::InvalidParameter(format!("Inset must be between 0.0 and 0.45, got: {}", inset)),
+                line!(),
+                file!(),
+            ));
+        }
+        self.inset = inset;
+        Ok(self)
+    }
+
+    /// Get the fill threshold
+    pub fn fill_threshold(&self) -> f32 {
+        self.fill_threshold
+    }
+
+    /// Get the inset
+    pub fn inset(&self) -> f32 {
+        self.inset
+    }
+
+    /// Read the answers from an image file
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the image cannot be loaded or read
+    #[instrument(skip_all, fields(path = ?path.as_ref()))]
+    pub fn detect_from_file(&self, path: impl AsRef<Path>, grid: &AnswerGrid) -> Result<Vec<OmrAnswer>, OmrError> {
+        let path = path.as_ref();
+        let path_str = path.to_str().ok_or_else(|| {
+            OmrError::new(
+                OmrErrorKind::ImageLoad(format!("Invalid path encoding: {:?}", path)),
+                line!(),
+                file!(),
+            )
+        })?;
+
+        let image = imgcodecs::imread(path_str, imgcodecs::IMREAD_GRAYSCALE).map_err(|e| {
+            OmrError::new(OmrErrorKind::ImageLoad(format!("{}: {}", path_str, e)), line!(), file!())
+        })?;
+
+        self.detect(&image, grid)
+    }
+
+    /// Read the answers from an image, one per question of the grid
+    ///
+    /// The image is binarized with Otsu's method so ink counts as filled
+    /// regardless of scan brightness. Bubbles outside the image count as empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the image is empty or cannot be processed
+    #[instrument(skip(self, image, grid), fields(width = image.cols(), height = image.rows(), questions = grid.questions))]
+    pub fn detect(&self, image: &Mat, grid: &AnswerGrid) -> Result<Vec<OmrAnswer>, OmrError> {
+        if image.empty() {
+            return Err(OmrError::new(OmrErrorKind::ImageEmpty, line!(), file!()));
+        }
+
+        let binary = binarize(image)?;
+        let image_bounds = Rect::new(0, 0, binary.cols(), binary.rows());
+
+        let mut answers = Vec::with_capacity(grid.questions);
+        for question in 0..grid.questions {
+            let mut fills = Vec::with_capacity(grid.choices.len());
+            for choice in 0..grid.choices.len() {
+                let fill = match grid.bubble(question, choice) {
+                    Some(bubble) => fill_ratio(&binary, self.inset_rect(bubble) & image_bounds)?,
+                    None => 0.0,
+                };
+                trace!(question, choice, fill, "Scored bubble");
+                fills.push(fill);
+            }
+            answers.push(OmrAnswer::from_fills(question, fills, grid, self.fill_threshold));
+        }
+
+        debug!(
+            answered = answers.iter().filter(|a| !a.is_blank()).count(),
+            ambiguous = answers.iter().filter(|a| a.ambiguous).count(),
+            "Read answer sheet"
+        );
+        Ok(answers)
+    }
+
+    /// Shrink a bubble by the inset on each side
+    fn inset_rect(&self, bubble: Rect) -> Rect {
+        let dx = (bubble.width as f32 * self.inset) as i32;
+        let dy = (bubble.height as f32 * self.inset) as i32;
+        Rect::new(bubble.x + dx, bubble.y + dy, bubble.width - 2 * dx, bubble.height - 2 * dy)
+    }
+}
+
+impl Default for OmrDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Convert an image to grayscale and binarize it with ink as non-zero
+fn binarize(image: &Mat) -> Result<Mat, OmrError> {
+    let gray = if image.channels() == 1 {
+        image.clone()
+    } else {
+        let mut gray = Mat::default();
+        imgproc::cvt_color(image, &mut gray, imgproc::COLOR_BGR2GRAY, 0, core::AlgorithmHint::ALGO_HINT_DEFAULT)
+            .map_err(|e| {
+                OmrError::new(
+                    OmrErrorKind::ImageProcessing(format!("convert image to grayscale: {}", e)),
+                    line!(),
+                    file!(),
+                )
+            })?;
+        gray
+    };
+
+    let mut binary = Mat::default();
+    imgproc::threshold(
+        &gray,
+        &mut binary,
+        0.0,
+        255.0,
+        imgproc::THRESH_BINARY_INV | imgproc::THRESH_OTSU,
+    )
+    .map_err(|e| {
+        OmrError::new(
+            OmrErrorKind::ImageProcessing(format!("binarize image: {}", e)),
+            line!(),
+            file!(),
+        )
+    })?;
+    Ok(binary)
+}
+
+/// Share of non-zero pixels within a region of a binary image
+fn fill_ratio(binary: &Mat, region: Rect) -> Result<f32, OmrError> {
+    let area = region.area();
+    if area <= 0 {
+        return Ok(0.0);
+    }
+
+    let roi = Mat::roi(binary, region)
+        .and_then(|roi| roi.try_clone())
+        .map_err(|e| {
+            OmrError::new(
+                OmrErrorKind::ImageProcessing(format!("crop bubble {:?}: {}", region, e)),
+                line!(),
+                file!(),
+            )
+        })?;
+    let ink = core::count_non_zero(&roi).map_err(|e| {
+        OmrError::new(
+            OmrErrorKind::ImageProcessing(format!("count ink in bubble {:?}: {}", region, e)),
+            line!(),
+            file!(),
+        )
+    })?;
+
+    Ok(ink as f32 / area as f32)
+}
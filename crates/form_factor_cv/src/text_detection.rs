@@ -92,7 +92,7 @@ impl std::fmt::Display for TextDetectionErrorKind {
 }
 
 /// Text detection error with location information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TextDetectionError {
     /// Error category
     pub kind: TextDetectionErrorKind,
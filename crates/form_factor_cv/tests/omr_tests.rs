@@ -0,0 +1,72 @@
+//! Integration tests for optical mark recognition answer grids
+//!
+//! Run with `cargo test -p form_factor_cv --features omr`.
+
+#![cfg(feature = "omr")]
+
+use form_factor_cv::{AnswerGrid, OmrAnswer, OmrDetector};
+use opencv::core::Rect;
+
+/// Three questions of four choices, spaced apart
+fn grid() -> AnswerGrid {
+    AnswerGrid::new(10, 20, 8, 6, 3, ["A", "B", "C", "D"])
+        .expect("Valid grid")
+        .with_spacing(12, 10)
+        .expect("Valid grid")
+}
+
+#[test]
+fn bubbles_are_laid_out_in_rows_of_choices() {
+    let grid = grid();
+    assert_eq!(grid.bubble(0, 0), Some(Rect::new(10, 20, 8, 6)));
+    assert_eq!(grid.bubble(2, 3), Some(Rect::new(46, 40, 8, 6)));
+    assert_eq!(grid.bubble(3, 0), None);
+    assert_eq!(grid.bubble(0, 4), None);
+    assert_eq!(grid.bounds(), Rect::new(10, 20, 44, 26));
+}
+
+#[test]
+fn invalid_grids_are_rejected() {
+    assert!(AnswerGrid::new(0, 0, 0, 6, 3, ["A"]).is_err());
+    assert!(AnswerGrid::new(0, 0, 8, 6, 0, ["A"]).is_err());
+    assert!(AnswerGrid::new(0, 0, 8, 6, 3, Vec::<String>::new()).is_err());
+    assert!(grid().with_spacing(4, 10).is_err());
+}
+
+#[test]
+fn single_choice_answers_need_one_clear_mark() {
+    let grid = grid();
+
+    let answer = OmrAnswer::from_fills(0, vec![0.05, 0.8, 0.1, 0.0], &grid, 0.45);
+    assert_eq!(answer.selected(), &vec![1]);
+    assert_eq!(answer.value(), "B");
+
+    let blank = OmrAnswer::from_fills(1, vec![0.05, 0.1, 0.1, 0.0], &grid, 0.45);
+    assert!(blank.is_blank());
+    assert_eq!(blank.value(), "");
+
+    // An erased mark next to the real one is still read
+    let erased = OmrAnswer::from_fills(2, vec![0.5, 0.0, 0.9, 0.0], &grid, 0.45);
+    assert_eq!(erased.value(), "C");
+    assert!(!*erased.ambiguous());
+
+    let double = OmrAnswer::from_fills(2, vec![0.8, 0.0, 0.75, 0.0], &grid, 0.45);
+    assert!(double.is_blank());
+    assert!(*double.ambiguous());
+}
+
+#[test]
+fn multiple_choice_answers_list_every_mark() {
+    let grid = grid().allow_multiple();
+    let answer = OmrAnswer::from_fills(0, vec![0.8, 0.0, 0.75, 0.5], &grid, 0.45);
+    assert_eq!(answer.value(), "A,C,D");
+    assert!(!*answer.ambiguous());
+}
+
+#[test]
+fn detector_parameters_are_validated() {
+    assert!(OmrDetector::new().with_fill_threshold(1.5).is_err());
+    assert!(OmrDetector::new().with_inset(0.5).is_err());
+    let detector = OmrDetector::new().with_fill_threshold(0.3).expect("Valid threshold");
+    assert_eq!(detector.fill_threshold(), 0.3);
+}
@@ -0,0 +1,239 @@
+//! Confidence calibration learned from review decisions
+//!
+//! Tesseract's confidence is a poor guide to whether a value is right, and
+//! how poor depends on the form: 70% may mean "usually right" on a typed
+//! invoice and "usually wrong" on a handwritten application. Each review
+//! decision on an extracted value records whether the value was right,
+//! together with its raw confidence, against the form's template and the
+//! field's name. [`ConfidenceCalibration::calibrate`] turns a raw confidence
+//! into the accuracy observed for similar values, which batch review
+//! compares with its threshold instead of the raw number.
+//!
+//! Confidences are grouped into bands of [`CALIBRATION_BAND_WIDTH`]. A band
+//! is trusted once it holds [`MIN_CALIBRATION_SAMPLES`] decisions; until then
+//! the template's decisions over all fields are used, and failing those the
+//! raw confidence.
+
+use crate::config_dir;
+use form_factor_core::{IoError, IoOperation};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, instrument, warn};
+
+/// Width of a confidence band, in confidence points (0-100)
+pub const CALIBRATION_BAND_WIDTH: f32 = 10.0;
+
+/// Decisions a band needs before its accuracy is used
+pub const MIN_CALIBRATION_SAMPLES: u32 = 5;
+
+/// Number of confidence bands covering 0-100
+const BAND_COUNT: usize = 10;
+
+/// Review decisions in one confidence band
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalibrationBand {
+    /// Values found right as extracted
+    pub correct: u32,
+    /// Values reviewed
+    pub total: u32,
+}
+
+/// Observed accuracy by raw confidence, for one field or template
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalibrationCurve {
+    #[serde(default)]
+    bands: [CalibrationBand; BAND_COUNT],
+}
+
+impl CalibrationCurve {
+    /// Record whether a value extracted with `confidence` (0-100) was right
+    pub fn record(&mut self, confidence: f32, correct: bool) {
+        let band = &mut self.bands[band_index(confidence)];
+        band.total += 1;
+        if correct {
+            band.correct += 1;
+        }
+    }
+
+    /// Decisions in the band holding `confidence`
+    pub fn band(&self, confidence: f32) -> CalibrationBand {
+        self.bands[band_index(confidence)]
+    }
+
+    /// Number of decisions recorded
+    pub fn samples(&self) -> u32 {
+        self.bands.iter().map(|band| band.total).sum()
+    }
+
+    /// Percentage of values right in the band holding `confidence`
+    ///
+    /// `None` until the band holds [`MIN_CALIBRATION_SAMPLES`] decisions.
+    pub fn accuracy(&self, confidence: f32) -> Option<f32> {
+        let band = self.band(confidence);
+        (band.total >= MIN_CALIBRATION_SAMPLES).then(|| band.correct as f32 * 100.0 / band.total as f32)
+    }
+}
+
+/// Band holding a confidence, with 100 in the top band
+fn band_index(confidence: f32) -> usize {
+    let confidence = if confidence.is_nan() {
+        0.0
+    } else {
+        confidence.clamp(0.0, 100.0)
+    };
+    ((confidence / CALIBRATION_BAND_WIDTH) as usize).min(BAND_COUNT - 1)
+}
+
+/// Calibration curves of one template
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateCalibration {
+    /// Decisions over all of the template's fields
+    #[serde(default)]
+    pub overall: CalibrationCurve,
+    /// Decisions per field name
+    #[serde(default)]
+    pub fields: BTreeMap<String, CalibrationCurve>,
+}
+
+/// Learned mapping from raw OCR confidence to accuracy, per template and field
+///
+/// Projects not started from a template are calibrated together under the
+/// empty template name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfidenceCalibration {
+    /// Curves by template name
+    #[serde(default)]
+    templates: BTreeMap<String, TemplateCalibration>,
+}
+
+impl ConfidenceCalibration {
+    /// Create an empty calibration, leaving confidences as they are
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a review decision on a value extracted with `confidence` (0-100)
+    pub fn record(&mut self, template: &str, field: &str, confidence: f32, correct: bool) {
+        let calibration = self.templates.entry(template.to_string()).or_default();
+        calibration.overall.record(confidence, correct);
+        calibration
+            .fields
+            .entry(field.to_string())
+            .or_default()
+            .record(confidence, correct);
+        debug!(template, field, confidence, correct, "Recorded calibration sample");
+    }
+
+    /// Curves learned for a template
+    pub fn template(&self, template: &str) -> Option<&TemplateCalibration> {
+        self.templates.get(template)
+    }
+
+    /// Expected accuracy (0-100) of a value extracted with `confidence`
+    ///
+    /// Uses the field's decisions when its band has enough of them, then
+    /// the template's, and otherwise returns `confidence` unchanged.
+    pub fn calibrate(&self, template: &str, field: &str, confidence: f32) -> f32 {
+        let Some(calibration) = self.templates.get(template) else {
+            return confidence;
+        };
+        calibration
+            .fields
+            .get(field)
+            .and_then(|curve| curve.accuracy(confidence))
+            .or_else(|| calibration.overall.accuracy(confidence))
+            .unwrap_or(confidence)
+    }
+
+    /// Whether no decisions have been recorded
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    /// Load the calibration from its file in the config directory
+    ///
+    /// Returns an empty calibration if the file doesn't exist or cannot be
+    /// read. Errors are logged but not propagated.
+    pub fn load() -> Self {
+        Self::load_from(&Self::config_path())
+    }
+
+    /// Load a calibration saved with [`save_to`](Self::save_to)
+    ///
+    /// Returns an empty calibration if the file doesn't exist or cannot be read.
+    #[instrument]
+    pub fn load_from(path: &Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                warn!(path = ?path, error = %e, "Failed to parse confidence calibration, starting fresh");
+                Self::new()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                debug!("No confidence calibration found, starting fresh");
+                Self::new()
+            }
+            Err(e) => {
+                warn!(path = ?path, error = %e, "Failed to read confidence calibration");
+                Self::new()
+            }
+        }
+    }
+
+    /// Save the calibration to its file in the config directory
+    ///
+    /// # Errors
+    ///
+    /// Returns `IoError` if the directory cannot be created or the file written.
+    pub fn save(&self) -> Result<(), IoError> {
+        self.save_to(&Self::config_path())
+    }
+
+    /// Save the calibration as JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns `IoError` if the directory cannot be created or the file written.
+    #[instrument(skip(self))]
+    pub fn save_to(&self, path: &Path) -> Result<(), IoError> {
+        let path_str = path.to_string_lossy().to_string();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                IoError::new(
+                    format!("Failed to create config directory: {}", e),
+                    parent.to_string_lossy().to_string(),
+                    IoOperation::Create,
+                    line!(),
+                    file!(),
+                )
+            })?;
+        }
+
+        let json = serde_json::to_string_pretty(self).map_err(|e| {
+            IoError::new(
+                format!("Failed to serialize confidence calibration: {}", e),
+                path_str.clone(),
+                IoOperation::Write,
+                line!(),
+                file!(),
+            )
+        })?;
+        std::fs::write(path, json).map_err(|e| {
+            IoError::new(
+                format!("Failed to write confidence calibration: {}", e),
+                path_str.clone(),
+                IoOperation::Write,
+                line!(),
+                file!(),
+            )
+        })?;
+
+        debug!(path = %path_str, templates = self.templates.len(), "Saved confidence calibration");
+        Ok(())
+    }
+
+    /// Returns `confidence_calibration.json` inside [`config_dir`]
+    fn config_path() -> PathBuf {
+        config_dir().join("confidence_calibration.json")
+    }
+}
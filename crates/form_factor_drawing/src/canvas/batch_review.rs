@@ -0,0 +1,571 @@
+//! Batch review of uncertain field values across many filled forms
+//!
+//! Each filled form is an instance: a saved project whose fields carry the
+//! values read off that form, with the extractor's confidence in each one.
+//! A [`BatchReview`] loads many instances and queues every field a person
+//! should look at, either because its confidence is under the threshold or
+//! because its value breaks the field's rule. Fields whose condition isn't
+//! met on a form are left out. The review window steps
+//! through the queue one field at a time: Enter accepts the value, or saves
+//! the correction if it was edited, Shift+Enter skips it, and the window
+//! keeps count of the throughput so far.
+//!
+//! Confidences are compared with the threshold after calibration: every
+//! accepted or corrected value teaches the review's
+//! [`ConfidenceCalibration`] how often values of that confidence are right
+//! on the instance's template, and the learned accuracy replaces the raw
+//! confidence the next time the queue is built. The same decisions are
+//! queued as [`OcrSample`]s for the application to report.
+
+use super::core::{CanvasError, DrawingCanvas};
+use super::journal::read_project;
+use crate::{ConfidenceCalibration, FieldIssue, OcrSample, field_applies};
+use form_factor_core::IoOperation;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tracing::{debug, error, info, instrument};
+
+/// Confidence (0-100) under which extracted values are queued for review
+pub const DEFAULT_REVIEW_THRESHOLD: f32 = 80.0;
+
+/// One filled form in a batch review
+#[derive(Debug, Clone)]
+pub struct ReviewInstance {
+    /// Name shown in the review window
+    pub name: String,
+    /// Project file the instance was loaded from and is saved back to
+    pub path: Option<PathBuf>,
+    /// The instance's project
+    pub canvas: DrawingCanvas,
+    /// Whether review changed any field since the instance was loaded or saved
+    pub modified: bool,
+}
+
+/// Why a field was queued for review
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReviewReason {
+    /// The extracted value's calibrated confidence is under the threshold
+    LowConfidence(f32),
+    /// The value breaks the field's rule
+    Invalid(FieldIssue),
+}
+
+impl fmt::Display for ReviewReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReviewReason::LowConfidence(confidence) => write!(f, "Low confidence ({:.0}%)", confidence),
+            ReviewReason::Invalid(issue) => write!(f, "{}", issue),
+        }
+    }
+}
+
+/// A field waiting for review
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReviewItem {
+    /// Index of the instance in the batch
+    pub instance: usize,
+    /// Index of the field's shape in the instance
+    pub field: usize,
+    /// Why the field needs review
+    pub reason: ReviewReason,
+}
+
+/// What the reviewer decided about a field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReviewAction {
+    /// The value is right as extracted
+    Accept,
+    /// Replace the value with the one read off the form
+    Correct(String),
+    /// Leave the field for later
+    Skip,
+}
+
+/// Counts and pace of review decisions
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ReviewStats {
+    /// Fields accepted as extracted
+    pub accepted: usize,
+    /// Fields given a corrected value
+    pub corrected: usize,
+    /// Fields skipped
+    pub skipped: usize,
+    /// Time of the first decision, in seconds
+    started: Option<f64>,
+    /// Time of the latest decision, in seconds
+    latest: Option<f64>,
+}
+
+impl ReviewStats {
+    /// Number of decisions made
+    pub fn reviewed(&self) -> usize {
+        self.accepted + self.corrected + self.skipped
+    }
+
+    /// Seconds between the first and latest decisions
+    pub fn elapsed_seconds(&self) -> f64 {
+        match (self.started, self.latest) {
+            (Some(started), Some(latest)) => latest - started,
+            _ => 0.0,
+        }
+    }
+
+    /// Decisions per minute, once there has been time to measure
+    ///
+    /// The first decision starts the clock, so the pace counts the decisions after it.
+    pub fn fields_per_minute(&self) -> Option<f64> {
+        let elapsed = self.elapsed_seconds();
+        (elapsed > 0.0).then(|| (self.reviewed() - 1) as f64 * 60.0 / elapsed)
+    }
+
+    /// Record a decision made at `now`, a monotonic time in seconds
+    fn record(&mut self, action: &ReviewAction, now: f64) {
+        match action {
+            ReviewAction::Accept => self.accepted += 1,
+            ReviewAction::Correct(_) => self.corrected += 1,
+            ReviewAction::Skip => self.skipped += 1,
+        }
+        self.started.get_or_insert(now);
+        self.latest = Some(now);
+    }
+}
+
+/// Review queue of flagged fields across many instances
+#[derive(Debug, Clone)]
+pub struct BatchReview {
+    pub(super) instances: Vec<ReviewInstance>,
+    queue: Vec<ReviewItem>,
+    position: usize,
+    threshold: f32,
+    stats: ReviewStats,
+    calibration: ConfidenceCalibration,
+    /// Decisions on extracted values not yet reported
+    samples: Vec<OcrSample>,
+
+    // Review window state
+    /// Value in the window's correction box
+    edit: String,
+    /// Whether the correction box takes keyboard focus on the next frame
+    refocus: bool,
+    /// Message about the last decision
+    pub(super) status: Option<String>,
+
+    // Instance manager state
+    /// Instances chosen for bulk actions
+    pub(super) selected: BTreeSet<usize>,
+    /// Template name typed for reassigning the chosen instances
+    pub(super) template_draft: String,
+    /// Whether deleting the chosen instances is waiting for confirmation
+    pub(super) confirm_delete: bool,
+}
+
+impl Default for BatchReview {
+    fn default() -> Self {
+        Self::new(DEFAULT_REVIEW_THRESHOLD)
+    }
+}
+
+impl BatchReview {
+    /// Empty review flagging values with confidence under `threshold` (0-100)
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            instances: Vec::new(),
+            queue: Vec::new(),
+            position: 0,
+            threshold,
+            stats: ReviewStats::default(),
+            calibration: ConfidenceCalibration::new(),
+            samples: Vec::new(),
+            edit: String::new(),
+            refocus: true,
+            status: None,
+            selected: BTreeSet::new(),
+            template_draft: String::new(),
+            confirm_delete: false,
+        }
+    }
+
+    /// Calibrate confidences with what earlier reviews learned
+    pub fn with_calibration(mut self, calibration: ConfidenceCalibration) -> Self {
+        self.calibration = calibration;
+        self.rebuild_queue();
+        self
+    }
+
+    /// Calibration learned so far, including this review's decisions
+    pub fn calibration(&self) -> &ConfidenceCalibration {
+        &self.calibration
+    }
+
+    /// Add an instance and queue its flagged fields
+    pub fn add_instance(&mut self, name: impl Into<String>, path: Option<PathBuf>, canvas: DrawingCanvas) {
+        self.instances.push(ReviewInstance {
+            name: name.into(),
+            path,
+            canvas,
+            modified: false,
+        });
+        self.rebuild_queue();
+    }
+
+    /// Load a project file as an instance
+    ///
+    /// Either encoding opens, compressed or not, and changes autosaved to the
+    /// file's journal are replayed, as when the project is opened. The
+    /// instance is saved back in the file's own encoding and compression.
+    #[instrument(skip(self))]
+    pub fn add_project_file(&mut self, path: &Path) -> Result<(), CanvasError> {
+        let (mut canvas, journal) = read_project(path, None)?;
+        let path_str = path.to_string_lossy().into_owned();
+        let snapshot = std::fs::read(path)
+            .map_err(|e| CanvasError::io(e, path_str.as_str(), IoOperation::Read, line!(), file!()))?;
+        canvas
+            .keep_file_format(&snapshot)
+            .map_err(|e| e.with_path(path_str.as_str(), IoOperation::Read))?;
+        canvas.journal = Some(journal);
+
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| canvas.project_name.clone());
+        self.add_instance(name, Some(path.to_path_buf()), canvas);
+        Ok(())
+    }
+
+    /// Instances in the batch
+    pub fn instances(&self) -> &[ReviewInstance] {
+        &self.instances
+    }
+
+    /// Number of instances review changed since they were loaded or saved
+    pub fn unsaved_instances(&self) -> usize {
+        self.instances.iter().filter(|instance| instance.modified).count()
+    }
+
+    /// Flagged fields, in review order
+    pub fn queue(&self) -> &[ReviewItem] {
+        &self.queue
+    }
+
+    /// Field under review, or `None` once the queue is done
+    pub fn current(&self) -> Option<&ReviewItem> {
+        self.queue.get(self.position)
+    }
+
+    /// Number of queued fields not yet decided
+    pub fn remaining(&self) -> usize {
+        self.queue.len().saturating_sub(self.position)
+    }
+
+    /// Confidence (0-100) under which values are flagged
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// Change the threshold and queue the fields it flags
+    ///
+    /// The queue restarts; accepted and corrected fields are no longer
+    /// flagged, while skipped ones come round again.
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold;
+        self.rebuild_queue();
+    }
+
+    /// Decisions made so far
+    pub fn stats(&self) -> &ReviewStats {
+        &self.stats
+    }
+
+    /// Apply a decision to the field under review and move to the next one
+    ///
+    /// `now` is a monotonic time in seconds (e.g. egui's input time), used
+    /// for the throughput. Returns false, staying on the field, when the
+    /// accepted or corrected value breaks the field's rule or the queue is done.
+    #[instrument(skip(self))]
+    pub fn apply(&mut self, action: ReviewAction, now: f64) -> bool {
+        let Some(item) = self.current().copied() else {
+            return false;
+        };
+        let Some(instance) = self.instances.get_mut(item.instance) else {
+            return false;
+        };
+        let Some(shape) = instance.canvas.shapes.get_mut(item.field) else {
+            return false;
+        };
+
+        match &action {
+            ReviewAction::Accept | ReviewAction::Correct(_) => {
+                let mut reviewed = shape.clone();
+                match &action {
+                    ReviewAction::Correct(value) => reviewed.set_value(value.trim()),
+                    _ => reviewed.confirm_value(),
+                }
+                if let Err(issue) = reviewed.check_value() {
+                    self.status = Some(format!("Not saved: {}", issue));
+                    self.refocus = true;
+                    return false;
+                }
+                if let Some(confidence) = shape.confidence() {
+                    let correct = reviewed.value().trim() == shape.value().trim();
+                    let template = instance.canvas.template_name.as_deref().unwrap_or_default();
+                    self.calibration.record(template, shape.name(), confidence, correct);
+                    self.samples.push(OcrSample {
+                        field: shape.name().to_string(),
+                        proposed: shape.value().to_string(),
+                        corrected: reviewed.value().to_string(),
+                        confidence,
+                    });
+                }
+                *shape = reviewed;
+                instance.modified = true;
+            }
+            ReviewAction::Skip => {}
+        }
+
+        debug!(instance = item.instance, field = item.field, "Reviewed field");
+        self.stats.record(&action, now);
+        self.status = None;
+        self.position += 1;
+        self.load_edit();
+        true
+    }
+
+    /// Take the accepted and corrected extracted values since the last call, oldest first
+    pub fn take_ocr_samples(&mut self) -> Vec<OcrSample> {
+        std::mem::take(&mut self.samples)
+    }
+
+    /// Save every modified instance back to its project file
+    ///
+    /// Returns the number of instances saved. Instances without a file are
+    /// left modified. Each file is written as a new snapshot with its journal
+    /// compacted; if someone else saved a file since it was loaded, saving
+    /// stops with a save conflict and that instance stays modified.
+    #[instrument(skip(self))]
+    pub fn save(&mut self) -> Result<usize, CanvasError> {
+        let mut saved = 0;
+        for instance in self.instances.iter_mut().filter(|instance| instance.modified) {
+            let Some(path) = &instance.path else {
+                continue;
+            };
+            instance.canvas.save_snapshot(path)?;
+            instance.modified = false;
+            saved += 1;
+        }
+        info!("Saved {} reviewed instance(s)", saved);
+        Ok(saved)
+    }
+
+    /// Queue every flagged field, instance by instance, and start from the top
+    pub(super) fn rebuild_queue(&mut self) {
+        let threshold = self.threshold;
+        let calibration = &self.calibration;
+        self.queue = self
+            .instances
+            .iter()
+            .enumerate()
+            .flat_map(|(instance, entry)| {
+                let template = entry.canvas.template_name.as_deref().unwrap_or_default();
+                entry
+                    .canvas
+                    .shapes
+                    .iter()
+                    .enumerate()
+                    .filter_map(move |(field, shape)| {
+                        // Fields that don't apply to this form aren't flagged, even when empty
+                        if !field_applies(&entry.canvas.shapes, field) {
+                            return None;
+                        }
+                        let confidence = shape
+                            .confidence()
+                            .map(|confidence| calibration.calibrate(template, shape.name(), confidence));
+                        let reason = match (shape.check_value(), confidence) {
+                            (Err(issue), _) => ReviewReason::Invalid(issue),
+                            (Ok(()), Some(confidence)) if confidence < threshold => {
+                                ReviewReason::LowConfidence(confidence)
+                            }
+                            _ => return None,
+                        };
+                        Some(ReviewItem {
+                            instance,
+                            field,
+                            reason,
+                        })
+                    })
+            })
+            .collect();
+        self.position = 0;
+        self.load_edit();
+    }
+
+    /// Fill the correction box with the value of the field under review
+    fn load_edit(&mut self) {
+        self.edit = self
+            .current()
+            .and_then(|item| self.instances.get(item.instance)?.canvas.shapes.get(item.field))
+            .map(|shape| shape.value().to_string())
+            .unwrap_or_default();
+        self.refocus = true;
+    }
+}
+
+impl DrawingCanvas {
+    /// Open the batch review window on a review
+    pub fn start_batch_review(&mut self, review: BatchReview) {
+        info!(
+            instances = review.instances.len(),
+            flagged = review.queue.len(),
+            "Starting batch review"
+        );
+        self.batch_review = Some(review);
+    }
+
+    /// Close the batch review window, returning the review
+    pub fn close_batch_review(&mut self) -> Option<BatchReview> {
+        self.batch_review.take()
+    }
+
+    /// Show the batch review window
+    ///
+    /// Returns true if the window was shown.
+    pub fn show_batch_review_panel(&mut self, ctx: &egui::Context) -> bool {
+        let Some(review) = &mut self.batch_review else {
+            return false;
+        };
+
+        let mut panel_open = true;
+        let mut action = None;
+        let mut save = false;
+        let mut export_instances = false;
+        let mut threshold = review.threshold;
+        let now = ctx.input(|i| i.time);
+        let refocus = std::mem::take(&mut review.refocus);
+
+        egui::Window::new("Batch Review")
+            .open(&mut panel_open)
+            .default_width(340.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Flag confidence under");
+                    ui.add(egui::Slider::new(&mut threshold, 0.0..=100.0).suffix("%"));
+                });
+                ui.separator();
+
+                let current = review.current().copied().and_then(|item| {
+                    let instance = review.instances.get(item.instance)?;
+                    Some((item, instance, instance.canvas.shapes.get(item.field)?))
+                });
+                match current {
+                    Some((item, instance, shape)) => {
+                        let name = match shape.name() {
+                            "" => format!("Field {}", item.field + 1),
+                            name => name.to_string(),
+                        };
+                        ui.horizontal(|ui| {
+                            ui.strong(name);
+                            ui.weak(format!("in {}", instance.name));
+                        });
+                        ui.weak(format!("{} of {}", review.position + 1, review.queue.len()));
+                        ui.colored_label(ui.visuals().warn_fg_color, item.reason.to_string());
+                        if let (ReviewReason::LowConfidence(calibrated), Some(raw)) = (item.reason, shape.confidence())
+                            && calibrated != raw
+                        {
+                            ui.weak(format!("Calibrated from {:.0}% OCR confidence", raw));
+                        }
+
+                        let edit = ui.add(
+                            egui::TextEdit::singleline(&mut review.edit)
+                                .id(ui.id().with("batch_review_value"))
+                                .desired_width(f32::INFINITY),
+                        );
+                        if refocus {
+                            edit.request_focus();
+                        }
+                        let edited = review.edit.trim() != shape.value().trim();
+                        if edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                            action = Some(if ui.input(|i| i.modifiers.shift) {
+                                ReviewAction::Skip
+                            } else if edited {
+                                ReviewAction::Correct(review.edit.clone())
+                            } else {
+                                ReviewAction::Accept
+                            });
+                        }
+
+                        ui.horizontal(|ui| {
+                            let label = if edited { "Correct" } else { "Accept" };
+                            if ui.button(label).clicked() {
+                                action = Some(if edited {
+                                    ReviewAction::Correct(review.edit.clone())
+                                } else {
+                                    ReviewAction::Accept
+                                });
+                            }
+                            if ui.button("Skip").clicked() {
+                                action = Some(ReviewAction::Skip);
+                            }
+                        });
+                        ui.weak("Enter: accept or correct · Shift+Enter: skip");
+                    }
+                    None if review.queue.is_empty() => {
+                        ui.label("No fields need review.");
+                    }
+                    None => {
+                        ui.label("All flagged fields have been reviewed.");
+                    }
+                }
+
+                if let Some(status) = &review.status {
+                    ui.colored_label(ui.visuals().error_fg_color, status);
+                }
+
+                ui.separator();
+                let stats = review.stats;
+                ui.label(format!(
+                    "Accepted {} · Corrected {} · Skipped {} · {} left",
+                    stats.accepted,
+                    stats.corrected,
+                    stats.skipped,
+                    review.remaining()
+                ));
+                if let Some(pace) = stats.fields_per_minute() {
+                    ui.weak(format!("{:.1} fields/min over {:.0}s", pace, stats.elapsed_seconds()));
+                }
+
+                let modified = review.unsaved_instances();
+                save = ui
+                    .add_enabled(
+                        modified > 0,
+                        egui::Button::new(format!("Save {} instance(s)", modified)),
+                    )
+                    .clicked();
+
+                ui.separator();
+                export_instances = review.show_instance_manager(ui);
+            });
+
+        if threshold != review.threshold {
+            review.set_threshold(threshold);
+        }
+        if let Some(action) = action {
+            review.apply(action, now);
+        }
+        if save && let Err(e) = review.save() {
+            error!("Failed to save reviewed instances: {}", e);
+            review.status = Some(e.to_string());
+        }
+        if export_instances {
+            self.request_instance_export();
+        }
+        if !panel_open {
+            // Keep what this review learned for the next one
+            if let Some(review) = self.batch_review.take()
+                && let Err(e) = review.calibration.save()
+            {
+                error!("Failed to save confidence calibration: {}", e);
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,226 @@
+//! Named viewport bookmarks saved with the project
+//!
+//! A bookmark remembers a zoom, pan and page under a name, so a reviewer
+//! comparing distant parts of a long form, such as its header and its
+//! totals, can jump between them instead of scrolling back and forth.
+//! Ctrl+B bookmarks the current view and Ctrl+1 to Ctrl+9 jump to the first
+//! nine bookmarks; the bookmarks window lists, renames and removes them.
+//!
+//! Jumping applies [`CanvasCommand::SetZoom`] and [`CanvasCommand::SetPan`],
+//! so the jump is broadcast like any other view change.
+
+use egui::{Key, Modifiers, Vec2};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use super::command::CanvasCommand;
+use super::core::DrawingCanvas;
+
+/// Bookmarks reachable with Ctrl and a digit key
+pub const MAX_BOOKMARK_SHORTCUTS: usize = 9;
+
+/// Digit keys jumping to the first bookmarks, in order
+const BOOKMARK_KEYS: [Key; MAX_BOOKMARK_SHORTCUTS] = [
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+];
+
+/// A saved view of the canvas
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ViewBookmark {
+    /// Name shown in the bookmarks window
+    pub name: String,
+    /// Zoom factor of the view
+    pub zoom: f32,
+    /// Pan offset of the view
+    pub pan: Vec2,
+    /// Page the view is on
+    #[serde(default)]
+    pub page: usize,
+}
+
+impl DrawingCanvas {
+    /// Bookmark the current view, returning the bookmark's index
+    ///
+    /// A blank name is replaced with "View N".
+    #[instrument(skip(self, name))]
+    pub fn add_bookmark(&mut self, name: impl Into<String>) -> usize {
+        let name = name.into().trim().to_string();
+        let name = if name.is_empty() {
+            format!("View {}", self.bookmarks.len() + 1)
+        } else {
+            name
+        };
+        debug!(name, zoom = self.zoom_level, "Adding view bookmark");
+        self.bookmarks.push(ViewBookmark {
+            name,
+            zoom: self.zoom_level,
+            pan: self.pan_offset,
+            // Projects hold a single page
+            page: 0,
+        });
+        self.bookmarks.len() - 1
+    }
+
+    /// Remove a bookmark by index
+    pub fn remove_bookmark(&mut self, index: usize) -> Option<ViewBookmark> {
+        (index < self.bookmarks.len()).then(|| self.bookmarks.remove(index))
+    }
+
+    /// Rename a bookmark, returning whether it exists
+    ///
+    /// Blank names are ignored.
+    pub fn rename_bookmark(&mut self, index: usize, name: impl Into<String>) -> bool {
+        let name = name.into().trim().to_string();
+        match self.bookmarks.get_mut(index) {
+            Some(bookmark) => {
+                if !name.is_empty() {
+                    bookmark.name = name;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replace a bookmark's view with the current one, keeping its name
+    ///
+    /// Returns whether the bookmark exists.
+    pub fn update_bookmark(&mut self, index: usize) -> bool {
+        let (zoom, pan) = (self.zoom_level, self.pan_offset);
+        self.bookmarks
+            .get_mut(index)
+            .map(|bookmark| {
+                bookmark.zoom = zoom;
+                bookmark.pan = pan;
+            })
+            .is_some()
+    }
+
+    /// Jump to the view saved in a bookmark
+    ///
+    /// Returns whether the bookmark exists.
+    #[instrument(skip(self))]
+    pub fn go_to_bookmark(&mut self, index: usize) -> bool {
+        let Some(bookmark) = self.bookmarks.get(index).cloned() else {
+            return false;
+        };
+        debug!(name = bookmark.name, "Jumping to view bookmark");
+        self.apply_command(CanvasCommand::SetZoom(bookmark.zoom));
+        self.apply_command(CanvasCommand::SetPan {
+            x: bookmark.pan.x,
+            y: bookmark.pan.y,
+        });
+        true
+    }
+
+    /// Toggle the bookmarks window
+    pub fn toggle_bookmarks_panel(&mut self) {
+        self.show_bookmarks = !self.show_bookmarks;
+    }
+
+    /// Bookmark the view on Ctrl+B and jump to bookmarks on Ctrl+1 to Ctrl+9
+    pub(super) fn handle_bookmark_input(&mut self, ui: &egui::Ui) {
+        let (add, jump) = ui.input_mut(|i| {
+            let add = i.consume_key(Modifiers::COMMAND, Key::B);
+            let jump = BOOKMARK_KEYS.iter().position(|key| i.consume_key(Modifiers::COMMAND, *key));
+            (add, jump)
+        });
+        if add {
+            self.add_bookmark("");
+        }
+        if let Some(index) = jump {
+            self.go_to_bookmark(index);
+        }
+    }
+
+    /// Show the bookmarks window
+    ///
+    /// Returns true if the window was shown.
+    pub fn show_bookmarks_panel(&mut self, ctx: &egui::Context) -> bool {
+        if !self.show_bookmarks {
+            return false;
+        }
+
+        let mut panel_open = true;
+        let mut add = false;
+        let mut go_to = None;
+        let mut update = None;
+        let mut remove = None;
+        let mut renames = Vec::new();
+        let new_name = &mut self.bookmark_name;
+        let bookmarks = &self.bookmarks;
+        egui::Window::new("Bookmarks")
+            .open(&mut panel_open)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(new_name).on_hover_text("Name for the current view");
+                    if ui.button("Add View").on_hover_text("Ctrl+B").clicked() {
+                        add = true;
+                    }
+                });
+                ui.separator();
+
+                if bookmarks.is_empty() {
+                    ui.label("No bookmarks yet");
+                    return;
+                }
+                egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                    egui::Grid::new("bookmarks_grid").num_columns(4).striped(true).show(ui, |ui| {
+                        for (idx, bookmark) in bookmarks.iter().enumerate() {
+                            let mut name = bookmark.name.clone();
+                            if ui.text_edit_singleline(&mut name).changed() {
+                                renames.push((idx, name));
+                            }
+                            let go = ui.button("Go");
+                            let go = if idx < MAX_BOOKMARK_SHORTCUTS {
+                                go.on_hover_text(format!("Ctrl+{}", idx + 1))
+                            } else {
+                                go
+                            };
+                            if go.clicked() {
+                                go_to = Some(idx);
+                            }
+                            if ui.button("Update").on_hover_text("Save the current view here").clicked() {
+                                update = Some(idx);
+                            }
+                            if ui.button("🗑").on_hover_text("Remove").clicked() {
+                                remove = Some(idx);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+
+        for (idx, name) in renames {
+            self.rename_bookmark(idx, name);
+        }
+        if add {
+            let name = std::mem::take(&mut self.bookmark_name);
+            self.add_bookmark(name);
+        }
+        if let Some(idx) = update {
+            self.update_bookmark(idx);
+        }
+        if let Some(idx) = go_to {
+            self.go_to_bookmark(idx);
+        }
+        if let Some(idx) = remove {
+            self.remove_bookmark(idx);
+        }
+        if !panel_open {
+            self.show_bookmarks = false;
+        }
+
+        true
+    }
+}
@@ -0,0 +1,226 @@
+//! Live collaboration on a project through the configured project store
+//!
+//! The collaboration window joins a named session in the
+//! [`ProjectStore`](crate::ProjectStore) from the user settings, the same
+//! shared directory or S3 bucket projects are stored in. Collaborators
+//! join the same session name from the same version of the project,
+//! usually one opened from the store. While joined, the canvas records its
+//! shape edits into a [`CollabSession`], syncs every
+//! [`COLLAB_SYNC_INTERVAL_SECS`], replaces its shapes with the merged
+//! shapes when a collaborator changed them, and draws the other
+//! collaborators' cursors. Detections and comments are not shared.
+//!
+//! Operations are stored unencrypted, so password-protected projects
+//! can't be shared: joining a session fails, and protecting a project
+//! leaves the session it is in.
+
+use super::core::DrawingCanvas;
+use crate::{CollabError, CollabErrorKind, CollabSession, Presence, StoreSyncTransport};
+use egui::Color32;
+use std::sync::Arc;
+use tracing::{debug, info, instrument, warn};
+
+/// Seconds between syncs with a collaboration session
+pub const COLLAB_SYNC_INTERVAL_SECS: f64 = 1.0;
+
+/// Cursor colors, picked for a collaborator by their actor ID
+const PEER_COLORS: [Color32; 6] = [
+    Color32::from_rgb(230, 25, 75),
+    Color32::from_rgb(60, 180, 75),
+    Color32::from_rgb(0, 130, 200),
+    Color32::from_rgb(245, 130, 48),
+    Color32::from_rgb(145, 30, 180),
+    Color32::from_rgb(240, 50, 230),
+];
+
+/// Cursor color of a collaborator
+fn peer_color(actor: &str) -> Color32 {
+    let sum = actor.bytes().fold(0usize, |sum, byte| sum.wrapping_mul(31).wrapping_add(byte as usize));
+    PEER_COLORS[sum % PEER_COLORS.len()]
+}
+
+/// Random bytes telling apart collaborators of the same name
+#[cfg(not(target_arch = "wasm32"))]
+fn actor_suffix() -> Result<[u8; 4], CollabError> {
+    let mut random = [0u8; 4];
+    getrandom::getrandom(&mut random)
+        .map_err(|e| CollabError::new(CollabErrorKind::ServerUnavailable(e.to_string()), line!(), file!()))?;
+    Ok(random)
+}
+
+/// Clock bytes telling apart collaborators of the same name, without an OS random source on the web
+#[cfg(target_arch = "wasm32")]
+fn actor_suffix() -> Result<[u8; 4], CollabError> {
+    let nanos = web_time::SystemTime::now()
+        .duration_since(web_time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.subsec_nanos())
+        .unwrap_or_default();
+    Ok(nanos.to_le_bytes())
+}
+
+/// Actor ID for a collaborator, unique even between collaborators of the same name
+fn new_actor(author: &str) -> Result<String, CollabError> {
+    let random = actor_suffix()?;
+    let name: String = author
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+        .collect();
+    let suffix: String = random.iter().map(|byte| format!("{:02x}", byte)).collect();
+    Ok(if name.is_empty() { suffix } else { format!("{}-{}", name, suffix) })
+}
+
+impl DrawingCanvas {
+    /// Toggle the collaboration window
+    pub fn toggle_collab_panel(&mut self) {
+        self.show_collab = !self.show_collab;
+        if self.show_collab && self.collab_session_name.is_empty() {
+            self.collab_session_name = self.stored_project().unwrap_or(&self.project_name).to_string();
+        }
+    }
+
+    /// Name of the collaboration session the canvas has joined, if any
+    pub fn collab_session(&self) -> Option<&str> {
+        self.collab.as_ref().map(|session| session.transport().session())
+    }
+
+    /// Join the collaboration session `name` in the configured project store
+    ///
+    /// The current shapes are taken as the version every collaborator
+    /// starts from. Leaves any session already joined.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CollabErrorKind::ProtectedProject`] error if the project
+    /// has a password. Returns an error if the store can't be opened, the
+    /// name can't be used, or the first sync fails; the canvas then stays
+    /// out of a session.
+    #[instrument(skip(self))]
+    pub fn start_collaboration(&mut self, name: &str) -> Result<(), CollabError> {
+        self.stop_collaboration();
+        if self.project_password.is_some() {
+            warn!(session = name, "Refusing to share a password-protected project");
+            return Err(CollabError::new(CollabErrorKind::ProtectedProject, line!(), file!()));
+        }
+        let store = self
+            .storage
+            .open()
+            .map_err(|e| CollabError::new(CollabErrorKind::ServerUnavailable(e.kind.to_string()), line!(), file!()))?;
+        let actor = new_actor(&self.comment_author)?;
+        let transport = StoreSyncTransport::new(Arc::from(store), name, actor.as_str())?;
+        let mut session = CollabSession::with_shapes(actor, transport, self.shapes.clone());
+        if session.sync()? > 0 {
+            self.set_shapes(session.shapes());
+        }
+        info!(session = name, actor = session.log().actor(), "Joined collaboration session");
+        self.collab = Some(session);
+        self.last_collab_sync = f64::NEG_INFINITY;
+        Ok(())
+    }
+
+    /// Leave the collaboration session, if any, keeping the shapes as they are
+    pub fn stop_collaboration(&mut self) {
+        if let Some(session) = self.collab.take() {
+            if let Err(e) = session.transport().leave() {
+                warn!("Could not remove presence from collaboration session: {}", e);
+            }
+            info!(session = session.transport().session(), "Left collaboration session");
+        }
+        self.peers.clear();
+    }
+
+    /// Sync with the collaboration session if [`COLLAB_SYNC_INTERVAL_SECS`] have passed
+    ///
+    /// `now` is the current time in seconds, e.g. from `egui::InputState::time`.
+    /// Shape edits since the last sync are sent, collaborators' edits are
+    /// applied to the shapes, and their cursors are updated. Returns the
+    /// number of collaborators' edits applied, or `None` when no sync was
+    /// due or no session is joined.
+    pub fn sync_collaboration(&mut self, now: f64) -> Option<Result<usize, CollabError>> {
+        let session = self.collab.as_mut()?;
+        if now - self.last_collab_sync < COLLAB_SYNC_INTERVAL_SECS {
+            return None;
+        }
+        self.last_collab_sync = now;
+
+        session.track(&self.shapes);
+        let received = match session.sync() {
+            Ok(received) => received,
+            Err(e) => return Some(Err(e)),
+        };
+        let actor = session.log().actor().to_string();
+        let presence = Presence {
+            color: peer_color(&actor),
+            actor,
+            display_name: self.comment_author.clone(),
+            cursor: self.cursor_canvas_pos.map(|pos| pos.0),
+        };
+        let peers = session.exchange_presence(presence);
+        if received > 0 {
+            debug!(received, "Applying collaborators' edits");
+            let shapes = session.shapes();
+            self.set_shapes(shapes);
+        }
+        match peers {
+            Ok(peers) => self.peers = peers,
+            Err(e) => return Some(Err(e)),
+        }
+        Some(Ok(received))
+    }
+
+    /// Show the collaboration window
+    ///
+    /// Returns true if the window was shown.
+    pub fn show_collab_panel(&mut self, ctx: &egui::Context) -> bool {
+        if !self.show_collab {
+            return false;
+        }
+
+        let mut panel_open = true;
+        let mut join = false;
+        let mut leave = false;
+        let joined = self.collab_session().map(str::to_string);
+        egui::Window::new("Collaborate")
+            .open(&mut panel_open)
+            .resizable(false)
+            .default_width(280.0)
+            .show(ctx, |ui| {
+                if let Some(name) = &joined {
+                    ui.label(format!("Joined session \"{}\" as {}.", name, self.comment_author));
+                    if self.peers.is_empty() {
+                        ui.weak("No one else is here yet");
+                    }
+                    for peer in &self.peers {
+                        ui.colored_label(peer.color, &peer.display_name);
+                    }
+                    leave = ui.button("Leave").clicked();
+                } else {
+                    ui.label("Edit shapes together with everyone who joins the same session from the project store.");
+                    ui.horizontal(|ui| {
+                        ui.label("Session");
+                        ui.text_edit_singleline(&mut self.collab_session_name);
+                    });
+                    join = ui
+                        .add_enabled(!self.collab_session_name.trim().is_empty(), egui::Button::new("Join"))
+                        .clicked();
+                }
+                if let Some(status) = &self.collab_status {
+                    ui.label(status.as_str());
+                }
+            });
+
+        if join {
+            let name = self.collab_session_name.trim().to_string();
+            self.collab_status = self.start_collaboration(&name).err().map(|e| e.kind.to_string());
+        } else if leave {
+            self.stop_collaboration();
+            self.collab_status = None;
+        }
+        if !panel_open {
+            debug!("Collaboration window closed");
+            self.show_collab = false;
+            self.collab_status = None;
+        }
+
+        true
+    }
+}
@@ -0,0 +1,351 @@
+//! Canvas commands and the reducer that applies them
+//!
+//! View state shared with plugins (zoom, pan, tool, layers and the canvas
+//! windows) and edits to single shapes and detections (moving, deleting,
+//! duplicating, reordering, hiding and locking) change only through
+//! [`DrawingCanvas::apply_command`], whether the request came from a plugin
+//! event or from input on the canvas itself.
+//! Every command that changed something is queued, so the application can
+//! broadcast one notification per frame and plugins can follow the canvas
+//! instead of keeping their own copy in step.
+
+use super::core::DrawingCanvas;
+use crate::{LayerType, Shape, ToolMode};
+use tracing::{debug, info, instrument, warn};
+
+/// Changes kept for [`DrawingCanvas::take_changes`] before the oldest are dropped
+///
+/// Bounds the queue for canvases whose changes are never broadcast.
+pub const MAX_PENDING_CHANGES: usize = 256;
+
+/// Canvas window that can be shown or hidden by a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::Display, strum::EnumIter)]
+pub enum CanvasPanel {
+    /// Review comment threads
+    Comments,
+    /// Page assignments
+    Workflow,
+    /// Session statistics
+    Stats,
+    /// Color legend over the canvas
+    Legend,
+    /// Find and rename
+    Rename,
+    /// Keyboard-first data entry
+    #[strum(to_string = "Data Entry")]
+    DataEntry,
+    /// PNG and SVG export
+    Export,
+    /// PDF printing
+    Print,
+    /// Saved views
+    Bookmarks,
+    /// Order data entry visits fields in
+    #[strum(to_string = "Tab Order")]
+    TabOrder,
+    /// Order the text read from detections makes up the document in
+    #[strum(to_string = "Reading Order")]
+    OcrOrder,
+    /// Word boxes over the detections
+    #[strum(to_string = "Word Boxes")]
+    OcrWords,
+    /// Field groups and repeating sections
+    #[strum(to_string = "Field Groups")]
+    FieldGroups,
+    /// Setting, changing or removing the project password
+    #[strum(to_string = "Project Password")]
+    Password,
+    /// Opening and saving projects in the configured project store
+    #[strum(to_string = "Project Store")]
+    Store,
+    /// Joining and leaving a collaboration session
+    #[strum(to_string = "Collaborate")]
+    Collaborate,
+}
+
+/// Change to the canvas view state or to one shape or detection
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CanvasCommand {
+    /// Set the zoom factor, clamped to 0.1 - 100
+    SetZoom(f32),
+    /// Set the pan offset
+    SetPan {
+        /// Horizontal offset
+        x: f32,
+        /// Vertical offset
+        y: f32,
+    },
+    /// Switch the active tool
+    SetTool(ToolMode),
+    /// Switch to the custom tool at this position in the canvas's tool registry
+    SelectCustomTool(usize),
+    /// Show or hide a layer
+    SetLayerVisible {
+        /// Layer to change
+        layer: LayerType,
+        /// Whether the layer is shown
+        visible: bool,
+    },
+    /// Select a layer, or clear the selection
+    SelectLayer(Option<LayerType>),
+    /// Remove everything on a layer; the grid has nothing to clear
+    ClearLayer(LayerType),
+    /// Turn the redaction preview on or off
+    SetRedactionMode(bool),
+    /// Turn the sample data preview of field layouts on or off
+    SetTemplatePreview(bool),
+    /// Show a canvas window if hidden, hide it if shown
+    TogglePanel(CanvasPanel),
+    /// Open the new-project window
+    OpenNewProject,
+    /// Arm the canvas so the next drag captures a screenshot of a region
+    BeginScreenshot,
+    /// Go back to the previously inspected shape or detection
+    SelectionBack,
+    /// Go forward to the shape or detection left by going back
+    SelectionForward,
+    /// Hide or show one shape or detection
+    SetObjectHidden {
+        /// Layer holding the object, [`LayerType::Shapes`] or [`LayerType::Detections`]
+        layer: LayerType,
+        /// Index of the object on its layer
+        index: usize,
+        /// Whether the object is hidden
+        hidden: bool,
+    },
+    /// Lock or unlock one shape or detection
+    SetObjectLocked {
+        /// Layer holding the object, [`LayerType::Shapes`] or [`LayerType::Detections`]
+        layer: LayerType,
+        /// Index of the object on its layer
+        index: usize,
+        /// Whether the object is locked
+        locked: bool,
+    },
+    /// Move one shape or detection; locked objects stay put
+    MoveObject {
+        /// Layer holding the object, [`LayerType::Shapes`] or [`LayerType::Detections`]
+        layer: LayerType,
+        /// Index of the object on its layer
+        index: usize,
+        /// Horizontal distance in canvas coordinates
+        dx: f32,
+        /// Vertical distance in canvas coordinates
+        dy: f32,
+    },
+    /// Remove one shape or detection
+    DeleteObject {
+        /// Layer holding the object, [`LayerType::Shapes`] or [`LayerType::Detections`]
+        layer: LayerType,
+        /// Index of the object on its layer
+        index: usize,
+    },
+    /// Insert a copy of a shape above the original
+    DuplicateShape(usize),
+    /// Move a shape above all others
+    BringToFront(usize),
+    /// Move a shape below all others
+    SendToBack(usize),
+}
+
+impl DrawingCanvas {
+    /// Apply a command to the canvas
+    ///
+    /// Returns whether the canvas changed. Commands that changed something
+    /// are queued for [`take_changes`](Self::take_changes); commands that
+    /// leave the state as it was, like selecting the current tool, are not.
+    #[instrument(skip(self))]
+    pub fn apply_command(&mut self, command: CanvasCommand) -> bool {
+        // Queue the zoom that was actually applied
+        let command = match command {
+            CanvasCommand::SetZoom(zoom) => CanvasCommand::SetZoom(zoom.clamp(0.1, 100.0)),
+            command => command,
+        };
+        let changed = match command {
+            CanvasCommand::SetZoom(zoom) => {
+                let changed = self.zoom_level != zoom;
+                self.zoom_level = zoom;
+                changed
+            }
+            CanvasCommand::SetPan { x, y } => {
+                let pan = egui::Vec2::new(x, y);
+                let changed = self.pan_offset != pan;
+                self.pan_offset = pan;
+                changed
+            }
+            CanvasCommand::SetTool(tool) => {
+                let changed = self.current_tool != tool || self.active_custom_tool.is_some();
+                self.current_tool = tool;
+                self.active_custom_tool = None;
+                changed
+            }
+            CanvasCommand::SelectCustomTool(index) => {
+                let changed = index < self.custom_tools.len() && self.active_custom_tool != Some(index);
+                if changed {
+                    self.active_custom_tool = Some(index);
+                }
+                changed
+            }
+            CanvasCommand::SetLayerVisible { layer, visible } => {
+                let changed = self.layer_manager.is_visible(layer) != visible;
+                self.layer_manager.set_visible(layer, visible);
+                changed
+            }
+            CanvasCommand::SelectLayer(layer) => {
+                let changed = self.selected_layer != layer;
+                self.selected_layer = layer;
+                changed
+            }
+            CanvasCommand::ClearLayer(layer) => match layer {
+                LayerType::Shapes => {
+                    self.clear_shapes();
+                    self.clear_stamps();
+                    info!("Cleared shapes layer");
+                    true
+                }
+                LayerType::Detections => {
+                    self.clear_detections();
+                    info!("Cleared detections layer");
+                    true
+                }
+                LayerType::Canvas => {
+                    self.clear_canvas_image();
+                    info!("Cleared canvas image");
+                    true
+                }
+                LayerType::Highlights => {
+                    let changed = !self.highlights.is_empty();
+                    self.clear_highlights();
+                    info!("Cleared highlights layer");
+                    changed
+                }
+                LayerType::Grid => false,
+            },
+            CanvasCommand::SetRedactionMode(enabled) => {
+                let changed = self.redaction_mode != enabled;
+                self.redaction_mode = enabled;
+                changed
+            }
+            CanvasCommand::SetTemplatePreview(enabled) => {
+                let changed = self.template_preview != enabled;
+                self.template_preview = enabled;
+                changed
+            }
+            CanvasCommand::TogglePanel(panel) => {
+                match panel {
+                    CanvasPanel::Comments => self.toggle_comments_panel(),
+                    CanvasPanel::Workflow => self.toggle_workflow_panel(),
+                    CanvasPanel::Stats => self.toggle_stats_panel(),
+                    CanvasPanel::Legend => self.toggle_legend(),
+                    CanvasPanel::Rename => self.toggle_rename_panel(),
+                    CanvasPanel::DataEntry => self.toggle_data_entry_panel(),
+                    CanvasPanel::Export => self.toggle_export_panel(),
+                    CanvasPanel::Print => self.toggle_print_panel(),
+                    CanvasPanel::Bookmarks => self.toggle_bookmarks_panel(),
+                    CanvasPanel::TabOrder => self.toggle_tab_order_panel(),
+                    CanvasPanel::OcrOrder => self.toggle_ocr_order_panel(),
+                    CanvasPanel::OcrWords => self.toggle_ocr_words(),
+                    CanvasPanel::FieldGroups => self.toggle_field_groups_panel(),
+                    CanvasPanel::Password => self.toggle_password_panel(),
+                    CanvasPanel::Store => self.toggle_store_panel(),
+                    CanvasPanel::Collaborate => self.toggle_collab_panel(),
+                }
+                true
+            }
+            CanvasCommand::OpenNewProject => {
+                self.open_new_project_panel();
+                true
+            }
+            CanvasCommand::BeginScreenshot => {
+                self.begin_screenshot();
+                true
+            }
+            CanvasCommand::SelectionBack => self.selection_back(),
+            CanvasCommand::SelectionForward => self.selection_forward(),
+            CanvasCommand::SetObjectHidden { layer, index, hidden } => {
+                let changed = match self.object_mut(layer, index) {
+                    Some(object) if object.is_hidden() != hidden => {
+                        object.set_hidden(hidden);
+                        true
+                    }
+                    _ => false,
+                };
+                if changed && hidden {
+                    self.deselect_object(layer, index);
+                }
+                changed
+            }
+            CanvasCommand::SetObjectLocked { layer, index, locked } => {
+                let changed = match self.object_mut(layer, index) {
+                    Some(object) if object.is_locked() != locked => {
+                        object.set_locked(locked);
+                        true
+                    }
+                    _ => false,
+                };
+                if changed && locked {
+                    self.deselect_object(layer, index);
+                }
+                changed
+            }
+            CanvasCommand::MoveObject { layer, index, dx, dy } => match self.object_mut(layer, index) {
+                Some(object) if !object.is_locked() && (dx != 0.0 || dy != 0.0) => {
+                    match object.translate(egui::Vec2::new(dx, dy)) {
+                        Ok(()) => true,
+                        Err(e) => {
+                            warn!("Could not move object: {}", e);
+                            false
+                        }
+                    }
+                }
+                _ => false,
+            },
+            CanvasCommand::DeleteObject { layer, index } => match layer {
+                LayerType::Shapes => self.delete_shape(index).is_some(),
+                LayerType::Detections => self.delete_detection(index).is_some(),
+                LayerType::Canvas | LayerType::Highlights | LayerType::Grid => false,
+            },
+            CanvasCommand::DuplicateShape(index) => self.duplicate_shape(index).is_some(),
+            CanvasCommand::BringToFront(index) => {
+                let changed = index + 1 < self.shapes.len();
+                self.bring_to_front(index);
+                changed
+            }
+            CanvasCommand::SendToBack(index) => {
+                let changed = index > 0 && index < self.shapes.len();
+                self.send_to_back(index);
+                changed
+            }
+        };
+
+        if changed {
+            debug!("Applied canvas command");
+            if self.changes.len() >= MAX_PENDING_CHANGES {
+                self.changes.remove(0);
+            }
+            self.changes.push(command);
+        }
+        changed
+    }
+
+    /// Shape or detection at `index` on `layer`
+    fn object_mut(&mut self, layer: LayerType, index: usize) -> Option<&mut Shape> {
+        match layer {
+            LayerType::Shapes => self.shapes.get_mut(index),
+            LayerType::Detections => self.detections.get_mut(index),
+            LayerType::Canvas | LayerType::Highlights | LayerType::Grid => None,
+        }
+    }
+
+    /// Drop the selection when it is the given object, which can no longer be picked
+    fn deselect_object(&mut self, layer: LayerType, index: usize) {
+        if layer == LayerType::Shapes && self.selected_shape == Some(index) {
+            self.selected_shape = None;
+        }
+    }
+
+    /// Take the commands that changed the canvas since the last call, oldest first
+    pub fn take_changes(&mut self) -> Vec<CanvasCommand> {
+        std::mem::take(&mut self.changes)
+    }
+}
@@ -0,0 +1,242 @@
+//! Comment threads on shapes and detections
+//!
+//! Threads are stored on the canvas and serialized with the project. They
+//! are surfaced as badges on the annotated objects and in a comments window.
+//! The window shows the threads of the selected shape, or of the detection
+//! or field it was opened for from a context menu or the data entry window.
+
+use super::core::DrawingCanvas;
+use crate::{Comment, CommentTarget, CommentThread, ImagePos};
+use egui::Color32;
+use tracing::{debug, instrument};
+
+/// Badge color for threads that still need attention
+const OPEN_BADGE_COLOR: Color32 = Color32::from_rgb(230, 126, 34);
+/// Badge color for objects whose threads are all resolved
+const RESOLVED_BADGE_COLOR: Color32 = Color32::from_rgb(150, 150, 150);
+
+impl DrawingCanvas {
+    /// Set the author name used for new comments
+    pub fn set_comment_author(&mut self, author: impl Into<String>) {
+        self.comment_author = author.into();
+    }
+
+    /// Toggle the comments window
+    pub fn toggle_comments_panel(&mut self) {
+        self.show_comments = !self.show_comments;
+        self.comment_target = None;
+    }
+
+    /// Open the comments window on the threads of a shape or detection
+    pub fn comment_on(&mut self, target: CommentTarget) {
+        debug!(%target, "Commenting on object");
+        self.show_comments = true;
+        self.comment_target = Some(target);
+        self.comment_reply_to = None;
+    }
+
+    /// Start a new comment thread on a shape or detection
+    ///
+    /// Returns the index of the new thread.
+    #[instrument(skip(self, body), fields(author = %self.comment_author))]
+    pub fn add_comment_thread(&mut self, target: CommentTarget, body: impl Into<String>) -> usize {
+        let comment = Comment::new(self.comment_author.clone(), body);
+        self.comment_threads.push(CommentThread::new(target, comment));
+        debug!(threads = self.comment_threads.len(), "Started comment thread");
+        self.comment_threads.len() - 1
+    }
+
+    /// Reply to an existing thread
+    ///
+    /// Returns false if the thread does not exist.
+    pub fn reply_to_thread(&mut self, thread: usize, body: impl Into<String>) -> bool {
+        let comment = Comment::new(self.comment_author.clone(), body);
+        match self.comment_threads.get_mut(thread) {
+            Some(t) => {
+                t.reply(comment);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Resolve or reopen a thread
+    ///
+    /// Returns false if the thread does not exist.
+    pub fn set_thread_resolved(&mut self, thread: usize, resolved: bool) -> bool {
+        match self.comment_threads.get_mut(thread) {
+            Some(t) => {
+                t.set_resolved(resolved);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Threads attached to a given object, with their indices
+    pub fn threads_for(&self, target: CommentTarget) -> impl Iterator<Item = (usize, &CommentThread)> {
+        self.comment_threads
+            .iter()
+            .enumerate()
+            .filter(move |(_, t)| t.target == target)
+    }
+
+    /// Number of threads that have not been resolved
+    pub fn unresolved_thread_count(&self) -> usize {
+        self.comment_threads.iter().filter(|t| !t.resolved).count()
+    }
+
+    /// Drop threads whose target no longer exists
+    pub(super) fn prune_comment_threads(&mut self) {
+        let shapes = self.shapes.len();
+        let detections = self.detections.len();
+        self.comment_threads.retain(|t| match t.target {
+            CommentTarget::Shape(idx) => idx < shapes,
+            CommentTarget::Detection(idx) => idx < detections,
+        });
+    }
+
+    /// Draw a badge on every object that has comments
+    ///
+    /// Shapes are in canvas coordinates; detections are mapped from image
+    /// pixels using the last recorded image transform.
+    pub(super) fn draw_comment_badges(&self, painter: &egui::Painter, to_screen: &egui::emath::TSTransform) {
+        let mut badges: Vec<(CommentTarget, usize, bool)> = Vec::new();
+        for thread in &self.comment_threads {
+            match badges.iter_mut().find(|(target, _, _)| *target == thread.target) {
+                Some((_, count, open)) => {
+                    *count += 1;
+                    *open |= !thread.resolved;
+                }
+                None => badges.push((thread.target, 1, !thread.resolved)),
+            }
+        }
+
+        for (target, count, open) in badges {
+            let anchor = match target {
+                CommentTarget::Shape(idx) => self.shapes.get(idx).map(|s| s.bounding_rect().right_top()),
+                CommentTarget::Detection(idx) => self.detections.get(idx).and_then(|d| {
+                    let corner = ImagePos(d.bounding_rect().right_top());
+                    self.coordinate_mapper.image_to_canvas(corner).map(|pos| pos.0)
+                }),
+            };
+            let Some(anchor) = anchor else {
+                continue;
+            };
+
+            let center = to_screen.mul_pos(anchor);
+            let color = if open { OPEN_BADGE_COLOR } else { RESOLVED_BADGE_COLOR };
+            painter.circle_filled(center, 8.0, color);
+            painter.text(
+                center,
+                egui::Align2::CENTER_CENTER,
+                count.to_string(),
+                egui::FontId::proportional(10.0),
+                Color32::WHITE,
+            );
+        }
+    }
+
+    /// Show the comments window
+    ///
+    /// Lists threads for the object the window was opened for, else for the
+    /// selected shape, or all threads when nothing is selected. Returns true
+    /// if the window was shown.
+    pub fn show_comments_panel(&mut self, ctx: &egui::Context) -> bool {
+        if !self.show_comments {
+            return false;
+        }
+
+        let selected = self.comment_target.or(self.selected_shape.map(CommentTarget::Shape));
+        let mut panel_open = true;
+        let mut resolve: Option<(usize, bool)> = None;
+
+        egui::Window::new("Comments")
+            .open(&mut panel_open)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Author:");
+                    ui.text_edit_singleline(&mut self.comment_author);
+                });
+                ui.separator();
+
+                let visible: Vec<usize> = self
+                    .comment_threads
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, t)| selected.is_none_or(|s| t.target == s))
+                    .map(|(i, _)| i)
+                    .collect();
+
+                if visible.is_empty() {
+                    ui.label("No comments");
+                }
+
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for idx in visible {
+                        let thread = &self.comment_threads[idx];
+                        ui.group(|ui| {
+                            ui.horizontal(|ui| {
+                                ui.strong(thread.target.to_string());
+                                if thread.resolved {
+                                    ui.weak("(resolved)");
+                                }
+                            });
+                            for comment in &thread.comments {
+                                ui.label(format!("{}: {}", comment.author, comment.body));
+                                if let Some(path) = &comment.attachment {
+                                    let name = path.file_name().unwrap_or(path.as_os_str()).to_string_lossy();
+                                    ui.weak(format!("📎 {}", name)).on_hover_text(path.display().to_string());
+                                }
+                            }
+                            ui.horizontal(|ui| {
+                                let label = if thread.resolved { "Reopen" } else { "Resolve" };
+                                if ui.button(label).clicked() {
+                                    resolve = Some((idx, !thread.resolved));
+                                }
+                                let replying = self.comment_reply_to == Some(idx);
+                                if ui.selectable_label(replying, "Reply").clicked() {
+                                    self.comment_reply_to = if replying { None } else { Some(idx) };
+                                }
+                            });
+                        });
+                    }
+                });
+
+                ui.separator();
+                let hint = match (self.comment_reply_to, selected) {
+                    (Some(_), _) => "Write a reply...".to_string(),
+                    (None, Some(target)) => format!("Comment on {}...", target),
+                    (None, None) => "Select a shape to comment on it".to_string(),
+                };
+                ui.add(egui::TextEdit::multiline(&mut self.comment_draft).hint_text(hint));
+
+                let can_post = !self.comment_draft.trim().is_empty()
+                    && (self.comment_reply_to.is_some() || selected.is_some());
+                if ui.add_enabled(can_post, egui::Button::new("Post")).clicked() {
+                    let body = std::mem::take(&mut self.comment_draft);
+                    match (self.comment_reply_to.take(), selected) {
+                        (Some(thread), _) => {
+                            self.reply_to_thread(thread, body);
+                        }
+                        (None, Some(target)) => {
+                            self.add_comment_thread(target, body);
+                        }
+                        (None, None) => {}
+                    }
+                }
+            });
+
+        if let Some((idx, resolved)) = resolve {
+            self.set_thread_resolved(idx, resolved);
+        }
+
+        if !panel_open {
+            self.show_comments = false;
+            self.comment_target = None;
+        }
+
+        true
+    }
+}
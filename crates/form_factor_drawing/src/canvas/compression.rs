@@ -0,0 +1,80 @@
+//! zstd compression of project files and detection caches
+//!
+//! Multi-page projects and their cached detections add up on network
+//! drives. With compression turned on in the canvas settings, project
+//! snapshots, including those written when autosave compacts its journal,
+//! and cached detections are written as zstd frames. A frame starts with
+//! [`ZSTD_MAGIC`], so loading recognizes compressed files by their first
+//! bytes and decodes them as a stream, and files written with compression
+//! off still open. Journal records are left uncompressed; they are small
+//! and appended one line at a time.
+
+use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas};
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::Read;
+#[cfg(not(target_arch = "wasm32"))]
+use tracing::debug;
+
+/// Bytes every zstd frame starts with
+pub const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Whether `bytes` start with a zstd frame
+pub fn is_compressed(bytes: &[u8]) -> bool {
+    bytes.starts_with(&ZSTD_MAGIC)
+}
+
+/// Compress `bytes` at zstd `level`
+///
+/// The frame carries a checksum, so corruption that would still decode is
+/// reported instead of loading altered content.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn compress(bytes: &[u8], level: i32) -> Result<Vec<u8>, CanvasError> {
+    let error = |e: std::io::Error| CanvasError::new(CanvasErrorKind::Compression(e.to_string()), line!(), file!());
+    let mut compressor = zstd::bulk::Compressor::new(level).map_err(error)?;
+    compressor.include_checksum(true).map_err(error)?;
+    let compressed = compressor.compress(bytes).map_err(error)?;
+    debug!(level, before = bytes.len(), after = compressed.len(), "Compressed");
+    Ok(compressed)
+}
+
+/// Compress `bytes` at zstd `level`
+///
+/// zstd is a C library and isn't built for the browser.
+#[cfg(target_arch = "wasm32")]
+pub fn compress(_bytes: &[u8], _level: i32) -> Result<Vec<u8>, CanvasError> {
+    Err(unavailable())
+}
+
+/// Reader decompressing the zstd stream in `bytes`
+#[cfg(not(target_arch = "wasm32"))]
+pub(super) fn decompressor(bytes: &[u8]) -> Result<impl Read + '_, CanvasError> {
+    zstd::stream::read::Decoder::with_buffer(bytes)
+        .map_err(|e| CanvasError::new(CanvasErrorKind::Compression(e.to_string()), line!(), file!()))
+}
+
+/// Reader decompressing the zstd stream in `bytes`
+#[cfg(target_arch = "wasm32")]
+pub(super) fn decompressor(_bytes: &[u8]) -> Result<std::io::Empty, CanvasError> {
+    Err(unavailable())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn unavailable() -> CanvasError {
+    CanvasError::new(
+        CanvasErrorKind::Compression("zstd is unavailable in the browser".to_string()),
+        line!(),
+        file!(),
+    )
+}
+
+impl DrawingCanvas {
+    /// `bytes`, compressed if compression is on in the canvas settings
+    pub(super) fn maybe_compress(&self, bytes: Vec<u8>) -> Result<Vec<u8>, CanvasError> {
+        let settings = self.canvas_settings.compression;
+        if settings.enabled {
+            compress(&bytes, settings.level)
+        } else {
+            Ok(bytes)
+        }
+    }
+}
@@ -0,0 +1,447 @@
+//! Save conflicts on project files shared between operators
+//!
+//! Two operators can open the same project file on a network share. The
+//! project's journal remembers the revision of the file it last read or
+//! wrote, and saves check it first. When someone else saved in between,
+//! the save fails with [`CanvasErrorKind::SaveConflict`] and the canvas
+//! opens a [`SaveConflict`] holding their version. Projects in the project
+//! store work the same way, failing with a
+//! [`StoreErrorKind::Conflict`](crate::StoreErrorKind::Conflict) instead. The conflict window
+//! lists their changes and offers to merge, to overwrite their version, or
+//! to keep editing without saving, in which case the next save asks again.
+//!
+//! Merging is three-way against the project as last saved. A field only
+//! one side changed takes that side's value, and a list both sides only
+//! appended to, such as the shapes when both operators drew new fields,
+//! keeps their additions followed by yours. Your additions move up past
+//! theirs, and the comments, tags, OCR text and orders that refer to
+//! them by index move with them. Where both sides changed a field in any
+//! other way, your value wins and the field is listed as overwritten. The
+//! effort statistics add up instead: their totals plus what you added.
+
+use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas};
+use super::diff::ProjectDiff;
+use super::journal::{project_map, read_project};
+use crate::{SessionStats, StoreError};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use tracing::{debug, error, info, instrument, warn};
+
+/// Where both operators saved the project
+#[derive(Debug, Clone)]
+enum ConflictSource {
+    /// Project file
+    File(PathBuf),
+    /// Project in the configured project store
+    Store {
+        /// Name the project is stored under
+        name: String,
+        /// Version the other operator saved
+        version: String,
+    },
+}
+
+/// Another operator's version of a project that a save would have overwritten
+#[derive(Debug, Clone)]
+pub struct SaveConflict {
+    /// Where both operators saved the project
+    source: ConflictSource,
+    /// Project as this canvas last saved or opened it
+    base: Map<String, Value>,
+    /// Project as the other operator saved it
+    theirs: Map<String, Value>,
+    /// Changes the other operator made since the base
+    their_changes: ProjectDiff,
+}
+
+impl SaveConflict {
+    /// Project file both operators saved to, or `None` for a stored project
+    pub fn path(&self) -> Option<&Path> {
+        match &self.source {
+            ConflictSource::File(path) => Some(path),
+            ConflictSource::Store { .. } => None,
+        }
+    }
+
+    /// Name of the stored project both operators saved, or `None` for a project file
+    pub fn stored_project(&self) -> Option<&str> {
+        match &self.source {
+            ConflictSource::File(_) => None,
+            ConflictSource::Store { name, .. } => Some(name),
+        }
+    }
+
+    /// Path or name of the project, for display
+    fn name(&self) -> String {
+        match &self.source {
+            ConflictSource::File(path) => path.display().to_string(),
+            ConflictSource::Store { name, .. } => format!("Stored project \"{}\"", name),
+        }
+    }
+
+    /// Changes the other operator made since this canvas last saved or opened the project
+    pub fn their_changes(&self) -> &ProjectDiff {
+        &self.their_changes
+    }
+
+    /// Conflict between the project as last saved, `base`, and the other operator's version
+    fn new(source: ConflictSource, base: Map<String, Value>, mut their_canvas: DrawingCanvas) -> Result<Self, CanvasError> {
+        let theirs = project_map(&their_canvas)?;
+        let mut base_canvas = from_map(base.clone())?;
+        base_canvas.project_name = "Last saved".to_string();
+        their_canvas.project_name = match source {
+            ConflictSource::File(_) => "On disk",
+            ConflictSource::Store { .. } => "In the store",
+        }
+        .to_string();
+        let their_changes = ProjectDiff::compare(&base_canvas, &their_canvas);
+        let (added, removed, moved) = their_changes.counts();
+        debug!(added, removed, moved, "Compared the other operator's version");
+        Ok(Self {
+            source,
+            base,
+            theirs,
+            their_changes,
+        })
+    }
+}
+
+/// Three-way merge of two versions of a project with the version both started from
+///
+/// Returns the merged project and the fields both sides changed, where
+/// `ours` was kept.
+pub(super) fn merge_projects(
+    base: &Map<String, Value>,
+    ours: &Map<String, Value>,
+    theirs: &Map<String, Value>,
+) -> (Map<String, Value>, Vec<String>) {
+    let ours = &shift_our_additions(base, ours, theirs);
+    let mut merged = theirs.clone();
+    let mut overwritten = Vec::new();
+    for (key, our_value) in ours {
+        let base_value = base.get(key);
+        if base_value == Some(our_value) {
+            continue;
+        }
+        let their_value = theirs.get(key);
+        let value = match (base_value, our_value, their_value) {
+            (_, _, Some(their_value)) if their_value == our_value || Some(their_value) == base_value => {
+                our_value.clone()
+            }
+            (Some(Value::Array(base)), Value::Array(our), Some(Value::Array(their)))
+                if our.starts_with(base) && their.starts_with(base) =>
+            {
+                Value::Array(their.iter().chain(&our[base.len()..]).cloned().collect())
+            }
+            (base_value, our_value, Some(their_value)) if key == "session_stats" => {
+                merge_session_stats(base_value, our_value, their_value)
+            }
+            _ => {
+                overwritten.push(key.clone());
+                our_value.clone()
+            }
+        };
+        merged.insert(key.clone(), value);
+    }
+    (merged, overwritten)
+}
+
+/// Effort statistics as the other side saved them, plus what this side added since `base`
+///
+/// Both operators' time and edits count, so the statistics never conflict.
+fn merge_session_stats(base: Option<&Value>, ours: &Value, theirs: &Value) -> Value {
+    let stats = |value: &Value| serde_json::from_value::<SessionStats>(value.clone()).ok();
+    let base = base.and_then(stats).unwrap_or_default();
+    let merged = match (stats(ours), stats(theirs)) {
+        (Some(our_stats), Some(their_stats)) => serde_json::to_value(our_stats.merged(&base, &their_stats)).ok(),
+        _ => None,
+    };
+    merged.unwrap_or_else(|| ours.clone())
+}
+
+/// Where our additions to a list start, and how far they move because both sides appended to it
+#[derive(Debug, Clone, Copy)]
+struct Shift {
+    /// Length of the list as both sides started from it
+    from: usize,
+    /// Number of elements the other side appended
+    by: usize,
+}
+
+impl Shift {
+    /// Shift of `key` when the merge will put our additions after theirs
+    fn of(key: &str, base: &Map<String, Value>, ours: &Map<String, Value>, theirs: &Map<String, Value>) -> Option<Self> {
+        match (base.get(key), ours.get(key), theirs.get(key)) {
+            (Some(Value::Array(base)), Some(Value::Array(our)), Some(Value::Array(their)))
+                if our != their && our.len() > base.len() && our.starts_with(base) && their.starts_with(base) =>
+            {
+                Some(Self {
+                    from: base.len(),
+                    by: their.len() - base.len(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Move the index in `value` if it refers to one of our additions
+    fn apply(shift: Option<Self>, value: &mut Value) {
+        if let (Some(shift), Some(index)) = (shift, value.as_u64())
+            && index as usize >= shift.from
+        {
+            *value = Value::from(index as usize + shift.by);
+        }
+    }
+}
+
+/// `ours` with the indices of our added shapes and detections moved past the other side's additions
+///
+/// Lists both sides appended to merge as theirs followed by ours, so our
+/// additions end up at higher indices than we gave them. The tables that
+/// refer to shapes and detections by index follow them.
+fn shift_our_additions(
+    base: &Map<String, Value>,
+    ours: &Map<String, Value>,
+    theirs: &Map<String, Value>,
+) -> Map<String, Value> {
+    let shapes = Shift::of("shapes", base, ours, theirs);
+    let detections = Shift::of("detections", base, ours, theirs);
+    let mut ours = ours.clone();
+    if shapes.is_none() && detections.is_none() {
+        return ours;
+    }
+
+    let mut each = |key: &str, shift_entry: &dyn Fn(&mut Value)| {
+        if let Some(Value::Array(entries)) = ours.get_mut(key) {
+            entries.iter_mut().for_each(shift_entry);
+        }
+    };
+    each("tab_order", &|index| Shift::apply(shapes, index));
+    each("ocr_order", &|index| Shift::apply(detections, index));
+    // (detection index, value) pairs
+    for key in ["pii_flags", "detection_tags"] {
+        each(key, &|pair| {
+            if let Some(index) = pair.get_mut(0) {
+                Shift::apply(detections, index);
+            }
+        });
+    }
+    each("ocr_records", &|record| {
+        if let Some(index) = record.get_mut("detection") {
+            Shift::apply(detections, index);
+        }
+    });
+    each("comment_threads", &|thread| {
+        if let Some(index) = thread.pointer_mut("/target/Shape") {
+            Shift::apply(shapes, index);
+        } else if let Some(index) = thread.pointer_mut("/target/Detection") {
+            Shift::apply(detections, index);
+        }
+    });
+    ours
+}
+
+/// Project deserialized from its JSON object
+fn from_map(project: Map<String, Value>) -> Result<DrawingCanvas, CanvasError> {
+    DrawingCanvas::from_json(&Value::Object(project).to_string())
+}
+
+fn store_error(e: StoreError) -> CanvasError {
+    CanvasError::new(CanvasErrorKind::Store(e.kind.to_string()), line!(), file!())
+}
+
+impl DrawingCanvas {
+    /// Open the conflict window if `result` failed with a save conflict, passing the result on
+    pub(super) fn note_save_conflict<T>(&mut self, result: Result<T, CanvasError>) -> Result<T, CanvasError> {
+        if let Err(e) = &result
+            && matches!(e.kind, CanvasErrorKind::SaveConflict(_))
+            && self.save_conflict.is_none()
+        {
+            match self.load_save_conflict() {
+                Ok(conflict) => self.save_conflict = Some(conflict),
+                Err(e) => warn!("Could not read the conflicting project file: {}", e),
+            }
+        }
+        result
+    }
+
+    /// Read the other operator's version of the project file
+    fn load_save_conflict(&self) -> Result<SaveConflict, CanvasError> {
+        let journal = self
+            .journal
+            .as_ref()
+            .ok_or_else(|| CanvasError::new(CanvasErrorKind::NoProjectFile, line!(), file!()))?;
+        let path = journal.project_path().to_path_buf();
+        let (their_canvas, _) = read_project(&path, self.project_password.as_deref())?;
+        let conflict = SaveConflict::new(ConflictSource::File(path), journal.saved().clone(), their_canvas)?;
+        info!(project = conflict.name(), "Project file was changed by another operator");
+        Ok(conflict)
+    }
+
+    /// Open the conflict window for the stored project `name`, which someone else saved since it was opened
+    pub(super) fn note_store_conflict(&mut self, name: &str) {
+        if self.save_conflict.is_some() {
+            return;
+        }
+        match self.load_store_conflict(name) {
+            Ok(conflict) => self.save_conflict = Some(conflict),
+            Err(e) => warn!("Could not read the conflicting stored project: {}", e),
+        }
+    }
+
+    /// Read the other operator's version of the stored project `name`
+    fn load_store_conflict(&self, name: &str) -> Result<SaveConflict, CanvasError> {
+        let base = self
+            .stored_project
+            .as_ref()
+            .map(|stored| stored.saved.clone())
+            .ok_or_else(|| CanvasError::new(CanvasErrorKind::NoProjectFile, line!(), file!()))?;
+        let (bytes, version) = self
+            .storage
+            .open()
+            .and_then(|store| store.read_project_versioned(name))
+            .map_err(store_error)?;
+        let their_canvas = match &self.project_password {
+            Some(password) => DrawingCanvas::from_protected_bytes(&bytes, password)?,
+            None => DrawingCanvas::from_bytes(&bytes)?,
+        };
+        let source = ConflictSource::Store {
+            name: name.to_string(),
+            version,
+        };
+        let conflict = SaveConflict::new(source, base, their_canvas)?;
+        info!(project = conflict.name(), "Stored project was changed by another operator");
+        Ok(conflict)
+    }
+
+    /// Resolve the open save conflict by merging both versions and saving the result
+    ///
+    /// See the module docs for how the versions are merged. Returns the
+    /// fields both sides changed, where this canvas's value was kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the merged project can't be read back or saved.
+    /// The conflict stays open.
+    #[instrument(skip(self, ctx))]
+    pub fn merge_save_conflict(&mut self, ctx: &egui::Context) -> Result<Vec<String>, CanvasError> {
+        let Some(conflict) = self.save_conflict.clone() else {
+            return Ok(Vec::new());
+        };
+        let (merged, overwritten) = merge_projects(&conflict.base, &project_map(self)?, &conflict.theirs);
+        let merged = from_map(merged)?;
+
+        // The merged project holds their changes, so it can be written over their version
+        match &conflict.source {
+            ConflictSource::File(path) => {
+                self.apply_project(merged, ctx, true);
+                self.save_to_file(&path.to_string_lossy())?;
+            }
+            ConflictSource::Store { name, version } => {
+                self.apply_project(merged, ctx, false);
+                self.save_over_stored(name, Some(version)).map_err(store_error)?;
+            }
+        }
+        self.save_conflict = None;
+        info!(project = conflict.name(), ?overwritten, "Merged conflicting project versions");
+        Ok(overwritten)
+    }
+
+    /// Resolve the open save conflict by saving this canvas's version over the other operator's
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project can't be written. The conflict stays open.
+    #[instrument(skip(self))]
+    pub fn overwrite_save_conflict(&mut self) -> Result<(), CanvasError> {
+        let Some(source) = self.save_conflict.as_ref().map(|c| c.source.clone()) else {
+            return Ok(());
+        };
+        match (source, self.journal.take()) {
+            (ConflictSource::Store { name, version }, _) => {
+                self.save_over_stored(&name, Some(&version)).map_err(store_error)?;
+            }
+            (ConflictSource::File(_), Some(mut journal)) => {
+                let result = journal.write_snapshot(self);
+                self.journal = Some(journal);
+                result?;
+            }
+            // A failed merge leaves the project without a journal, and nothing to check against
+            (ConflictSource::File(path), None) => self.save_to_file(&path.to_string_lossy())?,
+        }
+        self.save_conflict = None;
+        info!("Overwrote the conflicting project file");
+        Ok(())
+    }
+
+    /// Close the conflict window without saving
+    ///
+    /// The next save finds the conflict again.
+    pub fn cancel_save_conflict(&mut self) -> Option<SaveConflict> {
+        self.save_conflict.take()
+    }
+
+    /// Show the save conflict window
+    ///
+    /// Returns true if the window was shown.
+    pub fn show_save_conflict_panel(&mut self, ctx: &egui::Context) -> bool {
+        let Some(conflict) = &self.save_conflict else {
+            return false;
+        };
+
+        let mut merge = false;
+        let mut overwrite = false;
+        let mut compare = false;
+        let mut cancel = false;
+        egui::Window::new("Save Conflict")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!("{} was changed by someone else since you opened it.", conflict.name()));
+                let changes = conflict.their_changes();
+                let (added, removed, moved) = changes.counts();
+                ui.label(format!(
+                    "Their changes: {} added, {} removed, {} moved, {} field changes",
+                    added,
+                    removed,
+                    moved,
+                    changes.field_changes().len()
+                ));
+                ui.separator();
+                ui.horizontal(|ui| {
+                    merge = ui
+                        .button("Merge")
+                        .on_hover_text("Keep their changes and yours; yours win where both changed the same thing")
+                        .clicked();
+                    overwrite = ui
+                        .button("Overwrite")
+                        .on_hover_text("Save your version and discard their changes")
+                        .clicked();
+                    compare = ui.button("Show Their Changes").clicked();
+                    cancel = ui.button("Cancel").on_hover_text("Keep editing without saving").clicked();
+                });
+            });
+
+        if compare {
+            let changes = conflict.their_changes.clone();
+            self.start_project_diff(changes);
+        }
+        if merge {
+            match self.merge_save_conflict(ctx) {
+                Ok(overwritten) if !overwritten.is_empty() => {
+                    warn!("Merge kept your version of: {}", overwritten.join(", "));
+                }
+                Ok(_) => {}
+                Err(e) => error!("Failed to merge project versions: {}", e),
+            }
+        } else if overwrite {
+            if let Err(e) = self.overwrite_save_conflict() {
+                error!("Failed to overwrite project: {}", e);
+            }
+        } else if cancel {
+            self.cancel_save_conflict();
+        }
+
+        true
+    }
+}
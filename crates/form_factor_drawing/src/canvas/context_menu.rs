@@ -0,0 +1,366 @@
+//! Right-click context menus on the canvas
+//!
+//! Right-clicking a shape, a detection, or empty canvas opens a menu of
+//! actions for that object. Edits to single shapes and detections (delete,
+//! duplicate, reorder) are applied as [`CanvasCommand`]s, copy/paste
+//! directly; actions that need the
+//! application (file dialogs, detection, OCR) are queued as
+//! [`CanvasAction`]s for the application to route to its existing handlers.
+
+use crate::{CanvasPos, CommentTarget, LayerType, ScreenPos, Shape};
+use egui::Pos2;
+use tracing::{debug, instrument, warn};
+
+use super::command::CanvasCommand;
+use super::core::DrawingCanvas;
+
+/// Offset applied to duplicated and pasted shapes so they don't cover the original
+const DUPLICATE_OFFSET: egui::Vec2 = egui::vec2(10.0, 10.0);
+
+/// Canvas action that the application carries out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanvasAction {
+    /// Pick a form image and load it onto the canvas
+    LoadImage,
+    /// Detect text regions in the form image
+    DetectText,
+    /// Detect logos in the form image
+    DetectLogos,
+    /// Recognize text in the detections
+    RunOcr,
+    /// Pick a file and export the canvas as PNG
+    ExportPng,
+    /// Pick a file and export the canvas as SVG
+    ExportSvg,
+    /// Pick a file and export shape and detection geometry as GeoJSON
+    ExportGeoJson,
+    /// Pick a file and export the text read from the detections
+    ExportText,
+    /// Pick a file and print the canvas to PDF
+    ExportPdf,
+    /// Pick a file and save the captured screenshot as PNG
+    SaveScreenshot,
+    /// Pick a folder and export the instances checked in the batch review
+    ExportInstances,
+}
+
+impl DrawingCanvas {
+    /// Take the actions queued by the context menus since the last call
+    pub fn take_actions(&mut self) -> Vec<CanvasAction> {
+        std::mem::take(&mut self.pending_actions)
+    }
+
+    /// Remove a shape, keeping selection and comments attached to the right shapes
+    #[instrument(skip(self), fields(shapes = self.shapes.len()))]
+    pub fn delete_shape(&mut self, index: usize) -> Option<Shape> {
+        if index >= self.shapes.len() {
+            return None;
+        }
+        let removed = self.shapes.remove(index);
+        self.remap_shape_indices(|i| match i.cmp(&index) {
+            std::cmp::Ordering::Less => Some(i),
+            std::cmp::Ordering::Equal => None,
+            std::cmp::Ordering::Greater => Some(i - 1),
+        });
+        debug!(name = removed.name(), "Deleted shape");
+        Some(removed)
+    }
+
+    /// Insert a copy of a shape above the original, returning the copy's index
+    pub fn duplicate_shape(&mut self, index: usize) -> Option<usize> {
+        let shape = self.shapes.get(index)?.clone();
+        self.insert_copy(shape)
+    }
+
+    /// Move a shape above all others
+    pub fn bring_to_front(&mut self, index: usize) {
+        let last = self.shapes.len().saturating_sub(1);
+        if index >= last {
+            return;
+        }
+        let shape = self.shapes.remove(index);
+        self.shapes.push(shape);
+        self.remap_shape_indices(|i| match i.cmp(&index) {
+            std::cmp::Ordering::Less => Some(i),
+            std::cmp::Ordering::Equal => Some(last),
+            std::cmp::Ordering::Greater => Some(i - 1),
+        });
+    }
+
+    /// Move a shape below all others
+    pub fn send_to_back(&mut self, index: usize) {
+        if index == 0 || index >= self.shapes.len() {
+            return;
+        }
+        let shape = self.shapes.remove(index);
+        self.shapes.insert(0, shape);
+        self.remap_shape_indices(|i| match i.cmp(&index) {
+            std::cmp::Ordering::Less => Some(i + 1),
+            std::cmp::Ordering::Equal => Some(0),
+            std::cmp::Ordering::Greater => Some(i),
+        });
+    }
+
+    /// Remove a detection, keeping comments, PII flags, subtype tags and OCR text attached to the right detections
+    #[instrument(skip(self), fields(detections = self.detections.len()))]
+    pub fn delete_detection(&mut self, index: usize) -> Option<Shape> {
+        if index >= self.detections.len() {
+            return None;
+        }
+        let removed = self.detections.remove(index);
+        self.stop_detection_flashes();
+        let remap = |i: usize| match i.cmp(&index) {
+            std::cmp::Ordering::Less => Some(i),
+            std::cmp::Ordering::Equal => None,
+            std::cmp::Ordering::Greater => Some(i - 1),
+        };
+        self.pii_flags = std::mem::take(&mut self.pii_flags)
+            .into_iter()
+            .filter_map(|(i, kind)| remap(i).map(|i| (i, kind)))
+            .collect();
+        self.detection_tags = std::mem::take(&mut self.detection_tags)
+            .into_iter()
+            .filter_map(|(i, name)| remap(i).map(|i| (i, name)))
+            .collect();
+        self.ocr_records
+            .retain_mut(|record| remap(record.detection).map(|i| record.detection = i).is_some());
+        self.ocr_order = self.ocr_order.iter().filter_map(|i| remap(*i)).collect();
+        self.selected_ocr_word = self
+            .selected_ocr_word
+            .and_then(|(detection, word)| Some((remap(detection)?, word)));
+        self.selected_detections = self.selected_detections.iter().filter_map(|i| remap(*i)).collect();
+        if let Some(edit) = &mut self.ocr_edit {
+            match remap(edit.detection) {
+                Some(i) => edit.detection = i,
+                None => self.ocr_edit = None,
+            }
+        }
+        self.selection_history.remap(|target| match target {
+            CommentTarget::Detection(i) => remap(i).map(CommentTarget::Detection),
+            CommentTarget::Shape(_) => Some(target),
+        });
+        if let Some(CommentTarget::Detection(i)) = self.comment_target {
+            self.comment_target = remap(i).map(CommentTarget::Detection);
+        }
+        self.comment_threads.retain_mut(|thread| match thread.target {
+            CommentTarget::Detection(i) => remap(i).map(|i| thread.target = CommentTarget::Detection(i)).is_some(),
+            CommentTarget::Shape(_) => true,
+        });
+        debug!(name = removed.name(), "Deleted detection");
+        Some(removed)
+    }
+
+    /// Copy a shape to the canvas clipboard
+    pub fn copy_shape(&mut self, index: usize) {
+        self.shape_clipboard = self.shapes.get(index).cloned();
+    }
+
+    /// Copy a detection to the canvas clipboard as a shape in canvas coordinates
+    pub fn copy_detection(&mut self, index: usize) {
+        let Some(detection) = self.detections.get(index) else {
+            return;
+        };
+        if let Some(shape) = self.coordinate_mapper.image_shape_to_canvas(detection) {
+            self.shape_clipboard = Some(shape);
+        }
+    }
+
+    /// Whether the canvas clipboard holds a shape
+    pub fn can_paste(&self) -> bool {
+        self.shape_clipboard.is_some()
+    }
+
+    /// Add the clipboard shape to the canvas, returning its index
+    ///
+    /// With a position, the pasted shape is centered there; otherwise it is
+    /// offset slightly from where it was copied.
+    pub fn paste_shape(&mut self, at: Option<Pos2>) -> Option<usize> {
+        let mut shape = self.shape_clipboard.clone()?;
+        if let Some(at) = at {
+            let delta = at - shape.bounding_rect().center() - DUPLICATE_OFFSET;
+            if let Err(e) = shape.translate(delta) {
+                warn!("Could not move pasted shape: {}", e);
+            }
+        }
+        self.insert_copy(shape)
+    }
+
+    /// Add an offset copy of a shape on top and select it
+    fn insert_copy(&mut self, mut shape: Shape) -> Option<usize> {
+        if let Err(e) = shape.translate(DUPLICATE_OFFSET) {
+            warn!("Could not offset copied shape: {}", e);
+            return None;
+        }
+        let name = format!("{} copy", shape.name());
+        shape.set_name(name);
+        self.shapes.push(shape);
+        let index = self.shapes.len() - 1;
+        self.set_selected_shape(Some(index));
+        self.selected_layer = Some(LayerType::Shapes);
+        Some(index)
+    }
+
+    /// Update selection, tab order and comment targets after shapes were removed or reordered
+    fn remap_shape_indices(&mut self, remap: impl Fn(usize) -> Option<usize>) {
+        self.selected_shape = self.selected_shape.and_then(&remap);
+        self.tab_order = self.tab_order.iter().filter_map(|i| remap(*i)).collect();
+        self.selection_history.remap(|target| match target {
+            CommentTarget::Shape(i) => remap(i).map(CommentTarget::Shape),
+            CommentTarget::Detection(_) => Some(target),
+        });
+        if let Some(CommentTarget::Shape(i)) = self.comment_target {
+            self.comment_target = remap(i).map(CommentTarget::Shape);
+        }
+        if self.selected_shape.is_none() {
+            self.show_properties = false;
+        }
+        self.comment_threads.retain_mut(|thread| match thread.target {
+            CommentTarget::Shape(i) => remap(i).map(|i| thread.target = CommentTarget::Shape(i)).is_some(),
+            CommentTarget::Detection(_) => true,
+        });
+    }
+
+    /// Topmost visible shape or detection under a canvas position
+    pub(super) fn object_at(&self, pos: Pos2) -> Option<CommentTarget> {
+        if self.layer_manager.is_visible(LayerType::Shapes)
+            && let Some(idx) = self
+                .shapes
+                .iter()
+                .rposition(|shape| !shape.is_hidden() && shape.contains_point(pos))
+        {
+            return Some(CommentTarget::Shape(idx));
+        }
+        if self.layer_manager.is_visible(LayerType::Detections)
+            && let Some(image_pos) = self.coordinate_mapper.canvas_to_image(CanvasPos(pos))
+            && let Some(idx) = self
+                .detections
+                .iter()
+                .enumerate()
+                .rev()
+                .find(|(idx, d)| !d.is_hidden() && self.passes_subtype_filter(*idx) && d.contains_point(image_pos.0))
+                .map(|(idx, _)| idx)
+        {
+            return Some(CommentTarget::Detection(idx));
+        }
+        None
+    }
+
+    /// Open the context menu for whatever is under the pointer on right-click
+    pub(super) fn handle_context_menu(&mut self, response: &egui::Response) {
+        if response.secondary_clicked()
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            let canvas_pos = self.coordinate_mapper.screen_to_canvas(ScreenPos(pos)).0;
+            self.context_menu_target = self.object_at(canvas_pos);
+            self.context_menu_pos = canvas_pos;
+            if let Some(target @ CommentTarget::Detection(_)) = self.context_menu_target {
+                self.record_selection(target);
+            }
+            debug!(target = ?self.context_menu_target, "Context menu opened");
+        }
+
+        response.context_menu(|ui| match self.context_menu_target {
+            Some(CommentTarget::Shape(idx)) => self.shape_menu(ui, idx),
+            Some(CommentTarget::Detection(idx)) => self.detection_menu(ui, idx),
+            None => self.canvas_menu(ui),
+        });
+    }
+
+    fn shape_menu(&mut self, ui: &mut egui::Ui, idx: usize) {
+        if let Some(shape) = self.shapes.get(idx) {
+            ui.label(egui::RichText::new(shape.name()).strong());
+            ui.separator();
+        }
+        if ui.button("Assign to Field...").clicked() {
+            self.set_selected_shape(Some(idx));
+            self.selected_layer = Some(LayerType::Shapes);
+            self.show_properties = true;
+            self.focus_name_field = true;
+        }
+        let duplicate_shortcut =
+            ui.ctx().format_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::D));
+        if ui.add(egui::Button::new("Duplicate").shortcut_text(duplicate_shortcut)).clicked() {
+            self.apply_command(CanvasCommand::DuplicateShape(idx));
+        }
+        if ui.button("Repeat in Grid...").clicked() {
+            self.open_repeat_panel(idx);
+        }
+        if ui.button("Copy").clicked() {
+            self.copy_shape(idx);
+        }
+        if ui.button("Comment…").clicked() {
+            self.comment_on(CommentTarget::Shape(idx));
+        }
+        ui.separator();
+        if ui.button("Bring to Front").clicked() {
+            self.apply_command(CanvasCommand::BringToFront(idx));
+        }
+        if ui.button("Send to Back").clicked() {
+            self.apply_command(CanvasCommand::SendToBack(idx));
+        }
+        ui.separator();
+        if ui.button("Delete").clicked() {
+            self.apply_command(CanvasCommand::DeleteObject {
+                layer: LayerType::Shapes,
+                index: idx,
+            });
+        }
+    }
+
+    fn detection_menu(&mut self, ui: &mut egui::Ui, idx: usize) {
+        if let Some(detection) = self.detections.get(idx) {
+            ui.label(egui::RichText::new(detection.name()).strong());
+            ui.separator();
+        }
+        self.word_menu(ui, idx);
+        if ui.button("Run OCR").clicked() {
+            self.pending_actions.push(CanvasAction::RunOcr);
+        }
+        if ui.button("Copy as Shape").clicked() {
+            self.copy_detection(idx);
+        }
+        if ui.button("Comment…").clicked() {
+            self.comment_on(CommentTarget::Detection(idx));
+        }
+        ui.separator();
+        self.split_merge_menu(ui, idx);
+        ui.separator();
+        self.subtype_menu(ui, idx);
+        ui.separator();
+        if ui.button("Delete Detection").clicked() {
+            self.apply_command(CanvasCommand::DeleteObject {
+                layer: LayerType::Detections,
+                index: idx,
+            });
+        }
+    }
+
+    fn canvas_menu(&mut self, ui: &mut egui::Ui) {
+        if ui.add_enabled(self.can_paste(), egui::Button::new("Paste")).clicked() {
+            self.paste_shape(Some(self.context_menu_pos));
+        }
+        if ui.button("Load Image...").clicked() {
+            self.pending_actions.push(CanvasAction::LoadImage);
+        }
+        ui.separator();
+        let has_image = self.form_image_path.is_some();
+        if ui.add_enabled(has_image, egui::Button::new("Detect Text")).clicked() {
+            self.pending_actions.push(CanvasAction::DetectText);
+        }
+        if ui.add_enabled(has_image, egui::Button::new("Detect Logos")).clicked() {
+            self.pending_actions.push(CanvasAction::DetectLogos);
+        }
+        if !self.guides.is_empty() {
+            ui.separator();
+            if ui.button("Clear Guides").clicked() {
+                self.clear_guides();
+            }
+        }
+        if self.selected_detection_subtype.is_some() {
+            ui.separator();
+            if ui.button("Show All Detections").clicked() {
+                self.set_selected_detection_subtype(None);
+            }
+        }
+    }
+}
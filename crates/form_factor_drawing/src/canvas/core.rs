@@ -1,9 +1,50 @@
 //! Core canvas state and error types
 
-use crate::{LayerManager, LayerType, Shape, ToolMode};
+use super::batch_review::BatchReview;
+use super::bookmarks::ViewBookmark;
+use super::diff::ProjectDiff;
+use super::command::CanvasCommand;
+use super::conflict::SaveConflict;
+use super::context_menu::CanvasAction;
+use super::drop::DroppedFile;
+use super::encryption::PendingUnlock;
+use super::export::ExportFormat;
+use super::guides::Guide;
+use super::highlights::Highlight;
+use super::image_load::PendingImage;
+use super::journal::ProjectJournal;
+use super::navigation::SelectionHistory;
+use super::ocr_edit::OcrEdit;
+use super::ocr_pass::OcrRecord;
+use super::print::PrintOptions;
+use super::project_template::ProjectTemplate;
+use super::provenance::Provenance;
+use super::rename::RenameQuery;
+use super::repeat::RepeatGrid;
+use super::screenshot::ScreenshotStage;
+use super::stored_projects::StoredProject;
+use super::tab_order::ReadingDirection;
+use super::template_browser::{TemplateFilter, TemplateSortOrder};
+use super::template_load::PendingTemplates;
+use super::template_page::PageMatch;
+use super::text_export::TextExportOptions;
+use super::textures::{MB, TextureCache};
+use crate::{
+    CanvasPos, CanvasSettings, CollabSession, CommentTarget, CommentThread, CoordinateMapper, DetectionKind, DetectionSettings, FieldGroup,
+    ImagePos, LayerManager, LayerType, NamingScheme, OcrSample, OcrSettings, PiiKind, Presence, SessionStats, Settings, Shape,
+    Stamp, StorageSettings, StoreSyncTransport, ToolMode, ToolRegistry, Workflow,
+};
 use derive_getters::Getters;
+use form_factor_core::{Animations, IoOperation, StatusBar};
 use egui::{Color32, Pos2, Stroke};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// Version of the project file format written by this build
+///
+/// Files without a version predate versioning and load as version 0.
+pub const PROJECT_FORMAT_VERSION: u32 = 1;
 
 /// Default zoom level for new canvases
 pub(super) fn default_zoom_level() -> f32 {
@@ -13,41 +54,98 @@ pub(super) fn default_zoom_level() -> f32 {
 /// Kinds of errors that can occur in canvas operations
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CanvasErrorKind {
-    /// Failed to load image file
+    /// Failed to decode or encode an image file
     ImageLoad(String),
-    /// Failed to read file from disk
-    FileRead(String),
-    /// Failed to write file to disk
-    FileWrite(String),
+    /// File system operation failed
+    Io(std::io::ErrorKind),
     /// Failed to serialize data to JSON
     Serialization(String),
     /// Failed to deserialize data from JSON
     Deserialization(String),
     /// Operation requires a form image but none is loaded
     NoFormImageLoaded,
-    /// Text detection operation failed
-    TextDetection(String),
-    /// Logo detection operation failed
-    LogoDetection(String),
+    /// Text or logo detection failed
+    #[cfg(all(any(feature = "text-detection", feature = "logo-detection"), not(target_arch = "wasm32")))]
+    Cv(form_factor_cv::CvError),
+    /// No logo templates were found in the given directory
+    NoLogoTemplates(String),
     /// No recent projects found
     NoRecentProjects,
     /// OCR text extraction failed
-    OCRFailed(String),
+    #[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
+    Ocr(form_factor_ocr::OCRError),
+    /// Project file was written by a newer, unsupported format version
+    UnsupportedFormatVersion {
+        /// Version found in the file
+        found: u32,
+        /// Newest version this build can read
+        supported: u32,
+    },
+    /// Project file parsed but contains invalid data
+    InvalidProject(String),
+    /// Search pattern is not a valid regular expression
+    InvalidPattern(String),
+    /// Canvas export cannot be produced as requested
+    InvalidExport(String),
+    /// File is not a form image or project the canvas can open
+    UnsupportedFile(String),
+    /// System clipboard could not be read
+    Clipboard(String),
+    /// Project has not been saved to or opened from a file
+    NoProjectFile,
+    /// Data could not be compressed or decompressed
+    Compression(String),
+    /// Feature needs native libraries and is stubbed out in the browser build
+    NotInBrowser(String),
+    /// Field group or repeating section cannot be defined or changed as requested
+    InvalidFieldGroup(String),
+    /// Project file was changed by someone else since it was opened or saved
+    SaveConflict(String),
+    /// Project file is password protected and no password was given
+    PasswordRequired,
+    /// Password does not unlock the project file, or the file was altered
+    WrongPassword,
+    /// Project could not be encrypted
+    Encryption(String),
+    /// Project could not be read from or written to the project store
+    Store(String),
 }
 
 impl std::fmt::Display for CanvasErrorKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            CanvasErrorKind::ImageLoad(msg) => write!(f, "Failed to load image: {}", msg),
-            CanvasErrorKind::FileRead(msg) => write!(f, "Failed to read file: {}", msg),
-            CanvasErrorKind::FileWrite(msg) => write!(f, "Failed to write file: {}", msg),
+            CanvasErrorKind::ImageLoad(msg) => write!(f, "Failed to process image: {}", msg),
+            CanvasErrorKind::Io(kind) => write!(f, "I/O error: {}", kind),
             CanvasErrorKind::Serialization(msg) => write!(f, "Failed to serialize data: {}", msg),
             CanvasErrorKind::Deserialization(msg) => write!(f, "Failed to deserialize data: {}", msg),
             CanvasErrorKind::NoFormImageLoaded => write!(f, "No form image loaded"),
-            CanvasErrorKind::TextDetection(msg) => write!(f, "Text detection failed: {}", msg),
-            CanvasErrorKind::LogoDetection(msg) => write!(f, "Logo detection failed: {}", msg),
+            #[cfg(all(any(feature = "text-detection", feature = "logo-detection"), not(target_arch = "wasm32")))]
+            CanvasErrorKind::Cv(e) => write!(f, "Detection failed: {}", e),
+            CanvasErrorKind::NoLogoTemplates(dir) => write!(f, "No logo templates found in {}", dir),
             CanvasErrorKind::NoRecentProjects => write!(f, "No recent projects found"),
-            CanvasErrorKind::OCRFailed(msg) => write!(f, "OCR text extraction failed: {}", msg),
+            #[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
+            CanvasErrorKind::Ocr(e) => write!(f, "OCR text extraction failed: {}", e),
+            CanvasErrorKind::UnsupportedFormatVersion { found, supported } => write!(
+                f,
+                "Project format version {} is newer than supported version {}",
+                found, supported
+            ),
+            CanvasErrorKind::InvalidProject(msg) => write!(f, "Invalid project data: {}", msg),
+            CanvasErrorKind::InvalidPattern(msg) => write!(f, "Invalid search pattern: {}", msg),
+            CanvasErrorKind::InvalidExport(msg) => write!(f, "Cannot export canvas: {}", msg),
+            CanvasErrorKind::UnsupportedFile(msg) => write!(f, "Cannot open file: {}", msg),
+            CanvasErrorKind::Clipboard(msg) => write!(f, "Clipboard error: {}", msg),
+            CanvasErrorKind::NoProjectFile => write!(f, "Project has not been saved to a file"),
+            CanvasErrorKind::Compression(msg) => write!(f, "Compression failed: {}", msg),
+            CanvasErrorKind::NotInBrowser(feature) => write!(f, "{} is not available in the browser", feature),
+            CanvasErrorKind::InvalidFieldGroup(msg) => write!(f, "Invalid field group: {}", msg),
+            CanvasErrorKind::SaveConflict(path) => {
+                write!(f, "{} was changed by someone else since it was opened", path)
+            }
+            CanvasErrorKind::PasswordRequired => write!(f, "Project is password protected"),
+            CanvasErrorKind::WrongPassword => write!(f, "Wrong password, or the project file is damaged"),
+            CanvasErrorKind::Encryption(msg) => write!(f, "Encryption failed: {}", msg),
+            CanvasErrorKind::Store(msg) => write!(f, "Project store error: {}", msg),
         }
     }
 }
@@ -61,22 +159,87 @@ pub struct CanvasError {
     pub line: u32,
     /// File where the error was created
     pub file: &'static str,
+    /// Path of the file being accessed, if any
+    pub path: Option<String>,
+    /// File operation that failed, if any
+    pub operation: Option<IoOperation>,
+    /// Underlying error
+    source: Option<Arc<dyn std::error::Error + Send + Sync>>,
 }
 
 impl CanvasError {
     /// Create a new canvas error
     pub fn new(kind: CanvasErrorKind, line: u32, file: &'static str) -> Self {
-        Self { kind, line, file }
+        Self {
+            kind,
+            line,
+            file,
+            path: None,
+            operation: None,
+            source: None,
+        }
+    }
+
+    /// Record the file and operation that failed
+    pub fn with_path(mut self, path: impl Into<String>, operation: IoOperation) -> Self {
+        self.path = Some(path.into());
+        self.operation = Some(operation);
+        self
+    }
+
+    /// Attach the underlying error
+    pub fn with_source(mut self, source: impl std::error::Error + Send + Sync + 'static) -> Self {
+        self.source = Some(Arc::new(source));
+        self
+    }
+
+    /// Wrap a file system error
+    pub(super) fn io(
+        err: std::io::Error,
+        path: impl Into<String>,
+        operation: IoOperation,
+        line: u32,
+        file: &'static str,
+    ) -> Self {
+        Self::new(CanvasErrorKind::Io(err.kind()), line, file)
+            .with_path(path, operation)
+            .with_source(err)
+    }
+
+    /// Wrap an image decoding or encoding error
+    ///
+    /// File system failures inside the image codec are reported as
+    /// [`CanvasErrorKind::Io`] so callers can match on them uniformly.
+    pub(super) fn image(
+        err: image::ImageError,
+        path: impl Into<String>,
+        operation: IoOperation,
+        line: u32,
+        file: &'static str,
+    ) -> Self {
+        let kind = match &err {
+            image::ImageError::IoError(io) => CanvasErrorKind::Io(io.kind()),
+            other => CanvasErrorKind::ImageLoad(other.to_string()),
+        };
+        Self::new(kind, line, file).with_path(path, operation).with_source(err)
     }
 }
 
 impl std::fmt::Display for CanvasError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Canvas error: {} at line {} in {}", self.kind, self.line, self.file)
+        write!(f, "Canvas error: {}", self.kind)?;
+        if let (Some(operation), Some(path)) = (self.operation, &self.path) {
+            write!(f, " during {} of '{}'", operation, path)?;
+        }
+        write!(f, " at line {} in {}", self.line, self.file)
     }
 }
 
-impl std::error::Error for CanvasError {}
+impl std::error::Error for CanvasError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_deref().map(|e| e as &(dyn std::error::Error + 'static))
+    }
+}
 
 /// Canvas interaction state
 ///
@@ -110,18 +273,12 @@ pub(super) enum CanvasState {
     },
 }
 
-/// Detection sub-type for filtering detections layer
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum DetectionSubtype {
-    /// Logo detections
-    Logos,
-    /// Text detections
-    Text,
-}
-
 /// Drawing canvas state
 #[derive(Clone, Serialize, Deserialize, Getters)]
 pub struct DrawingCanvas {
+    /// Project file format version
+    #[serde(default)]
+    pub(super) format_version: u32,
     /// Project name
     pub(super) project_name: String,
     /// All completed shapes
@@ -157,17 +314,18 @@ pub struct DrawingCanvas {
     /// Whether the Detections layer dropdown is expanded
     #[serde(skip)]
     pub(super) detections_expanded: bool,
-    /// Selected detection sub-type (Logos or Text)
+    /// Name of the detection subtype the Detections layer is filtered to
     #[serde(skip)]
-    pub(super) selected_detection_subtype: Option<DetectionSubtype>,
+    pub(super) selected_detection_subtype: Option<String>,
 
     // Form image state (not serialized)
     #[serde(skip)]
     pub(super) form_image: Option<egui::TextureHandle>,
     #[serde(skip)]
     pub(super) form_image_size: Option<egui::Vec2>,
+    /// Form image still being decoded after its project was opened
     #[serde(skip)]
-    pub(super) pending_image_load: Option<String>,
+    pub(super) pending_image_load: Option<PendingImage>,
 
     // Zoom and pan state
     /// Current zoom level for the canvas
@@ -177,15 +335,13 @@ pub struct DrawingCanvas {
     #[serde(default)]
     pub(super) pan_offset: egui::Vec2,
 
-    // Settings state (not serialized)
+    // User settings (not serialized with the project)
+    /// Zoom sensitivity and grid spacing
     #[serde(skip)]
-    pub(super) show_settings: bool,
+    pub(super) canvas_settings: CanvasSettings,
+    /// Detection model, thresholds and template locations
     #[serde(skip)]
-    pub(super) zoom_sensitivity: f32,
-    #[serde(skip)]
-    pub(super) grid_spacing_horizontal: f32,
-    #[serde(skip)]
-    pub(super) grid_spacing_vertical: f32,
+    pub(super) detection_settings: DetectionSettings,
     /// Rotation angle of the grid overlay in radians
     #[serde(default)]
     pub(super) grid_rotation_angle: f32,
@@ -200,11 +356,455 @@ pub struct DrawingCanvas {
     pub(super) stroke: Stroke,
     /// Fill color for drawing shapes
     pub(super) fill_color: Color32,
+
+    // Redaction state
+    /// PII found in detection OCR text, as (detection index, kind) pairs
+    #[serde(default)]
+    pub(super) pii_flags: Vec<(usize, PiiKind)>,
+    /// Subtype tags on detections, as (detection index, subtype name) pairs
+    #[serde(default)]
+    pub(super) detection_tags: Vec<(usize, String)>,
+    /// Whether redaction regions are previewed on the canvas
+    #[serde(skip)]
+    pub(super) redaction_mode: bool,
+    /// Whether fields are previewed filled with sample data
+    #[serde(skip)]
+    pub(super) template_preview: bool,
+    /// Image, canvas and screen coordinate mapping of the last rendered frame
+    #[serde(skip)]
+    pub(super) coordinate_mapper: CoordinateMapper,
+    /// Cursor position in image pixels during the last rendered frame
+    #[serde(skip)]
+    pub(super) cursor_image_pos: Option<ImagePos>,
+    /// Cursor position in canvas units during the last rendered frame, shown to collaborators
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) cursor_canvas_pos: Option<CanvasPos>,
+
+    // Review comments
+    /// Comment threads attached to shapes and detections
+    #[serde(default)]
+    pub(super) comment_threads: Vec<CommentThread>,
+    /// Author name used for new comments
+    #[serde(skip)]
+    pub(super) comment_author: String,
+    /// Whether the comments window is open
+    #[serde(skip)]
+    pub(super) show_comments: bool,
+    /// Text of the comment being written
+    #[serde(skip)]
+    pub(super) comment_draft: String,
+    /// Thread the draft will be posted to, if replying
+    #[serde(skip)]
+    pub(super) comment_reply_to: Option<usize>,
+    /// Object the comments window was opened for, instead of the selected shape
+    #[serde(skip)]
+    pub(super) comment_target: Option<CommentTarget>,
+
+    // Team workflow
+    /// Page assignments and status
+    #[serde(default)]
+    pub(super) workflow: Workflow,
+    /// Whether the assignment window is open
+    #[serde(skip)]
+    pub(super) show_workflow: bool,
+    /// Assignee being typed for the current page
+    #[serde(skip)]
+    pub(super) assignee_draft: String,
+    /// Page the assignee draft was loaded from
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) assignee_draft_page: usize,
+    /// Assignee the page list is filtered to
+    #[serde(skip)]
+    pub(super) workflow_filter: Option<String>,
+
+    // Effort tracking
+    /// Cumulative annotation statistics
+    #[serde(default)]
+    pub(super) session_stats: SessionStats,
+    /// Whether the statistics window is open
+    #[serde(skip)]
+    pub(super) show_stats: bool,
+    /// Whether the color legend is drawn over the canvas
+    #[serde(skip)]
+    pub(super) show_legend: bool,
+
+    // Collaboration state (not serialized)
+    /// Presence of other collaborators, drawn as labelled cursors
+    #[serde(skip)]
+    pub(super) peers: Vec<Presence>,
+    /// Collaboration session the canvas has joined
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) collab: Option<CollabSession<StoreSyncTransport>>,
+    /// Whether the collaboration window is open
+    #[serde(skip)]
+    pub(super) show_collab: bool,
+    /// Session name typed in the collaboration window
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) collab_session_name: String,
+    /// Outcome of the last action in the collaboration window
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) collab_status: Option<String>,
+    /// Time of the last sync with the collaboration session, in seconds
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) last_collab_sync: f64,
+
+    // Context menu state (not serialized)
+    /// Object that was right-clicked, or `None` for empty canvas
+    #[serde(skip)]
+    pub(super) context_menu_target: Option<CommentTarget>,
+    /// Canvas position that was right-clicked
+    #[serde(skip)]
+    pub(super) context_menu_pos: Pos2,
+    /// Shape copied with the context menu
+    #[serde(skip)]
+    pub(super) shape_clipboard: Option<Shape>,
+    /// Actions chosen from context menus, waiting for the application
+    #[serde(skip)]
+    pub(super) pending_actions: Vec<CanvasAction>,
+    /// Commands that changed the canvas, waiting to be broadcast
+    #[serde(skip)]
+    pub(super) changes: Vec<CanvasCommand>,
+
+    // Guides
+    /// Guide lines shapes snap to, in canvas coordinates
+    #[serde(default)]
+    pub(super) guides: Vec<Guide>,
+    /// Index of the guide being dragged
+    #[serde(skip)]
+    pub(super) guide_drag: Option<usize>,
+
+    // Bookmarks
+    /// Saved views to jump between
+    #[serde(default)]
+    pub(super) bookmarks: Vec<ViewBookmark>,
+    /// Whether the bookmarks window is open
+    #[serde(skip)]
+    pub(super) show_bookmarks: bool,
+    /// Name typed for the next bookmark
+    #[serde(skip)]
+    pub(super) bookmark_name: String,
+
+    // Highlights
+    /// Highlighter strokes over the form image, in canvas coordinates
+    #[serde(default)]
+    pub(super) highlights: Vec<Highlight>,
+
+    // Stamps
+    /// Stamps placed with the Stamp tool, in canvas coordinates
+    #[serde(default)]
+    pub(super) stamps: Vec<Stamp>,
+    /// Name of the library symbol the Stamp tool places
+    #[serde(skip)]
+    pub(super) chosen_stamp: Option<String>,
+    /// Textures other than the form image, such as image stamps, under the memory budget
+    #[serde(skip)]
+    pub(super) textures: TextureCache,
+
+    // Style presets
+    /// Name of the style preset new shapes are drawn with
+    #[serde(skip)]
+    pub(super) chosen_style_preset: Option<String>,
+
+    // Project OCR settings
+    /// OCR settings for this project, overriding the app settings
+    #[serde(default)]
+    pub(super) ocr_settings: Option<OcrSettings>,
+    /// Text read from each detection by the last OCR pass over it
+    #[serde(default)]
+    pub(super) ocr_records: Vec<OcrRecord>,
+    /// Detection indices in the order their OCR text is read; unlisted detections follow
+    #[serde(default)]
+    #[getter(skip)]
+    pub(super) ocr_order: Vec<usize>,
+    /// Name of the template the project was started from, if any
+    #[serde(default)]
+    pub(super) template_name: Option<String>,
+    /// Name of the template page the fields came from, for multi-page templates
+    #[serde(default)]
+    pub(super) template_page: Option<String>,
+    /// Index of that page in the template, the page workflow assignments apply to
+    #[serde(default)]
+    pub(super) template_page_index: Option<usize>,
+    /// Where the form image, detections and text came from
+    #[serde(default)]
+    pub(super) provenance: Provenance,
+
+    // Grid repeat state (not serialized)
+    /// Shape the grid repeat window is open for
+    #[serde(skip)]
+    pub(super) repeat_target: Option<usize>,
+    /// Layout being edited in the grid repeat window
+    #[serde(skip)]
+    pub(super) repeat_grid: RepeatGrid,
+    /// Naming scheme edited in the grid repeat window
+    #[serde(skip)]
+    pub(super) repeat_naming: NamingScheme,
+
+    // Find and rename state (not serialized)
+    /// Whether the find and rename window is open
+    #[serde(skip)]
+    pub(super) show_rename: bool,
+    /// Query edited in the find and rename window
+    #[serde(skip)]
+    pub(super) rename_query: RenameQuery,
+
+    // Export state (not serialized)
+    /// Whether the canvas export window is open
+    #[serde(skip)]
+    pub(super) show_export: bool,
+    /// Format chosen in the export window
+    #[serde(skip)]
+    pub(super) export_format: ExportFormat,
+    /// Output pixels per image pixel for PNG exports
+    #[serde(skip)]
+    pub(super) export_scale: f32,
+    /// Layout of extracted text exports
+    #[serde(skip)]
+    pub(super) text_export_options: TextExportOptions,
+
+    // Print state (not serialized)
+    /// Whether the print window is open
+    #[serde(skip)]
+    pub(super) show_print: bool,
+    /// Layers, appendix and paper chosen in the print window
+    #[serde(skip)]
+    pub(super) print_options: PrintOptions,
+
+    // Drag-and-drop state (not serialized)
+    /// Dropped files still to open, next first
+    #[serde(skip)]
+    pub(super) drop_queue: VecDeque<DroppedFile>,
+
+    // Paste state (not serialized)
+    /// Pasted image waiting for a choice of where it goes
+    #[serde(skip)]
+    pub(super) pending_paste: Option<image::RgbaImage>,
+    /// Screenshot capture in progress
+    #[serde(skip)]
+    pub(super) screenshot_stage: ScreenshotStage,
+    /// Comment being written to go with a captured screenshot
+    #[serde(skip)]
+    pub(super) screenshot_comment: String,
+
+    // New-project state (not serialized)
+    /// Whether the new-project window is open
+    #[serde(skip)]
+    pub(super) show_new_project: bool,
+    /// Name typed for the new project
+    #[serde(skip)]
+    pub(super) new_project_name: String,
+    /// Templates offered in the new-project window
+    #[serde(skip)]
+    pub(super) project_templates: Vec<ProjectTemplate>,
+    /// Templates being loaded for the new-project window
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) pending_templates: Option<PendingTemplates>,
+    /// Template chosen in the new-project window, or `None` for a blank project
+    #[serde(skip)]
+    pub(super) selected_template: Option<usize>,
+    /// Name typed for saving the current project as a template
+    #[serde(skip)]
+    pub(super) new_template_name: String,
+    /// How pages added to a multi-page template are matched
+    #[serde(skip)]
+    pub(super) new_template_page_match: PageMatch,
+    /// Position in its packet of the form a template page is applied to, from 0
+    #[serde(skip)]
+    pub(super) template_packet_page: usize,
+    /// Which templates the new-project window lists
+    #[serde(skip)]
+    pub(super) template_filter: TemplateFilter,
+    /// Order the new-project window lists templates in
+    #[serde(skip)]
+    pub(super) template_sort: TemplateSortOrder,
+    /// Tag typed for adding to the selected template
+    #[serde(skip)]
+    pub(super) template_tag_draft: String,
+
+    // Field groups
+    /// Groups of fields and repeating sections
+    #[serde(default)]
+    pub(super) field_groups: Vec<FieldGroup>,
+    /// Whether the field groups window is open
+    #[serde(skip)]
+    pub(super) show_field_groups: bool,
+    /// Group being defined in the field groups window
+    #[serde(skip)]
+    pub(super) field_group_draft: FieldGroup,
+    /// Why the last change in the field groups window failed
+    #[serde(skip)]
+    pub(super) field_group_status: Option<String>,
+
+    // Tab order
+    /// Shape indices in the order data entry visits them; unlisted shapes follow
+    #[serde(default)]
+    pub(super) tab_order: Vec<usize>,
+    /// Whether the tab order window is open
+    #[serde(skip)]
+    pub(super) show_tab_order: bool,
+    /// Direction the tab order window orders rows in
+    #[serde(skip)]
+    pub(super) tab_order_direction: ReadingDirection,
+
+    // Data entry state (not serialized)
+    /// Whether the data entry window is open
+    #[serde(skip)]
+    pub(super) show_data_entry: bool,
+    /// Index of the shape whose value is being entered
+    #[serde(skip)]
+    pub(super) data_entry_field: Option<usize>,
+    /// Whether the canvas zooms to each field as it is reached
+    #[serde(skip)]
+    pub(super) data_entry_auto_zoom: bool,
+    /// Whether the data entry window shows the form image under each field
+    #[serde(skip)]
+    pub(super) data_entry_crop_preview: bool,
+    /// Whether the value box takes keyboard focus on the next frame
+    #[serde(skip)]
+    pub(super) data_entry_refocus: bool,
+    /// Message about the last attempt to move between fields
+    #[serde(skip)]
+    pub(super) data_entry_status: Option<String>,
+    /// Field being entered with its value and confidence when it was reached
+    #[serde(skip)]
+    pub(super) data_entry_start: Option<(usize, String, Option<f32>)>,
+
+    // Batch review state (not serialized)
+    /// Review of flagged fields across many instances, while its window is open
+    #[serde(skip)]
+    pub(super) batch_review: Option<BatchReview>,
+
+    // Project diff state (not serialized)
+    /// Changes between two versions of a project, while the diff window is open
+    #[serde(skip)]
+    pub(super) project_diff: Option<ProjectDiff>,
+
+    // Incremental save state (not serialized)
+    /// Journal of changes since the project file was last written, once it has a file
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) journal: Option<ProjectJournal>,
+    /// Another operator's version of the project file, while the save conflict window is open
+    #[serde(skip)]
+    pub(super) save_conflict: Option<SaveConflict>,
+
+    // Password protection state (not serialized)
+    /// Password the project is encrypted with when saved, if protected
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) project_password: Option<String>,
+    /// Protected project waiting for its password, while the unlock window is open
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) pending_unlock: Option<PendingUnlock>,
+    /// Whether the project password window is open
+    #[serde(skip)]
+    pub(super) show_password: bool,
+    /// New password typed in the project password window
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) password_draft: String,
+    /// New password typed again to confirm it
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) password_confirm: String,
+
+    // Project store (not serialized)
+    /// Where projects are stored, from the user settings
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) storage: StorageSettings,
+    /// Name and stored version of the project, if it was opened from or saved to the store
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) stored_project: Option<StoredProject>,
+    /// Whether the project store window is open
+    #[serde(skip)]
+    pub(super) show_store: bool,
+    /// Names of the stored projects, as last listed
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) store_listing: Vec<String>,
+    /// Name typed in the project store window to save under
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) store_name: String,
+    /// Outcome of the last action in the project store window
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) store_status: Option<String>,
+    /// Stored project chosen in the project store window, waiting to be opened
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) store_open_request: Option<String>,
+
+    // Animation state (not serialized)
+    /// Flash strength of newly added detections, by detection index
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) detection_flashes: Animations<usize, f32>,
+
+    // Navigation state (not serialized)
+    /// Shapes and detections inspected, for going back and forward
+    #[serde(skip)]
+    pub(super) selection_history: SelectionHistory,
+
+    // Custom tools (not serialized)
+    /// Tools registered by plugins and the application
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) custom_tools: ToolRegistry,
+    /// Index of the custom tool in use instead of `current_tool`
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) active_custom_tool: Option<usize>,
+
+    // OCR corrections (not serialized)
+    /// OCR text open in the in-place editor
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) ocr_edit: Option<OcrEdit>,
+    /// OCR readings settled on by a person, waiting to be reported
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) ocr_samples: Vec<OcrSample>,
+
+    // OCR reading order window (not serialized)
+    /// Whether the reading order window is open, with badges numbering the detections
+    #[serde(skip)]
+    pub(super) show_ocr_order: bool,
+    /// Direction the reading order window orders rows in
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) ocr_order_direction: ReadingDirection,
+
+    // OCR word boxes (not serialized)
+    /// Whether word boxes are drawn over the detections
+    #[serde(skip)]
+    pub(super) show_ocr_words: bool,
+    /// Detection and word index of the selected word
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) selected_ocr_word: Option<(usize, usize)>,
+
+    // Detection merge selection (not serialized)
+    /// Detections picked for merging, in the order they were picked
+    #[serde(skip)]
+    #[getter(skip)]
+    pub(super) selected_detections: Vec<usize>,
 }
 
 impl Default for DrawingCanvas {
     fn default() -> Self {
         Self {
+            format_version: PROJECT_FORMAT_VERSION,
             project_name: String::from("Untitled"),
             shapes: Vec::new(),
             detections: Vec::new(),
@@ -224,14 +824,128 @@ impl Default for DrawingCanvas {
             pending_image_load: None,
             zoom_level: 5.0,
             pan_offset: egui::Vec2::ZERO,
-            show_settings: false,
-            zoom_sensitivity: 5.0,
-            grid_spacing_horizontal: 10.0,
-            grid_spacing_vertical: 10.0,
+            canvas_settings: CanvasSettings::default(),
+            detection_settings: DetectionSettings::default(),
             grid_rotation_angle: 0.0,
             form_image_rotation: 0.0,
             stroke: Stroke::new(2.0, Color32::from_rgb(0, 120, 215)),
             fill_color: Color32::from_rgba_premultiplied(0, 120, 215, 30),
+            pii_flags: Vec::new(),
+            detection_tags: Vec::new(),
+            redaction_mode: false,
+            template_preview: false,
+            coordinate_mapper: CoordinateMapper::default(),
+            cursor_image_pos: None,
+            cursor_canvas_pos: None,
+            comment_threads: Vec::new(),
+            comment_author: String::from("Reviewer"),
+            show_comments: false,
+            comment_draft: String::new(),
+            comment_reply_to: None,
+            comment_target: None,
+            workflow: Workflow::new(),
+            show_workflow: false,
+            assignee_draft: String::new(),
+            assignee_draft_page: 0,
+            workflow_filter: None,
+            session_stats: SessionStats::new(),
+            show_stats: false,
+            show_legend: false,
+            peers: Vec::new(),
+            collab: None,
+            show_collab: false,
+            collab_session_name: String::new(),
+            collab_status: None,
+            last_collab_sync: 0.0,
+            context_menu_target: None,
+            context_menu_pos: Pos2::ZERO,
+            shape_clipboard: None,
+            pending_actions: Vec::new(),
+            changes: Vec::new(),
+            guides: Vec::new(),
+            bookmarks: Vec::new(),
+            show_bookmarks: false,
+            bookmark_name: String::new(),
+            highlights: Vec::new(),
+            stamps: Vec::new(),
+            chosen_stamp: None,
+            textures: TextureCache::default(),
+            chosen_style_preset: None,
+            guide_drag: None,
+            ocr_settings: None,
+            template_name: None,
+            template_page: None,
+            template_page_index: None,
+            provenance: Provenance::new(),
+            ocr_records: Vec::new(),
+            ocr_order: Vec::new(),
+            repeat_target: None,
+            repeat_grid: RepeatGrid::default(),
+            repeat_naming: NamingScheme::default(),
+            show_rename: false,
+            rename_query: RenameQuery::default(),
+            show_export: false,
+            export_format: ExportFormat::default(),
+            export_scale: 1.0,
+            text_export_options: TextExportOptions::default(),
+            show_print: false,
+            print_options: PrintOptions::default(),
+            drop_queue: VecDeque::new(),
+            pending_paste: None,
+            screenshot_stage: ScreenshotStage::Idle,
+            screenshot_comment: String::new(),
+            show_new_project: false,
+            new_project_name: String::new(),
+            project_templates: Vec::new(),
+            pending_templates: None,
+            selected_template: None,
+            new_template_name: String::new(),
+            new_template_page_match: PageMatch::default(),
+            template_packet_page: 0,
+            template_filter: TemplateFilter::default(),
+            template_sort: TemplateSortOrder::default(),
+            template_tag_draft: String::new(),
+            field_groups: Vec::new(),
+            show_field_groups: false,
+            field_group_draft: FieldGroup::default(),
+            field_group_status: None,
+            tab_order: Vec::new(),
+            show_tab_order: false,
+            tab_order_direction: ReadingDirection::default(),
+            show_data_entry: false,
+            data_entry_field: None,
+            data_entry_auto_zoom: true,
+            data_entry_crop_preview: true,
+            data_entry_refocus: false,
+            data_entry_status: None,
+            data_entry_start: None,
+            batch_review: None,
+            project_diff: None,
+            journal: None,
+            save_conflict: None,
+            project_password: None,
+            pending_unlock: None,
+            show_password: false,
+            password_draft: String::new(),
+            password_confirm: String::new(),
+            storage: StorageSettings::default(),
+            stored_project: None,
+            show_store: false,
+            store_listing: Vec::new(),
+            store_name: String::new(),
+            store_status: None,
+            store_open_request: None,
+            detection_flashes: Animations::new(),
+            selection_history: SelectionHistory::default(),
+            custom_tools: ToolRegistry::new(),
+            active_custom_tool: None,
+            ocr_edit: None,
+            ocr_samples: Vec::new(),
+            show_ocr_order: false,
+            ocr_order_direction: ReadingDirection::default(),
+            show_ocr_words: false,
+            selected_ocr_word: None,
+            selected_detections: Vec::new(),
         }
     }
 }
@@ -263,7 +977,7 @@ impl DrawingCanvas {
 
     /// Set the currently selected layer
     pub fn set_selected_layer(&mut self, layer: Option<LayerType>) {
-        self.selected_layer = layer;
+        self.apply_command(CanvasCommand::SelectLayer(layer));
     }
 
     /// Set whether the project name is being edited
@@ -296,10 +1010,13 @@ impl DrawingCanvas {
     /// Set the selected shape (for use within canvas module)
     pub(super) fn set_selected_shape(&mut self, shape: Option<usize>) {
         self.selected_shape = shape;
+        if let Some(idx) = shape {
+            self.record_selection(CommentTarget::Shape(idx));
+        }
     }
 
-    /// Set show properties flag (for use within canvas module)
-    pub(super) fn set_show_properties(&mut self, show: bool) {
+    /// Show or hide the properties of the selected shape
+    pub fn set_show_properties(&mut self, show: bool) {
         self.show_properties = show;
     }
 
@@ -331,6 +1048,7 @@ impl DrawingCanvas {
     /// Undo the last shape addition (removes the most recently added shape)
     pub fn undo(&mut self) {
         self.shapes.pop();
+        self.prune_comment_threads();
     }
 
     /// Get the number of shapes on the canvas
@@ -364,6 +1082,38 @@ impl DrawingCanvas {
             .count()
     }
 
+    /// Kind of a detection, which decides the color it is drawn in
+    ///
+    /// Detections read by OCR are [`DetectionKind::Ocr`]; the others are
+    /// logos or text regions according to the detector that found them.
+    pub fn detection_kind(&self, index: usize) -> Option<DetectionKind> {
+        let detection = self.detections.get(index)?;
+        let kind = if self.ocr_text(index).is_some() {
+            DetectionKind::Ocr
+        } else if detection.name().starts_with("Logo:") {
+            DetectionKind::Logo
+        } else {
+            DetectionKind::Text
+        };
+        Some(kind)
+    }
+
+    /// Detection as drawn, with its outline in the color of its subtype or kind
+    ///
+    /// Detections tagged with a subtype in the taxonomy take the subtype's
+    /// color; the others take the configured color for their kind.
+    pub fn styled_detection(&self, index: usize) -> Option<Shape> {
+        let kind = self.detection_kind(index)?;
+        let color = match self.detection_subtype(index) {
+            Some(subtype) => subtype.color,
+            None => self.detection_settings.colors.color(kind),
+        };
+        let mut detection = self.detections[index].clone();
+        let stroke = detection.stroke();
+        detection.set_stroke(Stroke::new(stroke.width, color));
+        Some(detection)
+    }
+
     /// Toggle the detections layer dropdown expansion state
     pub fn toggle_detections_expanded(&mut self) {
         self.detections_expanded = !self.detections_expanded;
@@ -374,23 +1124,96 @@ impl DrawingCanvas {
         self.detections_expanded
     }
 
-    /// Set the selected detection sub-type
-    pub fn set_selected_detection_subtype(&mut self, subtype: Option<DetectionSubtype>) {
+    /// Show only detections tagged with a subtype, or all detections with `None`
+    pub fn set_selected_detection_subtype(&mut self, subtype: Option<String>) {
         self.selected_detection_subtype = subtype;
     }
 
     /// Set the zoom level
     pub fn set_zoom(&mut self, zoom: f32) {
-        self.zoom_level = zoom.clamp(0.1, 100.0); // Clamp between 0.1 and 100
+        self.apply_command(CanvasCommand::SetZoom(zoom));
     }
 
     /// Set the pan offset
     pub fn set_pan_offset(&mut self, x: f32, y: f32) {
-        self.pan_offset = egui::Vec2::new(x, y);
+        self.apply_command(CanvasCommand::SetPan { x, y });
+    }
+
+    /// Zoom and pan so a region in canvas coordinates fills about half the view
+    ///
+    /// Uses the view size from the last frame; does nothing before the first.
+    pub fn zoom_to_rect(&mut self, rect: egui::Rect) {
+        let viewport = self.coordinate_mapper.viewport();
+        if !rect.is_finite() || viewport.width() <= 0.0 || viewport.height() <= 0.0 {
+            return;
+        }
+
+        let size = rect.size().max(egui::vec2(1.0, 1.0));
+        let zoom = (viewport.width() * 0.5 / size.x).min(viewport.height() * 0.5 / size.y);
+        // Same range as scroll and keyboard zoom
+        let zoom = zoom.clamp(1.0, 10.0);
+        let pan = (viewport.center() - rect.center()) * zoom;
+        self.apply_command(CanvasCommand::SetZoom(zoom));
+        self.apply_command(CanvasCommand::SetPan { x: pan.x, y: pan.y });
+    }
+
+    /// Pan so a region in canvas coordinates is centered, keeping the zoom
+    ///
+    /// Uses the view size from the last frame; does nothing before the first.
+    pub fn pan_to_rect(&mut self, rect: egui::Rect) {
+        let viewport = self.coordinate_mapper.viewport();
+        if !rect.is_finite() || viewport.width() <= 0.0 || viewport.height() <= 0.0 {
+            return;
+        }
+
+        let pan = (viewport.center() - rect.center()) * self.zoom_level;
+        self.apply_command(CanvasCommand::SetPan { x: pan.x, y: pan.y });
+    }
+
+    /// Apply the canvas, detection and storage sections of the user settings
+    pub fn apply_settings(&mut self, settings: &Settings) {
+        self.canvas_settings = settings.canvas.clone();
+        self.detection_settings = settings.detection.clone();
+        self.storage = settings.storage.clone();
+        self.textures.set_budget(settings.canvas.texture_budget_mb * MB);
     }
 
     /// Set the current tool mode
     pub fn set_tool(&mut self, tool: ToolMode) {
-        self.current_tool = tool;
+        self.apply_command(CanvasCommand::SetTool(tool));
+    }
+
+    /// Replace all shapes, e.g. with the materialized state of a collaboration session
+    pub fn set_shapes(&mut self, shapes: Vec<Shape>) {
+        if self.selected_shape.is_some_and(|idx| idx >= shapes.len()) {
+            self.selected_shape = None;
+            self.show_properties = false;
+        }
+        self.shapes = shapes;
+        self.prune_comment_threads();
+    }
+
+    /// Get a mutable reference to the page workflow
+    pub fn workflow_mut(&mut self) -> &mut Workflow {
+        &mut self.workflow
+    }
+
+    /// Status bar contents for the current cursor, zoom, tool and layer
+    pub fn status_bar(&self) -> StatusBar {
+        StatusBar {
+            cursor: self.cursor_image_pos.map(|pos| pos.0),
+            zoom: self.zoom_level,
+            tool: Some(self.active_tool_name()),
+            layer: self.selected_layer.map(|layer| layer.to_string()),
+            ..StatusBar::default()
+        }
+    }
+
+    /// Update the presence indicators for other collaborators
+    ///
+    /// Called by applications that drive their own [`CollabSession`]; a
+    /// session joined from the collaboration window updates them itself.
+    pub fn set_peers(&mut self, peers: Vec<Presence>) {
+        self.peers = peers;
     }
 }
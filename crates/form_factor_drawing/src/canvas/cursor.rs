@@ -0,0 +1,175 @@
+//! Pointer cursor feedback for the active tool and the object under the pointer
+//!
+//! Each frame the canvas hit-tests the hovered position and sets a cursor
+//! that shows what a press would do there: a crosshair for drawing tools,
+//! resize arrows over the selected shape's vertices in Edit mode, a rotate
+//! cursor while something can be rotated, and grab hands while panning.
+//!
+//! Panning drags the view with the middle button, or with the primary button
+//! while Space is held, whichever tool is active.
+
+use super::core::{CanvasState, DrawingCanvas};
+use super::tools::{callout_handles, vertex_at};
+use crate::tool::lock;
+use crate::{CanvasCommand, LayerType, ScreenPos, Shape, ToolMode};
+use egui::{CursorIcon, Pos2, Vec2};
+use tracing::{debug, instrument};
+
+/// Cursor shown while dragging would rotate something
+///
+/// egui has no rotate cursor; the alias cursor's curved arrow is the closest.
+pub const ROTATE_CURSOR: CursorIcon = CursorIcon::Alias;
+
+impl DrawingCanvas {
+    /// Cursor for the pointer at a canvas position, given the active tool and interaction
+    ///
+    /// Panning is not covered; it takes over the cursor while Space is held
+    /// or a pan drag is under way.
+    pub fn cursor_at(&self, pos: Pos2) -> CursorIcon {
+        if let Some(tool) = self.active_custom_tool.and_then(|index| self.custom_tools.get(index)) {
+            return lock(tool).cursor();
+        }
+
+        match &self.state {
+            CanvasState::Drawing { .. } => return CursorIcon::Crosshair,
+            CanvasState::Rotating { .. } => return ROTATE_CURSOR,
+            CanvasState::DraggingVertex { vertex_index } => {
+                return self
+                    .selected_shape
+                    .and_then(|idx| self.shapes.get(idx))
+                    .map_or(CursorIcon::Grabbing, |shape| vertex_cursor(shape, *vertex_index));
+            }
+            CanvasState::Idle => {}
+        }
+
+        let over_shape = self.shape_at(pos).is_some();
+        match self.current_tool {
+            ToolMode::Select if over_shape => CursorIcon::PointingHand,
+            ToolMode::Select => CursorIcon::Default,
+            ToolMode::Edit => {
+                let selected = self.selected_shape.and_then(|idx| self.shapes.get(idx));
+                match selected.and_then(|shape| Some((shape, vertex_at(shape, pos)?))) {
+                    Some((shape, vertex)) => vertex_cursor(shape, vertex),
+                    None if over_shape => CursorIcon::PointingHand,
+                    None => CursorIcon::Default,
+                }
+            }
+            ToolMode::Rotate if self.has_rotation_target() => ROTATE_CURSOR,
+            ToolMode::Rotate if over_shape => CursorIcon::PointingHand,
+            ToolMode::Rotate => CursorIcon::NotAllowed,
+            ToolMode::Rectangle
+            | ToolMode::Circle
+            | ToolMode::Ellipse
+            | ToolMode::Freehand
+            | ToolMode::Line
+            | ToolMode::Polyline
+            | ToolMode::Arrow
+            | ToolMode::Callout
+            | ToolMode::Highlighter
+            | ToolMode::Stamp => CursorIcon::Crosshair,
+        }
+    }
+
+    /// Whether a drag with the Rotate tool would rotate something, as `start_rotation` decides
+    fn has_rotation_target(&self) -> bool {
+        match self.selected_layer {
+            Some(LayerType::Shapes) => self.selected_shape.is_some_and(|idx| idx < self.shapes.len()),
+            Some(LayerType::Grid) => true,
+            Some(LayerType::Canvas) => self.form_image.is_some(),
+            Some(LayerType::Detections) | Some(LayerType::Highlights) | None => false,
+        }
+    }
+
+    /// Set the cursor for the hovered canvas position
+    pub(super) fn update_cursor(&self, response: &egui::Response) {
+        let Some(pos) = response.hover_pos() else {
+            return;
+        };
+        let canvas_pos = self.coordinate_mapper.screen_to_canvas(ScreenPos(pos)).0;
+        response.ctx.set_cursor_icon(self.cursor_at(canvas_pos));
+    }
+
+    /// Pan the view by dragging with the middle button, or the primary button while Space is held
+    ///
+    /// Returns true if the canvas input was used, leaving nothing for the tools.
+    #[instrument(skip(self, response))]
+    pub(super) fn handle_pan_input(&mut self, response: &egui::Response) -> bool {
+        let ctx = &response.ctx;
+        // Space only starts panning between interactions, so a shape being drawn is always finished
+        let space_held = !ctx.wants_keyboard_input()
+            && matches!(self.state, CanvasState::Idle)
+            && ctx.input(|i| i.key_down(egui::Key::Space));
+        let panning = response.dragged_by(egui::PointerButton::Middle)
+            || (space_held && response.dragged_by(egui::PointerButton::Primary));
+
+        if panning {
+            ctx.set_cursor_icon(CursorIcon::Grabbing);
+            let delta = response.drag_delta();
+            if delta != Vec2::ZERO {
+                let pan = self.pan_offset + delta;
+                self.apply_command(CanvasCommand::SetPan { x: pan.x, y: pan.y });
+            }
+            if response.drag_started() {
+                debug!("Started panning");
+            }
+            return true;
+        }
+        if response.drag_stopped_by(egui::PointerButton::Middle) {
+            return true;
+        }
+        if space_held {
+            if response.hovered() {
+                ctx.set_cursor_icon(CursorIcon::Grab);
+            }
+            return true;
+        }
+        false
+    }
+}
+
+/// Cursor over a vertex of a shape in Edit mode
+///
+/// Vertices that resize the shape get resize arrows along the direction they
+/// move; vertices that only move a point or the whole shape get the move cursor.
+fn vertex_cursor(shape: &Shape, vertex: usize) -> CursorIcon {
+    match shape {
+        Shape::Rectangle(rect) => {
+            let corners = rect.corners();
+            let center = corners.iter().fold(Vec2::ZERO, |sum, corner| sum + corner.to_vec2()) / 4.0;
+            corners
+                .get(vertex)
+                .map_or(CursorIcon::Move, |corner| resize_cursor(*corner - center.to_pos2()))
+        }
+        // The center moves the circle, the edge point on the right changes the radius
+        Shape::Circle(_) if vertex == 0 => CursorIcon::Move,
+        Shape::Circle(_) => CursorIcon::ResizeHorizontal,
+        Shape::Ellipse(_) if vertex == 0 => CursorIcon::Move,
+        Shape::Ellipse(ellipse) => ellipse
+            .handles()
+            .get(vertex - 1)
+            .map_or(CursorIcon::Move, |handle| resize_cursor(*handle - *ellipse.center())),
+        // The anchor moves a point; the two box corners resize the text box
+        Shape::Callout(callout) if vertex > 0 => callout_handles(callout)
+            .get(vertex)
+            .map_or(CursorIcon::Move, |corner| resize_cursor(*corner - callout.text_box().center())),
+        Shape::Polygon(_)
+        | Shape::Line(_)
+        | Shape::Polyline(_)
+        | Shape::Arrow(_)
+        | Shape::Callout(_) => CursorIcon::Move,
+    }
+}
+
+/// Resize arrows closest to a direction on screen
+fn resize_cursor(direction: Vec2) -> CursorIcon {
+    let (x, y) = (direction.x.abs(), direction.y.abs());
+    if x > 2.0 * y {
+        CursorIcon::ResizeHorizontal
+    } else if y > 2.0 * x {
+        CursorIcon::ResizeVertical
+    } else if (direction.x > 0.0) == (direction.y > 0.0) {
+        CursorIcon::ResizeNwSe
+    } else {
+        CursorIcon::ResizeNeSw
+    }
+}
@@ -0,0 +1,83 @@
+//! Custom tools registered by plugins and the application
+//!
+//! Registered tools sit next to the built-in [`ToolMode`](crate::ToolMode)s.
+//! While one is active, [`DrawingCanvas`] hands it the pointer input on the
+//! canvas instead of running a built-in tool.
+
+use super::core::DrawingCanvas;
+use crate::tool::lock;
+use crate::{CanvasCommand, CustomTool, ScreenPos, ToolInfo, ToolInput};
+use tracing::{debug, instrument};
+
+impl DrawingCanvas {
+    /// Register a custom tool
+    ///
+    /// Returns false if a built-in or registered tool already uses its id.
+    pub fn register_tool(&mut self, tool: Box<dyn CustomTool>) -> bool {
+        self.custom_tools.register(tool)
+    }
+
+    /// Toolbar entries of the registered custom tools, in registration order
+    pub fn custom_tool_infos(&self) -> Vec<ToolInfo> {
+        self.custom_tools.infos()
+    }
+
+    /// Switch to the registered custom tool with this id
+    ///
+    /// Returns false if no custom tool has the id.
+    pub fn select_custom_tool(&mut self, id: &str) -> bool {
+        let Some(index) = self.custom_tools.index_of(id) else {
+            return false;
+        };
+        self.apply_command(CanvasCommand::SelectCustomTool(index));
+        true
+    }
+
+    /// Id of the active custom tool, if one is in use instead of a built-in tool
+    pub fn active_custom_tool(&self) -> Option<String> {
+        Some(self.custom_tools.info(self.active_custom_tool?)?.id.clone())
+    }
+
+    /// Name of the tool in use, custom or built-in
+    pub fn active_tool_name(&self) -> String {
+        self.active_custom_tool().unwrap_or_else(|| self.current_tool.to_string())
+    }
+
+    /// Move the registered custom tools to another canvas, e.g. one just opened
+    ///
+    /// Tools whose ids the other canvas already uses stay behind and are dropped.
+    pub fn transfer_custom_tools(&mut self, to: &mut DrawingCanvas) {
+        to.custom_tools.merge(std::mem::take(&mut self.custom_tools));
+        self.active_custom_tool = None;
+    }
+
+    /// Hand this frame's pointer input to the active custom tool
+    ///
+    /// Returns false, leaving the input to the built-in tools, if no custom
+    /// tool is active.
+    #[instrument(skip(self, response), fields(tool = ?self.active_custom_tool()))]
+    pub(super) fn handle_custom_tool_input(&mut self, response: &egui::Response) -> bool {
+        let Some(tool) = self.active_custom_tool.and_then(|index| self.custom_tools.get(index)).cloned() else {
+            return false;
+        };
+        let mapper = self.coordinate_mapper;
+        let input = ToolInput {
+            pos: response
+                .interact_pointer_pos()
+                .or(response.hover_pos())
+                .map(|pos| mapper.screen_to_canvas(ScreenPos(pos)).0),
+            clicked: response.clicked(),
+            drag_started: response.drag_started(),
+            dragged: response.dragged(),
+            drag_stopped: response.drag_stopped(),
+            modifiers: response.ctx.input(|i| i.modifiers),
+        };
+
+        let mut tool = lock(&tool);
+        if input.clicked || input.drag_started || input.drag_stopped {
+            debug!(?input, "Custom tool input");
+        }
+        tool.handle_input(&input, self);
+        true
+    }
+}
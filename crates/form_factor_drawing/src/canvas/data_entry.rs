@@ -0,0 +1,450 @@
+//! Keyboard-first data entry
+//!
+//! The data entry window walks through the fields one at a time for
+//! heads-down typing of the values read off a form. The value box keeps the
+//! keyboard focus: Enter accepts the value and moves to the next field,
+//! Shift+Enter goes back, and Escape closes the window. As each field is
+//! reached it is selected and, unless turned off, the canvas zooms to it.
+//! A value that breaks the field's rule is announced under the box, and
+//! Enter stays on the field until it is fixed. Fields whose condition
+//! isn't met are passed over and left out of the field list. Fields are
+//! visited in the project's tab order.
+//!
+//! Next to the value box the window shows the part of the form image under
+//! the field, cropped from the loaded texture, so the value can be read
+//! and checked without looking back at the canvas. "Comment…" on the
+//! current field or in a field row's context menu opens the comments
+//! window on that field.
+//!
+//! Leaving a field whose value was changed counts as a correction in the
+//! session statistics. Fields still holding an extracted value queue an
+//! [`OcrSample`] when they are left changed or accepted unchanged, and an
+//! accepted value is confirmed.
+
+use super::core::DrawingCanvas;
+use crate::{CanvasPos, CommentTarget, FieldCondition, FieldFormat, FieldRule, OcrSample, field_applies};
+use strum::IntoEnumIterator;
+use tracing::{debug, instrument};
+
+/// Largest size of the field image in the data entry window, in points
+const CROP_PREVIEW_MAX: egui::Vec2 = egui::vec2(280.0, 120.0);
+
+impl DrawingCanvas {
+    /// Open the data entry window at the first field with a problem, or the first field
+    pub fn open_data_entry_panel(&mut self) {
+        self.show_data_entry = true;
+        self.data_entry_status = None;
+        let applicable = self.applicable_fields();
+        let start = applicable
+            .iter()
+            .find(|idx| self.shapes[**idx].check_value().is_err())
+            .or(applicable.first())
+            .copied();
+        match start {
+            Some(idx) => self.focus_field(idx),
+            None => self.data_entry_field = None,
+        }
+    }
+
+    /// Close the data entry window
+    pub fn close_data_entry_panel(&mut self) {
+        self.leave_field(false);
+        self.show_data_entry = false;
+        self.data_entry_status = None;
+    }
+
+    /// Toggle the data entry window
+    pub fn toggle_data_entry_panel(&mut self) {
+        if self.show_data_entry {
+            self.close_data_entry_panel();
+        } else {
+            self.open_data_entry_panel();
+        }
+    }
+
+    /// Zoom to each field as data entry reaches it
+    pub fn set_data_entry_auto_zoom(&mut self, auto_zoom: bool) {
+        self.data_entry_auto_zoom = auto_zoom;
+    }
+
+    /// Show the form image under each field in the data entry window
+    pub fn set_data_entry_crop_preview(&mut self, crop_preview: bool) {
+        self.data_entry_crop_preview = crop_preview;
+    }
+
+    /// Pixel crop `(x, y, width, height)` of the form image under a field
+    ///
+    /// Uses the coordinate mapping of the last rendered frame. Returns `None`
+    /// without a form image, for out-of-range indices, and for fields that
+    /// lie entirely off the image.
+    pub fn field_image_crop(&self, idx: usize) -> Option<(u32, u32, u32, u32)> {
+        self.form_image.as_ref()?;
+        let bounds = self.shapes.get(idx)?.bounding_rect();
+        let mapper = &self.coordinate_mapper;
+        let min = mapper.canvas_to_image(CanvasPos(bounds.min))?;
+        let max = mapper.canvas_to_image(CanvasPos(bounds.max))?;
+        let crop = mapper.pixel_crop(egui::Rect::from_two_pos(min.0, max.0));
+        (crop.2 > 0 && crop.3 > 0).then_some(crop)
+    }
+
+    /// Make a field the one being entered
+    ///
+    /// Selects its shape, zooms to it if auto-zoom is on, and gives the value
+    /// box the keyboard focus. Out-of-range indices are ignored.
+    #[instrument(skip(self))]
+    pub fn focus_field(&mut self, idx: usize) {
+        let Some(shape) = self.shapes.get(idx) else {
+            return;
+        };
+        let bounds = shape.bounding_rect();
+
+        self.leave_field(false);
+        self.start_field(idx);
+        self.data_entry_field = Some(idx);
+        self.set_selected_shape(Some(idx));
+        self.data_entry_refocus = true;
+        if self.data_entry_auto_zoom {
+            self.zoom_to_rect(bounds);
+        }
+        debug!("Entering field {}", idx);
+    }
+
+    /// Accept the current field's value and move to the next field
+    ///
+    /// Fields whose condition isn't met are passed over. Returns false,
+    /// staying on the field, if its value breaks its rule or it is the last
+    /// field that applies.
+    pub fn advance_field(&mut self) -> bool {
+        let Some(idx) = self.data_entry_field else {
+            return false;
+        };
+        if field_applies(&self.shapes, idx)
+            && let Some(Err(issue)) = self.shapes.get(idx).map(|shape| shape.check_value())
+        {
+            self.data_entry_status = Some(format!("Fix this field first: {}", issue));
+            self.data_entry_refocus = true;
+            return false;
+        }
+
+        self.leave_field(true);
+        if let Some(next) = self.next_field(idx) {
+            self.data_entry_status = None;
+            self.focus_field(next);
+            true
+        } else {
+            self.start_field(idx);
+            let issues = self
+                .applicable_fields()
+                .into_iter()
+                .filter(|idx| self.shapes[*idx].check_value().is_err())
+                .count();
+            self.data_entry_status = Some(match issues {
+                0 => "Last field reached; all values are valid".to_string(),
+                n => format!("Last field reached; {} field(s) still have problems", n),
+            });
+            self.data_entry_refocus = true;
+            false
+        }
+    }
+
+    /// Move back to the previous field
+    ///
+    /// Fields whose condition isn't met are passed over. Returns false if
+    /// already at the first field that applies.
+    pub fn previous_field(&mut self) -> bool {
+        let Some(idx) = self.data_entry_field else {
+            return false;
+        };
+        match self.previous_applicable_field(idx) {
+            Some(previous) => {
+                self.data_entry_status = None;
+                self.focus_field(previous);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remember the value a field had when data entry reached it
+    fn start_field(&mut self, idx: usize) {
+        self.data_entry_start = self
+            .shapes
+            .get(idx)
+            .map(|shape| (idx, shape.value().to_string(), shape.confidence()));
+    }
+
+    /// Finish with the field being entered, counting a correction if its value changed
+    ///
+    /// `accepted` is true when the value was accepted with Enter or Next.
+    fn leave_field(&mut self, accepted: bool) {
+        let Some((idx, original, confidence)) = self.data_entry_start.take() else {
+            return;
+        };
+        let Some(shape) = self.shapes.get_mut(idx) else {
+            return;
+        };
+        let changed = shape.value() != original;
+        if changed {
+            self.session_stats.record_field_corrected();
+            debug!(idx, "Corrected field value");
+        }
+        if let Some(confidence) = confidence
+            && (changed || accepted)
+        {
+            shape.confirm_value();
+            let field = match shape.name() {
+                "" => format!("Field {}", idx + 1),
+                name => name.to_string(),
+            };
+            self.ocr_samples.push(OcrSample {
+                field,
+                proposed: original,
+                corrected: shape.value().to_string(),
+                confidence,
+            });
+        }
+    }
+
+    /// Indices of the fields that apply given the values entered so far, in tab order
+    fn applicable_fields(&self) -> Vec<usize> {
+        self.field_order()
+            .into_iter()
+            .filter(|idx| field_applies(&self.shapes, *idx))
+            .collect()
+    }
+
+    /// First field after `idx` in tab order that applies
+    fn next_field(&self, idx: usize) -> Option<usize> {
+        let order = self.field_order();
+        let position = order.iter().position(|i| *i == idx)?;
+        order[position + 1..].iter().copied().find(|i| field_applies(&self.shapes, *i))
+    }
+
+    /// Last field before `idx` in tab order that applies
+    fn previous_applicable_field(&self, idx: usize) -> Option<usize> {
+        let order = self.field_order();
+        let position = order.iter().position(|i| *i == idx)?;
+        order[..position].iter().copied().rfind(|i| field_applies(&self.shapes, *i))
+    }
+
+    /// Set the value of the field being entered
+    pub fn set_data_entry_value(&mut self, value: impl Into<String>) {
+        if let Some(shape) = self.data_entry_field.and_then(|idx| self.shapes.get_mut(idx)) {
+            shape.set_value(value);
+        }
+    }
+
+    /// Show the data entry window
+    ///
+    /// Returns true if the window was shown.
+    pub fn show_data_entry_panel(&mut self, ctx: &egui::Context) -> bool {
+        if !self.show_data_entry {
+            return false;
+        }
+
+        let mut panel_open = true;
+        let mut advance = false;
+        let mut back = false;
+        let mut close = false;
+        let mut jump_to = None;
+        let mut comment_on = None;
+        let field = self.data_entry_field.filter(|idx| *idx < self.shapes.len());
+        let order = self.field_order();
+        let mut edit_order = false;
+        let refocus = std::mem::take(&mut self.data_entry_refocus);
+        let preview = field
+            .filter(|_| self.data_entry_crop_preview)
+            .and_then(|idx| self.field_crop_image(idx));
+
+        egui::Window::new("Data Entry")
+            .open(&mut panel_open)
+            .default_width(300.0)
+            .show(ctx, |ui| {
+                let Some(idx) = field else {
+                    ui.label("Draw shapes over the form's fields to enter their values.");
+                    return;
+                };
+
+                // Other named fields can control whether this one applies
+                let conditions: Vec<FieldCondition> = self
+                    .shapes
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, other)| *i != idx && !other.name().is_empty())
+                    .map(|(_, other)| match other.rule().format {
+                        FieldFormat::Checkbox => FieldCondition::Checked(other.name().to_string()),
+                        _ => FieldCondition::Filled(other.name().to_string()),
+                    })
+                    .collect();
+
+                let shape = &mut self.shapes[idx];
+                let name = match shape.name() {
+                    "" => format!("Field {}", idx + 1),
+                    name => name.to_string(),
+                };
+                ui.horizontal(|ui| {
+                    ui.strong(name);
+                    let position = order.iter().position(|i| *i == idx).unwrap_or(idx);
+                    ui.weak(format!("{} of {}", position + 1, order.len()));
+                    if ui.small_button("Comment…").clicked() {
+                        comment_on = Some(idx);
+                    }
+                });
+
+                if let Some(image) = preview {
+                    ui.add(image);
+                }
+
+                let mut value = shape.value().to_string();
+                let edit = ui.add(
+                    egui::TextEdit::singleline(&mut value)
+                        .id(ui.id().with("data_entry_value"))
+                        .desired_width(f32::INFINITY)
+                        .hint_text(format_hint(shape.rule())),
+                );
+                if edit.changed() {
+                    shape.set_value(value);
+                }
+                if refocus {
+                    edit.request_focus();
+                }
+                if edit.lost_focus() {
+                    ui.input(|i| {
+                        if i.key_pressed(egui::Key::Enter) {
+                            if i.modifiers.shift {
+                                back = true;
+                            } else {
+                                advance = true;
+                            }
+                        }
+                        close = i.key_pressed(egui::Key::Escape);
+                    });
+                }
+
+                // Problems are announced as they are typed
+                if let Err(issue) = shape.check_value() {
+                    ui.colored_label(ui.visuals().error_fg_color, format!("⚠ {}", issue));
+                }
+
+                let mut rule = shape.rule();
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("data_entry_format")
+                        .selected_text(rule.format.to_string())
+                        .show_ui(ui, |ui| {
+                            for format in FieldFormat::iter() {
+                                ui.selectable_value(&mut rule.format, format, format.to_string());
+                            }
+                        });
+                    ui.checkbox(&mut rule.required, "Required");
+                });
+                if rule != shape.rule() {
+                    shape.set_rule(rule);
+                }
+
+                let mut condition = shape.condition().cloned();
+                ui.horizontal(|ui| {
+                    ui.label("Applies");
+                    egui::ComboBox::from_id_salt("data_entry_condition")
+                        .selected_text(condition.as_ref().map_or("always".to_string(), |c| c.to_string()))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut condition, None, "always");
+                            for option in conditions {
+                                let label = option.to_string();
+                                ui.selectable_value(&mut condition, Some(option), label);
+                            }
+                        });
+                });
+                if condition.as_ref() != shape.condition() {
+                    shape.set_condition(condition);
+                }
+
+                if let Some(status) = &self.data_entry_status {
+                    ui.label(status);
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    back |= ui.button("◀ Previous").clicked();
+                    advance |= ui.button("Next ▶").clicked();
+                    ui.checkbox(&mut self.data_entry_auto_zoom, "Zoom to field");
+                    ui.checkbox(&mut self.data_entry_crop_preview, "Show image");
+                });
+                ui.horizontal(|ui| {
+                    ui.weak("Enter: next · Shift+Enter: previous · Esc: close");
+                    edit_order = ui.small_button("Tab Order…").clicked();
+                });
+
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    for &i in &order {
+                        if !field_applies(&self.shapes, i) {
+                            continue;
+                        }
+                        let shape = &self.shapes[i];
+                        let marker = if shape.check_value().is_ok() { "✓" } else { "⚠" };
+                        let label = match shape.name() {
+                            "" => format!("{} Field {}: {}", marker, i + 1, shape.value()),
+                            name => format!("{} {}: {}", marker, name, shape.value()),
+                        };
+                        let row = ui.selectable_label(i == idx, label);
+                        if row.clicked() {
+                            jump_to = Some(i);
+                        }
+                        row.context_menu(|ui| {
+                            if ui.button("Comment…").clicked() {
+                                comment_on = Some(i);
+                            }
+                        });
+                    }
+                });
+            });
+
+        if edit_order {
+            self.show_tab_order = true;
+        }
+        if let Some(idx) = comment_on {
+            self.comment_on(CommentTarget::Shape(idx));
+        }
+        if close || !panel_open {
+            self.leave_field(false);
+            self.show_data_entry = false;
+        } else if let Some(idx) = jump_to {
+            self.data_entry_status = None;
+            self.focus_field(idx);
+        } else if back {
+            self.previous_field();
+        } else if advance {
+            self.advance_field();
+        }
+        true
+    }
+
+    /// Image widget showing the form image under a field, scaled to fit the window
+    fn field_crop_image(&self, idx: usize) -> Option<egui::Image<'static>> {
+        let texture = self.form_image.as_ref()?;
+        let image_size = self.form_image_size?;
+        let (x, y, width, height) = self.field_image_crop(idx)?;
+
+        let uv = egui::Rect::from_min_size(
+            egui::pos2(x as f32 / image_size.x, y as f32 / image_size.y),
+            egui::vec2(width as f32 / image_size.x, height as f32 / image_size.y),
+        );
+        let size = egui::vec2(width as f32, height as f32);
+        let scale = (CROP_PREVIEW_MAX.x / size.x).min(CROP_PREVIEW_MAX.y / size.y);
+        Some(egui::Image::new((texture.id(), size * scale)).uv(uv))
+    }
+}
+
+/// Hint shown in an empty value box
+fn format_hint(rule: FieldRule) -> &'static str {
+    match (rule.format, rule.required) {
+        (FieldFormat::Text, true) => "Text (required)",
+        (FieldFormat::Text, false) => "Text",
+        (FieldFormat::Number, true) => "Number (required)",
+        (FieldFormat::Number, false) => "Number",
+        (FieldFormat::Date, true) => "YYYY-MM-DD (required)",
+        (FieldFormat::Date, false) => "YYYY-MM-DD",
+        (FieldFormat::Checkbox, true) => "x if checked (required)",
+        (FieldFormat::Checkbox, false) => "x if checked, blank if not",
+    }
+}
@@ -0,0 +1,198 @@
+//! On-disk cache of detection results
+//!
+//! Text and logo detection take seconds per page, yet are often re-run on
+//! an image they have already seen: after reopening a project, or with the
+//! same settings as last time. Each pass stores the detections it found
+//! under a key made of a hash of the image's contents and a hash of the
+//! detector configuration, so a later pass over identical pixels with an
+//! identical configuration loads the stored detections instead, even if
+//! the image file was renamed or moved. Editing the image, or changing the
+//! model, thresholds or logo templates, gives a different key.
+//!
+//! Each project has its own directory under [`detection_cache_dir`], so
+//! clearing one project's cache leaves the others alone. Entries are
+//! compressed when compression is on in the canvas settings.
+
+use super::compression::{decompressor, is_compressed};
+use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas};
+use crate::{Shape, config_dir};
+use form_factor_core::IoOperation;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::{debug, debug_span, info, instrument};
+#[cfg(all(any(feature = "text-detection", feature = "logo-detection"), not(target_arch = "wasm32")))]
+use tracing::warn;
+
+/// Directory holding the detection caches of all projects
+pub fn detection_cache_dir() -> PathBuf {
+    config_dir().join("detection_cache")
+}
+
+/// Remove every cached detection under `dir`
+///
+/// Returns the number of cache files removed. A missing directory has
+/// nothing to remove.
+pub fn clear_detection_cache(dir: &Path) -> Result<usize, CanvasError> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+    let count = cache_files(dir).len();
+    std::fs::remove_dir_all(dir)
+        .map_err(|e| CanvasError::io(e, dir.display().to_string(), IoOperation::Write, line!(), file!()))?;
+    info!(count, dir = %dir.display(), "Cleared detection cache");
+    Ok(count)
+}
+
+/// Cache files under `dir` and its project directories
+fn cache_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .flat_map(|path| {
+            if path.is_dir() {
+                cache_files(&path)
+            } else if path.extension().is_some_and(|ext| ext == "json") {
+                vec![path]
+            } else {
+                Vec::new()
+            }
+        })
+        .collect()
+}
+
+/// 64-bit FNV-1a hash, stable across runs and platforms
+pub(super) fn content_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Detections stored for one image and detector configuration
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    /// Detector configuration the detections were found with
+    detector: String,
+    /// Detections in image pixels
+    detections: Vec<Shape>,
+}
+
+impl DrawingCanvas {
+    /// This project's cache directory under `root`, e.g. [`detection_cache_dir`]
+    pub fn project_cache_dir(&self, root: &Path) -> PathBuf {
+        let name: String = self
+            .project_name
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        match name.trim_matches('_') {
+            "" => root.join("Untitled"),
+            _ => root.join(name),
+        }
+    }
+
+    /// Cache key of the form image under a detector configuration
+    ///
+    /// `detector` describes everything that affects the results, such as
+    /// the model, thresholds and templates.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no form image is loaded or it can't be read.
+    pub fn detection_cache_key(&self, detector: &str) -> Result<String, CanvasError> {
+        let path = self
+            .form_image_path
+            .as_ref()
+            .ok_or_else(|| CanvasError::new(CanvasErrorKind::NoFormImageLoaded, line!(), file!()))?;
+        let bytes = std::fs::read(path).map_err(|e| CanvasError::io(e, path, IoOperation::Read, line!(), file!()))?;
+        Ok(format!(
+            "{:016x}-{:016x}",
+            content_hash(&bytes),
+            content_hash(detector.as_bytes())
+        ))
+    }
+
+    /// Add the detections cached under `key` in `dir`
+    ///
+    /// Returns the number of detections added, or `None` on a cache miss.
+    /// Unreadable cache files count as misses.
+    #[instrument(skip(self))]
+    pub fn load_cached_detections(&mut self, dir: &Path, key: &str) -> Option<usize> {
+        let path = dir.join(format!("{}.json", key));
+        let bytes = std::fs::read(&path).ok()?;
+        let entry: Result<CacheEntry, String> = debug_span!("deserialize_detections", len = bytes.len())
+            .in_scope(|| {
+                if is_compressed(&bytes) {
+                    decompressor(&bytes)
+                        .map_err(|e| e.to_string())
+                        .and_then(|reader| serde_json::from_reader(reader).map_err(|e| e.to_string()))
+                } else {
+                    serde_json::from_slice(&bytes).map_err(|e| e.to_string())
+                }
+            });
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                debug!(path = %path.display(), "Ignoring unreadable cache entry: {}", e);
+                return None;
+            }
+        };
+        if let Some(invalid) = entry.detections.iter().find_map(|shape| shape.validate().err()) {
+            debug!(path = %path.display(), "Ignoring cache entry with invalid detection: {}", invalid);
+            return None;
+        }
+
+        let count = entry.detections.len();
+        let _insert = debug_span!("insert_detections", count).entered();
+        let first = self.detections.len();
+        self.detections.extend(entry.detections);
+        self.highlight_new_detections(first..self.detections.len());
+        info!(count, detector = %entry.detector, "Loaded detections from cache");
+        Some(count)
+    }
+
+    /// Store the detections from index `first` on under `key` in `dir`
+    ///
+    /// Returns the path of the cache file.
+    #[instrument(skip(self))]
+    pub fn cache_detections(
+        &self,
+        dir: &Path,
+        key: &str,
+        detector: &str,
+        first: usize,
+    ) -> Result<PathBuf, CanvasError> {
+        let entry = CacheEntry {
+            detector: detector.to_string(),
+            detections: self.detections.get(first..).unwrap_or_default().to_vec(),
+        };
+        let json = serde_json::to_vec(&entry)
+            .map_err(|e| CanvasError::new(CanvasErrorKind::Serialization(e.to_string()), line!(), file!()))?;
+        let json = self.maybe_compress(json)?;
+
+        std::fs::create_dir_all(dir)
+            .map_err(|e| CanvasError::io(e, dir.display().to_string(), IoOperation::Create, line!(), file!()))?;
+        let path = dir.join(format!("{}.json", key));
+        std::fs::write(&path, json)
+            .map_err(|e| CanvasError::io(e, path.display().to_string(), IoOperation::Write, line!(), file!()))?;
+        debug!(count = entry.detections.len(), path = %path.display(), "Cached detections");
+        Ok(path)
+    }
+
+    /// Cache directory and key for a detection pass, or `None` if caching is off
+    #[cfg(all(any(feature = "text-detection", feature = "logo-detection"), not(target_arch = "wasm32")))]
+    pub(super) fn detection_cache(&self, detector: &str) -> Option<(PathBuf, String)> {
+        if !self.detection_settings.cache_results {
+            return None;
+        }
+        match self.detection_cache_key(detector) {
+            Ok(key) => Some((self.project_cache_dir(&detection_cache_dir()), key)),
+            Err(e) => {
+                warn!("Detection cache unavailable: {}", e);
+                None
+            }
+        }
+    }
+}
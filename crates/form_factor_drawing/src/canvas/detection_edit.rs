@@ -0,0 +1,353 @@
+//! Splitting and merging detection regions
+//!
+//! Detectors rarely find regions the size of the form's fields: one box
+//! often covers a label and its value, while a long value comes back as
+//! several boxes. Splitting cuts a rectangular detection in two along a
+//! horizontal or vertical line; merging joins selected detections into one
+//! box covering them all. Shift+click with the Select tool picks the
+//! detections to merge.
+//!
+//! OCR text follows the regions where it can. Splitting divides the words
+//! OCR reported by which side of the cut they lie on, and merging joins the
+//! texts in reading order. When the words or a part's text are missing, the
+//! text is dropped instead, so the next differential OCR pass reads the new
+//! regions.
+
+use super::core::DrawingCanvas;
+use super::ocr_pass::OcrRecord;
+use super::ocr_words::OcrWord;
+use super::tab_order::{ReadingDirection, reading_order};
+use crate::{CanvasPos, CommentTarget, ImagePos, Shape};
+use egui::{Color32, Pos2, Rect, Stroke};
+use tracing::{debug, instrument, warn};
+
+/// Smallest width or height of a part left by a split, in image pixels
+pub const MIN_SPLIT_SIZE: f32 = 2.0;
+
+/// Outline of detections selected for merging
+const MERGE_SELECTION_STROKE: Stroke = Stroke {
+    width: 3.0,
+    color: Color32::from_rgb(255, 215, 0),
+};
+
+/// Direction of the line a detection is split along
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumIter, strum::Display)]
+pub enum SplitDirection {
+    /// Cut along a horizontal line into a top and a bottom part
+    Horizontal,
+    /// Cut along a vertical line into a left and a right part
+    Vertical,
+}
+
+impl DrawingCanvas {
+    /// Split a rectangular detection in two along a line at `at`, in image pixels
+    ///
+    /// `at` is a y coordinate for horizontal splits and an x coordinate for
+    /// vertical ones. The detection keeps the top or left part and a copy of
+    /// it is added for the other part; its index is returned. Returns `None`
+    /// if the detection is not an axis-aligned rectangle or either part
+    /// would be smaller than [`MIN_SPLIT_SIZE`]; a rotated rectangle can't be
+    /// cut along an image row or column without losing its rotation.
+    #[instrument(skip(self))]
+    pub fn split_detection(&mut self, index: usize, direction: SplitDirection, at: f32) -> Option<usize> {
+        let detection = self.detections.get(index)?;
+        let Shape::Rectangle(rect) = detection else {
+            warn!("Only rectangular detections can be split");
+            return None;
+        };
+        if !is_axis_aligned(rect.corners()) {
+            warn!("Rotated detections cannot be split");
+            return None;
+        }
+        let bounds = detection.bounding_rect();
+        if !at.is_finite() {
+            warn!("Split line is not a finite coordinate");
+            return None;
+        }
+        let (first, second) = match direction {
+            SplitDirection::Horizontal => (
+                Rect::from_min_max(bounds.min, Pos2::new(bounds.max.x, at)),
+                Rect::from_min_max(Pos2::new(bounds.min.x, at), bounds.max),
+            ),
+            SplitDirection::Vertical => (
+                Rect::from_min_max(bounds.min, Pos2::new(at, bounds.max.y)),
+                Rect::from_min_max(Pos2::new(at, bounds.min.y), bounds.max),
+            ),
+        };
+        if [first, second]
+            .iter()
+            .any(|part| part.width() < MIN_SPLIT_SIZE || part.height() < MIN_SPLIT_SIZE)
+        {
+            warn!(?bounds, "Split line leaves a part too small");
+            return None;
+        }
+
+        let mut parts = [rect.clone(), rect.clone()];
+        for (part, bounds) in parts.iter_mut().zip([first, second]) {
+            if let Err(e) = part.set_corners(corners(bounds)) {
+                warn!("Could not split detection: {}", e);
+                return None;
+            }
+        }
+        let [first_part, second_part] = parts;
+
+        // Words go to the part holding their middle
+        let words = self.ocr_words(index).to_vec();
+        let had_text = self.ocr_text(index).is_some();
+        let (first_words, second_words): (Vec<OcrWord>, Vec<OcrWord>) =
+            words.into_iter().partition(|word| first.contains(word.bounds.center()));
+
+        self.detections[index] = Shape::Rectangle(first_part);
+        self.detections.push(Shape::Rectangle(second_part));
+        let new_index = self.detections.len() - 1;
+        self.stop_detection_flashes();
+        if let Some((_, tag)) = self.detection_tags.iter().find(|(idx, _)| *idx == index) {
+            self.detection_tags.push((new_index, tag.clone()));
+        }
+        if let Some(position) = self.ocr_order.iter().position(|idx| *idx == index) {
+            self.ocr_order.insert(position + 1, new_index);
+        }
+        if self.ocr_edit_target() == Some(index) {
+            self.cancel_ocr_edit();
+        }
+        self.selected_ocr_word = self.selected_ocr_word.filter(|(detection, _)| *detection != index);
+        self.selected_detections.retain(|idx| *idx != index);
+
+        if had_text && !(first_words.is_empty() && second_words.is_empty()) {
+            self.record_words_as_text(index, first_words);
+            self.record_words_as_text(new_index, second_words);
+        } else {
+            // Without word boxes the text can't be divided; both parts are read again
+            self.ocr_records.retain(|record| record.detection != index);
+            let flags: Vec<_> = self.pii_flags.iter().filter(|(idx, _)| *idx == index).map(|(_, kind)| *kind).collect();
+            self.pii_flags.extend(flags.into_iter().map(|kind| (new_index, kind)));
+        }
+
+        debug!(new_index, %direction, at, "Split detection");
+        Some(new_index)
+    }
+
+    /// Merge detections into one rectangle covering them all
+    ///
+    /// The detection with the lowest index takes the merged region and keeps
+    /// its name and style; the others are removed. Their OCR text is joined
+    /// in reading order if every detection had been read, their comment
+    /// threads move to the merged detection, and it takes the first part's
+    /// subtype if it had none of its own. Returns the index
+    /// of the merged detection, or `None` if fewer than two detections are
+    /// given or any is missing or not a rectangle.
+    #[instrument(skip(self))]
+    pub fn merge_detections(&mut self, indices: &[usize]) -> Option<usize> {
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+        if indices.len() < 2 {
+            return None;
+        }
+        let mut bounds = Vec::with_capacity(indices.len());
+        for idx in &indices {
+            match self.detections.get(*idx) {
+                Some(detection @ Shape::Rectangle(_)) => bounds.push(detection.bounding_rect()),
+                Some(_) => {
+                    warn!(idx, "Only rectangular detections can be merged");
+                    return None;
+                }
+                None => {
+                    warn!(idx, "Cannot merge missing detection");
+                    return None;
+                }
+            }
+        }
+        let merged_bounds = bounds.iter().fold(Rect::NOTHING, |all, part| all.union(*part));
+        let target = indices[0];
+        let Some(Shape::Rectangle(mut merged)) = self.detections.get(target).cloned() else {
+            return None;
+        };
+        if let Err(e) = merged.set_corners(corners(merged_bounds)) {
+            warn!("Could not merge detections: {}", e);
+            return None;
+        }
+
+        // The parts' text, in reading order
+        let order = reading_order(&bounds, ReadingDirection::LeftToRight);
+        let records: Option<Vec<(Rect, OcrRecord)>> = order
+            .iter()
+            .map(|i| {
+                let record = self.ocr_records.iter().find(|record| record.detection == indices[*i])?;
+                Some((bounds[*i], record.clone()))
+            })
+            .collect();
+        let flags: Vec<_> = self
+            .pii_flags
+            .iter()
+            .filter(|(idx, _)| indices.contains(idx))
+            .map(|(_, kind)| *kind)
+            .collect();
+
+        self.selected_detections.clear();
+        self.detections[target] = Shape::Rectangle(merged);
+        for thread in &mut self.comment_threads {
+            if matches!(thread.target, CommentTarget::Detection(idx) if indices[1..].contains(&idx)) {
+                thread.target = CommentTarget::Detection(target);
+            }
+        }
+        if !self.detection_tags.iter().any(|(idx, _)| *idx == target) {
+            let tag = indices[1..]
+                .iter()
+                .find_map(|part| self.detection_tags.iter().find(|(idx, _)| idx == part))
+                .map(|(_, tag)| tag.clone());
+            if let Some(tag) = tag {
+                self.detection_tags.push((target, tag));
+                self.detection_tags.sort_by_key(|(idx, _)| *idx);
+            }
+        }
+        for idx in indices[1..].iter().rev() {
+            self.delete_detection(*idx);
+        }
+        self.ocr_records.retain(|record| record.detection != target);
+        self.pii_flags.retain(|(idx, _)| *idx != target);
+        if self.ocr_edit_target() == Some(target) {
+            self.cancel_ocr_edit();
+        }
+        self.selected_ocr_word = self.selected_ocr_word.filter(|(detection, _)| *detection != target);
+
+        match records {
+            Some(parts) => {
+                let text = join_lines(parts.iter().map(|(bounds, record)| (*bounds, record.text.trim())));
+                let confidences: Vec<f32> = parts.iter().filter_map(|(_, record)| record.confidence).collect();
+                let words = parts.into_iter().flat_map(|(_, record)| record.words).collect();
+                self.record_ocr(target, &text, mean(&confidences));
+                self.record_ocr_words(target, words);
+                self.flag_pii(target, &text);
+            }
+            None => {
+                // Some part was never read, so the merged region is read again
+                self.pii_flags.extend(flags.into_iter().map(|kind| (target, kind)));
+            }
+        }
+
+        debug!(target, merged = indices.len(), "Merged detections");
+        Some(target)
+    }
+
+    /// Add a detection to the detections selected for merging, or remove it
+    pub fn toggle_detection_selection(&mut self, index: usize) {
+        if index >= self.detections.len() {
+            return;
+        }
+        match self.selected_detections.iter().position(|idx| *idx == index) {
+            Some(position) => {
+                self.selected_detections.remove(position);
+            }
+            None => self.selected_detections.push(index),
+        }
+    }
+
+    /// Detections selected for merging, in the order they were picked
+    pub fn selected_detections(&self) -> &[usize] {
+        &self.selected_detections
+    }
+
+    /// Clear the detections selected for merging
+    pub fn clear_detection_selection(&mut self) {
+        self.selected_detections.clear();
+    }
+
+    /// Merge the detections selected for merging
+    pub fn merge_selected_detections(&mut self) -> Option<usize> {
+        let indices = self.selected_detections.clone();
+        self.merge_detections(&indices)
+    }
+
+    /// Toggle the detection at a canvas position in the merge selection
+    ///
+    /// Returns false if there is no detection there.
+    pub(super) fn toggle_detection_at(&mut self, pos: Pos2) -> bool {
+        match self.object_at(pos) {
+            Some(CommentTarget::Detection(idx)) => {
+                self.toggle_detection_selection(idx);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Outline the detections selected for merging
+    pub(super) fn draw_detection_selection(&self, painter: &egui::Painter, to_screen: &egui::emath::TSTransform) {
+        for idx in &self.selected_detections {
+            let Some(bounds) = self.detections.get(*idx).map(Shape::bounding_rect) else {
+                continue;
+            };
+            let (Some(min), Some(max)) = (
+                self.coordinate_mapper.image_to_canvas(ImagePos(bounds.min)),
+                self.coordinate_mapper.image_to_canvas(ImagePos(bounds.max)),
+            ) else {
+                continue;
+            };
+            let rect = Rect::from_min_max(to_screen.mul_pos(min.0), to_screen.mul_pos(max.0));
+            painter.rect_stroke(rect, 0.0, MERGE_SELECTION_STROKE, egui::StrokeKind::Outside);
+        }
+    }
+
+    /// Split and merge entries in a detection's context menu
+    pub(super) fn split_merge_menu(&mut self, ui: &mut egui::Ui, idx: usize) {
+        if let Some(ImagePos(at)) = self.coordinate_mapper.canvas_to_image(CanvasPos(self.context_menu_pos)) {
+            if ui.button("Split Horizontally Here").clicked() {
+                self.split_detection(idx, SplitDirection::Horizontal, at.y);
+            }
+            if ui.button("Split Vertically Here").clicked() {
+                self.split_detection(idx, SplitDirection::Vertical, at.x);
+            }
+        }
+        let selected = self.selected_detections.contains(&idx);
+        if ui.button(if selected { "Deselect for Merge" } else { "Select for Merge" }).clicked() {
+            self.toggle_detection_selection(idx);
+        }
+        let count = self.selected_detections.len();
+        if count >= 2 && ui.button(format!("Merge {} Selected Detections", count)).clicked() {
+            self.merge_selected_detections();
+        }
+    }
+
+    /// Record words as the text of a detection, one line per row of words
+    fn record_words_as_text(&mut self, detection_index: usize, words: Vec<OcrWord>) {
+        let text = join_lines(words.iter().map(|word| (word.bounds, word.text.as_str())));
+        let confidences: Vec<f32> = words.iter().map(|word| word.confidence).collect();
+        self.record_ocr(detection_index, &text, mean(&confidences));
+        self.record_ocr_words(detection_index, words);
+        self.flag_pii(detection_index, &text);
+    }
+}
+
+/// Corners of a box, clockwise from the top left
+fn corners(bounds: Rect) -> [Pos2; 4] {
+    [bounds.left_top(), bounds.right_top(), bounds.right_bottom(), bounds.left_bottom()]
+}
+
+/// Whether every edge of a rectangle runs along an image row or column
+fn is_axis_aligned(corners: &[Pos2; 4]) -> bool {
+    const TOLERANCE: f32 = 1e-3;
+    (0..4).all(|i| {
+        let edge = corners[(i + 1) % 4] - corners[i];
+        edge.x.abs() <= TOLERANCE || edge.y.abs() <= TOLERANCE
+    })
+}
+
+/// Join pieces of text in reading order, starting a new line where a piece lies below the last
+fn join_lines<'a>(pieces: impl Iterator<Item = (Rect, &'a str)>) -> String {
+    let mut text = String::new();
+    let mut previous: Option<Rect> = None;
+    for (bounds, piece) in pieces {
+        if let Some(previous) = previous {
+            text.push(if bounds.center().y > previous.bottom() { '\n' } else { ' ' });
+        }
+        text.push_str(piece);
+        previous = Some(bounds);
+    }
+    text
+}
+
+/// Mean of the values, or `None` if there are none
+fn mean(values: &[f32]) -> Option<f32> {
+    (!values.is_empty()).then(|| values.iter().sum::<f32>() / values.len() as f32)
+}
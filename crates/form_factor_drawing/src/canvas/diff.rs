@@ -0,0 +1,501 @@
+//! Differences between two versions of a project
+//!
+//! Annotation passes are audited by comparing the project before and after
+//! a pass. A [`ProjectDiff`] pairs up the shapes and detections of the two
+//! versions, first by name and then, for unnamed or renamed objects, by
+//! identical geometry. Objects left over were added or removed; paired
+//! objects whose geometry differs were moved, and any differing field
+//! properties are listed as field changes. Changes to the form image are
+//! listed too.
+//!
+//! The diff window lists the changes, and while it is open the canvas
+//! draws them over the current project: added objects in green, removed
+//! ones dashed in red, moved ones in orange with their old outline dashed,
+//! and objects with edited fields in blue.
+
+use super::core::{CanvasError, DrawingCanvas};
+use super::journal::read_project;
+use crate::{LayerType, Shape, StrokeStyle};
+use egui::{Color32, Stroke};
+use std::path::Path;
+use tracing::{info, instrument};
+
+/// Outline color of added objects in the diff overlay
+const ADDED_COLOR: Color32 = Color32::from_rgb(40, 170, 70);
+/// Outline color of removed objects in the diff overlay
+const REMOVED_COLOR: Color32 = Color32::from_rgb(210, 40, 40);
+/// Outline color of moved objects in the diff overlay
+const MOVED_COLOR: Color32 = Color32::from_rgb(235, 140, 0);
+/// Outline color of objects with edited fields in the diff overlay
+const EDITED_COLOR: Color32 = Color32::from_rgb(30, 110, 230);
+/// Outline width of objects in the diff overlay, in canvas units
+const DIFF_STROKE_WIDTH: f32 = 3.0;
+
+/// Change to one shape or detection between two versions of a project
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShapeChange {
+    /// Object only in the later version
+    Added {
+        /// Layer holding the object
+        layer: LayerType,
+        /// Index of the object in the later version
+        index: usize,
+        /// The added object
+        shape: Shape,
+    },
+    /// Object only in the earlier version
+    Removed {
+        /// Layer holding the object
+        layer: LayerType,
+        /// Index of the object in the earlier version
+        index: usize,
+        /// The removed object
+        shape: Shape,
+    },
+    /// Object in both versions whose geometry changed: moved, resized or reshaped
+    Moved {
+        /// Layer holding the object
+        layer: LayerType,
+        /// Index of the object in the later version
+        index: usize,
+        /// The object in the earlier version
+        before: Box<Shape>,
+        /// The object in the later version
+        after: Box<Shape>,
+    },
+}
+
+impl ShapeChange {
+    /// Layer holding the changed object
+    pub fn layer(&self) -> LayerType {
+        match self {
+            ShapeChange::Added { layer, .. } | ShapeChange::Removed { layer, .. } | ShapeChange::Moved { layer, .. } => {
+                *layer
+            }
+        }
+    }
+
+    /// Name of the changed object, or `Field N` for unnamed objects
+    pub fn label(&self) -> String {
+        match self {
+            ShapeChange::Added { index, shape, .. } | ShapeChange::Removed { index, shape, .. } => {
+                object_label(shape, *index)
+            }
+            ShapeChange::Moved { index, after, .. } => object_label(after, *index),
+        }
+    }
+}
+
+/// Change to one field property of an object in both versions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    /// Layer holding the object
+    pub layer: LayerType,
+    /// Index of the object in the later version
+    pub index: usize,
+    /// Name of the object in the later version, or `Field N` if unnamed
+    pub field: String,
+    /// Property that changed, e.g. `value`
+    pub property: String,
+    /// Property before the change
+    pub before: String,
+    /// Property after the change
+    pub after: String,
+}
+
+/// Change to the form image between two versions of a project
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImageChange {
+    /// A different image file, or an image added or removed
+    Replaced {
+        /// Image path in the earlier version
+        before: Option<String>,
+        /// Image path in the later version
+        after: Option<String>,
+    },
+    /// The image was rotated
+    Rotated {
+        /// Rotation in the earlier version, in radians
+        before: f32,
+        /// Rotation in the later version, in radians
+        after: f32,
+    },
+}
+
+impl std::fmt::Display for ImageChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = |path: &Option<String>| path.clone().unwrap_or_else(|| "none".to_string());
+        match self {
+            ImageChange::Replaced { before, after } => {
+                write!(f, "Form image {} → {}", path(before), path(after))
+            }
+            ImageChange::Rotated { before, after } => {
+                write!(f, "Image rotation {:.1}° → {:.1}°", before.to_degrees(), after.to_degrees())
+            }
+        }
+    }
+}
+
+/// Shapes, fields and image changed between two versions of a project
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectDiff {
+    before_name: String,
+    after_name: String,
+    shape_changes: Vec<ShapeChange>,
+    field_changes: Vec<FieldChange>,
+    image_changes: Vec<ImageChange>,
+    /// Whether the canvas draws the changes while the window is open
+    show_overlay: bool,
+}
+
+impl ProjectDiff {
+    /// Compare an earlier version of a project with a later one
+    #[instrument(skip_all, fields(before = before.shapes.len(), after = after.shapes.len()))]
+    pub fn compare(before: &DrawingCanvas, after: &DrawingCanvas) -> Self {
+        let mut diff = Self {
+            before_name: before.project_name.clone(),
+            after_name: after.project_name.clone(),
+            shape_changes: Vec::new(),
+            field_changes: Vec::new(),
+            image_changes: Vec::new(),
+            show_overlay: true,
+        };
+        diff.compare_layer(LayerType::Shapes, &before.shapes, &after.shapes);
+        diff.compare_layer(LayerType::Detections, &before.detections, &after.detections);
+
+        if before.form_image_path != after.form_image_path {
+            diff.image_changes.push(ImageChange::Replaced {
+                before: before.form_image_path.clone(),
+                after: after.form_image_path.clone(),
+            });
+        }
+        if before.form_image_rotation != after.form_image_rotation {
+            diff.image_changes.push(ImageChange::Rotated {
+                before: before.form_image_rotation,
+                after: after.form_image_rotation,
+            });
+        }
+        diff
+    }
+
+    /// Compare two project files
+    ///
+    /// The versions are named after the files.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either file can't be read or isn't a valid project.
+    #[instrument]
+    pub fn from_files(before: &Path, after: &Path) -> Result<Self, CanvasError> {
+        let load = |path: &Path| -> Result<DrawingCanvas, CanvasError> {
+            let (mut canvas, _) = read_project(path, None)?;
+            if let Some(stem) = path.file_stem() {
+                canvas.project_name = stem.to_string_lossy().into_owned();
+            }
+            Ok(canvas)
+        };
+        Ok(Self::compare(&load(before)?, &load(after)?))
+    }
+
+    /// Name of the earlier version
+    pub fn before_name(&self) -> &str {
+        &self.before_name
+    }
+
+    /// Name of the later version
+    pub fn after_name(&self) -> &str {
+        &self.after_name
+    }
+
+    /// Added, removed and moved objects
+    pub fn shape_changes(&self) -> &[ShapeChange] {
+        &self.shape_changes
+    }
+
+    /// Field properties changed on objects in both versions
+    pub fn field_changes(&self) -> &[FieldChange] {
+        &self.field_changes
+    }
+
+    /// Changes to the form image
+    pub fn image_changes(&self) -> &[ImageChange] {
+        &self.image_changes
+    }
+
+    /// Whether the two versions are the same
+    pub fn is_empty(&self) -> bool {
+        self.shape_changes.is_empty() && self.field_changes.is_empty() && self.image_changes.is_empty()
+    }
+
+    /// Number of added, removed and moved objects
+    pub fn counts(&self) -> (usize, usize, usize) {
+        self.shape_changes
+            .iter()
+            .fold((0, 0, 0), |(added, removed, moved), change| match change {
+                ShapeChange::Added { .. } => (added + 1, removed, moved),
+                ShapeChange::Removed { .. } => (added, removed + 1, moved),
+                ShapeChange::Moved { .. } => (added, removed, moved + 1),
+            })
+    }
+
+    /// Pair up the objects of one layer and record what changed
+    fn compare_layer(&mut self, layer: LayerType, before: &[Shape], after: &[Shape]) {
+        let mut pairs: Vec<Option<usize>> = vec![None; after.len()];
+        let mut paired = vec![false; before.len()];
+
+        // Objects keep their names across passes, so pair by name first
+        for (new_idx, shape) in after.iter().enumerate() {
+            if shape.name().is_empty() {
+                continue;
+            }
+            if let Some(old_idx) = (0..before.len()).find(|&i| !paired[i] && before[i].name() == shape.name()) {
+                paired[old_idx] = true;
+                pairs[new_idx] = Some(old_idx);
+            }
+        }
+        // Unnamed and renamed objects are recognized by their unchanged geometry
+        for (new_idx, shape) in after.iter().enumerate() {
+            if pairs[new_idx].is_some() {
+                continue;
+            }
+            let geometry = shape.to_geometry();
+            if let Some(old_idx) = (0..before.len()).find(|&i| !paired[i] && before[i].to_geometry() == geometry) {
+                paired[old_idx] = true;
+                pairs[new_idx] = Some(old_idx);
+            }
+        }
+
+        for (index, old) in before.iter().enumerate() {
+            if !paired[index] {
+                self.shape_changes.push(ShapeChange::Removed {
+                    layer,
+                    index,
+                    shape: old.clone(),
+                });
+            }
+        }
+        for (index, (new, pair)) in after.iter().zip(&pairs).enumerate() {
+            let Some(old) = pair.map(|old_idx| &before[old_idx]) else {
+                self.shape_changes.push(ShapeChange::Added {
+                    layer,
+                    index,
+                    shape: new.clone(),
+                });
+                continue;
+            };
+            if old.to_geometry() != new.to_geometry() {
+                self.shape_changes.push(ShapeChange::Moved {
+                    layer,
+                    index,
+                    before: Box::new(old.clone()),
+                    after: Box::new(new.clone()),
+                });
+            }
+            let field = object_label(new, index);
+            for ((property, before), (_, after)) in field_properties(old).into_iter().zip(field_properties(new)) {
+                if before != after {
+                    self.field_changes.push(FieldChange {
+                        layer,
+                        index,
+                        field: field.clone(),
+                        property: property.to_string(),
+                        before,
+                        after,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Name of an object, or `Field N` for unnamed objects
+fn object_label(shape: &Shape, index: usize) -> String {
+    match shape.name() {
+        "" => format!("Field {}", index + 1),
+        name => name.to_string(),
+    }
+}
+
+/// Field properties compared between versions, as (property, text) pairs
+fn field_properties(shape: &Shape) -> [(&'static str, String); 5] {
+    let rule = shape.rule();
+    [
+        ("name", shape.name().to_string()),
+        ("value", shape.value().to_string()),
+        (
+            "rule",
+            if rule.required {
+                format!("{}, required", rule.format)
+            } else {
+                rule.format.to_string()
+            },
+        ),
+        (
+            "condition",
+            shape.condition().map_or_else(String::new, |condition| condition.to_string()),
+        ),
+        ("sensitive", shape.is_sensitive().to_string()),
+    ]
+}
+
+/// Copy of a shape outlined in a diff color with no fill
+fn outlined(shape: &Shape, color: Color32, style: StrokeStyle) -> Shape {
+    let mut outline = shape.clone();
+    outline.set_stroke(Stroke::new(DIFF_STROKE_WIDTH, color));
+    outline.set_stroke_style(style);
+    outline.set_fill(Color32::TRANSPARENT);
+    outline
+}
+
+impl DrawingCanvas {
+    /// Open the project diff window and its canvas overlay on a diff
+    pub fn start_project_diff(&mut self, diff: ProjectDiff) {
+        let (added, removed, moved) = diff.counts();
+        info!(
+            added,
+            removed,
+            moved,
+            fields = diff.field_changes.len(),
+            "Comparing {} with {}",
+            diff.before_name,
+            diff.after_name
+        );
+        self.project_diff = Some(diff);
+    }
+
+    /// Close the project diff window, returning the diff
+    pub fn close_project_diff(&mut self) -> Option<ProjectDiff> {
+        self.project_diff.take()
+    }
+
+    /// Draw the open diff's changes over the canvas
+    pub(super) fn draw_project_diff(&self, painter: &egui::Painter, transform: &egui::emath::TSTransform) {
+        let Some(diff) = self.project_diff.as_ref().filter(|diff| diff.show_overlay) else {
+            return;
+        };
+        let mapper = self.coordinate_mapper;
+        // Detections are in image pixels; they can only be placed once the image is fitted
+        let to_canvas = |layer: LayerType, shape: Shape| match layer {
+            LayerType::Detections => mapper.image_shape_to_canvas(&shape),
+            _ => Some(shape),
+        };
+
+        for change in &diff.shape_changes {
+            match change {
+                ShapeChange::Added { layer, shape, .. } => {
+                    if let Some(shape) = to_canvas(*layer, outlined(shape, ADDED_COLOR, StrokeStyle::Solid)) {
+                        self.render_shape_transformed(&shape, painter, transform);
+                    }
+                }
+                ShapeChange::Removed { layer, shape, .. } => {
+                    if let Some(shape) = to_canvas(*layer, outlined(shape, REMOVED_COLOR, StrokeStyle::Dashed)) {
+                        self.render_shape_transformed(&shape, painter, transform);
+                    }
+                }
+                ShapeChange::Moved {
+                    layer, before, after, ..
+                } => {
+                    let old = to_canvas(*layer, outlined(before, MOVED_COLOR, StrokeStyle::Dashed));
+                    let new = to_canvas(*layer, outlined(after, MOVED_COLOR, StrokeStyle::Solid));
+                    if let (Some(old), Some(new)) = (old, new) {
+                        self.render_shape_transformed(&old, painter, transform);
+                        self.render_shape_transformed(&new, painter, transform);
+                        painter.arrow(
+                            transform.mul_pos(old.bounding_rect().center()),
+                            transform.mul_pos(new.bounding_rect().center())
+                                - transform.mul_pos(old.bounding_rect().center()),
+                            Stroke::new(1.5, MOVED_COLOR),
+                        );
+                    }
+                }
+            }
+        }
+
+        // Edited objects that also moved already have an outline
+        for change in &diff.field_changes {
+            let moved = diff.shape_changes.iter().any(|shape_change| {
+                matches!(shape_change, ShapeChange::Moved { layer, index, .. }
+                    if *layer == change.layer && *index == change.index)
+            });
+            if moved {
+                continue;
+            }
+            let shapes = match change.layer {
+                LayerType::Detections => &self.detections,
+                _ => &self.shapes,
+            };
+            if let Some(shape) = shapes.get(change.index)
+                && let Some(shape) = to_canvas(change.layer, outlined(shape, EDITED_COLOR, StrokeStyle::Solid))
+            {
+                self.render_shape_transformed(&shape, painter, transform);
+            }
+        }
+    }
+
+    /// Show the project diff window
+    ///
+    /// Returns true if the window was shown.
+    pub fn show_project_diff_panel(&mut self, ctx: &egui::Context) -> bool {
+        let Some(diff) = &mut self.project_diff else {
+            return false;
+        };
+
+        let mut panel_open = true;
+        egui::Window::new("Project Diff")
+            .open(&mut panel_open)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.strong(&diff.before_name);
+                    ui.label("→");
+                    ui.strong(&diff.after_name);
+                });
+                let (added, removed, moved) = diff.counts();
+                ui.label(format!(
+                    "{} added, {} removed, {} moved, {} field changes",
+                    added,
+                    removed,
+                    moved,
+                    diff.field_changes.len()
+                ));
+                ui.checkbox(&mut diff.show_overlay, "Show changes on canvas")
+                    .on_hover_text("Outline the changes over the current project");
+                ui.separator();
+
+                if diff.is_empty() {
+                    ui.weak("The projects are the same");
+                    return;
+                }
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    for change in &diff.image_changes {
+                        ui.colored_label(EDITED_COLOR, change.to_string());
+                    }
+                    for change in &diff.shape_changes {
+                        let (color, verb) = match change {
+                            ShapeChange::Added { .. } => (ADDED_COLOR, "Added"),
+                            ShapeChange::Removed { .. } => (REMOVED_COLOR, "Removed"),
+                            ShapeChange::Moved { .. } => (MOVED_COLOR, "Moved"),
+                        };
+                        ui.horizontal(|ui| {
+                            ui.colored_label(color, verb);
+                            ui.label(change.label());
+                            ui.weak(change.layer().to_string());
+                        });
+                    }
+                    for change in &diff.field_changes {
+                        ui.horizontal_wrapped(|ui| {
+                            ui.colored_label(EDITED_COLOR, &change.field);
+                            ui.weak(&change.property);
+                            ui.label(format!("\"{}\" → \"{}\"", change.before, change.after));
+                        });
+                    }
+                });
+            });
+
+        if !panel_open {
+            self.project_diff = None;
+        }
+
+        true
+    }
+}
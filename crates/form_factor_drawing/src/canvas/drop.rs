@@ -0,0 +1,267 @@
+//! Drag-and-drop of form images and project files
+//!
+//! Dropping one file opens it: an image becomes the form image and a
+//! project replaces the canvas. Dropping several opens the first and queues
+//! the rest, so a batch can be worked through one file at a time from the
+//! dropped files window. While files hover over the window an overlay says
+//! what dropping them will do.
+
+use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas};
+use super::provenance::SourceChannel;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{error, info, instrument, warn};
+
+/// Extensions opened as the form image
+pub const DROP_IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "tif", "tiff", "bmp", "webp"];
+
+/// Extensions opened as a project
+pub const DROP_PROJECT_EXTENSIONS: &[&str] = &["ffp", "json"];
+
+/// What a dropped file opens as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropKind {
+    /// Form image for the canvas
+    Image,
+    /// Saved project
+    Project,
+}
+
+impl DropKind {
+    /// Kind of a file from its name, or `None` for unsupported types
+    pub fn of(name: &str) -> Option<Self> {
+        let extension = std::path::Path::new(name).extension()?.to_str()?.to_ascii_lowercase();
+        if DROP_IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+            Some(DropKind::Image)
+        } else if DROP_PROJECT_EXTENSIONS.contains(&extension.as_str()) {
+            Some(DropKind::Project)
+        } else {
+            None
+        }
+    }
+}
+
+/// File dropped on the window, by path (desktop) or contents (browser)
+#[derive(Debug, Clone)]
+pub struct DroppedFile {
+    /// File name, or the full path when there is one
+    pub name: String,
+    /// Path of the file on disk
+    pub path: Option<PathBuf>,
+    /// Contents of the file, when there is no path to read
+    pub bytes: Option<Arc<[u8]>>,
+    /// What the file opens as
+    pub kind: DropKind,
+}
+
+impl DroppedFile {
+    /// File on disk; `None` for unsupported types
+    pub fn from_path(path: impl Into<PathBuf>) -> Option<Self> {
+        let path = path.into();
+        let name = path.to_string_lossy().into_owned();
+        Some(Self {
+            kind: DropKind::of(&name)?,
+            name,
+            path: Some(path),
+            bytes: None,
+        })
+    }
+
+    /// File contents with their name; `None` for unsupported types
+    pub fn from_bytes(name: impl Into<String>, bytes: impl Into<Arc<[u8]>>) -> Option<Self> {
+        let name = name.into();
+        Some(Self {
+            kind: DropKind::of(&name)?,
+            name,
+            path: None,
+            bytes: Some(bytes.into()),
+        })
+    }
+
+    fn from_egui(file: &egui::DroppedFile) -> Option<Self> {
+        match (&file.path, &file.bytes) {
+            (Some(path), _) => Self::from_path(path.clone()),
+            (None, Some(bytes)) => Self::from_bytes(file.name.clone(), bytes.clone()),
+            (None, None) => None,
+        }
+    }
+}
+
+impl DrawingCanvas {
+    /// Open a dropped file as the form image or the project
+    ///
+    /// Files are read from their path when they have one, so images keep a
+    /// path to save in the project and projects are added to recent projects.
+    #[instrument(skip(self, file, ctx), fields(name = %file.name, kind = ?file.kind))]
+    pub fn open_dropped_file(&mut self, file: &DroppedFile, ctx: &egui::Context) -> Result<(), CanvasError> {
+        match (file.kind, &file.path, &file.bytes) {
+            (DropKind::Image, Some(path), _) => {
+                self.load_form_image_from(&path.to_string_lossy(), SourceChannel::Dropped, ctx)
+            }
+            (DropKind::Image, None, Some(bytes)) => {
+                self.load_form_image_bytes_from(&file.name, bytes, SourceChannel::Dropped, ctx)
+            }
+            (DropKind::Project, Some(path), _) => self.load_from_file(&path.to_string_lossy(), ctx),
+            (DropKind::Project, None, Some(bytes)) => self.load_project_bytes(&file.name, bytes, ctx),
+            (_, None, None) => Err(CanvasError::new(
+                CanvasErrorKind::UnsupportedFile(format!("{} has no path or contents", file.name)),
+                line!(),
+                file!(),
+            )),
+        }
+    }
+
+    /// Add files to the end of the dropped files queue
+    pub fn queue_dropped_files(&mut self, files: impl IntoIterator<Item = DroppedFile>) {
+        self.drop_queue.extend(files);
+    }
+
+    /// Open the next queued file, or `None` if the queue is empty
+    pub fn open_next_dropped(&mut self, ctx: &egui::Context) -> Option<Result<(), CanvasError>> {
+        let file = self.drop_queue.pop_front()?;
+        info!("Opening queued file {} ({} left)", file.name, self.drop_queue.len());
+        Some(self.open_dropped_file(&file, ctx))
+    }
+
+    /// Forget all queued files
+    pub fn clear_drop_queue(&mut self) {
+        self.drop_queue.clear();
+    }
+
+    /// Handle files hovering over or dropped on the window this frame
+    ///
+    /// Paints the drop overlay while files hover. Supported dropped files
+    /// are queued and the first one is opened; unsupported ones are skipped.
+    /// Returns the result of opening, or `None` if nothing was opened.
+    pub fn handle_file_drop(&mut self, ctx: &egui::Context) -> Option<Result<(), CanvasError>> {
+        if !self.queue_file_drop(ctx) {
+            return None;
+        }
+        self.open_next_dropped(ctx)
+    }
+
+    /// Queue files dropped on the window this frame without opening them
+    ///
+    /// Like [`handle_file_drop`](Self::handle_file_drop), but leaves the
+    /// first file at the front of the queue, e.g. to ask before a dropped
+    /// project replaces unsaved changes. Returns true if files were queued.
+    pub fn queue_file_drop(&mut self, ctx: &egui::Context) -> bool {
+        paint_drop_overlay(ctx);
+
+        let dropped = ctx.input(|i| i.raw.dropped_files.clone());
+        if dropped.is_empty() {
+            return false;
+        }
+
+        let mut files = Vec::new();
+        for file in &dropped {
+            match DroppedFile::from_egui(file) {
+                Some(file) => files.push(file),
+                None => warn!(
+                    "Ignoring dropped file of unsupported type: {}",
+                    file.path.as_ref().map_or_else(|| file.name.clone(), |path| path.display().to_string())
+                ),
+            }
+        }
+        if files.is_empty() {
+            return false;
+        }
+
+        info!("Dropped {} file(s)", files.len());
+        // Dropped files go ahead of any earlier batch
+        for file in files.into_iter().rev() {
+            self.drop_queue.push_front(file);
+        }
+        true
+    }
+
+    /// Next queued file to open
+    pub fn next_dropped(&self) -> Option<&DroppedFile> {
+        self.drop_queue.front()
+    }
+
+    /// Show the queue of dropped files still to open
+    ///
+    /// Returns true if the window was shown.
+    pub fn show_drop_queue_panel(&mut self, ctx: &egui::Context) -> bool {
+        if self.drop_queue.is_empty() {
+            return false;
+        }
+
+        let mut open_next = false;
+        let mut clear = false;
+        egui::Window::new("Dropped Files")
+            .resizable(false)
+            .default_width(280.0)
+            .show(ctx, |ui| {
+                ui.label(format!("{} file(s) waiting", self.drop_queue.len()));
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    for file in &self.drop_queue {
+                        let kind = match file.kind {
+                            DropKind::Image => "Image",
+                            DropKind::Project => "Project",
+                        };
+                        ui.label(format!("{}: {}", kind, file_label(file)));
+                    }
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    open_next = ui.button("Open next").clicked();
+                    clear = ui.button("Clear").clicked();
+                });
+            });
+
+        if open_next && let Some(Err(e)) = self.open_next_dropped(ctx) {
+            error!("Failed to open dropped file: {}", e);
+        }
+        if clear {
+            self.clear_drop_queue();
+        }
+        true
+    }
+}
+
+/// File name without its directories
+fn file_label(file: &DroppedFile) -> String {
+    std::path::Path::new(&file.name)
+        .file_name()
+        .map_or_else(|| file.name.clone(), |name| name.to_string_lossy().into_owned())
+}
+
+/// Dim the window and say what dropping the hovered files will do
+fn paint_drop_overlay(ctx: &egui::Context) {
+    let hovered = ctx.input(|i| i.raw.hovered_files.clone());
+    if hovered.is_empty() {
+        return;
+    }
+
+    // Not every platform reveals the path while hovering
+    let kinds: Vec<Option<DropKind>> = hovered
+        .iter()
+        .map(|file| file.path.as_ref().and_then(|path| DropKind::of(&path.to_string_lossy())))
+        .collect();
+    let text = match kinds.as_slice() {
+        [Some(DropKind::Image)] => "Drop to open the form image".to_string(),
+        [Some(DropKind::Project)] => "Drop to open the project".to_string(),
+        [_] => "Drop to open the file".to_string(),
+        _ => format!("Drop to open {} files one at a time", kinds.len()),
+    };
+
+    let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("drop_overlay")));
+    let screen = ctx.content_rect();
+    painter.rect_filled(screen, 0.0, egui::Color32::from_black_alpha(160));
+    painter.rect_stroke(
+        screen.shrink(8.0),
+        8.0,
+        egui::Stroke::new(3.0, egui::Color32::LIGHT_BLUE),
+        egui::StrokeKind::Inside,
+    );
+    painter.text(
+        screen.center(),
+        egui::Align2::CENTER_CENTER,
+        text,
+        egui::FontId::proportional(24.0),
+        egui::Color32::WHITE,
+    );
+}
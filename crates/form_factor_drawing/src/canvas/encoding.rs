@@ -0,0 +1,152 @@
+//! Project file encodings
+//!
+//! Projects are saved as JSON by default. Freehand polygons with thousands
+//! of points make JSON slow to write and several times larger than the
+//! data, so projects can be saved as MessagePack instead. A binary project
+//! starts with [`BINARY_PROJECT_MAGIC`] and a format byte, which loading
+//! checks for before falling back to JSON, so either encoding opens
+//! without being told which it is. Both keep field names, so older files
+//! with missing fields load in either encoding. Either may be compressed;
+//! see the `compression` module.
+
+use super::compression::{decompressor, is_compressed};
+use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas};
+use super::encryption::is_encrypted;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use tracing::instrument;
+
+/// Bytes a binary project file starts with
+pub const BINARY_PROJECT_MAGIC: &[u8; 4] = b"FFPB";
+
+/// Format byte following the magic for MessagePack payloads
+const MESSAGEPACK_FORMAT: u8 = 1;
+
+/// Encoding of a project file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, strum::EnumIter, strum::Display)]
+pub enum ProjectEncoding {
+    /// Pretty-printed JSON, readable and diffable
+    #[default]
+    #[strum(to_string = "JSON")]
+    Json,
+    /// MessagePack behind a binary header, smaller and faster for large projects
+    #[strum(to_string = "Binary (MessagePack)")]
+    MessagePack,
+}
+
+impl ProjectEncoding {
+    /// Encoding of a project file's contents, judged by its header
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(BINARY_PROJECT_MAGIC) {
+            Self::MessagePack
+        } else {
+            Self::Json
+        }
+    }
+}
+
+impl DrawingCanvas {
+    /// Serialize the project state in `encoding`
+    pub fn to_bytes(&self, encoding: ProjectEncoding) -> Result<Vec<u8>, CanvasError> {
+        match encoding {
+            ProjectEncoding::Json => Ok(self.to_json()?.into_bytes()),
+            ProjectEncoding::MessagePack => {
+                let mut bytes = BINARY_PROJECT_MAGIC.to_vec();
+                bytes.push(MESSAGEPACK_FORMAT);
+                // Human-readable mode keeps every type in the shape it has in JSON
+                let mut serializer = rmp_serde::Serializer::new(&mut bytes).with_struct_map().with_human_readable();
+                self.serialize(&mut serializer).map_err(|e| {
+                    CanvasError::new(CanvasErrorKind::Serialization(e.to_string()), line!(), file!())
+                })?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Parse and validate project state in either encoding, compressed or not
+    ///
+    /// Validates like [`DrawingCanvas::from_json`]. A binary header with an
+    /// unknown format byte fails to deserialize, and a password-protected
+    /// project fails with [`CanvasErrorKind::PasswordRequired`]; see
+    /// [`DrawingCanvas::from_protected_bytes`].
+    #[instrument(skip(bytes), fields(len = bytes.len()))]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CanvasError> {
+        if is_encrypted(bytes) {
+            return Err(CanvasError::new(CanvasErrorKind::PasswordRequired, line!(), file!()));
+        }
+        if is_compressed(bytes) {
+            return Self::from_reader(decompressor(bytes)?);
+        }
+        match ProjectEncoding::detect(bytes) {
+            ProjectEncoding::Json => {
+                let json = std::str::from_utf8(bytes).map_err(|e| deserialization(e.to_string()))?;
+                Self::from_json(json)
+            }
+            ProjectEncoding::MessagePack => Self::from_messagepack(&bytes[BINARY_PROJECT_MAGIC.len()..]),
+        }
+    }
+
+    /// Parse and validate project state streamed from `reader`, in either encoding
+    fn from_reader(mut reader: impl Read) -> Result<Self, CanvasError> {
+        let header = read_header(&mut reader)?;
+        match ProjectEncoding::detect(&header) {
+            ProjectEncoding::Json => {
+                let loaded =
+                    serde_json::from_reader(header.as_slice().chain(reader)).map_err(|e| deserialization(e.to_string()))?;
+                Self::validated(loaded)
+            }
+            ProjectEncoding::MessagePack => Self::from_messagepack(reader),
+        }
+    }
+
+    /// Parse and validate a binary project following its magic: a format byte, then the payload
+    fn from_messagepack(mut reader: impl Read) -> Result<Self, CanvasError> {
+        let mut format = [0];
+        if reader.read_exact(&mut format).is_err() {
+            return Err(deserialization("binary project has no payload".to_string()));
+        }
+        if format[0] != MESSAGEPACK_FORMAT {
+            return Err(deserialization(format!("unknown binary project format {}", format[0])));
+        }
+        // Human-readable mode, as in `to_bytes`
+        let mut deserializer = rmp_serde::Deserializer::new(reader).with_human_readable();
+        let loaded = DrawingCanvas::deserialize(&mut deserializer).map_err(|e| deserialization(e.to_string()))?;
+        Self::validated(loaded)
+    }
+
+    /// Project state in the encoding and compression chosen in the canvas settings, encrypted if it has a password
+    pub fn project_bytes(&self) -> Result<Vec<u8>, CanvasError> {
+        let bytes = self.maybe_compress(self.to_bytes(self.canvas_settings.project_encoding)?)?;
+        self.maybe_encrypt(bytes)
+    }
+
+    /// Save in the encoding and compression of `snapshot`, the project file the canvas was read from
+    ///
+    /// Batch review writes instances back the way it found them, whatever
+    /// the encoding and compression chosen in the application's settings.
+    pub(super) fn keep_file_format(&mut self, snapshot: &[u8]) -> Result<(), CanvasError> {
+        let compressed = is_compressed(snapshot);
+        self.canvas_settings.project_encoding = if compressed {
+            ProjectEncoding::detect(&read_header(decompressor(snapshot)?)?)
+        } else {
+            ProjectEncoding::detect(snapshot)
+        };
+        self.canvas_settings.compression.enabled = compressed;
+        Ok(())
+    }
+}
+
+/// First bytes streamed from `reader`, enough to tell the encoding apart
+fn read_header(reader: impl Read) -> Result<Vec<u8>, CanvasError> {
+    let mut header = Vec::with_capacity(BINARY_PROJECT_MAGIC.len());
+    reader
+        .take(BINARY_PROJECT_MAGIC.len() as u64)
+        .read_to_end(&mut header)
+        .map_err(|e| CanvasError::new(CanvasErrorKind::Compression(e.to_string()), line!(), file!()))?;
+    Ok(header)
+}
+
+/// Error for a project that can't be deserialized
+fn deserialization(msg: String) -> CanvasError {
+    CanvasError::new(CanvasErrorKind::Deserialization(msg), line!(), file!())
+}
@@ -0,0 +1,405 @@
+//! Password-protected project files
+//!
+//! Projects holding sensitive scans travel on laptops that get lost. A
+//! project with a password is saved encrypted: the file starts with
+//! [`ENCRYPTED_PROJECT_MAGIC`] and a format byte, then the Argon2id cost
+//! parameters, a random salt and nonce, and the project encrypted with
+//! XChaCha20-Poly1305 under a key derived from the password. The header is
+//! authenticated along with the payload, and the payload is the project as
+//! it would otherwise be saved, in its encoding and compression.
+//!
+//! Opening a protected file without its password fails with
+//! [`CanvasErrorKind::PasswordRequired`] and opens the unlock window. A
+//! wrong password, or a file altered after it was saved, fails with
+//! [`CanvasErrorKind::WrongPassword`]. Once unlocked, the project keeps
+//! its password and every save, including autosaves, writes a full
+//! encrypted snapshot; journal records would hold the changes in plain
+//! text. The password is set, changed or removed in the project password
+//! window and takes effect on the next save.
+
+use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use std::path::PathBuf;
+use tracing::{debug, info, instrument, warn};
+
+/// Bytes a password-protected project file starts with
+pub const ENCRYPTED_PROJECT_MAGIC: &[u8; 4] = b"FFPX";
+
+/// Format byte following the magic for Argon2id keys and XChaCha20-Poly1305 payloads
+const ARGON2_XCHACHA_FORMAT: u8 = 1;
+
+/// Argon2id memory cost in KiB for new files
+#[cfg(not(target_arch = "wasm32"))]
+const ARGON2_MEMORY_KIB: u32 = 19 * 1024;
+/// Argon2id passes for new files
+#[cfg(not(target_arch = "wasm32"))]
+const ARGON2_ITERATIONS: u32 = 2;
+/// Argon2id lanes for new files
+#[cfg(not(target_arch = "wasm32"))]
+const ARGON2_LANES: u32 = 1;
+
+/// Largest Argon2id memory cost in KiB a file may ask for
+///
+/// The costs come from the file header, so a crafted file could otherwise
+/// ask for terabytes before the password is even checked.
+const MAX_ARGON2_MEMORY_KIB: u32 = 1024 * 1024;
+/// Largest number of Argon2id passes a file may ask for
+const MAX_ARGON2_ITERATIONS: u32 = 16;
+/// Largest number of Argon2id lanes a file may ask for
+const MAX_ARGON2_LANES: u32 = 16;
+
+/// Length of the random salt in bytes
+const SALT_LEN: usize = 16;
+/// Length of the random nonce in bytes
+const NONCE_LEN: usize = 24;
+/// Length of the header: magic, format, three cost parameters, salt and nonce
+const HEADER_LEN: usize = ENCRYPTED_PROJECT_MAGIC.len() + 1 + 3 * 4 + SALT_LEN + NONCE_LEN;
+
+/// Whether `bytes` are a password-protected project
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(ENCRYPTED_PROJECT_MAGIC)
+}
+
+/// Encrypt the bytes of a project file with `password`
+///
+/// Each call draws a new salt and nonce, so saving the same project twice
+/// gives different files.
+#[cfg(not(target_arch = "wasm32"))]
+#[instrument(skip_all, fields(len = bytes.len()))]
+pub fn encrypt(bytes: &[u8], password: &str) -> Result<Vec<u8>, CanvasError> {
+    let mut random = [0; SALT_LEN + NONCE_LEN];
+    getrandom::getrandom(&mut random).map_err(|e| encryption(e.to_string()))?;
+    let (salt, nonce) = random.split_at(SALT_LEN);
+
+    let mut header = ENCRYPTED_PROJECT_MAGIC.to_vec();
+    header.push(ARGON2_XCHACHA_FORMAT);
+    for cost in [ARGON2_MEMORY_KIB, ARGON2_ITERATIONS, ARGON2_LANES] {
+        header.extend(cost.to_le_bytes());
+    }
+    header.extend(salt);
+    header.extend(nonce);
+
+    let cipher = cipher(password, salt, [ARGON2_MEMORY_KIB, ARGON2_ITERATIONS, ARGON2_LANES])?;
+    let payload = Payload { msg: bytes, aad: &header };
+    let encrypted = cipher
+        .encrypt(XNonce::from_slice(nonce), payload)
+        .map_err(|e| encryption(e.to_string()))?;
+    header.extend(encrypted);
+    debug!(before = bytes.len(), after = header.len(), "Encrypted project");
+    Ok(header)
+}
+
+/// Encrypt the bytes of a project file with `password`
+///
+/// The browser build has no source of random salts and nonces.
+#[cfg(target_arch = "wasm32")]
+pub fn encrypt(_bytes: &[u8], _password: &str) -> Result<Vec<u8>, CanvasError> {
+    Err(encryption("password protection is unavailable in the browser".to_string()))
+}
+
+/// Decrypt a password-protected project file, giving the bytes of the project inside
+///
+/// # Errors
+///
+/// Returns [`CanvasErrorKind::WrongPassword`] if the password is wrong or
+/// the file was altered, and a deserialization error if it is not a
+/// protected project in a format this version reads or its header asks for
+/// more memory, passes or lanes than a project file may.
+#[instrument(skip_all, fields(len = bytes.len()))]
+pub fn decrypt(bytes: &[u8], password: &str) -> Result<Vec<u8>, CanvasError> {
+    if !is_encrypted(bytes) {
+        return Err(invalid("not a password-protected project".to_string()));
+    }
+    if bytes.len() < HEADER_LEN {
+        return Err(invalid("password-protected project is cut short".to_string()));
+    }
+    let (header, encrypted) = bytes.split_at(HEADER_LEN);
+    let format = header[ENCRYPTED_PROJECT_MAGIC.len()];
+    if format != ARGON2_XCHACHA_FORMAT {
+        return Err(invalid(format!("unknown password-protected project format {}", format)));
+    }
+    let mut fields = header[ENCRYPTED_PROJECT_MAGIC.len() + 1..].chunks(4);
+    let mut cost = || fields.next().map_or(0, |b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+    let costs = [cost(), cost(), cost()];
+    let [memory, iterations, lanes] = costs;
+    if memory > MAX_ARGON2_MEMORY_KIB || iterations > MAX_ARGON2_ITERATIONS || lanes > MAX_ARGON2_LANES {
+        warn!(memory, iterations, lanes, "Refusing password-protected project with excessive key costs");
+        return Err(invalid(format!(
+            "password-protected project asks for excessive key costs ({} KiB, {} passes, {} lanes)",
+            memory, iterations, lanes
+        )));
+    }
+    let salt = &header[HEADER_LEN - NONCE_LEN - SALT_LEN..HEADER_LEN - NONCE_LEN];
+    let nonce = &header[HEADER_LEN - NONCE_LEN..];
+
+    let cipher = cipher(password, salt, costs)?;
+    let decrypted = cipher
+        .decrypt(XNonce::from_slice(nonce), Payload { msg: encrypted, aad: header })
+        .map_err(|_| CanvasError::new(CanvasErrorKind::WrongPassword, line!(), file!()))?;
+    debug!(after = decrypted.len(), "Decrypted project");
+    Ok(decrypted)
+}
+
+/// Cipher keyed from `password` with Argon2id at `costs`: memory in KiB, passes and lanes
+fn cipher(password: &str, salt: &[u8], costs: [u32; 3]) -> Result<XChaCha20Poly1305, CanvasError> {
+    let [memory, iterations, lanes] = costs;
+    let params = Params::new(memory, iterations, lanes, Some(32)).map_err(|e| invalid(e.to_string()))?;
+    let mut key = [0; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| encryption(e.to_string()))?;
+    XChaCha20Poly1305::new_from_slice(&key).map_err(|e| encryption(e.to_string()))
+}
+
+fn encryption(msg: String) -> CanvasError {
+    CanvasError::new(CanvasErrorKind::Encryption(msg), line!(), file!())
+}
+
+fn invalid(msg: String) -> CanvasError {
+    CanvasError::new(CanvasErrorKind::Deserialization(msg), line!(), file!())
+}
+
+/// Where a project waiting for its password came from
+#[derive(Debug, Clone)]
+pub(super) enum LockedSource {
+    /// Project file on disk
+    File(PathBuf),
+    /// Bytes of a project file, e.g. one dropped on the browser window
+    Bytes {
+        /// File name, for display
+        name: String,
+        /// Contents of the file
+        bytes: Vec<u8>,
+    },
+    /// Project in the configured project store
+    Store(String),
+}
+
+/// Protected project waiting in the unlock window for its password
+#[derive(Debug, Clone)]
+pub(super) struct PendingUnlock {
+    source: LockedSource,
+    /// Password typed so far
+    password: String,
+    /// Why the last attempt failed
+    error: Option<String>,
+}
+
+impl PendingUnlock {
+    fn new(source: LockedSource) -> Self {
+        Self {
+            source,
+            password: String::new(),
+            error: None,
+        }
+    }
+
+    /// Path or name of the project, for display
+    fn name(&self) -> String {
+        match &self.source {
+            LockedSource::File(path) => path.display().to_string(),
+            LockedSource::Bytes { name, .. } => name.clone(),
+            LockedSource::Store(name) => format!("Stored project \"{}\"", name),
+        }
+    }
+}
+
+impl DrawingCanvas {
+    /// Parse and validate a password-protected project, or an unprotected one
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CanvasErrorKind::WrongPassword`] if `password` doesn't
+    /// unlock the project, or the errors of [`DrawingCanvas::from_bytes`].
+    pub fn from_protected_bytes(bytes: &[u8], password: &str) -> Result<Self, CanvasError> {
+        if is_encrypted(bytes) {
+            Self::from_bytes(&decrypt(bytes, password)?)
+        } else {
+            Self::from_bytes(bytes)
+        }
+    }
+
+    /// `bytes`, encrypted if the project has a password
+    pub(super) fn maybe_encrypt(&self, bytes: Vec<u8>) -> Result<Vec<u8>, CanvasError> {
+        match &self.project_password {
+            Some(password) => encrypt(&bytes, password),
+            None => Ok(bytes),
+        }
+    }
+
+    /// Protect the project with `password` from the next save on, or remove the protection with `None`
+    ///
+    /// An empty password removes the protection. Protecting the project
+    /// leaves any collaboration session, which would share it unencrypted.
+    pub fn set_project_password(&mut self, password: Option<String>) {
+        self.project_password = password.filter(|password| !password.is_empty());
+        if self.project_password.is_some() {
+            self.stop_collaboration();
+        }
+        info!(protected = self.project_password.is_some(), "Changed project password");
+    }
+
+    /// Whether the project is saved encrypted with a password
+    pub fn is_password_protected(&self) -> bool {
+        self.project_password.is_some()
+    }
+
+    /// Open the unlock window if `result` failed for want of a password, passing the result on
+    pub(super) fn note_locked<T>(
+        &mut self,
+        result: Result<T, CanvasError>,
+        source: impl FnOnce() -> LockedSource,
+    ) -> Result<T, CanvasError> {
+        if let Err(e) = &result
+            && matches!(e.kind, CanvasErrorKind::PasswordRequired)
+        {
+            let pending = PendingUnlock::new(source());
+            info!(project = pending.name(), "Project is password protected, asking for the password");
+            self.pending_unlock = Some(pending);
+        }
+        result
+    }
+
+    /// Path or name of the protected project waiting in the unlock window, if any
+    pub fn locked_project(&self) -> Option<String> {
+        self.pending_unlock.as_ref().map(PendingUnlock::name)
+    }
+
+    /// Close the unlock window without opening the project
+    pub fn cancel_unlock(&mut self) {
+        self.pending_unlock = None;
+    }
+
+    /// Show the unlock window for a protected project being opened
+    ///
+    /// Returns true if the window was shown.
+    pub fn show_unlock_panel(&mut self, ctx: &egui::Context) -> bool {
+        let Some(pending) = &mut self.pending_unlock else {
+            return false;
+        };
+
+        let mut unlock = false;
+        let mut cancel = false;
+        egui::Window::new("Unlock Project")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!("{} is password protected.", pending.name()));
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut pending.password)
+                        .password(true)
+                        .hint_text("Password"),
+                );
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    unlock = true;
+                }
+                if let Some(error) = &pending.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                ui.horizontal(|ui| {
+                    unlock |= ui
+                        .add_enabled(!pending.password.is_empty(), egui::Button::new("Unlock"))
+                        .clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+
+        if cancel {
+            self.cancel_unlock();
+        } else if unlock && !pending.password.is_empty() {
+            let attempt = pending.clone();
+            let result = match &attempt.source {
+                LockedSource::File(path) => self
+                    .load_protected_file(&path.to_string_lossy(), &attempt.password, ctx)
+                    .map_err(|e| e.kind.to_string()),
+                LockedSource::Bytes { name, bytes } => self
+                    .load_protected_bytes(name, bytes, &attempt.password, ctx)
+                    .map_err(|e| e.kind.to_string()),
+                LockedSource::Store(name) => self
+                    .open_protected_stored_project(name, &attempt.password, ctx)
+                    .map_err(|e| e.kind.to_string()),
+            };
+            match result {
+                Ok(()) => self.pending_unlock = None,
+                Err(e) => {
+                    warn!("Could not unlock {}: {}", attempt.name(), e);
+                    if let Some(pending) = &mut self.pending_unlock {
+                        pending.password.clear();
+                        pending.error = Some(e);
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Toggle the project password window
+    pub fn toggle_password_panel(&mut self) {
+        self.show_password = !self.show_password;
+        self.password_draft.clear();
+        self.password_confirm.clear();
+    }
+
+    /// Show the project password window for setting, changing or removing the password
+    ///
+    /// Returns true if the window was shown.
+    pub fn show_password_panel(&mut self, ctx: &egui::Context) -> bool {
+        if !self.show_password {
+            return false;
+        }
+
+        let mut panel_open = true;
+        let mut set = false;
+        let mut remove = false;
+        let protected = self.project_password.is_some();
+        let draft = &mut self.password_draft;
+        let confirm = &mut self.password_confirm;
+        egui::Window::new("Project Password")
+            .open(&mut panel_open)
+            .resizable(false)
+            .default_width(280.0)
+            .show(ctx, |ui| {
+                ui.label(if protected {
+                    "This project is saved encrypted with a password."
+                } else {
+                    "This project is saved without a password."
+                });
+                egui::Grid::new("project_password_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Password");
+                    ui.add(egui::TextEdit::singleline(draft).password(true));
+                    ui.end_row();
+                    ui.label("Confirm");
+                    ui.add(egui::TextEdit::singleline(confirm).password(true));
+                    ui.end_row();
+                });
+                let matches = !draft.is_empty() && draft == confirm;
+                if !confirm.is_empty() && draft != confirm {
+                    ui.colored_label(ui.visuals().error_fg_color, "The passwords don't match");
+                }
+                ui.weak("A forgotten password can't be recovered. Changes apply from the next save.");
+                ui.horizontal(|ui| {
+                    let label = if protected { "Change Password" } else { "Set Password" };
+                    set = ui.add_enabled(matches, egui::Button::new(label)).clicked();
+                    remove = protected && ui.button("Remove Password").clicked();
+                });
+            });
+
+        if set {
+            let password = std::mem::take(&mut self.password_draft);
+            self.set_project_password(Some(password));
+        } else if remove {
+            self.set_project_password(None);
+        }
+        if set || remove || !panel_open {
+            self.show_password = false;
+            self.password_draft.clear();
+            self.password_confirm.clear();
+        }
+
+        true
+    }
+}
+
@@ -0,0 +1,704 @@
+//! Rendering the canvas to PNG and SVG
+//!
+//! Exports compose the form image with the visible highlight, detection and
+//! shape layers in image pixel coordinates, so the output lines up with the
+//! original scan. PNG exports are rasterized at a chosen scale; SVG exports
+//! keep annotations as vector elements over the embedded form image.
+//! Without a form image, the export covers the drawn shapes and highlights
+//! in canvas units. Highlights are multiplied onto what lies beneath them,
+//! like a highlighter pen on paper.
+//!
+//! Drawn shapes are mapped onto the image using the coordinate mapper
+//! recorded during the last rendered frame, as with redacted exports.
+
+use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas};
+use super::context_menu::CanvasAction;
+use super::highlights::Highlight;
+use super::stamps::{mapped_stamp, rasterize_stamp, svg_stamp};
+use super::text_export::TextFormat;
+use crate::coords::map_shape;
+use crate::shape::{CALLOUT_FONT_SIZE, CALLOUT_PADDING, LENGTH_FONT_SIZE};
+use crate::{LayerType, Shape, Stamp, length_label};
+use ab_glyph::FontRef;
+use egui::{Color32, Pos2, Stroke, Vec2};
+use form_factor_core::IoOperation;
+use image::{Rgba, RgbaImage};
+use tracing::{debug, instrument};
+
+/// Largest width or height of a PNG export, in pixels
+pub const MAX_EXPORT_DIMENSION: u32 = 16384;
+
+/// Margin around drawn shapes when exporting without a form image
+const SHAPES_MARGIN: f32 = 10.0;
+
+/// File format of a canvas export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, strum::EnumIter, strum::Display)]
+pub enum ExportFormat {
+    /// Raster image at a chosen scale
+    #[default]
+    #[strum(to_string = "PNG")]
+    Png,
+    /// Vector annotations over the embedded form image
+    #[strum(to_string = "SVG")]
+    Svg,
+    /// Geometry and field properties of every shape and detection
+    #[strum(to_string = "GeoJSON")]
+    GeoJson,
+    /// Text read from the detections, in reading order
+    #[strum(to_string = "Text")]
+    Text,
+}
+
+/// Everything an export draws, in export units
+pub(super) struct ExportScene {
+    /// Size of the exported area
+    pub(super) size: Vec2,
+    /// Form image drawn as the background, if the Canvas layer is included
+    image_path: Option<String>,
+    /// Highlighter strokes, if the Highlights layer is included
+    highlights: Vec<Highlight>,
+    /// Included annotation layers, bottom to top
+    layers: Vec<(LayerType, Vec<Shape>)>,
+    /// Stamps drawn over the shapes, if the Shapes layer is included
+    stamps: Vec<Stamp>,
+}
+
+impl DrawingCanvas {
+    /// Gather the visible layers in export units
+    fn export_scene(&self) -> Result<ExportScene, CanvasError> {
+        self.scene_with_layers(|layer| self.layer_manager.is_visible(layer))
+    }
+
+    /// Gather the layers accepted by `include` in export units, regardless of visibility
+    pub(super) fn scene_with_layers(&self, include: impl Fn(LayerType) -> bool) -> Result<ExportScene, CanvasError> {
+        let invalid = |msg: &str| CanvasError::new(CanvasErrorKind::InvalidExport(msg.to_string()), line!(), file!());
+        // Hidden objects are left out of exports like hidden layers
+        let shapes: Vec<&Shape> = self.shapes.iter().filter(|shape| !shape.is_hidden()).collect();
+        let shapes_visible = include(LayerType::Shapes) && !shapes.is_empty();
+        let highlights_visible = include(LayerType::Highlights) && !self.highlights.is_empty();
+        let stamps_visible = include(LayerType::Shapes) && !self.stamps.is_empty();
+        let mut layers = Vec::new();
+
+        if let Some(path) = &self.form_image_path {
+            let (width, height) = image::image_dimensions(path)
+                .map_err(|e| CanvasError::image(e, path.as_str(), IoOperation::Read, line!(), file!()))?;
+            if include(LayerType::Detections) {
+                let detections = (0..self.detections.len())
+                    .filter(|&idx| !self.detections[idx].is_hidden())
+                    .filter_map(|idx| self.styled_detection(idx))
+                    .collect();
+                layers.push((LayerType::Detections, detections));
+            }
+            if shapes_visible {
+                let shapes = shapes
+                    .iter()
+                    .map(|shape| self.coordinate_mapper.canvas_shape_to_image(shape))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| invalid("the canvas has not been rendered with the form image yet"))?;
+                layers.push((LayerType::Shapes, shapes));
+            }
+            // Highlights and stamps are mapped like shapes, with the recorded image fit
+            let (scale, offset) = match self.coordinate_mapper.image_fit() {
+                Some(fit) => (1.0 / fit.scale, Pos2::new(-fit.offset.x / fit.scale, -fit.offset.y / fit.scale)),
+                None if highlights_visible || stamps_visible => {
+                    return Err(invalid("the canvas has not been rendered with the form image yet"));
+                }
+                None => (1.0, Pos2::ZERO),
+            };
+            let image_path = include(LayerType::Canvas).then(|| path.clone());
+            return Ok(ExportScene {
+                size: Vec2::new(width as f32, height as f32),
+                image_path,
+                highlights: self.mapped_highlights(highlights_visible, scale, offset),
+                layers,
+                stamps: self.mapped_stamps(stamps_visible, scale, offset),
+            });
+        }
+
+        if shapes.is_empty() && self.highlights.is_empty() && self.stamps.is_empty() {
+            return Err(invalid("there is no form image or annotation to export"));
+        }
+        let shape_bounds = shapes.iter().filter(|_| shapes_visible).map(|shape| shape.bounding_rect());
+        let highlight_bounds = self.highlights.iter().filter(|_| highlights_visible).map(Highlight::bounding_rect);
+        let stamp_bounds = self.stamps.iter().filter(|_| stamps_visible).map(|stamp| stamp.bounds);
+        let Some(bounds) = shape_bounds.chain(highlight_bounds).chain(stamp_bounds).reduce(|a, b| a.union(b)) else {
+            return Err(invalid("the shapes and highlights layers are hidden and there is no form image"));
+        };
+        let bounds = bounds.expand(SHAPES_MARGIN);
+        let origin = (-bounds.min.to_vec2()).to_pos2();
+        if shapes_visible {
+            let shapes = shapes.iter().map(|shape| map_shape(shape, 1.0, origin)).collect();
+            layers.push((LayerType::Shapes, shapes));
+        }
+        Ok(ExportScene {
+            size: bounds.size(),
+            image_path: None,
+            highlights: self.mapped_highlights(highlights_visible, 1.0, origin),
+            layers,
+            stamps: self.mapped_stamps(stamps_visible, 1.0, origin),
+        })
+    }
+
+    fn mapped_highlights(&self, include: bool, scale: f32, offset: Pos2) -> Vec<Highlight> {
+        match include {
+            true => self.highlights.iter().map(|highlight| highlight.mapped(scale, offset)).collect(),
+            false => Vec::new(),
+        }
+    }
+
+    fn mapped_stamps(&self, include: bool, scale: f32, offset: Pos2) -> Vec<Stamp> {
+        match include {
+            true => self.stamps.iter().map(|stamp| mapped_stamp(stamp, scale, offset)).collect(),
+            false => Vec::new(),
+        }
+    }
+
+    /// Render the visible layers to an image
+    ///
+    /// `scale` is the number of output pixels per form image pixel (or per
+    /// canvas unit without a form image), so 2.0 doubles the resolution.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - There is nothing to export
+    /// - The scale is not positive or the result exceeds [`MAX_EXPORT_DIMENSION`]
+    /// - Shapes are visible but the canvas has not been rendered with the form image
+    /// - The form image cannot be read
+    pub fn render_png(&self, scale: f32) -> Result<RgbaImage, CanvasError> {
+        self.render_scene(&self.export_scene()?, scale)
+    }
+
+    /// Rasterize a scene at `scale` output pixels per export unit
+    pub(super) fn render_scene(&self, scene: &ExportScene, scale: f32) -> Result<RgbaImage, CanvasError> {
+        let size = scene.size * scale;
+        let max = MAX_EXPORT_DIMENSION as f32;
+        if !scale.is_finite() || scale <= 0.0 || size.x < 1.0 || size.y < 1.0 || size.x > max || size.y > max {
+            let msg = format!("scale {} gives a {:.0}x{:.0} image", scale, size.x, size.y);
+            return Err(CanvasError::new(CanvasErrorKind::InvalidExport(msg), line!(), file!()));
+        }
+        let (width, height) = (size.x.round() as u32, size.y.round() as u32);
+
+        let mut target = match &scene.image_path {
+            Some(path) => {
+                let image = image::open(path)
+                    .map_err(|e| CanvasError::image(e, path.as_str(), IoOperation::Read, line!(), file!()))?
+                    .to_rgba8();
+                image::imageops::resize(&image, width, height, image::imageops::FilterType::Triangle)
+            }
+            None => RgbaImage::from_pixel(width, height, Rgba([255, 255, 255, 255])),
+        };
+        for highlight in &scene.highlights {
+            highlight.mapped(scale, Pos2::ZERO).rasterize(&mut target);
+        }
+        for (_, shapes) in &scene.layers {
+            for shape in shapes {
+                let scaled = map_shape(shape, scale, Pos2::ZERO);
+                rasterize(&mut target, &scaled, scale);
+            }
+        }
+        if !scene.stamps.is_empty() {
+            let font = FontRef::try_from_slice(epaint_default_fonts::UBUNTU_LIGHT)
+                .map_err(|e| CanvasError::new(CanvasErrorKind::InvalidExport(e.to_string()), line!(), file!()))?;
+            for stamp in &scene.stamps {
+                rasterize_stamp(&mut target, &font, &mapped_stamp(stamp, scale, Pos2::ZERO));
+            }
+        }
+        Ok(target)
+    }
+
+    /// Render the visible layers and save them as a PNG file
+    ///
+    /// Returns the size of the written image. See [`Self::render_png`] for
+    /// the meaning of `scale` and the possible errors.
+    #[instrument(skip(self), fields(output_path, scale))]
+    pub fn export_png(&self, output_path: &str, scale: f32) -> Result<(u32, u32), CanvasError> {
+        let image = self.render_png(scale)?;
+        image
+            .save_with_format(output_path, image::ImageFormat::Png)
+            .map_err(|e| CanvasError::image(e, output_path, IoOperation::Write, line!(), file!()))?;
+        tracing::info!("Exported canvas to {} ({}x{})", output_path, image.width(), image.height());
+        Ok(image.dimensions())
+    }
+
+    /// Build an SVG document of the visible layers
+    ///
+    /// The form image is embedded as a data URI, and each layer becomes a
+    /// group of vector elements titled with the shape names.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as [`Self::render_png`],
+    /// apart from the scale checks.
+    pub fn to_svg(&self) -> Result<String, CanvasError> {
+        let scene = self.export_scene()?;
+        let (width, height) = (scene.size.x, scene.size.y);
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+            w = width,
+            h = height
+        );
+        svg.push_str(&format!("  <title>{}</title>\n", xml_escape(&self.project_name)));
+
+        match &scene.image_path {
+            Some(path) => {
+                let bytes = std::fs::read(path)
+                    .map_err(|e| CanvasError::io(e, path.as_str(), IoOperation::Read, line!(), file!()))?;
+                svg.push_str(&format!(
+                    "  <image x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" href=\"data:{};base64,{}\"/>\n",
+                    width,
+                    height,
+                    image_mime(path),
+                    base64(&bytes)
+                ));
+            }
+            None => svg.push_str(&format!("  <rect width=\"{}\" height=\"{}\" fill=\"#ffffff\"/>\n", width, height)),
+        }
+
+        if !scene.highlights.is_empty() {
+            svg.push_str(&format!("  <g id=\"{}\">\n", LayerType::Highlights.to_string().to_lowercase()));
+            for highlight in &scene.highlights {
+                svg.push_str("    ");
+                svg.push_str(&svg_highlight(highlight));
+                svg.push('\n');
+            }
+            svg.push_str("  </g>\n");
+        }
+        for (layer, shapes) in &scene.layers {
+            svg.push_str(&format!("  <g id=\"{}\">\n", layer.to_string().to_lowercase()));
+            for shape in shapes {
+                svg.push_str("    ");
+                svg.push_str(&svg_element(shape));
+                svg.push('\n');
+            }
+            svg.push_str("  </g>\n");
+        }
+        if !scene.stamps.is_empty() {
+            svg.push_str("  <g id=\"stamps\">\n");
+            for element in scene.stamps.iter().filter_map(svg_stamp) {
+                svg.push_str("    ");
+                svg.push_str(&element);
+                svg.push('\n');
+            }
+            svg.push_str("  </g>\n");
+        }
+        svg.push_str("</svg>\n");
+        Ok(svg)
+    }
+
+    /// Save the visible layers as an SVG file
+    #[instrument(skip(self), fields(output_path))]
+    pub fn export_svg(&self, output_path: &str) -> Result<(), CanvasError> {
+        let svg = self.to_svg()?;
+        std::fs::write(output_path, svg)
+            .map_err(|e| CanvasError::io(e, output_path, IoOperation::Write, line!(), file!()))?;
+        tracing::info!("Exported canvas to {}", output_path);
+        Ok(())
+    }
+
+    /// Toggle the canvas export window
+    pub fn toggle_export_panel(&mut self) {
+        self.show_export = !self.show_export;
+    }
+
+    /// Show the canvas export window
+    ///
+    /// Choosing a format queues [`CanvasAction::ExportPng`],
+    /// [`CanvasAction::ExportSvg`], [`CanvasAction::ExportGeoJson`] or
+    /// [`CanvasAction::ExportText`] for the application to pick a file.
+    /// Returns true if the window was shown.
+    pub fn show_export_panel(&mut self, ctx: &egui::Context) -> bool {
+        if !self.show_export {
+            return false;
+        }
+
+        let mut panel_open = true;
+        let mut export = None;
+        let image_size = self.form_image_size;
+        egui::Window::new("Export Canvas")
+            .open(&mut panel_open)
+            .resizable(false)
+            .default_width(260.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for format in <ExportFormat as strum::IntoEnumIterator>::iter() {
+                        ui.selectable_value(&mut self.export_format, format, format.to_string());
+                    }
+                });
+                if self.export_format == ExportFormat::Png {
+                    ui.add(
+                        egui::Slider::new(&mut self.export_scale, 0.25..=4.0)
+                            .text("Scale")
+                            .logarithmic(true),
+                    );
+                    if let Some(size) = image_size {
+                        let size = size * self.export_scale;
+                        ui.label(format!("{:.0} x {:.0} px", size.x, size.y));
+                    }
+                }
+                if self.export_format == ExportFormat::Text {
+                    let options = &mut self.text_export_options;
+                    ui.horizontal(|ui| {
+                        for format in <TextFormat as strum::IntoEnumIterator>::iter() {
+                            ui.radio_value(&mut options.format, format, format.to_string());
+                        }
+                    });
+                    ui.checkbox(&mut options.page_headers, "Page header");
+                    ui.checkbox(&mut options.region_headers, "Region headers");
+                }
+                match self.export_format {
+                    ExportFormat::GeoJson => ui.label("Includes every shape and detection with its properties"),
+                    ExportFormat::Text => ui.label("Includes the text read from every detection, in reading order"),
+                    ExportFormat::Png | ExportFormat::Svg => ui.label("Includes the form image and visible layers"),
+                };
+                ui.separator();
+                if ui.button("Export...").clicked() {
+                    export = Some(self.export_format);
+                }
+            });
+
+        if let Some(format) = export {
+            debug!(%format, scale = self.export_scale, "Canvas export requested");
+            self.pending_actions.push(match format {
+                ExportFormat::Png => CanvasAction::ExportPng,
+                ExportFormat::Svg => CanvasAction::ExportSvg,
+                ExportFormat::GeoJson => CanvasAction::ExportGeoJson,
+                ExportFormat::Text => CanvasAction::ExportText,
+            });
+            self.show_export = false;
+        } else if !panel_open {
+            self.show_export = false;
+        }
+
+        true
+    }
+}
+
+/// Boundary of a shape for rasterization
+enum Outline {
+    Polygon(Vec<Pos2>),
+    Circle(Pos2, f32),
+    /// A filled area plus open lines, for lines, polylines and markups
+    Markup { area: Vec<Pos2>, lines: Vec<[Pos2; 2]> },
+}
+
+impl Outline {
+    fn of(shape: &Shape) -> Self {
+        match shape {
+            Shape::Rectangle(rect) => Outline::Polygon(rect.corners().to_vec()),
+            Shape::Circle(circle) => Outline::Circle(circle.center, circle.radius),
+            Shape::Ellipse(ellipse) => Outline::Polygon(ellipse.to_egui_points()),
+            Shape::Polygon(poly) => Outline::Polygon(poly.to_egui_points()),
+            Shape::Line(line) => Outline::Markup {
+                area: Vec::new(),
+                lines: vec![line.points()],
+            },
+            Shape::Polyline(polyline) => Outline::Markup {
+                area: Vec::new(),
+                lines: polyline.points().windows(2).map(|pair| [pair[0], pair[1]]).collect(),
+            },
+            Shape::Arrow(arrow) => {
+                let head = arrow.head();
+                Outline::Markup {
+                    area: head.to_vec(),
+                    lines: vec![[*arrow.start(), head[1].lerp(head[2], 0.5)]],
+                }
+            }
+            Shape::Callout(callout) => {
+                let text_box = callout.text_box();
+                Outline::Markup {
+                    area: vec![text_box.left_top(), text_box.right_top(), text_box.right_bottom(), text_box.left_bottom()],
+                    lines: vec![[*callout.anchor(), callout.leader_end()]],
+                }
+            }
+        }
+    }
+
+    /// Whether a point is inside the shape, and its distance to the boundary
+    fn sample(&self, p: Pos2) -> (bool, f32) {
+        match self {
+            Outline::Circle(center, radius) => {
+                let distance = center.distance(p);
+                (distance <= *radius, (distance - radius).abs())
+            }
+            Outline::Polygon(points) => sample_polygon(points, p),
+            Outline::Markup { area, lines } => {
+                let (inside, distance) = sample_polygon(area, p);
+                let line_distance = lines
+                    .iter()
+                    .map(|[a, b]| segment_distance(p, *a, *b))
+                    .fold(f32::INFINITY, f32::min);
+                (inside, distance.min(line_distance))
+            }
+        }
+    }
+}
+
+/// Whether a point is inside a polygon, and its distance to the edges
+///
+/// An empty polygon has no inside and no edges.
+fn sample_polygon(points: &[Pos2], p: Pos2) -> (bool, f32) {
+    let mut inside = false;
+    let mut distance = f32::INFINITY;
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        if (a.y > p.y) != (b.y > p.y) && p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+            inside = !inside;
+        }
+        distance = distance.min(segment_distance(p, *a, b));
+    }
+    (inside, distance)
+}
+
+pub(super) fn segment_distance(p: Pos2, a: Pos2, b: Pos2) -> f32 {
+    let ab = b - a;
+    let t = if ab.length_sq() > 0.0 {
+        ((p - a).dot(ab) / ab.length_sq()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    p.distance(a + ab * t)
+}
+
+fn paint(shape: &Shape) -> (Color32, Stroke) {
+    match shape {
+        Shape::Rectangle(rect) => (rect.fill, rect.stroke),
+        Shape::Circle(circle) => (circle.fill, circle.stroke),
+        Shape::Ellipse(ellipse) => (ellipse.fill, ellipse.stroke),
+        Shape::Polygon(poly) => (poly.fill, poly.stroke),
+        Shape::Line(line) => (Color32::TRANSPARENT, line.stroke),
+        Shape::Polyline(polyline) => (Color32::TRANSPARENT, polyline.stroke),
+        // Arrowheads are filled with the line color
+        Shape::Arrow(arrow) => (arrow.stroke.color, arrow.stroke),
+        Shape::Callout(callout) => (callout.fill, callout.stroke),
+    }
+}
+
+/// Draw a shape's fill and outline onto an image, sampling pixel centers
+fn rasterize(target: &mut RgbaImage, shape: &Shape, stroke_scale: f32) {
+    let (fill, stroke) = paint(shape);
+    let half_width = stroke.width * stroke_scale / 2.0;
+    let outline = Outline::of(shape);
+    let bounds = shape.bounding_rect().expand(half_width + 1.0);
+    let (x0, y0) = (bounds.min.x.floor().max(0.0) as u32, bounds.min.y.floor().max(0.0) as u32);
+    let (x1, y1) = (
+        (bounds.max.x.ceil() as u32).min(target.width()),
+        (bounds.max.y.ceil() as u32).min(target.height()),
+    );
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let (inside, distance) = outline.sample(Pos2::new(x as f32 + 0.5, y as f32 + 0.5));
+            let color = if half_width > 0.0 && distance <= half_width {
+                stroke.color
+            } else if inside {
+                fill
+            } else {
+                continue;
+            };
+            blend(target.get_pixel_mut(x, y), color);
+        }
+    }
+}
+
+/// Alpha-composite a color over a pixel
+pub(super) fn blend(pixel: &mut Rgba<u8>, color: Color32) {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    let alpha = a as f32 / 255.0;
+    for (channel, source) in pixel.0.iter_mut().zip([r, g, b]) {
+        *channel = (source as f32 * alpha + *channel as f32 * (1.0 - alpha)).round() as u8;
+    }
+    pixel.0[3] = (a as f32 + pixel.0[3] as f32 * (1.0 - alpha)).round() as u8;
+}
+
+pub(super) fn svg_paint(color: Color32) -> (String, f32) {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    (format!("#{:02x}{:02x}{:02x}", r, g, b), a as f32 / 255.0)
+}
+
+fn svg_element(shape: &Shape) -> String {
+    let (fill, stroke) = paint(shape);
+    let (fill, fill_opacity) = svg_paint(fill);
+    let (stroke_color, stroke_opacity) = svg_paint(stroke.color);
+    let mut attributes = format!(
+        "fill=\"{}\" fill-opacity=\"{:.3}\" stroke=\"{}\" stroke-opacity=\"{:.3}\" stroke-width=\"{}\"",
+        fill, fill_opacity, stroke_color, stroke_opacity, stroke.width
+    );
+    if let Some((dash, gap)) = shape.stroke_style().pattern(stroke.width) {
+        attributes.push_str(&format!(" stroke-dasharray=\"{:.2} {:.2}\"", dash, gap));
+        // Zero-length dashes only show as dots with round caps
+        if dash == 0.0 {
+            attributes.push_str(" stroke-linecap=\"round\"");
+        }
+    }
+    let title = match shape.name() {
+        "" => String::new(),
+        name => format!("<title>{}</title>", xml_escape(name)),
+    };
+    match shape {
+        Shape::Circle(circle) => format!(
+            "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{:.2}\" {}>{}</circle>",
+            circle.center.x, circle.center.y, circle.radius, attributes, title
+        ),
+        Shape::Ellipse(ellipse) => {
+            let center = ellipse.center();
+            format!(
+                "<ellipse cx=\"{:.2}\" cy=\"{:.2}\" rx=\"{:.2}\" ry=\"{:.2}\" \
+                 transform=\"rotate({:.2} {:.2} {:.2})\" {}>{}</ellipse>",
+                center.x,
+                center.y,
+                ellipse.radii().x,
+                ellipse.radii().y,
+                ellipse.rotation().to_degrees(),
+                center.x,
+                center.y,
+                attributes,
+                title
+            )
+        }
+        Shape::Rectangle(_) | Shape::Polygon(_) => {
+            let points = match Outline::of(shape) {
+                Outline::Polygon(points) => points,
+                Outline::Circle(..) | Outline::Markup { .. } => Vec::new(),
+            };
+            let points: Vec<String> = points.iter().map(|p| format!("{:.2},{:.2}", p.x, p.y)).collect();
+            format!("<polygon points=\"{}\" {}>{}</polygon>", points.join(" "), attributes, title)
+        }
+        Shape::Line(line) => {
+            let [start, end] = line.points();
+            let label = svg_length(line.show_length, line.center(), line.length(), stroke.color);
+            format!(
+                "<g {}>{}<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\"/>{}</g>",
+                attributes, title, start.x, start.y, end.x, end.y, label
+            )
+        }
+        Shape::Polyline(polyline) => {
+            let points: Vec<String> = polyline.points().iter().map(|p| format!("{:.2},{:.2}", p.x, p.y)).collect();
+            let label = svg_length(polyline.show_length, polyline.midpoint(), polyline.length(), stroke.color);
+            format!(
+                "<g {}>{}<polyline points=\"{}\"/>{}</g>",
+                attributes,
+                title,
+                points.join(" "),
+                label
+            )
+        }
+        Shape::Arrow(arrow) => {
+            let head = arrow.head();
+            let base = head[1].lerp(head[2], 0.5);
+            let points: Vec<String> = head.iter().map(|p| format!("{:.2},{:.2}", p.x, p.y)).collect();
+            format!(
+                "<g {}>{}<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\"/><polygon points=\"{}\" stroke=\"none\"/></g>",
+                attributes,
+                title,
+                arrow.start().x,
+                arrow.start().y,
+                base.x,
+                base.y,
+                points.join(" ")
+            )
+        }
+        Shape::Callout(callout) => {
+            let (text_box, leader_end) = (callout.text_box(), callout.leader_end());
+            let (text_color, text_opacity) = svg_paint(stroke.color);
+            let left = text_box.min.x + CALLOUT_PADDING;
+            let lines: String = callout
+                .text
+                .lines()
+                .map(|line| format!("<tspan x=\"{:.2}\" dy=\"1.2em\">{}</tspan>", left, xml_escape(line)))
+                .collect();
+            format!(
+                "<g {}>{}<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\"/>\
+                 <rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\"/>\
+                 <text y=\"{:.2}\" font-size=\"{}\" fill=\"{}\" fill-opacity=\"{:.3}\" stroke=\"none\">{}</text></g>",
+                attributes,
+                title,
+                callout.anchor().x,
+                callout.anchor().y,
+                leader_end.x,
+                leader_end.y,
+                text_box.min.x,
+                text_box.min.y,
+                text_box.width(),
+                text_box.height(),
+                text_box.min.y + CALLOUT_PADDING,
+                CALLOUT_FONT_SIZE,
+                text_color,
+                text_opacity,
+                lines
+            )
+        }
+    }
+}
+
+/// Length label centered above `at`, or nothing if it is not shown
+fn svg_length(show: bool, at: Pos2, length: f32, color: Color32) -> String {
+    if !show {
+        return String::new();
+    }
+    let (color, opacity) = svg_paint(color);
+    format!(
+        "<text x=\"{:.2}\" y=\"{:.2}\" text-anchor=\"middle\" font-size=\"{}\" fill=\"{}\" fill-opacity=\"{:.3}\" \
+         stroke=\"none\">{}</text>",
+        at.x,
+        at.y - 2.0,
+        LENGTH_FONT_SIZE,
+        color,
+        opacity,
+        length_label(length)
+    )
+}
+
+/// Round-capped stroke multiplied onto the layers beneath it
+fn svg_highlight(highlight: &Highlight) -> String {
+    let (color, opacity) = svg_paint(highlight.color);
+    let points: Vec<String> = highlight.points.iter().map(|p| format!("{:.2},{:.2}", p.x, p.y)).collect();
+    format!(
+        "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-opacity=\"{:.3}\" stroke-width=\"{:.2}\" \
+         stroke-linecap=\"round\" stroke-linejoin=\"round\" style=\"mix-blend-mode:multiply\"/>",
+        points.join(" "),
+        color,
+        opacity,
+        highlight.width
+    )
+}
+
+pub(super) fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// MIME type of an image file, from its extension
+pub(super) fn image_mime(path: &str) -> &'static str {
+    let extension = std::path::Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("webp") => "image/webp",
+        _ => "image/png",
+    }
+}
+
+/// Standard base64 with padding, for embedding images
+pub(super) fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, byte)| acc | (*byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
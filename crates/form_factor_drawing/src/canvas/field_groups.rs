@@ -0,0 +1,340 @@
+//! Field groups, repeating section rows and the field groups window
+//!
+//! Groups are saved with the project and carried by templates. Rows of a
+//! repeating section belong to the filled form: adding a row copies the
+//! first row's fields, with empty values, below the last row; removing one
+//! deletes the last row's fields. A row's conditions on fields of its own
+//! row follow the copy, so `amount 3` depends on `quantity 3` rather than
+//! on `quantity`.
+
+use tracing::{debug, instrument, warn};
+
+use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas};
+use crate::{FieldCondition, FieldGroup, RepeatingSection, Shape, row_field_name};
+
+impl DrawingCanvas {
+    /// Add a field group, replacing any group with the same name
+    ///
+    /// Every field of the group must exist, and a repeating section must
+    /// move its rows by a nonzero pitch.
+    #[instrument(skip(self, group), fields(group = %group.name))]
+    pub fn add_field_group(&mut self, group: FieldGroup) -> Result<(), CanvasError> {
+        let invalid = |msg: String| CanvasError::new(CanvasErrorKind::InvalidFieldGroup(msg), line!(), file!());
+        if group.name.trim().is_empty() {
+            return Err(invalid("group needs a name".to_string()));
+        }
+        if group.fields.is_empty() {
+            return Err(invalid(format!("{} has no fields", group.name)));
+        }
+        if let Some(missing) = group.fields.iter().find(|field| self.field_index(field).is_none()) {
+            return Err(invalid(format!("no field named {}", missing)));
+        }
+        if group.repeat.is_some_and(|section| section.pitch == egui::Vec2::ZERO) {
+            return Err(invalid(format!("rows of {} need a pitch", group.name)));
+        }
+
+        debug!(fields = group.fields.len(), repeating = group.is_repeating(), "Added field group");
+        match self.field_groups.iter_mut().find(|existing| existing.name == group.name) {
+            Some(existing) => *existing = group,
+            None => self.field_groups.push(group),
+        }
+        Ok(())
+    }
+
+    /// Remove a field group, leaving its fields on the canvas
+    pub fn remove_field_group(&mut self, name: &str) -> Option<FieldGroup> {
+        let index = self.field_groups.iter().position(|group| group.name == name)?;
+        Some(self.field_groups.remove(index))
+    }
+
+    /// Field group with a name
+    pub fn field_group(&self, name: &str) -> Option<&FieldGroup> {
+        self.field_groups.iter().find(|group| group.name == name)
+    }
+
+    /// Group a field belongs to, with the field's row in it
+    pub fn group_of_field(&self, index: usize) -> Option<(&FieldGroup, usize)> {
+        let name = self.shapes.get(index)?.name();
+        self.field_groups
+            .iter()
+            .find_map(|group| group.locate(name).map(|(row, _)| (group, row)))
+    }
+
+    /// Number of rows of a group on this form
+    ///
+    /// Counts rows from the first while any of their fields exist; a group
+    /// that does not repeat has at most one row.
+    pub fn section_rows(&self, name: &str) -> usize {
+        let Some(group) = self.field_group(name) else {
+            return 0;
+        };
+        let limit = if group.is_repeating() { usize::MAX } else { 1 };
+        (1..=limit)
+            .take_while(|row| group.fields.iter().any(|field| self.field_index(&row_field_name(field, *row)).is_some()))
+            .count()
+    }
+
+    /// Add a row to a repeating section, returning the new fields' indices
+    #[instrument(skip(self))]
+    pub fn add_section_row(&mut self, name: &str) -> Result<Vec<usize>, CanvasError> {
+        let invalid = |msg: String| CanvasError::new(CanvasErrorKind::InvalidFieldGroup(msg), line!(), file!());
+        let group = self.field_group(name).cloned().ok_or_else(|| invalid(format!("no group named {}", name)))?;
+        let section = group.repeat.ok_or_else(|| invalid(format!("{} does not repeat", name)))?;
+        let rows = self.section_rows(name);
+        if rows == 0 {
+            return Err(invalid(format!("first row of {} is missing", name)));
+        }
+        if section.max_rows.is_some_and(|max| rows >= max) {
+            return Err(invalid(format!("{} is limited to {} rows", name, rows)));
+        }
+
+        let row = rows + 1;
+        let delta = section.pitch * rows as f32;
+        let mut added = Vec::with_capacity(group.fields.len());
+        for field in &group.fields {
+            let Some(index) = self.field_index(field) else {
+                continue;
+            };
+            let mut shape = self.shapes[index].clone();
+            if let Err(e) = shape.translate(delta) {
+                warn!("Could not place section row field: {}", e);
+                continue;
+            }
+            shape.set_name(row_field_name(field, row));
+            shape.set_value("");
+            let condition = shape.condition().map(|condition| condition_in_row(condition, &group, row));
+            shape.set_condition(condition);
+            self.shapes.push(shape);
+            added.push(self.shapes.len() - 1);
+        }
+        debug!(row, fields = added.len(), "Added section row");
+        Ok(added)
+    }
+
+    /// Remove the last row of a repeating section
+    ///
+    /// Returns false if the section is at its minimum number of rows.
+    #[instrument(skip(self))]
+    pub fn remove_section_row(&mut self, name: &str) -> bool {
+        let Some(group) = self.field_group(name).cloned() else {
+            return false;
+        };
+        let min_rows = group.repeat.map_or(usize::MAX, |section| section.min_rows.max(1));
+        let rows = self.section_rows(name);
+        if rows <= min_rows {
+            return false;
+        }
+
+        for field in &group.fields {
+            if let Some(index) = self.field_index(&row_field_name(field, rows)) {
+                self.delete_shape(index);
+            }
+        }
+        debug!(row = rows, "Removed section row");
+        true
+    }
+
+    /// Add or remove rows until a repeating section has `rows` rows, within its limits
+    ///
+    /// Returns the number of rows the section ends with.
+    pub fn set_section_rows(&mut self, name: &str, rows: usize) -> Result<usize, CanvasError> {
+        let section = self.field_group(name).and_then(|group| group.repeat).ok_or_else(|| {
+            CanvasError::new(
+                CanvasErrorKind::InvalidFieldGroup(format!("{} is not a repeating section", name)),
+                line!(),
+                file!(),
+            )
+        })?;
+        let rows = section.clamp_rows(rows);
+        while self.section_rows(name) < rows {
+            self.add_section_row(name)?;
+        }
+        while self.section_rows(name) > rows && self.remove_section_row(name) {}
+        Ok(self.section_rows(name))
+    }
+
+    /// Give every repeating section at least its minimum number of rows
+    pub(super) fn fill_section_rows(&mut self) {
+        let sections: Vec<(String, usize)> = self
+            .field_groups
+            .iter()
+            .filter_map(|group| group.repeat.map(|section| (group.name.clone(), section.min_rows)))
+            .collect();
+        for (name, min_rows) in sections {
+            if self.section_rows(&name) < min_rows
+                && let Err(e) = self.set_section_rows(&name, min_rows)
+            {
+                warn!("Could not add rows to {}: {}", name, e);
+            }
+        }
+    }
+
+    /// Index of the shape with a field name
+    fn field_index(&self, name: &str) -> Option<usize> {
+        self.shapes.iter().position(|shape| shape.name() == name)
+    }
+
+    /// Toggle the field groups window
+    pub fn toggle_field_groups_panel(&mut self) {
+        self.show_field_groups = !self.show_field_groups;
+    }
+
+    /// Show the field groups window
+    ///
+    /// Lists the groups with the rows each repeating section has on this
+    /// form, and defines new groups from named fields. Returns true if the
+    /// window was shown.
+    pub fn show_field_groups_panel(&mut self, ctx: &egui::Context) -> bool {
+        if !self.show_field_groups {
+            return false;
+        }
+
+        let rows: Vec<usize> = self.field_groups.iter().map(|group| self.section_rows(&group.name)).collect();
+        let field_names: Vec<String> = self
+            .shapes
+            .iter()
+            .map(Shape::name)
+            .filter(|name| !name.is_empty() && !self.field_groups.iter().any(|group| group.locate(name).is_some()))
+            .map(str::to_string)
+            .collect();
+        let mut panel_open = true;
+        let mut add_row = None;
+        let mut remove_row = None;
+        let mut remove_group = None;
+        let mut create = false;
+        let groups = &self.field_groups;
+        let draft = &mut self.field_group_draft;
+        let status = &self.field_group_status;
+        egui::Window::new("Field Groups")
+            .open(&mut panel_open)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                if groups.is_empty() {
+                    ui.label("No field groups yet");
+                }
+                egui::Grid::new("field_groups_grid").num_columns(3).striped(true).show(ui, |ui| {
+                    for (group, rows) in groups.iter().zip(&rows) {
+                        ui.label(&group.name).on_hover_text(group.fields.join(", "));
+                        if group.is_repeating() {
+                            ui.horizontal(|ui| {
+                                if ui.small_button("−").on_hover_text("Remove the last row").clicked() {
+                                    remove_row = Some(group.name.clone());
+                                }
+                                ui.label(format!("{} row(s)", rows));
+                                if ui.small_button("+").on_hover_text("Add a row").clicked() {
+                                    add_row = Some(group.name.clone());
+                                }
+                            });
+                        } else {
+                            ui.weak(format!("{} field(s)", group.fields.len()));
+                        }
+                        if ui.small_button("🗑").on_hover_text("Remove the group, keeping its fields").clicked() {
+                            remove_group = Some(group.name.clone());
+                        }
+                        ui.end_row();
+                    }
+                });
+
+                ui.separator();
+                ui.strong("New group");
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut draft.name);
+                });
+                egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+                    if field_names.is_empty() {
+                        ui.weak("Name fields to add them to a group");
+                    }
+                    for name in &field_names {
+                        let mut included = draft.fields.contains(name);
+                        if ui.checkbox(&mut included, name).changed() {
+                            if included {
+                                draft.fields.push(name.clone());
+                            } else {
+                                draft.fields.retain(|field| field != name);
+                            }
+                        }
+                    }
+                });
+
+                let mut repeats = draft.repeat.is_some();
+                ui.checkbox(&mut repeats, "Repeats for each item");
+                match (repeats, &mut draft.repeat) {
+                    (true, None) => draft.repeat = Some(RepeatingSection::new(egui::vec2(0.0, 20.0))),
+                    (false, Some(_)) => draft.repeat = None,
+                    _ => {}
+                }
+                if let Some(section) = &mut draft.repeat {
+                    egui::Grid::new("field_group_section_grid").num_columns(2).show(ui, |ui| {
+                        ui.label("Row offset:");
+                        ui.horizontal(|ui| {
+                            ui.add(egui::DragValue::new(&mut section.pitch.x).prefix("x ").speed(1.0));
+                            ui.add(egui::DragValue::new(&mut section.pitch.y).prefix("y ").speed(1.0));
+                        });
+                        ui.end_row();
+
+                        ui.label("Minimum rows:");
+                        ui.add(egui::DragValue::new(&mut section.min_rows).range(1..=1000));
+                        ui.end_row();
+
+                        ui.label("Maximum rows:");
+                        let mut max_rows = section.max_rows.unwrap_or(0);
+                        ui.add(egui::DragValue::new(&mut max_rows).range(0..=1000))
+                            .on_hover_text("0 for no limit");
+                        section.max_rows = (max_rows > 0).then_some(max_rows);
+                        ui.end_row();
+                    });
+                }
+                create = ui.button("Create Group").clicked();
+
+                if let Some(status) = status {
+                    ui.colored_label(ui.visuals().warn_fg_color, status);
+                }
+            });
+
+        if create {
+            let group = self.field_group_draft.clone();
+            match self.add_field_group(group) {
+                Ok(()) => {
+                    self.field_group_draft = FieldGroup::default();
+                    self.field_group_status = None;
+                    self.fill_section_rows();
+                }
+                Err(e) => self.field_group_status = Some(e.kind.to_string()),
+            }
+        }
+        if let Some(name) = add_row {
+            self.field_group_status = self.add_section_row(&name).err().map(|e| e.kind.to_string());
+        }
+        if let Some(name) = remove_row {
+            self.remove_section_row(&name);
+        }
+        if let Some(name) = remove_group {
+            self.remove_field_group(&name);
+        }
+        if !panel_open {
+            self.show_field_groups = false;
+        }
+
+        true
+    }
+}
+
+/// A copied field's condition, pointing at the same row when it depends on a field of the group
+fn condition_in_row(condition: &FieldCondition, group: &FieldGroup, row: usize) -> FieldCondition {
+    let in_row = |field: &str| {
+        if group.fields.iter().any(|own| own == field) {
+            row_field_name(field, row)
+        } else {
+            field.to_string()
+        }
+    };
+    match condition {
+        FieldCondition::Checked(field) => FieldCondition::Checked(in_row(field)),
+        FieldCondition::Filled(field) => FieldCondition::Filled(in_row(field)),
+        FieldCondition::Equals { field, value } => FieldCondition::Equals {
+            field: in_row(field),
+            value: value.clone(),
+        },
+    }
+}
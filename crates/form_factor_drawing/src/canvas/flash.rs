@@ -0,0 +1,112 @@
+//! Highlighting newly added detections
+//!
+//! Detections that arrive from a detector or the detection cache pulse a
+//! halo briefly, so a reviewer sees what a run added among the detections
+//! already on the canvas. The pulses are [`Tween`]s advanced by frame time,
+//! so they last [`DETECTION_FLASH_SECONDS`] at any frame rate. On a large
+//! form the view can also zoom to the new detections' combined extent.
+//! Both are preferences in [`DetectionSettings`](crate::DetectionSettings).
+//!
+//! Flashes are keyed by detection index, so removing or replacing
+//! detections stops them rather than letting them move to other detections.
+
+use egui::{Color32, Stroke};
+use form_factor_core::{AppContext, Easing, Tween};
+use std::ops::Range;
+
+use super::core::DrawingCanvas;
+use crate::{ImagePos, Shape};
+
+/// How long a new detection flashes, in seconds
+pub const DETECTION_FLASH_SECONDS: f32 = 0.8;
+
+/// Extra stroke width of the halo at the peak of a flash, in canvas units
+const FLASH_HALO_WIDTH: f32 = 8.0;
+
+/// Opacity of the halo at the peak of a flash
+const FLASH_HALO_OPACITY: f32 = 0.6;
+
+impl DrawingCanvas {
+    /// Advance animations by the time since the last frame
+    ///
+    /// Call once per frame before [`Self::ui`]. Requests further frames
+    /// while anything is still animating.
+    pub fn advance_animations(&mut self, ctx: &AppContext) {
+        self.detection_flashes.tick(ctx);
+    }
+
+    /// Whether any canvas animation is running
+    pub fn is_animating(&self) -> bool {
+        self.detection_flashes.is_animating()
+    }
+
+    /// Strength of the flash on the detection at `index`, from 0 to 1
+    pub fn detection_flash(&self, index: usize) -> Option<f32> {
+        self.detection_flashes.get(&index)
+    }
+
+    /// Point out the detections at `indices` after a detection run added them
+    ///
+    /// Flashes them and zooms to them, as the detection settings allow.
+    pub(super) fn highlight_new_detections(&mut self, indices: Range<usize>) {
+        if indices.is_empty() {
+            return;
+        }
+        if self.detection_settings.zoom_to_new
+            && let Some(extent) = self.detection_extent(indices.clone())
+        {
+            self.zoom_to_rect(extent);
+        }
+        if !self.detection_settings.flash_new {
+            return;
+        }
+        for index in indices {
+            self.flash_detection(index);
+        }
+    }
+
+    /// Flash the detection at `index` once
+    pub(super) fn flash_detection(&mut self, index: usize) {
+        let pulse = Tween::new(0.0, 1.0, DETECTION_FLASH_SECONDS).with_easing(Easing::Pulse);
+        self.detection_flashes.start(index, pulse);
+    }
+
+    /// Bounding rectangle of the detections at `indices`, in canvas coordinates
+    ///
+    /// `None` without a form image, which detections are positioned on.
+    pub(super) fn detection_extent(&self, indices: Range<usize>) -> Option<egui::Rect> {
+        let bounds = self
+            .detections
+            .get(indices)?
+            .iter()
+            .map(Shape::bounding_rect)
+            .reduce(|a, b| a.union(b))?;
+        let mapper = &self.coordinate_mapper;
+        let min = mapper.image_to_canvas(ImagePos(bounds.min))?;
+        let max = mapper.image_to_canvas(ImagePos(bounds.max))?;
+        Some(egui::Rect::from_two_pos(min.0, max.0))
+    }
+
+    /// Stop every detection flash after detections are removed or replaced
+    pub(super) fn stop_detection_flashes(&mut self) {
+        self.detection_flashes.clear();
+    }
+
+    /// Halo drawn beneath a flashing detection, in the detection's coordinates
+    pub(super) fn flash_halo(&self, index: usize, detection: &Shape) -> Option<Shape> {
+        let strength = self.detection_flash(index).filter(|strength| *strength > 0.0)?;
+        let stroke = detection.stroke();
+        let color = if stroke.color == Color32::TRANSPARENT {
+            Color32::WHITE
+        } else {
+            stroke.color
+        };
+
+        let mut halo = detection.clone();
+        halo.set_stroke(Stroke::new(
+            stroke.width + FLASH_HALO_WIDTH * strength,
+            color.gamma_multiply(FLASH_HALO_OPACITY * strength),
+        ));
+        Some(halo)
+    }
+}
@@ -0,0 +1,185 @@
+//! GeoJSON export of annotation geometry
+//!
+//! Every shape and detection becomes a GeoJSON feature carrying its
+//! geometry and field properties, so spatial tools and notebooks can
+//! analyze the annotations without parsing project files. Coordinates are
+//! image pixels with y pointing down when a form image is loaded, matching
+//! the other exports; without one, shapes are in canvas units. Each feature
+//! also holds its geometry as WKT text for tools that read that instead.
+//!
+//! Unlike the image exports, hidden objects are included and marked as
+//! hidden, so the export describes the whole project.
+
+use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas};
+use crate::{LayerType, Shape};
+use form_factor_core::IoOperation;
+use geo_types::{Coord, Geometry, LineString};
+use serde_json::{Map, Value, json};
+use tracing::instrument;
+
+impl DrawingCanvas {
+    /// Build a GeoJSON feature collection of all shapes and detections
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a form image is loaded but the canvas has not
+    /// been rendered with it yet, so shapes cannot be mapped onto it.
+    pub fn to_geojson(&self) -> Result<String, CanvasError> {
+        let on_image = self.form_image_path.is_some();
+        let shapes = if on_image {
+            self.shapes
+                .iter()
+                .map(|shape| self.coordinate_mapper.canvas_shape_to_image(shape))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| {
+                    CanvasError::new(
+                        CanvasErrorKind::InvalidExport(
+                            "the canvas has not been rendered with the form image yet".to_string(),
+                        ),
+                        line!(),
+                        file!(),
+                    )
+                })?
+        } else {
+            self.shapes.clone()
+        };
+
+        let mut features: Vec<Value> = shapes
+            .iter()
+            .enumerate()
+            .map(|(idx, shape)| feature(shape, shape_properties(shape, LayerType::Shapes, idx)))
+            .collect();
+        for (idx, detection) in self.detections.iter().enumerate() {
+            let mut properties = shape_properties(detection, LayerType::Detections, idx);
+            if let Some(kind) = self.detection_kind(idx) {
+                properties.insert("kind".to_string(), json!(kind.to_string()));
+            }
+            if let Some(subtype) = self.detection_tag(idx) {
+                properties.insert("subtype".to_string(), json!(subtype));
+            }
+            if let Some(text) = self.ocr_text(idx) {
+                properties.insert("ocr_text".to_string(), json!(text));
+            }
+            features.push(feature(detection, properties));
+        }
+
+        let collection = json!({
+            "type": "FeatureCollection",
+            "name": self.project_name,
+            "units": if on_image { "image" } else { "canvas" },
+            "features": features,
+        });
+        serde_json::to_string_pretty(&collection)
+            .map_err(|e| CanvasError::new(CanvasErrorKind::Serialization(e.to_string()), line!(), file!()))
+    }
+
+    /// Save all shapes and detections as a GeoJSON file
+    ///
+    /// Returns the number of features written.
+    #[instrument(skip(self), fields(output_path))]
+    pub fn export_geojson(&self, output_path: &str) -> Result<usize, CanvasError> {
+        let geojson = self.to_geojson()?;
+        std::fs::write(output_path, geojson)
+            .map_err(|e| CanvasError::io(e, output_path, IoOperation::Write, line!(), file!()))?;
+        let count = self.shapes.len() + self.detections.len();
+        tracing::info!("Exported {} features to {}", count, output_path);
+        Ok(count)
+    }
+}
+
+/// Name of a shape's variant, e.g. `Rectangle`
+fn shape_type(shape: &Shape) -> &'static str {
+    match shape {
+        Shape::Rectangle(_) => "Rectangle",
+        Shape::Circle(_) => "Circle",
+        Shape::Ellipse(_) => "Ellipse",
+        Shape::Polygon(_) => "Polygon",
+        Shape::Line(_) => "Line",
+        Shape::Polyline(_) => "Polyline",
+        Shape::Arrow(_) => "Arrow",
+        Shape::Callout(_) => "Callout",
+    }
+}
+
+/// Field properties shared by shapes and detections
+fn shape_properties(shape: &Shape, layer: LayerType, index: usize) -> Map<String, Value> {
+    let mut properties = Map::new();
+    properties.insert("layer".to_string(), json!(layer.to_string()));
+    properties.insert("index".to_string(), json!(index));
+    properties.insert("name".to_string(), json!(shape.name()));
+    properties.insert("shape".to_string(), json!(shape_type(shape)));
+    properties.insert("value".to_string(), json!(shape.value()));
+    properties.insert("sensitive".to_string(), json!(shape.is_sensitive()));
+    properties.insert("confidence".to_string(), json!(shape.confidence()));
+    properties.insert("rule".to_string(), json!(shape.rule()));
+    properties.insert("hidden".to_string(), json!(shape.is_hidden()));
+    properties.insert("locked".to_string(), json!(shape.is_locked()));
+    properties
+}
+
+/// GeoJSON feature of a shape with its WKT geometry added to the properties
+fn feature(shape: &Shape, mut properties: Map<String, Value>) -> Value {
+    let geometry = shape.to_geometry();
+    properties.insert("wkt".to_string(), json!(wkt(&geometry)));
+    json!({
+        "type": "Feature",
+        "geometry": geojson_geometry(&geometry),
+        "properties": properties,
+    })
+}
+
+/// GeoJSON position of a coordinate
+fn position(coord: &Coord<f64>) -> Value {
+    json!([coord.x, coord.y])
+}
+
+/// GeoJSON positions of a line string
+fn positions(line: &LineString<f64>) -> Value {
+    Value::Array(line.0.iter().map(position).collect())
+}
+
+/// GeoJSON geometry object of the geometry types shapes produce
+fn geojson_geometry(geometry: &Geometry<f64>) -> Value {
+    match geometry {
+        Geometry::Point(point) => json!({"type": "Point", "coordinates": position(&point.0)}),
+        Geometry::LineString(line) => json!({"type": "LineString", "coordinates": positions(line)}),
+        Geometry::Polygon(polygon) => {
+            let rings: Vec<Value> = std::iter::once(polygon.exterior())
+                .chain(polygon.interiors())
+                .map(positions)
+                .collect();
+            json!({"type": "Polygon", "coordinates": rings})
+        }
+        Geometry::GeometryCollection(collection) => json!({
+            "type": "GeometryCollection",
+            "geometries": collection.iter().map(geojson_geometry).collect::<Vec<_>>(),
+        }),
+        _ => Value::Null,
+    }
+}
+
+/// WKT coordinate list of a line string, e.g. `(0 0, 10 0)`
+fn wkt_ring(line: &LineString<f64>) -> String {
+    let coords: Vec<String> = line.0.iter().map(|c| format!("{} {}", c.x, c.y)).collect();
+    format!("({})", coords.join(", "))
+}
+
+/// Well-known text of the geometry types shapes produce
+fn wkt(geometry: &Geometry<f64>) -> String {
+    match geometry {
+        Geometry::Point(point) => format!("POINT ({} {})", point.x(), point.y()),
+        Geometry::LineString(line) => format!("LINESTRING {}", wkt_ring(line)),
+        Geometry::Polygon(polygon) => {
+            let rings: Vec<String> = std::iter::once(polygon.exterior())
+                .chain(polygon.interiors())
+                .map(wkt_ring)
+                .collect();
+            format!("POLYGON ({})", rings.join(", "))
+        }
+        Geometry::GeometryCollection(collection) => {
+            let parts: Vec<String> = collection.iter().map(wkt).collect();
+            format!("GEOMETRYCOLLECTION ({})", parts.join(", "))
+        }
+        _ => "GEOMETRYCOLLECTION EMPTY".to_string(),
+    }
+}
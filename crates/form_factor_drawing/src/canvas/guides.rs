@@ -0,0 +1,400 @@
+//! Rulers and guide lines for the drawing canvas
+//!
+//! Rulers along the top and left edges of the canvas show image pixel
+//! coordinates (or canvas units when no image is loaded). Dragging out of a
+//! ruler creates a guide line; guides can be dragged to reposition them and
+//! dragged back onto a ruler to remove them. Shape corners snap to nearby
+//! guides while drawing and editing. Guides are stored in canvas
+//! coordinates and saved with the project.
+
+use egui::{Color32, Pos2, Stroke};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::core::DrawingCanvas;
+
+/// Thickness of the rulers in screen pixels
+const RULER_SIZE: f32 = 18.0;
+
+/// Distance in screen pixels within which points snap to a guide
+const GUIDE_SNAP_PX: f32 = 8.0;
+
+/// Minimum screen distance between labelled ruler ticks
+const MIN_TICK_SPACING: f32 = 60.0;
+
+/// Color of guide lines
+const GUIDE_COLOR: Color32 = Color32::from_rgb(0, 190, 220);
+
+/// Direction of a guide line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display)]
+pub enum GuideOrientation {
+    /// Runs left to right at a fixed y
+    Horizontal,
+    /// Runs top to bottom at a fixed x
+    Vertical,
+}
+
+/// A guide line that shapes snap to
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Guide {
+    /// Direction of the line
+    pub orientation: GuideOrientation,
+    /// Canvas y of a horizontal guide, or canvas x of a vertical one
+    pub position: f32,
+}
+
+impl Guide {
+    /// Create a guide
+    pub fn new(orientation: GuideOrientation, position: f32) -> Self {
+        Self {
+            orientation,
+            position,
+        }
+    }
+}
+
+/// Mapping between ruler units and screen pixels along one axis
+struct RulerAxis {
+    /// Screen coordinate of ruler unit 0
+    origin: f32,
+    /// Screen pixels per ruler unit
+    px_per_unit: f32,
+}
+
+impl RulerAxis {
+    fn to_screen(&self, unit: f32) -> f32 {
+        self.origin + unit * self.px_per_unit
+    }
+
+    fn to_unit(&self, screen: f32) -> f32 {
+        (screen - self.origin) / self.px_per_unit
+    }
+
+    /// Smallest 1, 2 or 5 x 10^n unit step at least `MIN_TICK_SPACING` pixels wide
+    fn tick_step(&self) -> f32 {
+        let min_step = MIN_TICK_SPACING / self.px_per_unit;
+        let magnitude = 10f32.powf(min_step.log10().floor());
+        [1.0, 2.0, 5.0, 10.0]
+            .into_iter()
+            .map(|m| m * magnitude)
+            .find(|step| *step >= min_step)
+            .unwrap_or(10.0 * magnitude)
+    }
+}
+
+impl DrawingCanvas {
+    /// Add a guide, returning its index
+    pub fn add_guide(&mut self, orientation: GuideOrientation, position: f32) -> usize {
+        self.guides.push(Guide::new(orientation, position));
+        self.guides.len() - 1
+    }
+
+    /// Remove a guide by index
+    pub fn remove_guide(&mut self, index: usize) -> Option<Guide> {
+        (index < self.guides.len()).then(|| self.guides.remove(index))
+    }
+
+    /// Remove all guides
+    pub fn clear_guides(&mut self) {
+        self.guides.clear();
+    }
+
+    /// Snap a canvas position to the nearest guides within snapping distance
+    ///
+    /// Horizontal and vertical guides snap independently, so a point near a
+    /// crossing snaps to both. Returns the position unchanged if snapping is
+    /// disabled in the canvas settings.
+    pub fn snap_to_guides(&self, pos: Pos2) -> Pos2 {
+        if !self.canvas_settings.snap_to_guides {
+            return pos;
+        }
+        let threshold = GUIDE_SNAP_PX / self.zoom_level;
+        let nearest = |orientation: GuideOrientation, value: f32| {
+            self.guides
+                .iter()
+                .filter(|guide| guide.orientation == orientation)
+                .map(|guide| guide.position)
+                .filter(|position| (position - value).abs() <= threshold)
+                .min_by(|a, b| (a - value).abs().total_cmp(&(b - value).abs()))
+                .unwrap_or(value)
+        };
+        Pos2::new(
+            nearest(GuideOrientation::Vertical, pos.x),
+            nearest(GuideOrientation::Horizontal, pos.y),
+        )
+    }
+
+    /// Ruler unit mappings for both axes
+    ///
+    /// Rulers count image pixels when an image is loaded, canvas units otherwise.
+    fn ruler_axes(&self, to_screen: &egui::emath::TSTransform) -> (RulerAxis, RulerAxis) {
+        let (scale, offset) = self
+            .coordinate_mapper
+            .image_fit()
+            .map_or((1.0, Pos2::ZERO), |fit| (fit.scale, fit.offset));
+        let zoom = to_screen.scaling;
+        let axis = |offset: f32, translation: f32| RulerAxis {
+            origin: offset * zoom + translation,
+            px_per_unit: scale * zoom,
+        };
+        (
+            axis(offset.x, to_screen.translation.x),
+            axis(offset.y, to_screen.translation.y),
+        )
+    }
+
+    /// Draw guides and rulers and handle dragging guides in and out of the rulers
+    pub(super) fn show_rulers_and_guides(
+        &mut self,
+        ui: &mut egui::Ui,
+        canvas_rect: egui::Rect,
+        painter: &egui::Painter,
+        to_screen: &egui::emath::TSTransform,
+    ) {
+        self.draw_guides(painter, canvas_rect, to_screen);
+        self.handle_guide_drags(ui, canvas_rect, to_screen);
+
+        if self.canvas_settings.show_rulers {
+            self.draw_rulers(painter, canvas_rect, to_screen);
+        }
+    }
+
+    fn draw_guides(
+        &self,
+        painter: &egui::Painter,
+        canvas_rect: egui::Rect,
+        to_screen: &egui::emath::TSTransform,
+    ) {
+        for (idx, guide) in self.guides.iter().enumerate() {
+            let width = if self.guide_drag == Some(idx) {
+                2.0
+            } else {
+                1.0
+            };
+            let stroke = Stroke::new(width, GUIDE_COLOR);
+            match guide.orientation {
+                GuideOrientation::Horizontal => {
+                    let y = to_screen.mul_pos(Pos2::new(0.0, guide.position)).y;
+                    painter.hline(canvas_rect.x_range(), y, stroke);
+                }
+                GuideOrientation::Vertical => {
+                    let x = to_screen.mul_pos(Pos2::new(guide.position, 0.0)).x;
+                    painter.vline(x, canvas_rect.y_range(), stroke);
+                }
+            }
+        }
+    }
+
+    fn handle_guide_drags(
+        &mut self,
+        ui: &mut egui::Ui,
+        canvas_rect: egui::Rect,
+        to_screen: &egui::emath::TSTransform,
+    ) {
+        let (top_ruler, left_ruler) = ruler_rects(canvas_rect);
+        let from_screen = to_screen.inverse();
+
+        // Start a new guide by dragging out of a ruler
+        if self.canvas_settings.show_rulers {
+            let id = ui.id().with("canvas_rulers");
+            for (rect, orientation, cursor) in [
+                (
+                    top_ruler,
+                    GuideOrientation::Horizontal,
+                    egui::CursorIcon::ResizeVertical,
+                ),
+                (
+                    left_ruler,
+                    GuideOrientation::Vertical,
+                    egui::CursorIcon::ResizeHorizontal,
+                ),
+            ] {
+                let response = ui
+                    .interact(rect, id.with(orientation.to_string()), egui::Sense::drag())
+                    .on_hover_cursor(cursor);
+                if response.drag_started()
+                    && let Some(pos) = response.interact_pointer_pos()
+                {
+                    let canvas_pos = from_screen.mul_pos(pos);
+                    let position = match orientation {
+                        GuideOrientation::Horizontal => canvas_pos.y,
+                        GuideOrientation::Vertical => canvas_pos.x,
+                    };
+                    self.guide_drag = Some(self.add_guide(orientation, position));
+                    debug!(%orientation, position, "Guide created from ruler");
+                }
+            }
+        }
+
+        // Pick up existing guides in Select mode
+        if self.current_tool == crate::ToolMode::Select && self.guide_drag.is_none() {
+            for idx in 0..self.guides.len() {
+                let guide = self.guides[idx];
+                let (rect, cursor) = match guide.orientation {
+                    GuideOrientation::Horizontal => {
+                        let y = to_screen.mul_pos(Pos2::new(0.0, guide.position)).y;
+                        (
+                            egui::Rect::from_x_y_ranges(canvas_rect.x_range(), y - 3.0..=y + 3.0),
+                            egui::CursorIcon::ResizeVertical,
+                        )
+                    }
+                    GuideOrientation::Vertical => {
+                        let x = to_screen.mul_pos(Pos2::new(guide.position, 0.0)).x;
+                        (
+                            egui::Rect::from_x_y_ranges(x - 3.0..=x + 3.0, canvas_rect.y_range()),
+                            egui::CursorIcon::ResizeHorizontal,
+                        )
+                    }
+                };
+                if !canvas_rect.intersects(rect) {
+                    continue;
+                }
+                let response = ui
+                    .interact(
+                        rect,
+                        ui.id().with(("canvas_guide", idx)),
+                        egui::Sense::drag(),
+                    )
+                    .on_hover_cursor(cursor);
+                if response.drag_started() {
+                    self.guide_drag = Some(idx);
+                }
+            }
+        }
+
+        // Follow the pointer while a guide is being dragged
+        let Some(idx) = self.guide_drag else {
+            return;
+        };
+        let (pointer, released) =
+            ui.input(|i| (i.pointer.interact_pos(), !i.pointer.primary_down()));
+        if let (Some(pointer), Some(guide)) = (pointer, self.guides.get_mut(idx)) {
+            let canvas_pos = from_screen.mul_pos(pointer);
+            guide.position = match guide.orientation {
+                GuideOrientation::Horizontal => canvas_pos.y,
+                GuideOrientation::Vertical => canvas_pos.x,
+            };
+        }
+        if released {
+            self.guide_drag = None;
+            let dropped_on_ruler = pointer.is_some_and(|p| {
+                let ruler = match self.guides.get(idx).map(|g| g.orientation) {
+                    Some(GuideOrientation::Horizontal) => top_ruler,
+                    _ => left_ruler,
+                };
+                ruler.contains(p) || !canvas_rect.contains(p)
+            });
+            if dropped_on_ruler {
+                self.remove_guide(idx);
+                debug!(idx, "Guide removed");
+            }
+        }
+    }
+
+    fn draw_rulers(
+        &self,
+        painter: &egui::Painter,
+        canvas_rect: egui::Rect,
+        to_screen: &egui::emath::TSTransform,
+    ) {
+        let (top_ruler, left_ruler) = ruler_rects(canvas_rect);
+        let visuals = painter.ctx().style().visuals.clone();
+        let background = visuals.extreme_bg_color;
+        let tick_stroke = Stroke::new(1.0, visuals.weak_text_color());
+        let font = egui::FontId::monospace(9.0);
+        let (x_axis, y_axis) = self.ruler_axes(to_screen);
+
+        painter.rect_filled(top_ruler, 0.0, background);
+        painter.rect_filled(left_ruler, 0.0, background);
+
+        // Top ruler: x coordinates
+        let step = x_axis.tick_step();
+        let first = (x_axis.to_unit(top_ruler.left()) / step).ceil() as i64;
+        let last = (x_axis.to_unit(top_ruler.right()) / step).floor() as i64;
+        for i in first..=last {
+            let value = i as f32 * step;
+            let x = x_axis.to_screen(value);
+            if x < left_ruler.right() {
+                continue;
+            }
+            painter.vline(x, top_ruler.y_range(), tick_stroke);
+            painter.text(
+                Pos2::new(x + 2.0, top_ruler.top()),
+                egui::Align2::LEFT_TOP,
+                format!("{}", value),
+                font.clone(),
+                visuals.text_color(),
+            );
+            for minor in 1..5 {
+                let x = x_axis.to_screen(value + step * minor as f32 / 5.0);
+                painter.vline(
+                    x,
+                    top_ruler.bottom() - 4.0..=top_ruler.bottom(),
+                    tick_stroke,
+                );
+            }
+        }
+
+        // Left ruler: y coordinates
+        let step = y_axis.tick_step();
+        let first = (y_axis.to_unit(left_ruler.top()) / step).ceil() as i64;
+        let last = (y_axis.to_unit(left_ruler.bottom()) / step).floor() as i64;
+        for i in first..=last {
+            let value = i as f32 * step;
+            let y = y_axis.to_screen(value);
+            if y < top_ruler.bottom() {
+                continue;
+            }
+            painter.hline(left_ruler.x_range(), y, tick_stroke);
+            painter.text(
+                Pos2::new(left_ruler.left() + 1.0, y + 2.0),
+                egui::Align2::LEFT_TOP,
+                format!("{}", value),
+                font.clone(),
+                visuals.text_color(),
+            );
+            for minor in 1..5 {
+                let y = y_axis.to_screen(value + step * minor as f32 / 5.0);
+                painter.hline(
+                    left_ruler.right() - 4.0..=left_ruler.right(),
+                    y,
+                    tick_stroke,
+                );
+            }
+        }
+
+        // Guide markers on the rulers
+        for guide in &self.guides {
+            let marker = to_screen.mul_pos(Pos2::new(guide.position, guide.position));
+            match guide.orientation {
+                GuideOrientation::Horizontal => {
+                    painter.hline(
+                        left_ruler.x_range(),
+                        marker.y,
+                        Stroke::new(2.0, GUIDE_COLOR),
+                    );
+                }
+                GuideOrientation::Vertical => {
+                    painter.vline(marker.x, top_ruler.y_range(), Stroke::new(2.0, GUIDE_COLOR));
+                }
+            }
+        }
+
+        // Corner square
+        let corner = egui::Rect::from_min_size(canvas_rect.min, egui::Vec2::splat(RULER_SIZE));
+        painter.rect_filled(corner, 0.0, background);
+    }
+}
+
+/// Screen areas of the top and left rulers
+fn ruler_rects(canvas_rect: egui::Rect) -> (egui::Rect, egui::Rect) {
+    let top = egui::Rect::from_min_max(
+        canvas_rect.min,
+        Pos2::new(canvas_rect.right(), canvas_rect.top() + RULER_SIZE),
+    );
+    let left = egui::Rect::from_min_max(
+        canvas_rect.min,
+        Pos2::new(canvas_rect.left() + RULER_SIZE, canvas_rect.bottom()),
+    );
+    (top, left)
+}
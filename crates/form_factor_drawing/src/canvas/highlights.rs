@@ -0,0 +1,141 @@
+//! Highlighter strokes over the form image
+//!
+//! Reviewers draw thick translucent strokes with the Highlighter tool to
+//! mark regions to revisit. Highlights live on their own layer, above the
+//! form image and below detections and shapes, so they never cover the
+//! annotations. They are stored in canvas coordinates and saved with the
+//! project.
+//!
+//! Exports blend highlights multiply-style, so the text underneath keeps
+//! its darkness and only the paper takes the color. egui has no multiply
+//! blending, so on screen they are drawn with plain transparency instead.
+
+use egui::{Color32, Pos2, Rect, Stroke};
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::core::DrawingCanvas;
+use super::export::segment_distance;
+
+/// Width of new highlighter strokes in canvas units
+pub const HIGHLIGHT_WIDTH: f32 = 16.0;
+
+/// Color of new highlighter strokes
+pub const HIGHLIGHT_COLOR: Color32 = Color32::from_rgba_unmultiplied_const(255, 220, 0, 110);
+
+/// A thick translucent stroke marking a region to revisit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Highlight {
+    /// Points along the stroke, in canvas coordinates
+    pub points: Vec<Pos2>,
+    /// Stroke width in canvas units
+    pub width: f32,
+    /// Stroke color; its alpha is the strength of the highlight
+    pub color: Color32,
+}
+
+impl Highlight {
+    /// Create a highlight through `points`
+    pub fn new(points: Vec<Pos2>, width: f32, color: Color32) -> Self {
+        Self { points, width, color }
+    }
+
+    /// Area covered by the stroke
+    pub fn bounding_rect(&self) -> Rect {
+        Rect::from_points(&self.points).expand(self.width / 2.0)
+    }
+
+    /// Whether a point lies under the stroke
+    pub fn contains_point(&self, pos: Pos2) -> bool {
+        self.distance(pos) <= self.width / 2.0
+    }
+
+    /// Distance from a point to the center line of the stroke
+    fn distance(&self, pos: Pos2) -> f32 {
+        match self.points.as_slice() {
+            [] => f32::INFINITY,
+            [point] => point.distance(pos),
+            points => points
+                .windows(2)
+                .map(|pair| segment_distance(pos, pair[0], pair[1]))
+                .fold(f32::INFINITY, f32::min),
+        }
+    }
+
+    /// Scale the stroke about the origin and then translate it by `offset`
+    pub(super) fn mapped(&self, scale: f32, offset: Pos2) -> Self {
+        Self {
+            points: self
+                .points
+                .iter()
+                .map(|p| Pos2::new(p.x * scale + offset.x, p.y * scale + offset.y))
+                .collect(),
+            width: self.width * scale,
+            color: self.color,
+        }
+    }
+
+    /// Draw the stroke through a canvas-to-screen transform
+    pub(super) fn render(&self, painter: &egui::Painter, transform: &egui::emath::TSTransform) {
+        let points: Vec<Pos2> = self.points.iter().map(|p| transform.mul_pos(*p)).collect();
+        let width = self.width * transform.scaling;
+        match points.as_slice() {
+            [] => {}
+            [point] => {
+                painter.circle_filled(*point, width / 2.0, self.color);
+            }
+            _ => {
+                painter.add(egui::Shape::line(points, Stroke::new(width, self.color)));
+            }
+        }
+    }
+
+    /// Multiply the stroke onto an image, sampling pixel centers
+    ///
+    /// Each covered pixel is darkened once, so overlapping parts of one
+    /// stroke don't build up.
+    pub(super) fn rasterize(&self, target: &mut RgbaImage) {
+        let bounds = self.bounding_rect().expand(1.0);
+        let (x0, y0) = (bounds.min.x.floor().max(0.0) as u32, bounds.min.y.floor().max(0.0) as u32);
+        let (x1, y1) = (
+            (bounds.max.x.ceil().max(0.0) as u32).min(target.width()),
+            (bounds.max.y.ceil().max(0.0) as u32).min(target.height()),
+        );
+        let [r, g, b, a] = self.color.to_srgba_unmultiplied();
+        let alpha = a as f32 / 255.0;
+        // Per-channel factor: full strength multiplies by the color, none leaves the pixel
+        let factors = [r, g, b].map(|channel| 1.0 - alpha + alpha * channel as f32 / 255.0);
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                if !self.contains_point(Pos2::new(x as f32 + 0.5, y as f32 + 0.5)) {
+                    continue;
+                }
+                let pixel = target.get_pixel_mut(x, y);
+                for (channel, factor) in pixel.0.iter_mut().zip(factors) {
+                    *channel = (*channel as f32 * factor).round() as u8;
+                }
+            }
+        }
+    }
+}
+
+impl DrawingCanvas {
+    /// Add a highlight, returning its index
+    pub fn add_highlight(&mut self, highlight: Highlight) -> usize {
+        self.highlights.push(highlight);
+        self.highlights.len() - 1
+    }
+
+    /// Remove a highlight by index
+    pub fn remove_highlight(&mut self, index: usize) -> Option<Highlight> {
+        (index < self.highlights.len()).then(|| self.highlights.remove(index))
+    }
+
+    /// Remove all highlights
+    pub fn clear_highlights(&mut self) {
+        debug!(count = self.highlights.len(), "Clearing highlights");
+        self.highlights.clear();
+    }
+}
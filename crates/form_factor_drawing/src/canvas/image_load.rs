@@ -0,0 +1,92 @@
+//! Form images decoded in the background when a project opens
+//!
+//! Decoding a large scanned form takes most of the time it takes to open
+//! a project. Opening a project file reads its annotations and returns;
+//! the form image is decoded on a worker thread and appears on the canvas
+//! in the first frame after it is ready. Shapes and detections can be
+//! viewed and edited meanwhile. Browsers have no threads, so there the
+//! image is decoded in the first frame the canvas is drawn.
+
+use super::core::{CanvasError, DrawingCanvas};
+use form_factor_core::IoOperation;
+use image::{DynamicImage, ImageResult};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// How often the canvas checks on an image being decoded
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Form image being decoded while its project is already open
+#[derive(Debug, Clone)]
+pub(super) struct PendingImage {
+    /// Path of the image file
+    path: String,
+    /// Decoded image, once the worker is done
+    decoded: Arc<Mutex<Option<ImageResult<DynamicImage>>>>,
+}
+
+impl PendingImage {
+    /// Start decoding the image at `path`
+    pub(super) fn start(path: &str) -> Self {
+        let pending = Self {
+            path: path.to_string(),
+            decoded: Arc::new(Mutex::new(None)),
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let (path, decoded) = (pending.path.clone(), Arc::clone(&pending.decoded));
+            std::thread::spawn(move || {
+                let image = image::open(&path);
+                if let Ok(mut slot) = decoded.lock() {
+                    *slot = Some(image);
+                }
+            });
+        }
+        pending
+    }
+
+    /// The decoded image, if decoding has finished
+    fn take(&self) -> Option<ImageResult<DynamicImage>> {
+        if cfg!(target_arch = "wasm32") {
+            return Some(image::open(&self.path));
+        }
+        self.decoded.lock().ok()?.take()
+    }
+}
+
+impl DrawingCanvas {
+    /// Whether the form image of a project being opened is still being decoded
+    pub fn form_image_loading(&self) -> bool {
+        self.pending_image_load.is_some()
+    }
+
+    /// Decode the form image at `path` in the background, replacing the current one
+    pub(super) fn load_form_image_lazily(&mut self, path: &str) {
+        self.form_image = None;
+        self.form_image_size = None;
+        self.form_image_path = Some(path.to_string());
+        self.pending_image_load = Some(PendingImage::start(path));
+        debug!(path, "Decoding form image in the background");
+    }
+
+    /// Show the form image once it has been decoded, or check again shortly
+    pub(super) fn poll_form_image(&mut self, ctx: &egui::Context) {
+        let Some(pending) = &self.pending_image_load else {
+            return;
+        };
+        let Some(decoded) = pending.take() else {
+            ctx.request_repaint_after(POLL_INTERVAL);
+            return;
+        };
+        let path = pending.path.clone();
+        self.pending_image_load = None;
+        match decoded {
+            Ok(image) => self.set_form_image(image, &path, ctx),
+            Err(e) => {
+                let e = CanvasError::image(e, &path, IoOperation::Read, line!(), file!());
+                warn!("Could not load form image from {}: {}", path, e);
+            }
+        }
+    }
+}
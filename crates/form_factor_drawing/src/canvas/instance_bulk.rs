@@ -0,0 +1,256 @@
+//! Bulk actions on the instances of a batch review
+//!
+//! A batch of scanned forms can run to hundreds of instances, too many to
+//! open and change one at a time. The batch review window lists the
+//! instances with a checkbox each, and acts on every checked instance at
+//! once: exporting their projects to a folder, moving them to another
+//! template, setting their workflow status, or deleting them. Exported
+//! projects keep each instance's encoding, compression and password.
+//!
+//! Reassigning and changing status mark the instances modified, to be
+//! written back with the review's save. Deleting removes the instances from
+//! the batch and their project files from disk straight away.
+
+use super::batch_review::BatchReview;
+use super::compression::is_compressed;
+use super::context_menu::CanvasAction;
+use super::core::{CanvasError, DrawingCanvas};
+use super::encoding::ProjectEncoding;
+use super::encryption::is_encrypted;
+use crate::TaskStatus;
+use form_factor_core::IoOperation;
+use std::path::{Path, PathBuf};
+use strum::IntoEnumIterator;
+use tracing::{error, info, instrument};
+
+/// File extension for a project file holding `bytes`
+fn project_extension(bytes: &[u8]) -> &'static str {
+    let plain_json =
+        !is_encrypted(bytes) && !is_compressed(bytes) && ProjectEncoding::detect(bytes) == ProjectEncoding::Json;
+    if plain_json { "json" } else { "ffp" }
+}
+
+impl BatchReview {
+    /// Check or uncheck an instance for bulk actions
+    pub fn select_instance(&mut self, instance: usize, selected: bool) {
+        if selected && instance < self.instances.len() {
+            self.selected.insert(instance);
+        } else {
+            self.selected.remove(&instance);
+        }
+        self.confirm_delete = false;
+    }
+
+    /// Check every instance
+    pub fn select_all_instances(&mut self) {
+        self.selected = (0..self.instances.len()).collect();
+        self.confirm_delete = false;
+    }
+
+    /// Uncheck every instance
+    pub fn clear_instance_selection(&mut self) {
+        self.selected.clear();
+        self.confirm_delete = false;
+    }
+
+    /// Checked instances, in batch order
+    pub fn selected_instances(&self) -> Vec<usize> {
+        self.selected.iter().copied().collect()
+    }
+
+    /// Write the project of every checked instance to `dir`, named after the instance
+    ///
+    /// Each project is written the way the instance saves it, see
+    /// [`DrawingCanvas::project_bytes`]: plain JSON as `{name}.json`, and
+    /// binary, compressed or password-protected projects as `{name}.ffp`.
+    /// Returns the paths written.
+    #[instrument(skip(self), fields(selected = self.selected.len()))]
+    pub fn export_selected(&self, dir: &Path) -> Result<Vec<PathBuf>, CanvasError> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| CanvasError::io(e, dir.display().to_string(), IoOperation::Create, line!(), file!()))?;
+        let mut written = Vec::new();
+        for instance in self.selected.iter().filter_map(|idx| self.instances.get(*idx)) {
+            let bytes = instance
+                .canvas
+                .project_bytes()
+                .map_err(|e| e.with_path(instance.name.as_str(), IoOperation::Write))?;
+            let path = dir.join(format!("{}.{}", instance.name, project_extension(&bytes)));
+            let path_str = path.to_string_lossy().into_owned();
+            std::fs::write(&path, bytes)
+                .map_err(|e| CanvasError::io(e, path_str.as_str(), IoOperation::Write, line!(), file!()))?;
+            written.push(path);
+        }
+        info!("Exported {} instance(s) to {}", written.len(), dir.display());
+        Ok(written)
+    }
+
+    /// Move every checked instance to another template, or to none
+    ///
+    /// Confidences are calibrated per template, so the review queue is
+    /// rebuilt. Returns the number of instances changed.
+    pub fn reassign_selected(&mut self, template: Option<&str>) -> usize {
+        let mut changed = 0;
+        let selected = &self.selected;
+        for (_, instance) in self.instances.iter_mut().enumerate().filter(|(idx, _)| selected.contains(idx)) {
+            if instance.canvas.template_name.as_deref() == template {
+                continue;
+            }
+            instance.canvas.template_name = template.map(str::to_string);
+            instance.canvas.template_page = None;
+            instance.canvas.template_page_index = None;
+            instance.modified = true;
+            changed += 1;
+        }
+        if changed > 0 {
+            self.rebuild_queue();
+        }
+        info!(template, changed, "Reassigned instances");
+        changed
+    }
+
+    /// Set the workflow status of every checked instance
+    ///
+    /// Returns the number of instances changed.
+    pub fn set_selected_status(&mut self, status: TaskStatus) -> usize {
+        let mut changed = 0;
+        let selected = &self.selected;
+        for (_, instance) in self.instances.iter_mut().enumerate().filter(|(idx, _)| selected.contains(idx)) {
+            // Projects hold a single page
+            if instance.canvas.workflow.status(0) == status {
+                continue;
+            }
+            instance.canvas.workflow.set_status(0, status);
+            instance.modified = true;
+            changed += 1;
+        }
+        info!(%status, changed, "Changed instance status");
+        changed
+    }
+
+    /// Remove every checked instance from the batch and delete its project file
+    ///
+    /// The review queue restarts without the removed instances. Stops at
+    /// the first file that cannot be deleted, leaving that instance and the
+    /// ones after it in the batch. Returns the number of instances removed.
+    #[instrument(skip(self), fields(selected = self.selected.len()))]
+    pub fn delete_selected(&mut self) -> Result<usize, CanvasError> {
+        let mut removed = 0;
+        let mut result = Ok(());
+        for idx in self.selected.iter().rev().copied().collect::<Vec<_>>() {
+            if let Some(path) = self.instances.get(idx).and_then(|instance| instance.path.as_ref())
+                && let Err(e) = std::fs::remove_file(path)
+                && e.kind() != std::io::ErrorKind::NotFound
+            {
+                result = Err(CanvasError::io(e, path.to_string_lossy(), IoOperation::Delete, line!(), file!()));
+                break;
+            }
+            self.instances.remove(idx);
+            self.selected.remove(&idx);
+            removed += 1;
+        }
+        self.selected.clear();
+        self.confirm_delete = false;
+        self.rebuild_queue();
+        info!("Deleted {} instance(s)", removed);
+        result.map(|()| removed)
+    }
+
+    /// Show the instance list with its bulk actions
+    ///
+    /// Returns true if exporting the checked instances was requested.
+    pub(super) fn show_instance_manager(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut export = false;
+        let title = format!("Instances ({} of {} checked)", self.selected.len(), self.instances.len());
+        egui::CollapsingHeader::new(title).id_salt("batch_instances").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                if ui.small_button("All").clicked() {
+                    self.select_all_instances();
+                }
+                if ui.small_button("None").clicked() {
+                    self.clear_instance_selection();
+                }
+            });
+
+            let mut toggled = None;
+            egui::ScrollArea::vertical().max_height(160.0).id_salt("batch_instance_list").show(ui, |ui| {
+                for (idx, instance) in self.instances.iter().enumerate() {
+                    let mut checked = self.selected.contains(&idx);
+                    let mut label = instance.name.clone();
+                    if instance.modified {
+                        label.push_str(" *");
+                    }
+                    let template = instance.canvas.template_name.as_deref().unwrap_or("no template");
+                    let hover = format!("{} · {}", template, instance.canvas.workflow.status(0));
+                    if ui.checkbox(&mut checked, label).on_hover_text(hover).changed() {
+                        toggled = Some((idx, checked));
+                    }
+                }
+            });
+            if let Some((idx, checked)) = toggled {
+                self.select_instance(idx, checked);
+            }
+
+            ui.add_enabled_ui(!self.selected.is_empty(), |ui| {
+                ui.horizontal(|ui| {
+                    export = ui.button("Export...").on_hover_text("Write the checked projects to a folder").clicked();
+                    let mut status = None;
+                    egui::ComboBox::from_id_salt("batch_instance_status")
+                        .selected_text("Set status")
+                        .show_ui(ui, |ui| {
+                            for option in TaskStatus::iter() {
+                                if ui.selectable_label(false, option.to_string()).clicked() {
+                                    status = Some(option);
+                                }
+                            }
+                        });
+                    if let Some(status) = status {
+                        self.set_selected_status(status);
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.template_draft)
+                            .hint_text("Template name")
+                            .desired_width(160.0),
+                    );
+                    if ui.button("Reassign").on_hover_text("Leave blank for no template").clicked() {
+                        let template = self.template_draft.trim().to_string();
+                        self.reassign_selected((!template.is_empty()).then_some(template.as_str()));
+                    }
+                });
+                if self.confirm_delete {
+                    ui.horizontal(|ui| {
+                        ui.colored_label(
+                            ui.visuals().error_fg_color,
+                            format!("Delete {} project file(s)?", self.selected.len()),
+                        );
+                        if ui.button("Delete").clicked()
+                            && let Err(e) = self.delete_selected()
+                        {
+                            error!("Failed to delete instances: {}", e);
+                            self.status = Some(e.to_string());
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.confirm_delete = false;
+                        }
+                    });
+                } else if ui.button("Delete...").clicked() {
+                    self.confirm_delete = true;
+                }
+            });
+        });
+        export
+    }
+}
+
+impl DrawingCanvas {
+    /// The open batch review, for changing it from outside the window
+    pub fn batch_review_mut(&mut self) -> Option<&mut BatchReview> {
+        self.batch_review.as_mut()
+    }
+
+    /// Ask the application for a folder to export the checked instances to
+    pub(super) fn request_instance_export(&mut self) {
+        self.pending_actions.push(CanvasAction::ExportInstances);
+    }
+}
@@ -9,19 +9,28 @@
 //! - Text detection integration (with feature flag)
 //! - OCR text extraction (with feature flag)
 
-use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas};
+use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas, PROJECT_FORMAT_VERSION};
+#[cfg(all(feature = "logo-detection", not(target_arch = "wasm32")))]
+use super::detection_cache::content_hash;
+use super::encryption::LockedSource;
+use super::highlights::Highlight;
+use super::journal::{ProjectJournal, journal_path, read_project};
+use super::provenance::SourceChannel;
 use crate::{LayerType, RecentProjects};
-#[cfg(any(feature = "text-detection", feature = "logo-detection"))]
-use crate::{Rectangle, Shape};
-#[cfg(feature = "text-detection")]
+use form_factor_core::IoOperation;
+#[cfg(all(any(feature = "text-detection", feature = "logo-detection"), not(target_arch = "wasm32")))]
+use crate::Rectangle;
+#[cfg(all(any(feature = "text-detection", feature = "logo-detection", feature = "ocr"), not(target_arch = "wasm32")))]
+use crate::Shape;
+#[cfg(all(feature = "text-detection", not(target_arch = "wasm32")))]
 use form_factor_cv::TextDetector;
-#[cfg(feature = "logo-detection")]
+#[cfg(all(feature = "logo-detection", not(target_arch = "wasm32")))]
 use form_factor_cv::LogoDetector;
-#[cfg(any(feature = "text-detection", feature = "logo-detection"))]
+#[cfg(all(any(feature = "text-detection", feature = "logo-detection"), not(target_arch = "wasm32")))]
 use egui::{Color32, Pos2, Stroke};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::{debug, instrument, warn};
-#[cfg(any(feature = "text-detection", feature = "logo-detection"))]
+#[cfg(all(any(feature = "text-detection", feature = "logo-detection", feature = "ocr"), not(target_arch = "wasm32")))]
 use tracing::trace;
 
 impl DrawingCanvas {
@@ -29,20 +38,40 @@ impl DrawingCanvas {
     pub fn clear(&mut self) {
         debug!("Clearing canvas: shapes={}, detections={}", self.shapes.len(), self.detections.len());
         self.shapes.clear();
+        self.tab_order.clear();
         self.detections.clear();
+        self.stop_detection_flashes();
+        self.selection_history.clear();
+        self.pii_flags.clear();
+        self.detection_tags.clear();
+        self.ocr_records.clear();
+        self.ocr_order.clear();
+        self.selected_ocr_word = None;
+        self.selected_detections.clear();
+        self.prune_comment_threads();
     }
 
     /// Clear only shapes from the canvas
     pub fn clear_shapes(&mut self) {
         debug!("Clearing shapes: count={}", self.shapes.len());
         self.shapes.clear();
+        self.tab_order.clear();
         self.selected_shape = None;
+        self.prune_comment_threads();
     }
 
     /// Clear only detections from the canvas
     pub fn clear_detections(&mut self) {
         debug!("Clearing detections: count={}", self.detections.len());
         self.detections.clear();
+        self.stop_detection_flashes();
+        self.pii_flags.clear();
+        self.detection_tags.clear();
+        self.ocr_records.clear();
+        self.ocr_order.clear();
+        self.selected_ocr_word = None;
+        self.selected_detections.clear();
+        self.prune_comment_threads();
     }
 
     /// Clear the canvas image (form image)
@@ -63,11 +92,50 @@ impl DrawingCanvas {
 
     /// Load a form image from a file path
     pub fn load_form_image(&mut self, path: &str, ctx: &egui::Context) -> Result<(), CanvasError> {
-        // Load the image from disk
-        let img = image::open(path).map_err(|e| {
-            CanvasError::new(CanvasErrorKind::ImageLoad(e.to_string()), line!(), file!())
-        })?;
+        self.load_form_image_from(path, SourceChannel::Opened, ctx)
+    }
+
+    /// Load a form image from a file path, recording how it arrived
+    pub(super) fn load_form_image_from(
+        &mut self,
+        path: &str,
+        channel: SourceChannel,
+        ctx: &egui::Context,
+    ) -> Result<(), CanvasError> {
+        // Read once, so the recorded hash is of the bytes decoded
+        let bytes = std::fs::read(path).map_err(|e| CanvasError::io(e, path, IoOperation::Read, line!(), file!()))?;
+        let img = image::load_from_memory(&bytes)
+            .map_err(|e| CanvasError::image(e, path, IoOperation::Read, line!(), file!()))?;
+        self.set_form_image(img, path, ctx);
+        self.record_source(path, &bytes, channel);
+        Ok(())
+    }
 
+    /// Load a form image from encoded bytes, such as a file dropped into a browser
+    ///
+    /// `name` is recorded as the form image path, so a project saved afterwards
+    /// refers to the image by that name.
+    pub fn load_form_image_bytes(&mut self, name: &str, bytes: &[u8], ctx: &egui::Context) -> Result<(), CanvasError> {
+        self.load_form_image_bytes_from(name, bytes, SourceChannel::Uploaded, ctx)
+    }
+
+    /// Load a form image from encoded bytes, recording how it arrived
+    pub(super) fn load_form_image_bytes_from(
+        &mut self,
+        name: &str,
+        bytes: &[u8],
+        channel: SourceChannel,
+        ctx: &egui::Context,
+    ) -> Result<(), CanvasError> {
+        let img = image::load_from_memory(bytes)
+            .map_err(|e| CanvasError::image(e, name, IoOperation::Read, line!(), file!()))?;
+        self.set_form_image(img, name, ctx);
+        self.record_source(name, bytes, channel);
+        Ok(())
+    }
+
+    /// Upload a decoded form image as a texture and fit it to the window
+    pub(super) fn set_form_image(&mut self, img: image::DynamicImage, path: &str, ctx: &egui::Context) {
         // Convert to RGBA8
         let size = [img.width() as usize, img.height() as usize];
         let img_rgba = img.to_rgba8();
@@ -83,7 +151,8 @@ impl DrawingCanvas {
             egui::TextureOptions::default(),
         );
 
-        // Store the texture and metadata
+        // Store the texture and metadata, superseding any image still being decoded
+        self.pending_image_load = None;
         self.form_image_size = Some(egui::Vec2::new(img.width() as f32, img.height() as f32));
         self.form_image = Some(texture);
         self.form_image_path = Some(path.to_string());
@@ -93,21 +162,116 @@ impl DrawingCanvas {
         self.pan_offset = egui::Vec2::ZERO;
 
         tracing::info!("Loaded form image: {} ({}x{})", path, img.width(), img.height());
-        Ok(())
+    }
+
+    /// Serialize the project state to JSON
+    pub fn to_json(&self) -> Result<String, CanvasError> {
+        serde_json::to_string_pretty(self).map_err(|e| {
+            CanvasError::new(CanvasErrorKind::Serialization(e.to_string()), line!(), file!())
+        })
+    }
+
+    /// Parse and validate project state from JSON
+    ///
+    /// Never panics on malformed input: syntax errors, files from a newer
+    /// format version, and geometry the canvas cannot work with (non-finite
+    /// coordinates, degenerate shapes, out-of-range references) are all
+    /// reported as typed errors. Comment threads on missing objects are dropped.
+    #[instrument(skip(json), fields(len = json.len()))]
+    pub fn from_json(json: &str) -> Result<Self, CanvasError> {
+        let loaded: DrawingCanvas = serde_json::from_str(json).map_err(|e| {
+            CanvasError::new(CanvasErrorKind::Deserialization(e.to_string()), line!(), file!())
+        })?;
+        Self::validated(loaded)
+    }
+
+    /// Check a deserialized project and bring it up to the current format version
+    pub(super) fn validated(mut loaded: DrawingCanvas) -> Result<Self, CanvasError> {
+        if loaded.format_version > PROJECT_FORMAT_VERSION {
+            return Err(CanvasError::new(
+                CanvasErrorKind::UnsupportedFormatVersion {
+                    found: loaded.format_version,
+                    supported: PROJECT_FORMAT_VERSION,
+                },
+                line!(),
+                file!(),
+            ));
+        }
+        debug!(format_version = loaded.format_version, "Parsed project file");
+
+        let invalid = |msg: String| CanvasError::new(CanvasErrorKind::InvalidProject(msg), line!(), file!());
+
+        for (label, shapes) in [("Shape", &loaded.shapes), ("Detection", &loaded.detections)] {
+            for (idx, shape) in shapes.iter().enumerate() {
+                shape.validate().map_err(|e| invalid(format!("{} {}: {}", label, idx, e.kind)))?;
+            }
+        }
+        if !loaded.zoom_level.is_finite() || loaded.zoom_level <= 0.0 {
+            return Err(invalid(format!("zoom level {}", loaded.zoom_level)));
+        }
+        if let Some((idx, _)) = loaded.pii_flags.iter().find(|(idx, _)| *idx >= loaded.detections.len()) {
+            return Err(invalid(format!("PII flag on missing detection {}", idx)));
+        }
+        if let Some((idx, _)) = loaded.detection_tags.iter().find(|(idx, _)| *idx >= loaded.detections.len()) {
+            return Err(invalid(format!("Subtype tag on missing detection {}", idx)));
+        }
+        if let Some(record) = loaded.ocr_records.iter().find(|record| record.detection >= loaded.detections.len()) {
+            return Err(invalid(format!("OCR text on missing detection {}", record.detection)));
+        }
+        if let Some(guide) = loaded.guides.iter().find(|guide| !guide.position.is_finite()) {
+            return Err(invalid(format!("{} guide at {}", guide.orientation, guide.position)));
+        }
+        let finite = |highlight: &Highlight| {
+            highlight.width.is_finite() && highlight.points.iter().all(|p| p.x.is_finite() && p.y.is_finite())
+        };
+        if let Some(idx) = loaded.highlights.iter().position(|highlight| !finite(highlight)) {
+            return Err(invalid(format!("highlight {} is not finite", idx)));
+        }
+        let placed = |stamp: &crate::Stamp| stamp.bounds.is_finite() && stamp.bounds.is_positive();
+        if let Some(idx) = loaded.stamps.iter().position(|stamp| !placed(stamp)) {
+            return Err(invalid(format!("stamp {} has no finite area", idx)));
+        }
+
+        loaded.prune_comment_threads();
+        loaded.format_version = PROJECT_FORMAT_VERSION;
+        Ok(loaded)
     }
 
     /// Save the project state to a file
+    ///
+    /// Writes a full snapshot, in the encoding chosen in the canvas
+    /// settings, and starts an empty journal beside it, which later
+    /// autosaves append to.
+    ///
+    /// Saving again to the project's own file fails with a save conflict,
+    /// and opens the conflict window, if someone else saved it since.
     #[instrument(skip(self), fields(path, shapes = self.shapes.len(), detections = self.detections.len()))]
-    pub fn save_to_file(&self, path: &str) -> Result<(), CanvasError> {
+    pub fn save_to_file(&mut self, path: &str) -> Result<(), CanvasError> {
         debug!("Saving project: shapes={}, detections={}", self.shapes.len(), self.detections.len());
 
-        let json = serde_json::to_string_pretty(self).map_err(|e| {
-            CanvasError::new(CanvasErrorKind::Serialization(e.to_string()), line!(), file!())
-        })?;
+        if let Some(journal) = &self.journal
+            && journal.project_path() == Path::new(path)
+        {
+            let checked = journal.check_unchanged();
+            self.note_save_conflict(checked)?;
+        }
 
-        std::fs::write(path, json).map_err(|e| {
-            CanvasError::new(CanvasErrorKind::FileWrite(e.to_string()), line!(), file!())
-        })?;
+        let bytes = self.project_bytes().map_err(|e| e.with_path(path, IoOperation::Write))?;
+
+        std::fs::write(path, &bytes)
+            .map_err(|e| CanvasError::io(e, path, IoOperation::Write, line!(), file!()))?;
+
+        // Changes from here on go to the journal, and Save writes to the file rather than the store
+        self.stored_project = None;
+        self.journal = match ProjectJournal::start(Path::new(path), &bytes, self) {
+            Ok(journal) => Some(journal),
+            Err(e) => {
+                // A journal left from earlier edits must not be replayed over this snapshot
+                warn!("Could not start project journal, autosave is off: {}", e);
+                let _ = std::fs::remove_file(journal_path(Path::new(path)));
+                None
+            }
+        };
 
         // Add to recent projects
         let mut recent = RecentProjects::load();
@@ -121,22 +285,97 @@ impl DrawingCanvas {
     }
 
     /// Load the project state from a file
+    ///
+    /// Returns once the annotations are read; the form image is decoded in
+    /// the background and shown when ready. A password-protected file
+    /// fails with [`CanvasErrorKind::PasswordRequired`] and opens the
+    /// unlock window.
+    #[instrument(skip(self, ctx), fields(path))]
     pub fn load_from_file(&mut self, path: &str, ctx: &egui::Context) -> Result<(), CanvasError> {
-        self.load_from_file_impl(path, ctx, false)
+        let result = self.open_project_file(path, None, ctx);
+        self.note_locked(result, || LockedSource::File(PathBuf::from(path)))
     }
 
-    /// Load the project state from a file (internal implementation)
-    /// If defer_image_load is true, the image will be loaded on the next update() call
-    #[instrument(skip(self, ctx), fields(path, defer_image_load))]
-    fn load_from_file_impl(&mut self, path: &str, ctx: &egui::Context, defer_image_load: bool) -> Result<(), CanvasError> {
-        let json = std::fs::read_to_string(path).map_err(|e| {
-            CanvasError::new(CanvasErrorKind::FileRead(e.to_string()), line!(), file!())
-        })?;
+    /// Load the project state from a file protected with `password`
+    ///
+    /// Unprotected files open too, and are protected with `password` from
+    /// the next save on.
+    #[instrument(skip(self, password, ctx), fields(path))]
+    pub fn load_protected_file(&mut self, path: &str, password: &str, ctx: &egui::Context) -> Result<(), CanvasError> {
+        self.open_project_file(path, Some(password), ctx)
+    }
 
-        let loaded: DrawingCanvas = serde_json::from_str(&json).map_err(|e| {
-            CanvasError::new(CanvasErrorKind::Deserialization(e.to_string()), line!(), file!())
-        })?;
+    /// Load the project state from a file, unlocking it with `password` if given
+    fn open_project_file(
+        &mut self,
+        path: &str,
+        password: Option<&str>,
+        ctx: &egui::Context,
+    ) -> Result<(), CanvasError> {
+        let (loaded, journal) = read_project(Path::new(path), password)?;
+        self.apply_project(loaded, ctx, true);
+        self.project_password = password.map(str::to_string);
+        self.journal = Some(journal);
+
+        // Add to recent projects
+        let mut recent = RecentProjects::load();
+        recent.add(PathBuf::from(path));
+        if let Err(e) = recent.save() {
+            tracing::warn!("Failed to save recent projects: {}", e);
+        }
+
+        tracing::info!("Loaded project from: {}", path);
+        Ok(())
+    }
+
+    /// Load project state from the bytes of a project file, e.g. one dropped on the window
+    ///
+    /// `name` is used in errors only; the project is not added to recent
+    /// projects. A password-protected project opens the unlock window.
+    #[instrument(skip(self, bytes, ctx), fields(len = bytes.len()))]
+    pub fn load_project_bytes(&mut self, name: &str, bytes: &[u8], ctx: &egui::Context) -> Result<(), CanvasError> {
+        let result = self.open_project_bytes(name, bytes, None, ctx);
+        self.note_locked(result, || LockedSource::Bytes {
+            name: name.to_string(),
+            bytes: bytes.to_vec(),
+        })
+    }
+
+    /// Load project state from the bytes of a project file protected with `password`
+    #[instrument(skip(self, bytes, password, ctx), fields(len = bytes.len()))]
+    pub fn load_protected_bytes(
+        &mut self,
+        name: &str,
+        bytes: &[u8],
+        password: &str,
+        ctx: &egui::Context,
+    ) -> Result<(), CanvasError> {
+        self.open_project_bytes(name, bytes, Some(password), ctx)
+    }
+
+    /// Load project state from the bytes of a project file, unlocking it with `password` if given
+    fn open_project_bytes(
+        &mut self,
+        name: &str,
+        bytes: &[u8],
+        password: Option<&str>,
+        ctx: &egui::Context,
+    ) -> Result<(), CanvasError> {
+        let loaded = match password {
+            Some(password) => DrawingCanvas::from_protected_bytes(bytes, password),
+            None => DrawingCanvas::from_bytes(bytes),
+        }
+        .map_err(|e| e.with_path(name, IoOperation::Read))?;
+        self.apply_project(loaded, ctx, false);
+        self.project_password = password.map(str::to_string);
 
+        tracing::info!("Loaded project: {}", name);
+        Ok(())
+    }
+
+    /// Replace the project state with a loaded project, keeping runtime state
+    /// If defer_image_load is true, the image is decoded in the background
+    pub(super) fn apply_project(&mut self, loaded: DrawingCanvas, ctx: &egui::Context, defer_image_load: bool) {
         debug!("Deserialized project state: shapes={}, detections={}",
                loaded.shapes.len(), loaded.detections.len());
 
@@ -144,6 +383,8 @@ impl DrawingCanvas {
         self.project_name = loaded.project_name;
         self.shapes = loaded.shapes;
         self.detections = loaded.detections;
+        self.stop_detection_flashes();
+        self.selection_history.clear();
         self.current_tool = loaded.current_tool;
         self.layer_manager = loaded.layer_manager;
         self.stroke = loaded.stroke;
@@ -152,6 +393,29 @@ impl DrawingCanvas {
         self.pan_offset = loaded.pan_offset;
         self.grid_rotation_angle = loaded.grid_rotation_angle;
         self.form_image_rotation = loaded.form_image_rotation;
+        self.pii_flags = loaded.pii_flags;
+        self.detection_tags = loaded.detection_tags;
+        self.comment_threads = loaded.comment_threads;
+        self.workflow = loaded.workflow;
+        self.session_stats = loaded.session_stats;
+        self.guides = loaded.guides;
+        self.bookmarks = loaded.bookmarks;
+        self.tab_order = loaded.tab_order;
+        self.field_groups = loaded.field_groups;
+        self.guide_drag = None;
+        self.highlights = loaded.highlights;
+        self.stamps = loaded.stamps;
+        self.ocr_settings = loaded.ocr_settings;
+        self.ocr_records = loaded.ocr_records;
+        self.ocr_order = loaded.ocr_order;
+        self.template_name = loaded.template_name;
+        self.template_page = loaded.template_page;
+        self.template_page_index = loaded.template_page_index;
+        // Until saved or opened from a file, the project has nowhere to autosave to
+        self.journal = None;
+        self.stored_project = None;
+        // The shapes of another project are not edits to share with the session
+        self.stop_collaboration();
 
         debug!("Loaded project state: shapes={}, detections={}, detections_layer_visible={}",
                self.shapes.len(),
@@ -161,33 +425,24 @@ impl DrawingCanvas {
         // If there was a form image saved, try to reload it
         if let Some(form_path) = &loaded.form_image_path {
             if defer_image_load {
-                // Defer image loading until the first update() call
-                self.pending_image_load = Some(form_path.clone());
-                self.form_image_path = Some(form_path.clone());
-                tracing::debug!("Deferred loading of form image: {}", form_path);
+                self.load_form_image_lazily(form_path);
             } else {
                 // Load image immediately
                 if let Err(e) = self.load_form_image(form_path, ctx) {
                     tracing::warn!("Could not reload form image from {}: {}", form_path, e);
                     // Don't fail the entire load if the image is missing
                     self.form_image_path = loaded.form_image_path;
+                    self.pending_image_load = None;
                 }
             }
         } else {
             self.form_image_path = None;
             self.form_image = None;
             self.form_image_size = None;
+            self.pending_image_load = None;
         }
-
-        // Add to recent projects
-        let mut recent = RecentProjects::load();
-        recent.add(PathBuf::from(path));
-        if let Err(e) = recent.save() {
-            tracing::warn!("Failed to save recent projects: {}", e);
-        }
-
-        tracing::info!("Loaded project from: {}", path);
-        Ok(())
+        // Reloading the form image is not a new source
+        self.provenance = loaded.provenance;
     }
 
     /// Load the most recent project on startup (defers image loading)
@@ -196,13 +451,13 @@ impl DrawingCanvas {
         if let Some(recent_path) = recent.most_recent()
             && let Some(path_str) = recent_path.to_str()
         {
-            return self.load_from_file_impl(path_str, ctx, true);
+            return self.load_from_file(path_str, ctx);
         }
         Err(CanvasError::new(CanvasErrorKind::NoRecentProjects, line!(), file!()))
     }
 
     /// Detect text regions in the loaded form image
-    #[cfg(feature = "text-detection")]
+    #[cfg(all(feature = "text-detection", not(target_arch = "wasm32")))]
     #[instrument(skip(self), fields(confidence_threshold, existing_detections = self.detections.len()))]
     pub fn detect_text_regions(&mut self, confidence_threshold: f32) -> Result<usize, CanvasError> {
         // Check if we have a form image loaded
@@ -211,20 +466,41 @@ impl DrawingCanvas {
 
         tracing::info!("Detecting text regions in: {}", form_path);
 
-        // Create text detector with default model path
-        let detector = TextDetector::new("models/DB_TD500_resnet50.onnx".to_string()).map_err(|e| {
-            CanvasError::new(CanvasErrorKind::TextDetection(e.to_string()), line!(), file!())
+        // Reuse the results of an identical earlier pass
+        let model_path = self.detection_settings.text_model_path.clone();
+        let detector_config = format!("text;model={};confidence={}", model_path, confidence_threshold);
+        let cache = self.detection_cache(&detector_config);
+        if let Some((dir, key)) = &cache
+            && let Some(count) = self.load_cached_detections(dir, key)
+        {
+            self.provenance.text_detector = Some(detector_config);
+            return Ok(count);
+        }
+        let form_path = self.form_image_path.as_ref()
+            .ok_or_else(|| CanvasError::new(CanvasErrorKind::NoFormImageLoaded, line!(), file!()))?;
+
+        // Create text detector with the configured model
+        let detector = TextDetector::new(model_path.clone()).map_err(|e| {
+            CanvasError::new(CanvasErrorKind::Cv(e.into()), line!(), file!())
+                .with_path(model_path, IoOperation::Read)
         })?;
 
         // Detect text regions
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
         let regions = detector.detect_from_file(form_path.as_str(), confidence_threshold).map_err(|e| {
-            CanvasError::new(CanvasErrorKind::TextDetection(e.to_string()), line!(), file!())
+            CanvasError::new(CanvasErrorKind::Cv(e.into()), line!(), file!())
+                .with_path(form_path.as_str(), IoOperation::Read)
         })?;
 
         let count = regions.len();
         tracing::info!("Detected {} text regions", count);
+        #[cfg(feature = "metrics")]
+        record_detection(started, count);
 
         // Create rectangle shapes for each detected region
+        let _insert = tracing::debug_span!("insert_detections", count).entered();
+        let first = self.detections.len();
         for (i, region) in regions.iter().enumerate() {
             let top_left = Pos2::new(*region.x() as f32, *region.y() as f32);
             let bottom_right = Pos2::new(
@@ -233,7 +509,7 @@ impl DrawingCanvas {
             );
 
             // Create a rectangle shape with a distinctive color for text regions
-            let stroke = Stroke::new(2.0, Color32::from_rgb(255, 165, 0)); // Orange
+            let stroke = Stroke::new(2.0, self.detection_settings.colors.text);
             let fill = Color32::TRANSPARENT; // No fill, outline only
 
             match Rectangle::from_corners(top_left, bottom_right, stroke, fill) {
@@ -248,28 +524,88 @@ impl DrawingCanvas {
         }
 
         debug!("Added {} detections, total now: {}", count, self.detections.len());
+        self.highlight_new_detections(first..self.detections.len());
+        if let Some((dir, key)) = &cache
+            && let Err(e) = self.cache_detections(dir, key, &detector_config, first)
+        {
+            warn!("Failed to cache text detections: {}", e);
+        }
+        self.provenance.text_detector = Some(detector_config);
 
         Ok(count)
     }
 
+    /// Detect text regions in the loaded form image
+    ///
+    /// Text detection runs on OpenCV, which isn't built for the browser.
+    #[cfg(all(feature = "text-detection", target_arch = "wasm32"))]
+    pub fn detect_text_regions(&mut self, _confidence_threshold: f32) -> Result<usize, CanvasError> {
+        Err(CanvasError::new(CanvasErrorKind::NotInBrowser("Text detection".to_string()), line!(), file!()))
+    }
+
     /// Extract text from all detections using OCR
     ///
     /// Returns a vector of (detection_index, OCR_result) pairs
-    #[cfg(feature = "ocr")]
+    #[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
     #[instrument(skip(self, ocr), fields(detections = self.detections.len()))]
     pub fn extract_text_from_detections(
         &self,
         ocr: &form_factor_ocr::OCREngine,
+    ) -> Result<Vec<(usize, form_factor_ocr::OCRResult)>, CanvasError> {
+        let indices: Vec<usize> = (0..self.detections.len()).collect();
+        self.extract_text_from_indices(ocr, &indices)
+    }
+
+    /// Extract text only from detections changed since the last OCR pass
+    ///
+    /// See [`DrawingCanvas::changed_ocr_regions`]. Returns a vector of
+    /// (detection_index, OCR_result) pairs, to be recorded with
+    /// [`DrawingCanvas::record_ocr_text`].
+    #[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
+    #[instrument(skip(self, ocr), fields(detections = self.detections.len()))]
+    pub fn extract_text_from_changed_detections(
+        &self,
+        ocr: &form_factor_ocr::OCREngine,
+    ) -> Result<Vec<(usize, form_factor_ocr::OCRResult)>, CanvasError> {
+        self.extract_text_from_indices(ocr, &self.changed_ocr_regions())
+    }
+
+    /// Extract text from the given detections
+    ///
+    /// Detections on number fields, see [`DrawingCanvas::reads_numbers`],
+    /// are read in numeric mode.
+    #[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
+    fn extract_text_from_indices(
+        &self,
+        ocr: &form_factor_ocr::OCREngine,
+        indices: &[usize],
     ) -> Result<Vec<(usize, form_factor_ocr::OCRResult)>, CanvasError> {
         let form_path = self.form_image_path.as_ref()
             .ok_or_else(|| CanvasError::new(CanvasErrorKind::NoFormImageLoaded, line!(), file!()))?;
 
-        tracing::info!("Extracting text from {} detections", self.detections.len());
+        tracing::info!("Extracting text from {} of {} detections", indices.len(), self.detections.len());
+
+        // Amount fields are read by a numeric engine with the same language and threshold
+        let numeric = if indices.iter().any(|&idx| self.reads_numbers(idx)) {
+            let config = ocr.config().clone().with_numeric_mode();
+            Some(form_factor_ocr::OCREngine::new(config).map_err(|e| {
+                CanvasError::new(CanvasErrorKind::Ocr(e), line!(), file!())
+            })?)
+        } else {
+            None
+        };
 
         let mut results = Vec::new();
 
-        for (idx, detection) in self.detections.iter().enumerate() {
-            match self.extract_text_from_shape(ocr, form_path, detection) {
+        for &idx in indices {
+            let Some(detection) = self.detections.get(idx) else {
+                continue;
+            };
+            let engine = match &numeric {
+                Some(numeric) if self.reads_numbers(idx) => numeric,
+                _ => ocr,
+            };
+            match self.extract_text_from_shape(engine, form_path, detection) {
                 Ok(result) => {
                     debug!(
                         "Detection {}: extracted {} chars with {:.1}% confidence",
@@ -277,6 +613,9 @@ impl DrawingCanvas {
                         result.text().len(),
                         result.confidence()
                     );
+                    #[cfg(feature = "metrics")]
+                    form_factor_core::MetricsRegistry::global()
+                        .observe(form_factor_core::OCR_CONFIDENCE, *result.confidence() as f64);
                     results.push((idx, result));
                 }
                 Err(e) => {
@@ -285,66 +624,26 @@ impl DrawingCanvas {
             }
         }
 
-        tracing::info!("Extracted text from {}/{} detections", results.len(), self.detections.len());
+        tracing::info!("Extracted text from {}/{} detections", results.len(), indices.len());
         Ok(results)
     }
 
     /// Extract text from a specific shape using OCR
-    #[cfg(feature = "ocr")]
+    #[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
     fn extract_text_from_shape(
         &self,
         ocr: &form_factor_ocr::OCREngine,
         image_path: &str,
         shape: &Shape,
     ) -> Result<form_factor_ocr::OCRResult, CanvasError> {
-        use crate::Shape;
-
-        // Get bounding box of the shape in image pixel coordinates
-        let bbox = match shape {
-            Shape::Rectangle(rect) => {
-                // Find min/max coords
-                let xs: Vec<f32> = rect.corners().iter().map(|p| p.x).collect();
-                let ys: Vec<f32> = rect.corners().iter().map(|p| p.y).collect();
-
-                let x_min = xs.iter().fold(f32::INFINITY, |a, &b| a.min(b)) as u32;
-                let y_min = ys.iter().fold(f32::INFINITY, |a, &b| a.min(b)) as u32;
-                let x_max = xs.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b)) as u32;
-                let y_max = ys.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b)) as u32;
-
-                let width = x_max.saturating_sub(x_min);
-                let height = y_max.saturating_sub(y_min);
-
-                (x_min, y_min, width, height)
-            }
-            Shape::Circle(circle) => {
-                let x = (circle.center.x - circle.radius).max(0.0) as u32;
-                let y = (circle.center.y - circle.radius).max(0.0) as u32;
-                let diameter = (circle.radius * 2.0) as u32;
-                (x, y, diameter, diameter)
-            }
-            Shape::Polygon(poly) => {
-                // Get bounding box from polygon points
-                let points = poly.to_egui_points();
-                let xs: Vec<f32> = points.iter().map(|p| p.x).collect();
-                let ys: Vec<f32> = points.iter().map(|p| p.y).collect();
-
-                let x_min = xs.iter().fold(f32::INFINITY, |a, &b| a.min(b)) as u32;
-                let y_min = ys.iter().fold(f32::INFINITY, |a, &b| a.min(b)) as u32;
-                let x_max = xs.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b)) as u32;
-                let y_max = ys.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b)) as u32;
-
-                let width = x_max.saturating_sub(x_min);
-                let height = y_max.saturating_sub(y_min);
-
-                (x_min, y_min, width, height)
-            }
-        };
+        // Detections are already in image pixels; round outward and clamp to the image
+        let bbox = self.coordinate_mapper.pixel_crop(shape.bounding_rect());
 
         trace!("Shape bbox in image coords: {:?}", bbox);
 
         // Extract text from this region
         ocr.extract_text_from_region_file(image_path, bbox).map_err(|e| {
-            CanvasError::new(CanvasErrorKind::OCRFailed(e.to_string()), line!(), file!())
+            CanvasError::new(CanvasErrorKind::Ocr(e), line!(), file!())
         })
     }
 
@@ -363,7 +662,7 @@ impl DrawingCanvas {
     /// - No form image is loaded
     /// - Logo templates cannot be loaded
     /// - Logo detection fails
-    #[cfg(feature = "logo-detection")]
+    #[cfg(all(feature = "logo-detection", not(target_arch = "wasm32")))]
     #[instrument(skip(self), fields(existing_detections = self.detections.len()))]
     pub fn detect_logos(&mut self) -> Result<usize, CanvasError> {
         // Check if we have a form image loaded
@@ -372,40 +671,30 @@ impl DrawingCanvas {
 
         tracing::info!("Detecting logos in: {}", form_path);
 
-        // Create logo detector with template matching and multiple scales
-        // Wide scale range to handle logos from 10% to 200% of template size
-        // TODO: Make these configurable via UI or settings
+        // Create logo detector with template matching at the configured scales
+        let settings = &self.detection_settings;
         let mut detector = LogoDetector::builder()
             .template_matching()
-            .with_confidence_threshold(0.5)  // Balanced threshold for good recall
-            .with_scales(vec![0.1, 0.15, 0.2, 0.3, 0.4, 0.5, 0.65, 0.75, 1.0, 1.25, 1.5, 2.0])
+            .with_confidence_threshold(settings.logo_confidence)
+            .with_scales(settings.logo_scales.clone())
             .build();
 
         // Load all logo templates from the logos directory
-        let logos_dir = std::path::Path::new("logos");
+        let logos_dir = std::path::Path::new(&settings.logos_dir);
         if !logos_dir.exists() {
             return Err(CanvasError::new(
-                CanvasErrorKind::LogoDetection("logos directory does not exist".to_string()),
+                CanvasErrorKind::NoLogoTemplates(logos_dir.display().to_string()),
                 line!(),
                 file!(),
             ));
         }
 
         let mut logo_count = 0;
-        for entry in std::fs::read_dir(logos_dir).map_err(|e| {
-            CanvasError::new(
-                CanvasErrorKind::LogoDetection(format!("Failed to read logos directory: {}", e)),
-                line!(),
-                file!(),
-            )
-        })? {
-            let entry = entry.map_err(|e| {
-                CanvasError::new(
-                    CanvasErrorKind::LogoDetection(format!("Failed to read directory entry: {}", e)),
-                    line!(),
-                    file!(),
-                )
-            })?;
+        // Templates are part of the configuration: changing one invalidates cached results
+        let mut template_hashes = Vec::new();
+        let dir_error = |e| CanvasError::io(e, logos_dir.display().to_string(), IoOperation::Open, line!(), file!());
+        for entry in std::fs::read_dir(logos_dir).map_err(dir_error)? {
+            let entry = entry.map_err(dir_error)?;
 
             let path = entry.path();
             if path.is_file() {
@@ -423,6 +712,8 @@ impl DrawingCanvas {
                             warn!("Failed to load logo {}: {}", logo_name, e);
                         } else {
                             logo_count += 1;
+                            let bytes = std::fs::read(&path).unwrap_or_default();
+                            template_hashes.push(format!("{}:{:016x}", logo_name, content_hash(&bytes)));
                         }
                     }
                 }
@@ -431,7 +722,7 @@ impl DrawingCanvas {
 
         if logo_count == 0 {
             return Err(CanvasError::new(
-                CanvasErrorKind::LogoDetection("No logo templates found in logos directory".to_string()),
+                CanvasErrorKind::NoLogoTemplates(logos_dir.display().to_string()),
                 line!(),
                 file!(),
             ));
@@ -439,15 +730,39 @@ impl DrawingCanvas {
 
         tracing::info!("Loaded {} logo templates", logo_count);
 
+        // Reuse the results of an identical earlier pass
+        template_hashes.sort();
+        let detector_config = format!(
+            "logo;confidence={};scales={:?};templates={}",
+            settings.logo_confidence,
+            settings.logo_scales,
+            template_hashes.join(",")
+        );
+        let cache = self.detection_cache(&detector_config);
+        if let Some((dir, key)) = &cache
+            && let Some(count) = self.load_cached_detections(dir, key)
+        {
+            self.provenance.logo_detector = Some(detector_config);
+            return Ok(count);
+        }
+        let form_path = self.form_image_path.as_ref()
+            .ok_or_else(|| CanvasError::new(CanvasErrorKind::NoFormImageLoaded, line!(), file!()))?;
+
         // Detect logos in the form image
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
         let results = detector.detect_logos_from_path(form_path.as_str()).map_err(|e| {
-            CanvasError::new(CanvasErrorKind::LogoDetection(e), line!(), file!())
+            CanvasError::new(CanvasErrorKind::Cv(e.into()), line!(), file!())
         })?;
 
         let detection_count = results.len();
         tracing::info!("Detected {} logo instances", detection_count);
+        #[cfg(feature = "metrics")]
+        record_detection(started, detection_count);
 
         // Create rectangle shapes for each detected logo
+        let _insert = tracing::debug_span!("insert_detections", count = detection_count).entered();
+        let first = self.detections.len();
         for (i, result) in results.iter().enumerate() {
             let top_left = Pos2::new(result.location.x as f32, result.location.y as f32);
             let bottom_right = Pos2::new(
@@ -456,7 +771,7 @@ impl DrawingCanvas {
             );
 
             // Create a rectangle shape with a distinctive color for logo detections
-            let stroke = Stroke::new(3.0, Color32::from_rgb(0, 255, 0)); // Green
+            let stroke = Stroke::new(3.0, self.detection_settings.colors.logo);
             let fill = Color32::TRANSPARENT; // No fill, outline only
 
             match Rectangle::from_corners(top_left, bottom_right, stroke, fill) {
@@ -476,7 +791,30 @@ impl DrawingCanvas {
         }
 
         debug!("Added {} logo detections, total detections now: {}", detection_count, self.detections.len());
+        self.highlight_new_detections(first..self.detections.len());
+        if let Some((dir, key)) = &cache
+            && let Err(e) = self.cache_detections(dir, key, &detector_config, first)
+        {
+            warn!("Failed to cache logo detections: {}", e);
+        }
+        self.provenance.logo_detector = Some(detector_config);
 
         Ok(detection_count)
     }
+
+    /// Detect logos in the loaded form image
+    ///
+    /// Logo detection runs on OpenCV, which isn't built for the browser.
+    #[cfg(all(feature = "logo-detection", target_arch = "wasm32"))]
+    pub fn detect_logos(&mut self) -> Result<usize, CanvasError> {
+        Err(CanvasError::new(CanvasErrorKind::NotInBrowser("Logo detection".to_string()), line!(), file!()))
+    }
+}
+
+/// Record the latency and yield of one detection pass
+#[cfg(all(feature = "metrics", any(feature = "text-detection", feature = "logo-detection"), not(target_arch = "wasm32")))]
+fn record_detection(started: std::time::Instant, count: usize) {
+    let registry = form_factor_core::MetricsRegistry::global();
+    registry.observe(form_factor_core::DETECTION_LATENCY_SECONDS, started.elapsed().as_secs_f64());
+    registry.increment(form_factor_core::DETECTIONS_TOTAL, count as u64);
 }
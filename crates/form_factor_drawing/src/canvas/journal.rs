@@ -0,0 +1,483 @@
+//! Incremental project saves as append-only deltas
+//!
+//! Rewriting a project with thousands of shapes on every autosave writes
+//! megabytes that are almost all unchanged. Once a project has a file, the
+//! canvas keeps a journal beside it (`form.ffp.journal`) and an autosave
+//! appends one delta record holding only what changed since the last save:
+//! the shapes and detections at changed indices, and any other project
+//! field whole. Opening the project replays the journal over the snapshot
+//! in the project file. Zooming, panning and the effort statistics are
+//! written along with the next edit, not on their own.
+//!
+//! When the journal grows past half the snapshot's size, or past
+//! [`MAX_JOURNAL_RECORDS`] records, the next save compacts it: the full
+//! project is written as a new snapshot and the journal starts over. The
+//! journal's first line holds a hash of the snapshot it applies to, so a
+//! journal left behind by a compaction interrupted between the two steps
+//! is ignored rather than replayed onto the wrong snapshot. A record cut
+//! short by a crash mid-write is dropped along with anything after it.
+//!
+//! The journal remembers a hash of the snapshot and journal as it last
+//! read or wrote them. Before writing it checks the file still has that
+//! revision, so an operator saving a project another operator changed on a
+//! shared drive gets a save conflict instead of silently overwriting them.
+
+use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas};
+use super::detection_cache::content_hash;
+use form_factor_core::IoOperation;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, instrument, warn};
+
+/// Seconds between autosaves of a project that has a file
+pub const AUTOSAVE_INTERVAL_SECS: f64 = 30.0;
+
+/// Journal records after which the next save compacts the journal
+pub const MAX_JOURNAL_RECORDS: usize = 500;
+
+/// Path of the journal kept beside a project file
+pub fn journal_path(project_path: &Path) -> PathBuf {
+    let mut name = project_path.as_os_str().to_owned();
+    name.push(".journal");
+    PathBuf::from(name)
+}
+
+/// What an incremental save wrote
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalSave {
+    /// Nothing changed since the last save
+    Unchanged,
+    /// A delta record was appended to the journal
+    Delta {
+        /// Size of the record in bytes
+        bytes: usize,
+    },
+    /// The journal was compacted into a new snapshot
+    Snapshot {
+        /// Size of the snapshot in bytes
+        bytes: usize,
+    },
+}
+
+/// First line of a journal, naming the snapshot it applies to
+#[derive(Debug, Serialize, Deserialize)]
+struct JournalHeader {
+    /// Hash of the snapshot file's contents
+    base: String,
+}
+
+/// Change to one top-level project field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum FieldDelta {
+    /// The field's new value
+    Replace(Value),
+    /// Changed elements of a list field, e.g. the shapes
+    Elements {
+        /// New length of the list
+        len: usize,
+        /// New elements by index
+        set: Vec<(usize, Value)>,
+    },
+}
+
+/// Changes to a project since the previous record
+type ProjectDelta = BTreeMap<String, FieldDelta>;
+
+/// Changes that turn `base` into `project`, field by field
+fn delta(base: &Map<String, Value>, project: &Map<String, Value>) -> ProjectDelta {
+    let mut delta = ProjectDelta::new();
+    for (key, value) in project {
+        match (base.get(key), value) {
+            (Some(old), new) if old == new => {}
+            (Some(Value::Array(old)), Value::Array(new)) => {
+                let set = new
+                    .iter()
+                    .enumerate()
+                    .filter(|(idx, element)| old.get(*idx) != Some(*element))
+                    .map(|(idx, element)| (idx, element.clone()))
+                    .collect();
+                delta.insert(key.clone(), FieldDelta::Elements { len: new.len(), set });
+            }
+            _ => {
+                delta.insert(key.clone(), FieldDelta::Replace(value.clone()));
+            }
+        }
+    }
+    delta
+}
+
+/// Project fields that change while merely viewing a project: the view and the effort statistics
+const VIEW_FIELDS: [&str; 3] = ["zoom_level", "pan_offset", "session_stats"];
+
+/// Whether `delta` changes anything the operator edited, rather than only the view
+fn has_edits(delta: &ProjectDelta) -> bool {
+    delta.keys().any(|key| !VIEW_FIELDS.contains(&key.as_str()))
+}
+
+/// Apply a delta record to a project
+fn apply(project: &mut Map<String, Value>, delta: ProjectDelta) {
+    for (key, change) in delta {
+        match change {
+            FieldDelta::Replace(value) => {
+                project.insert(key, value);
+            }
+            FieldDelta::Elements { len, set } => {
+                let entry = project.entry(key).or_insert_with(|| Value::Array(Vec::new()));
+                if !entry.is_array() {
+                    *entry = Value::Array(Vec::new());
+                }
+                if let Value::Array(elements) = entry {
+                    elements.resize(len, Value::Null);
+                    for (idx, value) in set {
+                        if let Some(element) = elements.get_mut(idx) {
+                            *element = value;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Project as the JSON object deltas are computed against
+pub(super) fn project_map(canvas: &DrawingCanvas) -> Result<Map<String, Value>, CanvasError> {
+    match serde_json::to_value(canvas) {
+        Ok(Value::Object(project)) => Ok(project),
+        Ok(_) => Err(CanvasError::new(
+            CanvasErrorKind::Serialization("project is not a JSON object".to_string()),
+            line!(),
+            file!(),
+        )),
+        Err(e) => Err(CanvasError::new(CanvasErrorKind::Serialization(e.to_string()), line!(), file!())),
+    }
+}
+
+/// Read a project file in either encoding, replaying its journal, if any
+///
+/// Returns the project and a journal that continues it. `password`
+/// unlocks a password-protected file.
+pub(super) fn read_project(
+    path: &Path,
+    password: Option<&str>,
+) -> Result<(DrawingCanvas, ProjectJournal), CanvasError> {
+    let path_str = path.to_string_lossy().into_owned();
+    let snapshot =
+        std::fs::read(path).map_err(|e| CanvasError::io(e, path_str.as_str(), IoOperation::Read, line!(), file!()))?;
+    let read = || -> Result<(DrawingCanvas, usize), CanvasError> {
+        let loaded = match password {
+            Some(password) => DrawingCanvas::from_protected_bytes(&snapshot, password)?,
+            None => DrawingCanvas::from_bytes(&snapshot)?,
+        };
+        let deltas = journal_records(path, &snapshot);
+        if deltas.is_empty() {
+            return Ok((loaded, 0));
+        }
+        let mut project = project_map(&loaded)?;
+        let records = deltas.len();
+        deltas.into_iter().for_each(|delta| apply(&mut project, delta));
+        Ok((DrawingCanvas::from_json(&Value::Object(project).to_string())?, records))
+    };
+    let (loaded, records) = read().map_err(|e| e.with_path(path_str.as_str(), IoOperation::Read))?;
+
+    let saved = project_map(&loaded)?;
+    Ok((loaded, ProjectJournal::resume(path, &snapshot, saved, records)))
+}
+
+/// Delta records in the journal beside a snapshot
+///
+/// A missing journal, or one written for a different snapshot, has none.
+fn journal_records(project_path: &Path, snapshot: &[u8]) -> Vec<ProjectDelta> {
+    let path = journal_path(project_path);
+    let Ok(journal) = std::fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    let mut lines = journal.lines();
+    let base = format!("{:016x}", content_hash(snapshot));
+    match lines.next().map(serde_json::from_str::<JournalHeader>) {
+        Some(Ok(header)) if header.base == base => {}
+        _ => {
+            warn!(path = %path.display(), "Ignoring journal written for another snapshot");
+            return Vec::new();
+        }
+    }
+
+    let mut records = Vec::new();
+    for line in lines {
+        match serde_json::from_str::<ProjectDelta>(line) {
+            Ok(delta) => records.push(delta),
+            Err(e) => {
+                // Only a crash mid-append leaves a bad record, and it is always the last
+                warn!(path = %path.display(), replayed = records.len(), "Dropping unreadable journal record: {}", e);
+                break;
+            }
+        }
+    }
+    info!(path = %path.display(), records = records.len(), "Replaying project journal");
+    records
+}
+
+/// Revision of a project file as it is on disk: a hash of the snapshot and its journal
+///
+/// `None` if the project file is missing.
+pub(super) fn file_revision(project_path: &Path) -> Option<u64> {
+    let mut contents = std::fs::read(project_path).ok()?;
+    contents.extend(std::fs::read(journal_path(project_path)).unwrap_or_default());
+    Some(content_hash(&contents))
+}
+
+/// Journal of unsaved changes kept beside a project file
+#[derive(Debug, Clone)]
+pub(super) struct ProjectJournal {
+    /// Project file holding the snapshot
+    project_path: PathBuf,
+    /// Project as saved: the snapshot with the journal applied
+    saved: Map<String, Value>,
+    /// Size of the snapshot in bytes
+    snapshot_bytes: usize,
+    /// Size of the journal in bytes
+    journal_bytes: usize,
+    /// Delta records in the journal
+    records: usize,
+    /// Revision of the project file when this journal last read or wrote it
+    revision: u64,
+    /// Time of the last autosave, in seconds on the egui clock
+    pub(super) last_autosave: f64,
+}
+
+impl ProjectJournal {
+    /// Journal continuing a project read from `project_path`
+    ///
+    /// `saved` is the project with any existing journal already replayed.
+    fn resume(project_path: &Path, snapshot: &[u8], saved: Map<String, Value>, records: usize) -> Self {
+        let journal_bytes = std::fs::metadata(journal_path(project_path)).map_or(0, |meta| meta.len() as usize);
+        Self {
+            project_path: project_path.to_path_buf(),
+            saved,
+            snapshot_bytes: snapshot.len(),
+            journal_bytes,
+            records,
+            revision: file_revision(project_path).unwrap_or_default(),
+            last_autosave: 0.0,
+        }
+    }
+
+    /// Project file the journal belongs to
+    pub(super) fn project_path(&self) -> &Path {
+        &self.project_path
+    }
+
+    /// Start an empty journal for `canvas`, just written to `project_path` as `snapshot`
+    pub(super) fn start(project_path: &Path, snapshot: &[u8], canvas: &DrawingCanvas) -> Result<Self, CanvasError> {
+        let header = serde_json::to_string(&JournalHeader {
+            base: format!("{:016x}", content_hash(snapshot)),
+        })
+        .map_err(|e| CanvasError::new(CanvasErrorKind::Serialization(e.to_string()), line!(), file!()))?;
+
+        let path = journal_path(project_path);
+        let header = format!("{}\n", header);
+        std::fs::write(&path, &header)
+            .map_err(|e| CanvasError::io(e, path.display().to_string(), IoOperation::Write, line!(), file!()))?;
+        Ok(Self {
+            project_path: project_path.to_path_buf(),
+            saved: project_map(canvas)?,
+            snapshot_bytes: snapshot.len(),
+            journal_bytes: header.len(),
+            records: 0,
+            revision: file_revision(project_path).unwrap_or_default(),
+            last_autosave: 0.0,
+        })
+    }
+
+    /// Project as last saved: the snapshot with the journal applied
+    pub(super) fn saved(&self) -> &Map<String, Value> {
+        &self.saved
+    }
+
+    /// Fail with a save conflict if the project file changed since this journal last read or wrote it
+    ///
+    /// Another operator saving the same file on a network share changes
+    /// its snapshot or journal; writing over it would lose their edits.
+    pub(super) fn check_unchanged(&self) -> Result<(), CanvasError> {
+        if file_revision(&self.project_path) == Some(self.revision) {
+            return Ok(());
+        }
+        warn!(path = %self.project_path.display(), "Project file changed since it was last read");
+        Err(CanvasError::new(
+            CanvasErrorKind::SaveConflict(self.project_path.display().to_string()),
+            line!(),
+            file!(),
+        ))
+    }
+
+    /// Whether the next save should write a snapshot instead of a delta
+    fn needs_compaction(&self) -> bool {
+        self.records >= MAX_JOURNAL_RECORDS || self.journal_bytes > self.snapshot_bytes / 2
+    }
+
+    /// Save a project's changes as a delta, or as a snapshot when the journal is due for compaction
+    ///
+    /// Password-protected projects are always saved as encrypted snapshots.
+    #[instrument(skip(self, canvas), fields(path = %self.project_path.display(), records = self.records))]
+    pub(super) fn save(&mut self, canvas: &DrawingCanvas) -> Result<JournalSave, CanvasError> {
+        let project = project_map(canvas)?;
+        let delta = delta(&self.saved, &project);
+        // The view and statistics are saved along with the next edit
+        if !has_edits(&delta) {
+            return Ok(JournalSave::Unchanged);
+        }
+        self.check_unchanged()?;
+        // Records of a protected project would hold its changes in plain text
+        if self.needs_compaction() || canvas.project_password.is_some() {
+            return self.write_snapshot(canvas);
+        }
+
+        let record = serde_json::to_string(&delta)
+            .map_err(|e| CanvasError::new(CanvasErrorKind::Serialization(e.to_string()), line!(), file!()))?;
+        let record = format!("{}\n", record);
+        let path = journal_path(&self.project_path);
+        let write_error =
+            |e| CanvasError::io(e, path.display().to_string(), IoOperation::Write, line!(), file!());
+        let mut journal = std::fs::OpenOptions::new().append(true).open(&path).map_err(write_error)?;
+        journal.write_all(record.as_bytes()).map_err(write_error)?;
+
+        self.saved = project;
+        self.journal_bytes += record.len();
+        self.records += 1;
+        self.revision = file_revision(&self.project_path).unwrap_or_default();
+        debug!(bytes = record.len(), fields = delta.len(), "Appended journal record");
+        Ok(JournalSave::Delta { bytes: record.len() })
+    }
+
+    /// Write the whole project as a new snapshot and start an empty journal
+    ///
+    /// Fails with a save conflict if the file changed since it was read.
+    #[instrument(skip(self, canvas), fields(path = %self.project_path.display(), records = self.records))]
+    pub(super) fn compact(&mut self, canvas: &DrawingCanvas) -> Result<JournalSave, CanvasError> {
+        self.check_unchanged()?;
+        self.write_snapshot(canvas)
+    }
+
+    /// Write the whole project as a new snapshot, over any changes made to the file since it was read
+    pub(super) fn write_snapshot(&mut self, canvas: &DrawingCanvas) -> Result<JournalSave, CanvasError> {
+        let snapshot = canvas.project_bytes()?;
+        let path_str = self.project_path.display().to_string();
+
+        // Replace the snapshot in one step, so a crash leaves the old or the new one whole
+        let temp = self.project_path.with_extension("ffp.tmp");
+        std::fs::write(&temp, &snapshot)
+            .map_err(|e| CanvasError::io(e, temp.display().to_string(), IoOperation::Write, line!(), file!()))?;
+        std::fs::rename(&temp, &self.project_path)
+            .map_err(|e| CanvasError::io(e, path_str.as_str(), IoOperation::Write, line!(), file!()))?;
+
+        let last_autosave = self.last_autosave;
+        *self = Self::start(&self.project_path, &snapshot, canvas)?;
+        self.last_autosave = last_autosave;
+        info!(bytes = snapshot.len(), "Compacted project journal into a snapshot");
+        Ok(JournalSave::Snapshot { bytes: snapshot.len() })
+    }
+}
+
+impl DrawingCanvas {
+    /// Save changes since the last save to the project's journal
+    ///
+    /// Appends a delta record, or writes a full snapshot when the journal
+    /// is due for compaction. See the module docs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project has not been saved to or opened from
+    /// a file yet, or the journal can't be written. If someone else saved
+    /// the file since, the error is a save conflict and the conflict window
+    /// opens.
+    pub fn save_incremental(&mut self) -> Result<JournalSave, CanvasError> {
+        let mut journal = self
+            .journal
+            .take()
+            .ok_or_else(|| CanvasError::new(CanvasErrorKind::NoProjectFile, line!(), file!()))?;
+        let result = journal.save(self);
+        self.journal = Some(journal);
+        self.note_save_conflict(result)
+    }
+
+    /// Compact the project's journal into a new snapshot now
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project has no file or it can't be written,
+    /// or a save conflict as for [`DrawingCanvas::save_incremental`].
+    pub fn compact_journal(&mut self) -> Result<JournalSave, CanvasError> {
+        let mut journal = self
+            .journal
+            .take()
+            .ok_or_else(|| CanvasError::new(CanvasErrorKind::NoProjectFile, line!(), file!()))?;
+        let result = journal.compact(self);
+        self.journal = Some(journal);
+        self.note_save_conflict(result)
+    }
+
+    /// Write the project to `path` as a new snapshot, unless someone else saved it since it was read
+    ///
+    /// A project read from `path` compacts its journal there, after the same
+    /// check as [`DrawingCanvas::save_incremental`]; any other project is
+    /// written whole and starts a journal. Unlike
+    /// [`DrawingCanvas::save_to_file`], recent projects are left alone, so
+    /// batch review can save many instances this way.
+    pub(super) fn save_snapshot(&mut self, path: &Path) -> Result<JournalSave, CanvasError> {
+        let mut journal = match self.journal.take() {
+            Some(journal) if journal.project_path() == path => journal,
+            _ => {
+                let path_str = path.display().to_string();
+                let bytes = self.project_bytes().map_err(|e| e.with_path(path_str.as_str(), IoOperation::Write))?;
+                std::fs::write(path, &bytes)
+                    .map_err(|e| CanvasError::io(e, path_str.as_str(), IoOperation::Write, line!(), file!()))?;
+                self.journal = Some(ProjectJournal::start(path, &bytes, self)?);
+                return Ok(JournalSave::Snapshot { bytes: bytes.len() });
+            }
+        };
+        let result = journal.compact(self);
+        self.journal = Some(journal);
+        result
+    }
+
+    /// Save incrementally if [`AUTOSAVE_INTERVAL_SECS`] have passed since the last autosave
+    ///
+    /// `now` is the current time in seconds, e.g. from `egui::InputState::time`.
+    /// Returns `None` when no save was due, the project has no file yet, or
+    /// a save conflict is waiting to be resolved.
+    pub fn autosave(&mut self, now: f64) -> Option<Result<JournalSave, CanvasError>> {
+        if self.save_conflict.is_some() {
+            return None;
+        }
+        let journal = self.journal.as_mut()?;
+        if now - journal.last_autosave < AUTOSAVE_INTERVAL_SECS {
+            return None;
+        }
+        journal.last_autosave = now;
+        Some(self.save_incremental())
+    }
+
+    /// Whether the project changed since it was last saved or opened
+    ///
+    /// A project without a file counts as changed once it has a form
+    /// image, fields or detections, unless it is in the project store.
+    /// Zooming, panning and the effort statistics ticking over don't
+    /// count as changes.
+    pub fn has_unsaved_changes(&self) -> bool {
+        let saved = match (&self.journal, &self.stored_project) {
+            (Some(journal), _) => &journal.saved,
+            (None, Some(stored)) => &stored.saved,
+            (None, None) => {
+                return self.form_image_path.is_some() || !self.shapes.is_empty() || !self.detections.is_empty();
+            }
+        };
+        project_map(self).map_or(true, |project| has_edits(&delta(saved, &project)))
+    }
+
+    /// Project file the canvas saves incrementally to, if any
+    pub fn project_file(&self) -> Option<&Path> {
+        self.journal.as_ref().map(ProjectJournal::project_path)
+    }
+}
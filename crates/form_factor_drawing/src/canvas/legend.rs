@@ -0,0 +1,122 @@
+//! Legend overlay explaining annotation colors
+//!
+//! The legend sits in the lower-left corner of the canvas and lists the
+//! shapes layer and each kind of detection with its color and the number of
+//! objects of that kind, so a new reviewer can tell what the boxes mean.
+//! Detections tagged with a subtype are counted under the subtype instead,
+//! which is listed only while in use. Detection colors come from the
+//! detection color settings and the taxonomy.
+
+use super::core::DrawingCanvas;
+use crate::{DetectionKind, LayerType};
+use egui::{Color32, Rect};
+use strum::IntoEnumIterator;
+
+/// Gap between the legend and the canvas edge, and around its rows
+const LEGEND_PADDING: f32 = 8.0;
+
+/// Height of one legend row
+const LEGEND_ROW_HEIGHT: f32 = 18.0;
+
+/// Side of a color swatch
+const LEGEND_SWATCH_SIZE: f32 = 12.0;
+
+/// One row of the legend
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegendEntry {
+    /// What the color marks, e.g. `Shapes` or `Logo`
+    pub label: String,
+    /// Outline color of the objects
+    pub color: Color32,
+    /// Number of objects of this kind
+    pub count: usize,
+    /// Whether the layer holding the objects is shown
+    pub visible: bool,
+}
+
+impl DrawingCanvas {
+    /// Show the legend if hidden, hide it if shown
+    pub fn toggle_legend(&mut self) {
+        self.show_legend = !self.show_legend;
+    }
+
+    /// Rows of the legend: the shapes layer, each kind of detection, then subtypes in use
+    pub fn legend_entries(&self) -> Vec<LegendEntry> {
+        let mut entries = vec![LegendEntry {
+            label: LayerType::Shapes.to_string(),
+            color: self.stroke.color,
+            count: self.shapes.len(),
+            visible: self.layer_manager.is_visible(LayerType::Shapes),
+        }];
+
+        let detections_visible = self.layer_manager.is_visible(LayerType::Detections);
+        let colors = &self.detection_settings.colors;
+        entries.extend(DetectionKind::iter().map(|kind| {
+            LegendEntry {
+                label: kind.to_string(),
+                color: colors.color(kind),
+                count: (0..self.detections.len())
+                    .filter(|&idx| self.detection_subtype(idx).is_none() && self.detection_kind(idx) == Some(kind))
+                    .count(),
+                visible: detections_visible,
+            }
+        }));
+        entries.extend(
+            self.detection_settings
+                .taxonomy
+                .subtypes
+                .iter()
+                .map(|subtype| LegendEntry {
+                    label: subtype.name.clone(),
+                    color: subtype.color,
+                    count: self.subtype_count(&subtype.name),
+                    visible: detections_visible,
+                })
+                .filter(|entry| entry.count > 0),
+        );
+        entries
+    }
+
+    /// Paint the legend in the lower-left corner of the canvas, if shown
+    pub(super) fn draw_legend(&self, painter: &egui::Painter, canvas_rect: Rect) {
+        if !self.show_legend {
+            return;
+        }
+
+        let entries = self.legend_entries();
+        let font = egui::FontId::proportional(12.0);
+        let text_color = Color32::from_gray(30);
+        let galleys: Vec<_> = entries
+            .iter()
+            .map(|entry| {
+                let text = format!("{} ({})", entry.label, entry.count);
+                painter.layout_no_wrap(text, font.clone(), text_color)
+            })
+            .collect();
+        let text_width = galleys.iter().map(|galley| galley.size().x).fold(0.0, f32::max);
+
+        let size = egui::vec2(
+            LEGEND_SWATCH_SIZE + text_width + LEGEND_PADDING * 3.0,
+            LEGEND_ROW_HEIGHT * entries.len() as f32 + LEGEND_PADDING * 2.0,
+        );
+        let min = canvas_rect.left_bottom() + egui::vec2(LEGEND_PADDING, -LEGEND_PADDING - size.y);
+        let frame = Rect::from_min_size(min, size);
+        painter.rect_filled(frame, 4.0, Color32::from_white_alpha(230));
+        painter.rect_stroke(frame, 4.0, (1.0, Color32::from_gray(160)), egui::StrokeKind::Inside);
+
+        for (row, (entry, galley)) in entries.iter().zip(galleys).enumerate() {
+            let center_y = frame.top() + LEGEND_PADDING + LEGEND_ROW_HEIGHT * (row as f32 + 0.5);
+            // Objects on hidden layers are listed faded
+            let alpha = if entry.visible { 1.0 } else { 0.35 };
+
+            let swatch = Rect::from_center_size(
+                egui::pos2(frame.left() + LEGEND_PADDING + LEGEND_SWATCH_SIZE / 2.0, center_y),
+                egui::Vec2::splat(LEGEND_SWATCH_SIZE),
+            );
+            painter.rect_filled(swatch, 2.0, entry.color.gamma_multiply(alpha));
+
+            let text_pos = egui::pos2(swatch.right() + LEGEND_PADDING, center_y - galley.size().y / 2.0);
+            painter.galley(text_pos, galley, text_color.gamma_multiply(alpha));
+        }
+    }
+}
@@ -5,11 +5,168 @@
 //! - `io`: File I/O, serialization, and image loading
 //! - `tools`: Tool interaction and state management
 //! - `rendering`: UI rendering and painting logic
+//! - `batch_review`: Review queue of low-confidence fields across many filled forms
+//! - `bookmarks`: Named viewport bookmarks saved with the project
+//! - `collab_session`: Live collaboration on shapes through the project store
+//! - `command`: Canvas commands and the reducer that applies them
+//! - `comments`: Review comment threads, badges, and the comments window
+//! - `conflict`: Save conflicts on shared project files and merging both versions
+//! - `compression`: zstd compression of project files and detection caches
+//! - `context_menu`: Right-click menus for shapes, detections, and empty canvas
+//! - `cursor`: Pointer cursor for the active tool and hover target, and panning the view
+//! - `custom_tools`: Tools registered by plugins next to the built-in tools
+//! - `data_entry`: Keyboard-first entry of field values
+//! - `detection_edit`: Splitting and merging detection regions along with their OCR text
+//! - `detection_cache`: On-disk cache of detection results per image and detector configuration
+//! - `diff`: Differences between two versions of a project and their canvas overlay
+//! - `drop`: Drag-and-drop of form images and project files
+//! - `encryption`: Password-protected project files and the unlock and password windows
+//! - `encoding`: JSON and binary MessagePack project file encodings
+//! - `export`: PNG and SVG rendering of the visible layers
+//! - `field_groups`: Field groups, repeating section rows and the field groups window
+//! - `flash`: Pulsing halos on newly added detections
+//! - `geojson`: GeoJSON export of shape and detection geometry with field properties
+//! - `guides`: Rulers and guide lines that shapes snap to
+//! - `highlights`: Highlighter strokes on their own layer over the form image
+//! - `image_load`: Background decoding of form images when a project opens
+//! - `instance_bulk`: Bulk actions on the instances of a batch review
+//! - `journal`: Incremental project saves as delta records between compacted snapshots
+//! - `legend`: Legend overlay explaining annotation colors
+//! - `navigation`: Back and forward through the shapes and detections inspected
+//! - `ocr_edit`: Correcting OCR text in place on the canvas
+//! - `ocr_order`: Order in which OCR text reads and the reading order window
+//! - `ocr_pass`: OCR text per detection and the regions changed since it was read
+//! - `ocr_words`: Word boxes within OCR text, selecting words and making fields from them
+//! - `paste`: Pasting clipboard images as the form image or next page
+//! - `print`: PDF printing with selected layers and a field appendix
+//! - `project_template`: Project templates and the new-project window
+//! - `provenance`: Where a project's form image, detections and text came from
+//! - `refill`: Typesetting field values onto a clean form image as PNG or PDF
+//! - `rename`: Find and rename across shape and detection names
+//! - `repeat`: Ctrl+D duplication and grid repeat of shapes
+//! - `redaction`: Sensitive field flags, PII flags, and redacted export
+//! - `screenshot`: Capturing a canvas region to the clipboard, a PNG or a comment
+//! - `snapshot`: Read-only canvas snapshots for plugins
+//! - `stamps`: Stamps placed with the Stamp tool on the Shapes layer
+//! - `stats`: Session statistics window
+//! - `stored_projects`: Opening and saving projects in the configured project store
+//! - `subtypes`: Subtype tags on detections and the subtype filter
+//! - `tab_order`: Order in which data entry visits the fields
+//! - `template_browser`: Searching, filtering and sorting project templates
+//! - `template_load`: Project templates loaded in the background for the new-project window
+//! - `template_page`: Pages of multi-page templates and matching them to packet pages
+//! - `template_preview`: Previewing field layouts with sample data
+//! - `text_export`: Plain text and Markdown export of the text read from the form
+//! - `textures`: Texture memory budget with least-recently-used eviction
+//! - `tooltips`: Tooltips describing the field or detection under the pointer
+//! - `validation_report`: CSV and HTML reports of field errors across many filled forms
+//! - `workflow`: Page assignment window
 
+mod batch_review;
+mod bookmarks;
+mod collab_session;
+mod command;
+mod comments;
+mod compression;
+mod conflict;
+mod context_menu;
+mod cursor;
+mod custom_tools;
 mod core;
+mod data_entry;
+mod detection_cache;
+mod detection_edit;
+mod diff;
+mod drop;
+mod encoding;
+mod encryption;
+mod export;
+mod field_groups;
+mod flash;
+mod geojson;
+mod guides;
+mod highlights;
+mod image_load;
+mod instance_bulk;
 mod io;
+mod journal;
+mod legend;
+mod navigation;
+mod ocr_edit;
+mod ocr_order;
+mod ocr_pass;
+mod ocr_words;
+mod paste;
+mod print;
+mod project_template;
+mod provenance;
+mod redaction;
+mod refill;
+mod rename;
+mod repeat;
 mod rendering;
+mod screenshot;
+mod snapshot;
+mod stamps;
+mod stats;
+mod stored_projects;
+mod subtypes;
+mod tab_order;
+mod template_browser;
+mod template_load;
+mod template_page;
+mod template_preview;
+mod text_export;
+mod textures;
+mod tooltips;
 mod tools;
+mod validation_report;
+mod workflow;
 
 // Re-export public types
-pub use core::{CanvasError, CanvasErrorKind, DetectionSubtype, DrawingCanvas};
+pub use batch_review::{
+    BatchReview, DEFAULT_REVIEW_THRESHOLD, ReviewAction, ReviewInstance, ReviewItem, ReviewReason, ReviewStats,
+};
+pub use bookmarks::{MAX_BOOKMARK_SHORTCUTS, ViewBookmark};
+pub use collab_session::COLLAB_SYNC_INTERVAL_SECS;
+pub use command::{CanvasCommand, CanvasPanel, MAX_PENDING_CHANGES};
+pub use compression::{ZSTD_MAGIC, compress, is_compressed};
+pub use conflict::SaveConflict;
+pub use context_menu::CanvasAction;
+pub use cursor::ROTATE_CURSOR;
+pub use detection_cache::{clear_detection_cache, detection_cache_dir};
+pub use detection_edit::{MIN_SPLIT_SIZE, SplitDirection};
+pub use diff::{FieldChange, ImageChange, ProjectDiff, ShapeChange};
+pub use drop::{DROP_IMAGE_EXTENSIONS, DROP_PROJECT_EXTENSIONS, DropKind, DroppedFile};
+pub use encoding::{BINARY_PROJECT_MAGIC, ProjectEncoding};
+pub use encryption::{ENCRYPTED_PROJECT_MAGIC, decrypt, encrypt, is_encrypted};
+pub use export::{ExportFormat, MAX_EXPORT_DIMENSION};
+pub use flash::DETECTION_FLASH_SECONDS;
+pub use guides::{Guide, GuideOrientation};
+pub use journal::{AUTOSAVE_INTERVAL_SECS, JournalSave, MAX_JOURNAL_RECORDS, journal_path};
+pub use highlights::{HIGHLIGHT_COLOR, HIGHLIGHT_WIDTH, Highlight};
+pub use legend::LegendEntry;
+pub use navigation::{MAX_SELECTION_HISTORY, SelectionHistory};
+pub use ocr_pass::OcrRecord;
+pub use ocr_words::OcrWord;
+pub use paste::{PasteTarget, clipboard_image, pasted_images_dir};
+pub use print::{PageSize, PrintOptions};
+pub use project_template::{ProjectTemplate, project_templates_dir};
+pub use provenance::{Provenance, SourceChannel};
+pub use refill::{REFILL_INK, RefillValue};
+pub use screenshot::{ScreenshotCapture, ScreenshotStage, copy_image_to_clipboard, screenshots_dir};
+pub use snapshot::CanvasSnapshot;
+pub use tab_order::ReadingDirection;
+pub use text_export::{TextExportOptions, TextFormat};
+pub use template_browser::{
+    TemplateFilter, TemplateSortOrder, template_departments, template_languages, template_tags,
+};
+pub use template_load::TemplateLoadProgress;
+pub use template_page::{PageMatch, PageOrientation, TemplateAnchor, TemplatePage};
+pub use template_preview::{MIN_PREVIEW_TEXT_SCALE, PreviewValue, sample_value};
+pub use textures::{DEFAULT_TEXTURE_BUDGET_MB, TextureCache};
+pub use tooltips::HoverTooltip;
+pub use rename::{RenameMatch, RenameQuery};
+pub use validation_report::{InstanceSummary, ReportFormat, Severity, ValidationFinding, ValidationReport};
+pub use repeat::{MAX_REPEAT_COPIES, RepeatGrid};
+pub use core::{CanvasError, CanvasErrorKind, DrawingCanvas, PROJECT_FORMAT_VERSION};
@@ -0,0 +1,174 @@
+//! Back and forward through the shapes and detections inspected
+//!
+//! Selecting a shape or field, or opening the context menu of a detection,
+//! is recorded in a [`SelectionHistory`]. Like a browser's history, going
+//! back returns to the previous object and going forward undoes that, so a
+//! reviewer can step away to check something and come straight back. Mouse
+//! buttons 4 and 5 and Alt+Left and Alt+Right step through it.
+//!
+//! Going to an object selects a shape, or flashes a detection, and pans the
+//! view to it without changing the zoom. Entries for objects that have
+//! since been deleted are skipped.
+
+use egui::{Modifiers, PointerButton};
+use tracing::{debug, instrument};
+
+use super::core::DrawingCanvas;
+use crate::{CommentTarget, LayerType, Shape};
+
+/// Objects kept behind the current one before the oldest are forgotten
+pub const MAX_SELECTION_HISTORY: usize = 100;
+
+/// Shapes and detections inspected, in order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelectionHistory {
+    /// Objects before the current one, oldest first
+    back: Vec<CommentTarget>,
+    /// Object being inspected
+    current: Option<CommentTarget>,
+    /// Objects left by going back, the next one last
+    forward: Vec<CommentTarget>,
+}
+
+impl SelectionHistory {
+    /// Record inspecting an object
+    ///
+    /// Inspecting the current object again changes nothing; anything else
+    /// forgets the objects ahead, as following a new link in a browser does.
+    pub fn visit(&mut self, target: CommentTarget) {
+        if self.current == Some(target) {
+            return;
+        }
+        if let Some(current) = self.current.replace(target) {
+            if self.back.len() >= MAX_SELECTION_HISTORY {
+                self.back.remove(0);
+            }
+            self.back.push(current);
+        }
+        self.forward.clear();
+    }
+
+    /// Step back to the previous object for which `exists` holds
+    ///
+    /// Objects passed over are forgotten. Returns the new current object.
+    pub fn back(&mut self, exists: impl Fn(CommentTarget) -> bool) -> Option<CommentTarget> {
+        let target = std::iter::from_fn(|| self.back.pop()).find(|target| exists(*target))?;
+        if let Some(current) = self.current.replace(target) {
+            self.forward.push(current);
+        }
+        Some(target)
+    }
+
+    /// Step forward to the next object for which `exists` holds
+    ///
+    /// Objects passed over are forgotten. Returns the new current object.
+    pub fn forward(&mut self, exists: impl Fn(CommentTarget) -> bool) -> Option<CommentTarget> {
+        let target = std::iter::from_fn(|| self.forward.pop()).find(|target| exists(*target))?;
+        if let Some(current) = self.current.replace(target) {
+            self.back.push(current);
+        }
+        Some(target)
+    }
+
+    /// Object being inspected
+    pub fn current(&self) -> Option<CommentTarget> {
+        self.current
+    }
+
+    /// Whether there is an object to go back to
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    /// Whether there is an object to go forward to
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+
+    /// Update entries after objects were removed or reordered
+    ///
+    /// Entries `remap` maps to `None` are dropped.
+    pub fn remap(&mut self, remap: impl Fn(CommentTarget) -> Option<CommentTarget>) {
+        self.current = self.current.and_then(&remap);
+        for entries in [&mut self.back, &mut self.forward] {
+            *entries = entries.iter().filter_map(|target| remap(*target)).collect();
+        }
+    }
+
+    /// Forget every object
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl DrawingCanvas {
+    /// Go back to the previously inspected shape or detection
+    ///
+    /// Returns whether there was one to go back to.
+    #[instrument(skip(self))]
+    pub fn selection_back(&mut self) -> bool {
+        let mut history = std::mem::take(&mut self.selection_history);
+        let target = history.back(|target| self.object_exists(target));
+        self.selection_history = history;
+        target.inspect(|target| self.reveal(*target)).is_some()
+    }
+
+    /// Go forward to the shape or detection left by going back
+    ///
+    /// Returns whether there was one to go forward to.
+    #[instrument(skip(self))]
+    pub fn selection_forward(&mut self) -> bool {
+        let mut history = std::mem::take(&mut self.selection_history);
+        let target = history.forward(|target| self.object_exists(target));
+        self.selection_history = history;
+        target.inspect(|target| self.reveal(*target)).is_some()
+    }
+
+    /// Step through the history on mouse buttons 4 and 5 or Alt+Left and Alt+Right
+    pub(super) fn handle_navigation_input(&mut self, ui: &egui::Ui) {
+        let (back, forward) = ui.input_mut(|i| {
+            let back = i.pointer.button_pressed(PointerButton::Extra1)
+                || i.consume_key(Modifiers::ALT, egui::Key::ArrowLeft);
+            let forward = i.pointer.button_pressed(PointerButton::Extra2)
+                || i.consume_key(Modifiers::ALT, egui::Key::ArrowRight);
+            (back, forward)
+        });
+        if back {
+            self.selection_back();
+        } else if forward {
+            self.selection_forward();
+        }
+    }
+
+    /// Record that an object is being inspected
+    pub(super) fn record_selection(&mut self, target: CommentTarget) {
+        self.selection_history.visit(target);
+    }
+
+    fn object_exists(&self, target: CommentTarget) -> bool {
+        match target {
+            CommentTarget::Shape(idx) => self.shapes.get(idx).is_some_and(|shape| !shape.is_hidden()),
+            CommentTarget::Detection(idx) => self.detections.get(idx).is_some_and(|d| !d.is_hidden()),
+        }
+    }
+
+    /// Select or flash an object from the history and pan it into view
+    fn reveal(&mut self, target: CommentTarget) {
+        debug!(?target, "Revealing object from selection history");
+        let bounds = match target {
+            CommentTarget::Shape(idx) => {
+                self.selected_shape = Some(idx);
+                self.selected_layer = Some(LayerType::Shapes);
+                self.show_properties = true;
+                self.shapes.get(idx).map(Shape::bounding_rect)
+            }
+            CommentTarget::Detection(idx) => {
+                self.flash_detection(idx);
+                self.detection_extent(idx..idx + 1)
+            }
+        };
+        if let Some(bounds) = bounds {
+            self.pan_to_rect(bounds);
+        }
+    }
+}
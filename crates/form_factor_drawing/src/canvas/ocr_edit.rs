@@ -0,0 +1,171 @@
+//! Correcting OCR text in place on the canvas
+//!
+//! Double-clicking a detection that has been through OCR opens a small
+//! editor anchored below the region. Saving replaces the recorded text and
+//! queues an [`OcrSample`], which the application passes on so OCR accuracy
+//! can be tracked. Corrected text was checked by a person, so it
+//! no longer carries the engine's confidence.
+
+use super::core::{CanvasState, DrawingCanvas};
+use crate::{CanvasPos, CommentTarget, ImagePos, OcrSample, ScreenPos};
+use tracing::{debug, instrument, warn};
+
+/// Gap between a detection and the editor anchored below it, in screen points
+const EDITOR_GAP: f32 = 4.0;
+
+/// OCR text being edited on the canvas
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct OcrEdit {
+    /// Detection whose text is edited
+    pub(super) detection: usize,
+    /// Text in the editor
+    text: String,
+    /// Whether the editor still needs keyboard focus
+    focus: bool,
+}
+
+impl DrawingCanvas {
+    /// Open the in-place editor on a detection's OCR text
+    ///
+    /// Returns false if the detection has not been through OCR.
+    pub fn begin_ocr_edit(&mut self, detection_index: usize) -> bool {
+        let Some(text) = self.ocr_text(detection_index) else {
+            debug!(detection_index, "Detection has no OCR text to edit");
+            return false;
+        };
+        self.ocr_edit = Some(OcrEdit {
+            detection: detection_index,
+            text: text.to_string(),
+            focus: true,
+        });
+        debug!(detection_index, "Editing OCR text");
+        true
+    }
+
+    /// Detection whose OCR text is open in the editor
+    pub fn ocr_edit_target(&self) -> Option<usize> {
+        self.ocr_edit.as_ref().map(|edit| edit.detection)
+    }
+
+    /// Close the editor without changing the text
+    pub fn cancel_ocr_edit(&mut self) {
+        if self.ocr_edit.take().is_some() {
+            debug!("OCR text edit cancelled");
+        }
+    }
+
+    /// Replace the OCR text of a detection with a person's correction
+    ///
+    /// Counts a correction in the session statistics if the text changed,
+    /// and queues an [`OcrSample`] if OCR reported a confidence for it.
+    /// Returns false if the detection has not been through OCR.
+    #[instrument(skip(self, text))]
+    pub fn correct_ocr_text(&mut self, detection_index: usize, text: &str) -> bool {
+        let Some(original) = self.ocr_text(detection_index).map(str::to_string) else {
+            warn!(detection_index, "Cannot correct OCR text of a detection that was not read");
+            return false;
+        };
+        if original == text {
+            return true;
+        }
+
+        let confidence = self.ocr_confidence(detection_index);
+        self.record_ocr_text(detection_index, text);
+        self.session_stats.record_field_corrected();
+        if let Some(confidence) = confidence {
+            let field = match self.detections.get(detection_index).map(|detection| detection.name()) {
+                Some("") | None => format!("Detection {}", detection_index + 1),
+                Some(name) => name.to_string(),
+            };
+            self.ocr_samples.push(OcrSample {
+                field,
+                proposed: original,
+                corrected: text.to_string(),
+                confidence,
+            });
+        }
+        debug!(?confidence, "Corrected OCR text");
+        true
+    }
+
+    /// Take the OCR readings people settled on since the last call, oldest first
+    ///
+    /// Includes the decisions made in the batch review window.
+    pub fn take_ocr_samples(&mut self) -> Vec<OcrSample> {
+        let mut samples = std::mem::take(&mut self.ocr_samples);
+        if let Some(review) = &mut self.batch_review {
+            samples.extend(review.take_ocr_samples());
+        }
+        samples
+    }
+
+    /// Open the editor on the detection double-clicked with a built-in tool
+    pub(super) fn handle_ocr_edit_input(&mut self, response: &egui::Response) {
+        if !response.double_clicked() || self.active_custom_tool.is_some() || !matches!(self.state, CanvasState::Idle) {
+            return;
+        }
+        let Some(pos) = response.interact_pointer_pos() else {
+            return;
+        };
+        let canvas_pos = self.coordinate_mapper.screen_to_canvas(ScreenPos(pos)).0;
+        if let Some(CommentTarget::Detection(idx)) = self.object_at(canvas_pos) {
+            self.begin_ocr_edit(idx);
+        }
+    }
+
+    /// Show the editor below the detection being edited
+    ///
+    /// Enter or Save keeps the text, Shift+Enter starts a new line, and
+    /// Escape or Cancel closes the editor unchanged.
+    pub(super) fn show_ocr_edit_popup(&mut self, ui: &egui::Ui) {
+        let Some(edit) = &mut self.ocr_edit else {
+            return;
+        };
+        let mapper = self.coordinate_mapper;
+        let anchor = self
+            .detections
+            .get(edit.detection)
+            .map(|detection| detection.bounding_rect())
+            .and_then(|region| mapper.image_to_canvas(ImagePos(region.left_bottom())))
+            .map(|pos| mapper.canvas_to_screen(CanvasPos(pos.0)).0);
+        let Some(anchor) = anchor else {
+            // The detection was removed or the form image closed
+            self.ocr_edit = None;
+            return;
+        };
+
+        let mut save = false;
+        let mut cancel = false;
+        egui::Area::new(ui.id().with("ocr_edit"))
+            .order(egui::Order::Foreground)
+            .fixed_pos(anchor + egui::vec2(0.0, EDITOR_GAP))
+            .show(ui.ctx(), |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    let editor = ui.add(
+                        egui::TextEdit::multiline(&mut edit.text)
+                            .desired_rows(1)
+                            .desired_width(240.0)
+                            .return_key(egui::KeyboardShortcut::new(egui::Modifiers::SHIFT, egui::Key::Enter)),
+                    );
+                    if edit.focus {
+                        editor.request_focus();
+                        edit.focus = false;
+                    }
+                    let (enter, escape) =
+                        ui.input(|i| (i.key_pressed(egui::Key::Enter) && !i.modifiers.shift, i.key_pressed(egui::Key::Escape)));
+                    save = (editor.has_focus() || editor.lost_focus()) && enter;
+                    cancel = escape;
+                    ui.horizontal(|ui| {
+                        save |= ui.button("Save").clicked();
+                        cancel |= ui.button("Cancel").clicked();
+                    });
+                });
+            });
+
+        if save && let Some(edit) = self.ocr_edit.take() {
+            self.correct_ocr_text(edit.detection, &edit.text);
+        } else if cancel {
+            self.cancel_ocr_edit();
+        }
+    }
+}
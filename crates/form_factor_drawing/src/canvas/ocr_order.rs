@@ -0,0 +1,222 @@
+//! Order in which the text read from detections makes up the document
+//!
+//! Detections come back from the detector in whatever order it found them,
+//! so their text joined in detection order jumps around the page. The
+//! reading order window lists the detections that have OCR text in reading
+//! order and numbers them on the canvas; dragging a detection moves it, and
+//! Auto orders them in rows from the top with each row read across.
+//!
+//! The order is saved with the project. Detections missing from the saved
+//! order, such as ones read after it was set, follow the ordered ones in
+//! detection order.
+
+use egui::{Color32, Rect};
+use tracing::{debug, instrument};
+
+use super::core::DrawingCanvas;
+use super::tab_order::{ReadingDirection, drop_position, reading_order};
+use crate::ImagePos;
+
+/// Color of the reading order badges on the canvas
+const BADGE_COLOR: Color32 = Color32::from_rgb(30, 110, 200);
+
+/// Radius of the reading order badges, in screen points
+const BADGE_RADIUS: f32 = 9.0;
+
+impl DrawingCanvas {
+    /// Indices of the detections with OCR text, in the order their text is read
+    pub fn ocr_reading_order(&self) -> Vec<usize> {
+        let count = self.detections.len();
+        let mut seen = vec![false; count];
+        let mut order: Vec<usize> = self
+            .ocr_order
+            .iter()
+            .copied()
+            .filter(|idx| *idx < count && self.ocr_text(*idx).is_some() && !std::mem::replace(&mut seen[*idx], true))
+            .collect();
+        order.extend(
+            self.ocr_records
+                .iter()
+                .map(|record| record.detection)
+                .filter(|idx| *idx < count && !seen[*idx]),
+        );
+        order
+    }
+
+    /// Set the order the text of detections is read in, as detection indices
+    ///
+    /// Unknown and repeated indices, and detections without OCR text, are
+    /// dropped; detections left out follow the listed ones in detection order.
+    pub fn set_ocr_reading_order(&mut self, order: Vec<usize>) {
+        self.ocr_order = order;
+        self.ocr_order = self.ocr_reading_order();
+    }
+
+    /// Move the detection at position `from` in the reading order to position `to`
+    ///
+    /// Returns false if either position is out of range.
+    #[instrument(skip(self))]
+    pub fn move_in_ocr_reading_order(&mut self, from: usize, to: usize) -> bool {
+        let mut order = self.ocr_reading_order();
+        if from >= order.len() || to >= order.len() {
+            return false;
+        }
+        let idx = order.remove(from);
+        order.insert(to, idx);
+        self.ocr_order = order;
+        true
+    }
+
+    /// Order the detections with OCR text by reading direction
+    ///
+    /// Detections whose middles fall within the height of the topmost
+    /// detection of a row join that row; rows are read from the top.
+    #[instrument(skip(self))]
+    pub fn auto_ocr_reading_order(&mut self, direction: ReadingDirection) {
+        let read: Vec<usize> = self
+            .ocr_records
+            .iter()
+            .map(|record| record.detection)
+            .filter(|idx| *idx < self.detections.len())
+            .collect();
+        let bounds: Vec<Rect> = read.iter().map(|idx| self.detections[*idx].bounding_rect()).collect();
+        self.ocr_order = reading_order(&bounds, direction).into_iter().map(|i| read[i]).collect();
+        debug!(detections = self.ocr_order.len(), "Ordered OCR text by reading direction");
+    }
+
+    /// Go back to reading text in detection order
+    pub fn reset_ocr_reading_order(&mut self) {
+        self.ocr_order.clear();
+    }
+
+    /// Text read from all detections, in reading order, one detection per line
+    ///
+    /// Detections whose text is blank are left out.
+    pub fn ocr_document_text(&self) -> String {
+        self.ocr_reading_order()
+            .into_iter()
+            .filter_map(|idx| self.ocr_text(idx))
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Toggle the reading order window and its badges on the canvas
+    pub fn toggle_ocr_order_panel(&mut self) {
+        self.show_ocr_order = !self.show_ocr_order;
+    }
+
+    /// Number the detections with their position in the reading order while the window is open
+    pub(super) fn draw_ocr_order_badges(&self, painter: &egui::Painter, to_screen: &egui::emath::TSTransform) {
+        if !self.show_ocr_order {
+            return;
+        }
+        for (position, idx) in self.ocr_reading_order().into_iter().enumerate() {
+            let Some(detection) = self.detections.get(idx) else {
+                continue;
+            };
+            if detection.is_hidden() {
+                continue;
+            }
+            let corner = ImagePos(detection.bounding_rect().left_top());
+            let Some(anchor) = self.coordinate_mapper.image_to_canvas(corner) else {
+                continue;
+            };
+
+            let center = to_screen.mul_pos(anchor.0);
+            painter.circle_filled(center, BADGE_RADIUS, BADGE_COLOR);
+            painter.text(
+                center,
+                egui::Align2::CENTER_CENTER,
+                (position + 1).to_string(),
+                egui::FontId::proportional(10.0),
+                Color32::WHITE,
+            );
+        }
+    }
+
+    /// Show the reading order window
+    ///
+    /// Returns true if the window was shown.
+    pub fn show_ocr_order_panel(&mut self, ctx: &egui::Context) -> bool {
+        if !self.show_ocr_order {
+            return false;
+        }
+
+        let order = self.ocr_reading_order();
+        let mut panel_open = true;
+        let mut auto = false;
+        let mut reset = false;
+        let mut moved = None;
+        let direction = &mut self.ocr_order_direction;
+        let (detections, records) = (&self.detections, &self.ocr_records);
+        egui::Window::new("Reading Order")
+            .open(&mut panel_open)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("ocr_order_direction")
+                        .selected_text(direction.to_string())
+                        .show_ui(ui, |ui| {
+                            for option in <ReadingDirection as strum::IntoEnumIterator>::iter() {
+                                ui.selectable_value(direction, option, option.to_string());
+                            }
+                        });
+                    auto = ui.button("Auto").on_hover_text("Order text by reading direction").clicked();
+                    reset = ui.button("Reset").on_hover_text("Read text in detection order").clicked();
+                });
+                ui.weak("Drag detections to reorder them");
+                ui.separator();
+
+                if order.is_empty() {
+                    ui.label("Extract text from the detections to order it.");
+                    return;
+                }
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    for (position, idx) in order.iter().enumerate() {
+                        let text = records
+                            .iter()
+                            .find(|record| record.detection == *idx)
+                            .map_or("", |record| record.text.trim());
+                        let name = match detections[*idx].name() {
+                            "" => format!("Detection {}", idx + 1),
+                            name => name.to_string(),
+                        };
+                        let label = format!("{}. {}: {}", position + 1, name, text.lines().next().unwrap_or(""));
+                        let response = ui
+                            .dnd_drag_source(egui::Id::new(("ocr_order", *idx)), position, |ui| ui.label(label))
+                            .response
+                            .on_hover_text(text);
+                        if let Some(pointer) = ui.input(|i| i.pointer.interact_pos())
+                            && response.dnd_hover_payload::<usize>().is_some()
+                        {
+                            // Line where the dragged detection will land
+                            let rect = response.rect;
+                            let y = if pointer.y < rect.center().y { rect.top() } else { rect.bottom() };
+                            ui.painter().hline(rect.x_range(), y, ui.visuals().selection.stroke);
+                        }
+                        if let Some(from) = response.dnd_release_payload::<usize>() {
+                            let below = ui
+                                .input(|i| i.pointer.interact_pos())
+                                .is_some_and(|pointer| pointer.y >= response.rect.center().y);
+                            moved = Some((*from, drop_position(*from, position, below)));
+                        }
+                    }
+                });
+            });
+
+        if auto {
+            self.auto_ocr_reading_order(self.ocr_order_direction);
+        } else if reset {
+            self.reset_ocr_reading_order();
+        } else if let Some((from, to)) = moved {
+            self.move_in_ocr_reading_order(from, to);
+        }
+        if !panel_open {
+            self.show_ocr_order = false;
+        }
+
+        true
+    }
+}
@@ -0,0 +1,126 @@
+//! OCR text kept per detection, for re-reading only what changed
+//!
+//! Each OCR pass records the text it read from a detection along with the
+//! detection's region at the time. A detection whose region has since been
+//! moved or resized, or that was added after the pass, no longer matches
+//! its record and is picked up by the next differential pass.
+//!
+//! Detections on amount and other number fields are read in numeric mode,
+//! so letters can't creep into the digits.
+
+use super::core::DrawingCanvas;
+use super::ocr_words::OcrWord;
+use crate::{CanvasPos, FieldFormat, ImagePos};
+use egui::Rect;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+/// Text read from one detection by an OCR pass
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OcrRecord {
+    /// Index of the detection the text was read from
+    pub detection: usize,
+    /// Detection region when the text was read, in image pixels
+    pub region: Rect,
+    /// Text read from the region
+    pub text: String,
+    /// Mean confidence (0-100) the OCR engine reported for the text
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    /// Words the text was read as, with their boxes
+    #[serde(default)]
+    pub words: Vec<OcrWord>,
+}
+
+impl DrawingCanvas {
+    /// Record the text read from a detection in its current region
+    ///
+    /// Replaces any earlier record for the detection.
+    pub fn record_ocr_text(&mut self, detection_index: usize, text: &str) {
+        self.record_ocr(detection_index, text, None);
+    }
+
+    /// Record the text read from a detection along with the engine's confidence (0-100)
+    ///
+    /// Replaces any earlier record for the detection.
+    pub fn record_ocr_result(&mut self, detection_index: usize, text: &str, confidence: f32) {
+        self.record_ocr(detection_index, text, Some(confidence));
+    }
+
+    pub(super) fn record_ocr(&mut self, detection_index: usize, text: &str, confidence: Option<f32>) {
+        let Some(detection) = self.detections.get(detection_index) else {
+            warn!(detection_index, "Cannot record OCR text on missing detection");
+            return;
+        };
+        let region = detection.bounding_rect();
+
+        self.ocr_records.retain(|record| record.detection != detection_index);
+        self.ocr_records.push(OcrRecord {
+            detection: detection_index,
+            region,
+            text: text.to_string(),
+            confidence,
+            words: Vec::new(),
+        });
+        self.ocr_records.sort_by_key(|record| record.detection);
+        debug!(detection_index, chars = text.len(), ?confidence, "Recorded OCR text");
+    }
+
+    /// Text last read from a detection, if it has been through OCR
+    pub fn ocr_text(&self, detection_index: usize) -> Option<&str> {
+        self.ocr_records
+            .iter()
+            .find(|record| record.detection == detection_index)
+            .map(|record| record.text.as_str())
+    }
+
+    /// Confidence (0-100) of the text last read from a detection, if the engine reported one
+    pub fn ocr_confidence(&self, detection_index: usize) -> Option<f32> {
+        self.ocr_records
+            .iter()
+            .find(|record| record.detection == detection_index)
+            .and_then(|record| record.confidence)
+    }
+
+    /// Detections whose region changed, or that have no text, since the last OCR pass
+    pub fn changed_ocr_regions(&self) -> Vec<usize> {
+        self.detections
+            .iter()
+            .enumerate()
+            .filter(|(idx, detection)| {
+                self.ocr_records
+                    .iter()
+                    .find(|record| record.detection == *idx)
+                    .is_none_or(|record| record.region != detection.bounding_rect())
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Whether a detection should be read in numeric OCR mode
+    ///
+    /// True when the detection itself has a number rule, or when its center
+    /// lies on a shape whose rule is a number, e.g. an amount field drawn
+    /// over the form.
+    pub fn reads_numbers(&self, detection_index: usize) -> bool {
+        let Some(detection) = self.detections.get(detection_index) else {
+            return false;
+        };
+        if detection.rule().format == FieldFormat::Number {
+            return true;
+        }
+
+        let center = ImagePos(detection.bounding_rect().center());
+        let Some(CanvasPos(center)) = self.coordinate_mapper.image_to_canvas(center) else {
+            return false;
+        };
+        self.shapes
+            .iter()
+            .any(|shape| shape.rule().format == FieldFormat::Number && shape.bounding_rect().contains(center))
+    }
+
+    /// Forget all recorded OCR text, so the next differential pass reads every detection
+    pub fn clear_ocr_records(&mut self) {
+        self.ocr_records.clear();
+    }
+}
@@ -0,0 +1,214 @@
+//! Word boxes within the text OCR read from detections
+//!
+//! A detection region often holds more than one value, such as a label and
+//! an amount read together. OCR reports where each word sits, and the word
+//! boxes sub-layer draws them over their detections. With the boxes shown,
+//! clicking a word selects it, and the detection's context menu can turn the
+//! word under the pointer into a field or fill the selected field with it.
+//!
+//! Words are kept with the OCR record of their detection and go away when
+//! the detection is read again or its text is corrected.
+
+use super::core::DrawingCanvas;
+use crate::{CanvasPos, ImagePos, LayerType, Rectangle, Shape};
+use egui::{Color32, Pos2, Rect, Stroke};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument, warn};
+
+/// Outline of word boxes
+const WORD_STROKE: Stroke = Stroke {
+    width: 1.0,
+    color: Color32::from_rgb(0, 150, 136),
+};
+
+/// Outline of the selected word box
+const SELECTED_WORD_STROKE: Stroke = Stroke {
+    width: 2.0,
+    color: Color32::from_rgb(255, 215, 0),
+};
+
+/// One word OCR read from a detection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OcrWord {
+    /// The word as read
+    pub text: String,
+    /// Confidence (0-100) the OCR engine reported for the word
+    pub confidence: f32,
+    /// Box around the word, in image pixels
+    pub bounds: Rect,
+}
+
+#[cfg(all(feature = "ocr", not(target_arch = "wasm32")))]
+impl From<&form_factor_ocr::WordResult> for OcrWord {
+    fn from(word: &form_factor_ocr::WordResult) -> Self {
+        let (x, y, width, height) = word.bbox().to_tuple();
+        Self {
+            text: word.text().clone(),
+            confidence: *word.confidence(),
+            bounds: Rect::from_min_size(Pos2::new(x as f32, y as f32), egui::vec2(width as f32, height as f32)),
+        }
+    }
+}
+
+impl DrawingCanvas {
+    /// Record the words the text of a detection was read as
+    ///
+    /// Call after recording the text, which replaces earlier words.
+    pub fn record_ocr_words(&mut self, detection_index: usize, words: Vec<OcrWord>) {
+        let Some(record) = self.ocr_records.iter_mut().find(|record| record.detection == detection_index) else {
+            warn!(detection_index, "Cannot record words of a detection without OCR text");
+            return;
+        };
+        debug!(detection_index, words = words.len(), "Recorded OCR words");
+        record.words = words;
+    }
+
+    /// Words last read from a detection, empty if OCR reported none
+    pub fn ocr_words(&self, detection_index: usize) -> &[OcrWord] {
+        self.ocr_records
+            .iter()
+            .find(|record| record.detection == detection_index)
+            .map_or(&[], |record| record.words.as_slice())
+    }
+
+    /// Show or hide the word boxes over the detections
+    pub fn toggle_ocr_words(&mut self) {
+        self.show_ocr_words = !self.show_ocr_words;
+        if !self.show_ocr_words {
+            self.selected_ocr_word = None;
+        }
+    }
+
+    /// Detection and word index of the word box at a canvas position, while the boxes are shown
+    pub fn ocr_word_at(&self, pos: Pos2) -> Option<(usize, usize)> {
+        if !self.show_ocr_words || !self.layer_manager.is_visible(LayerType::Detections) {
+            return None;
+        }
+        let ImagePos(pos) = self.coordinate_mapper.canvas_to_image(CanvasPos(pos))?;
+        self.ocr_records
+            .iter()
+            .filter(|record| self.word_boxes_visible(record.detection))
+            .find_map(|record| {
+                let word = record.words.iter().position(|word| word.bounds.contains(pos))?;
+                Some((record.detection, word))
+            })
+    }
+
+    /// Detection and word index of the selected word
+    pub fn selected_ocr_word(&self) -> Option<(usize, usize)> {
+        self.selected_ocr_word
+            .filter(|(detection, word)| *word < self.ocr_words(*detection).len())
+    }
+
+    /// Select a word, or clear the word selection
+    ///
+    /// Returns false if the detection has no such word.
+    pub fn select_ocr_word(&mut self, word: Option<(usize, usize)>) -> bool {
+        if let Some((detection, index)) = word
+            && index >= self.ocr_words(detection).len()
+        {
+            return false;
+        }
+        self.selected_ocr_word = word;
+        true
+    }
+
+    /// Add a rectangle field over a word, holding the word as its value
+    ///
+    /// Selects the new field and returns its index, or `None` if the word
+    /// doesn't exist or the canvas has not been rendered with the form image.
+    #[instrument(skip(self))]
+    pub fn field_from_ocr_word(&mut self, detection_index: usize, word_index: usize) -> Option<usize> {
+        let word = self.ocr_words(detection_index).get(word_index)?.clone();
+        let min = self.coordinate_mapper.image_to_canvas(ImagePos(word.bounds.min))?;
+        let max = self.coordinate_mapper.image_to_canvas(ImagePos(word.bounds.max))?;
+        let mut shape = match Rectangle::from_corners(min.0, max.0, self.stroke, self.fill_color) {
+            Ok(rect) => Shape::Rectangle(rect),
+            Err(e) => {
+                warn!("Could not create field from word: {}", e);
+                return None;
+            }
+        };
+        shape.set_extracted_value(word.text.clone(), Some(word.confidence));
+
+        self.shapes.push(shape);
+        let index = self.shapes.len() - 1;
+        self.set_selected_shape(Some(index));
+        self.selected_layer = Some(LayerType::Shapes);
+        self.show_properties = true;
+        self.focus_name_field = true;
+        debug!(index, "Created field from word");
+        Some(index)
+    }
+
+    /// Fill a field with a word, keeping the word's confidence
+    ///
+    /// Returns false if the word or the field doesn't exist.
+    pub fn assign_ocr_word(&mut self, detection_index: usize, word_index: usize, shape_index: usize) -> bool {
+        let Some(word) = self.ocr_words(detection_index).get(word_index).cloned() else {
+            return false;
+        };
+        let Some(shape) = self.shapes.get_mut(shape_index) else {
+            return false;
+        };
+        shape.set_extracted_value(word.text, Some(word.confidence));
+        debug!(detection_index, word_index, shape_index, "Assigned word to field");
+        true
+    }
+
+    /// Whether the word boxes of a detection are drawn
+    fn word_boxes_visible(&self, detection_index: usize) -> bool {
+        self.detections
+            .get(detection_index)
+            .is_some_and(|detection| !detection.is_hidden())
+            && self.passes_subtype_filter(detection_index)
+    }
+
+    /// Draw the word boxes over their detections, with the selected word highlighted
+    pub(super) fn draw_ocr_words(&self, painter: &egui::Painter, to_screen: &egui::emath::TSTransform) {
+        if !self.show_ocr_words {
+            return;
+        }
+        let selected = self.selected_ocr_word();
+        for record in self.ocr_records.iter().filter(|record| self.word_boxes_visible(record.detection)) {
+            for (index, word) in record.words.iter().enumerate() {
+                let (Some(min), Some(max)) = (
+                    self.coordinate_mapper.image_to_canvas(ImagePos(word.bounds.min)),
+                    self.coordinate_mapper.image_to_canvas(ImagePos(word.bounds.max)),
+                ) else {
+                    continue;
+                };
+                let rect = Rect::from_min_max(to_screen.mul_pos(min.0), to_screen.mul_pos(max.0));
+                let stroke = if selected == Some((record.detection, index)) {
+                    painter.rect_filled(rect, 0.0, SELECTED_WORD_STROKE.color.gamma_multiply(0.25));
+                    SELECTED_WORD_STROKE
+                } else {
+                    WORD_STROKE
+                };
+                painter.rect_stroke(rect, 0.0, stroke, egui::StrokeKind::Outside);
+            }
+        }
+    }
+
+    /// Menu entries for the word under the pointer in a detection's context menu
+    pub(super) fn word_menu(&mut self, ui: &mut egui::Ui, detection_index: usize) {
+        let Some((detection, word)) = self
+            .ocr_word_at(self.context_menu_pos)
+            .filter(|(detection, _)| *detection == detection_index)
+        else {
+            return;
+        };
+        if let Some(text) = self.ocr_words(detection).get(word).map(|word| word.text.clone()) {
+            ui.label(format!("Word: {}", text));
+        }
+        if ui.button("Create Field from Word").clicked() {
+            self.field_from_ocr_word(detection, word);
+        }
+        if let Some(shape) = self.selected_shape
+            && ui.button("Fill Selected Field with Word").clicked()
+        {
+            self.assign_ocr_word(detection, word, shape);
+        }
+        ui.separator();
+    }
+}
@@ -0,0 +1,197 @@
+//! Pasting images from the system clipboard
+//!
+//! Ctrl+V on the canvas takes an image from the clipboard, e.g. a screenshot
+//! of a form shared over chat. Without a form image it becomes the form
+//! image; otherwise a window asks whether to replace the form image or add it
+//! as the next page in the dropped files queue. Pasted images are written to
+//! disk first, so detection, OCR and saved projects can find them by path.
+
+use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas};
+use super::drop::DroppedFile;
+use super::provenance::SourceChannel;
+use crate::config_dir;
+use form_factor_core::IoOperation;
+use image::RgbaImage;
+use std::path::{Path, PathBuf};
+use tracing::{debug, error, info, instrument};
+
+/// Where a pasted image goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasteTarget {
+    /// Replace the form image
+    FormImage,
+    /// Queue as the next page to open
+    NextPage,
+}
+
+/// Directory pasted images are written to
+pub fn pasted_images_dir() -> PathBuf {
+    config_dir().join("pasted")
+}
+
+/// Read an image from the system clipboard
+#[cfg(not(target_arch = "wasm32"))]
+pub fn clipboard_image() -> Result<RgbaImage, CanvasError> {
+    let clipboard_error =
+        |e: arboard::Error| CanvasError::new(CanvasErrorKind::Clipboard(e.to_string()), line!(), file!());
+
+    let image = arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_image())
+        .map_err(clipboard_error)?;
+    RgbaImage::from_raw(image.width as u32, image.height as u32, image.bytes.into_owned()).ok_or_else(|| {
+        CanvasError::new(
+            CanvasErrorKind::Clipboard("clipboard image has an unexpected size".to_string()),
+            line!(),
+            file!(),
+        )
+    })
+}
+
+/// Read an image from the system clipboard
+///
+/// Browsers only hand clipboard images to paste events, which egui doesn't forward.
+#[cfg(target_arch = "wasm32")]
+pub fn clipboard_image() -> Result<RgbaImage, CanvasError> {
+    Err(CanvasError::new(
+        CanvasErrorKind::Clipboard("clipboard images are unavailable in the browser".to_string()),
+        line!(),
+        file!(),
+    ))
+}
+
+impl DrawingCanvas {
+    /// Write a pasted image to `dir` and use it as `target`
+    ///
+    /// Returns the path the image was written to.
+    #[instrument(skip(self, image, ctx), fields(width = image.width(), height = image.height()))]
+    pub fn paste_image(
+        &mut self,
+        image: &RgbaImage,
+        target: PasteTarget,
+        dir: &Path,
+        ctx: &egui::Context,
+    ) -> Result<PathBuf, CanvasError> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| CanvasError::io(e, dir.display().to_string(), IoOperation::Create, line!(), file!()))?;
+        let path = unique_paste_path(dir);
+        let path_str = path.to_string_lossy().into_owned();
+        image
+            .save(&path)
+            .map_err(|e| CanvasError::image(e, path_str.as_str(), IoOperation::Write, line!(), file!()))?;
+
+        match target {
+            PasteTarget::FormImage => self.load_form_image_from(&path_str, SourceChannel::Pasted, ctx)?,
+            PasteTarget::NextPage => {
+                // Pasted images are always PNG, so the path is supported
+                self.queue_dropped_files(DroppedFile::from_path(&path));
+            }
+        }
+        info!("Pasted image to {} as {:?}", path_str, target);
+        Ok(path)
+    }
+
+    /// Paste a clipboard image when Ctrl+V is pressed outside a text field
+    ///
+    /// Without a form image the image is pasted straight away and the result
+    /// returned; otherwise it waits for a choice in the paste window.
+    pub fn handle_paste_shortcut(&mut self, ctx: &egui::Context) -> Option<Result<PathBuf, CanvasError>> {
+        if ctx.wants_keyboard_input() || !paste_shortcut_released(ctx) {
+            return None;
+        }
+
+        let image = match clipboard_image() {
+            Ok(image) => image,
+            Err(e) => {
+                // Usually text on the clipboard, which other widgets handle
+                debug!("No image to paste: {}", e);
+                return None;
+            }
+        };
+
+        if self.form_image.is_none() {
+            return Some(self.paste_image(&image, PasteTarget::FormImage, &pasted_images_dir(), ctx));
+        }
+        self.pending_paste = Some(image);
+        None
+    }
+
+    /// Discard a pasted image waiting for a choice
+    pub fn cancel_paste(&mut self) {
+        self.pending_paste = None;
+    }
+
+    /// Show the window asking where a pasted image goes
+    ///
+    /// Returns true if the window was shown.
+    pub fn show_paste_panel(&mut self, ctx: &egui::Context) -> bool {
+        let Some(image) = &self.pending_paste else {
+            return false;
+        };
+
+        let mut choice = None;
+        let mut cancel = false;
+        egui::Window::new("Paste Image")
+            .resizable(false)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!("Pasted image: {} x {} pixels", image.width(), image.height()));
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Replace form image").clicked() {
+                        choice = Some(PasteTarget::FormImage);
+                    }
+                    if ui.button("Add as next page").clicked() {
+                        choice = Some(PasteTarget::NextPage);
+                    }
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+
+        if let Some(target) = choice
+            && let Some(image) = self.pending_paste.take()
+            && let Err(e) = self.paste_image(&image, target, &pasted_images_dir(), ctx)
+        {
+            error!("Failed to paste image: {}", e);
+        }
+        if cancel {
+            self.cancel_paste();
+        }
+        true
+    }
+}
+
+/// Whether Ctrl+V (Cmd+V on macOS) was released this frame
+///
+/// egui turns the press into a text paste, and drops it entirely when the
+/// clipboard holds an image, so the release is the reliable signal.
+fn paste_shortcut_released(ctx: &egui::Context) -> bool {
+    ctx.input(|i| {
+        i.events.iter().any(|event| {
+            matches!(
+                event,
+                egui::Event::Key {
+                    key: egui::Key::V,
+                    pressed: false,
+                    modifiers,
+                    ..
+                } if modifiers.command
+            )
+        })
+    })
+}
+
+/// Unused file name for a pasted image in `dir`
+fn unique_paste_path(dir: &Path) -> PathBuf {
+    let millis = web_time::SystemTime::now()
+        .duration_since(web_time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+    (0..)
+        .map(|n| match n {
+            0 => dir.join(format!("pasted_{}.png", millis)),
+            n => dir.join(format!("pasted_{}_{}.png", millis, n)),
+        })
+        .find(|path| !path.exists())
+        .unwrap_or_else(|| dir.join("pasted.png"))
+}
@@ -0,0 +1,401 @@
+//! Printing the canvas to PDF
+//!
+//! A print lays out the form image with a chosen set of annotation layers on
+//! one page, independent of which layers are visible on screen, followed by
+//! an optional appendix listing every field with a blank to record its value.
+//! The composition is rasterized like a PNG export and embedded as a JPEG, so
+//! the PDF opens in any viewer and prints at the chosen scale.
+
+use super::context_menu::CanvasAction;
+use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas};
+use crate::{LayerType, Shape};
+use form_factor_core::IoOperation;
+use tracing::{debug, instrument};
+
+/// Page margin, in points
+pub(super) const MARGIN: f32 = 36.0;
+
+/// Font size of page headings, in points
+const HEADING_SIZE: f32 = 14.0;
+
+/// Font size of appendix rows, in points
+const ROW_SIZE: f32 = 10.0;
+
+/// Distance between appendix rows, in points
+const ROW_LEADING: f32 = 18.0;
+
+/// Longest field name printed before truncation
+const MAX_NAME_CHARS: usize = 44;
+
+/// JPEG quality of the embedded composition
+const JPEG_QUALITY: u8 = 90;
+
+/// Paper size of a print
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, strum::EnumIter, strum::Display)]
+pub enum PageSize {
+    /// US Letter, 8.5 x 11 inches
+    #[default]
+    Letter,
+    /// ISO A4, 210 x 297 mm
+    A4,
+}
+
+impl PageSize {
+    /// Width and height in points (1/72 inch)
+    pub fn points(&self) -> (f32, f32) {
+        match self {
+            PageSize::Letter => (612.0, 792.0),
+            PageSize::A4 => (595.0, 842.0),
+        }
+    }
+}
+
+/// What a print includes and how it is laid out
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrintOptions {
+    /// Layers drawn on the form page; include [`LayerType::Canvas`] for the form image
+    pub layers: Vec<LayerType>,
+    /// Add pages listing the fields with space for their values
+    pub appendix: bool,
+    /// Paper size
+    pub page_size: PageSize,
+    /// Rendered pixels per form image pixel
+    pub scale: f32,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        Self {
+            layers: vec![LayerType::Canvas, LayerType::Detections, LayerType::Shapes],
+            appendix: true,
+            page_size: PageSize::default(),
+            scale: 1.0,
+        }
+    }
+}
+
+impl PrintOptions {
+    /// Print the given layers without an appendix
+    pub fn new(layers: impl IntoIterator<Item = LayerType>) -> Self {
+        Self {
+            layers: layers.into_iter().collect(),
+            appendix: false,
+            ..Self::default()
+        }
+    }
+
+    /// Set whether the field appendix is added
+    pub fn with_appendix(mut self, appendix: bool) -> Self {
+        self.appendix = appendix;
+        self
+    }
+
+    /// Set the paper size
+    pub fn with_page_size(mut self, page_size: PageSize) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Set the rendering scale
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Whether a layer is printed
+    pub fn includes(&self, layer: LayerType) -> bool {
+        self.layers.contains(&layer)
+    }
+
+    /// Add or remove a layer
+    pub fn set_layer(&mut self, layer: LayerType, include: bool) {
+        self.layers.retain(|l| *l != layer);
+        if include {
+            self.layers.push(layer);
+        }
+    }
+}
+
+impl DrawingCanvas {
+    /// Build a PDF document of the canvas
+    ///
+    /// The first page shows the form image with the layers selected in
+    /// `options`, scaled to fit within the page margins. With the appendix
+    /// enabled, the following pages list the fields of the printed layers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as [`Self::render_png`],
+    /// applied to the selected layers instead of the visible ones.
+    pub fn to_pdf(&self, options: &PrintOptions) -> Result<Vec<u8>, CanvasError> {
+        let scene = self.scene_with_layers(|layer| options.includes(layer))?;
+        let image = image::DynamicImage::ImageRgba8(self.render_scene(&scene, options.scale)?).to_rgb8();
+        let jpeg = encode_jpeg(&image)?;
+
+        let (page_width, page_height) = options.page_size.points();
+        let title = if self.project_name.is_empty() {
+            "Untitled"
+        } else {
+            self.project_name.as_str()
+        };
+
+        // Fit the composition below the heading, keeping its aspect ratio
+        let top = page_height - MARGIN - HEADING_SIZE * 2.0;
+        let fit = ((page_width - 2.0 * MARGIN) / scene.size.x).min((top - MARGIN) / scene.size.y);
+        let (width, height) = (scene.size.x * fit, scene.size.y * fit);
+        let x = (page_width - width) / 2.0;
+        let mut form_page = heading(title, page_height);
+        form_page.push_str(&format!(
+            "q {:.2} 0 0 {:.2} {:.2} {:.2} cm /Im1 Do Q\n",
+            width,
+            height,
+            x,
+            top - height
+        ));
+        let mut pages = vec![form_page];
+
+        if options.appendix {
+            pages.extend(self.appendix_pages(options, title, page_width, page_height));
+        }
+
+        debug!(pages = pages.len(), image = ?image.dimensions(), "Built print document");
+        Ok(write_pdf(&pages, &jpeg, image.dimensions(), (page_width, page_height)))
+    }
+
+    /// Save a PDF document of the canvas
+    ///
+    /// See [`Self::to_pdf`] for the layout and possible errors.
+    #[instrument(skip(self, options), fields(output_path))]
+    pub fn export_pdf(&self, output_path: &str, options: &PrintOptions) -> Result<(), CanvasError> {
+        let pdf = self.to_pdf(options)?;
+        std::fs::write(output_path, pdf)
+            .map_err(|e| CanvasError::io(e, output_path, IoOperation::Write, line!(), file!()))?;
+        tracing::info!("Printed canvas to {}", output_path);
+        Ok(())
+    }
+
+    /// Content streams of the field appendix
+    fn appendix_pages(&self, options: &PrintOptions, title: &str, page_width: f32, page_height: f32) -> Vec<String> {
+        let mut rows: Vec<(&str, &str)> = Vec::new();
+        if options.includes(LayerType::Shapes) {
+            rows.extend(self.shapes.iter().map(|shape| (Shape::name(shape), "Field")));
+        }
+        if options.includes(LayerType::Detections) {
+            rows.extend(self.detections.iter().map(|shape| (Shape::name(shape), "Detection")));
+        }
+
+        let first_row = page_height - MARGIN - HEADING_SIZE * 2.0 - ROW_LEADING;
+        let per_page = (((first_row - MARGIN) / ROW_LEADING) as usize + 1).max(1);
+        let value_x = MARGIN + 330.0;
+        let heading_text = format!("{} - Field Values", title);
+
+        let mut pages = Vec::new();
+        let chunks: Vec<&[(&str, &str)]> = if rows.is_empty() {
+            vec![&[]]
+        } else {
+            rows.chunks(per_page).collect()
+        };
+        for chunk in chunks {
+            let mut page = heading(&heading_text, page_height);
+            if chunk.is_empty() {
+                page.push_str(&text(MARGIN, first_row, ROW_SIZE, "No fields in the printed layers"));
+            }
+            for (i, (name, kind)) in chunk.iter().enumerate() {
+                let y = first_row - i as f32 * ROW_LEADING;
+                let name = match name.chars().count() {
+                    0 => "(unnamed)".to_string(),
+                    n if n > MAX_NAME_CHARS => {
+                        format!("{}...", name.chars().take(MAX_NAME_CHARS - 3).collect::<String>())
+                    }
+                    _ => name.to_string(),
+                };
+                page.push_str(&text(MARGIN, y, ROW_SIZE, &name));
+                page.push_str(&text(MARGIN + 260.0, y, ROW_SIZE, kind));
+                page.push_str(&format!(
+                    "0.5 w {:.2} {:.2} m {:.2} {:.2} l S\n",
+                    value_x,
+                    y - 2.0,
+                    page_width - MARGIN,
+                    y - 2.0
+                ));
+            }
+            pages.push(page);
+        }
+        pages
+    }
+
+    /// Toggle the print window
+    pub fn toggle_print_panel(&mut self) {
+        self.show_print = !self.show_print;
+    }
+
+    /// Show the print window
+    ///
+    /// Printing queues [`CanvasAction::ExportPdf`] for the application to
+    /// pick a file. Returns true if the window was shown.
+    pub fn show_print_panel(&mut self, ctx: &egui::Context) -> bool {
+        if !self.show_print {
+            return false;
+        }
+
+        let mut panel_open = true;
+        let mut print = false;
+        let options = &mut self.print_options;
+        egui::Window::new("Print")
+            .open(&mut panel_open)
+            .resizable(false)
+            .default_width(260.0)
+            .show(ctx, |ui| {
+                ui.label("Layers");
+                for (layer, label) in [
+                    (LayerType::Canvas, "Form image"),
+                    (LayerType::Highlights, "Highlights"),
+                    (LayerType::Detections, "Detections"),
+                    (LayerType::Shapes, "Fields"),
+                ] {
+                    let mut include = options.includes(layer);
+                    if ui.checkbox(&mut include, label).changed() {
+                        options.set_layer(layer, include);
+                    }
+                }
+                ui.separator();
+                ui.checkbox(&mut options.appendix, "Field value appendix");
+                ui.horizontal(|ui| {
+                    ui.label("Paper:");
+                    for size in <PageSize as strum::IntoEnumIterator>::iter() {
+                        ui.selectable_value(&mut options.page_size, size, size.to_string());
+                    }
+                });
+                ui.add(
+                    egui::Slider::new(&mut options.scale, 0.5..=4.0)
+                        .text("Resolution")
+                        .logarithmic(true),
+                );
+                ui.separator();
+                if ui.button("Print to PDF...").clicked() {
+                    print = true;
+                }
+            });
+
+        if print {
+            debug!(options = ?self.print_options, "Print requested");
+            self.pending_actions.push(CanvasAction::ExportPdf);
+            self.show_print = false;
+        } else if !panel_open {
+            self.show_print = false;
+        }
+
+        true
+    }
+}
+
+/// Content stream drawing a page heading
+fn heading(title: &str, page_height: f32) -> String {
+    text(MARGIN, page_height - MARGIN - HEADING_SIZE, HEADING_SIZE, title)
+}
+
+/// Encode an image for embedding in a PDF
+pub(super) fn encode_jpeg(image: &image::RgbImage) -> Result<Vec<u8>, CanvasError> {
+    let mut jpeg = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, JPEG_QUALITY)
+        .encode_image(image)
+        .map_err(|e| CanvasError::new(CanvasErrorKind::ImageLoad(e.to_string()), line!(), file!()))?;
+    Ok(jpeg)
+}
+
+/// Content stream drawing one line of Helvetica text
+pub(super) fn text(x: f32, y: f32, size: f32, content: &str) -> String {
+    format!(
+        "BT /F1 {} Tf {:.2} {:.2} Td ({}) Tj ET\n",
+        size,
+        x,
+        y,
+        pdf_string(content)
+    )
+}
+
+/// Escape text for a PDF literal string in WinAnsi encoding
+///
+/// Characters outside Latin-1 are replaced with `?`.
+fn pdf_string(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    for c in content.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            '\u{a0}'..='\u{ff}' => out.push_str(&format!("\\{:03o}", c as u32)),
+            _ => out.push('?'),
+        }
+    }
+    out
+}
+
+/// Assemble a PDF with one image XObject shown on the first page
+///
+/// Objects 1-4 are the catalog, page tree, font and image; each page then
+/// takes two objects, the page and its content stream.
+pub(super) fn write_pdf(
+    pages: &[String],
+    jpeg: &[u8],
+    (image_width, image_height): (u32, u32),
+    (width, height): (f32, f32),
+) -> Vec<u8> {
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 5 + 2 * i).collect();
+    let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+
+    let mut objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()).into_bytes(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_vec(),
+    ];
+    let mut image = format!(
+        "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /DCTDecode /Length {} >>\nstream\n",
+        image_width,
+        image_height,
+        jpeg.len()
+    )
+    .into_bytes();
+    image.extend_from_slice(jpeg);
+    image.extend_from_slice(b"\nendstream");
+    objects.push(image);
+
+    for (page, content) in page_ids.iter().zip(pages) {
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R >> /XObject << /Im1 4 0 R >> >> /Contents {} 0 R >>",
+                width,
+                height,
+                page + 1
+            )
+            .into_bytes(),
+        );
+        objects.push(format!("<< /Length {} >>\nstream\n{}endstream", content.len(), content).into_bytes());
+    }
+
+    let mut pdf = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+
+    let xref = pdf.len();
+    pdf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .as_bytes(),
+    );
+    pdf
+}
@@ -0,0 +1,649 @@
+//! Project templates and the new-project window
+//!
+//! A template is a project minus its form image and detections: the layer
+//! setup, the OCR settings and the field overlays a form type always needs.
+//! Templates are saved from the current project and live as JSON files in
+//! [`project_templates_dir`]. Starting a new project from one gives a canvas
+//! ready for the next form of that type instead of a blank untitled canvas.
+//!
+//! Each template counts the projects started from it. A deprecated template
+//! is hidden from the new-project window so no new projects use it, but
+//! projects already made from it keep their fields and template name.
+
+use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas};
+use super::guides::Guide;
+use super::template_browser::{TemplateSortOrder, template_departments, template_languages, template_tags};
+use super::template_page::{PageMatch, TemplatePage};
+use crate::{FieldGroup, LayerManager, OcrSettings, Shape, config_dir};
+use form_factor_core::IoOperation;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use std::path::{Path, PathBuf};
+use tracing::{debug, error, info, instrument, warn};
+
+/// Directory project templates are saved in
+pub fn project_templates_dir() -> PathBuf {
+    config_dir().join("project_templates")
+}
+
+/// Starting point for new projects of one form type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTemplate {
+    /// Template name, shown in the new-project window
+    pub name: String,
+    /// What the template is for
+    #[serde(default)]
+    pub description: String,
+    /// Number of the form the template is for, like `W-2` or `I-9`
+    #[serde(default)]
+    pub form_number: String,
+    /// Department the template belongs to
+    #[serde(default)]
+    pub department: String,
+    /// Labels for finding the template
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Layer visibility and locks
+    #[serde(default)]
+    pub layers: LayerManager,
+    /// OCR settings for projects made from the template, or `None` for the app settings
+    #[serde(default)]
+    pub ocr: Option<OcrSettings>,
+    /// Field overlays, in canvas coordinates
+    #[serde(default)]
+    pub fields: Vec<Shape>,
+    /// Guide lines fields snap to
+    #[serde(default)]
+    pub guides: Vec<Guide>,
+    /// Order data entry visits the fields in, as indices into `fields`
+    #[serde(default)]
+    pub tab_order: Vec<usize>,
+    /// Field groups and repeating sections
+    #[serde(default)]
+    pub groups: Vec<FieldGroup>,
+    /// Page layouts of a multi-page template, empty for a single page
+    #[serde(default)]
+    pub pages: Vec<TemplatePage>,
+    /// How packet pages pick their template page
+    #[serde(default)]
+    pub page_match: PageMatch,
+    /// Number of projects started from the template
+    #[serde(default)]
+    pub instances: u64,
+    /// Whether the template is retired from starting new projects
+    #[serde(default)]
+    pub deprecated: bool,
+}
+
+impl ProjectTemplate {
+    /// Empty template with default layers and no fields
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: String::new(),
+            form_number: String::new(),
+            department: String::new(),
+            tags: Vec::new(),
+            layers: LayerManager::default(),
+            ocr: None,
+            fields: Vec::new(),
+            guides: Vec::new(),
+            tab_order: Vec::new(),
+            groups: Vec::new(),
+            pages: Vec::new(),
+            page_match: PageMatch::default(),
+            instances: 0,
+            deprecated: false,
+        }
+    }
+
+    /// Template reproducing the setup of an existing project
+    ///
+    /// Keeps the layers, OCR settings, fields, groups, guides and tab order; drops the form
+    /// image, detections, comments and statistics.
+    pub fn from_canvas(name: impl Into<String>, canvas: &DrawingCanvas) -> Self {
+        Self {
+            name: name.into(),
+            description: String::new(),
+            form_number: String::new(),
+            department: String::new(),
+            tags: Vec::new(),
+            layers: canvas.layer_manager.clone(),
+            ocr: canvas.ocr_settings.clone(),
+            fields: canvas.shapes.clone(),
+            guides: canvas.guides.clone(),
+            tab_order: canvas.tab_order.clone(),
+            groups: canvas.field_groups.clone(),
+            pages: Vec::new(),
+            page_match: PageMatch::default(),
+            instances: 0,
+            deprecated: false,
+        }
+    }
+
+    /// Set the description
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Set the OCR settings of projects made from the template
+    pub fn with_ocr(mut self, ocr: OcrSettings) -> Self {
+        self.ocr = Some(ocr);
+        self
+    }
+
+    /// Retire the template from starting new projects, or bring it back
+    pub fn with_deprecated(mut self, deprecated: bool) -> Self {
+        self.deprecated = deprecated;
+        self
+    }
+
+    /// Count a project started from the template and save the count in `dir`
+    ///
+    /// Returns the path the template was saved to.
+    pub fn record_instance(&mut self, dir: &Path) -> Result<PathBuf, CanvasError> {
+        self.instances += 1;
+        debug!(template = %self.name, instances = self.instances, "Recorded template use");
+        self.save_in(dir)
+    }
+
+    /// File name for the template, from its name
+    pub fn file_name(&self) -> String {
+        let slug: String = self
+            .name
+            .trim()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let slug = if slug.is_empty() { "template".to_string() } else { slug };
+        format!("{}.json", slug)
+    }
+
+    /// Save the template as JSON
+    #[instrument(skip(self), fields(name = %self.name, fields = self.fields.len()))]
+    pub fn save_to(&self, path: &Path) -> Result<(), CanvasError> {
+        let path_str = path.to_string_lossy().into_owned();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                CanvasError::io(
+                    e,
+                    parent.to_string_lossy().into_owned(),
+                    IoOperation::Create,
+                    line!(),
+                    file!(),
+                )
+            })?;
+        }
+
+        let json = serde_json::to_string_pretty(self).map_err(|e| {
+            CanvasError::new(CanvasErrorKind::Serialization(e.to_string()), line!(), file!())
+                .with_path(path_str.as_str(), IoOperation::Write)
+        })?;
+        std::fs::write(path, json)
+            .map_err(|e| CanvasError::io(e, path_str.as_str(), IoOperation::Write, line!(), file!()))?;
+
+        info!("Saved project template to {}", path_str);
+        Ok(())
+    }
+
+    /// Save the template in `dir` under its [`file_name`](Self::file_name)
+    ///
+    /// Returns the path it was saved to, replacing any template of the same name.
+    pub fn save_in(&self, dir: &Path) -> Result<PathBuf, CanvasError> {
+        let path = dir.join(self.file_name());
+        self.save_to(&path)?;
+        Ok(path)
+    }
+
+    /// Load a template saved with [`save_to`](Self::save_to)
+    #[instrument]
+    pub fn load_from(path: &Path) -> Result<Self, CanvasError> {
+        let path_str = path.to_string_lossy().into_owned();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| CanvasError::io(e, path_str.as_str(), IoOperation::Read, line!(), file!()))?;
+        serde_json::from_str(&json).map_err(|e| {
+            CanvasError::new(CanvasErrorKind::Deserialization(e.to_string()), line!(), file!())
+                .with_path(path_str.as_str(), IoOperation::Read)
+        })
+    }
+
+    /// All templates in `dir`, sorted by name
+    ///
+    /// Files that fail to load are skipped with a warning; a missing
+    /// directory has no templates.
+    #[instrument]
+    pub fn load_all(dir: &Path) -> Vec<Self> {
+        let mut templates: Vec<Self> =
+            Self::template_files(dir).iter().filter_map(|path| Self::load_or_warn(path)).collect();
+        Self::sort(&mut templates);
+        templates
+    }
+
+    /// Paths of the template files in `dir`
+    pub(super) fn template_files(dir: &Path) -> Vec<PathBuf> {
+        match std::fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+                .collect(),
+            Err(e) => {
+                debug!(error = %e, "No project templates");
+                Vec::new()
+            }
+        }
+    }
+
+    /// Load a template, or warn and skip it
+    pub(super) fn load_or_warn(path: &Path) -> Option<Self> {
+        Self::load_from(path)
+            .map_err(|e| warn!("Skipping project template {}: {}", path.display(), e))
+            .ok()
+    }
+
+    /// Sort templates by name, ignoring case
+    pub(super) fn sort(templates: &mut [Self]) {
+        templates.sort_by_key(|template| template.name.to_lowercase());
+    }
+}
+
+impl DrawingCanvas {
+    /// Replace the project with a new one set up from `template`
+    ///
+    /// The form image, detections, comments and statistics are cleared;
+    /// settings and other application state are kept.
+    #[instrument(skip(self, project_name, template, ctx), fields(template = %template.name))]
+    pub fn start_project_from_template(
+        &mut self,
+        project_name: impl Into<String>,
+        template: &ProjectTemplate,
+        ctx: &egui::Context,
+    ) {
+        let mut project = DrawingCanvas::new();
+        project.project_name = project_name.into();
+        project.layer_manager = template.layers.clone();
+        project.ocr_settings = template.ocr.clone();
+        project.shapes = template.fields.clone();
+        project.guides = template.guides.clone();
+        project.tab_order = template.tab_order.clone();
+        project.field_groups = template.groups.clone();
+        project.fill_section_rows();
+        project.template_name = Some(template.name.clone());
+        // A multi-page template starts on its first page
+        project.apply_template_page(template, 0);
+
+        self.apply_project(project, ctx, false);
+        self.project_password = None;
+        self.selected_shape = None;
+        self.show_properties = false;
+        info!("Started project {} from template", self.project_name);
+    }
+
+    /// Replace the project with a blank untitled one
+    pub fn start_blank_project(&mut self, ctx: &egui::Context) {
+        self.start_project_from_template("Untitled", &ProjectTemplate::new("Blank"), ctx);
+        self.template_name = None;
+    }
+
+    /// Set the name of the template the project belongs to, or `None` for none
+    pub fn set_template_name(&mut self, name: Option<String>) {
+        self.template_name = name;
+    }
+
+    /// Set the OCR settings of this project, or `None` to use the app settings
+    pub fn set_ocr_settings(&mut self, ocr: Option<OcrSettings>) {
+        self.ocr_settings = ocr;
+    }
+
+    /// Open the new-project window, reloading the saved templates in the background
+    pub fn open_new_project_panel(&mut self) {
+        self.load_project_templates_lazily(&project_templates_dir());
+        self.selected_template = None;
+        self.new_project_name = String::from("Untitled");
+        self.show_new_project = true;
+    }
+
+    /// Close the new-project window and its template browser
+    pub fn close_new_project_panel(&mut self) {
+        self.show_new_project = false;
+    }
+
+    /// Toggle the new-project window
+    pub fn toggle_new_project_panel(&mut self) {
+        if self.show_new_project {
+            self.close_new_project_panel();
+        } else {
+            self.open_new_project_panel();
+        }
+    }
+
+    /// Show the new-project window
+    ///
+    /// Lists a blank project and the saved templates, and saves the current
+    /// project as a template. Returns true if the window was shown.
+    pub fn show_new_project_panel(&mut self, ctx: &egui::Context) -> bool {
+        if !self.show_new_project {
+            return false;
+        }
+
+        let templates_loaded = self.poll_project_templates(ctx);
+        let mut panel_open = true;
+        let mut create = false;
+        let mut save_template = false;
+        let mut add_page = false;
+        let mut apply_page = false;
+        let mut toggle_deprecated = false;
+        let mut save_details = false;
+        egui::Window::new("New Project")
+            .open(&mut panel_open)
+            .resizable(false)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut self.new_project_name);
+                });
+                ui.separator();
+
+                ui.label("Start from");
+                if let Some(progress) = self.project_templates_loading() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        match progress.total {
+                            0 => ui.label("Finding templates..."),
+                            total => ui.label(format!("Loading templates: {} of {}", progress.loaded, total)),
+                        };
+                    });
+                    if progress.total > 0 {
+                        ui.add(egui::ProgressBar::new(progress.loaded as f32 / progress.total as f32));
+                    }
+                }
+                if !self.project_templates.is_empty() {
+                    self.show_template_filters(ui);
+                }
+                let listed = self.template_filter.apply(&self.project_templates, self.template_sort);
+                // Filtering out the selected template goes back to a blank project
+                if self.selected_template.is_some_and(|i| !listed.contains(&i)) {
+                    self.selected_template = None;
+                }
+                egui::ScrollArea::vertical().max_height(180.0).show(ui, |ui| {
+                    ui.radio_value(&mut self.selected_template, None, "Blank project");
+                    for i in listed {
+                        let template = &self.project_templates[i];
+                        let mut label = format!("{} ({})", template.name, template.instances);
+                        if template.deprecated {
+                            label.push_str(" - deprecated");
+                        }
+                        ui.radio_value(&mut self.selected_template, Some(i), label)
+                            .on_hover_text(template_summary(template));
+                    }
+                });
+                if templates_loaded && self.project_templates.is_empty() {
+                    ui.weak("No templates yet. Save a project as a template below.");
+                }
+                if let Some(template) = self.selected_template.and_then(|i| self.project_templates.get(i)) {
+                    ui.label(template_summary(template));
+                    let (label, hover) = if template.deprecated {
+                        ("Restore", "Offer the template for new projects again")
+                    } else {
+                        ("Deprecate", "Stop offering the template for new projects; existing projects are kept")
+                    };
+                    toggle_deprecated = ui.button(label).on_hover_text(hover).clicked();
+                    if !template.pages.is_empty() {
+                        ui.horizontal(|ui| {
+                            ui.label("Packet page:");
+                            ui.add(egui::DragValue::new(&mut self.template_packet_page).custom_formatter(|n, _| {
+                                format!("{}", n as usize + 1)
+                            }));
+                            apply_page = ui
+                                .button("Apply to Current Form")
+                                .on_hover_text("Replace the fields with the matching page of the template")
+                                .clicked();
+                        });
+                    }
+                }
+                save_details = self.show_template_details(ui);
+
+                ui.separator();
+                let deprecated = self
+                    .selected_template
+                    .and_then(|i| self.project_templates.get(i))
+                    .is_some_and(|template| template.deprecated);
+                if ui.add_enabled(!deprecated, egui::Button::new("Create")).clicked() {
+                    create = true;
+                }
+
+                ui.separator();
+                ui.label("Save the current project as a template");
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.new_template_name).hint_text("Template name"));
+                    let named = !self.new_template_name.trim().is_empty();
+                    save_template = ui.add_enabled(named, egui::Button::new("Save")).clicked();
+                    add_page = ui
+                        .add_enabled(named, egui::Button::new("Add as Page"))
+                        .on_hover_text("Add the project as a page of a multi-page template")
+                        .clicked();
+                });
+                let mut preview = self.template_preview;
+                if ui.checkbox(&mut preview, "Preview fields with sample data").changed() {
+                    self.set_template_preview(preview);
+                }
+                if self.template_preview {
+                    let small = self.preview_values().iter().filter(|value| !value.fits).count();
+                    if small > 0 {
+                        let warning = format!("{} field(s) too small for sample data", small);
+                        ui.colored_label(ui.visuals().warn_fg_color, warning);
+                    }
+                }
+                egui::ComboBox::from_label("Match pages by")
+                    .selected_text(self.new_template_page_match.to_string())
+                    .show_ui(ui, |ui| {
+                        for page_match in PageMatch::iter() {
+                            ui.selectable_value(&mut self.new_template_page_match, page_match, page_match.to_string());
+                        }
+                    });
+            });
+
+        if save_template {
+            let mut template = ProjectTemplate::from_canvas(self.new_template_name.trim(), self);
+            // Replacing a template keeps its usage
+            if let Some(existing) = self.project_templates.iter().find(|existing| existing.name == template.name) {
+                template.instances = existing.instances;
+                template.deprecated = existing.deprecated;
+            }
+            match template.save_in(&project_templates_dir()) {
+                Ok(_) => {
+                    self.new_template_name.clear();
+                    self.load_project_templates_lazily(&project_templates_dir());
+                }
+                Err(e) => error!("Failed to save project template: {}", e),
+            }
+        }
+
+        if add_page {
+            let name = self.new_template_name.trim().to_string();
+            let mut template = self
+                .project_templates
+                .iter()
+                .find(|template| template.name == name)
+                .cloned()
+                .unwrap_or_else(|| ProjectTemplate {
+                    fields: Vec::new(),
+                    tab_order: Vec::new(),
+                    groups: Vec::new(),
+                    ..ProjectTemplate::from_canvas(name.as_str(), self)
+                });
+            template.page_match = self.new_template_page_match;
+            template.add_page(TemplatePage::from_canvas(self.project_name.clone(), self));
+            match template.save_in(&project_templates_dir()) {
+                Ok(_) => {
+                    info!("Added page {} to template {} ({} pages)", self.project_name, name, template.pages.len());
+                    self.load_project_templates_lazily(&project_templates_dir());
+                }
+                Err(e) => error!("Failed to save project template: {}", e),
+            }
+        }
+
+        if save_details
+            && let Some(template) = self.selected_template.and_then(|i| self.project_templates.get(i))
+            && let Err(e) = template.save_in(&project_templates_dir())
+        {
+            error!("Failed to save project template: {}", e);
+        }
+
+        if toggle_deprecated
+            && let Some(template) = self.selected_template.and_then(|i| self.project_templates.get_mut(i))
+        {
+            template.deprecated = !template.deprecated;
+            info!(template = %template.name, deprecated = template.deprecated, "Changed template deprecation");
+            if let Err(e) = template.save_in(&project_templates_dir()) {
+                error!("Failed to save project template: {}", e);
+            }
+        }
+
+        let selected = self.selected_template.and_then(|i| self.project_templates.get(i).cloned());
+        if apply_page && let Some(template) = selected {
+            self.apply_matching_template_page(&template, self.template_packet_page);
+            self.show_new_project = false;
+        }
+
+        if create {
+            let template = self
+                .selected_template
+                .and_then(|i| self.project_templates.get(i).cloned())
+                .unwrap_or_else(|| ProjectTemplate::new("Blank"));
+            let name = match self.new_project_name.trim() {
+                "" => "Untitled".to_string(),
+                name => name.to_string(),
+            };
+            self.start_project_from_template(name, &template, ctx);
+            if let Some(used) = self.selected_template.and_then(|i| self.project_templates.get_mut(i))
+                && let Err(e) = used.record_instance(&project_templates_dir())
+            {
+                warn!("Failed to record use of template {}: {}", used.name, e);
+            }
+            self.show_new_project = false;
+        } else if !panel_open {
+            self.show_new_project = false;
+        }
+
+        true
+    }
+}
+
+impl DrawingCanvas {
+    /// Search box, filters and sort order for the template list
+    fn show_template_filters(&mut self, ui: &mut egui::Ui) {
+        ui.add(
+            egui::TextEdit::singleline(&mut self.template_filter.query)
+                .hint_text("Search name, form number, tags...")
+                .desired_width(f32::INFINITY),
+        );
+        let tags = template_tags(&self.project_templates);
+        let departments = template_departments(&self.project_templates);
+        let languages = template_languages(&self.project_templates);
+        let filter = &mut self.template_filter;
+        ui.horizontal_wrapped(|ui| {
+            filter_combo(ui, "template_tag", "Any tag", &mut filter.tag, &tags);
+            filter_combo(ui, "template_department", "Any department", &mut filter.department, &departments);
+            filter_combo(ui, "template_language", "Any language", &mut filter.language, &languages);
+            egui::ComboBox::from_id_salt("template_sort")
+                .selected_text(format!("Sort: {}", self.template_sort))
+                .show_ui(ui, |ui| {
+                    for order in TemplateSortOrder::iter() {
+                        ui.selectable_value(&mut self.template_sort, order, order.to_string());
+                    }
+                });
+        });
+        if self.project_templates.iter().any(|template| template.deprecated) {
+            ui.checkbox(&mut self.template_filter.include_deprecated, "Show deprecated templates");
+        }
+    }
+
+    /// Editor for the selected template's form number, department and tags
+    ///
+    /// Returns true if the changes should be saved.
+    fn show_template_details(&mut self, ui: &mut egui::Ui) -> bool {
+        let Some(template) = self.selected_template.and_then(|i| self.project_templates.get_mut(i)) else {
+            return false;
+        };
+        let mut save = false;
+        egui::CollapsingHeader::new("Details").id_salt("template_details").show(ui, |ui| {
+            egui::Grid::new("template_details_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Form number:");
+                ui.text_edit_singleline(&mut template.form_number);
+                ui.end_row();
+                ui.label("Department:");
+                ui.text_edit_singleline(&mut template.department);
+                ui.end_row();
+            });
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Tags:");
+                let mut removed = None;
+                for tag in &template.tags {
+                    if ui.small_button(format!("{} ×", tag)).on_hover_text("Remove tag").clicked() {
+                        removed = Some(tag.clone());
+                    }
+                }
+                if let Some(tag) = removed {
+                    template.remove_tag(&tag);
+                }
+            });
+            ui.horizontal(|ui| {
+                let response = ui.add(egui::TextEdit::singleline(&mut self.template_tag_draft).hint_text("New tag"));
+                let entered = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if (ui.button("Add Tag").clicked() || entered) && template.add_tag(self.template_tag_draft.as_str()) {
+                    self.template_tag_draft.clear();
+                }
+            });
+            save = ui.button("Save Details").clicked();
+        });
+        save
+    }
+}
+
+/// Combo box choosing one of `values`, or none
+fn filter_combo(ui: &mut egui::Ui, id: &str, any: &str, chosen: &mut Option<String>, values: &[String]) {
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(chosen.as_deref().unwrap_or(any))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(chosen, None, any);
+            for value in values {
+                ui.selectable_value(chosen, Some(value.clone()), value.as_str());
+            }
+        });
+}
+
+/// One-line description of what a template sets up
+fn template_summary(template: &ProjectTemplate) -> String {
+    let mut summary = format!(
+        "{} field(s), {} guide(s), used by {} project(s)",
+        template.fields.len(),
+        template.guides.len(),
+        template.instances
+    );
+    if !template.pages.is_empty() {
+        summary.push_str(&format!(", {} page(s) matched by {}", template.pages.len(), template.page_match));
+    }
+    if let Some(ocr) = &template.ocr {
+        summary.push_str(&format!(", OCR {} at {}%", ocr.language, ocr.min_confidence));
+    }
+    if !template.tags.is_empty() {
+        summary.push_str(&format!("\nTags: {}", template.tags.join(", ")));
+    }
+    if !template.description.is_empty() {
+        summary = format!("{}\n{}", template.description, summary);
+    }
+    let heading: Vec<&str> =
+        [template.form_number.as_str(), template.department.as_str()].into_iter().filter(|s| !s.is_empty()).collect();
+    if !heading.is_empty() {
+        summary = format!("{}\n{}", heading.join(" · "), summary);
+    }
+    summary
+}
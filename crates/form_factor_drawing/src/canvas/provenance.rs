@@ -0,0 +1,136 @@
+//! Where a project and its extracted data came from
+//!
+//! Audits ask which scan a project was made from, how the scan got into the
+//! app, and which detectors and OCR engine produced its detections and text.
+//! New projects record the app version and time they were created. Loading
+//! a form image records its path, a hash of its contents and how it arrived,
+//! and each detection and OCR pass records the configuration it ran with.
+//! Provenance is saved with the project; projects saved before it was
+//! recorded show it as unknown.
+
+use super::core::DrawingCanvas;
+use super::detection_cache::content_hash;
+use crate::utc::UtcDateTime;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use web_time::{SystemTime, UNIX_EPOCH};
+
+/// How a form image came into a project
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, strum::Display)]
+pub enum SourceChannel {
+    /// Not recorded
+    #[default]
+    Unknown,
+    /// Opened from a file on disk
+    Opened,
+    /// Dropped onto the window
+    Dropped,
+    /// Pasted from the clipboard
+    Pasted,
+    /// Read from bytes, such as a file uploaded to the browser
+    Uploaded,
+}
+
+/// Where a project's form image, detections and text came from
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Path or name of the form image when it was loaded
+    #[serde(default)]
+    pub source_path: Option<String>,
+    /// FNV-1a hash of the form image file, in hex
+    #[serde(default)]
+    pub source_hash: Option<String>,
+    /// How the form image came into the project
+    #[serde(default)]
+    pub channel: SourceChannel,
+    /// Configuration of the last text detection, including the model
+    #[serde(default)]
+    pub text_detector: Option<String>,
+    /// Configuration of the last logo detection, including the templates
+    #[serde(default)]
+    pub logo_detector: Option<String>,
+    /// Configuration of the OCR engine that last read the detections
+    #[serde(default)]
+    pub ocr_engine: Option<String>,
+    /// Version of the app that created the project, or empty if not recorded
+    #[serde(default)]
+    pub app_version: String,
+    /// When the project was created, in seconds since the Unix epoch, or 0 if not recorded
+    #[serde(default)]
+    pub created_at: u64,
+}
+
+impl Provenance {
+    /// Provenance of a project created now by this version of the app
+    pub fn new() -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Self {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at,
+            ..Self::default()
+        }
+    }
+
+    /// Label and value of each entry, for display
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        let or_unknown = |value: &Option<String>| value.clone().unwrap_or_else(|| "unknown".to_string());
+        let app_version = match self.app_version.as_str() {
+            "" => "unknown".to_string(),
+            version => version.to_string(),
+        };
+        let created_at = match self.created_at {
+            0 => "unknown".to_string(),
+            secs => utc_timestamp(secs),
+        };
+        vec![
+            ("Source", or_unknown(&self.source_path)),
+            ("Source hash", or_unknown(&self.source_hash)),
+            ("Channel", self.channel.to_string()),
+            ("Text detector", or_unknown(&self.text_detector)),
+            ("Logo detector", or_unknown(&self.logo_detector)),
+            ("OCR engine", or_unknown(&self.ocr_engine)),
+            ("App version", app_version),
+            ("Created", created_at),
+        ]
+    }
+}
+
+/// Seconds since the Unix epoch as a UTC date and time, like `2024-03-01 09:30 UTC`
+fn utc_timestamp(secs: u64) -> String {
+    let t = UtcDateTime::from_unix(secs);
+    format!("{:04}-{:02}-{:02} {:02}:{:02} UTC", t.year, t.month, t.day, t.hour, t.minute)
+}
+
+impl DrawingCanvas {
+    /// Record the form image just loaded from `bytes` and how it arrived
+    pub(super) fn record_source(&mut self, path: &str, bytes: &[u8], channel: SourceChannel) {
+        let hash = format!("{:016x}", content_hash(bytes));
+        debug!(path, %hash, %channel, "Recorded form image source");
+        self.provenance.source_path = Some(path.to_string());
+        self.provenance.source_hash = Some(hash);
+        self.provenance.channel = channel;
+    }
+
+    /// Record the configuration of the OCR engine that read the detections
+    pub fn record_ocr_engine(&mut self, engine: impl Into<String>) {
+        self.provenance.ocr_engine = Some(engine.into());
+    }
+
+    /// Show the project's provenance as a collapsed section
+    pub(super) fn show_provenance(&self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Project Source")
+            .id_salt("project_provenance")
+            .show(ui, |ui| {
+                egui::Grid::new("project_provenance_grid").num_columns(2).show(ui, |ui| {
+                    for (label, value) in self.provenance.entries() {
+                        ui.label(label);
+                        ui.add(egui::Label::new(value).wrap());
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+}
@@ -0,0 +1,156 @@
+//! Redaction of sensitive fields and detected PII
+//!
+//! Sensitive shapes are stored in canvas coordinates, while detections are
+//! stored in image pixel coordinates. Mapping shapes back to the image uses
+//! the transform recorded during the last rendered frame.
+
+use super::command::CanvasCommand;
+use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas};
+use crate::{CanvasPos, PiiKind, RedactionReason, RedactionRegion, detect_pii, redact_image};
+use egui::Rect;
+use form_factor_core::IoOperation;
+use tracing::{debug, instrument, warn};
+
+impl DrawingCanvas {
+    /// Toggle the on-canvas redaction preview
+    pub fn toggle_redaction_mode(&mut self) {
+        self.apply_command(CanvasCommand::SetRedactionMode(!self.redaction_mode));
+        debug!(redaction_mode = self.redaction_mode, "Toggled redaction mode");
+    }
+
+    /// Enable or disable the on-canvas redaction preview
+    pub fn set_redaction_mode(&mut self, enabled: bool) {
+        self.apply_command(CanvasCommand::SetRedactionMode(enabled));
+    }
+
+    /// Scan OCR text from a detection for PII and record any matches
+    ///
+    /// Previous flags for the detection are replaced. Returns the kinds of
+    /// PII found.
+    pub fn flag_pii(&mut self, detection_index: usize, text: &str) -> Vec<PiiKind> {
+        self.pii_flags.retain(|(idx, _)| *idx != detection_index);
+
+        if detection_index >= self.detections.len() {
+            warn!(detection_index, "Cannot flag PII on missing detection");
+            return Vec::new();
+        }
+
+        let kinds = detect_pii(text);
+        for kind in &kinds {
+            self.pii_flags.push((detection_index, *kind));
+        }
+
+        if !kinds.is_empty() {
+            debug!(detection_index, ?kinds, "Flagged PII in detection");
+        }
+        kinds
+    }
+
+    /// Remove all PII flags
+    pub fn clear_pii_flags(&mut self) {
+        self.pii_flags.clear();
+    }
+
+    /// Collect every region to redact, in image pixel coordinates
+    ///
+    /// Sensitive shapes are only included once the canvas has been rendered
+    /// with a form image, since that frame defines how canvas coordinates map
+    /// onto the image. Exports check [`DrawingCanvas::unmapped_sensitive_shapes`]
+    /// first, so this only leaves them out of the on-canvas preview.
+    pub fn redaction_regions(&self) -> Vec<RedactionRegion> {
+        let mut regions: Vec<RedactionRegion> = self
+            .pii_flags
+            .iter()
+            .filter_map(|(idx, kind)| {
+                self.detections
+                    .get(*idx)
+                    .map(|detection| RedactionRegion::new(detection.bounding_rect(), RedactionReason::Pii(*kind)))
+            })
+            .collect();
+
+        let sensitive: Vec<Rect> = self
+            .shapes
+            .iter()
+            .filter(|shape| shape.is_sensitive())
+            .map(|shape| shape.bounding_rect())
+            .collect();
+
+        if !sensitive.is_empty() {
+            match self.coordinate_mapper.image_fit() {
+                Some(_) => {
+                    let mapper = self.coordinate_mapper;
+                    regions.extend(sensitive.into_iter().filter_map(|bounds| {
+                        let min = mapper.canvas_to_image(CanvasPos(bounds.min))?;
+                        let max = mapper.canvas_to_image(CanvasPos(bounds.max))?;
+                        Some(RedactionRegion::new(
+                            Rect::from_min_max(min.0, max.0),
+                            RedactionReason::SensitiveField,
+                        ))
+                    }));
+                }
+                None => {
+                    warn!(
+                        count = sensitive.len(),
+                        "Canvas has not been rendered with a form image, skipping sensitive shapes"
+                    );
+                }
+            }
+        }
+
+        regions
+    }
+
+    /// Number of sensitive shapes that can't be mapped onto the form image yet
+    ///
+    /// Non-zero until the canvas has been rendered with the form image.
+    pub fn unmapped_sensitive_shapes(&self) -> usize {
+        if self.coordinate_mapper.image_fit().is_some() {
+            return 0;
+        }
+        self.shapes.iter().filter(|shape| shape.is_sensitive()).count()
+    }
+
+    /// Export a copy of the form image with all redaction regions blacked out
+    ///
+    /// Returns the number of regions that were redacted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No form image is loaded
+    /// - Sensitive shapes can't be mapped onto the image because the canvas
+    ///   has not been rendered with it, which would leave them unredacted
+    /// - The form image cannot be read
+    /// - The redacted image cannot be written to `output_path`
+    #[instrument(skip(self), fields(output_path, pii_flags = self.pii_flags.len()))]
+    pub fn export_redacted_image(&self, output_path: &str) -> Result<usize, CanvasError> {
+        let form_path = self.form_image_path.as_ref()
+            .ok_or_else(|| CanvasError::new(CanvasErrorKind::NoFormImageLoaded, line!(), file!()))?;
+        let unmapped = self.unmapped_sensitive_shapes();
+        if unmapped > 0 {
+            warn!(unmapped, "Refusing redacted export with unmapped sensitive shapes");
+            return Err(CanvasError::new(
+                CanvasErrorKind::InvalidExport(format!(
+                    "{} sensitive field(s) can't be redacted until the canvas has been rendered with the form image",
+                    unmapped
+                )),
+                line!(),
+                file!(),
+            ));
+        }
+
+        let mut image = image::open(form_path)
+            .map_err(|e| CanvasError::image(e, form_path.as_str(), IoOperation::Read, line!(), file!()))?
+            .to_rgba8();
+
+        let regions = self.redaction_regions();
+        let redacted = redact_image(&mut image, &regions);
+
+        image
+            .save(output_path)
+            .map_err(|e| CanvasError::image(e, output_path, IoOperation::Write, line!(), file!()))?;
+
+        tracing::info!("Exported redacted image to {} ({} regions)", output_path, redacted);
+        Ok(redacted)
+    }
+}
@@ -0,0 +1,269 @@
+//! Refilled copies of forms
+//!
+//! A refill typesets the values entered on an instance's fields onto a clean
+//! image of the blank form, producing a tidy copy of a messy handwritten
+//! original. Each value is written on one line inside its field, left
+//! aligned and shrunk until it fits. Fields without a value or whose
+//! condition isn't met are left blank, and checked checkboxes are marked
+//! with an `X`.
+//!
+//! Fields are placed through the coordinate mapper of the last rendered
+//! frame, like exports, and scaled to the clean image, so its resolution
+//! may differ from the scan the values were read from. PNG refills are
+//! rasterized with the default UI font; PDF refills keep the values as
+//! Helvetica text over the embedded image.
+
+use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas};
+use super::export::blend;
+use super::print::{MARGIN, encode_jpeg, text, write_pdf};
+use crate::{FieldFormat, PageSize, field_applies, is_checked};
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont, point};
+use egui::{Align, Color32, Pos2, Rect, Vec2};
+use form_factor_core::IoOperation;
+use image::RgbaImage;
+use tracing::{debug, instrument};
+
+/// Color of typeset values
+pub const REFILL_INK: Color32 = Color32::from_rgb(20, 20, 60);
+
+/// Share of a field's height taken by its text
+pub(super) const TEXT_HEIGHT_RATIO: f32 = 0.7;
+
+/// Space between a field's left edge and its text, as a share of its height
+pub(super) const TEXT_INSET_RATIO: f32 = 0.15;
+
+/// Smallest font size used, in pixels or points
+const MIN_FONT_SIZE: f32 = 4.0;
+
+/// Average Helvetica character width, as a share of the font size
+pub(super) const HELVETICA_CHAR_WIDTH: f32 = 0.55;
+
+/// One value to typeset
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefillValue {
+    /// Field bounds in form image pixels
+    pub bounds: Rect,
+    /// Text written in the field
+    pub text: String,
+}
+
+impl DrawingCanvas {
+    /// Values of the fields to typeset, with their bounds in form image pixels
+    ///
+    /// Hidden fields, empty values, unchecked checkboxes and fields whose
+    /// condition isn't met are left out. Line breaks become spaces.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are values to place but the canvas has not
+    /// been rendered with a form image yet.
+    pub fn refill_values(&self) -> Result<Vec<RefillValue>, CanvasError> {
+        let mut values = Vec::new();
+        for (idx, shape) in self.shapes.iter().enumerate() {
+            let value = shape.value().trim();
+            if shape.is_hidden() || value.is_empty() || !field_applies(&self.shapes, idx) {
+                continue;
+            }
+            let text = match shape.rule().format {
+                FieldFormat::Checkbox if is_checked(value) => "X".to_string(),
+                FieldFormat::Checkbox => continue,
+                _ => value.split_whitespace().collect::<Vec<_>>().join(" "),
+            };
+            let bounds = self
+                .coordinate_mapper
+                .canvas_shape_to_image(shape)
+                .ok_or_else(|| {
+                    let msg = "the canvas has not been rendered with the form image yet".to_string();
+                    CanvasError::new(CanvasErrorKind::InvalidExport(msg), line!(), file!())
+                })?
+                .bounding_rect();
+            values.push(RefillValue { bounds, text });
+        }
+        Ok(values)
+    }
+
+    /// Typeset the field values onto a clean image of the form
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::refill_values`].
+    pub fn render_refill(&self, template: &RgbaImage) -> Result<RgbaImage, CanvasError> {
+        let font = FontRef::try_from_slice(epaint_default_fonts::UBUNTU_LIGHT)
+            .map_err(|e| CanvasError::new(CanvasErrorKind::InvalidExport(e.to_string()), line!(), file!()))?;
+        let scale = self.template_scale(template);
+
+        let mut target = template.clone();
+        for value in self.refill_values()? {
+            let bounds = Rect::from_min_max(
+                (value.bounds.min.to_vec2() * scale).to_pos2(),
+                (value.bounds.max.to_vec2() * scale).to_pos2(),
+            );
+            let inset = bounds.height() * TEXT_INSET_RATIO;
+            draw_text(
+                &mut target,
+                &font,
+                bounds.shrink2(Vec2::new(inset, 0.0)),
+                &value.text,
+                bounds.height() * TEXT_HEIGHT_RATIO,
+                REFILL_INK,
+                Align::Min,
+            );
+        }
+        Ok(target)
+    }
+
+    /// Build a one-page PDF of the clean form with the field values as text
+    ///
+    /// The image is fitted within the page margins.
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::refill_values`].
+    pub fn refill_pdf(&self, template: &RgbaImage, page_size: PageSize) -> Result<Vec<u8>, CanvasError> {
+        let (page_width, page_height) = page_size.points();
+        let size = Vec2::new(template.width() as f32, template.height() as f32);
+        let fit = ((page_width - 2.0 * MARGIN) / size.x).min((page_height - 2.0 * MARGIN) / size.y);
+        let (width, height) = (size.x * fit, size.y * fit);
+        let origin = Pos2::new((page_width - width) / 2.0, (page_height - height) / 2.0);
+
+        let mut page = format!(
+            "q {:.2} 0 0 {:.2} {:.2} {:.2} cm /Im1 Do Q\n",
+            width, height, origin.x, origin.y
+        );
+        // Field pixels to points, with the PDF's y axis pointing up
+        let scale = self.template_scale(template) * fit;
+        let values = self.refill_values()?;
+        let [r, g, b, _] = REFILL_INK.to_normalized_gamma_f32();
+        page.push_str(&format!("{:.3} {:.3} {:.3} rg\n", r, g, b));
+        for value in &values {
+            let field_height = value.bounds.height() * scale.y;
+            let field_width = value.bounds.width() * scale.x;
+            let inset = field_height * TEXT_INSET_RATIO;
+            let chars = value.text.chars().count().max(1) as f32;
+            let font_size = (field_height * TEXT_HEIGHT_RATIO)
+                .min((field_width - 2.0 * inset) / (chars * HELVETICA_CHAR_WIDTH))
+                .max(MIN_FONT_SIZE);
+            let x = origin.x + value.bounds.min.x * scale.x + inset;
+            let bottom = origin.y + height - value.bounds.max.y * scale.y;
+            // Center the cap height of the text in the field
+            let y = bottom + (field_height - font_size * 0.7) / 2.0;
+            page.push_str(&text(x, y, font_size, &value.text));
+        }
+
+        let image = image::DynamicImage::ImageRgba8(template.clone()).to_rgb8();
+        let jpeg = encode_jpeg(&image)?;
+        debug!(values = values.len(), image = ?image.dimensions(), "Built refill document");
+        Ok(write_pdf(&[page], &jpeg, image.dimensions(), (page_width, page_height)))
+    }
+
+    /// Save a refilled copy of the form over the clean image at `template_path`
+    ///
+    /// Output paths ending in `.pdf` get a PDF on `page_size` paper; any
+    /// other path gets a PNG at the clean image's resolution.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the clean image cannot be read, the output
+    /// cannot be written, or under the conditions of [`Self::refill_values`].
+    #[instrument(skip(self), fields(template_path, output_path))]
+    pub fn export_refill(
+        &self,
+        template_path: &str,
+        output_path: &str,
+        page_size: PageSize,
+    ) -> Result<(), CanvasError> {
+        let template = image::open(template_path)
+            .map_err(|e| CanvasError::image(e, template_path, IoOperation::Read, line!(), file!()))?
+            .to_rgba8();
+
+        let is_pdf = std::path::Path::new(output_path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+        if is_pdf {
+            let pdf = self.refill_pdf(&template, page_size)?;
+            std::fs::write(output_path, pdf)
+                .map_err(|e| CanvasError::io(e, output_path, IoOperation::Write, line!(), file!()))?;
+        } else {
+            self.render_refill(&template)?
+                .save_with_format(output_path, image::ImageFormat::Png)
+                .map_err(|e| CanvasError::image(e, output_path, IoOperation::Write, line!(), file!()))?;
+        }
+        tracing::info!("Exported refilled form to {}", output_path);
+        Ok(())
+    }
+
+    /// Clean image pixels per form image pixel, along each axis
+    fn template_scale(&self, template: &RgbaImage) -> Vec2 {
+        let template_size = Vec2::new(template.width() as f32, template.height() as f32);
+        match self.coordinate_mapper.image_fit() {
+            Some(fit) => template_size / fit.image_size,
+            None => Vec2::splat(1.0),
+        }
+    }
+}
+
+/// Write one line of text at up to `size` pixels inside `bounds`, shrinking it to fit
+///
+/// The text is centered vertically and aligned horizontally by `align`.
+pub(super) fn draw_text(
+    target: &mut RgbaImage,
+    font: &FontRef<'_>,
+    bounds: Rect,
+    content: &str,
+    size: f32,
+    ink: Color32,
+    align: Align,
+) {
+    let width_at = |size: f32| {
+        let scaled = font.as_scaled(PxScale::from(size));
+        let mut width = 0.0;
+        let mut previous = None;
+        for c in content.chars() {
+            let id = font.glyph_id(c);
+            if let Some(previous) = previous {
+                width += scaled.kern(previous, id);
+            }
+            width += scaled.h_advance(id);
+            previous = Some(id);
+        }
+        width
+    };
+
+    let mut size = size;
+    let width = width_at(size);
+    if width > bounds.width() && width > 0.0 {
+        size *= bounds.width() / width;
+    }
+    let size = size.max(MIN_FONT_SIZE);
+
+    let scaled = font.as_scaled(PxScale::from(size));
+    let baseline = bounds.center().y + (scaled.ascent() + scaled.descent()) / 2.0;
+    let mut x = match align {
+        Align::Min => bounds.min.x,
+        Align::Center => bounds.center().x - width_at(size) / 2.0,
+        Align::Max => bounds.max.x - width_at(size),
+    };
+    let mut previous = None;
+    for c in content.chars() {
+        let id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            x += scaled.kern(previous, id);
+        }
+        let glyph = id.with_scale_and_position(size, point(x, baseline));
+        x += scaled.h_advance(id);
+        previous = Some(id);
+
+        let Some(outlined) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let origin = outlined.px_bounds().min;
+        outlined.draw(|gx, gy, coverage| {
+            let (px, py) = (origin.x as i64 + gx as i64, origin.y as i64 + gy as i64);
+            if px < 0 || py < 0 || px >= target.width() as i64 || py >= target.height() as i64 {
+                return;
+            }
+            let ink = ink.gamma_multiply(coverage.clamp(0.0, 1.0));
+            blend(target.get_pixel_mut(px as u32, py as u32), ink);
+        });
+    }
+}
@@ -0,0 +1,271 @@
+//! Find and rename across shape and detection names
+//!
+//! A [`RenameQuery`] describes a find/replace over names, either as plain
+//! text or as a regular expression with `$1`-style capture references. The
+//! preview lists every affected object with its old and new name and flags
+//! names that would collide, so bulk renames can be checked before they are
+//! applied. Applying a rename also updates the field conditions and field
+//! groups that refer to renamed shapes by name.
+
+use regex::{Regex, RegexBuilder};
+use std::collections::HashMap;
+use tracing::{debug, instrument};
+
+use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas};
+use crate::{CommentTarget, FieldCondition};
+
+/// Find/replace over object names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameQuery {
+    /// Text or regular expression to find
+    pub find: String,
+    /// Replacement; may use `$1` or `${name}` captures when `regex` is set
+    pub replace: String,
+    /// Treat `find` as a regular expression
+    pub regex: bool,
+    /// Match letter case exactly
+    pub case_sensitive: bool,
+    /// Rename detections as well as shapes
+    pub include_detections: bool,
+}
+
+impl Default for RenameQuery {
+    fn default() -> Self {
+        Self {
+            find: String::new(),
+            replace: String::new(),
+            regex: false,
+            case_sensitive: true,
+            include_detections: false,
+        }
+    }
+}
+
+impl RenameQuery {
+    /// Plain-text, case-sensitive rename of shapes
+    pub fn new(find: impl Into<String>, replace: impl Into<String>) -> Self {
+        Self {
+            find: find.into(),
+            replace: replace.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Treat the search text as a regular expression
+    pub fn with_regex(mut self, regex: bool) -> Self {
+        self.regex = regex;
+        self
+    }
+
+    /// Set whether matching is case sensitive
+    pub fn with_case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Set whether detections are renamed too
+    pub fn with_detections(mut self, include: bool) -> Self {
+        self.include_detections = include;
+        self
+    }
+
+    fn compile(&self) -> Result<Regex, CanvasError> {
+        let pattern = if self.regex {
+            self.find.clone()
+        } else {
+            regex::escape(&self.find)
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+            .map_err(|e| CanvasError::new(CanvasErrorKind::InvalidPattern(e.to_string()), line!(), file!()))
+    }
+}
+
+/// One object affected by a rename
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameMatch {
+    /// Shape or detection being renamed
+    pub target: CommentTarget,
+    /// Current name
+    pub old_name: String,
+    /// Name after the rename
+    pub new_name: String,
+    /// Whether another object of the same kind would end up with the same name
+    pub conflict: bool,
+}
+
+impl DrawingCanvas {
+    /// List the objects a rename would change, without changing them
+    ///
+    /// Objects whose name is unchanged by the replacement are omitted. An
+    /// empty search text matches nothing.
+    pub fn preview_rename(&self, query: &RenameQuery) -> Result<Vec<RenameMatch>, CanvasError> {
+        if query.find.is_empty() {
+            return Ok(Vec::new());
+        }
+        let pattern = query.compile()?;
+        let replacement = if query.regex {
+            query.replace.as_str()
+        } else {
+            // Literal replacement: `$` has no special meaning
+            &query.replace.replace('$', "$$")
+        };
+
+        let mut groups = vec![(CommentTarget::Shape as fn(usize) -> CommentTarget, &self.shapes)];
+        if query.include_detections {
+            groups.push((CommentTarget::Detection, &self.detections));
+        }
+
+        let mut matches = Vec::new();
+        for (target, objects) in groups {
+            let new_names: Vec<String> = objects
+                .iter()
+                .map(|shape| pattern.replace_all(shape.name(), replacement).into_owned())
+                .collect();
+            for (idx, shape) in objects.iter().enumerate() {
+                if new_names[idx] == shape.name() {
+                    continue;
+                }
+                let conflict = new_names
+                    .iter()
+                    .enumerate()
+                    .any(|(other, name)| other != idx && *name == new_names[idx]);
+                matches.push(RenameMatch {
+                    target: target(idx),
+                    old_name: shape.name().to_string(),
+                    new_name: new_names[idx].clone(),
+                    conflict,
+                });
+            }
+        }
+        Ok(matches)
+    }
+
+    /// Apply a rename, returning the objects that changed
+    ///
+    /// Conditions and field groups that name a renamed shape are updated to
+    /// its new name.
+    #[instrument(skip(self), fields(shapes = self.shapes.len(), detections = self.detections.len()))]
+    pub fn apply_rename(&mut self, query: &RenameQuery) -> Result<Vec<RenameMatch>, CanvasError> {
+        let matches = self.preview_rename(query)?;
+        for rename in &matches {
+            let shape = match rename.target {
+                CommentTarget::Shape(idx) => self.shapes.get_mut(idx),
+                CommentTarget::Detection(idx) => self.detections.get_mut(idx),
+            };
+            if let Some(shape) = shape {
+                shape.set_name(rename.new_name.clone());
+            }
+        }
+
+        let renamed: HashMap<&str, &str> = matches
+            .iter()
+            .filter(|rename| matches!(rename.target, CommentTarget::Shape(_)))
+            .map(|rename| (rename.old_name.as_str(), rename.new_name.as_str()))
+            .collect();
+        if !renamed.is_empty() {
+            let rename_field = |field: &mut String| {
+                if let Some(new_name) = renamed.get(field.as_str()) {
+                    *field = new_name.to_string();
+                }
+            };
+            for shape in &mut self.shapes {
+                let Some(mut condition) = shape.condition().cloned() else {
+                    continue;
+                };
+                match &mut condition {
+                    FieldCondition::Checked(field) | FieldCondition::Filled(field) => rename_field(field),
+                    FieldCondition::Equals { field, .. } => rename_field(field),
+                }
+                if shape.condition() != Some(&condition) {
+                    shape.set_condition(Some(condition));
+                }
+            }
+            for group in &mut self.field_groups {
+                group.fields.iter_mut().for_each(rename_field);
+            }
+        }
+        debug!(renamed = matches.len(), "Applied rename");
+        Ok(matches)
+    }
+
+    /// Toggle the find and rename window
+    pub fn toggle_rename_panel(&mut self) {
+        self.show_rename = !self.show_rename;
+    }
+
+    /// Show the find and rename window
+    ///
+    /// Returns true if the window was shown.
+    pub fn show_rename_panel(&mut self, ctx: &egui::Context) -> bool {
+        if !self.show_rename {
+            return false;
+        }
+
+        let preview = self.preview_rename(&self.rename_query);
+        let mut panel_open = true;
+        let mut apply = false;
+        let query = &mut self.rename_query;
+        egui::Window::new("Find and Rename")
+            .open(&mut panel_open)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                egui::Grid::new("rename_query_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Find:");
+                    ui.text_edit_singleline(&mut query.find);
+                    ui.end_row();
+
+                    ui.label("Replace:");
+                    ui.text_edit_singleline(&mut query.replace);
+                    ui.end_row();
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut query.regex, "Regex");
+                    ui.checkbox(&mut query.case_sensitive, "Match case");
+                    ui.checkbox(&mut query.include_detections, "Detections");
+                });
+                ui.separator();
+
+                match &preview {
+                    Err(e) => {
+                        ui.colored_label(ui.visuals().error_fg_color, e.kind.to_string());
+                    }
+                    Ok(matches) => {
+                        ui.label(format!("{} to rename", matches.len()));
+                        egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                            egui::Grid::new("rename_preview_grid").num_columns(3).striped(true).show(ui, |ui| {
+                                for rename in matches {
+                                    ui.label(&rename.old_name);
+                                    ui.label("→");
+                                    if rename.conflict {
+                                        ui.colored_label(ui.visuals().warn_fg_color, &rename.new_name)
+                                            .on_hover_text("Another object will have this name");
+                                    } else {
+                                        ui.label(&rename.new_name);
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                        });
+                        let enabled = !matches.is_empty();
+                        if ui.add_enabled(enabled, egui::Button::new("Rename All")).clicked() {
+                            apply = true;
+                        }
+                    }
+                }
+            });
+
+        if apply {
+            let query = self.rename_query.clone();
+            if let Err(e) = self.apply_rename(&query) {
+                tracing::warn!("Rename failed: {}", e);
+            }
+        }
+        if !panel_open {
+            self.show_rename = false;
+        }
+
+        true
+    }
+}
@@ -10,11 +10,13 @@
 //! - Vertex editing handles
 //! - Coordinate transformation utilities
 
+use super::command::CanvasCommand;
 use super::core::DrawingCanvas;
-use crate::{LayerType, Shape, ToolMode};
+use crate::{CoordinateMapper, ImagePos, LayerType, ScreenPos, Shape, StrokeStyle, ToolMode, length_label};
 use egui::{Color32, Pos2, Stroke};
 use geo::CoordsIter;
-use tracing::{debug, trace, warn};
+use strum::IntoEnumIterator;
+use tracing::{debug, trace};
 
 impl DrawingCanvas {
     /// Render the canvas UI
@@ -24,13 +26,13 @@ impl DrawingCanvas {
             trace!("Frame start: detections={}, shapes={}", self.detections.len(), self.shapes.len());
         }
 
-        // Process any pending image loads (deferred from startup)
-        if let Some(pending_path) = self.pending_image_load.take() {
-            tracing::debug!("Processing deferred image load: {}", pending_path);
-            if let Err(e) = self.load_form_image(&pending_path, ui.ctx()) {
-                tracing::warn!("Could not load deferred form image from {}: {}", pending_path, e);
-            }
-        }
+        // Show the form image of a just-opened project once it is decoded
+        self.poll_form_image(ui.ctx());
+        self.begin_texture_frame();
+
+        // Advance the active-time clock
+        let (now, had_input) = ui.input(|i| (i.time, !i.events.is_empty() || i.pointer.is_moving()));
+        self.session_stats.tick(now, had_input);
 
         // Canvas area
         let (response, painter) = ui.allocate_painter(
@@ -45,7 +47,7 @@ impl DrawingCanvas {
         if response.hovered() {
             let scroll_delta = ui.input(|i| i.smooth_scroll_delta.y);
             if scroll_delta != 0.0 {
-                zoom_delta = scroll_delta * 0.001 * self.zoom_sensitivity; // Apply zoom sensitivity
+                zoom_delta = scroll_delta * 0.001 * self.canvas_settings.zoom_sensitivity; // Apply zoom sensitivity
             }
         }
 
@@ -54,25 +56,34 @@ impl DrawingCanvas {
             ui.input(|i| {
                 if i.modifiers.ctrl || i.modifiers.command {
                     if i.key_pressed(egui::Key::Minus) {
-                        zoom_delta = -0.1 * self.zoom_sensitivity;
+                        zoom_delta = -0.1 * self.canvas_settings.zoom_sensitivity;
                     } else if i.key_pressed(egui::Key::Plus) || i.key_pressed(egui::Key::Equals) {
-                        zoom_delta = 0.1 * self.zoom_sensitivity;
+                        zoom_delta = 0.1 * self.canvas_settings.zoom_sensitivity;
                     }
                 }
             });
         }
 
+        // Duplicate the selected shape with Ctrl+D
+        self.handle_duplicate_shortcut(ui);
+        // Back and forward through inspected objects with mouse buttons 4/5
+        self.handle_navigation_input(ui);
+        // Bookmark the view with Ctrl+B and jump to bookmarks with Ctrl+1-9
+        self.handle_bookmark_input(ui);
+
         // Apply zoom delta and clamp to zoom range (1.0 - 10.0)
         if zoom_delta != 0.0 {
             let old_zoom = self.zoom_level;
-            self.zoom_level = (self.zoom_level + zoom_delta).clamp(1.0, 10.0);
+            let zoom = (self.zoom_level + zoom_delta).clamp(1.0, 10.0);
+            self.apply_command(CanvasCommand::SetZoom(zoom));
 
             // Adjust pan offset to zoom toward the center of the viewport
             if let Some(hover_pos) = response.hover_pos() {
                 let canvas_center = response.rect.center();
                 let zoom_point = hover_pos - canvas_center;
-                let zoom_factor = self.zoom_level / old_zoom;
-                self.pan_offset = self.pan_offset * zoom_factor + zoom_point * (1.0 - zoom_factor);
+                let zoom_factor = zoom / old_zoom;
+                let pan = self.pan_offset * zoom_factor + zoom_point * (1.0 - zoom_factor);
+                self.apply_command(CanvasCommand::SetPan { x: pan.x, y: pan.y });
             }
         }
 
@@ -85,30 +96,23 @@ impl DrawingCanvas {
             );
         }
 
-        // Apply zoom transformation to a child painter
-        let canvas_center = response.rect.center();
-        let to_screen = egui::emath::TSTransform::from_translation(canvas_center.to_vec2() + self.pan_offset)
-            * egui::emath::TSTransform::from_scaling(self.zoom_level)
-            * egui::emath::TSTransform::from_translation(-canvas_center.to_vec2());
+        // Record this frame's coordinate mapping; everything below converts through it
+        self.coordinate_mapper = CoordinateMapper::new(response.rect, self.zoom_level, self.pan_offset)
+            .with_image_size(self.form_image_size);
+        let mapper = self.coordinate_mapper;
+        let to_screen = mapper.to_screen();
+
+        // Track the cursor in image pixels for the status bar
+        self.cursor_image_pos = response
+            .hover_pos()
+            .and_then(|pos| mapper.screen_to_image(ScreenPos(pos)));
+        self.cursor_canvas_pos = response.hover_pos().map(|pos| mapper.screen_to_canvas(ScreenPos(pos)));
 
-        // Draw form image on Canvas layer if loaded
+        // Draw form image on Canvas layer if loaded, fitted and centered within the canvas
         if self.layer_manager.is_visible(LayerType::Canvas)
-            && let (Some(texture), Some(image_size)) = (&self.form_image, self.form_image_size)
+            && let (Some(texture), Some(fit)) = (&self.form_image, mapper.image_fit())
         {
-            // Calculate scaling to fit image within canvas while maintaining aspect ratio
-            let canvas_size = response.rect.size();
-            let scale_x = canvas_size.x / image_size.x;
-            let scale_y = canvas_size.y / image_size.y;
-            let scale = scale_x.min(scale_y); // Use the smaller scale to fit entirely
-
-            let fitted_size = image_size * scale;
-
-            // Center the image within the canvas
-            let offset_x = (canvas_size.x - fitted_size.x) / 2.0;
-            let offset_y = (canvas_size.y - fitted_size.y) / 2.0;
-            let image_pos = response.rect.min + egui::vec2(offset_x, offset_y);
-
-            let image_rect = egui::Rect::from_min_size(image_pos, fitted_size);
+            let image_rect = fit.canvas_rect();
 
             // If rotation is applied, use textured mesh for rotation
             if self.form_image_rotation != 0.0 {
@@ -178,6 +182,13 @@ impl DrawingCanvas {
             }
         }
 
+        // Draw highlights over the form image, beneath detections and shapes
+        if self.layer_manager.is_visible(LayerType::Highlights) {
+            for highlight in &self.highlights {
+                highlight.render(&painter, &to_screen);
+            }
+        }
+
         // Draw detections if Detections layer is visible (with zoom transformation)
         // Note: Detections are stored in image pixel coordinates and need to be mapped to canvas space
         let detections_visible = self.layer_manager.is_visible(LayerType::Detections);
@@ -185,26 +196,30 @@ impl DrawingCanvas {
             debug!("Rendering frame: detections={}, layer_visible={}, image_size={:?}, canvas_size={:?}",
                    self.detections.len(), detections_visible, self.form_image_size, response.rect.size());
         }
-        if detections_visible && let (Some(image_size), Some(_texture)) = (self.form_image_size, &self.form_image) {
-            // Calculate the image-to-canvas coordinate transform
-            let canvas_size = response.rect.size();
-            let scale_x = canvas_size.x / image_size.x;
-            let scale_y = canvas_size.y / image_size.y;
-            let scale = scale_x.min(scale_y);
-            let fitted_size = image_size * scale;
-            let offset_x = (canvas_size.x - fitted_size.x) / 2.0;
-            let offset_y = (canvas_size.y - fitted_size.y) / 2.0;
-            let image_offset = response.rect.min + egui::vec2(offset_x, offset_y);
-
-            debug!("Image transform: scale={:.3}, offset=({:.1}, {:.1})", scale, offset_x, offset_y);
+        if detections_visible && let (Some(fit), Some(_texture)) = (mapper.image_fit(), &self.form_image) {
+            debug!("Image transform: scale={:.3}, offset={:?}", fit.scale, fit.offset);
 
             for (idx, detection) in self.detections.iter().enumerate() {
+                if detection.is_hidden() || !self.passes_subtype_filter(idx) {
+                    continue;
+                }
                 trace!("Rendering detection {}/{}: {:?}", idx + 1, self.detections.len(), detection);
 
+                // Draw in the configured color for the detection's subtype or kind
+                let Some(styled) = self.styled_detection(idx) else {
+                    continue;
+                };
                 // Convert detection from image pixel coordinates to canvas coordinates
-                let detection_in_canvas_space = self.map_detection_to_canvas(detection, scale, image_offset);
-                self.render_shape_transformed(&detection_in_canvas_space, &painter, &to_screen);
+                if let Some(detection_in_canvas_space) = mapper.image_shape_to_canvas(&styled) {
+                    if let Some(halo) = self.flash_halo(idx, &detection_in_canvas_space) {
+                        self.render_shape_transformed(&halo, &painter, &to_screen);
+                    }
+                    self.render_shape_transformed(&detection_in_canvas_space, &painter, &to_screen);
+                }
             }
+            // Word boxes are a sub-layer of the detections
+            self.draw_ocr_words(&painter, &to_screen);
+            self.draw_detection_selection(&painter, &to_screen);
         } else if detections_visible && !self.detections.is_empty() {
             debug!("Detections layer visible but image not loaded: {} detections not rendered", self.detections.len());
         } else if !self.detections.is_empty() {
@@ -215,6 +230,9 @@ impl DrawingCanvas {
         let shapes_visible = self.layer_manager.is_visible(LayerType::Shapes);
         if shapes_visible {
             for (idx, shape) in self.shapes.iter().enumerate() {
+                if shape.is_hidden() {
+                    continue;
+                }
                 self.render_shape_transformed(shape, &painter, &to_screen);
 
                 // Draw selection highlight
@@ -237,6 +255,13 @@ impl DrawingCanvas {
                             let transformed_radius = circle.radius * self.zoom_level;
                             painter.circle_stroke(transformed_center, transformed_radius, highlight_stroke);
                         }
+                        Shape::Ellipse(ellipse) => {
+                            let points: Vec<Pos2> = ellipse.to_egui_points()
+                                .iter()
+                                .map(|p| to_screen.mul_pos(*p))
+                                .collect();
+                            painter.add(egui::Shape::closed_line(points, highlight_stroke));
+                        }
                         Shape::Polygon(poly) => {
                             let points: Vec<Pos2> = poly.to_egui_points()
                                 .iter()
@@ -244,6 +269,34 @@ impl DrawingCanvas {
                                 .collect();
                             painter.add(egui::Shape::closed_line(points, highlight_stroke));
                         }
+                        Shape::Line(line) => {
+                            painter.line_segment(line.points().map(|p| to_screen.mul_pos(p)), highlight_stroke);
+                        }
+                        Shape::Polyline(polyline) => {
+                            let points: Vec<Pos2> = polyline.points()
+                                .iter()
+                                .map(|p| to_screen.mul_pos(*p))
+                                .collect();
+                            painter.add(egui::Shape::line(points, highlight_stroke));
+                        }
+                        Shape::Arrow(arrow) => {
+                            painter.line_segment(
+                                [to_screen.mul_pos(*arrow.start()), to_screen.mul_pos(*arrow.end())],
+                                highlight_stroke,
+                            );
+                        }
+                        Shape::Callout(callout) => {
+                            painter.line_segment(
+                                [to_screen.mul_pos(*callout.anchor()), to_screen.mul_pos(callout.leader_end())],
+                                highlight_stroke,
+                            );
+                            painter.rect_stroke(
+                                to_screen * *callout.text_box(),
+                                0.0,
+                                highlight_stroke,
+                                egui::StrokeKind::Outside,
+                            );
+                        }
                     }
 
                     // Draw edit vertices if in Edit mode
@@ -252,14 +305,54 @@ impl DrawingCanvas {
                     }
                 }
             }
+            self.render_stamps(ui.ctx(), &painter, &to_screen);
+        }
+
+        // Fill fields with sample data while previewing the layout
+        self.draw_template_preview(&painter, &to_screen);
+
+        // Outline changes from the open project diff
+        self.draw_project_diff(&painter, &to_screen);
+
+        // Preview redaction regions as blacked-out boxes
+        if self.redaction_mode {
+            for region in self.redaction_regions() {
+                if let (Some(min), Some(max)) = (
+                    mapper.image_to_screen(ImagePos(region.bounds.min)),
+                    mapper.image_to_screen(ImagePos(region.bounds.max)),
+                ) {
+                    painter.rect_filled(egui::Rect::from_min_max(min.0, max.0), 0.0, Color32::from_black_alpha(220));
+                }
+            }
+        }
+
+        // Draw comment badges on annotated objects
+        self.draw_comment_badges(&painter, &to_screen);
+
+        // Number the read detections while their reading order is being edited
+        self.draw_ocr_order_badges(&painter, &to_screen);
+
+        // Draw collaborator cursors
+        for peer in &self.peers {
+            if let Some(cursor) = peer.cursor {
+                let pos = to_screen.mul_pos(cursor);
+                painter.circle_filled(pos, 5.0, peer.color);
+                painter.text(
+                    pos + egui::vec2(8.0, -8.0),
+                    egui::Align2::LEFT_BOTTOM,
+                    &peer.display_name,
+                    egui::FontId::proportional(12.0),
+                    peer.color,
+                );
+            }
         }
 
         // Draw grid on top of everything if Grid layer is visible
         if self.layer_manager.is_visible(LayerType::Grid) {
             debug!(
                 grid_visible = true,
-                grid_spacing_h = self.grid_spacing_horizontal,
-                grid_spacing_v = self.grid_spacing_vertical,
+                grid_spacing_h = self.canvas_settings.grid_spacing_horizontal,
+                grid_spacing_v = self.canvas_settings.grid_spacing_vertical,
                 "Calling draw_grid (rendering on top)"
             );
             self.draw_grid(&painter, &response.rect, &to_screen);
@@ -267,8 +360,27 @@ impl DrawingCanvas {
             trace!("Grid layer is not visible, skipping grid render");
         }
 
-        // Handle mouse interactions and draw preview (with zoom transformation)
-        self.handle_input(&response, &painter, &to_screen);
+        // Color legend in the lower-left corner
+        self.draw_legend(&painter, response.rect);
+
+        // Rulers and guides sit above the canvas so they take drags first
+        self.show_rulers_and_guides(ui, response.rect, &painter, &to_screen);
+
+        // Screenshot region selection takes the pointer while armed, then panning
+        if !self.handle_screenshot_input(&response, &painter) && !self.handle_pan_input(&response) {
+            // Handle mouse interactions and draw preview (with zoom transformation)
+            self.handle_input(&response, &painter, &to_screen);
+            // After the tool ran, so the cursor matches the interaction it started or ended
+            self.update_cursor(&response);
+            self.show_hover_tooltip(&response);
+            self.handle_ocr_edit_input(&response);
+        }
+
+        // Right-click menus
+        self.handle_context_menu(&response);
+
+        // In-place editor for OCR text, on top of the canvas
+        self.show_ocr_edit_popup(ui);
     }
 
     /// Show inline properties UI for the selected shape
@@ -283,6 +395,7 @@ impl DrawingCanvas {
             return;
         };
 
+        let length_scale = self.length_scale();
         let Some(shape) = self.shapes.get_mut(idx) else {
             trace!("Selected shape index {} out of bounds", idx);
             self.selected_shape = None;
@@ -382,6 +495,25 @@ impl DrawingCanvas {
                 ui.label(format!("Radius: {:.1}", circle.radius));
                 ui.label(format!("Center: ({:.1}, {:.1})", circle.center.x, circle.center.y));
             }
+            Shape::Ellipse(ellipse) => {
+                ui.label("Type: Ellipse");
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    let response = ui.add(egui::TextEdit::singleline(&mut ellipse.name).id_salt("ellipse_name"));
+                    if self.focus_name_field {
+                        response.request_focus();
+                        self.focus_name_field = false;
+                    }
+                });
+
+                ui.separator();
+
+                ui.label(format!("Radii: {:.1} x {:.1}", ellipse.radii().x, ellipse.radii().y));
+                ui.label(format!("Rotation: {:.1}°", ellipse.rotation().to_degrees()));
+                ui.label(format!("Center: ({:.1}, {:.1})", ellipse.center().x, ellipse.center().y));
+            }
             Shape::Polygon(poly) => {
                 ui.label("Type: Polygon");
                 ui.separator();
@@ -419,10 +551,128 @@ impl DrawingCanvas {
 
                 ui.label(format!("Points: {}", poly.polygon().exterior().coords_count()));
             }
+            Shape::Line(line) => {
+                ui.label("Type: Line");
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    let response = ui.add(egui::TextEdit::singleline(&mut line.name).id_salt("line_name"));
+                    if self.focus_name_field {
+                        response.request_focus();
+                        self.focus_name_field = false;
+                    }
+                });
+
+                ui.separator();
+
+                ui.label(format!("Length: {}", length_label(line.length() * length_scale)));
+                ui.checkbox(&mut line.show_length, "Show length");
+            }
+            Shape::Polyline(polyline) => {
+                ui.label("Type: Polyline");
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    let response = ui.add(egui::TextEdit::singleline(&mut polyline.name).id_salt("polyline_name"));
+                    if self.focus_name_field {
+                        response.request_focus();
+                        self.focus_name_field = false;
+                    }
+                });
+
+                ui.separator();
+
+                ui.label(format!("Points: {}", polyline.points().len()));
+                ui.label(format!("Length: {}", length_label(polyline.length() * length_scale)));
+                ui.checkbox(&mut polyline.show_length, "Show length");
+            }
+            Shape::Arrow(arrow) => {
+                ui.label("Type: Arrow");
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    let response = ui.add(egui::TextEdit::singleline(&mut arrow.name).id_salt("arrow_name"));
+                    if self.focus_name_field {
+                        response.request_focus();
+                        self.focus_name_field = false;
+                    }
+                });
+
+                ui.separator();
+
+                ui.label(format!("Length: {:.1}", arrow.start().distance(*arrow.end())));
+            }
+            Shape::Callout(callout) => {
+                ui.label("Type: Callout");
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    ui.add(egui::TextEdit::singleline(&mut callout.name).id_salt("callout_name"));
+                });
+
+                ui.label("Text:");
+                // New callouts are blank, so focus the note rather than the name
+                let response = ui.add(
+                    egui::TextEdit::multiline(&mut callout.text)
+                        .id_salt("callout_text")
+                        .desired_rows(3),
+                );
+                if self.focus_name_field {
+                    response.request_focus();
+                    self.focus_name_field = false;
+                }
+            }
+        }
+
+        ui.separator();
+
+        if let Some(shape) = self.shapes.get_mut(idx) {
+            let mut sensitive = shape.is_sensitive();
+            if ui
+                .checkbox(&mut sensitive, "Sensitive (redact on export)")
+                .changed()
+            {
+                shape.set_sensitive(sensitive);
+                debug!(shape_index = idx, sensitive, "Updated shape sensitivity");
+            }
         }
 
         ui.separator();
 
+        if let Some(shape) = self.shapes.get_mut(idx) {
+            let mut stroke = shape.stroke();
+            let mut stroke_style = shape.stroke_style();
+            ui.horizontal(|ui| {
+                ui.label("Stroke:");
+                ui.add(egui::DragValue::new(&mut stroke.width).range(0.5..=20.0).speed(0.1).suffix(" px"));
+                ui.color_edit_button_srgba(&mut stroke.color);
+                egui::ComboBox::from_id_salt("shape_stroke_style")
+                    .selected_text(stroke_style.to_string())
+                    .show_ui(ui, |ui| {
+                        for option in StrokeStyle::iter() {
+                            ui.selectable_value(&mut stroke_style, option, option.to_string());
+                        }
+                    });
+            });
+            if stroke != shape.stroke() {
+                shape.set_stroke(stroke);
+                debug!(shape_index = idx, width = stroke.width, "Updated shape stroke");
+            }
+            if stroke_style != shape.stroke_style() {
+                shape.set_stroke_style(stroke_style);
+                debug!(shape_index = idx, ?stroke_style, "Updated shape stroke style");
+            }
+        }
+
+        ui.separator();
+        self.show_provenance(ui);
+
+        ui.separator();
+
         if ui.button("Deselect").clicked() {
             self.selected_shape = None;
             self.show_properties = false;
@@ -442,6 +692,7 @@ impl DrawingCanvas {
             return false;
         };
 
+        let length_scale = self.length_scale();
         let Some(shape) = self.shapes.get_mut(idx) else {
             self.selected_shape = None;
             self.show_properties = false;
@@ -501,6 +752,29 @@ impl DrawingCanvas {
 
                     ui.separator();
 
+                    ui.button("Close").clicked()
+                }),
+            Shape::Ellipse(ellipse) => egui::Window::new("Ellipse Properties")
+                .open(&mut panel_open)
+                .resizable(false)
+                .default_width(300.0)
+                .show(ctx, |ui| {
+                    ui.heading("Selected Ellipse");
+                    ui.separator();
+
+                    ui.horizontal(|ui| {
+                        ui.label("Name:");
+                        ui.text_edit_singleline(&mut ellipse.name);
+                    });
+
+                    ui.separator();
+
+                    ui.label(format!("Radii: {:.1} x {:.1}", ellipse.radii().x, ellipse.radii().y));
+                    ui.label(format!("Rotation: {:.1}°", ellipse.rotation().to_degrees()));
+                    ui.label(format!("Center: ({:.1}, {:.1})", ellipse.center().x, ellipse.center().y));
+
+                    ui.separator();
+
                     ui.button("Close").clicked()
                 }),
             Shape::Polygon(poly) => egui::Window::new("Polygon Properties")
@@ -524,83 +798,98 @@ impl DrawingCanvas {
 
                     ui.button("Close").clicked()
                 }),
-        };
+            Shape::Line(line) => egui::Window::new("Line Properties")
+                .open(&mut panel_open)
+                .resizable(false)
+                .default_width(300.0)
+                .show(ctx, |ui| {
+                    ui.heading("Selected Line");
+                    ui.separator();
 
-        // Close if window was closed or Close button was clicked
-        if !panel_open || close_clicked.is_some_and(|r| r.inner.unwrap_or(false)) {
-            self.show_properties = false;
-            self.selected_shape = None;
-        }
+                    ui.horizontal(|ui| {
+                        ui.label("Name:");
+                        ui.text_edit_singleline(&mut line.name);
+                    });
 
-        true
-    }
+                    ui.separator();
 
-    /// Show settings inline in the side panel
-    pub fn show_inline_settings(&mut self, ui: &mut egui::Ui) {
-        ui.heading("Settings");
-        ui.separator();
+                    ui.label(format!("Length: {}", length_label(line.length() * length_scale)));
+                    ui.checkbox(&mut line.show_length, "Show length");
 
-        ui.label("Zoom Sensitivity:");
-        ui.add(
-            egui::Slider::new(&mut self.zoom_sensitivity, 0.1..=10.0)
-                .text("Sensitivity")
-                .logarithmic(true)
-        );
-        ui.label("Higher values make zoom more responsive");
+                    ui.separator();
 
-        ui.separator();
+                    ui.button("Close").clicked()
+                }),
+            Shape::Polyline(polyline) => egui::Window::new("Polyline Properties")
+                .open(&mut panel_open)
+                .resizable(false)
+                .default_width(300.0)
+                .show(ctx, |ui| {
+                    ui.heading("Selected Polyline");
+                    ui.separator();
 
-        ui.label("Grid Spacing (Horizontal):");
-        ui.add(
-            egui::Slider::new(&mut self.grid_spacing_horizontal, 0.1..=100.0)
-                .text("Horizontal")
-                .logarithmic(true)
-        );
+                    ui.horizontal(|ui| {
+                        ui.label("Name:");
+                        ui.text_edit_singleline(&mut polyline.name);
+                    });
 
-        ui.label("Grid Spacing (Vertical):");
-        ui.add(
-            egui::Slider::new(&mut self.grid_spacing_vertical, 0.1..=100.0)
-                .text("Vertical")
-                .logarithmic(true)
-        );
-        ui.label("Distance between grid lines");
-    }
+                    ui.separator();
 
-    /// Show settings panel
-    /// Returns true if the settings panel was shown
-    #[allow(dead_code)]
-    pub fn show_settings_panel(&mut self, ctx: &egui::Context) -> bool {
-        if !self.show_settings {
-            return false;
-        }
+                    ui.label(format!("Points: {}", polyline.points().len()));
+                    ui.label(format!("Length: {}", length_label(polyline.length() * length_scale)));
+                    ui.checkbox(&mut polyline.show_length, "Show length");
 
-        let mut panel_open = true;
-        egui::Window::new("Settings")
-            .open(&mut panel_open)
-            .resizable(false)
-            .default_width(300.0)
-            .show(ctx, |ui| {
-                ui.heading("Settings");
-                ui.separator();
+                    ui.separator();
 
-                ui.label("Zoom Sensitivity:");
-                ui.add(
-                    egui::Slider::new(&mut self.zoom_sensitivity, 0.1..=10.0)
-                        .text("Sensitivity")
-                        .logarithmic(true)
-                );
-                ui.label("Higher values make zoom more responsive");
+                    ui.button("Close").clicked()
+                }),
+            Shape::Arrow(arrow) => egui::Window::new("Arrow Properties")
+                .open(&mut panel_open)
+                .resizable(false)
+                .default_width(300.0)
+                .show(ctx, |ui| {
+                    ui.heading("Selected Arrow");
+                    ui.separator();
 
-                ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label("Name:");
+                        ui.text_edit_singleline(&mut arrow.name);
+                    });
 
-                if ui.button("Close").clicked() {
-                    self.show_settings = false;
-                }
-            });
+                    ui.separator();
+
+                    ui.label(format!("Length: {:.1}", arrow.start().distance(*arrow.end())));
+
+                    ui.separator();
+
+                    ui.button("Close").clicked()
+                }),
+            Shape::Callout(callout) => egui::Window::new("Callout Properties")
+                .open(&mut panel_open)
+                .resizable(false)
+                .default_width(300.0)
+                .show(ctx, |ui| {
+                    ui.heading("Selected Callout");
+                    ui.separator();
+
+                    ui.horizontal(|ui| {
+                        ui.label("Name:");
+                        ui.text_edit_singleline(&mut callout.name);
+                    });
+
+                    ui.label("Text:");
+                    ui.text_edit_multiline(&mut callout.text);
 
-        // Close if window was closed
-        if !panel_open {
-            self.show_settings = false;
+                    ui.separator();
+
+                    ui.button("Close").clicked()
+                }),
+        };
+
+        // Close if window was closed or Close button was clicked
+        if !panel_open || close_clicked.is_some_and(|r| r.inner.unwrap_or(false)) {
+            self.show_properties = false;
+            self.selected_shape = None;
         }
 
         true
@@ -610,8 +899,8 @@ impl DrawingCanvas {
     fn draw_grid(&self, painter: &egui::Painter, canvas_rect: &egui::Rect, transform: &egui::emath::TSTransform) {
         let _span = tracing::debug_span!(
             "draw_grid",
-            spacing_h = self.grid_spacing_horizontal,
-            spacing_v = self.grid_spacing_vertical
+            spacing_h = self.canvas_settings.grid_spacing_horizontal,
+            spacing_v = self.canvas_settings.grid_spacing_vertical
         ).entered();
 
         debug!(
@@ -641,8 +930,8 @@ impl DrawingCanvas {
         );
 
         // Determine grid line positions in world coordinates
-        let spacing_h = self.grid_spacing_horizontal;
-        let spacing_v = self.grid_spacing_vertical;
+        let spacing_h = self.canvas_settings.grid_spacing_horizontal;
+        let spacing_v = self.canvas_settings.grid_spacing_vertical;
         let start_x = (canvas_min.x / spacing_h).floor() * spacing_h;
         let start_y = (canvas_min.y / spacing_v).floor() * spacing_v;
 
@@ -721,66 +1010,26 @@ impl DrawingCanvas {
     }
 
     /// Render a shape with zoom transformation applied
-    fn render_shape_transformed(&self, shape: &Shape, painter: &egui::Painter, transform: &egui::emath::TSTransform) {
+    pub(super) fn render_shape_transformed(&self, shape: &Shape, painter: &egui::Painter, transform: &egui::emath::TSTransform) {
         match shape {
-            Shape::Rectangle(rect) => {
-                // Note: rotation removed from shapes
-                // let center = self.get_shape_center(shape);
-
-                // Apply rotation then zoom/pan transform
-                let transformed_corners: Vec<Pos2> = rect.corners()
-                    .iter()
-                    .map(|p| {
-                        // Note: rotation_angle removed - shapes no longer have implicit rotation
-                        // Apply zoom/pan transform
-                        transform.mul_pos(*p)
-                    })
-                    .collect();
-
-                // Draw filled quadrilateral
-                painter.add(egui::Shape::convex_polygon(
-                    transformed_corners.clone(),
-                    rect.fill,
-                    egui::Stroke::NONE,
-                ));
-                // Draw outline
-                painter.add(egui::Shape::closed_line(
-                    transformed_corners,
-                    rect.stroke,
-                ));
-            }
-            Shape::Circle(circle) => {
-                // Note: rotation_angle removed - circles are symmetric anyway
-                let transformed_center = transform.mul_pos(circle.center);
-                let transformed_radius = circle.radius * self.zoom_level;
-                painter.circle(transformed_center, transformed_radius, circle.fill, circle.stroke);
-            }
-            Shape::Polygon(poly) => {
-                // Note: rotation removed from shapes
-                // let center = self.get_shape_center(shape);
-
-                let points: Vec<Pos2> = poly.to_egui_points()
-                    .iter()
-                    .map(|p| {
-                        // Note: rotation_angle removed - apply zoom/pan transform only
-                        transform.mul_pos(*p)
-                    })
-                    .collect();
-
-                if points.len() > 2 {
-                    // Draw filled polygon
-                    painter.add(egui::Shape::convex_polygon(
-                        points.clone(),
-                        poly.fill,
-                        egui::Stroke::NONE,
-                    ));
-                    // Draw outline
-                    painter.add(egui::Shape::closed_line(points, poly.stroke));
-                }
-            }
+            Shape::Rectangle(rect) => rect.render(painter, transform),
+            Shape::Circle(circle) => circle.render(painter, transform),
+            Shape::Polygon(poly) => poly.render(painter, transform),
+            Shape::Ellipse(ellipse) => ellipse.render(painter, transform),
+            Shape::Line(line) => line.render(painter, transform, self.length_scale()),
+            Shape::Polyline(polyline) => polyline.render(painter, transform, self.length_scale()),
+            Shape::Arrow(arrow) => arrow.render(painter, transform),
+            Shape::Callout(callout) => callout.render(painter, transform),
         }
     }
 
+    /// Form image pixels per canvas unit, so length labels read like the rulers
+    ///
+    /// Without a form image, lengths are shown in canvas units.
+    fn length_scale(&self) -> f32 {
+        self.coordinate_mapper.image_fit().map_or(1.0, |fit| 1.0 / fit.scale)
+    }
+
     /// Draw edit vertices with zoom transformation applied
     fn draw_edit_vertices_transformed(&self, shape: &Shape, painter: &egui::Painter, transform: &egui::emath::TSTransform) {
         const VERTEX_SIZE: f32 = 6.0;
@@ -852,6 +1101,86 @@ impl DrawingCanvas {
                     );
                 }
             }
+            Shape::Ellipse(ellipse) => {
+                for vertex_pos in std::iter::once(*ellipse.center()).chain(ellipse.handles()) {
+                    let transformed_vertex = transform.mul_pos(vertex_pos);
+                    painter.rect_filled(
+                        egui::Rect::from_center_size(transformed_vertex, egui::vec2(VERTEX_SIZE, VERTEX_SIZE)),
+                        0.0,
+                        vertex_fill,
+                    );
+                    painter.rect_stroke(
+                        egui::Rect::from_center_size(transformed_vertex, egui::vec2(VERTEX_SIZE, VERTEX_SIZE)),
+                        0.0,
+                        vertex_stroke,
+                        egui::StrokeKind::Outside,
+                    );
+                }
+            }
+            Shape::Line(line) => {
+                for vertex_pos in line.points() {
+                    let transformed_vertex = transform.mul_pos(vertex_pos);
+                    painter.rect_filled(
+                        egui::Rect::from_center_size(transformed_vertex, egui::vec2(VERTEX_SIZE, VERTEX_SIZE)),
+                        0.0,
+                        vertex_fill,
+                    );
+                    painter.rect_stroke(
+                        egui::Rect::from_center_size(transformed_vertex, egui::vec2(VERTEX_SIZE, VERTEX_SIZE)),
+                        0.0,
+                        vertex_stroke,
+                        egui::StrokeKind::Outside,
+                    );
+                }
+            }
+            Shape::Polyline(polyline) => {
+                for vertex_pos in polyline.points() {
+                    let transformed_vertex = transform.mul_pos(*vertex_pos);
+                    painter.rect_filled(
+                        egui::Rect::from_center_size(transformed_vertex, egui::vec2(VERTEX_SIZE, VERTEX_SIZE)),
+                        0.0,
+                        vertex_fill,
+                    );
+                    painter.rect_stroke(
+                        egui::Rect::from_center_size(transformed_vertex, egui::vec2(VERTEX_SIZE, VERTEX_SIZE)),
+                        0.0,
+                        vertex_stroke,
+                        egui::StrokeKind::Outside,
+                    );
+                }
+            }
+            Shape::Arrow(arrow) => {
+                for vertex_pos in [*arrow.start(), *arrow.end()] {
+                    let transformed_vertex = transform.mul_pos(vertex_pos);
+                    painter.rect_filled(
+                        egui::Rect::from_center_size(transformed_vertex, egui::vec2(VERTEX_SIZE, VERTEX_SIZE)),
+                        0.0,
+                        vertex_fill,
+                    );
+                    painter.rect_stroke(
+                        egui::Rect::from_center_size(transformed_vertex, egui::vec2(VERTEX_SIZE, VERTEX_SIZE)),
+                        0.0,
+                        vertex_stroke,
+                        egui::StrokeKind::Outside,
+                    );
+                }
+            }
+            Shape::Callout(callout) => {
+                for vertex_pos in super::tools::callout_handles(callout) {
+                    let transformed_vertex = transform.mul_pos(vertex_pos);
+                    painter.rect_filled(
+                        egui::Rect::from_center_size(transformed_vertex, egui::vec2(VERTEX_SIZE, VERTEX_SIZE)),
+                        0.0,
+                        vertex_fill,
+                    );
+                    painter.rect_stroke(
+                        egui::Rect::from_center_size(transformed_vertex, egui::vec2(VERTEX_SIZE, VERTEX_SIZE)),
+                        0.0,
+                        vertex_stroke,
+                        egui::StrokeKind::Outside,
+                    );
+                }
+            }
         }
     }
 
@@ -875,92 +1204,4 @@ impl DrawingCanvas {
         // Translate back
         Pos2::new(center.x + rotated_x, center.y + rotated_y)
     }
-
-    /// Map a detection shape from image pixel coordinates to canvas coordinates
-    /// Detections are stored in image pixel space (e.g., 0-3400 x 0-4400),
-    /// but need to be converted to canvas space where the image is scaled and centered
-    fn map_detection_to_canvas(&self, detection: &Shape, scale: f32, image_offset: Pos2) -> Shape {
-        use crate::{Circle, PolygonShape, Rectangle};
-
-        match detection {
-            Shape::Rectangle(rect) => {
-                let mapped_corners: Vec<Pos2> = rect.corners()
-                    .iter()
-                    .map(|p| {
-                        // Scale from image pixels to fitted canvas size, then offset
-                        Pos2::new(
-                            p.x * scale + image_offset.x,
-                            p.y * scale + image_offset.y,
-                        )
-                    })
-                    .collect();
-
-                // Rectangle now uses from_four_corners constructor
-                Rectangle::from_four_corners(
-                    [mapped_corners[0], mapped_corners[1], mapped_corners[2], mapped_corners[3]],
-                    rect.stroke,
-                    rect.fill,
-                )
-                .map(|mut r| {
-                    r.name = rect.name.clone();
-                    Shape::Rectangle(r)
-                })
-                .unwrap_or_else(|e| {
-                    warn!("Failed to map rectangle during serialization: {}", e);
-                    // Fallback to original rectangle (unscaled)
-                    Shape::Rectangle(rect.clone())
-                })
-            }
-            Shape::Circle(circle) => {
-                let mapped_center = Pos2::new(
-                    circle.center.x * scale + image_offset.x,
-                    circle.center.y * scale + image_offset.y,
-                );
-                let mapped_radius = circle.radius * scale;
-
-                // Circle::new returns Result, but we're mapping from existing valid circle
-                // so this should not fail unless coordinates became invalid during transformation
-                Circle::new(mapped_center, mapped_radius, circle.stroke, circle.fill)
-                    .map(|mut c| {
-                        c.name = circle.name.clone();
-                        Shape::Circle(c)
-                    })
-                    .unwrap_or_else(|e| {
-                        warn!("Failed to map circle during serialization: {}", e);
-                        // Fallback to original circle (unscaled)
-                        Shape::Circle(circle.clone())
-                    })
-            }
-            Shape::Polygon(poly) => {
-                // Map polygon points from image space to canvas space
-                let image_points = poly.to_egui_points();
-                let mapped_points: Vec<Pos2> = image_points
-                    .iter()
-                    .map(|p| {
-                        Pos2::new(
-                            p.x * scale + image_offset.x,
-                            p.y * scale + image_offset.y,
-                        )
-                    })
-                    .collect();
-
-                // Convert back to geo coordinates for storage (geo uses f64)
-                let _geo_points: Vec<geo::Coord<f64>> = mapped_points
-                    .iter()
-                    .map(|p| geo::Coord { x: p.x as f64, y: p.y as f64 })
-                    .collect();
-
-                // Use from_points constructor
-                PolygonShape::from_points(mapped_points, poly.stroke, poly.fill)
-                    .map(|mut p| {
-                        p.name = poly.name.clone();
-                        Shape::Polygon(p)
-                    })
-                    .unwrap_or_else(|e| {
-                        warn!("Failed to map polygon: {}", e);
-                        Shape::Polygon(poly.clone())
-                    })
-            }
-        }
-    }
 }
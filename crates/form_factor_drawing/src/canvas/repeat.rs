@@ -0,0 +1,251 @@
+//! Shape duplication shortcut and grid repeat
+//!
+//! Ctrl+D duplicates the selected shape. "Repeat in Grid" stamps copies of
+//! a shape across rows and columns with fixed offsets, which is how repeated
+//! table rows and multi-line answer boxes are annotated in one step. Copies
+//! can be named with a [`NamingScheme`] using their row and column.
+
+use egui::{Vec2, vec2};
+use tracing::{debug, instrument, warn};
+
+use super::command::CanvasCommand;
+use super::core::DrawingCanvas;
+use crate::{NameParts, NamingScheme};
+
+/// Upper bound on copies made by one grid repeat
+pub const MAX_REPEAT_COPIES: usize = 1000;
+
+/// Layout of a grid repeat
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RepeatGrid {
+    /// Number of rows, including the original's row
+    pub rows: usize,
+    /// Number of columns, including the original's column
+    pub columns: usize,
+    /// Displacement from one row to the next, in canvas units
+    pub row_offset: Vec2,
+    /// Displacement from one column to the next, in canvas units
+    pub column_offset: Vec2,
+}
+
+impl Default for RepeatGrid {
+    fn default() -> Self {
+        Self {
+            rows: 2,
+            columns: 1,
+            row_offset: vec2(0.0, 20.0),
+            column_offset: vec2(40.0, 0.0),
+        }
+    }
+}
+
+impl RepeatGrid {
+    /// Create a grid layout
+    pub fn new(rows: usize, columns: usize, row_offset: Vec2, column_offset: Vec2) -> Self {
+        Self {
+            rows,
+            columns,
+            row_offset,
+            column_offset,
+        }
+    }
+
+    /// Grid that stacks copies directly below and beside a shape of the given size
+    pub fn adjacent(rows: usize, columns: usize, size: Vec2) -> Self {
+        Self::new(rows, columns, vec2(0.0, size.y), vec2(size.x, 0.0))
+    }
+
+    /// Number of copies the grid adds, not counting the original
+    pub fn copies(&self) -> usize {
+        (self.rows * self.columns).saturating_sub(1)
+    }
+}
+
+impl DrawingCanvas {
+    /// Fill a grid with copies of a shape, returning the copies' indices
+    ///
+    /// The original occupies the first row and column. Copies are named after
+    /// the original with their 1-based position in row-major order, so a
+    /// shape "amount" repeated in 3 rows yields "amount 2" and "amount 3".
+    /// Nothing is added if the grid would exceed [`MAX_REPEAT_COPIES`].
+    #[instrument(skip(self), fields(shapes = self.shapes.len()))]
+    pub fn repeat_shape(&mut self, index: usize, grid: &RepeatGrid) -> Vec<usize> {
+        self.repeat_shape_with(index, grid, |parts| format!("{} {}", parts.name, parts.index + 1))
+    }
+
+    /// Fill a grid with copies of a shape, naming every cell with a scheme
+    ///
+    /// Unlike [`Self::repeat_shape`], the original is renamed too, so a
+    /// 30-row repeat with `line_{row}_amount` and width 2 yields
+    /// `line_01_amount` through `line_30_amount`.
+    #[instrument(skip(self), fields(shapes = self.shapes.len()))]
+    pub fn repeat_shape_named(&mut self, index: usize, grid: &RepeatGrid, naming: &NamingScheme) -> Vec<usize> {
+        let Some(name) = self.shapes.get(index).map(|shape| shape.name().to_string()) else {
+            return Vec::new();
+        };
+        let added = self.repeat_shape_with(index, grid, |parts| naming.format(parts));
+        if !added.is_empty() {
+            let first = naming.format(&NameParts { name: &name, ..NameParts::default() });
+            self.shapes[index].set_name(first);
+        }
+        added
+    }
+
+    fn repeat_shape_with(
+        &mut self,
+        index: usize,
+        grid: &RepeatGrid,
+        name_for: impl Fn(&NameParts<'_>) -> String,
+    ) -> Vec<usize> {
+        let Some(original) = self.shapes.get(index).cloned() else {
+            return Vec::new();
+        };
+        if grid.copies() > MAX_REPEAT_COPIES {
+            warn!(copies = grid.copies(), "Grid repeat exceeds copy limit");
+            return Vec::new();
+        }
+
+        let mut added = Vec::with_capacity(grid.copies());
+        for row in 0..grid.rows {
+            for column in 0..grid.columns {
+                if row == 0 && column == 0 {
+                    continue;
+                }
+                let mut shape = original.clone();
+                let delta = grid.row_offset * row as f32 + grid.column_offset * column as f32;
+                if let Err(e) = shape.translate(delta) {
+                    warn!("Could not place repeated shape: {}", e);
+                    continue;
+                }
+                shape.set_name(name_for(&NameParts {
+                    index: row * grid.columns + column,
+                    row,
+                    column,
+                    name: original.name(),
+                }));
+                self.shapes.push(shape);
+                added.push(self.shapes.len() - 1);
+            }
+        }
+        debug!(copies = added.len(), "Repeated shape in grid");
+        added
+    }
+
+    /// Open the grid repeat window for a shape
+    ///
+    /// Offsets start at the shape's size so copies sit edge to edge.
+    pub fn open_repeat_panel(&mut self, index: usize) {
+        let Some(shape) = self.shapes.get(index) else {
+            return;
+        };
+        self.repeat_grid = RepeatGrid::adjacent(2, 1, shape.bounding_rect().size());
+        let naming = &self.canvas_settings.field_naming;
+        self.repeat_naming = if naming.uses_grid() { naming.clone() } else { NamingScheme::default() };
+        self.repeat_target = Some(index);
+    }
+
+    /// Show the grid repeat window
+    ///
+    /// Returns true if the window was shown.
+    pub fn show_repeat_panel(&mut self, ctx: &egui::Context) -> bool {
+        let Some(index) = self.repeat_target else {
+            return false;
+        };
+        let Some(name) = self.shapes.get(index).map(|shape| shape.name().to_string()) else {
+            self.repeat_target = None;
+            return false;
+        };
+
+        let mut panel_open = true;
+        let mut apply = false;
+        let grid = &mut self.repeat_grid;
+        let naming = &mut self.repeat_naming;
+        egui::Window::new("Repeat in Grid")
+            .open(&mut panel_open)
+            .resizable(false)
+            .default_width(260.0)
+            .show(ctx, |ui| {
+                ui.label(format!("Shape: {}", name));
+                ui.separator();
+                egui::Grid::new("repeat_grid").num_columns(3).show(ui, |ui| {
+                    ui.label("Rows:");
+                    ui.add(egui::DragValue::new(&mut grid.rows).range(1..=100));
+                    ui.end_row();
+
+                    ui.label("Columns:");
+                    ui.add(egui::DragValue::new(&mut grid.columns).range(1..=100));
+                    ui.end_row();
+
+                    ui.label("Row offset:");
+                    ui.add(egui::DragValue::new(&mut grid.row_offset.x).prefix("x ").speed(0.5));
+                    ui.add(egui::DragValue::new(&mut grid.row_offset.y).prefix("y ").speed(0.5));
+                    ui.end_row();
+
+                    ui.label("Column offset:");
+                    ui.add(egui::DragValue::new(&mut grid.column_offset.x).prefix("x ").speed(0.5));
+                    ui.add(egui::DragValue::new(&mut grid.column_offset.y).prefix("y ").speed(0.5));
+                    ui.end_row();
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Names:");
+                    ui.add(egui::TextEdit::singleline(&mut naming.pattern).hint_text("line_{row}_{name}"));
+                });
+                if naming.is_enabled() {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut naming.start).prefix("start "));
+                        ui.add(egui::DragValue::new(&mut naming.width).range(1..=6).prefix("digits "));
+                    });
+                    let last = NameParts {
+                        index: grid.copies(),
+                        row: grid.rows.saturating_sub(1),
+                        column: grid.columns.saturating_sub(1),
+                        name: &name,
+                    };
+                    ui.weak(format!(
+                        "{} ... {}",
+                        naming.format(&NameParts { name: &name, ..NameParts::default() }),
+                        naming.format(&last)
+                    ));
+                } else {
+                    ui.weak("Empty pattern numbers copies after the shape name");
+                }
+                ui.separator();
+                let copies = grid.copies();
+                ui.label(format!("{} copies", copies));
+                let enabled = copies > 0 && copies <= MAX_REPEAT_COPIES;
+                if ui.add_enabled(enabled, egui::Button::new("Repeat")).clicked() {
+                    apply = true;
+                }
+            });
+
+        if apply {
+            let grid = self.repeat_grid;
+            if self.repeat_naming.is_enabled() {
+                let naming = self.repeat_naming.clone();
+                self.repeat_shape_named(index, &grid, &naming);
+            } else {
+                self.repeat_shape(index, &grid);
+            }
+            self.repeat_target = None;
+        } else if !panel_open {
+            self.repeat_target = None;
+        }
+
+        true
+    }
+
+    /// Duplicate the selected shape on Ctrl+D
+    ///
+    /// Works while the auto-focused name field has focus, since text fields
+    /// don't use the shortcut themselves.
+    pub(super) fn handle_duplicate_shortcut(&mut self, ui: &egui::Ui) {
+        let Some(index) = self.selected_shape else {
+            return;
+        };
+        if ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::D)) {
+            debug!(index, "Duplicate shortcut");
+            self.apply_command(CanvasCommand::DuplicateShape(index));
+        }
+    }
+}
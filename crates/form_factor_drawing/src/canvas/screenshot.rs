@@ -0,0 +1,356 @@
+//! Screenshots of a canvas region for bug reports and feedback
+//!
+//! Starting a capture arms the canvas: the next drag selects a region of the
+//! screen instead of using the active tool. The region is taken from a
+//! screenshot of the window, so it shows the canvas exactly as drawn,
+//! annotations included. A window then offers to copy the capture to the
+//! clipboard, save it as a PNG, or attach it to a new comment on the object
+//! it shows.
+
+use super::context_menu::CanvasAction;
+use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas};
+use crate::{CanvasPos, CommentTarget, ScreenPos, config_dir};
+use egui::{Color32, Pos2, Rect, Stroke};
+use form_factor_core::IoOperation;
+use image::RgbaImage;
+use std::path::{Path, PathBuf};
+use tracing::{debug, error, info, instrument, warn};
+
+/// Marker passed with the screenshot request, to recognize the reply
+struct ScreenshotRequest;
+
+/// Directory screenshots attached to comments are written to
+pub fn screenshots_dir() -> PathBuf {
+    config_dir().join("screenshots")
+}
+
+/// Captured region of the canvas
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScreenshotCapture {
+    /// Pixels of the region as shown on screen
+    pub image: RgbaImage,
+    /// Selected region in screen points
+    pub region: Rect,
+    /// Selected region in canvas coordinates
+    pub canvas_region: Rect,
+}
+
+/// Progress of a screenshot capture
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum ScreenshotStage {
+    /// No capture in progress
+    #[default]
+    Idle,
+    /// Waiting for a region to be dragged out on the canvas
+    Selecting {
+        /// Screen position where the drag started
+        start: Option<Pos2>,
+        /// Latest screen position of the drag
+        end: Option<Pos2>,
+    },
+    /// Region selected, waiting for the window screenshot
+    Waiting {
+        /// Selected region in screen points
+        region: Rect,
+    },
+    /// Region captured, waiting for a choice of what to do with it
+    Captured(ScreenshotCapture),
+}
+
+/// Write an image to the system clipboard
+#[cfg(not(target_arch = "wasm32"))]
+pub fn copy_image_to_clipboard(image: &RgbaImage) -> Result<(), CanvasError> {
+    let data = arboard::ImageData {
+        width: image.width() as usize,
+        height: image.height() as usize,
+        bytes: std::borrow::Cow::Borrowed(image.as_raw()),
+    };
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_image(data))
+        .map_err(|e| CanvasError::new(CanvasErrorKind::Clipboard(e.to_string()), line!(), file!()))
+}
+
+/// Write an image to the system clipboard
+///
+/// Browsers only accept clipboard images from copy events, which egui doesn't forward.
+#[cfg(target_arch = "wasm32")]
+pub fn copy_image_to_clipboard(_image: &RgbaImage) -> Result<(), CanvasError> {
+    Err(CanvasError::new(
+        CanvasErrorKind::Clipboard("clipboard images are unavailable in the browser".to_string()),
+        line!(),
+        file!(),
+    ))
+}
+
+impl DrawingCanvas {
+    /// Arm the canvas so the next drag selects a region to capture
+    pub fn begin_screenshot(&mut self) {
+        self.screenshot_stage = ScreenshotStage::Selecting { start: None, end: None };
+        self.screenshot_comment.clear();
+        debug!("Screenshot region selection started");
+    }
+
+    /// Abandon the capture in progress
+    pub fn cancel_screenshot(&mut self) {
+        self.screenshot_stage = ScreenshotStage::Idle;
+    }
+
+    /// The captured region, once the screenshot has arrived
+    pub fn screenshot(&self) -> Option<&ScreenshotCapture> {
+        match &self.screenshot_stage {
+            ScreenshotStage::Captured(capture) => Some(capture),
+            _ => None,
+        }
+    }
+
+    /// Object a comment with the capture is attached to
+    ///
+    /// The selected shape if there is one, otherwise the topmost visible
+    /// shape, then detection, overlapping the captured region.
+    pub fn screenshot_target(&self) -> Option<CommentTarget> {
+        let capture = self.screenshot()?;
+        if let Some(idx) = self.selected_shape {
+            return Some(CommentTarget::Shape(idx));
+        }
+        let region = capture.canvas_region;
+        if let Some(idx) = self
+            .shapes
+            .iter()
+            .rposition(|shape| !shape.is_hidden() && shape.bounding_rect().intersects(region))
+        {
+            return Some(CommentTarget::Shape(idx));
+        }
+        let mapper = &self.coordinate_mapper;
+        let image_region = mapper
+            .canvas_to_image(CanvasPos(region.min))
+            .zip(mapper.canvas_to_image(CanvasPos(region.max)))
+            .map(|(min, max)| Rect::from_two_pos(min.0, max.0))?;
+        self.detections
+            .iter()
+            .rposition(|d| !d.is_hidden() && d.bounding_rect().intersects(image_region))
+            .map(CommentTarget::Detection)
+    }
+
+    /// Save the captured region as a PNG
+    #[instrument(skip(self))]
+    pub fn save_screenshot(&self, output_path: &str) -> Result<(), CanvasError> {
+        let Some(capture) = self.screenshot() else {
+            return Err(CanvasError::new(
+                CanvasErrorKind::InvalidExport("no screenshot has been captured".to_string()),
+                line!(),
+                file!(),
+            ));
+        };
+        capture
+            .image
+            .save(output_path)
+            .map_err(|e| CanvasError::image(e, output_path, IoOperation::Write, line!(), file!()))?;
+        info!("Saved screenshot to {}", output_path);
+        Ok(())
+    }
+
+    /// Write the captured region to `dir` and start a comment thread showing it
+    ///
+    /// Returns the path the image was written to. The capture is finished
+    /// once attached.
+    #[instrument(skip(self, body))]
+    pub fn attach_screenshot(&mut self, target: CommentTarget, body: &str, dir: &Path) -> Result<PathBuf, CanvasError> {
+        let Some(capture) = self.screenshot() else {
+            return Err(CanvasError::new(
+                CanvasErrorKind::InvalidExport("no screenshot has been captured".to_string()),
+                line!(),
+                file!(),
+            ));
+        };
+        std::fs::create_dir_all(dir)
+            .map_err(|e| CanvasError::io(e, dir.display().to_string(), IoOperation::Create, line!(), file!()))?;
+        let path = unique_screenshot_path(dir);
+        let path_str = path.to_string_lossy().into_owned();
+        capture
+            .image
+            .save(&path)
+            .map_err(|e| CanvasError::image(e, path_str.as_str(), IoOperation::Write, line!(), file!()))?;
+
+        let thread = self.add_comment_thread(target, body);
+        self.comment_threads[thread].comments[0].attachment = Some(path.clone());
+        self.screenshot_stage = ScreenshotStage::Idle;
+        info!("Attached screenshot {} to {}", path_str, target);
+        Ok(path)
+    }
+
+    /// Track a screenshot capture for this frame
+    ///
+    /// Picks up the window screenshot once it arrives and, while a region
+    /// is being selected, takes drags on the canvas and draws the selection.
+    /// Returns true if the canvas input was used.
+    pub(super) fn handle_screenshot_input(&mut self, response: &egui::Response, painter: &egui::Painter) -> bool {
+        self.receive_screenshot(&response.ctx);
+
+        let ScreenshotStage::Selecting { start, end } = &mut self.screenshot_stage else {
+            return false;
+        };
+        if response.ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            debug!("Screenshot cancelled");
+            self.screenshot_stage = ScreenshotStage::Idle;
+            return true;
+        }
+        response.ctx.set_cursor_icon(egui::CursorIcon::Crosshair);
+
+        if response.drag_started() {
+            *start = response.interact_pointer_pos();
+        }
+        if start.is_some()
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            *end = Some(pos);
+        }
+        let Some(selection) = start
+            .zip(*end)
+            .map(|(a, b)| Rect::from_two_pos(a, b).intersect(response.rect))
+        else {
+            return true;
+        };
+        painter.rect_filled(selection, 0.0, Color32::from_white_alpha(40));
+        painter.rect_stroke(
+            selection,
+            0.0,
+            Stroke::new(1.5, Color32::from_rgb(0, 120, 215)),
+            egui::StrokeKind::Outside,
+        );
+
+        if response.drag_stopped() {
+            if selection.width() < 1.0 || selection.height() < 1.0 {
+                *start = None;
+                *end = None;
+                return true;
+            }
+            response
+                .ctx
+                .send_viewport_cmd(egui::ViewportCommand::Screenshot(egui::UserData::new(
+                    ScreenshotRequest,
+                )));
+            self.screenshot_stage = ScreenshotStage::Waiting { region: selection };
+            debug!(?selection, "Screenshot requested");
+        }
+        true
+    }
+
+    /// Crop the window screenshot to the selected region, if it arrived this frame
+    fn receive_screenshot(&mut self, ctx: &egui::Context) {
+        let ScreenshotStage::Waiting { region } = self.screenshot_stage else {
+            return;
+        };
+        let screenshot = ctx.input(|i| {
+            i.events.iter().find_map(|event| match event {
+                egui::Event::Screenshot { user_data, image, .. }
+                    if user_data
+                        .data
+                        .as_ref()
+                        .is_some_and(|data| data.is::<ScreenshotRequest>()) =>
+                {
+                    Some(image.clone())
+                }
+                _ => None,
+            })
+        });
+        let Some(screenshot) = screenshot else {
+            return;
+        };
+
+        let cropped = screenshot.region(&region, Some(ctx.pixels_per_point()));
+        let [width, height] = cropped.size;
+        let pixels = cropped.pixels.iter().flat_map(|c| c.to_srgba_unmultiplied()).collect();
+        let Some(image) = RgbaImage::from_raw(width as u32, height as u32, pixels) else {
+            warn!(width, height, "Screenshot region has an unexpected size");
+            self.screenshot_stage = ScreenshotStage::Idle;
+            return;
+        };
+
+        let mapper = &self.coordinate_mapper;
+        let canvas_region = Rect::from_two_pos(
+            mapper.screen_to_canvas(ScreenPos(region.min)).0,
+            mapper.screen_to_canvas(ScreenPos(region.max)).0,
+        );
+        info!(width, height, "Captured screenshot region");
+        self.screenshot_stage = ScreenshotStage::Captured(ScreenshotCapture {
+            image,
+            region,
+            canvas_region,
+        });
+    }
+
+    /// Show the window offering what to do with a captured region
+    ///
+    /// Saving queues [`CanvasAction::SaveScreenshot`] for the application
+    /// to pick a file. Returns true if the window was shown.
+    pub fn show_screenshot_panel(&mut self, ctx: &egui::Context) -> bool {
+        let Some(capture) = self.screenshot() else {
+            return false;
+        };
+        let (width, height) = capture.image.dimensions();
+        let target = self.screenshot_target();
+
+        let mut panel_open = true;
+        let mut copy = false;
+        let mut save = false;
+        let mut attach = false;
+        egui::Window::new("Screenshot")
+            .open(&mut panel_open)
+            .resizable(false)
+            .collapsible(false)
+            .default_width(280.0)
+            .show(ctx, |ui| {
+                ui.label(format!("Captured {} x {} pixels", width, height));
+                ui.horizontal(|ui| {
+                    copy = ui.button("Copy to clipboard").clicked();
+                    save = ui.button("Save PNG...").clicked();
+                });
+                ui.separator();
+
+                match target {
+                    Some(target) => ui.label(format!("Comment on {}:", target)),
+                    None => ui.weak("Select a shape, or capture one, to comment on it"),
+                };
+                ui.text_edit_multiline(&mut self.screenshot_comment);
+                attach = ui
+                    .add_enabled(target.is_some(), egui::Button::new("Attach to new comment"))
+                    .clicked();
+            });
+
+        if copy
+            && let Some(capture) = self.screenshot()
+            && let Err(e) = copy_image_to_clipboard(&capture.image)
+        {
+            error!("Failed to copy screenshot: {}", e);
+        }
+        if save {
+            self.pending_actions.push(CanvasAction::SaveScreenshot);
+        }
+        if attach && let Some(target) = target {
+            let body = std::mem::take(&mut self.screenshot_comment);
+            match self.attach_screenshot(target, &body, &screenshots_dir()) {
+                Ok(_) => self.show_comments = true,
+                Err(e) => error!("Failed to attach screenshot: {}", e),
+            }
+        }
+        if !panel_open {
+            self.cancel_screenshot();
+        }
+        true
+    }
+}
+
+/// Unused file name for a screenshot in `dir`
+fn unique_screenshot_path(dir: &Path) -> PathBuf {
+    let millis = web_time::SystemTime::now()
+        .duration_since(web_time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+    (0..)
+        .map(|n| match n {
+            0 => dir.join(format!("screenshot_{}.png", millis)),
+            n => dir.join(format!("screenshot_{}_{}.png", millis, n)),
+        })
+        .find(|path| !path.exists())
+        .unwrap_or_else(|| dir.join("screenshot.png"))
+}
@@ -0,0 +1,129 @@
+//! Read-only canvas snapshots for plugins
+//!
+//! A [`CanvasSnapshot`] copies what plugins display (shapes, detections,
+//! layers, selection, view and style presets) once per frame. Clones share
+//! that copy, so every plugin renders from the same state without
+//! borrowing the live canvas.
+
+use super::core::DrawingCanvas;
+use crate::{LayerManager, LayerType, Shape, ToolInfo, ToolMode};
+use std::sync::Arc;
+
+/// Immutable view of the canvas at one point in time
+///
+/// Cloning is cheap; clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct CanvasSnapshot {
+    state: Arc<SnapshotState>,
+}
+
+/// Canvas state shared by the clones of a snapshot
+#[derive(Debug, Default)]
+struct SnapshotState {
+    shapes: Vec<Shape>,
+    detections: Vec<Shape>,
+    layers: LayerManager,
+    selected_shape: Option<usize>,
+    selected_layer: Option<LayerType>,
+    tool: ToolMode,
+    custom_tools: Vec<ToolInfo>,
+    active_custom_tool: Option<String>,
+    zoom: f32,
+    pan_offset: egui::Vec2,
+    style_presets: Vec<String>,
+    style_preset: Option<String>,
+}
+
+impl CanvasSnapshot {
+    /// Shapes drawn on the canvas, in canvas coordinates
+    pub fn shapes(&self) -> &[Shape] {
+        &self.state.shapes
+    }
+
+    /// Detections, in image pixel coordinates
+    pub fn detections(&self) -> &[Shape] {
+        &self.state.detections
+    }
+
+    /// Layer visibility and locks
+    pub fn layers(&self) -> &LayerManager {
+        &self.state.layers
+    }
+
+    /// Index of the selected shape
+    pub fn selected_shape(&self) -> Option<usize> {
+        self.state.selected_shape
+    }
+
+    /// The selected shape
+    pub fn selected(&self) -> Option<&Shape> {
+        self.state.selected_shape.and_then(|idx| self.state.shapes.get(idx))
+    }
+
+    /// Selected layer
+    pub fn selected_layer(&self) -> Option<LayerType> {
+        self.state.selected_layer
+    }
+
+    /// Active tool
+    pub fn tool(&self) -> ToolMode {
+        self.state.tool
+    }
+
+    /// Registered custom tools in toolbar order
+    pub fn custom_tools(&self) -> &[ToolInfo] {
+        &self.state.custom_tools
+    }
+
+    /// Id of the custom tool in use instead of [`tool`](Self::tool)
+    pub fn active_custom_tool(&self) -> Option<&str> {
+        self.state.active_custom_tool.as_deref()
+    }
+
+    /// Zoom factor
+    pub fn zoom(&self) -> f32 {
+        self.state.zoom
+    }
+
+    /// Pan offset
+    pub fn pan_offset(&self) -> egui::Vec2 {
+        self.state.pan_offset
+    }
+
+    /// Names of the style presets in toolbar order
+    pub fn style_presets(&self) -> &[String] {
+        &self.state.style_presets
+    }
+
+    /// Name of the style preset new shapes are drawn with
+    pub fn style_preset(&self) -> Option<&str> {
+        self.state.style_preset.as_deref()
+    }
+
+    /// Whether two snapshots share the same state
+    pub fn ptr_eq(&self, other: &CanvasSnapshot) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+impl DrawingCanvas {
+    /// Take a read-only snapshot of the canvas for plugins
+    pub fn snapshot(&self) -> CanvasSnapshot {
+        CanvasSnapshot {
+            state: Arc::new(SnapshotState {
+                shapes: self.shapes.clone(),
+                detections: self.detections.clone(),
+                layers: self.layer_manager.clone(),
+                selected_shape: self.selected_shape,
+                selected_layer: self.selected_layer,
+                tool: self.current_tool,
+                custom_tools: self.custom_tool_infos(),
+                active_custom_tool: self.active_custom_tool(),
+                zoom: self.zoom_level,
+                pan_offset: self.pan_offset,
+                style_presets: self.canvas_settings.style_presets.names(),
+                style_preset: self.style_preset().map(|preset| preset.name.clone()),
+            }),
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//! Stamps placed with the Stamp tool
+//!
+//! Stamps belong to the Shapes layer and are drawn above the shapes: they
+//! are shown, exported and cleared along with it. Clicking with the Stamp
+//! tool places the symbol chosen in the stamp picker, centered on the
+//! click, and Shift+click removes the stamp under the pointer. The picker
+//! lists the stamp library from the canvas settings.
+//!
+//! Image stamps are uploaded as textures the first time they are drawn; an
+//! image that cannot be read is drawn as a crossed-out box on screen and
+//! left out of exports.
+
+use super::core::DrawingCanvas;
+use super::export::{base64, blend, image_mime, svg_paint, xml_escape};
+use super::refill::draw_text;
+use crate::stamp::{STAMP_BORDER_RATIO, STAMP_FONT_RATIO};
+use crate::{Stamp, StampSource, StampSymbol, ToolMode};
+use ab_glyph::FontRef;
+use egui::{Align, Align2, Color32, FontId, Pos2, Rect, Stroke, StrokeKind, Vec2};
+use image::RgbaImage;
+use tracing::{debug, warn};
+
+impl DrawingCanvas {
+    /// Add a stamp, returning its index
+    pub fn add_stamp(&mut self, stamp: Stamp) -> usize {
+        self.stamps.push(stamp);
+        self.stamps.len() - 1
+    }
+
+    /// Remove a stamp by index
+    pub fn remove_stamp(&mut self, index: usize) -> Option<Stamp> {
+        (index < self.stamps.len()).then(|| self.stamps.remove(index))
+    }
+
+    /// Remove all stamps
+    pub fn clear_stamps(&mut self) {
+        self.stamps.clear();
+    }
+
+    /// Index of the topmost stamp covering a canvas position
+    pub fn stamp_at(&self, pos: Pos2) -> Option<usize> {
+        self.stamps.iter().rposition(|stamp| stamp.bounds.contains(pos))
+    }
+
+    /// Symbol the Stamp tool places: the chosen one, or the first in the library
+    pub fn stamp_symbol(&self) -> Option<&StampSymbol> {
+        let library = &self.canvas_settings.stamps;
+        self.chosen_stamp
+            .as_deref()
+            .and_then(|name| library.get(name))
+            .or_else(|| library.symbols.first())
+    }
+
+    /// Choose the library symbol the Stamp tool places
+    pub fn choose_stamp(&mut self, name: impl Into<String>) {
+        self.chosen_stamp = Some(name.into());
+    }
+
+    /// Place the chosen symbol centered on a canvas position
+    ///
+    /// Returns the index of the new stamp, or `None` if the library is empty.
+    pub fn place_stamp(&mut self, center: Pos2) -> Option<usize> {
+        let stamp = Stamp::new(self.stamp_symbol()?.clone(), center);
+        let idx = self.add_stamp(stamp);
+        debug!(stamp_index = idx, symbol = %self.stamps[idx].symbol.name, "Placed stamp");
+        Some(idx)
+    }
+
+    /// Place a stamp at a click, or remove the one under a Shift+click
+    pub(super) fn handle_stamp_click(&mut self, pos: Pos2, remove: bool) {
+        if remove {
+            if let Some(idx) = self.stamp_at(pos) {
+                self.stamps.remove(idx);
+                debug!(stamp_index = idx, "Removed stamp");
+            }
+        } else if self.place_stamp(pos).is_none() {
+            warn!("The stamp library is empty; add a stamp in the settings");
+        }
+    }
+
+    /// Draw the stamps through a canvas-to-screen transform
+    pub(super) fn render_stamps(
+        &mut self,
+        ctx: &egui::Context,
+        painter: &egui::Painter,
+        transform: &egui::emath::TSTransform,
+    ) {
+        for stamp in &self.stamps {
+            let rect = *transform * stamp.bounds;
+            match &stamp.symbol.source {
+                StampSource::Text { text, color } => {
+                    let border = Stroke::new(rect.height() * STAMP_BORDER_RATIO, *color);
+                    painter.rect_stroke(rect, rect.height() * 0.1, border, StrokeKind::Inside);
+                    painter.text(
+                        rect.center(),
+                        Align2::CENTER_CENTER,
+                        text,
+                        FontId::proportional(rect.height() * STAMP_FONT_RATIO),
+                        *color,
+                    );
+                }
+                StampSource::Image { path } => {
+                    let texture = self
+                        .textures
+                        .get_or_load(&format!("stamp:{}", path), || load_stamp_texture(ctx, path));
+                    match texture {
+                        Some(texture) => {
+                            let uv = Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0));
+                            painter.image(texture.id(), rect, uv, Color32::WHITE);
+                        }
+                        None => {
+                            let stroke = Stroke::new(1.0, Color32::GRAY);
+                            painter.rect_stroke(rect, 0.0, stroke, StrokeKind::Inside);
+                            painter.line_segment([rect.left_top(), rect.right_bottom()], stroke);
+                            painter.line_segment([rect.right_top(), rect.left_bottom()], stroke);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Show the stamp picker while the Stamp tool is active
+    ///
+    /// Returns true if the window was shown.
+    pub fn show_stamp_panel(&mut self, ctx: &egui::Context) -> bool {
+        if self.current_tool != ToolMode::Stamp {
+            return false;
+        }
+
+        let chosen = self.stamp_symbol().map(|symbol| symbol.name.clone());
+        let mut choice = None;
+        egui::Window::new("Stamps")
+            .resizable(false)
+            .default_width(180.0)
+            .show(ctx, |ui| {
+                let symbols = &self.canvas_settings.stamps.symbols;
+                if symbols.is_empty() {
+                    ui.label("The stamp library is empty; add stamps in the settings");
+                }
+                for symbol in symbols {
+                    let selected = chosen.as_deref() == Some(symbol.name.as_str());
+                    if ui.selectable_label(selected, &symbol.name).clicked() {
+                        choice = Some(symbol.name.clone());
+                    }
+                }
+                ui.separator();
+                ui.label("Click to place; Shift+click a stamp to remove it");
+            });
+
+        if let Some(name) = choice {
+            debug!(%name, "Stamp chosen");
+            self.choose_stamp(name);
+        }
+        true
+    }
+}
+
+/// Upload an image stamp as a texture, or `None` if it cannot be read
+fn load_stamp_texture(ctx: &egui::Context, path: &str) -> Option<egui::TextureHandle> {
+    let image = image::open(path)
+        .inspect_err(|e| warn!(path, error = %e, "Failed to read stamp image"))
+        .ok()?
+        .to_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    let color_image = egui::ColorImage::from_rgba_unmultiplied(size, image.as_flat_samples().as_slice());
+    Some(ctx.load_texture(format!("stamp:{}", path), color_image, egui::TextureOptions::LINEAR))
+}
+
+/// Scale a stamp about the origin and then translate it by `offset`
+pub(super) fn mapped_stamp(stamp: &Stamp, scale: f32, offset: Pos2) -> Stamp {
+    let map = |p: Pos2| Pos2::new(p.x * scale + offset.x, p.y * scale + offset.y);
+    Stamp {
+        symbol: stamp.symbol.clone(),
+        bounds: Rect::from_min_max(map(stamp.bounds.min), map(stamp.bounds.max)),
+    }
+}
+
+/// Draw a stamp onto an image
+pub(super) fn rasterize_stamp(target: &mut RgbaImage, font: &FontRef<'_>, stamp: &Stamp) {
+    let bounds = stamp.bounds;
+    match &stamp.symbol.source {
+        StampSource::Text { text, color } => {
+            let border = bounds.height() * STAMP_BORDER_RATIO;
+            let inner = bounds.shrink(border);
+            let (x0, y0) = (bounds.min.x.floor().max(0.0) as u32, bounds.min.y.floor().max(0.0) as u32);
+            let (x1, y1) = (
+                (bounds.max.x.ceil().max(0.0) as u32).min(target.width()),
+                (bounds.max.y.ceil().max(0.0) as u32).min(target.height()),
+            );
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    let p = Pos2::new(x as f32 + 0.5, y as f32 + 0.5);
+                    if bounds.contains(p) && !inner.contains(p) {
+                        blend(target.get_pixel_mut(x, y), *color);
+                    }
+                }
+            }
+            let size = bounds.height() * STAMP_FONT_RATIO;
+            let text_area = inner.shrink2(Vec2::new(border, 0.0));
+            draw_text(target, font, text_area, text, size, *color, Align::Center);
+        }
+        StampSource::Image { path } => {
+            let image = match image::open(path) {
+                Ok(image) => image.to_rgba8(),
+                Err(e) => {
+                    warn!(path, error = %e, "Stamp image left out of the export");
+                    return;
+                }
+            };
+            let (width, height) = (bounds.width().round().max(1.0) as u32, bounds.height().round().max(1.0) as u32);
+            let resized = image::imageops::resize(&image, width, height, image::imageops::FilterType::Triangle);
+            let (left, top) = (bounds.min.x.round() as i64, bounds.min.y.round() as i64);
+            for (x, y, pixel) in resized.enumerate_pixels() {
+                let (px, py) = (left + x as i64, top + y as i64);
+                if px < 0 || py < 0 || px >= target.width() as i64 || py >= target.height() as i64 {
+                    continue;
+                }
+                let [r, g, b, a] = pixel.0;
+                blend(target.get_pixel_mut(px as u32, py as u32), Color32::from_rgba_unmultiplied(r, g, b, a));
+            }
+        }
+    }
+}
+
+/// SVG element of a stamp, or `None` for an image stamp that cannot be read
+pub(super) fn svg_stamp(stamp: &Stamp) -> Option<String> {
+    let bounds = stamp.bounds;
+    let title = format!("<title>{}</title>", xml_escape(&stamp.symbol.name));
+    match &stamp.symbol.source {
+        StampSource::Text { text, color } => {
+            let (color, opacity) = svg_paint(*color);
+            let border = bounds.height() * STAMP_BORDER_RATIO;
+            Some(format!(
+                "<g>{}<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" rx=\"{:.2}\" fill=\"none\" \
+                 stroke=\"{}\" stroke-opacity=\"{:.3}\" stroke-width=\"{:.2}\"/>\
+                 <text x=\"{:.2}\" y=\"{:.2}\" text-anchor=\"middle\" dominant-baseline=\"central\" \
+                 font-size=\"{:.2}\" font-weight=\"bold\" fill=\"{}\" fill-opacity=\"{:.3}\">{}</text></g>",
+                title,
+                bounds.min.x + border / 2.0,
+                bounds.min.y + border / 2.0,
+                bounds.width() - border,
+                bounds.height() - border,
+                bounds.height() * 0.1,
+                color,
+                opacity,
+                border,
+                bounds.center().x,
+                bounds.center().y,
+                bounds.height() * STAMP_FONT_RATIO,
+                color,
+                opacity,
+                xml_escape(text)
+            ))
+        }
+        StampSource::Image { path } => {
+            let bytes = std::fs::read(path)
+                .inspect_err(|e| warn!(path, error = %e, "Stamp image left out of the export"))
+                .ok()?;
+            Some(format!(
+                "<image x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" \
+                 preserveAspectRatio=\"none\" href=\"data:{};base64,{}\">{}</image>",
+                bounds.min.x,
+                bounds.min.y,
+                bounds.width(),
+                bounds.height(),
+                image_mime(path),
+                base64(&bytes),
+                title
+            ))
+        }
+    }
+}
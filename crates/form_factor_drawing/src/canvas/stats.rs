@@ -0,0 +1,69 @@
+//! Session statistics window
+
+use super::core::DrawingCanvas;
+
+impl DrawingCanvas {
+    /// Toggle the session statistics window
+    pub fn toggle_stats_panel(&mut self) {
+        self.show_stats = !self.show_stats;
+    }
+
+    /// Show the session statistics window
+    ///
+    /// Returns true if the window was shown.
+    pub fn show_stats_panel(&mut self, ctx: &egui::Context) -> bool {
+        if !self.show_stats {
+            return false;
+        }
+
+        let stats = &self.session_stats;
+        let rate = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{:.1}", v));
+
+        let mut panel_open = true;
+        egui::Window::new("Session Stats")
+            .open(&mut panel_open)
+            .resizable(false)
+            .default_width(240.0)
+            .show(ctx, |ui| {
+                ui.label(format!("Form: {}", self.project_name));
+                ui.separator();
+                egui::Grid::new("session_stats_grid").num_columns(2).show(ui, |ui| {
+                    let minutes = stats.active_seconds() / 60.0;
+                    ui.label("Active time:");
+                    ui.label(format!("{:.0}h {:02.0}m", (minutes / 60.0).floor(), minutes % 60.0));
+                    ui.end_row();
+
+                    ui.label("Shapes created:");
+                    ui.label(stats.shapes_created().to_string());
+                    ui.end_row();
+
+                    ui.label("Fields corrected:");
+                    ui.label(stats.fields_corrected().to_string());
+                    ui.end_row();
+
+                    ui.label("Shapes / hour:");
+                    ui.label(rate(stats.shapes_per_hour()));
+                    ui.end_row();
+
+                    ui.label("Corrections / hour:");
+                    ui.label(rate(stats.corrections_per_hour()));
+                    ui.end_row();
+
+                    let megabytes = |bytes: usize| bytes as f64 / (1024.0 * 1024.0);
+                    ui.label("Texture memory:");
+                    ui.label(format!(
+                        "{:.1} of {:.0} MB",
+                        megabytes(self.texture_memory()),
+                        megabytes(self.textures.budget())
+                    ));
+                    ui.end_row();
+                });
+            });
+
+        if !panel_open {
+            self.show_stats = false;
+        }
+
+        true
+    }
+}
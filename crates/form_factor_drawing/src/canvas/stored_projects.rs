@@ -0,0 +1,265 @@
+//! Opening and saving projects in the configured project store
+//!
+//! The user settings choose a [`ProjectStore`](crate::ProjectStore), a
+//! directory or an S3 bucket, shared by the operators of a deployment. The
+//! project store window lists the stored projects, opens one, and saves the
+//! canvas under a name. A project opened from or saved to the store
+//! remembers its stored version and is saved back there until it is saved
+//! to a file: if someone else saved it since, saving fails with a
+//! [`StoreErrorKind::Conflict`] rather than overwriting their changes, and
+//! the save conflict window offers to merge or overwrite.
+//! A password-protected project is stored encrypted and opens through the
+//! unlock window, keeping its password for the next save.
+
+use super::core::DrawingCanvas;
+use super::encryption::LockedSource;
+use super::journal::project_map;
+use crate::{StoreError, StoreErrorKind};
+use serde_json::{Map, Value};
+use tracing::{debug, info, instrument, warn};
+
+/// Project as it was opened from or last saved to the store
+#[derive(Debug, Clone)]
+pub(super) struct StoredProject {
+    /// Name the project is stored under
+    pub(super) name: String,
+    /// Version of the stored project, to save back only over that version
+    version: String,
+    /// Project as stored, to tell whether it changed since
+    pub(super) saved: Map<String, Value>,
+}
+
+impl StoredProject {
+    fn new(name: &str, version: String, canvas: &DrawingCanvas) -> Self {
+        Self {
+            name: name.to_string(),
+            version,
+            saved: project_map(canvas).unwrap_or_default(),
+        }
+    }
+}
+
+impl DrawingCanvas {
+    /// Toggle the project store window, listing the stored projects when it opens
+    pub fn toggle_store_panel(&mut self) {
+        self.show_store = !self.show_store;
+        if self.show_store {
+            if self.store_name.is_empty() {
+                self.store_name = self.stored_project().unwrap_or(&self.project_name).to_string();
+            }
+            self.refresh_store_listing();
+        }
+    }
+
+    /// Name the project is stored under, if it was opened from or saved to the store
+    pub fn stored_project(&self) -> Option<&str> {
+        self.stored_project.as_ref().map(|stored| stored.name.as_str())
+    }
+
+    /// Names of the projects in the configured store, sorted
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store can't be opened or listed.
+    pub fn stored_projects(&self) -> Result<Vec<String>, StoreError> {
+        self.storage.open()?.list_projects()
+    }
+
+    /// Replace the canvas with a project from the configured store
+    ///
+    /// A password-protected project fails with a [`StoreErrorKind::Format`]
+    /// error and opens the unlock window.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store can't be opened or the project is
+    /// missing or can't be read; the canvas is left as it was.
+    #[instrument(skip(self, ctx))]
+    pub fn open_stored_project(&mut self, name: &str, ctx: &egui::Context) -> Result<(), StoreError> {
+        self.read_stored_project(name, None, ctx)
+    }
+
+    /// Replace the canvas with a project from the configured store protected with `password`
+    ///
+    /// Unprotected projects open too, and are protected with `password`
+    /// from the next save on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store can't be opened, the project is
+    /// missing or can't be read, or `password` doesn't unlock it.
+    #[instrument(skip(self, password, ctx))]
+    pub fn open_protected_stored_project(
+        &mut self,
+        name: &str,
+        password: &str,
+        ctx: &egui::Context,
+    ) -> Result<(), StoreError> {
+        self.read_stored_project(name, Some(password), ctx)
+    }
+
+    /// Replace the canvas with a stored project, unlocking it with `password` if given
+    fn read_stored_project(&mut self, name: &str, password: Option<&str>, ctx: &egui::Context) -> Result<(), StoreError> {
+        let (bytes, version) = self.storage.open()?.read_project_versioned(name)?;
+        let parsed = match password {
+            Some(password) => DrawingCanvas::from_protected_bytes(&bytes, password),
+            None => DrawingCanvas::from_bytes(&bytes),
+        };
+        let loaded = self
+            .note_locked(parsed, || LockedSource::Store(name.to_string()))
+            .map_err(|e| StoreError::new(StoreErrorKind::Format(format!("{}: {}", name, e.kind)), line!(), file!()))?;
+        self.apply_project(loaded, ctx, false);
+        self.project_password = password.map(str::to_string);
+        self.stored_project = Some(StoredProject::new(name, version, self));
+        info!("Opened stored project");
+        Ok(())
+    }
+
+    /// Save the project to the configured store under `name`
+    ///
+    /// Saving under the name the project was opened or last saved under
+    /// replaces it there, unless someone else saved it since. Any other
+    /// name must not be taken yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`StoreErrorKind::Conflict`] error if the stored project
+    /// changed since it was opened, opening the save conflict window, or
+    /// if the name is taken, or another error if the store can't be
+    /// opened or written.
+    #[instrument(skip(self))]
+    pub fn save_to_store(&mut self, name: &str) -> Result<(), StoreError> {
+        let version = match &self.stored_project {
+            Some(stored) if stored.name == name => Some(stored.version.clone()),
+            _ => None,
+        };
+        let result = self.save_over_stored(name, version.as_deref());
+        if let Err(e) = &result
+            && matches!(e.kind, StoreErrorKind::Conflict(_))
+            && version.is_some()
+        {
+            self.note_store_conflict(name);
+        }
+        result
+    }
+
+    /// Save the project to the store under `name` if it is still at `version`
+    pub(super) fn save_over_stored(&mut self, name: &str, version: Option<&str>) -> Result<(), StoreError> {
+        let version = self.storage.open()?.save_project_if(name, self, version)?;
+        self.stored_project = Some(StoredProject::new(name, version, self));
+        info!(name, "Saved project to the store");
+        Ok(())
+    }
+
+    /// Take the stored project chosen in the project store window, to open once unsaved work is dealt with
+    pub fn take_store_open_request(&mut self) -> Option<String> {
+        self.store_open_request.take()
+    }
+
+    /// Open a project chosen in the project store window, reporting the outcome there
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`DrawingCanvas::open_stored_project`].
+    pub fn open_store_request(&mut self, name: &str, ctx: &egui::Context) -> Result<(), StoreError> {
+        let result = self.open_stored_project(name, ctx);
+        self.store_status = Some(match &result {
+            Ok(()) => {
+                self.store_name = name.to_string();
+                format!("Opened \"{}\"", name)
+            }
+            Err(_) if self.locked_project().is_some() => format!("\"{}\" is password protected", name),
+            Err(e) => e.kind.to_string(),
+        });
+        result
+    }
+
+    /// List the stored projects again for the project store window
+    fn refresh_store_listing(&mut self) {
+        match self.stored_projects() {
+            Ok(names) => self.store_listing = names,
+            Err(e) => {
+                warn!("Could not list stored projects: {}", e);
+                self.store_listing.clear();
+                self.store_status = Some(e.kind.to_string());
+            }
+        }
+    }
+
+    /// Show the project store window
+    ///
+    /// A project chosen to open waits for [`DrawingCanvas::take_store_open_request`],
+    /// so the application can ask about unsaved work first. Returns true if
+    /// the window was shown.
+    pub fn show_store_panel(&mut self, ctx: &egui::Context) -> bool {
+        if !self.show_store {
+            return false;
+        }
+
+        let mut panel_open = true;
+        let mut open = None;
+        let mut save = false;
+        let mut refresh = false;
+        let stored = self.stored_project().map(str::to_string);
+        egui::Window::new("Project Store")
+            .open(&mut panel_open)
+            .resizable(true)
+            .default_width(300.0)
+            .show(ctx, |ui| {
+                if let Some(name) = &stored {
+                    ui.label(format!("This project is stored as \"{}\".", name));
+                }
+                egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                    if self.store_listing.is_empty() {
+                        ui.weak("No stored projects");
+                    }
+                    for name in &self.store_listing {
+                        ui.horizontal(|ui| {
+                            if ui.button("Open").clicked() {
+                                open = Some(name.clone());
+                            }
+                            ui.label(name);
+                        });
+                    }
+                });
+                refresh = ui.button("Refresh").clicked();
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Name");
+                    ui.text_edit_singleline(&mut self.store_name);
+                });
+                save = ui
+                    .add_enabled(!self.store_name.trim().is_empty(), egui::Button::new("Save to Store"))
+                    .clicked();
+                if let Some(status) = &self.store_status {
+                    ui.label(status.as_str());
+                }
+            });
+
+        if let Some(name) = open {
+            self.store_open_request = Some(name);
+        } else if save {
+            let name = self.store_name.trim().to_string();
+            self.store_status = Some(match self.save_to_store(&name) {
+                Ok(()) => {
+                    self.refresh_store_listing();
+                    format!("Saved \"{}\"", name)
+                }
+                Err(e) if matches!(e.kind, StoreErrorKind::Conflict(_)) && self.stored_project() != Some(&name) => {
+                    format!("A project named \"{}\" is already stored", name)
+                }
+                Err(e) => e.kind.to_string(),
+            });
+        } else if refresh {
+            self.store_status = None;
+            self.refresh_store_listing();
+        }
+        if !panel_open {
+            debug!("Project store window closed");
+            self.show_store = false;
+            self.store_status = None;
+        }
+
+        true
+    }
+}
@@ -0,0 +1,87 @@
+//! Subtype tags on detections and the subtype filter
+//!
+//! A detection can be tagged with a subtype from the detection taxonomy in
+//! the settings, from its context menu. Tagged detections are drawn and
+//! listed in the legend in their subtype's color, and the Detections layer
+//! can be narrowed on screen to one subtype. Tags are saved with the
+//! project and stay with their detections when others are deleted.
+
+use super::core::DrawingCanvas;
+use crate::DetectionSubtype;
+use tracing::debug;
+
+impl DrawingCanvas {
+    /// Tag a detection with a subtype from the taxonomy, or clear its tag with `None`
+    ///
+    /// Returns false, leaving the tags as they were, if there is no such
+    /// detection or the subtype is not in the taxonomy.
+    pub fn tag_detection(&mut self, index: usize, subtype: Option<String>) -> bool {
+        if index >= self.detections.len() {
+            return false;
+        }
+        if let Some(name) = &subtype
+            && self.detection_settings.taxonomy.get(name).is_none()
+        {
+            return false;
+        }
+        self.detection_tags.retain(|(idx, _)| *idx != index);
+        if let Some(name) = subtype {
+            debug!(detection_index = index, subtype = %name, "Tagged detection");
+            self.detection_tags.push((index, name));
+            self.detection_tags.sort_by_key(|(idx, _)| *idx);
+        }
+        true
+    }
+
+    /// Name of the subtype a detection is tagged with
+    pub fn detection_tag(&self, index: usize) -> Option<&str> {
+        self.detection_tags
+            .iter()
+            .find(|(idx, _)| *idx == index)
+            .map(|(_, name)| name.as_str())
+    }
+
+    /// Subtype a detection is tagged with, if it is still in the taxonomy
+    pub fn detection_subtype(&self, index: usize) -> Option<&DetectionSubtype> {
+        self.detection_settings.taxonomy.get(self.detection_tag(index)?)
+    }
+
+    /// Number of detections tagged with a subtype
+    pub fn subtype_count(&self, name: &str) -> usize {
+        self.detection_tags.iter().filter(|(_, tag)| tag == name).count()
+    }
+
+    /// Whether a detection is shown under the subtype filter
+    pub fn passes_subtype_filter(&self, index: usize) -> bool {
+        match &self.selected_detection_subtype {
+            Some(name) => self.detection_tag(index) == Some(name.as_str()),
+            None => true,
+        }
+    }
+
+    /// Subtype submenu and filter entries of a detection's context menu
+    pub(super) fn subtype_menu(&mut self, ui: &mut egui::Ui, idx: usize) {
+        let current = self.detection_tag(idx).map(str::to_string);
+        let mut choice = current.clone();
+        ui.menu_button("Subtype", |ui| {
+            ui.radio_value(&mut choice, None, "None");
+            for subtype in &self.detection_settings.taxonomy.subtypes {
+                ui.radio_value(&mut choice, Some(subtype.name.clone()), &subtype.name)
+                    .on_hover_text(&subtype.description);
+            }
+        });
+        if choice != current {
+            self.tag_detection(idx, choice);
+        }
+
+        if let Some(name) = self.detection_tag(idx).map(str::to_string)
+            && self.selected_detection_subtype.as_ref() != Some(&name)
+            && ui.button(format!("Show Only {}", name)).clicked()
+        {
+            self.set_selected_detection_subtype(Some(name));
+        }
+        if self.selected_detection_subtype.is_some() && ui.button("Show All Detections").clicked() {
+            self.set_selected_detection_subtype(None);
+        }
+    }
+}
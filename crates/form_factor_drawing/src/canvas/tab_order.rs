@@ -0,0 +1,204 @@
+//! Order in which data entry visits the fields
+//!
+//! Fields are entered in the order they were drawn unless the project sets
+//! a tab order, which rarely matches: fields get added as they are noticed,
+//! not as the form reads. The tab order window lists the fields in entry
+//! order; dragging a field moves it, and Auto orders them by reading
+//! direction, in rows from the top with each row read across.
+//!
+//! The order is saved with the project and carried by templates made from
+//! it. Fields missing from the saved order, such as ones drawn after it was
+//! set, follow the ordered fields in the order they were drawn.
+
+use egui::Rect;
+use tracing::{debug, instrument};
+
+use super::core::DrawingCanvas;
+use crate::Shape;
+
+/// Direction fields are read across a row when ordering them automatically
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, strum::EnumIter, strum::Display)]
+pub enum ReadingDirection {
+    /// Rows read from left to right
+    #[default]
+    #[strum(to_string = "Left to right")]
+    LeftToRight,
+    /// Rows read from right to left
+    #[strum(to_string = "Right to left")]
+    RightToLeft,
+}
+
+impl DrawingCanvas {
+    /// Shape indices in the order data entry visits them
+    pub fn field_order(&self) -> Vec<usize> {
+        let count = self.shapes.len();
+        let mut seen = vec![false; count];
+        let mut order: Vec<usize> = self
+            .tab_order
+            .iter()
+            .copied()
+            .filter(|idx| *idx < count && !std::mem::replace(&mut seen[*idx], true))
+            .collect();
+        order.extend((0..count).filter(|idx| !seen[*idx]));
+        order
+    }
+
+    /// Set the order data entry visits fields in, as shape indices
+    ///
+    /// Unknown and repeated indices are dropped; fields left out follow the
+    /// listed ones in the order they were drawn.
+    pub fn set_tab_order(&mut self, order: Vec<usize>) {
+        self.tab_order = order;
+        self.tab_order = self.field_order();
+    }
+
+    /// Move the field at position `from` in the tab order to position `to`
+    ///
+    /// Returns false if either position is out of range.
+    #[instrument(skip(self))]
+    pub fn move_in_tab_order(&mut self, from: usize, to: usize) -> bool {
+        let mut order = self.field_order();
+        if from >= order.len() || to >= order.len() {
+            return false;
+        }
+        let idx = order.remove(from);
+        order.insert(to, idx);
+        self.tab_order = order;
+        true
+    }
+
+    /// Order the fields by reading direction
+    ///
+    /// Fields whose middles fall within the height of the topmost field of
+    /// a row join that row; rows are read from the top.
+    #[instrument(skip(self))]
+    pub fn auto_tab_order(&mut self, direction: ReadingDirection) {
+        let bounds: Vec<Rect> = self.shapes.iter().map(Shape::bounding_rect).collect();
+        self.tab_order = reading_order(&bounds, direction);
+        debug!(fields = self.tab_order.len(), "Ordered fields by reading direction");
+    }
+
+    /// Go back to entering fields in the order they were drawn
+    pub fn reset_tab_order(&mut self) {
+        self.tab_order.clear();
+    }
+
+    /// Toggle the tab order window
+    pub fn toggle_tab_order_panel(&mut self) {
+        self.show_tab_order = !self.show_tab_order;
+    }
+
+    /// Show the tab order window
+    ///
+    /// Returns true if the window was shown.
+    pub fn show_tab_order_panel(&mut self, ctx: &egui::Context) -> bool {
+        if !self.show_tab_order {
+            return false;
+        }
+
+        let order = self.field_order();
+        let mut panel_open = true;
+        let mut auto = false;
+        let mut reset = false;
+        let mut moved = None;
+        let direction = &mut self.tab_order_direction;
+        let shapes = &self.shapes;
+        egui::Window::new("Tab Order")
+            .open(&mut panel_open)
+            .default_width(280.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("tab_order_direction")
+                        .selected_text(direction.to_string())
+                        .show_ui(ui, |ui| {
+                            for option in <ReadingDirection as strum::IntoEnumIterator>::iter() {
+                                ui.selectable_value(direction, option, option.to_string());
+                            }
+                        });
+                    auto = ui.button("Auto").on_hover_text("Order fields by reading direction").clicked();
+                    reset = ui.button("Reset").on_hover_text("Enter fields in the order they were drawn").clicked();
+                });
+                ui.weak("Drag fields to reorder them");
+                ui.separator();
+
+                if order.is_empty() {
+                    ui.label("Draw shapes over the form's fields to order them.");
+                    return;
+                }
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    for (position, idx) in order.iter().enumerate() {
+                        let label = match shapes[*idx].name() {
+                            "" => format!("{}. Field {}", position + 1, idx + 1),
+                            name => format!("{}. {}", position + 1, name),
+                        };
+                        let response = ui
+                            .dnd_drag_source(egui::Id::new(("tab_order", *idx)), position, |ui| ui.label(label))
+                            .response;
+                        if let Some(pointer) = ui.input(|i| i.pointer.interact_pos())
+                            && response.dnd_hover_payload::<usize>().is_some()
+                        {
+                            // Line where the dragged field will land
+                            let rect = response.rect;
+                            let y = if pointer.y < rect.center().y { rect.top() } else { rect.bottom() };
+                            ui.painter().hline(rect.x_range(), y, ui.visuals().selection.stroke);
+                        }
+                        if let Some(from) = response.dnd_release_payload::<usize>() {
+                            let below = ui
+                                .input(|i| i.pointer.interact_pos())
+                                .is_some_and(|pointer| pointer.y >= response.rect.center().y);
+                            moved = Some((*from, drop_position(*from, position, below)));
+                        }
+                    }
+                });
+            });
+
+        if auto {
+            self.auto_tab_order(self.tab_order_direction);
+        } else if reset {
+            self.reset_tab_order();
+        } else if let Some((from, to)) = moved {
+            self.move_in_tab_order(from, to);
+        }
+        if !panel_open {
+            self.show_tab_order = false;
+        }
+
+        true
+    }
+}
+
+/// Position a field dragged from `from` ends at when dropped above or below position `target`
+pub(super) fn drop_position(from: usize, target: usize, below: bool) -> usize {
+    let slot = if below { target + 1 } else { target };
+    // Removing the dragged field first shifts later positions up
+    if slot > from { slot - 1 } else { slot }
+}
+
+/// Indices of `bounds` in reading order: rows from the top, each read across
+pub(super) fn reading_order(bounds: &[Rect], direction: ReadingDirection) -> Vec<usize> {
+    let mut by_top: Vec<usize> = (0..bounds.len()).collect();
+    by_top.sort_by(|a, b| bounds[*a].top().total_cmp(&bounds[*b].top()));
+
+    // Each row ends at the bottom of the field that started it
+    let mut rows: Vec<(f32, Vec<usize>)> = Vec::new();
+    for idx in by_top {
+        let middle = bounds[idx].center().y;
+        match rows.last_mut() {
+            Some((bottom, row)) if middle <= *bottom => row.push(idx),
+            _ => rows.push((bounds[idx].bottom(), vec![idx])),
+        }
+    }
+
+    rows.into_iter()
+        .flat_map(|(_, mut row)| {
+            row.sort_by(|a, b| {
+                let across = bounds[*a].center().x.total_cmp(&bounds[*b].center().x);
+                match direction {
+                    ReadingDirection::LeftToRight => across,
+                    ReadingDirection::RightToLeft => across.reverse(),
+                }
+            });
+            row
+        })
+        .collect()
+}
@@ -0,0 +1,200 @@
+//! Searching, filtering and sorting project templates
+//!
+//! An office with hundreds of templates finds the one it needs by typing
+//! part of its name, form number, description or a tag, and narrowing the
+//! list by tag, department or OCR language. Templates without a department
+//! or OCR settings never match a filter on those. Searches ignore case.
+
+use super::project_template::ProjectTemplate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Order templates are listed in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, strum::EnumIter, strum::Display)]
+pub enum TemplateSortOrder {
+    /// Alphabetical by name
+    #[default]
+    Name,
+    /// By form number, templates without one last
+    #[strum(to_string = "Form number")]
+    FormNumber,
+    /// By department, then name
+    Department,
+    /// Most projects started first
+    #[strum(to_string = "Most used")]
+    MostUsed,
+}
+
+/// Which templates to list
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TemplateFilter {
+    /// Text to find in the name, form number, department, description or tags
+    pub query: String,
+    /// Tag the template must have
+    pub tag: Option<String>,
+    /// Department the template must belong to
+    pub department: Option<String>,
+    /// OCR language the template must read, like `spa`
+    pub language: Option<String>,
+    /// Whether deprecated templates are listed
+    pub include_deprecated: bool,
+}
+
+impl TemplateFilter {
+    /// Filter listing templates that mention `query`
+    pub fn search(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Only list templates with `tag`
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    /// Only list templates of `department`
+    pub fn with_department(mut self, department: impl Into<String>) -> Self {
+        self.department = Some(department.into());
+        self
+    }
+
+    /// Only list templates reading `language`
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Whether a template passes the filter
+    pub fn matches(&self, template: &ProjectTemplate) -> bool {
+        if template.deprecated && !self.include_deprecated {
+            return false;
+        }
+        if let Some(tag) = &self.tag
+            && !template.has_tag(tag)
+        {
+            return false;
+        }
+        if let Some(department) = &self.department
+            && !template.department.eq_ignore_ascii_case(department)
+        {
+            return false;
+        }
+        if let Some(language) = &self.language {
+            let reads = template
+                .ocr
+                .as_ref()
+                .is_some_and(|ocr| ocr.language.split('+').any(|code| code.eq_ignore_ascii_case(language)));
+            if !reads {
+                return false;
+            }
+        }
+
+        let query = self.query.trim().to_lowercase();
+        query.is_empty()
+            || [&template.name, &template.form_number, &template.department, &template.description]
+                .into_iter()
+                .chain(&template.tags)
+                .any(|text| text.to_lowercase().contains(&query))
+    }
+
+    /// Indices of the templates passing the filter, in `order`
+    pub fn apply(&self, templates: &[ProjectTemplate], order: TemplateSortOrder) -> Vec<usize> {
+        let mut listed: Vec<usize> = (0..templates.len()).filter(|idx| self.matches(&templates[*idx])).collect();
+        listed.sort_by(|a, b| {
+            let (a, b) = (&templates[*a], &templates[*b]);
+            let by_name = || a.name.to_lowercase().cmp(&b.name.to_lowercase());
+            match order {
+                TemplateSortOrder::Name => by_name(),
+                TemplateSortOrder::FormNumber => (a.form_number.is_empty(), &a.form_number)
+                    .cmp(&(b.form_number.is_empty(), &b.form_number))
+                    .then_with(by_name),
+                TemplateSortOrder::Department => (a.department.is_empty(), a.department.to_lowercase())
+                    .cmp(&(b.department.is_empty(), b.department.to_lowercase()))
+                    .then_with(by_name),
+                TemplateSortOrder::MostUsed => b.instances.cmp(&a.instances).then_with(by_name),
+            }
+        });
+        listed
+    }
+}
+
+/// Every tag used by the templates, sorted
+///
+/// Tags differing only in case are listed once, as first spelled.
+pub fn template_tags(templates: &[ProjectTemplate]) -> Vec<String> {
+    distinct(templates.iter().flat_map(|template| template.tags.iter().cloned()))
+}
+
+/// Every department with templates, sorted, ignoring case like [`template_tags`]
+pub fn template_departments(templates: &[ProjectTemplate]) -> Vec<String> {
+    distinct(templates.iter().map(|template| template.department.clone()))
+}
+
+/// Every OCR language the templates read, sorted
+pub fn template_languages(templates: &[ProjectTemplate]) -> Vec<String> {
+    distinct(
+        templates
+            .iter()
+            .filter_map(|template| template.ocr.as_ref())
+            .flat_map(|ocr| ocr.language.split('+').map(str::to_string).collect::<Vec<_>>()),
+    )
+}
+
+/// Non-blank values without repeats, sorted ignoring case
+fn distinct(values: impl Iterator<Item = String>) -> Vec<String> {
+    let mut seen = BTreeMap::new();
+    for value in values {
+        let value = value.trim();
+        if !value.is_empty() {
+            seen.entry(value.to_lowercase()).or_insert_with(|| value.to_string());
+        }
+    }
+    seen.into_values().collect()
+}
+
+impl ProjectTemplate {
+    /// Set the department the template belongs to
+    pub fn with_department(mut self, department: impl Into<String>) -> Self {
+        self.department = department.into();
+        self
+    }
+
+    /// Set the number of the form the template is for
+    pub fn with_form_number(mut self, form_number: impl Into<String>) -> Self {
+        self.form_number = form_number.into();
+        self
+    }
+
+    /// Add a tag
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.add_tag(tag);
+        self
+    }
+
+    /// Add a tag, unless it is blank or the template already has it
+    ///
+    /// Returns whether the tag was added.
+    pub fn add_tag(&mut self, tag: impl Into<String>) -> bool {
+        let tag = tag.into().trim().to_string();
+        if tag.is_empty() || self.has_tag(&tag) {
+            return false;
+        }
+        self.tags.push(tag);
+        true
+    }
+
+    /// Remove a tag, returning whether the template had it
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let before = self.tags.len();
+        self.tags.retain(|existing| !existing.eq_ignore_ascii_case(tag.trim()));
+        self.tags.len() != before
+    }
+
+    /// Whether the template has a tag, ignoring case
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|existing| existing.eq_ignore_ascii_case(tag.trim()))
+    }
+}
@@ -0,0 +1,120 @@
+//! Project templates loaded in the background for the new-project window
+//!
+//! With hundreds of templates, reading and parsing every file takes long
+//! enough to freeze the window that lists them. Opening the window starts
+//! a worker thread that lists the template files and parses them one by
+//! one, while the window shows how many are done. The list appears in the
+//! first frame after the last file is parsed. Browsers have no threads, so
+//! there the templates are loaded in the first frame the window is drawn.
+
+use super::core::DrawingCanvas;
+use super::project_template::ProjectTemplate;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::debug;
+
+/// How often the window checks on templates being loaded
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How far a background template load has got
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TemplateLoadProgress {
+    /// Template files parsed so far, including ones that failed to load
+    pub loaded: usize,
+    /// Template files found, or 0 while the directory is being listed
+    pub total: usize,
+}
+
+/// State shared with the worker
+#[derive(Debug, Default)]
+struct TemplateLoad {
+    progress: TemplateLoadProgress,
+    /// Loaded templates, once every file has been parsed
+    templates: Option<Vec<ProjectTemplate>>,
+}
+
+/// Templates being loaded while the new-project window is open
+#[derive(Debug, Clone)]
+pub(super) struct PendingTemplates {
+    /// Directory the templates are loaded from
+    dir: PathBuf,
+    load: Arc<Mutex<TemplateLoad>>,
+}
+
+impl PendingTemplates {
+    /// Start loading the templates in `dir`
+    pub(super) fn start(dir: &Path) -> Self {
+        let pending = Self {
+            dir: dir.to_path_buf(),
+            load: Arc::new(Mutex::new(TemplateLoad::default())),
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let (dir, load) = (pending.dir.clone(), Arc::clone(&pending.load));
+            std::thread::spawn(move || {
+                let files = ProjectTemplate::template_files(&dir);
+                if let Ok(mut load) = load.lock() {
+                    load.progress.total = files.len();
+                }
+                let mut templates = Vec::with_capacity(files.len());
+                for path in files {
+                    templates.extend(ProjectTemplate::load_or_warn(&path));
+                    if let Ok(mut load) = load.lock() {
+                        load.progress.loaded += 1;
+                    }
+                }
+                ProjectTemplate::sort(&mut templates);
+                if let Ok(mut load) = load.lock() {
+                    load.templates = Some(templates);
+                }
+            });
+        }
+        pending
+    }
+
+    /// How far the load has got
+    fn progress(&self) -> TemplateLoadProgress {
+        self.load.lock().map(|load| load.progress).unwrap_or_default()
+    }
+
+    /// The loaded templates, if loading has finished
+    fn take(&self) -> Option<Vec<ProjectTemplate>> {
+        if cfg!(target_arch = "wasm32") {
+            return Some(ProjectTemplate::load_all(&self.dir));
+        }
+        self.load.lock().ok()?.templates.take()
+    }
+}
+
+impl DrawingCanvas {
+    /// Load the templates in `dir` in the background, replacing the listed ones once done
+    pub fn load_project_templates_lazily(&mut self, dir: &Path) {
+        self.pending_templates = Some(PendingTemplates::start(dir));
+        debug!(dir = %dir.display(), "Loading project templates in the background");
+    }
+
+    /// How far loading the templates has got, or `None` if they are not being loaded
+    pub fn project_templates_loading(&self) -> Option<TemplateLoadProgress> {
+        self.pending_templates.as_ref().map(PendingTemplates::progress)
+    }
+
+    /// List the templates once they have been loaded, or check again shortly
+    ///
+    /// Returns true if no templates are still being loaded.
+    pub fn poll_project_templates(&mut self, ctx: &egui::Context) -> bool {
+        let Some(pending) = &self.pending_templates else {
+            return true;
+        };
+        let Some(templates) = pending.take() else {
+            ctx.request_repaint_after(POLL_INTERVAL);
+            return false;
+        };
+        debug!(count = templates.len(), "Loaded project templates");
+        self.pending_templates = None;
+        self.project_templates = templates;
+        // Indices into the old list no longer apply
+        self.selected_template = None;
+        true
+    }
+}
@@ -0,0 +1,267 @@
+//! Pages of multi-page templates and matching them to packet pages
+//!
+//! A packet of forms often mixes layouts: a portrait cover sheet, landscape
+//! schedules, a portrait signature page. A [`ProjectTemplate`] can hold one
+//! [`TemplatePage`] per layout, each with its own reference image, anchors
+//! and fields. When a page of a packet is opened, the template's
+//! [`PageMatch`] strategy picks the template page whose fields it gets:
+//!
+//! - [`PageMatch::Sequence`]: the packet's nth page gets the nth template
+//!   page, and pages past the end get the last one
+//! - [`PageMatch::Orientation`]: the first page laid out the same way,
+//!   portrait or landscape
+//! - [`PageMatch::AspectRatio`]: the page whose reference image has the
+//!   closest proportions
+//! - [`PageMatch::Anchors`]: the page with the most anchors found among the
+//!   logo detections
+//!
+//! Pages a strategy cannot tell apart fall back to the sequence. Anchors
+//! are the logos detected on the reference image when the page was added,
+//! such as an agency seal that only appears on the cover sheet.
+
+use egui::{Rect, Vec2};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tracing::{debug, info, instrument};
+
+use super::core::DrawingCanvas;
+use super::project_template::ProjectTemplate;
+use crate::{FieldGroup, Shape};
+
+/// Prefix of the names logo detection gives its detections
+const LOGO_PREFIX: &str = "Logo: ";
+
+/// How a page is laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::Display)]
+pub enum PageOrientation {
+    /// Taller than wide
+    Portrait,
+    /// Wider than tall
+    Landscape,
+}
+
+impl PageOrientation {
+    /// Orientation of a page of the given size; square pages count as portrait
+    pub fn of(size: Vec2) -> Self {
+        if size.x > size.y { Self::Landscape } else { Self::Portrait }
+    }
+}
+
+/// Strategy for picking the template page a packet page gets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, strum::EnumIter, strum::Display)]
+pub enum PageMatch {
+    /// Template pages in packet order
+    #[default]
+    Sequence,
+    /// Same portrait or landscape layout
+    Orientation,
+    /// Closest proportions
+    #[strum(to_string = "Aspect ratio")]
+    AspectRatio,
+    /// Most anchors detected
+    Anchors,
+}
+
+/// Landmark that identifies a template page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateAnchor {
+    /// Name of the logo template that finds the landmark
+    pub name: String,
+    /// Where the landmark is on the reference image, in image pixels
+    pub region: Rect,
+}
+
+/// One page layout of a multi-page template
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TemplatePage {
+    /// Page name, such as "Schedule C"
+    pub name: String,
+    /// Form image the page was laid out on
+    #[serde(default)]
+    pub reference_image: Option<PathBuf>,
+    /// Size of the reference image in pixels
+    #[serde(default)]
+    pub reference_size: Option<Vec2>,
+    /// Landmarks that identify the page
+    #[serde(default)]
+    pub anchors: Vec<TemplateAnchor>,
+    /// Field overlays, in canvas coordinates
+    #[serde(default)]
+    pub fields: Vec<Shape>,
+    /// Order data entry visits the fields in, as indices into `fields`
+    #[serde(default)]
+    pub tab_order: Vec<usize>,
+    /// Field groups and repeating sections
+    #[serde(default)]
+    pub groups: Vec<FieldGroup>,
+}
+
+impl TemplatePage {
+    /// Empty page with no reference image
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Page reproducing the fields and form image of an existing project
+    ///
+    /// The project's logo detections become the page's anchors.
+    pub fn from_canvas(name: impl Into<String>, canvas: &DrawingCanvas) -> Self {
+        let anchors = canvas
+            .detections
+            .iter()
+            .filter_map(|detection| {
+                Some(TemplateAnchor {
+                    name: logo_name(detection.name())?.to_string(),
+                    region: detection.bounding_rect(),
+                })
+            })
+            .collect();
+        Self {
+            name: name.into(),
+            reference_image: canvas.form_image_path.as_ref().map(PathBuf::from),
+            reference_size: canvas.form_image_size,
+            anchors,
+            fields: canvas.shapes.clone(),
+            tab_order: canvas.tab_order.clone(),
+            groups: canvas.field_groups.clone(),
+        }
+    }
+
+    /// Add an anchor
+    pub fn with_anchor(mut self, name: impl Into<String>, region: Rect) -> Self {
+        self.anchors.push(TemplateAnchor {
+            name: name.into(),
+            region,
+        });
+        self
+    }
+
+    /// Set the size of the reference image
+    pub fn with_reference_size(mut self, size: Vec2) -> Self {
+        self.reference_size = Some(size);
+        self
+    }
+
+    /// Orientation of the reference image, if its size is known
+    pub fn orientation(&self) -> Option<PageOrientation> {
+        self.reference_size.map(PageOrientation::of)
+    }
+}
+
+impl ProjectTemplate {
+    /// Add a page, replacing any page with the same name
+    pub fn add_page(&mut self, page: TemplatePage) {
+        match self.pages.iter_mut().find(|existing| existing.name == page.name) {
+            Some(existing) => *existing = page,
+            None => self.pages.push(page),
+        }
+    }
+
+    /// Add a page
+    pub fn with_page(mut self, page: TemplatePage) -> Self {
+        self.add_page(page);
+        self
+    }
+
+    /// Pick how packet pages are matched to template pages
+    pub fn with_page_match(mut self, page_match: PageMatch) -> Self {
+        self.page_match = page_match;
+        self
+    }
+
+    /// Index of the template page for a packet page
+    ///
+    /// `packet_index` is the page's position in its packet, from 0.
+    /// `image_size` is the size of its form image and `logos` the names of
+    /// the logos detected on it. `None` for a template without pages.
+    pub fn match_page<'a>(
+        &self,
+        packet_index: usize,
+        image_size: Option<Vec2>,
+        logos: impl IntoIterator<Item = &'a str>,
+    ) -> Option<usize> {
+        let last = self.pages.len().checked_sub(1)?;
+        let sequence = packet_index.min(last);
+        // Ties go to the page nearest the sequence's choice
+        let distance = |idx: &usize| idx.abs_diff(sequence);
+
+        let matched = match self.page_match {
+            PageMatch::Sequence => None,
+            PageMatch::Orientation => image_size.map(PageOrientation::of).and_then(|orientation| {
+                (0..=last)
+                    .filter(|idx| self.pages[*idx].orientation() == Some(orientation))
+                    .min_by_key(distance)
+            }),
+            PageMatch::AspectRatio => image_size.and_then(aspect_ratio).and_then(|ratio| {
+                (0..=last)
+                    .filter_map(|idx| {
+                        let page_ratio = self.pages[idx].reference_size.and_then(aspect_ratio)?;
+                        Some((idx, (page_ratio / ratio).ln().abs()))
+                    })
+                    .min_by(|a, b| a.1.total_cmp(&b.1).then(distance(&a.0).cmp(&distance(&b.0))))
+                    .map(|(idx, _)| idx)
+            }),
+            PageMatch::Anchors => {
+                let logos: Vec<&str> = logos.into_iter().collect();
+                (0..=last)
+                    .map(|idx| {
+                        let found =
+                            self.pages[idx].anchors.iter().filter(|anchor| logos.contains(&anchor.name.as_str()));
+                        (idx, found.count())
+                    })
+                    .filter(|(_, found)| *found > 0)
+                    .max_by(|a, b| a.1.cmp(&b.1).then(distance(&b.0).cmp(&distance(&a.0))))
+                    .map(|(idx, _)| idx)
+            }
+        };
+        Some(matched.unwrap_or(sequence))
+    }
+}
+
+impl DrawingCanvas {
+    /// Replace the fields with those of a template page, keeping the form image and detections
+    ///
+    /// Returns false if the template has no such page.
+    #[instrument(skip(self, template), fields(template = %template.name))]
+    pub fn apply_template_page(&mut self, template: &ProjectTemplate, index: usize) -> bool {
+        let Some(page) = template.pages.get(index) else {
+            return false;
+        };
+        self.clear_shapes();
+        self.shapes = page.fields.clone();
+        self.tab_order = page.tab_order.clone();
+        self.field_groups = page.groups.clone();
+        self.fill_section_rows();
+        self.template_name = Some(template.name.clone());
+        self.template_page = Some(page.name.clone());
+        self.template_page_index = Some(index);
+        info!(page = page.name, "Applied template page");
+        true
+    }
+
+    /// Apply the template page matching this form, returning its index
+    ///
+    /// Matches by the form image's size and the logos detected on it, so
+    /// load the image and run logo detection first when the template
+    /// matches by those. `None` for a template without pages.
+    pub fn apply_matching_template_page(&mut self, template: &ProjectTemplate, packet_index: usize) -> Option<usize> {
+        let logos: Vec<&str> = self.detections.iter().filter_map(|detection| logo_name(detection.name())).collect();
+        let page = template.match_page(packet_index, self.form_image_size, logos)?;
+        debug!(page, strategy = %template.page_match, "Matched template page");
+        self.apply_template_page(template, page).then_some(page)
+    }
+}
+
+/// Logo template name from a logo detection's name, like "Logo: seal (97.0%, scale=1.00x)"
+fn logo_name(detection: &str) -> Option<&str> {
+    let rest = detection.strip_prefix(LOGO_PREFIX)?;
+    Some(rest.rsplit_once(" (").map_or(rest, |(name, _)| name))
+}
+
+/// Width over height, if both are positive
+fn aspect_ratio(size: Vec2) -> Option<f32> {
+    (size.x > 0.0 && size.y > 0.0).then(|| size.x / size.y)
+}
@@ -0,0 +1,123 @@
+//! Previewing field layouts with sample data
+//!
+//! While the preview is on, every field is drawn filled with a plausible
+//! placeholder for its name and format, like `Jane Doe` in a name field or
+//! `2024-03-15` in a date field, written the way a refill would write it.
+//! Template authors can see at a glance which fields are too small for
+//! real values before saving the template: fields the sample only fits in
+//! after shrinking the text by more than half are outlined in red.
+//!
+//! The preview only draws; field values are left untouched.
+
+use super::command::CanvasCommand;
+use super::core::DrawingCanvas;
+use super::refill::{HELVETICA_CHAR_WIDTH, REFILL_INK, TEXT_HEIGHT_RATIO, TEXT_INSET_RATIO};
+use crate::FieldFormat;
+use egui::{Align2, Color32, FontId, Rect, Stroke, StrokeKind, emath::TSTransform};
+use tracing::debug;
+
+/// Smallest share of the refill text size a sample may be shrunk to and still fit
+pub const MIN_PREVIEW_TEXT_SCALE: f32 = 0.5;
+
+/// Outline of fields too small for their sample
+const OVERFLOW_COLOR: Color32 = Color32::from_rgb(220, 40, 40);
+
+/// Placeholder value for a field, from its name and format
+///
+/// Checkboxes are checked, numbers and dates are fixed samples, and text
+/// fields get a value guessed from common words in their name.
+pub fn sample_value(name: &str, format: FieldFormat) -> String {
+    let name = name.to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|word| name.contains(word));
+    let sample = match format {
+        FieldFormat::Checkbox => "X",
+        FieldFormat::Number => "1,234.56",
+        FieldFormat::Date => "2024-03-15",
+        FieldFormat::Text if has(&["email"]) => "jane.doe@example.com",
+        FieldFormat::Text if has(&["phone", "tel", "fax"]) => "(555) 010-0199",
+        FieldFormat::Text if has(&["ssn"]) => "123-45-6789",
+        FieldFormat::Text if has(&["zip", "postal"]) => "90210",
+        FieldFormat::Text if has(&["state"]) => "CA",
+        FieldFormat::Text if has(&["city"]) => "Springfield",
+        FieldFormat::Text if has(&["address", "street"]) => "123 Main St",
+        FieldFormat::Text if has(&["date", "dob"]) => "2024-03-15",
+        FieldFormat::Text if has(&["amount", "total", "price", "wage", "tax", "cost"]) => "1,234.56",
+        FieldFormat::Text if has(&["name", "signature"]) => "Jane Doe",
+        FieldFormat::Text => "Sample text",
+    };
+    sample.to_string()
+}
+
+/// Sample value shown in one field
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreviewValue {
+    /// Index of the field
+    pub field: usize,
+    /// Placeholder written in the field
+    pub text: String,
+    /// Whether the placeholder fits without shrinking past [`MIN_PREVIEW_TEXT_SCALE`]
+    pub fits: bool,
+}
+
+impl DrawingCanvas {
+    /// Toggle the sample data preview
+    pub fn toggle_template_preview(&mut self) {
+        self.apply_command(CanvasCommand::SetTemplatePreview(!self.template_preview));
+        debug!(template_preview = self.template_preview, "Toggled template preview");
+    }
+
+    /// Turn the sample data preview on or off
+    pub fn set_template_preview(&mut self, enabled: bool) {
+        self.apply_command(CanvasCommand::SetTemplatePreview(enabled));
+    }
+
+    /// Sample values of the visible fields, in drawing order
+    pub fn preview_values(&self) -> Vec<PreviewValue> {
+        self.shapes
+            .iter()
+            .enumerate()
+            .filter(|(_, shape)| !shape.is_hidden())
+            .map(|(field, shape)| {
+                let text = sample_value(shape.name(), shape.rule().format);
+                let fits = sample_fits(shape.bounding_rect(), &text);
+                PreviewValue { field, text, fits }
+            })
+            .collect()
+    }
+
+    /// Draw the sample values over the fields while the preview is on
+    pub(super) fn draw_template_preview(&self, painter: &egui::Painter, to_screen: &TSTransform) {
+        if !self.template_preview {
+            return;
+        }
+        for value in self.preview_values() {
+            let bounds = *to_screen * self.shapes[value.field].bounding_rect();
+            let inset = bounds.height() * TEXT_INSET_RATIO;
+            let width = (bounds.width() - 2.0 * inset).max(1.0);
+            let mut font = FontId::proportional(bounds.height() * TEXT_HEIGHT_RATIO);
+            let galley = painter.layout_no_wrap(value.text.clone(), font.clone(), REFILL_INK);
+            if galley.size().x > width {
+                font.size *= width / galley.size().x;
+            }
+            painter.text(
+                egui::pos2(bounds.min.x + inset, bounds.center().y),
+                Align2::LEFT_CENTER,
+                &value.text,
+                font,
+                REFILL_INK,
+            );
+            if !value.fits {
+                painter.rect_stroke(bounds, 0.0, Stroke::new(2.0, OVERFLOW_COLOR), StrokeKind::Outside);
+            }
+        }
+    }
+}
+
+/// Whether `text` fits on one line in `bounds` at a readable size
+///
+/// Estimates the width from the average character width, like PDF refills.
+fn sample_fits(bounds: Rect, text: &str) -> bool {
+    let size = bounds.height() * TEXT_HEIGHT_RATIO * MIN_PREVIEW_TEXT_SCALE;
+    let inset = bounds.height() * TEXT_INSET_RATIO;
+    text.chars().count() as f32 * size * HELVETICA_CHAR_WIDTH <= bounds.width() - 2.0 * inset
+}
@@ -0,0 +1,116 @@
+//! Export of the text read from the form as a plain transcript
+//!
+//! The transcript holds the OCR text of every detection in reading order,
+//! for people who need the words on the form rather than its layout. It is
+//! written as plain text or Markdown, optionally with a header naming the
+//! page and a header above each region's text.
+
+use super::core::{CanvasError, DrawingCanvas};
+use form_factor_core::IoOperation;
+use tracing::instrument;
+
+/// File format of an extracted text export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, strum::EnumIter, strum::Display)]
+pub enum TextFormat {
+    /// Plain text, one region per line unless region headers are added
+    #[default]
+    #[strum(to_string = "Plain text")]
+    PlainText,
+    /// Markdown with headings for the page and regions
+    #[strum(to_string = "Markdown")]
+    Markdown,
+}
+
+impl TextFormat {
+    /// File extension for the format, without the dot
+    pub fn extension(self) -> &'static str {
+        match self {
+            TextFormat::PlainText => "txt",
+            TextFormat::Markdown => "md",
+        }
+    }
+}
+
+/// How extracted text is laid out in an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextExportOptions {
+    /// File format
+    pub format: TextFormat,
+    /// Start with a header naming the page
+    pub page_headers: bool,
+    /// Put a header naming each region above its text
+    pub region_headers: bool,
+}
+
+impl Default for TextExportOptions {
+    fn default() -> Self {
+        Self {
+            format: TextFormat::default(),
+            page_headers: true,
+            region_headers: false,
+        }
+    }
+}
+
+impl DrawingCanvas {
+    /// Text read from the detections in reading order, laid out as the options ask
+    ///
+    /// Detections whose text is blank are left out.
+    pub fn extracted_text(&self, options: &TextExportOptions) -> String {
+        let regions: Vec<(String, &str)> = self
+            .ocr_reading_order()
+            .into_iter()
+            .filter_map(|idx| {
+                let text = self.ocr_text(idx)?.trim();
+                let name = match self.detections.get(idx)?.name().trim() {
+                    "" => format!("Region {}", idx + 1),
+                    name => name.to_string(),
+                };
+                (!text.is_empty()).then_some((name, text))
+            })
+            .collect();
+
+        let markdown = options.format == TextFormat::Markdown;
+        let mut out = String::new();
+        if options.page_headers {
+            let page = match &self.template_page {
+                Some(page) => format!("{} - {}", self.project_name, page),
+                None => self.project_name.clone(),
+            };
+            if markdown {
+                out.push_str(&format!("# {}\n\n", page));
+            } else {
+                out.push_str(&format!("=== {} ===\n\n", page));
+            }
+        }
+        // Markdown runs adjacent lines together, so each region is its own paragraph
+        let separator = if markdown || options.region_headers { "\n\n" } else { "\n" };
+        let blocks: Vec<String> = regions
+            .iter()
+            .map(|(name, text)| match (options.region_headers, markdown) {
+                (true, true) => format!("## {}\n\n{}", name, text),
+                (true, false) => format!("[{}]\n{}", name, text),
+                (false, _) => text.to_string(),
+            })
+            .collect();
+        out.push_str(&blocks.join(separator));
+        out.push('\n');
+        out
+    }
+
+    /// Save the text read from the detections as a text or Markdown file
+    ///
+    /// Returns the number of regions written.
+    #[instrument(skip(self), fields(output_path))]
+    pub fn export_extracted_text(&self, output_path: &str, options: &TextExportOptions) -> Result<usize, CanvasError> {
+        std::fs::write(output_path, self.extracted_text(options))
+            .map_err(|e| CanvasError::io(e, output_path, IoOperation::Write, line!(), file!()))?;
+        let count = self
+            .ocr_reading_order()
+            .into_iter()
+            .filter(|idx| self.ocr_text(*idx).is_some_and(|text| !text.trim().is_empty()))
+            .count();
+        tracing::info!("Exported text of {} regions to {}", count, output_path);
+        Ok(count)
+    }
+}
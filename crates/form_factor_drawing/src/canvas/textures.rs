@@ -0,0 +1,181 @@
+//! Texture memory budget with least-recently-used eviction
+//!
+//! Every texture uploaded for the canvas holds width × height × 4 bytes of
+//! GPU and CPU memory until it is dropped. The form image is always kept,
+//! since the canvas draws it every frame; other textures, such as image
+//! stamps, live in a [`TextureCache`] that drops the textures used least
+//! recently once the total goes over the budget set in the canvas settings.
+//! A dropped texture is uploaded again the next time it is drawn. Textures
+//! drawn in the latest frame are never dropped, so a screen that needs more
+//! than the budget goes over it rather than reloading images every frame.
+
+use super::core::DrawingCanvas;
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Texture memory budget used until settings are applied, in megabytes
+pub const DEFAULT_TEXTURE_BUDGET_MB: usize = 512;
+
+/// Bytes in a megabyte
+pub(super) const MB: usize = 1024 * 1024;
+
+/// Texture kept in a [`TextureCache`]
+#[derive(Clone)]
+struct CachedTexture {
+    /// Uploaded texture, or `None` if its image could not be read
+    texture: Option<egui::TextureHandle>,
+    /// Memory the texture holds
+    bytes: usize,
+    /// Frame the texture was last drawn in
+    last_used: u64,
+}
+
+/// Uploaded textures by key, evicted least recently used first when over budget
+#[derive(Clone)]
+pub struct TextureCache {
+    /// Textures by key, e.g. the image path
+    entries: HashMap<String, CachedTexture>,
+    /// Memory the textures may hold, in bytes
+    budget: usize,
+    /// Current frame
+    frame: u64,
+    /// Textures evicted so far
+    evictions: usize,
+}
+
+impl std::fmt::Debug for TextureCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TextureCache")
+            .field("textures", &self.entries.len())
+            .field("used", &self.used())
+            .field("budget", &self.budget)
+            .field("evictions", &self.evictions)
+            .finish()
+    }
+}
+
+impl Default for TextureCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_TEXTURE_BUDGET_MB * MB)
+    }
+}
+
+impl TextureCache {
+    /// Create an empty cache holding at most `budget` bytes
+    pub fn new(budget: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            budget,
+            frame: 0,
+            evictions: 0,
+        }
+    }
+
+    /// Memory the textures may hold, in bytes
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    /// Change the budget; textures over it are evicted at the next frame
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+    }
+
+    /// Memory the cached textures hold, in bytes
+    pub fn used(&self) -> usize {
+        self.entries.values().map(|entry| entry.bytes).sum()
+    }
+
+    /// Number of cached textures, including images that could not be read
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether a texture is cached under `key`
+    pub fn contains(&self, key: &str) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// Number of textures evicted so far
+    pub fn evictions(&self) -> usize {
+        self.evictions
+    }
+
+    /// Texture under `key`, uploading it with `load` if it isn't cached
+    ///
+    /// Marks the texture as used in the current frame. `load` returns
+    /// `None` for an image that can't be read, which is remembered so the
+    /// image isn't read again every frame.
+    pub fn get_or_load(
+        &mut self,
+        key: &str,
+        load: impl FnOnce() -> Option<egui::TextureHandle>,
+    ) -> Option<&egui::TextureHandle> {
+        let frame = self.frame;
+        let entry = self.entries.entry(key.to_string()).or_insert_with(|| {
+            let texture = load();
+            let bytes = texture.as_ref().map_or(0, egui::TextureHandle::byte_size);
+            debug!(key, bytes, "Uploaded texture");
+            CachedTexture {
+                texture,
+                bytes,
+                last_used: frame,
+            }
+        });
+        entry.last_used = frame;
+        entry.texture.as_ref()
+    }
+
+    /// Drop the texture under `key`, e.g. after its image changed on disk
+    pub fn remove(&mut self, key: &str) -> bool {
+        self.entries.remove(key).is_some()
+    }
+
+    /// Start a new frame, first evicting textures until `reserved` plus the cache fit the budget
+    ///
+    /// `reserved` is memory held by textures outside the cache. Textures
+    /// used in the frame that just ended are kept even over budget.
+    pub fn begin_frame(&mut self, reserved: usize) {
+        let limit = self.budget.saturating_sub(reserved);
+        let mut used = self.used();
+        while used > limit {
+            let Some((key, bytes)) = self
+                .entries
+                .iter()
+                .filter(|(_, entry)| entry.last_used < self.frame && entry.bytes > 0)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, entry)| (key.clone(), entry.bytes))
+            else {
+                break;
+            };
+            self.entries.remove(&key);
+            self.evictions += 1;
+            used -= bytes;
+            debug!(key, bytes, used, limit, "Evicted texture");
+        }
+        self.frame += 1;
+    }
+}
+
+impl DrawingCanvas {
+    /// Memory held by the canvas's textures, in bytes
+    pub fn texture_memory(&self) -> usize {
+        self.form_image_bytes() + self.textures.used()
+    }
+
+    /// Memory held by the form image texture, in bytes
+    fn form_image_bytes(&self) -> usize {
+        self.form_image.as_ref().map_or(0, egui::TextureHandle::byte_size)
+    }
+
+    /// Start a frame of texture use, evicting textures over the budget
+    pub(super) fn begin_texture_frame(&mut self) {
+        let reserved = self.form_image_bytes();
+        self.textures.begin_frame(reserved);
+    }
+}
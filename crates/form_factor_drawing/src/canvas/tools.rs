@@ -2,30 +2,46 @@
 //!
 //! This module handles all user interactions with the canvas tools:
 //! - Selection: Clicking on shapes to select them
-//! - Drawing: Creating new shapes (rectangles, circles, polygons)
+//! - Drawing: Creating new shapes (rectangles, circles, ellipses, polygons, arrows, callouts)
 //! - Editing: Dragging vertices to modify shapes
 //! - Rotation: Rotating shapes, grid, or form image
 //!
 //! The interaction state machine prevents invalid state combinations
 //! (e.g., drawing while rotating) and ensures consistent behavior.
 
-use crate::{Circle, LayerType, PolygonShape, Rectangle, Shape, ToolMode};
+use crate::{
+    Arrow, Callout, Circle, Ellipse, LayerType, Line, PolygonShape, Polyline, Rectangle, ScreenPos, Shape, StylePreset,
+    ToolMode,
+};
 use egui::Pos2;
 use tracing::{debug, instrument, trace, warn};
 
 use super::core::DrawingCanvas;
+use super::highlights::{HIGHLIGHT_COLOR, HIGHLIGHT_WIDTH, Highlight};
+
+/// Canvas distance within which a press picks up a vertex in Edit mode
+const VERTEX_CLICK_RADIUS: f32 = 8.0;
+
+/// Size of a newly drawn callout's text box, in canvas units
+const CALLOUT_BOX_SIZE: egui::Vec2 = egui::vec2(160.0, 48.0);
+
+/// Screen distance within which drawn polyline points are dropped as noise
+const POLYLINE_TOLERANCE_PX: f32 = 3.0;
 
 impl DrawingCanvas {
     /// Handle input events for the current tool mode
     ///
     /// This is the main input dispatcher that delegates to specific handlers
-    /// based on the current tool mode and interaction state.
+    /// based on the current tool mode and interaction state. An active custom
+    /// tool gets the input instead of the built-in tools.
     #[instrument(skip(self, response, painter, transform), fields(tool = ?self.current_tool()))]
     pub(super) fn handle_input(&mut self, response: &egui::Response, painter: &egui::Painter, transform: &egui::emath::TSTransform) {
+        if self.handle_custom_tool_input(response) {
+            return;
+        }
         // Helper to transform screen coordinates to canvas coordinates
-        let transform_pos = |screen_pos: Pos2| -> Pos2 {
-            transform.inverse().mul_pos(screen_pos)
-        };
+        let mapper = self.coordinate_mapper;
+        let transform_pos = |screen_pos: Pos2| -> Pos2 { mapper.screen_to_canvas(ScreenPos(screen_pos)).0 };
         match self.current_tool() {
             ToolMode::Select => {
                 let _span = tracing::debug_span!("selection").entered();
@@ -39,14 +55,20 @@ impl DrawingCanvas {
                         "Canvas clicked"
                     );
 
+                    // Shift+click picks detections to merge
+                    let shift = response.ctx.input(|i| i.modifiers.shift);
                     if let Some(pos) = response.interact_pointer_pos() {
                         let canvas_pos = transform_pos(pos);
                         trace!(?pos, ?canvas_pos, "Using interact_pointer_pos");
-                        self.handle_selection_click(canvas_pos);
+                        if !(shift && self.toggle_detection_at(canvas_pos)) {
+                            self.handle_selection_click(canvas_pos);
+                        }
                     } else if let Some(pos) = response.hover_pos() {
                         let canvas_pos = transform_pos(pos);
                         trace!(?pos, ?canvas_pos, "Using hover_pos fallback");
-                        self.handle_selection_click(canvas_pos);
+                        if !(shift && self.toggle_detection_at(canvas_pos)) {
+                            self.handle_selection_click(canvas_pos);
+                        }
                     } else {
                         debug!("No position available for click");
                     }
@@ -81,7 +103,23 @@ impl DrawingCanvas {
                     }
                 }
             }
-            ToolMode::Rectangle | ToolMode::Circle | ToolMode::Freehand => {
+            ToolMode::Stamp => {
+                if response.clicked()
+                    && let Some(pos) = response.interact_pointer_pos().or(response.hover_pos())
+                {
+                    let remove = response.ctx.input(|i| i.modifiers.shift);
+                    self.handle_stamp_click(transform_pos(pos), remove);
+                }
+            }
+            ToolMode::Rectangle
+            | ToolMode::Circle
+            | ToolMode::Ellipse
+            | ToolMode::Freehand
+            | ToolMode::Line
+            | ToolMode::Polyline
+            | ToolMode::Arrow
+            | ToolMode::Callout
+            | ToolMode::Highlighter => {
                 // Handle drawing tools
                 if let Some(pos) = response.interact_pointer_pos() {
                     let canvas_pos = transform_pos(pos);
@@ -159,42 +197,29 @@ impl DrawingCanvas {
         }
     }
 
+    /// Topmost visible, unlocked shape containing a canvas position
+    ///
+    /// Shapes are tested in reverse so the most recently drawn one wins.
+    pub(super) fn shape_at(&self, pos: Pos2) -> Option<usize> {
+        self.shapes()
+            .iter()
+            .enumerate()
+            .rev()
+            // Hidden and locked shapes can't be picked on the canvas
+            .find(|(_, shape)| !shape.is_hidden() && !shape.is_locked() && shape.contains_point(pos))
+            .map(|(idx, _)| idx)
+    }
+
     /// Handle a selection click at the given canvas position
     ///
-    /// Performs hit testing on all shapes to find the topmost shape
-    /// that contains the click point. Updates selection state and
+    /// Performs hit testing on all visible, unlocked shapes to find the
+    /// topmost shape that contains the click point. Updates selection state and
     /// automatically selects the Shapes layer if a shape is selected.
     #[instrument(skip(self), fields(pos = ?pos, total_shapes = self.shapes().len()))]
     pub(super) fn handle_selection_click(&mut self, pos: Pos2) {
         let _span = tracing::debug_span!("hit_testing").entered();
 
-        // Find the topmost shape that contains the click point
-        // Iterate in reverse to select the most recently drawn shape first
-        let mut selected = None;
-        for (idx, shape) in self.shapes().iter().enumerate().rev() {
-            let contains = match shape {
-                Shape::Rectangle(rect) => {
-                    let contains = rect.contains_point(pos);
-                    debug!(idx, contains, "Testing rectangle");
-                    contains
-                }
-                Shape::Circle(circle) => {
-                    let contains = circle.contains_point(pos);
-                    debug!(idx, contains, "Testing circle");
-                    contains
-                }
-                Shape::Polygon(poly) => {
-                    let contains = poly.contains_point(pos);
-                    debug!(idx, contains, "Testing polygon");
-                    contains
-                }
-            };
-
-            if contains {
-                selected = Some(idx);
-                break;
-            }
-        }
+        let selected = self.shape_at(pos);
 
         debug!(?selected, selected_shape_old = ?self.selected_shape(), "Selection result");
 
@@ -217,6 +242,8 @@ impl DrawingCanvas {
 
         self.set_selected_shape(selected);
         self.set_show_properties(selected.is_some());
+        // Word boxes lie under the shapes, so a click on a shape doesn't select a word
+        self.selected_ocr_word = if selected.is_none() { self.ocr_word_at(pos) } else { None };
 
         // When a shape is selected, also select the Shapes layer for rotation
         if selected.is_some() {
@@ -228,13 +255,13 @@ impl DrawingCanvas {
     /// Start drawing a new shape
     ///
     /// Initializes the drawing state based on the current tool mode.
-    /// For freehand polygons, starts collecting points. For rectangles
-    /// and circles, records the starting position.
+    /// For freehand polygons, polylines and highlights, starts collecting
+    /// points. For rectangles, circles and ellipses, records the starting position.
     pub(super) fn start_drawing(&mut self, pos: Pos2) {
-        let points = if *self.current_tool() == ToolMode::Freehand {
-            vec![pos]
+        let (pos, points) = if matches!(self.current_tool(), ToolMode::Freehand | ToolMode::Polyline | ToolMode::Highlighter) {
+            (pos, vec![pos])
         } else {
-            Vec::new()
+            (self.snap_to_guides(pos), Vec::new())
         };
 
         self.set_state(super::core::CanvasState::Drawing {
@@ -252,9 +279,16 @@ impl DrawingCanvas {
     pub(super) fn continue_drawing(&mut self, pos: Pos2, painter: &egui::Painter, transform: &egui::emath::TSTransform) {
         // Store values needed for rendering before mutably borrowing state
         let current_tool = *self.current_tool();
-        let fill_color = *self.fill_color();
-        let stroke = *self.stroke();
+        let (stroke, fill_color) = match self.style_preset() {
+            Some(preset) => (preset.stroke, preset.fill),
+            None => (*self.stroke(), *self.fill_color()),
+        };
         let zoom_level = *self.zoom_level();
+        let pos = if matches!(current_tool, ToolMode::Freehand | ToolMode::Polyline | ToolMode::Highlighter) {
+            pos
+        } else {
+            self.snap_to_guides(pos)
+        };
 
         // Update the drawing state with the new position
         if let super::core::CanvasState::Drawing { start, current_end, points } = self.state_mut() {
@@ -277,6 +311,11 @@ impl DrawingCanvas {
                     let transformed_radius = radius * zoom_level;
                     painter.circle(transformed_center, transformed_radius, fill_color, stroke);
                 }
+                ToolMode::Ellipse => {
+                    if let Ok(ellipse) = Ellipse::from_corners(*start, pos, stroke, fill_color) {
+                        ellipse.render(painter, transform);
+                    }
+                }
                 ToolMode::Freehand => {
                     points.push(pos);
                     if points.len() > 2 {
@@ -308,6 +347,28 @@ impl DrawingCanvas {
                         ));
                     }
                 }
+                ToolMode::Line => {
+                    painter.line_segment([transform.mul_pos(*start), transform.mul_pos(pos)], stroke);
+                }
+                ToolMode::Polyline => {
+                    points.push(pos);
+                    let transformed_points: Vec<Pos2> = points.iter().map(|p| transform.mul_pos(*p)).collect();
+                    painter.add(egui::Shape::line(transformed_points, stroke));
+                }
+                ToolMode::Arrow => {
+                    if let Ok(arrow) = Arrow::new(*start, pos, stroke) {
+                        arrow.render(painter, transform);
+                    }
+                }
+                ToolMode::Callout => {
+                    if let Ok(callout) = Callout::new(*start, callout_box(*start, pos), "", stroke, fill_color) {
+                        callout.render(painter, transform);
+                    }
+                }
+                ToolMode::Highlighter => {
+                    points.push(pos);
+                    Highlight::new(points.clone(), HIGHLIGHT_WIDTH, HIGHLIGHT_COLOR).render(painter, transform);
+                }
                 ToolMode::Select => {
                     // Selection preview could go here
                 }
@@ -317,16 +378,47 @@ impl DrawingCanvas {
                 ToolMode::Rotate => {
                     // Rotate mode doesn't draw new shapes
                 }
+                ToolMode::Stamp => {
+                    // Stamps are placed with a click
+                }
             }
         }
     }
 
+    /// Style preset new shapes are drawn with
+    ///
+    /// `None` if no preset is chosen or the chosen one was removed from the
+    /// settings; new shapes then take the canvas default style.
+    pub fn style_preset(&self) -> Option<&StylePreset> {
+        let name = self.chosen_style_preset.as_deref()?;
+        self.canvas_settings.style_presets.get(name)
+    }
+
+    /// Choose the style preset new shapes are drawn with, or `None` for the default style
+    pub fn choose_style_preset(&mut self, name: Option<String>) {
+        debug!(preset = ?name, "Chose style preset");
+        self.chosen_style_preset = name;
+    }
+
     /// Finalize and create the shape
     ///
     /// Creates the final shape from the drawing state and adds it to
     /// the canvas. Automatically selects the newly created shape and
-    /// focuses the name field for easy naming.
+    /// focuses the name field for easy naming. Highlighter strokes go to
+    /// the highlights layer instead.
     pub(super) fn finalize_shape(&mut self) {
+        if *self.current_tool() == ToolMode::Highlighter {
+            if let super::core::CanvasState::Drawing { points, .. } = self.state()
+                && points.len() >= 2
+            {
+                let highlight = Highlight::new(points.clone(), HIGHLIGHT_WIDTH, HIGHLIGHT_COLOR);
+                let idx = self.add_highlight(highlight);
+                debug!(highlight_index = idx, "Added highlight");
+            }
+            self.set_state(super::core::CanvasState::Idle);
+            return;
+        }
+
         let shape = if let super::core::CanvasState::Drawing { start, current_end, points } = self.state() {
             match self.current_tool() {
                 ToolMode::Rectangle => {
@@ -356,6 +448,19 @@ impl DrawingCanvas {
                         None
                     }
                 }
+                ToolMode::Ellipse => {
+                    if let Some(end) = current_end {
+                        Ellipse::from_corners(*start, *end, *self.stroke(), *self.fill_color())
+                            .map(Shape::Ellipse)
+                            .map_err(|e| {
+                                warn!("Failed to create ellipse: {}", e);
+                                e
+                            })
+                            .ok()
+                    } else {
+                        None
+                    }
+                }
                 ToolMode::Freehand => {
                     if points.len() >= 3 {
                         // Create a closed polygon from the points
@@ -370,7 +475,63 @@ impl DrawingCanvas {
                         None
                     }
                 }
+                ToolMode::Line => {
+                    if let Some(end) = current_end {
+                        Line::new(*start, *end, *self.stroke())
+                            .map(Shape::Line)
+                            .map_err(|e| {
+                                warn!("Failed to create line: {}", e);
+                                e
+                            })
+                            .ok()
+                    } else {
+                        None
+                    }
+                }
+                ToolMode::Polyline => {
+                    if points.len() >= 2 {
+                        // Straighten the hand-drawn path into segments
+                        let tolerance = POLYLINE_TOLERANCE_PX / self.zoom_level;
+                        Polyline::simplified(points.clone(), tolerance, *self.stroke())
+                            .map(Shape::Polyline)
+                            .map_err(|e| {
+                                warn!("Failed to create polyline: {}", e);
+                                e
+                            })
+                            .ok()
+                    } else {
+                        None
+                    }
+                }
+                ToolMode::Arrow => {
+                    if let Some(end) = current_end {
+                        Arrow::new(*start, *end, *self.stroke())
+                            .map(Shape::Arrow)
+                            .map_err(|e| {
+                                warn!("Failed to create arrow: {}", e);
+                                e
+                            })
+                            .ok()
+                    } else {
+                        None
+                    }
+                }
+                ToolMode::Callout => {
+                    if let Some(end) = current_end {
+                        Callout::new(*start, callout_box(*start, *end), "", *self.stroke(), *self.fill_color())
+                            .map(Shape::Callout)
+                            .map_err(|e| {
+                                warn!("Failed to create callout: {}", e);
+                                e
+                            })
+                            .ok()
+                    } else {
+                        None
+                    }
+                }
                 ToolMode::Select => None,
+                ToolMode::Highlighter => None,
+                ToolMode::Stamp => None,
                 ToolMode::Edit => None,
                 ToolMode::Rotate => None,
             }
@@ -378,8 +539,17 @@ impl DrawingCanvas {
             None
         };
 
-        if let Some(shape) = shape {
+        if let Some(mut shape) = shape {
+            let naming = &self.canvas_settings.field_naming;
+            if naming.is_enabled() {
+                let taken: Vec<&str> = self.shapes.iter().map(Shape::name).collect();
+                shape.set_name(naming.next_name(&taken));
+            }
+            if let Some(preset) = self.style_preset() {
+                preset.apply_to(&mut shape);
+            }
             self.add_shape(shape);
+            self.session_stats.record_shape_created();
 
             // Automatically select the newly created shape so user can name it
             let new_shape_idx = self.shapes().len() - 1;
@@ -403,8 +573,6 @@ impl DrawingCanvas {
     /// click position and begins vertex dragging if one is found within
     /// the click radius.
     pub(super) fn start_vertex_drag(&mut self, pos: Pos2) {
-        const VERTEX_CLICK_RADIUS: f32 = 8.0;
-
         let Some(idx) = *self.selected_shape() else {
             // No shape selected, try to select one
             self.handle_selection_click(pos);
@@ -416,35 +584,7 @@ impl DrawingCanvas {
         };
 
         // Find which vertex was clicked
-        let clicked_vertex = match shape {
-            Shape::Rectangle(rect) => {
-                // Check all 4 corners
-                rect.corners()
-                    .iter()
-                    .enumerate()
-                    .find(|(_, corner)| pos.distance(**corner) < VERTEX_CLICK_RADIUS)
-                    .map(|(i, _)| i)
-            }
-            Shape::Circle(circle) => {
-                if pos.distance(*circle.center()) < VERTEX_CLICK_RADIUS {
-                    Some(0)
-                } else {
-                    let edge_point = egui::pos2(circle.center().x + circle.radius(), circle.center().y);
-                    if pos.distance(edge_point) < VERTEX_CLICK_RADIUS {
-                        Some(1)
-                    } else {
-                        None
-                    }
-                }
-            }
-            Shape::Polygon(poly) => {
-                poly.to_egui_points()
-                    .iter()
-                    .enumerate()
-                    .find(|(_, vertex_pos)| pos.distance(**vertex_pos) < VERTEX_CLICK_RADIUS)
-                    .map(|(i, _)| i)
-            }
-        };
+        let clicked_vertex = vertex_at(shape, pos);
 
         if let Some(vertex_idx) = clicked_vertex {
             debug!(vertex_idx, "Starting vertex drag");
@@ -456,9 +596,11 @@ impl DrawingCanvas {
     ///
     /// Updates the position of the vertex being dragged to follow the
     /// mouse cursor. Different shapes handle vertex updates differently:
-    /// rectangles update corners, circles update center or radius, and
-    /// polygons update individual vertex positions.
+    /// rectangles update corners, circles update center or radius, ellipses
+    /// update center or one radius, and polygons update individual vertex
+    /// positions.
     pub(super) fn continue_vertex_drag(&mut self, pos: Pos2) {
+        let pos = self.snap_to_guides(pos);
         let super::core::CanvasState::DraggingVertex { vertex_index: vertex_idx } = *self.state() else {
             return;
         };
@@ -499,12 +641,56 @@ impl DrawingCanvas {
                     _ => {}
                 }
             }
+            Shape::Ellipse(ellipse) => {
+                let result = match vertex_idx {
+                    0 => ellipse.set_center(pos),
+                    axis => ellipse.set_handle(axis - 1, pos),
+                };
+                if let Err(e) = result {
+                    warn!("Failed to update ellipse handle {}: {}", vertex_idx, e);
+                }
+            }
             Shape::Polygon(poly) => {
                 // Update the specific vertex using setter method
                 if let Err(e) = poly.set_vertex(vertex_idx, pos) {
                     warn!("Failed to update polygon vertex {}: {}", vertex_idx, e);
                 }
             }
+            Shape::Line(line) => {
+                let result = match vertex_idx {
+                    0 => line.set_start(pos),
+                    _ => line.set_end(pos),
+                };
+                if let Err(e) = result {
+                    warn!("Failed to update line end {}: {}", vertex_idx, e);
+                }
+            }
+            Shape::Polyline(polyline) => {
+                if let Err(e) = polyline.set_vertex(vertex_idx, pos) {
+                    warn!("Failed to update polyline vertex {}: {}", vertex_idx, e);
+                }
+            }
+            Shape::Arrow(arrow) => {
+                let result = match vertex_idx {
+                    0 => arrow.set_start(pos),
+                    _ => arrow.set_end(pos),
+                };
+                if let Err(e) = result {
+                    warn!("Failed to update arrow point {}: {}", vertex_idx, e);
+                }
+            }
+            Shape::Callout(callout) => {
+                // Handle 0 is the anchor; 1 and 2 are opposite corners of the box
+                let text_box = *callout.text_box();
+                let result = match vertex_idx {
+                    0 => callout.set_anchor(pos),
+                    1 => callout.set_text_box(egui::Rect::from_two_pos(pos, text_box.max)),
+                    _ => callout.set_text_box(egui::Rect::from_two_pos(text_box.min, pos)),
+                };
+                if let Err(e) = result {
+                    warn!("Failed to update callout handle {}: {}", vertex_idx, e);
+                }
+            }
         }
     }
 
@@ -589,6 +775,9 @@ impl DrawingCanvas {
             Some(LayerType::Detections) => {
                 debug!("Detections layer selected - detections cannot be rotated");
             }
+            Some(LayerType::Highlights) => {
+                debug!("Highlights layer selected - highlights cannot be rotated");
+            }
             None => {
                 debug!("No layer selected for rotation - user must select a layer first");
             }
@@ -645,11 +834,36 @@ impl DrawingCanvas {
                                 warn!("Failed to rotate circle: {}", e);
                             }
                         }
+                        Shape::Ellipse(ellipse) => {
+                            if let Err(e) = ellipse.rotate(rotation_angle, center_pos) {
+                                warn!("Failed to rotate ellipse: {}", e);
+                            }
+                        }
                         Shape::Polygon(poly) => {
                             if let Err(e) = poly.rotate(rotation_angle, center_pos) {
                                 warn!("Failed to rotate polygon: {}", e);
                             }
                         }
+                        Shape::Line(line) => {
+                            if let Err(e) = line.rotate(rotation_angle, center_pos) {
+                                warn!("Failed to rotate line: {}", e);
+                            }
+                        }
+                        Shape::Polyline(polyline) => {
+                            if let Err(e) = polyline.rotate(rotation_angle, center_pos) {
+                                warn!("Failed to rotate polyline: {}", e);
+                            }
+                        }
+                        Shape::Arrow(arrow) => {
+                            if let Err(e) = arrow.rotate(rotation_angle, center_pos) {
+                                warn!("Failed to rotate arrow: {}", e);
+                            }
+                        }
+                        Shape::Callout(callout) => {
+                            if let Err(e) = callout.rotate(rotation_angle, center_pos) {
+                                warn!("Failed to rotate callout: {}", e);
+                            }
+                        }
                     }
                 }
             }
@@ -659,8 +873,8 @@ impl DrawingCanvas {
             Some(LayerType::Canvas) => {
                 self.set_form_image_rotation(form_image_rotation - angle_delta);
             }
-            Some(LayerType::Detections) => {
-                // Detections cannot be rotated
+            Some(LayerType::Detections | LayerType::Highlights) => {
+                // Detections and highlights cannot be rotated
             }
             None => {}
         }
@@ -688,8 +902,10 @@ impl DrawingCanvas {
     ///
     /// Calculates the geometric center (centroid) of the shape:
     /// - Rectangle: Average of all 4 corners
-    /// - Circle: The center point
+    /// - Circle, Ellipse: The center point
     /// - Polygon: Average of all vertices
+    /// - Arrow: Midpoint of the line
+    /// - Callout: Center of the anchor and box together
     pub(super) fn get_shape_center(&self, shape: &Shape) -> Pos2 {
         match shape {
             Shape::Rectangle(rect) => {
@@ -698,6 +914,7 @@ impl DrawingCanvas {
                 Pos2::new(sum_x / 4.0, sum_y / 4.0)
             }
             Shape::Circle(circle) => *circle.center(),
+            Shape::Ellipse(ellipse) => *ellipse.center(),
             Shape::Polygon(poly) => {
                 let points = poly.to_egui_points();
                 let sum_x: f32 = points.iter().map(|p| p.x).sum();
@@ -705,6 +922,78 @@ impl DrawingCanvas {
                 let count = points.len() as f32;
                 Pos2::new(sum_x / count, sum_y / count)
             }
+            Shape::Line(line) => line.center(),
+            Shape::Polyline(polyline) => polyline.center(),
+            Shape::Arrow(arrow) => arrow.center(),
+            Shape::Callout(callout) => callout.center(),
         }
     }
 }
+
+/// Index of the vertex of `shape` within click range of `pos`, as vertex dragging numbers them
+pub(super) fn vertex_at(shape: &Shape, pos: Pos2) -> Option<usize> {
+    match shape {
+        Shape::Rectangle(rect) => {
+            // Check all 4 corners
+            rect.corners()
+                .iter()
+                .enumerate()
+                .find(|(_, corner)| pos.distance(**corner) < VERTEX_CLICK_RADIUS)
+                .map(|(i, _)| i)
+        }
+        Shape::Circle(circle) => {
+            if pos.distance(*circle.center()) < VERTEX_CLICK_RADIUS {
+                Some(0)
+            } else {
+                let edge_point = egui::pos2(circle.center().x + circle.radius(), circle.center().y);
+                if pos.distance(edge_point) < VERTEX_CLICK_RADIUS {
+                    Some(1)
+                } else {
+                    None
+                }
+            }
+        }
+        // The center, then the ends of the x and y axes
+        Shape::Ellipse(ellipse) => std::iter::once(*ellipse.center())
+            .chain(ellipse.handles())
+            .position(|point| pos.distance(point) < VERTEX_CLICK_RADIUS),
+        Shape::Polygon(poly) => {
+            poly.to_egui_points()
+                .iter()
+                .enumerate()
+                .find(|(_, vertex_pos)| pos.distance(**vertex_pos) < VERTEX_CLICK_RADIUS)
+                .map(|(i, _)| i)
+        }
+        Shape::Line(line) => line
+            .points()
+            .iter()
+            .position(|point| pos.distance(*point) < VERTEX_CLICK_RADIUS),
+        Shape::Polyline(polyline) => polyline
+            .points()
+            .iter()
+            .position(|point| pos.distance(*point) < VERTEX_CLICK_RADIUS),
+        Shape::Arrow(arrow) => [*arrow.start(), *arrow.end()]
+            .iter()
+            .position(|point| pos.distance(*point) < VERTEX_CLICK_RADIUS),
+        Shape::Callout(callout) => callout_handles(callout)
+            .iter()
+            .position(|handle| pos.distance(*handle) < VERTEX_CLICK_RADIUS),
+    }
+}
+
+/// Text box of a callout drawn from `anchor` to `end`
+///
+/// The box takes its default size and sits beside `end`, on the side away
+/// from the anchor, so the leader stays visible.
+fn callout_box(anchor: Pos2, end: Pos2) -> egui::Rect {
+    let min = Pos2::new(
+        if end.x < anchor.x { end.x - CALLOUT_BOX_SIZE.x } else { end.x },
+        if end.y < anchor.y { end.y - CALLOUT_BOX_SIZE.y } else { end.y },
+    );
+    egui::Rect::from_min_size(min, CALLOUT_BOX_SIZE)
+}
+
+/// Edit handles of a callout: its anchor, then two opposite corners of its box
+pub(super) fn callout_handles(callout: &Callout) -> [Pos2; 3] {
+    [*callout.anchor(), callout.text_box().min, callout.text_box().max]
+}
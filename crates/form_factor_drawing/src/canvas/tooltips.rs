@@ -0,0 +1,93 @@
+//! Tooltips describing the object under the pointer
+//!
+//! Resting the pointer on a detection shows its name, the text OCR read from
+//! it and the engine's confidence; on a field it shows the field's label and
+//! type. This saves selecting the object and opening its properties just to
+//! check what was read.
+
+use super::core::{CanvasState, DrawingCanvas};
+use crate::{CommentTarget, ScreenPos};
+use egui::Pos2;
+
+/// Contents of the tooltip for an object on the canvas
+#[derive(Debug, Clone, PartialEq)]
+pub struct HoverTooltip {
+    /// Name of the object, shown in bold
+    pub title: String,
+    /// Details below the name, one per line
+    pub lines: Vec<String>,
+}
+
+impl DrawingCanvas {
+    /// Tooltip for the topmost visible field or detection at a canvas position
+    pub fn hover_tooltip(&self, pos: Pos2) -> Option<HoverTooltip> {
+        match self.object_at(pos)? {
+            CommentTarget::Shape(idx) => {
+                let shape = self.shapes.get(idx)?;
+                let rule = shape.rule();
+                let mut lines = vec![if rule.required {
+                    format!("Type: {} (required)", rule.format)
+                } else {
+                    format!("Type: {}", rule.format)
+                }];
+                if !shape.value().is_empty() {
+                    lines.push(format!("Value: {}", shape.value()));
+                }
+                if let Some(confidence) = shape.confidence() {
+                    lines.push(format!("Confidence: {:.0}%", confidence));
+                }
+                Some(HoverTooltip {
+                    title: title(shape.name(), "Unnamed field"),
+                    lines,
+                })
+            }
+            CommentTarget::Detection(idx) => {
+                let detection = self.detections.get(idx)?;
+                let mut lines = Vec::new();
+                if let Some(subtype) = self.detection_subtype(idx) {
+                    lines.push(format!("Subtype: {}", subtype.name));
+                }
+                match self.ocr_text(idx) {
+                    Some(text) => lines.push(format!("Text: {}", text.trim())),
+                    None => lines.push("Not read by OCR yet".to_string()),
+                }
+                if let Some(confidence) = self.ocr_confidence(idx).or(detection.confidence()) {
+                    lines.push(format!("Confidence: {:.0}%", confidence));
+                }
+                Some(HoverTooltip {
+                    title: title(detection.name(), "Unnamed detection"),
+                    lines,
+                })
+            }
+        }
+    }
+
+    /// Show the tooltip for the hovered object, unless an interaction is under way
+    pub(super) fn show_hover_tooltip(&self, response: &egui::Response) {
+        if !matches!(self.state, CanvasState::Idle) || response.is_pointer_button_down_on() || self.ocr_edit.is_some() {
+            return;
+        }
+        let Some(pos) = response.hover_pos() else {
+            return;
+        };
+        let canvas_pos = self.coordinate_mapper.screen_to_canvas(ScreenPos(pos)).0;
+        let Some(tooltip) = self.hover_tooltip(canvas_pos) else {
+            return;
+        };
+        response.clone().on_hover_ui_at_pointer(|ui| {
+            ui.strong(&tooltip.title);
+            for line in &tooltip.lines {
+                ui.label(line);
+            }
+        });
+    }
+}
+
+/// Name to show for an object, or `fallback` if it has none
+fn title(name: &str, fallback: &str) -> String {
+    if name.trim().is_empty() {
+        fallback.to_string()
+    } else {
+        name.to_string()
+    }
+}
@@ -0,0 +1,281 @@
+//! Validation reports over many filled forms
+//!
+//! QA samples filled forms by reading a report rather than opening each
+//! project. A [`ValidationReport`] checks every field of every instance
+//! and lists what it finds: values that break the field's rule are errors,
+//! while low-confidence values and values entered on fields whose condition
+//! isn't met are warnings. The report is written as CSV for spreadsheets
+//! or as a self-contained HTML page.
+
+use super::batch_review::BatchReview;
+use super::core::{CanvasError, CanvasErrorKind, DrawingCanvas};
+use super::export::xml_escape;
+use crate::field_applies;
+use form_factor_core::IoOperation;
+use std::path::Path;
+use tracing::{info, instrument};
+
+/// How serious a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, strum::Display)]
+pub enum Severity {
+    /// The value is wrong and must be fixed
+    Error,
+    /// The value should be checked by a person
+    Warning,
+}
+
+/// File format of a written report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::Display, strum::EnumIter)]
+pub enum ReportFormat {
+    /// Comma-separated values, one finding per row
+    #[strum(to_string = "CSV")]
+    Csv,
+    /// HTML page with a summary table and the findings of each instance
+    #[strum(to_string = "HTML")]
+    Html,
+}
+
+impl ReportFormat {
+    /// Format matching a file's extension, if it has a known one
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "csv" => Some(ReportFormat::Csv),
+            "html" | "htm" => Some(ReportFormat::Html),
+            _ => None,
+        }
+    }
+}
+
+/// Problem found with one field of one instance
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationFinding {
+    /// Index of the instance in the report
+    pub instance: usize,
+    /// Field name, or `Field N` for unnamed fields
+    pub field: String,
+    /// Value found in the field
+    pub value: String,
+    /// How serious the finding is
+    pub severity: Severity,
+    /// What is wrong
+    pub message: String,
+}
+
+/// Per-instance totals in a report
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstanceSummary {
+    /// Instance name
+    pub name: String,
+    /// Number of fields that apply to the instance
+    pub fields: usize,
+    /// Number of error findings
+    pub errors: usize,
+    /// Number of warning findings
+    pub warnings: usize,
+}
+
+/// Errors and warnings across a set of instances
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationReport {
+    threshold: f32,
+    instances: Vec<InstanceSummary>,
+    findings: Vec<ValidationFinding>,
+}
+
+impl ValidationReport {
+    /// Empty report warning about values with confidence under `threshold` (0-100)
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            threshold,
+            instances: Vec::new(),
+            findings: Vec::new(),
+        }
+    }
+
+    /// Report on every instance of a batch review, using its threshold
+    pub fn from_review(review: &BatchReview) -> Self {
+        let mut report = Self::new(review.threshold());
+        for instance in review.instances() {
+            report.add_instance(&instance.name, &instance.canvas);
+        }
+        report
+    }
+
+    /// Check every field of an instance and add what is found
+    #[instrument(skip(self, canvas), fields(shapes = canvas.shapes.len()))]
+    pub fn add_instance(&mut self, name: &str, canvas: &DrawingCanvas) {
+        let instance = self.instances.len();
+        let mut summary = InstanceSummary {
+            name: name.to_string(),
+            fields: 0,
+            errors: 0,
+            warnings: 0,
+        };
+
+        for (idx, shape) in canvas.shapes.iter().enumerate() {
+            let applies = field_applies(&canvas.shapes, idx);
+            let problem = if applies {
+                summary.fields += 1;
+                match (shape.check_value(), shape.confidence()) {
+                    (Err(issue), _) => Some((Severity::Error, issue.to_string())),
+                    (Ok(()), Some(confidence)) if confidence < self.threshold => {
+                        Some((Severity::Warning, format!("Low confidence ({:.0}%)", confidence)))
+                    }
+                    _ => None,
+                }
+            } else if !shape.value().trim().is_empty() {
+                let reason = shape
+                    .condition()
+                    .map_or_else(String::new, |condition| format!(" (applies {})", condition));
+                Some((
+                    Severity::Warning,
+                    format!("Value on a field that doesn't apply{}", reason),
+                ))
+            } else {
+                None
+            };
+
+            if let Some((severity, message)) = problem {
+                match severity {
+                    Severity::Error => summary.errors += 1,
+                    Severity::Warning => summary.warnings += 1,
+                }
+                self.findings.push(ValidationFinding {
+                    instance,
+                    field: match shape.name() {
+                        "" => format!("Field {}", idx + 1),
+                        name => name.to_string(),
+                    },
+                    value: shape.value().to_string(),
+                    severity,
+                    message,
+                });
+            }
+        }
+        self.instances.push(summary);
+    }
+
+    /// Instances in the report, in the order they were added
+    pub fn instances(&self) -> &[InstanceSummary] {
+        &self.instances
+    }
+
+    /// Every finding, grouped by instance
+    pub fn findings(&self) -> &[ValidationFinding] {
+        &self.findings
+    }
+
+    /// Number of errors across all instances
+    pub fn error_count(&self) -> usize {
+        self.instances.iter().map(|instance| instance.errors).sum()
+    }
+
+    /// Number of warnings across all instances
+    pub fn warning_count(&self) -> usize {
+        self.instances.iter().map(|instance| instance.warnings).sum()
+    }
+
+    /// Report as CSV with a header row and one finding per row
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("instance,field,severity,message,value\n");
+        for finding in &self.findings {
+            let row = [
+                self.instances[finding.instance].name.as_str(),
+                finding.field.as_str(),
+                &finding.severity.to_string(),
+                finding.message.as_str(),
+                finding.value.as_str(),
+            ]
+            .map(csv_field)
+            .join(",");
+            csv.push_str(&row);
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Report as a standalone HTML page
+    pub fn to_html(&self) -> String {
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Validation Report</title>\n<style>\n\
+             body { font-family: sans-serif; }\n\
+             table { border-collapse: collapse; margin-bottom: 1.5em; }\n\
+             th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; }\n\
+             .Error { color: #b00020; }\n\
+             .Warning { color: #a15c00; }\n\
+             </style>\n</head>\n<body>\n<h1>Validation Report</h1>\n",
+        );
+        html.push_str(&format!(
+            "<p>{} instance(s), {} error(s), {} warning(s)</p>\n",
+            self.instances.len(),
+            self.error_count(),
+            self.warning_count()
+        ));
+
+        html.push_str("<table>\n<tr><th>Instance</th><th>Fields</th><th>Errors</th><th>Warnings</th></tr>\n");
+        for instance in &self.instances {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                xml_escape(&instance.name),
+                instance.fields,
+                instance.errors,
+                instance.warnings
+            ));
+        }
+        html.push_str("</table>\n");
+
+        for (idx, instance) in self.instances.iter().enumerate() {
+            let findings: Vec<&ValidationFinding> = self.findings.iter().filter(|f| f.instance == idx).collect();
+            if findings.is_empty() {
+                continue;
+            }
+            html.push_str(&format!("<h2>{}</h2>\n<table>\n", xml_escape(&instance.name)));
+            html.push_str("<tr><th>Field</th><th>Severity</th><th>Message</th><th>Value</th></tr>\n");
+            for finding in findings {
+                html.push_str(&format!(
+                    "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    finding.severity,
+                    xml_escape(&finding.field),
+                    finding.severity,
+                    xml_escape(&finding.message),
+                    xml_escape(&finding.value)
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    /// Write the report in the format given by the file extension (`.csv`, `.html`)
+    #[instrument(skip(self), fields(instances = self.instances.len(), findings = self.findings.len()))]
+    pub fn save(&self, path: &Path) -> Result<(), CanvasError> {
+        let path_str = path.to_string_lossy().into_owned();
+        let contents = match ReportFormat::from_path(path) {
+            Some(ReportFormat::Csv) => self.to_csv(),
+            Some(ReportFormat::Html) => self.to_html(),
+            None => {
+                return Err(CanvasError::new(
+                    CanvasErrorKind::UnsupportedFile(format!("{} (expected .csv or .html)", path_str)),
+                    line!(),
+                    file!(),
+                ));
+            }
+        };
+        std::fs::write(path, contents)
+            .map_err(|e| CanvasError::io(e, path_str.as_str(), IoOperation::Write, line!(), file!()))?;
+
+        info!("Saved validation report to {}", path_str);
+        Ok(())
+    }
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,115 @@
+//! Page assignment window
+
+use super::core::DrawingCanvas;
+use crate::TaskStatus;
+use strum::IntoEnumIterator;
+
+impl DrawingCanvas {
+    /// Template page the canvas is on, the first page when none was applied
+    pub fn current_page(&self) -> usize {
+        self.template_page_index.unwrap_or(0)
+    }
+
+    /// Toggle the page assignment window
+    pub fn toggle_workflow_panel(&mut self) {
+        self.show_workflow = !self.show_workflow;
+        self.load_assignee_draft();
+    }
+
+    /// Fill the assignee field from the current page's task
+    fn load_assignee_draft(&mut self) {
+        let page = self.current_page();
+        self.assignee_draft = self
+            .workflow
+            .task(page)
+            .and_then(|t| t.assignee.clone())
+            .unwrap_or_default();
+        self.assignee_draft_page = page;
+    }
+
+    /// Show the page assignment window
+    ///
+    /// Lists page tasks, optionally filtered to one assignee, and lets the
+    /// user reassign the current page and update its status. Returns true if
+    /// the window was shown.
+    pub fn show_workflow_panel(&mut self, ctx: &egui::Context) -> bool {
+        if !self.show_workflow {
+            return false;
+        }
+
+        if self.assignee_draft_page != self.current_page() {
+            self.load_assignee_draft();
+        }
+
+        let mut panel_open = true;
+        egui::Window::new("Assignments")
+            .open(&mut panel_open)
+            .default_width(280.0)
+            .show(ctx, |ui| {
+                let page = self.current_page();
+                match &self.template_page {
+                    Some(name) => ui.heading(format!("Page {}: {}", page + 1, name)),
+                    None => ui.heading(format!("Page {}", page + 1)),
+                };
+                let mut status = self.workflow.status(page);
+
+                ui.horizontal(|ui| {
+                    ui.label("Assignee:");
+                    if ui.text_edit_singleline(&mut self.assignee_draft).lost_focus() {
+                        let assignee = self.assignee_draft.trim();
+                        self.workflow
+                            .assign(page, (!assignee.is_empty()).then(|| assignee.to_string()));
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Status:");
+                    egui::ComboBox::from_id_salt("page_status")
+                        .selected_text(status.to_string())
+                        .show_ui(ui, |ui| {
+                            for option in TaskStatus::iter() {
+                                ui.selectable_value(&mut status, option, option.to_string());
+                            }
+                        });
+                });
+                if status != self.workflow.status(page) {
+                    self.workflow.set_status(page, status);
+                }
+
+                ui.separator();
+                ui.heading("All Pages");
+
+                let filter_label = self.workflow_filter.clone().unwrap_or_else(|| "Everyone".to_string());
+                let assignees: Vec<String> = self.workflow.assignees().into_iter().map(String::from).collect();
+                egui::ComboBox::from_label("Assignee")
+                    .selected_text(filter_label)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.workflow_filter, None, "Everyone");
+                        for name in assignees {
+                            ui.selectable_value(&mut self.workflow_filter, Some(name.clone()), name);
+                        }
+                    });
+
+                let filter = self.workflow_filter.as_deref();
+                for task in self
+                    .workflow
+                    .tasks()
+                    .iter()
+                    .filter(|t| filter.is_none() || t.assignee.as_deref() == filter)
+                {
+                    ui.label(format!(
+                        "Page {}: {} ({})",
+                        task.page + 1,
+                        task.assignee.as_deref().unwrap_or("-"),
+                        task.status
+                    ));
+                }
+            });
+
+        if !panel_open {
+            self.show_workflow = false;
+        }
+
+        true
+    }
+}
@@ -0,0 +1,710 @@
+//! Collaborative annotation over a shared operation log
+//!
+//! Shape edits are expressed as operations stamped with a Lamport timestamp
+//! and the author's actor ID. Replaying the operations in timestamp order
+//! gives every replica the same shapes regardless of the order in which they
+//! arrived, so two annotators can edit the same project concurrently and
+//! converge once their logs are synced.
+//!
+//! Syncing is abstracted behind [`SyncTransport`]. [`StoreSyncTransport`]
+//! syncs through the configured [`ProjectStore`], a shared directory or an
+//! S3 bucket, so collaborators need no server beyond the one they already
+//! store projects in. [`InMemorySyncServer`] implements it for local
+//! sessions and tests.
+//!
+//! Collaborators start from the same version of a project, e.g. one
+//! opened from the project store. [`CollabSession::with_shapes`] seeds the
+//! log with its shapes under IDs every replica derives alike, and
+//! [`CollabSession::track`] turns later changes to the canvas shapes,
+//! fields included, into operations. Detections and comments are not
+//! shared. The canvas drives a session from its collaboration window.
+
+use crate::store::checked_name;
+use crate::{ProjectStore, Shape, StoreError, StoreErrorKind};
+use egui::{Color32, Pos2};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, instrument, trace, warn};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds after its last update that a collaborator counts as gone
+pub const PRESENCE_TIMEOUT_SECS: u64 = 30;
+
+/// Actor of the operations seeding a session with the shapes it started from
+const BASE_ACTOR: &str = "base";
+
+/// Kinds of errors that can occur while syncing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CollabErrorKind {
+    /// The sync server could not be reached or is in a broken state
+    ServerUnavailable(String),
+    /// A session or actor name that can't be used on the sync server
+    InvalidName(String),
+    /// Operations or presence on the sync server could not be read
+    InvalidData(String),
+    /// The project is password protected, and sharing it would store its shapes unencrypted
+    ProtectedProject,
+}
+
+impl std::fmt::Display for CollabErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CollabErrorKind::ServerUnavailable(msg) => write!(f, "Sync server unavailable: {}", msg),
+            CollabErrorKind::InvalidName(name) => write!(f, "Invalid session or collaborator name: {:?}", name),
+            CollabErrorKind::InvalidData(msg) => write!(f, "Unreadable sync data: {}", msg),
+            CollabErrorKind::ProtectedProject => {
+                write!(f, "Password-protected projects can't be shared in a collaboration session")
+            }
+        }
+    }
+}
+
+/// Error type for collaboration operations
+#[derive(Debug, Clone)]
+pub struct CollabError {
+    /// The kind of error that occurred
+    pub kind: CollabErrorKind,
+    /// Line number where the error was created
+    pub line: u32,
+    /// File where the error was created
+    pub file: &'static str,
+}
+
+impl CollabError {
+    /// Create a new collaboration error
+    pub fn new(kind: CollabErrorKind, line: u32, file: &'static str) -> Self {
+        Self { kind, line, file }
+    }
+}
+
+impl std::fmt::Display for CollabError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Collab Error: {} at line {} in {}", self.kind, self.line, self.file)
+    }
+}
+
+impl std::error::Error for CollabError {}
+
+/// Unique identifier for an operation: a Lamport timestamp plus the author
+///
+/// Ordering compares the counter first and breaks ties by actor, which
+/// gives every replica the same total order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct OpId {
+    /// Lamport counter
+    pub counter: u64,
+    /// Actor that created the operation
+    pub actor: String,
+}
+
+/// A single edit to the shared shape set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ShapeEdit {
+    /// Add a shape; the operation ID becomes the shape's identity
+    Insert(Shape),
+    /// Replace the geometry and style of a previously inserted shape
+    Replace {
+        /// ID of the insert operation that created the shape
+        target: OpId,
+        /// New shape contents
+        shape: Shape,
+    },
+    /// Remove a previously inserted shape
+    Remove {
+        /// ID of the insert operation that created the shape
+        target: OpId,
+    },
+}
+
+/// An edit stamped with its operation ID
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Operation {
+    /// Unique, totally ordered identifier
+    pub id: OpId,
+    /// The edit performed
+    pub edit: ShapeEdit,
+}
+
+/// Replicated log of shape operations for one actor
+///
+/// Local edits are recorded with [`OperationLog::record`]; edits from peers
+/// are merged with [`OperationLog::merge`]. Merging is idempotent, so the
+/// same operation can safely be delivered more than once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationLog {
+    /// This replica's actor ID
+    actor: String,
+    /// Highest Lamport counter seen so far
+    clock: u64,
+    /// All known operations, in arrival order
+    operations: Vec<Operation>,
+    /// IDs of known operations, for deduplication
+    #[serde(skip)]
+    seen: HashSet<OpId>,
+}
+
+impl OperationLog {
+    /// Create an empty log for the given actor
+    pub fn new(actor: impl Into<String>) -> Self {
+        Self {
+            actor: actor.into(),
+            clock: 0,
+            operations: Vec::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// This replica's actor ID
+    pub fn actor(&self) -> &str {
+        &self.actor
+    }
+
+    /// All known operations, in arrival order
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    /// Record a local edit and return the stamped operation
+    pub fn record(&mut self, edit: ShapeEdit) -> Operation {
+        self.clock += 1;
+        let op = Operation {
+            id: OpId {
+                counter: self.clock,
+                actor: self.actor.clone(),
+            },
+            edit,
+        };
+        trace!(id = ?op.id, "Recorded local operation");
+        self.seen.insert(op.id.clone());
+        self.operations.push(op.clone());
+        op
+    }
+
+    /// Merge operations from a peer
+    ///
+    /// Returns the number of operations that were new to this replica.
+    #[instrument(skip(self, ops), fields(actor = %self.actor))]
+    pub fn merge(&mut self, ops: impl IntoIterator<Item = Operation>) -> usize {
+        if self.seen.len() != self.operations.len() {
+            // Rebuild the index after deserialization
+            self.seen = self.operations.iter().map(|op| op.id.clone()).collect();
+        }
+
+        let mut added = 0;
+        for op in ops {
+            if self.seen.insert(op.id.clone()) {
+                self.clock = self.clock.max(op.id.counter);
+                self.operations.push(op);
+                added += 1;
+            }
+        }
+        debug!(added, "Merged remote operations");
+        added
+    }
+
+    /// Replay the log into the current set of shapes
+    ///
+    /// Operations are applied in [`OpId`] order, so replicas holding the same
+    /// operations produce the same shapes. The most recent replace wins, and
+    /// a remove is final since it always sorts after the insert it targets.
+    pub fn materialize(&self) -> Vec<(OpId, Shape)> {
+        let mut ordered: Vec<&Operation> = self.operations.iter().collect();
+        ordered.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut shapes: BTreeMap<OpId, Shape> = BTreeMap::new();
+
+        for op in ordered {
+            match &op.edit {
+                ShapeEdit::Insert(shape) => {
+                    shapes.insert(op.id.clone(), shape.clone());
+                }
+                ShapeEdit::Replace { target, shape } => {
+                    if let Some(existing) = shapes.get_mut(target) {
+                        *existing = shape.clone();
+                    }
+                }
+                ShapeEdit::Remove { target } => {
+                    shapes.remove(target);
+                }
+            }
+        }
+
+        shapes.into_iter().collect()
+    }
+}
+
+/// Where a collaborator is and what they are looking at
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Presence {
+    /// Actor ID of the collaborator
+    pub actor: String,
+    /// Name shown next to the collaborator's cursor
+    pub display_name: String,
+    /// Cursor position in canvas coordinates, if over the canvas
+    pub cursor: Option<Pos2>,
+    /// Color used for the collaborator's cursor and label
+    pub color: Color32,
+}
+
+/// Transport that exchanges operations and presence with other replicas
+pub trait SyncTransport {
+    /// Send local operations to the server
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server cannot accept the operations.
+    fn push(&mut self, ops: &[Operation]) -> Result<(), CollabError>;
+
+    /// Fetch operations the server has received since the last pull
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server cannot be reached.
+    fn pull(&mut self) -> Result<Vec<Operation>, CollabError>;
+
+    /// Publish this replica's presence
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server cannot be reached.
+    fn publish_presence(&mut self, presence: Presence) -> Result<(), CollabError>;
+
+    /// Presence of every other connected collaborator
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server cannot be reached.
+    fn peers(&mut self) -> Result<Vec<Presence>, CollabError>;
+}
+
+/// Shared state held by the in-memory server
+#[derive(Debug, Default)]
+struct ServerState {
+    operations: Vec<Operation>,
+    presence: BTreeMap<String, Presence>,
+}
+
+/// In-process sync server, shared between clients by cloning
+///
+/// Useful for local multi-window sessions and for tests.
+#[derive(Debug, Clone, Default)]
+pub struct InMemorySyncServer {
+    state: Arc<Mutex<ServerState>>,
+}
+
+impl InMemorySyncServer {
+    /// Create an empty server
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Connect a client for the given actor
+    pub fn connect(&self, actor: impl Into<String>) -> InMemorySyncClient {
+        InMemorySyncClient {
+            actor: actor.into(),
+            server: self.clone(),
+            cursor: 0,
+        }
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, ServerState>, CollabError> {
+        self.state.lock().map_err(|e| {
+            CollabError::new(CollabErrorKind::ServerUnavailable(e.to_string()), line!(), file!())
+        })
+    }
+}
+
+/// Client connection to an [`InMemorySyncServer`]
+#[derive(Debug, Clone)]
+pub struct InMemorySyncClient {
+    actor: String,
+    server: InMemorySyncServer,
+    /// Number of server operations already pulled
+    cursor: usize,
+}
+
+impl SyncTransport for InMemorySyncClient {
+    fn push(&mut self, ops: &[Operation]) -> Result<(), CollabError> {
+        let mut state = self.server.lock()?;
+        state.operations.extend_from_slice(ops);
+        Ok(())
+    }
+
+    fn pull(&mut self) -> Result<Vec<Operation>, CollabError> {
+        let state = self.server.lock()?;
+        let ops = state.operations[self.cursor..].to_vec();
+        self.cursor = state.operations.len();
+        Ok(ops)
+    }
+
+    fn publish_presence(&mut self, presence: Presence) -> Result<(), CollabError> {
+        let mut state = self.server.lock()?;
+        state.presence.insert(presence.actor.clone(), presence);
+        Ok(())
+    }
+
+    fn peers(&mut self) -> Result<Vec<Presence>, CollabError> {
+        let state = self.server.lock()?;
+        Ok(state
+            .presence
+            .values()
+            .filter(|p| p.actor != self.actor)
+            .cloned()
+            .collect())
+    }
+}
+
+/// A replica's log paired with its transport
+#[derive(Debug, Clone)]
+pub struct CollabSession<T: SyncTransport> {
+    log: OperationLog,
+    transport: T,
+    /// Local operations not yet pushed
+    outbox: Vec<Operation>,
+    /// Shapes as last tracked or synced, with the IDs of the operations that inserted them
+    tracked: Vec<(OpId, Shape)>,
+}
+
+impl<T: SyncTransport> CollabSession<T> {
+    /// Start a session for the given actor over a transport
+    pub fn new(actor: impl Into<String>, transport: T) -> Self {
+        Self {
+            log: OperationLog::new(actor),
+            transport,
+            outbox: Vec::new(),
+            tracked: Vec::new(),
+        }
+    }
+
+    /// Start a session on a project that already has `shapes`
+    ///
+    /// Every collaborator starting from the same shapes seeds the same
+    /// operations, so the shapes are shared without being sent.
+    pub fn with_shapes(actor: impl Into<String>, transport: T, shapes: Vec<Shape>) -> Self {
+        let mut session = Self::new(actor, transport);
+        let base = shapes.into_iter().enumerate().map(|(index, shape)| Operation {
+            id: OpId {
+                counter: 0,
+                actor: format!("{}/{:08}", BASE_ACTOR, index),
+            },
+            edit: ShapeEdit::Insert(shape),
+        });
+        session.log.merge(base);
+        session.tracked = session.log.materialize();
+        session
+    }
+
+    /// Record the edits that turn the shapes as last tracked or synced into `shapes`
+    ///
+    /// Shapes are matched by position: the run that differs between the
+    /// two lists becomes replaces, then inserts or removes for the
+    /// difference in length. Returns the number of edits recorded.
+    pub fn track(&mut self, shapes: &[Shape]) -> usize {
+        let old = std::mem::take(&mut self.tracked);
+        let prefix = old.iter().zip(shapes).take_while(|((_, a), b)| a == *b).count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(shapes[prefix..].iter().rev())
+            .take_while(|((_, a), b)| a == *b)
+            .count();
+        let removed = &old[prefix..old.len() - suffix];
+        let added = &shapes[prefix..shapes.len() - suffix];
+
+        let mut tracked = old[..prefix].to_vec();
+        for (index, shape) in added.iter().enumerate() {
+            let id = match removed.get(index) {
+                Some((target, _)) => {
+                    self.edit(ShapeEdit::Replace {
+                        target: target.clone(),
+                        shape: shape.clone(),
+                    });
+                    target.clone()
+                }
+                None => self.edit(ShapeEdit::Insert(shape.clone())),
+            };
+            tracked.push((id, shape.clone()));
+        }
+        for (target, _) in removed.iter().skip(added.len()) {
+            self.edit(ShapeEdit::Remove { target: target.clone() });
+        }
+        tracked.extend_from_slice(&old[old.len() - suffix..]);
+        self.tracked = tracked;
+
+        let edits = added.len().max(removed.len());
+        if edits > 0 {
+            debug!(edits, "Tracked local shape edits");
+        }
+        edits
+    }
+
+    /// The replicated operation log
+    pub fn log(&self) -> &OperationLog {
+        &self.log
+    }
+
+    /// Record a local edit; it is sent on the next [`CollabSession::sync`]
+    pub fn edit(&mut self, edit: ShapeEdit) -> OpId {
+        let op = self.log.record(edit);
+        let id = op.id.clone();
+        self.outbox.push(op);
+        id
+    }
+
+    /// Push pending local edits and merge remote ones
+    ///
+    /// Returns the number of new remote operations merged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transport fails. Unsent edits stay queued.
+    #[instrument(skip(self), fields(actor = %self.log.actor(), pending = self.outbox.len()))]
+    pub fn sync(&mut self) -> Result<usize, CollabError> {
+        if !self.outbox.is_empty() {
+            self.transport.push(&self.outbox)?;
+            self.outbox.clear();
+        }
+        let remote = self.transport.pull()?;
+        let added = self.log.merge(remote);
+        if added > 0 {
+            self.tracked = self.log.materialize();
+        }
+        Ok(added)
+    }
+
+    /// Publish presence and return the presence of other collaborators
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transport fails.
+    pub fn exchange_presence(&mut self, presence: Presence) -> Result<Vec<Presence>, CollabError> {
+        self.transport.publish_presence(presence)?;
+        self.transport.peers()
+    }
+
+    /// Current shapes, in a deterministic order shared by all replicas
+    pub fn shapes(&self) -> Vec<Shape> {
+        self.log.materialize().into_iter().map(|(_, shape)| shape).collect()
+    }
+
+    /// The transport the session syncs over
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+}
+
+// ============================================================================
+// Project store
+// ============================================================================
+
+/// Presence as stored, with when it was published
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredPresence {
+    presence: Presence,
+    /// Seconds since the Unix epoch
+    updated: u64,
+}
+
+/// Transport syncing through a [`ProjectStore`], a shared directory or an S3 bucket
+///
+/// Each collaborator writes each batch of operations it pushes to a new
+/// object, `collab/{session}/operations/{actor}/{batch}.json`, numbered in
+/// order, and its presence to `collab/{session}/presence/{actor}.json`. It
+/// reads everyone else's, only fetching batches it has not seen yet, so no
+/// two collaborators write the same object and a sync costs as much as the
+/// operations it carries. Collaborators whose presence is older than
+/// [`PRESENCE_TIMEOUT_SECS`] are taken to have left.
+#[derive(Clone)]
+pub struct StoreSyncTransport {
+    store: Arc<dyn ProjectStore + Send + Sync>,
+    session: String,
+    actor: String,
+    /// Number of the next batch this collaborator pushes, once its existing batches were listed
+    next_batch: Option<u64>,
+    /// Number of the next batch to pull from each other collaborator
+    pulled: BTreeMap<String, u64>,
+}
+
+impl std::fmt::Debug for StoreSyncTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StoreSyncTransport")
+            .field("session", &self.session)
+            .field("actor", &self.actor)
+            .field("next_batch", &self.next_batch)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StoreSyncTransport {
+    /// Transport for `actor` in the collaboration session `session` of `store`
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CollabErrorKind::InvalidName`] error if the session or
+    /// actor can't be used as a store key segment.
+    pub fn new(
+        store: Arc<dyn ProjectStore + Send + Sync>,
+        session: impl Into<String>,
+        actor: impl Into<String>,
+    ) -> Result<Self, CollabError> {
+        let (session, actor) = (session.into(), actor.into());
+        for name in [&session, &actor] {
+            if checked_name(name).is_err() {
+                return Err(CollabError::new(CollabErrorKind::InvalidName(name.clone()), line!(), file!()));
+            }
+        }
+        Ok(Self {
+            store,
+            session,
+            actor,
+            next_batch: None,
+            pulled: BTreeMap::new(),
+        })
+    }
+
+    /// Name of the collaboration session
+    pub fn session(&self) -> &str {
+        &self.session
+    }
+
+    /// Remove this collaborator's presence, so others stop showing it at once
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store can't be written.
+    pub fn leave(&self) -> Result<(), CollabError> {
+        self.store.delete(&self.key("presence", &self.actor)).map_err(unavailable)
+    }
+
+    fn prefix(&self, kind: &str) -> String {
+        format!("collab/{}/{}/", self.session, kind)
+    }
+
+    fn key(&self, kind: &str, actor: &str) -> String {
+        format!("{}{}.json", self.prefix(kind), actor)
+    }
+
+    /// Other collaborators with objects of `kind`, with their keys
+    fn others(&self, kind: &str) -> Result<Vec<(String, String)>, CollabError> {
+        let prefix = self.prefix(kind);
+        let keys = self.store.list(&prefix).map_err(unavailable)?;
+        Ok(keys
+            .into_iter()
+            .filter_map(|key| {
+                let actor = key.strip_prefix(&prefix)?.strip_suffix(".json")?.to_string();
+                (actor != self.actor && !actor.contains('/')).then_some((actor, key))
+            })
+            .collect())
+    }
+
+    /// Key of `actor`'s batch of operations numbered `batch`
+    ///
+    /// Numbers are zero-padded so the store lists batches in order.
+    fn batch_key(&self, actor: &str, batch: u64) -> String {
+        format!("{}{}/{:020}.json", self.prefix("operations"), actor, batch)
+    }
+
+    /// Batches of operations in the store, as (actor, batch number, key), in order
+    fn batches(&self) -> Result<Vec<(String, u64, String)>, CollabError> {
+        let prefix = self.prefix("operations");
+        let keys = self.store.list(&prefix).map_err(unavailable)?;
+        Ok(keys
+            .into_iter()
+            .filter_map(|key| {
+                let (actor, batch) = key.strip_prefix(&prefix)?.strip_suffix(".json")?.split_once('/')?;
+                let batch = batch.parse().ok()?;
+                Some((actor.to_string(), batch, key))
+            })
+            .collect())
+    }
+
+    /// Contents of `key`, or `None` if it was removed since it was listed
+    fn read<V: serde::de::DeserializeOwned>(&self, key: &str) -> Result<Option<V>, CollabError> {
+        let bytes = match self.store.read(key) {
+            Ok(bytes) => bytes,
+            Err(e) if matches!(e.kind, StoreErrorKind::NotFound(_)) => return Ok(None),
+            Err(e) => return Err(unavailable(e)),
+        };
+        serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| CollabError::new(CollabErrorKind::InvalidData(format!("{}: {}", key, e)), line!(), file!()))
+    }
+
+    fn write(&self, key: &str, value: &impl Serialize) -> Result<(), CollabError> {
+        let bytes = serde_json::to_vec(value)
+            .map_err(|e| CollabError::new(CollabErrorKind::InvalidData(e.to_string()), line!(), file!()))?;
+        self.store.write(key, &bytes).map_err(unavailable)
+    }
+}
+
+fn unavailable(e: StoreError) -> CollabError {
+    CollabError::new(CollabErrorKind::ServerUnavailable(e.kind.to_string()), line!(), file!())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+impl SyncTransport for StoreSyncTransport {
+    fn push(&mut self, ops: &[Operation]) -> Result<(), CollabError> {
+        if ops.is_empty() {
+            return Ok(());
+        }
+        // Rejoining a session continues after the batches pushed before
+        let batch = match self.next_batch {
+            Some(batch) => batch,
+            None => self
+                .batches()?
+                .into_iter()
+                .filter(|(actor, _, _)| *actor == self.actor)
+                .map(|(_, batch, _)| batch + 1)
+                .max()
+                .unwrap_or_default(),
+        };
+        self.write(&self.batch_key(&self.actor, batch), &ops)?;
+        self.next_batch = Some(batch + 1);
+        trace!(batch, operations = ops.len(), "Pushed a batch of operations");
+        Ok(())
+    }
+
+    fn pull(&mut self) -> Result<Vec<Operation>, CollabError> {
+        let mut ops = Vec::new();
+        for (actor, batch, key) in self.batches()? {
+            if actor == self.actor || batch < self.pulled.get(&actor).copied().unwrap_or_default() {
+                continue;
+            }
+            if let Some(theirs) = self.read::<Vec<Operation>>(&key)? {
+                ops.extend(theirs);
+            }
+            self.pulled.insert(actor, batch + 1);
+        }
+        Ok(ops)
+    }
+
+    fn publish_presence(&mut self, presence: Presence) -> Result<(), CollabError> {
+        let stored = StoredPresence {
+            presence,
+            updated: unix_now(),
+        };
+        self.write(&self.key("presence", &self.actor), &stored)
+    }
+
+    fn peers(&mut self) -> Result<Vec<Presence>, CollabError> {
+        let now = unix_now();
+        let mut peers = Vec::new();
+        for (actor, key) in self.others("presence")? {
+            match self.read::<StoredPresence>(&key) {
+                Ok(Some(stored)) if now.saturating_sub(stored.updated) <= PRESENCE_TIMEOUT_SECS => {
+                    peers.push(stored.presence);
+                }
+                Ok(_) => {}
+                Err(e) => warn!(actor, "Ignoring unreadable presence: {}", e),
+            }
+        }
+        Ok(peers)
+    }
+}
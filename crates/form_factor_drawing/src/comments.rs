@@ -0,0 +1,92 @@
+//! Review comment threads attached to canvas objects
+//!
+//! Threads reference shapes and detections by index, matching how the
+//! canvas tracks selection. They are persisted with the project.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use web_time::{SystemTime, UNIX_EPOCH};
+
+/// Canvas object a comment thread is attached to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CommentTarget {
+    /// A user-drawn shape or field, by index
+    Shape(usize),
+    /// A detection, by index
+    Detection(usize),
+}
+
+impl std::fmt::Display for CommentTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommentTarget::Shape(idx) => write!(f, "Shape {}", idx + 1),
+            CommentTarget::Detection(idx) => write!(f, "Detection {}", idx + 1),
+        }
+    }
+}
+
+/// A single comment in a thread
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Comment {
+    /// Name of the reviewer who wrote the comment
+    pub author: String,
+    /// Comment text
+    pub body: String,
+    /// Creation time in seconds since the Unix epoch
+    pub created_at: u64,
+    /// Image shown with the comment, e.g. a screenshot of the canvas
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachment: Option<PathBuf>,
+}
+
+impl Comment {
+    /// Create a comment stamped with the current time
+    pub fn new(author: impl Into<String>, body: impl Into<String>) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Self {
+            author: author.into(),
+            body: body.into(),
+            created_at,
+            attachment: None,
+        }
+    }
+}
+
+/// A thread of comments on one canvas object
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommentThread {
+    /// Object the thread is attached to
+    pub target: CommentTarget,
+    /// Comments in the order they were posted
+    pub comments: Vec<Comment>,
+    /// Whether the discussion has been resolved
+    #[serde(default)]
+    pub resolved: bool,
+}
+
+impl CommentThread {
+    /// Start a new thread with an opening comment
+    pub fn new(target: CommentTarget, first: Comment) -> Self {
+        Self {
+            target,
+            comments: vec![first],
+            resolved: false,
+        }
+    }
+
+    /// Add a reply to the thread
+    ///
+    /// Replying to a resolved thread reopens it.
+    pub fn reply(&mut self, comment: Comment) {
+        self.comments.push(comment);
+        self.resolved = false;
+    }
+
+    /// Mark the thread as resolved or reopen it
+    pub fn set_resolved(&mut self, resolved: bool) {
+        self.resolved = resolved;
+    }
+}
@@ -0,0 +1,299 @@
+//! Coordinate spaces and the mapping between them
+//!
+//! The canvas works in three coordinate spaces:
+//! - Image space ([`ImagePos`]): pixels of the form image. Detections and
+//!   OCR crops live here.
+//! - Canvas space ([`CanvasPos`]): unzoomed canvas units, where the form
+//!   image is fitted and centered in the viewport. Drawn shapes live here.
+//! - Screen space ([`ScreenPos`]): egui points after zoom and pan, where
+//!   pointer input arrives and painting happens.
+//!
+//! A [`CoordinateMapper`] captures the viewport, zoom, pan and image fit of
+//! one rendered frame and converts between all three, so conversions don't
+//! get reimplemented (and subtly diverge) at each call site.
+
+use crate::{Arrow, Callout, Circle, Ellipse, Line, PolygonShape, Polyline, Rectangle, Shape};
+use egui::emath::TSTransform;
+use egui::{Pos2, Rect, Vec2};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Position in form image pixels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImagePos(pub Pos2);
+
+/// Position in unzoomed canvas units
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CanvasPos(pub Pos2);
+
+/// Position in screen points after zoom and pan
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScreenPos(pub Pos2);
+
+/// Where the form image sits in canvas space
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageFit {
+    /// Size of the form image in pixels
+    pub image_size: Vec2,
+    /// Canvas units per image pixel
+    pub scale: f32,
+    /// Canvas position of the image's top-left pixel
+    pub offset: Pos2,
+}
+
+impl ImageFit {
+    /// Fit an image within a viewport, preserving its aspect ratio and centering it
+    pub fn new(viewport: Rect, image_size: Vec2) -> Self {
+        let size = viewport.size();
+        let scale = (size.x / image_size.x).min(size.y / image_size.y);
+        let offset = viewport.min + (size - image_size * scale) / 2.0;
+        Self {
+            image_size,
+            scale,
+            offset,
+        }
+    }
+
+    /// Area covered by the image in canvas space
+    pub fn canvas_rect(&self) -> Rect {
+        Rect::from_min_size(self.offset, self.image_size * self.scale)
+    }
+}
+
+/// Conversions between image, canvas and screen coordinates for one frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoordinateMapper {
+    viewport: Rect,
+    zoom: f32,
+    pan: Vec2,
+    image: Option<ImageFit>,
+}
+
+impl Default for CoordinateMapper {
+    fn default() -> Self {
+        Self::new(Rect::ZERO, 1.0, Vec2::ZERO)
+    }
+}
+
+impl CoordinateMapper {
+    /// Mapper for a viewport with the given zoom and pan and no form image
+    ///
+    /// Zoom scales about the viewport center, then the pan is applied.
+    pub fn new(viewport: Rect, zoom: f32, pan: Vec2) -> Self {
+        Self {
+            viewport,
+            zoom,
+            pan,
+            image: None,
+        }
+    }
+
+    /// Fit a form image of the given pixel size within the viewport
+    pub fn with_image_size(mut self, image_size: Option<Vec2>) -> Self {
+        self.image = image_size.map(|size| ImageFit::new(self.viewport, size));
+        self
+    }
+
+    /// Screen area of the canvas
+    pub fn viewport(&self) -> Rect {
+        self.viewport
+    }
+
+    /// Screen points per canvas unit
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Placement of the form image, if one is loaded
+    pub fn image_fit(&self) -> Option<ImageFit> {
+        self.image
+    }
+
+    /// Canvas-to-screen transform, for painting canvas-space geometry
+    pub fn to_screen(&self) -> TSTransform {
+        let center = self.viewport.center().to_vec2();
+        TSTransform::from_translation(center + self.pan)
+            * TSTransform::from_scaling(self.zoom)
+            * TSTransform::from_translation(-center)
+    }
+
+    /// Map a canvas position to the screen
+    pub fn canvas_to_screen(&self, pos: CanvasPos) -> ScreenPos {
+        ScreenPos(self.to_screen().mul_pos(pos.0))
+    }
+
+    /// Map a screen position to the canvas
+    pub fn screen_to_canvas(&self, pos: ScreenPos) -> CanvasPos {
+        CanvasPos(self.to_screen().inverse().mul_pos(pos.0))
+    }
+
+    /// Map an image pixel to the canvas; `None` without a form image
+    pub fn image_to_canvas(&self, pos: ImagePos) -> Option<CanvasPos> {
+        self.image
+            .map(|fit| CanvasPos(Pos2::new(pos.0.x * fit.scale, pos.0.y * fit.scale) + fit.offset.to_vec2()))
+    }
+
+    /// Map a canvas position to image pixels; `None` without a form image
+    pub fn canvas_to_image(&self, pos: CanvasPos) -> Option<ImagePos> {
+        self.image.map(|fit| {
+            let p = pos.0 - fit.offset;
+            ImagePos(Pos2::new(p.x / fit.scale, p.y / fit.scale))
+        })
+    }
+
+    /// Map an image pixel to the screen; `None` without a form image
+    pub fn image_to_screen(&self, pos: ImagePos) -> Option<ScreenPos> {
+        self.image_to_canvas(pos).map(|pos| self.canvas_to_screen(pos))
+    }
+
+    /// Map a screen position to image pixels; `None` without a form image
+    pub fn screen_to_image(&self, pos: ScreenPos) -> Option<ImagePos> {
+        self.canvas_to_image(self.screen_to_canvas(pos))
+    }
+
+    /// Map a shape from image pixels to canvas space; `None` without a form image
+    pub fn image_shape_to_canvas(&self, shape: &Shape) -> Option<Shape> {
+        self.image.map(|fit| map_shape(shape, fit.scale, fit.offset))
+    }
+
+    /// Map a shape from canvas space to image pixels; `None` without a form image
+    pub fn canvas_shape_to_image(&self, shape: &Shape) -> Option<Shape> {
+        self.image.map(|fit| {
+            map_shape(
+                shape,
+                1.0 / fit.scale,
+                Pos2::new(-fit.offset.x / fit.scale, -fit.offset.y / fit.scale),
+            )
+        })
+    }
+
+    /// Pixel crop `(x, y, width, height)` covering an image-space rectangle
+    ///
+    /// Edges are rounded outward so partially covered pixels are kept, and
+    /// the crop is clamped to the image when one is loaded.
+    pub fn pixel_crop(&self, bounds: Rect) -> (u32, u32, u32, u32) {
+        let limit = self
+            .image
+            .map(|fit| fit.image_size)
+            .unwrap_or(Vec2::splat(f32::INFINITY));
+        let x0 = bounds.min.x.floor().clamp(0.0, limit.x);
+        let y0 = bounds.min.y.floor().clamp(0.0, limit.y);
+        let x1 = bounds.max.x.ceil().clamp(x0, limit.x);
+        let y1 = bounds.max.y.ceil().clamp(y0, limit.y);
+        (x0 as u32, y0 as u32, (x1 - x0) as u32, (y1 - y0) as u32)
+    }
+}
+
+/// Scale a shape about the origin and then translate it by `offset`
+///
+/// This is the one affine map between coordinate spaces; the mapper methods
+/// and exports build on it.
+pub(crate) fn map_shape(shape: &Shape, scale: f32, offset: Pos2) -> Shape {
+    let map = |p: &Pos2| Pos2::new(p.x * scale + offset.x, p.y * scale + offset.y);
+
+    let mut mapped = match shape {
+        Shape::Rectangle(rect) => {
+            let mapped_corners: Vec<Pos2> = rect.corners().iter().map(map).collect();
+
+            Rectangle::from_four_corners(
+                [
+                    mapped_corners[0],
+                    mapped_corners[1],
+                    mapped_corners[2],
+                    mapped_corners[3],
+                ],
+                rect.stroke,
+                rect.fill,
+            )
+            .map(|mut r| {
+                r.name = rect.name.clone();
+                Shape::Rectangle(r)
+            })
+            .unwrap_or_else(|e| {
+                warn!("Failed to map rectangle: {}", e);
+                Shape::Rectangle(rect.clone())
+            })
+        }
+        Shape::Circle(circle) => Circle::new(map(&circle.center), circle.radius * scale, circle.stroke, circle.fill)
+            .map(|mut c| {
+                c.name = circle.name.clone();
+                Shape::Circle(c)
+            })
+            .unwrap_or_else(|e| {
+                warn!("Failed to map circle: {}", e);
+                Shape::Circle(circle.clone())
+            }),
+        Shape::Ellipse(ellipse) => Ellipse::new(
+            map(ellipse.center()),
+            *ellipse.radii() * scale,
+            *ellipse.rotation(),
+            ellipse.stroke,
+            ellipse.fill,
+        )
+        .map(|mut e| {
+            e.name = ellipse.name.clone();
+            Shape::Ellipse(e)
+        })
+        .unwrap_or_else(|e| {
+            warn!("Failed to map ellipse: {}", e);
+            Shape::Ellipse(ellipse.clone())
+        }),
+        Shape::Polygon(poly) => {
+            let mapped_points: Vec<Pos2> = poly.to_egui_points().iter().map(map).collect();
+
+            PolygonShape::from_points(mapped_points, poly.stroke, poly.fill)
+                .map(|mut p| {
+                    p.name = poly.name.clone();
+                    Shape::Polygon(p)
+                })
+                .unwrap_or_else(|e| {
+                    warn!("Failed to map polygon: {}", e);
+                    Shape::Polygon(poly.clone())
+                })
+        }
+        Shape::Line(line) => Line::new(map(line.start()), map(line.end()), line.stroke)
+            .map(|mut l| {
+                l.name = line.name.clone();
+                l.show_length = line.show_length;
+                Shape::Line(l)
+            })
+            .unwrap_or_else(|e| {
+                warn!("Failed to map line: {}", e);
+                Shape::Line(line.clone())
+            }),
+        Shape::Polyline(polyline) => Polyline::from_points(polyline.points().iter().map(map).collect(), polyline.stroke)
+            .map(|mut p| {
+                p.name = polyline.name.clone();
+                p.show_length = polyline.show_length;
+                Shape::Polyline(p)
+            })
+            .unwrap_or_else(|e| {
+                warn!("Failed to map polyline: {}", e);
+                Shape::Polyline(polyline.clone())
+            }),
+        Shape::Arrow(arrow) => Arrow::new(map(arrow.start()), map(arrow.end()), arrow.stroke)
+            .map(|mut a| {
+                a.name = arrow.name.clone();
+                Shape::Arrow(a)
+            })
+            .unwrap_or_else(|e| {
+                warn!("Failed to map arrow: {}", e);
+                Shape::Arrow(arrow.clone())
+            }),
+        Shape::Callout(callout) => {
+            let text_box = Rect::from_min_max(map(&callout.text_box().min), map(&callout.text_box().max));
+
+            Callout::new(map(callout.anchor()), text_box, callout.text.clone(), callout.stroke, callout.fill)
+                .map(|mut c| {
+                    c.name = callout.name.clone();
+                    Shape::Callout(c)
+                })
+                .unwrap_or_else(|e| {
+                    warn!("Failed to map callout: {}", e);
+                    Shape::Callout(callout.clone())
+                })
+        }
+    };
+    mapped.set_stroke_style(shape.stroke_style());
+    mapped
+}
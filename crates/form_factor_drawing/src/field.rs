@@ -0,0 +1,226 @@
+//! Field values and validation rules
+//!
+//! Each shape marks a form field. Data entry records the value read from
+//! the form on the shape itself, and the field's [`FieldRule`] says whether
+//! a value is required and what it must look like. Checking a value gives a
+//! [`FieldIssue`] describing what is wrong, suitable for showing next to
+//! the field.
+//!
+//! Some fields only apply to some forms: the spouse's name matters only if
+//! the "married" box is checked. A [`FieldCondition`] names the field that
+//! controls another, and [`field_applies`] evaluates it, so fields that
+//! don't apply are neither asked for nor flagged as empty.
+
+use crate::Shape;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Values that mark a checkbox as checked, compared case-insensitively
+const CHECKED_VALUES: &[&str] = &["x", "✓", "✔", "yes", "y", "true", "checked", "on", "1"];
+
+/// Values that mark a checkbox as unchecked, besides an empty value
+const UNCHECKED_VALUES: &[&str] = &["no", "n", "false", "unchecked", "off", "0"];
+
+/// Kind of value a field holds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, strum::EnumIter, strum::Display)]
+pub enum FieldFormat {
+    /// Any text
+    #[default]
+    Text,
+    /// A number, optionally with a sign, thousands separators, decimals or a currency symbol
+    Number,
+    /// A calendar date as `YYYY-MM-DD` or `MM/DD/YYYY`
+    Date,
+    /// A checkbox: checked as `x`, `yes`, `true` or `1`, unchecked when empty or `no`
+    Checkbox,
+}
+
+/// Requirements on a field's value
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FieldRule {
+    /// Whether the field must have a value
+    pub required: bool,
+    /// Kind of value the field holds
+    pub format: FieldFormat,
+}
+
+/// Problem with a field's value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldIssue {
+    /// Required field has no value
+    Missing,
+    /// Value of a number field is not a number
+    NotANumber,
+    /// Value of a date field is not a valid date
+    NotADate,
+    /// Value of a checkbox field is neither checked nor unchecked
+    NotACheckbox,
+}
+
+impl fmt::Display for FieldIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldIssue::Missing => write!(f, "A value is required"),
+            FieldIssue::NotANumber => write!(f, "Expected a number"),
+            FieldIssue::NotADate => write!(f, "Expected a date as YYYY-MM-DD or MM/DD/YYYY"),
+            FieldIssue::NotACheckbox => write!(f, "Expected x or yes for checked, blank or no for unchecked"),
+        }
+    }
+}
+
+impl FieldRule {
+    /// Rule for an optional field of the given format
+    pub fn new(format: FieldFormat) -> Self {
+        Self {
+            required: false,
+            format,
+        }
+    }
+
+    /// Require a value
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Check a value against the rule
+    ///
+    /// Surrounding whitespace is ignored. Empty values only fail required fields.
+    pub fn check(&self, value: &str) -> Result<(), FieldIssue> {
+        let value = value.trim();
+        if value.is_empty() {
+            return if self.required {
+                Err(FieldIssue::Missing)
+            } else {
+                Ok(())
+            };
+        }
+
+        match self.format {
+            FieldFormat::Text => Ok(()),
+            FieldFormat::Number if parse_number(value).is_some() => Ok(()),
+            FieldFormat::Number => Err(FieldIssue::NotANumber),
+            FieldFormat::Date if parse_date(value).is_some() => Ok(()),
+            FieldFormat::Date => Err(FieldIssue::NotADate),
+            FieldFormat::Checkbox if is_checked(value) || is_unchecked(value) => Ok(()),
+            FieldFormat::Checkbox => Err(FieldIssue::NotACheckbox),
+        }
+    }
+}
+
+/// When a field applies, in terms of the value of another field
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FieldCondition {
+    /// The named field is a checked checkbox
+    Checked(String),
+    /// The named field has any value
+    Filled(String),
+    /// The named field has this value, ignoring case and surrounding whitespace
+    Equals {
+        /// Name of the controlling field
+        field: String,
+        /// Value that makes the field apply
+        value: String,
+    },
+}
+
+impl FieldCondition {
+    /// Name of the field the condition depends on
+    pub fn field(&self) -> &str {
+        match self {
+            FieldCondition::Checked(field) | FieldCondition::Filled(field) => field,
+            FieldCondition::Equals { field, .. } => field,
+        }
+    }
+
+    /// Whether the controlling field's value meets the condition
+    pub fn is_met_by(&self, value: &str) -> bool {
+        match self {
+            FieldCondition::Checked(_) => is_checked(value),
+            FieldCondition::Filled(_) => !value.trim().is_empty(),
+            FieldCondition::Equals { value: expected, .. } => value.trim().eq_ignore_ascii_case(expected.trim()),
+        }
+    }
+}
+
+impl fmt::Display for FieldCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldCondition::Checked(field) => write!(f, "when {} is checked", field),
+            FieldCondition::Filled(field) => write!(f, "when {} is filled", field),
+            FieldCondition::Equals { field, value } => write!(f, "when {} is \"{}\"", field, value),
+        }
+    }
+}
+
+/// Whether a checkbox value means checked
+pub fn is_checked(value: &str) -> bool {
+    let value = value.trim();
+    CHECKED_VALUES.iter().any(|checked| value.eq_ignore_ascii_case(checked))
+}
+
+/// Whether a checkbox value means unchecked
+fn is_unchecked(value: &str) -> bool {
+    let value = value.trim();
+    value.is_empty() || UNCHECKED_VALUES.iter().any(|unchecked| value.eq_ignore_ascii_case(unchecked))
+}
+
+/// Whether the field at `idx` applies, given the values of all `fields`
+///
+/// Fields without a condition always apply. A conditional field applies
+/// when the field it names applies itself and its value meets the
+/// condition. Conditions naming a missing field, or looping back on
+/// themselves, don't apply.
+pub fn field_applies(fields: &[Shape], idx: usize) -> bool {
+    let mut current = idx;
+    // Each step follows one condition, so a longer chain must contain a loop
+    for _ in 0..=fields.len() {
+        let Some(condition) = fields.get(current).and_then(|field| field.condition()) else {
+            return fields.get(current).is_some();
+        };
+        let Some(controller) = fields.iter().position(|field| field.name() == condition.field()) else {
+            return false;
+        };
+        if !condition.is_met_by(fields[controller].value()) {
+            return false;
+        }
+        current = controller;
+    }
+    false
+}
+
+/// Parse a number written on a form, e.g. `-1,234.50` or `$12`
+fn parse_number(value: &str) -> Option<f64> {
+    let cleaned: String = value
+        .trim_start_matches(['$', '€', '£'])
+        .chars()
+        .filter(|c| *c != ',')
+        .collect();
+    cleaned.parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+/// Parse `YYYY-MM-DD` or `MM/DD/YYYY` into year, month and day
+fn parse_date(value: &str) -> Option<(u32, u32, u32)> {
+    let parts: Vec<&str> = if value.contains('-') {
+        value.split('-').collect()
+    } else {
+        value.split('/').collect()
+    };
+    let numbers: Vec<u32> = parts.iter().map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    let (year, month, day) = match (value.contains('-'), numbers.as_slice()) {
+        (true, [year, month, day]) if parts[0].len() == 4 => (*year, *month, *day),
+        (false, [month, day, year]) if parts[2].len() == 4 => (*year, *month, *day),
+        _ => return None,
+    };
+
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return None,
+    };
+    (1..=days_in_month).contains(&day).then_some((year, month, day))
+}
@@ -0,0 +1,123 @@
+//! Field groups and repeating sections
+//!
+//! A [`FieldGroup`] names fields that belong together, such as the lines of
+//! an address. A group with a [`RepeatingSection`] is a block of fields that
+//! repeats down the form once per item, like the description, quantity and
+//! amount columns of an invoice. The template declares only the first row;
+//! each filled form adds and removes rows to match its own number of items,
+//! instead of the template declaring a fixed number of identical fields.
+//!
+//! Rows after the first are copies of the first row's fields, moved by the
+//! section's pitch and named like grid repeats: row 3 of `amount` is
+//! `amount 3`.
+
+use egui::Vec2;
+use serde::{Deserialize, Serialize};
+
+/// Name of a field in a row of a repeating section
+///
+/// Rows are numbered from 1; the first row holds the fields as named in
+/// the group.
+pub fn row_field_name(field: &str, row: usize) -> String {
+    if row <= 1 {
+        field.to_string()
+    } else {
+        format!("{} {}", field, row)
+    }
+}
+
+/// How a group's fields repeat down the form
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RepeatingSection {
+    /// Displacement from one row to the next, in canvas units
+    pub pitch: Vec2,
+    /// Rows every form has, including the first
+    #[serde(default = "default_min_rows")]
+    pub min_rows: usize,
+    /// Most rows that fit on the form, or `None` without a limit
+    #[serde(default)]
+    pub max_rows: Option<usize>,
+}
+
+fn default_min_rows() -> usize {
+    1
+}
+
+impl RepeatingSection {
+    /// Section with rows `pitch` apart, at least one row and no limit
+    pub fn new(pitch: Vec2) -> Self {
+        Self {
+            pitch,
+            min_rows: 1,
+            max_rows: None,
+        }
+    }
+
+    /// Require at least `rows` rows on every form
+    pub fn with_min_rows(mut self, rows: usize) -> Self {
+        self.min_rows = rows.max(1);
+        self
+    }
+
+    /// Allow at most `rows` rows
+    pub fn with_max_rows(mut self, rows: usize) -> Self {
+        self.max_rows = Some(rows.max(1));
+        self
+    }
+
+    /// Clamp a row count to the section's limits
+    pub fn clamp_rows(&self, rows: usize) -> usize {
+        let rows = rows.max(self.min_rows).max(1);
+        self.max_rows.map_or(rows, |max| rows.min(max.max(self.min_rows)))
+    }
+}
+
+/// Named set of fields that belong together
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FieldGroup {
+    /// Group name, such as `line_items`
+    pub name: String,
+    /// Names of the fields in the group, or in the first row of a repeating section
+    pub fields: Vec<String>,
+    /// How the fields repeat, or `None` for a group that appears once
+    #[serde(default)]
+    pub repeat: Option<RepeatingSection>,
+}
+
+impl FieldGroup {
+    /// Group of fields that appears once
+    pub fn new(name: impl Into<String>, fields: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            name: name.into(),
+            fields: fields.into_iter().map(Into::into).collect(),
+            repeat: None,
+        }
+    }
+
+    /// Make the group a repeating section
+    pub fn repeating(mut self, section: RepeatingSection) -> Self {
+        self.repeat = Some(section);
+        self
+    }
+
+    /// Whether the group is a repeating section
+    pub fn is_repeating(&self) -> bool {
+        self.repeat.is_some()
+    }
+
+    /// Row and group field of a field name, if the field belongs to the group
+    ///
+    /// Only a repeating section has rows after the first.
+    pub fn locate(&self, name: &str) -> Option<(usize, &str)> {
+        self.fields.iter().find_map(|field| {
+            if name == field {
+                return Some((1, field.as_str()));
+            }
+            if !self.is_repeating() {
+                return None;
+            }
+            let row = name.strip_prefix(field.as_str())?.strip_prefix(' ')?.parse::<usize>().ok()?;
+            (row > 1).then_some((row, field.as_str()))
+        })
+    }
+}
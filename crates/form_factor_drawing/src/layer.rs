@@ -2,13 +2,18 @@
 //!
 //! Layers are rendered in a fixed z-order from bottom to top:
 //! 1. Canvas (background form image) - bottom
-//! 2. Detections (automatically detected regions)
-//! 3. Shapes (user-drawn annotations)
-//! 4. Grid (alignment grid overlay) - top
+//! 2. Highlights (highlighter strokes over the form image)
+//! 3. Detections (automatically detected regions)
+//! 4. Shapes (user-drawn annotations)
+//! 5. Grid (alignment grid overlay) - top
+//!
+//! Project files saved before a layer existed load with that layer in its
+//! default state.
 
 use derive_getters::Getters;
 use enum_map::{Enum, EnumMap};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
 use strum::IntoEnumIterator;
 
@@ -32,6 +37,8 @@ use strum::IntoEnumIterator;
 pub enum LayerType {
     /// The canvas layer (background form image) - rendered first (bottom)
     Canvas,
+    /// The highlights layer (highlighter strokes over the form image)
+    Highlights,
     /// The detections layer (automatically detected regions)
     Detections,
     /// The shapes layer (user-drawn annotations)
@@ -44,6 +51,7 @@ impl fmt::Display for LayerType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             LayerType::Canvas => write!(f, "Canvas"),
+            LayerType::Highlights => write!(f, "Highlights"),
             LayerType::Detections => write!(f, "Detections"),
             LayerType::Shapes => write!(f, "Shapes"),
             LayerType::Grid => write!(f, "Grid"),
@@ -138,14 +146,29 @@ impl std::error::Error for LayerError {}
 /// - Type-safe access via EnumMap
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LayerManager {
+    #[serde(deserialize_with = "deserialize_layers")]
     layers: EnumMap<LayerType, Layer>,
 }
 
+/// Read the saved layers, keeping the defaults for layers missing from older projects
+fn deserialize_layers<'de, D>(deserializer: D) -> Result<EnumMap<LayerType, Layer>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let saved = BTreeMap::<LayerType, Layer>::deserialize(deserializer)?;
+    let mut layers = LayerManager::new().layers;
+    for (layer_type, layer) in saved {
+        layers[layer_type] = layer;
+    }
+    Ok(layers)
+}
+
 impl LayerManager {
     /// Create a new layer manager with default layers
     ///
     /// Default state:
     /// - Canvas: visible, unlocked
+    /// - Highlights: visible, unlocked
     /// - Detections: visible, unlocked
     /// - Shapes: visible, unlocked
     /// - Grid: hidden, unlocked
@@ -153,6 +176,7 @@ impl LayerManager {
         Self {
             layers: enum_map::enum_map! {
                 LayerType::Canvas => Layer::new("Canvas", LayerType::Canvas),
+                LayerType::Highlights => Layer::new("Highlights", LayerType::Highlights),
                 LayerType::Detections => Layer::new("Detections", LayerType::Detections),
                 LayerType::Shapes => Layer::new("Shapes", LayerType::Shapes),
                 LayerType::Grid => Layer::new_hidden("Grid", LayerType::Grid),
@@ -223,7 +247,7 @@ impl LayerManager {
         Ok(())
     }
 
-    /// Get the number of layers (always 4)
+    /// Get the number of layers (always 5)
     pub fn len(&self) -> usize {
         self.layers.len()
     }
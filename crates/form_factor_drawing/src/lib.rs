@@ -6,14 +6,76 @@
 #![warn(missing_docs)]
 #![forbid(unsafe_code)]
 
+mod calibration;
 mod canvas;
+mod collab;
+mod comments;
+mod coords;
+mod field;
+mod field_group;
 mod layer;
+mod naming;
+mod ocr_sample;
 mod recent_projects;
+mod redaction;
+mod session_stats;
+mod settings;
 mod shape;
+mod simulator;
+mod stamp;
+mod store;
+mod style_preset;
+mod taxonomy;
 mod tool;
+mod utc;
+mod workflow;
 
-pub use canvas::{CanvasError, CanvasErrorKind, DetectionSubtype, DrawingCanvas};
+pub use calibration::{
+    CALIBRATION_BAND_WIDTH, CalibrationBand, CalibrationCurve, ConfidenceCalibration, MIN_CALIBRATION_SAMPLES,
+    TemplateCalibration,
+};
+pub use canvas::{
+    AUTOSAVE_INTERVAL_SECS, BINARY_PROJECT_MAGIC, BatchReview, COLLAB_SYNC_INTERVAL_SECS, CanvasAction, CanvasCommand, CanvasError, CanvasErrorKind, CanvasPanel, CanvasSnapshot,
+    DEFAULT_REVIEW_THRESHOLD, DEFAULT_TEXTURE_BUDGET_MB, DETECTION_FLASH_SECONDS, DROP_IMAGE_EXTENSIONS, DROP_PROJECT_EXTENSIONS, DrawingCanvas, DropKind, ENCRYPTED_PROJECT_MAGIC,
+    DroppedFile, ExportFormat, FieldChange, Guide, GuideOrientation, HIGHLIGHT_COLOR, HIGHLIGHT_WIDTH, Highlight, HoverTooltip, ImageChange, InstanceSummary, JournalSave, LegendEntry, MAX_EXPORT_DIMENSION,
+    MAX_BOOKMARK_SHORTCUTS, MAX_JOURNAL_RECORDS, MAX_PENDING_CHANGES, MAX_REPEAT_COPIES, MAX_SELECTION_HISTORY, MIN_PREVIEW_TEXT_SCALE, MIN_SPLIT_SIZE, OcrRecord, OcrWord, PROJECT_FORMAT_VERSION, PageMatch, PageOrientation, PageSize, PasteTarget, PreviewValue, PrintOptions,
+    ProjectDiff, ProjectEncoding, ProjectTemplate, Provenance, REFILL_INK, ROTATE_CURSOR, ReadingDirection, RefillValue, RenameMatch, RenameQuery, RepeatGrid, ReportFormat, ReviewAction,
+    ReviewInstance, ReviewItem, ReviewReason, ReviewStats, SaveConflict, ScreenshotCapture, ScreenshotStage, SelectionHistory, Severity, ShapeChange, SourceChannel, SplitDirection,
+    TemplateAnchor, TemplateFilter, TemplateLoadProgress, TemplatePage, TemplateSortOrder, TextExportOptions, TextFormat, TextureCache, ValidationFinding, ValidationReport, ViewBookmark, ZSTD_MAGIC, clear_detection_cache, clipboard_image, compress, copy_image_to_clipboard,
+    decrypt, detection_cache_dir, encrypt, is_compressed, is_encrypted, journal_path, pasted_images_dir, project_templates_dir, sample_value, screenshots_dir,
+    template_departments, template_languages, template_tags,
+};
+pub use collab::{
+    CollabError, CollabErrorKind, CollabSession, InMemorySyncClient, InMemorySyncServer, OpId,
+    Operation, OperationLog, PRESENCE_TIMEOUT_SECS, Presence, ShapeEdit, StoreSyncTransport, SyncTransport,
+};
+pub use comments::{Comment, CommentTarget, CommentThread};
+pub use coords::{CanvasPos, CoordinateMapper, ImageFit, ImagePos, ScreenPos};
+pub use field::{FieldCondition, FieldFormat, FieldIssue, FieldRule, field_applies, is_checked};
+pub use field_group::{FieldGroup, RepeatingSection, row_field_name};
 pub use layer::{Layer, LayerError, LayerManager, LayerType};
-pub use recent_projects::RecentProjects;
-pub use shape::{Circle, CircleBuilder, PolygonShape, Rectangle, Shape, ShapeError, ShapeErrorKind};
-pub use tool::ToolMode;
+pub use naming::{NameParts, NamingScheme};
+pub use ocr_sample::OcrSample;
+pub use recent_projects::{MAX_RECENT_PROJECTS, RecentProjects, config_dir};
+pub use redaction::{PiiKind, RedactionReason, RedactionRegion, detect_pii, redact_image};
+pub use session_stats::{IDLE_TIMEOUT_SECS, SessionStats};
+pub use settings::{
+    ACTION_OPEN_SETTINGS, ACTION_TOGGLE_FRAME_TIMING, ACTION_TOGGLE_LOG, AppearanceSettings, CanvasSettings, CompressionSettings, DetectionColors, DetectionKind,
+    DetectionSettings, OcrSettings, Settings, ShortcutSettings, Theme,
+};
+pub use shape::{
+    Arrow, Callout, Circle, CircleBuilder, Ellipse, Line, PolygonShape, Polyline, Rectangle, Shape, ShapeError,
+    ShapeErrorKind, StrokeStyle, length_label,
+};
+pub use simulator::CanvasSimulator;
+pub use stamp::{STAMP_HEIGHT, Stamp, StampLibrary, StampSource, StampSymbol};
+pub use store::{
+    FileStore, HttpClient, HttpRequest, HttpResponse, ProjectStore, S3Credentials, S3Settings, S3Store, StorageSettings,
+    StoreError, StoreErrorKind, default_store_dir,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use store::UreqClient;
+pub use style_preset::{StylePreset, StylePresets};
+pub use taxonomy::{DetectionSubtype, DetectionTaxonomy};
+pub use tool::{CustomTool, SharedTool, ToolInfo, ToolInput, ToolMode, ToolRegistry};
+pub use workflow::{PageTask, TaskStatus, Workflow};
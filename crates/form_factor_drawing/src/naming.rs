@@ -0,0 +1,118 @@
+//! Automatic field naming
+//!
+//! A naming scheme turns a pattern such as `field_{n}` or
+//! `line_{row}_amount` into names for new shapes, so tables and repeated
+//! boxes get consistent, ordered field names without typing each one.
+//!
+//! Patterns may contain these placeholders:
+//! - `{n}`: running number of the field
+//! - `{row}`, `{col}`: grid position when repeating a shape in a grid
+//! - `{name}`: name of the shape being repeated
+//!
+//! Numbers start at [`NamingScheme::start`] and are zero-padded to
+//! [`NamingScheme::width`] digits. Any other text is copied as-is.
+
+use serde::{Deserialize, Serialize};
+
+/// Values substituted into a naming pattern
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NameParts<'a> {
+    /// Zero-based running number
+    pub index: usize,
+    /// Zero-based grid row
+    pub row: usize,
+    /// Zero-based grid column
+    pub column: usize,
+    /// Name of the shape being copied
+    pub name: &'a str,
+}
+
+/// Pattern for generating field names
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NamingScheme {
+    /// Name pattern; empty disables automatic naming
+    pub pattern: String,
+    /// First number used for `{n}`, `{row}` and `{col}`
+    pub start: usize,
+    /// Minimum digits for numbers, padded with zeros
+    pub width: usize,
+}
+
+impl Default for NamingScheme {
+    fn default() -> Self {
+        Self {
+            pattern: String::new(),
+            start: 1,
+            width: 1,
+        }
+    }
+}
+
+impl NamingScheme {
+    /// Create a scheme numbering from 1 without padding
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the first number
+    pub fn with_start(mut self, start: usize) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Set the minimum number of digits
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Whether the scheme generates names
+    pub fn is_enabled(&self) -> bool {
+        !self.pattern.trim().is_empty()
+    }
+
+    /// Whether the pattern distinguishes grid rows or columns
+    pub fn uses_grid(&self) -> bool {
+        self.pattern.contains("{row}") || self.pattern.contains("{col}")
+    }
+
+    /// Expand the pattern
+    pub fn format(&self, parts: &NameParts<'_>) -> String {
+        let number = |value: usize| format!("{:0width$}", value + self.start, width = self.width);
+        let mut out = String::with_capacity(self.pattern.len());
+        let mut rest = self.pattern.as_str();
+        while let Some(open) = rest.find('{') {
+            out.push_str(&rest[..open]);
+            let tail = &rest[open..];
+            let (value, len) = if tail.starts_with("{n}") {
+                (number(parts.index), 3)
+            } else if tail.starts_with("{row}") {
+                (number(parts.row), 5)
+            } else if tail.starts_with("{col}") {
+                (number(parts.column), 5)
+            } else if tail.starts_with("{name}") {
+                (parts.name.to_string(), 6)
+            } else {
+                ("{".to_string(), 1)
+            };
+            out.push_str(&value);
+            rest = &tail[len..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// Lowest-numbered name not already taken
+    ///
+    /// Patterns without `{n}` always produce the same name, taken or not.
+    pub fn next_name(&self, taken: &[&str]) -> String {
+        (0..=taken.len())
+            .map(|index| self.format(&NameParts { index, ..NameParts::default() }))
+            .find(|name| !self.pattern.contains("{n}") || !taken.contains(&name.as_str()))
+            .unwrap_or_default()
+    }
+}
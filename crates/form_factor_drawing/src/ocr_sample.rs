@@ -0,0 +1,32 @@
+//! OCR readings paired with the values operators kept
+//!
+//! Each time a person settles on the value of something OCR read, the canvas
+//! queues an [`OcrSample`]: when data entry leaves a field that still held
+//! an extracted value, when batch review accepts or corrects a field, and
+//! when OCR text is corrected on the canvas. The application passes the
+//! samples on as `ocr`/`correction` events, so OCR accuracy and confidence
+//! calibration can be tracked.
+
+use serde::{Deserialize, Serialize};
+
+/// One OCR reading and the value the operator kept
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OcrSample {
+    /// Name of the field the text was read from
+    pub field: String,
+    /// Text proposed by OCR
+    pub proposed: String,
+    /// Final text after operator review
+    pub corrected: String,
+    /// OCR confidence (0-100)
+    pub confidence: f32,
+}
+
+impl OcrSample {
+    /// Whether the operator changed the OCR value
+    ///
+    /// Leading and trailing whitespace is ignored.
+    pub fn was_corrected(&self) -> bool {
+        self.proposed.trim() != self.corrected.trim()
+    }
+}
@@ -2,24 +2,49 @@
 //!
 //! Maintains a list of recently opened project files with automatic
 //! persistence to platform-specific config directories.
+//!
+//! Favorite projects can be pinned so they are never evicted, and entries
+//! can be grouped under a label such as the form type they were made for.
+//! Unpinned entries are capped at [`RecentProjects::max_entries`], and
+//! entries whose files were moved or deleted can be pruned.
 
 use form_factor_core::{IoError, IoOperation};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use tracing::{debug, instrument, warn};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, instrument, warn};
 
-/// Maximum number of recent projects to track
-const MAX_RECENT_PROJECTS: usize = 10;
+/// Default maximum number of unpinned recent projects to track
+pub const MAX_RECENT_PROJECTS: usize = 10;
 
 /// Application name for config directory
 const APP_NAME: &str = "form_factor";
 
 /// Recent projects list
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct RecentProjects {
     /// List of recent project paths (most recent first)
     #[serde(default)]
     projects: Vec<PathBuf>,
+    /// Pinned project paths, in the order they were pinned
+    #[serde(default)]
+    pinned: Vec<PathBuf>,
+    /// Group label of each grouped project, e.g. its form type
+    #[serde(default)]
+    groups: BTreeMap<PathBuf, String>,
+    /// Maximum number of unpinned projects to keep
+    #[serde(default = "default_max_entries")]
+    max_entries: usize,
+}
+
+fn default_max_entries() -> usize {
+    MAX_RECENT_PROJECTS
+}
+
+impl Default for RecentProjects {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl RecentProjects {
@@ -27,14 +52,40 @@ impl RecentProjects {
     pub fn new() -> Self {
         Self {
             projects: Vec::new(),
+            pinned: Vec::new(),
+            groups: BTreeMap::new(),
+            max_entries: MAX_RECENT_PROJECTS,
         }
     }
 
     /// Get the list of recent project paths (most recent first)
+    ///
+    /// Includes pinned projects.
     pub fn projects(&self) -> &[PathBuf] {
         &self.projects
     }
 
+    /// Pinned project paths, in the order they were pinned
+    pub fn pinned(&self) -> &[PathBuf] {
+        &self.pinned
+    }
+
+    /// Unpinned project paths (most recent first)
+    pub fn unpinned(&self) -> impl Iterator<Item = &PathBuf> {
+        self.projects.iter().filter(|path| !self.is_pinned(path))
+    }
+
+    /// Maximum number of unpinned projects kept
+    pub fn max_entries(&self) -> usize {
+        self.max_entries
+    }
+
+    /// Set the maximum number of unpinned projects kept, evicting the oldest beyond it
+    pub fn set_max_entries(&mut self, max_entries: usize) {
+        self.max_entries = max_entries;
+        self.evict();
+    }
+
     /// Get the number of recent projects
     pub fn len(&self) -> usize {
         self.projects.len()
@@ -48,7 +99,8 @@ impl RecentProjects {
     /// Add a project to the recent list (moves to front if already exists)
     ///
     /// If the path is already in the list, it is moved to the front.
-    /// The list is automatically truncated to maintain at most 10 entries.
+    /// The oldest unpinned entries are evicted to keep at most
+    /// [`max_entries`](Self::max_entries) of them.
     ///
     /// # Examples
     ///
@@ -70,8 +122,18 @@ impl RecentProjects {
         // Add to front
         self.projects.insert(0, path);
 
-        // Keep only MAX_RECENT_PROJECTS
-        self.projects.truncate(MAX_RECENT_PROJECTS);
+        self.evict();
+    }
+
+    /// Remove a project from the list, including its pin and group
+    ///
+    /// Returns false if the project wasn't in the list.
+    pub fn remove(&mut self, path: &Path) -> bool {
+        let len = self.projects.len();
+        self.projects.retain(|p| p != path);
+        self.pinned.retain(|p| p != path);
+        self.groups.remove(path);
+        self.projects.len() != len
     }
 
     /// Get the most recent project path
@@ -79,6 +141,112 @@ impl RecentProjects {
         self.projects.first()
     }
 
+    /// Whether a project is pinned
+    pub fn is_pinned(&self, path: &Path) -> bool {
+        self.pinned.iter().any(|p| p == path)
+    }
+
+    /// Pin a project so it is never evicted
+    ///
+    /// Returns false if the project isn't in the list.
+    pub fn pin(&mut self, path: &Path) -> bool {
+        if !self.projects.iter().any(|p| p == path) {
+            return false;
+        }
+        if !self.is_pinned(path) {
+            self.pinned.push(path.to_path_buf());
+        }
+        true
+    }
+
+    /// Unpin a project, evicting the oldest unpinned entries if there are now too many
+    pub fn unpin(&mut self, path: &Path) {
+        self.pinned.retain(|p| p != path);
+        self.evict();
+    }
+
+    /// Group label of a project
+    pub fn group(&self, path: &Path) -> Option<&str> {
+        self.groups.get(path).map(String::as_str)
+    }
+
+    /// Put a project in a group, or take it out of its group with `None`
+    ///
+    /// Blank labels count as no group. Returns false if the project isn't in the list.
+    pub fn set_group(&mut self, path: &Path, group: Option<String>) -> bool {
+        if !self.projects.iter().any(|p| p == path) {
+            return false;
+        }
+        match group.map(|group| group.trim().to_string()).filter(|group| !group.is_empty()) {
+            Some(group) => self.groups.insert(path.to_path_buf(), group),
+            None => self.groups.remove(path),
+        };
+        true
+    }
+
+    /// Distinct group labels, sorted
+    pub fn group_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.groups.values().map(String::as_str).collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Unpinned projects by group
+    ///
+    /// Groups are ordered by their most recent project, with ungrouped
+    /// projects (`None`) last. Projects within a group are most recent first.
+    pub fn grouped(&self) -> Vec<(Option<&str>, Vec<&PathBuf>)> {
+        let mut grouped: Vec<(Option<&str>, Vec<&PathBuf>)> = Vec::new();
+        let mut ungrouped = Vec::new();
+        for path in self.unpinned() {
+            match self.group(path) {
+                Some(group) => match grouped.iter_mut().find(|(name, _)| *name == Some(group)) {
+                    Some((_, paths)) => paths.push(path),
+                    None => grouped.push((Some(group), vec![path])),
+                },
+                None => ungrouped.push(path),
+            }
+        }
+        if !ungrouped.is_empty() {
+            grouped.push((None, ungrouped));
+        }
+        grouped
+    }
+
+    /// Remove projects whose files no longer exist
+    ///
+    /// Pinned projects are removed too. Returns the removed paths.
+    #[instrument(skip(self), fields(count = self.projects.len()))]
+    pub fn prune_missing(&mut self) -> Vec<PathBuf> {
+        let missing: Vec<PathBuf> = self.projects.iter().filter(|path| !path.exists()).cloned().collect();
+        for path in &missing {
+            self.remove(path);
+        }
+        if !missing.is_empty() {
+            info!(removed = missing.len(), "Pruned missing recent projects");
+        }
+        missing
+    }
+
+    /// Drop the oldest unpinned projects beyond the maximum
+    fn evict(&mut self) {
+        let mut unpinned = 0;
+        let mut evicted = Vec::new();
+        for path in &self.projects {
+            if self.pinned.contains(path) {
+                continue;
+            }
+            unpinned += 1;
+            if unpinned > self.max_entries {
+                evicted.push(path.clone());
+            }
+        }
+        for path in &evicted {
+            self.remove(path);
+        }
+    }
+
     /// Load recent projects from config file
     ///
     /// Returns a default empty list if the config file doesn't exist or cannot be read.
@@ -160,36 +328,42 @@ impl RecentProjects {
 
     /// Get the config file path
     ///
-    /// Returns a platform-specific path:
-    /// - Linux: `$XDG_CONFIG_HOME/form_factor/recent_projects.json` or `~/.config/form_factor/recent_projects.json`
-    /// - macOS: `~/Library/Application Support/form_factor/recent_projects.json`
-    /// - Windows: `%APPDATA%\form_factor\recent_projects.json`
+    /// Returns `recent_projects.json` inside [`config_dir`].
     fn config_path() -> PathBuf {
-        // Use platform-specific config directory
-        let config_dir = if cfg!(target_os = "linux") {
-            std::env::var("XDG_CONFIG_HOME")
-                .map(PathBuf::from)
-                .unwrap_or_else(|_| {
-                    let mut home = PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| String::from(".")));
-                    home.push(".config");
-                    home
-                })
-        } else if cfg!(target_os = "macos") {
-            let mut home = PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| String::from(".")));
-            home.push("Library");
-            home.push("Application Support");
-            home
-        } else if cfg!(target_os = "windows") {
-            std::env::var("APPDATA")
-                .map(PathBuf::from)
-                .unwrap_or_else(|_| PathBuf::from("."))
-        } else {
-            PathBuf::from(".")
-        };
-
-        let mut path = config_dir;
-        path.push(APP_NAME);
+        let mut path = config_dir();
         path.push("recent_projects.json");
         path
     }
 }
+
+/// Platform-specific configuration directory for the application
+///
+/// - Linux: `$XDG_CONFIG_HOME/form_factor` or `~/.config/form_factor`
+/// - macOS: `~/Library/Application Support/form_factor`
+/// - Windows: `%APPDATA%\form_factor`
+pub fn config_dir() -> PathBuf {
+    let config_dir = if cfg!(target_os = "linux") {
+        std::env::var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                let mut home = PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| String::from(".")));
+                home.push(".config");
+                home
+            })
+    } else if cfg!(target_os = "macos") {
+        let mut home = PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| String::from(".")));
+        home.push("Library");
+        home.push("Application Support");
+        home
+    } else if cfg!(target_os = "windows") {
+        std::env::var("APPDATA")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("."))
+    } else {
+        PathBuf::from(".")
+    };
+
+    let mut path = config_dir;
+    path.push(APP_NAME);
+    path
+}
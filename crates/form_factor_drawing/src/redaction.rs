@@ -0,0 +1,155 @@
+//! PII detection and image redaction
+//!
+//! This module provides regex-based detection of personally identifiable
+//! information (PII) in OCR text, and blacks out regions of a form image
+//! for export. Regions come from two sources:
+//! - Shapes the user has flagged as sensitive
+//! - Detections whose OCR text matched a PII pattern
+
+use egui::Rect;
+use image::{Rgba, RgbaImage};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+use strum::{EnumIter, IntoEnumIterator};
+use tracing::{debug, instrument};
+
+static SSN_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b\d{3}-\d{2}-\d{4}\b").expect("Valid SSN pattern"));
+
+static EMAIL_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b").expect("Valid email pattern")
+});
+
+static PHONE_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:\+?1[-.\s]?)?\(?\b\d{3}\)?[-.\s]?\d{3}[-.\s]\d{4}\b").expect("Valid phone pattern")
+});
+
+static CREDIT_CARD_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:\d{4}[- ]?){3}\d{1,4}\b").expect("Valid credit card pattern")
+});
+
+/// Category of personally identifiable information
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, EnumIter)]
+pub enum PiiKind {
+    /// US Social Security number (e.g. 123-45-6789)
+    Ssn,
+    /// Email address
+    Email,
+    /// North American phone number
+    Phone,
+    /// Payment card number (13-16 digits, optionally grouped)
+    CreditCard,
+}
+
+impl PiiKind {
+    /// Compiled pattern used to recognize this kind of PII
+    fn pattern(&self) -> &'static Regex {
+        match self {
+            PiiKind::Ssn => &SSN_PATTERN,
+            PiiKind::Email => &EMAIL_PATTERN,
+            PiiKind::Phone => &PHONE_PATTERN,
+            PiiKind::CreditCard => &CREDIT_CARD_PATTERN,
+        }
+    }
+
+    /// Check whether the given text contains this kind of PII
+    pub fn is_match(&self, text: &str) -> bool {
+        self.pattern().is_match(text)
+    }
+}
+
+impl std::fmt::Display for PiiKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PiiKind::Ssn => write!(f, "SSN"),
+            PiiKind::Email => write!(f, "Email"),
+            PiiKind::Phone => write!(f, "Phone"),
+            PiiKind::CreditCard => write!(f, "Credit Card"),
+        }
+    }
+}
+
+/// Detect all kinds of PII present in a piece of text
+///
+/// # Example
+///
+/// ```
+/// use form_factor_drawing::{detect_pii, PiiKind};
+///
+/// let kinds = detect_pii("Contact: jane@example.com, SSN 123-45-6789");
+/// assert!(kinds.contains(&PiiKind::Email));
+/// assert!(kinds.contains(&PiiKind::Ssn));
+/// ```
+pub fn detect_pii(text: &str) -> Vec<PiiKind> {
+    PiiKind::iter().filter(|kind| kind.is_match(text)).collect()
+}
+
+/// Why a region is being redacted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedactionReason {
+    /// The user flagged the shape as sensitive
+    SensitiveField,
+    /// OCR text in a detection matched a PII pattern
+    Pii(PiiKind),
+}
+
+impl std::fmt::Display for RedactionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RedactionReason::SensitiveField => write!(f, "Sensitive field"),
+            RedactionReason::Pii(kind) => write!(f, "PII: {}", kind),
+        }
+    }
+}
+
+/// A rectangular region of the form image to black out
+///
+/// Bounds are in image pixel coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RedactionRegion {
+    /// Bounds of the region in image pixel coordinates
+    pub bounds: Rect,
+    /// Why this region is redacted
+    pub reason: RedactionReason,
+}
+
+impl RedactionRegion {
+    /// Create a new redaction region
+    pub fn new(bounds: Rect, reason: RedactionReason) -> Self {
+        Self { bounds, reason }
+    }
+}
+
+/// Black out each region in the image
+///
+/// Regions are clipped to the image bounds. Returns the number of regions
+/// that covered at least one pixel.
+#[instrument(skip(image, regions), fields(width = image.width(), height = image.height(), regions = regions.len()))]
+pub fn redact_image(image: &mut RgbaImage, regions: &[RedactionRegion]) -> usize {
+    let (width, height) = image.dimensions();
+    let black = Rgba([0, 0, 0, 255]);
+    let mut redacted = 0;
+
+    for region in regions {
+        let x_min = region.bounds.min.x.floor().clamp(0.0, width as f32) as u32;
+        let y_min = region.bounds.min.y.floor().clamp(0.0, height as f32) as u32;
+        let x_max = region.bounds.max.x.ceil().clamp(0.0, width as f32) as u32;
+        let y_max = region.bounds.max.y.ceil().clamp(0.0, height as f32) as u32;
+
+        if x_min >= x_max || y_min >= y_max {
+            debug!(?region, "Redaction region lies outside the image, skipping");
+            continue;
+        }
+
+        for y in y_min..y_max {
+            for x in x_min..x_max {
+                image.put_pixel(x, y, black);
+            }
+        }
+        redacted += 1;
+    }
+
+    debug!(redacted, "Applied redactions");
+    redacted
+}
@@ -0,0 +1,108 @@
+//! Annotation effort tracking
+//!
+//! Statistics accumulate across sessions and are saved with the project, so
+//! supervisors can estimate how long each form type takes to annotate.
+
+use serde::{Deserialize, Serialize};
+
+/// Seconds without input after which the annotator is considered idle
+pub const IDLE_TIMEOUT_SECS: f64 = 60.0;
+
+/// Cumulative annotation metrics for a project
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionStats {
+    /// Time spent actively annotating, in seconds
+    #[serde(default)]
+    active_seconds: f64,
+    /// Number of shapes drawn
+    #[serde(default)]
+    shapes_created: u32,
+    /// Number of field values changed in data entry and OCR texts corrected
+    #[serde(default)]
+    fields_corrected: u32,
+
+    // Runtime clock state (not serialized)
+    /// Time of the previous tick
+    #[serde(skip)]
+    last_tick: Option<f64>,
+    /// Time of the most recent input
+    #[serde(skip)]
+    last_input: Option<f64>,
+}
+
+impl SessionStats {
+    /// Create empty statistics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advance the activity clock
+    ///
+    /// `now` is a monotonic time in seconds (e.g. egui's input time) and
+    /// `had_input` reports whether the user interacted since the last tick.
+    /// Time counts as active until [`IDLE_TIMEOUT_SECS`] after the last input.
+    pub fn tick(&mut self, now: f64, had_input: bool) {
+        if let (Some(prev), Some(last_input)) = (self.last_tick, self.last_input) {
+            let active_until = now.min(last_input + IDLE_TIMEOUT_SECS);
+            if active_until > prev {
+                self.active_seconds += active_until - prev;
+            }
+        }
+        if had_input {
+            self.last_input = Some(now);
+        }
+        self.last_tick = Some(now);
+    }
+
+    /// Record that a shape was drawn
+    pub fn record_shape_created(&mut self) {
+        self.shapes_created += 1;
+    }
+
+    /// Record that a field value or OCR text was corrected
+    pub fn record_field_corrected(&mut self) {
+        self.fields_corrected += 1;
+    }
+
+    /// Active annotation time in seconds
+    pub fn active_seconds(&self) -> f64 {
+        self.active_seconds
+    }
+
+    /// Number of shapes drawn
+    pub fn shapes_created(&self) -> u32 {
+        self.shapes_created
+    }
+
+    /// Number of field values and OCR texts corrected
+    pub fn fields_corrected(&self) -> u32 {
+        self.fields_corrected
+    }
+
+    /// Shapes drawn per active hour, or `None` before any active time
+    pub fn shapes_per_hour(&self) -> Option<f64> {
+        self.per_hour(self.shapes_created)
+    }
+
+    /// Corrections per active hour, or `None` before any active time
+    pub fn corrections_per_hour(&self) -> Option<f64> {
+        self.per_hour(self.fields_corrected)
+    }
+
+    /// Statistics of two sessions that started from `base`: `theirs` plus what `self` added since
+    ///
+    /// Used when two operators saved the same project, so neither
+    /// operator's effort is lost.
+    pub(crate) fn merged(&self, base: &Self, theirs: &Self) -> Self {
+        Self {
+            active_seconds: theirs.active_seconds + (self.active_seconds - base.active_seconds).max(0.0),
+            shapes_created: theirs.shapes_created + self.shapes_created.saturating_sub(base.shapes_created),
+            fields_corrected: theirs.fields_corrected + self.fields_corrected.saturating_sub(base.fields_corrected),
+            ..self.clone()
+        }
+    }
+
+    fn per_hour(&self, count: u32) -> Option<f64> {
+        (self.active_seconds > 0.0).then(|| count as f64 * 3600.0 / self.active_seconds)
+    }
+}
@@ -0,0 +1,403 @@
+//! Persistent application settings
+//!
+//! User preferences that apply across projects (canvas behavior, detection
+//! and OCR parameters, keyboard shortcuts, appearance) are stored as JSON in
+//! the platform config directory. Every field has a default, so settings
+//! files from older versions load with new options filled in.
+
+use crate::{DEFAULT_TEXTURE_BUDGET_MB, DetectionTaxonomy, NamingScheme, ProjectEncoding, StampLibrary, StorageSettings, StylePresets, config_dir};
+use form_factor_core::{IoError, IoOperation};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, instrument, warn};
+
+/// Shortcut action that toggles the log viewer
+pub const ACTION_TOGGLE_LOG: &str = "toggle_log";
+
+/// Shortcut action that opens the settings overlay
+pub const ACTION_OPEN_SETTINGS: &str = "open_settings";
+
+/// Shortcut action that toggles the frame timing overlay
+pub const ACTION_TOGGLE_FRAME_TIMING: &str = "toggle_frame_timing";
+
+/// All user settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Settings {
+    /// Canvas navigation and grid
+    #[serde(default)]
+    pub canvas: CanvasSettings,
+    /// Text and logo detection
+    #[serde(default)]
+    pub detection: DetectionSettings,
+    /// Text recognition
+    #[serde(default)]
+    pub ocr: OcrSettings,
+    /// Keyboard shortcuts
+    #[serde(default)]
+    pub shortcuts: ShortcutSettings,
+    /// Theme and scaling
+    #[serde(default)]
+    pub appearance: AppearanceSettings,
+    /// Where projects and instances are stored
+    #[serde(default)]
+    pub storage: StorageSettings,
+}
+
+/// Canvas navigation and grid settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CanvasSettings {
+    /// Multiplier applied to scroll and keyboard zoom steps
+    pub zoom_sensitivity: f32,
+    /// Distance between vertical grid lines
+    pub grid_spacing_horizontal: f32,
+    /// Distance between horizontal grid lines
+    pub grid_spacing_vertical: f32,
+    /// Whether rulers are shown along the canvas edges
+    pub show_rulers: bool,
+    /// Whether shapes snap to guide lines
+    pub snap_to_guides: bool,
+    /// Names given to newly drawn shapes
+    pub field_naming: NamingScheme,
+    /// Symbols the Stamp tool can place
+    pub stamps: StampLibrary,
+    /// Named styles new shapes can be drawn with
+    pub style_presets: StylePresets,
+    /// Encoding projects are saved in
+    pub project_encoding: ProjectEncoding,
+    /// Compression of saved projects and detection caches
+    pub compression: CompressionSettings,
+    /// Memory canvas textures may hold before the least recently used are dropped, in megabytes
+    pub texture_budget_mb: usize,
+}
+
+impl Default for CanvasSettings {
+    fn default() -> Self {
+        Self {
+            zoom_sensitivity: 5.0,
+            grid_spacing_horizontal: 10.0,
+            grid_spacing_vertical: 10.0,
+            show_rulers: true,
+            snap_to_guides: true,
+            field_naming: NamingScheme::default(),
+            stamps: StampLibrary::default(),
+            style_presets: StylePresets::default(),
+            project_encoding: ProjectEncoding::default(),
+            compression: CompressionSettings::default(),
+            texture_budget_mb: DEFAULT_TEXTURE_BUDGET_MB,
+        }
+    }
+}
+
+/// zstd compression of saved projects and detection caches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompressionSettings {
+    /// Whether project files and cached detections are compressed
+    pub enabled: bool,
+    /// zstd level, from 1 (fastest) to 19 (smallest)
+    pub level: i32,
+}
+
+impl Default for CompressionSettings {
+    fn default() -> Self {
+        Self { enabled: false, level: 3 }
+    }
+}
+
+/// Text and logo detection settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DetectionSettings {
+    /// Minimum confidence for text regions (0.0-1.0)
+    pub text_confidence: f32,
+    /// Path to the text detection model
+    pub text_model_path: String,
+    /// Minimum confidence for logo matches (0.0-1.0)
+    pub logo_confidence: f64,
+    /// Template scales tried during logo matching
+    pub logo_scales: Vec<f64>,
+    /// Directory containing logo template images
+    pub logos_dir: String,
+    /// Colors used to draw each kind of detection
+    pub colors: DetectionColors,
+    /// Subtypes detections can be tagged with
+    pub taxonomy: DetectionTaxonomy,
+    /// Whether detection results are cached per image and reused by identical passes
+    pub cache_results: bool,
+    /// Whether newly added detections flash briefly
+    pub flash_new: bool,
+    /// Whether the view zooms to newly added detections
+    pub zoom_to_new: bool,
+}
+
+impl Default for DetectionSettings {
+    fn default() -> Self {
+        Self {
+            text_confidence: 0.5,
+            text_model_path: "models/DB_TD500_resnet50.onnx".to_string(),
+            logo_confidence: 0.5,
+            logo_scales: vec![0.1, 0.15, 0.2, 0.3, 0.4, 0.5, 0.65, 0.75, 1.0, 1.25, 1.5, 2.0],
+            logos_dir: "logos".to_string(),
+            colors: DetectionColors::default(),
+            taxonomy: DetectionTaxonomy::default(),
+            cache_results: true,
+            flash_new: true,
+            zoom_to_new: false,
+        }
+    }
+}
+
+/// Kind of detection, each drawn in its own color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, strum::EnumIter, strum::Display)]
+pub enum DetectionKind {
+    /// Text region found by text detection
+    Text,
+    /// Logo matched against a template
+    Logo,
+    /// Detection whose text has been read by OCR
+    #[strum(to_string = "OCR")]
+    Ocr,
+}
+
+/// Colors used to draw detections, by kind
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DetectionColors {
+    /// Text regions
+    pub text: egui::Color32,
+    /// Logo matches
+    pub logo: egui::Color32,
+    /// Detections read by OCR
+    pub ocr: egui::Color32,
+}
+
+impl DetectionColors {
+    /// Color for a kind of detection
+    pub fn color(&self, kind: DetectionKind) -> egui::Color32 {
+        match kind {
+            DetectionKind::Text => self.text,
+            DetectionKind::Logo => self.logo,
+            DetectionKind::Ocr => self.ocr,
+        }
+    }
+
+    /// Mutable color for a kind of detection, for editing
+    pub fn color_mut(&mut self, kind: DetectionKind) -> &mut egui::Color32 {
+        match kind {
+            DetectionKind::Text => &mut self.text,
+            DetectionKind::Logo => &mut self.logo,
+            DetectionKind::Ocr => &mut self.ocr,
+        }
+    }
+}
+
+impl Default for DetectionColors {
+    fn default() -> Self {
+        Self {
+            text: egui::Color32::from_rgb(255, 165, 0),
+            logo: egui::Color32::from_rgb(30, 144, 255),
+            ocr: egui::Color32::from_rgb(0, 200, 0),
+        }
+    }
+}
+
+/// Text recognition settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OcrSettings {
+    /// Tesseract language code (e.g. "eng", "eng+fra")
+    pub language: String,
+    /// Minimum confidence for accepted text (0-100)
+    pub min_confidence: i32,
+    /// Whether to preprocess images before recognition
+    pub preprocessing: bool,
+}
+
+impl Default for OcrSettings {
+    fn default() -> Self {
+        Self {
+            language: "eng".to_string(),
+            min_confidence: 60,
+            preprocessing: true,
+        }
+    }
+}
+
+/// Keyboard shortcut bindings
+///
+/// Maps action names to key names as understood by [`egui::Key::from_name`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShortcutSettings {
+    /// Key name for each action
+    pub bindings: BTreeMap<String, String>,
+}
+
+impl ShortcutSettings {
+    /// Key bound to an action, if any and valid
+    pub fn key(&self, action: &str) -> Option<egui::Key> {
+        self.bindings.get(action).and_then(|name| egui::Key::from_name(name))
+    }
+
+    /// Actions whose binding is not a recognized key name
+    pub fn invalid_bindings(&self) -> Vec<&str> {
+        self.bindings
+            .iter()
+            .filter(|(_, name)| egui::Key::from_name(name).is_none())
+            .map(|(action, _)| action.as_str())
+            .collect()
+    }
+}
+
+impl Default for ShortcutSettings {
+    fn default() -> Self {
+        let bindings = [
+            (ACTION_TOGGLE_LOG, "F12"),
+            (ACTION_OPEN_SETTINGS, "F10"),
+            (ACTION_TOGGLE_FRAME_TIMING, "F9"),
+        ]
+            .into_iter()
+            .map(|(action, key)| (action.to_string(), key.to_string()))
+            .collect();
+        Self { bindings }
+    }
+}
+
+/// Color theme preference
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, strum::EnumIter, strum::Display)]
+pub enum Theme {
+    /// Follow the operating system
+    #[default]
+    System,
+    /// Light theme
+    Light,
+    /// Dark theme
+    Dark,
+}
+
+impl From<Theme> for egui::ThemePreference {
+    fn from(theme: Theme) -> Self {
+        match theme {
+            Theme::System => egui::ThemePreference::System,
+            Theme::Light => egui::ThemePreference::Light,
+            Theme::Dark => egui::ThemePreference::Dark,
+        }
+    }
+}
+
+/// Theme and scaling settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppearanceSettings {
+    /// Color theme
+    pub theme: Theme,
+    /// Interface scale factor (1.0 = 100%)
+    pub ui_scale: f32,
+}
+
+impl Default for AppearanceSettings {
+    fn default() -> Self {
+        Self {
+            theme: Theme::System,
+            ui_scale: 1.0,
+        }
+    }
+}
+
+impl AppearanceSettings {
+    /// Apply theme and scale to an egui context
+    pub fn apply(&self, ctx: &egui::Context) {
+        ctx.set_theme(self.theme);
+        ctx.set_zoom_factor(self.ui_scale.clamp(0.5, 3.0));
+    }
+}
+
+impl Settings {
+    /// Default settings file path: `settings.json` in the config directory
+    pub fn default_path() -> PathBuf {
+        config_dir().join("settings.json")
+    }
+
+    /// Load settings from the default path
+    ///
+    /// Returns defaults if the file doesn't exist or cannot be read.
+    pub fn load() -> Self {
+        Self::load_from(&Self::default_path())
+    }
+
+    /// Load settings from a file
+    ///
+    /// Returns defaults if the file doesn't exist or cannot be read.
+    /// Errors are logged but not propagated.
+    #[instrument]
+    pub fn load_from(path: &Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(json) => match serde_json::from_str(&json) {
+                Ok(settings) => {
+                    debug!(?path, "Loaded settings");
+                    settings
+                }
+                Err(e) => {
+                    warn!(?path, error = %e, "Failed to parse settings, using defaults");
+                    Self::default()
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                debug!("No settings file found, using defaults");
+                Self::default()
+            }
+            Err(e) => {
+                warn!(?path, error = %e, "Failed to read settings");
+                Self::default()
+            }
+        }
+    }
+
+    /// Save settings to the default path
+    pub fn save(&self) -> Result<(), IoError> {
+        self.save_to(&Self::default_path())
+    }
+
+    /// Save settings to a file, creating its directory if needed
+    #[instrument(skip(self))]
+    pub fn save_to(&self, path: &Path) -> Result<(), IoError> {
+        let path_str = path.to_string_lossy().to_string();
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                IoError::new(
+                    format!("Failed to create config directory: {}", e),
+                    parent.to_string_lossy().to_string(),
+                    IoOperation::Create,
+                    line!(),
+                    file!(),
+                )
+            })?;
+        }
+
+        let json = serde_json::to_string_pretty(self).map_err(|e| {
+            IoError::new(
+                format!("Failed to serialize settings: {}", e),
+                &path_str,
+                IoOperation::Write,
+                line!(),
+                file!(),
+            )
+        })?;
+
+        std::fs::write(path, json).map_err(|e| {
+            IoError::new(
+                format!("Failed to write settings: {}", e),
+                &path_str,
+                IoOperation::Write,
+                line!(),
+                file!(),
+            )
+        })?;
+
+        debug!(?path, "Saved settings");
+        Ok(())
+    }
+}
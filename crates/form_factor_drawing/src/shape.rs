@@ -3,24 +3,25 @@
 //! This module provides geometric shapes for canvas annotations, leveraging
 //! the `geo` crate for robust geometry operations and spatial queries.
 
+use crate::{FieldCondition, FieldIssue, FieldRule};
 use derive_builder::Builder;
 use derive_getters::Getters;
 use egui::{Color32, Pos2, Stroke};
-use geo::{Contains, Point};
-use geo_types::{Coord, LineString, Polygon as GeoPolygon};
+use geo::{Contains, Point, Simplify};
+use geo_types::{Coord, Geometry, GeometryCollection, LineString, Polygon as GeoPolygon};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// Kind of error that can occur during shape creation and manipulation
 #[derive(Debug, Clone, PartialEq)]
 pub enum ShapeErrorKind {
-    /// Polygon has fewer than 3 points
+    /// Shape has fewer points than it needs (3 for polygons, 2 for polylines)
     TooFewPoints(usize),
 
     /// Coordinate contains NaN or infinity
     InvalidCoordinate,
 
-    /// Circle radius is not positive
+    /// Circle or ellipse radius is not positive
     InvalidRadius(f32),
 
     /// Shape has zero area or all points are collinear
@@ -31,13 +32,13 @@ impl fmt::Display for ShapeErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ShapeErrorKind::TooFewPoints(n) => {
-                write!(f, "Polygon must have at least 3 points, got {}", n)
+                write!(f, "Shape has too few points, got {}", n)
             }
             ShapeErrorKind::InvalidCoordinate => {
                 write!(f, "Invalid coordinate: point contains NaN or infinity")
             }
             ShapeErrorKind::InvalidRadius(r) => {
-                write!(f, "Circle radius must be positive, got {}", r)
+                write!(f, "Radius must be positive, got {}", r)
             }
             ShapeErrorKind::DegenerateShape => {
                 write!(f, "Degenerate shape: all points are collinear or coincident")
@@ -92,6 +93,28 @@ fn pos2_to_coord(p: Pos2) -> Result<Coord<f64>, ShapeError> {
     })
 }
 
+/// Check that a polygon's exterior has at least `min_points` finite vertices
+fn validate_ring(polygon: &GeoPolygon<f64>, min_points: usize) -> Result<(), ShapeError> {
+    let exterior = polygon.exterior();
+    if exterior.coords().any(|c| !c.x.is_finite() || !c.y.is_finite()) {
+        return Err(ShapeError::new(
+            ShapeErrorKind::InvalidCoordinate,
+            line!(),
+            file!(),
+        ));
+    }
+    // A closed ring repeats its first point at the end
+    let points = exterior.0.len().saturating_sub(usize::from(exterior.is_closed()));
+    if points < min_points {
+        return Err(ShapeError::new(
+            ShapeErrorKind::TooFewPoints(points),
+            line!(),
+            file!(),
+        ));
+    }
+    Ok(())
+}
+
 /// Convert a geo Coord<f64> to an egui Pos2
 #[inline]
 fn coord_to_pos2(c: Coord<f64>) -> Pos2 {
@@ -105,8 +128,80 @@ pub enum Shape {
     Rectangle(Rectangle),
     /// A circular shape
     Circle(Circle),
+    /// An elliptical shape with independent radii and rotation
+    Ellipse(Ellipse),
     /// A polygonal shape
     Polygon(PolygonShape),
+    /// A straight line, e.g. a fold line or an underlined field
+    Line(Line),
+    /// An open path of straight segments
+    Polyline(Polyline),
+    /// An arrow markup
+    Arrow(Arrow),
+    /// A callout markup with a leader line and text box
+    Callout(Callout),
+}
+
+/// How a shape's outline is drawn
+///
+/// Dashes and dots scale with the stroke width, so a thicker outline keeps
+/// the same look.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, strum::EnumIter, strum::Display)]
+pub enum StrokeStyle {
+    /// A continuous line
+    #[default]
+    Solid,
+    /// Dashes separated by short gaps
+    Dashed,
+    /// Round dots
+    Dotted,
+}
+
+impl StrokeStyle {
+    /// Length of one dash and of the gap after it, for a stroke of `width`
+    ///
+    /// Dots are dashes of zero length drawn with round caps. Solid outlines
+    /// have no pattern.
+    pub fn pattern(self, width: f32) -> Option<(f32, f32)> {
+        let width = width.max(1.0);
+        match self {
+            StrokeStyle::Solid => None,
+            StrokeStyle::Dashed => Some((width * 4.0, width * 2.5)),
+            StrokeStyle::Dotted => Some((0.0, width * 2.5)),
+        }
+    }
+
+    /// Draw a path of screen points in this style
+    ///
+    /// A closed path joins its last point back to the first.
+    pub fn paint_path(self, painter: &egui::Painter, mut points: Vec<Pos2>, closed: bool, stroke: Stroke) {
+        let Some(pattern) = self.pattern(stroke.width) else {
+            if closed {
+                painter.add(egui::Shape::closed_line(points, stroke));
+            } else {
+                painter.add(egui::Shape::line(points, stroke));
+            }
+            return;
+        };
+        if closed && let Some(&first) = points.first() {
+            points.push(first);
+        }
+        match pattern {
+            (0.0, gap) => {
+                painter.extend(egui::Shape::dotted_line(&points, stroke.color, gap, stroke.width / 2.0));
+            }
+            (dash, gap) => {
+                painter.extend(egui::Shape::dashed_line(&points, stroke, dash, gap));
+            }
+        }
+    }
+}
+
+/// Points around a circle, for drawing a patterned outline
+fn circle_points(center: Pos2, radius: f32) -> Vec<Pos2> {
+    (0..ELLIPSE_SEGMENTS)
+        .map(|i| center + egui::Vec2::angled(i as f32 * std::f32::consts::TAU / ELLIPSE_SEGMENTS as f32) * radius)
+        .collect()
 }
 
 /// A quadrilateral annotation (4-sided polygon, initially a rectangle)
@@ -125,6 +220,30 @@ pub struct Rectangle {
     pub fill: Color32,
     /// User-defined name for this shape
     pub name: String,
+    /// Whether this shape covers sensitive data that must be redacted on export
+    #[serde(default)]
+    pub sensitive: bool,
+    /// Value entered for the field this shape marks
+    #[serde(default)]
+    pub value: String,
+    /// Requirements on the field's value
+    #[serde(default)]
+    pub rule: FieldRule,
+    /// Confidence (0-100) of an extracted value, or `None` once a person entered or confirmed it
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    /// When the field applies, or `None` if it always does
+    #[serde(default)]
+    pub condition: Option<FieldCondition>,
+    /// Whether the object is left out of rendering, hit-testing and export
+    #[serde(default)]
+    pub hidden: bool,
+    /// Whether the object is protected from being picked on the canvas
+    #[serde(default)]
+    pub locked: bool,
+    /// Whether the outline is solid, dashed or dotted
+    #[serde(default)]
+    pub stroke_style: StrokeStyle,
 }
 
 impl Rectangle {
@@ -179,6 +298,14 @@ impl Rectangle {
             stroke,
             fill,
             name: String::new(),
+            sensitive: false,
+            value: String::new(),
+            rule: FieldRule::default(),
+            confidence: None,
+            condition: None,
+            hidden: false,
+            locked: false,
+            stroke_style: StrokeStyle::default(),
         })
     }
 
@@ -208,6 +335,14 @@ impl Rectangle {
             stroke,
             fill,
             name: String::new(),
+            sensitive: false,
+            value: String::new(),
+            rule: FieldRule::default(),
+            confidence: None,
+            condition: None,
+            hidden: false,
+            locked: false,
+            stroke_style: StrokeStyle::default(),
         })
     }
 
@@ -334,6 +469,13 @@ impl Rectangle {
         let point = Point::from(coord);
         self.polygon.contains(&point)
     }
+
+    /// Draw the quadrilateral, mapping its corners through `transform`
+    pub fn render(&self, painter: &egui::Painter, transform: &egui::emath::TSTransform) {
+        let points: Vec<Pos2> = self.corners.iter().map(|p| transform.mul_pos(*p)).collect();
+        painter.add(egui::Shape::convex_polygon(points.clone(), self.fill, Stroke::NONE));
+        self.stroke_style.paint_path(painter, points, true, self.stroke);
+    }
 }
 
 /// A circular annotation
@@ -354,6 +496,38 @@ pub struct Circle {
     /// User-defined name for this shape
     #[builder(default = "String::new()")]
     pub name: String,
+    /// Whether this shape covers sensitive data that must be redacted on export
+    #[serde(default)]
+    #[builder(default)]
+    pub sensitive: bool,
+    /// Value entered for the field this shape marks
+    #[serde(default)]
+    #[builder(default)]
+    pub value: String,
+    /// Requirements on the field's value
+    #[serde(default)]
+    #[builder(default)]
+    pub rule: FieldRule,
+    /// Confidence (0-100) of an extracted value, or `None` once a person entered or confirmed it
+    #[serde(default)]
+    #[builder(default)]
+    pub confidence: Option<f32>,
+    /// When the field applies, or `None` if it always does
+    #[serde(default)]
+    #[builder(default)]
+    pub condition: Option<FieldCondition>,
+    /// Whether the object is left out of rendering, hit-testing and export
+    #[serde(default)]
+    #[builder(default)]
+    pub hidden: bool,
+    /// Whether the object is protected from being picked on the canvas
+    #[serde(default)]
+    #[builder(default)]
+    pub locked: bool,
+    /// Whether the outline is solid, dashed or dotted
+    #[serde(default)]
+    #[builder(default)]
+    pub stroke_style: StrokeStyle,
 }
 
 impl Circle {
@@ -387,6 +561,14 @@ impl Circle {
             stroke,
             fill,
             name: String::new(),
+            sensitive: false,
+            value: String::new(),
+            rule: FieldRule::default(),
+            confidence: None,
+            condition: None,
+            hidden: false,
+            locked: false,
+            stroke_style: StrokeStyle::default(),
         })
     }
 
@@ -470,6 +652,263 @@ impl Circle {
         let distance = self.center.distance(pos);
         distance <= self.radius
     }
+
+    /// Draw the circle, mapping it through `transform`
+    pub fn render(&self, painter: &egui::Painter, transform: &egui::emath::TSTransform) {
+        let center = transform.mul_pos(self.center);
+        let radius = self.radius * transform.scaling;
+        if self.stroke_style == StrokeStyle::Solid {
+            painter.circle(center, radius, self.fill, self.stroke);
+        } else {
+            painter.circle_filled(center, radius, self.fill);
+            self.stroke_style.paint_path(painter, circle_points(center, radius), true, self.stroke);
+        }
+    }
+}
+
+/// Number of straight segments an ellipse is drawn and exported with
+const ELLIPSE_SEGMENTS: usize = 64;
+
+/// An elliptical annotation, e.g. an oval answer bubble
+///
+/// The ellipse is described by its center, its two radii along its own
+/// axes and the rotation of those axes. Vertex editing drags the ends of
+/// the two axes, changing one radius at a time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Getters)]
+pub struct Ellipse {
+    /// Center point of the ellipse
+    center: Pos2,
+    /// Radii along the ellipse's own x and y axes
+    radii: egui::Vec2,
+    /// Rotation of the axes in radians (positive = counter-clockwise)
+    rotation: f32,
+    /// Stroke style for the outline
+    pub stroke: Stroke,
+    /// Fill color
+    pub fill: Color32,
+    /// User-defined name for this shape
+    pub name: String,
+    /// Whether this shape covers sensitive data that must be redacted on export
+    #[serde(default)]
+    pub sensitive: bool,
+    /// Value entered for the field this shape marks
+    #[serde(default)]
+    pub value: String,
+    /// Requirements on the field's value
+    #[serde(default)]
+    pub rule: FieldRule,
+    /// Confidence (0-100) of an extracted value, or `None` once a person entered or confirmed it
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    /// When the field applies, or `None` if it always does
+    #[serde(default)]
+    pub condition: Option<FieldCondition>,
+    /// Whether the object is left out of rendering, hit-testing and export
+    #[serde(default)]
+    pub hidden: bool,
+    /// Whether the object is protected from being picked on the canvas
+    #[serde(default)]
+    pub locked: bool,
+    /// Whether the outline is solid, dashed or dotted
+    #[serde(default)]
+    pub stroke_style: StrokeStyle,
+}
+
+impl Ellipse {
+    /// Create a new ellipse
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidCoordinate` if the center or rotation contains NaN or infinity.
+    /// Returns `ShapeError::InvalidRadius` if either radius is not positive and finite.
+    pub fn new(
+        center: Pos2,
+        radii: egui::Vec2,
+        rotation: f32,
+        stroke: Stroke,
+        fill: Color32,
+    ) -> Result<Self, ShapeError> {
+        Self::check_geometry(center, radii, rotation)?;
+        Ok(Self {
+            center,
+            radii,
+            rotation,
+            stroke,
+            fill,
+            name: String::new(),
+            sensitive: false,
+            value: String::new(),
+            rule: FieldRule::default(),
+            confidence: None,
+            condition: None,
+            hidden: false,
+            locked: false,
+            stroke_style: StrokeStyle::default(),
+        })
+    }
+
+    /// Create an unrotated ellipse filling the box between two opposite corners
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Ellipse::new`], including when the
+    /// corners share an x or y coordinate.
+    pub fn from_corners(a: Pos2, b: Pos2, stroke: Stroke, fill: Color32) -> Result<Self, ShapeError> {
+        let radii = egui::vec2((b.x - a.x).abs(), (b.y - a.y).abs()) / 2.0;
+        Self::new(a.lerp(b, 0.5), radii, 0.0, stroke, fill)
+    }
+
+    /// Check the invariants of an ellipse's geometry
+    fn check_geometry(center: Pos2, radii: egui::Vec2, rotation: f32) -> Result<(), ShapeError> {
+        pos2_to_coord(center)?;
+        if !rotation.is_finite() {
+            return Err(ShapeError::new(
+                ShapeErrorKind::InvalidCoordinate,
+                line!(),
+                file!(),
+            ));
+        }
+        for radius in [radii.x, radii.y] {
+            if !radius.is_finite() || radius <= 0.0 {
+                return Err(ShapeError::new(
+                    ShapeErrorKind::InvalidRadius(radius),
+                    line!(),
+                    file!(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Set the center position
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidCoordinate` if the position is invalid.
+    pub fn set_center(&mut self, center: Pos2) -> Result<(), ShapeError> {
+        Self::check_geometry(center, self.radii, self.rotation)?;
+        self.center = center;
+        Ok(())
+    }
+
+    /// Set both radii
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidRadius` if either radius is not positive and finite.
+    pub fn set_radii(&mut self, radii: egui::Vec2) -> Result<(), ShapeError> {
+        Self::check_geometry(self.center, radii, self.rotation)?;
+        self.radii = radii;
+        Ok(())
+    }
+
+    /// Set the rotation of the axes in radians
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidCoordinate` if the angle is NaN or infinite.
+    pub fn set_rotation(&mut self, rotation: f32) -> Result<(), ShapeError> {
+        Self::check_geometry(self.center, self.radii, rotation)?;
+        self.rotation = rotation;
+        Ok(())
+    }
+
+    /// Unit vectors along the ellipse's x and y axes
+    fn axes(&self) -> [egui::Vec2; 2] {
+        let (sin, cos) = self.rotation.sin_cos();
+        [egui::vec2(cos, sin), egui::vec2(-sin, cos)]
+    }
+
+    /// Ends of the x and y semi-axes, which vertex editing drags
+    pub fn handles(&self) -> [Pos2; 2] {
+        let [x_axis, y_axis] = self.axes();
+        [self.center + x_axis * self.radii.x, self.center + y_axis * self.radii.y]
+    }
+
+    /// Drag the end of a semi-axis, changing that radius
+    ///
+    /// Index 0 is the x axis and 1 the y axis; other indices are ignored.
+    /// The new radius is the distance from the center to `pos` measured
+    /// along the axis, so the rotation is kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidRadius` if the radius would not be positive.
+    pub fn set_handle(&mut self, index: usize, pos: Pos2) -> Result<(), ShapeError> {
+        let Some(axis) = self.axes().get(index).copied() else {
+            return Ok(());
+        };
+        let radius = (pos - self.center).dot(axis).abs();
+        let mut radii = self.radii;
+        radii[index] = radius;
+        self.set_radii(radii)
+    }
+
+    /// Rotate this ellipse around a pivot point
+    ///
+    /// The center rotates around the pivot and the axes turn with it.
+    ///
+    /// # Arguments
+    ///
+    /// * `angle` - Rotation angle in radians (positive = counter-clockwise)
+    /// * `pivot` - Point to rotate around
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidCoordinate` if rotation produces invalid coordinates.
+    pub fn rotate(&mut self, angle: f32, pivot: Pos2) -> Result<(), ShapeError> {
+        let center = rotate_about(self.center, angle, pivot);
+        let rotation = (self.rotation + angle).rem_euclid(std::f32::consts::TAU);
+        Self::check_geometry(center, self.radii, rotation)?;
+        (self.center, self.rotation) = (center, rotation);
+        Ok(())
+    }
+
+    /// Translate this ellipse by a delta vector
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidCoordinate` if translation produces invalid coordinates.
+    pub fn translate(&mut self, delta: egui::Vec2) -> Result<(), ShapeError> {
+        self.set_center(self.center + delta)
+    }
+
+    /// Test if a point is inside this ellipse
+    pub fn contains_point(&self, pos: Pos2) -> bool {
+        if !pos.x.is_finite() || !pos.y.is_finite() {
+            return false;
+        }
+        let [x_axis, y_axis] = self.axes();
+        let d = pos - self.center;
+        let (x, y) = (d.dot(x_axis) / self.radii.x, d.dot(y_axis) / self.radii.y);
+        x * x + y * y <= 1.0
+    }
+
+    /// Points around the outline, for drawing and exporting as a polygon
+    pub fn to_egui_points(&self) -> Vec<Pos2> {
+        let [x_axis, y_axis] = self.axes();
+        (0..ELLIPSE_SEGMENTS)
+            .map(|i| {
+                let (sin, cos) = (i as f32 * std::f32::consts::TAU / ELLIPSE_SEGMENTS as f32).sin_cos();
+                self.center + x_axis * (self.radii.x * cos) + y_axis * (self.radii.y * sin)
+            })
+            .collect()
+    }
+
+    /// Axis-aligned bounding box of the outline
+    pub fn bounding_rect(&self) -> egui::Rect {
+        let [x_axis, y_axis] = self.axes();
+        let half_width = (self.radii.x * x_axis.x).hypot(self.radii.y * y_axis.x);
+        let half_height = (self.radii.x * x_axis.y).hypot(self.radii.y * y_axis.y);
+        egui::Rect::from_center_size(self.center, egui::vec2(half_width, half_height) * 2.0)
+    }
+
+    /// Draw the ellipse through a canvas-to-screen transform
+    pub fn render(&self, painter: &egui::Painter, transform: &egui::emath::TSTransform) {
+        let points: Vec<Pos2> = self.to_egui_points().into_iter().map(|p| transform.mul_pos(p)).collect();
+        painter.add(egui::Shape::convex_polygon(points.clone(), self.fill, Stroke::NONE));
+        self.stroke_style.paint_path(painter, points, true, self.stroke);
+    }
 }
 
 /// A polygon annotation (closed shape)
@@ -486,6 +925,30 @@ pub struct PolygonShape {
     pub fill: Color32,
     /// User-defined name for this shape
     pub name: String,
+    /// Whether this shape covers sensitive data that must be redacted on export
+    #[serde(default)]
+    pub sensitive: bool,
+    /// Value entered for the field this shape marks
+    #[serde(default)]
+    pub value: String,
+    /// Requirements on the field's value
+    #[serde(default)]
+    pub rule: FieldRule,
+    /// Confidence (0-100) of an extracted value, or `None` once a person entered or confirmed it
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    /// When the field applies, or `None` if it always does
+    #[serde(default)]
+    pub condition: Option<FieldCondition>,
+    /// Whether the object is left out of rendering, hit-testing and export
+    #[serde(default)]
+    pub hidden: bool,
+    /// Whether the object is protected from being picked on the canvas
+    #[serde(default)]
+    pub locked: bool,
+    /// Whether the outline is solid, dashed or dotted
+    #[serde(default)]
+    pub stroke_style: StrokeStyle,
 }
 
 impl PolygonShape {
@@ -524,6 +987,14 @@ impl PolygonShape {
             stroke,
             fill,
             name: String::new(),
+            sensitive: false,
+            value: String::new(),
+            rule: FieldRule::default(),
+            confidence: None,
+            condition: None,
+            hidden: false,
+            locked: false,
+            stroke_style: StrokeStyle::default(),
         })
     }
 
@@ -674,50 +1145,1265 @@ impl PolygonShape {
         let point = Point::from(coord);
         self.polygon.contains(&point)
     }
+
+    /// Draw the polygon, mapping its vertices through `transform`
+    pub fn render(&self, painter: &egui::Painter, transform: &egui::emath::TSTransform) {
+        let points: Vec<Pos2> = self.to_egui_points().into_iter().map(|p| transform.mul_pos(p)).collect();
+        if points.len() > 2 {
+            painter.add(egui::Shape::convex_polygon(points.clone(), self.fill, Stroke::NONE));
+            self.stroke_style.paint_path(painter, points, true, self.stroke);
+        }
+    }
 }
 
-impl Shape {
-    /// Render this shape to the given painter
-    pub fn render(&self, painter: &egui::Painter) {
-        match self {
-            Shape::Rectangle(rect) => {
-                // Draw as a filled quadrilateral
-                painter.add(egui::Shape::convex_polygon(
-                    rect.corners().to_vec(),
-                    rect.fill,
-                    egui::Stroke::NONE,
-                ));
-                // Draw the outline
-                painter.add(egui::Shape::closed_line(
-                    rect.corners().to_vec(),
-                    rect.stroke,
-                ));
-            }
-            Shape::Circle(circle) => {
-                painter.circle(circle.center, circle.radius, circle.fill, circle.stroke);
-            }
-            Shape::Polygon(poly) => {
-                let points = poly.to_egui_points();
-                if points.len() > 2 {
-                    // Draw filled polygon
-                    painter.add(egui::Shape::convex_polygon(
-                        points.clone(),
-                        poly.fill,
-                        egui::Stroke::NONE,
-                    ));
-                    // Draw polygon outline as a closed path
-                    painter.add(egui::Shape::closed_line(points, poly.stroke));
-                }
-            }
+/// Distance from a markup's lines within which a point hits it, in canvas units
+const MARKUP_HIT_TOLERANCE: f32 = 4.0;
+
+/// Smallest arrowhead length, in canvas units
+const MIN_ARROWHEAD_LENGTH: f32 = 10.0;
+
+/// Arrowhead length per unit of stroke width
+const ARROWHEAD_STROKE_RATIO: f32 = 4.0;
+
+/// Half-angle of the arrowhead, in radians
+const ARROWHEAD_ANGLE: f32 = 0.45;
+
+/// Font size of callout text, in canvas units
+pub(crate) const CALLOUT_FONT_SIZE: f32 = 14.0;
+
+/// Space between a callout's box and its text, in canvas units
+pub(crate) const CALLOUT_PADDING: f32 = 4.0;
+
+/// Distance from `p` to the segment from `a` to `b`
+fn segment_distance(p: Pos2, a: Pos2, b: Pos2) -> f32 {
+    let ab = b - a;
+    let t = if ab.length_sq() > 0.0 {
+        ((p - a).dot(ab) / ab.length_sq()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    p.distance(a + ab * t)
+}
+
+/// Whether `p` lies inside or on the triangle `[a, b, c]`, in either winding
+fn triangle_contains([a, b, c]: [Pos2; 3], p: Pos2) -> bool {
+    let side = |from: Pos2, to: Pos2| (to - from).x * (p - from).y - (to - from).y * (p - from).x;
+    let sides = [side(a, b), side(b, c), side(c, a)];
+    sides.iter().all(|s| *s >= 0.0) || sides.iter().all(|s| *s <= 0.0)
+}
+
+/// Rotate a point around a pivot
+fn rotate_about(point: Pos2, angle: f32, pivot: Pos2) -> Pos2 {
+    let (sin, cos) = angle.sin_cos();
+    let d = point - pivot;
+    Pos2::new(d.x * cos - d.y * sin + pivot.x, d.x * sin + d.y * cos + pivot.y)
+}
+
+/// An arrow markup pointing from a tail to a tip
+///
+/// Arrows are review markups rather than form fields. They are drawn as a
+/// line with a filled head at the tip, and hit anywhere along the line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Getters)]
+pub struct Arrow {
+    /// Tail of the arrow
+    start: Pos2,
+    /// Tip of the arrow, where the head is drawn
+    end: Pos2,
+    /// Stroke style for the line and head
+    pub stroke: Stroke,
+    /// User-defined name for this shape
+    pub name: String,
+    /// Whether this shape covers sensitive data that must be redacted on export
+    #[serde(default)]
+    pub sensitive: bool,
+    /// Value entered for the field this shape marks
+    #[serde(default)]
+    pub value: String,
+    /// Requirements on the field's value
+    #[serde(default)]
+    pub rule: FieldRule,
+    /// Confidence (0-100) of an extracted value, or `None` once a person entered or confirmed it
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    /// When the field applies, or `None` if it always does
+    #[serde(default)]
+    pub condition: Option<FieldCondition>,
+    /// Whether the object is left out of rendering, hit-testing and export
+    #[serde(default)]
+    pub hidden: bool,
+    /// Whether the object is protected from being picked on the canvas
+    #[serde(default)]
+    pub locked: bool,
+    /// Whether the outline is solid, dashed or dotted
+    #[serde(default)]
+    pub stroke_style: StrokeStyle,
+}
+
+impl Arrow {
+    /// Create an arrow from its tail to its tip
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidCoordinate` if either point contains NaN or infinity.
+    /// Returns `ShapeError::DegenerateShape` if the tail and tip are the same point.
+    pub fn new(start: Pos2, end: Pos2, stroke: Stroke) -> Result<Self, ShapeError> {
+        Self::check_points(start, end)?;
+        Ok(Self {
+            start,
+            end,
+            stroke,
+            name: String::new(),
+            sensitive: false,
+            value: String::new(),
+            rule: FieldRule::default(),
+            confidence: None,
+            condition: None,
+            hidden: false,
+            locked: false,
+            stroke_style: StrokeStyle::default(),
+        })
+    }
+
+    fn check_points(start: Pos2, end: Pos2) -> Result<(), ShapeError> {
+        pos2_to_coord(start)?;
+        pos2_to_coord(end)?;
+        if start.distance(end) < f32::EPSILON {
+            return Err(ShapeError::new(
+                ShapeErrorKind::DegenerateShape,
+                line!(),
+                file!(),
+            ));
         }
+        Ok(())
     }
 
-    /// Test if a point is inside this shape
+    /// Move the tail
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Arrow::new`].
+    pub fn set_start(&mut self, start: Pos2) -> Result<(), ShapeError> {
+        Self::check_points(start, self.end)?;
+        self.start = start;
+        Ok(())
+    }
+
+    /// Move the tip
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Arrow::new`].
+    pub fn set_end(&mut self, end: Pos2) -> Result<(), ShapeError> {
+        Self::check_points(self.start, end)?;
+        self.end = end;
+        Ok(())
+    }
+
+    /// Midpoint of the arrow
+    pub fn center(&self) -> Pos2 {
+        self.start.lerp(self.end, 0.5)
+    }
+
+    /// Corners of the arrowhead: the tip, then the two barbs
+    ///
+    /// The head grows with the stroke width but never outruns the line.
+    pub fn head(&self) -> [Pos2; 3] {
+        let shaft = self.end - self.start;
+        let length = (self.stroke.width * ARROWHEAD_STROKE_RATIO)
+            .max(MIN_ARROWHEAD_LENGTH)
+            .min(shaft.length());
+        let back = -shaft.normalized() * length;
+        [
+            self.end,
+            self.end + egui::emath::Rot2::from_angle(ARROWHEAD_ANGLE) * back,
+            self.end + egui::emath::Rot2::from_angle(-ARROWHEAD_ANGLE) * back,
+        ]
+    }
+
+    /// Rotate this arrow around a pivot point
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidCoordinate` if rotation produces invalid coordinates.
+    pub fn rotate(&mut self, angle: f32, pivot: Pos2) -> Result<(), ShapeError> {
+        let (start, end) = (rotate_about(self.start, angle, pivot), rotate_about(self.end, angle, pivot));
+        Self::check_points(start, end)?;
+        (self.start, self.end) = (start, end);
+        Ok(())
+    }
+
+    /// Translate this arrow by a delta vector
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidCoordinate` if translation produces invalid coordinates.
+    pub fn translate(&mut self, delta: egui::Vec2) -> Result<(), ShapeError> {
+        let (start, end) = (self.start + delta, self.end + delta);
+        Self::check_points(start, end)?;
+        (self.start, self.end) = (start, end);
+        Ok(())
+    }
+
+    /// Test if a point is on the line or inside the head
     pub fn contains_point(&self, pos: Pos2) -> bool {
-        match self {
-            Shape::Rectangle(rect) => rect.contains_point(pos),
-            Shape::Circle(circle) => circle.contains_point(pos),
-            Shape::Polygon(poly) => poly.contains_point(pos),
+        if !pos.x.is_finite() || !pos.y.is_finite() {
+            return false;
+        }
+        let tolerance = (self.stroke.width / 2.0).max(MARKUP_HIT_TOLERANCE);
+        segment_distance(pos, self.start, self.end) <= tolerance || triangle_contains(self.head(), pos)
+    }
+
+    /// Draw the arrow, mapping its points through `transform`
+    pub fn render(&self, painter: &egui::Painter, transform: &egui::emath::TSTransform) {
+        let head: Vec<Pos2> = self.head().iter().map(|p| transform.mul_pos(*p)).collect();
+        // Stop the shaft at the base of the head so thick strokes don't poke through the tip
+        let base = head[1].lerp(head[2], 0.5);
+        self.stroke_style.paint_path(painter, vec![transform.mul_pos(self.start), base], false, self.stroke);
+        painter.add(egui::Shape::convex_polygon(head, self.stroke.color, egui::Stroke::NONE));
+    }
+}
+
+/// A callout markup: a text box with a leader line to the point it describes
+///
+/// Callouts are review markups rather than form fields. The box stays axis
+/// aligned; the leader runs from the anchor to the nearest point on the box.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Getters)]
+pub struct Callout {
+    /// Point the leader line points at
+    anchor: Pos2,
+    /// Box holding the text
+    text_box: egui::Rect,
+    /// Note written in the box
+    pub text: String,
+    /// Stroke style for the leader line and box outline
+    pub stroke: Stroke,
+    /// Fill color of the box
+    pub fill: Color32,
+    /// User-defined name for this shape
+    pub name: String,
+    /// Whether this shape covers sensitive data that must be redacted on export
+    #[serde(default)]
+    pub sensitive: bool,
+    /// Value entered for the field this shape marks
+    #[serde(default)]
+    pub value: String,
+    /// Requirements on the field's value
+    #[serde(default)]
+    pub rule: FieldRule,
+    /// Confidence (0-100) of an extracted value, or `None` once a person entered or confirmed it
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    /// When the field applies, or `None` if it always does
+    #[serde(default)]
+    pub condition: Option<FieldCondition>,
+    /// Whether the object is left out of rendering, hit-testing and export
+    #[serde(default)]
+    pub hidden: bool,
+    /// Whether the object is protected from being picked on the canvas
+    #[serde(default)]
+    pub locked: bool,
+    /// Whether the outline is solid, dashed or dotted
+    #[serde(default)]
+    pub stroke_style: StrokeStyle,
+}
+
+impl Callout {
+    /// Create a callout whose leader points at `anchor`
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidCoordinate` if any coordinate contains NaN or infinity.
+    /// Returns `ShapeError::DegenerateShape` if the text box has zero area.
+    pub fn new(
+        anchor: Pos2,
+        text_box: egui::Rect,
+        text: impl Into<String>,
+        stroke: Stroke,
+        fill: Color32,
+    ) -> Result<Self, ShapeError> {
+        pos2_to_coord(anchor)?;
+        let text_box = Self::check_box(text_box)?;
+        Ok(Self {
+            anchor,
+            text_box,
+            text: text.into(),
+            stroke,
+            fill,
+            name: String::new(),
+            sensitive: false,
+            value: String::new(),
+            rule: FieldRule::default(),
+            confidence: None,
+            condition: None,
+            hidden: false,
+            locked: false,
+            stroke_style: StrokeStyle::default(),
+        })
+    }
+
+    /// Validate a text box, normalizing its corners
+    fn check_box(text_box: egui::Rect) -> Result<egui::Rect, ShapeError> {
+        pos2_to_coord(text_box.min)?;
+        pos2_to_coord(text_box.max)?;
+        let text_box = egui::Rect::from_two_pos(text_box.min, text_box.max);
+        if text_box.width() < f32::EPSILON || text_box.height() < f32::EPSILON {
+            return Err(ShapeError::new(
+                ShapeErrorKind::DegenerateShape,
+                line!(),
+                file!(),
+            ));
+        }
+        Ok(text_box)
+    }
+
+    /// Move the point the leader points at
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidCoordinate` if the position is invalid.
+    pub fn set_anchor(&mut self, anchor: Pos2) -> Result<(), ShapeError> {
+        pos2_to_coord(anchor)?;
+        self.anchor = anchor;
+        Ok(())
+    }
+
+    /// Move or resize the text box
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Callout::new`].
+    pub fn set_text_box(&mut self, text_box: egui::Rect) -> Result<(), ShapeError> {
+        self.text_box = Self::check_box(text_box)?;
+        Ok(())
+    }
+
+    /// Where the leader line meets the text box
+    pub fn leader_end(&self) -> Pos2 {
+        let clamped = self.text_box.clamp(self.anchor);
+        if clamped != self.anchor {
+            return clamped;
+        }
+        // Anchor inside the box: meet the nearest edge
+        let Pos2 { x, y } = self.anchor;
+        let edges = [
+            (x - self.text_box.min.x, Pos2::new(self.text_box.min.x, y)),
+            (self.text_box.max.x - x, Pos2::new(self.text_box.max.x, y)),
+            (y - self.text_box.min.y, Pos2::new(x, self.text_box.min.y)),
+            (self.text_box.max.y - y, Pos2::new(x, self.text_box.max.y)),
+        ];
+        edges
+            .into_iter()
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map_or(self.anchor, |(_, edge)| edge)
+    }
+
+    /// Center of the anchor and text box together
+    pub fn center(&self) -> Pos2 {
+        self.text_box.union(egui::Rect::from_pos(self.anchor)).center()
+    }
+
+    /// Rotate this callout around a pivot point
+    ///
+    /// The anchor and the center of the box rotate; the box stays axis aligned
+    /// so its text remains readable.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidCoordinate` if rotation produces invalid coordinates.
+    pub fn rotate(&mut self, angle: f32, pivot: Pos2) -> Result<(), ShapeError> {
+        let anchor = rotate_about(self.anchor, angle, pivot);
+        let center = rotate_about(self.text_box.center(), angle, pivot);
+        pos2_to_coord(anchor)?;
+        self.text_box = Self::check_box(egui::Rect::from_center_size(center, self.text_box.size()))?;
+        self.anchor = anchor;
+        Ok(())
+    }
+
+    /// Translate this callout by a delta vector
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidCoordinate` if translation produces invalid coordinates.
+    pub fn translate(&mut self, delta: egui::Vec2) -> Result<(), ShapeError> {
+        pos2_to_coord(self.anchor + delta)?;
+        self.text_box = Self::check_box(self.text_box.translate(delta))?;
+        self.anchor += delta;
+        Ok(())
+    }
+
+    /// Test if a point is inside the box or on the leader line
+    pub fn contains_point(&self, pos: Pos2) -> bool {
+        if !pos.x.is_finite() || !pos.y.is_finite() {
+            return false;
+        }
+        let tolerance = (self.stroke.width / 2.0).max(MARKUP_HIT_TOLERANCE);
+        self.text_box.contains(pos) || segment_distance(pos, self.anchor, self.leader_end()) <= tolerance
+    }
+
+    /// Draw the callout, mapping its points through `transform`
+    ///
+    /// Text wraps to the width of the box and is clipped to it.
+    pub fn render(&self, painter: &egui::Painter, transform: &egui::emath::TSTransform) {
+        let text_box = *transform * self.text_box;
+        self.stroke_style.paint_path(
+            painter,
+            vec![transform.mul_pos(self.anchor), transform.mul_pos(self.leader_end())],
+            false,
+            self.stroke,
+        );
+        painter.rect_filled(text_box, 0.0, self.fill);
+        let corners = vec![text_box.left_top(), text_box.right_top(), text_box.right_bottom(), text_box.left_bottom()];
+        self.stroke_style.paint_path(painter, corners, true, self.stroke);
+
+        if self.text.is_empty() {
+            return;
+        }
+        let padding = CALLOUT_PADDING * transform.scaling;
+        let inner = text_box.shrink(padding);
+        let galley = painter.layout(
+            self.text.clone(),
+            egui::FontId::proportional(CALLOUT_FONT_SIZE * transform.scaling),
+            self.stroke.color,
+            inner.width().max(0.0),
+        );
+        painter
+            .with_clip_rect(text_box.intersect(painter.clip_rect()))
+            .galley(inner.min, galley, self.stroke.color);
+    }
+}
+
+/// Font size of length labels, in canvas units
+pub(crate) const LENGTH_FONT_SIZE: f32 = 12.0;
+
+/// Text of a length label
+pub fn length_label(length: f32) -> String {
+    format!("{:.1}", length)
+}
+
+/// Total length of a path through `points`
+fn path_length(points: &[Pos2]) -> f32 {
+    points.windows(2).map(|pair| pair[0].distance(pair[1])).sum()
+}
+
+/// Point halfway along a path through `points`
+fn path_midpoint(points: &[Pos2]) -> Pos2 {
+    let mut remaining = path_length(points) / 2.0;
+    for pair in points.windows(2) {
+        let segment = pair[0].distance(pair[1]);
+        if segment >= remaining && segment > 0.0 {
+            return pair[0].lerp(pair[1], remaining / segment);
+        }
+        remaining -= segment;
+    }
+    points.first().copied().unwrap_or(Pos2::ZERO)
+}
+
+/// Distance from `p` to the nearest segment of a path through `points`
+fn path_distance(points: &[Pos2], p: Pos2) -> f32 {
+    points
+        .windows(2)
+        .map(|pair| segment_distance(p, pair[0], pair[1]))
+        .fold(f32::INFINITY, f32::min)
+}
+
+/// Check that a path has at least two finite points and is not a single spot
+fn check_path(points: &[Pos2]) -> Result<(), ShapeError> {
+    if points.len() < 2 {
+        return Err(ShapeError::new(
+            ShapeErrorKind::TooFewPoints(points.len()),
+            line!(),
+            file!(),
+        ));
+    }
+    for point in points {
+        pos2_to_coord(*point)?;
+    }
+    if path_length(points) < f32::EPSILON {
+        return Err(ShapeError::new(
+            ShapeErrorKind::DegenerateShape,
+            line!(),
+            file!(),
+        ));
+    }
+    Ok(())
+}
+
+/// Draw a path's length beside its midpoint, mapping through `transform`
+///
+/// `length_scale` converts canvas units to the units shown, so the label
+/// can read in form image pixels like the rulers.
+fn render_length(
+    painter: &egui::Painter,
+    transform: &egui::emath::TSTransform,
+    points: &[Pos2],
+    length_scale: f32,
+    color: Color32,
+) {
+    let at = transform.mul_pos(path_midpoint(points)) - egui::vec2(0.0, 2.0);
+    painter.text(
+        at,
+        egui::Align2::CENTER_BOTTOM,
+        length_label(path_length(points) * length_scale),
+        egui::FontId::proportional(LENGTH_FONT_SIZE * transform.scaling),
+        color,
+    );
+}
+
+/// A straight line between two points
+///
+/// Lines mark fold lines, separators, and fields that are underlines rather
+/// than boxes. They have no fill and are hit anywhere along the line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Getters)]
+pub struct Line {
+    /// First end of the line
+    start: Pos2,
+    /// Second end of the line
+    end: Pos2,
+    /// Stroke style for the line
+    pub stroke: Stroke,
+    /// Whether the length is shown beside the line
+    #[serde(default)]
+    pub show_length: bool,
+    /// User-defined name for this shape
+    pub name: String,
+    /// Whether this shape covers sensitive data that must be redacted on export
+    #[serde(default)]
+    pub sensitive: bool,
+    /// Value entered for the field this shape marks
+    #[serde(default)]
+    pub value: String,
+    /// Requirements on the field's value
+    #[serde(default)]
+    pub rule: FieldRule,
+    /// Confidence (0-100) of an extracted value, or `None` once a person entered or confirmed it
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    /// When the field applies, or `None` if it always does
+    #[serde(default)]
+    pub condition: Option<FieldCondition>,
+    /// Whether the object is left out of rendering, hit-testing and export
+    #[serde(default)]
+    pub hidden: bool,
+    /// Whether the object is protected from being picked on the canvas
+    #[serde(default)]
+    pub locked: bool,
+    /// Whether the outline is solid, dashed or dotted
+    #[serde(default)]
+    pub stroke_style: StrokeStyle,
+}
+
+impl Line {
+    /// Create a line between two points
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidCoordinate` if either point contains NaN or infinity.
+    /// Returns `ShapeError::DegenerateShape` if both ends are the same point.
+    pub fn new(start: Pos2, end: Pos2, stroke: Stroke) -> Result<Self, ShapeError> {
+        check_path(&[start, end])?;
+        Ok(Self {
+            start,
+            end,
+            stroke,
+            show_length: false,
+            name: String::new(),
+            sensitive: false,
+            value: String::new(),
+            rule: FieldRule::default(),
+            confidence: None,
+            condition: None,
+            hidden: false,
+            locked: false,
+            stroke_style: StrokeStyle::default(),
+        })
+    }
+
+    /// Move the first end
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Line::new`].
+    pub fn set_start(&mut self, start: Pos2) -> Result<(), ShapeError> {
+        check_path(&[start, self.end])?;
+        self.start = start;
+        Ok(())
+    }
+
+    /// Move the second end
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Line::new`].
+    pub fn set_end(&mut self, end: Pos2) -> Result<(), ShapeError> {
+        check_path(&[self.start, end])?;
+        self.end = end;
+        Ok(())
+    }
+
+    /// Both ends, first to second
+    pub fn points(&self) -> [Pos2; 2] {
+        [self.start, self.end]
+    }
+
+    /// Length of the line in canvas units
+    pub fn length(&self) -> f32 {
+        self.start.distance(self.end)
+    }
+
+    /// Midpoint of the line
+    pub fn center(&self) -> Pos2 {
+        self.start.lerp(self.end, 0.5)
+    }
+
+    /// Rotate this line around a pivot point
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidCoordinate` if rotation produces invalid coordinates.
+    pub fn rotate(&mut self, angle: f32, pivot: Pos2) -> Result<(), ShapeError> {
+        let (start, end) = (rotate_about(self.start, angle, pivot), rotate_about(self.end, angle, pivot));
+        check_path(&[start, end])?;
+        (self.start, self.end) = (start, end);
+        Ok(())
+    }
+
+    /// Translate this line by a delta vector
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidCoordinate` if translation produces invalid coordinates.
+    pub fn translate(&mut self, delta: egui::Vec2) -> Result<(), ShapeError> {
+        let (start, end) = (self.start + delta, self.end + delta);
+        check_path(&[start, end])?;
+        (self.start, self.end) = (start, end);
+        Ok(())
+    }
+
+    /// Test if a point is on the line
+    pub fn contains_point(&self, pos: Pos2) -> bool {
+        if !pos.x.is_finite() || !pos.y.is_finite() {
+            return false;
+        }
+        let tolerance = (self.stroke.width / 2.0).max(MARKUP_HIT_TOLERANCE);
+        segment_distance(pos, self.start, self.end) <= tolerance
+    }
+
+    /// Draw the line, mapping its points through `transform`
+    ///
+    /// The length label, if shown, is multiplied by `length_scale`.
+    pub fn render(&self, painter: &egui::Painter, transform: &egui::emath::TSTransform, length_scale: f32) {
+        let points = vec![transform.mul_pos(self.start), transform.mul_pos(self.end)];
+        self.stroke_style.paint_path(painter, points, false, self.stroke);
+        if self.show_length {
+            render_length(painter, transform, &self.points(), length_scale, self.stroke.color);
+        }
+    }
+}
+
+/// An open path of straight segments
+///
+/// Unlike [`PolygonShape`], the last point does not join the first and the
+/// path has no fill. Polylines are hit anywhere along their segments.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Getters)]
+pub struct Polyline {
+    /// Points along the path, in order
+    points: Vec<Pos2>,
+    /// Stroke style for the segments
+    pub stroke: Stroke,
+    /// Whether the total length is shown beside the path
+    #[serde(default)]
+    pub show_length: bool,
+    /// User-defined name for this shape
+    pub name: String,
+    /// Whether this shape covers sensitive data that must be redacted on export
+    #[serde(default)]
+    pub sensitive: bool,
+    /// Value entered for the field this shape marks
+    #[serde(default)]
+    pub value: String,
+    /// Requirements on the field's value
+    #[serde(default)]
+    pub rule: FieldRule,
+    /// Confidence (0-100) of an extracted value, or `None` once a person entered or confirmed it
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    /// When the field applies, or `None` if it always does
+    #[serde(default)]
+    pub condition: Option<FieldCondition>,
+    /// Whether the object is left out of rendering, hit-testing and export
+    #[serde(default)]
+    pub hidden: bool,
+    /// Whether the object is protected from being picked on the canvas
+    #[serde(default)]
+    pub locked: bool,
+    /// Whether the outline is solid, dashed or dotted
+    #[serde(default)]
+    pub stroke_style: StrokeStyle,
+}
+
+impl Polyline {
+    /// Create a polyline through `points`
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::TooFewPoints` if fewer than 2 points are provided.
+    /// Returns `ShapeError::InvalidCoordinate` if any point contains NaN or infinity.
+    /// Returns `ShapeError::DegenerateShape` if all points are the same.
+    pub fn from_points(points: Vec<Pos2>, stroke: Stroke) -> Result<Self, ShapeError> {
+        check_path(&points)?;
+        Ok(Self {
+            points,
+            stroke,
+            show_length: false,
+            name: String::new(),
+            sensitive: false,
+            value: String::new(),
+            rule: FieldRule::default(),
+            confidence: None,
+            condition: None,
+            hidden: false,
+            locked: false,
+            stroke_style: StrokeStyle::default(),
+        })
+    }
+
+    /// Create a polyline from a hand-drawn path, dropping points within
+    /// `tolerance` of the simplified path
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Polyline::from_points`].
+    pub fn simplified(points: Vec<Pos2>, tolerance: f32, stroke: Stroke) -> Result<Self, ShapeError> {
+        let coords: Vec<Coord<f64>> = points.iter().map(|&p| pos2_to_coord(p)).collect::<Result<_, _>>()?;
+        let path = LineString::from(coords).simplify(tolerance as f64);
+        Self::from_points(path.coords().map(|c| coord_to_pos2(*c)).collect(), stroke)
+    }
+
+    /// Move a vertex
+    ///
+    /// Out of range indices are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Polyline::from_points`].
+    pub fn set_vertex(&mut self, index: usize, pos: Pos2) -> Result<(), ShapeError> {
+        if index >= self.points.len() {
+            return Ok(());
+        }
+        let mut points = self.points.clone();
+        points[index] = pos;
+        self.set_vertices(points)
+    }
+
+    /// Replace all vertices at once
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Polyline::from_points`].
+    pub fn set_vertices(&mut self, points: Vec<Pos2>) -> Result<(), ShapeError> {
+        check_path(&points)?;
+        self.points = points;
+        Ok(())
+    }
+
+    /// Total length of the path in canvas units
+    pub fn length(&self) -> f32 {
+        path_length(&self.points)
+    }
+
+    /// Point halfway along the path, where the length label goes
+    pub fn midpoint(&self) -> Pos2 {
+        path_midpoint(&self.points)
+    }
+
+    /// Center of the vertices
+    pub fn center(&self) -> Pos2 {
+        let sum = self.points.iter().fold(egui::Vec2::ZERO, |sum, p| sum + p.to_vec2());
+        (sum / self.points.len().max(1) as f32).to_pos2()
+    }
+
+    /// Rotate this polyline around a pivot point
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidCoordinate` if rotation produces invalid coordinates.
+    pub fn rotate(&mut self, angle: f32, pivot: Pos2) -> Result<(), ShapeError> {
+        self.set_vertices(self.points.iter().map(|p| rotate_about(*p, angle, pivot)).collect())
+    }
+
+    /// Translate this polyline by a delta vector
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidCoordinate` if translation produces invalid coordinates.
+    pub fn translate(&mut self, delta: egui::Vec2) -> Result<(), ShapeError> {
+        self.set_vertices(self.points.iter().map(|p| *p + delta).collect())
+    }
+
+    /// Test if a point is on one of the segments
+    pub fn contains_point(&self, pos: Pos2) -> bool {
+        if !pos.x.is_finite() || !pos.y.is_finite() {
+            return false;
+        }
+        let tolerance = (self.stroke.width / 2.0).max(MARKUP_HIT_TOLERANCE);
+        path_distance(&self.points, pos) <= tolerance
+    }
+
+    /// Draw the polyline, mapping its points through `transform`
+    ///
+    /// The length label, if shown, is multiplied by `length_scale`.
+    pub fn render(&self, painter: &egui::Painter, transform: &egui::emath::TSTransform, length_scale: f32) {
+        let points: Vec<Pos2> = self.points.iter().map(|p| transform.mul_pos(*p)).collect();
+        self.stroke_style.paint_path(painter, points, false, self.stroke);
+        if self.show_length {
+            render_length(painter, transform, &self.points, length_scale, self.stroke.color);
+        }
+    }
+}
+
+impl Shape {
+    /// Render this shape to the given painter
+    pub fn render(&self, painter: &egui::Painter) {
+        match self {
+            Shape::Rectangle(rect) => rect.render(painter, &egui::emath::TSTransform::IDENTITY),
+            Shape::Circle(circle) => circle.render(painter, &egui::emath::TSTransform::IDENTITY),
+            Shape::Ellipse(ellipse) => ellipse.render(painter, &egui::emath::TSTransform::IDENTITY),
+            Shape::Polygon(poly) => poly.render(painter, &egui::emath::TSTransform::IDENTITY),
+            Shape::Line(line) => line.render(painter, &egui::emath::TSTransform::IDENTITY, 1.0),
+            Shape::Polyline(polyline) => polyline.render(painter, &egui::emath::TSTransform::IDENTITY, 1.0),
+            Shape::Arrow(arrow) => arrow.render(painter, &egui::emath::TSTransform::IDENTITY),
+            Shape::Callout(callout) => callout.render(painter, &egui::emath::TSTransform::IDENTITY),
+        }
+    }
+
+    /// Test if a point is inside this shape
+    pub fn contains_point(&self, pos: Pos2) -> bool {
+        match self {
+            Shape::Rectangle(rect) => rect.contains_point(pos),
+            Shape::Circle(circle) => circle.contains_point(pos),
+            Shape::Ellipse(ellipse) => ellipse.contains_point(pos),
+            Shape::Polygon(poly) => poly.contains_point(pos),
+            Shape::Line(line) => line.contains_point(pos),
+            Shape::Polyline(polyline) => polyline.contains_point(pos),
+            Shape::Arrow(arrow) => arrow.contains_point(pos),
+            Shape::Callout(callout) => callout.contains_point(pos),
+        }
+    }
+
+    /// Translate this shape by a delta vector
+    ///
+    /// # Errors
+    ///
+    /// Returns `ShapeError::InvalidCoordinate` if translation produces invalid coordinates.
+    pub fn translate(&mut self, delta: egui::Vec2) -> Result<(), ShapeError> {
+        match self {
+            Shape::Rectangle(rect) => rect.translate(delta),
+            Shape::Circle(circle) => circle.translate(delta),
+            Shape::Ellipse(ellipse) => ellipse.translate(delta),
+            Shape::Polygon(poly) => poly.translate(delta),
+            Shape::Line(line) => line.translate(delta),
+            Shape::Polyline(polyline) => polyline.translate(delta),
+            Shape::Arrow(arrow) => arrow.translate(delta),
+            Shape::Callout(callout) => callout.translate(delta),
+        }
+    }
+
+    /// Outline stroke of this shape
+    pub fn stroke(&self) -> Stroke {
+        match self {
+            Shape::Rectangle(rect) => rect.stroke,
+            Shape::Circle(circle) => circle.stroke,
+            Shape::Ellipse(ellipse) => ellipse.stroke,
+            Shape::Polygon(poly) => poly.stroke,
+            Shape::Line(line) => line.stroke,
+            Shape::Polyline(polyline) => polyline.stroke,
+            Shape::Arrow(arrow) => arrow.stroke,
+            Shape::Callout(callout) => callout.stroke,
+        }
+    }
+
+    /// Set the outline stroke of this shape
+    pub fn set_stroke(&mut self, stroke: Stroke) {
+        match self {
+            Shape::Rectangle(rect) => rect.stroke = stroke,
+            Shape::Circle(circle) => circle.stroke = stroke,
+            Shape::Ellipse(ellipse) => ellipse.stroke = stroke,
+            Shape::Polygon(poly) => poly.stroke = stroke,
+            Shape::Line(line) => line.stroke = stroke,
+            Shape::Polyline(polyline) => polyline.stroke = stroke,
+            Shape::Arrow(arrow) => arrow.stroke = stroke,
+            Shape::Callout(callout) => callout.stroke = stroke,
+        }
+    }
+
+    /// Whether this shape's outline is solid, dashed or dotted
+    pub fn stroke_style(&self) -> StrokeStyle {
+        match self {
+            Shape::Rectangle(rect) => rect.stroke_style,
+            Shape::Circle(circle) => circle.stroke_style,
+            Shape::Ellipse(ellipse) => ellipse.stroke_style,
+            Shape::Polygon(poly) => poly.stroke_style,
+            Shape::Line(line) => line.stroke_style,
+            Shape::Polyline(polyline) => polyline.stroke_style,
+            Shape::Arrow(arrow) => arrow.stroke_style,
+            Shape::Callout(callout) => callout.stroke_style,
+        }
+    }
+
+    /// Set whether this shape's outline is solid, dashed or dotted
+    pub fn set_stroke_style(&mut self, stroke_style: StrokeStyle) {
+        match self {
+            Shape::Rectangle(rect) => rect.stroke_style = stroke_style,
+            Shape::Circle(circle) => circle.stroke_style = stroke_style,
+            Shape::Ellipse(ellipse) => ellipse.stroke_style = stroke_style,
+            Shape::Polygon(poly) => poly.stroke_style = stroke_style,
+            Shape::Line(line) => line.stroke_style = stroke_style,
+            Shape::Polyline(polyline) => polyline.stroke_style = stroke_style,
+            Shape::Arrow(arrow) => arrow.stroke_style = stroke_style,
+            Shape::Callout(callout) => callout.stroke_style = stroke_style,
+        }
+    }
+
+    /// Set the fill color of this shape; lines, polylines and arrows have no fill
+    pub fn set_fill(&mut self, fill: Color32) {
+        match self {
+            Shape::Rectangle(rect) => rect.fill = fill,
+            Shape::Circle(circle) => circle.fill = fill,
+            Shape::Ellipse(ellipse) => ellipse.fill = fill,
+            Shape::Polygon(poly) => poly.fill = fill,
+            Shape::Callout(callout) => callout.fill = fill,
+            Shape::Line(_) | Shape::Polyline(_) | Shape::Arrow(_) => {}
+        }
+    }
+
+    /// Get the user-defined name of this shape
+    pub fn name(&self) -> &str {
+        match self {
+            Shape::Rectangle(rect) => &rect.name,
+            Shape::Circle(circle) => &circle.name,
+            Shape::Ellipse(ellipse) => &ellipse.name,
+            Shape::Polygon(poly) => &poly.name,
+            Shape::Line(line) => &line.name,
+            Shape::Polyline(polyline) => &polyline.name,
+            Shape::Arrow(arrow) => &arrow.name,
+            Shape::Callout(callout) => &callout.name,
+        }
+    }
+
+    /// Set the user-defined name of this shape
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        let name = name.into();
+        match self {
+            Shape::Rectangle(rect) => rect.name = name,
+            Shape::Circle(circle) => circle.name = name,
+            Shape::Ellipse(ellipse) => ellipse.name = name,
+            Shape::Polygon(poly) => poly.name = name,
+            Shape::Line(line) => line.name = name,
+            Shape::Polyline(polyline) => polyline.name = name,
+            Shape::Arrow(arrow) => arrow.name = name,
+            Shape::Callout(callout) => callout.name = name,
+        }
+    }
+
+    /// Whether this shape is flagged as covering sensitive data
+    pub fn is_sensitive(&self) -> bool {
+        match self {
+            Shape::Rectangle(rect) => rect.sensitive,
+            Shape::Circle(circle) => circle.sensitive,
+            Shape::Ellipse(ellipse) => ellipse.sensitive,
+            Shape::Polygon(poly) => poly.sensitive,
+            Shape::Line(line) => line.sensitive,
+            Shape::Polyline(polyline) => polyline.sensitive,
+            Shape::Arrow(arrow) => arrow.sensitive,
+            Shape::Callout(callout) => callout.sensitive,
+        }
+    }
+
+    /// Flag or unflag this shape as covering sensitive data
+    pub fn set_sensitive(&mut self, sensitive: bool) {
+        match self {
+            Shape::Rectangle(rect) => rect.sensitive = sensitive,
+            Shape::Circle(circle) => circle.sensitive = sensitive,
+            Shape::Ellipse(ellipse) => ellipse.sensitive = sensitive,
+            Shape::Polygon(poly) => poly.sensitive = sensitive,
+            Shape::Line(line) => line.sensitive = sensitive,
+            Shape::Polyline(polyline) => polyline.sensitive = sensitive,
+            Shape::Arrow(arrow) => arrow.sensitive = sensitive,
+            Shape::Callout(callout) => callout.sensitive = sensitive,
+        }
+    }
+
+    /// Value entered for the field this shape marks
+    pub fn value(&self) -> &str {
+        match self {
+            Shape::Rectangle(rect) => &rect.value,
+            Shape::Circle(circle) => &circle.value,
+            Shape::Ellipse(ellipse) => &ellipse.value,
+            Shape::Polygon(poly) => &poly.value,
+            Shape::Line(line) => &line.value,
+            Shape::Polyline(polyline) => &polyline.value,
+            Shape::Arrow(arrow) => &arrow.value,
+            Shape::Callout(callout) => &callout.value,
+        }
+    }
+
+    /// Set the value entered for the field this shape marks
+    ///
+    /// A value entered by a person is trusted, so this clears the confidence.
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.set_extracted_value(value, None);
+    }
+
+    /// Set a value read by OCR or another extractor, with its confidence (0-100)
+    pub fn set_extracted_value(&mut self, value: impl Into<String>, confidence: Option<f32>) {
+        let value = value.into();
+        match self {
+            Shape::Rectangle(rect) => (rect.value, rect.confidence) = (value, confidence),
+            Shape::Circle(circle) => (circle.value, circle.confidence) = (value, confidence),
+            Shape::Ellipse(ellipse) => (ellipse.value, ellipse.confidence) = (value, confidence),
+            Shape::Polygon(poly) => (poly.value, poly.confidence) = (value, confidence),
+            Shape::Line(line) => (line.value, line.confidence) = (value, confidence),
+            Shape::Polyline(polyline) => (polyline.value, polyline.confidence) = (value, confidence),
+            Shape::Arrow(arrow) => (arrow.value, arrow.confidence) = (value, confidence),
+            Shape::Callout(callout) => (callout.value, callout.confidence) = (value, confidence),
+        }
+    }
+
+    /// Confidence (0-100) of an extracted value, or `None` if a person entered or confirmed it
+    pub fn confidence(&self) -> Option<f32> {
+        match self {
+            Shape::Rectangle(rect) => rect.confidence,
+            Shape::Circle(circle) => circle.confidence,
+            Shape::Ellipse(ellipse) => ellipse.confidence,
+            Shape::Polygon(poly) => poly.confidence,
+            Shape::Line(line) => line.confidence,
+            Shape::Polyline(polyline) => polyline.confidence,
+            Shape::Arrow(arrow) => arrow.confidence,
+            Shape::Callout(callout) => callout.confidence,
+        }
+    }
+
+    /// Mark the value as confirmed by a person, clearing its confidence
+    pub fn confirm_value(&mut self) {
+        match self {
+            Shape::Rectangle(rect) => rect.confidence = None,
+            Shape::Circle(circle) => circle.confidence = None,
+            Shape::Ellipse(ellipse) => ellipse.confidence = None,
+            Shape::Polygon(poly) => poly.confidence = None,
+            Shape::Line(line) => line.confidence = None,
+            Shape::Polyline(polyline) => polyline.confidence = None,
+            Shape::Arrow(arrow) => arrow.confidence = None,
+            Shape::Callout(callout) => callout.confidence = None,
+        }
+    }
+
+    /// Requirements on the field's value
+    pub fn rule(&self) -> FieldRule {
+        match self {
+            Shape::Rectangle(rect) => rect.rule,
+            Shape::Circle(circle) => circle.rule,
+            Shape::Ellipse(ellipse) => ellipse.rule,
+            Shape::Polygon(poly) => poly.rule,
+            Shape::Line(line) => line.rule,
+            Shape::Polyline(polyline) => polyline.rule,
+            Shape::Arrow(arrow) => arrow.rule,
+            Shape::Callout(callout) => callout.rule,
+        }
+    }
+
+    /// Set the requirements on the field's value
+    pub fn set_rule(&mut self, rule: FieldRule) {
+        match self {
+            Shape::Rectangle(rect) => rect.rule = rule,
+            Shape::Circle(circle) => circle.rule = rule,
+            Shape::Ellipse(ellipse) => ellipse.rule = rule,
+            Shape::Polygon(poly) => poly.rule = rule,
+            Shape::Line(line) => line.rule = rule,
+            Shape::Polyline(polyline) => polyline.rule = rule,
+            Shape::Arrow(arrow) => arrow.rule = rule,
+            Shape::Callout(callout) => callout.rule = rule,
+        }
+    }
+
+    /// When the field applies, or `None` if it always does
+    pub fn condition(&self) -> Option<&FieldCondition> {
+        match self {
+            Shape::Rectangle(rect) => rect.condition.as_ref(),
+            Shape::Circle(circle) => circle.condition.as_ref(),
+            Shape::Ellipse(ellipse) => ellipse.condition.as_ref(),
+            Shape::Polygon(poly) => poly.condition.as_ref(),
+            Shape::Line(line) => line.condition.as_ref(),
+            Shape::Polyline(polyline) => polyline.condition.as_ref(),
+            Shape::Arrow(arrow) => arrow.condition.as_ref(),
+            Shape::Callout(callout) => callout.condition.as_ref(),
+        }
+    }
+
+    /// Make the field apply only when a condition holds, or always with `None`
+    pub fn set_condition(&mut self, condition: Option<FieldCondition>) {
+        match self {
+            Shape::Rectangle(rect) => rect.condition = condition,
+            Shape::Circle(circle) => circle.condition = condition,
+            Shape::Ellipse(ellipse) => ellipse.condition = condition,
+            Shape::Polygon(poly) => poly.condition = condition,
+            Shape::Line(line) => line.condition = condition,
+            Shape::Polyline(polyline) => polyline.condition = condition,
+            Shape::Arrow(arrow) => arrow.condition = condition,
+            Shape::Callout(callout) => callout.condition = condition,
+        }
+    }
+
+    /// Whether the object is left out of rendering, hit-testing and export
+    pub fn is_hidden(&self) -> bool {
+        match self {
+            Shape::Rectangle(rect) => rect.hidden,
+            Shape::Circle(circle) => circle.hidden,
+            Shape::Ellipse(ellipse) => ellipse.hidden,
+            Shape::Polygon(poly) => poly.hidden,
+            Shape::Line(line) => line.hidden,
+            Shape::Polyline(polyline) => polyline.hidden,
+            Shape::Arrow(arrow) => arrow.hidden,
+            Shape::Callout(callout) => callout.hidden,
+        }
+    }
+
+    /// Hide or show the object
+    pub fn set_hidden(&mut self, hidden: bool) {
+        match self {
+            Shape::Rectangle(rect) => rect.hidden = hidden,
+            Shape::Circle(circle) => circle.hidden = hidden,
+            Shape::Ellipse(ellipse) => ellipse.hidden = hidden,
+            Shape::Polygon(poly) => poly.hidden = hidden,
+            Shape::Line(line) => line.hidden = hidden,
+            Shape::Polyline(polyline) => polyline.hidden = hidden,
+            Shape::Arrow(arrow) => arrow.hidden = hidden,
+            Shape::Callout(callout) => callout.hidden = hidden,
+        }
+    }
+
+    /// Whether the object is protected from being picked on the canvas
+    pub fn is_locked(&self) -> bool {
+        match self {
+            Shape::Rectangle(rect) => rect.locked,
+            Shape::Circle(circle) => circle.locked,
+            Shape::Ellipse(ellipse) => ellipse.locked,
+            Shape::Polygon(poly) => poly.locked,
+            Shape::Line(line) => line.locked,
+            Shape::Polyline(polyline) => polyline.locked,
+            Shape::Arrow(arrow) => arrow.locked,
+            Shape::Callout(callout) => callout.locked,
+        }
+    }
+
+    /// Lock or unlock the object
+    pub fn set_locked(&mut self, locked: bool) {
+        match self {
+            Shape::Rectangle(rect) => rect.locked = locked,
+            Shape::Circle(circle) => circle.locked = locked,
+            Shape::Ellipse(ellipse) => ellipse.locked = locked,
+            Shape::Polygon(poly) => poly.locked = locked,
+            Shape::Line(line) => line.locked = locked,
+            Shape::Polyline(polyline) => polyline.locked = locked,
+            Shape::Arrow(arrow) => arrow.locked = locked,
+            Shape::Callout(callout) => callout.locked = locked,
+        }
+    }
+
+    /// Check the field's value against its rule
+    pub fn check_value(&self) -> Result<(), FieldIssue> {
+        self.rule().check(self.value())
+    }
+
+    /// Check the invariants the constructors enforce
+    ///
+    /// Shapes read from a project file bypass the constructors, so loaders
+    /// call this to reject geometry the canvas cannot work with.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same error kinds as the corresponding constructor.
+    pub fn validate(&self) -> Result<(), ShapeError> {
+        match self {
+            Shape::Rectangle(rect) => {
+                for corner in rect.corners() {
+                    pos2_to_coord(*corner)?;
+                }
+                validate_ring(&rect.polygon, 4)
+            }
+            Shape::Circle(circle) => {
+                pos2_to_coord(circle.center)?;
+                if !circle.radius.is_finite() || circle.radius <= 0.0 {
+                    return Err(ShapeError::new(
+                        ShapeErrorKind::InvalidRadius(circle.radius),
+                        line!(),
+                        file!(),
+                    ));
+                }
+                Ok(())
+            }
+            Shape::Ellipse(ellipse) => Ellipse::check_geometry(ellipse.center, ellipse.radii, ellipse.rotation),
+            Shape::Polygon(poly) => validate_ring(&poly.polygon, 3),
+            Shape::Line(line) => check_path(&line.points()),
+            Shape::Polyline(polyline) => check_path(&polyline.points),
+            Shape::Arrow(arrow) => Arrow::check_points(arrow.start, arrow.end),
+            Shape::Callout(callout) => {
+                pos2_to_coord(callout.anchor)?;
+                Callout::check_box(callout.text_box).map(|_| ())
+            }
+        }
+    }
+
+    /// Get the axis-aligned bounding box of this shape
+    pub fn bounding_rect(&self) -> egui::Rect {
+        match self {
+            Shape::Rectangle(rect) => egui::Rect::from_points(rect.corners()),
+            Shape::Circle(circle) => {
+                egui::Rect::from_center_size(circle.center, egui::Vec2::splat(circle.radius * 2.0))
+            }
+            Shape::Ellipse(ellipse) => ellipse.bounding_rect(),
+            Shape::Polygon(poly) => egui::Rect::from_points(&poly.to_egui_points()),
+            Shape::Line(line) => egui::Rect::from_points(&line.points()),
+            Shape::Polyline(polyline) => egui::Rect::from_points(&polyline.points),
+            Shape::Arrow(arrow) => {
+                let [tip, left, right] = arrow.head();
+                egui::Rect::from_points(&[arrow.start, tip, left, right])
+            }
+            Shape::Callout(callout) => callout.text_box.union(egui::Rect::from_pos(callout.anchor)),
+        }
+    }
+
+    /// Geometry of this shape for spatial tools
+    ///
+    /// Closed shapes become polygons, with circles and ellipses traced by
+    /// their outline points. Lines, polylines and arrows become line
+    /// strings, and callouts a collection of the text box and the point
+    /// the leader points at.
+    pub fn to_geometry(&self) -> Geometry<f64> {
+        let coord = |p: &Pos2| Coord {
+            x: p.x as f64,
+            y: p.y as f64,
+        };
+        let ring = |points: &[Pos2]| GeoPolygon::new(LineString::from_iter(points.iter().map(coord)), vec![]);
+        match self {
+            Shape::Rectangle(rect) => Geometry::Polygon(rect.polygon.clone()),
+            Shape::Circle(circle) => Geometry::Polygon(ring(&circle_points(circle.center, circle.radius))),
+            Shape::Ellipse(ellipse) => Geometry::Polygon(ring(&ellipse.to_egui_points())),
+            Shape::Polygon(poly) => Geometry::Polygon(poly.polygon.clone()),
+            Shape::Line(line) => Geometry::LineString(line.points().iter().map(coord).collect()),
+            Shape::Polyline(polyline) => Geometry::LineString(polyline.points.iter().map(coord).collect()),
+            Shape::Arrow(arrow) => Geometry::LineString([arrow.start, arrow.end].iter().map(coord).collect()),
+            Shape::Callout(callout) => {
+                let corners = [
+                    callout.text_box.left_top(),
+                    callout.text_box.right_top(),
+                    callout.text_box.right_bottom(),
+                    callout.text_box.left_bottom(),
+                ];
+                Geometry::GeometryCollection(GeometryCollection::from(vec![
+                    Geometry::Polygon(ring(&corners)),
+                    Geometry::Point(Point::from(coord(&callout.anchor))),
+                ]))
+            }
         }
     }
 }
@@ -0,0 +1,401 @@
+//! Scripted input for driving a canvas without a window
+//!
+//! [`CanvasSimulator`] runs a [`DrawingCanvas`] inside a headless
+//! [`egui::Context`] and feeds it synthetic pointer and keyboard events
+//! through [`egui::RawInput`], the same path a real backend uses. Every frame
+//! advances a fixed clock, so scripts replay identically on every run.
+//!
+//! Low-level pointer methods take screen positions. The shape-level helpers
+//! ([`CanvasSimulator::draw_rectangle`], [`CanvasSimulator::select_at`], ...)
+//! take canvas coordinates, the space shapes are stored in, and map them
+//! through the canvas's current zoom and pan.
+//!
+//! # Example
+//!
+//! ```
+//! use form_factor_drawing::{CanvasSimulator, DrawingCanvas};
+//! use egui::pos2;
+//!
+//! let mut canvas = DrawingCanvas::new();
+//! canvas.set_zoom(1.0);
+//!
+//! let mut sim = CanvasSimulator::new(canvas);
+//! sim.draw_rectangle(pos2(100.0, 100.0), pos2(200.0, 160.0));
+//! sim.rename_selected("invoice_number");
+//!
+//! assert_eq!(sim.canvas().shapes()[0].name(), "invoice_number");
+//! ```
+
+use crate::{CanvasPos, CoordinateMapper, DrawingCanvas, ToolMode};
+use egui::{Event, Key, Modifiers, PointerButton, Pos2, RawInput, Rect, Vec2};
+use tracing::{debug, instrument};
+
+/// Fixed time step between simulated frames, in seconds
+const FRAME_DT: f64 = 1.0 / 60.0;
+
+/// Number of intermediate pointer moves used for a drag
+const DRAG_STEPS: usize = 8;
+
+/// How long a drag holds the button before moving, in seconds
+///
+/// Longer than egui's maximum click duration, so the drag starts at the
+/// press position instead of wherever the pointer crossed the drag threshold.
+const DRAG_HOLD_SECS: f64 = 1.0;
+
+/// Headless driver that injects synthetic input into a canvas
+pub struct CanvasSimulator {
+    /// Context the canvas is rendered into
+    ctx: egui::Context,
+    /// Canvas under test
+    canvas: DrawingCanvas,
+    /// Size of the simulated screen in points
+    screen_size: Vec2,
+    /// Simulated time of the next frame
+    time: f64,
+    /// Screen area the canvas occupied in the last frame
+    canvas_rect: Rect,
+    /// Events delivered with the next frame
+    pending: Vec<Event>,
+    /// Screenshot requests not yet answered by [`CanvasSimulator::deliver_screenshot`]
+    screenshot_requests: Vec<egui::UserData>,
+    /// Cursor the last frame asked for
+    cursor: egui::CursorIcon,
+}
+
+impl CanvasSimulator {
+    /// Create a simulator with a 1280x800 screen
+    ///
+    /// Runs one frame so the canvas layout is known before the first input.
+    pub fn new(canvas: DrawingCanvas) -> Self {
+        let mut sim = Self {
+            ctx: egui::Context::default(),
+            canvas,
+            screen_size: Vec2::new(1280.0, 800.0),
+            time: 0.0,
+            canvas_rect: Rect::NOTHING,
+            pending: Vec::new(),
+            screenshot_requests: Vec::new(),
+            cursor: egui::CursorIcon::Default,
+        };
+        sim.step();
+        sim
+    }
+
+    /// Set the size of the simulated screen
+    pub fn with_screen_size(mut self, size: Vec2) -> Self {
+        self.screen_size = size;
+        self.step();
+        self
+    }
+
+    /// The canvas under test
+    pub fn canvas(&self) -> &DrawingCanvas {
+        &self.canvas
+    }
+
+    /// Mutable access to the canvas under test
+    pub fn canvas_mut(&mut self) -> &mut DrawingCanvas {
+        &mut self.canvas
+    }
+
+    /// Consume the simulator and return the canvas
+    pub fn into_canvas(self) -> DrawingCanvas {
+        self.canvas
+    }
+
+    /// The egui context the canvas is rendered into
+    pub fn ctx(&self) -> &egui::Context {
+        &self.ctx
+    }
+
+    /// Simulated time of the next frame, in seconds
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Queue a raw event for the next frame
+    pub fn push_event(&mut self, event: Event) {
+        self.pending.push(event);
+    }
+
+    /// Screen area the canvas occupied in the last frame
+    pub fn canvas_rect(&self) -> Rect {
+        self.canvas_rect
+    }
+
+    /// Map a canvas position to the screen using the current zoom and pan
+    pub fn to_screen(&self, canvas_pos: Pos2) -> Pos2 {
+        CoordinateMapper::new(self.canvas_rect, *self.canvas.zoom_level(), *self.canvas.pan_offset())
+            .canvas_to_screen(CanvasPos(canvas_pos))
+            .0
+    }
+
+    /// Run one frame, delivering all queued events
+    ///
+    /// The canvas fills the central panel and the inline properties panel
+    /// is shown on the right, as in the application layout.
+    #[instrument(skip(self), fields(time = self.time, events = self.pending.len()))]
+    pub fn step(&mut self) {
+        let input = RawInput {
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, self.screen_size)),
+            time: Some(self.time),
+            predicted_dt: FRAME_DT as f32,
+            focused: true,
+            events: std::mem::take(&mut self.pending),
+            ..Default::default()
+        };
+
+        let canvas = &mut self.canvas;
+        let canvas_rect = &mut self.canvas_rect;
+        let output = self.ctx.run(input, |ctx| {
+            // Fixed width keeps the canvas area stable as the panel content changes
+            egui::SidePanel::right("simulator_properties")
+                .exact_width(280.0)
+                .show(ctx, |ui| canvas.show_inline_properties(ui));
+            egui::CentralPanel::default().show(ctx, |ui| {
+                *canvas_rect = ui.available_rect_before_wrap();
+                canvas.ui(ui);
+            });
+        });
+
+        self.cursor = output.platform_output.cursor_icon;
+
+        // A real backend answers screenshot requests with the rendered window
+        let commands = output.viewport_output.into_values().flat_map(|viewport| viewport.commands);
+        self.screenshot_requests.extend(commands.filter_map(|command| match command {
+            egui::ViewportCommand::Screenshot(user_data) => Some(user_data),
+            _ => None,
+        }));
+
+        self.time += FRAME_DT;
+    }
+
+    /// Cursor the canvas asked for in the last frame
+    pub fn cursor(&self) -> egui::CursorIcon {
+        self.cursor
+    }
+
+    /// Whether the canvas is waiting for a screenshot of the window
+    pub fn screenshot_requested(&self) -> bool {
+        !self.screenshot_requests.is_empty()
+    }
+
+    /// Answer pending screenshot requests with `image` as the window contents and run a frame
+    ///
+    /// Without a renderer the simulator can't take screenshots itself, so
+    /// scripts supply the window image.
+    pub fn deliver_screenshot(&mut self, image: egui::ColorImage) {
+        let image = std::sync::Arc::new(image);
+        for user_data in std::mem::take(&mut self.screenshot_requests) {
+            self.push_event(Event::Screenshot {
+                viewport_id: egui::ViewportId::ROOT,
+                user_data,
+                image: image.clone(),
+            });
+        }
+        self.step();
+    }
+
+    /// Run several frames without new input
+    pub fn run_frames(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.step();
+        }
+    }
+
+    /// Let time pass without input, then run a frame
+    pub fn wait(&mut self, seconds: f64) {
+        self.time += seconds;
+        self.step();
+    }
+
+    /// Move the pointer to a screen position and run a frame
+    pub fn pointer_move(&mut self, pos: Pos2) {
+        self.push_event(Event::PointerMoved(pos));
+        self.step();
+    }
+
+    /// Press the primary button at a screen position and run a frame
+    pub fn pointer_down(&mut self, pos: Pos2) {
+        self.push_event(Event::PointerMoved(pos));
+        self.push_event(Self::button(pos, true));
+        self.step();
+    }
+
+    /// Release the primary button at a screen position and run a frame
+    pub fn pointer_up(&mut self, pos: Pos2) {
+        self.push_event(Event::PointerMoved(pos));
+        self.push_event(Self::button(pos, false));
+        self.step();
+    }
+
+    /// Click the primary button at a screen position
+    pub fn click(&mut self, pos: Pos2) {
+        self.pointer_move(pos);
+        self.pointer_down(pos);
+        self.pointer_up(pos);
+        // Let the click's effects (e.g. focus requests) settle
+        self.step();
+    }
+
+    /// Double-click the primary button at a screen position
+    pub fn double_click(&mut self, pos: Pos2) {
+        self.pointer_move(pos);
+        for _ in 0..2 {
+            self.pointer_down(pos);
+            self.pointer_up(pos);
+        }
+        self.step();
+    }
+
+    /// Drag with the primary button between two screen positions
+    pub fn drag(&mut self, from: Pos2, to: Pos2) {
+        self.drag_with(PointerButton::Primary, from, to);
+    }
+
+    /// Drag with any button between two screen positions
+    pub fn drag_with(&mut self, button: PointerButton, from: Pos2, to: Pos2) {
+        self.pointer_move(from);
+        self.push_event(Self::button_event(button, from, true));
+        self.step();
+        self.wait(DRAG_HOLD_SECS);
+        for i in 1..=DRAG_STEPS {
+            let t = i as f32 / DRAG_STEPS as f32;
+            self.pointer_move(from.lerp(to, t));
+        }
+        self.push_event(Event::PointerMoved(to));
+        self.push_event(Self::button_event(button, to, false));
+        self.step();
+        self.step();
+    }
+
+    /// Press and release a key with the given modifiers
+    pub fn key_press(&mut self, key: Key, modifiers: Modifiers) {
+        for pressed in [true, false] {
+            self.push_event(Event::Key {
+                key,
+                physical_key: None,
+                pressed,
+                repeat: false,
+                modifiers,
+            });
+        }
+        self.step();
+    }
+
+    /// Type text into the focused widget
+    pub fn type_text(&mut self, text: &str) {
+        self.push_event(Event::Text(text.to_string()));
+        self.step();
+    }
+
+    /// Draw a rectangle by dragging between two canvas corners
+    #[instrument(skip(self))]
+    pub fn draw_rectangle(&mut self, from: Pos2, to: Pos2) {
+        self.canvas.set_tool(ToolMode::Rectangle);
+        self.drag(self.to_screen(from), self.to_screen(to));
+        debug!(
+            shapes = self.canvas.shape_count(),
+            "Simulated rectangle drawn"
+        );
+    }
+
+    /// Draw a circle by dragging from its canvas center to its edge
+    #[instrument(skip(self))]
+    pub fn draw_circle(&mut self, center: Pos2, edge: Pos2) {
+        self.canvas.set_tool(ToolMode::Circle);
+        self.drag(self.to_screen(center), self.to_screen(edge));
+        debug!(shapes = self.canvas.shape_count(), "Simulated circle drawn");
+    }
+
+    /// Draw an ellipse by dragging between opposite canvas corners of its bounding box
+    #[instrument(skip(self))]
+    pub fn draw_ellipse(&mut self, from: Pos2, to: Pos2) {
+        self.canvas.set_tool(ToolMode::Ellipse);
+        self.drag(self.to_screen(from), self.to_screen(to));
+        debug!(shapes = self.canvas.shape_count(), "Simulated ellipse drawn");
+    }
+
+    /// Draw a straight line by dragging between its canvas ends
+    #[instrument(skip(self))]
+    pub fn draw_line(&mut self, from: Pos2, to: Pos2) {
+        self.canvas.set_tool(ToolMode::Line);
+        self.drag(self.to_screen(from), self.to_screen(to));
+        debug!(shapes = self.canvas.shape_count(), "Simulated line drawn");
+    }
+
+    /// Draw a polyline by dragging through canvas points in order
+    #[instrument(skip(self))]
+    pub fn draw_polyline(&mut self, points: &[Pos2]) {
+        let Some((first, rest)) = points.split_first() else {
+            return;
+        };
+        self.canvas.set_tool(ToolMode::Polyline);
+        let mut from = self.to_screen(*first);
+        self.pointer_move(from);
+        self.pointer_down(from);
+        self.wait(DRAG_HOLD_SECS);
+        for point in rest {
+            let to = self.to_screen(*point);
+            for i in 1..=DRAG_STEPS {
+                self.pointer_move(from.lerp(to, i as f32 / DRAG_STEPS as f32));
+            }
+            from = to;
+        }
+        self.pointer_up(from);
+        self.step();
+        debug!(shapes = self.canvas.shape_count(), "Simulated polyline drawn");
+    }
+
+    /// Draw a highlighter stroke by dragging between two canvas positions
+    #[instrument(skip(self))]
+    pub fn draw_highlight(&mut self, from: Pos2, to: Pos2) {
+        self.canvas.set_tool(ToolMode::Highlighter);
+        self.drag(self.to_screen(from), self.to_screen(to));
+        debug!(
+            highlights = self.canvas.highlights().len(),
+            "Simulated highlight drawn"
+        );
+    }
+
+    /// Click a canvas position with the stamp tool
+    #[instrument(skip(self))]
+    pub fn stamp_at(&mut self, pos: Pos2) {
+        self.canvas.set_tool(ToolMode::Stamp);
+        self.click(self.to_screen(pos));
+        debug!(stamps = self.canvas.stamps().len(), "Simulated stamp placed");
+    }
+
+    /// Click a canvas position with the select tool
+    #[instrument(skip(self))]
+    pub fn select_at(&mut self, pos: Pos2) {
+        self.canvas.set_tool(ToolMode::Select);
+        self.click(self.to_screen(pos));
+        debug!(selected = ?self.canvas.selected_shape(), "Simulated selection");
+    }
+
+    /// Replace the text of the focused name field and press Enter
+    ///
+    /// Selecting or drawing a shape focuses its name field, so this renames
+    /// the selected shape the way a user would.
+    #[instrument(skip(self))]
+    pub fn rename_selected(&mut self, name: &str) {
+        self.key_press(Key::A, Modifiers::COMMAND);
+        self.type_text(name);
+        self.key_press(Key::Enter, Modifiers::NONE);
+    }
+
+    fn button(pos: Pos2, pressed: bool) -> Event {
+        Self::button_event(PointerButton::Primary, pos, pressed)
+    }
+
+    /// Press or release of a pointer button
+    fn button_event(button: PointerButton, pos: Pos2, pressed: bool) -> Event {
+        Event::PointerButton {
+            pos,
+            button,
+            pressed,
+            modifiers: Modifiers::NONE,
+        }
+    }
+}
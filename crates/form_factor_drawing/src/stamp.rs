@@ -0,0 +1,180 @@
+//! Stamp symbols for document review
+//!
+//! Stamps mark a form with a verdict such as APPROVED or PAID, the way a
+//! rubber stamp would on paper. The stamp library in the canvas settings
+//! holds the symbols the Stamp tool can place: text drawn in a bordered box,
+//! and images imported by the user. New libraries start with Approved,
+//! Rejected and Paid.
+//!
+//! A placed [`Stamp`] keeps a copy of its symbol, so a project looks the
+//! same after the symbol is edited or removed from the library.
+
+use egui::{Color32, Pos2, Rect, Vec2};
+use form_factor_core::{IoError, IoOperation};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tracing::{debug, instrument};
+
+/// Height of newly placed stamps in canvas units
+pub const STAMP_HEIGHT: f32 = 40.0;
+
+/// Width of one character of a text stamp, as a share of its height
+const TEXT_CHAR_WIDTH: f32 = 0.45;
+
+/// Space around the text of a text stamp, as a share of its height
+const TEXT_PADDING: f32 = 0.4;
+
+/// Font size of a text stamp, as a share of its height
+pub(crate) const STAMP_FONT_RATIO: f32 = 0.55;
+
+/// Border width of a text stamp, as a share of its height
+pub(crate) const STAMP_BORDER_RATIO: f32 = 0.07;
+
+/// What a stamp symbol draws
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum StampSource {
+    /// Bold text in a bordered box
+    Text {
+        /// Text of the stamp
+        text: String,
+        /// Color of the text and border
+        color: Color32,
+    },
+    /// Image file imported by the user
+    Image {
+        /// Path of the image file
+        path: String,
+    },
+}
+
+/// A symbol the Stamp tool can place
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StampSymbol {
+    /// Name shown in the stamp picker, unique within a library
+    pub name: String,
+    /// What the stamp draws
+    pub source: StampSource,
+}
+
+impl StampSymbol {
+    /// Text stamp drawn in `color`
+    pub fn text(name: impl Into<String>, text: impl Into<String>, color: Color32) -> Self {
+        Self {
+            name: name.into(),
+            source: StampSource::Text {
+                text: text.into(),
+                color,
+            },
+        }
+    }
+
+    /// Image stamp read from `path`
+    pub fn image(name: impl Into<String>, path: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            source: StampSource::Image { path: path.into() },
+        }
+    }
+
+    /// Width over height of the placed stamp
+    ///
+    /// Image stamps keep the proportions of their image, or are square if
+    /// it cannot be read.
+    pub fn aspect_ratio(&self) -> f32 {
+        match &self.source {
+            StampSource::Text { text, .. } => text.chars().count() as f32 * TEXT_CHAR_WIDTH + 2.0 * TEXT_PADDING,
+            StampSource::Image { path } => match image::image_dimensions(path) {
+                Ok((width, height)) if height > 0 => width as f32 / height as f32,
+                _ => 1.0,
+            },
+        }
+    }
+}
+
+/// Stamp symbols available to the Stamp tool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StampLibrary {
+    /// Symbols in picker order
+    pub symbols: Vec<StampSymbol>,
+}
+
+impl Default for StampLibrary {
+    fn default() -> Self {
+        Self {
+            symbols: vec![
+                StampSymbol::text("Approved", "APPROVED", Color32::from_rgb(0, 140, 60)),
+                StampSymbol::text("Rejected", "REJECTED", Color32::from_rgb(200, 30, 30)),
+                StampSymbol::text("Paid", "PAID", Color32::from_rgb(30, 80, 200)),
+            ],
+        }
+    }
+}
+
+impl StampLibrary {
+    /// Symbol with the given name
+    pub fn get(&self, name: &str) -> Option<&StampSymbol> {
+        self.symbols.iter().find(|symbol| symbol.name == name)
+    }
+
+    /// Add a symbol, replacing any symbol of the same name in place
+    pub fn add(&mut self, symbol: StampSymbol) {
+        match self.symbols.iter_mut().find(|existing| existing.name == symbol.name) {
+            Some(existing) => *existing = symbol,
+            None => self.symbols.push(symbol),
+        }
+    }
+
+    /// Remove a symbol by name
+    pub fn remove(&mut self, name: &str) -> Option<StampSymbol> {
+        let idx = self.symbols.iter().position(|symbol| symbol.name == name)?;
+        Some(self.symbols.remove(idx))
+    }
+
+    /// Add an image file as a symbol named after the file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file is not a readable image.
+    #[instrument(skip(self))]
+    pub fn import_image(&mut self, path: &Path) -> Result<StampSymbol, IoError> {
+        let path_str = path.to_string_lossy().to_string();
+        image::image_dimensions(path).map_err(|e| {
+            IoError::new(
+                format!("Failed to read stamp image: {}", e),
+                &path_str,
+                IoOperation::Read,
+                line!(),
+                file!(),
+            )
+        })?;
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| path_str.clone());
+        debug!(%name, "Imported stamp image");
+        let symbol = StampSymbol::image(name, path_str);
+        self.add(symbol.clone());
+        Ok(symbol)
+    }
+}
+
+/// A stamp placed on the canvas
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stamp {
+    /// Symbol the stamp draws
+    pub symbol: StampSymbol,
+    /// Area covered by the stamp, in canvas coordinates
+    pub bounds: Rect,
+}
+
+impl Stamp {
+    /// Stamp of `symbol` centered on `center`, [`STAMP_HEIGHT`] tall
+    pub fn new(symbol: StampSymbol, center: Pos2) -> Self {
+        let size = Vec2::new(STAMP_HEIGHT * symbol.aspect_ratio(), STAMP_HEIGHT);
+        Self {
+            symbol,
+            bounds: Rect::from_center_size(center, size),
+        }
+    }
+}
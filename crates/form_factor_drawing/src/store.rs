@@ -0,0 +1,1078 @@
+//! Pluggable storage for projects and their instances
+//!
+//! Projects and the filled-in instances of each project are kept in a
+//! [`ProjectStore`], so a deployment can keep them on a local disk or in
+//! S3-compatible object storage without mapping network drives. A store
+//! only has to read, write, list and delete objects under string keys; the
+//! project and instance operations are built on those:
+//!
+//! - `projects/{project}.json`: a project
+//! - `instances/{project}/{instance}.json`: an instance of a project
+//! - `collab/{session}/...`: the operations and presence of each
+//!   collaborator in a collaboration session, see
+//!   [`StoreSyncTransport`](crate::StoreSyncTransport)
+//!
+//! [`FileStore`] keeps objects as files under a root directory.
+//! [`S3Store`] keeps them in a bucket, signing each request with AWS
+//! Signature Version 4 and sending it through an [`HttpClient`], so tests
+//! and other transports can stand in for the network. Which store is used
+//! is chosen by [`StorageSettings`]. S3 credentials are read from the
+//! environment rather than the settings file, to keep secrets out of it.
+//!
+//! Operators sharing a store can save the same project. Every object has a
+//! version, a hash of its contents on disk and its ETag in S3, so a project
+//! can be opened with its version and saved back only if no one else wrote
+//! it since; S3 checks this itself with `If-Match` and `If-None-Match`.
+
+use crate::{DrawingCanvas, config_dir, utc::UtcDateTime};
+use hmac::{Hmac, Mac};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{debug, instrument};
+
+/// Key prefix of projects
+const PROJECTS_PREFIX: &str = "projects/";
+
+/// Key prefix of instances
+const INSTANCES_PREFIX: &str = "instances/";
+
+/// Extension of stored projects and instances
+const PROJECT_EXTENSION: &str = ".json";
+
+/// Times a conditional write to a [`FileStore`] tries to take an object's lock
+const LOCK_ATTEMPTS: u32 = 50;
+
+/// Wait between attempts to take a [`FileStore`] lock
+const LOCK_RETRY: std::time::Duration = std::time::Duration::from_millis(20);
+
+/// Age past which a [`FileStore`] lock is taken to be left over from a crash
+const STALE_LOCK: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Numbers the temporary files of [`FileStore`] writes within this process
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Kinds of errors that can occur while using a store
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreErrorKind {
+    /// No object is stored under the key
+    NotFound(String),
+    /// A project or instance name that can't be used in a key
+    InvalidName(String),
+    /// The store's disk could not be read or written
+    Io(String),
+    /// The storage service refused the request or could not be reached
+    Backend(String),
+    /// A stored project could not be read or written
+    Format(String),
+    /// The store is not configured correctly
+    Config(String),
+    /// The object was written or removed by someone else since it was read
+    Conflict(String),
+}
+
+impl std::fmt::Display for StoreErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoreErrorKind::NotFound(key) => write!(f, "Nothing stored under {}", key),
+            StoreErrorKind::InvalidName(name) => write!(f, "Invalid project or instance name: {:?}", name),
+            StoreErrorKind::Io(msg) => write!(f, "Storage I/O error: {}", msg),
+            StoreErrorKind::Backend(msg) => write!(f, "Storage service error: {}", msg),
+            StoreErrorKind::Format(msg) => write!(f, "Stored project error: {}", msg),
+            StoreErrorKind::Config(msg) => write!(f, "Storage configuration error: {}", msg),
+            StoreErrorKind::Conflict(key) => write!(f, "{} was changed by someone else since it was read", key),
+        }
+    }
+}
+
+/// Error type for store operations
+#[derive(Debug, Clone)]
+pub struct StoreError {
+    /// The kind of error that occurred
+    pub kind: StoreErrorKind,
+    /// Line number where the error was created
+    pub line: u32,
+    /// File where the error was created
+    pub file: &'static str,
+}
+
+impl StoreError {
+    /// Create a new store error
+    pub fn new(kind: StoreErrorKind, line: u32, file: &'static str) -> Self {
+        Self { kind, line, file }
+    }
+}
+
+impl std::fmt::Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Store Error: {} at line {} in {}", self.kind, self.line, self.file)
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+/// Storage for projects and their instances
+///
+/// Implementors provide the object operations; the project and instance
+/// operations are provided on top of them.
+pub trait ProjectStore {
+    /// Keys of every object whose key starts with `prefix`, sorted
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be listed.
+    fn list(&self, prefix: &str) -> Result<Vec<String>, StoreError>;
+
+    /// Contents of the object under `key`
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`StoreErrorKind::NotFound`] error if nothing is stored
+    /// under the key, or another error if it cannot be read.
+    fn read(&self, key: &str) -> Result<Vec<u8>, StoreError>;
+
+    /// Store `bytes` under `key`, replacing any object already there
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the object cannot be written.
+    fn write(&self, key: &str, bytes: &[u8]) -> Result<(), StoreError>;
+
+    /// Contents of the object under `key`, with its version
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`StoreErrorKind::NotFound`] error if nothing is stored
+    /// under the key, or another error if it cannot be read.
+    fn read_versioned(&self, key: &str) -> Result<(Vec<u8>, String), StoreError>;
+
+    /// Store `bytes` under `key` if the object is still at `version`, returning its new version
+    ///
+    /// A `version` of `None` writes only if nothing is stored under the key.
+    /// The check and the write are one step: of two writers at the same
+    /// version, only one succeeds.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`StoreErrorKind::Conflict`] error, writing nothing, if the
+    /// object is at another version, or another error if it cannot be written.
+    fn write_if(&self, key: &str, bytes: &[u8], version: Option<&str>) -> Result<String, StoreError>;
+
+    /// Remove the object under `key`; removing a missing object succeeds
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the object cannot be removed.
+    fn delete(&self, key: &str) -> Result<(), StoreError>;
+
+    /// Names of the stored projects, sorted
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be listed.
+    fn list_projects(&self) -> Result<Vec<String>, StoreError> {
+        Ok(names_under(&self.list(PROJECTS_PREFIX)?, PROJECTS_PREFIX))
+    }
+
+    /// Open a stored project
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project is missing or can't be read.
+    fn open_project(&self, project: &str) -> Result<DrawingCanvas, StoreError> {
+        open(self, &project_key(project)?)
+    }
+
+    /// Store a project in the encoding and compression chosen in its canvas settings
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is invalid or the project can't be written.
+    fn save_project(&self, project: &str, canvas: &DrawingCanvas) -> Result<(), StoreError> {
+        save(self, &project_key(project)?, canvas)
+    }
+
+    /// Open a stored project with its version, to save it back with [`ProjectStore::save_project_if`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project is missing or can't be read.
+    fn open_project_versioned(&self, project: &str) -> Result<(DrawingCanvas, String), StoreError> {
+        open_versioned(self, &project_key(project)?)
+    }
+
+    /// Bytes of a stored project with their version, for a project that may be password protected
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is invalid or the project is missing or can't be read.
+    fn read_project_versioned(&self, project: &str) -> Result<(Vec<u8>, String), StoreError> {
+        self.read_versioned(&project_key(project)?)
+    }
+
+    /// Store a project if no one else saved it since it was opened at `version`, returning the new version
+    ///
+    /// A `version` of `None` stores a new project, failing if one of that
+    /// name already exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`StoreErrorKind::Conflict`] error if the stored project is
+    /// at another version, or another error if it can't be written.
+    fn save_project_if(
+        &self,
+        project: &str,
+        canvas: &DrawingCanvas,
+        version: Option<&str>,
+    ) -> Result<String, StoreError> {
+        save_if(self, &project_key(project)?, canvas, version)
+    }
+
+    /// Remove a project and all of its instances
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is invalid or anything can't be removed.
+    fn delete_project(&self, project: &str) -> Result<(), StoreError> {
+        let key = project_key(project)?;
+        for instance in self.list(&instances_prefix(project)?)? {
+            self.delete(&instance)?;
+        }
+        self.delete(&key)?;
+        debug!(project, "Deleted stored project");
+        Ok(())
+    }
+
+    /// Names of the stored instances of a project, sorted
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is invalid or the store cannot be listed.
+    fn list_instances(&self, project: &str) -> Result<Vec<String>, StoreError> {
+        let prefix = instances_prefix(project)?;
+        Ok(names_under(&self.list(&prefix)?, &prefix))
+    }
+
+    /// Open a stored instance of a project
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the instance is missing or can't be read.
+    fn open_instance(&self, project: &str, instance: &str) -> Result<DrawingCanvas, StoreError> {
+        open(self, &instance_key(project, instance)?)
+    }
+
+    /// Store an instance of a project
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a name is invalid or the instance can't be written.
+    fn save_instance(&self, project: &str, instance: &str, canvas: &DrawingCanvas) -> Result<(), StoreError> {
+        save(self, &instance_key(project, instance)?, canvas)
+    }
+
+    /// Open a stored instance with its version, to save it back with [`ProjectStore::save_instance_if`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the instance is missing or can't be read.
+    fn open_instance_versioned(&self, project: &str, instance: &str) -> Result<(DrawingCanvas, String), StoreError> {
+        open_versioned(self, &instance_key(project, instance)?)
+    }
+
+    /// Store an instance if no one else saved it since it was opened at `version`, returning the new version
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`StoreErrorKind::Conflict`] error if the stored instance is
+    /// at another version, or another error if it can't be written.
+    fn save_instance_if(
+        &self,
+        project: &str,
+        instance: &str,
+        canvas: &DrawingCanvas,
+        version: Option<&str>,
+    ) -> Result<String, StoreError> {
+        save_if(self, &instance_key(project, instance)?, canvas, version)
+    }
+
+    /// Remove a stored instance of a project
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a name is invalid or the instance can't be removed.
+    fn delete_instance(&self, project: &str, instance: &str) -> Result<(), StoreError> {
+        self.delete(&instance_key(project, instance)?)
+    }
+}
+
+/// A project or instance name, if it can be used as a single key segment
+pub(crate) fn checked_name(name: &str) -> Result<&str, StoreError> {
+    let invalid = name.is_empty()
+        || name == "."
+        || name == ".."
+        || name.trim() != name
+        || name.contains(['/', '\\'])
+        || name.chars().any(char::is_control);
+    if invalid {
+        return Err(StoreError::new(StoreErrorKind::InvalidName(name.to_string()), line!(), file!()));
+    }
+    Ok(name)
+}
+
+fn project_key(project: &str) -> Result<String, StoreError> {
+    Ok(format!("{}{}{}", PROJECTS_PREFIX, checked_name(project)?, PROJECT_EXTENSION))
+}
+
+fn instances_prefix(project: &str) -> Result<String, StoreError> {
+    Ok(format!("{}{}/", INSTANCES_PREFIX, checked_name(project)?))
+}
+
+fn instance_key(project: &str, instance: &str) -> Result<String, StoreError> {
+    Ok(format!("{}{}{}", instances_prefix(project)?, checked_name(instance)?, PROJECT_EXTENSION))
+}
+
+/// Names of the projects or instances stored directly under `prefix`
+fn names_under(keys: &[String], prefix: &str) -> Vec<String> {
+    let mut names: Vec<String> = keys
+        .iter()
+        .filter_map(|key| key.strip_prefix(prefix)?.strip_suffix(PROJECT_EXTENSION))
+        .filter(|name| !name.contains('/'))
+        .map(str::to_string)
+        .collect();
+    names.sort();
+    names
+}
+
+fn open<S: ProjectStore + ?Sized>(store: &S, key: &str) -> Result<DrawingCanvas, StoreError> {
+    parse(key, &store.read(key)?)
+}
+
+fn open_versioned<S: ProjectStore + ?Sized>(store: &S, key: &str) -> Result<(DrawingCanvas, String), StoreError> {
+    let (bytes, version) = store.read_versioned(key)?;
+    Ok((parse(key, &bytes)?, version))
+}
+
+fn parse(key: &str, bytes: &[u8]) -> Result<DrawingCanvas, StoreError> {
+    DrawingCanvas::from_bytes(bytes)
+        .map_err(|e| StoreError::new(StoreErrorKind::Format(format!("{}: {}", key, e)), line!(), file!()))
+}
+
+fn save<S: ProjectStore + ?Sized>(store: &S, key: &str, canvas: &DrawingCanvas) -> Result<(), StoreError> {
+    let bytes = serialize(key, canvas)?;
+    store.write(key, &bytes)?;
+    debug!(key, len = bytes.len(), "Stored project");
+    Ok(())
+}
+
+fn save_if<S: ProjectStore + ?Sized>(
+    store: &S,
+    key: &str,
+    canvas: &DrawingCanvas,
+    version: Option<&str>,
+) -> Result<String, StoreError> {
+    let bytes = serialize(key, canvas)?;
+    let version = store.write_if(key, &bytes, version)?;
+    debug!(key, len = bytes.len(), %version, "Stored project");
+    Ok(version)
+}
+
+fn serialize(key: &str, canvas: &DrawingCanvas) -> Result<Vec<u8>, StoreError> {
+    canvas
+        .project_bytes()
+        .map_err(|e| StoreError::new(StoreErrorKind::Format(format!("{}: {}", key, e)), line!(), file!()))
+}
+
+fn conflict(key: &str, line: u32) -> StoreError {
+    StoreError::new(StoreErrorKind::Conflict(key.to_string()), line, file!())
+}
+
+// ============================================================================
+// Filesystem
+// ============================================================================
+
+/// Store keeping objects as files under a root directory
+///
+/// Keys map to paths below the root, so the directory can be browsed and
+/// backed up like any other. Writes go to a temporary file that is then
+/// renamed over the old one, so readers never see half a project. An
+/// object's version is a SHA-256 hash of the file. A conditional write
+/// creates a lock file next to the object and holds it while it checks
+/// the version and renames, so two operators on a network share can't
+/// both pass the check; a lock left behind by a crash is broken after
+/// thirty seconds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStore {
+    root: PathBuf,
+}
+
+impl FileStore {
+    /// Store under `root`, which is created when first written to
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Directory the objects are stored under
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        key.split('/').fold(self.root.clone(), |path, segment| path.join(segment))
+    }
+
+    fn io_error(e: std::io::Error, path: &Path, line: u32) -> StoreError {
+        let kind = match e.kind() {
+            std::io::ErrorKind::NotFound => StoreErrorKind::NotFound(path.display().to_string()),
+            _ => StoreErrorKind::Io(format!("{}: {}", path.display(), e)),
+        };
+        StoreError::new(kind, line, file!())
+    }
+}
+
+impl FileStore {
+    /// Take the lock on `key` for a conditional write, released when dropped
+    ///
+    /// Fails with a [`StoreErrorKind::Conflict`] if another writer holds it
+    /// for longer than the retries wait.
+    fn lock(&self, key: &str) -> Result<FileLock, StoreError> {
+        let path = self.path(key).with_extension("lock");
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| Self::io_error(e, parent, line!()))?;
+        }
+        for _ in 0..LOCK_ATTEMPTS {
+            match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(FileLock { path }),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let stale = std::fs::metadata(&path)
+                        .and_then(|meta| meta.modified())
+                        .is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age > STALE_LOCK));
+                    if stale {
+                        debug!(path = %path.display(), "Breaking stale store lock");
+                        let _ = std::fs::remove_file(&path);
+                    } else {
+                        std::thread::sleep(LOCK_RETRY);
+                    }
+                }
+                Err(e) => return Err(Self::io_error(e, &path, line!())),
+            }
+        }
+        debug!(key, "Store object is locked by another writer");
+        Err(conflict(key, line!()))
+    }
+}
+
+/// Lock file held by a conditional write to a [`FileStore`]
+#[derive(Debug)]
+struct FileLock {
+    path: PathBuf,
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+impl ProjectStore for FileStore {
+    fn list(&self, prefix: &str) -> Result<Vec<String>, StoreError> {
+        fn walk(dir: &Path, key: &str, keys: &mut Vec<String>) -> std::io::Result<()> {
+            for entry in std::fs::read_dir(dir)? {
+                let entry = entry?;
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let child = if key.is_empty() { name } else { format!("{}/{}", key, name) };
+                if entry.file_type()?.is_dir() {
+                    walk(&entry.path(), &child, keys)?;
+                } else if !child.ends_with(".tmp") && !child.ends_with(".lock") {
+                    keys.push(child);
+                }
+            }
+            Ok(())
+        }
+
+        // Only the directory holding the prefix can contain matching keys
+        let dir = prefix.rsplit_once('/').map_or("", |(dir, _)| dir);
+        let start = if dir.is_empty() { self.root.clone() } else { self.path(dir) };
+        let mut keys = Vec::new();
+        match walk(&start, dir, &mut keys) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(Self::io_error(e, &start, line!())),
+        }
+        keys.retain(|key| key.starts_with(prefix));
+        keys.sort();
+        Ok(keys)
+    }
+
+    fn read(&self, key: &str) -> Result<Vec<u8>, StoreError> {
+        let path = self.path(key);
+        std::fs::read(&path).map_err(|e| Self::io_error(e, &path, line!()))
+    }
+
+    fn write(&self, key: &str, bytes: &[u8]) -> Result<(), StoreError> {
+        let path = self.path(key);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| Self::io_error(e, parent, line!()))?;
+        }
+        // A name of its own, so concurrent writers of one key don't share a temporary file
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let count = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let temp = path.with_file_name(format!("{}.{}-{}.tmp", name, std::process::id(), count));
+        std::fs::write(&temp, bytes).map_err(|e| Self::io_error(e, &temp, line!()))?;
+        std::fs::rename(&temp, &path).map_err(|e| Self::io_error(e, &path, line!()))
+    }
+
+    fn read_versioned(&self, key: &str) -> Result<(Vec<u8>, String), StoreError> {
+        let bytes = self.read(key)?;
+        let version = hex(&Sha256::digest(&bytes));
+        Ok((bytes, version))
+    }
+
+    fn write_if(&self, key: &str, bytes: &[u8], version: Option<&str>) -> Result<String, StoreError> {
+        let _lock = self.lock(key)?;
+        let current = match self.read_versioned(key) {
+            Ok((_, current)) => Some(current),
+            Err(e) if matches!(e.kind, StoreErrorKind::NotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+        if current.as_deref() != version {
+            return Err(conflict(key, line!()));
+        }
+        self.write(key, bytes)?;
+        Ok(hex(&Sha256::digest(bytes)))
+    }
+
+    fn delete(&self, key: &str) -> Result<(), StoreError> {
+        let path = self.path(key);
+        match std::fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(Self::io_error(e, &path, line!())),
+        }
+        // Tidy directories left empty, such as a deleted project's instances
+        let mut dir = path.parent();
+        while let Some(parent) = dir
+            && parent != self.root
+            && std::fs::remove_dir(parent).is_ok()
+        {
+            dir = parent.parent();
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// S3
+// ============================================================================
+
+/// An HTTP request to a storage service
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    /// Method, like `GET` or `PUT`
+    pub method: &'static str,
+    /// Full URL, with the path and query already percent-encoded
+    pub url: String,
+    /// Headers, including the signature once signed
+    pub headers: Vec<(String, String)>,
+    /// Body
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    /// Request without headers or body
+    pub fn new(method: &'static str, url: impl Into<String>) -> Self {
+        Self {
+            method,
+            url: url.into(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// Value of a header, ignoring the case of its name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A storage service's response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    /// Status code
+    pub status: u16,
+    /// Headers, like the `ETag` of a stored object
+    pub headers: Vec<(String, String)>,
+    /// Body
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Value of a header, ignoring the case of its name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Sends requests to a storage service
+pub trait HttpClient {
+    /// Send a request and return the response, whatever its status
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the service could not be reached.
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, StoreError>;
+}
+
+/// [`HttpClient`] sending requests over the network
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct UreqClient {
+    agent: ureq::Agent,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl UreqClient {
+    /// Client with a fresh connection pool
+    pub fn new() -> Self {
+        Self { agent: ureq::Agent::new() }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for UreqClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl HttpClient for UreqClient {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, StoreError> {
+        use std::io::Read;
+
+        let call = request
+            .headers
+            .iter()
+            .fold(self.agent.request(request.method, &request.url), |call, (name, value)| {
+                call.set(name, value)
+            });
+        let response = match call.send_bytes(&request.body) {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(e) => return Err(StoreError::new(StoreErrorKind::Backend(e.to_string()), line!(), file!())),
+        };
+        let status = response.status();
+        let headers = response
+            .headers_names()
+            .into_iter()
+            .filter_map(|name| Some((name.clone(), response.header(&name)?.to_string())))
+            .collect();
+        let mut body = Vec::new();
+        response
+            .into_reader()
+            .read_to_end(&mut body)
+            .map_err(|e| StoreError::new(StoreErrorKind::Backend(e.to_string()), line!(), file!()))?;
+        Ok(HttpResponse { status, headers, body })
+    }
+}
+
+/// Keys for signing S3 requests
+#[derive(Clone, PartialEq, Eq)]
+pub struct S3Credentials {
+    /// Access key ID
+    pub access_key_id: String,
+    /// Secret access key
+    pub secret_access_key: String,
+    /// Session token of temporary credentials
+    pub session_token: Option<String>,
+}
+
+impl std::fmt::Debug for S3Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3Credentials")
+            .field("access_key_id", &self.access_key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl S3Credentials {
+    /// Credentials from `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key ID or secret key is not set.
+    pub fn from_env() -> Result<Self, StoreError> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        let missing =
+            |name: &str| StoreError::new(StoreErrorKind::Config(format!("{} is not set", name)), line!(), file!());
+        Ok(Self {
+            access_key_id: var("AWS_ACCESS_KEY_ID").ok_or_else(|| missing("AWS_ACCESS_KEY_ID"))?,
+            secret_access_key: var("AWS_SECRET_ACCESS_KEY").ok_or_else(|| missing("AWS_SECRET_ACCESS_KEY"))?,
+            session_token: var("AWS_SESSION_TOKEN"),
+        })
+    }
+
+    /// Sign a request with AWS Signature Version 4, as it would be sent at `secs`
+    ///
+    /// Adds the `host`, `x-amz-date`, `x-amz-content-sha256` and
+    /// `authorization` headers, and `x-amz-security-token` for temporary
+    /// credentials. Every header already on the request is signed too.
+    pub fn sign(&self, request: &mut HttpRequest, region: &str, secs: u64) {
+        let t = UtcDateTime::from_unix(secs);
+        let date = format!("{:04}{:02}{:02}", t.year, t.month, t.day);
+        let timestamp = format!("{}T{:02}{:02}{:02}Z", date, t.hour, t.minute, t.second);
+        let (host, path, query) = split_url(&request.url);
+        let (host, path, query) = (host.to_string(), path.to_string(), query.to_string());
+        let payload_hash = hex(&Sha256::digest(&request.body));
+
+        request.headers.retain(|(name, _)| !name.eq_ignore_ascii_case("authorization"));
+        for (name, value) in [
+            ("host", host),
+            ("x-amz-date", timestamp.clone()),
+            ("x-amz-content-sha256", payload_hash.clone()),
+        ]
+        .into_iter()
+        .chain(self.session_token.clone().map(|token| ("x-amz-security-token", token)))
+        {
+            if request.header(name).is_none() {
+                request.headers.push((name.to_string(), value));
+            }
+        }
+
+        let mut headers: Vec<(String, String)> = request
+            .headers
+            .iter()
+            .map(|(name, value)| (name.to_lowercase(), value.trim().to_string()))
+            .collect();
+        headers.sort();
+        let canonical_headers: String = headers.iter().map(|(name, value)| format!("{}:{}\n", name, value)).collect();
+        let signed_headers = headers.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(";");
+        let mut query: Vec<&str> = query.split('&').filter(|pair| !pair.is_empty()).collect();
+        query.sort();
+        let canonical_query = query
+            .iter()
+            .map(|pair| if pair.contains('=') { pair.to_string() } else { format!("{}=", pair) })
+            .collect::<Vec<_>>()
+            .join("&");
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            request.method, path, canonical_query, canonical_headers, signed_headers, payload_hash
+        );
+
+        let scope = format!("{}/{}/s3/aws4_request", date, region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            timestamp,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let key = [date.as_str(), region, "s3", "aws4_request"]
+            .into_iter()
+            .fold(format!("AWS4{}", self.secret_access_key).into_bytes(), |key, part| {
+                hmac_sha256(&key, part.as_bytes())
+            });
+        let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+        request.headers.push((
+            "authorization".to_string(),
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                self.access_key_id, scope, signed_headers, signature
+            ),
+        ));
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    // HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap_or_else(|_| unreachable!());
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Host, path and query of a URL like `https://host:9000/path?query`
+fn split_url(url: &str) -> (&str, &str, &str) {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let (rest, query) = rest.split_once('?').unwrap_or((rest, ""));
+    match rest.find('/') {
+        Some(slash) => (&rest[..slash], &rest[slash..], query),
+        None => (rest, "/", query),
+    }
+}
+
+/// Percent-encode everything but unreserved characters, and `/` if `keep_slash`
+fn uri_encode(text: &str, keep_slash: bool) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            b'/' if keep_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Undo the escaping of text in an XML element
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+static LIST_KEY: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<Key>([^<]*)</Key>").expect("Valid regex"));
+static LIST_CONTINUATION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"<NextContinuationToken>([^<]*)</NextContinuationToken>").expect("Valid regex")
+});
+
+/// Store keeping objects in an S3-compatible bucket
+///
+/// Requests use path-style URLs, `{endpoint}/{bucket}/{key}`, which AWS,
+/// MinIO and other S3-compatible services all accept. An object's version
+/// is its ETag, and conditional writes send it in `If-Match`, or
+/// `If-None-Match: *` for a new object, so the service itself refuses a
+/// write over someone else's.
+pub struct S3Store {
+    settings: S3Settings,
+    credentials: S3Credentials,
+    client: Box<dyn HttpClient + Send + Sync>,
+}
+
+impl std::fmt::Debug for S3Store {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3Store")
+            .field("settings", &self.settings)
+            .field("credentials", &self.credentials)
+            .finish_non_exhaustive()
+    }
+}
+
+impl S3Store {
+    /// Store in the bucket of `settings`, sending requests through `client`
+    pub fn new(
+        settings: S3Settings,
+        credentials: S3Credentials,
+        client: impl HttpClient + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            settings,
+            credentials,
+            client: Box::new(client),
+        }
+    }
+
+    /// Bucket and prefix the objects are stored under
+    pub fn settings(&self) -> &S3Settings {
+        &self.settings
+    }
+
+    fn bucket_url(&self) -> String {
+        format!("{}/{}", self.settings.endpoint.trim_end_matches('/'), uri_encode(&self.settings.bucket, false))
+    }
+
+    fn object_url(&self, key: &str) -> String {
+        format!("{}/{}", self.bucket_url(), uri_encode(&format!("{}{}", self.settings.prefix, key), true))
+    }
+
+    #[instrument(skip(self, request), fields(method = request.method, url = %request.url))]
+    fn send(&self, mut request: HttpRequest, key: &str) -> Result<HttpResponse, StoreError> {
+        let secs = web_time::SystemTime::now()
+            .duration_since(web_time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        self.credentials.sign(&mut request, &self.settings.region, secs);
+        let response = self.client.send(&request)?;
+        debug!(status = response.status, "Storage service responded");
+        match response.status {
+            200..=299 => Ok(response),
+            404 => Err(StoreError::new(StoreErrorKind::NotFound(key.to_string()), line!(), file!())),
+            // 409 is returned when a concurrent conditional write won the race
+            409 | 412 => Err(conflict(key, line!())),
+            status => {
+                let body = String::from_utf8_lossy(&response.body);
+                let detail = body.chars().take(200).collect::<String>();
+                Err(StoreError::new(
+                    StoreErrorKind::Backend(format!("{} {} returned {}: {}", request.method, key, status, detail)),
+                    line!(),
+                    file!(),
+                ))
+            }
+        }
+    }
+}
+
+impl ProjectStore for S3Store {
+    fn list(&self, prefix: &str) -> Result<Vec<String>, StoreError> {
+        let full_prefix = format!("{}{}", self.settings.prefix, prefix);
+        let mut keys = Vec::new();
+        let mut continuation: Option<String> = None;
+        loop {
+            let mut url = format!("{}?list-type=2&prefix={}", self.bucket_url(), uri_encode(&full_prefix, false));
+            if let Some(token) = &continuation {
+                url.push_str(&format!("&continuation-token={}", uri_encode(token, false)));
+            }
+            let response = self.send(HttpRequest::new("GET", url), prefix)?;
+            let body = String::from_utf8_lossy(&response.body);
+            keys.extend(LIST_KEY.captures_iter(&body).filter_map(|found| {
+                xml_unescape(&found[1])
+                    .strip_prefix(&self.settings.prefix)
+                    .map(str::to_string)
+            }));
+            continuation = LIST_CONTINUATION.captures(&body).map(|found| xml_unescape(&found[1]));
+            if continuation.is_none() || !body.contains("<IsTruncated>true</IsTruncated>") {
+                break;
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+
+    fn read(&self, key: &str) -> Result<Vec<u8>, StoreError> {
+        Ok(self.send(HttpRequest::new("GET", self.object_url(key)), key)?.body)
+    }
+
+    fn write(&self, key: &str, bytes: &[u8]) -> Result<(), StoreError> {
+        let request = HttpRequest {
+            body: bytes.to_vec(),
+            ..HttpRequest::new("PUT", self.object_url(key))
+        };
+        self.send(request, key).map(|_| ())
+    }
+
+    fn read_versioned(&self, key: &str) -> Result<(Vec<u8>, String), StoreError> {
+        let response = self.send(HttpRequest::new("GET", self.object_url(key)), key)?;
+        let version = etag(&response, key)?;
+        Ok((response.body, version))
+    }
+
+    fn write_if(&self, key: &str, bytes: &[u8], version: Option<&str>) -> Result<String, StoreError> {
+        let condition = match version {
+            Some(etag) => ("If-Match".to_string(), etag.to_string()),
+            None => ("If-None-Match".to_string(), "*".to_string()),
+        };
+        let request = HttpRequest {
+            headers: vec![condition],
+            body: bytes.to_vec(),
+            ..HttpRequest::new("PUT", self.object_url(key))
+        };
+        etag(&self.send(request, key)?, key)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), StoreError> {
+        match self.send(HttpRequest::new("DELETE", self.object_url(key)), key) {
+            Ok(_) => Ok(()),
+            Err(e) if matches!(e.kind, StoreErrorKind::NotFound(_)) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// ETag of the object a response is about
+fn etag(response: &HttpResponse, key: &str) -> Result<String, StoreError> {
+    response.header("etag").map(str::to_string).ok_or_else(|| {
+        StoreError::new(StoreErrorKind::Backend(format!("{} has no ETag", key)), line!(), file!())
+    })
+}
+
+// ============================================================================
+// Settings
+// ============================================================================
+
+/// Bucket of an S3-compatible service
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct S3Settings {
+    /// Service URL, like `https://s3.us-east-1.amazonaws.com` or `http://localhost:9000`
+    pub endpoint: String,
+    /// Bucket name
+    pub bucket: String,
+    /// Region requests are signed for
+    pub region: String,
+    /// Prefix of every key, like `form_factor/`, to share a bucket
+    pub prefix: String,
+}
+
+impl Default for S3Settings {
+    fn default() -> Self {
+        Self {
+            endpoint: "https://s3.us-east-1.amazonaws.com".to_string(),
+            bucket: String::new(),
+            region: "us-east-1".to_string(),
+            prefix: String::new(),
+        }
+    }
+}
+
+/// Where projects and instances are stored
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "backend", rename_all = "snake_case")]
+pub enum StorageSettings {
+    /// Files under a directory on a local or mounted disk
+    Filesystem {
+        /// Directory the projects are stored under
+        root: PathBuf,
+    },
+    /// Objects in an S3-compatible bucket, with credentials from the environment
+    S3(S3Settings),
+}
+
+impl Default for StorageSettings {
+    fn default() -> Self {
+        StorageSettings::Filesystem {
+            root: default_store_dir(),
+        }
+    }
+}
+
+/// Directory projects are stored under by default
+pub fn default_store_dir() -> PathBuf {
+    config_dir().join("store")
+}
+
+impl StorageSettings {
+    /// Open the configured store
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if S3 is chosen without a bucket or credentials, or
+    /// in a browser, which can't reach it.
+    pub fn open(&self) -> Result<Box<dyn ProjectStore + Send + Sync>, StoreError> {
+        match self {
+            StorageSettings::Filesystem { root } => Ok(Box::new(FileStore::new(root))),
+            StorageSettings::S3(settings) => {
+                if settings.bucket.is_empty() {
+                    return Err(StoreError::new(
+                        StoreErrorKind::Config("no S3 bucket is set".to_string()),
+                        line!(),
+                        file!(),
+                    ));
+                }
+                let credentials = S3Credentials::from_env()?;
+                #[cfg(not(target_arch = "wasm32"))]
+                return Ok(Box::new(S3Store::new(settings.clone(), credentials, UreqClient::new())));
+                #[cfg(target_arch = "wasm32")]
+                {
+                    let _ = credentials;
+                    Err(StoreError::new(
+                        StoreErrorKind::Config("S3 storage is not available in the browser".to_string()),
+                        line!(),
+                        file!(),
+                    ))
+                }
+            }
+        }
+    }
+}
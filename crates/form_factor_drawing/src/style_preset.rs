@@ -0,0 +1,100 @@
+//! Named annotation styles
+//!
+//! A style preset gives new shapes a stroke, fill and outline pattern by
+//! name, so a team can agree that fields are outlined in blue and issues
+//! are dashed in red and have every project look the same. Presets live in
+//! the canvas settings and are chosen from the toolbar; with no preset
+//! chosen, new shapes take the canvas default style. New preset lists start
+//! with Field and Issue.
+
+use crate::{Shape, StrokeStyle};
+use egui::{Color32, Stroke};
+use serde::{Deserialize, Serialize};
+
+/// Stroke, fill and outline pattern given to new shapes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StylePreset {
+    /// Name shown in the toolbar, unique within a preset list
+    pub name: String,
+    /// Outline width and color
+    pub stroke: Stroke,
+    /// Fill color of closed shapes
+    pub fill: Color32,
+    /// Whether the outline is solid, dashed or dotted
+    #[serde(default)]
+    pub stroke_style: StrokeStyle,
+}
+
+impl StylePreset {
+    /// Preset with the given stroke, fill and outline pattern
+    pub fn new(name: impl Into<String>, stroke: Stroke, fill: Color32, stroke_style: StrokeStyle) -> Self {
+        Self {
+            name: name.into(),
+            stroke,
+            fill,
+            stroke_style,
+        }
+    }
+
+    /// Give a shape this preset's stroke, fill and outline pattern
+    pub fn apply_to(&self, shape: &mut Shape) {
+        shape.set_stroke(self.stroke);
+        shape.set_fill(self.fill);
+        shape.set_stroke_style(self.stroke_style);
+    }
+}
+
+/// Style presets available from the toolbar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StylePresets {
+    /// Presets in toolbar order
+    pub presets: Vec<StylePreset>,
+}
+
+impl Default for StylePresets {
+    fn default() -> Self {
+        Self {
+            presets: vec![
+                StylePreset::new(
+                    "Field",
+                    Stroke::new(2.0, Color32::from_rgb(0, 120, 215)),
+                    Color32::TRANSPARENT,
+                    StrokeStyle::Solid,
+                ),
+                StylePreset::new(
+                    "Issue",
+                    Stroke::new(2.0, Color32::from_rgb(200, 30, 30)),
+                    Color32::TRANSPARENT,
+                    StrokeStyle::Dashed,
+                ),
+            ],
+        }
+    }
+}
+
+impl StylePresets {
+    /// Preset with the given name
+    pub fn get(&self, name: &str) -> Option<&StylePreset> {
+        self.presets.iter().find(|preset| preset.name == name)
+    }
+
+    /// Add a preset, replacing any preset of the same name in place
+    pub fn add(&mut self, preset: StylePreset) {
+        match self.presets.iter_mut().find(|existing| existing.name == preset.name) {
+            Some(existing) => *existing = preset,
+            None => self.presets.push(preset),
+        }
+    }
+
+    /// Remove a preset by name
+    pub fn remove(&mut self, name: &str) -> Option<StylePreset> {
+        let idx = self.presets.iter().position(|preset| preset.name == name)?;
+        Some(self.presets.remove(idx))
+    }
+
+    /// Names of the presets in toolbar order
+    pub fn names(&self) -> Vec<String> {
+        self.presets.iter().map(|preset| preset.name.clone()).collect()
+    }
+}
@@ -0,0 +1,78 @@
+//! Detection subtype taxonomy
+//!
+//! Detections can be tagged with a subtype from a user-defined taxonomy,
+//! such as Signature or Barcode, to classify what was found beyond the
+//! detector that found it. The taxonomy lives in the detection settings, so
+//! a classification scheme can grow without code changes; each subtype has
+//! a color that tagged detections are drawn in and a description shown
+//! when tagging. New taxonomies start with Logo, Text and Signature.
+
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+
+/// Category detections can be tagged with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectionSubtype {
+    /// Name of the subtype, unique within a taxonomy
+    pub name: String,
+    /// Outline color of detections tagged with the subtype
+    pub color: Color32,
+    /// What the subtype covers
+    #[serde(default)]
+    pub description: String,
+}
+
+impl DetectionSubtype {
+    /// Subtype drawn in `color`
+    pub fn new(name: impl Into<String>, color: Color32, description: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            color,
+            description: description.into(),
+        }
+    }
+}
+
+/// Subtypes detections can be tagged with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DetectionTaxonomy {
+    /// Subtypes in menu order
+    pub subtypes: Vec<DetectionSubtype>,
+}
+
+impl Default for DetectionTaxonomy {
+    fn default() -> Self {
+        Self {
+            subtypes: vec![
+                DetectionSubtype::new("Logo", Color32::from_rgb(30, 144, 255), "Company or agency marks"),
+                DetectionSubtype::new("Text", Color32::from_rgb(255, 165, 0), "Printed text"),
+                DetectionSubtype::new("Signature", Color32::from_rgb(150, 60, 200), "Handwritten signatures"),
+            ],
+        }
+    }
+}
+
+impl DetectionTaxonomy {
+    /// Subtype with the given name
+    pub fn get(&self, name: &str) -> Option<&DetectionSubtype> {
+        self.subtypes.iter().find(|subtype| subtype.name == name)
+    }
+
+    /// Add a subtype, replacing any subtype of the same name in place
+    pub fn add(&mut self, subtype: DetectionSubtype) {
+        match self.subtypes.iter_mut().find(|existing| existing.name == subtype.name) {
+            Some(existing) => *existing = subtype,
+            None => self.subtypes.push(subtype),
+        }
+    }
+
+    /// Remove a subtype by name
+    ///
+    /// Detections tagged with it keep the tag, and are drawn in their kind's
+    /// color until a subtype of that name is added again.
+    pub fn remove(&mut self, name: &str) -> Option<DetectionSubtype> {
+        let idx = self.subtypes.iter().position(|subtype| subtype.name == name)?;
+        Some(self.subtypes.remove(idx))
+    }
+}
@@ -1,9 +1,13 @@
 //! Drawing tool modes for canvas interaction
 //!
-//! Tools are displayed in UI menus in enum discriminant order.
+//! Tools are displayed in UI menus in enum discriminant order, followed by
+//! the [`CustomTool`]s plugins and applications register in a [`ToolRegistry`].
 
+use crate::DrawingCanvas;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use strum::IntoEnumIterator;
 
 /// The active drawing tool mode
 ///
@@ -42,11 +46,49 @@ pub enum ToolMode {
     /// dragging sets the radius.
     Circle,
 
+    /// Draw ellipses
+    ///
+    /// Click and drag from one corner of the ellipse's bounding box to the
+    /// opposite corner.
+    Ellipse,
+
     /// Draw freehand closed polygons
     ///
     /// Click to add vertices, double-click or close to finish the polygon.
     Freehand,
 
+    /// Draw straight lines
+    ///
+    /// Click and drag from one end of the line to the other.
+    Line,
+
+    /// Draw open polylines
+    ///
+    /// Click and drag along the path; the stroke is simplified to straight
+    /// segments when released.
+    Polyline,
+
+    /// Draw arrow markups
+    ///
+    /// Click and drag from the tail to the tip of the arrow.
+    Arrow,
+
+    /// Draw callout markups
+    ///
+    /// Click on the point to describe and drag to where the text box goes.
+    Callout,
+
+    /// Mark regions with a highlighter
+    ///
+    /// Click and drag to draw a thick translucent stroke on the highlights layer.
+    Highlighter,
+
+    /// Place stamps
+    ///
+    /// Click to place the symbol chosen in the stamp picker; Shift+click a
+    /// stamp to remove it.
+    Stamp,
+
     /// Edit shape vertices
     ///
     /// Select a shape and drag individual vertices to modify its geometry.
@@ -65,9 +107,160 @@ impl fmt::Display for ToolMode {
             ToolMode::Select => write!(f, "Select"),
             ToolMode::Rectangle => write!(f, "Rectangle"),
             ToolMode::Circle => write!(f, "Circle"),
+            ToolMode::Ellipse => write!(f, "Ellipse"),
             ToolMode::Freehand => write!(f, "Freehand"),
+            ToolMode::Line => write!(f, "Line"),
+            ToolMode::Polyline => write!(f, "Polyline"),
+            ToolMode::Arrow => write!(f, "Arrow"),
+            ToolMode::Callout => write!(f, "Callout"),
+            ToolMode::Highlighter => write!(f, "Highlighter"),
+            ToolMode::Stamp => write!(f, "Stamp"),
             ToolMode::Edit => write!(f, "Edit"),
             ToolMode::Rotate => write!(f, "Rotate"),
         }
     }
 }
+
+/// Pointer input handed to the active custom tool, in canvas coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ToolInput {
+    /// Pointer position, if the pointer is over or dragging on the canvas
+    pub pos: Option<egui::Pos2>,
+    /// The canvas was clicked this frame
+    pub clicked: bool,
+    /// A drag started this frame
+    pub drag_started: bool,
+    /// The pointer is dragging
+    pub dragged: bool,
+    /// A drag ended this frame
+    pub drag_stopped: bool,
+    /// Modifier keys held
+    pub modifiers: egui::Modifiers,
+}
+
+/// Tool added by a plugin or application next to the built-in [`ToolMode`]s
+///
+/// While the tool is active, the canvas hands it all pointer input instead
+/// of running a built-in tool.
+pub trait CustomTool: Send {
+    /// Unique id, used to select the tool; must not be a built-in tool's name
+    fn id(&self) -> &str;
+
+    /// Name shown in the toolbar, the id unless overridden
+    fn label(&self) -> &str {
+        self.id()
+    }
+
+    /// Short symbol shown before the label in the toolbar
+    fn icon(&self) -> &str {
+        ""
+    }
+
+    /// Pointer shape while the tool is over the canvas
+    fn cursor(&self) -> egui::CursorIcon {
+        egui::CursorIcon::Crosshair
+    }
+
+    /// Handle one frame of pointer input on the canvas
+    fn handle_input(&mut self, input: &ToolInput, canvas: &mut DrawingCanvas);
+}
+
+/// Toolbar entry for a registered custom tool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolInfo {
+    /// Tool id, sent to select the tool
+    pub id: String,
+    /// Name shown in the toolbar
+    pub label: String,
+    /// Short symbol shown before the label
+    pub icon: String,
+}
+
+/// Custom tool shared between clones of a canvas
+pub type SharedTool = Arc<Mutex<Box<dyn CustomTool>>>;
+
+/// Custom tools in registration order
+///
+/// Toolbar entries are read once on registration, so looking tools up never
+/// locks a tool that may be handling input.
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    tools: Vec<(ToolInfo, SharedTool)>,
+}
+
+impl fmt::Debug for ToolRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.tools.iter().map(|(info, _)| &info.id)).finish()
+    }
+}
+
+impl ToolRegistry {
+    /// Registry with no tools
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a tool
+    ///
+    /// Returns false, leaving the registry unchanged, if a built-in tool has
+    /// the same name or a registered tool the same id.
+    pub fn register(&mut self, tool: Box<dyn CustomTool>) -> bool {
+        let id = tool.id();
+        if ToolMode::iter().any(|mode| mode.to_string() == id) || self.index_of(id).is_some() {
+            tracing::warn!(tool = id, "Tool id already taken, ignoring tool");
+            return false;
+        }
+        tracing::debug!(tool = id, "Registered custom tool");
+        let info = ToolInfo {
+            id: id.to_string(),
+            label: tool.label().to_string(),
+            icon: tool.icon().to_string(),
+        };
+        self.tools.push((info, Arc::new(Mutex::new(tool))));
+        true
+    }
+
+    /// Add the tools of `other` whose ids are not taken here
+    pub fn merge(&mut self, other: ToolRegistry) {
+        for (info, tool) in other.tools {
+            if self.index_of(&info.id).is_none() {
+                self.tools.push((info, tool));
+            }
+        }
+    }
+
+    /// Position of the tool with this id
+    pub fn index_of(&self, id: &str) -> Option<usize> {
+        self.tools.iter().position(|(info, _)| info.id == id)
+    }
+
+    /// Toolbar entry of the tool at `index`
+    pub fn info(&self, index: usize) -> Option<&ToolInfo> {
+        self.tools.get(index).map(|(info, _)| info)
+    }
+
+    /// Tool at `index`
+    pub fn get(&self, index: usize) -> Option<&SharedTool> {
+        self.tools.get(index).map(|(_, tool)| tool)
+    }
+
+    /// Toolbar entries, in registration order
+    pub fn infos(&self) -> Vec<ToolInfo> {
+        self.tools.iter().map(|(info, _)| info.clone()).collect()
+    }
+
+    /// Number of registered tools
+    pub fn len(&self) -> usize {
+        self.tools.len()
+    }
+
+    /// Whether no tools are registered
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+}
+
+/// Lock a shared tool, recovering it if a previous holder panicked
+pub(crate) fn lock(tool: &SharedTool) -> MutexGuard<'_, Box<dyn CustomTool>> {
+    tool.lock().unwrap_or_else(PoisonError::into_inner)
+}
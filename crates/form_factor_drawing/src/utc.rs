@@ -0,0 +1,39 @@
+//! UTC calendar dates and times from Unix timestamps
+//!
+//! Timestamps are kept as seconds since the Unix epoch. Showing them, or
+//! signing storage requests with them, needs the calendar date and time in
+//! UTC, which the standard library does not provide.
+
+/// A moment as a UTC calendar date and time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct UtcDateTime {
+    pub(crate) year: i64,
+    pub(crate) month: i64,
+    pub(crate) day: i64,
+    pub(crate) hour: u64,
+    pub(crate) minute: u64,
+    pub(crate) second: u64,
+}
+
+impl UtcDateTime {
+    /// Date and time `secs` seconds after the Unix epoch
+    pub(crate) fn from_unix(secs: u64) -> Self {
+        // Days to civil date, after Howard Hinnant's `civil_from_days`
+        let days = (secs / 86_400) as i64 + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+        let time = secs % 86_400;
+        Self {
+            year: year_of_era + era * 400 + i64::from(month <= 2),
+            month,
+            day: day_of_year - (153 * shifted_month + 2) / 5 + 1,
+            hour: time / 3_600,
+            minute: time % 3_600 / 60,
+            second: time % 60,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Page assignment and progress tracking
+//!
+//! Lets a small team split a batch: each page is assigned to a user and
+//! carries a status. Projects currently hold a single page (page 0); the
+//! page index keeps the model ready for multi-page projects.
+
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+/// Progress of the work on a page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize, EnumIter)]
+pub enum TaskStatus {
+    /// Nobody has picked up the page
+    #[default]
+    Unassigned,
+    /// Someone is working on the page
+    InProgress,
+    /// The page is finished
+    Done,
+}
+
+impl std::fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskStatus::Unassigned => write!(f, "Unassigned"),
+            TaskStatus::InProgress => write!(f, "In Progress"),
+            TaskStatus::Done => write!(f, "Done"),
+        }
+    }
+}
+
+/// Assignment and status of a single page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageTask {
+    /// Page index within the project
+    pub page: usize,
+    /// User the page is assigned to
+    pub assignee: Option<String>,
+    /// Current progress
+    pub status: TaskStatus,
+}
+
+/// Assignments for every page in a project
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Workflow {
+    #[serde(default)]
+    tasks: Vec<PageTask>,
+}
+
+impl Workflow {
+    /// Create an empty workflow
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All page tasks, ordered by page
+    pub fn tasks(&self) -> &[PageTask] {
+        &self.tasks
+    }
+
+    /// Get the task for a page, if one has been recorded
+    pub fn task(&self, page: usize) -> Option<&PageTask> {
+        self.tasks.iter().find(|t| t.page == page)
+    }
+
+    fn task_mut(&mut self, page: usize) -> &mut PageTask {
+        let pos = match self.tasks.binary_search_by_key(&page, |t| t.page) {
+            Ok(pos) => pos,
+            Err(pos) => {
+                self.tasks.insert(
+                    pos,
+                    PageTask {
+                        page,
+                        assignee: None,
+                        status: TaskStatus::Unassigned,
+                    },
+                );
+                pos
+            }
+        };
+        &mut self.tasks[pos]
+    }
+
+    /// Assign a page to a user, or clear the assignment with `None`
+    ///
+    /// Assigning an unassigned page moves it to in-progress; clearing the
+    /// assignment of an unfinished page moves it back to unassigned.
+    pub fn assign(&mut self, page: usize, assignee: Option<String>) {
+        let task = self.task_mut(page);
+        match (&assignee, task.status) {
+            (Some(_), TaskStatus::Unassigned) => task.status = TaskStatus::InProgress,
+            (None, TaskStatus::InProgress) => task.status = TaskStatus::Unassigned,
+            _ => {}
+        }
+        task.assignee = assignee;
+    }
+
+    /// Set the status of a page
+    pub fn set_status(&mut self, page: usize, status: TaskStatus) {
+        self.task_mut(page).status = status;
+    }
+
+    /// Status of a page; pages without a task are unassigned
+    pub fn status(&self, page: usize) -> TaskStatus {
+        self.task(page).map(|t| t.status).unwrap_or_default()
+    }
+
+    /// Tasks assigned to the given user
+    pub fn tasks_for<'a>(&'a self, assignee: &'a str) -> impl Iterator<Item = &'a PageTask> {
+        self.tasks
+            .iter()
+            .filter(move |t| t.assignee.as_deref() == Some(assignee))
+    }
+
+    /// Distinct assignees, sorted by name
+    pub fn assignees(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.tasks.iter().filter_map(|t| t.assignee.as_deref()).collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+}
@@ -10,5 +10,5 @@ mod ocr;
 
 pub use ocr::{
     BoundingBox, EngineMode, OCRConfig, OCREngine, OCRError, OCRErrorKind, OCRResult,
-    PageSegmentationMode, WordResult,
+    PageSegmentationMode, WordResult, normalize_number, parse_tsv_words,
 };
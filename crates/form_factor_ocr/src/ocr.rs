@@ -90,6 +90,12 @@ const MIN_CONFIDENCE: f32 = 0.0;
 /// Maximum valid confidence value
 const MAX_CONFIDENCE: f32 = 100.0;
 
+/// Characters recognized in numeric mode: digits, separators, signs and currency symbols
+const NUMERIC_CHARACTERS: &str = "0123456789.,-+()$€£";
+
+/// Currency symbols kept in front of normalized numbers
+const CURRENCY_SYMBOLS: [char; 3] = ['$', '€', '£'];
+
 // ============================================================================
 // Error Types
 // ============================================================================
@@ -125,7 +131,7 @@ impl std::fmt::Display for OCRErrorKind {
 }
 
 /// OCR error with location information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct OCRError {
     /// Error category
     pub kind: OCRErrorKind,
@@ -238,6 +244,16 @@ pub struct OCRConfig {
     /// If None, uses system default
     #[serde(default)]
     pub tessdata_path: Option<String>,
+
+    /// Characters Tesseract may recognize (optional)
+    /// If None, any character may be recognized
+    #[serde(default)]
+    pub char_whitelist: Option<String>,
+
+    /// Rewrite recognized numbers with `.` as the decimal separator
+    /// and without thousands separators, see [`normalize_number`]
+    #[serde(default)]
+    pub normalize_numbers: bool,
 }
 
 fn default_language() -> String {
@@ -261,6 +277,8 @@ impl Default for OCRConfig {
             min_confidence: DEFAULT_MIN_CONFIDENCE,
             preprocess: true,
             tessdata_path: None,
+            char_whitelist: None,
+            normalize_numbers: false,
         }
     }
 }
@@ -308,6 +326,98 @@ impl OCRConfig {
         self.tessdata_path = Some(path.into());
         self
     }
+
+    /// Restrict recognition to the given characters (builder pattern)
+    pub fn with_char_whitelist(mut self, characters: impl Into<String>) -> Self {
+        self.char_whitelist = Some(characters.into());
+        self
+    }
+
+    /// Enable or disable number normalization (builder pattern)
+    pub fn with_number_normalization(mut self, enable: bool) -> Self {
+        self.normalize_numbers = enable;
+        self
+    }
+
+    /// Configuration for amount and other numeric fields
+    ///
+    /// Equivalent to `OCRConfig::default().with_numeric_mode()`.
+    pub fn numeric() -> Self {
+        Self::default().with_numeric_mode()
+    }
+
+    /// Read a single line of digits, separators, signs and currency symbols
+    ///
+    /// Keeps the language, confidence threshold and other settings, so a
+    /// numeric engine can be derived from the one used for other fields.
+    /// Letters are never recognized, and recognized numbers are normalized
+    /// with [`normalize_number`].
+    pub fn with_numeric_mode(self) -> Self {
+        self.with_psm(PageSegmentationMode::SingleLine)
+            .with_char_whitelist(NUMERIC_CHARACTERS)
+            .with_number_normalization(true)
+    }
+}
+
+/// Rewrite a recognized number with `.` as the decimal separator
+///
+/// Whitespace and thousands separators are dropped, and an amount in
+/// parentheses becomes negative, so `$ 1.234,50` reads `$1234.50` and
+/// `(12)` reads `-12`. When both `.` and `,` appear the last one is the
+/// decimal separator. A single `.` or `,` is a thousands separator only if
+/// exactly three digits follow it and a nonzero digit precedes it, so
+/// `12,50 €` reads `12.50€` and `1,000` reads `1000`. A currency code of up
+/// to three letters before or after the amount is kept, so `1.234,5 kr`
+/// reads `1234.5kr`; other text with letters in it is returned trimmed but
+/// otherwise unchanged.
+pub fn normalize_number(text: &str) -> String {
+    let text = text.trim();
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let body_start = compact.find(|c: char| !c.is_alphabetic()).unwrap_or(compact.len());
+    let body_end = compact
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !c.is_alphabetic())
+        .map_or(body_start, |(idx, c)| idx + c.len_utf8());
+    let (prefix, body, suffix) = (&compact[..body_start], &compact[body_start..body_end], &compact[body_end..]);
+    let currency_code = if prefix.is_empty() { suffix } else { prefix };
+    let has_currency_code = (prefix.is_empty() || suffix.is_empty()) && currency_code.chars().count() <= 3;
+    if !has_currency_code || body.is_empty() || body.chars().any(char::is_alphabetic) {
+        return text.to_string();
+    }
+
+    let (negative, body) = match body.strip_prefix('(').and_then(|inner| inner.strip_suffix(')')) {
+        Some(inner) => (true, inner),
+        None => (false, body),
+    };
+
+    let groups_thousands = |sep: usize| {
+        body[sep + 1..].chars().filter(char::is_ascii_digit).count() == 3
+            && body[..sep].chars().any(|c| c.is_ascii_digit() && c != '0')
+    };
+    let decimal = match (body.rfind('.'), body.rfind(',')) {
+        (Some(dot), Some(comma)) => Some(dot.max(comma)),
+        (Some(sep), None) | (None, Some(sep)) if body.matches(&body[sep..=sep]).count() == 1 && !groups_thousands(sep) => {
+            Some(sep)
+        }
+        _ => None,
+    };
+
+    let mut normalized = String::with_capacity(compact.len() + 1);
+    if negative {
+        normalized.push('-');
+    }
+    normalized.push_str(prefix);
+    for (idx, c) in body.char_indices() {
+        match c {
+            '.' | ',' if Some(idx) == decimal => normalized.push('.'),
+            '.' | ',' | '(' | ')' => {}
+            c if c.is_ascii_digit() || c == '-' || c == '+' || CURRENCY_SYMBOLS.contains(&c) => normalized.push(c),
+            _ => {}
+        }
+    }
+    normalized.push_str(suffix);
+    normalized
 }
 
 /// Page Segmentation Mode - how Tesseract should segment the page
@@ -373,10 +483,11 @@ pub struct OCRResult {
     #[serde(default)]
     meets_threshold: bool,
 
-    /// Individual word-level results (currently not implemented)
+    /// Individual words with their confidence and bounding box
     ///
-    /// This field is reserved for future word-level confidence and bounding box data.
-    /// Currently always None.
+    /// Boxes are in pixels of the image passed in; for regions, of the
+    /// full image rather than the crop. `None` if Tesseract reported no
+    /// word boxes.
     #[serde(default)]
     words: Option<Vec<WordResult>>,
 }
@@ -391,6 +502,21 @@ impl OCRResult {
             words: None,
         }
     }
+
+    /// Attach the words the text was read as
+    pub(crate) fn with_words(mut self, words: Vec<WordResult>) -> Self {
+        self.words = (!words.is_empty()).then_some(words);
+        self
+    }
+
+    /// Move the word boxes by an offset, from a cropped region into the full image
+    fn offset_words(mut self, dx: i32, dy: i32) -> Self {
+        for word in self.words.iter_mut().flatten() {
+            word.bbox.x += dx;
+            word.bbox.y += dy;
+        }
+        self
+    }
 }
 
 /// Word-level OCR result with position and confidence
@@ -409,6 +535,43 @@ pub struct WordResult {
     bbox: BoundingBox,
 }
 
+impl WordResult {
+    /// Create a word result
+    pub fn new(text: impl Into<String>, confidence: f32, bbox: BoundingBox) -> Self {
+        Self {
+            text: text.into(),
+            confidence,
+            bbox,
+        }
+    }
+}
+
+/// Tesseract TSV level of rows describing single words
+const TSV_WORD_LEVEL: &str = "5";
+
+/// Words in Tesseract's TSV output
+///
+/// Each row holds level, page, block, paragraph, line and word numbers,
+/// then left, top, width, height, confidence and text. Rows of other levels,
+/// blank words and rows that don't parse are skipped.
+pub fn parse_tsv_words(tsv: &str) -> Vec<WordResult> {
+    tsv.lines()
+        .filter_map(|row| {
+            let columns: Vec<&str> = row.split('\t').collect();
+            let [level, _, _, _, _, _, left, top, width, height, confidence, text] = columns.as_slice() else {
+                return None;
+            };
+            if *level != TSV_WORD_LEVEL || text.trim().is_empty() {
+                return None;
+            }
+            let bbox = BoundingBox::new(left.parse().ok()?, top.parse().ok()?, width.parse().ok()?, height.parse().ok()?)
+                .ok()?;
+            let confidence = confidence.parse::<f32>().ok()?.clamp(MIN_CONFIDENCE, MAX_CONFIDENCE);
+            Some(WordResult::new(text.trim(), confidence, bbox))
+        })
+        .collect()
+}
+
 /// OCR Engine for text extraction
 pub struct OCREngine {
     config: OCRConfig,
@@ -540,6 +703,15 @@ impl OCREngine {
             file!(),
         ))?;
 
+        if let Some(ref whitelist) = self.config.char_whitelist {
+            lt.set_variable(Variable::TesseditCharWhitelist, whitelist)
+                .map_err(|e| OCRError::new(
+                    OCRErrorKind::Initialization(format!("Failed to set character whitelist: {}", e)),
+                    line!(),
+                    file!(),
+                ))?;
+        }
+
         // Encode image as PNG for leptess (new API requires encoded image data)
         let mut png_data = Vec::new();
         {
@@ -575,6 +747,12 @@ impl OCREngine {
                 file!(),
             ))?;
 
+        let text = if self.config.normalize_numbers {
+            normalize_number(&text)
+        } else {
+            text
+        };
+
         // Get confidence and clamp to valid range
         let confidence = (lt.mean_text_conf() as f32).clamp(MIN_CONFIDENCE, MAX_CONFIDENCE);
 
@@ -582,7 +760,22 @@ impl OCREngine {
 
         let meets_threshold = confidence >= self.config.min_confidence as f32;
 
-        Ok(OCRResult::new(text, confidence, meets_threshold))
+        // Word boxes are extra detail; the text stands without them
+        let mut words = match lt.get_tsv_text(0) {
+            Ok(tsv) => parse_tsv_words(&tsv),
+            Err(e) => {
+                warn!("Failed to read word boxes: {}", e);
+                Vec::new()
+            }
+        };
+        if self.config.normalize_numbers {
+            for word in &mut words {
+                word.text = normalize_number(&word.text);
+            }
+        }
+        trace!(words = words.len(), "Read word boxes");
+
+        Ok(OCRResult::new(text, confidence, meets_threshold).with_words(words))
     }
 
     /// Extract text from a specific region of an image file
@@ -662,7 +855,9 @@ impl OCREngine {
         // Crop to region
         let cropped = image.crop_imm(x, y, width, height);
 
+        // Word boxes are reported in the full image
         self.extract_text(&cropped)
+            .map(|result| result.offset_words(x as i32, y as i32))
     }
 
     /// Preprocess image for better OCR accuracy
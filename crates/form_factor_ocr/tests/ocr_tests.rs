@@ -0,0 +1,41 @@
+//! Integration tests for reading Tesseract's output
+
+use form_factor_ocr::{BoundingBox, WordResult, normalize_number, parse_tsv_words};
+
+#[test]
+fn tsv_rows_become_words() {
+    let tsv = "1\t1\t0\t0\t0\t0\t0\t0\t200\t40\t-1\t\n\
+               5\t1\t1\t1\t1\t1\t4\t6\t50\t20\t96.5\tTotal\n\
+               5\t1\t1\t1\t1\t2\t60\t6\t70\t20\t88\t$1,200\n\
+               5\t1\t1\t1\t1\t3\t140\t6\t10\t20\t95\t \n";
+
+    let words = parse_tsv_words(tsv);
+
+    assert_eq!(
+        words,
+        vec![
+            WordResult::new("Total", 96.5, BoundingBox { x: 4, y: 6, width: 50, height: 20 }),
+            WordResult::new("$1,200", 88.0, BoundingBox { x: 60, y: 6, width: 70, height: 20 }),
+        ]
+    );
+}
+
+#[test]
+fn a_comma_before_cents_is_the_decimal_separator() {
+    assert_eq!(normalize_number("12,50 €"), "12.50€");
+    assert_eq!(normalize_number("12.50 €"), "12.50€");
+}
+
+#[test]
+fn the_last_separator_is_the_decimal_one() {
+    assert_eq!(normalize_number("1.234,5 kr"), "1234.5kr");
+    assert_eq!(normalize_number("$ 1,234.50"), "$1234.50");
+}
+
+#[test]
+fn three_digits_after_a_single_separator_are_a_thousands_group() {
+    assert_eq!(normalize_number("1,000"), "1000");
+    assert_eq!(normalize_number("1.000"), "1000");
+    assert_eq!(normalize_number("0.125"), "0.125");
+    assert_eq!(normalize_number("(12)"), "-12");
+}
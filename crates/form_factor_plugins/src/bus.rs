@@ -1,8 +1,26 @@
 //! Event bus for plugin communication.
+//!
+//! Each event travels with the tracing span it was sent from. Draining the
+//! bus with [`EventBus::drain_traced`] gives every event an `event_bus_hop`
+//! span that follows from the sender's, so the log events of one request,
+//! such as a detection run and the notification of its results, stay
+//! linked across the hop.
 
 use crate::event::AppEvent;
 use tokio::sync::mpsc;
-use tracing::{debug, warn};
+use tracing::{Span, debug, debug_span, warn};
+
+/// Event in transit on the bus, with the span it belongs to
+///
+/// On the channel the span is the sender's; once drained it is the
+/// event's `event_bus_hop` span. Enter it while handling the event.
+#[derive(Debug, Clone)]
+pub struct TracedEvent {
+    /// The event
+    pub event: AppEvent,
+    /// Span the event belongs to
+    pub span: Span,
+}
 
 /// Event bus for plugin-to-plugin and plugin-to-app communication.
 ///
@@ -11,9 +29,9 @@ use tracing::{debug, warn};
 /// distribute events to all registered plugins.
 pub struct EventBus {
     /// Sender side of the event channel
-    tx: mpsc::UnboundedSender<AppEvent>,
+    tx: mpsc::UnboundedSender<TracedEvent>,
     /// Receiver side of the event channel
-    rx: mpsc::UnboundedReceiver<AppEvent>,
+    rx: mpsc::UnboundedReceiver<TracedEvent>,
 }
 
 impl EventBus {
@@ -37,7 +55,7 @@ impl EventBus {
     ///
     /// Returns `None` if no events are currently available.
     pub fn try_recv(&mut self) -> Option<AppEvent> {
-        self.rx.try_recv().ok()
+        self.rx.try_recv().ok().map(|traced| traced.event)
     }
 
     /// Collects all currently available events from the bus.
@@ -55,6 +73,24 @@ impl EventBus {
         events
     }
 
+    /// Collects all currently available events, each with its hop span.
+    ///
+    /// Each span follows from the span the event was sent from. Enter it
+    /// while handling the event so the handler's logs are linked to the sender's.
+    pub fn drain_traced(&mut self) -> Vec<TracedEvent> {
+        let mut events = Vec::new();
+        while let Ok(TracedEvent { event, span: sender }) = self.rx.try_recv() {
+            let name: &'static str = (&event).into();
+            let span = debug_span!("event_bus_hop", event = name);
+            span.follows_from(&sender);
+            events.push(TracedEvent { event, span });
+        }
+        if !events.is_empty() {
+            debug!(count = events.len(), "Drained traced events from bus");
+        }
+        events
+    }
+
     /// Gets the number of events currently queued in the bus.
     ///
     /// Note: This is an approximation and may not be exact due to concurrent access.
@@ -77,7 +113,7 @@ impl Default for EventBus {
 /// events concurrently.
 #[derive(Clone)]
 pub struct EventSender {
-    tx: mpsc::UnboundedSender<AppEvent>,
+    tx: mpsc::UnboundedSender<TracedEvent>,
 }
 
 impl EventSender {
@@ -86,18 +122,19 @@ impl EventSender {
     /// This is primarily useful for unit tests where you need to create
     /// a sender without a full EventBus.
     #[cfg(test)]
-    pub fn new_test() -> (Self, mpsc::UnboundedReceiver<AppEvent>) {
+    pub fn new_test() -> (Self, mpsc::UnboundedReceiver<TracedEvent>) {
         let (tx, rx) = mpsc::unbounded_channel();
         (Self { tx }, rx)
     }
 
-    /// Sends an event to the bus.
+    /// Sends an event to the bus from the current span.
     ///
     /// # Errors
     /// Returns an error if the bus has been closed (receiver dropped).
     pub fn send(&self, event: AppEvent) -> Result<(), SendError> {
         debug!(?event, "Sending event to bus");
-        self.tx.send(event).map_err(|_e| {
+        let span = Span::current();
+        self.tx.send(TracedEvent { event, span }).map_err(|_e| {
             warn!("Failed to send event: receiver closed");
             SendError {
                 kind: SendErrorKind::ReceiverClosed,
@@ -1,11 +1,16 @@
 //! Canvas plugin for drawing tools and canvas manipulation.
 //!
 //! This plugin provides UI for:
-//! - Tool selection (Select, Rectangle, Circle, Freehand, Edit, Rotate)
+//! - Tool selection (Select, Rectangle, Circle, Freehand, Edit, Rotate), followed
+//!   by the custom tools registered with the canvas
+//! - Style preset selection for new shapes
 //! - Canvas pan and zoom controls
-//! - Drawing state display
+//! - Drawing state display, read from the canvas snapshot
+//! - Opening the review comments, page assignment, session stats, bookmarks,
+//!   log, and settings windows
 
 use crate::{event::AppEvent, plugin::{Plugin, PluginContext}};
+use form_factor_core::{ANCHOR_TOOLBAR, register_anchor};
 use form_factor_drawing::ToolMode;
 use strum::IntoEnumIterator;
 use tracing::{debug, instrument};
@@ -14,11 +19,14 @@ use tracing::{debug, instrument};
 ///
 /// Provides a toolbar with:
 /// - Tool mode selection buttons
+/// - Style preset picker
 /// - Zoom level display and controls
 /// - Pan offset display
 pub struct CanvasPlugin {
     /// Current selected tool mode
     current_tool: ToolMode,
+    /// Id of the custom tool selected instead of a built-in tool
+    custom_tool: Option<String>,
     /// Current zoom level (1.0 = 100%)
     zoom: f32,
     /// Pan offset X
@@ -32,6 +40,7 @@ impl CanvasPlugin {
     pub fn new() -> Self {
         Self {
             current_tool: ToolMode::default(),
+            custom_tool: None,
             zoom: 1.0,
             pan_x: 0.0,
             pan_y: 0.0,
@@ -40,22 +49,62 @@ impl CanvasPlugin {
 
     /// Renders the tool selection buttons.
     fn render_tool_buttons(&mut self, ui: &mut egui::Ui, ctx: &PluginContext) {
-        ui.horizontal(|ui| {
+        let row = ui.horizontal(|ui| {
             ui.label("Tools:");
 
             for tool in ToolMode::iter() {
                 let label = format!("{:?}", tool);
-                let selected = self.current_tool == tool;
+                let selected = self.custom_tool.is_none() && self.current_tool == tool;
 
                 if ui.selectable_label(selected, label).clicked() {
                     debug!(?tool, "Tool selected");
                     self.current_tool = tool;
+                    self.custom_tool = None;
                     ctx.events.emit(AppEvent::ToolSelected {
                         tool_name: format!("{:?}", tool),
                     });
                 }
             }
+
+            for tool in ctx.canvas.custom_tools() {
+                let label = if tool.icon.is_empty() {
+                    tool.label.clone()
+                } else {
+                    format!("{} {}", tool.icon, tool.label)
+                };
+                let selected = self.custom_tool.as_ref() == Some(&tool.id);
+
+                if ui.selectable_label(selected, label).clicked() {
+                    debug!(tool = %tool.id, "Custom tool selected");
+                    self.custom_tool = Some(tool.id.clone());
+                    ctx.events.emit(AppEvent::ToolSelected {
+                        tool_name: tool.id.clone(),
+                    });
+                }
+            }
+        });
+        register_anchor(ui.ctx(), ANCHOR_TOOLBAR, row.response.rect);
+    }
+
+    /// Renders the style preset picker for new shapes.
+    fn render_style_presets(&self, ui: &mut egui::Ui, ctx: &PluginContext) {
+        let current = ctx.canvas.style_preset().map(str::to_string);
+        let mut choice = current.clone();
+        ui.horizontal(|ui| {
+            ui.label("Style:");
+            egui::ComboBox::from_id_salt("toolbar_style_preset")
+                .selected_text(current.as_deref().unwrap_or("Default"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut choice, None, "Default");
+                    for name in ctx.canvas.style_presets() {
+                        ui.selectable_value(&mut choice, Some(name.clone()), name);
+                    }
+                });
         });
+        if choice != current {
+            debug!(preset = ?choice, "Style preset selected");
+            ctx.events.emit(AppEvent::StylePresetSelected { name: choice });
+        }
     }
 
     /// Renders zoom controls.
@@ -89,6 +138,21 @@ impl CanvasPlugin {
             ui.label(format!("X: {:.1}, Y: {:.1}", self.pan_x, self.pan_y));
         });
     }
+
+    /// Renders shape and detection counts and the selected shape from the canvas snapshot.
+    fn render_canvas_summary(&self, ui: &mut egui::Ui, ctx: &PluginContext) {
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "Shapes: {}  Detections: {}",
+                ctx.canvas.shapes().len(),
+                ctx.canvas.detections().len()
+            ));
+            if let Some(shape) = ctx.canvas.selected() {
+                ui.separator();
+                ui.label(format!("Selected: {}", shape.name()));
+            }
+        });
+    }
 }
 
 impl Default for CanvasPlugin {
@@ -107,8 +171,57 @@ impl Plugin for CanvasPlugin {
         ui.group(|ui| {
             ui.heading("Canvas Tools");
             self.render_tool_buttons(ui, ctx);
+            self.render_style_presets(ui, ctx);
             self.render_zoom_controls(ui, ctx);
             self.render_pan_display(ui);
+            self.render_canvas_summary(ui, ctx);
+
+            ui.horizontal(|ui| {
+                if ui.button("Comments").clicked() {
+                    debug!("Comments panel toggled");
+                    ctx.events.emit(AppEvent::CommentsPanelToggled);
+                }
+                if ui.button("Assignments").clicked() {
+                    debug!("Workflow panel toggled");
+                    ctx.events.emit(AppEvent::WorkflowPanelToggled);
+                }
+                if ui.button("Stats").clicked() {
+                    debug!("Stats panel toggled");
+                    ctx.events.emit(AppEvent::StatsPanelToggled);
+                }
+                if ui.button("Bookmarks").clicked() {
+                    debug!("Bookmarks panel toggled");
+                    ctx.events.emit(AppEvent::BookmarksPanelToggled);
+                }
+                if ui.button("Legend").clicked() {
+                    debug!("Legend toggled");
+                    ctx.events.emit(AppEvent::LegendToggled);
+                }
+                if ui.button("Screenshot").clicked() {
+                    debug!("Screenshot requested");
+                    ctx.events.emit(AppEvent::ScreenshotRequested);
+                }
+                if ui.button("Rename").clicked() {
+                    debug!("Rename panel toggled");
+                    ctx.events.emit(AppEvent::RenamePanelToggled);
+                }
+                if ui.button("Data Entry").clicked() {
+                    debug!("Data entry panel toggled");
+                    ctx.events.emit(AppEvent::DataEntryPanelToggled);
+                }
+                if ui.button("Log").clicked() {
+                    debug!("Log viewer toggled");
+                    ctx.events.emit(AppEvent::LogViewerToggled);
+                }
+                if ui.button("Settings").clicked() {
+                    debug!("Settings requested");
+                    ctx.events.emit(AppEvent::SettingsRequested);
+                }
+                if ui.button("Tour").clicked() {
+                    debug!("Guided tour requested");
+                    ctx.events.emit(AppEvent::TourRequested);
+                }
+            });
         });
     }
 
@@ -126,6 +239,22 @@ impl Plugin for CanvasPlugin {
                 self.pan_y = *y;
                 None
             }
+            AppEvent::CanvasChanged {
+                zoom, pan_x, pan_y, tool, ..
+            } => {
+                debug!(zoom, pan_x, pan_y, tool, "Canvas changed");
+                self.zoom = *zoom;
+                self.pan_x = *pan_x;
+                self.pan_y = *pan_y;
+                match ToolMode::iter().find(|mode| mode.to_string() == *tool) {
+                    Some(mode) => {
+                        self.current_tool = mode;
+                        self.custom_tool = None;
+                    }
+                    None => self.custom_tool = Some(tool.clone()),
+                }
+                None
+            }
             _ => None,
         }
     }
@@ -172,4 +301,24 @@ mod tests {
         assert_eq!(plugin.pan_x, 10.0);
         assert_eq!(plugin.pan_y, 20.0);
     }
+
+    #[test]
+    fn test_canvas_changed_event_handling() {
+        let mut plugin = CanvasPlugin::new();
+        let (sender, _rx) = crate::EventSender::new_test();
+        let ctx = PluginContext::new(sender);
+
+        let event = AppEvent::CanvasChanged {
+            zoom: 3.0,
+            pan_x: -4.0,
+            pan_y: 8.0,
+            tool: "Circle".to_string(),
+            visible_layers: vec!["Canvas".to_string()],
+            selected_layer: None,
+        };
+        plugin.on_event(&event, &ctx);
+
+        assert_eq!((plugin.zoom, plugin.pan_x, plugin.pan_y), (3.0, -4.0, 8.0));
+        assert_eq!(plugin.current_tool, ToolMode::Circle);
+    }
 }
@@ -6,6 +6,7 @@
 //! - Detection results display
 
 use crate::{event::AppEvent, plugin::{Plugin, PluginContext}};
+use form_factor_core::{ANCHOR_DETECTION, register_anchor};
 use tracing::{debug, instrument};
 
 /// Plugin for computer vision detection features.
@@ -44,7 +45,7 @@ impl Plugin for DetectionPlugin {
 
     #[instrument(skip(self, ui, ctx))]
     fn ui(&mut self, ui: &mut egui::Ui, ctx: &PluginContext) {
-        ui.group(|ui| {
+        let group = ui.group(|ui| {
             ui.heading("Detection");
 
             ui.horizontal(|ui| {
@@ -64,6 +65,7 @@ impl Plugin for DetectionPlugin {
             ui.label(format!("Text regions: {}", self.text_count));
             ui.label(format!("Logos: {}", self.logo_count));
         });
+        register_anchor(ui.ctx(), ANCHOR_DETECTION, group.response.rect);
     }
 
     #[instrument(skip(self, _ctx), fields(plugin = "detection"))]
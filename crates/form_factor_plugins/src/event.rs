@@ -1,12 +1,27 @@
 //! Event types for plugin communication.
+//!
+//! Events can be encoded as self-describing JSON with [`AppEvent::encode`]
+//! for plugins that live outside the process or were built against an
+//! older copy of this module. The encoding carries
+//! [`EVENT_SCHEMA_VERSION`], and [`AppEvent::decode`] turns events it does
+//! not know into [`AppEvent::Unrecognized`] instead of failing, so older
+//! plugins keep working when new events are added.
 
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use tracing::instrument;
+
+/// Version of the event encoding written by [`AppEvent::encode`]
+///
+/// Bump when an existing event changes shape. Adding events or adding
+/// fields to an event does not need a new version.
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
 
 /// Events that can be sent between plugins and the application.
 ///
 /// Events enable decoupled communication between plugins. Plugins can emit
 /// events when something happens and subscribe to events from other plugins.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, strum::VariantNames, strum::IntoStaticStr)]
 #[non_exhaustive]
 pub enum AppEvent {
     /// Canvas zoom level changed
@@ -23,6 +38,25 @@ pub enum AppEvent {
         y: f32,
     },
 
+    /// Canvas view state after commands were applied to it
+    ///
+    /// Sent at most once per frame by the application. Plugins showing
+    /// canvas state should follow this event rather than the requests.
+    CanvasChanged {
+        /// Zoom factor
+        zoom: f32,
+        /// Pan X offset
+        pan_x: f32,
+        /// Pan Y offset
+        pan_y: f32,
+        /// Name of the active tool
+        tool: String,
+        /// Names of the visible layers, bottom to top
+        visible_layers: Vec<String>,
+        /// Name of the selected layer
+        selected_layer: Option<String>,
+    },
+
     /// A shape was selected
     ShapeSelected {
         /// Index of the selected shape
@@ -52,6 +86,46 @@ pub enum AppEvent {
         layer_name: String,
     },
 
+    /// One shape or detection was hidden or shown
+    ObjectVisibilityChanged {
+        /// Name of the layer holding the object
+        layer_name: String,
+        /// Index of the object on its layer
+        index: usize,
+        /// Whether the object is visible
+        visible: bool,
+    },
+
+    /// One shape or detection was locked or unlocked
+    ObjectLockChanged {
+        /// Name of the layer holding the object
+        layer_name: String,
+        /// Index of the object on its layer
+        index: usize,
+        /// Whether the object is locked
+        locked: bool,
+    },
+
+    /// Request to move one shape or detection
+    ObjectMoveRequested {
+        /// Name of the layer holding the object
+        layer_name: String,
+        /// Index of the object on its layer
+        index: usize,
+        /// Horizontal distance in canvas coordinates
+        dx: f32,
+        /// Vertical distance in canvas coordinates
+        dy: f32,
+    },
+
+    /// Request to remove one shape or detection
+    ObjectDeleteRequested {
+        /// Name of the layer holding the object
+        layer_name: String,
+        /// Index of the object on its layer
+        index: usize,
+    },
+
     /// A file was opened
     FileOpened {
         /// Path to the opened file
@@ -64,15 +138,30 @@ pub enum AppEvent {
         path: PathBuf,
     },
 
+    /// User requested to start a new project, optionally from a template
+    NewProjectRequested,
+
     /// User requested to open a file
     OpenFileRequested,
 
+    /// User requested to review flagged field values across many project files
+    BatchReviewRequested,
+
+    /// User requested to compare two versions of a project
+    ProjectDiffRequested,
+
+    /// User requested a report of field errors and warnings across many project files
+    ValidationReportRequested,
+
     /// User requested to save a file
     SaveFileRequested,
 
     /// User requested to save file with new name
     SaveAsRequested,
 
+    /// User requested to load a form image onto the canvas
+    LoadImageRequested,
+
     /// Text detection was requested
     TextDetectionRequested,
 
@@ -82,6 +171,108 @@ pub enum AppEvent {
     /// OCR text extraction was requested
     OcrExtractionRequested,
 
+    /// OCR text extraction was requested for detections changed since the last pass
+    OcrRefreshRequested,
+
+    /// User requested to show or hide the comments window
+    CommentsPanelToggled,
+
+    /// User requested to show or hide the page assignment window
+    WorkflowPanelToggled,
+
+    /// User requested to show or hide the session statistics window
+    StatsPanelToggled,
+
+    /// User requested to show or hide the view bookmarks window
+    BookmarksPanelToggled,
+
+    /// User requested to show or hide the color legend over the canvas
+    LegendToggled,
+
+    /// User requested to capture a screenshot of a canvas region
+    ScreenshotRequested,
+
+    /// User requested to go back to the previously inspected shape or detection
+    SelectionBackRequested,
+
+    /// User requested to go forward to the shape or detection left by going back
+    SelectionForwardRequested,
+
+    /// User requested to show or hide the find and rename window
+    RenamePanelToggled,
+
+    /// User requested to show or hide the data entry window
+    DataEntryPanelToggled,
+
+    /// User requested to show or hide the field tab order window
+    TabOrderPanelToggled,
+
+    /// User requested to show or hide the OCR reading order window
+    OcrOrderPanelToggled,
+
+    /// User requested to show or hide the OCR word boxes over the detections
+    OcrWordsToggled,
+
+    /// User requested to show or hide the field groups window
+    FieldGroupsPanelToggled,
+
+    /// User requested to set, change or remove the project password
+    ProjectPasswordRequested,
+
+    /// User requested to show or hide the project store window
+    ProjectStoreRequested,
+
+    /// User requested to show or hide the collaboration window
+    CollaborationRequested,
+
+    /// User requested to show or hide the log viewer
+    LogViewerToggled,
+
+    /// User requested to show or hide the frame timing overlay
+    FrameTimingToggled,
+
+    /// User requested to open the settings overlay
+    SettingsRequested,
+
+    /// User requested to replay the guided tour
+    TourRequested,
+
+    /// A plugin asked to open one of its overlays
+    OverlayOpenRequested {
+        /// Id of the overlay, as returned from [`Plugin::overlays`](crate::Plugin::overlays)
+        id: String,
+    },
+
+    /// Redaction preview was toggled on or off
+    RedactionModeChanged {
+        /// Whether redaction regions are previewed on the canvas
+        enabled: bool,
+    },
+
+    /// Sample data preview of field layouts was toggled on or off
+    TemplatePreviewChanged {
+        /// Whether fields are drawn filled with sample data
+        enabled: bool,
+    },
+
+    /// User requested to export a redacted copy of the form image
+    RedactedExportRequested,
+
+    /// User requested to export the canvas as PNG or SVG
+    CanvasExportRequested,
+
+    /// User requested to export the text read from the form as a text or Markdown file
+    TextExportRequested,
+
+    /// User requested to print the canvas to PDF
+    PrintRequested,
+
+    /// User requested a clean copy of the form with the entered values typeset onto it
+    RefillExportRequested,
+
+    /// User requested to export a diagnostic bundle for a bug report
+    DiagnosticBundleRequested,
+
     /// Detection results are available
     DetectionComplete {
         /// Number of detections found
@@ -92,10 +283,16 @@ pub enum AppEvent {
 
     /// A tool was selected
     ToolSelected {
-        /// Name of the selected tool
+        /// Name of the selected built-in tool, or id of the selected custom tool
         tool_name: String,
     },
 
+    /// A style preset was chosen for new shapes
+    StylePresetSelected {
+        /// Name of the preset, `None` for the default style
+        name: Option<String>,
+    },
+
     /// Custom event with arbitrary data
     Custom {
         /// Plugin that sent the event
@@ -105,6 +302,29 @@ pub enum AppEvent {
         /// JSON-encoded event data
         data: String,
     },
+
+    /// Encoded event this build does not know, kept so it can be passed on
+    ///
+    /// Produced by [`AppEvent::decode`] for events added after this build,
+    /// or changed in a newer schema version.
+    #[serde(skip)]
+    Unrecognized {
+        /// Schema version the event was encoded with
+        version: u32,
+        /// Name of the event
+        name: String,
+        /// JSON-encoded event fields, `null` for events without fields
+        data: String,
+    },
+}
+
+/// Encoded form of an event: schema version, event name and fields
+#[derive(Debug, Serialize, Deserialize)]
+struct EventEnvelope {
+    version: u32,
+    event: String,
+    #[serde(default)]
+    data: serde_json::Value,
 }
 
 impl AppEvent {
@@ -129,6 +349,78 @@ impl AppEvent {
         })
     }
 
+    /// Encodes the event as self-describing JSON tagged with [`EVENT_SCHEMA_VERSION`].
+    ///
+    /// An [`AppEvent::Unrecognized`] event is written back out as it was received.
+    ///
+    /// # Errors
+    /// Returns an error if the event cannot be serialized to JSON.
+    pub fn encode(&self) -> Result<String, serde_json::Error> {
+        let envelope = match self {
+            Self::Unrecognized { version, name, data } => EventEnvelope {
+                version: *version,
+                event: name.clone(),
+                data: serde_json::from_str(data)?,
+            },
+            event => {
+                let (event, data) = match serde_json::to_value(event)? {
+                    // Events without fields serialize as their name
+                    serde_json::Value::String(name) => (name, serde_json::Value::Null),
+                    serde_json::Value::Object(map) => map
+                        .into_iter()
+                        .next()
+                        .unwrap_or((String::new(), serde_json::Value::Null)),
+                    other => (other.to_string(), serde_json::Value::Null),
+                };
+                EventEnvelope {
+                    version: EVENT_SCHEMA_VERSION,
+                    event,
+                    data,
+                }
+            }
+        };
+        serde_json::to_string(&envelope)
+    }
+
+    /// Decodes an event written by [`AppEvent::encode`].
+    ///
+    /// Events this build does not know, and events from a newer schema
+    /// version whose fields no longer match, decode as
+    /// [`AppEvent::Unrecognized`]. Fields added to a known event are ignored.
+    ///
+    /// # Errors
+    /// Returns an error if the text is not an encoded event, has version 0,
+    /// or holds a known event from this or an older version that doesn't
+    /// match its definition.
+    #[instrument(level = "debug", skip(encoded), fields(len = encoded.len()))]
+    pub fn decode(encoded: &str) -> Result<Self, DecodeError> {
+        use strum::VariantNames;
+
+        let envelope: EventEnvelope = serde_json::from_str(encoded).map_err(DecodeError::Json)?;
+        if envelope.version == 0 {
+            return Err(DecodeError::UnsupportedVersion(envelope.version));
+        }
+
+        let unrecognized = |envelope: EventEnvelope| Self::Unrecognized {
+            version: envelope.version,
+            name: envelope.event,
+            data: envelope.data.to_string(),
+        };
+        if envelope.event == "Unrecognized" || !Self::VARIANTS.contains(&envelope.event.as_str()) {
+            return Ok(unrecognized(envelope));
+        }
+
+        let value = match &envelope.data {
+            serde_json::Value::Null => serde_json::Value::String(envelope.event.clone()),
+            data => serde_json::Value::Object(serde_json::Map::from_iter([(envelope.event.clone(), data.clone())])),
+        };
+        match serde_json::from_value(value) {
+            Ok(event) => Ok(event),
+            Err(_) if envelope.version > EVENT_SCHEMA_VERSION => Ok(unrecognized(envelope)),
+            Err(e) => Err(DecodeError::Json(e)),
+        }
+    }
+
     /// Attempts to deserialize the data from a custom event.
     ///
     /// # Errors
@@ -142,13 +434,15 @@ impl AppEvent {
     }
 }
 
-/// Error that can occur when decoding custom event data.
+/// Error that can occur when decoding an event or custom event data.
 #[derive(Debug)]
 pub enum DecodeError {
     /// The event is not a custom event
     NotCustomEvent,
     /// JSON deserialization failed
     Json(serde_json::Error),
+    /// The encoded event has a schema version no build has written
+    UnsupportedVersion(u32),
 }
 
 impl std::fmt::Display for DecodeError {
@@ -156,6 +450,7 @@ impl std::fmt::Display for DecodeError {
         match self {
             DecodeError::NotCustomEvent => write!(f, "Event is not a custom event"),
             DecodeError::Json(e) => write!(f, "JSON deserialization failed: {}", e),
+            DecodeError::UnsupportedVersion(version) => write!(f, "Unsupported event schema version {}", version),
         }
     }
 }
@@ -163,7 +458,7 @@ impl std::fmt::Display for DecodeError {
 impl std::error::Error for DecodeError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            DecodeError::NotCustomEvent => None,
+            DecodeError::NotCustomEvent | DecodeError::UnsupportedVersion(_) => None,
             DecodeError::Json(e) => Some(e),
         }
     }
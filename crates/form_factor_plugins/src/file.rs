@@ -4,26 +4,46 @@
 //! - Opening files
 //! - Saving the current project
 //! - Save-as functionality
-//! - Recent files list
+//! - Project password protection
+//! - Opening and saving in the configured project store
+//! - Recent files list with pinned favorites, groups and pruning
 //! - Current file path display
+//! - Redaction preview and redacted image export
+//! - Diagnostic bundle export for bug reports
 
 use crate::{event::AppEvent, plugin::{Plugin, PluginContext}};
+use form_factor_core::{ANCHOR_FILE, register_anchor};
+use form_factor_drawing::RecentProjects;
 use std::path::PathBuf;
-use tracing::{debug, instrument};
+use tracing::{debug, instrument, warn};
+
+/// Change to the recent files list requested from the panel
+enum RecentAction {
+    Pin(PathBuf),
+    Unpin(PathBuf),
+    SetGroup(PathBuf, Option<String>),
+    Remove(PathBuf),
+    PruneMissing,
+    SetMaxEntries(usize),
+}
 
 /// Plugin for file operations.
 ///
 /// Provides a panel with:
-/// - File operation buttons (Open, Save, Save As)
+/// - File operation buttons (New, Open, Save, Save As)
 /// - Current file path display
-/// - Recent files list
+/// - Recent files list, pinned favorites first, then by group
 pub struct FilePlugin {
     /// Currently open file path
     current_file: Option<PathBuf>,
-    /// List of recently opened files
-    recent_files: Vec<PathBuf>,
-    /// Maximum number of recent files to track
-    max_recent: usize,
+    /// Recently opened files, with pins and groups
+    recent_files: RecentProjects,
+    /// Whether changes made in the panel are saved to the recent projects config
+    persist_recent: bool,
+    /// Name typed for a new group in a recent file's context menu
+    new_group: String,
+    /// Whether the redaction preview is enabled
+    redaction_mode: bool,
 }
 
 impl FilePlugin {
@@ -31,28 +51,72 @@ impl FilePlugin {
     pub fn new() -> Self {
         Self {
             current_file: None,
-            recent_files: Vec::new(),
-            max_recent: 10,
+            recent_files: RecentProjects::new(),
+            persist_recent: false,
+            new_group: String::new(),
+            redaction_mode: false,
         }
     }
 
     /// Creates a new file plugin with a specified maximum number of recent files.
     pub fn with_max_recent(max_recent: usize) -> Self {
+        let mut plugin = Self::new();
+        plugin.recent_files.set_max_entries(max_recent);
+        plugin
+    }
+
+    /// Creates a file plugin showing the saved recent projects.
+    ///
+    /// Pins, groups and pruning done in the panel are saved back to the
+    /// recent projects config.
+    pub fn with_recent_projects(recent_files: RecentProjects) -> Self {
         Self {
-            current_file: None,
-            recent_files: Vec::new(),
-            max_recent,
+            recent_files,
+            persist_recent: true,
+            ..Self::new()
         }
     }
 
+    /// Recently opened files.
+    pub fn recent_files(&self) -> &RecentProjects {
+        &self.recent_files
+    }
+
     /// Renders the file operation buttons.
     fn render_file_buttons(&self, ui: &mut egui::Ui, ctx: &PluginContext) {
         ui.horizontal(|ui| {
+            if ui
+                .button("New...")
+                .on_hover_text("Start a blank project or one set up from a template")
+                .clicked()
+            {
+                debug!("New project requested");
+                ctx.events.emit(AppEvent::NewProjectRequested);
+            }
+
             if ui.button("Open...").clicked() {
                 debug!("Open file requested");
                 ctx.events.emit(AppEvent::OpenFileRequested);
             }
 
+            if ui
+                .button("Review...")
+                .on_hover_text("Step through low-confidence values across many filled forms")
+                .clicked()
+            {
+                debug!("Batch review requested");
+                ctx.events.emit(AppEvent::BatchReviewRequested);
+            }
+
+            if ui
+                .button("Compare...")
+                .on_hover_text("Show what changed between two versions of a project")
+                .clicked()
+            {
+                debug!("Project diff requested");
+                ctx.events.emit(AppEvent::ProjectDiffRequested);
+            }
+
             let save_enabled = self.current_file.is_some();
             if ui
                 .add_enabled(save_enabled, egui::Button::new("Save"))
@@ -66,9 +130,118 @@ impl FilePlugin {
                 debug!("Save as requested");
                 ctx.events.emit(AppEvent::SaveAsRequested);
             }
+
+            if ui
+                .button("Password...")
+                .on_hover_text("Save the project encrypted with a password")
+                .clicked()
+            {
+                debug!("Project password requested");
+                ctx.events.emit(AppEvent::ProjectPasswordRequested);
+            }
+
+            if ui
+                .button("Store...")
+                .on_hover_text("Open or save projects in the shared project store")
+                .clicked()
+            {
+                debug!("Project store requested");
+                ctx.events.emit(AppEvent::ProjectStoreRequested);
+            }
+
+            if ui
+                .button("Collaborate...")
+                .on_hover_text("Edit shapes together with others through the project store")
+                .clicked()
+            {
+                debug!("Collaboration requested");
+                ctx.events.emit(AppEvent::CollaborationRequested);
+            }
+
+            if ui.button("Load Image...").clicked() {
+                debug!("Load image requested");
+                ctx.events.emit(AppEvent::LoadImageRequested);
+            }
+        });
+    }
+
+    /// Renders the redaction preview toggle and export button.
+    fn render_redaction(&mut self, ui: &mut egui::Ui, ctx: &PluginContext) {
+        ui.horizontal(|ui| {
+            if ui.checkbox(&mut self.redaction_mode, "Redaction preview").changed() {
+                debug!(enabled = self.redaction_mode, "Redaction mode changed");
+                ctx.events.emit(AppEvent::RedactionModeChanged {
+                    enabled: self.redaction_mode,
+                });
+            }
+
+            if ui.button("Export Redacted...").clicked() {
+                debug!("Redacted export requested");
+                ctx.events.emit(AppEvent::RedactedExportRequested);
+            }
         });
     }
 
+    /// Renders the canvas export button.
+    fn render_canvas_export(&self, ui: &mut egui::Ui, ctx: &PluginContext) {
+        if ui
+            .button("Export Canvas...")
+            .on_hover_text("Save the form image with visible annotations as PNG or SVG")
+            .clicked()
+        {
+            debug!("Canvas export requested");
+            ctx.events.emit(AppEvent::CanvasExportRequested);
+        }
+    }
+
+    /// Renders the print button.
+    fn render_print(&self, ui: &mut egui::Ui, ctx: &PluginContext) {
+        if ui
+            .button("Print to PDF...")
+            .on_hover_text("Lay out the form image with chosen layers and a field value appendix for paper review")
+            .clicked()
+        {
+            debug!("Print requested");
+            ctx.events.emit(AppEvent::PrintRequested);
+        }
+    }
+
+    /// Renders the refilled form export button.
+    fn render_refill(&self, ui: &mut egui::Ui, ctx: &PluginContext) {
+        if ui
+            .button("Export Refilled Form...")
+            .on_hover_text("Typeset the entered field values onto a clean template image as PNG or PDF")
+            .clicked()
+        {
+            debug!("Refilled form export requested");
+            ctx.events.emit(AppEvent::RefillExportRequested);
+        }
+    }
+
+    /// Renders the validation report export button.
+    fn render_validation_report(&self, ui: &mut egui::Ui, ctx: &PluginContext) {
+        if ui
+            .button("Validation Report...")
+            .on_hover_text("List field errors and warnings across many filled forms as CSV or HTML")
+            .clicked()
+        {
+            debug!("Validation report requested");
+            ctx.events.emit(AppEvent::ValidationReportRequested);
+        }
+    }
+
+    /// Renders the diagnostic bundle export button.
+    fn render_diagnostics(&self, ui: &mut egui::Ui, ctx: &PluginContext) {
+        if ui
+            .button("Export Diagnostic Bundle...")
+            .on_hover_text("Save logs, crash reports and an anonymized project summary for a bug report")
+            .clicked()
+        {
+            debug!("Diagnostic bundle requested");
+            ctx.events.emit(AppEvent::DiagnosticBundleRequested);
+        }
+    }
+
     /// Renders the current file path display.
     fn render_current_file(&self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
@@ -81,36 +254,164 @@ impl FilePlugin {
         });
     }
 
-    /// Renders the recent files list.
-    fn render_recent_files(&self, ui: &mut egui::Ui, ctx: &PluginContext) {
-        if !self.recent_files.is_empty() {
-            ui.separator();
+    /// Renders the recent files list: pinned files, then each group, then the rest.
+    fn render_recent_files(&mut self, ui: &mut egui::Ui, ctx: &PluginContext) {
+        if self.recent_files.is_empty() {
+            return;
+        }
+
+        ui.separator();
+        let mut actions = Vec::new();
+        ui.horizontal(|ui| {
             ui.label("Recent files:");
+            if ui
+                .small_button("Remove missing")
+                .on_hover_text("Forget files that were moved or deleted")
+                .clicked()
+            {
+                actions.push(RecentAction::PruneMissing);
+            }
+            let mut max_entries = self.recent_files.max_entries();
+            if ui
+                .add(egui::DragValue::new(&mut max_entries).range(1..=50).prefix("Keep "))
+                .on_hover_text("Unpinned files to remember")
+                .changed()
+            {
+                actions.push(RecentAction::SetMaxEntries(max_entries));
+            }
+        });
 
-            for path in &self.recent_files {
-                if ui.button(path.display().to_string()).clicked() {
-                    debug!(?path, "Recent file clicked");
-                    ctx.events.emit(AppEvent::FileOpened {
-                        path: path.clone(),
-                    });
+        if !self.recent_files.pinned().is_empty() {
+            ui.label("Pinned:");
+            for path in self.recent_files.pinned() {
+                Self::render_recent_file(ui, ctx, &self.recent_files, &mut self.new_group, path, &mut actions);
+            }
+        }
+        for (group, paths) in self.recent_files.grouped() {
+            match group {
+                Some(group) => {
+                    egui::CollapsingHeader::new(group)
+                        .id_salt(("recent_group", group))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for path in paths {
+                                Self::render_recent_file(ui, ctx, &self.recent_files, &mut self.new_group, path, &mut actions);
+                            }
+                        });
+                }
+                None => {
+                    for path in paths {
+                        Self::render_recent_file(ui, ctx, &self.recent_files, &mut self.new_group, path, &mut actions);
+                    }
                 }
             }
         }
+
+        if !actions.is_empty() {
+            self.apply_recent_actions(actions);
+        }
     }
 
-    /// Adds a file to the recent files list.
-    fn add_recent_file(&mut self, path: PathBuf) {
-        // Remove if already in list
-        self.recent_files.retain(|p| p != &path);
+    /// Renders one recent file, with pin, group and remove in its context menu.
+    fn render_recent_file(
+        ui: &mut egui::Ui,
+        ctx: &PluginContext,
+        recent_files: &RecentProjects,
+        new_group: &mut String,
+        path: &PathBuf,
+        actions: &mut Vec<RecentAction>,
+    ) {
+        let pinned = recent_files.is_pinned(path);
+        let label = if pinned {
+            format!("📌 {}", path.display())
+        } else {
+            path.display().to_string()
+        };
+        let response = ui.button(label);
+        if response.clicked() {
+            debug!(?path, "Recent file clicked");
+            ctx.events.emit(AppEvent::FileOpened {
+                path: path.clone(),
+            });
+        }
+
+        response.context_menu(|ui| {
+            if pinned {
+                if ui.button("Unpin").clicked() {
+                    actions.push(RecentAction::Unpin(path.clone()));
+                    ui.close();
+                }
+            } else if ui.button("Pin").clicked() {
+                actions.push(RecentAction::Pin(path.clone()));
+                ui.close();
+            }
+
+            ui.menu_button("Group", |ui| {
+                let current = recent_files.group(path);
+                for name in recent_files.group_names() {
+                    if ui.radio(current == Some(name), name).clicked() {
+                        actions.push(RecentAction::SetGroup(path.clone(), Some(name.to_string())));
+                        ui.close();
+                    }
+                }
+                if ui.radio(current.is_none(), "None").clicked() {
+                    actions.push(RecentAction::SetGroup(path.clone(), None));
+                    ui.close();
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let edit = ui.add(egui::TextEdit::singleline(new_group).hint_text("New group").desired_width(120.0));
+                    let submit = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if (ui.button("Add").clicked() || submit) && !new_group.trim().is_empty() {
+                        actions.push(RecentAction::SetGroup(path.clone(), Some(std::mem::take(new_group))));
+                        ui.close();
+                    }
+                });
+            });
 
-        // Add to front
-        self.recent_files.insert(0, path);
+            ui.separator();
+            if ui.button("Remove from list").clicked() {
+                actions.push(RecentAction::Remove(path.clone()));
+                ui.close();
+            }
+        });
+    }
 
-        // Trim to max size
-        if self.recent_files.len() > self.max_recent {
-            self.recent_files.truncate(self.max_recent);
+    /// Applies changes requested from the recent files list and saves them.
+    fn apply_recent_actions(&mut self, actions: Vec<RecentAction>) {
+        for action in actions {
+            match action {
+                RecentAction::Pin(path) => {
+                    self.recent_files.pin(&path);
+                }
+                RecentAction::Unpin(path) => self.recent_files.unpin(&path),
+                RecentAction::SetGroup(path, group) => {
+                    self.recent_files.set_group(&path, group);
+                }
+                RecentAction::Remove(path) => {
+                    self.recent_files.remove(&path);
+                }
+                RecentAction::PruneMissing => {
+                    let removed = self.recent_files.prune_missing();
+                    debug!(removed = removed.len(), "Pruned missing recent files");
+                }
+                RecentAction::SetMaxEntries(max_entries) => self.recent_files.set_max_entries(max_entries),
+            }
+        }
+
+        if self.persist_recent
+            && let Err(e) = self.recent_files.save()
+        {
+            warn!("Failed to save recent projects: {}", e);
         }
     }
+
+    /// Adds a file to the recent files list.
+    ///
+    /// The canvas saves the list itself when it opens or saves a project.
+    fn add_recent_file(&mut self, path: PathBuf) {
+        self.recent_files.add(path);
+    }
 }
 
 impl Default for FilePlugin {
@@ -126,13 +427,22 @@ impl Plugin for FilePlugin {
 
     #[instrument(skip(self, ui, ctx))]
     fn ui(&mut self, ui: &mut egui::Ui, ctx: &PluginContext) {
-        ui.group(|ui| {
+        let group = ui.group(|ui| {
             ui.heading("File");
             self.render_file_buttons(ui, ctx);
             ui.separator();
             self.render_current_file(ui);
             self.render_recent_files(ui, ctx);
+            ui.separator();
+            self.render_redaction(ui, ctx);
+            ui.separator();
+            self.render_canvas_export(ui, ctx);
+            self.render_print(ui, ctx);
+            self.render_refill(ui, ctx);
+            self.render_validation_report(ui, ctx);
+            self.render_diagnostics(ui, ctx);
         });
+        register_anchor(ui.ctx(), ANCHOR_FILE, group.response.rect);
     }
 
     #[instrument(skip(self, _ctx), fields(plugin = "file"))]
@@ -162,6 +472,7 @@ impl Plugin for FilePlugin {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use form_factor_drawing::MAX_RECENT_PROJECTS;
 
     #[test]
     fn test_file_plugin_creation() {
@@ -169,7 +480,8 @@ mod tests {
         assert_eq!(plugin.name(), "file");
         assert!(plugin.current_file.is_none());
         assert!(plugin.recent_files.is_empty());
-        assert_eq!(plugin.max_recent, 10);
+        assert_eq!(plugin.recent_files.max_entries(), MAX_RECENT_PROJECTS);
+        assert!(!plugin.redaction_mode);
     }
 
     #[test]
@@ -186,7 +498,7 @@ mod tests {
 
         assert_eq!(plugin.current_file, Some(test_path.clone()));
         assert_eq!(plugin.recent_files.len(), 1);
-        assert_eq!(plugin.recent_files[0], test_path);
+        assert_eq!(plugin.recent_files.projects()[0], test_path);
     }
 
     #[test]
@@ -204,9 +516,9 @@ mod tests {
 
         // Should only keep the 3 most recent
         assert_eq!(plugin.recent_files.len(), 3);
-        assert_eq!(plugin.recent_files[0], PathBuf::from("/test/file5.txt"));
-        assert_eq!(plugin.recent_files[1], PathBuf::from("/test/file4.txt"));
-        assert_eq!(plugin.recent_files[2], PathBuf::from("/test/file3.txt"));
+        assert_eq!(plugin.recent_files.projects()[0], PathBuf::from("/test/file5.txt"));
+        assert_eq!(plugin.recent_files.projects()[1], PathBuf::from("/test/file4.txt"));
+        assert_eq!(plugin.recent_files.projects()[2], PathBuf::from("/test/file3.txt"));
     }
 
     #[test]
@@ -223,6 +535,42 @@ mod tests {
 
         // Should only appear once
         assert_eq!(plugin.recent_files.len(), 1);
-        assert_eq!(plugin.recent_files[0], test_path);
+        assert_eq!(plugin.recent_files.projects()[0], test_path);
+    }
+
+    #[test]
+    fn test_pinned_recent_file_survives_limit() {
+        let mut plugin = FilePlugin::with_max_recent(2);
+        let (sender, _rx) = crate::EventSender::new_test();
+        let ctx = PluginContext::new(sender);
+
+        let favorite = PathBuf::from("/test/favorite.txt");
+        plugin.on_event(&AppEvent::FileOpened { path: favorite.clone() }, &ctx);
+        plugin.apply_recent_actions(vec![
+            RecentAction::Pin(favorite.clone()),
+            RecentAction::SetGroup(favorite.clone(), Some("W-2".to_string())),
+        ]);
+
+        for i in 1..=3 {
+            let path = PathBuf::from(format!("/test/file{}.txt", i));
+            plugin.on_event(&AppEvent::FileOpened { path }, &ctx);
+        }
+
+        assert!(plugin.recent_files.is_pinned(&favorite));
+        assert_eq!(plugin.recent_files.group(&favorite), Some("W-2"));
+        assert_eq!(plugin.recent_files.len(), 3);
+    }
+
+    #[test]
+    fn test_remove_recent_file() {
+        let mut plugin = FilePlugin::new();
+        let (sender, _rx) = crate::EventSender::new_test();
+        let ctx = PluginContext::new(sender);
+
+        let test_path = PathBuf::from("/test/file.txt");
+        plugin.on_event(&AppEvent::FileOpened { path: test_path.clone() }, &ctx);
+        plugin.apply_recent_actions(vec![RecentAction::Remove(test_path)]);
+
+        assert!(plugin.recent_files.is_empty());
     }
 }
@@ -5,9 +5,11 @@
 //! - Layer lock status
 //! - Layer selection
 //! - Layer z-order display
+//! - Per-object visibility and lock toggles
 
 use crate::{event::AppEvent, plugin::{Plugin, PluginContext}};
-use form_factor_drawing::LayerType;
+use form_factor_core::{ANCHOR_LAYERS, register_anchor};
+use form_factor_drawing::{LayerType, Shape};
 use strum::IntoEnumIterator;
 use tracing::{debug, instrument};
 
@@ -59,6 +61,16 @@ impl LayersPlugin {
         }
     }
 
+    /// Layers whose visibility toggle is on, bottom to top.
+    pub fn visible_layers(&self) -> Vec<LayerType> {
+        self.layers.iter().filter(|l| l.visible).map(|l| l.layer_type).collect()
+    }
+
+    /// Layer highlighted as selected, if any.
+    pub fn selected_layer(&self) -> Option<LayerType> {
+        self.selected_layer
+    }
+
     /// Renders the layer list.
     fn render_layer_list(&mut self, ui: &mut egui::Ui, ctx: &PluginContext) {
         ui.vertical(|ui| {
@@ -133,6 +145,57 @@ impl LayersPlugin {
                 }
             });
         });
+
+        if matches!(layer.layer_type, LayerType::Shapes | LayerType::Detections) {
+            let objects = match layer.layer_type {
+                LayerType::Shapes => ctx.canvas.shapes(),
+                _ => ctx.canvas.detections(),
+            };
+            if !objects.is_empty() {
+                ui.indent(("layer_objects", index), |ui| {
+                    Self::render_object_list(ui, &layer.name, objects, ctx);
+                });
+            }
+        }
+    }
+
+    /// Renders the objects on a layer with their visibility and lock toggles.
+    fn render_object_list(ui: &mut egui::Ui, layer_name: &str, objects: &[Shape], ctx: &PluginContext) {
+        egui::CollapsingHeader::new(format!("{} objects ({})", layer_name, objects.len()))
+            .id_salt(("layer_objects", layer_name))
+            .show(ui, |ui| {
+                for (index, object) in objects.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let label = match object.name() {
+                            "" => format!("Object {}", index + 1),
+                            name => name.to_string(),
+                        };
+                        ui.label(label);
+
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            let lock_icon = if object.is_locked() { "🔒" } else { "🔓" };
+                            if ui.button(lock_icon).on_hover_text("Toggle lock").clicked() {
+                                debug!(layer_name, index, locked = !object.is_locked(), "Object lock toggled");
+                                ctx.events.emit(AppEvent::ObjectLockChanged {
+                                    layer_name: layer_name.to_string(),
+                                    index,
+                                    locked: !object.is_locked(),
+                                });
+                            }
+
+                            let eye_icon = if object.is_hidden() { "⚫" } else { "👁" };
+                            if ui.button(eye_icon).on_hover_text("Toggle visibility").clicked() {
+                                debug!(layer_name, index, visible = object.is_hidden(), "Object visibility toggled");
+                                ctx.events.emit(AppEvent::ObjectVisibilityChanged {
+                                    layer_name: layer_name.to_string(),
+                                    index,
+                                    visible: object.is_hidden(),
+                                });
+                            }
+                        });
+                    });
+                }
+            });
     }
 }
 
@@ -149,11 +212,12 @@ impl Plugin for LayersPlugin {
 
     #[instrument(skip(self, ui, ctx))]
     fn ui(&mut self, ui: &mut egui::Ui, ctx: &PluginContext) {
-        ui.group(|ui| {
+        let group = ui.group(|ui| {
             ui.heading("Layers");
             ui.separator();
             self.render_layer_list(ui, ctx);
         });
+        register_anchor(ui.ctx(), ANCHOR_LAYERS, group.response.rect);
     }
 
     #[instrument(skip(self, _ctx), fields(plugin = "layers"))]
@@ -175,6 +239,22 @@ impl Plugin for LayersPlugin {
                 }
                 None
             }
+            AppEvent::CanvasChanged {
+                visible_layers,
+                selected_layer,
+                ..
+            } => {
+                debug!(?visible_layers, ?selected_layer, "Received canvas state");
+                for layer in &mut self.layers {
+                    layer.visible = visible_layers.contains(&layer.name);
+                }
+                self.selected_layer = self
+                    .layers
+                    .iter()
+                    .find(|l| Some(&l.name) == selected_layer.as_ref())
+                    .map(|l| l.layer_type);
+                None
+            }
             _ => None,
         }
     }
@@ -192,7 +272,7 @@ mod tests {
     fn test_layers_plugin_creation() {
         let plugin = LayersPlugin::new();
         assert_eq!(plugin.name(), "layers");
-        assert_eq!(plugin.layers.len(), 4); // Canvas, Detections, Shapes, Grid
+        assert_eq!(plugin.layers.len(), 5); // Canvas, Highlights, Detections, Shapes, Grid
         assert!(plugin.selected_layer.is_none());
     }
 
@@ -12,6 +12,7 @@
 //! - **Event Bus**: Message passing system using `tokio::sync::mpsc`
 //! - **Plugin Manager**: Coordinates plugin lifecycle and event distribution
 //! - **App Events**: Typed events for inter-plugin communication
+//! - **Command Palette**: Fuzzy-searchable list of actions that emit events
 //!
 //! # Features
 //!
@@ -22,6 +23,7 @@
 //! - `plugin-file` - File open/save operations
 //! - `plugin-detection` - Computer vision detection features
 //! - `plugin-ocr` - OCR text extraction
+//! - `plugin-quality` - OCR accuracy dashboard
 //! - `all-plugins` - Enable all available plugins
 //!
 //! # Example
@@ -56,12 +58,14 @@
 mod bus;
 mod event;
 mod manager;
+mod palette;
 mod plugin;
 
 // Re-export public API
-pub use bus::{EventBus, EventSender, SendError, SendErrorKind};
-pub use event::{AppEvent, DecodeError};
+pub use bus::{EventBus, EventSender, SendError, SendErrorKind, TracedEvent};
+pub use event::{AppEvent, DecodeError, EVENT_SCHEMA_VERSION};
 pub use manager::PluginManager;
+pub use palette::{Command, CommandPalette, CommandRegistry, PALETTE_SHORTCUT, fuzzy_score};
 pub use plugin::{Plugin, PluginBuilder, PluginContext};
 
 // Feature-gated plugin modules
@@ -79,3 +83,6 @@ pub mod detection;
 
 #[cfg(feature = "plugin-ocr")]
 pub mod ocr;
+
+#[cfg(feature = "plugin-quality")]
+pub mod quality;
@@ -1,7 +1,11 @@
 //! Plugin manager for coordinating multiple plugins.
 
-use crate::{bus::EventBus, plugin::{Plugin, PluginContext}};
+use crate::{bus::{EventBus, TracedEvent}, plugin::{Plugin, PluginContext}};
+use form_factor_core::OverlaySpec;
+use std::collections::BTreeSet;
 use tracing::{debug, info, instrument, warn};
+#[cfg(feature = "canvas")]
+use form_factor_drawing::{CanvasSnapshot, CustomTool};
 
 /// Manages the lifecycle and coordination of all plugins.
 ///
@@ -15,6 +19,16 @@ pub struct PluginManager {
     plugins: Vec<Box<dyn Plugin>>,
     /// Event bus for plugin communication
     event_bus: EventBus,
+    /// Overlays contributed by plugins, with the index of the contributing plugin
+    overlays: Vec<(usize, OverlaySpec)>,
+    /// Names of plugins hidden from the sidebar
+    hidden: BTreeSet<String>,
+    /// Canvas snapshot handed to plugins in their context
+    #[cfg(feature = "canvas")]
+    canvas: CanvasSnapshot,
+    /// Custom tools contributed by plugins, waiting to be registered with the canvas
+    #[cfg(feature = "canvas")]
+    tools: Vec<Box<dyn CustomTool>>,
 }
 
 impl PluginManager {
@@ -24,6 +38,12 @@ impl PluginManager {
         Self {
             plugins: Vec::new(),
             event_bus: EventBus::new(),
+            overlays: Vec::new(),
+            hidden: BTreeSet::new(),
+            #[cfg(feature = "canvas")]
+            canvas: CanvasSnapshot::default(),
+            #[cfg(feature = "canvas")]
+            tools: Vec::new(),
         }
     }
 
@@ -39,6 +59,21 @@ impl PluginManager {
         let ctx = self.create_context();
         plugin.on_load(&ctx);
 
+        for spec in plugin.overlays() {
+            if self.overlays.iter().any(|(_, registered)| registered.id == spec.id) {
+                warn!(plugin = %plugin_name, overlay = %spec.id, "Overlay id already taken, ignoring overlay");
+                continue;
+            }
+            debug!(plugin = %plugin_name, overlay = %spec.id, "Plugin contributed overlay");
+            self.overlays.push((self.plugins.len(), spec));
+        }
+
+        #[cfg(feature = "canvas")]
+        for tool in plugin.tools() {
+            debug!(plugin = %plugin_name, tool = tool.id(), "Plugin contributed tool");
+            self.tools.push(tool);
+        }
+
         self.plugins.push(plugin);
         debug!(plugin = %plugin_name, total = self.plugins.len(), "Plugin registered");
     }
@@ -53,7 +88,49 @@ impl PluginManager {
         self.plugins.iter().map(|p| p.name()).collect()
     }
 
-    /// Renders all enabled plugins.
+    /// Returns whether the named plugin is drawn in the sidebar.
+    pub fn is_plugin_visible(&self, name: &str) -> bool {
+        !self.hidden.contains(name)
+    }
+
+    /// Shows or hides the named plugin in the sidebar.
+    ///
+    /// Hidden plugins still receive events; only their UI is skipped.
+    pub fn set_plugin_visible(&mut self, name: &str, visible: bool) {
+        if visible {
+            self.hidden.remove(name);
+        } else {
+            self.hidden.insert(name.to_string());
+        }
+    }
+
+    /// Returns the names of the plugins hidden from the sidebar.
+    pub fn hidden_plugins(&self) -> &BTreeSet<String> {
+        &self.hidden
+    }
+
+    /// Replaces the set of plugins hidden from the sidebar.
+    pub fn set_hidden_plugins(&mut self, hidden: BTreeSet<String>) {
+        self.hidden = hidden;
+    }
+
+    /// Sets the canvas snapshot plugins see until the next call.
+    ///
+    /// Call once per frame, before [`process_events`](Self::process_events)
+    /// and [`render_plugins`](Self::render_plugins), so every plugin reads
+    /// the same state.
+    #[cfg(feature = "canvas")]
+    pub fn set_canvas_snapshot(&mut self, canvas: CanvasSnapshot) {
+        self.canvas = canvas;
+    }
+
+    /// Returns the canvas snapshot plugins currently see.
+    #[cfg(feature = "canvas")]
+    pub fn canvas_snapshot(&self) -> &CanvasSnapshot {
+        &self.canvas
+    }
+
+    /// Renders all enabled plugins not hidden from the sidebar.
     ///
     /// This should be called once per frame from the main UI loop.
     #[instrument(skip(self, ui))]
@@ -61,19 +138,46 @@ impl PluginManager {
         let ctx = self.create_context();
 
         for plugin in &mut self.plugins {
-            if plugin.is_enabled() {
+            if plugin.is_enabled() && !self.hidden.contains(plugin.name()) {
                 plugin.ui(ui, &ctx);
             }
         }
     }
 
+    /// Takes the custom tools contributed by plugins registered since the last call.
+    ///
+    /// The application registers them with the canvas.
+    #[cfg(feature = "canvas")]
+    pub fn take_tools(&mut self) -> Vec<Box<dyn CustomTool>> {
+        std::mem::take(&mut self.tools)
+    }
+
+    /// Returns the overlays contributed by registered plugins.
+    ///
+    /// Register them with the application's overlay manager and draw their
+    /// contents with [`render_overlay`](Self::render_overlay).
+    pub fn overlays(&self) -> impl Iterator<Item = &OverlaySpec> {
+        self.overlays.iter().map(|(_, spec)| spec)
+    }
+
+    /// Renders the contents of a plugin-contributed overlay.
+    ///
+    /// Returns whether the overlay stays open, or `None` if no plugin
+    /// contributed an overlay with this id.
+    pub fn render_overlay(&mut self, id: &str, ui: &mut egui::Ui) -> Option<bool> {
+        let index = self.overlays.iter().find(|(_, spec)| spec.id == id).map(|(index, _)| *index)?;
+        let ctx = self.create_context();
+        let plugin = self.plugins.get_mut(index)?;
+        Some(plugin.overlay_ui(id, ui, &ctx))
+    }
+
     /// Processes all pending events and distributes them to plugins.
     ///
     /// This should be called once per frame, typically before rendering.
     /// Plugins can emit new events in response to received events.
     #[instrument(skip(self))]
     pub fn process_events(&mut self) {
-        let events = self.event_bus.drain_events();
+        let events = self.event_bus.drain_traced();
 
         if events.is_empty() {
             return;
@@ -81,8 +185,9 @@ impl PluginManager {
 
         debug!(event_count = events.len(), "Processing events");
 
-        // Distribute each event to all plugins
-        for event in &events {
+        // Distribute each event to all plugins, inside the span it arrived with
+        for TracedEvent { event, span } in &events {
+            let _hop = span.enter();
             let ctx = self.create_context();
 
             for plugin in &mut self.plugins {
@@ -123,6 +228,7 @@ impl PluginManager {
         }
 
         self.plugins.clear();
+        self.overlays.clear();
         info!("All plugins shut down");
     }
 
@@ -138,7 +244,11 @@ impl PluginManager {
 
     /// Creates a plugin context for event handling.
     fn create_context(&self) -> PluginContext {
-        PluginContext::new(self.event_bus.sender())
+        PluginContext {
+            events: self.event_bus.sender(),
+            #[cfg(feature = "canvas")]
+            canvas: self.canvas.clone(),
+        }
     }
 }
 
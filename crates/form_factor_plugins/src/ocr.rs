@@ -2,6 +2,7 @@
 //!
 //! This plugin provides UI for:
 //! - OCR text extraction
+//! - Re-reading detections changed since the last extraction
 //! - Extracted text display
 //! - Language selection
 
@@ -49,6 +50,15 @@ impl Plugin for OcrPlugin {
                 ctx.events.emit(AppEvent::OcrExtractionRequested);
             }
 
+            if ui
+                .button("Re-OCR Changed Regions")
+                .on_hover_text("Read only detections moved, resized or added since the last pass")
+                .clicked()
+            {
+                debug!("OCR refresh requested");
+                ctx.events.emit(AppEvent::OcrRefreshRequested);
+            }
+
             ui.separator();
 
             if !self.extracted_text.is_empty() {
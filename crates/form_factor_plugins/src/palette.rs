@@ -0,0 +1,442 @@
+//! Command palette for keyboard-driven access to application actions.
+//!
+//! Every action the application exposes is described by a [`Command`] that
+//! carries the [`AppEvent`] it triggers. The [`CommandPalette`] overlay lets
+//! users fuzzy-search those commands by title or category and runs the
+//! chosen one by emitting its event on the event bus, so commands are
+//! handled exactly like the equivalent button press.
+
+use crate::{bus::EventSender, event::AppEvent};
+use tracing::{debug, instrument};
+
+/// Keyboard shortcut that opens the command palette.
+pub const PALETTE_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND.plus(egui::Modifiers::SHIFT), egui::Key::P);
+
+/// Maximum number of matches listed at once.
+const MAX_VISIBLE_MATCHES: usize = 12;
+
+/// Number of recently run commands ranked first for an empty query.
+const MAX_RECENT: usize = 5;
+
+/// An application action that can be run from the command palette.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Command {
+    /// Unique identifier, e.g. `"file.save"`
+    pub id: String,
+    /// Title shown in the palette
+    pub title: String,
+    /// Group shown next to the title, e.g. `"File"`
+    pub category: String,
+    /// Event emitted when the command runs
+    pub event: AppEvent,
+}
+
+impl Command {
+    /// Creates a command.
+    pub fn new(
+        id: impl Into<String>,
+        title: impl Into<String>,
+        category: impl Into<String>,
+        event: AppEvent,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            category: category.into(),
+            event,
+        }
+    }
+
+    /// Text matched against the search query.
+    fn search_text(&self) -> String {
+        format!("{}: {}", self.category, self.title)
+    }
+}
+
+/// Collection of commands available in the palette.
+#[derive(Debug, Clone, Default)]
+pub struct CommandRegistry {
+    commands: Vec<Command>,
+}
+
+impl CommandRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry with the built-in application commands.
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        for tool in [
+            "Select",
+            "Rectangle",
+            "Circle",
+            "Ellipse",
+            "Freehand",
+            "Line",
+            "Polyline",
+            "Arrow",
+            "Callout",
+            "Highlighter",
+            "Stamp",
+            "Edit",
+            "Rotate",
+        ] {
+            registry.register(Command::new(
+                format!("tool.{}", tool.to_lowercase()),
+                format!("{} Tool", tool),
+                "Tools",
+                AppEvent::ToolSelected {
+                    tool_name: tool.to_string(),
+                },
+            ));
+        }
+
+        let commands = [
+            ("file.new", "New Project", "File", AppEvent::NewProjectRequested),
+            ("file.open", "Open Project", "File", AppEvent::OpenFileRequested),
+            ("file.review", "Review Batch of Projects", "File", AppEvent::BatchReviewRequested),
+            ("file.compare", "Compare Two Projects", "File", AppEvent::ProjectDiffRequested),
+            ("file.save", "Save Project", "File", AppEvent::SaveFileRequested),
+            ("file.save_as", "Save Project As", "File", AppEvent::SaveAsRequested),
+            ("file.password", "Set Project Password", "File", AppEvent::ProjectPasswordRequested),
+            ("file.store", "Open or Save in Project Store", "File", AppEvent::ProjectStoreRequested),
+            ("file.collaborate", "Collaborate Through Project Store", "File", AppEvent::CollaborationRequested),
+            ("file.load_image", "Load Form Image", "File", AppEvent::LoadImageRequested),
+            ("detect.text", "Detect Text", "Detection", AppEvent::TextDetectionRequested),
+            ("detect.logos", "Detect Logos", "Detection", AppEvent::LogoDetectionRequested),
+            ("ocr.extract", "Extract Text", "OCR", AppEvent::OcrExtractionRequested),
+            ("ocr.refresh", "Re-OCR Changed Regions", "OCR", AppEvent::OcrRefreshRequested),
+            ("export.redacted", "Export Redacted Image", "Export", AppEvent::RedactedExportRequested),
+            ("export.canvas", "Export Canvas as PNG/SVG", "Export", AppEvent::CanvasExportRequested),
+            ("export.text", "Export Extracted Text", "Export", AppEvent::TextExportRequested),
+            ("export.pdf", "Print to PDF", "Export", AppEvent::PrintRequested),
+            ("export.refill", "Export Refilled Form", "Export", AppEvent::RefillExportRequested),
+            (
+                "export.validation",
+                "Export Validation Report",
+                "Export",
+                AppEvent::ValidationReportRequested,
+            ),
+            (
+                "export.diagnostics",
+                "Export Diagnostic Bundle",
+                "Export",
+                AppEvent::DiagnosticBundleRequested,
+            ),
+            (
+                "mode.redaction_on",
+                "Show Redaction Preview",
+                "Mode",
+                AppEvent::RedactionModeChanged { enabled: true },
+            ),
+            (
+                "mode.redaction_off",
+                "Hide Redaction Preview",
+                "Mode",
+                AppEvent::RedactionModeChanged { enabled: false },
+            ),
+            (
+                "mode.template_preview_on",
+                "Preview Fields with Sample Data",
+                "Mode",
+                AppEvent::TemplatePreviewChanged { enabled: true },
+            ),
+            (
+                "mode.template_preview_off",
+                "Hide Sample Data Preview",
+                "Mode",
+                AppEvent::TemplatePreviewChanged { enabled: false },
+            ),
+            ("view.comments", "Toggle Comments", "View", AppEvent::CommentsPanelToggled),
+            ("view.assignments", "Toggle Assignments", "View", AppEvent::WorkflowPanelToggled),
+            ("view.stats", "Toggle Session Stats", "View", AppEvent::StatsPanelToggled),
+            ("view.bookmarks", "Toggle View Bookmarks", "View", AppEvent::BookmarksPanelToggled),
+            ("view.legend", "Toggle Color Legend", "View", AppEvent::LegendToggled),
+            ("view.ocr_words", "Toggle OCR Word Boxes", "View", AppEvent::OcrWordsToggled),
+            ("tools.screenshot", "Capture Screenshot Region", "Tools", AppEvent::ScreenshotRequested),
+            ("edit.rename", "Find and Rename...", "Edit", AppEvent::RenamePanelToggled),
+            ("edit.data_entry", "Enter Field Values", "Edit", AppEvent::DataEntryPanelToggled),
+            ("edit.tab_order", "Edit Field Tab Order", "Edit", AppEvent::TabOrderPanelToggled),
+            ("edit.ocr_order", "Edit OCR Reading Order", "Edit", AppEvent::OcrOrderPanelToggled),
+            ("edit.field_groups", "Edit Field Groups", "Edit", AppEvent::FieldGroupsPanelToggled),
+            ("edit.selection_back", "Back to Previous Selection", "Edit", AppEvent::SelectionBackRequested),
+            ("edit.selection_forward", "Forward to Next Selection", "Edit", AppEvent::SelectionForwardRequested),
+            ("view.log", "Toggle Log Viewer", "View", AppEvent::LogViewerToggled),
+            ("view.frame_timing", "Toggle Frame Timing", "View", AppEvent::FrameTimingToggled),
+            ("app.settings", "Open Settings", "Application", AppEvent::SettingsRequested),
+            ("app.tour", "Replay Guided Tour", "Application", AppEvent::TourRequested),
+        ];
+        for (id, title, category, event) in commands {
+            registry.register(Command::new(id, title, category, event));
+        }
+        registry
+    }
+
+    /// Adds a command, replacing any existing command with the same id.
+    pub fn register(&mut self, command: Command) {
+        self.commands.retain(|existing| existing.id != command.id);
+        self.commands.push(command);
+    }
+
+    /// Removes a command by id, returning it if it was registered.
+    pub fn unregister(&mut self, id: &str) -> Option<Command> {
+        let index = self.commands.iter().position(|command| command.id == id)?;
+        Some(self.commands.remove(index))
+    }
+
+    /// All registered commands in registration order.
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    /// Looks up a command by id.
+    pub fn get(&self, id: &str) -> Option<&Command> {
+        self.commands.iter().find(|command| command.id == id)
+    }
+
+    /// Commands matching a query, best match first.
+    ///
+    /// An empty query matches every command in registration order.
+    pub fn search(&self, query: &str) -> Vec<&Command> {
+        let mut scored: Vec<(u32, usize, &Command)> = self
+            .commands
+            .iter()
+            .enumerate()
+            .filter_map(|(index, command)| {
+                fuzzy_score(query, &command.search_text()).map(|score| (score, index, command))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        scored.into_iter().map(|(_, _, command)| command).collect()
+    }
+}
+
+/// Scores how well `query` fuzzy-matches `candidate`.
+///
+/// Every query character must appear in the candidate in order, ignoring
+/// case and whitespace in the query. Consecutive matches and matches at
+/// the start of words score higher. Returns `None` if the query does not
+/// match.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<u32> {
+    let candidate: Vec<char> = candidate.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+
+    for wanted in query.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase) {
+        let offset = candidate[position..].iter().position(|&c| c == wanted)?;
+        let index = position + offset;
+
+        score += 1;
+        if previous.is_some_and(|prev| prev + 1 == index) {
+            score += 5;
+        }
+        if index == 0 || !candidate[index - 1].is_alphanumeric() {
+            score += 10;
+        }
+
+        previous = Some(index);
+        position = index + 1;
+    }
+    Some(score)
+}
+
+/// Searchable overlay listing every registered command.
+#[derive(Debug, Clone)]
+pub struct CommandPalette {
+    registry: CommandRegistry,
+    open: bool,
+    query: String,
+    selected: usize,
+    recent: Vec<String>,
+}
+
+impl Default for CommandPalette {
+    fn default() -> Self {
+        Self::new(CommandRegistry::with_defaults())
+    }
+}
+
+impl CommandPalette {
+    /// Creates a closed palette over a command registry.
+    pub fn new(registry: CommandRegistry) -> Self {
+        Self {
+            registry,
+            open: false,
+            query: String::new(),
+            selected: 0,
+            recent: Vec::new(),
+        }
+    }
+
+    /// Commands available in the palette.
+    pub fn registry(&self) -> &CommandRegistry {
+        &self.registry
+    }
+
+    /// Mutable access to the commands, for registering app-specific actions.
+    pub fn registry_mut(&mut self) -> &mut CommandRegistry {
+        &mut self.registry
+    }
+
+    /// Whether the palette is showing.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Opens the palette with an empty query.
+    pub fn open(&mut self) {
+        self.open = true;
+        self.query.clear();
+        self.selected = 0;
+    }
+
+    /// Closes the palette.
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    /// Opens a closed palette or closes an open one.
+    pub fn toggle(&mut self) {
+        if self.open {
+            self.close();
+        } else {
+            self.open();
+        }
+    }
+
+    /// Current search text.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Replaces the search text and selects the best match.
+    pub fn set_query(&mut self, query: impl Into<String>) {
+        self.query = query.into();
+        self.selected = 0;
+    }
+
+    /// Ids of recently run commands, most recent first.
+    pub fn recent(&self) -> &[String] {
+        &self.recent
+    }
+
+    /// Commands matching the current query, best match first.
+    ///
+    /// With an empty query, recently run commands are listed first.
+    pub fn matches(&self) -> Vec<&Command> {
+        let mut matches = self.registry.search(&self.query);
+        if self.query.trim().is_empty() {
+            let rank = |command: &Command| {
+                self.recent
+                    .iter()
+                    .position(|id| *id == command.id)
+                    .unwrap_or(usize::MAX)
+            };
+            matches.sort_by_key(|command| rank(command));
+        }
+        matches
+    }
+
+    /// Runs a command by id, emitting its event and closing the palette.
+    ///
+    /// Returns `false` if no command has that id.
+    #[instrument(skip(self, events))]
+    pub fn run(&mut self, id: &str, events: &EventSender) -> bool {
+        let Some(command) = self.registry.get(id) else {
+            return false;
+        };
+        debug!(command = %command.id, "Running command");
+        events.emit(command.event.clone());
+
+        self.recent.retain(|recent| recent != id);
+        self.recent.insert(0, id.to_string());
+        self.recent.truncate(MAX_RECENT);
+        self.close();
+        true
+    }
+
+    /// Toggles the palette when [`PALETTE_SHORTCUT`] is pressed.
+    pub fn handle_shortcut(&mut self, ctx: &egui::Context) {
+        if ctx.input_mut(|i| i.consume_shortcut(&PALETTE_SHORTCUT)) {
+            self.toggle();
+        }
+    }
+
+    /// Draws the palette if it is open and runs the chosen command.
+    ///
+    /// Arrow keys move the selection, Enter runs it and Escape closes the
+    /// palette. Returns the id of the command that ran this frame.
+    pub fn show(&mut self, ctx: &egui::Context, events: &EventSender) -> Option<String> {
+        if !self.open {
+            return None;
+        }
+
+        let (up, down, enter, escape) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+            )
+        });
+        if escape {
+            self.close();
+            return None;
+        }
+
+        let ids: Vec<String> = self.matches().into_iter().map(|command| command.id.clone()).collect();
+        if down {
+            self.selected = (self.selected + 1).min(ids.len().saturating_sub(1));
+        }
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        self.selected = self.selected.min(ids.len().saturating_sub(1));
+
+        let mut chosen = enter.then(|| ids.get(self.selected).cloned()).flatten();
+        egui::Area::new(egui::Id::new("command_palette"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 80.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_width(420.0);
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.query)
+                            .hint_text("Type a command")
+                            .desired_width(f32::INFINITY),
+                    );
+                    response.request_focus();
+                    if response.changed() {
+                        self.selected = 0;
+                    }
+                    ui.separator();
+
+                    if ids.is_empty() {
+                        ui.weak("No matching commands");
+                    }
+                    for (index, id) in ids.iter().take(MAX_VISIBLE_MATCHES).enumerate() {
+                        let Some(command) = self.registry.get(id) else {
+                            continue;
+                        };
+                        let row = ui.horizontal(|ui| {
+                            let label = ui.selectable_label(index == self.selected, &command.title);
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                ui.weak(&command.category);
+                            });
+                            label
+                        });
+                        if row.inner.clicked() {
+                            chosen = Some(id.clone());
+                        }
+                    }
+                });
+            });
+
+        let id = chosen?;
+        self.run(&id, events).then_some(id)
+    }
+}
@@ -1,6 +1,9 @@
 //! Plugin trait and context.
 
 use crate::{bus::EventSender, event::AppEvent};
+use form_factor_core::OverlaySpec;
+#[cfg(feature = "canvas")]
+use form_factor_drawing::{CanvasSnapshot, CustomTool};
 
 /// Context provided to plugins during rendering and event handling.
 ///
@@ -9,12 +12,26 @@ use crate::{bus::EventSender, event::AppEvent};
 pub struct PluginContext {
     /// Event sender for publishing events
     pub events: EventSender,
+    /// Read-only view of the canvas, taken once per frame by the application
+    #[cfg(feature = "canvas")]
+    pub canvas: CanvasSnapshot,
 }
 
 impl PluginContext {
     /// Creates a new plugin context.
     pub fn new(events: EventSender) -> Self {
-        Self { events }
+        Self {
+            events,
+            #[cfg(feature = "canvas")]
+            canvas: CanvasSnapshot::default(),
+        }
+    }
+
+    /// Sets the canvas snapshot plugins read from.
+    #[cfg(feature = "canvas")]
+    pub fn with_canvas(mut self, canvas: CanvasSnapshot) -> Self {
+        self.canvas = canvas;
+        self
     }
 }
 
@@ -38,6 +55,41 @@ pub trait Plugin: Send {
     /// * `ctx` - Plugin context with access to events and app state
     fn ui(&mut self, ui: &mut egui::Ui, ctx: &PluginContext);
 
+    /// Returns the overlays this plugin contributes.
+    ///
+    /// The application registers them with its overlay manager, which draws
+    /// their chrome, stacks them by z-order and applies their dismissal
+    /// policies. Open one by emitting [`AppEvent::OverlayOpenRequested`];
+    /// the plugin only draws the contents in [`overlay_ui`](Self::overlay_ui).
+    fn overlays(&self) -> Vec<OverlaySpec> {
+        Vec::new()
+    }
+
+    /// Returns the custom tools this plugin contributes.
+    ///
+    /// Called once on registration. The application registers them with the
+    /// canvas, which lists them in the toolbar after the built-in tools and
+    /// hands them the canvas input while they are selected.
+    #[cfg(feature = "canvas")]
+    fn tools(&self) -> Vec<Box<dyn CustomTool>> {
+        Vec::new()
+    }
+
+    /// Renders the contents of one of this plugin's overlays.
+    ///
+    /// Called every frame while the overlay is open.
+    ///
+    /// # Arguments
+    /// * `id` - Id of the overlay, as returned from [`overlays`](Self::overlays)
+    /// * `ui` - The egui UI inside the overlay
+    /// * `ctx` - Plugin context with access to events and app state
+    ///
+    /// # Returns
+    /// Whether the overlay stays open; return `false` to close it, e.g. from a dialog's OK button.
+    fn overlay_ui(&mut self, _id: &str, _ui: &mut egui::Ui, _ctx: &PluginContext) -> bool {
+        true
+    }
+
     /// Handles an event from the event bus.
     ///
     /// Plugins can react to events from other plugins or the application.
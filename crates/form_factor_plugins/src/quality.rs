@@ -0,0 +1,288 @@
+//! OCR quality dashboard plugin.
+//!
+//! This plugin compares OCR-proposed values with the values operators
+//! settled on and shows:
+//! - Per-field accuracy and correction rate
+//! - Confidence calibration (does 90% confidence mean 90% correct?)
+//!
+//! Samples arrive as custom events from the `ocr` plugin namespace with
+//! event type `correction` and an [`OcrSample`] payload. The canvas queues a
+//! sample whenever a person settles on an extracted value, in data entry,
+//! batch review or the on-canvas OCR text editor, and the application shell
+//! and embeddable widget send them on.
+
+use crate::{
+    event::AppEvent,
+    plugin::{Plugin, PluginContext},
+};
+use egui_plot::{Bar, BarChart, Line, Plot, PlotPoints};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub use form_factor_drawing::OcrSample;
+use tracing::{debug, instrument, warn};
+
+/// Number of confidence buckets in the calibration chart.
+const CALIBRATION_BINS: usize = 10;
+
+/// Accuracy statistics for one field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldQuality {
+    /// Field name
+    pub field: String,
+    /// Number of samples recorded
+    pub samples: usize,
+    /// Number of samples the operator corrected
+    pub corrected: usize,
+}
+
+impl FieldQuality {
+    /// Fraction of samples OCR got right (0.0-1.0).
+    pub fn accuracy(&self) -> f64 {
+        1.0 - self.correction_rate()
+    }
+
+    /// Fraction of samples the operator corrected (0.0-1.0).
+    pub fn correction_rate(&self) -> f64 {
+        if self.samples == 0 {
+            return 0.0;
+        }
+        self.corrected as f64 / self.samples as f64
+    }
+}
+
+/// Observed accuracy for a range of OCR confidence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalibrationBin {
+    /// Lower bound of the confidence range (inclusive)
+    pub min_confidence: f32,
+    /// Upper bound of the confidence range (exclusive, except for the last bin)
+    pub max_confidence: f32,
+    /// Number of samples in this range
+    pub samples: usize,
+    /// Number of samples OCR got right
+    pub correct: usize,
+}
+
+impl CalibrationBin {
+    /// Observed accuracy, or `None` for an empty bin.
+    pub fn accuracy(&self) -> Option<f64> {
+        (self.samples > 0).then(|| self.correct as f64 / self.samples as f64)
+    }
+}
+
+/// Collection of OCR samples with aggregate metrics.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QualityReport {
+    samples: Vec<OcrSample>,
+}
+
+impl QualityReport {
+    /// Creates an empty report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a sample to the report.
+    pub fn record(&mut self, sample: OcrSample) {
+        self.samples.push(sample);
+    }
+
+    /// All recorded samples.
+    pub fn samples(&self) -> &[OcrSample] {
+        &self.samples
+    }
+
+    /// Fraction of all samples OCR got right, or `None` with no samples.
+    pub fn overall_accuracy(&self) -> Option<f64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let correct = self.samples.iter().filter(|s| !s.was_corrected()).count();
+        Some(correct as f64 / self.samples.len() as f64)
+    }
+
+    /// Per-field statistics, sorted by field name.
+    pub fn per_field(&self) -> Vec<FieldQuality> {
+        let mut fields: BTreeMap<&str, FieldQuality> = BTreeMap::new();
+        for sample in &self.samples {
+            let entry = fields.entry(&sample.field).or_insert_with(|| FieldQuality {
+                field: sample.field.clone(),
+                samples: 0,
+                corrected: 0,
+            });
+            entry.samples += 1;
+            if sample.was_corrected() {
+                entry.corrected += 1;
+            }
+        }
+        fields.into_values().collect()
+    }
+
+    /// Accuracy grouped into equal-width confidence bins over 0-100.
+    pub fn calibration(&self, bins: usize) -> Vec<CalibrationBin> {
+        let bins = bins.max(1);
+        let width = 100.0 / bins as f32;
+        let mut result: Vec<CalibrationBin> = (0..bins)
+            .map(|i| CalibrationBin {
+                min_confidence: i as f32 * width,
+                max_confidence: (i + 1) as f32 * width,
+                samples: 0,
+                correct: 0,
+            })
+            .collect();
+
+        for sample in &self.samples {
+            let idx = ((sample.confidence.clamp(0.0, 100.0) / width) as usize).min(bins - 1);
+            result[idx].samples += 1;
+            if !sample.was_corrected() {
+                result[idx].correct += 1;
+            }
+        }
+        result
+    }
+}
+
+/// Plugin showing OCR accuracy metrics.
+pub struct QualityPlugin {
+    /// Samples collected this session
+    report: QualityReport,
+}
+
+impl QualityPlugin {
+    /// Creates a new quality dashboard plugin.
+    pub fn new() -> Self {
+        Self {
+            report: QualityReport::new(),
+        }
+    }
+
+    /// The collected quality report.
+    pub fn report(&self) -> &QualityReport {
+        &self.report
+    }
+
+    /// Renders the per-field accuracy table and bar chart.
+    fn render_fields(&self, ui: &mut egui::Ui) {
+        let fields = self.report.per_field();
+
+        egui::Grid::new("quality_fields")
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Field");
+                ui.strong("Samples");
+                ui.strong("Accuracy");
+                ui.strong("Corrected");
+                ui.end_row();
+                for field in &fields {
+                    ui.label(&field.field);
+                    ui.label(field.samples.to_string());
+                    ui.label(format!("{:.0}%", field.accuracy() * 100.0));
+                    ui.label(format!("{:.0}%", field.correction_rate() * 100.0));
+                    ui.end_row();
+                }
+            });
+
+        let bars: Vec<Bar> = fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| Bar::new(i as f64, f.accuracy() * 100.0).name(&f.field))
+            .collect();
+        Plot::new("quality_field_accuracy")
+            .height(120.0)
+            .include_y(0.0)
+            .include_y(100.0)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .show(ui, |plot| plot.bar_chart(BarChart::new("Accuracy %", bars)));
+    }
+
+    /// Renders the confidence calibration chart.
+    fn render_calibration(&self, ui: &mut egui::Ui) {
+        let observed: PlotPoints = self
+            .report
+            .calibration(CALIBRATION_BINS)
+            .iter()
+            .filter_map(|bin| {
+                bin.accuracy().map(|acc| {
+                    let mid = (bin.min_confidence + bin.max_confidence) as f64 / 2.0;
+                    [mid, acc * 100.0]
+                })
+            })
+            .collect();
+        let ideal: PlotPoints = vec![[0.0, 0.0], [100.0, 100.0]].into();
+
+        Plot::new("quality_calibration")
+            .height(150.0)
+            .x_axis_label("Confidence %")
+            .y_axis_label("Accuracy %")
+            .include_x(0.0)
+            .include_x(100.0)
+            .include_y(0.0)
+            .include_y(100.0)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .show(ui, |plot| {
+                plot.line(Line::new("Ideal", ideal));
+                plot.line(Line::new("Observed", observed));
+            });
+    }
+}
+
+impl Default for QualityPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Plugin for QualityPlugin {
+    fn name(&self) -> &str {
+        "quality"
+    }
+
+    #[instrument(skip(self, ui, _ctx))]
+    fn ui(&mut self, ui: &mut egui::Ui, _ctx: &PluginContext) {
+        ui.group(|ui| {
+            ui.heading("OCR Quality");
+
+            let Some(overall) = self.report.overall_accuracy() else {
+                ui.label("No corrections recorded yet");
+                return;
+            };
+
+            ui.label(format!(
+                "Overall accuracy: {:.1}% ({} samples)",
+                overall * 100.0,
+                self.report.samples().len()
+            ));
+            ui.separator();
+            self.render_fields(ui);
+            ui.separator();
+            ui.label("Confidence calibration:");
+            self.render_calibration(ui);
+        });
+    }
+
+    #[instrument(skip(self, _ctx), fields(plugin = "quality"))]
+    fn on_event(&mut self, event: &AppEvent, _ctx: &PluginContext) -> Option<AppEvent> {
+        match event {
+            AppEvent::Custom {
+                plugin, event_type, ..
+            } if plugin == "ocr" && event_type == "correction" => {
+                match event.decode_custom::<OcrSample>() {
+                    Ok(sample) => {
+                        debug!(field = %sample.field, corrected = sample.was_corrected(), "OCR sample recorded");
+                        self.report.record(sample);
+                    }
+                    Err(e) => warn!("Invalid OCR correction event: {}", e),
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn description(&self) -> &str {
+        "OCR accuracy, correction rate, and confidence calibration"
+    }
+}
@@ -0,0 +1,16 @@
+//! Integration tests for the event bus
+
+use form_factor_plugins::{AppEvent, EventBus};
+
+#[test]
+fn drain_traced_keeps_order() {
+    let mut bus = EventBus::new();
+    let sender = bus.sender();
+
+    sender.send(AppEvent::SelectionCleared).expect("Sent");
+    sender.send(AppEvent::TextDetectionRequested).expect("Sent");
+
+    let events: Vec<_> = bus.drain_traced().into_iter().map(|traced| traced.event).collect();
+    assert_eq!(events, vec![AppEvent::SelectionCleared, AppEvent::TextDetectionRequested]);
+    assert!(bus.drain_traced().is_empty());
+}
@@ -0,0 +1,68 @@
+//! Integration tests for versioned event encoding
+
+use form_factor_plugins::{AppEvent, DecodeError};
+use std::path::PathBuf;
+
+#[test]
+fn encode_decode_roundtrip() {
+    let events = [
+        AppEvent::OpenFileRequested,
+        AppEvent::CanvasZoomChanged { zoom: 2.5 },
+        AppEvent::FileSaved {
+            path: PathBuf::from("forms/w2.ffp"),
+        },
+        AppEvent::custom("ocr", "text_extracted", &vec!["Name"]).expect("Serializable"),
+    ];
+
+    for event in events {
+        let encoded = event.encode().expect("Encodable");
+        assert_eq!(AppEvent::decode(&encoded).expect("Decodable"), event);
+    }
+    assert_eq!(
+        AppEvent::OpenFileRequested.encode().expect("Encodable"),
+        r#"{"version":1,"event":"OpenFileRequested","data":null}"#
+    );
+}
+
+#[test]
+fn unknown_events_decode_as_unrecognized() {
+    let encoded = r#"{"version":3,"event":"HologramRequested","data":{"depth":4}}"#;
+
+    let event = AppEvent::decode(encoded).expect("Decodable");
+
+    assert_eq!(
+        event,
+        AppEvent::Unrecognized {
+            version: 3,
+            name: "HologramRequested".to_string(),
+            data: r#"{"depth":4}"#.to_string(),
+        }
+    );
+    // Passed on unchanged
+    assert_eq!(AppEvent::decode(&event.encode().expect("Encodable")).expect("Decodable"), event);
+}
+
+#[test]
+fn changed_events_depend_on_version() {
+    // Extra fields from a newer build are ignored
+    let added = r#"{"version":2,"event":"CanvasZoomChanged","data":{"zoom":2.0,"animate":true}}"#;
+    assert_eq!(
+        AppEvent::decode(added).expect("Decodable"),
+        AppEvent::CanvasZoomChanged { zoom: 2.0 }
+    );
+
+    // A newer version that reshaped the event degrades; the current version is an error
+    let newer = r#"{"version":2,"event":"CanvasZoomChanged","data":{"factor":2.0}}"#;
+    assert!(matches!(
+        AppEvent::decode(newer).expect("Decodable"),
+        AppEvent::Unrecognized { version: 2, .. }
+    ));
+    let current = r#"{"version":1,"event":"CanvasZoomChanged","data":{"factor":2.0}}"#;
+    assert!(matches!(AppEvent::decode(current), Err(DecodeError::Json(_))));
+
+    let unversioned = r#"{"version":0,"event":"OpenFileRequested"}"#;
+    assert!(matches!(
+        AppEvent::decode(unversioned),
+        Err(DecodeError::UnsupportedVersion(0))
+    ));
+}
@@ -0,0 +1,29 @@
+//! Integration tests for the layers plugin
+//!
+//! Run with `cargo test -p form_factor_plugins --features plugin-layers`.
+
+#![cfg(feature = "plugin-layers")]
+
+use form_factor_drawing::LayerType;
+use form_factor_plugins::layers::LayersPlugin;
+use form_factor_plugins::{AppEvent, EventBus, Plugin, PluginContext};
+
+#[test]
+fn canvas_changed_event_syncs_visibility_and_selection() {
+    let mut plugin = LayersPlugin::new();
+    let bus = EventBus::new();
+    let ctx = PluginContext::new(bus.sender());
+
+    let event = AppEvent::CanvasChanged {
+        zoom: 1.0,
+        pan_x: 0.0,
+        pan_y: 0.0,
+        tool: "Select".to_string(),
+        visible_layers: vec!["Canvas".to_string(), "Shapes".to_string()],
+        selected_layer: Some("Detections".to_string()),
+    };
+    plugin.on_event(&event, &ctx);
+
+    assert_eq!(plugin.visible_layers(), [LayerType::Canvas, LayerType::Shapes]);
+    assert_eq!(plugin.selected_layer(), Some(LayerType::Detections));
+}
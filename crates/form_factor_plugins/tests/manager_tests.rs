@@ -0,0 +1,91 @@
+//! Integration tests for the plugin manager's overlays and sidebar
+
+use form_factor_core::OverlaySpec;
+use form_factor_plugins::{Plugin, PluginContext, PluginManager};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Plugin with a confirmation overlay, counting how often its sidebar panel is drawn
+struct DialogPlugin {
+    name: &'static str,
+    confirmed: bool,
+    drawn: Arc<AtomicUsize>,
+}
+
+impl DialogPlugin {
+    fn new(name: &'static str, confirmed: bool) -> Self {
+        Self {
+            name,
+            confirmed,
+            drawn: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+}
+
+impl Plugin for DialogPlugin {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn ui(&mut self, _ui: &mut egui::Ui, _ctx: &PluginContext) {
+        self.drawn.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn overlays(&self) -> Vec<OverlaySpec> {
+        vec![OverlaySpec::new("dialog.confirm", "Confirm")]
+    }
+
+    fn overlay_ui(&mut self, _id: &str, _ui: &mut egui::Ui, _ctx: &PluginContext) -> bool {
+        !self.confirmed
+    }
+}
+
+/// Run one frame with `draw` in the central panel
+fn frame(ctx: &egui::Context, mut draw: impl FnMut(&mut egui::Ui)) {
+    let _ = ctx.run(egui::RawInput::default(), |ctx| {
+        egui::CentralPanel::default().show(ctx, |ui| draw(ui));
+    });
+}
+
+#[test]
+fn the_first_plugin_to_contribute_an_overlay_draws_it() {
+    let mut manager = PluginManager::new();
+    manager.register(Box::new(DialogPlugin::new("first", true)));
+    manager.register(Box::new(DialogPlugin::new("second", false)));
+
+    let ids: Vec<&str> = manager.overlays().map(|spec| spec.id.as_str()).collect();
+    assert_eq!(ids, vec!["dialog.confirm"], "Duplicate overlay ids are ignored");
+
+    let mut open = None;
+    let mut unknown = None;
+    frame(&egui::Context::default(), |ui| {
+        open = manager.render_overlay("dialog.confirm", ui);
+        unknown = manager.render_overlay("missing", ui);
+    });
+
+    assert_eq!(open, Some(false));
+    assert_eq!(unknown, None);
+    manager.shutdown();
+}
+
+#[test]
+fn hidden_plugins_are_not_drawn() {
+    let plugin = DialogPlugin::new("layers", false);
+    let drawn = plugin.drawn.clone();
+    let mut manager = PluginManager::new();
+    manager.register(Box::new(plugin));
+    let ctx = egui::Context::default();
+
+    manager.set_plugin_visible("layers", false);
+    assert!(!manager.is_plugin_visible("layers"));
+    assert_eq!(manager.hidden_plugins().len(), 1);
+    frame(&ctx, |ui| manager.render_plugins(ui));
+    assert_eq!(drawn.load(Ordering::Relaxed), 0);
+
+    manager.set_hidden_plugins(BTreeSet::new());
+    frame(&ctx, |ui| manager.render_plugins(ui));
+    assert!(manager.is_plugin_visible("layers"));
+    assert_eq!(drawn.load(Ordering::Relaxed), 1);
+    manager.shutdown();
+}
@@ -0,0 +1,50 @@
+//! Integration tests for the command palette
+
+use form_factor_plugins::{AppEvent, Command, CommandPalette, CommandRegistry, EventBus, fuzzy_score};
+
+#[test]
+fn fuzzy_score_requires_ordered_characters() {
+    assert!(fuzzy_score("dtx", "Detection: Detect Text").is_some());
+    assert!(fuzzy_score("txd", "Detect Text").is_none());
+    assert_eq!(fuzzy_score("", "anything"), Some(0));
+}
+
+#[test]
+fn fuzzy_score_prefers_word_starts_and_runs() {
+    let word_start = fuzzy_score("save", "File: Save Project").expect("Matches");
+    let scattered = fuzzy_score("save", "Show Redaction Preview Image").unwrap_or(0);
+    assert!(word_start > scattered);
+}
+
+#[test]
+fn search_ranks_best_match_first() {
+    let registry = CommandRegistry::with_defaults();
+    let results = registry.search("detect text");
+    assert_eq!(results.first().map(|c| c.id.as_str()), Some("detect.text"));
+    assert!(registry.search("zzz").is_empty());
+}
+
+#[test]
+fn register_replaces_same_id() {
+    let mut registry = CommandRegistry::new();
+    registry.register(Command::new("a", "First", "Test", AppEvent::SaveFileRequested));
+    registry.register(Command::new("a", "Second", "Test", AppEvent::SaveAsRequested));
+    assert_eq!(registry.commands().len(), 1);
+    assert_eq!(registry.get("a").map(|c| c.title.as_str()), Some("Second"));
+    assert!(registry.unregister("a").is_some());
+    assert!(registry.get("a").is_none());
+}
+
+#[test]
+fn run_emits_event_and_tracks_recent() {
+    let mut bus = EventBus::new();
+    let mut palette = CommandPalette::default();
+    palette.open();
+
+    assert!(palette.run("file.save", &bus.sender()));
+    assert_eq!(bus.try_recv(), Some(AppEvent::SaveFileRequested));
+    assert!(!palette.is_open());
+    assert_eq!(palette.matches().first().map(|c| c.id.as_str()), Some("file.save"));
+
+    assert!(!palette.run("missing", &bus.sender()));
+}
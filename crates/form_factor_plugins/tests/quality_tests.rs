@@ -0,0 +1,62 @@
+//! Integration tests for the OCR quality dashboard
+//!
+//! Run with `cargo test -p form_factor_plugins --features plugin-quality`.
+
+#![cfg(feature = "plugin-quality")]
+
+use form_factor_plugins::quality::{OcrSample, QualityPlugin, QualityReport};
+use form_factor_plugins::{AppEvent, EventBus, Plugin, PluginContext};
+
+/// OCR sample of `field`, read as `proposed` and settled as `corrected`
+fn sample(field: &str, proposed: &str, corrected: &str, confidence: f32) -> OcrSample {
+    OcrSample {
+        field: field.to_string(),
+        proposed: proposed.to_string(),
+        corrected: corrected.to_string(),
+        confidence,
+    }
+}
+
+#[test]
+fn accuracy_is_reported_per_field() {
+    let mut report = QualityReport::new();
+    report.record(sample("name", "Jane", "Jane", 90.0));
+    report.record(sample("name", "J4ne", "Jane", 40.0));
+    report.record(sample("zip", "12345 ", "12345", 95.0));
+
+    let fields = report.per_field();
+    assert_eq!(fields.len(), 2);
+    assert_eq!(fields[0].field, "name");
+    assert_eq!(fields[0].accuracy(), 0.5);
+    assert_eq!(fields[1].correction_rate(), 0.0);
+    assert_eq!(report.overall_accuracy(), Some(2.0 / 3.0));
+}
+
+#[test]
+fn calibration_bins_group_samples_by_confidence() {
+    let mut report = QualityReport::new();
+    report.record(sample("a", "x", "x", 95.0));
+    report.record(sample("a", "x", "y", 100.0));
+    report.record(sample("a", "x", "y", 5.0));
+
+    let bins = report.calibration(10);
+    assert_eq!(bins.len(), 10);
+    assert_eq!(bins[0].accuracy(), Some(0.0));
+    assert_eq!(bins[9].samples, 2);
+    assert_eq!(bins[9].accuracy(), Some(0.5));
+    assert_eq!(bins[5].accuracy(), None);
+}
+
+#[test]
+fn correction_event_records_a_sample() {
+    let mut plugin = QualityPlugin::new();
+    let bus = EventBus::new();
+    let ctx = PluginContext::new(bus.sender());
+
+    let event = AppEvent::custom("ocr", "correction", &sample("name", "Jon", "John", 70.0))
+        .expect("Serializable sample");
+    plugin.on_event(&event, &ctx);
+
+    assert_eq!(plugin.report().samples().len(), 1);
+    assert!(plugin.report().samples()[0].was_corrected());
+}